@@ -1,8 +1,9 @@
-use runtime::large_data_transfer::{pricing, redundancy::RedundancyPolicy};
+use runtime::large_data_transfer::{pricing, redundancy::{ErasureScheme, RedundancyPolicy}};
 use runtime::distributed_storage::{run_auto_heal, ReplicationManager, StorageNode};
 use runtime::large_data_transfer::network::coordinator::NetworkTransferCoordinator;
 use std::sync::Arc;
 use runtime::large_data_transfer::descriptor::LargeDataDescriptor;
+use runtime::large_data_transfer::{ChunkManagerConfig, PinSet};
 use std::path::PathBuf;
 use std::io::{BufWriter, Write, Read};
 use std::fs::{File, create_dir_all};
@@ -17,6 +18,9 @@ pub async fn handle_dfs(args: &[String]) -> Result<(), Box<dyn std::error::Error
     match args[0].as_str() {
         "quote" => quote_price(&args[1..])?,
         "get" => get_file(&args[1..])?,
+        "pin" => pin_file(&args[1..])?,
+        "unpin" => unpin_file(&args[1..])?,
+        "pins" => list_pins()?,
         "rebalance" => {
             println!("🔄 Triggering network rebalance (auto-heal)…");
             // Stub – create empty managers for now
@@ -42,18 +46,34 @@ pub async fn handle_dfs(args: &[String]) -> Result<(), Box<dyn std::error::Error
 
 fn quote_price(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     if args.is_empty() {
-        eprintln!("Usage: dfs quote <FILE> [--copies N]");
+        eprintln!("Usage: dfs quote <FILE> [--copies N | --erasure K+M]");
         return Ok(());
     }
     let file = &args[0];
     let copies = parse_copies(args).unwrap_or(1);
+    let erasure = parse_erasure(args)?;
     let bytes = std::fs::metadata(file)?.len() as u128;
-    let policy = RedundancyPolicy { copies, geo_spread: true };
+    let policy = RedundancyPolicy { copies, geo_spread: true, erasure };
     let quote = pricing::quote(bytes, policy, PRICE_PER_GIB_BCAI);
-    println!("💰 Quote: {:.2} GiB, {} copies → {} BCAI", quote.total_bytes as f64/1.073741824e9, copies, quote.price_bcai);
+    let redundancy = match erasure {
+        Some(scheme) => format!("{}+{} shards", scheme.data_shards, scheme.parity_shards),
+        None => format!("{} copies", copies),
+    };
+    println!("💰 Quote: {:.2} GiB, {} → {} BCAI", quote.total_bytes as f64/1.073741824e9, redundancy, quote.price_bcai);
     Ok(())
 }
 
+/// `--erasure K+M`: K data and M parity shards.
+fn parse_erasure(args: &[String]) -> Result<Option<ErasureScheme>, Box<dyn std::error::Error>> {
+    let Some(value) = args.windows(2).find(|w| w[0] == "--erasure").map(|w| &w[1]) else {
+        return Ok(None);
+    };
+    let (k, m) = value.split_once('+').ok_or("--erasure expects K+M, e.g. 4+2")?;
+    let scheme = ErasureScheme::new(k.parse()?, m.parse()?);
+    scheme.validate()?;
+    Ok(Some(scheme))
+}
+
 fn parse_copies(args: &[String]) -> Option<u8> {
     args.windows(2)
         .find(|w| w[0] == "--copies")
@@ -62,12 +82,103 @@ fn parse_copies(args: &[String]) -> Option<u8> {
 
 fn print_help() {
     println!("DFS subcommands:");
-    println!("  dfs quote <FILE> [--copies N]   – price estimation");
+    println!("  dfs quote <FILE> [--copies N | --erasure K+M] – price estimation");
     println!("  dfs get <DESCRIPTOR_HASH> <OUT_FILE> – retrieve file");
+    println!("  dfs pin <DESCRIPTOR_HASH> [--owner NAME] – keep a file cached");
+    println!("  dfs unpin <DESCRIPTOR_HASH> [--owner NAME] – release a pin");
+    println!("  dfs pins                        – list pinned files");
     println!("  dfs rebalance                   – trigger auto-heal");
     println!("  dfs stats                       – show storage stats");
 }
 
+fn dfs_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(PathBuf::from(std::env::var("HOME")?).join(".bcai/dfs"))
+}
+
+/// Pins kept in `~/.bcai/dfs/pins.json`, for the node to start its chunk
+/// manager with.
+fn load_pins() -> Result<PinSet, Box<dyn std::error::Error>> {
+    let path = dfs_dir()?.join("pins.json");
+    if !path.exists() {
+        return Ok(PinSet::new(ChunkManagerConfig::default().pin_quota_bytes));
+    }
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+fn save_pins(pins: &PinSet) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = dfs_dir()?;
+    create_dir_all(&dir)?;
+    serde_json::to_writer_pretty(File::create(dir.join("pins.json"))?, pins)?;
+    Ok(())
+}
+
+fn parse_owner(args: &[String]) -> String {
+    args.windows(2)
+        .find(|w| w[0] == "--owner")
+        .map_or_else(|| "local".to_string(), |w| w[1].clone())
+}
+
+fn pin_file(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if args.is_empty() {
+        eprintln!("Usage: dfs pin <DESCRIPTOR_HASH> [--owner NAME]");
+        return Ok(());
+    }
+    let desc_path = dfs_dir()?.join("descriptors").join(format!("{}.json", args[0]));
+    if !desc_path.exists() {
+        eprintln!("Descriptor not found: {}", desc_path.display());
+        return Ok(());
+    }
+    let descriptor: LargeDataDescriptor = serde_json::from_reader(File::open(&desc_path)?)?;
+    let owner = parse_owner(args);
+    let mut pins = load_pins()?;
+    let pin = pins.pin(&owner, &descriptor)?.clone();
+    save_pins(&pins)?;
+    println!(
+        "📌 Pinned {} for {} ({} chunks, {} of {} bytes of quota used)",
+        pin.file_hash,
+        owner,
+        pin.chunks.len(),
+        pins.used_by(&owner),
+        pins.quota_bytes
+    );
+    Ok(())
+}
+
+fn unpin_file(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if args.is_empty() {
+        eprintln!("Usage: dfs unpin <DESCRIPTOR_HASH> [--owner NAME]");
+        return Ok(());
+    }
+    let owner = parse_owner(args);
+    let mut pins = load_pins()?;
+    if pins.unpin(&owner, &args[0]) {
+        save_pins(&pins)?;
+        println!("📍 Unpinned {} for {}", args[0], owner);
+    } else {
+        eprintln!("{} is not pinned by {}", args[0], owner);
+    }
+    Ok(())
+}
+
+fn list_pins() -> Result<(), Box<dyn std::error::Error>> {
+    let pins = load_pins()?;
+    if pins.pins().next().is_none() {
+        println!("No pinned files.");
+        return Ok(());
+    }
+    println!("{:<20} {:<66} {:>8} {:>14}", "OWNER", "FILE", "CHUNKS", "BYTES");
+    for pin in pins.pins() {
+        println!(
+            "{:<20} {:<66} {:>8} {:>14}",
+            pin.owner,
+            pin.file_hash,
+            pin.chunks.len(),
+            pin.size_bytes
+        );
+    }
+    Ok(())
+}
+
 fn get_file(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     if args.len() < 2 {
         eprintln!("Usage: dfs get <DESCRIPTOR_HASH> <OUT_FILE>");
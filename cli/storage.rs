@@ -23,7 +23,7 @@ pub async fn handle_store(args: &[String]) -> Result<(), Box<dyn std::error::Err
     let quote_only = args.iter().any(|a| a == "--quote-only");
 
     let bytes = std::fs::metadata(file_path)?.len() as u128;
-    let policy = RedundancyPolicy { copies, geo_spread: true };
+    let policy = RedundancyPolicy { copies, geo_spread: true, erasure: None };
     let quote = pricing::quote(bytes, policy, PRICE_PER_GIB_BCAI);
 
     println!("💰 Price Quote: storing {:.2} GiB with {} extra copies = {} BCAI", quote.total_bytes as f64/1.073741824e9, copies, quote.price_bcai);
@@ -1,6 +1,10 @@
+use bcai::ml::model_card::SignedModelCard;
 use jobmanager_lib::{load_jobs, Job};
 use tiny_http::{Response, Server};
 
+/// Directory holding exported signed model cards, one `<name>.json` each.
+pub const MODEL_CARD_DIR: &str = "model_cards";
+
 /// Render a list of jobs as simple HTML.
 pub fn render_jobs(jobs: &[Job]) -> String {
     let mut html = String::from("<html><body><h1>Jobs</h1><ul>");
@@ -15,7 +19,26 @@ pub fn render_jobs(jobs: &[Job]) -> String {
     html
 }
 
-/// Start a simple HTTP server that serves the job list at `/jobs`.
+/// Render a signed model card, flagging cards whose signature does not verify.
+pub fn render_model_card(signed: &SignedModelCard) -> String {
+    let status = if signed.verify() {
+        format!("<p>Signed by {}</p>", signed.signer)
+    } else {
+        String::from("<p><strong>Signature verification failed</strong></p>")
+    };
+    format!("<html><body>{}{}</body></html>", status, signed.card.to_html())
+}
+
+fn load_model_card(name: &str) -> Option<SignedModelCard> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    let path = std::path::Path::new(MODEL_CARD_DIR).join(format!("{}.json", name));
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+/// Start a simple HTTP server that serves the job list at `/jobs` and model
+/// cards at `/model-cards/<name>`.
 pub fn serve(addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let server = Server::http(addr)?;
     for request in server.incoming_requests() {
@@ -28,6 +51,17 @@ pub fn serve(addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
                 let response = Response::from_string(html).with_header(header);
                 request.respond(response)?;
             }
+            url if url.starts_with("/model-cards/") => {
+                match load_model_card(&url["/model-cards/".len()..]) {
+                    Some(card) => {
+                        let header = tiny_http::Header::from_bytes(b"Content-Type", b"text/html")
+                            .expect("valid header bytes");
+                        let response = Response::from_string(render_model_card(&card)).with_header(header);
+                        request.respond(response)?;
+                    }
+                    None => request.respond(Response::empty(404))?,
+                }
+            }
             _ => {
                 request.respond(Response::empty(404))?;
             }
@@ -48,6 +82,7 @@ mod tests {
             reward: 10,
             assigned_to: None,
             completed: false,
+            traceparent: None,
         }];
         let html = render_jobs(&jobs);
         assert!(html.contains("test"));
@@ -249,9 +249,9 @@ async fn handle_daemon_command(handle: P2PHandle, command: P2pCommands, network_
 
             // 5. Broadcast the new block to the network
             let wire_message = WireMessage::Block(new_block);
-            let message_bytes = bincode::serialize(&wire_message).unwrap();
+            let message_bytes = runtime::p2p_service::encode_message(&wire_message);
 
-            match handle.send_message("bcai_global".to_string(), message_bytes).await {
+            match handle.gossip(runtime::gossip::GossipTopic::Blocks, message_bytes).await {
                 Ok(_) => format!("Mined and broadcast new block: {}", block_hash),
                 Err(e) => format!("Error broadcasting block: {}", e),
             }
@@ -1,6 +1,7 @@
 use crate::commands::{Cli, Commands};
 use crate::error::DevnetError;
-use crate::ops::{job_ops, ledger_ops, system_ops};
+use crate::ledger::AssetRules;
+use crate::ops::{genesis_ops, job_ops, ledger_ops, sim_ops, system_ops};
 use clap::Parser;
 
 /// Entry point invoked by `main.rs`.
@@ -8,22 +9,35 @@ pub fn run() -> Result<(), DevnetError> {
     use Commands::*;
 
     let cli = Cli::parse();
+    let store = cli.profile.open()?;
+    let store = store.as_ref();
 
     match cli.command {
-        Init => ledger_ops::init_ledger(),
-        Mint { account, amount } => ledger_ops::mint(&account, amount),
-        Transfer { from, to, amount } => ledger_ops::transfer(&from, &to, amount),
-        Stake { account, amount } => ledger_ops::stake(&account, amount),
-        Unstake { account, amount } => ledger_ops::unstake(&account, amount),
-        Slash { account, amount } => ledger_ops::slash(&account, amount),
-        Burn { account, amount } => ledger_ops::burn(&account, amount),
-        Balance { account } => ledger_ops::balance(&account),
-        Reputation { account } => ledger_ops::reputation(&account),
-        AdjustRep { account, delta } => ledger_ops::adjust_reputation(&account, delta),
+        Init => ledger_ops::init_ledger(store),
+        Mint { account, amount, asset } => ledger_ops::mint(store, &asset, &account, amount),
+        Transfer { from, to, amount, asset } => ledger_ops::transfer(store, &asset, &from, &to, amount),
+        Stake { account, amount, asset } => ledger_ops::stake(store, &asset, &account, amount),
+        Unstake { account, amount, asset } => ledger_ops::unstake(store, &asset, &account, amount),
+        Slash { account, amount } => ledger_ops::slash(store, &account, amount),
+        Burn { account, amount, asset } => ledger_ops::burn(store, &asset, &account, amount),
+        Balance { account, asset } => ledger_ops::balance(store, &asset, &account),
+        RegisterAsset { asset, origin_chain, stakeable, non_transferable } => ledger_ops::register_asset(
+            store,
+            &asset,
+            AssetRules { origin_chain: Some(origin_chain), transferable: !non_transferable, stakeable },
+        ),
+        Assets => ledger_ops::assets(store),
+        Reputation { account } => ledger_ops::reputation(store, &account),
+        AdjustRep { account, delta } => ledger_ops::adjust_reputation(store, &account, delta),
         Mine => system_ops::mine(),
         Train { size, seed, difficulty } => system_ops::train_pouw(size, seed, difficulty),
         Mnist => system_ops::train_mnist(),
         Neural { layers, epochs, samples } => system_ops::train_neural(layers, epochs, samples),
-        Job { job } => job_ops::handle_job_command(job),
+        Job { job } => job_ops::handle_job_command(store, job),
+        Genesis { genesis } => genesis_ops::handle_genesis_command(genesis),
+        Ledger { ledger } => ledger_ops::handle_ledger_command(store, ledger),
+        Stream { stream } => ledger_ops::handle_stream_command(store, stream),
+        Bridge { bridge } => ledger_ops::handle_bridge_command(store, bridge),
+        Simulate { simulate } => sim_ops::handle_simulate_command(simulate),
     }
 } 
\ No newline at end of file
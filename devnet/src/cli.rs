@@ -1,6 +1,7 @@
 //! Defines the command-line interface for the devnet.
 
 use clap::{Parser, Subcommand};
+use runtime::job::Milestone;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -52,7 +53,7 @@ pub enum P2pCommands {
 #[derive(Subcommand, Serialize, Deserialize, Debug)]
 pub enum TxCommands {
     /// Create and broadcast a new transfer transaction.
-    /// Use 'keygen' to create a keypair and 'p2p account nonce' to get the next nonce.
+    /// Use 'keygen' to create a keypair; the daemon picks the nonce unless `--nonce` is given.
     Create {
         /// Path to the secret key file of the sender (e.g., 'wallet.key').
         #[arg(long)]
@@ -63,12 +64,15 @@ pub enum TxCommands {
         /// Amount to transfer.
         #[arg(long)]
         amount: u64,
-        /// The next valid nonce for the sender's account.
+        /// Nonce for the transaction; allocated by the daemon if left out.
         #[arg(long)]
-        nonce: u64,
+        nonce: Option<u64>,
         /// Fee for the transaction.
         #[arg(long, default_value_t = 1)]
         fee: u64,
+        /// Show what the transaction would do without submitting it.
+        #[arg(long)]
+        dry_run: bool,
     },
 }
 
@@ -94,5 +98,92 @@ pub enum JobCommands {
         /// The number of training iterations to perform.
         #[arg(long, default_value_t = 10)]
         iterations: u32,
+        /// Reward to escrow on chain for the miner that trains the job.
+        #[arg(long, default_value_t = 0, requires = "from_secret_key_file")]
+        reward: u64,
+        /// Secret key file of the poster, who pays the reward.
+        #[arg(long)]
+        from_secret_key_file: Option<PathBuf>,
+        /// Nonce for the job posting transaction; allocated by the daemon
+        /// if left out.
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Fee for the job posting transaction.
+        #[arg(long, default_value_t = 1)]
+        fee: u64,
+        /// GPU memory, in GB, a node needs to be assigned the job.
+        #[arg(long, default_value_t = 0)]
+        min_gpu_memory_gb: u32,
+        /// Stake a node needs to be assigned the job.
+        #[arg(long, default_value_t = 0)]
+        min_stake: u64,
+        /// Reputation a node needs to be assigned the job.
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        min_reputation: i32,
+        /// Worker or pool to run the job; repeat to allow several. Without
+        /// any, every capable worker may.
+        #[arg(long = "pin")]
+        pinned: Vec<String>,
+        /// Worker or pool never to run the job; may be repeated.
+        #[arg(long = "exclude")]
+        excluded: Vec<String>,
+        /// Part of the reward, as description=reward, released once the
+        /// evaluator signs it off; may be repeated.
+        #[arg(long = "milestone", value_parser = parse_milestone, requires = "milestone_evaluator")]
+        milestones: Vec<Milestone>,
+        /// Public key, in hex, of the account that signs off milestones.
+        #[arg(long)]
+        milestone_evaluator: Option<String>,
+        /// Show what the escrow transaction would do without submitting
+        /// the job.
+        #[arg(long, requires = "from_secret_key_file")]
+        dry_run: bool,
+    },
+    /// Sign off a milestone of an escrowed job, paying its reward to the
+    /// worker.
+    SignOff {
+        /// The job whose milestone was reached.
+        #[arg(long)]
+        job_id: u64,
+        /// Index of the milestone, in the order it was submitted.
+        #[arg(long)]
+        milestone: u32,
+        /// Public key, in hex, of the worker to pay.
+        #[arg(long)]
+        worker: String,
+        /// Secret key file of the job's milestone evaluator.
+        #[arg(long)]
+        from_secret_key_file: PathBuf,
+        /// Nonce for the sign-off transaction; allocated by the daemon if
+        /// left out.
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Fee for the sign-off transaction.
+        #[arg(long, default_value_t = 1)]
+        fee: u64,
+    },
+    /// Cancel an escrowed job nobody has mined or signed off yet,
+    /// refunding its escrow to the poster.
+    Cancel {
+        /// The job to cancel.
+        #[arg(long)]
+        job_id: u64,
+        /// Secret key file of the job's poster.
+        #[arg(long)]
+        from_secret_key_file: PathBuf,
+        /// Nonce for the cancel transaction; allocated by the daemon if
+        /// left out.
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Fee for the cancel transaction.
+        #[arg(long, default_value_t = 1)]
+        fee: u64,
     },
+}
+
+fn parse_milestone(s: &str) -> Result<Milestone, String> {
+    let (description, reward) =
+        s.rsplit_once('=').ok_or_else(|| format!("expected description=reward, got {}", s))?;
+    let reward = reward.parse().map_err(|e| format!("invalid reward in {}: {}", s, e))?;
+    Ok(Milestone { description: description.to_string(), reward })
 } 
\ No newline at end of file
@@ -12,6 +12,9 @@ use std::{
 };
 use tokio::sync::Mutex;
 
+use crate::daemon::nonces::SharedNonces;
+use crate::daemon::scheduler::SharedScheduler;
+
 /// Shared alias for pending transactions.
 pub(super) type Mempool = Arc<Mutex<HashSet<Transaction>>>;
 /// Shared alias for queued compute jobs.
@@ -28,7 +31,10 @@ pub struct CommandHandler {
     pub(super) mempool: Mempool,                   // pending transactions
     pub(super) job_queue: JobQueue,                // queued training jobs
     pub(super) p2p_handle: P2PHandle,              // network interface
-    job_id_counter: u64,                           // monotonically increasing job id
+    pub(super) job_id_counter: u64,                // monotonically increasing job id
+    pub(super) scheduler: Option<SharedScheduler>, // admits jobs under backpressure
+    pub(super) nonces: Option<SharedNonces>,       // allocates and orders nonces
+    pub(super) read_only: bool,                    // follower: refuses writes
 }
 
 impl CommandHandler {
@@ -45,14 +51,41 @@ impl CommandHandler {
             job_queue,
             p2p_handle,
             job_id_counter: 0,
+            scheduler: None,
+            nonces: None,
+            read_only: false,
         }
     }
 
+    /// Refuse job submissions while `scheduler` reports a full queue.
+    pub fn with_scheduler(mut self, scheduler: SharedScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Allocate nonces left out of commands from `nonces`, and hold
+    /// transactions that arrive ahead of their nonce until their turn.
+    pub fn with_nonces(mut self, nonces: SharedNonces) -> Self {
+        self.nonces = Some(nonces);
+        self
+    }
+
+    /// Refuse mining, transactions and jobs, as a read-only follower does.
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
     /// Entry point that performs top-level routing based on the parsed CLI command.
     pub async fn handle_command(
         &mut self,
         command: P2pCommands,
     ) -> Result<String, Box<dyn Error>> {
+        if self.read_only
+            && matches!(command, P2pCommands::Mine | P2pCommands::Tx { .. } | P2pCommands::Job { .. })
+        {
+            return Ok("This node is a read-only follower; send writes to a full node.".to_string());
+        }
         match command {
             P2pCommands::Info => self.info().await,
             P2pCommands::Mine => self.mine().await,
@@ -1,5 +1,7 @@
-use super::core::{CommandHandler, JobQueue};
-use runtime::job::Job;
+use super::core::CommandHandler;
+use crate::daemon::fair_queue::poster_of;
+use runtime::blockchain::Transaction;
+use runtime::job::{Job, JobRequirements};
 use std::error::Error;
 use tracing::info;
 
@@ -14,16 +16,124 @@ impl CommandHandler {
                 model_id,
                 dataset_id,
                 iterations,
+                reward,
+                from_secret_key_file,
+                nonce,
+                fee,
+                min_gpu_memory_gb,
+                min_stake,
+                min_reputation,
+                pinned,
+                excluded,
+                milestones,
+                milestone_evaluator,
+                dry_run,
             } => {
+                if let Some(scheduler) = self.scheduler.as_ref().filter(|_| !dry_run) {
+                    let queued = self.job_queue.lock().await.len();
+                    scheduler.lock().await.admit(queued)?;
+                }
+                // Skip ids already escrowed on chain, e.g. by an earlier daemon run.
+                {
+                    let chain = self.blockchain.lock().await;
+                    while chain.state.job_escrows.contains_key(&self.job_id_counter) {
+                        self.job_id_counter += 1;
+                    }
+                }
                 let job_id = self.job_id_counter;
-                self.job_id_counter += 1;
 
-                let job = Job::new(job_id, model_id, dataset_id, iterations);
-                self.job_queue.lock().await.push_back(job.clone());
+                let mut job = Job::new(job_id, model_id, dataset_id, iterations).with_reward(reward);
+                let requirements =
+                    JobRequirements { min_gpu_memory_gb, min_stake, min_reputation, pinned, excluded };
+                if requirements != JobRequirements::default() {
+                    job = job.with_requirements(requirements);
+                }
+                if let Some(evaluator) = milestone_evaluator {
+                    job = job.with_milestones(milestones, &evaluator);
+                }
+                if dry_run {
+                    let path = from_secret_key_file.ok_or("--dry-run needs --from-secret-key-file")?;
+                    let secret_key = self.read_secret_key(&path)?;
+                    let poster = hex::encode(secret_key.to_public().to_bytes());
+                    let nonce = match nonce {
+                        Some(nonce) => nonce,
+                        None => self.next_nonce(&poster).await,
+                    };
+                    let tx = Transaction::new_post_job_signed(&secret_key, job.posted_by(&poster, nonce), fee, nonce);
+                    return self.dry_run(&tx).await;
+                }
+                self.job_id_counter += 1;
+                let trace = job.trace.clone().expect("new jobs are traced");
+                let escrow = match (reward, from_secret_key_file) {
+                    (0, _) | (_, None) => None,
+                    (_, Some(path)) => {
+                        let secret_key = self.read_secret_key(&path)?;
+                        let poster = hex::encode(secret_key.to_public().to_bytes());
+                        let nonce = self.nonce_or_allocate(nonce, &poster).await;
+                        // Escrowed jobs go by the id their post derives.
+                        job = job.posted_by(&poster, nonce);
+                        let tx = Transaction::new_post_job_signed(&secret_key, job.clone(), fee, nonce);
+                        if let Some(scheduler) = &self.scheduler {
+                            let held = {
+                                let chain = self.blockchain.lock().await;
+                                let queue = self.job_queue.lock().await;
+                                queue
+                                    .iter()
+                                    .filter_map(|j| poster_of(&chain.state, j))
+                                    .filter(|p| p.id == tx.from)
+                                    .count()
+                            };
+                            scheduler.lock().await.admit_poster(&tx.from, held)?;
+                        }
+                        Some(self.submit_transaction(tx).await?)
+                    }
+                };
+                trace.span("post").in_scope(|| info!("Added new job to queue: {:?}", job));
+                let job_id = job.id;
+                self.job_queue.lock().await.push_back(job);
 
-                info!("Added new job to queue: {:?}", job);
-                Ok(format!("Submitted job with ID: {}", job_id))
+                Ok(match escrow {
+                    Some(tx_hash) => format!(
+                        "Submitted job with ID: {} (trace {}), escrowing {} in transaction {}",
+                        job_id, trace.trace_id, reward, tx_hash
+                    ),
+                    None => format!("Submitted job with ID: {} (trace {})", job_id, trace.trace_id),
+                })
+            }
+            crate::cli::JobCommands::Cancel { job_id, from_secret_key_file, nonce, fee } => {
+                let secret_key = self.read_secret_key(&from_secret_key_file)?;
+                let poster = hex::encode(secret_key.to_public().to_bytes());
+                let nonce = self.nonce_or_allocate(nonce, &poster).await;
+                let tx = Transaction::new_cancel_job_signed(&secret_key, job_id, fee, nonce);
+                let tx_hash = self.submit_transaction(tx).await?;
+                self.job_queue.lock().await.retain(|job| job.id != job_id);
+                Ok(format!("Cancelled job {} in transaction {}", job_id, tx_hash))
+            }
+            crate::cli::JobCommands::SignOff {
+                job_id,
+                milestone,
+                worker,
+                from_secret_key_file,
+                nonce,
+                fee,
+            } => {
+                let secret_key = self.read_secret_key(&from_secret_key_file)?;
+                let evaluator = hex::encode(secret_key.to_public().to_bytes());
+                let nonce = self.nonce_or_allocate(nonce, &evaluator).await;
+                let tx = Transaction::new_milestone_sign_off_signed(
+                    &secret_key,
+                    job_id,
+                    milestone,
+                    worker,
+                    fee,
+                    nonce,
+                );
+                let tx_hash = self.submit_transaction(tx).await?;
+                Ok(format!(
+                    "Signed off milestone {} of job {} in transaction {}",
+                    milestone, job_id, tx_hash
+                ))
             }
         }
     }
-} 
\ No newline at end of file
+}
@@ -1,10 +1,11 @@
 use super::core::{CommandHandler, Mempool};
 use runtime::{
-    blockchain::{self, validation, Transaction},
+    blockchain::{self, transaction::StorageTx, validation, Transaction},
+    gossip::GossipTopic,
     miner,
-    p2p_service::WireMessage,
+    p2p_service::{encode_message, WireMessage},
+    pouw,
 };
-use libp2p::gossipsub::IdentTopic;
 use std::collections::HashSet;
 use std::error::Error;
 use tracing::{error, info};
@@ -28,8 +29,27 @@ impl CommandHandler {
         };
         let block_hash = new_block.hash.clone();
         let num_txs = new_block.transactions.len();
-        let total_fees: u64 = new_block.transactions.iter().map(|tx| tx.fee).sum();
-        let miner_reward = blockchain::constants::BLOCK_REWARD.saturating_add(total_fees);
+        let (total_fees, scoring, escrowed) = {
+            let chain = self.blockchain.lock().await;
+            // The job may have been posted in this very block.
+            let escrowed = new_block.task.job_id.and_then(|id| {
+                chain.state.job_escrows.get(&id).map(|e| e.mining_reward()).or_else(|| {
+                    new_block.transactions.iter().find_map(|tx| match &tx.storage {
+                        Some(StorageTx::PostJob { job }) if job.id == id => Some(job.reward - job.milestone_reward()),
+                        _ => None,
+                    })
+                })
+            });
+            // Settlement follows the parameters genesis fixed, and the
+            // burned share of each fee never reaches the miner.
+            let params = &chain.state.consensus;
+            let total_fees: u64 = new_block.transactions.iter().map(|tx| params.rebate.kept(tx.fee)).sum();
+            (total_fees, params.scoring.clone(), escrowed)
+        };
+        let score = pouw::score(new_block.solution.accuracy, &scoring);
+        let base_reward = score.reward_share(blockchain::constants::BLOCK_REWARD);
+        let job_reward = escrowed.map_or(0, |reward| score.reward_share(reward));
+        let miner_reward = base_reward.saturating_add(job_reward).saturating_add(total_fees);
 
         let included_txs = new_block.transactions.clone();
         let block_to_broadcast = new_block.clone();
@@ -47,33 +67,40 @@ impl CommandHandler {
         }
 
         let message = WireMessage::Block(block_to_broadcast);
-        let topic = IdentTopic::new("bcai_global");
         self.p2p_handle
-            .send_message(topic, serde_json::to_vec(&message)?)
+            .gossip(GossipTopic::Blocks, encode_message(&message))
             .await?;
 
         Ok(format!(
-            "Success! Mined and broadcast new block #{}:\n  Hash: {}\n  Transactions: {}\n  Miner Reward: {} ({} base + {} fees)",
+            "Success! Mined and broadcast new block #{}:\n  Hash: {}\n  Transactions: {}\n  Miner Reward: {} ({} base + {} job at {}% credit + {} fees)",
             self.blockchain.lock().await.blocks.len() - 1,
             block_hash,
             num_txs,
             miner_reward,
-            blockchain::constants::BLOCK_REWARD,
+            base_reward,
+            job_reward,
+            score.reward_bps / 100,
             total_fees
         ))
     }
 
     /// Remove included or invalid transactions from the mempool.
     async fn prune_mempool(&self, included_txs: &[Transaction]) {
-        let mut mempool_guard = self.mempool.lock().await;
         let chain_guard = self.blockchain.lock().await;
+        let mut mempool_guard = self.mempool.lock().await;
 
         let included_hashes: HashSet<_> = included_txs.iter().map(|tx| tx.hash()).collect();
         mempool_guard.retain(|tx| !included_hashes.contains(&tx.hash()));
 
-        mempool_guard.retain(|tx| {
-            validation::validate_transaction_stateful(tx, &chain_guard.state).is_ok()
-        });
+        // Keep what still applies in each sender's nonce order.
+        let mut state = chain_guard.state.clone();
+        let mut remaining: Vec<Transaction> = mempool_guard.drain().collect();
+        remaining.sort_by(|a, b| (&a.from, a.nonce).cmp(&(&b.from, b.nonce)));
+        for tx in remaining {
+            if validation::validate_transaction_stateful(&tx, &state).is_ok() && state.apply_transaction(&tx).is_ok() {
+                mempool_guard.insert(tx);
+            }
+        }
 
         info!(
             "Mempool pruned. Included: {}. Remaining: {}.",
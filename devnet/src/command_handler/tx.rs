@@ -1,16 +1,12 @@
 use super::core::CommandHandler;
-use runtime::{
-    blockchain::{validation, Transaction},
-    p2p_service::WireMessage,
-};
+use crate::daemon::{mempool, nonces};
+use runtime::blockchain::Transaction;
 use schnorrkel::{PublicKey, SecretKey};
 use std::{
     error::Error,
     fs,
     path::Path,
 };
-use tracing::info;
-use libp2p::gossipsub::IdentTopic;
 
 impl CommandHandler {
     /// Handle transaction-related subcommands.
@@ -25,8 +21,14 @@ impl CommandHandler {
                 amount,
                 fee,
                 nonce,
+                dry_run,
             } => {
                 let secret_key = self.read_secret_key(&from_secret_key_file)?;
+                let from = hex::encode(secret_key.to_public().to_bytes());
+                let nonce = match (nonce, dry_run) {
+                    (None, true) => self.next_nonce(&from).await,
+                    _ => self.nonce_or_allocate(nonce, &from).await,
+                };
 
                 let to_pk_bytes = hex::decode(&to_pubkey)
                     .map_err(|_| "Invalid recipient public key hex")?;
@@ -38,44 +40,54 @@ impl CommandHandler {
                     to_public_key,
                     amount,
                     fee,
-                    nonce.unwrap(),
+                    nonce,
                 );
+                if dry_run {
+                    return self.dry_run(&tx).await;
+                }
 
-                self.validate_transaction_for_mempool(&tx).await?;
-
-                let tx_hash = tx.hash();
-                self.mempool.lock().await.insert(tx.clone());
-
-                let message = WireMessage::Transaction(tx);
-                let topic = IdentTopic::new("bcai_global");
-                self.p2p_handle
-                    .send_message(topic, serde_json::to_vec(&message)?)
-                    .await?;
-
+                let tx_hash = self.submit_transaction(tx).await?;
                 Ok(format!("Submitted transaction {} to network.", tx_hash))
             }
         }
     }
 
-    async fn validate_transaction_for_mempool(&self, tx: &Transaction) -> Result<(), String> {
-        let chain = self.blockchain.lock().await;
+    /// Validate a transaction, add it to the mempool and broadcast it,
+    /// returning its hash. With a nonce service, a transaction ahead of its
+    /// sender's next nonce is held until its turn; see [`mempool::submit`].
+    pub(super) async fn submit_transaction(&self, tx: Transaction) -> Result<String, Box<dyn Error>> {
+        Ok(mempool::submit(&self.blockchain, &self.mempool, &self.p2p_handle, self.nonces.as_ref(), tx).await?)
+    }
 
-        validation::validate_transaction_stateless(tx)
-            .and_then(|_| validation::validate_transaction_stateful(tx, &chain.state))
-            .map_err(|e| e.to_string())?;
+    /// The nonce `account`'s next transaction into the mempool must carry.
+    pub(super) async fn next_nonce(&self, account: &str) -> u64 {
+        mempool::next_nonce(&self.blockchain, &self.mempool, account).await
+    }
 
-        let mempool_guard = self.mempool.lock().await;
-        if mempool_guard
-            .iter()
-            .any(|mempool_tx| mempool_tx.hash() == tx.hash())
-        {
-            return Err("Transaction already in mempool".to_string());
+    /// `nonce` if given, else one allocated for `account`: from the nonce
+    /// service if there is one, so concurrent commands get distinct nonces.
+    pub(super) async fn nonce_or_allocate(&self, nonce: Option<u64>, account: &str) -> u64 {
+        if let Some(nonce) = nonce {
+            return nonce;
         }
+        let next = self.next_nonce(account).await;
+        match &self.nonces {
+            Some(service) => service.lock().await.allocate(account, next, nonces::unix_now()),
+            None => next,
+        }
+    }
 
-        Ok(())
+    /// Describe what `tx` would do if included in the next block.
+    pub(super) async fn dry_run(&self, tx: &Transaction) -> Result<String, Box<dyn Error>> {
+        let simulation = self.blockchain.lock().await.simulate_transaction(tx)?;
+        Ok(format!(
+            "Dry run of transaction {}, nothing submitted:\n{}",
+            simulation.hash,
+            serde_json::to_string_pretty(&simulation)?
+        ))
     }
 
-    fn read_secret_key(&self, path: &Path) -> Result<SecretKey, Box<dyn Error>> {
+    pub(super) fn read_secret_key(&self, path: &Path) -> Result<SecretKey, Box<dyn Error>> {
         let key_bytes = fs::read(path)?;
         SecretKey::from_bytes(&key_bytes)
             .map_err(|e| format!("Failed to create secret key from bytes: {}", e).into())
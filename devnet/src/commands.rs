@@ -1,10 +1,18 @@
 //! Clap-based command-line arguments for the devnet token/staking CLI.
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::ledger::NATIVE_ASSET;
+use crate::persistence::Profile;
 
 #[derive(Parser, Debug)]
 #[command(name = "devnet", about = "Dev network CLI with token, staking and training features")]
 pub struct Cli {
+    /// Storage profile: `test` keeps state in memory, `devnet` writes JSON
+    /// files and `node` uses an embedded database.
+    #[arg(long, global = true, value_enum, default_value_t = Profile::Devnet)]
+    pub profile: Profile,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -14,19 +22,64 @@ pub enum Commands {
     /// Initialize ledger file
     Init,
     /// Mint tokens
-    Mint { account: String, amount: u64 },
+    Mint {
+        account: String,
+        amount: u64,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
     /// Transfer tokens
-    Transfer { from: String, to: String, amount: u64 },
+    Transfer {
+        from: String,
+        to: String,
+        amount: u64,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
     /// Stake tokens
-    Stake { account: String, amount: u64 },
+    Stake {
+        account: String,
+        amount: u64,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
     /// Unstake tokens
-    Unstake { account: String, amount: u64 },
+    Unstake {
+        account: String,
+        amount: u64,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
     /// Slash staked tokens to the treasury
     Slash { account: String, amount: u64 },
     /// Burn tokens from an account
-    Burn { account: String, amount: u64 },
+    Burn {
+        account: String,
+        amount: u64,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
     /// Show balances
-    Balance { account: String },
+    Balance {
+        account: String,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
+    /// Register a bridged asset
+    RegisterAsset {
+        asset: String,
+        /// Chain the asset is bridged from
+        #[arg(long)]
+        origin_chain: String,
+        /// Allow the asset to be staked
+        #[arg(long)]
+        stakeable: bool,
+        /// Forbid transfers of the asset
+        #[arg(long)]
+        non_transferable: bool,
+    },
+    /// List registered assets
+    Assets,
     /// Show reputation score
     Reputation { account: String },
     /// Adjust reputation by delta
@@ -34,7 +87,14 @@ pub enum Commands {
     /// Mine a block executing a dummy GPU task
     Mine,
     /// Run a PoUW training task
-    Train { size: usize, seed: u64, difficulty: u32 },
+    Train {
+        size: usize,
+        seed: u64,
+        /// PoW target override; defaults to the retargeting algorithm's
+        /// starting difficulty.
+        #[arg(long)]
+        difficulty: Option<u32>,
+    },
     /// Train a logistic regression model on the digits dataset
     Mnist,
     /// Train a neural network
@@ -51,16 +111,180 @@ pub enum Commands {
         #[command(subcommand)]
         job: JobCommands,
     },
+    /// Manage the chain genesis file
+    Genesis {
+        #[command(subcommand)]
+        genesis: GenesisCommands,
+    },
+    /// Inspect the ledger's history
+    Ledger {
+        #[command(subcommand)]
+        ledger: LedgerCommands,
+    },
+    /// Manage payment streams
+    Stream {
+        #[command(subcommand)]
+        stream: StreamCommands,
+    },
+    /// Move tokens between the devnet and external chains
+    Bridge {
+        #[command(subcommand)]
+        bridge: BridgeCommands,
+    },
+    /// Run offline simulations
+    Simulate {
+        #[command(subcommand)]
+        simulate: SimulateCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SimulateCommands {
+    /// Simulate posters, workers and storage providers under the chain's
+    /// reward, fee and slashing rules and print supply, price and
+    /// participation trajectories as CSV
+    Economy {
+        /// TOML simulation config; omitted fields take their defaults
+        #[arg(long)]
+        config: PathBuf,
+        /// Write the CSV here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StreamCommands {
+    /// Pay an amount per hour from one account to another until cancelled
+    Open {
+        from: String,
+        to: String,
+        rate_per_hour: u64,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
+    /// Settle a stream and close it
+    Cancel { id: u64 },
+    /// Settle every open stream, e.g. once per epoch
+    Settle,
+    /// List open streams
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BridgeCommands {
+    /// Lock tokens on an external chain and mint them, wrapped, to an
+    /// account once the lock is confirmed
+    Deposit {
+        account: String,
+        amount: u64,
+        /// e.g. ethereum, polygon or bsc
+        #[arg(long)]
+        chain: String,
+        /// Address the tokens are locked from
+        #[arg(long)]
+        from_address: String,
+        /// Wrapped asset to mint; defaults to the chain's, e.g. wETH
+        #[arg(long)]
+        asset: Option<String>,
+    },
+    /// Burn wrapped tokens and unlock them on an external chain once the
+    /// burn is confirmed
+    Withdraw {
+        account: String,
+        amount: u64,
+        #[arg(long)]
+        chain: String,
+        /// Address the tokens are unlocked to
+        #[arg(long)]
+        to_address: String,
+        #[arg(long)]
+        asset: Option<String>,
+    },
+    /// Show a bridge transfer, or every pending one
+    Status { id: Option<u64> },
+    /// Add a wrapped asset and native tokens to the asset's swap pool
+    AddLiquidity { provider: String, asset: String, amount: u64, native: u64 },
+    /// Swap a wrapped asset into native tokens through its pool
+    Swap {
+        account: String,
+        asset: String,
+        amount: u64,
+        /// Fail if the swap would pay out fewer native tokens
+        #[arg(long, default_value_t = 0)]
+        min_out: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum LedgerCommands {
+    /// List every change to an account's balance
+    History { account: String },
+    /// Show an account's balance at a past height or time
+    At {
+        account: String,
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+        /// Ledger height, i.e. number of operations applied
+        #[arg(long, conflicts_with = "timestamp", required_unless_present = "timestamp")]
+        height: Option<u64>,
+        /// Unix seconds
+        #[arg(long)]
+        timestamp: Option<i64>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 pub enum JobCommands {
     /// Post a new job
-    Post { poster: String, description: String, reward: u64 },
+    Post {
+        poster: String,
+        description: String,
+        reward: u64,
+        /// Asset the reward is paid in, e.g. a wrapped token like wETH
+        #[arg(long, default_value = NATIVE_ASSET)]
+        asset: String,
+    },
     /// Assign a worker
-    Assign { job_id: u64, worker: String },
-    /// Complete a job
-    Complete { job_id: u64 },
+    Assign { job_id: String, worker: String },
+    /// Complete a job, paying its reward to the worker
+    Complete {
+        job_id: String,
+        worker: String,
+        /// Swap a bridged reward into native tokens, paying out at least this many
+        #[arg(long)]
+        swap_min_out: Option<u64>,
+    },
     /// List jobs
     List,
-} 
\ No newline at end of file
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GenesisCommands {
+    /// Write a genesis.json for a new testnet
+    Generate {
+        /// Where to write the file
+        #[arg(short, long, default_value = "genesis.json")]
+        output: PathBuf,
+        #[arg(long, default_value = "bcai-devnet")]
+        chain_id: String,
+        /// Genesis block timestamp; defaults to now
+        #[arg(long)]
+        timestamp: Option<i64>,
+        /// Funded account as `pubkey=amount`; repeatable
+        #[arg(long = "balance", value_parser = parse_allocation)]
+        balances: Vec<(String, u64)>,
+        /// Genesis validator as `pubkey=stake`; repeatable
+        #[arg(long = "validator", value_parser = parse_allocation)]
+        validators: Vec<(String, u64)>,
+        /// Storage price in BCAI per GiB per copy
+        #[arg(long)]
+        price_per_gb: Option<u128>,
+    },
+}
+
+fn parse_allocation(s: &str) -> Result<(String, u64), String> {
+    let (key, amount) = s.split_once('=').ok_or_else(|| format!("expected pubkey=amount, got {}", s))?;
+    let amount = amount.parse().map_err(|e| format!("invalid amount in {}: {}", s, e))?;
+    Ok((key.to_string(), amount))
+}
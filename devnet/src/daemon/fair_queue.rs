@@ -0,0 +1,176 @@
+//! Weighted fair queuing across job posters.
+//!
+//! Served in arrival order, a poster who submits hundreds of jobs at once
+//! would hold every free node until the flood drains. Instead each poster is
+//! a flow with a weight: stake raises it, recent usage lowers it. A pass
+//! orders the waiting jobs by virtual finish time, the k-th waiting job of a
+//! poster finishing at `k / weight`, so posters interleave in proportion to
+//! their weights whatever the size of their backlog. Jobs with no known
+//! poster, i.e. not escrowed on chain, share one anonymous flow.
+//!
+//! Usage is the number of jobs assigned to a poster, decayed every pass, so
+//! a poster who was just served heavily yields to the others for a while.
+
+use runtime::blockchain::state::State;
+use runtime::job::Job;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Flow of jobs whose poster is unknown.
+pub const ANONYMOUS: &str = "";
+
+/// Usage below which an idle flow is forgotten.
+const FORGET_BELOW: f64 = 0.01;
+
+/// Who posted a queued job, and their stake when it was scheduled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poster {
+    pub id: String,
+    pub stake: u64,
+}
+
+/// The poster of `job` as recorded in its on-chain escrow.
+pub fn poster_of(state: &State, job: &Job) -> Option<Poster> {
+    let escrow = state.job_escrows.get(&job.id)?;
+    let stake = state.stakes.get(&escrow.poster).copied().unwrap_or(0);
+    Some(Poster { id: escrow.poster.clone(), stake })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FairnessConfig {
+    /// Stake that adds one to a poster's weight.
+    pub stake_unit: u64,
+    /// Cap on the weight stake can give.
+    pub max_stake_weight: f64,
+    /// Recent assignments that halve a poster's weight.
+    pub usage_unit: f64,
+    /// Factor applied to every poster's usage once per pass.
+    pub usage_decay: f64,
+    /// Share of [`super::scheduler::SchedulerConfig::max_queued`] one poster
+    /// may hold before their submissions are refused.
+    pub max_poster_share: f64,
+}
+
+impl Default for FairnessConfig {
+    fn default() -> Self {
+        Self { stake_unit: 1_000, max_stake_weight: 8.0, usage_unit: 10.0, usage_decay: 0.98, max_poster_share: 0.5 }
+    }
+}
+
+impl FairnessConfig {
+    /// Weight of a poster with `stake` and decayed `usage`.
+    pub fn weight(&self, stake: u64, usage: f64) -> f64 {
+        let staked = 1.0 + stake as f64 / self.stake_unit.max(1) as f64;
+        staked.min(self.max_stake_weight.max(1.0)) / (1.0 + usage / self.usage_unit.max(f64::EPSILON))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Flow {
+    stake: u64,
+    usage: f64,
+    waiting: usize,
+    assigned: u64,
+}
+
+/// Per-poster figures served by `getFairnessStats`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowStats {
+    /// Poster public key, empty for the anonymous flow.
+    pub poster: String,
+    pub stake: u64,
+    pub weight: f64,
+    pub usage: f64,
+    /// Jobs waiting for a node at the last pass.
+    pub waiting: usize,
+    /// Jobs assigned over the daemon's lifetime.
+    pub assigned: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FairnessStats {
+    pub passes: u64,
+    /// Jain's index over the usage-to-weight ratio of flows with waiting
+    /// jobs: 1.0 when each is served exactly in proportion to its weight.
+    pub fairness_index: f64,
+    pub flows: Vec<FlowStats>,
+}
+
+#[derive(Debug, Default)]
+pub struct FairQueue {
+    config: FairnessConfig,
+    flows: BTreeMap<String, Flow>,
+    passes: u64,
+}
+
+impl FairQueue {
+    pub fn new(config: FairnessConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &FairnessConfig {
+        &self.config
+    }
+
+    /// Starts a pass: decays usage and orders the `waiting` jobs, given in
+    /// arrival order, by virtual finish time. Each job comes back with the
+    /// flow it belongs to.
+    pub fn order<'a>(&mut self, waiting: Vec<(&'a Job, Option<Poster>)>) -> Vec<(&'a Job, String)> {
+        self.passes += 1;
+        for flow in self.flows.values_mut() {
+            flow.usage *= self.config.usage_decay;
+            flow.waiting = 0;
+        }
+
+        let mut tagged = Vec::with_capacity(waiting.len());
+        for (position, (job, poster)) in waiting.into_iter().enumerate() {
+            let (id, stake) = poster.map_or((ANONYMOUS.to_string(), 0), |p| (p.id, p.stake));
+            let flow = self.flows.entry(id.clone()).or_default();
+            flow.stake = stake;
+            flow.waiting += 1;
+            let finish = flow.waiting as f64 / self.config.weight(flow.stake, flow.usage);
+            tagged.push((finish, position, job, id));
+        }
+        self.flows.retain(|_, f| f.waiting > 0 || f.usage >= FORGET_BELOW);
+        tagged.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        tagged.into_iter().map(|(_, _, job, flow)| (job, flow)).collect()
+    }
+
+    /// Charges `flow` for a job assigned to one of its posters' nodes.
+    pub fn charge(&mut self, flow: &str) {
+        let flow = self.flows.entry(flow.to_string()).or_default();
+        flow.usage += 1.0;
+        flow.assigned += 1;
+    }
+
+    pub fn weight(&self, flow: &str) -> Option<f64> {
+        self.flows.get(flow).map(|f| self.config.weight(f.stake, f.usage))
+    }
+
+    pub fn stats(&self) -> FairnessStats {
+        let flows: Vec<FlowStats> = self
+            .flows
+            .iter()
+            .map(|(poster, f)| FlowStats {
+                poster: poster.clone(),
+                stake: f.stake,
+                weight: self.config.weight(f.stake, f.usage),
+                usage: f.usage,
+                waiting: f.waiting,
+                assigned: f.assigned,
+            })
+            .collect();
+        let ratios: Vec<f64> = flows
+            .iter()
+            .filter(|f| f.waiting > 0)
+            .map(|f| f.usage / self.config.weight(f.stake, 0.0))
+            .collect();
+        let sum: f64 = ratios.iter().sum();
+        let squares: f64 = ratios.iter().map(|r| r * r).sum();
+        let fairness_index = if squares > 0.0 { sum * sum / (ratios.len() as f64 * squares) } else { 1.0 };
+        FairnessStats { passes: self.passes, fairness_index, flows }
+    }
+}
@@ -0,0 +1,224 @@
+//! Follower mode: a read replica of the chain.
+//!
+//! A follower imports the blocks its peers gossip, requests from them the
+//! history gossip did not bring (anything before the first block it heard,
+//! or missed while offline), and serves JSON-RPC,
+//! GraphQL, WebSocket and webhook traffic from them, but never mines,
+//! schedules or runs jobs, and refuses transactions. Teams add followers to
+//! scale out query capacity without adding to consensus; they start with
+//! [`FollowerConfig`] limits well below a full node's. The daemon runs as a
+//! follower when [`FOLLOWER_ENV`](super::FOLLOWER_ENV) is set.
+
+use runtime::blockchain::{Block, Blockchain};
+use runtime::connection_manager::ConnectionLimits;
+use runtime::p2p_service::{P2PConfig, P2PHandle, WireMessage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::warn;
+
+/// Follower state shared between the block importer and the RPC server.
+pub type SharedFollower = Arc<Mutex<Follower>>;
+
+/// Whether the daemon takes part in consensus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeMode {
+    /// Mines, schedules jobs and accepts transactions.
+    #[default]
+    Full,
+    /// Only follows the chain and serves reads.
+    Follower,
+}
+
+impl NodeMode {
+    /// The mode `value` of [`FOLLOWER_ENV`](super::FOLLOWER_ENV) asks for.
+    pub fn from_env_value(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()) {
+            Some(v) if matches!(v.as_str(), "1" | "true" | "yes") => Self::Follower,
+            _ => Self::Full,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FollowerConfig {
+    /// Peers kept connected; a few suffice to hear every block.
+    pub max_peers: usize,
+    /// Payload bodies fetched from peers at once.
+    pub max_concurrent_fetches: usize,
+    /// Blocks received ahead of the tip kept until the ones before arrive.
+    pub max_pending_blocks: usize,
+}
+
+impl Default for FollowerConfig {
+    fn default() -> Self {
+        Self { max_peers: 8, max_concurrent_fetches: 1, max_pending_blocks: 64 }
+    }
+}
+
+impl FollowerConfig {
+    /// P2P settings of a follower: fewer connections and fetches than a
+    /// full node, only to validators, and no roles offered.
+    pub fn p2p_config(&self) -> P2PConfig {
+        let connections = ConnectionLimits {
+            validators: self.max_peers,
+            storage_providers: 0,
+            relays: 0,
+            max_peers: self.max_peers,
+            ..ConnectionLimits::default()
+        };
+        P2PConfig {
+            max_concurrent_fetches: self.max_concurrent_fetches,
+            roles: Vec::new(),
+            connections,
+            ..P2PConfig::default()
+        }
+    }
+}
+
+/// How far a node has followed the chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub mode: NodeMode,
+    pub height: u64,
+    /// Gossiped blocks added to the chain.
+    pub imported: u64,
+    /// Gossiped blocks that failed validation.
+    pub rejected: u64,
+    /// Blocks held until the ones before them arrive.
+    pub pending: usize,
+    pub last_error: Option<String>,
+}
+
+/// Imports gossiped blocks in order.
+#[derive(Debug, Default)]
+pub struct Follower {
+    config: FollowerConfig,
+    /// Blocks ahead of the tip, by index.
+    pending: BTreeMap<u32, Block>,
+    imported: u64,
+    rejected: u64,
+    last_error: Option<String>,
+}
+
+impl Follower {
+    pub fn new(config: FollowerConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &FollowerConfig {
+        &self.config
+    }
+
+    /// Adds `block` to `chain` if it extends the tip, followed by the held
+    /// blocks it connects. A block further ahead is held until the ones
+    /// before it arrive; one the chain already has is ignored. Returns the
+    /// number of blocks added.
+    pub fn receive(&mut self, chain: &mut Blockchain, block: Block) -> usize {
+        let next = chain.get_tip().index + 1;
+        if block.index < next {
+            return 0;
+        }
+        if block.index > next {
+            if self.pending.len() < self.config.max_pending_blocks || self.pending.contains_key(&block.index) {
+                self.pending.insert(block.index, block);
+            }
+            return 0;
+        }
+        let mut added = 0;
+        let mut candidate = Some(block);
+        while let Some(block) = candidate.take() {
+            let index = block.index;
+            match chain.add_block(block) {
+                Ok(()) => {
+                    added += 1;
+                    self.imported += 1;
+                    candidate = self.pending.remove(&(index + 1));
+                }
+                Err(e) => {
+                    self.rejected += 1;
+                    self.last_error = Some(format!("block {}: {}", index, e));
+                }
+            }
+        }
+        let next = chain.get_tip().index + 1;
+        self.pending = self.pending.split_off(&next);
+        added
+    }
+
+    /// Adds the blocks of a range a peer sent, in order. Returns the number
+    /// of blocks added.
+    pub fn receive_range(&mut self, chain: &mut Blockchain, blocks: Vec<Block>) -> usize {
+        blocks.into_iter().map(|block| self.receive(chain, block)).sum()
+    }
+
+    /// Height to request blocks from when blocks are held waiting for ones
+    /// gossip never brought.
+    pub fn missing_from(&self, chain: &Blockchain) -> Option<u64> {
+        let next = chain.get_tip().index + 1;
+        self.pending.keys().next().filter(|&&first| first > next).map(|_| next as u64)
+    }
+
+    pub fn status(&self, chain: &Blockchain) -> SyncStatus {
+        SyncStatus {
+            mode: NodeMode::Follower,
+            height: chain.height(),
+            imported: self.imported,
+            rejected: self.rejected,
+            pending: self.pending.len(),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// Imports the blocks arriving on `blocks` into `blockchain` until the P2P
+/// service stops, requesting missing history through `p2p_handle`.
+pub async fn run_follower(
+    follower: SharedFollower,
+    blockchain: Arc<Mutex<Blockchain>>,
+    mut blocks: mpsc::UnboundedReceiver<Block>,
+    p2p_handle: P2PHandle,
+) {
+    while let Some(block) = blocks.recv().await {
+        {
+            let mut chain = blockchain.lock().await;
+            follower.lock().await.receive(&mut chain, block);
+        }
+        sync_missing(&follower, &blockchain, &p2p_handle).await;
+    }
+}
+
+/// Requests the blocks below the held ones from peers, a range at a time,
+/// until the gap closes or no peer has them.
+async fn sync_missing(follower: &SharedFollower, blockchain: &Mutex<Blockchain>, p2p_handle: &P2PHandle) {
+    let peers = match p2p_handle.get_peers().await {
+        Ok(peers) => peers,
+        Err(e) => {
+            warn!("Cannot list peers to sync from: {}", e);
+            return;
+        }
+    };
+    for peer in peers {
+        loop {
+            let missing = {
+                let chain = blockchain.lock().await;
+                follower.lock().await.missing_from(&chain)
+            };
+            let Some(from_height) = missing else {
+                return;
+            };
+            let blocks = match p2p_handle.request(peer, WireMessage::GetBlocks { from_height }).await {
+                Ok(WireMessage::Blocks(blocks)) => blocks,
+                _ => break,
+            };
+            let mut chain = blockchain.lock().await;
+            if follower.lock().await.receive_range(&mut chain, blocks) == 0 {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! GraphQL explorer API over the chain indexer.
+//!
+//! Served over plain HTTP (`POST /` with a `{"query", "variables"}` body) at
+//! [`GRAPHQL_ADDR`](super::GRAPHQL_ADDR). Blocks, transactions, accounts, jobs
+//! and models link to each other, so an explorer page is one nested query
+//! instead of a JSON-RPC call per view. Lists are Relay-style connections,
+//! newest first, taking `first` (at most [`MAX_PAGE`]) and an `after` cursor.
+
+use super::indexer::{BlockRecord, JobRecord, ModelRecord, Page, SharedIndexer, TxRecord};
+use super::rpc::{read_http_body, write_http};
+use async_graphql::connection::{Connection, CursorType, Edge};
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, OutputType, Result, Schema};
+use runtime::blockchain::Blockchain;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+/// Items returned when a list query does not say how many.
+pub const DEFAULT_PAGE: usize = 20;
+/// Most items a single list field returns.
+pub const MAX_PAGE: usize = 100;
+/// Deepest nesting a query may use.
+const MAX_DEPTH: usize = 10;
+/// Highest complexity score a query may reach.
+const MAX_COMPLEXITY: usize = 2_000;
+
+pub type ExplorerSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub fn schema(indexer: SharedIndexer, blockchain: Arc<Mutex<Blockchain>>) -> ExplorerSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(indexer)
+        .data(blockchain)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+fn indexer<'a>(ctx: &Context<'a>) -> &'a SharedIndexer {
+    ctx.data_unchecked::<SharedIndexer>()
+}
+
+/// Validated `(after, first)` of a list field.
+fn page_args(after: Option<String>, first: Option<i32>) -> Result<(Option<usize>, usize)> {
+    let after = after.map(|c| usize::decode_cursor(&c).map_err(|_| "invalid cursor")).transpose()?;
+    let first = match first {
+        None => DEFAULT_PAGE,
+        Some(n) if n < 0 => return Err("first must not be negative".into()),
+        Some(n) => (n as usize).min(MAX_PAGE),
+    };
+    Ok((after, first))
+}
+
+fn connection<T, N: OutputType>(page: Page<T>, has_previous: bool, node: impl Fn(T) -> N) -> Connection<usize, N> {
+    let mut connection = Connection::new(has_previous, page.has_next);
+    connection.edges.extend(page.items.into_iter().map(|(key, item)| Edge::new(key, node(item))));
+    connection
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Number of the newest indexed block.
+    async fn height(&self, ctx: &Context<'_>) -> Option<u32> {
+        indexer(ctx).lock().await.height()
+    }
+
+    /// A block by number or hash.
+    async fn block(&self, ctx: &Context<'_>, number: Option<u32>, hash: Option<String>) -> Result<Option<BlockNode>> {
+        let index = indexer(ctx).lock().await;
+        let block = match (number, hash) {
+            (Some(number), None) => index.block(number),
+            (None, Some(hash)) => index.block_by_hash(&hash),
+            _ => return Err("give exactly one of number and hash".into()),
+        };
+        Ok(block.cloned().map(BlockNode))
+    }
+
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, BlockNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.blocks(after, first);
+        Ok(connection(page, after.is_some(), BlockNode))
+    }
+
+    async fn transaction(&self, ctx: &Context<'_>, hash: String) -> Option<TransactionNode> {
+        indexer(ctx).lock().await.transaction(&hash).cloned().map(TransactionNode)
+    }
+
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, TransactionNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.transactions(None, after, first);
+        Ok(connection(page, after.is_some(), TransactionNode))
+    }
+
+    /// Any address; accounts that never transacted have a zero balance.
+    async fn account(&self, address: String) -> AccountNode {
+        AccountNode(address)
+    }
+
+    async fn job(&self, ctx: &Context<'_>, id: u64) -> Option<JobNode> {
+        indexer(ctx).lock().await.job(id).cloned().map(JobNode)
+    }
+
+    async fn jobs(&self, ctx: &Context<'_>, after: Option<String>, first: Option<i32>) -> Result<Connection<usize, JobNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.jobs(after, first);
+        Ok(connection(page, after.is_some(), JobNode))
+    }
+
+    /// The latest endpoint registered for `model_id`.
+    async fn model(&self, ctx: &Context<'_>, model_id: String) -> Option<ModelNode> {
+        indexer(ctx).lock().await.model(&model_id).cloned().map(ModelNode)
+    }
+
+    async fn models(
+        &self,
+        ctx: &Context<'_>,
+        owner: Option<String>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, ModelNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.models(owner.as_deref(), after, first);
+        Ok(connection(page, after.is_some(), ModelNode))
+    }
+}
+
+pub struct BlockNode(BlockRecord);
+
+#[Object(name = "Block")]
+impl BlockNode {
+    async fn number(&self) -> u32 {
+        self.0.number
+    }
+
+    async fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    async fn prev_hash(&self) -> &str {
+        &self.0.prev_hash
+    }
+
+    async fn timestamp(&self) -> i64 {
+        self.0.timestamp
+    }
+
+    async fn difficulty(&self) -> u32 {
+        self.0.difficulty
+    }
+
+    async fn miner(&self) -> AccountNode {
+        AccountNode(self.0.miner.clone())
+    }
+
+    async fn transaction_count(&self) -> usize {
+        self.0.transactions.len()
+    }
+
+    /// The block's transactions, in block order.
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, TransactionNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.block_transactions(self.0.number, after, first);
+        Ok(connection(page, after.is_some(), TransactionNode))
+    }
+
+    /// The escrowed job this block's PoUW task trained.
+    async fn job(&self, ctx: &Context<'_>) -> Option<JobNode> {
+        let id = self.0.job_id?;
+        indexer(ctx).lock().await.job(id).cloned().map(JobNode)
+    }
+}
+
+pub struct TransactionNode(TxRecord);
+
+#[Object(name = "Transaction")]
+impl TransactionNode {
+    async fn hash(&self) -> &str {
+        &self.0.hash
+    }
+
+    async fn from(&self) -> AccountNode {
+        AccountNode(self.0.from.clone())
+    }
+
+    async fn to(&self) -> AccountNode {
+        AccountNode(self.0.to.clone())
+    }
+
+    async fn amount(&self) -> u64 {
+        self.0.amount
+    }
+
+    async fn fee(&self) -> u64 {
+        self.0.fee
+    }
+
+    async fn nonce(&self) -> u64 {
+        self.0.nonce
+    }
+
+    /// `transfer`, or the kind of storage payload, e.g. `registerEndpoint`.
+    async fn kind(&self) -> &str {
+        &self.0.kind
+    }
+
+    async fn block(&self, ctx: &Context<'_>) -> Option<BlockNode> {
+        indexer(ctx).lock().await.block(self.0.block).cloned().map(BlockNode)
+    }
+}
+
+pub struct AccountNode(String);
+
+#[Object(name = "Account")]
+impl AccountNode {
+    async fn address(&self) -> &str {
+        &self.0
+    }
+
+    async fn balance(&self, ctx: &Context<'_>) -> u64 {
+        ctx.data_unchecked::<Arc<Mutex<Blockchain>>>().lock().await.get_balance(&self.0)
+    }
+
+    async fn nonce(&self, ctx: &Context<'_>) -> u64 {
+        ctx.data_unchecked::<Arc<Mutex<Blockchain>>>().lock().await.get_nonce(&self.0)
+    }
+
+    /// Transactions the account sent or received, newest first.
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, TransactionNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.transactions(Some(&self.0), after, first);
+        Ok(connection(page, after.is_some(), TransactionNode))
+    }
+
+    /// Models the account registered endpoints for.
+    async fn models(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> Result<Connection<usize, ModelNode>> {
+        let (after, first) = page_args(after, first)?;
+        let page = indexer(ctx).lock().await.models(Some(&self.0), after, first);
+        Ok(connection(page, after.is_some(), ModelNode))
+    }
+}
+
+pub struct JobNode(JobRecord);
+
+#[Object(name = "Job")]
+impl JobNode {
+    async fn id(&self) -> u64 {
+        self.0.job.id
+    }
+
+    async fn model_id(&self) -> &str {
+        &self.0.job.model_id
+    }
+
+    async fn dataset_id(&self) -> &str {
+        &self.0.job.dataset_id
+    }
+
+    async fn iterations(&self) -> u32 {
+        self.0.job.iterations
+    }
+
+    async fn reward(&self) -> u64 {
+        self.0.job.reward
+    }
+
+    async fn kind(&self) -> Option<&str> {
+        self.0.job.kind.as_deref()
+    }
+
+    async fn completed(&self) -> bool {
+        self.0.completed_in.is_some()
+    }
+
+    /// The block whose PoUW task trained the job.
+    async fn completed_in(&self, ctx: &Context<'_>) -> Option<BlockNode> {
+        let number = self.0.completed_in?;
+        indexer(ctx).lock().await.block(number).cloned().map(BlockNode)
+    }
+
+    /// The model's registered endpoint, if any.
+    async fn model(&self, ctx: &Context<'_>) -> Option<ModelNode> {
+        indexer(ctx).lock().await.model(&self.0.job.model_id).cloned().map(ModelNode)
+    }
+}
+
+pub struct ModelNode(ModelRecord);
+
+#[Object(name = "Model")]
+impl ModelNode {
+    async fn model_id(&self) -> &str {
+        &self.0.model_id
+    }
+
+    async fn endpoint_id(&self) -> &str {
+        &self.0.endpoint_id
+    }
+
+    async fn owner(&self) -> AccountNode {
+        AccountNode(self.0.owner.clone())
+    }
+
+    async fn primary(&self) -> &str {
+        &self.0.primary
+    }
+
+    async fn standbys(&self) -> &[String] {
+        &self.0.standbys
+    }
+
+    /// The block the endpoint was registered in.
+    async fn registered_in(&self, ctx: &Context<'_>) -> Option<BlockNode> {
+        indexer(ctx).lock().await.block(self.0.block).cloned().map(BlockNode)
+    }
+}
+
+/// Serves the explorer schema over HTTP.
+#[derive(Clone)]
+pub struct GraphqlServer {
+    schema: ExplorerSchema,
+}
+
+impl GraphqlServer {
+    pub fn new(schema: ExplorerSchema) -> Self {
+        Self { schema }
+    }
+
+    /// Accept HTTP connections on `addr` until the listener fails.
+    pub async fn serve(self, addr: &str) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("GraphQL listening on http://{}", addr);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    error!("GraphQL connection error: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let body = match read_http_body(&mut stream).await? {
+            Some(body) => body,
+            None => return write_http(&mut stream, "400 Bad Request", b"").await,
+        };
+        let response = match serde_json::from_slice::<async_graphql::Request>(&body) {
+            Ok(request) => self.schema.execute(request).await,
+            Err(e) => async_graphql::Response::from_errors(vec![async_graphql::ServerError::new(e.to_string(), None)]),
+        };
+        let body = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+        write_http(&mut stream, "200 OK", &body).await
+    }
+}
@@ -0,0 +1,289 @@
+//! In-memory chain indexer behind the GraphQL explorer API.
+//!
+//! The chain only stores blocks in order, so finding an account's
+//! transactions or the block that completed a job means scanning every block.
+//! The indexer follows the chain and the job queue and keeps lookup tables
+//! for blocks, transactions, accounts, jobs and registered models, so
+//! explorer queries never hold the blockchain lock while they walk history.
+//!
+//! Lists are returned newest first and paged by key: a cursor is the key of
+//! the last item seen (block number, transaction sequence, job id or model
+//! sequence), so pages stay stable while new items are indexed.
+
+use super::types::JobQueue;
+use runtime::blockchain::{transaction::StorageTx, Block, Blockchain, Transaction};
+use runtime::job::Job;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// How often the indexer follows the chain.
+const INDEX_INTERVAL: Duration = Duration::from_secs(1);
+
+pub type SharedIndexer = Arc<Mutex<Indexer>>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BlockRecord {
+    pub number: u32,
+    pub hash: String,
+    pub prev_hash: String,
+    pub timestamp: i64,
+    pub difficulty: u32,
+    pub miner: String,
+    /// Sequence numbers of the block's transactions, in block order.
+    pub transactions: Vec<usize>,
+    /// Escrowed job mined as the block's PoUW task.
+    pub job_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TxRecord {
+    /// Position of the transaction in the whole chain.
+    pub seq: usize,
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub nonce: u64,
+    /// `transfer`, or the storage payload kind.
+    pub kind: String,
+    pub block: u32,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct JobRecord {
+    pub job: Job,
+    /// Block whose PoUW task trained the job.
+    pub completed_in: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ModelRecord {
+    pub seq: usize,
+    pub model_id: String,
+    pub endpoint_id: String,
+    pub owner: String,
+    pub primary: String,
+    pub standbys: Vec<String>,
+    pub block: u32,
+}
+
+/// One page of a newest-first list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    /// Items with their cursor keys.
+    pub items: Vec<(usize, T)>,
+    pub has_next: bool,
+}
+
+/// The `first` items of newest-first `items` older than `after`.
+fn paginate<'a, T: Clone + 'a>(
+    items: impl Iterator<Item = (usize, &'a T)>,
+    after: Option<usize>,
+    first: usize,
+) -> Page<T> {
+    let mut items: Vec<_> = items
+        .filter(|(key, _)| after.is_none_or(|after| *key < after))
+        .take(first + 1)
+        .map(|(key, item)| (key, item.clone()))
+        .collect();
+    let has_next = items.len() > first;
+    items.truncate(first);
+    Page { items, has_next }
+}
+
+#[derive(Debug, Default)]
+pub struct Indexer {
+    blocks: Vec<BlockRecord>,
+    block_by_hash: HashMap<String, u32>,
+    txs: Vec<TxRecord>,
+    tx_by_hash: HashMap<String, usize>,
+    account_txs: HashMap<String, Vec<usize>>,
+    jobs: BTreeMap<u64, JobRecord>,
+    /// Block that completed each job, kept for jobs not seen in the queue.
+    completions: HashMap<u64, u32>,
+    models: Vec<ModelRecord>,
+}
+
+impl Indexer {
+    /// Index blocks added since the last sync. If the indexed tip is no
+    /// longer on the chain, chain-derived tables are rebuilt from genesis.
+    pub fn sync(&mut self, chain: &Blockchain) {
+        let diverged = self
+            .blocks
+            .last()
+            .is_some_and(|tip| chain.blocks.get(tip.number as usize).is_none_or(|b| b.hash != tip.hash));
+        if diverged {
+            let jobs = std::mem::take(&mut self.jobs);
+            *self = Indexer::default();
+            self.jobs = jobs.into_iter().map(|(id, r)| (id, JobRecord { completed_in: None, ..r })).collect();
+        }
+        for block in chain.blocks.iter().skip(self.blocks.len()) {
+            self.index_block(block);
+        }
+    }
+
+    /// Record jobs waiting in the queue. Jobs stay indexed once they leave it.
+    pub fn sync_jobs<'a>(&mut self, queue: impl IntoIterator<Item = &'a Job>) {
+        for job in queue {
+            let completed_in = self.completions.get(&job.id).copied();
+            self.jobs.insert(job.id, JobRecord { job: job.clone(), completed_in });
+        }
+    }
+
+    fn index_block(&mut self, block: &Block) {
+        let mut transactions = Vec::with_capacity(block.transactions.len());
+        for tx in &block.transactions {
+            let seq = self.index_transaction(tx, block.index);
+            transactions.push(seq);
+        }
+        if let Some(job_id) = block.task.job_id {
+            self.completions.insert(job_id, block.index);
+            if let Some(record) = self.jobs.get_mut(&job_id) {
+                record.completed_in = Some(block.index);
+            }
+        }
+        self.block_by_hash.insert(block.hash.clone(), block.index);
+        self.blocks.push(BlockRecord {
+            number: block.index,
+            hash: block.hash.clone(),
+            prev_hash: block.prev_hash.clone(),
+            timestamp: block.timestamp,
+            difficulty: block.difficulty,
+            miner: block.miner.clone(),
+            transactions,
+            job_id: block.task.job_id,
+        });
+    }
+
+    fn index_transaction(&mut self, tx: &Transaction, block: u32) -> usize {
+        let seq = self.txs.len();
+        let hash = tx.hash();
+        let kind = match &tx.storage {
+            None => "transfer".to_string(),
+            Some(storage) => storage_kind(storage),
+        };
+        if let Some(StorageTx::RegisterEndpoint { record }) = &tx.storage {
+            self.models.push(ModelRecord {
+                seq: self.models.len(),
+                model_id: record.model_id.clone(),
+                endpoint_id: record.endpoint_id.clone(),
+                owner: record.owner.clone(),
+                primary: record.primary.clone(),
+                standbys: record.standbys.clone(),
+                block,
+            });
+        }
+        for account in [&tx.from, &tx.to] {
+            let txs = self.account_txs.entry(account.clone()).or_default();
+            // Self-transfers are listed once.
+            if txs.last() != Some(&seq) {
+                txs.push(seq);
+            }
+        }
+        self.tx_by_hash.insert(hash.clone(), seq);
+        self.txs.push(TxRecord {
+            seq,
+            hash,
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            amount: tx.amount,
+            fee: tx.fee,
+            nonce: tx.nonce,
+            kind,
+            block,
+        });
+        seq
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        self.blocks.last().map(|b| b.number)
+    }
+
+    pub fn block(&self, number: u32) -> Option<&BlockRecord> {
+        self.blocks.get(number as usize)
+    }
+
+    pub fn block_by_hash(&self, hash: &str) -> Option<&BlockRecord> {
+        self.block_by_hash.get(hash).and_then(|n| self.block(*n))
+    }
+
+    pub fn blocks(&self, after: Option<usize>, first: usize) -> Page<BlockRecord> {
+        paginate(self.blocks.iter().rev().map(|b| (b.number as usize, b)), after, first)
+    }
+
+    pub fn transaction(&self, hash: &str) -> Option<&TxRecord> {
+        self.tx_by_hash.get(hash).map(|seq| &self.txs[*seq])
+    }
+
+    /// All transactions, or those `account` sent or received.
+    pub fn transactions(&self, account: Option<&str>, after: Option<usize>, first: usize) -> Page<TxRecord> {
+        match account {
+            None => paginate(self.txs.iter().rev().map(|tx| (tx.seq, tx)), after, first),
+            Some(account) => {
+                let seqs = self.account_txs.get(account).map(Vec::as_slice).unwrap_or_default();
+                paginate(seqs.iter().rev().map(|seq| (*seq, &self.txs[*seq])), after, first)
+            }
+        }
+    }
+
+    /// Transactions of block `number`, in block order. Cursors are positions
+    /// within the block.
+    pub fn block_transactions(&self, number: u32, after: Option<usize>, first: usize) -> Page<TxRecord> {
+        let seqs = self.block(number).map(|b| b.transactions.as_slice()).unwrap_or_default();
+        let start = after.map_or(0, |after| after + 1);
+        let items: Vec<_> =
+            seqs.iter().enumerate().skip(start).take(first).map(|(i, seq)| (i, self.txs[*seq].clone())).collect();
+        Page { has_next: start + items.len() < seqs.len(), items }
+    }
+
+    /// Whether `account` appears in any indexed transaction.
+    pub fn has_account(&self, account: &str) -> bool {
+        self.account_txs.contains_key(account) || self.blocks.iter().any(|b| b.miner == account)
+    }
+
+    pub fn job(&self, id: u64) -> Option<&JobRecord> {
+        self.jobs.get(&id)
+    }
+
+    pub fn jobs(&self, after: Option<usize>, first: usize) -> Page<JobRecord> {
+        paginate(self.jobs.iter().rev().map(|(id, r)| (*id as usize, r)), after, first)
+    }
+
+    /// The latest registration of an endpoint serving `model_id`.
+    pub fn model(&self, model_id: &str) -> Option<&ModelRecord> {
+        self.models.iter().rev().find(|m| m.model_id == model_id)
+    }
+
+    pub fn models(&self, owner: Option<&str>, after: Option<usize>, first: usize) -> Page<ModelRecord> {
+        let models = self.models.iter().rev().filter(|m| owner.is_none_or(|o| m.owner == o));
+        paginate(models.map(|m| (m.seq, m)), after, first)
+    }
+}
+
+/// Name of a storage payload's variant, e.g. `registerEndpoint`.
+fn storage_kind(storage: &StorageTx) -> String {
+    let value = serde_json::to_value(storage).unwrap_or_default();
+    let name = match &value {
+        serde_json::Value::Object(map) => map.keys().next().cloned(),
+        serde_json::Value::String(name) => Some(name.clone()),
+        _ => None,
+    };
+    let name = name.unwrap_or_else(|| "storage".into());
+    let mut chars = name.chars();
+    chars.next().map(|c| c.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or(name)
+}
+
+/// Follows the chain and job queue until the daemon exits.
+pub async fn run_indexer(indexer: SharedIndexer, blockchain: Arc<Mutex<Blockchain>>, job_queue: JobQueue) {
+    let mut ticker = tokio::time::interval(INDEX_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let mut index = indexer.lock().await;
+        index.sync(&*blockchain.lock().await);
+        index.sync_jobs(job_queue.lock().await.iter());
+    }
+}
@@ -0,0 +1,172 @@
+//! Registry of workers and pools with their track record.
+//!
+//! Every node that registers with the scheduler is listed here with the
+//! price it asks per job and the pool it works for, if any. The scheduler
+//! reports each assignment and how it ended: a job that left the queue while
+//! assigned counts as completed by its node, one handed back because its node
+//! went away as abandoned. Records outlive the node's registration, so a
+//! worker that drops out keeps its history when it comes back.
+//!
+//! Pools group workers under one name. Posters delegate a job to a pool by
+//! pinning the pool's name, see [`runtime::job::JobRequirements::pinned`];
+//! any member may then run it. The listings are served over JSON-RPC
+//! (`listWorkers`, `listPools`, `getWorker`, `getPool`).
+
+use runtime::node::NodeCapability;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a worker offers when it registers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerListing {
+    /// Price asked per job.
+    #[serde(default)]
+    pub price: u64,
+    /// Pool the worker runs jobs for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+}
+
+/// A worker as listed in the registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerRecord {
+    pub id: String,
+    #[serde(flatten)]
+    pub listing: WorkerListing,
+    /// Whether the worker is registered with the scheduler right now.
+    pub online: bool,
+    pub gpus: u32,
+    pub gpu_memory_gb: u32,
+    pub reputation: i32,
+    /// Jobs assigned to the worker.
+    pub assigned: u64,
+    pub completed: u64,
+    pub abandoned: u64,
+    /// See [`reliability`].
+    pub reliability: Option<f64>,
+}
+
+/// A pool and the combined record of its members.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolRecord {
+    pub name: String,
+    pub members: Vec<String>,
+    /// Members registered right now.
+    pub online: usize,
+    /// Lowest price asked by a member that is online.
+    pub min_price: Option<u64>,
+    pub assigned: u64,
+    pub completed: u64,
+    pub abandoned: u64,
+    /// See [`reliability`].
+    pub reliability: Option<f64>,
+}
+
+/// Share of finished jobs that were completed rather than abandoned, or
+/// `None` before any job finished.
+pub fn reliability(completed: u64, abandoned: u64) -> Option<f64> {
+    let finished = completed + abandoned;
+    (finished > 0).then(|| completed as f64 / finished as f64)
+}
+
+#[derive(Debug, Default)]
+pub struct Marketplace {
+    workers: BTreeMap<String, WorkerRecord>,
+}
+
+impl Marketplace {
+    /// Lists `id` as online with `capability`. A listing replaces the one it
+    /// registered with before; `None` keeps it.
+    pub fn register(&mut self, id: &str, capability: &NodeCapability, listing: Option<WorkerListing>) {
+        let record = self.workers.entry(id.to_string()).or_insert_with(|| WorkerRecord {
+            id: id.to_string(),
+            listing: WorkerListing::default(),
+            online: false,
+            gpus: 0,
+            gpu_memory_gb: 0,
+            reputation: 0,
+            assigned: 0,
+            completed: 0,
+            abandoned: 0,
+            reliability: None,
+        });
+        if let Some(listing) = listing {
+            record.listing = listing;
+        }
+        record.online = true;
+        record.gpus = capability.gpus;
+        record.gpu_memory_gb = capability.gpu_memory_gb;
+        record.reputation = capability.reputation.overall();
+    }
+
+    /// `id` left the scheduler; its record stays.
+    pub fn unregister(&mut self, id: &str) {
+        if let Some(record) = self.workers.get_mut(id) {
+            record.online = false;
+        }
+    }
+
+    /// The pool `id` works for, if any.
+    pub fn pool_of(&self, id: &str) -> Option<&str> {
+        self.workers.get(id)?.listing.pool.as_deref()
+    }
+
+    pub fn assigned(&mut self, id: &str) {
+        self.update(id, |r| r.assigned += 1);
+    }
+
+    pub fn completed(&mut self, id: &str) {
+        self.update(id, |r| r.completed += 1);
+    }
+
+    pub fn abandoned(&mut self, id: &str) {
+        self.update(id, |r| r.abandoned += 1);
+    }
+
+    fn update(&mut self, id: &str, f: impl FnOnce(&mut WorkerRecord)) {
+        if let Some(record) = self.workers.get_mut(id) {
+            f(record);
+            record.reliability = reliability(record.completed, record.abandoned);
+        }
+    }
+
+    pub fn worker(&self, id: &str) -> Option<&WorkerRecord> {
+        self.workers.get(id)
+    }
+
+    /// Every worker ever registered, by id.
+    pub fn workers(&self) -> Vec<WorkerRecord> {
+        self.workers.values().cloned().collect()
+    }
+
+    pub fn pool(&self, name: &str) -> Option<PoolRecord> {
+        let members: Vec<&WorkerRecord> =
+            self.workers.values().filter(|r| r.listing.pool.as_deref() == Some(name)).collect();
+        if members.is_empty() {
+            return None;
+        }
+        let completed = members.iter().map(|r| r.completed).sum();
+        let abandoned = members.iter().map(|r| r.abandoned).sum();
+        Some(PoolRecord {
+            name: name.to_string(),
+            members: members.iter().map(|r| r.id.clone()).collect(),
+            online: members.iter().filter(|r| r.online).count(),
+            min_price: members.iter().filter(|r| r.online).map(|r| r.listing.price).min(),
+            assigned: members.iter().map(|r| r.assigned).sum(),
+            completed,
+            abandoned,
+            reliability: reliability(completed, abandoned),
+        })
+    }
+
+    /// Every pool with at least one member, by name.
+    pub fn pools(&self) -> Vec<PoolRecord> {
+        let mut names: Vec<&str> = self.workers.values().filter_map(|r| r.listing.pool.as_deref()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names.into_iter().filter_map(|name| self.pool(name)).collect()
+    }
+}
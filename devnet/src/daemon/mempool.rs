@@ -0,0 +1,103 @@
+//! Admission into the mempool blocks are mined from.
+//!
+//! The CLI and JSON-RPC both submit through here, so a transaction from
+//! either is checked against the same state, lands in the same mempool the
+//! miner draws from and is gossiped to peers the same way.
+
+use super::nonces::{self, SharedNonces};
+use super::types::Mempool;
+use runtime::{
+    blockchain::{validation, Blockchain, Transaction},
+    gossip::GossipTopic,
+    p2p_service::{encode_message, P2PHandle, WireMessage},
+};
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Check that `tx` applies after its sender's earlier transactions in
+/// `mempool` and is not there already.
+pub fn check_admission(chain: &Blockchain, mempool: &HashSet<Transaction>, tx: &Transaction) -> Result<(), String> {
+    let mut state = chain.state.clone();
+    let mut earlier: Vec<&Transaction> = mempool
+        .iter()
+        .filter(|m| m.from == tx.from && m.nonce >= state.get_nonce(&tx.from) && m.nonce < tx.nonce)
+        .collect();
+    earlier.sort_by_key(|m| m.nonce);
+    for m in earlier {
+        if validation::validate_transaction_stateful(m, &state).is_ok() {
+            state.apply_transaction(m).map_err(|e| e.to_string())?;
+        }
+    }
+    validation::validate_transaction_stateless(tx)
+        .and_then(|_| validation::validate_transaction_stateful(tx, &state))
+        .map_err(|e| e.to_string())?;
+
+    if mempool.iter().any(|m| m.hash() == tx.hash()) {
+        return Err("Transaction already in mempool".to_string());
+    }
+    Ok(())
+}
+
+/// Validate `tx`, add it to the mempool and gossip it.
+pub async fn broadcast(
+    blockchain: &Mutex<Blockchain>,
+    mempool: &Mempool,
+    p2p_handle: &P2PHandle,
+    tx: Transaction,
+) -> Result<(), String> {
+    {
+        let chain = blockchain.lock().await;
+        let mut mempool = mempool.lock().await;
+        check_admission(&chain, &mempool, &tx)?;
+        mempool.insert(tx.clone());
+    }
+    let topic = GossipTopic::for_transaction(&tx);
+    p2p_handle.gossip(topic, encode_message(&WireMessage::Transaction(tx))).await.map_err(|e| e.to_string())
+}
+
+/// Broadcast `tx`, returning its hash. With a nonce service, a transaction
+/// ahead of its sender's next nonce is held instead, and is broadcast, along
+/// with any held after it, once the nonces before it are in the mempool.
+pub async fn submit(
+    blockchain: &Mutex<Blockchain>,
+    mempool: &Mempool,
+    p2p_handle: &P2PHandle,
+    nonces: Option<&SharedNonces>,
+    tx: Transaction,
+) -> Result<String, String> {
+    let tx_hash = tx.hash();
+    let Some(nonces) = nonces else {
+        broadcast(blockchain, mempool, p2p_handle, tx).await?;
+        return Ok(tx_hash);
+    };
+    let from = tx.from.clone();
+    let next = next_nonce(blockchain, mempool, &from).await;
+    let Some(mut ready) = nonces.lock().await.admit(tx, next).map_err(|e| e.to_string())? else {
+        info!("Holding transaction {} until nonce {} of {} arrives", tx_hash, next, from);
+        return Ok(tx_hash);
+    };
+    loop {
+        let nonce = ready.nonce;
+        let hash = ready.hash();
+        if let Err(e) = broadcast(blockchain, mempool, p2p_handle, ready).await {
+            nonces.lock().await.release(&from, nonce);
+            if hash == tx_hash {
+                return Err(e);
+            }
+            warn!("Dropping held transaction {}: {}", hash, e);
+            return Ok(tx_hash);
+        }
+        let next = next_nonce(blockchain, mempool, &from).await;
+        match nonces.lock().await.take_ready(&from, next) {
+            Some(tx) => ready = tx,
+            None => return Ok(tx_hash),
+        }
+    }
+}
+
+/// The nonce `account`'s next transaction into the mempool must carry.
+pub async fn next_nonce(blockchain: &Mutex<Blockchain>, mempool: &Mempool, account: &str) -> u64 {
+    let chain = blockchain.lock().await;
+    nonces::next_nonce(&chain, &*mempool.lock().await, account)
+}
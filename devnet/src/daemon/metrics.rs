@@ -0,0 +1,66 @@
+//! Periodic metrics reports.
+//!
+//! A full node reports its storage metrics once a minute. Reports are
+//! signed with the node's `Metrics` session key, read from
+//! [`METRICS_KEY_FILE`] under `$HOME`, so the account key never has to be on
+//! the node; the report is sent for the account that registered the key.
+
+use super::types::METRICS_KEY_FILE;
+use ed25519_dalek::SigningKey;
+use runtime::{
+    blockchain::{Blockchain, Transaction},
+    distributed_storage::allocation::NodeMetrics,
+    gossip::GossipTopic,
+    p2p_service::{encode_message, P2PHandle, WireMessage},
+    session_keys::SessionRole,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::error;
+
+/// Time between two reports.
+const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Gossip this node's metrics every [`REPORT_INTERVAL`].
+pub async fn publish_metrics(blockchain: Arc<Mutex<Blockchain>>, p2p_handle: P2PHandle) {
+    loop {
+        // sleep first to allow network init
+        tokio::time::sleep(REPORT_INTERVAL).await;
+
+        let key_path = std::env::var("HOME").unwrap_or(".".into()) + METRICS_KEY_FILE;
+        let key = match std::fs::read(&key_path).ok().and_then(|b| <[u8; 32]>::try_from(b).ok()) {
+            Some(bytes) => SigningKey::from_bytes(&bytes),
+            None => {
+                error!("Metrics session key not found at {}", key_path);
+                continue;
+            }
+        };
+        let public_key = hex::encode(key.verifying_key().to_bytes());
+        let (account, nonce) = {
+            let chain = blockchain.lock().await;
+            match chain.state.session_account(SessionRole::Metrics, &public_key) {
+                Some(account) => (account.to_string(), chain.state.get_nonce(account)),
+                None => {
+                    error!("Metrics session key {} is not registered on chain", public_key);
+                    continue;
+                }
+            }
+        };
+
+        // Build NodeMetrics for this node (stub values)
+        let metrics = vec![NodeMetrics {
+            node_id: account.clone(),
+            reputation: 0.9,
+            free_capacity: 0.8,
+            latency_ms: 20,
+            region: "us".into(),
+            energy_score: 0.7,
+            utilisation: 0.2,
+        }];
+        let tx = Transaction::new_update_metrics_signed(&account, &key, metrics, nonce);
+        if let Err(e) = p2p_handle.gossip(GossipTopic::Metrics, encode_message(&WireMessage::Transaction(tx))).await {
+            error!("Failed to broadcast metrics: {}", e);
+        }
+    }
+}
@@ -1,15 +1,28 @@
 //! Devnet daemon – IPC gateway + P2P bootstrap.
 //!
-//! `mod.rs` keeps the high-level orchestration logic below 100 LOC.  All
-//! constants and shared type aliases live in `types.rs` so this file remains
-//! lightweight.
-
+//! `mod.rs` only wires the services together: it loads the chain, starts P2P
+//! and the background tasks, spawns the JSON-RPC, GraphQL and WebSocket
+//! servers and then serves IPC commands. Each service lives in its own module,
+//! and all constants and shared type aliases live in `types.rs`.
+
+pub mod fair_queue;
+pub mod follower;
+pub mod graphql;
+pub mod indexer;
+pub mod marketplace;
+pub mod mempool;
+pub mod metrics;
+pub mod nonces;
+pub mod rpc;
+pub mod scheduler;
 mod types;
+pub mod webhooks;
+pub mod ws;
 
 use crate::cli::P2pCommands;
 use crate::command_handler::CommandHandler;
 use runtime::{
-    blockchain::{Blockchain, transaction::Transaction, constants::METRICS_ORACLE_PUB},
+    blockchain::Blockchain,
     job::Job,
     p2p_service::{P2PConfig, P2PHandle, P2PService},
 };
@@ -32,76 +45,113 @@ pub async fn daemon_main() {
     }
 
     // --- Service bootstrap -------------------------------------------------
-    let blockchain = Arc::new(Mutex::new(Blockchain::new(Default::default())));
+    if let Ok(value) = std::env::var(SEED_ENV) {
+        match value.parse::<u64>() {
+            Ok(seed) => runtime::rng::install(Some(seed)),
+            Err(_) => {
+                error!("{} must be an unsigned integer, got {:?}", SEED_ENV, value);
+                return;
+            }
+        }
+    }
+    let chain = match std::env::var(GENESIS_ENV) {
+        Ok(path) => match Blockchain::from_genesis(&path) {
+            Ok(chain) => chain,
+            Err(e) => {
+                error!("Failed to load genesis from {}: {}", path, e);
+                return;
+            }
+        },
+        Err(_) => Blockchain::new(Default::default()),
+    };
+    runtime::pouw::task_types::install(runtime::pouw::TaskTypeRegistry::with_defaults());
+    let blockchain = Arc::new(Mutex::new(chain));
     let mempool: Mempool = Arc::new(Mutex::new(std::collections::HashSet::new()));
     let job_queue: JobQueue = Arc::new(Mutex::new(std::collections::VecDeque::<Job>::new()));
 
-    let (p2p_service, p2p_handle) = P2PService::new(P2PConfig::default())
+    // --- Follower mode ---------------------------------------------------------
+    let mode = follower::NodeMode::from_env_value(std::env::var(FOLLOWER_ENV).ok().as_deref());
+    let follower: Option<follower::SharedFollower> = (mode == follower::NodeMode::Follower)
+        .then(|| Arc::new(Mutex::new(follower::Follower::new(Default::default()))));
+    let p2p_config = match &follower {
+        Some(follower) => follower.lock().await.config().p2p_config(),
+        None => P2PConfig::default(),
+    };
+
+    let (mut p2p_service, p2p_handle) = P2PService::new(p2p_config)
         .await
         .expect("failed to create P2P service");
-    let p2p_handle_clone = p2p_handle.clone();
-    let blockchain_clone = blockchain.clone();
+    if let Some(follower) = &follower {
+        info!("Running as a read-only follower");
+        let (block_sink, blocks) = tokio::sync::mpsc::unbounded_channel();
+        p2p_service = p2p_service.with_block_sink(block_sink);
+        tokio::spawn(follower::run_follower(follower.clone(), blockchain.clone(), blocks, p2p_handle.clone()));
+    }
+    p2p_service = p2p_service.with_block_source(blockchain.clone());
     tokio::spawn(async move { p2p_service.run().await });
 
-    // Spawn periodic metrics publisher (every 60s)
+    // Spawn periodic metrics publisher; followers publish nothing.
+    if follower.is_none() {
+        tokio::spawn(metrics::publish_metrics(blockchain.clone(), p2p_handle.clone()));
+    }
+
+    let mut command_handler = CommandHandler::new(
+        blockchain.clone(),
+        mempool.clone(),
+        job_queue.clone(),
+        p2p_handle.clone(),
+    );
+    let webhooks: webhooks::SharedWebhooks = Arc::new(Mutex::new(webhooks::Webhooks::new(Default::default())));
+    let mut rpc_server = rpc::RpcServer::new(blockchain.clone(), job_queue.clone()).with_webhooks(webhooks.clone());
+    match &follower {
+        Some(follower) => {
+            // Followers neither schedule jobs nor take transactions.
+            command_handler = command_handler.with_read_only();
+            rpc_server = rpc_server.with_follower(follower.clone());
+        }
+        None => {
+            // --- Job scheduling ------------------------------------------------
+            let scheduler: scheduler::SharedScheduler =
+                Arc::new(Mutex::new(scheduler::Scheduler::new(Default::default())));
+            tokio::spawn(scheduler::run_scheduler(scheduler.clone(), job_queue.clone(), blockchain.clone()));
+
+            // --- Nonce allocation ----------------------------------------------
+            let nonces: nonces::SharedNonces =
+                Arc::new(Mutex::new(nonces::NonceService::new(Default::default())));
+            tokio::spawn(nonces::run_nonces(nonces.clone()));
+
+            command_handler = command_handler.with_scheduler(scheduler.clone()).with_nonces(nonces.clone());
+            rpc_server = rpc_server.with_scheduler(scheduler).with_nonces(nonces).with_mempool(mempool, p2p_handle);
+        }
+    }
+
+    // --- JSON-RPC ------------------------------------------------------------
     tokio::spawn(async move {
-        use runtime::distributed_storage::allocation::{NodeMetrics, StoragePolicy};
-        use schnorrkel::SecretKey;
-        use libp2p::gossipsub::IdentTopic;
-        use runtime::p2p_service::WireMessage;
-        loop {
-            // sleep first to allow network init
-            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
-
-            // Load oracle secret key from $HOME/.bcai/metrics_oracle.key
-            let key_path = std::env::var("HOME").unwrap_or(".".into()) + "/.bcai/metrics_oracle.key";
-            let sk_bytes = match std::fs::read(&key_path) {
-                Ok(b) => b,
-                Err(_) => {
-                    error!("Metrics oracle key not found at {}", key_path);
-                    continue;
-                }
-            };
-            let secret_key = match SecretKey::from_bytes(&sk_bytes) {
-                Ok(k) => k,
-                Err(e) => { error!("Invalid oracle key: {}", e); continue; }
-            };
-
-            // Build NodeMetrics for this node (stub values)
-            let metrics = vec![NodeMetrics {
-                node_id: METRICS_ORACLE_PUB.to_string(),
-                reputation: 0.9,
-                free_capacity: 0.8,
-                latency_ms: 20,
-                region: "us".into(),
-                energy_score: 0.7,
-                utilisation: 0.2,
-            }];
-
-            // determine nonce
-            let nonce = {
-                let chain = blockchain_clone.lock().await;
-                chain.state.get_nonce(METRICS_ORACLE_PUB)
-            };
-
-            let tx = Transaction::new_update_metrics_signed(&secret_key, metrics, nonce);
-
-            // broadcast
-            if let Err(e) = p2p_handle_clone
-                .send_message(IdentTopic::new("bcai_global"), serde_json::to_vec(&WireMessage::Transaction(tx)).unwrap())
-                .await
-            {
-                error!("Failed to broadcast metrics: {}", e);
-            }
+        if let Err(e) = rpc_server.serve(RPC_ADDR).await {
+            error!("JSON-RPC server stopped: {}", e);
         }
     });
 
-    let mut command_handler = CommandHandler::new(
-        blockchain,
-        mempool,
-        job_queue,
-        p2p_handle,
-    );
+    // --- GraphQL explorer ------------------------------------------------------
+    let indexer: indexer::SharedIndexer = Arc::new(Mutex::new(indexer::Indexer::default()));
+    tokio::spawn(indexer::run_indexer(indexer.clone(), blockchain.clone(), job_queue.clone()));
+    let graphql_server = graphql::GraphqlServer::new(graphql::schema(indexer, blockchain.clone()));
+    tokio::spawn(async move {
+        if let Err(e) = graphql_server.serve(GRAPHQL_ADDR).await {
+            error!("GraphQL server stopped: {}", e);
+        }
+    });
+
+    // --- WebSocket subscriptions ----------------------------------------------
+    let (ws_server, events) = ws::SubscriptionServer::new();
+    let sender = webhooks::HttpSender { timeout: webhooks.lock().await.config().timeout };
+    tokio::spawn(webhooks::run_webhooks(webhooks, events.subscribe(), sender));
+    tokio::spawn(ws::watch_chain(blockchain.clone(), job_queue.clone(), events));
+    tokio::spawn(async move {
+        if let Err(e) = ws_server.serve(WS_ADDR).await {
+            error!("WebSocket server stopped: {}", e);
+        }
+    });
 
     // --- IPC socket --------------------------------------------------------
     let listener = match UnixListener::bind(SOCKET_PATH) {
@@ -141,4 +191,4 @@ async fn handle_socket_command(
 }
 
 // Add re-exports for external consumers
-pub use types::{PID_FILE, SOCKET_PATH}; 
\ No newline at end of file
+pub use types::{FOLLOWER_ENV, GENESIS_ENV, GRAPHQL_ADDR, PID_FILE, RPC_ADDR, SEED_ENV, SOCKET_PATH, WS_ADDR}; 
\ No newline at end of file
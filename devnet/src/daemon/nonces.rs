@@ -0,0 +1,186 @@
+//! Nonce allocation for clients posting from one account at once.
+//!
+//! Automated posters allocate nonces from the daemon (`allocateNonce` over
+//! JSON-RPC, or by leaving out `--nonce` in the CLI) instead of reading the
+//! account's nonce and counting on their own, see
+//! [`runtime::blockchain::nonce`]. Transactions may then arrive in any
+//! order: one ahead of its turn is held here until the nonces before it are
+//! in the mempool rather than refused as too high, and the nonces a held
+//! transaction waits for are reported as gaps.
+
+use runtime::blockchain::{nonce::DEFAULT_LEASE_SECS, Blockchain, NonceAllocator, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+/// Nonce state shared between the RPC server and the CLI handler.
+pub type SharedNonces = Arc<Mutex<NonceService>>;
+
+/// Time between sweeps of expired leases.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NonceConfig {
+    /// Seconds an allocated nonce stays reserved for its client.
+    pub lease_secs: u64,
+    /// How far past an account's next nonce a transaction may be held.
+    pub max_ahead: u64,
+    /// Transactions held across all accounts.
+    pub max_held: usize,
+}
+
+impl Default for NonceConfig {
+    fn default() -> Self {
+        Self { lease_secs: DEFAULT_LEASE_SECS, max_ahead: 64, max_held: 1024 }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum NonceError {
+    #[error("nonce too low: expected {expected}, got {got}")]
+    TooLow { expected: u64, got: u64 },
+    #[error("nonce {got} is more than {max_ahead} past the next nonce {expected}")]
+    TooFarAhead { expected: u64, got: u64, max_ahead: u64 },
+    #[error("a transaction with nonce {0} is already waiting")]
+    Duplicate(u64),
+    #[error("too many transactions are waiting for earlier nonces")]
+    Full,
+}
+
+/// An account's nonces as the daemon sees them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NonceStatus {
+    pub account: String,
+    /// Nonce the account's next transaction into the mempool must carry.
+    pub next: u64,
+    /// Nonce the next allocation hands out.
+    pub next_allocation: u64,
+    /// Allocated or held, not yet in the mempool.
+    pub in_flight: Vec<u64>,
+    /// Held until the nonces before them arrive.
+    pub held: Vec<u64>,
+    /// Nonces the held transactions wait for.
+    pub gaps: Vec<u64>,
+}
+
+/// Per-account allocators and transactions held for their turn.
+#[derive(Debug, Default)]
+pub struct NonceService {
+    config: NonceConfig,
+    accounts: HashMap<String, NonceAllocator>,
+    held: HashMap<String, BTreeMap<u64, Transaction>>,
+}
+
+impl NonceService {
+    pub fn new(config: NonceConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &NonceConfig {
+        &self.config
+    }
+
+    /// `account`'s allocator synced to `next`, its next mempool nonce.
+    fn allocator(&mut self, account: &str, next: u64) -> &mut NonceAllocator {
+        let lease = self.config.lease_secs;
+        let allocator =
+            self.accounts.entry(account.to_string()).or_insert_with(|| NonceAllocator::new(next).with_lease(lease));
+        allocator.sync(next);
+        allocator
+    }
+
+    /// Reserve a nonce for a transaction from `account`.
+    pub fn allocate(&mut self, account: &str, next: u64, now: u64) -> u64 {
+        self.allocator(account, next).allocate(now)
+    }
+
+    /// Hand `nonce` out again, e.g. because its transaction was refused.
+    pub fn release(&mut self, account: &str, nonce: u64) -> bool {
+        self.accounts.get_mut(account).is_some_and(|a| a.release(nonce))
+    }
+
+    /// Take `tx` in, given its sender's next mempool nonce `next`. Returns
+    /// it if it is its turn to enter the mempool; otherwise it is held.
+    pub fn admit(&mut self, tx: Transaction, next: u64) -> Result<Option<Transaction>, NonceError> {
+        let (expected, got) = (next, tx.nonce);
+        if got < expected {
+            return Err(NonceError::TooLow { expected, got });
+        }
+        if got - expected > self.config.max_ahead {
+            return Err(NonceError::TooFarAhead { expected, got, max_ahead: self.config.max_ahead });
+        }
+        if got > expected {
+            if self.held.get(&tx.from).is_some_and(|held| held.contains_key(&got)) {
+                return Err(NonceError::Duplicate(got));
+            }
+            if self.held.values().map(BTreeMap::len).sum::<usize>() >= self.config.max_held {
+                return Err(NonceError::Full);
+            }
+        }
+        self.allocator(&tx.from, next).submit(got);
+        if got == expected {
+            return Ok(Some(tx));
+        }
+        self.held.entry(tx.from.clone()).or_default().insert(got, tx);
+        Ok(None)
+    }
+
+    /// The held transaction of `account` whose turn it is now that its next
+    /// mempool nonce is `next`. Held transactions below `next` lost to
+    /// another with the same nonce and are dropped.
+    pub fn take_ready(&mut self, account: &str, next: u64) -> Option<Transaction> {
+        self.allocator(account, next);
+        let held = self.held.get_mut(account)?;
+        held.retain(|nonce, _| *nonce >= next);
+        let ready = held.remove(&next);
+        if held.is_empty() {
+            self.held.remove(account);
+        }
+        ready
+    }
+
+    pub fn status(&mut self, account: &str, next: u64, now: u64) -> NonceStatus {
+        let allocator = self.allocator(account, next);
+        allocator.expire(now);
+        let (next_allocation, in_flight, gaps) = (allocator.peek(), allocator.in_flight(), allocator.gaps());
+        let held = self.held.get(account).map(|h| h.keys().copied().collect()).unwrap_or_default();
+        NonceStatus { account: account.to_string(), next, next_allocation, in_flight, held, gaps }
+    }
+
+    /// Free the leases that ran out by `now` and forget idle accounts.
+    pub fn expire(&mut self, now: u64) {
+        for allocator in self.accounts.values_mut() {
+            allocator.expire(now);
+        }
+        let held = &self.held;
+        self.accounts.retain(|account, allocator| !allocator.is_idle() || held.contains_key(account));
+    }
+}
+
+/// The nonce `account`'s next transaction into the mempool must carry:
+/// the chain's next nonce, counting its pending transactions and those in
+/// `mempool`.
+pub fn next_nonce(chain: &Blockchain, mempool: &HashSet<Transaction>, account: &str) -> u64 {
+    mempool
+        .iter()
+        .filter(|tx| tx.from == account)
+        .map(|tx| tx.nonce.saturating_add(1))
+        .fold(chain.pending_nonce(account), u64::max)
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Sweep expired leases so their nonces are handed out again.
+pub async fn run_nonces(nonces: SharedNonces) {
+    let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        ticker.tick().await;
+        nonces.lock().await.expire(unix_now());
+    }
+}
@@ -0,0 +1,531 @@
+//! JSON-RPC 2.0 endpoint for block explorers and SDKs.
+//!
+//! Served over plain HTTP (`POST /`) next to the Unix-socket IPC so external
+//! tools can query the node without going through the devnet CLI. Only the
+//! handful of methods explorers need are exposed; everything else returns the
+//! standard "method not found" error.
+
+use super::follower::{NodeMode, SharedFollower, SyncStatus};
+use super::marketplace::WorkerListing;
+use super::mempool;
+use super::nonces::{self, SharedNonces};
+use super::scheduler::SharedScheduler;
+use super::types::Mempool;
+use super::webhooks::{SharedWebhooks, WebhookFilter};
+use super::ws::{transaction_events, ChainEvent};
+use runtime::blockchain::{Blockchain, Transaction};
+use runtime::job::Job;
+use runtime::node::NodeCapability;
+use runtime::p2p_service::P2PHandle;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+use tracing::{error, info};
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY_BYTES: usize = 1 << 20;
+
+// Standard JSON-RPC 2.0 error codes.
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_REQUEST: i64 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+/// Application-level error: the transaction was rejected by the chain.
+const TX_REJECTED: i64 = -32000;
+/// Application-level error: the daemon runs without a job scheduler.
+const SCHEDULER_UNAVAILABLE: i64 = -32001;
+/// Application-level error: the daemon runs without webhook delivery.
+const WEBHOOKS_UNAVAILABLE: i64 = -32002;
+/// Application-level error: the daemon runs without nonce allocation.
+const NONCES_UNAVAILABLE: i64 = -32003;
+/// Application-level error: the daemon is a read-only follower.
+const READ_ONLY: i64 = -32004;
+/// Application-level error: the daemon runs without a mempool.
+const MEMPOOL_UNAVAILABLE: i64 = -32005;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default)]
+    pub id: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    pub id: Value,
+}
+
+impl RpcResponse {
+    pub(crate) fn ok(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0".into(), result: Some(result), error: None, id }
+    }
+
+    pub(crate) fn err(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            result: None,
+            error: Some(RpcError { code, message: message.into() }),
+            id,
+        }
+    }
+}
+
+/// Shared node state the RPC methods read from.
+#[derive(Clone)]
+pub struct RpcServer {
+    blockchain: Arc<Mutex<Blockchain>>,
+    job_queue: Arc<Mutex<VecDeque<Job>>>,
+    scheduler: Option<SharedScheduler>,
+    webhooks: Option<SharedWebhooks>,
+    nonces: Option<SharedNonces>,
+    /// The mempool blocks are mined from, and the network to gossip to.
+    mempool: Option<(Mempool, P2PHandle)>,
+    /// Set on a read-only follower.
+    follower: Option<SharedFollower>,
+}
+
+impl RpcServer {
+    pub fn new(blockchain: Arc<Mutex<Blockchain>>, job_queue: Arc<Mutex<VecDeque<Job>>>) -> Self {
+        Self { blockchain, job_queue, scheduler: None, webhooks: None, nonces: None, mempool: None, follower: None }
+    }
+
+    /// Serves the scheduler's state and accepts node registrations.
+    pub fn with_scheduler(mut self, scheduler: SharedScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Accepts webhook registrations and serves their delivery state.
+    pub fn with_webhooks(mut self, webhooks: SharedWebhooks) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Allocates nonces, and holds transactions sent ahead of their nonce
+    /// until their turn.
+    pub fn with_nonces(mut self, nonces: SharedNonces) -> Self {
+        self.nonces = Some(nonces);
+        self
+    }
+
+    /// Accepts transactions into `mempool`, the one the node mines from,
+    /// and gossips them through `p2p_handle`, as the CLI does.
+    pub fn with_mempool(mut self, mempool: Mempool, p2p_handle: P2PHandle) -> Self {
+        self.mempool = Some((mempool, p2p_handle));
+        self
+    }
+
+    /// Serves as a read-only follower: reports its sync progress and
+    /// refuses transactions.
+    pub fn with_follower(mut self, follower: SharedFollower) -> Self {
+        self.follower = Some(follower);
+        self
+    }
+
+    /// Accept HTTP connections on `addr` until the listener fails.
+    pub async fn serve(self, addr: &str) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("JSON-RPC listening on http://{}", addr);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.handle_connection(stream).await {
+                    error!("JSON-RPC connection error: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let body = match read_http_body(&mut stream).await? {
+            Some(body) => body,
+            None => return write_http(&mut stream, "400 Bad Request", b"").await,
+        };
+        let response = match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Array(batch)) => {
+                let mut out = Vec::with_capacity(batch.len());
+                for item in batch {
+                    out.push(self.handle_value(item).await);
+                }
+                serde_json::to_vec(&out)
+            }
+            Ok(value) => serde_json::to_vec(&self.handle_value(value).await),
+            Err(e) => serde_json::to_vec(&RpcResponse::err(Value::Null, PARSE_ERROR, e.to_string())),
+        }
+        .map_err(std::io::Error::other)?;
+        write_http(&mut stream, "200 OK", &response).await
+    }
+
+    async fn handle_value(&self, value: Value) -> RpcResponse {
+        match serde_json::from_value::<RpcRequest>(value) {
+            Ok(req) if req.jsonrpc == "2.0" => self.dispatch(req).await,
+            Ok(req) => RpcResponse::err(req.id, INVALID_REQUEST, "jsonrpc must be \"2.0\""),
+            Err(e) => RpcResponse::err(Value::Null, INVALID_REQUEST, e.to_string()),
+        }
+    }
+
+    /// Route a parsed request to its method implementation.
+    pub async fn dispatch(&self, req: RpcRequest) -> RpcResponse {
+        let id = req.id.clone();
+        let result = match req.method.as_str() {
+            "getBlockByNumber" => self.get_block_by_number(&req.params).await,
+            "getTransaction" => self.get_transaction(&req.params).await,
+            "getBalance" => self.get_balance(&req.params).await,
+            "getJob" => self.get_job(&req.params).await,
+            "sendRawTransaction" => self.send_raw_transaction(&req.params).await,
+            "simulateTransaction" => self.simulate_transaction(&req.params).await,
+            "registerNode" => self.register_node(&req.params).await,
+            "getSchedulerStatus" => self.get_scheduler_status().await,
+            "getJobAssignment" => self.get_job_assignment(&req.params).await,
+            "getSchedulingDecisions" => self.get_scheduling_decisions(&req.params).await,
+            "getFairnessStats" => self.get_fairness_stats().await,
+            "listWorkers" => Ok(json!(self.scheduler()?.lock().await.marketplace().workers())),
+            "listPools" => Ok(json!(self.scheduler()?.lock().await.marketplace().pools())),
+            "getWorker" => self.get_worker(&req.params).await,
+            "getPool" => self.get_pool(&req.params).await,
+            "registerWebhook" => self.register_webhook(&req.params).await,
+            "removeWebhook" => self.remove_webhook(&req.params).await,
+            "listWebhooks" => Ok(json!(self.webhooks()?.lock().await.webhooks())),
+            "getWebhookStats" => Ok(json!(self.webhooks()?.lock().await.stats())),
+            "getWebhookDeadLetters" => Ok(json!(self.webhooks()?.lock().await.dead_letters())),
+            "replayWebhookDelivery" => self.replay_webhook_delivery(&req.params).await,
+            "allocateNonce" => self.allocate_nonce(&req.params).await,
+            "releaseNonce" => self.release_nonce(&req.params).await,
+            "getNonceStatus" => self.get_nonce_status(&req.params).await,
+            "getSyncStatus" => self.get_sync_status().await,
+            other => Err((METHOD_NOT_FOUND, format!("method not found: {}", other))),
+        };
+        match result {
+            Ok(value) => RpcResponse::ok(id, value),
+            Err((code, message)) => RpcResponse::err(id, code, message),
+        }
+    }
+
+    async fn get_block_by_number(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let chain = self.blockchain.lock().await;
+        let block = match first_param(params) {
+            Some(Value::String(tag)) if tag == "latest" => chain.blocks.last(),
+            Some(v) => {
+                let number = v.as_u64().ok_or_else(|| invalid("block number must be an integer or \"latest\""))?;
+                chain.blocks.get(number as usize)
+            }
+            None => return Err(invalid("missing block number")),
+        };
+        Ok(block.map(|b| json!(b)).unwrap_or(Value::Null))
+    }
+
+    async fn get_transaction(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let hash = string_param(params, "transaction hash")?;
+        let chain = self.blockchain.lock().await;
+        for block in &chain.blocks {
+            if let Some(tx) = block.transactions.iter().find(|tx| tx.hash() == hash) {
+                return Ok(json!({ "transaction": tx, "blockNumber": block.index, "blockHash": block.hash }));
+            }
+        }
+        let pooled = match &self.mempool {
+            Some((mempool, _)) => mempool.lock().await.iter().find(|tx| tx.hash() == hash).cloned(),
+            None => None,
+        };
+        let pending = pooled.as_ref().or_else(|| {
+            chain.pending_transactions.iter().chain(&chain.deferred_transactions).find(|tx| tx.hash() == hash)
+        });
+        Ok(pending
+            .map(|tx| json!({ "transaction": tx, "blockNumber": Value::Null, "blockHash": Value::Null }))
+            .unwrap_or(Value::Null))
+    }
+
+    async fn get_balance(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let pubkey = string_param(params, "account public key")?;
+        let chain = self.blockchain.lock().await;
+        Ok(json!({ "balance": chain.get_balance(&pubkey), "nonce": chain.get_nonce(&pubkey) }))
+    }
+
+    async fn get_job(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let job_id = first_param(params)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("job id must be an integer"))?;
+        let queue = self.job_queue.lock().await;
+        Ok(queue.iter().find(|j| j.id == job_id).map(|j| json!(j)).unwrap_or(Value::Null))
+    }
+
+    /// Accepts a hex-encoded bincode `Transaction` or a JSON one, validates
+    /// it against the chain and the mempool, adds it to the mempool and
+    /// gossips it. Returns the transaction hash. With nonce allocation, a
+    /// transaction ahead of its sender's next nonce is held until the ones
+    /// before it arrive.
+    async fn send_raw_transaction(&self, params: &Value) -> Result<Value, (i64, String)> {
+        if self.follower.is_some() {
+            return Err((READ_ONLY, "follower nodes do not accept transactions".to_string()));
+        }
+        let Some((mempool, p2p_handle)) = &self.mempool else {
+            return Err((MEMPOOL_UNAVAILABLE, "the daemon runs without a mempool".to_string()));
+        };
+        let tx = raw_transaction(params)?;
+        let hash = mempool::submit(&self.blockchain, mempool, p2p_handle, self.nonces.as_ref(), tx)
+            .await
+            .map_err(|e| (TX_REJECTED, e))?;
+        Ok(json!(hash))
+    }
+
+    /// Takes the same parameter as `sendRawTransaction` and returns the
+    /// transaction's fee, state changes and events without submitting it.
+    async fn simulate_transaction(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let tx = raw_transaction(params)?;
+        let chain = self.blockchain.lock().await;
+        let simulation = chain.simulate_transaction(&tx).map_err(|e| (TX_REJECTED, e.to_string()))?;
+        // Events as they would be reported for the next block.
+        let block = chain.blocks.len() as u32;
+        let mut events = transaction_events(&tx, block);
+        for change in simulation.changes.iter().filter(|c| c.field == "balances") {
+            let balance = change.after.as_u64().unwrap_or(0);
+            events.push(ChainEvent::BalanceChanged { account: change.key.clone(), balance, block });
+        }
+        let mut result = json!(simulation);
+        result["events"] = json!(events);
+        Ok(result)
+    }
+
+    async fn get_sync_status(&self) -> Result<Value, (i64, String)> {
+        let chain = self.blockchain.lock().await;
+        let status = match &self.follower {
+            Some(follower) => follower.lock().await.status(&chain),
+            None => SyncStatus { mode: NodeMode::Full, height: chain.height(), ..SyncStatus::default() },
+        };
+        Ok(json!(status))
+    }
+
+    fn scheduler(&self) -> Result<&SharedScheduler, (i64, String)> {
+        self.scheduler.as_ref().ok_or_else(|| (SCHEDULER_UNAVAILABLE, "scheduler is not running".to_string()))
+    }
+
+    /// Params: `[nodeId, capability]`, optionally followed by the worker's
+    /// marketplace listing `{price, pool}`.
+    async fn register_node(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let node_id = string_param(params, "node id")?;
+        let capability: NodeCapability = params
+            .get(1)
+            .cloned()
+            .ok_or_else(|| invalid("missing capability"))
+            .and_then(|v| serde_json::from_value(v).map_err(|e| invalid(&e.to_string())))?;
+        let listing: Option<WorkerListing> = params
+            .get(2)
+            .map(|v| serde_json::from_value(v.clone()).map_err(|e| invalid(&e.to_string())))
+            .transpose()?;
+        let mut scheduler = self.scheduler()?.lock().await;
+        match listing {
+            Some(listing) => scheduler.register_listed(&node_id, capability, listing),
+            None => scheduler.register_node(&node_id, capability),
+        }
+        .map_err(|e| invalid(&e.to_string()))?;
+        Ok(json!(true))
+    }
+
+    async fn get_scheduler_status(&self) -> Result<Value, (i64, String)> {
+        Ok(json!(self.scheduler()?.lock().await.status()))
+    }
+
+    async fn get_job_assignment(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let job_id = first_param(params)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("job id must be an integer"))?;
+        Ok(json!(self.scheduler()?.lock().await.assignment(job_id)))
+    }
+
+    /// Params: optional `[since]`, the first decision sequence number wanted.
+    async fn get_scheduling_decisions(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let since = match first_param(params) {
+            None => 0,
+            Some(v) => v.as_u64().ok_or_else(|| invalid("since must be an integer"))?,
+        };
+        Ok(json!(self.scheduler()?.lock().await.decisions(since)))
+    }
+
+    /// Per-poster weights and usage, for tuning the fair queue.
+    async fn get_fairness_stats(&self) -> Result<Value, (i64, String)> {
+        Ok(json!(self.scheduler()?.lock().await.fairness()))
+    }
+
+    /// Params: `[workerId]`. Null for a worker never registered.
+    async fn get_worker(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let id = string_param(params, "worker id")?;
+        Ok(json!(self.scheduler()?.lock().await.marketplace().worker(&id)))
+    }
+
+    /// Params: `[poolName]`. Null for a pool without members.
+    async fn get_pool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = string_param(params, "pool name")?;
+        Ok(json!(self.scheduler()?.lock().await.marketplace().pool(&name)))
+    }
+
+    fn webhooks(&self) -> Result<&SharedWebhooks, (i64, String)> {
+        self.webhooks.as_ref().ok_or_else(|| (WEBHOOKS_UNAVAILABLE, "webhooks are not enabled".to_string()))
+    }
+
+    /// Params: `[url, filter]`. Returns the webhook id and the secret its
+    /// payloads are signed with, which is not shown again.
+    async fn register_webhook(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let url = string_param(params, "webhook url")?;
+        let filter: WebhookFilter = params
+            .get(1)
+            .cloned()
+            .ok_or_else(|| invalid("missing event filter"))
+            .and_then(|v| serde_json::from_value(v).map_err(|e| invalid(&e.to_string())))?;
+        let hook = self.webhooks()?.lock().await.register(&url, filter).map_err(|e| invalid(&e.to_string()))?;
+        Ok(json!({ "id": hook.id, "secret": hook.secret }))
+    }
+
+    async fn remove_webhook(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let id = string_param(params, "webhook id")?;
+        self.webhooks()?.lock().await.remove(&id).map_err(|e| invalid(&e.to_string()))?;
+        Ok(json!(true))
+    }
+
+    async fn replay_webhook_delivery(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let id = first_param(params)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| invalid("delivery id must be an integer"))?;
+        Ok(json!(self.webhooks()?.lock().await.replay(id, std::time::Instant::now())))
+    }
+
+    fn nonces(&self) -> Result<&SharedNonces, (i64, String)> {
+        self.nonces.as_ref().ok_or_else(|| (NONCES_UNAVAILABLE, "nonce allocation is not enabled".to_string()))
+    }
+
+    /// Next mempool nonce of the account named by the first param.
+    async fn account_nonce(&self, params: &Value) -> Result<(String, u64), (i64, String)> {
+        let account = string_param(params, "account public key")?;
+        self.nonces()?;
+        let next = match &self.mempool {
+            Some((mempool, _)) => mempool::next_nonce(&self.blockchain, mempool, &account).await,
+            None => nonces::next_nonce(&*self.blockchain.lock().await, &HashSet::new(), &account),
+        };
+        Ok((account, next))
+    }
+
+    /// Params: `[account]`. Reserves the account's next free nonce for
+    /// the lease time; release it if the transaction won't be sent.
+    async fn allocate_nonce(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (account, next) = self.account_nonce(params).await?;
+        let mut service = self.nonces()?.lock().await;
+        Ok(json!(service.allocate(&account, next, nonces::unix_now())))
+    }
+
+    /// Params: `[account, nonce]`.
+    async fn release_nonce(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let account = string_param(params, "account public key")?;
+        let nonce = params.get(1).and_then(Value::as_u64).ok_or_else(|| invalid("nonce must be an integer"))?;
+        Ok(json!(self.nonces()?.lock().await.release(&account, nonce)))
+    }
+
+    async fn get_nonce_status(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (account, next) = self.account_nonce(params).await?;
+        Ok(json!(self.nonces()?.lock().await.status(&account, next, nonces::unix_now())))
+    }
+}
+
+fn invalid(message: &str) -> (i64, String) {
+    (INVALID_PARAMS, message.to_string())
+}
+
+/// Params may be positional (`[x]`) or a bare value.
+fn first_param(params: &Value) -> Option<&Value> {
+    match params {
+        Value::Array(items) => items.first(),
+        Value::Null => None,
+        other => Some(other),
+    }
+}
+
+/// Decodes a hex-encoded bincode `Transaction` parameter, or a transaction
+/// given as a JSON object.
+fn raw_transaction(params: &Value) -> Result<Transaction, (i64, String)> {
+    if let Some(tx @ Value::Object(_)) = first_param(params) {
+        return serde_json::from_value(tx.clone()).map_err(|e| invalid(&e.to_string()));
+    }
+    let raw = string_param(params, "raw transaction")?;
+    let bytes = hex::decode(raw.trim_start_matches("0x")).map_err(|e| invalid(&e.to_string()))?;
+    bincode::deserialize(&bytes).map_err(|e| invalid(&e.to_string()))
+}
+
+fn string_param(params: &Value, what: &str) -> Result<String, (i64, String)> {
+    first_param(params)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| invalid(&format!("missing {}", what)))
+}
+
+/// Reads one HTTP/1.1 request and returns its body. Returns `None` for
+/// malformed or oversized requests.
+pub(crate) async fn read_http_body(stream: &mut TcpStream) -> std::io::Result<Option<Vec<u8>>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_BODY_BYTES {
+            return Ok(None);
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&buf[..header_end]).to_ascii_lowercase();
+    if !headers.starts_with("post ") {
+        return Ok(None);
+    }
+    let content_length = headers
+        .lines()
+        .find_map(|l| l.strip_prefix("content-length:"))
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+
+    let mut body = buf.split_off(header_end);
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+    Ok(Some(body))
+}
+
+pub(crate) async fn write_http(stream: &mut TcpStream, status: &str, body: &[u8]) -> std::io::Result<()> {
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await
+}
@@ -0,0 +1,441 @@
+//! Capability-aware job scheduler.
+//!
+//! A background task periodically matches the job queue against the
+//! capabilities nodes have registered with the daemon. Each job goes to the
+//! best capable node that still has a free slot: highest reputation first,
+//! then the least loaded, then the most GPU memory. Reputation is weighted
+//! per job kind by [`SchedulerConfig::reputation`], so a training job favours
+//! nodes with good training results and an inference job nodes with good
+//! uptime. Jobs no node can take
+//! stay queued and are retried on the next pass. The queue itself is bounded;
+//! once it holds [`SchedulerConfig::max_queued`] jobs new submissions are
+//! refused until the backlog drains.
+//!
+//! Waiting jobs are considered in weighted fair order across their posters,
+//! see [`super::fair_queue`], and no single poster may fill more than
+//! [`FairnessConfig::max_poster_share`] of the queue.
+//!
+//! A node registering with a benchmark is matched on what the benchmark
+//! measured, not on the hardware it claims: the benchmark must be signed by
+//! the node, recent, and carry the output of the seeded kernel, see
+//! [`runtime::node::capability_probe`]. Once it goes stale the node keeps
+//! no GPUs until it registers a fresh one. With
+//! [`AttestationConfig::required`] set, nodes without a benchmark get no
+//! GPUs either.
+//!
+//! Posters may pin a job to chosen workers or pools, or exclude some, see
+//! [`JobRequirements`](runtime::job::JobRequirements); only the workers they
+//! allow are considered. Each assignment and how it ended feeds the
+//! worker's record in the [`Marketplace`].
+//!
+//! Every assignment, deferral and release is kept in a bounded decision log
+//! served over JSON-RPC.
+
+use super::fair_queue::{poster_of, FairQueue, FairnessConfig, FairnessStats, Poster};
+use super::marketplace::{Marketplace, WorkerListing};
+use super::nonces::unix_now;
+use super::types::JobQueue;
+use runtime::blockchain::Blockchain;
+use runtime::job::Job;
+use runtime::node::capability_probe::DEFAULT_MAX_AGE_SECS;
+use runtime::node::{CapabilityType, NodeCapability, ReputationConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Scheduler state shared between the scheduling task and the RPC server.
+pub type SharedScheduler = Arc<Mutex<Scheduler>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Time between scheduling passes.
+    pub interval: Duration,
+    /// Jobs a single node runs at once.
+    pub max_jobs_per_node: usize,
+    /// Queued jobs at which new submissions are refused.
+    pub max_queued: usize,
+    /// Decisions kept for the RPC log.
+    pub history: usize,
+    /// How reputation dimensions are weighted per job kind.
+    #[serde(default)]
+    pub reputation: ReputationConfig,
+    /// How posters share the nodes.
+    #[serde(default)]
+    pub fairness: FairnessConfig,
+    /// Which hardware claims are trusted.
+    #[serde(default)]
+    pub attestation: AttestationConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationConfig {
+    /// Only GPUs shown by a benchmark count; nodes without one are
+    /// matched as CPU-only.
+    pub required: bool,
+    /// Age in seconds after which a benchmark no longer counts.
+    pub max_age_secs: u64,
+}
+
+impl Default for AttestationConfig {
+    fn default() -> Self {
+        Self { required: false, max_age_secs: DEFAULT_MAX_AGE_SECS }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            max_jobs_per_node: 2,
+            max_queued: 256,
+            history: 1024,
+            reputation: ReputationConfig::default(),
+            fairness: FairnessConfig::default(),
+            attestation: AttestationConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SchedulerError {
+    #[error("job queue is full ({0} jobs queued), try again later")]
+    QueueFull(usize),
+    #[error("poster {poster} already has {held} jobs queued, try again later")]
+    PosterShareExceeded { poster: String, held: usize },
+    #[error("benchmark of node {0} is not signed by it")]
+    InvalidBenchmark(String),
+    #[error("benchmark of node {0} is stale or its kernel output does not check")]
+    UnattestedBenchmark(String),
+}
+
+/// Why a job is still waiting.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DeferReason {
+    /// No registered node meets the job's requirements.
+    NoCapableNode,
+    /// Capable nodes exist but the poster pinned others or excluded them.
+    NoAllowedNode,
+    /// Capable nodes exist but all of them are at capacity.
+    NodesBusy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum Outcome {
+    Assigned { node: String },
+    Deferred { reason: DeferReason },
+    /// The job left the queue, e.g. because it was mined, freeing the node.
+    Released { node: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulingDecision {
+    /// Sequence number, increasing across the daemon's lifetime.
+    pub seq: u64,
+    pub job_id: u64,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+/// Summary served by `getSchedulerStatus`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerStatus {
+    pub nodes: usize,
+    pub queued: usize,
+    pub assigned: usize,
+    pub deferred: usize,
+    /// Whether new submissions are currently refused.
+    pub backpressure: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    config: SchedulerConfig,
+    nodes: BTreeMap<String, NodeCapability>,
+    assignments: BTreeMap<u64, String>,
+    /// Last reason each waiting job was deferred, so repeats are not logged.
+    deferred: HashMap<u64, DeferReason>,
+    decisions: VecDeque<SchedulingDecision>,
+    next_seq: u64,
+    queued: usize,
+    fairness: FairQueue,
+    marketplace: Marketplace,
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        let fairness = FairQueue::new(config.fairness.clone());
+        Self { config, fairness, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Record or update the capabilities of `node_id`. A benchmark attached
+    /// to the capability must be signed by the node itself.
+    pub fn register_node(&mut self, node_id: &str, capability: NodeCapability) -> Result<(), SchedulerError> {
+        self.register_node_at(node_id, capability, unix_now())
+    }
+
+    /// [`Scheduler::register_node`], listing the node in the marketplace
+    /// with `listing`.
+    pub fn register_listed(
+        &mut self,
+        node_id: &str,
+        capability: NodeCapability,
+        listing: WorkerListing,
+    ) -> Result<(), SchedulerError> {
+        self.register(node_id, capability, Some(listing), unix_now())
+    }
+
+    /// [`Scheduler::register_node`] as of `now`. A benchmark must attest
+    /// the node's hardware at `now`, and replaces what the node claims.
+    pub fn register_node_at(
+        &mut self,
+        node_id: &str,
+        capability: NodeCapability,
+        now: u64,
+    ) -> Result<(), SchedulerError> {
+        self.register(node_id, capability, None, now)
+    }
+
+    fn register(
+        &mut self,
+        node_id: &str,
+        capability: NodeCapability,
+        listing: Option<WorkerListing>,
+        now: u64,
+    ) -> Result<(), SchedulerError> {
+        let capability = match &capability.benchmark {
+            Some(benchmark) => {
+                if !benchmark.verify() || benchmark.signer != node_id {
+                    return Err(SchedulerError::InvalidBenchmark(node_id.to_string()));
+                }
+                capability
+                    .measured(now, self.config.attestation.max_age_secs)
+                    .ok_or_else(|| SchedulerError::UnattestedBenchmark(node_id.to_string()))?
+            }
+            None if self.config.attestation.required => unmeasured(capability),
+            None => capability,
+        };
+        self.marketplace.register(node_id, &capability, listing);
+        self.nodes.insert(node_id.to_string(), capability);
+        Ok(())
+    }
+
+    /// Drop the GPUs of nodes whose benchmark went stale by `now` until they
+    /// register a fresh one. Returns those nodes.
+    pub fn expire_attestations(&mut self, now: u64) -> Vec<String> {
+        let max_age = self.config.attestation.max_age_secs;
+        let mut stale = Vec::new();
+        for (id, cap) in self.nodes.iter_mut() {
+            if cap.benchmark.as_ref().is_some_and(|b| !b.is_fresh(now, max_age)) {
+                *cap = unmeasured(cap.clone());
+                self.marketplace.register(id, cap, None);
+                stale.push(id.clone());
+            }
+        }
+        stale
+    }
+
+    /// Forget `node_id`; its jobs are reassigned on the next pass.
+    pub fn remove_node(&mut self, node_id: &str) -> Vec<u64> {
+        self.nodes.remove(node_id);
+        self.marketplace.unregister(node_id);
+        let orphaned: Vec<u64> =
+            self.assignments.iter().filter(|(_, n)| *n == node_id).map(|(id, _)| *id).collect();
+        for job_id in &orphaned {
+            self.assignments.remove(job_id);
+            self.marketplace.abandoned(node_id);
+            self.log(*job_id, Outcome::Released { node: node_id.to_string() });
+        }
+        orphaned
+    }
+
+    /// Refuses new work while the queue is at its limit.
+    pub fn admit(&self, queued: usize) -> Result<(), SchedulerError> {
+        if queued >= self.config.max_queued {
+            return Err(SchedulerError::QueueFull(queued));
+        }
+        Ok(())
+    }
+
+    /// Refuses new work from `poster` while they hold their full share of
+    /// the queue with `held` jobs.
+    pub fn admit_poster(&self, poster: &str, held: usize) -> Result<(), SchedulerError> {
+        let share = (self.config.max_queued as f64 * self.config.fairness.max_poster_share).ceil() as usize;
+        if held >= share.max(1) {
+            return Err(SchedulerError::PosterShareExceeded { poster: poster.to_string(), held });
+        }
+        Ok(())
+    }
+
+    /// One scheduling pass over `queue`, all of it from unknown posters.
+    /// Returns the decisions it made.
+    pub fn schedule<'a>(&mut self, queue: impl IntoIterator<Item = &'a Job>) -> Vec<SchedulingDecision> {
+        self.schedule_with(queue, |_| None)
+    }
+
+    /// One scheduling pass over `queue`, waiting jobs taken in weighted fair
+    /// order across the posters `poster` names. Returns the decisions it
+    /// made.
+    pub fn schedule_with<'a>(
+        &mut self,
+        queue: impl IntoIterator<Item = &'a Job>,
+        poster: impl Fn(&Job) -> Option<Poster>,
+    ) -> Vec<SchedulingDecision> {
+        let queue: Vec<&Job> = queue.into_iter().collect();
+        self.queued = queue.len();
+        let first = self.next_seq;
+
+        let live: HashSet<u64> = queue.iter().map(|j| j.id).collect();
+        let gone: Vec<(u64, String)> = self
+            .assignments
+            .iter()
+            .filter(|(id, _)| !live.contains(id))
+            .map(|(id, node)| (*id, node.clone()))
+            .collect();
+        for (job_id, node) in gone {
+            self.assignments.remove(&job_id);
+            self.marketplace.completed(&node);
+            self.log(job_id, Outcome::Released { node });
+        }
+        self.deferred.retain(|id, _| live.contains(id));
+
+        let waiting = queue
+            .into_iter()
+            .filter(|job| !self.assignments.contains_key(&job.id))
+            .map(|job| (job, poster(job)))
+            .collect();
+        for (job, flow) in self.fairness.order(waiting) {
+            match self.pick(job) {
+                Ok(node) => {
+                    self.fairness.charge(&flow);
+                    self.deferred.remove(&job.id);
+                    self.assignments.insert(job.id, node.clone());
+                    self.marketplace.assigned(&node);
+                    self.log(job.id, Outcome::Assigned { node });
+                }
+                Err(reason) => {
+                    if self.deferred.insert(job.id, reason) != Some(reason) {
+                        self.log(job.id, Outcome::Deferred { reason });
+                    }
+                }
+            }
+        }
+        self.decisions.iter().filter(|d| d.seq >= first).cloned().collect()
+    }
+
+    fn pick(&self, job: &Job) -> Result<String, DeferReason> {
+        let mut load: HashMap<&str, usize> = HashMap::new();
+        for node in self.assignments.values() {
+            *load.entry(node.as_str()).or_default() += 1;
+        }
+        let capable: Vec<(&String, &NodeCapability)> = self
+            .nodes
+            .iter()
+            .filter(|(_, cap)| job.requirements.as_ref().map_or(true, |r| r.satisfied_by(cap)))
+            .collect();
+        if capable.is_empty() {
+            return Err(DeferReason::NoCapableNode);
+        }
+        let capable: Vec<(&String, &NodeCapability)> = capable
+            .into_iter()
+            .filter(|(id, _)| {
+                job.requirements.as_ref().map_or(true, |r| r.allows(id, self.marketplace.pool_of(id)))
+            })
+            .collect();
+        if capable.is_empty() {
+            return Err(DeferReason::NoAllowedNode);
+        }
+        let weights = self.config.reputation.weights_for(job.kind.as_deref());
+        capable
+            .into_iter()
+            .map(|(id, cap)| (id, cap, cap.reputation.weighted(weights), load.get(id.as_str()).copied().unwrap_or(0)))
+            .filter(|(_, _, _, load)| *load < self.config.max_jobs_per_node)
+            .min_by(|(a_id, a, a_rep, a_load), (b_id, b, b_rep, b_load)| {
+                b_rep
+                    .total_cmp(a_rep)
+                    .then(a_load.cmp(b_load))
+                    .then(b.gpu_memory_gb.cmp(&a.gpu_memory_gb))
+                    .then(a_id.cmp(b_id))
+            })
+            .map(|(id, _, _, _)| id.clone())
+            .ok_or(DeferReason::NodesBusy)
+    }
+
+    fn log(&mut self, job_id: u64, outcome: Outcome) {
+        info!(job_id, ?outcome, "scheduling decision");
+        self.decisions.push_back(SchedulingDecision { seq: self.next_seq, job_id, outcome });
+        self.next_seq += 1;
+        while self.decisions.len() > self.config.history {
+            self.decisions.pop_front();
+        }
+    }
+
+    pub fn assignment(&self, job_id: u64) -> Option<&str> {
+        self.assignments.get(&job_id).map(String::as_str)
+    }
+
+    /// Decisions with a sequence number of at least `since`, oldest first.
+    pub fn decisions(&self, since: u64) -> Vec<SchedulingDecision> {
+        self.decisions.iter().filter(|d| d.seq >= since).cloned().collect()
+    }
+
+    pub fn status(&self) -> SchedulerStatus {
+        SchedulerStatus {
+            nodes: self.nodes.len(),
+            queued: self.queued,
+            assigned: self.assignments.len(),
+            deferred: self.deferred.len(),
+            backpressure: self.admit(self.queued).is_err(),
+        }
+    }
+
+    pub fn fairness(&self) -> FairnessStats {
+        self.fairness.stats()
+    }
+
+    pub fn marketplace(&self) -> &Marketplace {
+        &self.marketplace
+    }
+}
+
+/// `capability` with no benchmark and none of the GPUs it claims.
+fn unmeasured(mut capability: NodeCapability) -> NodeCapability {
+    capability.gpus = 0;
+    capability.gpu_memory_gb = 0;
+    capability.capability_types.retain(|t| *t != CapabilityType::GpuAccelerated);
+    capability.benchmark = None;
+    capability
+}
+
+/// Runs a scheduling pass over `job_queue` every configured interval until
+/// the daemon exits. Posters and their stake are read from the chain's job
+/// escrows.
+pub async fn run_scheduler(
+    scheduler: SharedScheduler,
+    job_queue: JobQueue,
+    blockchain: Arc<Mutex<Blockchain>>,
+) {
+    let interval = scheduler.lock().await.config.interval;
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        scheduler.lock().await.expire_attestations(unix_now());
+        let queue = job_queue.lock().await.clone();
+        let posters: HashMap<u64, Poster> = {
+            let chain = blockchain.lock().await;
+            queue.iter().filter_map(|job| Some((job.id, poster_of(&chain.state, job)?))).collect()
+        };
+        scheduler.lock().await.schedule_with(&queue, |job| posters.get(&job.id).cloned());
+    }
+}
@@ -12,6 +12,23 @@ use tokio::sync::Mutex;
 pub const SOCKET_PATH: &str = "/tmp/bcai_devnet.sock";
 /// PID file written on daemon startup so external scripts can manage it.
 pub const PID_FILE: &str = "/tmp/bcai_devnet.pid";
+/// Environment variable naming a `genesis.json` to initialise the chain from.
+pub const GENESIS_ENV: &str = "BCAI_GENESIS";
+/// Environment variable that, set to `1` or `true`, starts the daemon as a
+/// read-only follower.
+pub const FOLLOWER_ENV: &str = "BCAI_FOLLOWER";
+/// Environment variable holding a `u64` seed that makes the node's random
+/// choices reproducible; see [`runtime::rng`].
+pub const SEED_ENV: &str = "BCAI_SEED";
+/// Ed25519 `Metrics` session key the node signs its metrics reports with,
+/// relative to `$HOME`.
+pub const METRICS_KEY_FILE: &str = "/.bcai/metrics_session.key";
+/// Address of the JSON-RPC endpoint used by explorers and SDKs.
+pub const RPC_ADDR: &str = "127.0.0.1:8545";
+/// Address of the WebSocket endpoint serving chain event subscriptions.
+pub const WS_ADDR: &str = "127.0.0.1:8546";
+/// Address of the GraphQL explorer endpoint.
+pub const GRAPHQL_ADDR: &str = "127.0.0.1:8547";
 
 // --- Shared state ------------------------------------------------------------
 
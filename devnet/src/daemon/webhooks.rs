@@ -0,0 +1,412 @@
+//! Webhooks for chain events.
+//!
+//! Users register a URL together with an event filter over JSON-RPC
+//! (`registerWebhook`) and get back a secret. The daemon listens to the same
+//! [`ChainEvent`] broadcast that feeds WebSocket subscribers and POSTs every
+//! matching event as JSON, signed with HMAC-SHA256 over the timestamp and
+//! body so receivers can check it came from this node and is fresh. Failed
+//! deliveries are retried with exponential backoff; once
+//! [`WebhookConfig::max_attempts`] is spent they move to a bounded
+//! dead-letter queue, from which they can be listed and replayed.
+
+use super::ws::ChainEvent;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{info, warn};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const TIMESTAMP_HEADER: &str = "x-bcai-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-bcai-signature";
+pub const DELIVERY_HEADER: &str = "x-bcai-delivery";
+
+/// How often pending deliveries are checked for being due.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Webhook state shared between the delivery task and the RPC server.
+pub type SharedWebhooks = Arc<Mutex<Webhooks>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Deliveries attempted before an event is dead-lettered.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles with every further attempt.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Dead letters kept; the oldest are dropped first.
+    pub dead_letters: usize,
+    /// Time a receiver has to answer one delivery.
+    pub timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            dead_letters: 1024,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Wait after the `attempts`-th failed delivery.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WebhookError {
+    #[error("webhook URL must be http://host[:port][/path], got {0}")]
+    InvalidUrl(String),
+    #[error("webhook {0} not found")]
+    NotFound(String),
+}
+
+/// Which events a webhook receives.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum WebhookFilter {
+    AccountCredited { account: String },
+    /// Completion of `job_id`, or of any job when unset.
+    JobCompleted {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        job_id: Option<u64>,
+    },
+    /// Registrations of `model_id`, or of any model when unset.
+    ModelRegistered {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model_id: Option<String>,
+    },
+}
+
+impl WebhookFilter {
+    pub fn matches(&self, event: &ChainEvent) -> bool {
+        match (self, event) {
+            (WebhookFilter::AccountCredited { account }, ChainEvent::AccountCredited { account: a, .. }) => {
+                account == a
+            }
+            (WebhookFilter::JobCompleted { job_id }, ChainEvent::JobCompleted { job_id: id, .. }) => {
+                job_id.is_none_or(|j| j == *id)
+            }
+            (WebhookFilter::ModelRegistered { model_id }, ChainEvent::ModelRegistered { model_id: m, .. }) => {
+                model_id.as_ref().is_none_or(|wanted| wanted == m)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub filter: WebhookFilter,
+    /// Hex key the payloads are signed with.
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+}
+
+/// One event on its way to one webhook.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub id: u64,
+    pub webhook: String,
+    pub event: ChainEvent,
+    /// Failed attempts so far.
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    next_attempt: Option<Instant>,
+}
+
+impl Delivery {
+    /// The JSON body POSTed for this delivery.
+    pub fn body(&self) -> Vec<u8> {
+        json!({ "delivery": self.id, "webhook": self.webhook, "event": self.event }).to_string().into_bytes()
+    }
+}
+
+/// Delivery counters served by `getWebhookStats`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookStats {
+    pub webhooks: usize,
+    pub pending: usize,
+    pub delivered: u64,
+    pub retried: u64,
+    pub dead_lettered: u64,
+}
+
+fn mac(secret: &str) -> HmacSha256 {
+    HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+/// Hex HMAC-SHA256 of `"<timestamp>.<body>"` under `secret`.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = mac(secret);
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Checks a payload received from the daemon.
+pub fn verify(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else { return false };
+    let mut mac = mac(secret);
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Registered webhooks and their delivery queues.
+#[derive(Debug, Default)]
+pub struct Webhooks {
+    config: WebhookConfig,
+    hooks: BTreeMap<String, Webhook>,
+    pending: VecDeque<Delivery>,
+    dead: VecDeque<Delivery>,
+    next_hook: u64,
+    next_delivery: u64,
+    stats: WebhookStats,
+}
+
+impl Webhooks {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &WebhookConfig {
+        &self.config
+    }
+
+    /// Register `url` for events matching `filter`. The returned webhook
+    /// carries the secret its payloads are signed with.
+    pub fn register(&mut self, url: &str, filter: WebhookFilter) -> Result<Webhook, WebhookError> {
+        parse_url(url)?;
+        self.next_hook += 1;
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let hook = Webhook {
+            id: format!("wh-{}", self.next_hook),
+            url: url.to_string(),
+            filter,
+            secret: hex::encode(secret),
+        };
+        self.hooks.insert(hook.id.clone(), hook.clone());
+        info!(webhook = %hook.id, url, "webhook registered");
+        Ok(hook)
+    }
+
+    /// Forget `id` and drop its pending deliveries.
+    pub fn remove(&mut self, id: &str) -> Result<(), WebhookError> {
+        self.hooks.remove(id).ok_or_else(|| WebhookError::NotFound(id.to_string()))?;
+        self.pending.retain(|d| d.webhook != id);
+        Ok(())
+    }
+
+    pub fn webhooks(&self) -> Vec<Webhook> {
+        self.hooks.values().cloned().collect()
+    }
+
+    /// Queue `event` for every webhook whose filter matches it.
+    pub fn enqueue(&mut self, event: &ChainEvent, now: Instant) {
+        for hook in self.hooks.values().filter(|h| h.filter.matches(event)) {
+            self.next_delivery += 1;
+            self.pending.push_back(Delivery {
+                id: self.next_delivery,
+                webhook: hook.id.clone(),
+                event: event.clone(),
+                attempts: 0,
+                last_error: None,
+                next_attempt: Some(now),
+            });
+        }
+    }
+
+    /// Take the deliveries due by `now`, each with the webhook it goes to.
+    pub fn due(&mut self, now: Instant) -> Vec<(Delivery, Webhook)> {
+        let (due, waiting): (VecDeque<Delivery>, VecDeque<Delivery>) =
+            self.pending.drain(..).partition(|d| d.next_attempt.is_none_or(|at| at <= now));
+        self.pending = waiting;
+        due.into_iter().filter_map(|d| Some((d.clone(), self.hooks.get(&d.webhook)?.clone()))).collect()
+    }
+
+    /// Record how an attempt at `delivery` went. Failures are retried after
+    /// a backoff until the attempts run out, then dead-lettered.
+    pub fn complete(&mut self, mut delivery: Delivery, result: Result<(), String>, now: Instant) {
+        let error = match result {
+            Ok(()) => {
+                self.stats.delivered += 1;
+                return;
+            }
+            Err(error) => error,
+        };
+        delivery.attempts += 1;
+        warn!(
+            delivery = delivery.id,
+            webhook = %delivery.webhook,
+            attempts = delivery.attempts,
+            %error,
+            "webhook delivery failed"
+        );
+        delivery.last_error = Some(error);
+        if delivery.attempts < self.config.max_attempts {
+            self.stats.retried += 1;
+            delivery.next_attempt = Some(now + self.config.backoff(delivery.attempts));
+            self.pending.push_back(delivery);
+            return;
+        }
+        self.stats.dead_lettered += 1;
+        delivery.next_attempt = None;
+        self.dead.push_back(delivery);
+        while self.dead.len() > self.config.dead_letters {
+            self.dead.pop_front();
+        }
+    }
+
+    /// Dead letters, oldest first.
+    pub fn dead_letters(&self) -> Vec<Delivery> {
+        self.dead.iter().cloned().collect()
+    }
+
+    /// Move dead letter `delivery_id` back to the queue with a fresh set of
+    /// attempts. Returns whether it was found.
+    pub fn replay(&mut self, delivery_id: u64, now: Instant) -> bool {
+        let Some(i) = self.dead.iter().position(|d| d.id == delivery_id) else { return false };
+        let mut delivery = self.dead.remove(i).expect("position is in range");
+        delivery.attempts = 0;
+        delivery.next_attempt = Some(now);
+        self.pending.push_back(delivery);
+        true
+    }
+
+    pub fn stats(&self) -> WebhookStats {
+        WebhookStats { webhooks: self.hooks.len(), pending: self.pending.len(), ..self.stats.clone() }
+    }
+}
+
+/// Host, port and path of an `http://` URL.
+fn parse_url(url: &str) -> Result<(String, u16, String), WebhookError> {
+    let invalid = || WebhookError::InvalidUrl(url.to_string());
+    let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let path = if path.is_empty() { "/" } else { path };
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// Delivers one signed payload.
+pub trait WebhookSender {
+    fn post(
+        &self,
+        url: &str,
+        headers: &[(&str, String)],
+        body: &[u8],
+    ) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+/// Plain HTTP/1.1 sender; put a TLS-terminating proxy in front of receivers
+/// that need HTTPS.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpSender {
+    pub timeout: Duration,
+}
+
+impl HttpSender {
+    async fn send(url: &str, headers: &[(&str, String)], body: &[u8]) -> Result<(), String> {
+        let (host, port, path) = parse_url(url).map_err(|e| e.to_string())?;
+        let mut stream = TcpStream::connect((host.as_str(), port)).await.map_err(|e| e.to_string())?;
+        let mut request = format!(
+            "POST {path} HTTP/1.1\r\nHost: {host}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n",
+            body.len()
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await.map_err(|e| e.to_string())?;
+        stream.write_all(body).await.map_err(|e| e.to_string())?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.map_err(|e| e.to_string())?;
+        let status = String::from_utf8_lossy(&response);
+        let code = status.split_whitespace().nth(1).and_then(|c| c.parse::<u16>().ok());
+        match code {
+            Some(code) if (200..300).contains(&code) => Ok(()),
+            Some(code) => Err(format!("receiver answered {code}")),
+            None => Err("malformed HTTP response".to_string()),
+        }
+    }
+}
+
+impl WebhookSender for HttpSender {
+    async fn post(&self, url: &str, headers: &[(&str, String)], body: &[u8]) -> Result<(), String> {
+        tokio::time::timeout(self.timeout, Self::send(url, headers, body))
+            .await
+            .unwrap_or_else(|_| Err("timed out".to_string()))
+    }
+}
+
+/// Signs and sends `delivery` to `hook`.
+pub async fn deliver(sender: &impl WebhookSender, delivery: &Delivery, hook: &Webhook) -> Result<(), String> {
+    let body = delivery.body();
+    let timestamp = chrono::Utc::now().timestamp();
+    let headers = [
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (SIGNATURE_HEADER, sign(&hook.secret, timestamp, &body)),
+        (DELIVERY_HEADER, delivery.id.to_string()),
+    ];
+    sender.post(&hook.url, &headers, &body).await
+}
+
+/// Queues matching chain events and delivers due payloads until the daemon
+/// exits. The lock is never held across a delivery.
+pub async fn run_webhooks(
+    webhooks: SharedWebhooks,
+    mut events: broadcast::Receiver<ChainEvent>,
+    sender: impl WebhookSender,
+) {
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => webhooks.lock().await.enqueue(&event, Instant::now()),
+                Err(broadcast::error::RecvError::Lagged(n)) => warn!("webhooks lagged, dropped {} events", n),
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = ticker.tick() => {
+                let due = webhooks.lock().await.due(Instant::now());
+                for (delivery, hook) in due {
+                    let result = deliver(&sender, &delivery, &hook).await;
+                    webhooks.lock().await.complete(delivery, result, Instant::now());
+                }
+            }
+        }
+    }
+}
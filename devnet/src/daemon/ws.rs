@@ -0,0 +1,277 @@
+//! WebSocket subscription API for chain events.
+//!
+//! Clients open a WebSocket to [`WS_ADDR`](super::WS_ADDR) and send JSON-RPC
+//! `subscribe` requests naming a topic; the daemon then pushes matching
+//! events as `subscription` notifications until the client calls
+//! `unsubscribe` or disconnects. A single watcher task diffs chain and job
+//! queue state and fans events out to every connection over a broadcast
+//! channel, so connections never hold the blockchain lock themselves.
+
+use super::rpc::{RpcRequest, RpcResponse, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR};
+use super::types::JobQueue;
+use futures_util::{SinkExt, StreamExt};
+use runtime::blockchain::{transaction::StorageTx, Blockchain, Transaction};
+use runtime::job::Job;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, warn};
+
+/// How often the watcher diffs chain state.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// Events buffered per subscriber before slow clients start missing events.
+const EVENT_BUFFER: usize = 1024;
+
+/// Something that happened on the node that clients may subscribe to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum ChainEvent {
+    NewBlock { index: u32, hash: String, miner: String, timestamp: i64, tx_count: usize },
+    NewJob { job: Job },
+    BalanceChanged { account: String, balance: u64, block: u32 },
+    PouwEvaluation { task_id: String, evaluation_hash: String, block: u32 },
+    /// `account` received `amount` in a transfer.
+    AccountCredited { account: String, amount: u64, block: u32 },
+    /// An escrowed job was mined as the block's PoUW task.
+    JobCompleted { job_id: u64, miner: String, block: u32 },
+    /// An inference endpoint serving `model_id` was registered on chain.
+    ModelRegistered { model_id: String, endpoint_id: String, owner: String, block: u32 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum Topic {
+    NewBlocks,
+    NewJobs,
+    /// Balance changes; requires a list of watched accounts.
+    Balances,
+    PouwEvaluations,
+}
+
+/// One active subscription on a connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub topic: Topic,
+    /// Accounts watched by a [`Topic::Balances`] subscription.
+    pub accounts: HashSet<String>,
+}
+
+impl Subscription {
+    /// Parses `subscribe` params: `[topic]` or `["balances", [accounts...]]`.
+    pub fn from_params(params: &Value) -> Result<Self, String> {
+        let items = params.as_array().ok_or("params must be an array")?;
+        let topic: Topic = items
+            .first()
+            .cloned()
+            .ok_or("missing topic")
+            .and_then(|t| serde_json::from_value(t).map_err(|_| "unknown topic"))?;
+        let accounts: HashSet<String> = match items.get(1) {
+            Some(v) => serde_json::from_value(v.clone()).map_err(|_| "accounts must be a list of strings")?,
+            None => HashSet::new(),
+        };
+        if topic == Topic::Balances && accounts.is_empty() {
+            return Err("balances subscription needs at least one account".into());
+        }
+        Ok(Self { topic, accounts })
+    }
+
+    pub fn matches(&self, event: &ChainEvent) -> bool {
+        match (self.topic, event) {
+            (Topic::NewBlocks, ChainEvent::NewBlock { .. })
+            | (Topic::NewJobs, ChainEvent::NewJob { .. })
+            | (Topic::PouwEvaluations, ChainEvent::PouwEvaluation { .. }) => true,
+            (Topic::Balances, ChainEvent::BalanceChanged { account, .. }) => self.accounts.contains(account),
+            _ => false,
+        }
+    }
+}
+
+/// Diffs chain and job-queue state on a fixed interval and publishes the
+/// differences as events. Runs until the daemon exits.
+pub async fn watch_chain(
+    blockchain: Arc<Mutex<Blockchain>>,
+    job_queue: JobQueue,
+    events: broadcast::Sender<ChainEvent>,
+) {
+    let mut next_block = blockchain.lock().await.blocks.len();
+    let mut seen_jobs: HashSet<u64> = job_queue.lock().await.iter().map(|j| j.id).collect();
+    let mut ticker = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        for event in block_events(&*blockchain.lock().await, &mut next_block) {
+            // Sending only fails when nobody is subscribed, which is fine.
+            let _ = events.send(event);
+        }
+        for job in job_queue.lock().await.iter() {
+            if seen_jobs.insert(job.id) {
+                let _ = events.send(ChainEvent::NewJob { job: job.clone() });
+            }
+        }
+    }
+}
+
+/// Events for every block from `next_block` onwards; advances the cursor.
+pub fn block_events(chain: &Blockchain, next_block: &mut usize) -> Vec<ChainEvent> {
+    let mut events = Vec::new();
+    for block in chain.blocks.iter().skip(*next_block) {
+        events.push(ChainEvent::NewBlock {
+            index: block.index,
+            hash: block.hash.clone(),
+            miner: block.miner.clone(),
+            timestamp: block.timestamp,
+            tx_count: block.transactions.len(),
+        });
+        if let Some(job_id) = block.task.job_id {
+            events.push(ChainEvent::JobCompleted { job_id, miner: block.miner.clone(), block: block.index });
+        }
+        let mut touched: Vec<&String> = vec![&block.miner];
+        for tx in &block.transactions {
+            touched.extend([&tx.from, &tx.to]);
+            events.extend(transaction_events(tx, block.index));
+        }
+        let mut reported = HashSet::new();
+        for account in touched.into_iter().filter(|a| reported.insert(*a)) {
+            // Balances are reported as of the chain tip, not this block.
+            events.push(ChainEvent::BalanceChanged {
+                account: account.clone(),
+                balance: chain.state.get_balance(account),
+                block: block.index,
+            });
+        }
+    }
+    *next_block = chain.blocks.len();
+    events
+}
+
+/// Events of `tx` itself when included in block `block`; balance changes
+/// are reported per block by [`block_events`].
+pub fn transaction_events(tx: &Transaction, block: u32) -> Vec<ChainEvent> {
+    let mut events = Vec::new();
+    if tx.amount > 0 && !tx.to.is_empty() {
+        events.push(ChainEvent::AccountCredited { account: tx.to.clone(), amount: tx.amount, block });
+    }
+    match &tx.storage {
+        Some(StorageTx::PoUWEvaluationHash { task_id, evaluation_hash }) => {
+            events.push(ChainEvent::PouwEvaluation {
+                task_id: task_id.clone(),
+                evaluation_hash: evaluation_hash.clone(),
+                block,
+            });
+        }
+        Some(StorageTx::RegisterEndpoint { record }) => {
+            events.push(ChainEvent::ModelRegistered {
+                model_id: record.model_id.clone(),
+                endpoint_id: record.endpoint_id.clone(),
+                owner: record.owner.clone(),
+                block,
+            });
+        }
+        _ => {}
+    }
+    events
+}
+
+/// Accepts WebSocket connections and serves subscriptions.
+pub struct SubscriptionServer {
+    events: broadcast::Sender<ChainEvent>,
+}
+
+impl SubscriptionServer {
+    pub fn new() -> (Self, broadcast::Sender<ChainEvent>) {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        (Self { events: events.clone() }, events)
+    }
+
+    pub async fn serve(self, addr: &str) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("WebSocket subscriptions on ws://{}", addr);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let rx = self.events.subscribe();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, rx).await {
+                    error!("WebSocket connection error: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    mut rx: broadcast::Receiver<ChainEvent>,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    let mut ws = tokio_tungstenite::accept_async(stream).await?;
+    let mut subscriptions: HashMap<String, Subscription> = HashMap::new();
+    let mut next_id = 0u64;
+    loop {
+        tokio::select! {
+            incoming = ws.next() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e),
+                };
+                let response = handle_request(&text, &mut subscriptions, &mut next_id);
+                ws.send(Message::Text(json!(response).to_string())).await?;
+            }
+            event = rx.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("WebSocket subscriber lagged, dropped {} events", n);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                };
+                for (id, sub) in &subscriptions {
+                    if sub.matches(&event) {
+                        let note = json!({
+                            "jsonrpc": "2.0",
+                            "method": "subscription",
+                            "params": { "subscription": id, "result": event },
+                        });
+                        ws.send(Message::Text(note.to_string())).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Handles `subscribe` / `unsubscribe`, returning the JSON-RPC response.
+pub fn handle_request(
+    text: &str,
+    subscriptions: &mut HashMap<String, Subscription>,
+    next_id: &mut u64,
+) -> RpcResponse {
+    let req: RpcRequest = match serde_json::from_str(text) {
+        Ok(req) => req,
+        Err(e) => return RpcResponse::err(Value::Null, PARSE_ERROR, e.to_string()),
+    };
+    if req.jsonrpc != "2.0" {
+        return RpcResponse::err(req.id, INVALID_REQUEST, "jsonrpc must be \"2.0\"");
+    }
+    match req.method.as_str() {
+        "subscribe" => match Subscription::from_params(&req.params) {
+            Ok(sub) => {
+                *next_id += 1;
+                let id = format!("0x{:x}", next_id);
+                subscriptions.insert(id.clone(), sub);
+                RpcResponse::ok(req.id, json!(id))
+            }
+            Err(e) => RpcResponse::err(req.id, INVALID_PARAMS, e),
+        },
+        "unsubscribe" => {
+            let removed = req.params.get(0).and_then(Value::as_str).and_then(|id| subscriptions.remove(id));
+            RpcResponse::ok(req.id, json!(removed.is_some()))
+        }
+        other => RpcResponse::err(req.id, METHOD_NOT_FOUND, format!("method not found: {}", other)),
+    }
+}
@@ -12,13 +12,7 @@ pub enum JobError {
     InsufficientBalance,
 }
 
-#[derive(Debug, Error)]
-pub enum LedgerError {
-    #[error("Insufficient balance for transfer or stake")]
-    InsufficientBalance,
-    #[error("Account not found: {0}")]
-    AccountNotFound(String),
-}
+pub use crate::ledger::LedgerError;
 
 #[derive(Debug, Error)]
 pub enum DevnetError {
@@ -28,6 +22,14 @@ pub enum DevnetError {
     Serde(#[from] serde_json::Error),
     #[error("A job-related error occurred: {0}")]
     Job(#[from] JobError),
+    #[error("A job-related error occurred: {0}")]
+    JobManager(#[from] crate::job::JobManagerError),
     #[error("A ledger-related error occurred: {0}")]
     Ledger(#[from] LedgerError),
+    #[error("A storage backend error occurred: {0}")]
+    Storage(#[from] sled::Error),
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+    #[error("A genesis-related error occurred: {0}")]
+    Genesis(#[from] runtime::blockchain::BlockchainError),
 } 
\ No newline at end of file
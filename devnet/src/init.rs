@@ -1,14 +1,14 @@
 use crate::config::DevnetConfig;
 use crate::error::DevnetError;
 use crate::job::post_job;
-use crate::ledger::{mint, TREASURY};
-use crate::persistence::{load_jobs, load_ledger, save_jobs, save_ledger};
+use crate::ledger::{mint, NATIVE_ASSET, TREASURY};
+use crate::persistence::Persistence;
 
-pub async fn start_devnet_node(config: DevnetConfig) -> Result<(), DevnetError> {
+pub async fn start_devnet_node(store: &dyn Persistence, config: DevnetConfig) -> Result<(), DevnetError> {
     println!("🚀 Starting BCAI Devnet with config: {:?}", config);
 
     // Initialize ledger with treasury and initial allocations
-    let mut ledger = load_ledger()?;
+    let mut ledger = store.load_ledger()?;
     mint(&mut ledger, TREASURY, config.initial_tokens * 10); // Treasury gets 10x
 
     // Create initial nodes
@@ -29,10 +29,10 @@ pub async fn start_devnet_node(config: DevnetConfig) -> Result<(), DevnetError>
         );
     }
 
-    save_ledger(&ledger)?;
+    store.save_ledger(&ledger)?;
 
     // Initialize jobs
-    let mut jobs = load_jobs()?;
+    let mut jobs = store.load_jobs()?;
 
     // Create sample training job
     post_job(
@@ -41,10 +41,11 @@ pub async fn start_devnet_node(config: DevnetConfig) -> Result<(), DevnetError>
         TREASURY,
         "Sample neural network training task".to_string(),
         100,
+        NATIVE_ASSET,
     )?;
 
-    save_jobs(&jobs)?;
-    save_ledger(&ledger)?;
+    store.save_jobs(&jobs)?;
+    store.save_ledger(&ledger)?;
 
     println!("🎉 Devnet initialized successfully!");
     println!(
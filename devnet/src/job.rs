@@ -1,4 +1,4 @@
-use crate::ledger::{TokenLedger, TREASURY};
+use crate::ledger::{quote_swap, swap_to_native, LedgerError, TokenLedger, NATIVE_ASSET, TREASURY};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -7,6 +7,20 @@ pub struct Job {
     pub id: String,
     pub data: Vec<u8>,
     pub reward: u64,
+    /// Asset the reward is escrowed in, native or bridged.
+    #[serde(default = "native_asset")]
+    pub asset: String,
+}
+
+fn native_asset() -> String {
+    NATIVE_ASSET.to_string()
+}
+
+/// What a worker received for a completed job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    pub asset: String,
+    pub amount: u64,
 }
 
 #[derive(Debug, Error)]
@@ -17,25 +31,31 @@ pub enum JobManagerError {
     InvalidJobData,
     #[error("Insufficient balance")]
     InsufficientBalance,
+    #[error(transparent)]
+    Ledger(#[from] LedgerError),
 }
 
+/// Posts a job whose `reward` of `asset` is escrowed in the treasury.
 pub fn post_job(
     jobs: &mut Vec<Job>,
     ledger: &mut TokenLedger,
     poster: &str,
     description: String,
     reward: u64,
+    asset: &str,
 ) -> Result<(), JobManagerError> {
-    if ledger.balance(poster) < reward {
+    ledger.rules(asset)?;
+    if ledger.asset_balance(asset, poster) < reward {
         return Err(JobManagerError::InsufficientBalance);
     }
 
-    ledger.transfer(poster, TREASURY, reward).unwrap(); // Escrow
+    ledger.transfer_asset(asset, poster, TREASURY, reward)?; // Escrow
 
     let job = Job {
         id: format!("job_{}", jobs.len()),
         data: description.into_bytes(),
         reward,
+        asset: asset.to_string(),
     };
     jobs.push(job);
     Ok(())
@@ -49,19 +69,38 @@ pub fn assign_job(jobs: &mut [Job], job_id: &str, _worker: &str) -> Result<(), J
     Ok(())
 }
 
+/// Releases the escrowed reward of `job_id` to `worker`. A reward in a
+/// bridged asset is swapped to the native token through the asset's pool
+/// when `swap_min_out` is given, failing without paying anything if the
+/// swap would pay out less.
 pub fn complete_job(
     jobs: &mut [Job],
     ledger: &mut TokenLedger,
     job_id: &str,
-) -> Result<(), JobManagerError> {
+    worker: &str,
+    swap_min_out: Option<u64>,
+) -> Result<Settlement, JobManagerError> {
     let job = jobs
         .iter_mut()
         .find(|j| j.id == job_id)
         .ok_or_else(|| JobManagerError::JobNotFound(job_id.to_string()))?;
 
-    // In a real system, we'd transfer reward to the worker
-    ledger.transfer(TREASURY, "worker", job.reward).unwrap();
+    let swap = swap_min_out.filter(|_| job.asset != NATIVE_ASSET);
+    if let Some(min_out) = swap {
+        let quoted = quote_swap(ledger, &job.asset, job.reward)?;
+        if quoted < min_out {
+            return Err(LedgerError::SlippageExceeded { quoted, min_out }.into());
+        }
+    }
+    ledger.transfer_asset(&job.asset, TREASURY, worker, job.reward)?;
+    let settlement = match swap {
+        Some(min_out) => Settlement {
+            asset: NATIVE_ASSET.to_string(),
+            amount: swap_to_native(ledger, worker, &job.asset, job.reward, min_out)?,
+        },
+        None => Settlement { asset: job.asset.clone(), amount: job.reward },
+    };
     job.reward = 0;
 
-    Ok(())
+    Ok(settlement)
 } 
\ No newline at end of file
@@ -1,9 +1,18 @@
 // This file will contain the ledger actions.
 
-use super::{LedgerError, TokenLedger, TREASURY};
+use super::{LedgerError, LedgerOp, TokenLedger, NATIVE_ASSET, TREASURY};
 
 pub fn mint(ledger: &mut TokenLedger, account: &str, amount: u64) {
-    ledger.balances.insert(account.to_string(), amount);
+    ledger.apply(LedgerOp::Mint, &[(account, amount)]);
+}
+
+/// Credits `amount` of a registered asset, e.g. when the bridge locks it on
+/// its origin chain. Unlike [`mint`] this adds to the existing balance.
+pub fn mint_asset(ledger: &mut TokenLedger, asset: &str, account: &str, amount: u64) -> Result<(), LedgerError> {
+    ledger.rules(asset)?;
+    let balance = ledger.asset_balance(asset, account);
+    ledger.apply_asset(LedgerOp::Mint, asset, &[(account, balance + amount)]);
+    Ok(())
 }
 
 pub fn transfer(
@@ -12,39 +21,50 @@ pub fn transfer(
     to: &str,
     amount: u64,
 ) -> Result<(), LedgerError> {
-    if ledger.balances.get(from).copied().unwrap_or(0) < amount {
-        return Err(LedgerError::InsufficientBalance);
-    }
-    ledger.balances.insert(from.to_string(), ledger.balances[from] - amount);
-    ledger.balances
-        .insert(to.to_string(), ledger.balances.get(to).copied().unwrap_or(0) + amount);
-    Ok(())
+    ledger.transfer(from, to, amount)
 }
 
 pub fn stake(ledger: &mut TokenLedger, account: &str, amount: u64) -> Result<(), LedgerError> {
-    if ledger.balances.get(account).copied().unwrap_or(0) < amount {
+    stake_asset(ledger, NATIVE_ASSET, account, amount)
+}
+
+pub fn stake_asset(ledger: &mut TokenLedger, asset: &str, account: &str, amount: u64) -> Result<(), LedgerError> {
+    if !ledger.rules(asset)?.stakeable {
+        return Err(LedgerError::NotStakeable(asset.to_string()));
+    }
+    let balance = ledger.asset_balance(asset, account);
+    if balance < amount {
         return Err(LedgerError::InsufficientBalance);
     }
-    ledger.balances.insert(account.to_string(), ledger.balances[account] - amount);
+    ledger.apply_asset(LedgerOp::Stake, asset, &[(account, balance - amount)]);
     Ok(())
 }
 
 pub fn unstake(ledger: &mut TokenLedger, account: &str, amount: u64) -> Result<(), LedgerError> {
-    if ledger.balances.get(account).copied().unwrap_or(0) < amount {
+    unstake_asset(ledger, NATIVE_ASSET, account, amount)
+}
+
+pub fn unstake_asset(ledger: &mut TokenLedger, asset: &str, account: &str, amount: u64) -> Result<(), LedgerError> {
+    if !ledger.rules(asset)?.stakeable {
+        return Err(LedgerError::NotStakeable(asset.to_string()));
+    }
+    let balance = ledger.asset_balance(asset, account);
+    if balance < amount {
         return Err(LedgerError::InsufficientBalance);
     }
-    ledger.balances.insert(account.to_string(), ledger.balances[account] + amount);
+    ledger.apply_asset(LedgerOp::Unstake, asset, &[(account, balance + amount)]);
     Ok(())
 }
 
 pub fn slash(ledger: &mut TokenLedger, offender: &str, amount: u64) -> Result<(), LedgerError> {
-    if ledger.balances.get(offender).copied().unwrap_or(0) < amount {
+    if ledger.balance(offender) < amount {
         return Err(LedgerError::InsufficientBalance);
     }
-    ledger.balances.insert(offender.to_string(), ledger.balances[offender] - amount);
-    ledger.balances.insert(
-        TREASURY.to_string(),
-        ledger.balances.get(TREASURY).copied().unwrap_or(0) + amount,
+    let offender_balance = ledger.balance(offender);
+    let treasury_balance = ledger.balance(TREASURY);
+    ledger.apply(
+        LedgerOp::Slash,
+        &[(offender, offender_balance - amount), (TREASURY, treasury_balance + amount)],
     );
     Ok(())
 }
@@ -56,13 +76,19 @@ pub fn reputation(ledger: &TokenLedger, account: &str) -> i32 {
 pub fn adjust_reputation(ledger: &mut TokenLedger, account: &str, delta: i32) {
     let current = ledger.balances.get(account).copied().unwrap_or(0) as i64;
     let new_balance = std::cmp::max(0, current + delta as i64) as u64;
-    ledger.balances.insert(account.to_string(), new_balance);
+    ledger.apply(LedgerOp::AdjustReputation, &[(account, new_balance)]);
 }
 
 pub fn burn(ledger: &mut TokenLedger, account: &str, amount: u64) -> Result<(), LedgerError> {
-    if ledger.balances.get(account).copied().unwrap_or(0) < amount {
+    burn_asset(ledger, NATIVE_ASSET, account, amount)
+}
+
+pub fn burn_asset(ledger: &mut TokenLedger, asset: &str, account: &str, amount: u64) -> Result<(), LedgerError> {
+    ledger.rules(asset)?;
+    let balance = ledger.asset_balance(asset, account);
+    if balance < amount {
         return Err(LedgerError::InsufficientBalance);
     }
-    ledger.balances.insert(account.to_string(), ledger.balances[account] - amount);
+    ledger.apply_asset(LedgerOp::Burn, asset, &[(account, balance - amount)]);
     Ok(())
-} 
\ No newline at end of file
+}
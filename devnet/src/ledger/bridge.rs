@@ -0,0 +1,285 @@
+//! Bridge deposits and withdrawals between the devnet and external chains.
+//!
+//! A deposit locks tokens on an external chain and, once the lock has the
+//! confirmations [`BridgeConfig::min_confirmations`] asks for, mints the
+//! wrapped asset here. A withdrawal burns the wrapped asset right away and
+//! unlocks the tokens on the external chain once the burn is confirmed.
+//! There is no real external chain in the devnet: confirmations accrue from
+//! the chain's block time, and like streams the transfers are advanced
+//! lazily, whenever a bridge command runs.
+
+use super::{LedgerError, LedgerOp, TokenLedger, TREASURY};
+use chrono::{DateTime, Duration, Utc};
+use runtime::cross_chain_bridge::{
+    BridgeConfig, BridgeError, BridgeTransaction, BridgeTransactionStatus, BridgeTransactionType,
+    ChainId, CrossChainMessage, MessageStatus, MessageType,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BridgeDirection {
+    /// From an external chain into the devnet.
+    Deposit,
+    /// From the devnet out to an external chain.
+    Withdraw,
+}
+
+/// A transfer across the bridge and the message relaying it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeTransfer {
+    pub id: u64,
+    pub direction: BridgeDirection,
+    /// Devnet account credited or debited.
+    pub account: String,
+    /// Wrapped asset on the devnet.
+    pub asset: String,
+    pub transaction: BridgeTransaction,
+    pub message: CrossChainMessage,
+}
+
+impl BridgeTransfer {
+    /// The chain whose confirmations the transfer waits for.
+    pub fn external_chain(&self) -> ChainId {
+        match self.direction {
+            BridgeDirection::Deposit => self.transaction.source_chain,
+            BridgeDirection::Withdraw => self.transaction.destination_chain,
+        }
+    }
+
+    /// Confirmations still missing.
+    pub fn pending_confirmations(&self) -> u32 {
+        self.transaction.required_confirmations.saturating_sub(self.transaction.confirmations)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.transaction.status == BridgeTransactionStatus::Pending
+    }
+}
+
+/// Parses a chain by name, ignoring case and spaces, e.g. `ethereum` or
+/// `binancesmartchain`; `bsc` also works.
+pub fn parse_chain(name: &str) -> Result<ChainId, BridgeError> {
+    let wanted: String =
+        name.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+    let chains = [
+        ChainId::BCAI,
+        ChainId::Ethereum,
+        ChainId::Polygon,
+        ChainId::BinanceSmartChain,
+        ChainId::Avalanche,
+        ChainId::Solana,
+        ChainId::Arbitrum,
+        ChainId::Optimism,
+    ];
+    chains
+        .into_iter()
+        .find(|chain| {
+            let known: String = chain.name().chars().filter(|c| !c.is_whitespace()).collect();
+            known.to_lowercase() == wanted
+                || (wanted == "bsc" && *chain == ChainId::BinanceSmartChain)
+        })
+        .ok_or_else(|| BridgeError::UnsupportedChain(name.to_string()))
+}
+
+/// The wrapped asset of `chain`'s native token, e.g. `wETH`.
+pub fn wrapped_asset(chain: ChainId) -> String {
+    format!("w{}", chain.native_token())
+}
+
+/// Seconds between blocks of `chain`, by which confirmations accrue.
+pub fn block_time(chain: ChainId) -> i64 {
+    match chain {
+        ChainId::Ethereum => 12,
+        ChainId::BinanceSmartChain => 3,
+        ChainId::Polygon | ChainId::Avalanche => 2,
+        ChainId::BCAI => 10,
+        ChainId::Solana | ChainId::Arbitrum | ChainId::Optimism => 1,
+    }
+}
+
+/// The bridge fee on `amount`, rounded up.
+pub fn bridge_fee(config: &BridgeConfig, amount: u64) -> u64 {
+    (amount as f64 * config.bridge_fee_rate).ceil() as u64
+}
+
+fn check(config: &BridgeConfig, chain: ChainId, amount: u64) -> Result<u32, BridgeError> {
+    if config.emergency_pause_enabled {
+        return Err(BridgeError::SecurityError("bridge is paused".to_string()));
+    }
+    if !config.supported_chains.contains(&chain) {
+        return Err(BridgeError::UnsupportedChain(chain.name().to_string()));
+    }
+    if amount == 0 || amount > config.max_transaction_amount {
+        return Err(BridgeError::InvalidTransaction(format!(
+            "amount {} is not between 1 and {}",
+            amount, config.max_transaction_amount
+        )));
+    }
+    Ok(config.min_confirmations.get(&chain).copied().unwrap_or(1))
+}
+
+/// What to move across the bridge.
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeRequest<'a> {
+    pub chain: ChainId,
+    /// Address on `chain` tokens are locked at or unlocked to.
+    pub external_address: &'a str,
+    /// Devnet account credited or debited.
+    pub account: &'a str,
+    pub asset: &'a str,
+    pub amount: u64,
+}
+
+fn record(
+    ledger: &mut TokenLedger,
+    config: &BridgeConfig,
+    direction: BridgeDirection,
+    request: &BridgeRequest,
+    required_confirmations: u32,
+    now: i64,
+) -> u64 {
+    let id = ledger.bridge_transfers.keys().next_back().map_or(1, |id| id + 1);
+    let created_at = DateTime::from_timestamp(now, 0).unwrap_or_else(Utc::now);
+    let (
+        transaction_type,
+        (source_chain, source_address),
+        (destination_chain, destination_address),
+    ) = match direction {
+        BridgeDirection::Deposit => (
+            BridgeTransactionType::LockAndMint,
+            (request.chain, request.external_address),
+            (ChainId::BCAI, request.account),
+        ),
+        BridgeDirection::Withdraw => (
+            BridgeTransactionType::BurnAndUnlock,
+            (ChainId::BCAI, request.account),
+            (request.chain, request.external_address),
+        ),
+    };
+    let transaction = BridgeTransaction {
+        id: format!("bridge-{}", id),
+        transaction_type,
+        source_chain,
+        destination_chain,
+        source_address: source_address.to_string(),
+        destination_address: destination_address.to_string(),
+        token_address: request.asset.to_string(),
+        amount: request.amount,
+        fee: bridge_fee(config, request.amount),
+        nonce: id,
+        created_at,
+        expires_at: created_at + Duration::hours(config.transaction_timeout_hours as i64),
+        status: BridgeTransactionStatus::Pending,
+        confirmations: 0,
+        required_confirmations,
+        validator_signatures: Vec::new(),
+        metadata: HashMap::new(),
+    };
+    let message = CrossChainMessage {
+        message_id: format!("msg-{}", id),
+        source_chain,
+        destination_chain,
+        message_type: MessageType::Custom(format!("{:?}", direction).to_lowercase()),
+        payload: Vec::new(),
+        sender: source_address.to_string(),
+        recipient: destination_address.to_string(),
+        gas_limit: 0,
+        created_at,
+        status: MessageStatus::Pending,
+    };
+    ledger.bridge_transfers.insert(
+        id,
+        BridgeTransfer {
+            id,
+            direction,
+            account: request.account.to_string(),
+            asset: request.asset.to_string(),
+            transaction,
+            message,
+        },
+    );
+    id
+}
+
+/// Locks the request's amount on its chain for its account, to be minted
+/// as its asset once confirmed. The asset is registered as bridged from the
+/// chain if it is new. Returns the transfer id.
+pub fn deposit(
+    ledger: &mut TokenLedger,
+    config: &BridgeConfig,
+    request: &BridgeRequest,
+    now: i64,
+) -> Result<u64, LedgerError> {
+    let required = check(config, request.chain, request.amount)?;
+    if ledger.rules(request.asset).is_err() {
+        ledger.register_asset(request.asset, super::AssetRules::bridged(request.chain.name()))?;
+    }
+    Ok(record(ledger, config, BridgeDirection::Deposit, request, required, now))
+}
+
+/// Burns the request's amount of its asset from its account and unlocks
+/// it, less the fee, on its chain once the burn is confirmed. The fee goes
+/// to the treasury. Returns the transfer id.
+pub fn withdraw(
+    ledger: &mut TokenLedger,
+    config: &BridgeConfig,
+    request: &BridgeRequest,
+    now: i64,
+) -> Result<u64, LedgerError> {
+    let required = check(config, request.chain, request.amount)?;
+    ledger.rules(request.asset)?;
+    let balance = ledger.asset_balance(request.asset, request.account);
+    if balance < request.amount {
+        return Err(LedgerError::InsufficientBalance);
+    }
+    let fee = bridge_fee(config, request.amount);
+    let treasury = ledger.asset_balance(request.asset, TREASURY);
+    ledger.apply_asset(
+        LedgerOp::Bridge,
+        request.asset,
+        &[(request.account, balance - request.amount), (TREASURY, treasury + fee)],
+    );
+    Ok(record(ledger, config, BridgeDirection::Withdraw, request, required, now))
+}
+
+/// Counts the confirmations every pending transfer has at `now` and
+/// completes those with enough: deposits mint the asset, less the fee, to
+/// their account and the fee to the treasury. Returns the ids completed.
+pub fn advance_bridge(ledger: &mut TokenLedger, now: i64) -> Vec<u64> {
+    let mut completed = Vec::new();
+    let ids: Vec<u64> =
+        ledger.bridge_transfers.values().filter(|t| t.is_pending()).map(|t| t.id).collect();
+    for id in ids {
+        let transfer = ledger.bridge_transfers.get_mut(&id).expect("listed above");
+        let elapsed = now - transfer.transaction.created_at.timestamp();
+        let blocks = (elapsed.max(0) / block_time(transfer.external_chain())) as u32;
+        let tx = &mut transfer.transaction;
+        tx.confirmations = blocks.min(tx.required_confirmations);
+        if tx.confirmations > 0 && transfer.message.status == MessageStatus::Pending {
+            transfer.message.status = MessageStatus::Relayed;
+        }
+        if tx.confirmations < tx.required_confirmations {
+            continue;
+        }
+        tx.status = BridgeTransactionStatus::Executed;
+        transfer.message.status = MessageStatus::Executed;
+        if transfer.direction == BridgeDirection::Deposit {
+            let (account, asset, amount, fee) =
+                (transfer.account.clone(), transfer.asset.clone(), tx.amount, tx.fee);
+            let balance = ledger.asset_balance(&asset, &account);
+            let treasury = ledger.asset_balance(&asset, TREASURY);
+            ledger.apply_asset(
+                LedgerOp::Bridge,
+                &asset,
+                &[(account.as_str(), balance + amount - fee), (TREASURY, treasury + fee)],
+            );
+        }
+        completed.push(id);
+    }
+    completed
+}
+
+pub fn bridge_transfer(ledger: &TokenLedger, id: u64) -> Result<&BridgeTransfer, LedgerError> {
+    ledger.bridge_transfers.get(&id).ok_or(LedgerError::UnknownBridgeTransfer(id))
+}
@@ -0,0 +1,103 @@
+//! Ledger history for diagnosing balance discrepancies.
+//!
+//! Every operation on a [`TokenLedger`] records one [`LedgerEvent`] per
+//! balance it changes, all at the same height. The height is the number of
+//! operations applied so far, so a ledger can be rewound to any height or
+//! point in time by undoing the later events.
+
+use super::{TokenLedger, NATIVE_ASSET};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The ledger operation that changed a balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerOp {
+    Mint,
+    Transfer,
+    Stake,
+    Unstake,
+    Slash,
+    Burn,
+    AdjustReputation,
+    Stream,
+    Bridge,
+    Swap,
+}
+
+impl fmt::Display for LedgerOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LedgerOp::Mint => "mint",
+            LedgerOp::Transfer => "transfer",
+            LedgerOp::Stake => "stake",
+            LedgerOp::Unstake => "unstake",
+            LedgerOp::Slash => "slash",
+            LedgerOp::Burn => "burn",
+            LedgerOp::AdjustReputation => "adjust-rep",
+            LedgerOp::Stream => "stream",
+            LedgerOp::Bridge => "bridge",
+            LedgerOp::Swap => "swap",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One balance change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEvent {
+    /// Ledger height of the operation, starting at 1.
+    pub height: u64,
+    /// Unix seconds when the operation was applied.
+    pub timestamp: i64,
+    pub op: LedgerOp,
+    /// Asset whose balance changed; events recorded before bridged assets
+    /// existed are native.
+    #[serde(default = "native_asset")]
+    pub asset: String,
+    pub account: String,
+    pub before: u64,
+    pub after: u64,
+}
+
+fn native_asset() -> String {
+    NATIVE_ASSET.to_string()
+}
+
+/// A point in the ledger's history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerPoint {
+    /// After the operation at this height.
+    Height(u64),
+    /// After every operation applied at or before these unix seconds.
+    Timestamp(i64),
+}
+
+impl LedgerPoint {
+    fn includes(&self, event: &LedgerEvent) -> bool {
+        match *self {
+            LedgerPoint::Height(h) => event.height <= h,
+            LedgerPoint::Timestamp(t) => event.timestamp <= t,
+        }
+    }
+}
+
+/// Reconstructs the ledger as it was at `point`. Balances from before the
+/// recorded history are kept as they are.
+pub fn ledger_at(ledger: &TokenLedger, point: LedgerPoint) -> TokenLedger {
+    let mut past = ledger.clone();
+    while past.events.last().is_some_and(|e| !point.includes(e)) {
+        let event = past.events.pop().expect("checked above");
+        let balances = past.balances_mut(&event.asset);
+        if event.before == 0 {
+            balances.remove(&event.account);
+        } else {
+            balances.insert(event.account, event.before);
+        }
+    }
+    past
+}
+
+/// Every recorded change to `account`'s balances, oldest first.
+pub fn history<'a>(ledger: &'a TokenLedger, account: &'a str) -> impl Iterator<Item = &'a LedgerEvent> {
+    ledger.events.iter().filter(move |e| e.account == account)
+}
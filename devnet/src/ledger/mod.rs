@@ -1,30 +1,156 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use thiserror::Error;
 
 pub mod actions;
+pub mod bridge;
+pub mod history;
+pub mod stream;
+pub mod swap;
 
 pub use actions::*;
+pub use bridge::{
+    advance_bridge, bridge_fee, bridge_transfer, deposit, parse_chain, withdraw, wrapped_asset, BridgeDirection,
+    BridgeRequest, BridgeTransfer,
+};
+pub use history::{history, ledger_at, LedgerEvent, LedgerOp, LedgerPoint};
+pub use stream::{cancel_stream, open_stream, settle_account, settle_stream, settle_streams, PaymentStream};
+pub use swap::{add_liquidity, pool_account, quote_swap, swap_to_native};
 
 pub const TREASURY: &str = "treasury";
 
+/// Asset id of the chain's own token. Its balances live in
+/// [`TokenLedger::balances`]; every other asset is bridged in.
+pub const NATIVE_ASSET: &str = "BCAI";
+
+/// What holders may do with an asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetRules {
+    /// Chain the asset is bridged from; `None` for the native token.
+    pub origin_chain: Option<String>,
+    pub transferable: bool,
+    /// Whether the asset can be staked. Only the native token secures the
+    /// network by default.
+    pub stakeable: bool,
+}
+
+impl AssetRules {
+    pub fn native() -> Self {
+        Self { origin_chain: None, transferable: true, stakeable: true }
+    }
+
+    /// Transferable but not stakeable.
+    pub fn bridged(origin_chain: &str) -> Self {
+        Self { origin_chain: Some(origin_chain.to_string()), transferable: true, stakeable: false }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum LedgerError {
     #[error("Insufficient balance")]
     InsufficientBalance,
     #[error("Account not found: {0}")]
     AccountNotFound(String),
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+    #[error("Asset already registered: {0}")]
+    AssetExists(String),
+    #[error("Asset {0} cannot be transferred")]
+    NotTransferable(String),
+    #[error("Asset {0} cannot be staked")]
+    NotStakeable(String),
+    #[error("Unknown payment stream: {0}")]
+    UnknownStream(u64),
+    #[error("Invalid payment stream: {0}")]
+    InvalidStream(String),
+    #[error("Unknown bridge transfer: {0}")]
+    UnknownBridgeTransfer(u64),
+    #[error("Bridge error: {0}")]
+    Bridge(#[from] runtime::cross_chain_bridge::BridgeError),
+    #[error("No liquidity pool for {0}")]
+    NoLiquidityPool(String),
+    #[error("Swap would pay {quoted}, less than the minimum {min_out}")]
+    SlippageExceeded { quoted: u64, min_out: u64 },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenLedger {
+    /// Native token balances.
     pub balances: HashMap<String, u64>,
+    /// Bridged asset balances, keyed by asset id then account.
+    #[serde(default)]
+    pub assets: HashMap<String, HashMap<String, u64>>,
+    /// Rules of every registered bridged asset.
+    #[serde(default)]
+    pub asset_rules: HashMap<String, AssetRules>,
+    /// Every balance change, see [`history`].
+    #[serde(default)]
+    pub events: Vec<LedgerEvent>,
+    /// Open payment streams by id, see [`stream`].
+    #[serde(default)]
+    pub streams: BTreeMap<u64, PaymentStream>,
+    /// Id of the last stream opened, so ids of cancelled streams are never
+    /// handed out again.
+    #[serde(default)]
+    pub last_stream_id: u64,
+    /// Bridge transfers by id, see [`bridge`].
+    #[serde(default)]
+    pub bridge_transfers: BTreeMap<u64, BridgeTransfer>,
+    /// Pools swapping bridged assets to the native token, by asset, see
+    /// [`swap`].
+    #[serde(default)]
+    pub liquidity_pools: BTreeMap<String, runtime::cross_chain_bridge::LiquidityPool>,
 }
 
 impl TokenLedger {
     pub fn new() -> Self {
         Self {
             balances: HashMap::new(),
+            assets: HashMap::new(),
+            asset_rules: HashMap::new(),
+            events: Vec::new(),
+            streams: BTreeMap::new(),
+            last_stream_id: 0,
+            bridge_transfers: BTreeMap::new(),
+            liquidity_pools: BTreeMap::new(),
+        }
+    }
+
+    /// Number of operations applied so far.
+    pub fn height(&self) -> u64 {
+        self.events.last().map_or(0, |e| e.height)
+    }
+
+    /// Sets the native balances changed by one operation, recording an
+    /// event for each at the next height.
+    pub fn apply(&mut self, op: LedgerOp, updates: &[(&str, u64)]) {
+        self.apply_asset(op, NATIVE_ASSET, updates);
+    }
+
+    /// [`apply`](Self::apply) for balances of `asset`.
+    pub fn apply_asset(&mut self, op: LedgerOp, asset: &str, updates: &[(&str, u64)]) {
+        let height = self.height() + 1;
+        let timestamp = chrono::Utc::now().timestamp();
+        for &(account, after) in updates {
+            let before = self.balances_mut(asset).insert(account.to_string(), after).unwrap_or(0);
+            self.events.push(LedgerEvent {
+                height,
+                timestamp,
+                op,
+                asset: asset.to_string(),
+                account: account.to_string(),
+                before,
+                after,
+            });
+        }
+    }
+
+    /// Balances of `asset` by account.
+    pub fn balances_mut(&mut self, asset: &str) -> &mut HashMap<String, u64> {
+        if asset == NATIVE_ASSET {
+            &mut self.balances
+        } else {
+            self.assets.entry(asset.to_string()).or_default()
         }
     }
 
@@ -32,14 +158,46 @@ impl TokenLedger {
         self.balances.get(account).copied().unwrap_or(0)
     }
 
+    pub fn asset_balance(&self, asset: &str, account: &str) -> u64 {
+        if asset == NATIVE_ASSET {
+            return self.balance(account);
+        }
+        self.assets.get(asset).and_then(|b| b.get(account)).copied().unwrap_or(0)
+    }
+
+    /// Registers a bridged asset. The native asset is always registered.
+    pub fn register_asset(&mut self, asset: &str, rules: AssetRules) -> Result<(), LedgerError> {
+        if asset == NATIVE_ASSET || self.asset_rules.contains_key(asset) {
+            return Err(LedgerError::AssetExists(asset.to_string()));
+        }
+        self.asset_rules.insert(asset.to_string(), rules);
+        Ok(())
+    }
+
+    pub fn rules(&self, asset: &str) -> Result<AssetRules, LedgerError> {
+        if asset == NATIVE_ASSET {
+            return Ok(AssetRules::native());
+        }
+        self.asset_rules
+            .get(asset)
+            .cloned()
+            .ok_or_else(|| LedgerError::UnknownAsset(asset.to_string()))
+    }
+
     pub fn transfer(&mut self, from: &str, to: &str, amount: u64) -> Result<(), LedgerError> {
-        if self.balances.get(from).copied().unwrap_or(0) < amount {
+        self.transfer_asset(NATIVE_ASSET, from, to, amount)
+    }
+
+    pub fn transfer_asset(&mut self, asset: &str, from: &str, to: &str, amount: u64) -> Result<(), LedgerError> {
+        if !self.rules(asset)?.transferable {
+            return Err(LedgerError::NotTransferable(asset.to_string()));
+        }
+        if self.asset_balance(asset, from) < amount {
             return Err(LedgerError::InsufficientBalance);
         }
-        let from_balance = self.balances.get(from).copied().unwrap_or(0);
-        let to_balance = self.balances.get(to).copied().unwrap_or(0);
-        self.balances.insert(from.to_string(), from_balance - amount);
-        self.balances.insert(to.to_string(), to_balance + amount);
+        let from_balance = self.asset_balance(asset, from);
+        let to_balance = self.asset_balance(asset, to);
+        self.apply_asset(LedgerOp::Transfer, asset, &[(from, from_balance - amount), (to, to_balance + amount)]);
         Ok(())
     }
 } 
\ No newline at end of file
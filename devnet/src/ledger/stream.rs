@@ -0,0 +1,121 @@
+//! Payment streams: a fixed amount per hour from one account to another
+//! until the stream is cancelled.
+//!
+//! Nothing moves while time passes. A stream accrues by the second and is
+//! settled lazily, whenever one of its accounts touches the ledger or when
+//! [`settle_streams`] runs at the end of an epoch. If the payer cannot cover
+//! what has accrued, the shortfall stays owed and is paid by a later
+//! settlement.
+
+use super::{LedgerError, LedgerOp, TokenLedger};
+use serde::{Deserialize, Serialize};
+
+const SECS_PER_HOUR: u128 = 3_600;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentStream {
+    pub id: u64,
+    pub from: String,
+    pub to: String,
+    pub asset: String,
+    pub rate_per_hour: u64,
+    /// Unix seconds the stream started accruing.
+    pub start: i64,
+    /// Total paid to `to` so far.
+    pub paid: u64,
+}
+
+impl PaymentStream {
+    /// Total accrued from the start until `now`.
+    pub fn accrued(&self, now: i64) -> u64 {
+        let secs = now.saturating_sub(self.start).max(0) as u128;
+        (self.rate_per_hour as u128 * secs / SECS_PER_HOUR).min(u64::MAX as u128) as u64
+    }
+
+    /// Accrued but not yet paid as of `now`.
+    pub fn owed(&self, now: i64) -> u64 {
+        self.accrued(now).saturating_sub(self.paid)
+    }
+}
+
+/// Opens a stream of `rate_per_hour` of `asset` from `from` to `to`,
+/// accruing from `now`. Returns the stream id, never one used before.
+pub fn open_stream(
+    ledger: &mut TokenLedger,
+    from: &str,
+    to: &str,
+    asset: &str,
+    rate_per_hour: u64,
+    now: i64,
+) -> Result<u64, LedgerError> {
+    if from == to {
+        return Err(LedgerError::InvalidStream(format!("{} cannot stream to itself", from)));
+    }
+    if rate_per_hour == 0 {
+        return Err(LedgerError::InvalidStream("rate must be positive".into()));
+    }
+    if !ledger.rules(asset)?.transferable {
+        return Err(LedgerError::NotTransferable(asset.to_string()));
+    }
+    // Ledgers saved before the counter existed start after their last stream.
+    let last = ledger.streams.keys().next_back().map_or(0, |id| *id).max(ledger.last_stream_id);
+    let id = last + 1;
+    ledger.last_stream_id = id;
+    ledger.streams.insert(
+        id,
+        PaymentStream {
+            id,
+            from: from.to_string(),
+            to: to.to_string(),
+            asset: asset.to_string(),
+            rate_per_hour,
+            start: now,
+            paid: 0,
+        },
+    );
+    Ok(id)
+}
+
+/// Pays what stream `id` owes as of `now`, as far as the payer's balance
+/// allows. Returns the amount paid.
+pub fn settle_stream(ledger: &mut TokenLedger, id: u64, now: i64) -> Result<u64, LedgerError> {
+    let stream = ledger.streams.get(&id).ok_or(LedgerError::UnknownStream(id))?;
+    let amount = stream.owed(now).min(ledger.asset_balance(&stream.asset, &stream.from));
+    if amount == 0 {
+        return Ok(0);
+    }
+    let PaymentStream { from, to, asset, .. } = stream.clone();
+    let from_balance = ledger.asset_balance(&asset, &from);
+    let to_balance = ledger.asset_balance(&asset, &to);
+    ledger.apply_asset(LedgerOp::Stream, &asset, &[(&from, from_balance - amount), (&to, to_balance + amount)]);
+    if let Some(stream) = ledger.streams.get_mut(&id) {
+        stream.paid += amount;
+    }
+    Ok(amount)
+}
+
+/// Settles every stream paying from or to `account`, before the account's
+/// balance is read or changed.
+pub fn settle_account(ledger: &mut TokenLedger, account: &str, now: i64) -> u64 {
+    let ids: Vec<u64> = ledger
+        .streams
+        .values()
+        .filter(|s| s.from == account || s.to == account)
+        .map(|s| s.id)
+        .collect();
+    ids.into_iter().filter_map(|id| settle_stream(ledger, id, now).ok()).sum()
+}
+
+/// Settles every open stream, e.g. at the end of an epoch. Returns the total
+/// paid.
+pub fn settle_streams(ledger: &mut TokenLedger, now: i64) -> u64 {
+    let ids: Vec<u64> = ledger.streams.keys().copied().collect();
+    ids.into_iter().filter_map(|id| settle_stream(ledger, id, now).ok()).sum()
+}
+
+/// Settles stream `id` a final time and closes it. Returns the closed
+/// stream; anything the payer still could not cover is forgiven.
+pub fn cancel_stream(ledger: &mut TokenLedger, id: u64, now: i64) -> Result<PaymentStream, LedgerError> {
+    settle_stream(ledger, id, now)?;
+    ledger.streams.remove(&id).ok_or(LedgerError::UnknownStream(id))
+}
@@ -0,0 +1,110 @@
+//! Liquidity pools swapping bridged assets into the native token.
+//!
+//! Every bridged asset may have one constant-product pool against the
+//! native token. Its reserves are the balances of the pool's own account,
+//! [`pool_account`], so swaps and liquidity show up in the ledger history
+//! like any other operation; the [`LiquidityPool`] record keeps the pool's
+//! fee and a copy of its reserves for display.
+
+use super::{bridge::parse_chain, LedgerError, LedgerOp, TokenLedger, NATIVE_ASSET};
+use chrono::Utc;
+use runtime::cross_chain_bridge::{ChainId, LiquidityPool};
+use std::collections::HashMap;
+
+/// Fee of new pools, taken from the amount swapped in.
+pub const DEFAULT_POOL_FEE_RATE: f64 = 0.003;
+
+/// The account holding the reserves of `asset`'s pool.
+pub fn pool_account(asset: &str) -> String {
+    format!("pool:{}", asset)
+}
+
+/// Reserves of `asset`'s pool as `(asset, native)`.
+pub fn reserves(ledger: &TokenLedger, asset: &str) -> (u64, u64) {
+    let account = pool_account(asset);
+    (ledger.asset_balance(asset, &account), ledger.balance(&account))
+}
+
+fn sync_pool(ledger: &mut TokenLedger, asset: &str) {
+    let (asset_reserve, native_reserve) = reserves(ledger, asset);
+    if let Some(pool) = ledger.liquidity_pools.get_mut(asset) {
+        pool.token_reserves =
+            HashMap::from([(asset.to_string(), asset_reserve), (NATIVE_ASSET.to_string(), native_reserve)]);
+        pool.total_locked = asset_reserve;
+        pool.last_updated = Utc::now();
+    }
+}
+
+/// Moves `amount` of `asset` and `native` of the native token from
+/// `provider` into `asset`'s pool, opening it if needed.
+pub fn add_liquidity(
+    ledger: &mut TokenLedger,
+    provider: &str,
+    asset: &str,
+    amount: u64,
+    native: u64,
+) -> Result<(), LedgerError> {
+    let rules = ledger.rules(asset)?;
+    if asset == NATIVE_ASSET {
+        return Err(LedgerError::NoLiquidityPool(asset.to_string()));
+    }
+    let (asset_balance, native_balance) = (ledger.asset_balance(asset, provider), ledger.balance(provider));
+    if asset_balance < amount || native_balance < native {
+        return Err(LedgerError::InsufficientBalance);
+    }
+    let account = pool_account(asset);
+    let (asset_reserve, native_reserve) = reserves(ledger, asset);
+    ledger.apply_asset(LedgerOp::Swap, asset, &[(provider, asset_balance - amount), (&account, asset_reserve + amount)]);
+    ledger.apply(LedgerOp::Swap, &[(provider, native_balance - native), (&account, native_reserve + native)]);
+    if !ledger.liquidity_pools.contains_key(asset) {
+        let chain_id = rules.origin_chain.as_deref().and_then(|c| parse_chain(c).ok()).unwrap_or(ChainId::BCAI);
+        let pool = LiquidityPool {
+            chain_id,
+            token_reserves: HashMap::new(),
+            total_locked: 0,
+            total_minted: 0,
+            utilization_rate: 0.0,
+            fee_rate: DEFAULT_POOL_FEE_RATE,
+            last_updated: Utc::now(),
+        };
+        ledger.liquidity_pools.insert(asset.to_string(), pool);
+    }
+    sync_pool(ledger, asset);
+    Ok(())
+}
+
+/// Native tokens swapping `amount` of `asset` in would pay out now.
+pub fn quote_swap(ledger: &TokenLedger, asset: &str, amount: u64) -> Result<u64, LedgerError> {
+    let pool = ledger.liquidity_pools.get(asset).ok_or_else(|| LedgerError::NoLiquidityPool(asset.to_string()))?;
+    let (asset_reserve, native_reserve) = reserves(ledger, asset);
+    let amount_in = (amount as f64 * (1.0 - pool.fee_rate)) as u128;
+    let out = native_reserve as u128 * amount_in / (asset_reserve as u128 + amount_in).max(1);
+    Ok(out as u64)
+}
+
+/// Swaps `amount` of `account`'s `asset` into the native token through the
+/// asset's pool, failing if it would pay out less than `min_out`. Returns
+/// the native tokens received.
+pub fn swap_to_native(
+    ledger: &mut TokenLedger,
+    account: &str,
+    asset: &str,
+    amount: u64,
+    min_out: u64,
+) -> Result<u64, LedgerError> {
+    let out = quote_swap(ledger, asset, amount)?;
+    if out < min_out {
+        return Err(LedgerError::SlippageExceeded { quoted: out, min_out });
+    }
+    let balance = ledger.asset_balance(asset, account);
+    if balance < amount {
+        return Err(LedgerError::InsufficientBalance);
+    }
+    let pool = pool_account(asset);
+    let (asset_reserve, native_reserve) = reserves(ledger, asset);
+    let native_balance = ledger.balance(account);
+    ledger.apply_asset(LedgerOp::Swap, asset, &[(account, balance - amount), (&pool, asset_reserve + amount)]);
+    ledger.apply(LedgerOp::Swap, &[(account, native_balance + out), (&pool, native_reserve - out)]);
+    sync_pool(ledger, asset);
+    Ok(out)
+}
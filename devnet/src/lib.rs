@@ -10,4 +10,5 @@ pub mod persistence;
 pub mod commands;
 pub mod app;
 pub mod training;
+pub mod simulation;
 pub mod ops;
\ No newline at end of file
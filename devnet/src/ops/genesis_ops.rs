@@ -0,0 +1,33 @@
+use crate::commands::GenesisCommands;
+use crate::error::DevnetError;
+use runtime::blockchain::{GenesisConfig, GenesisValidator, StoragePricing};
+
+pub fn handle_genesis_command(cmd: GenesisCommands) -> Result<(), DevnetError> {
+    match cmd {
+        GenesisCommands::Generate { output, chain_id, timestamp, balances, validators, price_per_gb } => {
+            let defaults = GenesisConfig::default();
+            let genesis = GenesisConfig {
+                chain_id,
+                timestamp: timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp()),
+                initial_balances: if balances.is_empty() {
+                    defaults.initial_balances
+                } else {
+                    balances.into_iter().collect()
+                },
+                validators: validators
+                    .into_iter()
+                    .map(|(pubkey, stake)| GenesisValidator { pubkey, stake })
+                    .collect(),
+                storage_pricing: StoragePricing {
+                    price_per_gb_bcai: price_per_gb.unwrap_or(defaults.storage_pricing.price_per_gb_bcai),
+                    ..defaults.storage_pricing
+                },
+                ..GenesisConfig::default()
+            };
+            genesis.validate()?;
+            genesis.save(&output)?;
+            println!("wrote {} (chain {}, hash {})", output.display(), genesis.chain_id, genesis.hash());
+        }
+    }
+    Ok(())
+}
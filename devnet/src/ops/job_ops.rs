@@ -1,44 +1,45 @@
 use crate::error::DevnetError;
 use crate::job::{assign_job, complete_job, post_job};
-use crate::persistence::{load_jobs, save_jobs, load_ledger, save_ledger};
+use crate::persistence::Persistence;
 
-pub fn handle_job_command(cmd: crate::commands::JobCommands) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
-    let mut jobs = load_jobs()?;
+pub fn handle_job_command(store: &dyn Persistence, cmd: crate::commands::JobCommands) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    let mut jobs = store.load_jobs()?;
 
     match cmd {
-        crate::commands::JobCommands::Post { poster, description, reward } => {
-            if let Err(e) = post_job(&mut jobs, &mut ledger, &poster, description, reward) {
+        crate::commands::JobCommands::Post { poster, description, reward, asset } => {
+            if let Err(e) = post_job(&mut jobs, &mut ledger, &poster, description, reward, &asset) {
                 println!("{e}");
             } else if let Some(j) = jobs.last() {
                 println!("posted job #{}", j.id);
             }
         }
         crate::commands::JobCommands::Assign { job_id, worker } => {
-            if let Err(e) = assign_job(&mut jobs, job_id, &worker) {
+            if let Err(e) = assign_job(&mut jobs, &job_id, &worker) {
                 println!("{e}");
             }
         }
-        crate::commands::JobCommands::Complete { job_id } => {
-            if let Err(e) = complete_job(&mut jobs, &mut ledger, job_id) {
-                println!("{e}");
+        crate::commands::JobCommands::Complete { job_id, worker, swap_min_out } => {
+            match complete_job(&mut jobs, &mut ledger, &job_id, &worker, swap_min_out) {
+                Ok(paid) => println!("paid {} {} to {}", paid.amount, paid.asset, worker),
+                Err(e) => println!("{e}"),
             }
         }
         crate::commands::JobCommands::List => {
             for job in &jobs {
                 println!(
-                    "#{:<3} {:<20} reward:{:<5} assigned:{:<10} completed:{}",
+                    "#{:<6} {:<20} reward:{:<5} {:<6} completed:{}",
                     job.id,
-                    job.description,
+                    String::from_utf8_lossy(&job.data),
                     job.reward,
-                    job.assigned_to.as_deref().unwrap_or("-"),
-                    job.completed
+                    job.asset,
+                    job.reward == 0
                 );
             }
         }
     }
 
-    save_jobs(&jobs)?;
-    save_ledger(&ledger)?;
+    store.save_jobs(&jobs)?;
+    store.save_ledger(&ledger)?;
     Ok(())
 } 
\ No newline at end of file
@@ -1,61 +1,244 @@
-use crate::ledger::{actions as ledger_actions, TokenLedger};
-use crate::persistence::{load_ledger, save_ledger};
+use crate::commands::{BridgeCommands, LedgerCommands, StreamCommands};
+use crate::ledger::{
+    self, actions as ledger_actions, AssetRules, BridgeRequest, BridgeTransfer, LedgerError, LedgerPoint, TokenLedger,
+    NATIVE_ASSET,
+};
+use crate::persistence::Persistence;
 use crate::error::DevnetError;
+use runtime::cross_chain_bridge::BridgeConfig;
 
-pub fn init_ledger() -> Result<(), DevnetError> {
-    save_ledger(&TokenLedger::new())
+/// Streams are settled lazily, so every operation on an account first pays
+/// out the streams it takes part in.
+fn settle(ledger: &mut TokenLedger, accounts: &[&str]) {
+    let now = chrono::Utc::now().timestamp();
+    for account in accounts {
+        ledger::settle_account(ledger, account, now);
+    }
 }
 
-pub fn mint(account: &str, amount: u64) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
-    ledger_actions::mint(&mut ledger, account, amount);
-    save_ledger(&ledger)
+pub fn init_ledger(store: &dyn Persistence) -> Result<(), DevnetError> {
+    store.save_ledger(&TokenLedger::new())
 }
 
-pub fn transfer(from: &str, to: &str, amount: u64) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
-    ledger_actions::transfer(&mut ledger, from, to, amount)?;
-    save_ledger(&ledger)
+pub fn mint(store: &dyn Persistence, asset: &str, account: &str, amount: u64) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    if asset == NATIVE_ASSET {
+        ledger_actions::mint(&mut ledger, account, amount);
+    } else {
+        ledger_actions::mint_asset(&mut ledger, asset, account, amount)?;
+    }
+    store.save_ledger(&ledger)
 }
 
-pub fn stake(account: &str, amount: u64) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
-    let _ = ledger_actions::stake(&mut ledger, account, amount);
-    save_ledger(&ledger)
+pub fn transfer(store: &dyn Persistence, asset: &str, from: &str, to: &str, amount: u64) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    settle(&mut ledger, &[from, to]);
+    ledger.transfer_asset(asset, from, to, amount)?;
+    store.save_ledger(&ledger)
 }
 
-pub fn unstake(account: &str, amount: u64) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
-    let _ = ledger_actions::unstake(&mut ledger, account, amount);
-    save_ledger(&ledger)
+pub fn stake(store: &dyn Persistence, asset: &str, account: &str, amount: u64) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    settle(&mut ledger, &[account]);
+    ledger_actions::stake_asset(&mut ledger, asset, account, amount)?;
+    store.save_ledger(&ledger)
 }
 
-pub fn slash(account: &str, amount: u64) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
+pub fn unstake(store: &dyn Persistence, asset: &str, account: &str, amount: u64) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    settle(&mut ledger, &[account]);
+    ledger_actions::unstake_asset(&mut ledger, asset, account, amount)?;
+    store.save_ledger(&ledger)
+}
+
+pub fn slash(store: &dyn Persistence, account: &str, amount: u64) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    settle(&mut ledger, &[account]);
     let _ = ledger_actions::slash(&mut ledger, account, amount);
-    save_ledger(&ledger)
+    store.save_ledger(&ledger)
+}
+
+pub fn burn(store: &dyn Persistence, asset: &str, account: &str, amount: u64) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    settle(&mut ledger, &[account]);
+    ledger_actions::burn_asset(&mut ledger, asset, account, amount)?;
+    store.save_ledger(&ledger)
 }
 
-pub fn burn(account: &str, amount: u64) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
-    let _ = ledger_actions::burn(&mut ledger, account, amount);
-    save_ledger(&ledger)
+pub fn balance(store: &dyn Persistence, asset: &str, account: &str) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    ledger.rules(asset)?;
+    settle(&mut ledger, &[account]);
+    println!("balance: {} {}", ledger.asset_balance(asset, account), asset);
+    store.save_ledger(&ledger)
 }
 
-pub fn balance(account: &str) -> Result<(), DevnetError> {
-    let ledger = load_ledger()?;
-    println!("balance: {} staked: {}", ledger.balance(account), ledger.balance(account));
+pub fn register_asset(store: &dyn Persistence, asset: &str, rules: AssetRules) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    ledger.register_asset(asset, rules)?;
+    store.save_ledger(&ledger)
+}
+
+pub fn assets(store: &dyn Persistence) -> Result<(), DevnetError> {
+    let ledger = store.load_ledger()?;
+    let mut assets: Vec<(&String, &AssetRules)> = ledger.asset_rules.iter().collect();
+    assets.sort_by_key(|(id, _)| *id);
+    println!("{:<10} origin:{:<12} transferable:true stakeable:true", NATIVE_ASSET, "-");
+    for (id, rules) in assets {
+        println!(
+            "{:<10} origin:{:<12} transferable:{} stakeable:{}",
+            id,
+            rules.origin_chain.as_deref().unwrap_or("-"),
+            rules.transferable,
+            rules.stakeable
+        );
+    }
     Ok(())
 }
 
-pub fn reputation(account: &str) -> Result<(), DevnetError> {
-    let ledger = load_ledger()?;
+pub fn reputation(store: &dyn Persistence, account: &str) -> Result<(), DevnetError> {
+    let ledger = store.load_ledger()?;
     println!("reputation: {}", ledger_actions::reputation(&ledger, account));
     Ok(())
 }
 
-pub fn adjust_reputation(account: &str, delta: i32) -> Result<(), DevnetError> {
-    let mut ledger = load_ledger()?;
+pub fn adjust_reputation(store: &dyn Persistence, account: &str, delta: i32) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
     ledger_actions::adjust_reputation(&mut ledger, account, delta);
-    save_ledger(&ledger)
-} 
\ No newline at end of file
+    store.save_ledger(&ledger)
+}
+
+pub fn handle_ledger_command(store: &dyn Persistence, cmd: LedgerCommands) -> Result<(), DevnetError> {
+    let ledger = store.load_ledger()?;
+    match cmd {
+        LedgerCommands::History { account } => {
+            for event in ledger::history(&ledger, &account) {
+                let time = chrono::DateTime::from_timestamp(event.timestamp, 0)
+                    .map_or_else(|| event.timestamp.to_string(), |t| t.to_rfc3339());
+                println!(
+                    "#{:<5} {} {:<10} {:<8} {:>12} -> {:<12} ({:+})",
+                    event.height,
+                    time,
+                    event.op,
+                    event.asset,
+                    event.before,
+                    event.after,
+                    event.after as i128 - event.before as i128
+                );
+            }
+            println!("balance: {}", ledger.balance(&account));
+        }
+        LedgerCommands::At { account, asset, height, timestamp } => {
+            let point = match (height, timestamp) {
+                (Some(h), _) => LedgerPoint::Height(h),
+                (None, Some(t)) => LedgerPoint::Timestamp(t),
+                (None, None) => LedgerPoint::Height(ledger.height()),
+            };
+            let past = ledger::ledger_at(&ledger, point);
+            println!("height: {} balance: {} {}", past.height(), past.asset_balance(&asset, &account), asset);
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_stream_command(store: &dyn Persistence, cmd: StreamCommands) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    let now = chrono::Utc::now().timestamp();
+    match cmd {
+        StreamCommands::Open { from, to, rate_per_hour, asset } => {
+            let id = ledger::open_stream(&mut ledger, &from, &to, &asset, rate_per_hour, now)?;
+            println!("stream {} opened: {} {}/h from {} to {}", id, rate_per_hour, asset, from, to);
+        }
+        StreamCommands::Cancel { id } => {
+            let stream = ledger::cancel_stream(&mut ledger, id, now)?;
+            println!("stream {} cancelled after paying {} {}", id, stream.paid, stream.asset);
+        }
+        StreamCommands::Settle => {
+            let paid = ledger::settle_streams(&mut ledger, now);
+            println!("settled {} streams, paid {}", ledger.streams.len(), paid);
+        }
+        StreamCommands::List => {
+            for stream in ledger.streams.values() {
+                println!(
+                    "#{:<4} {} -> {} {}/h {:<8} paid:{} owed:{}",
+                    stream.id,
+                    stream.from,
+                    stream.to,
+                    stream.rate_per_hour,
+                    stream.asset,
+                    stream.paid,
+                    stream.owed(now)
+                );
+            }
+            return Ok(());
+        }
+    }
+    store.save_ledger(&ledger)
+}
+
+fn print_bridge_transfer(transfer: &BridgeTransfer) {
+    let tx = &transfer.transaction;
+    println!(
+        "#{:<4} {:?} {} {} {} {} -> {} confirmations:{}/{} ({} pending) tx:{:?} message:{:?}",
+        transfer.id,
+        transfer.direction,
+        transfer.account,
+        tx.amount,
+        transfer.asset,
+        tx.source_chain.name(),
+        tx.destination_chain.name(),
+        tx.confirmations,
+        tx.required_confirmations,
+        transfer.pending_confirmations(),
+        tx.status,
+        transfer.message.status
+    );
+}
+
+/// Bridge transfers advance lazily, so every bridge command first counts
+/// the confirmations accrued since the last one.
+pub fn handle_bridge_command(store: &dyn Persistence, cmd: BridgeCommands) -> Result<(), DevnetError> {
+    let mut ledger = store.load_ledger()?;
+    let now = chrono::Utc::now().timestamp();
+    let config = BridgeConfig::default();
+    for id in ledger::advance_bridge(&mut ledger, now) {
+        println!("bridge transfer {} executed", id);
+    }
+    match cmd {
+        BridgeCommands::Deposit { account, amount, chain, from_address, asset } => {
+            let chain = ledger::parse_chain(&chain).map_err(LedgerError::from)?;
+            let asset = asset.unwrap_or_else(|| ledger::wrapped_asset(chain));
+            let request =
+                BridgeRequest { chain, external_address: &from_address, account: &account, asset: &asset, amount };
+            let id = ledger::deposit(&mut ledger, &config, &request, now)?;
+            print_bridge_transfer(ledger::bridge_transfer(&ledger, id)?);
+        }
+        BridgeCommands::Withdraw { account, amount, chain, to_address, asset } => {
+            settle(&mut ledger, &[&account]);
+            let chain = ledger::parse_chain(&chain).map_err(LedgerError::from)?;
+            let asset = asset.unwrap_or_else(|| ledger::wrapped_asset(chain));
+            let request =
+                BridgeRequest { chain, external_address: &to_address, account: &account, asset: &asset, amount };
+            let id = ledger::withdraw(&mut ledger, &config, &request, now)?;
+            print_bridge_transfer(ledger::bridge_transfer(&ledger, id)?);
+        }
+        BridgeCommands::Status { id: Some(id) } => print_bridge_transfer(ledger::bridge_transfer(&ledger, id)?),
+        BridgeCommands::Status { id: None } => {
+            for transfer in ledger.bridge_transfers.values().filter(|t| t.is_pending()) {
+                print_bridge_transfer(transfer);
+            }
+        }
+        BridgeCommands::AddLiquidity { provider, asset, amount, native } => {
+            settle(&mut ledger, &[&provider]);
+            ledger::add_liquidity(&mut ledger, &provider, &asset, amount, native)?;
+            let (asset_reserve, native_reserve) = ledger::swap::reserves(&ledger, &asset);
+            println!("pool {}: {} {} / {} {}", asset, asset_reserve, asset, native_reserve, NATIVE_ASSET);
+        }
+        BridgeCommands::Swap { account, asset, amount, min_out } => {
+            settle(&mut ledger, &[&account]);
+            let out = ledger::swap_to_native(&mut ledger, &account, &asset, amount, min_out)?;
+            println!("swapped {} {} for {} {}", amount, asset, out, NATIVE_ASSET);
+        }
+    }
+    store.save_ledger(&ledger)
+}
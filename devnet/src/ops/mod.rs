@@ -1,3 +1,5 @@
 pub mod ledger_ops;
 pub mod system_ops;
-pub mod job_ops; 
\ No newline at end of file
+pub mod job_ops;
+pub mod genesis_ops;
+pub mod sim_ops;
@@ -0,0 +1,21 @@
+use crate::commands::SimulateCommands;
+use crate::error::DevnetError;
+use crate::simulation::{self, SimConfig};
+
+pub fn handle_simulate_command(cmd: SimulateCommands) -> Result<(), DevnetError> {
+    match cmd {
+        SimulateCommands::Economy { config, output } => {
+            let sim = SimConfig::load(&config)?;
+            let trajectory = simulation::run(&sim)?;
+            let csv = simulation::to_csv(&trajectory);
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, csv)?;
+                    println!("wrote {} samples over {} blocks to {}", trajectory.len(), sim.blocks, path.display());
+                }
+                None => print!("{}", csv),
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::error::DevnetError;
 use runtime::gpu;
+use runtime::pouw::RetargetConfig;
 use crate::training;
 
 pub fn mine() -> Result<(), DevnetError> {
@@ -11,7 +12,8 @@ pub fn mine() -> Result<(), DevnetError> {
     Ok(())
 }
 
-pub fn train_pouw(size: usize, seed: u64, difficulty: u32) -> Result<(), DevnetError> {
+pub fn train_pouw(size: usize, seed: u64, difficulty: Option<u32>) -> Result<(), DevnetError> {
+    let difficulty = difficulty.unwrap_or_else(|| RetargetConfig::default().initial.difficulty);
     if training::train_and_verify(size, seed, difficulty) {
         println!("training succeeded");
     } else {
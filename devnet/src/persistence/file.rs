@@ -0,0 +1,67 @@
+use super::{Persistence, JOBS_FILE, LEDGER_FILE};
+use crate::error::DevnetError;
+use crate::job::Job;
+use crate::ledger::TokenLedger;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LedgerWrapper {
+    ledger: TokenLedger,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JobsWrapper {
+    jobs: Vec<Job>,
+}
+
+/// Pretty-printed JSON files, one for the ledger and one for the jobs.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self { dir: dir.as_ref().to_path_buf() }
+    }
+}
+
+impl Persistence for FileStore {
+    fn load_ledger(&self) -> Result<TokenLedger, DevnetError> {
+        let path = self.dir.join(LEDGER_FILE);
+        if !path.exists() {
+            return Ok(TokenLedger::new());
+        }
+        let data = fs::read_to_string(path)?;
+        let wrapper: LedgerWrapper = serde_json::from_str(&data)?;
+        Ok(wrapper.ledger)
+    }
+
+    fn save_ledger(&self, ledger: &TokenLedger) -> Result<(), DevnetError> {
+        let data = serde_json::to_string_pretty(&LedgerWrapper {
+            ledger: ledger.clone(),
+        })?;
+        fs::write(self.dir.join(LEDGER_FILE), data)?;
+        Ok(())
+    }
+
+    fn load_jobs(&self) -> Result<Vec<Job>, DevnetError> {
+        let path = self.dir.join(JOBS_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(path)?;
+        let wrapper: JobsWrapper = serde_json::from_str(&data)?;
+        Ok(wrapper.jobs)
+    }
+
+    fn save_jobs(&self, jobs: &[Job]) -> Result<(), DevnetError> {
+        let data = serde_json::to_string_pretty(&JobsWrapper {
+            jobs: jobs.to_vec(),
+        })?;
+        fs::write(self.dir.join(JOBS_FILE), data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use super::Persistence;
+use crate::error::DevnetError;
+use crate::job::Job;
+use crate::ledger::TokenLedger;
+use std::sync::Mutex;
+
+/// Keeps state for the lifetime of the process only.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    ledger: Mutex<Option<TokenLedger>>,
+    jobs: Mutex<Vec<Job>>,
+}
+
+impl Persistence for MemoryStore {
+    fn load_ledger(&self) -> Result<TokenLedger, DevnetError> {
+        let ledger = self.ledger.lock().expect("ledger lock poisoned");
+        Ok(ledger.clone().unwrap_or_else(TokenLedger::new))
+    }
+
+    fn save_ledger(&self, ledger: &TokenLedger) -> Result<(), DevnetError> {
+        *self.ledger.lock().expect("ledger lock poisoned") = Some(ledger.clone());
+        Ok(())
+    }
+
+    fn load_jobs(&self) -> Result<Vec<Job>, DevnetError> {
+        Ok(self.jobs.lock().expect("jobs lock poisoned").clone())
+    }
+
+    fn save_jobs(&self, jobs: &[Job]) -> Result<(), DevnetError> {
+        *self.jobs.lock().expect("jobs lock poisoned") = jobs.to_vec();
+        Ok(())
+    }
+}
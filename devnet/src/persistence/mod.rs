@@ -0,0 +1,77 @@
+//! Storage for the devnet ledger and job list.
+//!
+//! Commands go through the [`Persistence`] trait, whose backend is chosen by
+//! [`Profile`]: tests keep state in memory, the devnet writes JSON files to
+//! the working directory and long-lived nodes use an embedded sled database.
+
+mod file;
+mod memory;
+mod sled_store;
+
+pub use file::FileStore;
+pub use memory::MemoryStore;
+pub use sled_store::SledStore;
+
+use crate::error::DevnetError;
+use crate::job::Job;
+use crate::ledger::TokenLedger;
+use std::path::PathBuf;
+
+pub const LEDGER_FILE: &str = "ledger.json";
+pub const JOBS_FILE: &str = "jobs.json";
+pub const SLED_DIR: &str = "devnet.sled";
+
+/// Loads and saves the devnet's ledger and jobs. Loading from an empty store
+/// yields an empty ledger and no jobs.
+pub trait Persistence: Send + Sync {
+    fn load_ledger(&self) -> Result<TokenLedger, DevnetError>;
+    fn save_ledger(&self, ledger: &TokenLedger) -> Result<(), DevnetError>;
+    fn load_jobs(&self) -> Result<Vec<Job>, DevnetError>;
+    fn save_jobs(&self, jobs: &[Job]) -> Result<(), DevnetError>;
+}
+
+/// Where a [`Persistence`] keeps its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    Memory,
+    /// JSON files in `dir`.
+    File { dir: PathBuf },
+    /// A sled database at `path`.
+    Sled { path: PathBuf },
+}
+
+impl Backend {
+    pub fn open(&self) -> Result<Box<dyn Persistence>, DevnetError> {
+        Ok(match self {
+            Backend::Memory => Box::new(MemoryStore::default()),
+            Backend::File { dir } => Box::new(FileStore::new(dir)),
+            Backend::Sled { path } => Box::new(SledStore::open(path)?),
+        })
+    }
+}
+
+/// How the devnet is being run, which decides its storage backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Profile {
+    /// Throwaway state for tests.
+    Test,
+    /// JSON files in the working directory.
+    #[default]
+    Devnet,
+    /// A long-lived node backed by an embedded database.
+    Node,
+}
+
+impl Profile {
+    pub fn backend(self) -> Backend {
+        match self {
+            Profile::Test => Backend::Memory,
+            Profile::Devnet => Backend::File { dir: PathBuf::from(".") },
+            Profile::Node => Backend::Sled { path: PathBuf::from(SLED_DIR) },
+        }
+    }
+
+    pub fn open(self) -> Result<Box<dyn Persistence>, DevnetError> {
+        self.backend().open()
+    }
+}
@@ -0,0 +1,54 @@
+use super::Persistence;
+use crate::error::DevnetError;
+use crate::job::Job;
+use crate::ledger::TokenLedger;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+
+const LEDGER_KEY: &[u8] = b"ledger";
+const JOBS_KEY: &[u8] = b"jobs";
+
+/// An embedded sled database holding the ledger and jobs as JSON values.
+/// Every save is flushed before it returns.
+#[derive(Debug, Clone)]
+pub struct SledStore {
+    db: sled::Db,
+}
+
+impl SledStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DevnetError> {
+        Ok(Self { db: sled::open(path)? })
+    }
+
+    fn get<T: DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>, DevnetError> {
+        match self.db.get(key)? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put<T: Serialize + ?Sized>(&self, key: &[u8], value: &T) -> Result<(), DevnetError> {
+        self.db.insert(key, serde_json::to_vec(value)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+impl Persistence for SledStore {
+    fn load_ledger(&self) -> Result<TokenLedger, DevnetError> {
+        Ok(self.get(LEDGER_KEY)?.unwrap_or_else(TokenLedger::new))
+    }
+
+    fn save_ledger(&self, ledger: &TokenLedger) -> Result<(), DevnetError> {
+        self.put(LEDGER_KEY, ledger)
+    }
+
+    fn load_jobs(&self) -> Result<Vec<Job>, DevnetError> {
+        Ok(self.get(JOBS_KEY)?.unwrap_or_default())
+    }
+
+    fn save_jobs(&self, jobs: &[Job]) -> Result<(), DevnetError> {
+        self.put(JOBS_KEY, jobs)
+    }
+}
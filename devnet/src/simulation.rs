@@ -0,0 +1,552 @@
+//! Discrete-event simulation of the token economy, for tuning reward, fee
+//! and slashing parameters before proposing them to governance.
+//!
+//! Job posters, PoUW workers and storage providers act block by block
+//! against a runtime [`State`]. The economics are the chain's own: jobs are
+//! escrowed by `PostJob` transactions, blocks are settled by
+//! [`BlockProcessor::apply_block`] (scoring, fee burn, rebates, escrow
+//! release), fraud is reverted by [`State::resolve_fraud_proof`] and
+//! storage outages are punished by [`State::slash_stake`]. Only behaviour is
+//! modelled here: who posts, who joins or leaves, and what a token is worth.
+//!
+//! The token price follows the equation of exchange: circulating supply
+//! times velocity equals the USD value of the jobs it pays for, so
+//! `price = demand_usd_per_block * holding_blocks / circulating`.
+
+use crate::error::DevnetError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use runtime::blockchain::block_processor::BlockProcessor;
+use runtime::blockchain::state::State;
+use runtime::blockchain::transaction::StorageTx;
+use runtime::blockchain::{Block, BlockchainConfig, BlockchainError, Transaction};
+use runtime::job::Job;
+use runtime::pouw::{PoUWTask, Solution};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Miner of blocks produced while no worker is active; its blocks settle
+/// transactions but no job.
+const IDLE_MINER: &str = "idle";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SimConfig {
+    /// Blocks to simulate.
+    pub blocks: u32,
+    pub seed: u64,
+    /// Blocks between trajectory samples.
+    pub sample_every: u32,
+    /// Blocks between participation updates.
+    pub adjust_every: u32,
+    /// Reward, fee burn and rebate parameters under study.
+    pub chain: BlockchainConfig,
+    pub market: MarketConfig,
+    pub posters: PosterConfig,
+    pub workers: WorkerConfig,
+    pub storage: StorageConfig,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            blocks: 1_000,
+            seed: 0,
+            sample_every: 10,
+            adjust_every: 50,
+            chain: BlockchainConfig::default(),
+            market: MarketConfig::default(),
+            posters: PosterConfig::default(),
+            workers: WorkerConfig::default(),
+            storage: StorageConfig::default(),
+        }
+    }
+}
+
+impl SimConfig {
+    /// Reads a TOML config; omitted fields take their defaults.
+    pub fn load(path: &Path) -> Result<Self, DevnetError> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| DevnetError::Config(format!("{}: {}", path.display(), e)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MarketConfig {
+    /// USD per token at genesis.
+    pub initial_price: f64,
+    /// Average number of blocks a token is held between payments.
+    pub holding_blocks: f64,
+    /// Blocks over which job demand is averaged when pricing.
+    pub demand_window: u32,
+    pub min_price: f64,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self { initial_price: 1.0, holding_blocks: 500.0, demand_window: 100, min_price: 0.0001 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PosterConfig {
+    pub count: u32,
+    /// Chance that a poster posts a job in a given block.
+    pub post_probability: f64,
+    /// USD a poster spends per job, escrowed reward plus storage.
+    pub budget_usd: f64,
+    /// Share of the budget paid to a storage provider for the job's data.
+    pub storage_share: f64,
+    /// Fee of each transaction a poster sends.
+    pub fee: u64,
+    /// Tokens each poster holds at genesis.
+    pub funding: u64,
+}
+
+impl Default for PosterConfig {
+    fn default() -> Self {
+        Self { count: 20, post_probability: 0.05, budget_usd: 20.0, storage_share: 0.2, fee: 2, funding: 5_000 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WorkerConfig {
+    pub initial: u32,
+    pub max: u32,
+    /// Running cost of one worker per block in USD.
+    pub cost_usd_per_block: f64,
+    /// Mean accuracy of submitted solutions in basis points.
+    pub accuracy: u32,
+    /// Solutions are drawn uniformly within this distance of `accuracy`.
+    pub accuracy_spread: u32,
+    /// Chance that a mined block is fraudulent; fraud is always proven.
+    pub fraud_rate: f64,
+    /// Share of the population that joins or leaves per update.
+    pub churn: f64,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            initial: 10,
+            max: 200,
+            cost_usd_per_block: 0.5,
+            accuracy: 8_500,
+            accuracy_spread: 1_000,
+            fraud_rate: 0.01,
+            churn: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub initial: u32,
+    pub max: u32,
+    /// Stake a provider locks to join.
+    pub stake: u64,
+    /// Providers whose stake is slashed below this leave.
+    pub min_stake: u64,
+    /// Slashed per outage.
+    pub slash: u64,
+    /// Chance that a provider has an outage in a given block.
+    pub failure_rate: f64,
+    pub cost_usd_per_block: f64,
+    pub churn: f64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            initial: 5,
+            max: 50,
+            stake: 500,
+            min_stake: 250,
+            slash: 50,
+            failure_rate: 0.002,
+            cost_usd_per_block: 0.1,
+            churn: 0.1,
+        }
+    }
+}
+
+/// Economy at the end of a block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Snapshot {
+    pub block: u32,
+    /// All tokens: balances, stakes and escrowed job rewards.
+    pub supply: u64,
+    pub circulating: u64,
+    pub staked: u64,
+    pub escrowed: u64,
+    /// USD per token.
+    pub price: f64,
+    pub workers: usize,
+    pub providers: usize,
+    /// Posters that posted since the previous sample.
+    pub active_posters: usize,
+    pub jobs_posted: u64,
+    pub jobs_pending: usize,
+    pub frauds: u64,
+    pub outages: u64,
+}
+
+impl Snapshot {
+    pub const CSV_HEADER: &'static str = "block,supply,circulating,staked,escrowed,price,workers,providers,\
+                                          active_posters,jobs_posted,jobs_pending,frauds,outages";
+
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{:.6},{},{},{},{},{},{},{}",
+            self.block,
+            self.supply,
+            self.circulating,
+            self.staked,
+            self.escrowed,
+            self.price,
+            self.workers,
+            self.providers,
+            self.active_posters,
+            self.jobs_posted,
+            self.jobs_pending,
+            self.frauds,
+            self.outages
+        )
+    }
+}
+
+/// Renders a trajectory as CSV with a header row.
+pub fn to_csv(trajectory: &[Snapshot]) -> String {
+    let mut csv = String::from(Snapshot::CSV_HEADER);
+    csv.push('\n');
+    for snapshot in trajectory {
+        csv.push_str(&snapshot.to_csv_row());
+        csv.push('\n');
+    }
+    csv
+}
+
+struct Simulation<'a> {
+    config: &'a SimConfig,
+    rng: StdRng,
+    state: State,
+    price: f64,
+    demand_usd: f64,
+    posters: Vec<String>,
+    workers: Vec<String>,
+    providers: Vec<String>,
+    next_agent: u64,
+    pending: VecDeque<Job>,
+    jobs_posted: u64,
+    frauds: u64,
+    outages: u64,
+    active_posters: std::collections::HashSet<String>,
+    /// Tokens earned since the last participation update.
+    worker_income: u64,
+    provider_income: u64,
+    prev_hash: String,
+}
+
+/// Runs the simulation and returns a snapshot every `sample_every` blocks
+/// and at the last block.
+pub fn run(config: &SimConfig) -> Result<Vec<Snapshot>, BlockchainError> {
+    let mut sim = Simulation::new(config)?;
+    let mut trajectory = vec![sim.snapshot(0)];
+    for block in 1..=config.blocks {
+        sim.step(block)?;
+        if block % config.sample_every.max(1) == 0 || block == config.blocks {
+            trajectory.push(sim.snapshot(block));
+            sim.active_posters.clear();
+        }
+    }
+    Ok(trajectory)
+}
+
+impl<'a> Simulation<'a> {
+    fn new(config: &'a SimConfig) -> Result<Self, BlockchainError> {
+        let mut sim = Self {
+            config,
+            rng: StdRng::seed_from_u64(config.seed),
+            state: State::new(),
+            price: config.market.initial_price,
+            demand_usd: 0.0,
+            posters: Vec::new(),
+            workers: Vec::new(),
+            providers: Vec::new(),
+            next_agent: 0,
+            pending: VecDeque::new(),
+            jobs_posted: 0,
+            frauds: 0,
+            outages: 0,
+            active_posters: Default::default(),
+            worker_income: 0,
+            provider_income: 0,
+            prev_hash: "0".repeat(64),
+        };
+        // The simulated chain's genesis carries the configured parameters.
+        sim.state.consensus = config.chain.consensus_params();
+        for _ in 0..config.posters.count {
+            let poster = sim.agent("poster");
+            sim.state.set_balance(&poster, config.posters.funding);
+            sim.posters.push(poster);
+        }
+        for _ in 0..config.workers.initial {
+            let worker = sim.agent("worker");
+            sim.workers.push(worker);
+        }
+        for _ in 0..config.storage.initial {
+            let provider = sim.agent("provider");
+            sim.state.set_balance(&provider, config.storage.stake);
+            sim.state.stake_tokens(&provider, config.storage.stake)?;
+            sim.providers.push(provider);
+        }
+        // Start demand where the genesis price clears the market.
+        sim.demand_usd = sim.price * sim.circulating() as f64 / config.market.holding_blocks;
+        Ok(sim)
+    }
+
+    fn agent(&mut self, role: &str) -> String {
+        self.next_agent += 1;
+        format!("{}-{}", role, self.next_agent)
+    }
+
+    fn step(&mut self, index: u32) -> Result<(), BlockchainError> {
+        let (transactions, demand_usd) = self.post_jobs();
+        self.mine(index, transactions)?;
+        self.storage_outages();
+
+        let alpha = 1.0 / self.config.market.demand_window.max(1) as f64;
+        self.demand_usd += alpha * (demand_usd - self.demand_usd);
+        let circulating = self.circulating().max(1) as f64;
+        self.price = (self.demand_usd * self.config.market.holding_blocks / circulating).max(self.config.market.min_price);
+
+        if index % self.config.adjust_every.max(1) == 0 {
+            self.adjust_participation()?;
+        }
+        Ok(())
+    }
+
+    /// Lets each poster post a job priced at the current token price,
+    /// buying tokens if it runs short. Returns the transactions and the USD
+    /// value posted.
+    fn post_jobs(&mut self) -> (Vec<Transaction>, f64) {
+        let config = self.config;
+        let cfg = &config.posters;
+        let mut transactions = Vec::new();
+        let mut demand_usd = 0.0;
+        for poster in self.posters.clone() {
+            if !self.rng.gen_bool(cfg.post_probability.clamp(0.0, 1.0)) {
+                continue;
+            }
+            let reward = ((cfg.budget_usd * (1.0 - cfg.storage_share) / self.price).ceil() as u64).max(1);
+            let storage = (cfg.budget_usd * cfg.storage_share / self.price).round() as u64;
+            let provider = match self.providers.len() {
+                0 => None,
+                n => Some(self.providers[self.rng.gen_range(0..n)].clone()),
+            }
+            .filter(|_| storage > 0);
+            let cost = reward + cfg.fee + provider.as_ref().map_or(0, |_| storage + cfg.fee);
+            let balance = self.state.get_balance(&poster);
+            if balance < cost && !self.buy(&poster, cost - balance) {
+                continue;
+            }
+
+            self.jobs_posted += 1;
+            let nonce = self.state.get_nonce(&poster);
+            let job = Job::new(0, "sim".into(), "sim".into(), 1).with_reward(reward).posted_by(&poster, nonce);
+            let mut post = Transaction::new(poster.clone(), String::new(), 0, cfg.fee, nonce);
+            post.storage = Some(StorageTx::PostJob { job: job.clone() });
+            transactions.push(post);
+            if let Some(provider) = provider {
+                transactions.push(Transaction::new(poster.clone(), provider, storage, cfg.fee, nonce + 1));
+                self.provider_income += storage;
+            }
+            self.pending.push_back(job);
+            self.active_posters.insert(poster);
+            demand_usd += cfg.budget_usd;
+        }
+        (transactions, demand_usd)
+    }
+
+    /// A random worker mines the oldest pending job and the block is
+    /// settled. A fraudulent block is proven at once.
+    fn mine(&mut self, index: u32, transactions: Vec<Transaction>) -> Result<(), BlockchainError> {
+        let config = self.config;
+        let cfg = &config.workers;
+        let miner = match self.workers.len() {
+            0 => None,
+            n => Some(self.rng.gen_range(0..n)),
+        };
+        let task = match miner.and_then(|_| self.pending.pop_front()) {
+            Some(job) => job.to_task(None),
+            None => PoUWTask::new_with_rng("sim".into(), "sim".into(), 1, &mut self.rng),
+        };
+        let low = cfg.accuracy.saturating_sub(cfg.accuracy_spread);
+        let high = cfg.accuracy.saturating_add(cfg.accuracy_spread).min(10_000).max(low);
+        let solution = Solution {
+            trained_model_hash: String::new(),
+            accuracy: if miner.is_some() { self.rng.gen_range(low..=high) } else { 0 },
+            nonce: 0,
+            computation_time_ms: 0,
+            training_time_ms: 0,
+            checkpoints: Vec::new(),
+            gradient_roots: Vec::new(),
+            zk_proof: None,
+        };
+        let name = miner.map_or(IDLE_MINER.to_string(), |i| self.workers[i].clone());
+        let block = Block::new(index, self.prev_hash.clone(), transactions, 0, name, task, solution);
+        BlockProcessor::apply_block(&block, &mut self.state)?;
+        self.prev_hash = block.hash;
+
+        if let Some(i) = miner {
+            if self.rng.gen_bool(cfg.fraud_rate.clamp(0.0, 1.0)) {
+                self.state.resolve_fraud_proof(index);
+                self.workers.swap_remove(i);
+                self.frauds += 1;
+            } else if let Some(claim) = self.state.pouw_claims.get(&index) {
+                self.worker_income += claim.reward;
+            }
+        }
+        Ok(())
+    }
+
+    fn storage_outages(&mut self) {
+        let config = self.config;
+        let cfg = &config.storage;
+        let mut i = 0;
+        while i < self.providers.len() {
+            let provider = &self.providers[i];
+            if self.rng.gen_bool(cfg.failure_rate.clamp(0.0, 1.0)) {
+                self.state.slash_stake(provider, cfg.slash);
+                self.outages += 1;
+                if self.state.stakes.get(provider).copied().unwrap_or(0) < cfg.min_stake {
+                    self.retire_provider(i);
+                    continue;
+                }
+            }
+            i += 1;
+        }
+    }
+
+    fn retire_provider(&mut self, i: usize) {
+        let provider = self.providers.swap_remove(i);
+        let stake = self.state.stakes.get(&provider).copied().unwrap_or(0);
+        let _ = self.state.unstake_tokens(&provider, stake);
+    }
+
+    /// Workers and providers join while their USD income over the last
+    /// period beats their running costs and leave while it does not.
+    fn adjust_participation(&mut self) -> Result<(), BlockchainError> {
+        let config = self.config;
+        let period = config.adjust_every.max(1) as f64;
+        let workers = &config.workers;
+        let profit = per_agent(self.worker_income, self.workers.len()) * self.price / period - workers.cost_usd_per_block;
+        match delta(self.workers.len(), workers.max, workers.churn, profit) {
+            d if d > 0 => {
+                for _ in 0..d {
+                    let worker = self.agent("worker");
+                    self.workers.push(worker);
+                }
+            }
+            d => {
+                let leaving = (-d) as usize;
+                self.workers.truncate(self.workers.len().saturating_sub(leaving));
+            }
+        }
+
+        let storage = &config.storage;
+        let profit =
+            per_agent(self.provider_income, self.providers.len()) * self.price / period - storage.cost_usd_per_block;
+        match delta(self.providers.len(), storage.max, storage.churn, profit) {
+            d if d > 0 => {
+                for _ in 0..d {
+                    let provider = self.agent("provider");
+                    if !self.buy(&provider, storage.stake) {
+                        break;
+                    }
+                    self.state.stake_tokens(&provider, storage.stake)?;
+                    self.providers.push(provider);
+                }
+            }
+            d => {
+                for _ in 0..(-d) as usize {
+                    if !self.providers.is_empty() {
+                        self.retire_provider(self.providers.len() - 1);
+                    }
+                }
+            }
+        }
+
+        self.worker_income = 0;
+        self.provider_income = 0;
+        Ok(())
+    }
+
+    /// Buys `amount` tokens off chain from the largest holder among workers
+    /// and providers, who sell their earnings to cover running costs.
+    fn buy(&mut self, buyer: &str, amount: u64) -> bool {
+        let seller = self
+            .workers
+            .iter()
+            .chain(&self.providers)
+            .filter(|s| s.as_str() != buyer)
+            .max_by_key(|s| self.state.get_balance(s))
+            .cloned();
+        match seller {
+            Some(seller) if self.state.get_balance(&seller) >= amount => {
+                *self.state.balances.get_mut(&seller).expect("seller has a balance") -= amount;
+                *self.state.balances.entry(buyer.to_string()).or_default() += amount;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn circulating(&self) -> u64 {
+        self.state.balances.values().sum()
+    }
+
+    fn snapshot(&self, block: u32) -> Snapshot {
+        let circulating = self.circulating();
+        let staked: u64 = self.state.stakes.values().sum();
+        let escrowed: u64 = self.state.job_escrows.values().map(|e| e.remaining()).sum();
+        Snapshot {
+            block,
+            supply: circulating + staked + escrowed,
+            circulating,
+            staked,
+            escrowed,
+            price: self.price,
+            workers: self.workers.len(),
+            providers: self.providers.len(),
+            active_posters: self.active_posters.len(),
+            jobs_posted: self.jobs_posted,
+            jobs_pending: self.pending.len(),
+            frauds: self.frauds,
+            outages: self.outages,
+        }
+    }
+}
+
+fn per_agent(income: u64, agents: usize) -> f64 {
+    income as f64 / agents.max(1) as f64
+}
+
+/// Agents joining (positive) or leaving (negative) a population of
+/// `current` given its per-agent `profit`: a `churn` share of it, at least
+/// one agent, within `0..=max`.
+fn delta(current: usize, max: u32, churn: f64, profit: f64) -> i64 {
+    let step = ((current as f64 * churn).ceil() as i64).max(1);
+    if profit > 0.0 {
+        step.min(max as i64 - current as i64).max(0)
+    } else if profit < 0.0 {
+        -step.min(current as i64)
+    } else {
+        0
+    }
+}
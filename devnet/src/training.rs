@@ -1,18 +1,19 @@
 use runtime::{
-    evaluator::Evaluator,
     mnist,
     neural_network::{generate_synthetic_data, NeuralNetwork, TrainingMetrics},
     pouw,
     trainer::Trainer,
 };
 
-/// Generate a PoUW task, train a solution and verify it.
+/// Generate a PoUW task, train a solution with the backend its model type
+/// selects and verify it.
 pub fn train_and_verify(size: usize, seed: u64, difficulty: u32) -> bool {
-    let task = pouw::generate_task(size, seed);
+    let task = pouw::generate_task(size as u32, seed);
     let trainer = Trainer::new("alice");
-    let solution = trainer.train(&task, difficulty);
-    let evaluator = Evaluator::new("bob");
-    evaluator.evaluate(&task, &solution, difficulty)
+    match trainer.registry().resolve(&task).and_then(|backend| backend.train(&task, difficulty)) {
+        Ok(solution) => task.verify(&solution, difficulty),
+        Err(_) => false,
+    }
 }
 
 /// Train a logistic regression model on the MNIST digits dataset.
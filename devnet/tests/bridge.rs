@@ -0,0 +1,73 @@
+use devnet::ledger::{
+    advance_bridge, bridge_fee, deposit, mint_asset, withdraw, wrapped_asset, BridgeRequest, LedgerError,
+    LedgerOp, TokenLedger, TREASURY,
+};
+use runtime::cross_chain_bridge::{BridgeConfig, BridgeTransactionStatus, ChainId, MessageStatus};
+
+const T0: i64 = 1_000_000;
+const ETH_BLOCK: i64 = 12;
+
+fn request(asset: &str, amount: u64) -> BridgeRequest<'_> {
+    BridgeRequest { chain: ChainId::Ethereum, external_address: "0xabc", account: "alice", asset, amount }
+}
+
+#[test]
+fn deposit_mints_once_confirmed() {
+    let mut ledger = TokenLedger::new();
+    let config = BridgeConfig::default();
+    let weth = wrapped_asset(ChainId::Ethereum);
+    let id = deposit(&mut ledger, &config, &request(&weth, 10_000), T0).unwrap();
+    assert_eq!(ledger.rules(&weth).unwrap().origin_chain.as_deref(), Some("Ethereum"));
+
+    // Half the confirmations: relayed but nothing minted yet.
+    assert!(advance_bridge(&mut ledger, T0 + 6 * ETH_BLOCK).is_empty());
+    let transfer = &ledger.bridge_transfers[&id];
+    assert_eq!((transfer.transaction.confirmations, transfer.pending_confirmations()), (6, 6));
+    assert_eq!(transfer.message.status, MessageStatus::Relayed);
+    assert_eq!(ledger.asset_balance(&weth, "alice"), 0);
+
+    assert_eq!(advance_bridge(&mut ledger, T0 + 12 * ETH_BLOCK), vec![id]);
+    let transfer = &ledger.bridge_transfers[&id];
+    assert_eq!(transfer.transaction.status, BridgeTransactionStatus::Executed);
+    assert_eq!(transfer.message.status, MessageStatus::Executed);
+    let fee = bridge_fee(&config, 10_000);
+    assert_eq!(fee, 10);
+    assert_eq!(ledger.asset_balance(&weth, "alice"), 10_000 - fee);
+    assert_eq!(ledger.asset_balance(&weth, TREASURY), fee);
+    assert_eq!(ledger.events.last().unwrap().op, LedgerOp::Bridge);
+
+    // Executed transfers are not minted twice.
+    assert!(advance_bridge(&mut ledger, T0 + 100 * ETH_BLOCK).is_empty());
+    assert_eq!(ledger.asset_balance(&weth, "alice"), 10_000 - fee);
+}
+
+#[test]
+fn withdraw_burns_right_away() {
+    let mut ledger = TokenLedger::new();
+    let config = BridgeConfig::default();
+    let weth = wrapped_asset(ChainId::Ethereum);
+    deposit(&mut ledger, &config, &request(&weth, 1), T0).unwrap();
+    mint_asset(&mut ledger, &weth, "alice", 5_000).unwrap();
+
+    let id = withdraw(&mut ledger, &config, &request(&weth, 2_000), T0).unwrap();
+    assert_eq!(ledger.asset_balance(&weth, "alice"), 3_000);
+    assert_eq!(ledger.asset_balance(&weth, TREASURY), 2);
+    assert!(ledger.bridge_transfers[&id].is_pending());
+
+    assert!(matches!(
+        withdraw(&mut ledger, &config, &request(&weth, 4_000), T0),
+        Err(LedgerError::InsufficientBalance)
+    ));
+}
+
+#[test]
+fn unsupported_chains_and_paused_bridges_are_refused() {
+    let mut ledger = TokenLedger::new();
+    let mut config = BridgeConfig::default();
+    let solana = BridgeRequest { chain: ChainId::Solana, ..request("wSOL", 10) };
+    assert!(matches!(deposit(&mut ledger, &config, &solana, T0), Err(LedgerError::Bridge(_))));
+
+    config.emergency_pause_enabled = true;
+    assert!(matches!(deposit(&mut ledger, &config, &request("wETH", 10), T0), Err(LedgerError::Bridge(_))));
+    assert!(ledger.bridge_transfers.is_empty());
+}
@@ -0,0 +1,75 @@
+use devnet::ledger::{
+    burn_asset, ledger_at, mint, mint_asset, stake, stake_asset, AssetRules, LedgerError, LedgerPoint, TokenLedger,
+    NATIVE_ASSET,
+};
+
+fn ledger() -> TokenLedger {
+    let mut ledger = TokenLedger::new();
+    ledger.register_asset("wETH", AssetRules::bridged("ethereum")).unwrap();
+    mint(&mut ledger, "alice", 100);
+    mint_asset(&mut ledger, "wETH", "alice", 7).unwrap();
+    ledger
+}
+
+#[test]
+fn assets_keep_separate_balances() {
+    let mut ledger = ledger();
+    mint_asset(&mut ledger, "wETH", "alice", 3).unwrap();
+    ledger.transfer_asset("wETH", "alice", "bob", 4).unwrap();
+
+    assert_eq!(ledger.asset_balance("wETH", "alice"), 6);
+    assert_eq!(ledger.asset_balance("wETH", "bob"), 4);
+    assert_eq!(ledger.balance("alice"), 100);
+    assert_eq!(ledger.asset_balance(NATIVE_ASSET, "alice"), 100);
+    assert!(matches!(
+        ledger.transfer_asset("wETH", "bob", "alice", 5),
+        Err(LedgerError::InsufficientBalance)
+    ));
+}
+
+#[test]
+fn per_asset_rules_are_enforced() {
+    let mut ledger = ledger();
+    assert!(matches!(stake_asset(&mut ledger, "wETH", "alice", 1), Err(LedgerError::NotStakeable(_))));
+    stake(&mut ledger, "alice", 10).unwrap();
+
+    ledger
+        .register_asset("soul", AssetRules { transferable: false, ..AssetRules::bridged("cosmos") })
+        .unwrap();
+    mint_asset(&mut ledger, "soul", "alice", 1).unwrap();
+    assert!(matches!(ledger.transfer_asset("soul", "alice", "bob", 1), Err(LedgerError::NotTransferable(_))));
+    burn_asset(&mut ledger, "soul", "alice", 1).unwrap();
+    assert_eq!(ledger.asset_balance("soul", "alice"), 0);
+}
+
+#[test]
+fn unregistered_assets_are_rejected() {
+    let mut ledger = ledger();
+    assert!(matches!(mint_asset(&mut ledger, "wBTC", "alice", 1), Err(LedgerError::UnknownAsset(_))));
+    assert!(matches!(ledger.transfer_asset("wBTC", "alice", "bob", 0), Err(LedgerError::UnknownAsset(_))));
+    assert!(matches!(
+        ledger.register_asset(NATIVE_ASSET, AssetRules::native()),
+        Err(LedgerError::AssetExists(_))
+    ));
+    assert!(matches!(
+        ledger.register_asset("wETH", AssetRules::bridged("ethereum")),
+        Err(LedgerError::AssetExists(_))
+    ));
+}
+
+#[test]
+fn history_rewinds_each_asset() {
+    let mut ledger = ledger();
+    ledger.transfer_asset("wETH", "alice", "bob", 2).unwrap();
+    let past = ledger_at(&ledger, LedgerPoint::Height(2));
+    assert_eq!(past.asset_balance("wETH", "alice"), 7);
+    assert_eq!(past.asset_balance("wETH", "bob"), 0);
+    assert_eq!(past.balance("alice"), 100);
+}
+
+#[test]
+fn ledgers_saved_before_assets_still_load() {
+    let ledger: TokenLedger = serde_json::from_str(r#"{"balances":{"alice":5}}"#).unwrap();
+    assert_eq!(ledger.balance("alice"), 5);
+    assert!(ledger.asset_rules.is_empty());
+}
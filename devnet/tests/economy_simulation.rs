@@ -0,0 +1,77 @@
+use devnet::simulation::{run, to_csv, SimConfig, Snapshot};
+
+fn quiet() -> SimConfig {
+    let mut config = SimConfig { blocks: 200, ..SimConfig::default() };
+    config.workers.fraud_rate = 0.0;
+    config.storage.failure_rate = 0.0;
+    config
+}
+
+fn last(trajectory: &[Snapshot]) -> &Snapshot {
+    trajectory.last().unwrap()
+}
+
+#[test]
+fn configs_fill_in_defaults() {
+    let config: SimConfig = toml::from_str(
+        r#"
+        blocks = 50
+        [chain.rebate]
+        rebate_bps = 2500
+        [workers]
+        initial = 3
+        "#,
+    )
+    .unwrap();
+    assert_eq!((config.blocks, config.workers.initial, config.chain.rebate.rebate_bps), (50, 3, 2_500));
+    assert_eq!(config.workers.max, SimConfig::default().workers.max);
+    assert_eq!(config.chain.rebate.epoch_blocks, 100);
+}
+
+#[test]
+fn trajectories_are_sampled_and_reproducible() {
+    let config = quiet();
+    let trajectory = run(&config).unwrap();
+    assert_eq!(trajectory.len(), 21);
+    assert_eq!((trajectory[0].block, last(&trajectory).block), (0, 200));
+    assert_eq!(trajectory[0].price, config.market.initial_price);
+    assert!(last(&trajectory).jobs_posted > 0);
+    assert_eq!(run(&config).unwrap(), trajectory);
+
+    let csv = to_csv(&trajectory);
+    assert_eq!(csv.lines().count(), 22);
+    assert!(csv.starts_with("block,supply,"));
+}
+
+#[test]
+fn supply_only_grows_without_burns_or_slashing() {
+    let trajectory = run(&quiet()).unwrap();
+    for pair in trajectory.windows(2) {
+        assert!(pair[1].supply >= pair[0].supply);
+    }
+    assert!(last(&trajectory).supply > trajectory[0].supply);
+    assert_eq!(last(&trajectory).frauds + last(&trajectory).outages, 0);
+}
+
+#[test]
+fn fee_burns_reduce_supply_growth() {
+    let mut burning = quiet();
+    burning.chain.rebate.rebate_bps = 10_000;
+    burning.chain.rebate.min_reputation = u64::MAX;
+    burning.posters.fee = 50;
+    let mut paying = burning.clone();
+    paying.chain.rebate.rebate_bps = 0;
+    assert!(last(&run(&burning).unwrap()).supply < last(&run(&paying).unwrap()).supply);
+}
+
+#[test]
+fn outages_slash_providers_out_of_the_network() {
+    let mut config = quiet();
+    config.storage.failure_rate = 0.5;
+    config.storage.max = 5;
+    let trajectory = run(&config).unwrap();
+    let end = last(&trajectory);
+    assert!(end.outages > 0);
+    assert!(end.staked < trajectory[0].staked);
+    assert!(end.supply < trajectory[0].supply + 200 * runtime::blockchain::constants::BLOCK_REWARD);
+}
@@ -0,0 +1,119 @@
+use devnet::daemon::follower::{Follower, FollowerConfig, NodeMode, SharedFollower};
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use runtime::blockchain::{Block, Blockchain, GenesisConfig};
+use runtime::job::Job;
+use runtime::miner;
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn chain() -> Blockchain {
+    Blockchain::with_genesis(GenesisConfig::default()).unwrap()
+}
+
+/// Mines `count` blocks on a full node's chain. One-epoch jobs keep mining
+/// fast; the verifier rejects solutions computed in under 100ms.
+async fn mine(count: u64) -> Vec<Block> {
+    let leader = Arc::new(Mutex::new(chain()));
+    let mut blocks = Vec::new();
+    for id in 0..count {
+        let queue = Arc::new(Mutex::new(VecDeque::from([Job::new(id, "m".into(), "d".into(), 1)])));
+        let mut block = miner::mine_block("miner".into(), leader.clone(), Arc::new(Mutex::new(HashSet::new())), queue)
+            .await
+            .unwrap();
+        block.solution.computation_time_ms = block.solution.computation_time_ms.max(100);
+        block.hash = block.calculate_hash();
+        leader.lock().await.add_block(block.clone()).unwrap();
+        blocks.push(block);
+    }
+    blocks
+}
+
+#[tokio::test]
+async fn followers_import_gossiped_blocks_in_order() {
+    let blocks = mine(3).await;
+    let mut follower = Follower::new(FollowerConfig::default());
+    let mut replica = chain();
+
+    // Blocks 2 and 3 wait for block 1.
+    assert_eq!(follower.receive(&mut replica, blocks[2].clone()), 0);
+    assert_eq!(follower.receive(&mut replica, blocks[1].clone()), 0);
+    assert_eq!(follower.status(&replica).pending, 2);
+    assert_eq!(follower.receive(&mut replica, blocks[0].clone()), 3);
+    assert_eq!(replica.get_tip().hash, blocks[2].hash);
+
+    // Blocks heard again are ignored.
+    assert_eq!(follower.receive(&mut replica, blocks[1].clone()), 0);
+    let status = follower.status(&replica);
+    assert_eq!((status.mode, status.imported, status.rejected, status.pending), (NodeMode::Follower, 3, 0, 0));
+}
+
+#[tokio::test]
+async fn followers_request_the_history_gossip_missed() {
+    let blocks = mine(4).await;
+    let mut follower = Follower::new(FollowerConfig::default());
+    let mut replica = chain();
+    assert_eq!(follower.missing_from(&replica), None);
+
+    // Joining late, the first block heard is the fourth.
+    assert_eq!(follower.receive(&mut replica, blocks[3].clone()), 0);
+    assert_eq!(follower.missing_from(&replica), Some(1));
+
+    // A peer answers with the first two; the third is still missing.
+    assert_eq!(follower.receive_range(&mut replica, blocks[..2].to_vec()), 2);
+    assert_eq!(follower.missing_from(&replica), Some(3));
+    assert_eq!(follower.receive_range(&mut replica, blocks[2..].to_vec()), 2);
+    assert_eq!(follower.missing_from(&replica), None);
+    assert_eq!(replica.get_tip().hash, blocks[3].hash);
+}
+
+#[tokio::test]
+async fn invalid_blocks_are_rejected() {
+    let blocks = mine(1).await;
+    let mut follower = Follower::new(FollowerConfig::default());
+    let mut replica = chain();
+    let mut forged = blocks[0].clone();
+    forged.miner = "thief".into();
+
+    assert_eq!(follower.receive(&mut replica, forged), 0);
+    let status = follower.status(&replica);
+    assert_eq!((status.imported, status.rejected), (0, 1));
+    assert!(status.last_error.is_some());
+    assert_eq!(follower.receive(&mut replica, blocks[0].clone()), 1);
+}
+
+#[test]
+fn followers_run_with_light_network_settings() {
+    let config = FollowerConfig::default();
+    let p2p = config.p2p_config();
+    assert_eq!(p2p.connections.max_peers, config.max_peers);
+    assert_eq!(p2p.max_concurrent_fetches, 1);
+    assert!(p2p.roles.is_empty());
+    assert_eq!(NodeMode::from_env_value(Some("true")), NodeMode::Follower);
+    assert_eq!(NodeMode::from_env_value(Some("0")), NodeMode::Full);
+    assert_eq!(NodeMode::from_env_value(None), NodeMode::Full);
+}
+
+async fn call(rpc: &RpcServer, method: &str, params: Value) -> Value {
+    let req = RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) };
+    serde_json::to_value(rpc.dispatch(req).await).unwrap()
+}
+
+#[tokio::test]
+async fn follower_rpc_is_read_only() {
+    let blockchain = Arc::new(Mutex::new(chain()));
+    let queue = Arc::new(Mutex::new(VecDeque::new()));
+    let follower: SharedFollower = Arc::new(Mutex::new(Follower::new(FollowerConfig::default())));
+
+    let full = RpcServer::new(blockchain.clone(), queue.clone());
+    assert_eq!(call(&full, "getSyncStatus", json!([])).await["result"]["mode"], "full");
+
+    let rpc = RpcServer::new(blockchain, queue).with_follower(follower);
+    let status = call(&rpc, "getSyncStatus", json!([])).await;
+    assert_eq!(status["result"]["mode"], "follower");
+    assert_eq!(status["result"]["height"], 1);
+    let sent = call(&rpc, "sendRawTransaction", json!(["00"])).await;
+    assert_eq!(sent["error"]["code"], -32004);
+    assert!(call(&rpc, "getBlockByNumber", json!(["latest"])).await["result"].is_object());
+}
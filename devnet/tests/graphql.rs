@@ -0,0 +1,141 @@
+use devnet::daemon::graphql::schema;
+use devnet::daemon::indexer::{Indexer, SharedIndexer};
+use runtime::blockchain::transaction::StorageTx;
+use runtime::blockchain::{Block, Blockchain, BlockchainConfig, Transaction};
+use runtime::endpoint::EndpointRecord;
+use runtime::job::Job;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn push_block(chain: &mut Blockchain, transactions: Vec<Transaction>, job_id: Option<u64>) {
+    let tip = chain.get_tip().clone();
+    let mut task = tip.task;
+    task.job_id = job_id;
+    let index = tip.index + 1;
+    chain.blocks.push(Block::new(index, tip.hash, transactions, tip.difficulty, "miner".into(), task, tip.solution));
+}
+
+fn transfer(from: &str, to: &str, nonce: u64) -> Transaction {
+    Transaction::new(from.into(), to.into(), 10, 1, nonce)
+}
+
+/// Genesis, two transfer blocks, then a block completing job 7 that also
+/// registers an endpoint for its model.
+fn chain() -> Blockchain {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    push_block(&mut chain, vec![transfer("alice", "bob", 0), transfer("bob", "carol", 0)], None);
+    push_block(&mut chain, vec![transfer("alice", "carol", 1)], None);
+    let mut register = transfer("alice", "", 2);
+    register.storage = Some(StorageTx::RegisterEndpoint {
+        record: EndpointRecord::new("ep-1", "resnet", "alice", "node-a", vec!["node-b".into()]),
+    });
+    push_block(&mut chain, vec![register], Some(7));
+    chain
+}
+
+fn job(id: u64) -> Job {
+    let mut job = Job::new(id, "resnet".into(), "cifar".into(), 3);
+    job.reward = 50;
+    job
+}
+
+#[test]
+fn indexer_follows_the_chain_and_rebuilds_after_a_reorg() {
+    let mut chain = chain();
+    let mut index = Indexer::default();
+    index.sync_jobs([&job(7), &job(8)]);
+    index.sync(&chain);
+    assert_eq!(index.height(), Some(3));
+    assert_eq!(index.job(7).unwrap().completed_in, Some(3));
+    assert_eq!(index.job(8).unwrap().completed_in, None);
+    assert_eq!(index.model("resnet").unwrap().endpoint_id, "ep-1");
+
+    let carol = index.transactions(Some("carol"), None, 10);
+    assert_eq!(carol.items.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(), vec![2, 1]);
+    let first = index.transactions(None, None, 2);
+    assert!(first.has_next);
+    let rest = index.transactions(None, Some(first.items[1].0), 10);
+    assert_eq!(rest.items.len(), 2);
+    assert!(!rest.has_next);
+
+    // A competing tip replaces the last block.
+    chain.blocks.pop();
+    push_block(&mut chain, Vec::new(), None);
+    index.sync(&chain);
+    assert_eq!(index.height(), Some(3));
+    assert_eq!(index.job(7).unwrap().completed_in, None);
+    assert!(index.model("resnet").is_none());
+    assert_eq!(index.block(3).unwrap().hash, chain.blocks[3].hash);
+}
+
+async fn query(indexer: &SharedIndexer, chain: Arc<Mutex<Blockchain>>, query: &str) -> Value {
+    let response = schema(indexer.clone(), chain).execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn nested_queries_walk_the_index() {
+    let chain = chain();
+    let mut index = Indexer::default();
+    index.sync_jobs([&job(7)]);
+    index.sync(&chain);
+    let indexer: SharedIndexer = Arc::new(Mutex::new(index));
+    let chain = Arc::new(Mutex::new(chain));
+
+    let data = query(
+        &indexer,
+        chain.clone(),
+        r#"{
+            blocks(first: 2) {
+                pageInfo { hasNextPage endCursor }
+                edges { node { number job { id model { endpointId owner { address } } } } }
+            }
+            block(number: 1) { transactionCount transactions(first: 1) { nodes { from { address } to { address } } } }
+        }"#,
+    )
+    .await;
+    let blocks = &data["blocks"];
+    assert_eq!(blocks["pageInfo"], json!({ "hasNextPage": true, "endCursor": "2" }));
+    assert_eq!(blocks["edges"][0]["node"]["number"], 3);
+    assert_eq!(blocks["edges"][0]["node"]["job"]["model"]["owner"]["address"], "alice");
+    assert_eq!(blocks["edges"][1]["node"]["job"], Value::Null);
+    assert_eq!(data["block"]["transactionCount"], 2);
+    assert_eq!(
+        data["block"]["transactions"]["nodes"],
+        json!([{ "from": { "address": "alice" }, "to": { "address": "bob" } }])
+    );
+
+    let data = query(
+        &indexer,
+        chain,
+        r#"{
+            account(address: "carol") { balance transactions(after: "2") { nodes { hash kind block { number } } } }
+            job(id: 7) { completed completedIn { number } }
+            models(owner: "alice") { nodes { modelId standbys registeredIn { number } } }
+        }"#,
+    )
+    .await;
+    let carol = &data["account"]["transactions"]["nodes"];
+    assert_eq!(carol.as_array().unwrap().len(), 1);
+    assert_eq!(carol[0]["kind"], "transfer");
+    assert_eq!(carol[0]["block"]["number"], 1);
+    assert_eq!(data["job"], json!({ "completed": true, "completedIn": { "number": 3 } }));
+    assert_eq!(
+        data["models"]["nodes"],
+        json!([{ "modelId": "resnet", "standbys": ["node-b"], "registeredIn": { "number": 3 } }])
+    );
+}
+
+#[tokio::test]
+async fn malformed_queries_are_refused() {
+    let indexer: SharedIndexer = Arc::new(Mutex::new(Indexer::default()));
+    let chain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let schema = schema(indexer, chain);
+    for bad in [r#"{ block(number: 1, hash: "x") { number } }"#, r#"{ blocks(after: "zz") { nodes { number } } }"#] {
+        assert!(!schema.execute(bad).await.errors.is_empty(), "{bad}");
+    }
+    let deep = "{ blocks { nodes { job { model { owner { transactions { nodes { block { job { model { owner { address } } } } } } } } } } } }";
+    assert!(!schema.execute(deep).await.errors.is_empty());
+}
@@ -0,0 +1,254 @@
+use devnet::daemon::fair_queue::{FairnessConfig, Poster};
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use devnet::daemon::scheduler::{
+    AttestationConfig, DeferReason, Outcome, Scheduler, SchedulerConfig, SchedulerError, SharedScheduler,
+};
+use ed25519_dalek::SigningKey;
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::job::{Job, JobRequirements};
+use runtime::node::capability_probe::{
+    attestation_seed, run_benchmark, BenchmarkConfig, GpuInfo, HardwareInfo, SignedBenchmark,
+};
+use runtime::node::{CapabilityType, NodeCapability, Reputation};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn node(gpu_memory_gb: u32, stake: u64, reputation: i32) -> NodeCapability {
+    NodeCapability {
+        cpus: 8,
+        gpus: u32::from(gpu_memory_gb > 0),
+        gpu_memory_gb,
+        available_stake: stake,
+        reputation: Reputation::uniform(reputation),
+        capability_types: vec![CapabilityType::Training],
+        min_benchmark_score: 0,
+        benchmark: None,
+    }
+}
+
+fn job(id: u64, min_gpu_memory_gb: u32) -> Job {
+    Job::new(id, "model".into(), "data".into(), 1)
+        .with_requirements(JobRequirements { min_gpu_memory_gb, ..JobRequirements::default() })
+}
+
+fn config() -> SchedulerConfig {
+    SchedulerConfig { max_jobs_per_node: 1, max_queued: 3, ..SchedulerConfig::default() }
+}
+
+#[test]
+fn jobs_go_to_the_best_capable_node() {
+    let mut scheduler = Scheduler::new(config());
+    scheduler.register_node("small", node(8, 100, 50)).unwrap();
+    scheduler.register_node("big", node(80, 100, 10)).unwrap();
+    scheduler.register_node("trusted", node(40, 100, 90)).unwrap();
+
+    let queue = vec![job(1, 40), job(2, 0), job(3, 80), job(4, 80)];
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.assignment(1), Some("trusted"));
+    assert_eq!(scheduler.assignment(2), Some("small"));
+    assert_eq!(scheduler.assignment(3), Some("big"));
+    assert_eq!(scheduler.assignment(4), None);
+
+    let impossible = job(5, 160);
+    let decisions = scheduler.schedule(queue.iter().chain([&impossible]));
+    // Job 4 was already deferred for the same reason, so only job 5 is new.
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].job_id, 5);
+    assert_eq!(decisions[0].outcome, Outcome::Deferred { reason: DeferReason::NoCapableNode });
+}
+
+#[test]
+fn finished_jobs_free_their_node() {
+    let mut scheduler = Scheduler::new(config());
+    scheduler.register_node("gpu", node(80, 0, 0)).unwrap();
+    let mut queue = VecDeque::from(vec![job(1, 80), job(2, 80)]);
+    let decisions = scheduler.schedule(&queue);
+    assert_eq!(decisions[1].outcome, Outcome::Deferred { reason: DeferReason::NodesBusy });
+
+    queue.pop_front();
+    let outcomes: Vec<_> = scheduler.schedule(&queue).into_iter().map(|d| (d.job_id, d.outcome)).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (1, Outcome::Released { node: "gpu".into() }),
+            (2, Outcome::Assigned { node: "gpu".into() }),
+        ]
+    );
+    assert_eq!(scheduler.status().deferred, 0);
+}
+
+#[test]
+fn removed_nodes_hand_back_their_jobs() {
+    let mut scheduler = Scheduler::new(config());
+    scheduler.register_node("a", node(0, 0, 5)).unwrap();
+    scheduler.register_node("b", node(0, 0, 1)).unwrap();
+    let queue = vec![job(1, 0)];
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.remove_node("a"), vec![1]);
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.assignment(1), Some("b"));
+}
+
+#[test]
+fn full_queues_refuse_new_jobs() {
+    let scheduler = Scheduler::new(config());
+    assert!(scheduler.admit(2).is_ok());
+    assert_eq!(scheduler.admit(3), Err(SchedulerError::QueueFull(3)));
+}
+
+/// A benchmark by `key` at `timestamp` of a node with one GPU of
+/// `gpu_memory_gb`, and the node's id.
+fn attested(key: &SigningKey, gpu_memory_gb: u64, timestamp: u64) -> (String, SignedBenchmark) {
+    let signer = hex::encode(key.verifying_key().to_bytes());
+    let config = BenchmarkConfig { matrix_size: 16, buffer_mb: 1, rounds: 1 };
+    let result = run_benchmark(&config, attestation_seed(&signer, timestamp));
+    let hardware = HardwareInfo {
+        cpus: 8,
+        cpu_model: "test".into(),
+        memory_mb: 16 * 1024,
+        disk_free_gb: 100,
+        gpus: vec![GpuInfo { model: "NVIDIA L4".into(), memory_mb: gpu_memory_gb * 1024 }],
+    };
+    (signer, SignedBenchmark::sign(hardware, result, timestamp, key))
+}
+
+#[test]
+fn matching_trusts_measured_gpus_over_claims() {
+    let attestation = AttestationConfig { required: true, max_age_secs: 100 };
+    let mut scheduler = Scheduler::new(SchedulerConfig { attestation, ..config() });
+    // Claims 80 GB but only shows 24.
+    let (overclaimer, benchmark) = attested(&SigningKey::from_bytes(&[1; 32]), 24, 1_000);
+    let mut claimed = node(80, 0, 90);
+    claimed.benchmark = Some(benchmark);
+    scheduler.register_node_at(&overclaimer, claimed, 1_000).unwrap();
+    // Claims 80 GB without any benchmark.
+    scheduler.register_node_at("unproven", node(80, 0, 50), 1_000).unwrap();
+    let (honest, benchmark) = attested(&SigningKey::from_bytes(&[2; 32]), 48, 1_000);
+    let mut measured = node(0, 0, 10);
+    measured.benchmark = Some(benchmark.clone());
+    scheduler.register_node_at(&honest, measured.clone(), 1_000).unwrap();
+
+    let queue = vec![job(1, 40), job(2, 80), job(3, 16)];
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.assignment(1), Some(honest.as_str()));
+    assert_eq!(scheduler.assignment(2), None);
+    assert_eq!(scheduler.assignment(3), Some(overclaimer.as_str()));
+
+    // Stale benchmarks are refused, and registered ones stop counting.
+    assert_eq!(
+        scheduler.register_node_at(&honest, measured, 1_101),
+        Err(SchedulerError::UnattestedBenchmark(honest.clone()))
+    );
+    let mut stale = vec![honest.clone(), overclaimer.clone()];
+    stale.sort();
+    assert_eq!(scheduler.expire_attestations(1_101), stale);
+    let queue = vec![job(4, 16)];
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.assignment(4), None);
+
+    // Someone else's benchmark is refused outright.
+    let mut borrowed = node(0, 0, 0);
+    borrowed.benchmark = Some(benchmark);
+    assert_eq!(
+        scheduler.register_node_at("borrower", borrowed, 1_000),
+        Err(SchedulerError::InvalidBenchmark("borrower".into()))
+    );
+}
+
+fn poster(id: &str, stake: u64) -> Option<Poster> {
+    Some(Poster { id: id.into(), stake })
+}
+
+/// Schedules `queue` on one single-slot node, finishing the assigned job
+/// after every pass, and returns the order jobs were assigned in.
+fn serve(
+    scheduler: &mut Scheduler,
+    mut queue: Vec<Job>,
+    posters: &dyn Fn(&Job) -> Option<Poster>,
+) -> Vec<u64> {
+    scheduler.register_node("gpu", node(80, 0, 0)).unwrap();
+    let mut served = Vec::new();
+    while !queue.is_empty() {
+        scheduler.schedule_with(&queue, posters);
+        let done = queue.iter().position(|j| scheduler.assignment(j.id).is_some()).unwrap();
+        served.push(queue.remove(done).id);
+    }
+    served
+}
+
+#[test]
+fn a_flooding_poster_cannot_starve_others() {
+    let mut scheduler = Scheduler::new(SchedulerConfig { max_jobs_per_node: 1, ..SchedulerConfig::default() });
+    // The flooder posts 100 jobs before anyone else gets a look in.
+    let queue: Vec<Job> = (0..103).map(|id| job(id, 0)).collect();
+    let posters = |j: &Job| if j.id < 100 { poster("flooder", 0) } else { poster("other", 0) };
+    let served = serve(&mut scheduler, queue, &posters);
+
+    let position = |id| served.iter().position(|s| *s == id).unwrap();
+    assert!(position(100) < 3, "served {:?}", &served[..8]);
+    assert!(position(102) < 8, "served {:?}", &served[..8]);
+
+    let stats = scheduler.fairness();
+    let flooder = stats.flows.iter().find(|f| f.poster == "flooder").unwrap();
+    assert_eq!(flooder.assigned, 100);
+    assert_eq!(stats.flows.len(), 2);
+    assert!(stats.flows.iter().all(|f| f.weight <= 1.0));
+}
+
+#[test]
+fn stake_buys_a_larger_share() {
+    let fairness = FairnessConfig { usage_unit: 1.0, usage_decay: 1.0, ..FairnessConfig::default() };
+    let config = SchedulerConfig { max_jobs_per_node: 1, fairness, ..SchedulerConfig::default() };
+    let mut scheduler = Scheduler::new(config);
+    let queue: Vec<Job> = (0..40).map(|id| job(id, 0)).collect();
+    // Even ids come from a poster staking three units, odd ids from none.
+    let posters = |j: &Job| if j.id % 2 == 0 { poster("whale", 3_000) } else { poster("minnow", 0) };
+    let served = serve(&mut scheduler, queue, &posters);
+
+    // Four times the weight, four jobs for every one of the minnow's.
+    let whale = served[..20].iter().filter(|id| *id % 2 == 0).count();
+    assert_eq!(whale, 16, "served {:?}", &served[..20]);
+    assert_eq!(served[3], 1);
+}
+
+#[test]
+fn one_poster_may_hold_only_their_share_of_the_queue() {
+    let scheduler = Scheduler::new(config());
+    // Half of three, rounded up.
+    assert!(scheduler.admit_poster("flooder", 1).is_ok());
+    assert_eq!(
+        scheduler.admit_poster("flooder", 2),
+        Err(SchedulerError::PosterShareExceeded { poster: "flooder".into(), held: 2 })
+    );
+}
+
+fn request(method: &str, params: Value) -> RpcRequest {
+    RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) }
+}
+
+#[tokio::test]
+async fn decisions_are_served_over_rpc() {
+    let blockchain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let queue = Arc::new(Mutex::new(VecDeque::from(vec![job(7, 16)])));
+    let scheduler: SharedScheduler = Arc::new(Mutex::new(Scheduler::new(config())));
+    let rpc = RpcServer::new(blockchain, queue.clone()).with_scheduler(scheduler.clone());
+
+    let registered = rpc.dispatch(request("registerNode", json!(["worker", node(24, 10, 0)]))).await;
+    assert_eq!(registered.result, Some(json!(true)));
+    scheduler.lock().await.schedule(queue.lock().await.iter());
+
+    let assignment = rpc.dispatch(request("getJobAssignment", json!([7]))).await;
+    assert_eq!(assignment.result, Some(json!("worker")));
+    let decisions = rpc.dispatch(request("getSchedulingDecisions", json!([0]))).await.result.unwrap();
+    assert_eq!(decisions[0]["outcome"], "assigned");
+    assert_eq!(decisions[0]["jobId"], 7);
+    let status = rpc.dispatch(request("getSchedulerStatus", Value::Null)).await.result.unwrap();
+    assert_eq!(status["assigned"], 1);
+    let fairness = rpc.dispatch(request("getFairnessStats", Value::Null)).await.result.unwrap();
+    assert_eq!(fairness["flows"][0]["assigned"], 1);
+
+    let bare = RpcServer::new(Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default()))), queue);
+    assert!(bare.dispatch(request("getSchedulerStatus", Value::Null)).await.error.is_some());
+}
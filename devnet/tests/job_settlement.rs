@@ -0,0 +1,68 @@
+use devnet::job::{complete_job, post_job, Job, JobManagerError, Settlement};
+use devnet::ledger::{
+    add_liquidity, mint, mint_asset, quote_swap, AssetRules, LedgerError, TokenLedger, NATIVE_ASSET, TREASURY,
+};
+
+const WETH: &str = "wETH";
+
+/// Alice holds 1 000 wETH and a pool trades 10 000 wETH against 100 000
+/// native tokens.
+fn ledger() -> TokenLedger {
+    let mut ledger = TokenLedger::new();
+    ledger.register_asset(WETH, AssetRules::bridged("Ethereum")).unwrap();
+    mint_asset(&mut ledger, WETH, "alice", 1_000).unwrap();
+    mint_asset(&mut ledger, WETH, "lp", 10_000).unwrap();
+    mint(&mut ledger, "lp", 100_000);
+    add_liquidity(&mut ledger, "lp", WETH, 10_000, 100_000).unwrap();
+    ledger
+}
+
+fn posted(ledger: &mut TokenLedger) -> Vec<Job> {
+    let mut jobs = Vec::new();
+    post_job(&mut jobs, ledger, "alice", "train".into(), 500, WETH).unwrap();
+    assert_eq!(ledger.asset_balance(WETH, "alice"), 500);
+    assert_eq!(ledger.asset_balance(WETH, TREASURY), 500);
+    jobs
+}
+
+#[test]
+fn bridged_rewards_settle_in_kind() {
+    let mut ledger = ledger();
+    let mut jobs = posted(&mut ledger);
+    let paid = complete_job(&mut jobs, &mut ledger, "job_0", "bob", None).unwrap();
+    assert_eq!(paid, Settlement { asset: WETH.into(), amount: 500 });
+    assert_eq!(ledger.asset_balance(WETH, "bob"), 500);
+    assert_eq!(ledger.asset_balance(WETH, TREASURY), 0);
+    assert_eq!(jobs[0].reward, 0);
+}
+
+#[test]
+fn bridged_rewards_swap_to_native() {
+    let mut ledger = ledger();
+    let mut jobs = posted(&mut ledger);
+    let quoted = quote_swap(&ledger, WETH, 500).unwrap();
+    assert!(quoted > 4_000 && quoted < 5_000, "{}", quoted);
+
+    // Asking for more than the pool pays leaves the reward in escrow.
+    let err = complete_job(&mut jobs, &mut ledger, "job_0", "bob", Some(quoted + 1)).unwrap_err();
+    assert!(matches!(err, JobManagerError::Ledger(LedgerError::SlippageExceeded { .. })), "{}", err);
+    assert_eq!(ledger.asset_balance(WETH, TREASURY), 500);
+
+    let paid = complete_job(&mut jobs, &mut ledger, "job_0", "bob", Some(quoted)).unwrap();
+    assert_eq!(paid, Settlement { asset: NATIVE_ASSET.into(), amount: quoted });
+    assert_eq!((ledger.balance("bob"), ledger.asset_balance(WETH, "bob")), (quoted, 0));
+    let pool = &ledger.liquidity_pools[WETH];
+    assert_eq!(pool.token_reserves[WETH], 10_500);
+    assert_eq!(pool.token_reserves[NATIVE_ASSET], 100_000 - quoted);
+}
+
+#[test]
+fn swaps_need_a_pool() {
+    let mut ledger = TokenLedger::new();
+    ledger.register_asset("wBNB", AssetRules::bridged("BinanceSmartChain")).unwrap();
+    mint_asset(&mut ledger, "wBNB", "alice", 100).unwrap();
+    let mut jobs = Vec::new();
+    post_job(&mut jobs, &mut ledger, "alice", "train".into(), 100, "wBNB").unwrap();
+    let err = complete_job(&mut jobs, &mut ledger, "job_0", "bob", Some(0)).unwrap_err();
+    assert!(matches!(err, JobManagerError::Ledger(LedgerError::NoLiquidityPool(_))), "{}", err);
+}
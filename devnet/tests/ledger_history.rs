@@ -0,0 +1,67 @@
+use devnet::ledger::{history, ledger_at, mint, slash, stake, LedgerOp, LedgerPoint, TokenLedger, TREASURY};
+
+fn ledger() -> TokenLedger {
+    let mut ledger = TokenLedger::new();
+    mint(&mut ledger, "alice", 100);
+    ledger.transfer("alice", "bob", 30).unwrap();
+    stake(&mut ledger, "alice", 20).unwrap();
+    slash(&mut ledger, "bob", 5).unwrap();
+    ledger
+}
+
+#[test]
+fn every_operation_records_one_height() {
+    let ledger = ledger();
+    assert_eq!(ledger.height(), 4);
+    let ops: Vec<(u64, LedgerOp, &str)> =
+        ledger.events.iter().map(|e| (e.height, e.op, e.account.as_str())).collect();
+    assert_eq!(
+        ops,
+        vec![
+            (1, LedgerOp::Mint, "alice"),
+            (2, LedgerOp::Transfer, "alice"),
+            (2, LedgerOp::Transfer, "bob"),
+            (3, LedgerOp::Stake, "alice"),
+            (4, LedgerOp::Slash, "bob"),
+            (4, LedgerOp::Slash, TREASURY),
+        ]
+    );
+}
+
+#[test]
+fn ledger_at_rewinds_to_a_height() {
+    let ledger = ledger();
+    let expected = [(0, 0, 0), (1, 100, 0), (2, 70, 30), (3, 50, 30), (4, 50, 25)];
+    for (height, alice, bob) in expected {
+        let past = ledger_at(&ledger, LedgerPoint::Height(height));
+        assert_eq!((past.height(), past.balance("alice"), past.balance("bob")), (height, alice, bob));
+    }
+    assert_eq!(ledger_at(&ledger, LedgerPoint::Height(0)).balance(TREASURY), 0);
+    assert_eq!(ledger_at(&ledger, LedgerPoint::Height(99)).balances, ledger.balances);
+}
+
+#[test]
+fn ledger_at_rewinds_to_a_time() {
+    let mut ledger = ledger();
+    for event in &mut ledger.events {
+        event.timestamp = 1_000 + event.height as i64 * 10;
+    }
+    assert_eq!(ledger_at(&ledger, LedgerPoint::Timestamp(1_025)).height(), 2);
+    assert_eq!(ledger_at(&ledger, LedgerPoint::Timestamp(999)).balances.len(), 0);
+}
+
+#[test]
+fn balances_from_before_the_history_are_kept() {
+    let mut ledger = TokenLedger::new();
+    ledger.balances.insert("carol".into(), 40);
+    ledger.transfer("carol", "dave", 10).unwrap();
+    let past = ledger_at(&ledger, LedgerPoint::Height(0));
+    assert_eq!((past.balance("carol"), past.balance("dave")), (40, 0));
+}
+
+#[test]
+fn history_lists_one_account_in_order() {
+    let ledger = ledger();
+    let changes: Vec<(u64, u64)> = history(&ledger, "bob").map(|e| (e.before, e.after)).collect();
+    assert_eq!(changes, vec![(0, 30), (30, 25)]);
+}
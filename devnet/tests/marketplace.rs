@@ -0,0 +1,131 @@
+use devnet::daemon::marketplace::WorkerListing;
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use devnet::daemon::scheduler::{DeferReason, Outcome, Scheduler, SchedulerConfig, SharedScheduler};
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::job::{Job, JobRequirements};
+use runtime::node::{CapabilityType, NodeCapability, Reputation};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn node(reputation: i32) -> NodeCapability {
+    NodeCapability {
+        cpus: 8,
+        gpus: 1,
+        gpu_memory_gb: 24,
+        available_stake: 0,
+        reputation: Reputation::uniform(reputation),
+        capability_types: vec![CapabilityType::Training],
+        min_benchmark_score: 0,
+        benchmark: None,
+    }
+}
+
+fn listing(price: u64, pool: &str) -> WorkerListing {
+    WorkerListing { price, pool: Some(pool.into()) }
+}
+
+fn job(id: u64, pinned: &[&str], excluded: &[&str]) -> Job {
+    Job::new(id, "model".into(), "data".into(), 1).with_requirements(JobRequirements {
+        pinned: pinned.iter().map(|s| s.to_string()).collect(),
+        excluded: excluded.iter().map(|s| s.to_string()).collect(),
+        ..JobRequirements::default()
+    })
+}
+
+fn scheduler() -> Scheduler {
+    let mut scheduler =
+        Scheduler::new(SchedulerConfig { max_jobs_per_node: 1, ..SchedulerConfig::default() });
+    scheduler.register_listed("a1", node(90), listing(5, "alpha")).unwrap();
+    scheduler.register_listed("a2", node(10), listing(3, "alpha")).unwrap();
+    scheduler.register_listed("b1", node(50), listing(4, "beta")).unwrap();
+    scheduler.register_node("solo", node(70)).unwrap();
+    scheduler
+}
+
+#[test]
+fn posters_pin_and_exclude_providers() {
+    let mut scheduler = scheduler();
+    let queue = vec![
+        job(1, &["beta"], &[]),
+        job(2, &["alpha"], &["a1"]),
+        job(3, &[], &["alpha", "b1"]),
+        job(4, &["beta"], &[]),
+        job(5, &["nobody"], &[]),
+    ];
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.assignment(1), Some("b1"));
+    assert_eq!(scheduler.assignment(2), Some("a2"));
+    assert_eq!(scheduler.assignment(3), Some("solo"));
+    // The only member of beta is busy; a1 is idle but not allowed.
+    assert_eq!(scheduler.assignment(4), None);
+    let deferred: Vec<_> =
+        scheduler.decisions(0).into_iter().filter(|d| d.job_id >= 4).map(|d| d.outcome).collect();
+    assert_eq!(
+        deferred,
+        vec![
+            Outcome::Deferred { reason: DeferReason::NodesBusy },
+            Outcome::Deferred { reason: DeferReason::NoAllowedNode },
+        ]
+    );
+}
+
+#[test]
+fn records_keep_each_workers_history() {
+    let mut scheduler = scheduler();
+    let mut queue = VecDeque::from(vec![job(1, &["alpha"], &["a2"]), job(2, &["a2"], &[])]);
+    scheduler.schedule(&queue);
+    queue.pop_front();
+    scheduler.schedule(&queue);
+    assert_eq!(scheduler.remove_node("a2"), vec![2]);
+
+    let market = scheduler.marketplace();
+    let a1 = market.worker("a1").unwrap();
+    assert_eq!((a1.assigned, a1.completed, a1.abandoned, a1.reliability), (1, 1, 0, Some(1.0)));
+    let a2 = market.worker("a2").unwrap();
+    assert!(!a2.online);
+    assert_eq!((a2.assigned, a2.abandoned, a2.reliability), (1, 1, Some(0.0)));
+    assert_eq!(market.worker("solo").unwrap().reliability, None);
+
+    let alpha = market.pool("alpha").unwrap();
+    assert_eq!(alpha.members, vec!["a1".to_string(), "a2".to_string()]);
+    assert_eq!((alpha.online, alpha.min_price, alpha.reliability), (1, Some(5), Some(0.5)));
+    let names: Vec<_> = market.pools().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["alpha", "beta"]);
+
+    // Coming back keeps the record and the listing.
+    scheduler.register_node("a2", node(10)).unwrap();
+    let a2 = scheduler.marketplace().worker("a2").unwrap();
+    assert!(a2.online);
+    assert_eq!((a2.abandoned, a2.listing.price), (1, 3));
+}
+
+fn request(method: &str, params: Value) -> RpcRequest {
+    RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) }
+}
+
+#[tokio::test]
+async fn listings_are_served_over_rpc() {
+    let blockchain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let queue = Arc::new(Mutex::new(VecDeque::new()));
+    let scheduler: SharedScheduler = Arc::new(Mutex::new(Scheduler::new(SchedulerConfig::default())));
+    let rpc = RpcServer::new(blockchain, queue).with_scheduler(scheduler);
+
+    let listed = json!(["w1", node(40), {"price": 7, "pool": "gpu-farm"}]);
+    assert_eq!(rpc.dispatch(request("registerNode", listed)).await.result, Some(json!(true)));
+    let bad = json!(["w2", node(40), {"price": "free"}]);
+    assert_eq!(rpc.dispatch(request("registerNode", bad)).await.error.unwrap().code, -32602);
+
+    let workers = rpc.dispatch(request("listWorkers", Value::Null)).await.result.unwrap();
+    assert_eq!(workers.as_array().unwrap().len(), 1);
+    assert_eq!(workers[0]["pool"], "gpu-farm");
+    assert_eq!(workers[0]["reliability"], Value::Null);
+    let worker = rpc.dispatch(request("getWorker", json!(["w1"]))).await.result.unwrap();
+    assert_eq!((worker["price"].clone(), worker["online"].clone()), (json!(7), json!(true)));
+    let pools = rpc.dispatch(request("listPools", Value::Null)).await.result.unwrap();
+    assert_eq!(pools[0]["members"], json!(["w1"]));
+    let pool = rpc.dispatch(request("getPool", json!(["gpu-farm"]))).await.result.unwrap();
+    assert_eq!(pool["minPrice"], 7);
+    assert_eq!(rpc.dispatch(request("getPool", json!(["other"]))).await.result, Some(Value::Null));
+}
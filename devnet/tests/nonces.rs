@@ -0,0 +1,130 @@
+use devnet::daemon::nonces::{next_nonce, NonceConfig, NonceError, NonceService, SharedNonces};
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::p2p_service::{command::Command, P2PHandle};
+use schnorrkel::{Keypair, SecretKey};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+fn key() -> SecretKey {
+    Keypair::generate().secret.clone()
+}
+
+fn pk_hex(sk: &SecretKey) -> String {
+    hex::encode(sk.to_public().to_bytes())
+}
+
+fn transfer(sender: &SecretKey, nonce: u64) -> Transaction {
+    Transaction::new_transfer(sender, key().to_public(), 10, 1, nonce)
+}
+
+#[test]
+fn early_transactions_wait_for_their_turn() {
+    let sender = key();
+    let from = pk_hex(&sender);
+    let mut nonces = NonceService::new(NonceConfig { max_ahead: 4, max_held: 2, ..NonceConfig::default() });
+    let allocated: Vec<u64> = (0..3).map(|_| nonces.allocate(&from, 0, 100)).collect();
+    assert_eq!(allocated, vec![0, 1, 2]);
+
+    // 2 and 1 arrive before 0 and are held.
+    assert_eq!(nonces.admit(transfer(&sender, 2), 0), Ok(None));
+    assert_eq!(nonces.admit(transfer(&sender, 2), 0), Err(NonceError::Duplicate(2)));
+    assert_eq!(nonces.admit(transfer(&sender, 1), 0), Ok(None));
+    let status = nonces.status(&from, 0, 100);
+    assert_eq!((status.held, status.gaps, status.next_allocation), (vec![1, 2], vec![0], 3));
+    assert_eq!(nonces.admit(transfer(&sender, 3), 0), Err(NonceError::Full));
+    assert_eq!(
+        nonces.admit(transfer(&sender, 5), 0),
+        Err(NonceError::TooFarAhead { expected: 0, got: 5, max_ahead: 4 })
+    );
+
+    // 0 goes straight through, then the held ones follow in order.
+    let first = nonces.admit(transfer(&sender, 0), 0).unwrap().unwrap();
+    assert_eq!(first.nonce, 0);
+    assert_eq!(nonces.take_ready(&from, 1).unwrap().nonce, 1);
+    assert_eq!(nonces.take_ready(&from, 2).unwrap().nonce, 2);
+    assert!(nonces.take_ready(&from, 3).is_none());
+    assert_eq!(nonces.admit(transfer(&sender, 2), 3), Err(NonceError::TooLow { expected: 3, got: 2 }));
+    let status = nonces.status(&from, 3, 100);
+    assert!(status.held.is_empty() && status.in_flight.is_empty());
+
+    // A client that never submits gives its nonce back when the lease ends.
+    assert_eq!(nonces.allocate(&from, 3, 100), 3);
+    assert_eq!(nonces.allocate(&from, 3, 100), 4);
+    nonces.expire(100 + nonces.config().lease_secs);
+    assert_eq!(nonces.allocate(&from, 3, 200), 3);
+    assert!(nonces.release(&from, 3));
+    assert_eq!(nonces.status(&from, 3, 200).next_allocation, 3);
+}
+
+#[test]
+fn the_mempool_counts_towards_the_next_nonce() {
+    let sender = key();
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    chain.state.set_balance(&pk_hex(&sender), 1_000);
+    chain.add_transaction(transfer(&sender, 0)).unwrap();
+    let mempool: HashSet<Transaction> = [transfer(&sender, 1), transfer(&key(), 7)].into();
+    assert_eq!(next_nonce(&chain, &mempool, &pk_hex(&sender)), 2);
+    assert_eq!(next_nonce(&chain, &HashSet::new(), &pk_hex(&sender)), 1);
+}
+
+/// A P2P handle whose gossip always goes through.
+fn p2p() -> P2PHandle {
+    let (sender, mut commands) = mpsc::channel(16);
+    tokio::spawn(async move {
+        while let Some(command) = commands.recv().await {
+            if let Command::SendMessage { response, .. } = command {
+                let _ = response.send(Ok(()));
+            }
+        }
+    });
+    P2PHandle::new(sender)
+}
+
+fn request(method: &str, params: Value) -> RpcRequest {
+    RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) }
+}
+
+#[tokio::test]
+async fn concurrent_clients_submit_out_of_order_over_rpc() {
+    let sender = key();
+    let from = pk_hex(&sender);
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    chain.state.set_balance(&from, 1_000);
+    let blockchain = Arc::new(Mutex::new(chain));
+    let nonces: SharedNonces = Arc::new(Mutex::new(NonceService::default()));
+    let mempool = Arc::new(Mutex::new(HashSet::new()));
+    let rpc = RpcServer::new(blockchain.clone(), Arc::new(Mutex::new(VecDeque::new())))
+        .with_nonces(nonces)
+        .with_mempool(mempool.clone(), p2p());
+
+    let mut allocated = Vec::new();
+    for _ in 0..3 {
+        allocated.push(rpc.dispatch(request("allocateNonce", json!([from]))).await.result.unwrap());
+    }
+    assert_eq!(allocated, vec![json!(0), json!(1), json!(2)]);
+
+    for nonce in [2, 1] {
+        let sent = rpc.dispatch(request("sendRawTransaction", json!([transfer(&sender, nonce)]))).await;
+        assert!(sent.error.is_none(), "{:?}", sent.error);
+    }
+    assert!(mempool.lock().await.is_empty());
+    let status = rpc.dispatch(request("getNonceStatus", json!([from]))).await.result.unwrap();
+    assert_eq!(status["held"], json!([1, 2]));
+    assert_eq!(status["gaps"], json!([0]));
+
+    rpc.dispatch(request("sendRawTransaction", json!([transfer(&sender, 0)]))).await.result.unwrap();
+    let mut pooled: Vec<u64> = mempool.lock().await.iter().map(|tx| tx.nonce).collect();
+    pooled.sort();
+    assert_eq!(pooled, vec![0, 1, 2]);
+
+    let stale = rpc.dispatch(request("sendRawTransaction", json!([transfer(&sender, 1)]))).await;
+    assert_eq!(stale.error.unwrap().code, -32000);
+    let released = rpc.dispatch(request("releaseNonce", json!([from, 9]))).await;
+    assert_eq!(released.result, Some(json!(false)));
+
+    let plain = RpcServer::new(blockchain, Arc::new(Mutex::new(VecDeque::new())));
+    assert_eq!(plain.dispatch(request("allocateNonce", json!([from]))).await.error.unwrap().code, -32003);
+}
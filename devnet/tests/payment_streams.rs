@@ -0,0 +1,100 @@
+use devnet::ledger::{
+    cancel_stream, mint, open_stream, settle_account, settle_stream, settle_streams, AssetRules, LedgerError,
+    LedgerOp, TokenLedger, NATIVE_ASSET,
+};
+
+const T0: i64 = 1_000_000;
+const HOUR: i64 = 3_600;
+
+fn ledger() -> TokenLedger {
+    let mut ledger = TokenLedger::new();
+    mint(&mut ledger, "alice", 1_000);
+    ledger
+}
+
+#[test]
+fn streams_accrue_by_the_second_and_settle_lazily() {
+    let mut ledger = ledger();
+    let id = open_stream(&mut ledger, "alice", "host", NATIVE_ASSET, 60, T0).unwrap();
+
+    // Nothing moves until the stream is settled.
+    assert_eq!(ledger.balance("host"), 0);
+    assert_eq!(ledger.streams[&id].owed(T0 + HOUR / 2), 30);
+
+    assert_eq!(settle_account(&mut ledger, "host", T0 + HOUR / 2), 30);
+    assert_eq!((ledger.balance("alice"), ledger.balance("host")), (970, 30));
+    assert_eq!(settle_stream(&mut ledger, id, T0 + HOUR / 2).unwrap(), 0);
+
+    // Partial minutes are not lost between settlements.
+    settle_stream(&mut ledger, id, T0 + HOUR / 2 + 30).unwrap();
+    settle_stream(&mut ledger, id, T0 + HOUR / 2 + 60).unwrap();
+    assert_eq!(ledger.balance("host"), 31);
+    assert_eq!(ledger.events.last().unwrap().op, LedgerOp::Stream);
+}
+
+#[test]
+fn shortfall_stays_owed_until_the_payer_is_funded() {
+    let mut ledger = TokenLedger::new();
+    mint(&mut ledger, "alice", 50);
+    let id = open_stream(&mut ledger, "alice", "host", NATIVE_ASSET, 100, T0).unwrap();
+
+    assert_eq!(settle_stream(&mut ledger, id, T0 + HOUR).unwrap(), 50);
+    assert_eq!(ledger.streams[&id].owed(T0 + HOUR), 50);
+
+    mint(&mut ledger, "alice", 500);
+    assert_eq!(settle_stream(&mut ledger, id, T0 + 2 * HOUR).unwrap(), 150);
+    assert_eq!((ledger.balance("alice"), ledger.balance("host")), (350, 200));
+}
+
+#[test]
+fn epoch_settlement_pays_every_stream() {
+    let mut ledger = ledger();
+    open_stream(&mut ledger, "alice", "host", NATIVE_ASSET, 10, T0).unwrap();
+    open_stream(&mut ledger, "alice", "storage", NATIVE_ASSET, 20, T0).unwrap();
+
+    assert_eq!(settle_streams(&mut ledger, T0 + 2 * HOUR), 60);
+    assert_eq!((ledger.balance("host"), ledger.balance("storage")), (20, 40));
+}
+
+#[test]
+fn cancelling_settles_and_closes_the_stream() {
+    let mut ledger = ledger();
+    let id = open_stream(&mut ledger, "alice", "host", NATIVE_ASSET, 10, T0).unwrap();
+
+    let closed = cancel_stream(&mut ledger, id, T0 + 3 * HOUR).unwrap();
+    assert_eq!(closed.paid, 30);
+    assert!(ledger.streams.is_empty());
+    assert!(matches!(settle_stream(&mut ledger, id, T0 + 4 * HOUR), Err(LedgerError::UnknownStream(1))));
+    assert_eq!(settle_account(&mut ledger, "alice", T0 + 4 * HOUR), 0);
+
+    // The id of a cancelled stream is not handed out again.
+    let next = open_stream(&mut ledger, "alice", "host", NATIVE_ASSET, 10, T0 + 4 * HOUR).unwrap();
+    assert_eq!(next, id + 1);
+}
+
+#[test]
+fn degenerate_streams_are_refused() {
+    let mut ledger = ledger();
+    assert!(matches!(
+        open_stream(&mut ledger, "alice", "alice", NATIVE_ASSET, 10, T0),
+        Err(LedgerError::InvalidStream(_))
+    ));
+    assert!(matches!(open_stream(&mut ledger, "alice", "host", NATIVE_ASSET, 0, T0), Err(LedgerError::InvalidStream(_))));
+    assert!(ledger.streams.is_empty());
+}
+
+#[test]
+fn streams_respect_asset_rules() {
+    let mut ledger = ledger();
+    assert!(matches!(
+        open_stream(&mut ledger, "alice", "host", "wETH", 1, T0),
+        Err(LedgerError::UnknownAsset(_))
+    ));
+    ledger
+        .register_asset("soul", AssetRules { origin_chain: Some("eth".into()), transferable: false, stakeable: false })
+        .unwrap();
+    assert!(matches!(
+        open_stream(&mut ledger, "alice", "host", "soul", 1, T0),
+        Err(LedgerError::NotTransferable(_))
+    ));
+}
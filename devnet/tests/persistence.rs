@@ -0,0 +1,56 @@
+use devnet::job::Job;
+use devnet::ledger::{mint, TokenLedger, NATIVE_ASSET};
+use devnet::persistence::{Backend, Persistence, Profile};
+use std::path::PathBuf;
+
+fn scratch(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bcai-devnet-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+fn round_trip(store: &dyn Persistence) {
+    assert!(store.load_ledger().unwrap().balances.is_empty());
+    assert!(store.load_jobs().unwrap().is_empty());
+
+    let mut ledger = TokenLedger::new();
+    mint(&mut ledger, "alice", 100);
+    store.save_ledger(&ledger).unwrap();
+    store.save_jobs(&[Job { id: "1".into(), data: vec![1, 2, 3], reward: 50, asset: NATIVE_ASSET.into() }]).unwrap();
+
+    assert_eq!(store.load_ledger().unwrap().balance("alice"), 100);
+    let jobs = store.load_jobs().unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!((jobs[0].id.as_str(), jobs[0].reward), ("1", 50));
+}
+
+#[test]
+fn memory_backend_round_trips() {
+    round_trip(Profile::Test.open().unwrap().as_ref());
+}
+
+#[test]
+fn file_backend_round_trips() {
+    let dir = scratch("file");
+    std::fs::create_dir_all(&dir).unwrap();
+    round_trip(Backend::File { dir: dir.clone() }.open().unwrap().as_ref());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn sled_backend_survives_reopening() {
+    let path = scratch("sled");
+    round_trip(Backend::Sled { path: path.clone() }.open().unwrap().as_ref());
+
+    let reopened = Backend::Sled { path: path.clone() }.open().unwrap();
+    assert_eq!(reopened.load_ledger().unwrap().balance("alice"), 100);
+    drop(reopened);
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn profiles_select_their_backends() {
+    assert_eq!(Profile::Test.backend(), Backend::Memory);
+    assert!(matches!(Profile::Devnet.backend(), Backend::File { .. }));
+    assert!(matches!(Profile::Node.backend(), Backend::Sled { .. }));
+}
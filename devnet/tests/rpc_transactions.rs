@@ -0,0 +1,75 @@
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use runtime::blockchain::Transaction;
+use runtime::job::Job;
+use runtime::p2p_service::{command::Command, decode_message, P2PHandle, WireMessage};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use testkit::NetworkSpec;
+use tokio::sync::{mpsc, Mutex};
+
+fn request(method: &str, params: Value) -> RpcRequest {
+    RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) }
+}
+
+/// A P2P handle whose gossip always goes through, and the messages gossiped.
+fn p2p() -> (P2PHandle, mpsc::UnboundedReceiver<Vec<u8>>) {
+    let (sender, mut commands) = mpsc::channel(16);
+    let (gossiped, messages) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(command) = commands.recv().await {
+            if let Command::SendMessage { message, response, .. } = command {
+                let _ = gossiped.send(message);
+                let _ = response.send(Ok(()));
+            }
+        }
+    });
+    (P2PHandle::new(sender), messages)
+}
+
+#[tokio::test]
+async fn transactions_sent_over_rpc_are_gossiped_and_mined() {
+    let network = NetworkSpec::accounts_only(2).build().unwrap();
+    let (sender, recipient) = (&network.accounts[0], &network.accounts[1]);
+    let nonce = network.chain.get_nonce(&sender.public_key);
+    let tx = Transaction::new_transfer(&sender.secret, recipient.secret.to_public(), 100, 1, nonce);
+    let blockchain = Arc::new(Mutex::new(network.chain));
+    let mempool = Arc::new(Mutex::new(HashSet::new()));
+    let (p2p_handle, mut gossiped) = p2p();
+    let rpc = RpcServer::new(blockchain.clone(), Arc::new(Mutex::new(VecDeque::new())))
+        .with_mempool(mempool.clone(), p2p_handle);
+
+    let sent = rpc.dispatch(request("sendRawTransaction", json!([tx]))).await;
+    assert_eq!(sent.result, Some(json!(tx.hash())));
+    assert!(mempool.lock().await.contains(&tx));
+    match decode_message(&gossiped.recv().await.unwrap()).unwrap() {
+        WireMessage::Transaction(gossip) => assert_eq!(gossip, tx),
+        other => panic!("gossiped {:?}", other),
+    }
+    let pending = rpc.dispatch(request("getTransaction", json!([tx.hash()]))).await.result.unwrap();
+    assert_eq!(pending["blockNumber"], Value::Null);
+
+    // The same transaction again is refused.
+    let again = rpc.dispatch(request("sendRawTransaction", json!([tx]))).await;
+    assert_eq!(again.error.unwrap().code, -32000);
+
+    // The miner draws from the mempool the RPC filled.
+    let queue = Arc::new(Mutex::new(VecDeque::from([Job::new(1, "m".into(), "d".into(), 1)])));
+    let block = testkit::mine(&blockchain, mempool.clone(), queue).await.unwrap();
+    assert_eq!(block.transactions, vec![tx.clone()]);
+    blockchain.lock().await.add_block(block.clone()).unwrap();
+
+    let mined = rpc.dispatch(request("getTransaction", json!([tx.hash()]))).await.result.unwrap();
+    assert_eq!(mined["blockNumber"], json!(block.index));
+}
+
+#[tokio::test]
+async fn nodes_without_a_mempool_refuse_transactions() {
+    let network = NetworkSpec::accounts_only(2).build().unwrap();
+    let (sender, recipient) = (&network.accounts[0], &network.accounts[1]);
+    let nonce = network.chain.get_nonce(&sender.public_key);
+    let tx = Transaction::new_transfer(&sender.secret, recipient.secret.to_public(), 1, 1, nonce);
+    let rpc = RpcServer::new(Arc::new(Mutex::new(network.chain)), Arc::new(Mutex::new(VecDeque::new())));
+    let sent = rpc.dispatch(request("sendRawTransaction", json!([tx]))).await;
+    assert_eq!(sent.error.unwrap().code, -32005);
+}
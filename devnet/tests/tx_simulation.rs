@@ -0,0 +1,84 @@
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use runtime::blockchain::{Blockchain, BlockchainConfig, Simulation, Transaction};
+use schnorrkel::{Keypair, SecretKey};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+fn key() -> SecretKey {
+    Keypair::generate().secret.clone()
+}
+
+fn pk_hex(sk: &SecretKey) -> String {
+    hex::encode(sk.to_public().to_bytes())
+}
+
+/// A chain where `sender` holds 1000 and half of every fee is burned for
+/// a rebate.
+fn chain(sender: &SecretKey) -> Blockchain {
+    let mut config = BlockchainConfig::default();
+    config.rebate.rebate_bps = 5_000;
+    let mut chain = Blockchain::new(config);
+    chain.state.set_balance(&pk_hex(sender), 1_000);
+    chain
+}
+
+/// The `(before, after)` of one state entry, if it changes.
+fn change(simulation: &Simulation, field: &str, key: &str) -> Option<(Value, Value)> {
+    simulation
+        .changes
+        .iter()
+        .find(|c| c.field == field && c.key == key)
+        .map(|c| (c.before.clone(), c.after.clone()))
+}
+
+#[test]
+fn simulation_reports_changes_without_applying_them() {
+    let (sender, recipient) = (key(), key());
+    let chain = chain(&sender);
+    let tx = Transaction::new_transfer(&sender, recipient.to_public(), 100, 5, 0);
+
+    let simulation = chain.simulate_transaction(&tx).unwrap();
+    assert_eq!((simulation.fee, simulation.burned, simulation.deferred), (5, 2, false));
+    assert_eq!(simulation.changes.len(), 4);
+    assert_eq!(change(&simulation, "balances", &pk_hex(&sender)), Some((json!(1_000), json!(895))));
+    assert_eq!(change(&simulation, "balances", &pk_hex(&recipient)), Some((Value::Null, json!(100))));
+    assert_eq!(change(&simulation, "nonces", &pk_hex(&sender)), Some((Value::Null, json!(1))));
+    assert_eq!(change(&simulation, "pending_rebates", &pk_hex(&sender)), Some((Value::Null, json!(2))));
+
+    // Nothing was applied.
+    assert_eq!(chain.get_balance(&pk_hex(&sender)), 1_000);
+    assert!(chain.pending_transactions.is_empty());
+
+    let overdrawn = Transaction::new_transfer(&sender, recipient.to_public(), 1_000, 5, 0);
+    assert!(chain.simulate_transaction(&overdrawn).is_err());
+}
+
+fn request(method: &str, params: Value) -> RpcRequest {
+    RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) }
+}
+
+#[tokio::test]
+async fn simulations_are_served_over_rpc() {
+    let (sender, recipient) = (key(), key());
+    let blockchain = Arc::new(Mutex::new(chain(&sender)));
+    let rpc = RpcServer::new(blockchain.clone(), Arc::new(Mutex::new(VecDeque::new())));
+    let tx = Transaction::new_transfer(&sender, recipient.to_public(), 100, 5, 0);
+
+    let result = rpc.dispatch(request("simulateTransaction", json!([tx]))).await.result.unwrap();
+    assert_eq!(result["hash"], tx.hash());
+    assert_eq!(result["fee"], 5);
+    let events = result["events"].as_array().unwrap();
+    assert!(events.contains(&json!({
+        "event": "accountCredited", "account": pk_hex(&recipient), "amount": 100, "block": 1
+    })));
+    assert!(events.contains(&json!({
+        "event": "balanceChanged", "account": pk_hex(&sender), "balance": 895, "block": 1
+    })));
+    assert!(blockchain.lock().await.pending_transactions.is_empty());
+
+    let forged = Transaction { amount: 900, ..tx };
+    let rejected = rpc.dispatch(request("simulateTransaction", json!([forged]))).await;
+    assert_eq!(rejected.error.unwrap().code, -32000);
+}
@@ -0,0 +1,165 @@
+use devnet::daemon::rpc::{RpcRequest, RpcServer};
+use devnet::daemon::webhooks::{
+    deliver, verify, HttpSender, SharedWebhooks, WebhookConfig, WebhookError, WebhookFilter, Webhooks,
+    SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use devnet::daemon::ws::ChainEvent;
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+fn credited(account: &str) -> ChainEvent {
+    ChainEvent::AccountCredited { account: account.into(), amount: 5, block: 1 }
+}
+
+#[test]
+fn filters_pick_their_events() {
+    let alice = WebhookFilter::AccountCredited { account: "alice".into() };
+    assert!(alice.matches(&credited("alice")));
+    assert!(!alice.matches(&credited("bob")));
+
+    let done = |job_id| ChainEvent::JobCompleted { job_id, miner: "m".into(), block: 2 };
+    assert!(WebhookFilter::JobCompleted { job_id: None }.matches(&done(3)));
+    assert!(!WebhookFilter::JobCompleted { job_id: Some(4) }.matches(&done(3)));
+    let model = ChainEvent::ModelRegistered {
+        model_id: "resnet".into(),
+        endpoint_id: "ep".into(),
+        owner: "alice".into(),
+        block: 3,
+    };
+    assert!(WebhookFilter::ModelRegistered { model_id: Some("resnet".into()) }.matches(&model));
+    assert!(!alice.matches(&model));
+
+    let mut hooks = Webhooks::default();
+    assert_eq!(
+        hooks.register("https://example.com/hook", alice.clone()),
+        Err(WebhookError::InvalidUrl("https://example.com/hook".into()))
+    );
+    assert!(hooks.register("http://127.0.0.1:9/hook", alice).is_ok());
+}
+
+#[test]
+fn failed_deliveries_back_off_then_dead_letter() {
+    let config = WebhookConfig { max_attempts: 3, ..WebhookConfig::default() };
+    let mut hooks = Webhooks::new(config);
+    let filter = WebhookFilter::AccountCredited { account: "a".into() };
+    let hook = hooks.register("http://127.0.0.1:9/", filter).unwrap();
+    let start = Instant::now();
+    hooks.enqueue(&credited("a"), start);
+    hooks.enqueue(&credited("b"), start);
+
+    let mut now = start;
+    for attempt in 1..=3 {
+        let due = hooks.due(now);
+        assert_eq!(due.len(), 1, "attempt {attempt}");
+        assert_eq!(due[0].1, hook);
+        hooks.complete(due[0].0.clone(), Err("connection refused".into()), now);
+        // Nothing is due again until the backoff has passed.
+        assert!(hooks.due(now).is_empty());
+        now += hooks.config().backoff(attempt);
+    }
+    assert_eq!(hooks.config().backoff(2), Duration::from_secs(2));
+
+    let dead = hooks.dead_letters();
+    assert_eq!(dead.len(), 1);
+    assert_eq!((dead[0].attempts, dead[0].last_error.as_deref()), (3, Some("connection refused")));
+    let stats = hooks.stats();
+    assert_eq!((stats.retried, stats.dead_lettered, stats.pending), (2, 1, 0));
+
+    assert!(hooks.replay(dead[0].id, now));
+    assert!(!hooks.replay(dead[0].id, now));
+    let due = hooks.due(now);
+    hooks.complete(due[0].0.clone(), Ok(()), now);
+    assert_eq!(hooks.stats().delivered, 1);
+    assert!(hooks.dead_letters().is_empty());
+}
+
+/// Accepts one HTTP request, answers with `status` and returns its headers
+/// and body.
+async fn receive(listener: &TcpListener, status: &str) -> (Vec<(String, String)>, Vec<u8>) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut raw = Vec::new();
+    let mut buf = [0u8; 4096];
+    let (head, body) = loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        raw.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&raw).to_string();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length: usize = head
+                .lines()
+                .find_map(|l| l.strip_prefix("Content-Length: "))
+                .and_then(|l| l.parse().ok())
+                .unwrap_or(0);
+            if body.len() >= length {
+                break (head.to_string(), body.as_bytes().to_vec());
+            }
+        }
+    };
+    stream.write_all(format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").as_bytes()).await.unwrap();
+    let headers = head
+        .lines()
+        .skip(1)
+        .filter_map(|l| l.split_once(": ").map(|(k, v)| (k.to_ascii_lowercase(), v.to_string())))
+        .collect();
+    (headers, body)
+}
+
+#[tokio::test]
+async fn payloads_are_signed_and_errors_reported() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hooks/chain", listener.local_addr().unwrap());
+    let mut hooks = Webhooks::default();
+    let hook = hooks.register(&url, WebhookFilter::AccountCredited { account: "alice".into() }).unwrap();
+    hooks.enqueue(&credited("alice"), Instant::now());
+    let (delivery, hook_for) = hooks.due(Instant::now()).remove(0);
+    assert_eq!(hook_for, hook);
+    let sender = HttpSender { timeout: Duration::from_secs(5) };
+
+    let (result, (headers, body)) =
+        tokio::join!(deliver(&sender, &delivery, &hook), receive(&listener, "204 No Content"));
+    assert_eq!(result, Ok(()));
+    let header = |name: &str| headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()).unwrap();
+    let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap();
+    assert!(verify(&hook.secret, timestamp, &body, &header(SIGNATURE_HEADER)));
+    assert!(!verify(&hook.secret, timestamp + 1, &body, &header(SIGNATURE_HEADER)));
+    let payload: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(payload["event"]["event"], "accountCredited");
+    assert_eq!(payload["event"]["account"], "alice");
+
+    let failing = receive(&listener, "500 Internal Server Error");
+    let (result, _) = tokio::join!(deliver(&sender, &delivery, &hook), failing);
+    assert_eq!(result, Err("receiver answered 500".to_string()));
+}
+
+fn request(method: &str, params: Value) -> RpcRequest {
+    RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: json!(1) }
+}
+
+#[tokio::test]
+async fn webhooks_are_managed_over_rpc() {
+    let blockchain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let queue = Arc::new(Mutex::new(VecDeque::new()));
+    let webhooks: SharedWebhooks = Arc::new(Mutex::new(Webhooks::default()));
+    let rpc = RpcServer::new(blockchain, queue).with_webhooks(webhooks.clone());
+
+    let filter = json!({ "event": "jobCompleted", "job_id": 7 });
+    let registered = rpc.dispatch(request("registerWebhook", json!(["http://127.0.0.1:9/", filter]))).await;
+    let registered = registered.result.unwrap();
+    assert_eq!(registered["id"], "wh-1");
+    assert_eq!(registered["secret"].as_str().unwrap().len(), 64);
+
+    let listed = rpc.dispatch(request("listWebhooks", Value::Null)).await.result.unwrap();
+    assert_eq!(listed[0]["filter"]["job_id"], 7);
+    assert!(listed[0].get("secret").is_none());
+
+    let bad = rpc.dispatch(request("registerWebhook", json!(["http://x/", { "event": "nope" }]))).await;
+    assert!(bad.error.is_some());
+    let removed = rpc.dispatch(request("removeWebhook", json!(["wh-1"]))).await;
+    assert_eq!(removed.result, Some(json!(true)));
+    assert_eq!(rpc.dispatch(request("getWebhookStats", Value::Null)).await.result.unwrap()["webhooks"], 0);
+}
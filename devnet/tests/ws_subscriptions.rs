@@ -0,0 +1,149 @@
+use devnet::daemon::ws::{block_events, handle_request, ChainEvent, Subscription, SubscriptionServer, Topic};
+use futures_util::{SinkExt, StreamExt};
+use runtime::blockchain::Transaction;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use testkit::{NetworkSpec, MINER};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+fn request(method: &str, params: Value) -> String {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 }).to_string()
+}
+
+fn balance(account: &str) -> ChainEvent {
+    ChainEvent::BalanceChanged { account: account.into(), balance: 5, block: 1 }
+}
+
+/// The next message on `ws`, as JSON.
+async fn recv<S>(ws: &mut S) -> Value
+where
+    S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    let message = tokio::time::timeout(Duration::from_secs(5), ws.next()).await.unwrap().unwrap().unwrap();
+    serde_json::from_str(message.to_text().unwrap()).unwrap()
+}
+
+#[test]
+fn subscriptions_are_numbered_and_removed() {
+    let mut subscriptions = HashMap::new();
+    let mut next_id = 0;
+
+    let blocks = handle_request(&request("subscribe", json!(["newBlocks"])), &mut subscriptions, &mut next_id);
+    assert_eq!(blocks.result, Some(json!("0x1")));
+    let alice = handle_request(&request("subscribe", json!(["balances", ["alice"]])), &mut subscriptions, &mut next_id);
+    assert_eq!(alice.result, Some(json!("0x2")));
+    assert_eq!(subscriptions["0x1"].topic, Topic::NewBlocks);
+    assert!(subscriptions["0x2"].matches(&balance("alice")));
+    assert!(!subscriptions["0x2"].matches(&balance("bob")));
+
+    let removed = handle_request(&request("unsubscribe", json!(["0x1"])), &mut subscriptions, &mut next_id);
+    assert_eq!(removed.result, Some(json!(true)));
+    let again = handle_request(&request("unsubscribe", json!(["0x1"])), &mut subscriptions, &mut next_id);
+    assert_eq!(again.result, Some(json!(false)));
+    assert_eq!(subscriptions.keys().collect::<Vec<_>>(), ["0x2"]);
+
+    // Ids are never reused.
+    let jobs = handle_request(&request("subscribe", json!(["newJobs"])), &mut subscriptions, &mut next_id);
+    assert_eq!(jobs.result, Some(json!("0x3")));
+}
+
+#[test]
+fn malformed_requests_are_refused() {
+    let mut subscriptions = HashMap::new();
+    let mut next_id = 0;
+    let mut code = |text: &str| handle_request(text, &mut subscriptions, &mut next_id).error.unwrap().code;
+
+    assert_eq!(code("not json"), -32700);
+    assert_eq!(code(&json!({ "jsonrpc": "1.0", "method": "subscribe", "params": ["newBlocks"], "id": 1 }).to_string()), -32600);
+    assert_eq!(code(&request("publish", json!([]))), -32601);
+    assert_eq!(code(&request("subscribe", json!({}))), -32602);
+    assert_eq!(code(&request("subscribe", json!([]))), -32602);
+    assert_eq!(code(&request("subscribe", json!(["everything"]))), -32602);
+    assert_eq!(code(&request("subscribe", json!(["balances"]))), -32602);
+    assert_eq!(code(&request("subscribe", json!(["balances", "alice"]))), -32602);
+    assert!(subscriptions.is_empty());
+    assert_eq!(next_id, 0);
+}
+
+#[tokio::test]
+async fn block_events_report_each_block_once() {
+    let network = NetworkSpec::accounts_only(2).build().unwrap();
+    let (sender, recipient) = (&network.accounts[0], &network.accounts[1]);
+    let nonce = network.chain.get_nonce(&sender.public_key);
+    let transfer = Transaction::new_transfer(&sender.secret, recipient.secret.to_public(), 100, 1, nonce);
+    let funds = network.chain.get_balance(&recipient.public_key);
+    let index = network.chain.blocks.len() as u32;
+    let chain = Arc::new(Mutex::new(network.chain));
+    let mempool = Arc::new(Mutex::new(HashSet::from([transfer])));
+
+    let block = testkit::mine(&chain, mempool, Arc::new(Mutex::new(VecDeque::new()))).await.unwrap();
+    let mut chain = chain.lock().await;
+    chain.add_block(block.clone()).unwrap();
+
+    let mut next_block = index as usize;
+    let events = block_events(&chain, &mut next_block);
+    assert_eq!(next_block, index as usize + 1);
+    assert_eq!(
+        events[0],
+        ChainEvent::NewBlock {
+            index,
+            hash: block.hash.clone(),
+            miner: MINER.into(),
+            timestamp: block.timestamp,
+            tx_count: 1,
+        }
+    );
+    let recipient = recipient.public_key.clone();
+    assert!(events.contains(&ChainEvent::AccountCredited { account: recipient.clone(), amount: 100, block: index }));
+    let balances: Vec<&ChainEvent> = events.iter().filter(|e| matches!(e, ChainEvent::BalanceChanged { .. })).collect();
+    assert_eq!(balances.len(), 3, "miner, sender and recipient once each: {:?}", balances);
+    assert!(events.contains(&ChainEvent::BalanceChanged { account: recipient, balance: funds + 100, block: index }));
+
+    assert!(block_events(&chain, &mut next_block).is_empty());
+}
+
+#[tokio::test]
+async fn subscribers_receive_matching_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+    let (server, events) = SubscriptionServer::new();
+    let serve_addr = addr.clone();
+    tokio::spawn(async move { server.serve(&serve_addr).await });
+
+    let mut ws = loop {
+        match tokio_tungstenite::connect_async(format!("ws://{}", addr)).await {
+            Ok((ws, _)) => break ws,
+            Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    };
+    ws.send(Message::Text(request("subscribe", json!(["balances", ["alice"]])))).await.unwrap();
+    let reply = recv(&mut ws).await;
+    assert_eq!(reply["result"], "0x1");
+
+    events.send(balance("bob")).unwrap();
+    events.send(balance("alice")).unwrap();
+    let note = recv(&mut ws).await;
+    assert_eq!(note["method"], "subscription");
+    assert_eq!(note["params"]["subscription"], "0x1");
+    assert_eq!(note["params"]["result"], json!(balance("alice")));
+
+    ws.send(Message::Text(request("unsubscribe", json!(["0x1"])))).await.unwrap();
+    assert_eq!(recv(&mut ws).await["result"], true);
+    events.send(balance("alice")).unwrap();
+    ws.send(Message::Text(request("unsubscribe", json!(["0x1"])))).await.unwrap();
+    // Nothing was pushed in between: the next message is the reply.
+    assert_eq!(recv(&mut ws).await["result"], false);
+}
+
+#[test]
+fn subscription_params_are_parsed() {
+    let sub = Subscription::from_params(&json!(["balances", ["alice", "bob"]])).unwrap();
+    assert_eq!(sub.topic, Topic::Balances);
+    assert_eq!(sub.accounts, HashSet::from(["alice".to_string(), "bob".to_string()]));
+    assert!(Subscription::from_params(&json!(["pouwEvaluations"])).unwrap().accounts.is_empty());
+}
@@ -43,6 +43,9 @@ fn main() -> Result<(), JobError> {
             let job = post_job(&mut jobs, description, reward);
             save_jobs(&jobs)?;
             println!("✅ Job {} posted with reward {}", job.id, reward);
+            if let Some(traceparent) = &job.traceparent {
+                println!("   traceparent: {}", traceparent);
+            }
         }
         Commands::Assign { job_id, worker } => {
             let mut jobs = load_jobs()?;
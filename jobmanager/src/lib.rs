@@ -10,6 +10,10 @@ pub struct Job {
     pub reward: u64,
     pub assigned_to: Option<String>,
     pub completed: bool,
+    /// W3C `traceparent` started when the job was posted, so the worker and
+    /// evaluator spans for this job join the poster's trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
 }
 
 #[derive(Debug, Error)]
@@ -41,11 +45,16 @@ pub fn save_jobs(jobs: &[Job]) -> Result<(), JobError> {
 
 pub fn post_job(jobs: &mut Vec<Job>, description: String, reward: u64) -> Job {
     let id = jobs.last().map(|j| j.id + 1).unwrap_or(1);
-    let job = Job { id, description, reward, assigned_to: None, completed: false };
+    let job = Job { id, description, reward, assigned_to: None, completed: false, traceparent: Some(new_traceparent()) };
     jobs.push(job.clone());
     job
 }
 
+fn new_traceparent() -> String {
+    let span_id = uuid::Uuid::new_v4().simple().to_string();
+    format!("00-{}-{}-01", uuid::Uuid::new_v4().simple(), &span_id[..16])
+}
+
 pub fn assign_job(jobs: &mut [Job], job_id: u64, worker: String) -> Result<(), JobError> {
     if let Some(job) = jobs.iter_mut().find(|j| j.id == job_id) {
         job.assigned_to = Some(worker);
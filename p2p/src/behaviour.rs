@@ -1,11 +1,14 @@
 use libp2p::{
+    autonat, dcutr, identify, mdns,
     ping::{Behaviour as Ping, Event as PingEvent},
     request_response::{Behaviour as RequestResponse, Event as RequestResponseEvent, ProtocolSupport},
-    swarm::NetworkBehaviour,
+    relay,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour},
+    PeerId,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::codec::JobCodec;
+use crate::codec::{HandshakeError, Hello, JobCodec, Session};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Capability {
@@ -15,12 +18,15 @@ pub struct Capability {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobRequest {
+    /// Sent by the dialing side as soon as a connection is up.
+    Hello(Hello),
     Handshake(Capability),
     Train(Vec<u8>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobResponse {
+    Hello(Hello),
     HandshakeAck(Capability),
     TrainResult(Vec<f32>),
 }
@@ -30,12 +36,36 @@ pub enum JobResponse {
 pub struct Behaviour {
     pub ping: Ping,
     pub req: RequestResponse<JobCodec>,
+    /// Tells peers the addresses they are seen at, which AutoNAT and hole
+    /// punching build on.
+    pub identify: identify::Behaviour,
+    /// Asks peers to dial back to learn whether this node is reachable.
+    pub autonat: autonat::Behaviour,
+    /// Circuit relay v2 server, enabled on publicly reachable nodes.
+    pub relay: Toggle<relay::Behaviour>,
+    /// Reserves circuits on relays so NATed nodes can be reached.
+    pub relay_client: relay::client::Behaviour,
+    /// Upgrades relayed connections to direct ones by hole punching.
+    pub dcutr: dcutr::Behaviour,
+    /// Finds peers on the local network, see [`crate::discovery`].
+    pub mdns: Toggle<mdns::tokio::Behaviour>,
 }
 
 #[derive(Debug)]
 pub enum NodeEvent {
     Ping(PingEvent),
     RequestResponse(RequestResponseEvent<JobRequest, JobResponse>),
+    Identify(identify::Event),
+    Nat(autonat::Event),
+    Relay(relay::Event),
+    RelayClient(relay::client::Event),
+    HolePunch(dcutr::Event),
+    Mdns(mdns::Event),
+    /// Versions and features were agreed with `peer`.
+    Handshake { peer: PeerId, session: Session },
+    /// `peer` shares no protocol version with us; the dialing side
+    /// disconnects.
+    Incompatible { peer: PeerId, error: HandshakeError },
 }
 
 impl From<PingEvent> for NodeEvent {
@@ -48,4 +78,40 @@ impl From<RequestResponseEvent<JobRequest, JobResponse>> for NodeEvent {
     fn from(e: RequestResponseEvent<JobRequest, JobResponse>) -> Self {
         NodeEvent::RequestResponse(e)
     }
-} 
\ No newline at end of file
+}
+
+impl From<identify::Event> for NodeEvent {
+    fn from(e: identify::Event) -> Self {
+        NodeEvent::Identify(e)
+    }
+}
+
+impl From<autonat::Event> for NodeEvent {
+    fn from(e: autonat::Event) -> Self {
+        NodeEvent::Nat(e)
+    }
+}
+
+impl From<relay::Event> for NodeEvent {
+    fn from(e: relay::Event) -> Self {
+        NodeEvent::Relay(e)
+    }
+}
+
+impl From<relay::client::Event> for NodeEvent {
+    fn from(e: relay::client::Event) -> Self {
+        NodeEvent::RelayClient(e)
+    }
+}
+
+impl From<dcutr::Event> for NodeEvent {
+    fn from(e: dcutr::Event) -> Self {
+        NodeEvent::HolePunch(e)
+    }
+}
+
+impl From<mdns::Event> for NodeEvent {
+    fn from(e: mdns::Event) -> Self {
+        NodeEvent::Mdns(e)
+    }
+}
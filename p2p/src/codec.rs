@@ -1,10 +1,119 @@
+//! Request/response codec for job traffic.
+//!
+//! The job protocol is registered once per supported version as
+//! `/bcai/job/<version>`, newest first, so multistream-select settles on the
+//! highest version both peers speak when a stream opens. Every frame starts
+//! with that version byte and frames of any other version are refused. Once
+//! connected, peers swap a [`Hello`] to agree on optional features.
+
 use async_trait::async_trait;
 use libp2p::request_response::Codec;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::io;
+use thiserror::Error;
 
 use crate::{JobRequest, JobResponse};
 
+/// Newest job protocol version this node speaks.
+pub const PROTOCOL_VERSION: u8 = 1;
+/// Oldest job protocol version this node still speaks.
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+
+/// Features this build announces.
+pub const FEATURES: &[&str] = &["train-linear"];
+
+pub fn protocol_name(version: u8) -> String {
+    format!("/bcai/job/{version}")
+}
+
+/// Names of every supported version of the job protocol, newest first.
+pub fn protocols() -> Vec<String> {
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).rev().map(protocol_name).collect()
+}
+
+/// The version a job protocol name stands for.
+pub fn version_of(protocol: &str) -> Option<u8> {
+    protocol.strip_prefix("/bcai/job/")?.parse().ok()
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum HandshakeError {
+    #[error("no common protocol version: we speak {local_min}..={local_max}, peer {remote_min}..={remote_max}")]
+    Incompatible { local_min: u8, local_max: u8, remote_min: u8, remote_max: u8 },
+    #[error("peer does not speak any job protocol version we support")]
+    UnsupportedProtocols,
+}
+
+/// Versions and features a node supports, exchanged on connect.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Hello {
+    pub min_version: u8,
+    pub max_version: u8,
+    pub features: BTreeSet<String>,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self {
+            min_version: MIN_PROTOCOL_VERSION,
+            max_version: PROTOCOL_VERSION,
+            features: FEATURES.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl Hello {
+    /// The highest version both sides speak and the features both announce.
+    pub fn negotiate(&self, remote: &Hello) -> Result<Session, HandshakeError> {
+        let version = self.max_version.min(remote.max_version);
+        if version < self.min_version.max(remote.min_version) {
+            return Err(HandshakeError::Incompatible {
+                local_min: self.min_version,
+                local_max: self.max_version,
+                remote_min: remote.min_version,
+                remote_max: remote.max_version,
+            });
+        }
+        let features = self.features.intersection(&remote.features).cloned().collect();
+        Ok(Session { version, features })
+    }
+}
+
+/// What two connected nodes agreed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub version: u8,
+    pub features: BTreeSet<String>,
+}
+
+impl Session {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.contains(feature)
+    }
+}
+
+/// Prefix `payload` with the version of `protocol`.
+pub fn encode_frame<P: Serialize>(protocol: &str, payload: &P) -> io::Result<Vec<u8>> {
+    let version = version_of(protocol).ok_or_else(|| invalid(format!("unknown protocol {protocol}")))?;
+    let mut bytes = vec![version];
+    bytes.extend(bincode::serialize(payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+    Ok(bytes)
+}
+
+/// Read a frame sent over `protocol`, refusing any other version.
+pub fn decode_frame<P: for<'de> Deserialize<'de>>(protocol: &str, bytes: &[u8]) -> io::Result<P> {
+    let (&version, body) = bytes.split_first().ok_or_else(|| invalid("empty frame".to_string()))?;
+    if Some(version) != version_of(protocol) {
+        return Err(invalid(format!("frame version {version} on {protocol}")));
+    }
+    bincode::deserialize(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
 #[derive(Clone, Default)]
 pub struct JobCodec;
 
@@ -14,51 +123,49 @@ impl Codec for JobCodec {
     type Request = JobRequest;
     type Response = JobResponse;
 
-    async fn read_request<T>(&mut self, _: &String, io: &mut T) -> io::Result<Self::Request>
+    async fn read_request<T>(&mut self, protocol: &String, io: &mut T) -> io::Result<Self::Request>
     where
         T: futures::AsyncRead + Unpin + Send,
     {
         let mut buf = Vec::new();
         futures::io::AsyncReadExt::read_to_end(io, &mut buf).await?;
-        Ok(bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?)
+        decode_frame(protocol, &buf)
     }
 
-    async fn read_response<T>(&mut self, _: &String, io: &mut T) -> io::Result<Self::Response>
+    async fn read_response<T>(&mut self, protocol: &String, io: &mut T) -> io::Result<Self::Response>
     where
         T: futures::AsyncRead + Unpin + Send,
     {
         let mut buf = Vec::new();
         futures::io::AsyncReadExt::read_to_end(io, &mut buf).await?;
-        Ok(bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?)
+        decode_frame(protocol, &buf)
     }
 
     async fn write_request<T>(
         &mut self,
-        _: &String,
+        protocol: &String,
         io: &mut T,
         req: Self::Request,
     ) -> io::Result<()>
     where
         T: futures::AsyncWrite + Unpin + Send,
     {
-        let bytes = bincode::serialize(&req)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let bytes = encode_frame(protocol, &req)?;
         futures::io::AsyncWriteExt::write_all(io, &bytes).await?;
         futures::io::AsyncWriteExt::close(io).await
     }
 
     async fn write_response<T>(
         &mut self,
-        _: &String,
+        protocol: &String,
         io: &mut T,
         res: Self::Response,
     ) -> io::Result<()>
     where
         T: futures::AsyncWrite + Unpin + Send,
     {
-        let bytes = bincode::serialize(&res)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let bytes = encode_frame(protocol, &res)?;
         futures::io::AsyncWriteExt::write_all(io, &bytes).await?;
         futures::io::AsyncWriteExt::close(io).await
     }
-} 
\ No newline at end of file
+}
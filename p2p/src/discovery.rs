@@ -0,0 +1,208 @@
+//! Finding peers without a fixed bootstrap node.
+//!
+//! Nodes find each other three ways: mDNS on the local network, a list of
+//! bootstrap addresses, and a [`PeerStore`] of peers recently connected to,
+//! kept on disk. After a restart a node redials the peers it last saw
+//! before falling back to the bootstrap list, so the network can be
+//! rejoined while every bootstrap node is down.
+
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Peers kept when no limit is given.
+pub const DEFAULT_CAPACITY: usize = 256;
+/// Dial failures in a row after which a peer is forgotten.
+pub const MAX_FAILURES: u32 = 3;
+/// Peers not seen for this long, in seconds, are not redialed: a week.
+pub const DEFAULT_MAX_AGE: u64 = 7 * 24 * 60 * 60;
+/// Stored peers redialed when rejoining.
+pub const REJOIN_PEERS: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("discovery I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("peer store is corrupt: {0}")]
+    Corrupt(#[from] serde_json::Error),
+}
+
+/// What is known about one peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerRecord {
+    /// Addresses the peer was reached at, most recent first.
+    pub addrs: Vec<Multiaddr>,
+    /// Unix seconds of the last successful connection.
+    pub last_seen: u64,
+    /// Dial failures since then.
+    pub failures: u32,
+}
+
+/// On-disk form of a record; peer ids and addresses as strings.
+#[derive(Serialize, Deserialize)]
+struct StoredPeer {
+    peer: String,
+    addrs: Vec<String>,
+    last_seen: u64,
+    #[serde(default)]
+    failures: u32,
+}
+
+/// Last-seen good peers, optionally backed by a JSON file.
+#[derive(Debug, Clone)]
+pub struct PeerStore {
+    path: Option<PathBuf>,
+    peers: HashMap<PeerId, PeerRecord>,
+    capacity: usize,
+    max_age: u64,
+}
+
+impl Default for PeerStore {
+    fn default() -> Self {
+        Self { path: None, peers: HashMap::new(), capacity: DEFAULT_CAPACITY, max_age: DEFAULT_MAX_AGE }
+    }
+}
+
+impl PeerStore {
+    /// A store backed by `path`, starting from what it holds. A missing file
+    /// is an empty store; entries that no longer parse are skipped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DiscoveryError> {
+        let path = path.as_ref().to_path_buf();
+        let stored: Vec<StoredPeer> = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let peers = stored
+            .into_iter()
+            .filter_map(|s| {
+                let peer = s.peer.parse().ok()?;
+                let addrs = s.addrs.iter().filter_map(|a| a.parse().ok()).collect();
+                Some((peer, PeerRecord { addrs, last_seen: s.last_seen, failures: s.failures }))
+            })
+            .collect();
+        Ok(Self { path: Some(path), peers, ..Self::default() })
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.evict();
+        self
+    }
+
+    /// Skip peers not seen for `max_age` seconds when rejoining.
+    pub fn with_max_age(mut self, max_age: u64) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    pub fn get(&self, peer: &PeerId) -> Option<&PeerRecord> {
+        self.peers.get(peer)
+    }
+
+    /// A connection to `peer` at `addr` succeeded.
+    pub fn record_seen(&mut self, peer: PeerId, addr: Multiaddr, now: u64) {
+        let addr = without_peer_id(addr);
+        let record = self.peers.entry(peer).or_insert_with(|| PeerRecord { addrs: Vec::new(), last_seen: now, failures: 0 });
+        record.addrs.retain(|a| *a != addr);
+        record.addrs.insert(0, addr);
+        record.last_seen = now;
+        record.failures = 0;
+        self.evict();
+    }
+
+    /// Dialing `peer` failed; it is forgotten after [`MAX_FAILURES`].
+    pub fn record_failure(&mut self, peer: &PeerId) {
+        if let Some(record) = self.peers.get_mut(peer) {
+            record.failures += 1;
+            if record.failures >= MAX_FAILURES {
+                self.peers.remove(peer);
+            }
+        }
+    }
+
+    /// Up to `limit` peers worth redialing at `now`, most recently seen
+    /// first.
+    pub fn candidates(&self, now: u64, limit: usize) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        let mut fresh: Vec<_> = self
+            .peers
+            .iter()
+            .filter(|(_, r)| !r.addrs.is_empty() && now.saturating_sub(r.last_seen) <= self.max_age)
+            .collect();
+        fresh.sort_by_key(|(peer, r)| (std::cmp::Reverse(r.last_seen), r.failures, **peer));
+        fresh.into_iter().take(limit).map(|(peer, r)| (*peer, r.addrs.clone())).collect()
+    }
+
+    /// Write the store to its file, if it has one.
+    pub fn save(&self) -> Result<(), DiscoveryError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let stored: Vec<StoredPeer> = self
+            .peers
+            .iter()
+            .map(|(peer, r)| StoredPeer {
+                peer: peer.to_string(),
+                addrs: r.addrs.iter().map(Multiaddr::to_string).collect(),
+                last_seen: r.last_seen,
+                failures: r.failures,
+            })
+            .collect();
+        // Write then rename, so a crash never leaves half a file behind.
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&stored)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Drop the least recently seen peers beyond capacity.
+    fn evict(&mut self) {
+        while self.peers.len() > self.capacity {
+            let oldest = self.peers.iter().min_by_key(|(peer, r)| (r.last_seen, **peer)).map(|(peer, _)| *peer);
+            match oldest {
+                Some(peer) => self.peers.remove(&peer),
+                None => break,
+            };
+        }
+    }
+}
+
+/// How a node finds peers.
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveryConfig {
+    /// Find peers on the local network over mDNS.
+    pub mdns: bool,
+    /// Addresses dialed when no stored peer is reachable. Addresses ending
+    /// in `/p2p/<peer id>` are skipped when that peer was already dialed.
+    pub bootstrap: Vec<Multiaddr>,
+    /// File the peer store is kept in; `None` keeps it in memory.
+    pub peer_store: Option<PathBuf>,
+}
+
+/// The peer id an address ends in, if any.
+pub fn peer_id_of(addr: &Multiaddr) -> Option<PeerId> {
+    match addr.iter().last() {
+        Some(Protocol::P2p(peer)) => Some(peer),
+        _ => None,
+    }
+}
+
+fn without_peer_id(mut addr: Multiaddr) -> Multiaddr {
+    if peer_id_of(&addr).is_some() {
+        addr.pop();
+    }
+    addr
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
@@ -1,4 +1,5 @@
 pub mod codec;
+pub mod discovery;
 pub mod behaviour;
 pub mod node;
 pub mod transport;
@@ -6,5 +7,8 @@ pub mod network;
 pub mod training;
 
 pub use behaviour::{Capability, JobRequest, JobResponse, NodeEvent, Behaviour};
+pub use codec::{HandshakeError, Hello, Session};
+pub use discovery::{DiscoveryConfig, DiscoveryError, PeerStore};
 pub use node::Node;
+pub use transport::{NatConfig, TransportPreference};
 pub use training::MLTrainer;
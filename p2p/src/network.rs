@@ -14,6 +14,10 @@ impl NetworkOperations {
         format!("/ip4/0.0.0.0/tcp/{port}").parse().expect("tcp addr")
     }
 
+    pub fn generate_quic_address(port: u16) -> Multiaddr {
+        format!("/ip4/0.0.0.0/udp/{port}/quic-v1").parse().expect("quic addr")
+    }
+
     pub fn create_handshake_request(capability: Capability) -> JobRequest {
         JobRequest::Handshake(capability)
     }
@@ -1,12 +1,25 @@
 use libp2p::{
-    identity,
-    swarm::{Swarm, SwarmEvent},
+    autonat::{self, NatStatus},
+    core::{muxing::StreamMuxerBox, transport::Boxed},
+    identity, mdns,
+    multiaddr::Protocol,
+    relay,
+    request_response::{Event as RequestResponseEvent, Message, OutboundFailure},
+    swarm::{dial_opts::DialOpts, Swarm, SwarmEvent},
     Multiaddr, PeerId,
 };
+use std::collections::HashMap;
+use std::num::NonZeroU8;
 
 use crate::{
-    behaviour::{Behaviour, Capability, NodeEvent},
-    transport::{create_memory_transport, create_tcp_transport, create_behaviour, create_swarm},
+    behaviour::{Behaviour, Capability, JobRequest, JobResponse, NodeEvent},
+    codec::{HandshakeError, Hello, Session},
+    discovery::{peer_id_of, unix_now, DiscoveryConfig, DiscoveryError, PeerStore, REJOIN_PEERS},
+    transport::{
+        create_behaviour, create_mdns, create_memory_transport, create_swarm, create_tcp_transport,
+        create_transport, order_addresses, relay_listen_address, with_relay, NatConfig,
+        TransportPreference,
+    },
     network::NetworkOperations,
     training::MLTrainer,
 };
@@ -15,6 +28,36 @@ pub struct Node {
     pub peer_id: PeerId,
     swarm: Swarm<Behaviour>,
     capability: Capability,
+    transport: TransportPreference,
+    /// Relays to reserve circuits on once AutoNAT finds the node private.
+    relays: Vec<(PeerId, Multiaddr)>,
+    relayed: bool,
+    /// What this node announces to peers on connect.
+    hello: Hello,
+    /// What was agreed with each connected peer.
+    sessions: HashMap<PeerId, Session>,
+    /// Peers recently connected to, redialed by [`Node::rejoin`].
+    peers: PeerStore,
+    bootstrap: Vec<Multiaddr>,
+}
+
+/// The discovery parts of a node under construction.
+#[derive(Default)]
+struct Discovery {
+    mdns: Option<mdns::tokio::Behaviour>,
+    peers: PeerStore,
+    bootstrap: Vec<Multiaddr>,
+}
+
+impl Discovery {
+    fn new(id: &identity::Keypair, config: DiscoveryConfig) -> Result<Self, DiscoveryError> {
+        let mdns = if config.mdns { Some(create_mdns(id)?) } else { None };
+        let peers = match &config.peer_store {
+            Some(path) => PeerStore::open(path)?,
+            None => PeerStore::default(),
+        };
+        Ok(Self { mdns, peers, bootstrap: config.bootstrap })
+    }
 }
 
 impl Node {
@@ -24,26 +67,87 @@ impl Node {
 
     /// Create a node using an in-memory transport. Primarily used for tests.
     pub fn new_memory(cpus: u8, gpus: u8) -> Self {
+        Self::new_memory_with_nat(NatConfig::default(), cpus, gpus)
+    }
+
+    /// In-memory node with the given NAT settings, e.g. to stand in for a
+    /// relay in tests.
+    pub fn new_memory_with_nat(nat: NatConfig, cpus: u8, gpus: u8) -> Self {
         let id = identity::Keypair::generate_ed25519();
-        let peer_id = PeerId::from(id.public());
+        let transport = create_memory_transport(&id).expect("memory transport");
+        Self::build(id, transport, TransportPreference::TcpOnly, nat, Capability { cpus, gpus }, Discovery::default())
+    }
 
+    /// In-memory node that finds peers as `discovery` says. Primarily used
+    /// to test rejoining from the peer store.
+    pub fn new_memory_with_discovery(discovery: DiscoveryConfig, cpus: u8, gpus: u8) -> Result<Self, DiscoveryError> {
+        let id = identity::Keypair::generate_ed25519();
         let transport = create_memory_transport(&id).expect("memory transport");
-        let behaviour = create_behaviour();
-        let swarm = create_swarm(transport, behaviour, peer_id);
-        
-        Self { peer_id, swarm, capability: Capability { cpus, gpus } }
+        let discovery = Discovery::new(&id, discovery)?;
+        let capability = Capability { cpus, gpus };
+        Ok(Self::build(id, transport, TransportPreference::TcpOnly, NatConfig::default(), capability, discovery))
     }
 
     /// Create a node that communicates over TCP.
     pub fn new_tcp(cpus: u8, gpus: u8) -> Self {
         let id = identity::Keypair::generate_ed25519();
-        let peer_id = PeerId::from(id.public());
-
         let transport = create_tcp_transport(&id).expect("tcp transport");
-        let behaviour = create_behaviour();
+        let capability = Capability { cpus, gpus };
+        Self::build(id, transport, TransportPreference::TcpOnly, NatConfig::default(), capability, Discovery::default())
+    }
+
+    /// Create a node that communicates over QUIC, TCP or both.
+    pub fn with_transport(preference: TransportPreference, cpus: u8, gpus: u8) -> Self {
+        Self::with_nat(preference, NatConfig::default(), cpus, gpus)
+    }
+
+    /// Like [`Node::with_transport`], with the given NAT settings.
+    pub fn with_nat(preference: TransportPreference, nat: NatConfig, cpus: u8, gpus: u8) -> Self {
+        let id = identity::Keypair::generate_ed25519();
+        let transport = create_transport(&id, preference).expect("transport");
+        Self::build(id, transport, preference, nat, Capability { cpus, gpus }, Discovery::default())
+    }
+
+    /// Like [`Node::with_nat`], finding peers as `discovery` says.
+    pub fn with_discovery(
+        preference: TransportPreference,
+        nat: NatConfig,
+        discovery: DiscoveryConfig,
+        cpus: u8,
+        gpus: u8,
+    ) -> Result<Self, DiscoveryError> {
+        let id = identity::Keypair::generate_ed25519();
+        let transport = create_transport(&id, preference).expect("transport");
+        let discovery = Discovery::new(&id, discovery)?;
+        Ok(Self::build(id, transport, preference, nat, Capability { cpus, gpus }, discovery))
+    }
+
+    fn build(
+        id: identity::Keypair,
+        transport: Boxed<(PeerId, StreamMuxerBox)>,
+        preference: TransportPreference,
+        nat: NatConfig,
+        capability: Capability,
+        discovery: Discovery,
+    ) -> Self {
+        let peer_id = PeerId::from(id.public());
+        let (relay_transport, relay_client) = relay::client::new(peer_id);
+        let transport = with_relay(&id, transport, relay_transport).expect("relay transport");
+        let behaviour = create_behaviour(&id, relay_client, nat, discovery.mdns);
         let swarm = create_swarm(transport, behaviour, peer_id);
 
-        Self { peer_id, swarm, capability: Capability { cpus, gpus } }
+        Self {
+            peer_id,
+            swarm,
+            capability,
+            transport: preference,
+            relays: Vec::new(),
+            relayed: false,
+            hello: Hello::default(),
+            sessions: HashMap::new(),
+            peers: discovery.peers,
+            bootstrap: discovery.bootstrap,
+        }
     }
 
     pub fn capability(&self) -> Capability {
@@ -63,10 +167,101 @@ impl Node {
         addr
     }
 
+    /// Listen on a UDP port for QUIC, returning the bound multiaddress.
+    pub fn listen_quic(&mut self, port: u16) -> Multiaddr {
+        let addr = NetworkOperations::generate_quic_address(port);
+        self.swarm.listen_on(addr.clone()).expect("listen_on");
+        addr
+    }
+
     pub fn dial(&mut self, addr: Multiaddr) {
         self.swarm.dial(addr).expect("dial");
     }
 
+    /// Dial `peer` at `addrs` one address at a time, in the order of the
+    /// node's transport preference, so a failed QUIC attempt falls back to
+    /// TCP.
+    pub fn dial_peer(&mut self, peer: PeerId, addrs: Vec<Multiaddr>) {
+        self.swarm.dial(self.peer_dial_opts(peer, addrs)).expect("dial");
+    }
+
+    fn peer_dial_opts(&self, peer: PeerId, addrs: Vec<Multiaddr>) -> DialOpts {
+        DialOpts::peer_id(peer)
+            .addresses(order_addresses(addrs, self.transport))
+            .override_dial_concurrency_factor(NonZeroU8::MIN)
+            .build()
+    }
+
+    /// Accept connections relayed by `relay` at `relay_addr`, reserving a
+    /// circuit there. Returns the address peers dial to reach this node.
+    pub fn listen_via_relay(&mut self, relay: PeerId, relay_addr: Multiaddr) -> Multiaddr {
+        let addr = relay_listen_address(&relay_addr, relay);
+        self.swarm.listen_on(addr.clone()).expect("listen_on");
+        addr.with(Protocol::P2p(self.peer_id))
+    }
+
+    /// Remember `relay` and ask it to probe our reachability. Circuits are
+    /// reserved on remembered relays once AutoNAT reports the node private.
+    pub fn add_relay(&mut self, relay: PeerId, relay_addr: Multiaddr) {
+        self.swarm.behaviour_mut().autonat.add_server(relay, Some(relay_addr.clone()));
+        self.relays.push((relay, relay_addr.clone()));
+        if self.relayed {
+            self.listen_via_relay(relay, relay_addr);
+        }
+    }
+
+    /// Announce an address this node is publicly reachable at. Relays only
+    /// grant reservations once they know one.
+    pub fn add_external_address(&mut self, addr: Multiaddr) {
+        self.swarm.add_external_address(addr);
+    }
+
+    /// What AutoNAT has found out about our reachability so far.
+    pub fn nat_status(&self) -> NatStatus {
+        self.swarm.behaviour().autonat.nat_status()
+    }
+
+    /// Announce `hello` instead of the versions and features this build
+    /// supports to peers connecting from now on.
+    pub fn advertise(&mut self, hello: Hello) {
+        self.hello = hello;
+    }
+
+    /// What was agreed with `peer`, once the hello exchange completed.
+    pub fn session(&self, peer: &PeerId) -> Option<&Session> {
+        self.sessions.get(peer)
+    }
+
+    /// Peers this node connected to, kept across restarts when the
+    /// discovery config names a file.
+    pub fn peers(&self) -> &PeerStore {
+        &self.peers
+    }
+
+    /// Redial the most recently seen stored peers, then the bootstrap
+    /// addresses of peers not among them. Returns how many dials started.
+    pub fn rejoin(&mut self) -> usize {
+        let mut dialed = 0;
+        let candidates = self.peers.candidates(unix_now(), REJOIN_PEERS);
+        for (peer, addrs) in &candidates {
+            let opts = self.peer_dial_opts(*peer, addrs.clone());
+            dialed += usize::from(self.swarm.dial(opts).is_ok());
+        }
+        for addr in self.bootstrap.clone() {
+            let known = peer_id_of(&addr).is_some_and(|peer| candidates.iter().any(|(p, _)| *p == peer));
+            if !known {
+                dialed += usize::from(self.swarm.dial(addr).is_ok());
+            }
+        }
+        dialed
+    }
+
+    /// Write the peer store to disk now. It is also written whenever a dial
+    /// succeeds or fails.
+    pub fn save_peers(&self) -> Result<(), DiscoveryError> {
+        self.peers.save()
+    }
+
     pub fn send_handshake(&mut self, peer: PeerId) {
         let req = NetworkOperations::create_handshake_request(self.capability.clone());
         self.swarm.behaviour_mut().req.send_request(&peer, req);
@@ -81,10 +276,99 @@ impl Node {
         MLTrainer::train_linear_regression(data)
     }
 
+    fn reserve_circuits(&mut self) {
+        if self.relayed {
+            return;
+        }
+        self.relayed = true;
+        for (relay, addr) in self.relays.clone() {
+            self.listen_via_relay(relay, addr);
+        }
+    }
+
+    /// Answer and settle hello exchanges, passing other events through.
+    fn on_hello(&mut self, evt: NodeEvent) -> NodeEvent {
+        let (peer, remote, dialer) = match evt {
+            NodeEvent::RequestResponse(RequestResponseEvent::Message {
+                peer,
+                message: Message::Request { request: JobRequest::Hello(remote), channel, .. },
+                ..
+            }) => {
+                let hello = JobResponse::Hello(self.hello.clone());
+                let _ = self.swarm.behaviour_mut().req.send_response(channel, hello);
+                (peer, remote, false)
+            }
+            NodeEvent::RequestResponse(RequestResponseEvent::Message {
+                peer,
+                message: Message::Response { response: JobResponse::Hello(remote), .. },
+                ..
+            }) => (peer, remote, true),
+            NodeEvent::RequestResponse(RequestResponseEvent::OutboundFailure {
+                peer,
+                error: OutboundFailure::UnsupportedProtocols,
+                ..
+            }) => {
+                let _ = self.swarm.disconnect_peer_id(peer);
+                return NodeEvent::Incompatible { peer, error: HandshakeError::UnsupportedProtocols };
+            }
+            other => return other,
+        };
+        match self.hello.negotiate(&remote) {
+            Ok(session) => {
+                self.sessions.insert(peer, session.clone());
+                NodeEvent::Handshake { peer, session }
+            }
+            Err(error) => {
+                if dialer {
+                    let _ = self.swarm.disconnect_peer_id(peer);
+                }
+                NodeEvent::Incompatible { peer, error }
+            }
+        }
+    }
+
+    /// Dial peers found over mDNS that are not connected yet.
+    fn dial_discovered(&mut self, found: Vec<(PeerId, Multiaddr)>) {
+        let mut by_peer: HashMap<PeerId, Vec<Multiaddr>> = HashMap::new();
+        for (peer, addr) in found {
+            by_peer.entry(peer).or_default().push(addr);
+        }
+        for (peer, addrs) in by_peer {
+            // Refused for peers already connected or being dialed.
+            let _ = self.swarm.dial(self.peer_dial_opts(peer, addrs));
+        }
+    }
+
     pub async fn next_event(&mut self) -> NodeEvent {
         loop {
             match self.swarm.select_next_some().await {
-                SwarmEvent::Behaviour(evt) => return evt,
+                SwarmEvent::Behaviour(evt) => {
+                    match &evt {
+                        NodeEvent::Nat(autonat::Event::StatusChanged { new: NatStatus::Private, .. }) => {
+                            self.reserve_circuits();
+                        }
+                        NodeEvent::Mdns(mdns::Event::Discovered(found)) => self.dial_discovered(found.clone()),
+                        _ => {}
+                    }
+                    return self.on_hello(evt);
+                }
+                SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } if endpoint.is_dialer() => {
+                    let hello = JobRequest::Hello(self.hello.clone());
+                    self.swarm.behaviour_mut().req.send_request(&peer_id, hello);
+                    // Only dialed addresses are worth keeping; a listener sees
+                    // the peer's ephemeral port.
+                    if !crate::transport::is_relayed(endpoint.get_remote_address()) {
+                        self.peers.record_seen(peer_id, endpoint.get_remote_address().clone(), unix_now());
+                        let _ = self.peers.save();
+                    }
+                }
+                SwarmEvent::OutgoingConnectionError { peer_id: Some(peer), .. } => {
+                    self.peers.record_failure(&peer);
+                    let _ = self.peers.save();
+                }
+                SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                    self.sessions.remove(&peer_id);
+                }
                 _ => {}
             }
         }
@@ -1,15 +1,23 @@
+use futures::future::Either;
 use libp2p::{
-    core::{transport::MemoryTransport, upgrade},
-    identity, noise,
+    core::{
+        muxing::StreamMuxerBox,
+        transport::{Boxed, MemoryTransport},
+        upgrade,
+    },
+    autonat, dcutr, identify, identity, mdns,
+    multiaddr::Protocol,
+    noise, quic, relay,
     request_response::{Config as RequestResponseConfig, ProtocolSupport},
     swarm::Swarm,
-    tcp, yamux, PeerId, Transport,
+    tcp, yamux, Multiaddr, PeerId, Transport,
 };
 use std::time::Duration;
 
 use crate::behaviour::Behaviour;
+use crate::codec;
 
-pub fn create_memory_transport(id: &identity::Keypair) -> Result<impl Transport<Output = (PeerId, libp2p::core::muxing::StreamMuxerBox)> + Clone, Box<dyn std::error::Error>> {
+pub fn create_memory_transport(id: &identity::Keypair) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn std::error::Error>> {
     let transport = MemoryTransport::default()
         .upgrade(upgrade::Version::V1)
         .authenticate(noise::Config::new(id)?)
@@ -19,7 +27,7 @@ pub fn create_memory_transport(id: &identity::Keypair) -> Result<impl Transport<
     Ok(transport)
 }
 
-pub fn create_tcp_transport(id: &identity::Keypair) -> Result<impl Transport<Output = (PeerId, libp2p::core::muxing::StreamMuxerBox)> + Clone, Box<dyn std::error::Error>> {
+pub fn create_tcp_transport(id: &identity::Keypair) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn std::error::Error>> {
     let transport = tcp::tokio::Transport::new(tcp::Config::default())
         .upgrade(upgrade::Version::V1)
         .authenticate(noise::Config::new(id)?)
@@ -29,21 +37,154 @@ pub fn create_tcp_transport(id: &identity::Keypair) -> Result<impl Transport<Out
     Ok(transport)
 }
 
-pub fn create_behaviour() -> Behaviour {
+/// Which transports a node runs, and which it tries first when a peer
+/// offers both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransportPreference {
+    /// QUIC, falling back to TCP when the peer has no QUIC address or the
+    /// QUIC attempt fails. QUIC saves round trips on connection setup and
+    /// gets through home NATs more often.
+    #[default]
+    QuicFirst,
+    TcpFirst,
+    QuicOnly,
+    TcpOnly,
+}
+
+impl TransportPreference {
+    pub fn uses_quic(self) -> bool {
+        self != TransportPreference::TcpOnly
+    }
+
+    pub fn uses_tcp(self) -> bool {
+        self != TransportPreference::QuicOnly
+    }
+
+    fn quic_first(self) -> bool {
+        matches!(self, TransportPreference::QuicFirst | TransportPreference::QuicOnly)
+    }
+}
+
+/// Whether `addr` is a `/udp/<port>/quic-v1` address.
+pub fn is_quic(addr: &Multiaddr) -> bool {
+    addr.iter().any(|p| matches!(p, Protocol::QuicV1))
+}
+
+/// `addrs` in the order to dial them, leaving out those of a transport
+/// `preference` does not run.
+pub fn order_addresses(
+    addrs: impl IntoIterator<Item = Multiaddr>,
+    preference: TransportPreference,
+) -> Vec<Multiaddr> {
+    let mut ordered: Vec<Multiaddr> = addrs
+        .into_iter()
+        .filter(|a| if is_quic(a) { preference.uses_quic() } else { preference.uses_tcp() })
+        .collect();
+    ordered.sort_by_key(|a| is_quic(a) != preference.quic_first());
+    ordered
+}
+
+/// TCP, QUIC or both, as `preference` says. Each address is dialed over the
+/// transport it names.
+pub fn create_transport(
+    id: &identity::Keypair,
+    preference: TransportPreference,
+) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn std::error::Error>> {
+    let tcp = tcp::tokio::Transport::new(tcp::Config::default())
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::Config::new(id)?)
+        .multiplex(yamux::Config::default())
+        .timeout(Duration::from_secs(20))
+        .map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)));
+    let quic = quic::tokio::Transport::new(quic::Config::new(id))
+        .map(|(peer, connection), _| (peer, StreamMuxerBox::new(connection)));
+    let transport = match preference {
+        TransportPreference::TcpOnly => tcp.boxed(),
+        TransportPreference::QuicOnly => quic.boxed(),
+        TransportPreference::QuicFirst | TransportPreference::TcpFirst => quic
+            .or_transport(tcp)
+            .map(|either, _| match either {
+                Either::Left(output) | Either::Right(output) => output,
+            })
+            .boxed(),
+    };
+    Ok(transport)
+}
+
+/// `transport` plus circuits through relays, which is how peers behind a NAT
+/// are reached before (or instead of) a hole-punched direct connection.
+pub fn with_relay(
+    id: &identity::Keypair,
+    transport: Boxed<(PeerId, StreamMuxerBox)>,
+    relay: relay::client::Transport,
+) -> Result<Boxed<(PeerId, StreamMuxerBox)>, Box<dyn std::error::Error>> {
+    let relayed = relay
+        .upgrade(upgrade::Version::V1)
+        .authenticate(noise::Config::new(id)?)
+        .multiplex(yamux::Config::default())
+        .map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)));
+    let transport = relayed
+        .or_transport(transport)
+        .map(|either, _| match either {
+            Either::Left(output) | Either::Right(output) => output,
+        })
+        .boxed();
+    Ok(transport)
+}
+
+/// How a node deals with NATs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NatConfig {
+    /// Relay circuits for NATed peers. Only nodes that are publicly
+    /// reachable should turn this on.
+    pub relay_server: bool,
+}
+
+/// Whether `addr` goes through a relay (contains `/p2p-circuit`).
+pub fn is_relayed(addr: &Multiaddr) -> bool {
+    addr.iter().any(|p| matches!(p, Protocol::P2pCircuit))
+}
+
+/// The address to listen on to accept connections relayed by `relay` at
+/// `relay_addr`.
+pub fn relay_listen_address(relay_addr: &Multiaddr, relay: PeerId) -> Multiaddr {
+    let mut addr = relay_addr.clone();
+    if !matches!(addr.iter().last(), Some(Protocol::P2p(_))) {
+        addr.push(Protocol::P2p(relay));
+    }
+    addr.with(Protocol::P2pCircuit)
+}
+
+pub fn create_behaviour(
+    id: &identity::Keypair,
+    relay_client: relay::client::Behaviour,
+    nat: NatConfig,
+    mdns: Option<mdns::tokio::Behaviour>,
+) -> Behaviour {
+    let peer_id = PeerId::from(id.public());
     let ping = libp2p::ping::Behaviour::default();
     let cfg = RequestResponseConfig::default();
-    let protocols = std::iter::once(("/job/1.0.0".to_string(), ProtocolSupport::Full));
+    let protocols = codec::protocols().into_iter().map(|p| (p, ProtocolSupport::Full));
     let req = libp2p::request_response::Behaviour::new(protocols, cfg);
-    Behaviour { ping, req }
+    let identify = identify::Behaviour::new(identify::Config::new("/bcai/1.0.0".to_string(), id.public()));
+    let autonat = autonat::Behaviour::new(peer_id, autonat::Config::default());
+    let relay = nat
+        .relay_server
+        .then(|| relay::Behaviour::new(peer_id, relay::Config::default()))
+        .into();
+    let dcutr = dcutr::Behaviour::new(peer_id);
+    Behaviour { ping, req, identify, autonat, relay, relay_client, dcutr, mdns: mdns.into() }
 }
 
-pub fn create_swarm<T>(transport: T, behaviour: Behaviour, peer_id: PeerId) -> Swarm<Behaviour>
-where
-    T: Transport + Clone + Send + Unpin + 'static,
-    T::Output: Send + Unpin,
-    T::Error: Send + Sync + std::error::Error,
-    T::Dial: Send,
-    T::ListenerUpgrade: Send,
-{
+pub fn create_swarm(
+    transport: Boxed<(PeerId, StreamMuxerBox)>,
+    behaviour: Behaviour,
+    peer_id: PeerId,
+) -> Swarm<Behaviour> {
     Swarm::new(transport, behaviour, peer_id, libp2p::swarm::Config::with_tokio_executor())
-} 
\ No newline at end of file
+}
+
+/// mDNS discovery for the node with key `id`.
+pub fn create_mdns(id: &identity::Keypair) -> std::io::Result<mdns::tokio::Behaviour> {
+    mdns::tokio::Behaviour::new(mdns::Config::default(), PeerId::from(id.public()))
+}
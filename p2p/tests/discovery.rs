@@ -0,0 +1,94 @@
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
+use p2p::discovery::{DiscoveryError, MAX_FAILURES};
+use p2p::{DiscoveryConfig, Node, NodeEvent, PeerStore};
+use std::path::PathBuf;
+use tokio::time::{timeout, Duration};
+
+fn addr(s: &str) -> Multiaddr {
+    s.parse().unwrap()
+}
+
+fn store_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bcai-peers-{name}-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn peer_store_keeps_recent_good_peers() {
+    let path = store_path("store");
+    let (old, fresh, flaky) = (PeerId::random(), PeerId::random(), PeerId::random());
+    let mut store = PeerStore::open(&path).unwrap().with_capacity(2);
+    store.record_seen(old, addr("/ip4/10.0.0.1/tcp/1"), 100);
+    store.record_seen(fresh, addr(&format!("/ip4/10.0.0.2/tcp/2/p2p/{fresh}")), 300);
+    store.record_seen(fresh, addr("/ip4/10.0.0.2/udp/2/quic-v1"), 300);
+    assert_eq!(store.get(&fresh).unwrap().addrs[1], addr("/ip4/10.0.0.2/tcp/2"));
+
+    // Over capacity, the least recently seen peer goes.
+    store.record_seen(flaky, addr("/ip4/10.0.0.3/tcp/3"), 200);
+    assert!(store.get(&old).is_none());
+    assert_eq!(store.candidates(300, 10).iter().map(|(p, _)| *p).collect::<Vec<_>>(), vec![fresh, flaky]);
+    for _ in 0..MAX_FAILURES {
+        store.record_failure(&flaky);
+    }
+    assert!(store.get(&flaky).is_none());
+    // Peers too old to trust are kept but not redialed.
+    assert!(store.clone().with_max_age(60).candidates(1_000, 10).is_empty());
+
+    store.save().unwrap();
+    let reopened = PeerStore::open(&path).unwrap();
+    assert_eq!(reopened.get(&fresh), store.get(&fresh));
+    assert_eq!(reopened.len(), 1);
+
+    std::fs::write(&path, b"not json").unwrap();
+    assert!(matches!(PeerStore::open(&path), Err(DiscoveryError::Corrupt(_))));
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Polls `node` and `other` until `node` agrees a session with `peer`.
+async fn connect(node: &mut Node, other: &mut Node, peer: PeerId) {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            tokio::select! {
+                e = node.next_event() => if matches!(e, NodeEvent::Handshake { peer: p, .. } if p == peer) { break },
+                _ = other.next_event() => {}
+            }
+        }
+    })
+    .await
+    .expect("no connection");
+}
+
+#[tokio::test]
+async fn restarted_nodes_rejoin_from_the_peer_store() {
+    let path = store_path("rejoin");
+    let config = DiscoveryConfig { peer_store: Some(path.clone()), ..DiscoveryConfig::default() };
+    let mut a = Node::new(1, 0);
+    let a_addr = a.listen();
+
+    let mut b = Node::new_memory_with_discovery(config.clone(), 1, 0).unwrap();
+    assert_eq!(b.rejoin(), 0);
+    b.dial(a_addr.clone());
+    connect(&mut b, &mut a, a.peer_id).await;
+    assert_eq!(b.peers().get(&a.peer_id).unwrap().addrs, vec![a_addr]);
+    drop(b);
+
+    // No bootstrap list: the stored peer is all the new node has.
+    let mut restarted = Node::new_memory_with_discovery(config, 1, 0).unwrap();
+    assert_eq!(restarted.peers().len(), 1);
+    assert_eq!(restarted.rejoin(), 1);
+    connect(&mut restarted, &mut a, a.peer_id).await;
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn bootstrap_addresses_are_the_fallback() {
+    let mut a = Node::new(1, 0);
+    let bootstrap = a.listen().with(Protocol::P2p(a.peer_id));
+    let config = DiscoveryConfig { bootstrap: vec![bootstrap], ..DiscoveryConfig::default() };
+    let mut b = Node::new_memory_with_discovery(config, 1, 0).unwrap();
+    assert_eq!(b.rejoin(), 1);
+    connect(&mut b, &mut a, a.peer_id).await;
+    // The bootstrap node is remembered for the next restart.
+    assert_eq!(b.peers().len(), 1);
+}
@@ -0,0 +1,89 @@
+use p2p::codec::{decode_frame, encode_frame, protocol_name, protocols, version_of, PROTOCOL_VERSION};
+use p2p::{HandshakeError, Hello, JobRequest, Node, NodeEvent};
+use std::collections::BTreeSet;
+use tokio::time::{timeout, Duration};
+
+fn hello(min_version: u8, max_version: u8, features: &[&str]) -> Hello {
+    Hello { min_version, max_version, features: features.iter().map(|f| f.to_string()).collect() }
+}
+
+#[test]
+fn frames_carry_the_negotiated_version() {
+    assert_eq!(protocols()[0], protocol_name(PROTOCOL_VERSION));
+    assert_eq!(version_of("/bcai/job/3"), Some(3));
+    assert_eq!(version_of("/job/1.0.0"), None);
+
+    let bytes = encode_frame(&protocol_name(1), &JobRequest::Train(vec![1, 2])).unwrap();
+    assert_eq!(bytes[0], 1);
+    assert!(matches!(decode_frame(&protocol_name(1), &bytes).unwrap(), JobRequest::Train(d) if d == [1, 2]));
+    assert!(decode_frame::<JobRequest>(&protocol_name(2), &bytes).is_err());
+    assert!(decode_frame::<JobRequest>(&protocol_name(1), &[]).is_err());
+}
+
+#[test]
+fn hellos_agree_on_versions_and_features() {
+    let ours = hello(1, 3, &["train-linear", "onion"]);
+    let session = ours.negotiate(&hello(2, 5, &["train-linear", "zk"])).unwrap();
+    assert_eq!(session.version, 3);
+    assert_eq!(session.features, BTreeSet::from(["train-linear".to_string()]));
+    assert!(session.supports("train-linear") && !session.supports("onion"));
+    assert_eq!(
+        ours.negotiate(&hello(4, 5, &[])),
+        Err(HandshakeError::Incompatible { local_min: 1, local_max: 3, remote_min: 4, remote_max: 5 })
+    );
+}
+
+#[tokio::test]
+async fn connected_nodes_exchange_hellos() {
+    let mut a = Node::new(1, 0);
+    let mut b = Node::new(1, 0);
+    let addr = a.listen();
+    b.dial(addr);
+
+    let (a_id, b_id) = (a.peer_id, b.peer_id);
+    let mut settled = (false, false);
+    timeout(Duration::from_secs(5), async {
+        while settled != (true, true) {
+            tokio::select! {
+                e = a.next_event() => if let NodeEvent::Handshake { peer, session } = e {
+                    assert_eq!((peer, session.version), (b_id, PROTOCOL_VERSION));
+                    settled.0 = true;
+                },
+                e = b.next_event() => if let NodeEvent::Handshake { peer, .. } = e {
+                    assert_eq!(peer, a_id);
+                    settled.1 = true;
+                },
+            }
+        }
+    })
+    .await
+    .expect("handshake timeout");
+    assert_eq!(a.session(&b_id), b.session(&a_id));
+    assert!(a.session(&b_id).unwrap().supports("train-linear"));
+}
+
+#[tokio::test]
+async fn nodes_without_a_common_version_part() {
+    let mut a = Node::new(1, 0);
+    let mut b = Node::new(1, 0);
+    b.advertise(hello(PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 1, &[]));
+    let addr = a.listen();
+    b.dial(addr);
+
+    let mut refused = (false, false);
+    timeout(Duration::from_secs(5), async {
+        while refused != (true, true) {
+            tokio::select! {
+                e = a.next_event() => if let NodeEvent::Incompatible { .. } = e { refused.0 = true },
+                e = b.next_event() => if let NodeEvent::Incompatible { error, .. } = e {
+                    assert!(matches!(error, HandshakeError::Incompatible { .. }));
+                    refused.1 = true;
+                },
+            }
+        }
+    })
+    .await
+    .expect("incompatible peers were not told apart");
+    assert!(a.session(&b.peer_id).is_none());
+    assert!(b.session(&a.peer_id).is_none());
+}
@@ -0,0 +1,57 @@
+use libp2p::{relay, Multiaddr, PeerId};
+use p2p::transport::{is_relayed, relay_listen_address};
+use p2p::{NatConfig, Node, NodeEvent};
+use tokio::time::{timeout, Duration};
+
+fn addr(s: &str) -> Multiaddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn relay_addresses_end_in_a_circuit() {
+    let relay = PeerId::random();
+    let listen = relay_listen_address(&addr("/ip4/10.0.0.1/tcp/4001"), relay);
+    assert_eq!(listen, addr(&format!("/ip4/10.0.0.1/tcp/4001/p2p/{relay}/p2p-circuit")));
+    // An address that already names the relay is not given a second /p2p.
+    let named = addr(&format!("/ip4/10.0.0.1/tcp/4001/p2p/{relay}"));
+    assert_eq!(relay_listen_address(&named, relay), listen);
+    assert!(is_relayed(&listen));
+    assert!(!is_relayed(&named));
+}
+
+#[tokio::test]
+async fn nated_peers_ping_through_a_relay() {
+    let mut relay = Node::new_memory_with_nat(NatConfig { relay_server: true }, 1, 0);
+    let mut a = Node::new(1, 0);
+    let mut b = Node::new(1, 0);
+    let relay_addr = relay.listen();
+    relay.add_external_address(relay_addr.clone());
+    let circuit = a.listen_via_relay(relay.peer_id, relay_addr);
+    assert!(is_relayed(&circuit));
+
+    let a_id = a.peer_id;
+    let b_id = b.peer_id;
+    let res = timeout(Duration::from_secs(20), async {
+        loop {
+            tokio::select! {
+                _ = relay.next_event() => {}
+                e = a.next_event() => {
+                    if let NodeEvent::RelayClient(relay::client::Event::ReservationReqAccepted { .. }) = e {
+                        b.dial(circuit.clone());
+                    }
+                    if matches!(e, NodeEvent::Ping(ref p) if p.peer == b_id && p.result.is_ok()) {
+                        break;
+                    }
+                }
+                e = b.next_event() => {
+                    if matches!(e, NodeEvent::Ping(ref p) if p.peer == a_id && p.result.is_ok()) {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+    .await;
+
+    assert!(res.is_ok(), "no ping through the relay");
+}
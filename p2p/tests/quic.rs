@@ -0,0 +1,67 @@
+use libp2p::Multiaddr;
+use p2p::transport::order_addresses;
+use p2p::{Node, NodeEvent, TransportPreference};
+use tokio::time::{timeout, Duration};
+
+fn addr(s: &str) -> Multiaddr {
+    s.parse().unwrap()
+}
+
+fn free_udp_port() -> u16 {
+    std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+fn free_tcp_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+async fn ping(a: &mut Node, b: &mut Node, within: Duration) -> bool {
+    timeout(within, async {
+        loop {
+            tokio::select! {
+                e = a.next_event() => if matches!(e, NodeEvent::Ping(_)) { break },
+                e = b.next_event() => if matches!(e, NodeEvent::Ping(_)) { break },
+            }
+        }
+    })
+    .await
+    .is_ok()
+}
+
+#[test]
+fn quic_addresses_are_dialed_first() {
+    let tcp = addr("/ip4/10.0.0.1/tcp/4001");
+    let quic = addr("/ip4/10.0.0.1/udp/4001/quic-v1");
+    let both = || vec![tcp.clone(), quic.clone()];
+    assert_eq!(order_addresses(both(), TransportPreference::QuicFirst), vec![quic.clone(), tcp.clone()]);
+    assert_eq!(order_addresses(both(), TransportPreference::TcpFirst), vec![tcp.clone(), quic.clone()]);
+    assert_eq!(order_addresses(both(), TransportPreference::QuicOnly), vec![quic.clone()]);
+    assert_eq!(order_addresses(both(), TransportPreference::TcpOnly), vec![tcp.clone()]);
+}
+
+#[tokio::test]
+async fn nodes_can_ping_over_quic() {
+    let mut a = Node::with_transport(TransportPreference::QuicOnly, 1, 0);
+    let mut b = Node::with_transport(TransportPreference::QuicOnly, 1, 0);
+    let port = free_udp_port();
+    a.listen_quic(port);
+    b.dial_peer(a.peer_id, vec![addr(&format!("/ip4/127.0.0.1/udp/{port}/quic-v1"))]);
+
+    assert!(ping(&mut a, &mut b, Duration::from_secs(10)).await, "ping timeout");
+}
+
+#[tokio::test]
+async fn failed_quic_dials_fall_back_to_tcp() {
+    let mut a = Node::with_transport(TransportPreference::QuicFirst, 1, 0);
+    let mut b = Node::with_transport(TransportPreference::QuicFirst, 1, 0);
+    let port = free_tcp_port();
+    a.listen_tcp(port);
+    // Nothing listens for QUIC, so the first attempt fails.
+    let addrs = vec![
+        addr(&format!("/ip4/127.0.0.1/tcp/{port}")),
+        addr(&format!("/ip4/127.0.0.1/udp/{}/quic-v1", free_udp_port())),
+    ];
+    b.dial_peer(a.peer_id, addrs);
+
+    assert!(ping(&mut a, &mut b, Duration::from_secs(20)).await, "ping timeout");
+}
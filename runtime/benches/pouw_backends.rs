@@ -0,0 +1,56 @@
+//! PoUW training backends.
+//!
+//! Before timing anything, every available backend trains the same tasks and
+//! must produce the CPU's checkpoints exactly; a backend that diverges would
+//! mine solutions verifiers reject. Build with `--features wgpu-solver` to
+//! include the GPU.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use runtime::pouw::backend::{CpuBackend, GradientBackend};
+use runtime::pouw::{generate_task, training};
+
+fn backends() -> Vec<&'static dyn GradientBackend> {
+    let mut backends: Vec<&'static dyn GradientBackend> = vec![&CpuBackend];
+    #[cfg(feature = "wgpu-solver")]
+    if let Some(gpu) = runtime::pouw::backend::WgpuBackend::shared() {
+        backends.push(gpu);
+    }
+    backends
+}
+
+fn assert_consistent(backends: &[&dyn GradientBackend]) {
+    for seed in 0..8 {
+        let mut task = generate_task(1, seed);
+        task.epochs = 64;
+        let reference = training::train_with(&CpuBackend, &task);
+        for backend in backends {
+            assert_eq!(
+                training::train_with(*backend, &task),
+                reference,
+                "{} diverges from the CPU on seed {}",
+                backend.name(),
+                seed
+            );
+        }
+    }
+}
+
+fn bench_training(c: &mut Criterion) {
+    let backends = backends();
+    assert_consistent(&backends);
+
+    let mut group = c.benchmark_group("pouw_training");
+    for epochs in [16u32, 128] {
+        let mut task = generate_task(1, 42);
+        task.epochs = epochs;
+        for backend in &backends {
+            group.bench_with_input(BenchmarkId::new(backend.name(), epochs), &task, |b, task| {
+                b.iter(|| training::train_with(*backend, black_box(task)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_training);
+criterion_main!(benches);
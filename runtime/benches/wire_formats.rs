@@ -0,0 +1,90 @@
+//! Wire message encodings.
+//!
+//! Encodes every kind of wire message in every format this build speaks,
+//! prints the encoded sizes, then times encoding and decoding. Build with
+//! `--features wire-rkyv` to include rkyv.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use runtime::blockchain::{Block, Blockchain, BlockchainConfig, Transaction};
+use runtime::wire::format::WireFormat;
+use runtime::wire::WireMessage;
+
+fn transaction(nonce: u64) -> Transaction {
+    let mut tx = Transaction::new("alice".into(), "bob".into(), 1_000 + nonce, 10, nonce);
+    tx.signature = Some("ab".repeat(64));
+    tx
+}
+
+fn block(index: u32) -> Block {
+    let mut block = Blockchain::new(BlockchainConfig::default()).get_tip().clone();
+    block.index = index;
+    block.transactions = (0..32).map(transaction).collect();
+    block
+}
+
+fn messages() -> Vec<(&'static str, WireMessage)> {
+    vec![
+        ("ping", WireMessage::Ping),
+        ("pong", WireMessage::Pong),
+        ("get_blocks", WireMessage::GetBlocks { from_height: 1_234 }),
+        ("transaction", WireMessage::Transaction(transaction(7))),
+        ("block", WireMessage::Block(block(1))),
+        ("blocks", WireMessage::Blocks((1..=16).map(block).collect())),
+    ]
+}
+
+fn print_sizes(messages: &[(&str, WireMessage)]) {
+    let formats = WireFormat::all();
+    print!("{:<12}", "message");
+    for format in &formats {
+        print!("{:>10}", format.name());
+    }
+    println!();
+    for (name, message) in messages {
+        print!("{:<12}", name);
+        for format in &formats {
+            let bytes = format.encode(message).expect("encodes");
+            let decoded: WireMessage = format.decode(&bytes).expect("decodes");
+            assert_eq!(
+                format.encode(&decoded).unwrap(),
+                bytes,
+                "{} changes {}",
+                format.name(),
+                name
+            );
+            print!("{:>10}", bytes.len());
+        }
+        println!();
+    }
+}
+
+fn bench_formats(c: &mut Criterion) {
+    let messages = messages();
+    print_sizes(&messages);
+
+    let mut encode = c.benchmark_group("wire_encode");
+    for (name, message) in &messages {
+        for format in WireFormat::all() {
+            encode.bench_with_input(
+                BenchmarkId::new(format.name(), name),
+                message,
+                |b, message| b.iter(|| format.encode(black_box(message)).unwrap()),
+            );
+        }
+    }
+    encode.finish();
+
+    let mut decode = c.benchmark_group("wire_decode");
+    for (name, message) in &messages {
+        for format in WireFormat::all() {
+            let bytes = format.encode(message).unwrap();
+            decode.bench_with_input(BenchmarkId::new(format.name(), name), &bytes, |b, bytes| {
+                b.iter(|| format.decode::<WireMessage>(black_box(bytes)).unwrap())
+            });
+        }
+    }
+    decode.finish();
+}
+
+criterion_group!(benches, bench_formats);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! Verifies a chain archive exported to a directory.
+
+use clap::Parser;
+use runtime::blockchain::archive::{verify_archive, DirectoryStore};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "archive-verify")]
+#[command(about = "Check the manifests, objects and block hash chain of a BCAI chain archive")]
+struct Cli {
+    /// Root directory of the archive.
+    dir: PathBuf,
+    /// Hex ed25519 key every manifest must be signed by.
+    #[arg(long)]
+    signer: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let store = DirectoryStore::new(&cli.dir);
+    let result = store.manifests().and_then(|manifests| verify_archive(&store, &manifests, cli.signer.as_deref()));
+    match result {
+        Ok(report) => {
+            println!(
+                "ok: {} ranges, {} blocks, {} snapshots, last block {}",
+                report.ranges,
+                report.blocks,
+                report.snapshots,
+                report.last_hash.as_deref().unwrap_or("-")
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("archive verification failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Signs consensus votes and block proposals for a validator daemon over a
+//! local Unix socket, keeping the validator key out of the daemon.
+
+use clap::Parser;
+use ed25519_dalek::SigningKey;
+use runtime::consensus_engine::{ConsensusSigner, LocalSigner, SignerServer};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::net::UnixListener;
+
+#[derive(Parser)]
+#[command(name = "remote-signer")]
+#[command(about = "Sign BCAI consensus messages for a validator over a Unix socket")]
+struct Cli {
+    /// Socket to listen on.
+    #[arg(long)]
+    socket: PathBuf,
+    /// File holding the hex ed25519 seed of the validator key.
+    #[arg(long)]
+    key_file: PathBuf,
+    /// File holding the secret clients authenticate with.
+    #[arg(long)]
+    secret_file: PathBuf,
+    /// Where to keep the last height and round signed, so a restart cannot
+    /// be tricked into signing twice.
+    #[arg(long)]
+    state_file: PathBuf,
+    /// Refuse to sign at or below this height, e.g. the chain's tip when
+    /// the state file was restored from a backup.
+    #[arg(long)]
+    min_height: Option<u32>,
+}
+
+fn read_key(path: &Path) -> Result<SigningKey, Box<dyn std::error::Error>> {
+    let seed = hex::decode(std::fs::read_to_string(path)?.trim())?;
+    let seed = <[u8; 32]>::try_from(seed).map_err(|_| "key seed must be 32 bytes")?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let signer = LocalSigner::new(read_key(&cli.key_file)?).with_state_path(&cli.state_file)?;
+    if let Some(height) = cli.min_height {
+        signer.guard().raise_floor(height)?;
+    }
+    let secret = std::fs::read(&cli.secret_file)?;
+    if cli.socket.exists() {
+        std::fs::remove_file(&cli.socket)?;
+    }
+    let listener = UnixListener::bind(&cli.socket)?;
+    // Only the owner may connect; the secret guards against the rest.
+    std::fs::set_permissions(&cli.socket, std::fs::Permissions::from_mode(0o600))?;
+    println!("signing as {} on {}", signer.public_key(), cli.socket.display());
+    Arc::new(SignerServer::new(signer, &secret)).serve(listener).await?;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("remote signer failed: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -353,8 +353,10 @@ async fn create_dfs_infrastructure(
             gpus: 0,
             gpu_memory_gb: 0,
             available_stake: 10000,
-            reputation: 100,
+            reputation: runtime::node::Reputation::uniform(100),
             capability_types: vec![CapabilityType::Storage, CapabilityType::Network],
+            min_benchmark_score: 0,
+            benchmark: None,
         },
         10000,
     )));
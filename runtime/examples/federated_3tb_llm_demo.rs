@@ -50,13 +50,15 @@ async fn demo_system_initialization() -> Result<(), Box<dyn std::error::Error>>
         gpus: 4,
         gpu_memory_gb: 64,
         available_stake: 100_000,
-        reputation: 100,
+        reputation: runtime::node::Reputation::uniform(100),
         capability_types: vec![
             CapabilityType::GpuAccelerated,
             CapabilityType::HighMemory,
             CapabilityType::Training,
             CapabilityType::Network,
         ],
+        min_benchmark_score: 0,
+        benchmark: None,
     };
     
     let coordinator_node = UnifiedNode::new(
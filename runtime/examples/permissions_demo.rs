@@ -27,8 +27,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             gpus: 2,
             gpu_memory_gb: 16,
             available_stake: 5000,
-            reputation: 100,
+            reputation: runtime::node::Reputation::uniform(100),
             capability_types: vec![CapabilityType::Storage, CapabilityType::Inference],
+            min_benchmark_score: 0,
+            benchmark: None,
         },
         5000,
     )));
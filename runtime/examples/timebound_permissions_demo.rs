@@ -55,8 +55,10 @@ async fn setup_demo_infrastructure() -> Result<Arc<DecentralizedFilesystem>, Box
             gpus: 2,
             gpu_memory_gb: 16,
             available_stake: 10000,
-            reputation: 100,
+            reputation: runtime::node::Reputation::uniform(100),
             capability_types: vec![CapabilityType::Storage, CapabilityType::BasicCompute],
+            min_benchmark_score: 0,
+            benchmark: None,
         },
         10000, // Initial tokens
     );
@@ -0,0 +1,463 @@
+//! Long-term archive of finalized chain history.
+//!
+//! An [`Archiver`] exports finalized blocks, in fixed-size ranges, to cold
+//! storage behind an [`ArchiveStore`]: a directory, an S3 bucket or the DFS
+//! chunk store. Each range is described by an [`ArchiveManifest`] holding
+//! the hashes of the exported objects and of the previous manifest, signed
+//! by the archiving node. Ranges can also carry a snapshot of the state.
+//! [`verify_archive`] re-checks a whole archive — signatures, manifest
+//! links, object hashes and the hash chain of the blocks — so an archival
+//! node can drop history it has exported and still prove what it was.
+//!
+//! A block is final once it is deeper than the challenge window, after
+//! which its PoUW reward can no longer be reverted by a fraud proof.
+
+use super::block::Block;
+use super::chain::Blockchain;
+use super::constants::CHALLENGE_WINDOW_BLOCKS;
+use super::state::State;
+use crate::large_data_transfer::chunk::{ChunkId, DataChunk};
+use crate::large_data_transfer::config::CompressionAlgorithm;
+use crate::large_data_transfer::manager::ChunkManager;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Store key prefix of manifests.
+pub const MANIFEST_DIR: &str = "manifests";
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("archive I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("archive store failed: {0}")]
+    Store(String),
+    #[error("archive object {0} not found")]
+    NotFound(String),
+    #[error("archive serialization failed: {0}")]
+    Serialization(String),
+    #[error("corrupt archive: {0}")]
+    Corrupt(String),
+}
+
+/// Cold storage for archive objects.
+pub trait ArchiveStore: Send + Sync {
+    /// Stores `bytes` under `key` and returns the location to read them
+    /// back from.
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<String, ArchiveError>;
+    fn get(&self, location: &str) -> Result<Vec<u8>, ArchiveError>;
+}
+
+/// Keys as paths under a local directory, e.g. a mounted volume.
+#[derive(Debug, Clone)]
+pub struct DirectoryStore {
+    root: PathBuf,
+}
+
+impl DirectoryStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, ArchiveError> {
+        let relative = Path::new(key);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(ArchiveError::Store(format!("bad key {:?}", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+
+    /// All manifests in the directory, ordered by their first block.
+    pub fn manifests(&self) -> Result<Vec<ArchiveManifest>, ArchiveError> {
+        let dir = self.root.join(MANIFEST_DIR);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut manifests = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let bytes = std::fs::read(entry?.path())?;
+            manifests.push(
+                serde_json::from_slice::<ArchiveManifest>(&bytes)
+                    .map_err(|e| ArchiveError::Serialization(e.to_string()))?,
+            );
+        }
+        manifests.sort_by_key(|m| m.from);
+        Ok(manifests)
+    }
+}
+
+impl ArchiveStore for DirectoryStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<String, ArchiveError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so a crash never leaves a truncated object.
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(partial, path)?;
+        Ok(key.to_string())
+    }
+
+    fn get(&self, location: &str) -> Result<Vec<u8>, ArchiveError> {
+        match std::fs::read(self.path(location)?) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(ArchiveError::NotFound(location.to_string())),
+            other => Ok(other?),
+        }
+    }
+}
+
+/// Objects in an S3 bucket, copied through the AWS CLI so that its usual
+/// credential and endpoint configuration applies.
+#[derive(Debug, Clone)]
+pub struct S3Store {
+    /// `s3://bucket/prefix`, without a trailing slash.
+    url: String,
+    cli: PathBuf,
+}
+
+impl S3Store {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.trim_end_matches('/').to_string(), cli: PathBuf::from("aws") }
+    }
+
+    /// Uses another S3-compatible CLI with the same `s3 cp` syntax.
+    pub fn with_cli(mut self, cli: impl Into<PathBuf>) -> Self {
+        self.cli = cli.into();
+        self
+    }
+}
+
+impl ArchiveStore for S3Store {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<String, ArchiveError> {
+        let location = format!("{}/{}", self.url, key);
+        let mut child = Command::new(&self.cli)
+            .args(["s3", "cp", "-", &location])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child.stdin.take().expect("stdin is piped").write_all(bytes)?;
+        let out = child.wait_with_output()?;
+        if !out.status.success() {
+            return Err(ArchiveError::Store(String::from_utf8_lossy(&out.stderr).trim().to_string()));
+        }
+        Ok(location)
+    }
+
+    fn get(&self, location: &str) -> Result<Vec<u8>, ArchiveError> {
+        if !location.starts_with(&format!("{}/", self.url)) {
+            return Err(ArchiveError::Store(format!("{} is outside {}", location, self.url)));
+        }
+        let out = Command::new(&self.cli).args(["s3", "cp", location, "-"]).stdin(Stdio::null()).output()?;
+        if !out.status.success() {
+            return Err(ArchiveError::NotFound(location.to_string()));
+        }
+        Ok(out.stdout)
+    }
+}
+
+/// Objects as LZ4-compressed chunks of the DFS chunk store, located by
+/// chunk id.
+#[derive(Debug, Clone)]
+pub struct DfsStore {
+    manager: Arc<ChunkManager>,
+}
+
+impl DfsStore {
+    pub fn new(manager: Arc<ChunkManager>) -> Self {
+        Self { manager }
+    }
+}
+
+impl ArchiveStore for DfsStore {
+    fn put(&self, _key: &str, bytes: &[u8]) -> Result<String, ArchiveError> {
+        let chunk = DataChunk::new_from_slice(bytes.to_vec(), 0, CompressionAlgorithm::Lz4)
+            .map_err(|e| ArchiveError::Store(e.to_string()))?;
+        let location = chunk.id().as_str().to_string();
+        self.manager.store_chunk(chunk).map_err(|e| ArchiveError::Store(e.to_string()))?;
+        Ok(location)
+    }
+
+    fn get(&self, location: &str) -> Result<Vec<u8>, ArchiveError> {
+        let id = ChunkId::from_hex(location).map_err(|e| ArchiveError::Store(e.to_string()))?;
+        let chunk = self.manager.get_chunk(&id).ok_or_else(|| ArchiveError::NotFound(location.to_string()))?;
+        chunk.decompress().map_err(|e| ArchiveError::Corrupt(e.to_string()))
+    }
+}
+
+/// An object written to the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveObject {
+    pub location: String,
+    /// Hex SHA-256 of the contents.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// A state snapshot exported with a range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedSnapshot {
+    /// Index of the block the state is as of.
+    pub height: u32,
+    pub object: ArchiveObject,
+}
+
+/// Signed description of one exported block range.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// First and last block of the range.
+    pub from: u32,
+    pub to: u32,
+    /// `prev_hash` of block `from` and hash of block `to`.
+    pub prev_hash: String,
+    pub last_hash: String,
+    /// The blocks as a JSON array.
+    pub blocks: ArchiveObject,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<ArchivedSnapshot>,
+    /// [`ArchiveManifest::hash`] of the previous range's manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_manifest: Option<String>,
+    pub created_at: i64,
+    /// Hex ed25519 key of the archiving node.
+    pub signer: String,
+    pub signature: Vec<u8>,
+}
+
+impl ArchiveManifest {
+    fn signing_bytes(&self) -> Vec<u8> {
+        let unsigned = Self { signature: Vec::new(), ..self.clone() };
+        let mut msg = b"bcai-archive-manifest".to_vec();
+        msg.extend(bincode::serialize(&unsigned).expect("serialize manifest"));
+        msg
+    }
+
+    /// Hex SHA-256 of the signed manifest.
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(bincode::serialize(self).expect("serialize manifest")))
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        let Ok(pk_bytes) = hex::decode(&self.signer) else { return false };
+        let Ok(pk_bytes) = <[u8; 32]>::try_from(pk_bytes) else { return false };
+        let Ok(vk) = VerifyingKey::from_bytes(&pk_bytes) else { return false };
+        let Ok(sig) = Signature::from_slice(&self.signature) else { return false };
+        vk.verify(&self.signing_bytes(), &sig).is_ok()
+    }
+
+    /// Store key of the manifest itself.
+    pub fn key(&self) -> String {
+        format!("{}/{:010}-{:010}.json", MANIFEST_DIR, self.from, self.to)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Blocks per exported range.
+    pub segment_blocks: u32,
+    /// Blocks a block must be buried under before it is exported.
+    pub finality_depth: u32,
+    /// Export a snapshot of the state with every range.
+    pub snapshots: bool,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self { segment_blocks: 1_000, finality_depth: CHALLENGE_WINDOW_BLOCKS, snapshots: true }
+    }
+}
+
+/// Exports finalized ranges of a chain, in order, picking up after the
+/// last range it exported.
+pub struct Archiver {
+    store: Arc<dyn ArchiveStore>,
+    key: SigningKey,
+    config: ArchiveConfig,
+    next: u32,
+    last_manifest: Option<String>,
+}
+
+impl Archiver {
+    pub fn new(store: Arc<dyn ArchiveStore>, key: SigningKey, config: ArchiveConfig) -> Self {
+        Self { store, key, config, next: 0, last_manifest: None }
+    }
+
+    /// Continues an archive whose latest range is described by `last`.
+    pub fn resume_after(mut self, last: &ArchiveManifest) -> Self {
+        self.next = last.to + 1;
+        self.last_manifest = Some(last.hash());
+        self
+    }
+
+    /// First block not yet exported.
+    pub fn next_block(&self) -> u32 {
+        self.next
+    }
+
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<ArchiveObject, ArchiveError> {
+        Ok(ArchiveObject {
+            location: self.store.put(key, bytes)?,
+            hash: hex::encode(Sha256::digest(bytes)),
+            size: bytes.len() as u64,
+        })
+    }
+
+    /// Exports every complete range of `chain` that is final, returning
+    /// their manifests.
+    pub fn archive_ready(&mut self, chain: &Blockchain) -> Result<Vec<ArchiveManifest>, ArchiveError> {
+        let segment = self.config.segment_blocks.max(1);
+        let tip = chain.get_tip().index;
+        let mut manifests = Vec::new();
+        loop {
+            let to = self.next.saturating_add(segment - 1);
+            if to.saturating_add(self.config.finality_depth) > tip {
+                break;
+            }
+            let blocks: Vec<&Block> = chain.blocks.iter().filter(|b| (self.next..=to).contains(&b.index)).collect();
+            if blocks.len() != segment as usize {
+                return Err(ArchiveError::Corrupt(format!("chain is missing blocks in {}..={}", self.next, to)));
+            }
+            let snapshot = self.config.snapshots.then_some((tip, &chain.state));
+            manifests.push(self.export(&blocks, snapshot)?);
+        }
+        Ok(manifests)
+    }
+
+    fn export(&mut self, blocks: &[&Block], snapshot: Option<(u32, &State)>) -> Result<ArchiveManifest, ArchiveError> {
+        let (first, last) = (blocks[0], blocks[blocks.len() - 1]);
+        let range = format!("{:010}-{:010}", first.index, last.index);
+        let blocks_object = self.put(&format!("blocks/{}.json", range), &json(&blocks)?)?;
+        let snapshot = match snapshot {
+            Some((height, state)) => Some(ArchivedSnapshot {
+                height,
+                object: self.put(&format!("state/{}-at-{:010}.json", range, height), &json(state)?)?,
+            }),
+            None => None,
+        };
+
+        let mut manifest = ArchiveManifest {
+            from: first.index,
+            to: last.index,
+            prev_hash: first.prev_hash.clone(),
+            last_hash: last.hash.clone(),
+            blocks: blocks_object,
+            snapshot,
+            prev_manifest: self.last_manifest.clone(),
+            created_at: chrono::Utc::now().timestamp(),
+            signer: hex::encode(self.key.verifying_key().to_bytes()),
+            signature: Vec::new(),
+        };
+        manifest.signature = self.key.sign(&manifest.signing_bytes()).to_bytes().to_vec();
+        self.store.put(&manifest.key(), &json(&manifest)?)?;
+
+        tracing::info!(from = manifest.from, to = manifest.to, "chain range archived");
+        self.next = last.index + 1;
+        self.last_manifest = Some(manifest.hash());
+        Ok(manifest)
+    }
+
+    /// Exports ready ranges of `chain` every `every`, forever.
+    pub async fn run(mut self, chain: Arc<tokio::sync::Mutex<Blockchain>>, every: std::time::Duration) {
+        let mut ticker = tokio::time::interval(every);
+        loop {
+            ticker.tick().await;
+            let result = {
+                let chain = chain.lock().await;
+                self.archive_ready(&chain)
+            };
+            if let Err(e) = result {
+                tracing::error!(error = %e, next = self.next, "chain archiving failed");
+            }
+        }
+    }
+}
+
+/// What [`verify_archive`] checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveReport {
+    pub ranges: usize,
+    pub blocks: u64,
+    pub snapshots: usize,
+    /// Hash of the last archived block.
+    pub last_hash: Option<String>,
+}
+
+fn json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, ArchiveError> {
+    serde_json::to_vec(value).map_err(|e| ArchiveError::Serialization(e.to_string()))
+}
+
+fn fetch(store: &dyn ArchiveStore, object: &ArchiveObject) -> Result<Vec<u8>, ArchiveError> {
+    let bytes = store.get(&object.location)?;
+    if bytes.len() as u64 != object.size || hex::encode(Sha256::digest(&bytes)) != object.hash {
+        return Err(ArchiveError::Corrupt(format!("{} does not match its manifest", object.location)));
+    }
+    Ok(bytes)
+}
+
+/// Reads back the blocks of a range, checking them against the manifest
+/// and their hash chain.
+pub fn restore_range(store: &dyn ArchiveStore, manifest: &ArchiveManifest) -> Result<Vec<Block>, ArchiveError> {
+    let blocks: Vec<Block> = serde_json::from_slice(&fetch(store, &manifest.blocks)?)
+        .map_err(|e| ArchiveError::Corrupt(format!("blocks {}..={}: {}", manifest.from, manifest.to, e)))?;
+    let mut prev_hash = manifest.prev_hash.as_str();
+    for (offset, block) in blocks.iter().enumerate() {
+        if block.index as u64 != manifest.from as u64 + offset as u64
+            || block.prev_hash != prev_hash
+            || block.hash != block.calculate_hash()
+        {
+            return Err(ArchiveError::Corrupt(format!("block {} breaks the hash chain", block.index)));
+        }
+        prev_hash = &block.hash;
+    }
+    if blocks.last().map(|b| b.index) != Some(manifest.to) || prev_hash != manifest.last_hash {
+        return Err(ArchiveError::Corrupt(format!("blocks {}..={} are incomplete", manifest.from, manifest.to)));
+    }
+    Ok(blocks)
+}
+
+/// Checks an archive given its manifests in order: each is signed, by
+/// `trusted_signer` when given, and links to the previous one; every object
+/// matches its hash; and the archived blocks form one hash chain.
+pub fn verify_archive(
+    store: &dyn ArchiveStore,
+    manifests: &[ArchiveManifest],
+    trusted_signer: Option<&str>,
+) -> Result<ArchiveReport, ArchiveError> {
+    let mut report = ArchiveReport { ranges: 0, blocks: 0, snapshots: 0, last_hash: None };
+    let mut previous: Option<&ArchiveManifest> = None;
+    for manifest in manifests {
+        let range = format!("{}..={}", manifest.from, manifest.to);
+        if !manifest.verify_signature() || trusted_signer.is_some_and(|s| s != manifest.signer) {
+            return Err(ArchiveError::Corrupt(format!("manifest {} is not signed by a trusted key", range)));
+        }
+        if let Some(previous) = previous {
+            let linked = manifest.prev_manifest.as_deref() == Some(previous.hash().as_str())
+                && manifest.from == previous.to + 1
+                && manifest.prev_hash == previous.last_hash;
+            if !linked {
+                return Err(ArchiveError::Corrupt(format!(
+                    "manifest {} does not follow {}..={}",
+                    range, previous.from, previous.to
+                )));
+            }
+        }
+        let blocks = restore_range(store, manifest)?;
+        if let Some(snapshot) = &manifest.snapshot {
+            serde_json::from_slice::<State>(&fetch(store, &snapshot.object)?)
+                .map_err(|e| ArchiveError::Corrupt(format!("snapshot of {}: {}", range, e)))?;
+            report.snapshots += 1;
+        }
+        report.ranges += 1;
+        report.blocks += blocks.len() as u64;
+        report.last_hash = Some(manifest.last_hash.clone());
+        previous = Some(manifest);
+    }
+    Ok(report)
+}
@@ -1,5 +1,6 @@
 use crate::blockchain::transaction::Transaction;
 use crate::pouw::{PoUWTask}; use crate::pouw::types::PoUWSolution;
+use crate::pouw::verifier::create_task_commitment;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -46,7 +47,9 @@ impl Block {
         block
     }
 
-    /// Calculates the block's hash based on its contents.
+    /// Calculates the block's hash based on its contents: the header, the
+    /// miner paid for it, the commitment to its PoUW task and the whole
+    /// solution, so none of them can be swapped on a relayed block.
     pub fn calculate_hash(&self) -> String {
         let mut hasher = Sha256::new();
         let tx_root = Transaction::merkle_root(&self.transactions);
@@ -60,6 +63,8 @@ impl Block {
             self.solution.trained_model_hash
         );
         hasher.update(contents);
+        let work = (&self.miner, create_task_commitment(&self.task), &self.solution);
+        hasher.update(bincode::serialize(&work).expect("serialize block work"));
         hex::encode(hasher.finalize())
     }
 
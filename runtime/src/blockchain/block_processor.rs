@@ -2,31 +2,92 @@ use crate::blockchain::{
     block::Block,
     constants::BLOCK_REWARD,
     error::BlockchainError,
-    state::BlockchainState,
+    state::{BlockchainState, PoUWClaim},
     validation,
 };
+use crate::blockchain::transaction::StorageTx;
+use crate::pouw::{evaluation::{self, ScoringConfig}, fraud, verifier::create_task_commitment, Retarget};
+use crate::trace::{stage_span, TraceContext};
 
 pub struct BlockProcessor;
 
 impl BlockProcessor {
-    /// Processes and validates a new block, applying transactions and rewarding the miner
+    /// Processes and validates a new block, applying transactions and rewarding the miner.
+    /// `expected` is the retarget the chain derived for the block's height.
     pub fn process_block(
         block: &Block,
         prev_block: &Block,
         state: &mut BlockchainState,
+        expected: &Retarget,
     ) -> Result<(), BlockchainError> {
         // Validate the block
-        validation::validate_block(block, prev_block, state)?;
+        validation::validate_block(block, prev_block, state, expected)?;
+        Self::apply_block(block, state)
+    }
 
-        // Apply transactions and calculate total fees
+    /// Applies an already validated block: its transactions, fees, rebates
+    /// and the miner's reward. Economic simulations call this directly to
+    /// run the real settlement code without mining. Settlement follows the
+    /// consensus parameters genesis put in `state`, never a node's config.
+    pub fn apply_block(
+        block: &Block,
+        state: &mut BlockchainState,
+    ) -> Result<(), BlockchainError> {
+        let params = state.consensus.clone();
+        // Apply transactions and calculate the fees left after the rebate
+        // burn
         let mut total_fees = 0;
         for tx in &block.transactions {
             state.apply_transaction(tx)?;
-            total_fees += tx.fee;
+            let burned = params.rebate.burn(tx.fee);
+            state.record_fee_burn(&tx.from, burned);
+            total_fees += tx.fee - burned;
         }
 
-        // Reward the miner
-        Self::reward_miner(block, total_fees, state)?;
+        // The block's task and the long tasks it registers are evaluated by
+        // validators selected now, so no submitter can choose them.
+        let opened = std::iter::once(hex::encode(create_task_commitment(&block.task))).chain(
+            block.transactions.iter().filter_map(|tx| match &tx.storage {
+                Some(StorageTx::RegisterLongTask { task }) => Some(task.id()),
+                _ => None,
+            }),
+        );
+        for task_id in opened.collect::<Vec<_>>() {
+            state.open_evaluation_task(&task_id, block.index, &block.hash, &params.validator_selection);
+        }
+
+        let settled = state.advance_evaluation_rounds(block.index, &params.commit_reveal);
+        if !settled.is_empty() {
+            tracing::debug!(block = block.index, ?settled, "evaluation rounds settled");
+        }
+        let failed = state.advance_storage_audits(block.index, &block.hash, &params.storage_audit);
+        if !failed.is_empty() {
+            tracing::debug!(block = block.index, ?failed, "storage audits failed");
+        }
+
+        // Reward the miner, open to fraud proofs for the challenge window
+        let reward = Self::reward_miner(block, total_fees, state, &params.scoring)?;
+        state.record_pouw_claim(
+            block.index,
+            PoUWClaim {
+                block_hash: block.hash.clone(),
+                miner: block.miner.clone(),
+                reward,
+                difficulty: block.difficulty,
+                evidence_hash: fraud::evidence_hash(&block.task, &block.solution),
+            },
+        );
+
+        // Jobs mined in this block are settled before timed out ones refund.
+        let refunded = state.advance_job_escrows(block.index);
+        if !refunded.is_empty() {
+            tracing::debug!(block = block.index, ?refunded, "job escrows timed out");
+        }
+
+        if params.rebate.is_epoch_end(block.index) {
+            let minted = state.settle_rebates(params.rebate.min_reputation);
+            tracing::debug!(epoch_end = block.index, minted, "fee rebates minted");
+        }
 
         // Record PoUW metrics for difficulty adjustment
         state.record_pouw_metrics(
@@ -37,14 +98,26 @@ impl BlockProcessor {
         Ok(())
     }
 
-    /// Rewards the miner with block reward plus transaction fees
+    /// Rewards the miner with the share of the block reward its solution's
+    /// score earns, the same share of the reward of the job it trained and
+    /// transaction fees, returning the amount paid
     fn reward_miner(
         block: &Block,
         total_fees: u64,
         state: &mut BlockchainState,
-    ) -> Result<(), BlockchainError> {
-        let accuracy_factor = block.solution.accuracy as f64 / 10000.0;
-        let base_reward = ((BLOCK_REWARD as f64) * accuracy_factor).round() as u64;
+        scoring: &ScoringConfig,
+    ) -> Result<u64, BlockchainError> {
+        let trace = TraceContext::continue_from(block.task.trace.as_deref());
+        let span = stage_span(trace.as_ref(), "settle");
+        let _entered = span.enter();
+        let score = evaluation::score(block.solution.accuracy, scoring);
+        let base_reward = score.reward_share(BLOCK_REWARD);
+        // The job's share is credited by the escrow release itself.
+        let job_reward = block
+            .task
+            .job_id
+            .and_then(|id| state.settle_job(id, &block.miner, &score))
+            .unwrap_or(0);
         let miner_reward = base_reward
             .checked_add(total_fees)
             .ok_or(BlockchainError::TransactionValidationError(
@@ -57,7 +130,8 @@ impl BlockProcessor {
             .ok_or(BlockchainError::TransactionValidationError(
                 "Miner balance overflow".to_string(),
             ))?;
+        tracing::debug!(miner = %block.miner, reward = miner_reward, job_reward, "miner rewarded");
 
-        Ok(())
+        Ok(miner_reward + job_reward)
     }
 } 
\ No newline at end of file
@@ -4,11 +4,14 @@ use crate::blockchain::{
     state::BlockchainState,
     transaction::Transaction,
     validation,
-    genesis::GenesisCreator,
+    genesis::{GenesisConfig, GenesisCreator},
     block_processor::BlockProcessor,
     account_manager::AccountManager,
+    constants::MAX_DEFERRED_PER_SENDER,
 };
-use std::collections::HashMap;
+use crate::pouw::difficulty::{self, BlockSample, Retarget};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 /// The main Blockchain struct, representing the distributed ledger.
 pub use crate::blockchain::error::BlockchainError;
@@ -21,6 +24,11 @@ pub struct Blockchain {
     pub config: BlockchainConfig,
     /// Pending transactions awaiting inclusion in a block.
     pub pending_transactions: Vec<Transaction>,
+    /// Time-locked transactions held back until their lock is satisfied.
+    pub deferred_transactions: Vec<Transaction>,
+    /// Genesis file the chain was initialised from; `None` for the built-in
+    /// development genesis.
+    pub genesis: Option<GenesisConfig>,
 }
 
 impl Blockchain {
@@ -32,36 +40,96 @@ impl Blockchain {
             account_nonces: HashMap::new(),
             config,
             pending_transactions: Vec::new(),
+            deferred_transactions: Vec::new(),
+            genesis: None,
         };
         blockchain.create_genesis_block();
         blockchain
     }
 
+    /// Loads `genesis.json` from `path` and builds the chain it describes.
+    pub fn from_genesis(path: impl AsRef<Path>) -> Result<Self, BlockchainError> {
+        Self::with_genesis(GenesisConfig::load(path)?)
+    }
+
+    /// Builds a chain from an in-memory genesis configuration.
+    pub fn with_genesis(genesis: GenesisConfig) -> Result<Self, BlockchainError> {
+        genesis.validate()?;
+        let mut blockchain = Self {
+            blocks: vec![GenesisCreator::create_genesis_block_from(&genesis)],
+            state: BlockchainState::new(),
+            account_nonces: HashMap::new(),
+            config: genesis.blockchain.clone(),
+            pending_transactions: Vec::new(),
+            deferred_transactions: Vec::new(),
+            genesis: None,
+        };
+        GenesisCreator::initialize_state_from(&genesis, &mut blockchain.state, &mut blockchain.account_nonces);
+        blockchain.genesis = Some(genesis);
+        Ok(blockchain)
+    }
+
     /// Creates the very first block in the chain.
     fn create_genesis_block(&mut self) {
         let genesis_block = GenesisCreator::create_genesis_block();
-        GenesisCreator::initialize_genesis_state(&mut self.state, &mut self.account_nonces);
+        GenesisCreator::initialize_genesis_state(&self.config, &mut self.state, &mut self.account_nonces);
         self.blocks.push(genesis_block);
     }
 
     /// Adds a new block to the chain, validating it and applying all its transactions to the state.
+    /// The block must use the difficulty and task size [`Blockchain::next_retarget`] expects.
     pub fn add_block(&mut self, block: Block) -> Result<(), BlockchainError> {
+        let expected = self.next_retarget();
         let prev_block = self.blocks.last().expect("Blockchain must have a genesis block");
-        BlockProcessor::process_block(&block, prev_block, &mut self.state)?;
+        BlockProcessor::process_block(&block, prev_block, &mut self.state, &expected)?;
+        for tx in &block.transactions {
+            self.account_nonces.insert(tx.from.clone(), self.state.get_nonce(&tx.from));
+        }
+        self.prune_pending(&block);
+        let timestamp = block.timestamp;
         self.blocks.push(block);
+        self.promote_deferred(timestamp);
         Ok(())
     }
 
+    /// Drops pending and deferred transactions `block` included, or whose
+    /// nonce it used up, so they stop counting towards
+    /// [`Blockchain::pending_nonce`].
+    fn prune_pending(&mut self, block: &Block) {
+        let included: HashSet<String> = block.transactions.iter().map(Transaction::hash).collect();
+        let nonces = &self.account_nonces;
+        let live = |tx: &Transaction| {
+            !included.contains(&tx.hash()) && tx.nonce >= AccountManager::get_nonce(nonces, &tx.from)
+        };
+        self.pending_transactions.retain(live);
+        self.deferred_transactions.retain(live);
+    }
+
     /// Validates a single transaction against the current confirmed state of the blockchain.
-    /// This is used to check if a transaction is valid for inclusion in the mempool.
+    /// This is used to check if a transaction is valid for inclusion in the mempool, so it must
+    /// carry the nonce following the sender's pending transactions.
     pub fn validate_transaction(&self, tx: &Transaction) -> Result<(), BlockchainError> {
-        validation::validate_transaction_with_state(tx, &self.state.balances, &self.account_nonces)
+        validation::validate_multisig_authorization(tx, &self.state)?;
+        let nonces = HashMap::from([(tx.from.clone(), self.pending_nonce(&tx.from))]);
+        validation::validate_transaction_with_state(tx, &self.state.balances, &nonces)
     }
 
+    /// The nonce the next transaction of an account must carry once mined.
     pub fn get_nonce(&self, pubkey_hex: &str) -> u64 {
         AccountManager::get_nonce(&self.account_nonces, pubkey_hex)
     }
 
+    /// The nonce the next transaction of an account must carry to enter the
+    /// mempool, i.e. [`Blockchain::get_nonce`] counting its pending
+    /// transactions.
+    pub fn pending_nonce(&self, pubkey_hex: &str) -> u64 {
+        self.pending_transactions
+            .iter()
+            .filter(|tx| tx.from == pubkey_hex)
+            .map(|tx| tx.nonce.saturating_add(1))
+            .fold(self.get_nonce(pubkey_hex), u64::max)
+    }
+
     pub fn get_balance(&self, pubkey_hex: &str) -> u64 {
         AccountManager::get_balance(&self.state, pubkey_hex)
     }
@@ -76,37 +144,117 @@ impl Blockchain {
         self.blocks.len() as u64
     }
 
-    /// Adds a transaction to the pending list after validation.
+    /// Adds a transaction to the pending list after validation. Transactions
+    /// whose time lock is not yet satisfied for the next block are parked in
+    /// the deferred pool, see [`Blockchain::check_deferrable`].
     pub fn add_transaction(&mut self, tx: Transaction) -> Result<(), BlockchainError> {
+        let now = chrono::Utc::now().timestamp();
+        if !tx.is_eligible(self.height(), now) {
+            self.check_deferrable(&tx)?;
+            self.deferred_transactions.push(tx);
+            return Ok(());
+        }
         self.validate_transaction(&tx)?;
         self.pending_transactions.push(tx);
         Ok(())
     }
 
+    /// Checks a time-locked transaction may wait in the deferred pool: it is
+    /// signed, its nonce is not used up or already deferred, the sender can
+    /// cover it along with the rest of its deferred transactions and has
+    /// fewer than [`MAX_DEFERRED_PER_SENDER`] waiting.
+    fn check_deferrable(&self, tx: &Transaction) -> Result<(), BlockchainError> {
+        validation::validate_transaction_stateless(tx)?;
+        let expected = self.pending_nonce(&tx.from);
+        let waiting: Vec<&Transaction> = self.deferred_transactions.iter().filter(|d| d.from == tx.from).collect();
+        if tx.nonce < expected || waiting.iter().any(|d| d.nonce == tx.nonce) {
+            return Err(BlockchainError::InvalidNonce { expected, got: tx.nonce });
+        }
+        if waiting.len() >= MAX_DEFERRED_PER_SENDER {
+            return Err(BlockchainError::TransactionValidationError(format!(
+                "{} already has {} deferred transactions",
+                tx.from, MAX_DEFERRED_PER_SENDER
+            )));
+        }
+        let required = waiting
+            .iter()
+            .chain([&tx])
+            .fold(0u64, |sum, d| sum.saturating_add(d.amount).saturating_add(d.fee));
+        let available = self.get_balance(&tx.from);
+        if available < required {
+            return Err(BlockchainError::InsufficientFunds { required, available });
+        }
+        Ok(())
+    }
+
+    /// Moves deferred transactions whose lock is satisfied for the next block
+    /// at `timestamp` into the pending list; [`Blockchain::add_block`] calls
+    /// this after every block. Transactions that no longer validate (e.g.
+    /// stale nonce) are dropped. Returns the number promoted.
+    pub fn promote_deferred(&mut self, timestamp: i64) -> usize {
+        let next_height = self.height();
+        let (mut ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.deferred_transactions)
+            .into_iter()
+            .partition(|tx| tx.is_eligible(next_height, timestamp));
+        self.deferred_transactions = waiting;
+        // A sender's deferred transactions may have arrived out of order.
+        ready.sort_by_key(|tx| tx.nonce);
+
+        let mut promoted = 0;
+        for tx in ready {
+            if self.validate_transaction(&tx).is_ok() {
+                self.pending_transactions.push(tx);
+                promoted += 1;
+            }
+        }
+        promoted
+    }
+
     /// Returns up to `limit` pending transactions.
     pub fn get_pending_transactions(&self, limit: usize) -> Vec<Transaction> {
         self.pending_transactions.iter().take(limit).cloned().collect()
     }
 
-    /// Adaptive difficulty based on recent PoUW metrics.
-    pub fn calculate_next_difficulty(&self) -> u32 {
-        let prev_difficulty = self.get_tip().difficulty;
-        let target_time = 60; // target solve time in seconds
-        let avg_time_ms = if self.state.pouw_metrics.is_empty() {
-            target_time * 1000
+    /// Difficulty and task size for the next block, retargeted from the
+    /// most recent blocks with the parameters fixed at genesis. The genesis
+    /// block is excluded from the window since its timestamp is fixed by the
+    /// genesis file. The task size carries on from the latest block mining
+    /// plain work, as job blocks train the job's own epochs, and the
+    /// solver-reported training time of a block counts for no more than the
+    /// time since its predecessor.
+    pub fn next_retarget(&self) -> Retarget {
+        let config = &self.state.consensus.retarget;
+        let tip = self.get_tip();
+        let current = if tip.index == 0 {
+            config.initial
         } else {
-            self.state
-                .pouw_metrics
+            let epochs = self.blocks[1..]
                 .iter()
-                .map(|m| m.1)
-                .sum::<u64>()
-                / self.state.pouw_metrics.len() as u64
+                .rev()
+                .find(|b| b.task.job_id.is_none())
+                .map_or(config.initial.epochs, |b| b.task.epochs);
+            Retarget { difficulty: tip.difficulty, epochs }
         };
-        crate::pouw::calculate_adaptive_difficulty(
-            prev_difficulty,
-            target_time,
-            avg_time_ms / 1000,
-        )
+        let start = self.blocks.len().saturating_sub(config.window).max(1);
+        let recent: Vec<BlockSample> = self.blocks[start..]
+            .iter()
+            .zip(&self.blocks[start - 1..])
+            .map(|(b, prev)| {
+                // Timestamps are whole seconds, so allow for one more.
+                let elapsed_ms = ((b.timestamp - prev.timestamp).max(0) as u64 + 1) * 1000;
+                BlockSample {
+                    timestamp: b.timestamp,
+                    epochs: b.task.epochs,
+                    training_ms: b.solution.training_time_ms.min(elapsed_ms),
+                }
+            })
+            .collect();
+        difficulty::retarget(config, current, &recent)
+    }
+
+    /// Difficulty for the next block, see [`Blockchain::next_retarget`].
+    pub fn calculate_next_difficulty(&self) -> u32 {
+        self.next_retarget().difficulty
     }
 
     /// Simple blockchain statistics.
@@ -114,6 +262,7 @@ impl Blockchain {
         BlockchainStats {
             height: self.height(),
             pending_txs: self.pending_transactions.len(),
+            deferred_txs: self.deferred_transactions.len(),
         }
     }
 }
@@ -123,4 +272,5 @@ impl Blockchain {
 pub struct BlockchainStats {
     pub height: u64,
     pub pending_txs: usize,
+    pub deferred_txs: usize,
 }
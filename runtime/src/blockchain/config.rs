@@ -1,14 +1,86 @@
+use crate::pouw::difficulty::RetargetConfig;
+use crate::blockchain::genesis::StoragePricing;
+use crate::blockchain::rebate::RebateConfig;
+use crate::distributed_storage::contracts::AuditConfig;
+use crate::pouw::commit_reveal::CommitRevealConfig;
+use crate::pouw::evaluation::ScoringConfig;
+use crate::pouw::{PoUWConfig, ValidatorSelectionConfig};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BlockchainConfig {
     pub max_transactions_per_block: usize,
+    /// Per-block difficulty and task-size retargeting.
+    #[serde(default)]
+    pub retarget: RetargetConfig,
+    /// Accuracy milestones that set the miner's share of the block reward.
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    /// Fee burn and epoch-end rebates for high-reputation payers.
+    #[serde(default)]
+    pub rebate: RebateConfig,
+    /// Phase lengths and penalties of commit-reveal evaluation rounds.
+    #[serde(default)]
+    pub commit_reveal: CommitRevealConfig,
+    /// Challenge interval, deadline and failure limit of storage audits.
+    #[serde(default)]
+    pub storage_audit: AuditConfig,
+}
+
+impl BlockchainConfig {
+    /// The parameters block settlement reads, which genesis fixes in the
+    /// chain state. Those a genesis file sets outside this config keep their
+    /// defaults.
+    pub fn consensus_params(&self) -> ConsensusParams {
+        ConsensusParams {
+            retarget: self.retarget.clone(),
+            scoring: self.scoring.clone(),
+            rebate: self.rebate.clone(),
+            commit_reveal: self.commit_reveal.clone(),
+            storage_audit: self.storage_audit.clone(),
+            ..ConsensusParams::default()
+        }
+    }
 }
 
 impl Default for BlockchainConfig {
     fn default() -> Self {
         Self {
             max_transactions_per_block: 1000,
+            retarget: RetargetConfig::default(),
+            scoring: ScoringConfig::default(),
+            rebate: RebateConfig::default(),
+            commit_reveal: CommitRevealConfig::default(),
+            storage_audit: AuditConfig::default(),
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Parameters every node must validate and settle blocks with alike. They
+/// are taken from the genesis configuration into [`State::consensus`], so a
+/// node's local config cannot change the difficulty and task size it
+/// expects, or how it verifies work, scores, burns, rebates, audits, selects
+/// validators or prices storage.
+///
+/// [`State::consensus`]: crate::blockchain::state::State::consensus
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConsensusParams {
+    #[serde(default)]
+    pub retarget: RetargetConfig,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub rebate: RebateConfig,
+    #[serde(default)]
+    pub commit_reveal: CommitRevealConfig,
+    #[serde(default)]
+    pub storage_audit: AuditConfig,
+    /// Task age and computation time limits PoUW solutions are checked
+    /// against.
+    #[serde(default)]
+    pub pouw: PoUWConfig,
+    #[serde(default)]
+    pub validator_selection: ValidatorSelectionConfig,
+    #[serde(default)]
+    pub storage_pricing: StoragePricing,
+}
@@ -1,6 +1,22 @@
 /// The reward a miner receives for successfully mining a new block.
 pub const BLOCK_REWARD: u64 = 100;
 
+/// Number of blocks after inclusion during which a block's PoUW solution can
+/// be challenged with a fraud proof.
+pub const CHALLENGE_WINDOW_BLOCKS: u32 = 100;
+
+/// Blocks an escrowed job may wait to be mined and have its milestones
+/// signed off before what is still escrowed returns to the poster, unless
+/// the job sets its own timeout.
+pub const JOB_TIMEOUT_BLOCKS: u32 = 10_000;
+
+/// Time-locked transactions one sender may have waiting in the deferred pool.
+pub const MAX_DEFERRED_PER_SENDER: usize = 16;
+
+/// Stake burned from a validator whose evaluation is flagged as an outlier
+/// when a consensus evaluation is recorded.
+pub const OUTLIER_SLASH: u64 = 10;
+
 /// The public key of the developer, pre-funded in the genesis block for testing.
 pub const DEV_PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee17e152516d0047913076135327269";
 
@@ -9,6 +25,3 @@ pub const DEV_FUNDING: u64 = 1_000_000_000;
 
 /// The context for signing transactions to prevent replay attacks on other systems.
 pub const SIGNING_CONTEXT: &[u8] = b"bcai-transaction";
-
-/// Public key authorised to submit UpdateMetrics admin transactions.
-pub const METRICS_ORACLE_PUB: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
\ No newline at end of file
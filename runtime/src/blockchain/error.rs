@@ -14,6 +14,8 @@ pub enum BlockchainError {
     InsufficientFunds { required: u64, available: u64 },
     #[error("Transaction validation failed: {0}")]
     TransactionValidationError(String),
+    #[error("Invalid genesis configuration: {0}")]
+    InvalidGenesis(String),
     #[error("No blocks in chain")]
     NoBlocksInChain,
 } 
\ No newline at end of file
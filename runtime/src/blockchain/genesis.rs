@@ -1,10 +1,168 @@
 use crate::blockchain::{
     block::Block,
-    constants::DEV_PUBLIC_KEY,
+    config::{BlockchainConfig, ConsensusParams},
+    constants::{DEV_FUNDING, DEV_PUBLIC_KEY},
+    error::BlockchainError,
     state::BlockchainState,
 };
-use crate::pouw::{PoUWTask}; use crate::pouw::types::PoUWSolution;
-use std::collections::HashMap;
+use crate::pouw::{PoUWConfig, PoUWTask, ValidatorSelectionConfig};
+use crate::pouw::types::PoUWSolution;
+use crate::large_data_transfer::{pricing, redundancy::RedundancyPolicy};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// A validator staked at genesis.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GenesisValidator {
+    /// Hex-encoded public key.
+    pub pubkey: String,
+    /// Tokens locked as stake; not counted in the account's balance.
+    pub stake: u64,
+}
+
+/// Storage pricing parameters in effect from genesis.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoragePricing {
+    /// Whole BCAI per GiB per stored copy.
+    pub price_per_gb_bcai: u128,
+    /// Extra copies kept when a client does not ask for a specific policy.
+    pub default_redundancy: u8,
+}
+
+impl Default for StoragePricing {
+    fn default() -> Self {
+        Self { price_per_gb_bcai: 1, default_redundancy: 2 }
+    }
+}
+
+impl StoragePricing {
+    /// Least a `StoreFile` may pay for `total_bytes` kept on `replicas`
+    /// nodes; no listed replicas means the default redundancy.
+    pub fn minimum_price(&self, total_bytes: u128, replicas: usize) -> u128 {
+        let copies = match replicas {
+            0 => self.default_redundancy,
+            n => u8::try_from(n - 1).unwrap_or(u8::MAX),
+        };
+        let policy = RedundancyPolicy { copies, geo_spread: false, erasure: None };
+        pricing::quote(total_bytes, policy, self.price_per_gb_bcai).price_bcai
+    }
+}
+
+/// Contents of a `genesis.json` file.
+///
+/// Balances are kept in a `BTreeMap` so the serialized form, and therefore
+/// [`GenesisConfig::hash`], does not depend on insertion order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    pub chain_id: String,
+    /// Unix timestamp written into the genesis block.
+    pub timestamp: i64,
+    #[serde(default)]
+    pub initial_balances: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub validators: Vec<GenesisValidator>,
+    #[serde(default)]
+    pub pouw: PoUWConfig,
+    #[serde(default)]
+    pub validator_selection: ValidatorSelectionConfig,
+    #[serde(default)]
+    pub storage_pricing: StoragePricing,
+    #[serde(default)]
+    pub blockchain: BlockchainConfig,
+}
+
+impl Default for GenesisConfig {
+    /// Single-node development genesis funding the developer key.
+    fn default() -> Self {
+        Self {
+            chain_id: "bcai-devnet".to_string(),
+            timestamp: 0,
+            initial_balances: BTreeMap::from([(DEV_PUBLIC_KEY.to_string(), DEV_FUNDING)]),
+            validators: Vec::new(),
+            pouw: PoUWConfig::default(),
+            validator_selection: ValidatorSelectionConfig::default(),
+            storage_pricing: StoragePricing::default(),
+            blockchain: BlockchainConfig::default(),
+        }
+    }
+}
+
+impl GenesisConfig {
+    /// Reads and validates a genesis file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BlockchainError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| BlockchainError::InvalidGenesis(format!("cannot read {}: {}", path.display(), e)))?;
+        let genesis: Self =
+            serde_json::from_slice(&bytes).map_err(|e| BlockchainError::InvalidGenesis(e.to_string()))?;
+        genesis.validate()?;
+        Ok(genesis)
+    }
+
+    /// Writes the genesis file as pretty-printed JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BlockchainError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| BlockchainError::InvalidGenesis(e.to_string()))?;
+        std::fs::write(path.as_ref(), json)
+            .map_err(|e| BlockchainError::InvalidGenesis(format!("cannot write {}: {}", path.as_ref().display(), e)))
+    }
+
+    /// Checks account keys, validator stakes and total supply.
+    pub fn validate(&self) -> Result<(), BlockchainError> {
+        let invalid = |msg: String| Err(BlockchainError::InvalidGenesis(msg));
+        if self.chain_id.trim().is_empty() {
+            return invalid("chain_id must not be empty".into());
+        }
+        let is_key = |k: &str| k.len() == 64 && k.chars().all(|c| c.is_ascii_hexdigit());
+        if let Some(account) = self.initial_balances.keys().find(|k| !is_key(k)) {
+            return invalid(format!("account {} is not a hex-encoded public key", account));
+        }
+        let mut seen = HashSet::new();
+        for validator in &self.validators {
+            if !is_key(&validator.pubkey) {
+                return invalid(format!("validator {} is not a hex-encoded public key", validator.pubkey));
+            }
+            if !seen.insert(&validator.pubkey) {
+                return invalid(format!("validator {} listed twice", validator.pubkey));
+            }
+            if validator.stake < self.validator_selection.min_stake {
+                return invalid(format!(
+                    "validator {} stakes {} below the minimum of {}",
+                    validator.pubkey, validator.stake, self.validator_selection.min_stake
+                ));
+            }
+        }
+        let supply = self
+            .initial_balances
+            .values()
+            .chain(self.validators.iter().map(|v| &v.stake))
+            .try_fold(0u64, |acc, v| acc.checked_add(*v));
+        if supply.is_none() {
+            return invalid("total supply overflows u64".into());
+        }
+        Ok(())
+    }
+
+    /// The consensus parameters the chain starts with: the `blockchain`
+    /// section plus the PoUW, validator selection and storage pricing
+    /// settings.
+    pub fn consensus_params(&self) -> ConsensusParams {
+        ConsensusParams {
+            pouw: self.pouw.clone(),
+            validator_selection: self.validator_selection.clone(),
+            storage_pricing: self.storage_pricing.clone(),
+            ..self.blockchain.consensus_params()
+        }
+    }
+
+    /// SHA-256 over the canonical JSON form. Used as the genesis block's
+    /// `prev_hash` so chains built from different files never share a tip.
+    pub fn hash(&self) -> String {
+        let bytes = serde_json::to_vec(self).expect("genesis config serializes");
+        hex::encode(Sha256::digest(bytes))
+    }
+}
 
 pub struct GenesisCreator;
 
@@ -16,15 +174,23 @@ impl GenesisCreator {
             dataset_id: "genesis_data".to_string(),
             model_hash: None,
             dataset_hash: None,
+            dataset_root: None,
             epochs: 0,
             timestamp: 0,
             challenge: [0u8; 32],
+            trace: None,
+            job_id: None,
+            kind: None,
         };
         let genesis_solution = PoUWSolution {
             trained_model_hash: "0".repeat(64),
             accuracy: 10000,
             nonce: 0,
             computation_time_ms: 0,
+            training_time_ms: 0,
+            checkpoints: Vec::new(),
+            gradient_roots: Vec::new(),
+            zk_proof: None,
         };
         
         Block::new(
@@ -38,15 +204,46 @@ impl GenesisCreator {
         )
     }
 
-    /// Initializes the genesis state with pre-funded developer account
+    /// Creates a reproducible genesis block committing to `genesis`.
+    pub fn create_genesis_block_from(genesis: &GenesisConfig) -> Block {
+        let mut block = Self::create_genesis_block();
+        block.prev_hash = genesis.hash();
+        block.timestamp = genesis.timestamp;
+        block.task.timestamp = genesis.timestamp.max(0) as u64;
+        block.hash = block.calculate_hash();
+        block
+    }
+
+    /// Initializes the genesis state with pre-funded developer account and
+    /// the consensus parameters of `config`
     pub fn initialize_genesis_state(
+        config: &BlockchainConfig,
         state: &mut BlockchainState,
         account_nonces: &mut HashMap<String, u64>,
     ) {
+        state.consensus = config.consensus_params();
         state
             .balances
             .insert(DEV_PUBLIC_KEY.to_string(), 1_000_000_000);
         account_nonces
             .insert(DEV_PUBLIC_KEY.to_string(), 0);
     }
-} 
\ No newline at end of file
+
+    /// Initializes balances, validator stakes and consensus parameters from
+    /// a genesis file.
+    pub fn initialize_state_from(
+        genesis: &GenesisConfig,
+        state: &mut BlockchainState,
+        account_nonces: &mut HashMap<String, u64>,
+    ) {
+        state.consensus = genesis.consensus_params();
+        for (account, balance) in &genesis.initial_balances {
+            state.balances.insert(account.clone(), *balance);
+            account_nonces.insert(account.clone(), 0);
+        }
+        for validator in &genesis.validators {
+            state.stakes.insert(validator.pubkey.clone(), validator.stake);
+            account_nonces.entry(validator.pubkey.clone()).or_insert(0);
+        }
+    }
+}
@@ -16,11 +16,20 @@ pub mod chain_ext;
 pub mod genesis;
 pub mod block_processor;
 pub mod account_manager;
+pub mod rebate;
+pub mod archive;
+pub mod simulation;
+pub mod nonce;
 
 // 2. Re-export the most important public types for easier access.
 pub use block::Block;
 pub use chain::Blockchain;
 pub use chain::BlockchainStats;
-pub use config::BlockchainConfig;
+pub use config::{BlockchainConfig, ConsensusParams};
 pub use error::BlockchainError;
+pub use archive::{ArchiveConfig, ArchiveManifest, ArchiveStore, Archiver};
+pub use rebate::RebateConfig;
+pub use simulation::{Simulation, StateChange};
+pub use nonce::NonceAllocator;
+pub use genesis::{GenesisConfig, GenesisValidator, StoragePricing};
 pub use transaction::Transaction; 
\ No newline at end of file
@@ -0,0 +1,136 @@
+//! Handing out nonces to concurrent submitters from one account.
+//!
+//! A transaction enters the mempool only with the nonce following its
+//! sender's last one, so submitters sharing an account can't each read the
+//! account's nonce and use it: two read the same nonce and one is refused as
+//! too low, or one counts ahead and is refused as too high. A
+//! [`NonceAllocator`] hands each nonce out once, in order. A nonce whose
+//! transaction will not be submitted is released, or its lease runs out,
+//! and is handed out again before any new one, so it doesn't leave a gap
+//! holding back the nonces after it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How long a handed-out nonce stays reserved, in seconds.
+pub const DEFAULT_LEASE_SECS: u64 = 60;
+
+/// Nonces of one account, from the next one the chain expects.
+#[derive(Debug, Clone)]
+pub struct NonceAllocator {
+    /// The account's next nonce; every nonce below it is used.
+    base: u64,
+    /// Lowest nonce never handed out.
+    next: u64,
+    /// Handed out but not yet submitted, with when each lease runs out.
+    leases: BTreeMap<u64, u64>,
+    /// Submitted but not yet used on chain.
+    submitted: BTreeSet<u64>,
+    /// Below `next` and free to hand out again.
+    free: BTreeSet<u64>,
+    lease_secs: u64,
+}
+
+impl NonceAllocator {
+    /// An allocator for an account whose next nonce is `base`.
+    pub fn new(base: u64) -> Self {
+        Self {
+            base,
+            next: base,
+            leases: BTreeMap::new(),
+            submitted: BTreeSet::new(),
+            free: BTreeSet::new(),
+            lease_secs: DEFAULT_LEASE_SECS,
+        }
+    }
+
+    pub fn with_lease(mut self, secs: u64) -> Self {
+        self.lease_secs = secs;
+        self
+    }
+
+    /// The account's next nonce as last synced.
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    /// The nonce [`NonceAllocator::allocate`] hands out next, unless a
+    /// lease runs out first.
+    pub fn peek(&self) -> u64 {
+        self.free.first().copied().unwrap_or(self.next)
+    }
+
+    /// Reserve the lowest free nonce until `now` plus the lease.
+    pub fn allocate(&mut self, now: u64) -> u64 {
+        self.expire(now);
+        let nonce = self.free.pop_first().unwrap_or_else(|| {
+            self.next += 1;
+            self.next - 1
+        });
+        self.leases.insert(nonce, now.saturating_add(self.lease_secs));
+        nonce
+    }
+
+    /// A transaction carrying `nonce` was submitted. Nonces not handed out
+    /// here are taken too; the ones they skip over become free.
+    pub fn submit(&mut self, nonce: u64) {
+        if nonce < self.base {
+            return;
+        }
+        self.leases.remove(&nonce);
+        self.free.remove(&nonce);
+        if nonce >= self.next {
+            self.free.extend(self.next..nonce);
+            self.next = nonce + 1;
+        }
+        self.submitted.insert(nonce);
+    }
+
+    /// `nonce` will not be used after all, e.g. its transaction was
+    /// refused; it is handed out again. Returns whether it was in flight.
+    pub fn release(&mut self, nonce: u64) -> bool {
+        let held = self.leases.remove(&nonce).is_some() | self.submitted.remove(&nonce);
+        if held {
+            self.free.insert(nonce);
+        }
+        held
+    }
+
+    /// The account's next nonce is now `base`, e.g. after a block or a
+    /// transaction entering the mempool. Nonces below it are forgotten.
+    pub fn sync(&mut self, base: u64) {
+        if base <= self.base {
+            return;
+        }
+        self.base = base;
+        self.next = self.next.max(base);
+        self.leases.retain(|n, _| *n >= base);
+        self.submitted.retain(|n| *n >= base);
+        self.free.retain(|n| *n >= base);
+    }
+
+    /// Free the nonces whose lease ran out by `now`, returning them.
+    pub fn expire(&mut self, now: u64) -> Vec<u64> {
+        let expired: Vec<u64> = self.leases.iter().filter(|(_, until)| **until <= now).map(|(n, _)| *n).collect();
+        for nonce in &expired {
+            self.leases.remove(nonce);
+            self.free.insert(*nonce);
+        }
+        expired
+    }
+
+    /// Nonces handed out or submitted and not yet used on chain, in order.
+    pub fn in_flight(&self) -> Vec<u64> {
+        self.leases.keys().chain(&self.submitted).copied().collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// Nonces not yet submitted that hold back submitted ones.
+    pub fn gaps(&self) -> Vec<u64> {
+        let Some(&top) = self.submitted.last() else { return Vec::new() };
+        (self.base..top).filter(|n| !self.submitted.contains(n)).collect()
+    }
+
+    /// Nothing is in flight, so the allocator can be dropped.
+    pub fn is_idle(&self) -> bool {
+        self.leases.is_empty() && self.submitted.is_empty()
+    }
+}
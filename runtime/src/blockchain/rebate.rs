@@ -0,0 +1,54 @@
+//! Burn-and-mint fee rebates for reliable workers.
+//!
+//! A configurable share of every transaction fee is burned instead of paid
+//! to the miner and booked against the payer. At the end of each epoch the
+//! booked amounts of payers whose reputation has reached the threshold are
+//! minted back to them; the rest stays burned.
+//!
+//! Reputation is earned on chain: a miner gains a point each time one of its
+//! block rewards leaves the challenge window unchallenged, loses a point per
+//! outlier evaluation and loses everything to a fraud proof.
+
+use serde::{Deserialize, Serialize};
+
+use crate::pouw::evaluation::FULL_REWARD_BPS;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RebateConfig {
+    /// Blocks per epoch; rebates are minted by the block whose index is a
+    /// multiple of this.
+    pub epoch_blocks: u32,
+    /// Share of each fee, in basis points, that is burned and may be
+    /// rebated. Zero disables the program.
+    pub rebate_bps: u32,
+    /// Reputation a payer needs at the end of the epoch to be rebated.
+    pub min_reputation: u64,
+}
+
+impl Default for RebateConfig {
+    fn default() -> Self {
+        Self {
+            epoch_blocks: 100,
+            rebate_bps: 0,
+            min_reputation: 10,
+        }
+    }
+}
+
+impl RebateConfig {
+    /// Part of `fee` burned when it is paid.
+    pub fn burn(&self, fee: u64) -> u64 {
+        (fee as u128 * self.rebate_bps.min(FULL_REWARD_BPS) as u128 / FULL_REWARD_BPS as u128) as u64
+    }
+
+    /// Part of `fee` left to the miner after the burn.
+    pub fn kept(&self, fee: u64) -> u64 {
+        fee - self.burn(fee)
+    }
+
+    /// Whether block `index` closes an epoch.
+    pub fn is_epoch_end(&self, index: u32) -> bool {
+        self.epoch_blocks > 0 && index > 0 && index % self.epoch_blocks == 0
+    }
+}
@@ -0,0 +1,105 @@
+//! Dry runs of transactions against the current chain state.
+//!
+//! Wallets, the CLI's `--dry-run` and explorers preview a transaction before
+//! it is submitted: it goes through the checks a block would run on it and
+//! is then applied to a copy of the state, which is compared with the
+//! original. Nothing is kept and the mempool is not touched.
+
+use super::{
+    chain::Blockchain, error::BlockchainError, state::State, transaction::Transaction, validation,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// One state entry the transaction would change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChange {
+    /// State table, e.g. `balances` or `job_escrows`.
+    pub field: String,
+    /// Entry within the table; empty for tables that are not keyed.
+    pub key: String,
+    /// `null` when the entry would be created.
+    pub before: Value,
+    /// `null` when the entry would be removed.
+    pub after: Value,
+}
+
+/// What including a transaction in the next block would do.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Simulation {
+    pub hash: String,
+    pub fee: u64,
+    /// Part of the fee burned towards a rebate; the rest goes to the miner.
+    pub burned: u64,
+    /// The time lock keeps the transaction out of the next block; it would
+    /// wait in the deferred pool.
+    pub deferred: bool,
+    /// Changes to the state, excluding the miner's reward, sorted by table
+    /// and key.
+    pub changes: Vec<StateChange>,
+}
+
+impl Blockchain {
+    /// Validates `tx` as the next block would and reports its effects on
+    /// the state without applying them.
+    pub fn simulate_transaction(&self, tx: &Transaction) -> Result<Simulation, BlockchainError> {
+        validation::validate_transaction_stateless(tx)?;
+        validation::validate_transaction_stateful(tx, &self.state)?;
+        let mut after = self.state.clone();
+        after.apply_transaction(tx)?;
+        let burned = self.state.consensus.rebate.burn(tx.fee);
+        after.record_fee_burn(&tx.from, burned);
+
+        let now = chrono::Utc::now().timestamp();
+        Ok(Simulation {
+            hash: tx.hash(),
+            fee: tx.fee,
+            burned,
+            deferred: !tx.is_eligible(self.height(), now),
+            changes: diff(&to_value(&self.state)?, &to_value(&after)?),
+        })
+    }
+}
+
+fn to_value(state: &State) -> Result<Value, BlockchainError> {
+    serde_json::to_value(state)
+        .map_err(|e| BlockchainError::TransactionValidationError(e.to_string()))
+}
+
+/// Entries of the serialised states `before` and `after` that differ.
+fn diff(before: &Value, after: &Value) -> Vec<StateChange> {
+    let (Value::Object(before), Value::Object(after)) = (before, after) else { return Vec::new() };
+    let mut changes = Vec::new();
+    let fields: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    for field in fields {
+        let old = before.get(field).unwrap_or(&Value::Null);
+        let new = after.get(field).unwrap_or(&Value::Null);
+        if old == new {
+            continue;
+        }
+        let (Value::Object(old), Value::Object(new)) = (old, new) else {
+            changes.push(StateChange {
+                field: field.clone(),
+                key: String::new(),
+                before: old.clone(),
+                after: new.clone(),
+            });
+            continue;
+        };
+        let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for key in keys {
+            let (old, new) =
+                (old.get(key).unwrap_or(&Value::Null), new.get(key).unwrap_or(&Value::Null));
+            if old != new {
+                changes.push(StateChange {
+                    field: field.clone(),
+                    key: key.clone(),
+                    before: old.clone(),
+                    after: new.clone(),
+                });
+            }
+        }
+    }
+    changes
+}
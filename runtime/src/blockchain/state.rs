@@ -1,6 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use super::{chain::BlockchainError, transaction::{Transaction, StorageTx}};
+use super::{
+    chain::BlockchainError,
+    config::ConsensusParams,
+    constants::{CHALLENGE_WINDOW_BLOCKS, JOB_TIMEOUT_BLOCKS, OUTLIER_SLASH},
+    transaction::{Transaction, StorageTx},
+};
+use crate::distributed_storage::contracts::{AuditConfig, ContractStatus, StorageContract, StorageProof};
+use crate::pouw::commit_reveal::{CommitRevealConfig, EvaluationRound};
+use crate::pouw::evaluation::Score;
+use crate::pouw::outlier::{self, AggregationConfig, ConsensusEvaluation};
+use crate::pouw::types::{SignedEvaluation, ValidatorSelectionConfig};
+use crate::pouw::validator_selection::select_validators;
+use sha2::{Digest, Sha256};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct State {
@@ -16,6 +28,140 @@ pub struct State {
     pub stakes: HashMap<String, u64>,
     /// Recent PoUW metrics (accuracy, computation time in ms) for difficulty adjustment.
     pub pouw_metrics: Vec<(u32, u64)>,
+    /// Registered multisig accounts keyed by their derived address.
+    #[serde(default)]
+    pub multisig_accounts: HashMap<String, crate::blockchain::transaction::MultisigAccount>,
+    /// PoUW rewards still open to a fraud proof, keyed by block index.
+    #[serde(default)]
+    pub pouw_claims: HashMap<u32, PoUWClaim>,
+    /// Multi-block PoUW tasks keyed by task id, with their committed progress.
+    #[serde(default)]
+    pub long_tasks: HashMap<String, crate::pouw::progress::TaskProgress>,
+    /// Consensus evaluations of PoUW tasks keyed by task id.
+    #[serde(default)]
+    pub consensus_evaluations: HashMap<String, ConsensusEvaluation>,
+    /// Posted jobs awaiting a miner, keyed by job id.
+    #[serde(default)]
+    pub job_escrows: HashMap<u64, JobEscrow>,
+    /// Reputation of workers, see [`crate::blockchain::rebate`].
+    #[serde(default)]
+    pub reputation: HashMap<String, u64>,
+    /// Fees burned this epoch, by payer, awaiting a possible rebate.
+    #[serde(default)]
+    pub pending_rebates: HashMap<String, u64>,
+    /// Open commit-reveal evaluation rounds keyed by task id.
+    #[serde(default)]
+    pub evaluation_rounds: HashMap<String, EvaluationRound>,
+    /// Inference endpoints keyed by endpoint id.
+    #[serde(default)]
+    pub endpoints: HashMap<String, crate::endpoint::EndpointRecord>,
+    /// Audited storage contracts keyed by contract id.
+    #[serde(default)]
+    pub storage_contracts: HashMap<String, StorageContract>,
+    /// Objects to re-replicate, by descriptor hash, with the node that
+    /// failed to prove it holds them.
+    #[serde(default)]
+    pub storage_repairs: HashMap<String, String>,
+    /// PoUW tasks open for evaluation, keyed by task id, with the
+    /// validators selected to evaluate them.
+    #[serde(default)]
+    pub evaluation_tasks: HashMap<String, EvaluationTask>,
+    /// Session keys keyed by the account they act for.
+    #[serde(default)]
+    pub session_keys: HashMap<String, crate::session_keys::SessionKeys>,
+    /// Scoring, fee rebate, commit-reveal and storage audit parameters,
+    /// set at genesis and read by every block.
+    #[serde(default)]
+    pub consensus: ConsensusParams,
+}
+
+/// A posted job and the account its escrowed reward came from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JobEscrow {
+    pub poster: String,
+    pub job: crate::job::Job,
+    /// Milestones of the job signed off and paid so far, by index.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signed_off: Vec<u32>,
+    /// Whether a block trained the job and settled the miner's share.
+    #[serde(default)]
+    pub mined: bool,
+    /// Block at which what is still escrowed returns to the poster; set by
+    /// the block that posts the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u32>,
+}
+
+impl JobEscrow {
+    pub fn new(poster: String, job: crate::job::Job) -> Self {
+        Self { poster, job, signed_off: Vec::new(), mined: false, expires_at: None }
+    }
+
+    /// Whether nothing has been paid out of the escrow yet.
+    pub fn is_untouched(&self) -> bool {
+        !self.mined && self.signed_off.is_empty()
+    }
+
+    /// Reward of the milestones not signed off yet.
+    pub fn unpaid_milestones(&self) -> u64 {
+        let paid: u64 = self
+            .signed_off
+            .iter()
+            .filter_map(|i| self.job.milestones.get(*i as usize))
+            .map(|m| m.reward)
+            .sum();
+        self.job.milestone_reward().saturating_sub(paid)
+    }
+
+    /// Share of the reward left to the miner that trains the job.
+    pub fn mining_reward(&self) -> u64 {
+        self.job.reward.saturating_sub(self.job.milestone_reward())
+    }
+
+    /// Reward still held: the milestones not signed off and, until the job
+    /// is mined, the mining share.
+    pub fn remaining(&self) -> u64 {
+        let mining = if self.mined { 0 } else { self.mining_reward() };
+        self.unpaid_milestones() + mining
+    }
+
+    /// Whether nothing is left to pay out.
+    pub fn is_settled(&self) -> bool {
+        self.remaining() == 0
+    }
+}
+
+/// A PoUW task open for evaluation and the validators selected for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvaluationTask {
+    /// Selected validators, in selection order.
+    pub selected: Vec<String>,
+    /// Index of the block that opened the task.
+    pub opened_at: u32,
+}
+
+impl EvaluationTask {
+    /// Evaluations needed to record a consensus: more than two thirds of
+    /// the selected validators.
+    pub fn quorum(&self) -> usize {
+        self.selected.len() * 2 / 3 + 1
+    }
+
+    pub fn is_selected(&self, validator: &str) -> bool {
+        self.selected.iter().any(|v| v == validator)
+    }
+}
+
+/// A block's PoUW reward, kept for the challenge window so that a valid
+/// fraud proof can revert it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PoUWClaim {
+    pub block_hash: String,
+    pub miner: String,
+    pub reward: u64,
+    pub difficulty: u32,
+    /// See [`crate::pouw::fraud::evidence_hash`].
+    pub evidence_hash: String,
 }
 
 impl State {
@@ -28,6 +174,20 @@ impl State {
             pouw_evaluations: HashMap::new(),
             stakes: HashMap::new(),
             pouw_metrics: Vec::new(),
+            multisig_accounts: HashMap::new(),
+            pouw_claims: HashMap::new(),
+            long_tasks: HashMap::new(),
+            consensus_evaluations: HashMap::new(),
+            job_escrows: HashMap::new(),
+            reputation: HashMap::new(),
+            pending_rebates: HashMap::new(),
+            evaluation_rounds: HashMap::new(),
+            endpoints: HashMap::new(),
+            storage_contracts: HashMap::new(),
+            storage_repairs: HashMap::new(),
+            evaluation_tasks: HashMap::new(),
+            session_keys: HashMap::new(),
+            consensus: ConsensusParams::default(),
         }
     }
 
@@ -44,7 +204,7 @@ impl State {
                     // Reward payouts are negative cost to sender (node). For now treat as zero cost.
                     tx.fee as u128
                 }
-                crate::blockchain::transaction::StorageTx::UpdateMetrics { metrics } => {
+                crate::blockchain::transaction::StorageTx::UpdateMetrics { metrics, .. } => {
                     // Apply metrics update without cost; update node_metrics map.
                     for m in metrics {
                         self.node_metrics.insert(m.node_id.clone(), m.clone());
@@ -56,6 +216,85 @@ impl State {
                     self.pouw_evaluations.insert(task_id.clone(), evaluation_hash.clone());
                     0u128
                 }
+                crate::blockchain::transaction::StorageTx::RegisterMultisig { signers, threshold } => {
+                    let account = crate::blockchain::transaction::MultisigAccount::new(signers.clone(), *threshold);
+                    self.multisig_accounts.insert(account.address(), account);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::SubmitFraudProof { proof } => {
+                    self.resolve_fraud_proof(proof.block_index);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::RecordConsensusEvaluation { task_id, evaluations } => {
+                    self.record_consensus_evaluation(task_id, evaluations);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::PostJob { job } => {
+                    self.job_escrows.insert(job.id, JobEscrow::new(tx.from.clone(), job.clone()));
+                    (job.reward as u128) + tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::RegisterLongTask { task } => {
+                    let progress = crate::pouw::progress::TaskProgress::new((**task).clone());
+                    self.long_tasks.insert(task.id(), progress);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::CommitProgress { delta } => {
+                    if let Some(progress) = self.long_tasks.get_mut(&delta.task_id) {
+                        progress.apply(delta);
+                    }
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::CommitEvaluation { commitment } => {
+                    let _ = self
+                        .evaluation_rounds
+                        .entry(commitment.task_id.clone())
+                        .or_insert_with(|| EvaluationRound::new(&commitment.task_id))
+                        .commit(commitment.clone());
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::RevealEvaluation { reveal } => {
+                    if let Some(round) = self.evaluation_rounds.get_mut(&reveal.evaluation.task_id) {
+                        let _ = round.reveal((**reveal).clone());
+                    }
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::RegisterEndpoint { record } => {
+                    self.endpoints.insert(record.endpoint_id.clone(), record.clone());
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::FailoverEndpoint { endpoint_id, primary, epoch } => {
+                    if let Some(record) = self.endpoints.get_mut(endpoint_id) {
+                        record.failover(primary, *epoch);
+                    }
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::SignOffMilestone { job_id, milestone, worker } => {
+                    self.sign_off_milestone(*job_id, *milestone, worker);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::CancelJob { job_id } => {
+                    self.cancel_job(&tx.from, *job_id);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::OpenStorageContract { contract } => {
+                    self.storage_contracts.insert(contract.contract_id.clone(), (**contract).clone());
+                    (contract.escrow as u128) + tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::SubmitStorageProof { proof } => {
+                    self.pass_storage_audit(&tx.from, proof);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::RepairStorageContract { contract_id, node, audits } => {
+                    (self.repair_storage_contract(contract_id, node, *audits).unwrap_or(0) as u128) + tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::AcceptStorageContract { contract_id } => {
+                    self.accept_storage_contract(&tx.from, contract_id);
+                    tx.fee as u128
+                }
+                crate::blockchain::transaction::StorageTx::RegisterSessionKeys { keys } => {
+                    self.session_keys.insert(keys.account.clone(), keys.clone());
+                    tx.fee as u128
+                }
             }
         } else {
             (tx.amount as u128) + tx.fee as u128
@@ -70,9 +309,8 @@ impl State {
             })?;
         }
 
-        // Update sender's nonce
-        let nonce = self.nonces.entry(tx.from.clone()).or_insert(0);
-        *nonce = tx.nonce; // The new nonce is the one from the applied transaction
+        // The sender's next transaction must carry the following nonce.
+        self.nonces.insert(tx.from.clone(), tx.nonce.saturating_add(1));
 
         if total_cost_u64 > 0 {
             let sender_balance = self
@@ -96,11 +334,19 @@ impl State {
         self.balances.get(pubkey).cloned().unwrap_or(0)
     }
 
-    /// Gets the current nonce for a given public key.
+    /// Gets the nonce the next transaction of a given public key must carry.
     pub fn get_nonce(&self, pubkey: &str) -> u64 {
         self.nonces.get(pubkey).cloned().unwrap_or(0)
     }
 
+    /// The account whose `role` session key is `public_key`, if any.
+    pub fn session_account(&self, role: crate::session_keys::SessionRole, public_key: &str) -> Option<&str> {
+        self.session_keys
+            .values()
+            .find(|keys| keys.key(role) == Some(public_key))
+            .map(|keys| keys.account.as_str())
+    }
+
     /// A special function to directly set a balance, used for genesis block creation.
     pub fn set_balance(&mut self, pubkey: &str, amount: u64) {
         self.balances.insert(pubkey.to_string(), amount);
@@ -138,6 +384,299 @@ impl State {
         }
     }
 
+    /// Keeps a block's reward open to challenge and drops claims whose
+    /// challenge window has closed as of block `index`.
+    /// Miners earn a reputation point for every claim that closes
+    /// unchallenged.
+    pub fn record_pouw_claim(&mut self, index: u32, claim: PoUWClaim) {
+        self.pouw_claims.insert(index, claim);
+        let reputation = &mut self.reputation;
+        self.pouw_claims.retain(|block, claim| {
+            let open = block.saturating_add(CHALLENGE_WINDOW_BLOCKS) > index;
+            if !open {
+                *reputation.entry(claim.miner.clone()).or_default() += 1;
+            }
+            open
+        });
+    }
+
+    /// Reverts the reward of a block proven fraudulent and slashes the
+    /// miner's entire stake. A reward the miner has already spent is only
+    /// reverted down to a zero balance. Each claim is resolved at most once.
+    pub fn resolve_fraud_proof(&mut self, block_index: u32) -> Option<PoUWClaim> {
+        let claim = self.pouw_claims.remove(&block_index)?;
+        let balance = self.balances.entry(claim.miner.clone()).or_insert(0);
+        *balance = balance.saturating_sub(claim.reward);
+        let stake = self.stakes.get(&claim.miner).copied().unwrap_or(0);
+        self.slash_stake(&claim.miner, stake);
+        self.reputation.remove(&claim.miner);
+        Some(claim)
+    }
+
+    /// Opens `task_id` for evaluation as of block `index`, selecting its
+    /// validators by stake with a seed derived from the block's hash, and
+    /// closes the tasks whose challenge window has passed, except long
+    /// tasks. A task is opened at most once.
+    pub fn open_evaluation_task(
+        &mut self,
+        task_id: &str,
+        index: u32,
+        block_hash: &str,
+        config: &ValidatorSelectionConfig,
+    ) -> &EvaluationTask {
+        let long_tasks = &self.long_tasks;
+        self.evaluation_tasks.retain(|id, task| {
+            long_tasks.contains_key(id) || task.opened_at.saturating_add(CHALLENGE_WINDOW_BLOCKS) > index
+        });
+        if !self.evaluation_tasks.contains_key(task_id) {
+            // Sorted, as the selection depends on the order of candidates.
+            let mut candidates: Vec<(String, u64)> = self.stakes.iter().map(|(k, v)| (k.clone(), *v)).collect();
+            candidates.sort();
+            let seed: [u8; 32] = Sha256::new().chain_update(block_hash).chain_update(task_id).finalize().into();
+            let selected = select_validators(candidates, seed, config);
+            self.evaluation_tasks.insert(task_id.to_string(), EvaluationTask { selected, opened_at: index });
+        }
+        &self.evaluation_tasks[task_id]
+    }
+
+    /// Aggregates the evaluations of `task_id`, records the consensus and
+    /// slashes [`OUTLIER_SLASH`] from every outlier's stake. A task's
+    /// consensus is recorded at most once.
+    pub fn record_consensus_evaluation(
+        &mut self,
+        task_id: &str,
+        evaluations: &[SignedEvaluation],
+    ) -> Option<&ConsensusEvaluation> {
+        if self.consensus_evaluations.contains_key(task_id) {
+            return None;
+        }
+        let consensus = outlier::aggregate(task_id, evaluations, &AggregationConfig::default()).ok()?;
+        self.evaluation_tasks.remove(task_id);
+        for validator in &consensus.outliers {
+            self.slash_stake(validator, OUTLIER_SLASH);
+            if let Some(reputation) = self.reputation.get_mut(validator) {
+                *reputation = reputation.saturating_sub(1);
+            }
+        }
+        Some(self.consensus_evaluations.entry(task_id.to_string()).or_insert(consensus))
+    }
+
+    /// Moves every commit-reveal round along as of block `index` and settles
+    /// the rounds whose reveal phase is over: penalties are slashed from
+    /// stake, each costing a reputation point, and the consensus, if any,
+    /// is recorded. Returns the ids of the settled tasks, sorted.
+    pub fn advance_evaluation_rounds(&mut self, index: u32, config: &CommitRevealConfig) -> Vec<String> {
+        let mut closed: Vec<String> = self
+            .evaluation_rounds
+            .iter_mut()
+            .filter_map(|(task_id, round)| round.advance(index, config).then(|| task_id.clone()))
+            .collect();
+        closed.sort();
+        for task_id in &closed {
+            let Some(round) = self.evaluation_rounds.remove(task_id) else { continue };
+            let settlement = round.settle(config);
+            for (validator, penalty) in &settlement.penalties {
+                self.slash_stake(validator, *penalty);
+                if let Some(reputation) = self.reputation.get_mut(validator) {
+                    *reputation = reputation.saturating_sub(1);
+                }
+            }
+            if let Some(consensus) = settlement.consensus {
+                self.evaluation_tasks.remove(task_id);
+                self.consensus_evaluations.entry(task_id.clone()).or_insert(consensus);
+            }
+        }
+        closed
+    }
+
+    /// Releases the mining share of a mined job's escrow: `miner` earns the
+    /// `score` share of the reward not set aside for milestones and the rest
+    /// is refunded to the poster. Milestone rewards stay in escrow until
+    /// signed off. Returns the miner's share, or `None` if the job has no
+    /// escrow or was already mined.
+    pub fn settle_job(&mut self, job_id: u64, miner: &str, score: &Score) -> Option<u64> {
+        let escrow = self.job_escrows.get_mut(&job_id)?;
+        if escrow.mined {
+            return None;
+        }
+        escrow.mined = true;
+        let held = escrow.mining_reward();
+        let earned = score.reward_share(held);
+        let poster = escrow.poster.clone();
+        self.close_settled_escrow(job_id);
+        *self.balances.entry(miner.to_string()).or_default() += earned;
+        *self.balances.entry(poster).or_default() += held - earned;
+        Some(earned)
+    }
+
+    fn close_settled_escrow(&mut self, job_id: u64) {
+        if self.job_escrows.get(&job_id).is_some_and(JobEscrow::is_settled) {
+            self.job_escrows.remove(&job_id);
+        }
+    }
+
+    /// Refunds what is left of the escrows whose timeout has passed at block
+    /// `index`, unsigned milestones included, and starts the timeout of
+    /// escrows posted in that block. Returns the ids of the jobs refunded.
+    pub fn advance_job_escrows(&mut self, index: u32) -> Vec<u64> {
+        let mut expired = Vec::new();
+        for (id, escrow) in self.job_escrows.iter_mut() {
+            let timeout = escrow.job.timeout_blocks.unwrap_or(JOB_TIMEOUT_BLOCKS);
+            match escrow.expires_at {
+                None => escrow.expires_at = Some(index.saturating_add(timeout)),
+                Some(at) if index >= at => expired.push(*id),
+                Some(_) => {}
+            }
+        }
+        expired.sort_unstable();
+        for id in &expired {
+            self.refund_job(*id);
+        }
+        expired
+    }
+
+    /// Withdraws a job nobody has been paid for yet, refunding its reward
+    /// to the poster. Returns the refund, or `None` if `sender` did not
+    /// post the job or part of it was already paid.
+    pub fn cancel_job(&mut self, sender: &str, job_id: u64) -> Option<u64> {
+        let escrow = self.job_escrows.get(&job_id)?;
+        if escrow.poster != sender || !escrow.is_untouched() {
+            return None;
+        }
+        self.refund_job(job_id)
+    }
+
+    /// Closes a job's escrow, returning what it still holds to the poster.
+    fn refund_job(&mut self, job_id: u64) -> Option<u64> {
+        let escrow = self.job_escrows.remove(&job_id)?;
+        let refund = escrow.remaining();
+        *self.balances.entry(escrow.poster).or_default() += refund;
+        Some(refund)
+    }
+
+    /// Pays milestone `milestone` of escrowed job `job_id` to `worker`.
+    /// Returns the amount paid, or `None` if there is no such milestone or
+    /// it was already paid.
+    pub fn sign_off_milestone(&mut self, job_id: u64, milestone: u32, worker: &str) -> Option<u64> {
+        let escrow = self.job_escrows.get_mut(&job_id)?;
+        let reward = escrow.job.milestones.get(milestone as usize)?.reward;
+        if escrow.signed_off.contains(&milestone) {
+            return None;
+        }
+        escrow.signed_off.push(milestone);
+        self.close_settled_escrow(job_id);
+        *self.balances.entry(worker.to_string()).or_default() += reward;
+        Some(reward)
+    }
+
+    /// Pays the node of a contract for answering its challenge with a valid
+    /// proof. Returns the instalment paid, or `None` if the proof does not
+    /// answer an open challenge.
+    pub fn pass_storage_audit(&mut self, sender: &str, proof: &StorageProof) -> Option<u64> {
+        let contract = self.storage_contracts.get_mut(&proof.contract_id)?;
+        contract.check_proof(sender, proof).ok()?;
+        let payout = contract.pass();
+        let unlocked = if contract.is_active() { 0 } else { contract.release() };
+        *self.balances.entry(contract.node.clone()).or_default() += payout;
+        *self.stakes.entry(contract.node.clone()).or_default() += unlocked;
+        Some(payout)
+    }
+
+    /// Activates a pending contract its node accepts, locking the
+    /// collateral out of the node's stake. Returns the collateral locked,
+    /// or `None` if the contract cannot be accepted.
+    pub fn accept_storage_contract(&mut self, sender: &str, contract_id: &str) -> Option<u64> {
+        let contract = self.storage_contracts.get_mut(contract_id)?;
+        let stake = self.stakes.entry(sender.to_string()).or_insert(0);
+        contract.check_accept(sender, *stake).ok()?;
+        let locked = contract.accept();
+        *stake -= locked;
+        Some(locked)
+    }
+
+    /// Moves a contract to `node` with `audits` more instalments escrowed,
+    /// pending the new node's acceptance, unlocks what is left of the old
+    /// node's collateral and clears the repair flag the old node left.
+    /// Returns the escrow added, or `None` if there is no such contract.
+    pub fn repair_storage_contract(&mut self, contract_id: &str, node: &str, audits: u32) -> Option<u64> {
+        let contract = self.storage_contracts.get_mut(contract_id)?;
+        let top_up = contract.payment_per_audit.saturating_mul(audits as u64);
+        let old = contract.node.clone();
+        *self.stakes.entry(old.clone()).or_default() += contract.release();
+        contract.reassign(node, top_up);
+        if self.storage_repairs.get(&contract.descriptor_hash) == Some(&old) {
+            self.storage_repairs.remove(&contract.descriptor_hash);
+        }
+        Some(top_up)
+    }
+
+    /// Runs the storage audits due at block `index` with hash
+    /// `block_hash`: challenges still open at their deadline fail, burning
+    /// part of the node's locked collateral and flagging the object for
+    /// re-replication, and contracts due for an audit are challenged. A
+    /// contract that fails for good refunds its escrow to the owner and
+    /// unlocks what is left of the collateral, as does a pending contract
+    /// its node did not accept in time. Returns the ids of the contracts
+    /// that failed an audit, sorted.
+    pub fn advance_storage_audits(&mut self, index: u32, block_hash: &str, config: &AuditConfig) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .storage_contracts
+            .iter()
+            .filter(|(_, contract)| contract.is_open())
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        let mut failed = Vec::new();
+        for id in ids {
+            let Some(contract) = self.storage_contracts.get_mut(&id) else { continue };
+            if contract.status == ContractStatus::Pending {
+                let deadline = *contract.accept_deadline.get_or_insert(index.saturating_add(config.accept_blocks));
+                if index >= deadline {
+                    contract.expire();
+                    let refund = std::mem::take(&mut contract.escrow);
+                    *self.balances.entry(contract.owner.clone()).or_default() += refund;
+                }
+            } else if contract.challenge.as_ref().is_some_and(|c| index >= c.deadline) {
+                contract.fail(config);
+                let (node, owner, descriptor_hash) =
+                    (contract.node.clone(), contract.owner.clone(), contract.descriptor_hash.clone());
+                let (refund, unlocked) = if contract.is_active() {
+                    (0, 0)
+                } else {
+                    (std::mem::take(&mut contract.escrow), contract.release())
+                };
+                *self.stakes.entry(node.clone()).or_default() += unlocked;
+                *self.balances.entry(owner).or_default() += refund;
+                self.storage_repairs.insert(descriptor_hash, node);
+                failed.push(id);
+            } else if contract.challenge.is_none() && index % config.interval_blocks.max(1) == 0 {
+                contract.issue_challenge(index, block_hash, config);
+            }
+        }
+        failed
+    }
+
+    /// Books `burned` fee tokens of `payer` for the epoch-end rebate.
+    pub fn record_fee_burn(&mut self, payer: &str, burned: u64) {
+        if burned > 0 {
+            *self.pending_rebates.entry(payer.to_string()).or_default() += burned;
+        }
+    }
+
+    /// Closes the epoch: mints back the fees burned by every payer whose
+    /// reputation is at least `min_reputation` and forgets the rest.
+    /// Returns the total minted.
+    pub fn settle_rebates(&mut self, min_reputation: u64) -> u64 {
+        let mut minted = 0;
+        for (payer, burned) in std::mem::take(&mut self.pending_rebates) {
+            if self.reputation.get(&payer).copied().unwrap_or(0) >= min_reputation {
+                *self.balances.entry(payer).or_default() += burned;
+                minted += burned;
+            }
+        }
+        minted
+    }
+
     /// Records PoUW metrics for future difficulty adjustments.
     pub fn record_pouw_metrics(&mut self, accuracy: u32, computation_ms: u64) {
         self.pouw_metrics.push((accuracy, computation_ms));
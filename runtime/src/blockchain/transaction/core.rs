@@ -16,15 +16,124 @@ pub enum StorageTx {
         node_id: String,
         reward: u128,
     },
-    /// Admin transaction carrying latest Node metrics snapshot.
+    /// Metrics a node reports about itself. Signed by the sender's
+    /// `Metrics` session key, named here, instead of its account key.
     UpdateMetrics {
         metrics: Vec<crate::distributed_storage::allocation::NodeMetrics>,
+        #[serde(default)]
+        session_key: String,
     },
     /// Commit the hash of a PoUW evaluation on-chain for integrity.
     PoUWEvaluationHash {
         task_id: String,
         evaluation_hash: String,
     },
+    /// Register an M-of-N multisig account controlled by `signers`.
+    RegisterMultisig {
+        signers: Vec<String>,
+        threshold: u32,
+    },
+    /// Challenge the PoUW solution of a recent block.
+    SubmitFraudProof {
+        proof: Box<crate::pouw::fraud::FraudProof>,
+    },
+    /// Record the consensus of the selected validators' evaluations of a
+    /// task, penalizing outliers.
+    RecordConsensusEvaluation {
+        task_id: String,
+        evaluations: Vec<crate::pouw::types::SignedEvaluation>,
+    },
+    /// Post a job, escrowing its reward from the sender until a miner
+    /// trains it as a block's PoUW task. The job's id must be
+    /// [`Job::posted_id`](crate::job::Job::posted_id) of the sender and the
+    /// transaction's nonce.
+    PostJob {
+        job: crate::job::Job,
+    },
+    /// Register a PoUW task that is trained over many blocks.
+    RegisterLongTask {
+        task: Box<crate::pouw::progress::LongTask>,
+    },
+    /// Commit a solver's progress on a long task.
+    CommitProgress {
+        delta: crate::pouw::progress::ProgressDelta,
+    },
+    /// Commit a validator's hidden evaluation of a task, opening the task's
+    /// commit-reveal round if needed.
+    CommitEvaluation {
+        commitment: crate::pouw::commit_reveal::SignedCommitment,
+    },
+    /// Reveal the evaluation behind a commitment.
+    RevealEvaluation {
+        reveal: Box<crate::pouw::commit_reveal::EvaluationReveal>,
+    },
+    /// Register an inference endpoint with its primary and standby nodes.
+    RegisterEndpoint {
+        record: crate::endpoint::EndpointRecord,
+    },
+    /// Promote a standby of an endpoint to primary after the primary failed.
+    FailoverEndpoint {
+        endpoint_id: String,
+        primary: String,
+        epoch: u64,
+    },
+    /// The job's milestone evaluator signs off milestone `milestone` of an
+    /// escrowed job, releasing its reward to `worker`.
+    SignOffMilestone {
+        job_id: u64,
+        milestone: u32,
+        worker: String,
+    },
+    /// The poster withdraws a job nobody has been paid for yet, refunding
+    /// its escrowed reward.
+    CancelJob {
+        job_id: u64,
+    },
+    /// Open a storage contract, escrowing its payments from the owner.
+    OpenStorageContract {
+        contract: Box<crate::distributed_storage::contracts::StorageContract>,
+    },
+    /// A storage node answers the open audit challenge of its contract.
+    SubmitStorageProof {
+        proof: crate::distributed_storage::contracts::StorageProof,
+    },
+    /// The node of a pending storage contract accepts it, locking the
+    /// contract's collateral out of its stake.
+    AcceptStorageContract {
+        contract_id: String,
+    },
+    /// The owner moves a storage contract to a new node after its object
+    /// was re-replicated there, escrowing `audits` more instalments. The
+    /// new node must accept the contract again.
+    RepairStorageContract {
+        contract_id: String,
+        node: String,
+        audits: u32,
+    },
+    /// Link the sender's account to its session keys, replacing those
+    /// registered before.
+    RegisterSessionKeys {
+        keys: crate::session_keys::SessionKeys,
+    },
+}
+
+/// Earliest point at which a transaction may be included in a block.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeLock {
+    /// Minimum index of the including block.
+    BlockHeight(u64),
+    /// Minimum timestamp (unix seconds) of the including block.
+    Timestamp(i64),
+}
+
+impl TimeLock {
+    /// Returns true once a block at `height` with `timestamp` may include the tx.
+    pub fn is_satisfied(&self, height: u64, timestamp: i64) -> bool {
+        match *self {
+            TimeLock::BlockHeight(h) => height >= h,
+            TimeLock::Timestamp(t) => timestamp >= t,
+        }
+    }
 }
 
 /// A signed value-transfer transaction on the chain.
@@ -39,11 +148,17 @@ pub struct Transaction {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub storage: Option<StorageTx>,
     pub signature: Option<String>,
+    /// Co-signer signatures authorising a spend from a multisig account.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub multisig_signatures: Vec<super::multisig::MultisigSignature>,
+    /// Optional time lock; the transaction is deferred until it is satisfied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_after: Option<TimeLock>,
 }
 
 impl Transaction {
     pub fn new(from: String, to: String, amount: u64, fee: u64, nonce: u64) -> Self {
-        Self { from, to, amount, fee, nonce, storage: None, signature: None }
+        Self { from, to, amount, fee, nonce, storage: None, signature: None, multisig_signatures: Vec::new(), valid_after: None }
     }
 
     /// Convenience constructor for file-storage payment
@@ -64,9 +179,23 @@ impl Transaction {
             nonce,
             storage: Some(StorageTx::StoreFile { descriptor_hash, total_bytes, price, replica_nodes }),
             signature: None,
+            multisig_signatures: Vec::new(),
+            valid_after: None,
         }
     }
 
+    /// Attach a time lock. Must be called before signing because the lock is
+    /// part of the signed payload.
+    pub fn with_valid_after(mut self, lock: TimeLock) -> Self {
+        self.valid_after = Some(lock);
+        self
+    }
+
+    /// Whether a block at `height` with `timestamp` may include this tx.
+    pub fn is_eligible(&self, height: u64, timestamp: i64) -> bool {
+        self.valid_after.is_none_or(|lock| lock.is_satisfied(height, timestamp))
+    }
+
     /// Lightweight accessor for signer hex string.
     pub fn signer(&self) -> &String { &self.from }
     /// Recipient hex string.
@@ -84,6 +213,17 @@ impl Transaction {
             let payload_bytes = bincode::serialize(payload).expect("serialize payload");
             buf.extend_from_slice(&payload_bytes);
         }
+        match self.valid_after {
+            Some(TimeLock::BlockHeight(h)) => {
+                buf.push(1);
+                buf.extend_from_slice(&h.to_le_bytes());
+            }
+            Some(TimeLock::Timestamp(t)) => {
+                buf.push(2);
+                buf.extend_from_slice(&t.to_le_bytes());
+            }
+            None => {}
+        }
         buf
     }
 } 
\ No newline at end of file
@@ -3,6 +3,9 @@ use sha2::{Digest, Sha256};
 
 impl Transaction {
     /// Compute deterministic SHA-256 hash of the transaction (including signature).
+    /// Co-signatures of a multisig spend are left out, like a witness, so
+    /// whoever relays the spend cannot change its id by adding, repeating or
+    /// dropping them.
     pub fn hash(&self) -> String {
         let mut hasher = Sha256::new();
         hasher.update(self.to_hash_bytes());
@@ -3,6 +3,9 @@
 mod core;
 mod signing;
 mod hashing;
+mod multisig;
 
 pub use core::Transaction;
-pub use core::StorageTx; 
\ No newline at end of file
+pub use core::StorageTx;
+pub use core::TimeLock;
+pub use multisig::{MultisigAccount, MultisigSignature}; 
\ No newline at end of file
@@ -0,0 +1,145 @@
+use super::core::{StorageTx, Transaction};
+use crate::blockchain::constants::SIGNING_CONTEXT;
+use schnorrkel::{signing_context, PublicKey, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// A single co-signer's signature over a multisig spend.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MultisigSignature {
+    /// Hex-encoded public key of the co-signer.
+    pub signer: String,
+    /// Hex-encoded Schnorrkel signature over `Transaction::to_hash_bytes`.
+    pub signature: String,
+}
+
+/// An M-of-N account registered on chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MultisigAccount {
+    /// Hex-encoded public keys allowed to co-sign, sorted.
+    pub signers: Vec<String>,
+    /// Number of distinct signers required to authorise a spend.
+    pub threshold: u32,
+}
+
+impl MultisigAccount {
+    /// Builds the account, normalising the signer order so that the derived
+    /// address does not depend on how the set was listed.
+    pub fn new(mut signers: Vec<String>, threshold: u32) -> Self {
+        signers.sort();
+        Self { signers, threshold }
+    }
+
+    /// Deterministic account address: SHA-256 over threshold and sorted signers.
+    pub fn address(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(b"bcai-multisig");
+        hasher.update(self.threshold.to_le_bytes());
+        for signer in &self.signers {
+            hasher.update(signer.as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Checks the threshold and signer set are well formed.
+    pub fn is_well_formed(&self) -> bool {
+        let unique: HashSet<&String> = self.signers.iter().collect();
+        self.threshold >= 1
+            && self.threshold as usize <= self.signers.len()
+            && unique.len() == self.signers.len()
+            && self.signers.iter().all(|s| {
+                hex::decode(s).ok().and_then(|b| PublicKey::from_bytes(&b).ok()).is_some()
+            })
+    }
+
+    /// Returns true if the transaction carries at least `threshold` valid
+    /// signatures, each from a different member of the signer set. A
+    /// signature from outside the set or a signer listed twice rejects it.
+    pub fn is_authorised(&self, tx: &Transaction) -> bool {
+        let members: HashSet<&String> = self.signers.iter().collect();
+        let mut approvals = HashSet::new();
+        let distinct_members =
+            tx.multisig_signatures.iter().all(|s| members.contains(&s.signer) && approvals.insert(&s.signer));
+        distinct_members && approvals.len() >= self.threshold as usize && tx.verify_multisig_signatures()
+    }
+}
+
+impl Transaction {
+    /// Create and sign a RegisterMultisig transaction. The fee is paid by the
+    /// creator; the new account starts with a zero balance.
+    pub fn new_register_multisig_signed(
+        from_secret_key: &SecretKey,
+        signers: Vec<String>,
+        threshold: u32,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        let signer_pk = from_secret_key.to_public();
+        let account = MultisigAccount::new(signers, threshold);
+        let mut tx = Transaction {
+            from: hex::encode(signer_pk.to_bytes()),
+            to: account.address(),
+            amount: 0,
+            fee,
+            nonce,
+            storage: Some(StorageTx::RegisterMultisig {
+                signers: account.signers,
+                threshold: account.threshold,
+            }),
+            signature: None,
+            multisig_signatures: Vec::new(),
+            valid_after: None,
+        };
+
+        let msg = tx.to_hash_bytes();
+        let sig = from_secret_key.sign(signing_context(SIGNING_CONTEXT).bytes(&msg), &signer_pk);
+        tx.signature = Some(hex::encode(sig.to_bytes()));
+        tx
+    }
+
+    /// Create an unsigned transfer out of a multisig account. Co-signers add
+    /// their approvals with [`Transaction::add_multisig_signature`].
+    pub fn new_multisig_transfer(
+        multisig_address: String,
+        to: String,
+        amount: u64,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Transaction::new(multisig_address, to, amount, fee, nonce)
+    }
+
+    /// Append a co-signer signature over the transaction body.
+    pub fn add_multisig_signature(&mut self, secret_key: &SecretKey) {
+        let signer_pk = secret_key.to_public();
+        let msg = self.to_hash_bytes();
+        let sig = secret_key.sign(signing_context(SIGNING_CONTEXT).bytes(&msg), &signer_pk);
+        self.multisig_signatures.push(MultisigSignature {
+            signer: hex::encode(signer_pk.to_bytes()),
+            signature: hex::encode(sig.to_bytes()),
+        });
+    }
+
+    /// Whether this transaction spends from a multisig account.
+    pub fn is_multisig(&self) -> bool {
+        !self.multisig_signatures.is_empty()
+    }
+
+    /// Verify every attached co-signer signature. Membership and threshold
+    /// are checked against chain state by [`MultisigAccount::is_authorised`].
+    pub fn verify_multisig_signatures(&self) -> bool {
+        let msg = self.to_hash_bytes();
+        self.multisig_signatures.iter().all(|s| {
+            let pk = match hex::decode(&s.signer).ok().and_then(|b| PublicKey::from_bytes(&b).ok()) {
+                Some(pk) => pk,
+                None => return false,
+            };
+            let sig = match hex::decode(&s.signature).ok().and_then(|b| Signature::from_bytes(&b).ok()) {
+                Some(sig) => sig,
+                None => return false,
+            };
+            pk.verify(signing_context(SIGNING_CONTEXT).bytes(&msg), &sig).is_ok()
+        })
+    }
+}
@@ -1,5 +1,6 @@
-use super::core::Transaction;
+use super::core::{StorageTx, Transaction};
 use crate::blockchain::constants::SIGNING_CONTEXT;
+use crate::session_keys::{self, SessionRole};
 use schnorrkel::{signing_context, Signature, PublicKey, SecretKey};
 
 impl Transaction {
@@ -20,6 +21,8 @@ impl Transaction {
             nonce,
             storage: None,
             signature: None,
+            multisig_signatures: Vec::new(),
+            valid_after: None,
         };
         // Sign hash bytes.
         let msg = tx.to_hash_bytes();
@@ -28,6 +31,15 @@ impl Transaction {
         tx
     }
 
+    /// Sign an already populated transaction, setting `from` to the signer.
+    pub fn sign(&mut self, secret_key: &SecretKey) {
+        let signer_pk = secret_key.to_public();
+        self.from = hex::encode(signer_pk.to_bytes());
+        let msg = self.to_hash_bytes();
+        let sig = secret_key.sign(signing_context(SIGNING_CONTEXT).bytes(&msg), &signer_pk);
+        self.signature = Some(hex::encode(sig.to_bytes()));
+    }
+
     /// Verify Schnorrkel signature matches `from` field.
     pub fn verify_signature(&self) -> bool {
         let signer_bytes = match hex::decode(&self.from) { Ok(b) => b, Err(_) => return false };
@@ -64,29 +76,42 @@ impl Transaction {
         tx
     }
 
-    /// Create and sign an admin UpdateMetrics transaction (fee 0). Only oracle key allowed.
+    /// Create an UpdateMetrics transaction (fee 0) reporting `account`'s
+    /// metrics, signed by its `Metrics` session key rather than the account key.
     pub fn new_update_metrics_signed(
-        oracle_secret_key: &SecretKey,
+        account: &str,
+        session_key: &ed25519_dalek::SigningKey,
         metrics: Vec<crate::distributed_storage::allocation::NodeMetrics>,
         nonce: u64,
     ) -> Self {
-        let signer_pk = oracle_secret_key.to_public();
         let mut tx = Transaction {
-            from: hex::encode(signer_pk.to_bytes()),
+            from: account.to_string(),
             to: String::new(),
             amount: 0,
             fee: 0,
             nonce,
-            storage: Some(super::core::StorageTx::UpdateMetrics { metrics }),
+            storage: Some(StorageTx::UpdateMetrics {
+                metrics,
+                session_key: hex::encode(session_key.verifying_key().to_bytes()),
+            }),
             signature: None,
+            multisig_signatures: Vec::new(),
+            valid_after: None,
         };
 
-        let msg = tx.to_hash_bytes();
-        let sig = oracle_secret_key.sign(signing_context(SIGNING_CONTEXT).bytes(&msg), &signer_pk);
-        tx.signature = Some(hex::encode(sig.to_bytes()));
+        let sig = session_keys::sign_as(SessionRole::Metrics, session_key, &tx.to_hash_bytes());
+        tx.signature = Some(hex::encode(sig));
         tx
     }
 
+    /// Verify an UpdateMetrics transaction is signed by the session key it
+    /// names. Whether that key is the sender's is checked against chain state.
+    pub fn verify_session_signature(&self) -> bool {
+        let Some(StorageTx::UpdateMetrics { session_key, .. }) = &self.storage else { return false };
+        let Ok(signature) = hex::decode(self.signature.clone().unwrap_or_default()) else { return false };
+        session_keys::verify_as(SessionRole::Metrics, session_key, &self.to_hash_bytes(), &signature)
+    }
+
     /// Create and sign a PoUWEvaluationHash storage transaction.
     pub fn new_pouw_evaluation_signed(
         from_secret_key: &SecretKey,
@@ -103,6 +128,8 @@ impl Transaction {
             nonce,
             storage: Some(super::core::StorageTx::PoUWEvaluationHash { task_id, evaluation_hash }),
             signature: None,
+            multisig_signatures: Vec::new(),
+            valid_after: None,
         };
 
         let msg = tx.to_hash_bytes();
@@ -110,4 +137,237 @@ impl Transaction {
         tx.signature = Some(hex::encode(sig.to_bytes()));
         tx
     }
+
+    /// Create and sign a SubmitFraudProof transaction. The challenger pays the fee.
+    pub fn new_fraud_proof_signed(
+        from_secret_key: &SecretKey,
+        proof: crate::pouw::fraud::FraudProof,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::SubmitFraudProof { proof: Box::new(proof) },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a RecordConsensusEvaluation transaction.
+    pub fn new_consensus_evaluation_signed(
+        from_secret_key: &SecretKey,
+        task_id: String,
+        evaluations: Vec<crate::pouw::types::SignedEvaluation>,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::RecordConsensusEvaluation { task_id, evaluations },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a PostJob transaction. The sender pays the job's reward
+    /// into escrow plus the fee.
+    pub fn new_post_job_signed(from_secret_key: &SecretKey, job: crate::job::Job, fee: u64, nonce: u64) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::PostJob { job }, fee, nonce)
+    }
+
+    /// Create and sign a SignOffMilestone transaction. The sender must be the
+    /// job's milestone evaluator.
+    pub fn new_milestone_sign_off_signed(
+        from_secret_key: &SecretKey,
+        job_id: u64,
+        milestone: u32,
+        worker: String,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::SignOffMilestone { job_id, milestone, worker },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a CancelJob transaction. The sender must be the job's
+    /// poster.
+    pub fn new_cancel_job_signed(from_secret_key: &SecretKey, job_id: u64, fee: u64, nonce: u64) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::CancelJob { job_id }, fee, nonce)
+    }
+
+    /// Create and sign an OpenStorageContract transaction. The sender must
+    /// be the contract's owner and pays its escrow plus the fee.
+    pub fn new_open_storage_contract_signed(
+        from_secret_key: &SecretKey,
+        contract: crate::distributed_storage::contracts::StorageContract,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::OpenStorageContract { contract: Box::new(contract) },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a SubmitStorageProof transaction. The sender must be
+    /// the contract's storage node.
+    pub fn new_storage_proof_signed(
+        from_secret_key: &SecretKey,
+        proof: crate::distributed_storage::contracts::StorageProof,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::SubmitStorageProof { proof }, fee, nonce)
+    }
+
+    /// Create and sign an AcceptStorageContract transaction. The sender must
+    /// be the contract's node and have the stake to back its collateral.
+    pub fn new_accept_storage_contract_signed(
+        from_secret_key: &SecretKey,
+        contract_id: String,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::AcceptStorageContract { contract_id },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a RepairStorageContract transaction. The sender must
+    /// be the contract's owner and pays the new escrow plus the fee.
+    pub fn new_repair_storage_contract_signed(
+        from_secret_key: &SecretKey,
+        contract_id: String,
+        node: String,
+        audits: u32,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::RepairStorageContract { contract_id, node, audits },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a RegisterSessionKeys transaction. The sender must be
+    /// the account the keys act for.
+    pub fn new_session_keys_signed(
+        from_secret_key: &SecretKey,
+        keys: crate::session_keys::SessionKeys,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::RegisterSessionKeys { keys }, fee, nonce)
+    }
+
+    /// Create and sign a RegisterLongTask transaction.
+    pub fn new_long_task_signed(
+        from_secret_key: &SecretKey,
+        task: crate::pouw::progress::LongTask,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::RegisterLongTask { task: Box::new(task) },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a CommitProgress transaction.
+    pub fn new_progress_signed(
+        from_secret_key: &SecretKey,
+        delta: crate::pouw::progress::ProgressDelta,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::CommitProgress { delta }, fee, nonce)
+    }
+
+    /// Create and sign a CommitEvaluation transaction.
+    pub fn new_evaluation_commit_signed(
+        from_secret_key: &SecretKey,
+        commitment: crate::pouw::commit_reveal::SignedCommitment,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::CommitEvaluation { commitment }, fee, nonce)
+    }
+
+    /// Create and sign a RevealEvaluation transaction.
+    pub fn new_evaluation_reveal_signed(
+        from_secret_key: &SecretKey,
+        reveal: crate::pouw::commit_reveal::EvaluationReveal,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::RevealEvaluation { reveal: Box::new(reveal) },
+            fee,
+            nonce,
+        )
+    }
+
+    /// Create and sign a RegisterEndpoint transaction; the signer must be the
+    /// record's owner.
+    pub fn new_register_endpoint_signed(
+        from_secret_key: &SecretKey,
+        record: crate::endpoint::EndpointRecord,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(from_secret_key, super::core::StorageTx::RegisterEndpoint { record }, fee, nonce)
+    }
+
+    /// Create and sign a FailoverEndpoint transaction promoting `primary` at
+    /// `epoch`.
+    pub fn new_failover_endpoint_signed(
+        from_secret_key: &SecretKey,
+        endpoint_id: String,
+        primary: String,
+        epoch: u64,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        Self::new_payload_signed(
+            from_secret_key,
+            super::core::StorageTx::FailoverEndpoint { endpoint_id, primary, epoch },
+            fee,
+            nonce,
+        )
+    }
+
+    fn new_payload_signed(
+        from_secret_key: &SecretKey,
+        payload: super::core::StorageTx,
+        fee: u64,
+        nonce: u64,
+    ) -> Self {
+        let mut tx = Transaction {
+            from: String::new(),
+            to: String::new(),
+            amount: 0,
+            fee,
+            nonce,
+            storage: Some(payload),
+            signature: None,
+            multisig_signatures: Vec::new(),
+            valid_after: None,
+        };
+        tx.sign(from_secret_key);
+        tx
+    }
 }
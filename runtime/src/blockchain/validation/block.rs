@@ -1,5 +1,9 @@
 use crate::blockchain::{block::Block, chain::BlockchainError, state::State};
 use super::transaction::{validate_transaction_stateless, validate_transaction_stateful};
+use super::job::validate_job_binding;
+use super::progress::validate_progress_per_block;
+use crate::pouw::{task_types, Retarget};
+use crate::trace::{stage_span, TraceContext};
 
 /// Validate the structural relation between a new block and its predecessor.
 pub fn validate_block_structure(block: &Block, prev_block: &Block) -> Result<(), BlockchainError> {
@@ -16,26 +20,66 @@ pub fn validate_block_structure(block: &Block, prev_block: &Block) -> Result<(),
     Ok(())
 }
 
-/// Perform full validation of a block (header, PoUW, transactions).
-pub fn validate_block(block: &Block, prev_block: &Block, state: &State) -> Result<(), BlockchainError> {
+/// Check that a block uses the retargeted difficulty and, unless it trains
+/// an escrowed job whose epochs [`validate_job_binding`] pins, the
+/// retargeted task size.
+pub fn validate_retarget(block: &Block, expected: &Retarget) -> Result<(), BlockchainError> {
+    if block.difficulty != expected.difficulty {
+        return Err(BlockchainError::InvalidBlock(format!(
+            "Invalid difficulty. Expected {}, got {}",
+            expected.difficulty, block.difficulty
+        )));
+    }
+    if block.task.job_id.is_none() && block.task.epochs != expected.epochs {
+        return Err(BlockchainError::InvalidBlock(format!(
+            "Invalid task size. Expected {} epochs, got {}",
+            expected.epochs, block.task.epochs
+        )));
+    }
+    Ok(())
+}
+
+/// Perform full validation of a block (header, retarget, PoUW, transactions).
+pub fn validate_block(
+    block: &Block,
+    prev_block: &Block,
+    state: &State,
+    expected: &Retarget,
+) -> Result<(), BlockchainError> {
     // Header checks
     validate_block_structure(block, prev_block)?;
     if block.calculate_hash() != block.hash {
         return Err(BlockchainError::InvalidBlock("Block hash is incorrect".into()));
     }
+    validate_retarget(block, expected)?;
 
-    // PoUW verification
-    if !block.task.verify(&block.solution, block.difficulty) {
+    // PoUW verification, with the limits fixed at genesis
+    let trace = TraceContext::continue_from(block.task.trace.as_deref());
+    let verified = stage_span(trace.as_ref(), "evaluate").in_scope(|| {
+        task_types::verify(&block.task, &block.solution, block.difficulty, &state.consensus.pouw)
+    });
+    if !verified {
         return Err(BlockchainError::InvalidBlock("Invalid PoUW solution".into()));
     }
 
     // Transaction checks on a temp state copy
+    validate_progress_per_block(&block.transactions)?;
     let mut temp_state = state.clone();
     for tx in &block.transactions {
+        if !tx.is_eligible(block.index as u64, block.timestamp) {
+            return Err(BlockchainError::InvalidBlock(format!(
+                "Transaction {} included before its time lock",
+                tx.hash()
+            )));
+        }
         validate_transaction_stateless(tx)?;
         validate_transaction_stateful(tx, &temp_state)?;
         temp_state.apply_transaction(tx)?;
     }
 
+    // A job is settled after the block's transactions, so it may be posted
+    // in the block that mines it.
+    validate_job_binding(&block.task, &temp_state)?;
+
     Ok(())
 } 
\ No newline at end of file
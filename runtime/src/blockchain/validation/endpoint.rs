@@ -0,0 +1,37 @@
+use crate::blockchain::{chain::BlockchainError, state::State};
+use crate::endpoint::{EndpointError, EndpointRecord};
+
+fn invalid(e: EndpointError) -> BlockchainError {
+    BlockchainError::TransactionValidationError(format!("Invalid endpoint transaction: {}", e))
+}
+
+/// Check an endpoint registration is well formed, new and made by its owner.
+pub fn validate_register_endpoint(
+    sender: &str,
+    record: &EndpointRecord,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    record.check_well_formed().map_err(invalid)?;
+    if record.owner != sender {
+        return Err(invalid(EndpointError::Unauthorised(sender.to_string(), record.endpoint_id.clone())));
+    }
+    if state.endpoints.contains_key(&record.endpoint_id) {
+        return Err(invalid(EndpointError::AlreadyRegistered(record.endpoint_id.clone())));
+    }
+    Ok(())
+}
+
+/// Check a failover promotes a standby of a known endpoint at the next epoch.
+pub fn validate_failover(
+    sender: &str,
+    endpoint_id: &str,
+    primary: &str,
+    epoch: u64,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    let record = state
+        .endpoints
+        .get(endpoint_id)
+        .ok_or_else(|| invalid(EndpointError::Unknown(endpoint_id.to_string())))?;
+    record.check_failover(sender, primary, epoch).map_err(invalid)
+}
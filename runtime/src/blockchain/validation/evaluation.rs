@@ -0,0 +1,92 @@
+use crate::blockchain::{chain::BlockchainError, state::{EvaluationTask, State}};
+use crate::pouw::commit_reveal::{EvaluationReveal, EvaluationRound, SignedCommitment};
+use crate::pouw::outlier::{aggregate, AggregationConfig};
+use crate::pouw::types::SignedEvaluation;
+
+/// The task's open evaluation, if its consensus is not recorded yet.
+fn open_task<'a>(task_id: &str, state: &'a State) -> Result<&'a EvaluationTask, BlockchainError> {
+    if state.consensus_evaluations.contains_key(task_id) {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Consensus for task {} already recorded",
+            task_id
+        )));
+    }
+    state.evaluation_tasks.get(task_id).ok_or_else(|| {
+        BlockchainError::TransactionValidationError(format!("Task {} is not open for evaluation", task_id))
+    })
+}
+
+/// Check that a task is open for evaluation and has no consensus yet, that
+/// a quorum of its selected validators evaluated it, each staked, and that
+/// the evaluations aggregate.
+pub fn validate_consensus_evaluation(
+    task_id: &str,
+    evaluations: &[SignedEvaluation],
+    state: &State,
+) -> Result<(), BlockchainError> {
+    let task = open_task(task_id, state)?;
+    if let Some(eval) = evaluations.iter().find(|e| !task.is_selected(&e.validator)) {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Evaluator {} was not selected for task {}",
+            eval.validator, task_id
+        )));
+    }
+    if evaluations.len() < task.quorum() {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Task {} needs evaluations from {} of its {} selected validators, got {}",
+            task_id,
+            task.quorum(),
+            task.selected.len(),
+            evaluations.len()
+        )));
+    }
+    let min_stake = state.consensus.validator_selection.min_stake;
+    if let Some(eval) = evaluations
+        .iter()
+        .find(|e| state.stakes.get(&e.validator).copied().unwrap_or(0) < min_stake)
+    {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Evaluator {} is not a staked validator",
+            eval.validator
+        )));
+    }
+    aggregate(task_id, evaluations, &AggregationConfig::default())
+        .map(|_| ())
+        .map_err(|e| BlockchainError::TransactionValidationError(format!("Invalid consensus evaluation: {}", e)))
+}
+
+/// Check that a commitment comes from a staked validator selected for a
+/// task open for evaluation and fits the task's round, which must still be
+/// in its commit phase.
+pub fn validate_evaluation_commit(commitment: &SignedCommitment, state: &State) -> Result<(), BlockchainError> {
+    let task = open_task(&commitment.task_id, state)?;
+    if !task.is_selected(&commitment.validator) {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Evaluator {} was not selected for task {}",
+            commitment.validator, commitment.task_id
+        )));
+    }
+    if state.stakes.get(&commitment.validator).copied().unwrap_or(0) < state.consensus.validator_selection.min_stake {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Evaluator {} is not a staked validator",
+            commitment.validator
+        )));
+    }
+    let result = match state.evaluation_rounds.get(&commitment.task_id) {
+        Some(round) => round.check_commit(commitment),
+        None => EvaluationRound::new(&commitment.task_id).check_commit(commitment),
+    };
+    result.map_err(|e| BlockchainError::TransactionValidationError(format!("Invalid evaluation commitment: {}", e)))
+}
+
+/// Check that a reveal opens a commitment of the task's round during its
+/// reveal phase.
+pub fn validate_evaluation_reveal(reveal: &EvaluationReveal, state: &State) -> Result<(), BlockchainError> {
+    let task_id = &reveal.evaluation.task_id;
+    let round = state.evaluation_rounds.get(task_id).ok_or_else(|| {
+        BlockchainError::TransactionValidationError(format!("No evaluation round open for task {}", task_id))
+    })?;
+    round
+        .check_reveal(reveal)
+        .map_err(|e| BlockchainError::TransactionValidationError(format!("Invalid evaluation reveal: {}", e)))
+}
@@ -0,0 +1,22 @@
+use crate::blockchain::{chain::BlockchainError, state::State};
+use crate::pouw::fraud::FraudProof;
+
+/// Check that a fraud proof targets a block still in its challenge window,
+/// carries that block's task and solution, and that the claimed check fails.
+pub fn validate_fraud_proof(proof: &FraudProof, state: &State) -> Result<(), BlockchainError> {
+    let claim = state.pouw_claims.get(&proof.block_index).ok_or_else(|| {
+        BlockchainError::TransactionValidationError(format!(
+            "Block {} is not open to challenge",
+            proof.block_index
+        ))
+    })?;
+    if claim.block_hash != proof.block_hash || claim.evidence_hash != proof.evidence_hash() {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Fraud proof does not match the solution in block {}",
+            proof.block_index
+        )));
+    }
+    proof
+        .verify(claim.difficulty)
+        .map_err(|e| BlockchainError::TransactionValidationError(format!("Invalid fraud proof: {}", e)))
+}
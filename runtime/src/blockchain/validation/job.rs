@@ -0,0 +1,93 @@
+use crate::blockchain::{chain::BlockchainError, state::State};
+use crate::job::Job;
+use crate::pouw::PoUWTask;
+
+/// Check a job `poster` posts with `nonce` has a reward, work to do and the
+/// id derived from both, unused.
+pub fn validate_post_job(poster: &str, nonce: u64, job: &Job, state: &State) -> Result<(), BlockchainError> {
+    if job.reward == 0 || job.iterations == 0 {
+        return Err(BlockchainError::TransactionValidationError(
+            "Posted job needs a reward and at least one iteration".into(),
+        ));
+    }
+    let expected = Job::posted_id(poster, nonce);
+    if job.id != expected {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Job posted by {} with nonce {} must have id {}, got {}",
+            poster, nonce, expected, job.id
+        )));
+    }
+    if state.job_escrows.contains_key(&job.id) {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Job {} already posted",
+            job.id
+        )));
+    }
+    if !job.milestones.is_empty() {
+        let total = job.milestones.iter().try_fold(0u64, |sum, m| sum.checked_add(m.reward));
+        if !job.milestone_evaluator.as_deref().is_some_and(|e| !e.is_empty())
+            || job.milestones.iter().any(|m| m.reward == 0)
+            || !total.is_some_and(|total| total <= job.reward)
+        {
+            return Err(BlockchainError::TransactionValidationError(format!(
+                "Milestones of job {} need an evaluator, a reward each and at most the job's reward in all",
+                job.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `sender` is the evaluator of an escrowed job and signs off one
+/// of its milestones not yet paid.
+pub fn validate_milestone_sign_off(
+    sender: &str,
+    job_id: u64,
+    milestone: u32,
+    worker: &str,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    let invalid = |reason: String| BlockchainError::TransactionValidationError(reason);
+    let escrow =
+        state.job_escrows.get(&job_id).ok_or_else(|| invalid(format!("Job {} has no escrow", job_id)))?;
+    if escrow.job.milestone_evaluator.as_deref() != Some(sender) {
+        return Err(invalid(format!("{} may not sign off milestones of job {}", sender, job_id)));
+    }
+    if escrow.job.milestones.get(milestone as usize).is_none() {
+        return Err(invalid(format!("Job {} has no milestone {}", job_id, milestone)));
+    }
+    if escrow.signed_off.contains(&milestone) {
+        return Err(invalid(format!("Milestone {} of job {} already signed off", milestone, job_id)));
+    }
+    if worker.is_empty() {
+        return Err(invalid("Milestone sign-off names no worker".into()));
+    }
+    Ok(())
+}
+
+/// Check that `sender` posted an escrowed job nobody has been paid for yet.
+pub fn validate_cancel_job(sender: &str, job_id: u64, state: &State) -> Result<(), BlockchainError> {
+    let invalid = |reason: String| BlockchainError::TransactionValidationError(reason);
+    let escrow =
+        state.job_escrows.get(&job_id).ok_or_else(|| invalid(format!("Job {} has no escrow", job_id)))?;
+    if escrow.poster != sender {
+        return Err(invalid(format!("{} may not cancel job {}", sender, job_id)));
+    }
+    if !escrow.is_untouched() {
+        return Err(invalid(format!("Job {} was already mined or paid a milestone", job_id)));
+    }
+    Ok(())
+}
+
+/// Check that a task claiming a job trains that job as escrowed in `state`.
+pub fn validate_job_binding(task: &PoUWTask, state: &State) -> Result<(), BlockchainError> {
+    let Some(job_id) = task.job_id else {
+        return Ok(());
+    };
+    match state.job_escrows.get(&job_id) {
+        Some(escrow) if escrow.mined => Err(BlockchainError::InvalidBlock(format!("Job {} was already mined", job_id))),
+        Some(escrow) if escrow.job.matches(task) => Ok(()),
+        Some(_) => Err(BlockchainError::InvalidBlock(format!("PoUW task does not match job {}", job_id))),
+        None => Err(BlockchainError::InvalidBlock(format!("Job {} has no escrow", job_id))),
+    }
+}
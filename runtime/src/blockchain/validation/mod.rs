@@ -1,14 +1,32 @@
 //! Blockchain validation utilities broken into focused sub-modules.
 
 mod block;
+mod endpoint;
+mod evaluation;
+mod fraud;
+mod job;
+mod progress;
+mod session;
+mod storage;
 mod pow;
 mod transaction;
 
-pub use block::{validate_block_structure, validate_block};
+pub use block::{validate_block_structure, validate_block, validate_retarget};
+pub use endpoint::{validate_failover, validate_register_endpoint};
+pub use evaluation::validate_consensus_evaluation;
+pub use fraud::validate_fraud_proof;
+pub use job::{validate_cancel_job, validate_job_binding, validate_post_job};
 pub use pow::validate_pow_solution;
+pub use progress::{validate_long_task, validate_progress, validate_progress_per_block};
+pub use session::validate_register_session_keys;
+pub use storage::{
+    validate_accept_storage_contract, validate_open_storage_contract, validate_repair_storage_contract,
+    validate_storage_proof,
+};
 pub use transaction::{
     validate_transaction_stateless,
     validate_transaction_stateful,
     validate_transaction_with_state,
+    validate_multisig_authorization,
     apply_transaction_to_state,
 }; 
\ No newline at end of file
@@ -0,0 +1,46 @@
+use crate::blockchain::{chain::BlockchainError, state::State, transaction::{StorageTx, Transaction}};
+use crate::pouw::progress::{LongTask, ProgressDelta};
+use std::collections::HashSet;
+
+/// Check a long task is new and can make progress.
+pub fn validate_long_task(task: &LongTask, state: &State) -> Result<(), BlockchainError> {
+    if task.task.epochs == 0 || task.max_epochs_per_block == 0 {
+        return Err(BlockchainError::TransactionValidationError(
+            "Long task needs at least one epoch and one epoch per block".into(),
+        ));
+    }
+    if state.long_tasks.contains_key(&task.id()) {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Long task {} already registered",
+            task.id()
+        )));
+    }
+    Ok(())
+}
+
+/// Check a progress delta continues its task's committed checkpoint.
+pub fn validate_progress(delta: &ProgressDelta, state: &State) -> Result<(), BlockchainError> {
+    let progress = state.long_tasks.get(&delta.task_id).ok_or_else(|| {
+        BlockchainError::TransactionValidationError(format!("Unknown long task {}", delta.task_id))
+    })?;
+    progress
+        .verify(delta)
+        .map_err(|e| BlockchainError::TransactionValidationError(format!("Invalid progress: {}", e)))
+}
+
+/// A block may advance each long task at most once, so that no block
+/// verifies more than the task's per-block epoch limit.
+pub fn validate_progress_per_block(transactions: &[Transaction]) -> Result<(), BlockchainError> {
+    let mut seen = HashSet::new();
+    for tx in transactions {
+        if let Some(StorageTx::CommitProgress { delta }) = &tx.storage {
+            if !seen.insert(&delta.task_id) {
+                return Err(BlockchainError::InvalidBlock(format!(
+                    "Long task {} advanced more than once",
+                    delta.task_id
+                )));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::blockchain::{chain::BlockchainError, state::State};
+use crate::session_keys::{SessionKeyError, SessionKeys, SessionRole};
+
+fn invalid(e: SessionKeyError) -> BlockchainError {
+    BlockchainError::TransactionValidationError(format!("Invalid session key transaction: {}", e))
+}
+
+/// Check a session key registration is well formed, made by its account at
+/// the next epoch and reuses no other account's key.
+pub fn validate_register_session_keys(
+    sender: &str,
+    keys: &SessionKeys,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    keys.check_well_formed().map_err(invalid)?;
+    keys.check_rotation(sender, state.session_keys.get(sender)).map_err(invalid)?;
+    let registered = state.session_keys.values().filter(|other| other.account != sender);
+    for other in registered {
+        if let Some(key) =
+            keys.keys.iter().find(|k| other.keys.iter().any(|o| o.public_key == k.public_key))
+        {
+            return Err(invalid(SessionKeyError::KeyInUse {
+                key: key.public_key.clone(),
+                account: other.account.clone(),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// Check `session_key` is the metrics session key `sender` registered.
+pub fn validate_update_metrics(sender: &str, session_key: &str, state: &State) -> Result<(), BlockchainError> {
+    if state.session_account(SessionRole::Metrics, session_key) != Some(sender) {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Metrics of {} are not signed by its metrics session key",
+            sender
+        )));
+    }
+    Ok(())
+}
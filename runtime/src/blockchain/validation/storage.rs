@@ -0,0 +1,83 @@
+use crate::blockchain::{chain::BlockchainError, state::State};
+use crate::distributed_storage::contracts::{ContractError, StorageContract, StorageProof};
+
+fn invalid(e: ContractError) -> BlockchainError {
+    BlockchainError::TransactionValidationError(format!("Invalid storage contract transaction: {}", e))
+}
+
+/// Check a file is paid for at least at the storage price fixed at genesis.
+pub fn validate_store_file(
+    total_bytes: u128,
+    price: u128,
+    replica_nodes: &[String],
+    state: &State,
+) -> Result<(), BlockchainError> {
+    let minimum = state.consensus.storage_pricing.minimum_price(total_bytes, replica_nodes.len());
+    if price < minimum {
+        return Err(BlockchainError::TransactionValidationError(format!(
+            "Storing {} bytes costs at least {}, got {}",
+            total_bytes, minimum, price
+        )));
+    }
+    Ok(())
+}
+
+/// Check a storage contract is well formed, new, opened by its owner and
+/// backed by enough of the node's stake.
+pub fn validate_open_storage_contract(
+    sender: &str,
+    contract: &StorageContract,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    contract.check_well_formed().map_err(invalid)?;
+    if contract.owner != sender {
+        return Err(invalid(ContractError::Malformed("sender is not the owner")));
+    }
+    if state.storage_contracts.contains_key(&contract.contract_id) {
+        return Err(invalid(ContractError::AlreadyExists(contract.contract_id.clone())));
+    }
+    let stake = state.stakes.get(&contract.node).copied().unwrap_or(0);
+    if stake < contract.collateral {
+        return Err(invalid(ContractError::InsufficientStake { required: contract.collateral, available: stake }));
+    }
+    Ok(())
+}
+
+/// Check a storage proof answers the open challenge of a known contract.
+pub fn validate_storage_proof(sender: &str, proof: &StorageProof, state: &State) -> Result<(), BlockchainError> {
+    let contract = state
+        .storage_contracts
+        .get(&proof.contract_id)
+        .ok_or_else(|| invalid(ContractError::Unknown(proof.contract_id.clone())))?;
+    contract.check_proof(sender, proof).map_err(invalid)
+}
+
+/// Check the node of a known pending contract accepts it with enough stake.
+pub fn validate_accept_storage_contract(
+    sender: &str,
+    contract_id: &str,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    let contract = state
+        .storage_contracts
+        .get(contract_id)
+        .ok_or_else(|| invalid(ContractError::Unknown(contract_id.to_string())))?;
+    let stake = state.stakes.get(sender).copied().unwrap_or(0);
+    contract.check_accept(sender, stake).map_err(invalid)
+}
+
+/// Check the owner moves a known contract to a new node with enough stake.
+pub fn validate_repair_storage_contract(
+    sender: &str,
+    contract_id: &str,
+    node: &str,
+    audits: u32,
+    state: &State,
+) -> Result<(), BlockchainError> {
+    let contract = state
+        .storage_contracts
+        .get(contract_id)
+        .ok_or_else(|| invalid(ContractError::Unknown(contract_id.to_string())))?;
+    let stake = state.stakes.get(node).copied().unwrap_or(0);
+    contract.check_repair(sender, node, audits, stake).map_err(invalid)
+}
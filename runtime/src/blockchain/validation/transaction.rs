@@ -1,22 +1,111 @@
-use crate::blockchain::{transaction::{Transaction, StorageTx}, chain::BlockchainError, state::State};
+use crate::blockchain::{transaction::{Transaction, StorageTx, MultisigAccount}, chain::BlockchainError, state::State};
+use super::endpoint::{validate_failover, validate_register_endpoint};
+use super::evaluation::{validate_consensus_evaluation, validate_evaluation_commit, validate_evaluation_reveal};
+use super::fraud::validate_fraud_proof;
+use super::job::{validate_cancel_job, validate_milestone_sign_off, validate_post_job};
+use super::session::{validate_register_session_keys, validate_update_metrics};
+use super::progress::{validate_long_task, validate_progress};
+use super::storage::{
+    validate_accept_storage_contract, validate_open_storage_contract, validate_repair_storage_contract,
+    validate_storage_proof, validate_store_file,
+};
 use std::collections::HashMap;
 
 /// Stateless checks such as signature validity.
 pub fn validate_transaction_stateless(tx: &Transaction) -> Result<(), BlockchainError> {
-    if !tx.verify_signature() {
+    let signed = if tx.is_multisig() {
+        tx.signature.is_none() && tx.storage.is_none() && tx.verify_multisig_signatures()
+    } else if let Some(StorageTx::UpdateMetrics { .. }) = &tx.storage {
+        tx.verify_session_signature()
+    } else {
+        tx.verify_signature()
+    };
+    if !signed {
         return Err(BlockchainError::TransactionValidationError("Invalid signature".into()));
     }
-    // Additional rule for UpdateMetrics – must come from oracle pub key.
-    if let Some(StorageTx::UpdateMetrics { .. }) = &tx.storage {
-        if tx.from != crate::blockchain::constants::METRICS_ORACLE_PUB {
+    // A node reports its own metrics only.
+    if let Some(StorageTx::UpdateMetrics { metrics, .. }) = &tx.storage {
+        if metrics.iter().any(|m| m.node_id != tx.from) {
             return Err(BlockchainError::TransactionValidationError("Unauthorised metrics submitter".into()));
         }
     }
+    if let Some(StorageTx::RegisterMultisig { signers, threshold }) = &tx.storage {
+        let account = MultisigAccount::new(signers.clone(), *threshold);
+        if !account.is_well_formed() || tx.to != account.address() {
+            return Err(BlockchainError::TransactionValidationError("Malformed multisig registration".into()));
+        }
+    }
     Ok(())
 }
 
+/// Check that a multisig spend meets the registered signer threshold.
+pub fn validate_multisig_authorization(tx: &Transaction, state: &State) -> Result<(), BlockchainError> {
+    if !tx.is_multisig() {
+        return Ok(());
+    }
+    match state.multisig_accounts.get(&tx.from) {
+        Some(account) if account.is_authorised(tx) => Ok(()),
+        Some(account) => Err(BlockchainError::TransactionValidationError(format!(
+            "Multisig threshold not met for {}: need {} of {}",
+            tx.from,
+            account.threshold,
+            account.signers.len()
+        ))),
+        None => Err(BlockchainError::TransactionValidationError(format!(
+            "Unknown multisig account {}",
+            tx.from
+        ))),
+    }
+}
+
 /// Validate nonce & balance against the current state.
 pub fn validate_transaction_stateful(tx: &Transaction, state: &State) -> Result<(), BlockchainError> {
+    validate_multisig_authorization(tx, state)?;
+    if let Some(StorageTx::RegisterMultisig { .. }) = &tx.storage {
+        if state.multisig_accounts.contains_key(&tx.to) {
+            return Err(BlockchainError::TransactionValidationError(format!(
+                "Multisig account {} already registered",
+                tx.to
+            )));
+        }
+    }
+
+    match &tx.storage {
+        Some(StorageTx::StoreFile { total_bytes, price, replica_nodes, .. }) => {
+            validate_store_file(*total_bytes, *price, replica_nodes, state)?
+        }
+        Some(StorageTx::SubmitFraudProof { proof }) => validate_fraud_proof(proof, state)?,
+        Some(StorageTx::RecordConsensusEvaluation { task_id, evaluations }) => {
+            validate_consensus_evaluation(task_id, evaluations, state)?
+        }
+        Some(StorageTx::PostJob { job }) => validate_post_job(&tx.from, tx.nonce, job, state)?,
+        Some(StorageTx::RegisterLongTask { task }) => validate_long_task(task, state)?,
+        Some(StorageTx::CommitProgress { delta }) => validate_progress(delta, state)?,
+        Some(StorageTx::CommitEvaluation { commitment }) => validate_evaluation_commit(commitment, state)?,
+        Some(StorageTx::RevealEvaluation { reveal }) => validate_evaluation_reveal(reveal, state)?,
+        Some(StorageTx::RegisterEndpoint { record }) => validate_register_endpoint(&tx.from, record, state)?,
+        Some(StorageTx::FailoverEndpoint { endpoint_id, primary, epoch }) => {
+            validate_failover(&tx.from, endpoint_id, primary, *epoch, state)?
+        }
+        Some(StorageTx::SignOffMilestone { job_id, milestone, worker }) => {
+            validate_milestone_sign_off(&tx.from, *job_id, *milestone, worker, state)?
+        }
+        Some(StorageTx::CancelJob { job_id }) => validate_cancel_job(&tx.from, *job_id, state)?,
+        Some(StorageTx::OpenStorageContract { contract }) => {
+            validate_open_storage_contract(&tx.from, contract, state)?
+        }
+        Some(StorageTx::SubmitStorageProof { proof }) => validate_storage_proof(&tx.from, proof, state)?,
+        Some(StorageTx::AcceptStorageContract { contract_id }) => {
+            validate_accept_storage_contract(&tx.from, contract_id, state)?
+        }
+        Some(StorageTx::RepairStorageContract { contract_id, node, audits }) => {
+            validate_repair_storage_contract(&tx.from, contract_id, node, *audits, state)?
+        }
+        Some(StorageTx::RegisterSessionKeys { keys }) => validate_register_session_keys(&tx.from, keys, state)?,
+        Some(StorageTx::UpdateMetrics { session_key, .. }) => validate_update_metrics(&tx.from, session_key, state)?,
+        _ => {}
+    }
+
     let expected_nonce = state.get_nonce(&tx.from);
     if tx.nonce != expected_nonce {
         return Err(BlockchainError::TransactionValidationError(format!(
@@ -31,8 +120,28 @@ pub fn validate_transaction_stateful(tx: &Transaction, state: &State) -> Result<
     let total_cost: u128 = match &tx.storage {
         Some(StorageTx::StoreFile { price, .. }) => (*price as u128) + tx.fee as u128,
         Some(StorageTx::RewardHolding { .. }) => tx.fee as u128, // node only pays fee
-        Some(StorageTx::UpdateMetrics { .. }) => 0u128, // admin tx no cost
+        Some(StorageTx::UpdateMetrics { .. }) => 0u128, // metrics reports cost nothing
         Some(StorageTx::PoUWEvaluationHash { .. }) => 0u128,
+        Some(StorageTx::RegisterMultisig { .. }) => tx.fee as u128,
+        Some(StorageTx::PostJob { job }) => (job.reward as u128) + tx.fee as u128,
+        Some(StorageTx::OpenStorageContract { contract }) => (contract.escrow as u128) + tx.fee as u128,
+        Some(StorageTx::RepairStorageContract { contract_id, audits, .. }) => {
+            let payment = state.storage_contracts.get(contract_id).map_or(0, |c| c.payment_per_audit);
+            (payment as u128) * (*audits as u128) + tx.fee as u128
+        }
+        Some(StorageTx::SubmitFraudProof { .. })
+        | Some(StorageTx::RecordConsensusEvaluation { .. })
+        | Some(StorageTx::RegisterLongTask { .. })
+        | Some(StorageTx::CommitProgress { .. })
+        | Some(StorageTx::CommitEvaluation { .. })
+        | Some(StorageTx::RevealEvaluation { .. })
+        | Some(StorageTx::RegisterEndpoint { .. })
+        | Some(StorageTx::FailoverEndpoint { .. })
+        | Some(StorageTx::SignOffMilestone { .. })
+        | Some(StorageTx::CancelJob { .. })
+        | Some(StorageTx::SubmitStorageProof { .. })
+        | Some(StorageTx::AcceptStorageContract { .. })
+        | Some(StorageTx::RegisterSessionKeys { .. }) => tx.fee as u128,
         None => (tx.amount as u128) + tx.fee as u128,
     };
 
@@ -53,7 +162,8 @@ pub fn validate_transaction_with_state(
     balances: &HashMap<String, u64>,
     nonces: &HashMap<String, u64>,
 ) -> Result<(), BlockchainError> {
-    if !tx.verify_signature() {
+    let signed = if tx.is_multisig() { tx.verify_multisig_signatures() } else { tx.verify_signature() };
+    if !signed {
         return Err(BlockchainError::InvalidSignature);
     }
 
@@ -0,0 +1,235 @@
+//! Keeps a node connected to enough peers of each role without saturating it.
+//!
+//! Peers advertise the roles they serve (validator, storage provider, relay).
+//! [`ConnectionManager`] holds target counts per role and a hard cap on
+//! connections. Each maintenance pass it returns a [`ConnectionPlan`]: dial
+//! known peers of roles below target, and disconnect the lowest-scoring
+//! connections once the cap is exceeded. The best `target` peers of every
+//! role are protected from pruning, so a node over its cap sheds its least
+//! useful connections rather than its only relay.
+//!
+//! The manager is generic over the peer id so it can be driven by the libp2p
+//! service as well as tested on its own.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A service a peer offers the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PeerRole {
+    Validator,
+    StorageProvider,
+    Relay,
+}
+
+impl PeerRole {
+    pub const ALL: [PeerRole; 3] = [PeerRole::Validator, PeerRole::StorageProvider, PeerRole::Relay];
+}
+
+/// Target peer counts and limits, see [`ConnectionManager`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionLimits {
+    pub validators: usize,
+    pub storage_providers: usize,
+    pub relays: usize,
+    /// Connections above which the lowest-value peers are pruned.
+    pub max_peers: usize,
+    /// Seconds a new connection is left alone, giving it time to report
+    /// its roles before it can be pruned.
+    pub grace_secs: u64,
+    /// Seconds before a peer is dialed again after an attempt.
+    pub dial_backoff_secs: u64,
+    /// Seconds between maintenance passes.
+    pub maintenance_interval_secs: u64,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            validators: 8,
+            storage_providers: 4,
+            relays: 2,
+            max_peers: 32,
+            grace_secs: 30,
+            dial_backoff_secs: 60,
+            maintenance_interval_secs: 15,
+        }
+    }
+}
+
+impl ConnectionLimits {
+    pub fn target(&self, role: PeerRole) -> usize {
+        match role {
+            PeerRole::Validator => self.validators,
+            PeerRole::StorageProvider => self.storage_providers,
+            PeerRole::Relay => self.relays,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct PeerEntry {
+    roles: BTreeSet<PeerRole>,
+    score: i64,
+    /// When the current connection was established, if connected.
+    connected_at: Option<u64>,
+    last_dial: Option<u64>,
+}
+
+/// What a maintenance pass wants the network layer to do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionPlan<P> {
+    pub prune: Vec<P>,
+    pub dial: Vec<P>,
+}
+
+impl<P> ConnectionPlan<P> {
+    pub fn is_empty(&self) -> bool {
+        self.prune.is_empty() && self.dial.is_empty()
+    }
+}
+
+/// Tracks known and connected peers and plans connection changes.
+#[derive(Debug, Clone)]
+pub struct ConnectionManager<P: Ord> {
+    limits: ConnectionLimits,
+    peers: BTreeMap<P, PeerEntry>,
+}
+
+impl<P: Ord + Clone> ConnectionManager<P> {
+    pub fn new(limits: ConnectionLimits) -> Self {
+        Self { limits, peers: BTreeMap::new() }
+    }
+
+    pub fn limits(&self) -> &ConnectionLimits {
+        &self.limits
+    }
+
+    /// Remember a peer found through discovery as a dial candidate.
+    pub fn discovered(&mut self, peer: P) {
+        self.peers.entry(peer).or_default();
+    }
+
+    pub fn connected(&mut self, peer: P, now: u64) {
+        self.peers.entry(peer).or_default().connected_at = Some(now);
+    }
+
+    pub fn disconnected(&mut self, peer: &P) {
+        if let Some(entry) = self.peers.get_mut(peer) {
+            entry.connected_at = None;
+        }
+    }
+
+    /// Record the roles a peer reported for itself.
+    pub fn set_roles(&mut self, peer: P, roles: impl IntoIterator<Item = PeerRole>) {
+        self.peers.entry(peer).or_default().roles = roles.into_iter().collect();
+    }
+
+    /// Raise or lower a peer's value, e.g. for useful messages or failures.
+    pub fn adjust_score(&mut self, peer: &P, delta: i64) {
+        if let Some(entry) = self.peers.get_mut(peer) {
+            entry.score = entry.score.saturating_add(delta);
+        }
+    }
+
+    pub fn is_connected(&self, peer: &P) -> bool {
+        self.peers.get(peer).is_some_and(|e| e.connected_at.is_some())
+    }
+
+    pub fn connected_count(&self) -> usize {
+        self.peers.values().filter(|e| e.connected_at.is_some()).count()
+    }
+
+    /// Connected peers serving `role`.
+    pub fn count(&self, role: PeerRole) -> usize {
+        self.peers.values().filter(|e| e.connected_at.is_some() && e.roles.contains(&role)).count()
+    }
+
+    /// Plan one maintenance pass at time `now`. Peers chosen for dialing
+    /// are not chosen again until the dial backoff passes.
+    pub fn plan(&mut self, now: u64) -> ConnectionPlan<P> {
+        let prune = self.plan_prune(now);
+        let dial = self.plan_dial(now, prune.len());
+        for peer in &dial {
+            if let Some(entry) = self.peers.get_mut(peer) {
+                entry.last_dial = Some(now);
+            }
+        }
+        ConnectionPlan { prune, dial }
+    }
+
+    /// Connected peers of `role`, best first.
+    fn ranked(&self, role: PeerRole) -> Vec<&P> {
+        let mut peers: Vec<(&P, &PeerEntry)> = self
+            .peers
+            .iter()
+            .filter(|(_, e)| e.connected_at.is_some() && e.roles.contains(&role))
+            .collect();
+        peers.sort_by(|(a, ea), (b, eb)| eb.score.cmp(&ea.score).then(a.cmp(b)));
+        peers.into_iter().map(|(p, _)| p).collect()
+    }
+
+    fn plan_prune(&self, now: u64) -> Vec<P> {
+        let connected = self.connected_count();
+        if connected <= self.limits.max_peers {
+            return Vec::new();
+        }
+        let protected: BTreeSet<&P> = PeerRole::ALL
+            .iter()
+            .flat_map(|role| self.ranked(*role).into_iter().take(self.limits.target(*role)))
+            .collect();
+        let mut candidates: Vec<(&P, &PeerEntry)> = self
+            .peers
+            .iter()
+            .filter(|(p, e)| {
+                e.connected_at.is_some_and(|at| now.saturating_sub(at) >= self.limits.grace_secs)
+                    && !protected.contains(p)
+            })
+            .collect();
+        candidates.sort_by(|(a, ea), (b, eb)| ea.score.cmp(&eb.score).then(a.cmp(b)));
+        candidates.into_iter().take(connected - self.limits.max_peers).map(|(p, _)| p.clone()).collect()
+    }
+
+    fn plan_dial(&self, now: u64, pruned: usize) -> Vec<P> {
+        let connected = self.connected_count().saturating_sub(pruned);
+        let mut room = self.limits.max_peers.saturating_sub(connected);
+        let dialable = |e: &PeerEntry| {
+            e.connected_at.is_none()
+                && e.last_dial.map_or(true, |at| now.saturating_sub(at) >= self.limits.dial_backoff_secs)
+        };
+
+        let mut chosen: Vec<P> = Vec::new();
+        let mut unfilled = 0;
+        for role in PeerRole::ALL {
+            let mut deficit = self.limits.target(role).saturating_sub(self.count(role));
+            let mut candidates: Vec<(&P, &PeerEntry)> = self
+                .peers
+                .iter()
+                .filter(|(p, e)| e.roles.contains(&role) && dialable(e) && !chosen.contains(*p))
+                .collect();
+            candidates.sort_by(|(a, ea), (b, eb)| eb.score.cmp(&ea.score).then(a.cmp(b)));
+            for (peer, _) in candidates {
+                if deficit == 0 || room == 0 {
+                    break;
+                }
+                chosen.push(peer.clone());
+                deficit -= 1;
+                room -= 1;
+            }
+            unfilled += deficit;
+        }
+
+        // Peers whose roles are not known yet may fill the remaining gaps;
+        // their roles are learned once connected.
+        let unknown = self
+            .peers
+            .iter()
+            .filter(|(p, e)| e.roles.is_empty() && dialable(e) && !chosen.contains(*p))
+            .map(|(p, _)| p.clone())
+            .take(unfilled.min(room))
+            .collect::<Vec<_>>();
+        chosen.extend(unknown);
+        chosen
+    }
+}
@@ -0,0 +1,168 @@
+//! High-water marks of what a validator signed, so it never signs twice.
+//!
+//! A [`SlashingGuard`] is consulted before every vote and block signature.
+//! Per kind of message it keeps the highest height and round signed and the
+//! block signed there, and refuses a different block at the same height and
+//! round, or anything at an earlier one. Signing the same block again is
+//! allowed, as a crashed validator may have to resend it.
+//!
+//! The marks are persisted before the signature is released. Each update is
+//! written to a temporary file, synced and renamed over the old one, so a
+//! crash leaves either the old marks or the new ones, never a torn file.
+//! A validator restored from a backup may come back with marks older than
+//! what it signed since; raising the guard's floor to the height of the
+//! chain's tip with [`SlashingGuard::raise_floor`] makes it refuse every
+//! height it may already have signed at.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GuardError {
+    #[error("slashing guard I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("slashing guard state is corrupt: {0}")]
+    Corrupt(String),
+    #[error("already signed block {signed} for {kind} at height {height} round {round}")]
+    DoubleSign { kind: String, height: u32, round: u32, signed: String },
+    #[error("{kind} at height {height} round {round} is behind the last one signed")]
+    Regression { kind: String, height: u32, round: u32 },
+    #[error("{kind} at height {height} is at or below the floor {floor}")]
+    BelowFloor { kind: String, height: u32, floor: u32 },
+}
+
+/// The last height, round and block signed for one kind of message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedPosition {
+    pub height: u32,
+    pub round: u32,
+    pub block_hash: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GuardState {
+    /// Nothing at or below this height is signed.
+    #[serde(default)]
+    floor: Option<u32>,
+    /// Last position signed, by kind of message.
+    #[serde(default)]
+    signed: BTreeMap<String, SignedPosition>,
+}
+
+/// Writes `state` to `path` so that a crash leaves the old or new file.
+fn persist(path: &Path, state: &GuardState) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(&serde_json::to_vec(state).expect("guard state serializes"))?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    // The rename itself is only durable once the directory is synced.
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Refuses signatures that could equivocate.
+#[derive(Debug, Default)]
+pub struct SlashingGuard {
+    state: Mutex<GuardState>,
+    /// Where the state is persisted; `None` keeps it in memory only.
+    path: Option<PathBuf>,
+}
+
+impl SlashingGuard {
+    /// A guard remembering only what this process signs.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// A guard persisted at `path`, starting from the marks already there.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, GuardError> {
+        let path = path.into();
+        let state = match std::fs::read(&path) {
+            Ok(bytes) => {
+                serde_json::from_slice(&bytes).map_err(|e| GuardError::Corrupt(e.to_string()))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => GuardState::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { state: Mutex::new(state), path: Some(path) })
+    }
+
+    fn save(&self, state: &GuardState) -> Result<(), GuardError> {
+        if let Some(path) = &self.path {
+            persist(path, state)?;
+        }
+        Ok(())
+    }
+
+    /// Last position signed for `kind`: `"proposal"` or a vote type such as
+    /// `"precommit"`.
+    pub fn last_signed(&self, kind: &str) -> Option<SignedPosition> {
+        self.state.lock().unwrap().signed.get(kind).cloned()
+    }
+
+    /// Height at or below which nothing is signed, if any.
+    pub fn floor(&self) -> Option<u32> {
+        self.state.lock().unwrap().floor
+    }
+
+    /// Refuses every signature at or below `height` from now on, e.g. the
+    /// chain's tip when the validator is restored. The floor never drops.
+    pub fn raise_floor(&self, height: u32) -> Result<(), GuardError> {
+        let mut state = self.state.lock().unwrap();
+        if state.floor.is_some_and(|floor| floor >= height) {
+            return Ok(());
+        }
+        let next = GuardState { floor: Some(height), signed: state.signed.clone() };
+        self.save(&next)?;
+        *state = next;
+        Ok(())
+    }
+
+    /// Records signing `block_hash` for `kind` at `height` and `round`,
+    /// unless that could equivocate. The record is persisted before this
+    /// returns, so the signature must only be released after.
+    pub fn check_and_record(
+        &self,
+        kind: &str,
+        height: u32,
+        round: u32,
+        block_hash: &str,
+    ) -> Result<(), GuardError> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(floor) = state.floor.filter(|floor| height <= *floor) {
+            return Err(GuardError::BelowFloor { kind: kind.to_string(), height, floor });
+        }
+        if let Some(last) = state.signed.get(kind) {
+            match (height, round).cmp(&(last.height, last.round)) {
+                Ordering::Less => {
+                    return Err(GuardError::Regression { kind: kind.to_string(), height, round })
+                }
+                Ordering::Equal if last.block_hash != block_hash => {
+                    return Err(GuardError::DoubleSign {
+                        kind: kind.to_string(),
+                        height,
+                        round,
+                        signed: last.block_hash.clone(),
+                    })
+                }
+                Ordering::Equal => return Ok(()),
+                Ordering::Greater => {}
+            }
+        }
+        let mut next = state.clone();
+        let position = SignedPosition { height, round, block_hash: block_hash.to_string() };
+        next.signed.insert(kind.to_string(), position);
+        self.save(&next)?;
+        *state = next;
+        Ok(())
+    }
+}
@@ -19,6 +19,11 @@ pub struct Vote {
     pub voter_id: String,
     pub block_hash: String,
     pub vote_type: VoteType,
+    /// Height of the block voted on.
+    #[serde(default)]
+    pub height: u32,
+    #[serde(default)]
+    pub round: u32,
     pub timestamp: u64,
     pub signature: String,
 }
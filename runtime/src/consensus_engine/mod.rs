@@ -1,8 +1,18 @@
 pub mod engine;
+pub mod guard;
 pub mod messages;
 pub mod state;
+// The remote signer talks over a Unix socket.
+#[cfg(unix)]
+pub mod signer;
 
 // Re-export commonly used types
 pub use engine::{ConsensusAlgorithm, ConsensusConfig, Validator};
+pub use guard::{GuardError, SignedPosition, SlashingGuard};
 pub use messages::{ConsensusProposal, ConsensusResult, Vote, VoteType};
-pub use state::ConsensusStats; 
\ No newline at end of file
+pub use state::ConsensusStats;
+#[cfg(unix)]
+pub use signer::{
+    verify_proposal, verify_vote, ConsensusSigner, LocalSigner, RemoteSigner, SignerError,
+    SignerServer,
+}; 
\ No newline at end of file
@@ -0,0 +1,390 @@
+//! Signing of consensus votes and block proposals, in process or by a
+//! separate signer process.
+//!
+//! A validator signs through a [`ConsensusSigner`]. A [`LocalSigner`] holds
+//! the key in memory; a [`RemoteSigner`] asks a [`SignerServer`], run as
+//! its own process, over a local Unix socket, so the key never enters the
+//! daemon. Requests and replies are JSON lines. Every request carries a
+//! counter and an HMAC-SHA256 under a secret both ends share: the server
+//! only signs for whoever holds the secret, and refuses a counter not above
+//! the last one it accepted, so captured requests cannot be replayed. A
+//! client therefore sends one request at a time.
+//!
+//! Either signer consults a [`SlashingGuard`] before every signature, so
+//! it refuses to equivocate; the server's guard is persisted so a restart
+//! does not forget what was signed.
+//!
+//! The key a signer holds is a `Consensus` session key: votes and proposals
+//! count only if they verify under the key the voter's account registered
+//! on chain for that role.
+
+use super::guard::{GuardError, SignedPosition, SlashingGuard};
+use super::messages::{ConsensusProposal, Vote, VoteType};
+use crate::blockchain::state::State;
+use crate::session_keys::SessionRole;
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier, VerifyingKey};
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+/// Domain tag of consensus signatures.
+const CONSENSUS_DOMAIN: &[u8] = b"bcai-consensus";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("signer I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed signer message: {0}")]
+    Protocol(String),
+    #[error("request not authenticated")]
+    Unauthorized,
+    #[error("request counter {got} not above {last}")]
+    Replayed { got: u64, last: u64 },
+    #[error(transparent)]
+    Guard(#[from] GuardError),
+    #[error("signer refused: {0}")]
+    Refused(String),
+}
+
+/// The message a vote's signature covers.
+pub fn vote_message(vote: &Vote) -> Vec<u8> {
+    let mut msg = CONSENSUS_DOMAIN.to_vec();
+    msg.extend_from_slice(b"vote");
+    msg.push(vote.vote_type as u8);
+    msg.extend_from_slice(&vote.height.to_be_bytes());
+    msg.extend_from_slice(&vote.round.to_be_bytes());
+    msg.extend_from_slice(&vote.timestamp.to_be_bytes());
+    msg.extend_from_slice(vote.voter_id.as_bytes());
+    msg.push(0);
+    msg.extend_from_slice(vote.block_hash.as_bytes());
+    msg
+}
+
+/// The message a proposal's signature covers: its header and the hash of
+/// the proposed block, not the votes gathered for it.
+pub fn proposal_message(proposal: &ConsensusProposal) -> Vec<u8> {
+    let mut msg = CONSENSUS_DOMAIN.to_vec();
+    msg.extend_from_slice(b"proposal");
+    msg.extend_from_slice(&proposal.block.index.to_be_bytes());
+    msg.extend_from_slice(&proposal.round.to_be_bytes());
+    msg.extend_from_slice(&proposal.timestamp.to_be_bytes());
+    msg.extend_from_slice(proposal.proposer_id.as_bytes());
+    msg.push(0);
+    msg.extend_from_slice(proposal.block.hash.as_bytes());
+    msg
+}
+
+fn verify(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let Ok(pk_bytes) = hex::decode(public_key) else { return false };
+    let Ok(pk_bytes) = <[u8; 32]>::try_from(pk_bytes) else { return false };
+    let Ok(vk) = VerifyingKey::from_bytes(&pk_bytes) else { return false };
+    let Ok(sig_bytes) = hex::decode(signature) else { return false };
+    let Ok(sig) = Signature::from_slice(&sig_bytes) else { return false };
+    vk.verify(message, &sig).is_ok()
+}
+
+/// The `Consensus` session key `account` registered in `state`.
+fn consensus_key<'a>(state: &'a State, account: &str) -> Option<&'a str> {
+    state.session_keys.get(account).and_then(|keys| keys.key(SessionRole::Consensus))
+}
+
+/// Whether `vote` is signed by the consensus session key its voter's
+/// account registered in `state`.
+pub fn verify_vote(vote: &Vote, state: &State) -> bool {
+    consensus_key(state, &vote.voter_id)
+        .is_some_and(|key| verify(key, &vote_message(vote), &vote.signature))
+}
+
+/// Whether `proposal` is signed by the consensus session key its
+/// proposer's account registered in `state`.
+pub fn verify_proposal(proposal: &ConsensusProposal, state: &State) -> bool {
+    consensus_key(state, &proposal.proposer_id)
+        .is_some_and(|key| verify(key, &proposal_message(proposal), &proposal.signature))
+}
+
+/// Signs a validator's votes and proposals.
+pub trait ConsensusSigner: Send + Sync {
+    /// Hex ed25519 key the signatures verify under.
+    fn public_key(&self) -> String;
+    /// Fills in `vote.signature`.
+    fn sign_vote<'a>(&'a self, vote: &'a mut Vote) -> BoxFuture<'a, Result<(), SignerError>>;
+    /// Fills in `proposal.signature`.
+    fn sign_proposal<'a>(
+        &'a self,
+        proposal: &'a mut ConsensusProposal,
+    ) -> BoxFuture<'a, Result<(), SignerError>>;
+}
+
+fn kind_of(vote_type: VoteType) -> &'static str {
+    match vote_type {
+        VoteType::Prevote => "prevote",
+        VoteType::Precommit => "precommit",
+        VoteType::Commit => "commit",
+    }
+}
+
+/// Signs with a key held in this process.
+pub struct LocalSigner {
+    key: SigningKey,
+    guard: SlashingGuard,
+}
+
+impl LocalSigner {
+    /// A signer whose guard remembers only what this process signs.
+    pub fn new(key: SigningKey) -> Self {
+        Self { key, guard: SlashingGuard::in_memory() }
+    }
+
+    pub fn with_guard(mut self, guard: SlashingGuard) -> Self {
+        self.guard = guard;
+        self
+    }
+
+    /// Persists what is signed to `path`, see [`SlashingGuard::open`].
+    pub fn with_state_path(self, path: impl Into<PathBuf>) -> Result<Self, SignerError> {
+        Ok(self.with_guard(SlashingGuard::open(path)?))
+    }
+
+    pub fn guard(&self) -> &SlashingGuard {
+        &self.guard
+    }
+
+    /// Last position signed for `kind`, see [`SlashingGuard::last_signed`].
+    pub fn last_signed(&self, kind: &str) -> Option<SignedPosition> {
+        self.guard.last_signed(kind)
+    }
+
+    fn sign_vote_now(&self, vote: &mut Vote) -> Result<(), SignerError> {
+        let kind = kind_of(vote.vote_type);
+        self.guard.check_and_record(kind, vote.height, vote.round, &vote.block_hash)?;
+        vote.signature = hex::encode(self.key.sign(&vote_message(vote)).to_bytes());
+        Ok(())
+    }
+
+    fn sign_proposal_now(&self, proposal: &mut ConsensusProposal) -> Result<(), SignerError> {
+        let (height, round) = (proposal.block.index, proposal.round);
+        self.guard.check_and_record("proposal", height, round, &proposal.block.hash)?;
+        proposal.signature = hex::encode(self.key.sign(&proposal_message(proposal)).to_bytes());
+        Ok(())
+    }
+}
+
+impl ConsensusSigner for LocalSigner {
+    fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().to_bytes())
+    }
+
+    fn sign_vote<'a>(&'a self, vote: &'a mut Vote) -> BoxFuture<'a, Result<(), SignerError>> {
+        Box::pin(async move { self.sign_vote_now(vote) })
+    }
+
+    fn sign_proposal<'a>(
+        &'a self,
+        proposal: &'a mut ConsensusProposal,
+    ) -> BoxFuture<'a, Result<(), SignerError>> {
+        Box::pin(async move { self.sign_proposal_now(proposal) })
+    }
+}
+
+/// What a [`RemoteSigner`] asks of the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignRequest {
+    PublicKey,
+    Vote { vote: Vote },
+    Proposal { proposal: Box<ConsensusProposal> },
+}
+
+/// A [`SignRequest`] as sent on the socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Envelope {
+    counter: u64,
+    /// Hex HMAC of the counter and the request.
+    mac: String,
+    /// The JSON of the request, kept as sent so the MAC covers its bytes.
+    request: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SignResponse {
+    PublicKey { public_key: String },
+    Signature { signature: String },
+    Refused { reason: String },
+}
+
+fn request_mac(secret: &[u8], counter: u64, request: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    mac.update(request.as_bytes());
+    mac
+}
+
+async fn write_line<T: Serialize>(stream: &mut UnixStream, message: &T) -> Result<(), SignerError> {
+    let mut line = serde_json::to_vec(message).expect("signer messages serialize");
+    line.push(b'\n');
+    stream.write_all(&line).await?;
+    Ok(())
+}
+
+/// Signs through a [`SignerServer`] listening on a Unix socket.
+pub struct RemoteSigner {
+    socket: PathBuf,
+    secret: Vec<u8>,
+    public_key: String,
+    /// Counter of the last request, held while it is answered.
+    counter: tokio::sync::Mutex<u64>,
+}
+
+impl RemoteSigner {
+    /// Connects to the server at `socket`, authenticating with `secret`,
+    /// and learns the key it signs with.
+    pub async fn connect(socket: impl AsRef<Path>, secret: &[u8]) -> Result<Self, SignerError> {
+        // Counters start at the time in nanoseconds so they keep rising
+        // across restarts of the daemon.
+        let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let mut signer = Self {
+            socket: socket.as_ref().to_path_buf(),
+            secret: secret.to_vec(),
+            public_key: String::new(),
+            counter: tokio::sync::Mutex::new(start as u64),
+        };
+        signer.public_key = match signer.call(SignRequest::PublicKey).await? {
+            SignResponse::PublicKey { public_key } => public_key,
+            other => {
+                return Err(SignerError::Protocol(format!(
+                    "expected a public key, got {:?}",
+                    other
+                )))
+            }
+        };
+        Ok(signer)
+    }
+
+    async fn call(&self, request: SignRequest) -> Result<SignResponse, SignerError> {
+        // Held until the reply, so requests reach the server in order.
+        let mut last = self.counter.lock().await;
+        *last += 1;
+        let counter = *last;
+        let request = serde_json::to_string(&request).expect("sign requests serialize");
+        let mac = hex::encode(request_mac(&self.secret, counter, &request).finalize().into_bytes());
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        write_line(&mut stream, &Envelope { counter, mac, request }).await?;
+        let mut line = String::new();
+        BufReader::new(&mut stream).read_line(&mut line).await?;
+        match serde_json::from_str(&line).map_err(|e| SignerError::Protocol(e.to_string()))? {
+            SignResponse::Refused { reason } => Err(SignerError::Refused(reason)),
+            response => Ok(response),
+        }
+    }
+
+    async fn signature(&self, request: SignRequest) -> Result<String, SignerError> {
+        match self.call(request).await? {
+            SignResponse::Signature { signature } => Ok(signature),
+            other => Err(SignerError::Protocol(format!("expected a signature, got {:?}", other))),
+        }
+    }
+}
+
+impl ConsensusSigner for RemoteSigner {
+    fn public_key(&self) -> String {
+        self.public_key.clone()
+    }
+
+    fn sign_vote<'a>(&'a self, vote: &'a mut Vote) -> BoxFuture<'a, Result<(), SignerError>> {
+        Box::pin(async move {
+            vote.signature = self.signature(SignRequest::Vote { vote: vote.clone() }).await?;
+            Ok(())
+        })
+    }
+
+    fn sign_proposal<'a>(
+        &'a self,
+        proposal: &'a mut ConsensusProposal,
+    ) -> BoxFuture<'a, Result<(), SignerError>> {
+        Box::pin(async move {
+            let request = SignRequest::Proposal { proposal: Box::new(proposal.clone()) };
+            proposal.signature = self.signature(request).await?;
+            Ok(())
+        })
+    }
+}
+
+/// Serves signatures of a [`LocalSigner`] to authenticated clients.
+pub struct SignerServer {
+    signer: LocalSigner,
+    secret: Vec<u8>,
+    last_counter: Mutex<u64>,
+}
+
+impl SignerServer {
+    pub fn new(signer: LocalSigner, secret: &[u8]) -> Self {
+        Self { signer, secret: secret.to_vec(), last_counter: Mutex::new(0) }
+    }
+
+    /// Checks the envelope's MAC and counter and carries out its request.
+    fn handle(&self, envelope: Envelope) -> Result<SignResponse, SignerError> {
+        let mac = hex::decode(&envelope.mac).map_err(|_| SignerError::Unauthorized)?;
+        request_mac(&self.secret, envelope.counter, &envelope.request)
+            .verify_slice(&mac)
+            .map_err(|_| SignerError::Unauthorized)?;
+        {
+            let mut last = self.last_counter.lock().unwrap();
+            if envelope.counter <= *last {
+                return Err(SignerError::Replayed { got: envelope.counter, last: *last });
+            }
+            *last = envelope.counter;
+        }
+        let request: SignRequest = serde_json::from_str(&envelope.request)
+            .map_err(|e| SignerError::Protocol(e.to_string()))?;
+        Ok(match request {
+            SignRequest::PublicKey => {
+                SignResponse::PublicKey { public_key: self.signer.public_key() }
+            }
+            SignRequest::Vote { mut vote } => {
+                self.signer.sign_vote_now(&mut vote)?;
+                SignResponse::Signature { signature: vote.signature }
+            }
+            SignRequest::Proposal { mut proposal } => {
+                self.signer.sign_proposal_now(&mut proposal)?;
+                SignResponse::Signature { signature: proposal.signature }
+            }
+        })
+    }
+
+    async fn serve_connection(&self, mut stream: UnixStream) -> Result<(), SignerError> {
+        let mut line = String::new();
+        BufReader::new(&mut stream).read_line(&mut line).await?;
+        let response = match serde_json::from_str::<Envelope>(&line) {
+            Ok(envelope) => self.handle(envelope),
+            Err(e) => Err(SignerError::Protocol(e.to_string())),
+        };
+        let response = response.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Refused signing request");
+            SignResponse::Refused { reason: e.to_string() }
+        });
+        write_line(&mut stream, &response).await
+    }
+
+    /// Answers requests on `listener` until it fails.
+    pub async fn serve(self: Arc<Self>, listener: UnixListener) -> Result<(), SignerError> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.serve_connection(stream).await {
+                    tracing::warn!(error = %e, "Signer connection failed");
+                }
+            });
+        }
+    }
+}
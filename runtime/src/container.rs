@@ -0,0 +1,311 @@
+//! Container execution of job workloads.
+//!
+//! Jobs that need a full software stack, e.g. Python and PyTorch, name an
+//! OCI image in a [`ContainerSpec`]. A [`ContainerRunner`] runs it through a
+//! Docker-compatible CLI (`docker`, `podman`) with the job's CPU, GPU and
+//! memory limits, no network and no new privileges. The job reads its
+//! inputs from [`INPUT_DIR`] and leaves artifacts in [`OUTPUT_DIR`];
+//! `metrics.json` there, a map of metric names to numbers, is reported as
+//! the job's metrics. The container's stdout and stderr are captured as its
+//! logs.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Where the job's inputs are mounted, read-only.
+pub const INPUT_DIR: &str = "/bcai/in";
+/// Where the job writes its artifacts.
+pub const OUTPUT_DIR: &str = "/bcai/out";
+/// Artifact read back as the job's metrics.
+pub const METRICS_FILE: &str = "metrics.json";
+/// Logs kept per run; older output is dropped.
+pub const MAX_LOG_BYTES: usize = 1 << 20;
+
+#[derive(Debug, Error)]
+pub enum ContainerError {
+    #[error("invalid container spec: {0}")]
+    InvalidSpec(String),
+    #[error("container runtime I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("container exceeded its {0}s time limit")]
+    Timeout(u64),
+    #[error("container exited with status {code}")]
+    Failed { code: i32, logs: String },
+    #[error("invalid metrics.json: {0}")]
+    Metrics(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// CPU cores, possibly fractional.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    #[serde(default)]
+    pub gpus: u32,
+    /// Wall-clock limit of the run.
+    pub timeout_secs: u64,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self { cpus: None, memory_mb: None, gpus: 0, timeout_secs: 3600 }
+    }
+}
+
+/// The image a job runs in and how.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerSpec {
+    /// OCI image reference, ideally pinned by digest.
+    pub image: String,
+    /// Overrides the image's command when not empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub limits: ResourceLimits,
+}
+
+impl ContainerSpec {
+    pub fn new(image: &str) -> Self {
+        Self { image: image.to_string(), command: Vec::new(), env: BTreeMap::new(), limits: ResourceLimits::default() }
+    }
+
+    pub fn with_command(mut self, command: &[&str]) -> Self {
+        self.command = command.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Rejects specs that could smuggle options into the runtime CLI.
+    pub fn validate(&self) -> Result<(), ContainerError> {
+        let image_ok = !self.image.is_empty()
+            && !self.image.starts_with('-')
+            && !self.image.chars().any(|c| c.is_whitespace() || c.is_control());
+        if !image_ok {
+            return Err(ContainerError::InvalidSpec(format!("bad image reference {:?}", self.image)));
+        }
+        for key in self.env.keys() {
+            let mut chars = key.chars();
+            let ok = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !ok {
+                return Err(ContainerError::InvalidSpec(format!("bad environment variable {:?}", key)));
+            }
+        }
+        if self.limits.cpus.is_some_and(|cpus| cpus.is_nan() || cpus <= 0.0) || self.limits.memory_mb == Some(0) {
+            return Err(ContainerError::InvalidSpec("resource limits must be positive".into()));
+        }
+        if self.limits.timeout_secs == 0 {
+            return Err(ContainerError::InvalidSpec("timeout must be positive".into()));
+        }
+        Ok(())
+    }
+}
+
+/// A file the job left in [`OUTPUT_DIR`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub name: String,
+    /// Hex SHA-256 of the contents.
+    pub hash: String,
+    pub size: u64,
+    /// Location on the worker.
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerOutcome {
+    /// Interleaved stdout and stderr, at most [`MAX_LOG_BYTES`].
+    pub logs: String,
+    /// Artifacts by name, sorted.
+    pub artifacts: Vec<Artifact>,
+    pub metrics: HashMap<String, f64>,
+    pub duration_ms: u64,
+}
+
+impl ContainerOutcome {
+    pub fn artifact(&self, name: &str) -> Option<&Artifact> {
+        self.artifacts.iter().find(|a| a.name == name)
+    }
+}
+
+/// Runs job containers through a Docker-compatible CLI.
+#[derive(Debug, Clone)]
+pub struct ContainerRunner {
+    runtime: PathBuf,
+    work_dir: PathBuf,
+}
+
+impl ContainerRunner {
+    /// A runner using `docker` that keeps each job's outputs under
+    /// `work_dir/job-<id>`.
+    pub fn new(work_dir: impl Into<PathBuf>) -> Self {
+        Self { runtime: PathBuf::from("docker"), work_dir: work_dir.into() }
+    }
+
+    /// Uses another CLI, e.g. `podman`.
+    pub fn with_runtime(mut self, runtime: impl Into<PathBuf>) -> Self {
+        self.runtime = runtime.into();
+        self
+    }
+
+    pub fn output_dir(&self, job_id: u64) -> PathBuf {
+        self.work_dir.join(format!("job-{}", job_id))
+    }
+
+    fn container_name(job_id: u64) -> String {
+        format!("bcai-job-{}", job_id)
+    }
+
+    /// Arguments of the `run` invocation for `spec`.
+    pub fn run_args(&self, job_id: u64, spec: &ContainerSpec, input: Option<&Path>, output: &Path) -> Vec<String> {
+        let mut args: Vec<String> = vec![
+            "run".into(),
+            "--rm".into(),
+            "--name".into(),
+            Self::container_name(job_id),
+            "--network".into(),
+            "none".into(),
+            "--security-opt".into(),
+            "no-new-privileges".into(),
+            "--pids-limit".into(),
+            "512".into(),
+        ];
+        if let Some(cpus) = spec.limits.cpus {
+            args.extend(["--cpus".into(), cpus.to_string()]);
+        }
+        if let Some(memory) = spec.limits.memory_mb {
+            // Equal swap and memory limits disable swap.
+            args.extend(["--memory".into(), format!("{}m", memory), "--memory-swap".into(), format!("{}m", memory)]);
+        }
+        if spec.limits.gpus > 0 {
+            args.extend(["--gpus".into(), spec.limits.gpus.to_string()]);
+        }
+        if let Some(input) = input {
+            args.extend(["-v".into(), format!("{}:{}:ro", input.display(), INPUT_DIR)]);
+        }
+        args.extend(["-v".into(), format!("{}:{}", output.display(), OUTPUT_DIR)]);
+        args.extend(["-e".into(), format!("BCAI_INPUT={}", INPUT_DIR), "-e".into(), format!("BCAI_OUTPUT={}", OUTPUT_DIR)]);
+        for (key, value) in &spec.env {
+            args.extend(["-e".into(), format!("{}={}", key, value)]);
+        }
+        args.push(spec.image.clone());
+        args.extend(spec.command.iter().cloned());
+        args
+    }
+
+    /// Runs the job's container to completion and collects its logs,
+    /// artifacts and metrics. `input` is mounted read-only when given.
+    pub fn run(&self, job_id: u64, spec: &ContainerSpec, input: Option<&Path>) -> Result<ContainerOutcome, ContainerError> {
+        spec.validate()?;
+        let output = self.output_dir(job_id);
+        if output.exists() {
+            std::fs::remove_dir_all(&output)?;
+        }
+        std::fs::create_dir_all(&output)?;
+
+        let started = Instant::now();
+        let mut child = Command::new(&self.runtime)
+            .args(self.run_args(job_id, spec, input, &output))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let readers = [
+            capture(child.stdout.take().expect("stdout is piped")),
+            capture(child.stderr.take().expect("stderr is piped")),
+        ];
+
+        let deadline = Duration::from_secs(spec.limits.timeout_secs);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if started.elapsed() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                // Killing the CLI does not necessarily stop the container.
+                let _ = Command::new(&self.runtime)
+                    .args(["rm", "-f", &Self::container_name(job_id)])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+                return Err(ContainerError::Timeout(spec.limits.timeout_secs));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        let logs = readers.map(|reader| reader.join().unwrap_or_default()).concat();
+        let logs = tail(logs, MAX_LOG_BYTES);
+
+        if !status.success() {
+            return Err(ContainerError::Failed { code: status.code().unwrap_or(-1), logs });
+        }
+        let artifacts = collect_artifacts(&output)?;
+        let metrics = match artifacts.iter().find(|a| a.name == METRICS_FILE) {
+            Some(file) => serde_json::from_slice(&std::fs::read(&file.path)?)
+                .map_err(|e| ContainerError::Metrics(e.to_string()))?,
+            None => HashMap::new(),
+        };
+        Ok(ContainerOutcome { logs, artifacts, metrics, duration_ms: started.elapsed().as_millis() as u64 })
+    }
+}
+
+fn capture(mut pipe: impl Read + Send + 'static) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// The last `max` bytes of `logs`, cut at a character boundary.
+fn tail(logs: String, max: usize) -> String {
+    if logs.len() <= max {
+        return logs;
+    }
+    let mut start = logs.len() - max;
+    while !logs.is_char_boundary(start) {
+        start += 1;
+    }
+    logs[start..].to_string()
+}
+
+fn collect_artifacts(dir: &Path) -> Result<Vec<Artifact>, ContainerError> {
+    let mut artifacts = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        // Symlinks could point outside the output directory.
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut std::fs::File::open(&path)?, &mut hasher)?;
+        artifacts.push(Artifact {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            hash: hex::encode(hasher.finalize()),
+            size,
+            path,
+        });
+    }
+    artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(artifacts)
+}
@@ -1,11 +1,5 @@
-pub mod chains;
-pub mod config;
-pub mod liquidity;
-pub mod messages;
-pub mod validators;
-
-pub use chains::*;
-pub use config::*;
-pub use liquidity::*;
-pub use messages::*;
-pub use validators::*; 
\ No newline at end of file
+pub use super::chains::*;
+pub use super::config::*;
+pub use super::liquidity::*;
+pub use super::messages::*;
+pub use super::validators::*;
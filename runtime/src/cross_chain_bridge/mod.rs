@@ -1,3 +1,9 @@
+mod chains;
+mod config;
+mod liquidity;
+mod messages;
+mod validators;
+
 pub mod external_chain;
 pub mod transactions;
 pub mod error;
@@ -0,0 +1,50 @@
+use super::tabular::TabularLoader;
+use super::DataError;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Loader for a comma-separated file with a header row. Fields may be
+/// double-quoted; a quoted field can contain commas and `""` escapes but
+/// not line breaks.
+pub struct CsvLoader;
+
+impl CsvLoader {
+    /// Opens `path`, predicting the column named `label`.
+    pub fn open(path: impl AsRef<Path>, label: &str) -> Result<TabularLoader, DataError> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header = match lines.next() {
+            Some(line) => split_record(&line?, 0)?,
+            None => return Err(DataError::Empty),
+        };
+        let records = lines
+            .enumerate()
+            .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .map(|(i, line)| split_record(&line?, i + 1));
+        TabularLoader::new(header, Box::new(records), label)
+    }
+}
+
+/// Splits one CSV line into fields.
+pub fn split_record(line: &str, record: usize) -> Result<Vec<String>, DataError> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(DataError::Parse { record, message: "unterminated quote".into() });
+    }
+    fields.push(field);
+    Ok(fields)
+}
@@ -0,0 +1,104 @@
+//! Datasets laid out like a HuggingFace hub repository.
+//!
+//! A split is read from `<dir>/<split>.jsonl` or, when sharded, from every
+//! `<dir>/data/<split>-*.jsonl` in name order. Each line is a JSON object
+//! of scalar fields. If `<dir>/dataset_info.json` declares the label as a
+//! `ClassLabel` (`{"features": {"<label>": {"names": [...]}}}`), its names
+//! fix the class numbering.
+
+use super::tabular::TabularLoader;
+use super::DataError;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+pub struct HubLoader;
+
+impl HubLoader {
+    /// Opens `split` of the dataset in `dir`, predicting the field `label`.
+    pub fn open(dir: impl AsRef<Path>, split: &str, label: &str) -> Result<TabularLoader, DataError> {
+        let dir = dir.as_ref();
+        let files = split_files(dir, split)?;
+        let mut lines = files
+            .into_iter()
+            .map(|path| Ok(BufReader::new(File::open(path)?).lines()))
+            .collect::<Result<Vec<_>, DataError>>()?
+            .into_iter()
+            .flatten()
+            .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .enumerate()
+            .map(|(i, line)| parse_object(&line?, i + 1));
+
+        // Columns are the first record's fields.
+        let first = lines.next().ok_or(DataError::Empty)??;
+        let header: Vec<String> = first.keys().cloned().collect();
+        let columns = header.clone();
+        let records = std::iter::once(Ok(first))
+            .chain(lines)
+            .enumerate()
+            .map(move |(i, object)| object.and_then(|o| row(&columns, o, i + 1)));
+        let loader = TabularLoader::new(header, Box::new(records), label)?;
+        Ok(match class_names(dir, label)? {
+            Some(names) => loader.with_label_names(names),
+            None => loader,
+        })
+    }
+}
+
+fn split_files(dir: &Path, split: &str) -> Result<Vec<PathBuf>, DataError> {
+    let single = dir.join(format!("{}.jsonl", split));
+    if single.is_file() {
+        return Ok(vec![single]);
+    }
+    let data = dir.join("data");
+    let prefix = format!("{}-", split);
+    let mut shards = Vec::new();
+    if data.is_dir() {
+        for entry in std::fs::read_dir(&data)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if name.starts_with(&prefix) && name.ends_with(".jsonl") {
+                shards.push(path);
+            }
+        }
+    }
+    if shards.is_empty() {
+        return Err(DataError::Schema(format!("no {} split in {}", split, dir.display())));
+    }
+    shards.sort();
+    Ok(shards)
+}
+
+fn parse_object(line: &str, record: usize) -> Result<serde_json::Map<String, Value>, DataError> {
+    match serde_json::from_str(line) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => Err(DataError::Parse { record, message: "not a JSON object".into() }),
+        Err(e) => Err(DataError::Parse { record, message: e.to_string() }),
+    }
+}
+
+fn row(columns: &[String], mut object: serde_json::Map<String, Value>, record: usize) -> Result<Vec<String>, DataError> {
+    columns
+        .iter()
+        .map(|column| match object.remove(column) {
+            Some(Value::String(s)) => Ok(s),
+            Some(Value::Bool(b)) => Ok(u8::from(b).to_string()),
+            Some(Value::Number(n)) => Ok(n.to_string()),
+            Some(_) => Err(DataError::Parse { record, message: format!("{} is not a scalar", column) }),
+            None => Err(DataError::Parse { record, message: format!("missing field {}", column) }),
+        })
+        .collect()
+}
+
+fn class_names(dir: &Path, label: &str) -> Result<Option<Vec<String>>, DataError> {
+    let path = dir.join("dataset_info.json");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let info: Value = serde_json::from_reader(File::open(path)?)
+        .map_err(|e| DataError::Schema(format!("dataset_info.json: {}", e)))?;
+    Ok(info["features"][label]["names"].as_array().map(|names| {
+        names.iter().map(|n| n.as_str().map_or_else(|| n.to_string(), str::to_string)).collect()
+    }))
+}
@@ -0,0 +1,67 @@
+//! Image classification folders: `<root>/<class>/<image>`, one
+//! subdirectory per class. Classes are numbered in name order and every
+//! image is converted to grayscale, resized and scaled to `[0, 1]`.
+
+use super::{Column, ColumnKind, DataError, DatasetLoader, Sample, Schema};
+use std::path::{Path, PathBuf};
+
+pub struct ImageFolderLoader {
+    schema: Schema,
+    files: Vec<(PathBuf, usize)>,
+    width: u32,
+    height: u32,
+}
+
+impl ImageFolderLoader {
+    /// Lists the images under `root`; they are decoded as they are streamed.
+    pub fn open(root: impl AsRef<Path>, width: u32, height: u32) -> Result<Self, DataError> {
+        let mut classes = Vec::new();
+        for entry in std::fs::read_dir(root.as_ref())? {
+            let path = entry?.path();
+            if path.is_dir() {
+                classes.push(path);
+            }
+        }
+        classes.sort();
+        let mut files = Vec::new();
+        for (label, class) in classes.iter().enumerate() {
+            let mut images = std::fs::read_dir(class)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            images.retain(|p| p.is_file());
+            images.sort();
+            files.extend(images.into_iter().map(|p| (p, label)));
+        }
+        if files.is_empty() {
+            return Err(DataError::Empty);
+        }
+
+        let names = classes
+            .iter()
+            .map(|c| c.file_name().unwrap_or_default().to_string_lossy().into_owned())
+            .collect();
+        let mut columns: Vec<Column> = (0..width * height)
+            .map(|i| Column { name: format!("pixel{}", i), kind: ColumnKind::Numeric })
+            .collect();
+        let label = columns.len();
+        columns.push(Column { name: "label".into(), kind: ColumnKind::Categorical(names) });
+        Ok(Self { schema: Schema { columns, label }, files, width, height })
+    }
+}
+
+impl DatasetLoader for ImageFolderLoader {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn samples(&mut self) -> Box<dyn Iterator<Item = Result<Sample, DataError>> + '_> {
+        let (width, height) = (self.width, self.height);
+        Box::new(self.files.iter().enumerate().map(move |(i, (path, label))| {
+            let image = image::open(path)
+                .map_err(|e| DataError::Parse { record: i + 1, message: format!("{}: {}", path.display(), e) })?
+                .resize_exact(width, height, image::imageops::FilterType::Triangle)
+                .into_luma8();
+            Ok(Sample { features: image.pixels().map(|p| p.0[0] as f32 / 255.0).collect(), label: *label })
+        }))
+    }
+}
@@ -0,0 +1,63 @@
+//! Dataset loading shared by the trainer, the PoUW solver and federated
+//! learning.
+//!
+//! Loaders stream [`Sample`]s from CSV files, JSON Lines splits laid out
+//! like a HuggingFace dataset repository, image folders (`image` feature)
+//! and Parquet files (`parquet` feature). Tabular sources infer a
+//! [`Schema`] from their first rows, so numeric and categorical columns need
+//! no declaration. [`transform`] normalizes features and splits samples
+//! into train and test sets.
+
+pub mod csv;
+pub mod hub;
+#[cfg(feature = "image")]
+pub mod images;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod schema;
+pub mod tabular;
+pub mod transform;
+
+pub use schema::{Column, ColumnKind, Schema};
+pub use tabular::TabularLoader;
+pub use transform::{train_test_split, Normalizer};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// One labelled example.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Sample {
+    pub features: Vec<f32>,
+    /// Class index, see [`Schema::num_classes`].
+    pub label: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum DataError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("record {record}: {message}")]
+    Parse { record: usize, message: String },
+    #[error("schema error: {0}")]
+    Schema(String),
+    #[error("dataset is empty")]
+    Empty,
+}
+
+/// A source of samples.
+pub trait DatasetLoader {
+    fn schema(&self) -> &Schema;
+
+    /// Streams the remaining samples; a loader is read once.
+    fn samples(&mut self) -> Box<dyn Iterator<Item = Result<Sample, DataError>> + '_>;
+}
+
+/// Reads every sample of `loader` into memory.
+pub fn load_all(loader: &mut dyn DatasetLoader) -> Result<Vec<Sample>, DataError> {
+    let samples = loader.samples().collect::<Result<Vec<_>, _>>()?;
+    if samples.is_empty() {
+        return Err(DataError::Empty);
+    }
+    Ok(samples)
+}
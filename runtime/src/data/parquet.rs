@@ -0,0 +1,43 @@
+use super::tabular::TabularLoader;
+use super::DataError;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use std::fs::File;
+use std::path::Path;
+
+/// Loader for a Parquet file of scalar columns, read row by row.
+pub struct ParquetLoader;
+
+impl ParquetLoader {
+    /// Opens `path`, predicting the column named `label`.
+    pub fn open(path: impl AsRef<Path>, label: &str) -> Result<TabularLoader, DataError> {
+        let reader = SerializedFileReader::new(File::open(path)?).map_err(schema_error)?;
+        let header = reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        let rows = reader.into_iter().enumerate().map(|(i, row)| {
+            let row = row.map_err(|e| DataError::Parse { record: i + 1, message: e.to_string() })?;
+            row.get_column_iter().map(|(name, field)| scalar(name, field, i + 1)).collect()
+        });
+        TabularLoader::new(header, Box::new(rows), label)
+    }
+}
+
+fn scalar(name: &str, field: &Field, record: usize) -> Result<String, DataError> {
+    match field {
+        Field::Str(s) => Ok(s.clone()),
+        Field::Bool(b) => Ok(u8::from(*b).to_string()),
+        Field::Byte(_) | Field::Short(_) | Field::Int(_) | Field::Long(_) | Field::UByte(_) | Field::UShort(_)
+        | Field::UInt(_) | Field::ULong(_) | Field::Float(_) | Field::Double(_) => Ok(field.to_string()),
+        _ => Err(DataError::Parse { record, message: format!("{} is not a scalar", name) }),
+    }
+}
+
+fn schema_error(e: parquet::errors::ParquetError) -> DataError {
+    DataError::Schema(e.to_string())
+}
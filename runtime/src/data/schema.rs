@@ -0,0 +1,114 @@
+use super::{DataError, Sample};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Rows a tabular loader reads ahead to infer its schema. Categories that
+/// first appear after them are rejected.
+pub const INFER_ROWS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnKind {
+    Numeric,
+    /// Encoded as the index of the value among these, which are sorted
+    /// unless declared by the source.
+    Categorical(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnKind,
+}
+
+/// Columns of a tabular source in record order, one of them the label.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+    pub columns: Vec<Column>,
+    /// Index of the label column.
+    pub label: usize,
+}
+
+impl Schema {
+    /// Infers column kinds from `rows`: a column is numeric if every value
+    /// parses as a number, categorical otherwise. The label column must be
+    /// categorical or hold non-negative integers.
+    pub fn infer(header: &[String], rows: &[Vec<String>], label: &str) -> Result<Self, DataError> {
+        let label = header
+            .iter()
+            .position(|h| h == label)
+            .ok_or_else(|| DataError::Schema(format!("no label column {}", label)))?;
+        let columns = header
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let values = rows.iter().filter_map(|r| r.get(i)).map(|v| v.trim());
+                let numeric = values.clone().all(|v| v.parse::<f32>().is_ok());
+                let kind = if numeric && !(i == label && values.clone().any(|v| !v.parse().is_ok_and(is_class_index))) {
+                    ColumnKind::Numeric
+                } else {
+                    ColumnKind::Categorical(values.map(str::to_string).collect::<BTreeSet<_>>().into_iter().collect())
+                };
+                Column { name: name.clone(), kind }
+            })
+            .collect();
+        Ok(Self { columns, label })
+    }
+
+    /// Number of label classes: the categories of a categorical label, or
+    /// `None` for integer labels.
+    pub fn num_classes(&self) -> Option<usize> {
+        match &self.columns[self.label].kind {
+            ColumnKind::Categorical(values) => Some(values.len()),
+            ColumnKind::Numeric => None,
+        }
+    }
+
+    /// Names of the feature columns in sample order.
+    pub fn feature_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != self.label)
+            .map(|(_, c)| c.name.as_str())
+            .collect()
+    }
+
+    /// Encodes record number `record` into a sample.
+    pub fn encode(&self, record: usize, values: &[String]) -> Result<Sample, DataError> {
+        if values.len() != self.columns.len() {
+            return Err(DataError::Parse {
+                record,
+                message: format!("expected {} values, got {}", self.columns.len(), values.len()),
+            });
+        }
+        let parse_error = |message: String| DataError::Parse { record, message };
+        let mut features = Vec::with_capacity(self.columns.len() - 1);
+        let mut label = 0;
+        for (i, (column, value)) in self.columns.iter().zip(values).enumerate() {
+            let value = value.trim();
+            let encoded = match &column.kind {
+                ColumnKind::Numeric => value
+                    .parse::<f32>()
+                    .map_err(|_| parse_error(format!("{} is not a number: {}", column.name, value)))?,
+                ColumnKind::Categorical(values) => values
+                    .iter()
+                    .position(|v| v == value)
+                    .ok_or_else(|| parse_error(format!("unknown {} category: {}", column.name, value)))?
+                    as f32,
+            };
+            if i == self.label {
+                if !is_class_index(encoded) {
+                    return Err(parse_error(format!("label is not a class index: {}", value)));
+                }
+                label = encoded as usize;
+            } else {
+                features.push(encoded);
+            }
+        }
+        Ok(Sample { features, label })
+    }
+}
+
+fn is_class_index(value: f32) -> bool {
+    value >= 0.0 && value.fract() == 0.0
+}
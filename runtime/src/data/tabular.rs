@@ -0,0 +1,61 @@
+use super::schema::INFER_ROWS;
+use super::{DataError, DatasetLoader, Sample, Schema};
+use std::collections::VecDeque;
+
+/// Records of a tabular source as strings, numbered from 1.
+pub type Records = Box<dyn Iterator<Item = Result<Vec<String>, DataError>>>;
+
+/// Streams samples out of string records, inferring the schema from the
+/// first [`INFER_ROWS`] of them. The CSV, hub and Parquet loaders are all
+/// built on it.
+pub struct TabularLoader {
+    schema: Schema,
+    buffered: VecDeque<Vec<String>>,
+    records: Records,
+    next_record: usize,
+}
+
+impl TabularLoader {
+    pub fn new(header: Vec<String>, mut records: Records, label: &str) -> Result<Self, DataError> {
+        let mut buffered = Vec::new();
+        while buffered.len() < INFER_ROWS {
+            match records.next() {
+                Some(record) => buffered.push(record?),
+                None => break,
+            }
+        }
+        let schema = Schema::infer(&header, &buffered, label)?;
+        Ok(Self { schema, buffered: buffered.into(), records, next_record: 1 })
+    }
+
+    /// Replaces the inferred label categories with ones declared by the
+    /// source, so class indices follow its numbering.
+    pub fn with_label_names(mut self, names: Vec<String>) -> Self {
+        let label = self.schema.label;
+        if matches!(self.schema.columns[label].kind, super::ColumnKind::Categorical(_)) {
+            self.schema.columns[label].kind = super::ColumnKind::Categorical(names);
+        }
+        self
+    }
+}
+
+impl DatasetLoader for TabularLoader {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn samples(&mut self) -> Box<dyn Iterator<Item = Result<Sample, DataError>> + '_> {
+        Box::new(std::iter::from_fn(move || {
+            let record = match self.buffered.pop_front() {
+                Some(record) => record,
+                None => match self.records.next()? {
+                    Ok(record) => record,
+                    Err(e) => return Some(Err(e)),
+                },
+            };
+            let number = self.next_record;
+            self.next_record += 1;
+            Some(self.schema.encode(number, &record))
+        }))
+    }
+}
@@ -0,0 +1,71 @@
+use super::{DataError, Sample};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+/// Per-feature standardization to zero mean and unit variance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Normalizer {
+    pub mean: Vec<f32>,
+    pub std: Vec<f32>,
+}
+
+impl Normalizer {
+    /// Fits mean and standard deviation in one pass, so `samples` may be a
+    /// loader's stream. Constant features keep a deviation of 1.
+    pub fn fit<'a>(samples: impl IntoIterator<Item = &'a Sample>) -> Result<Self, DataError> {
+        let mut count = 0u64;
+        let mut mean: Vec<f64> = Vec::new();
+        let mut m2: Vec<f64> = Vec::new();
+        for sample in samples {
+            if count == 0 {
+                mean = vec![0.0; sample.features.len()];
+                m2 = vec![0.0; sample.features.len()];
+            } else if sample.features.len() != mean.len() {
+                return Err(DataError::Schema(format!(
+                    "expected {} features, got {}",
+                    mean.len(),
+                    sample.features.len()
+                )));
+            }
+            count += 1;
+            for (i, &x) in sample.features.iter().enumerate() {
+                let x = x as f64;
+                let delta = x - mean[i];
+                mean[i] += delta / count as f64;
+                m2[i] += delta * (x - mean[i]);
+            }
+        }
+        if count == 0 {
+            return Err(DataError::Empty);
+        }
+        let std = m2
+            .iter()
+            .map(|m2| match (m2 / count as f64).sqrt() as f32 {
+                s if s > f32::EPSILON => s,
+                _ => 1.0,
+            })
+            .collect();
+        Ok(Self { mean: mean.into_iter().map(|m| m as f32).collect(), std })
+    }
+
+    pub fn apply(&self, sample: &mut Sample) {
+        for ((x, mean), std) in sample.features.iter_mut().zip(&self.mean).zip(&self.std) {
+            *x = (*x - mean) / std;
+        }
+    }
+
+    pub fn apply_all(&self, samples: &mut [Sample]) {
+        samples.iter_mut().for_each(|s| self.apply(s));
+    }
+}
+
+/// Shuffles `samples` with `seed` and splits off `test_fraction` of them,
+/// returning `(train, test)`. The same seed gives the same split.
+pub fn train_test_split(mut samples: Vec<Sample>, test_fraction: f32, seed: u64) -> (Vec<Sample>, Vec<Sample>) {
+    samples.shuffle(&mut StdRng::seed_from_u64(seed));
+    let test_len = (samples.len() as f32 * test_fraction.clamp(0.0, 1.0)).round() as usize;
+    let test = samples.split_off(samples.len() - test_len);
+    (samples, test)
+}
@@ -0,0 +1,447 @@
+//! Arbitration of disputed jobs by staked jurors.
+//!
+//! A poster who rejects a worker's result, or a worker who was not paid,
+//! opens a [`Dispute`] over the job's escrow against the worker the job is
+//! assigned to. A committee is drawn from the accounts staked in the ledger,
+//! at random but in proportion to stake, from the hash of the chain tip,
+//! which neither party controls; the parties themselves are never drawn.
+//! Both parties file signed [`Evidence`]: the hashes of logs and evaluation
+//! reports the jurors fetch and review off chain. Each juror then signs a
+//! [`Verdict`].
+//!
+//! Once every juror voted or the voting period is over, the verdict with the
+//! most votes is carried out on the escrow: released to the worker, refunded
+//! to the poster, or refunded with the worker's stake slashed. A tie refunds
+//! the poster. Jurors who voted with the majority are rewarded; those who
+//! voted against it or not at all lose part of their stake.
+
+use crate::blockchain::Blockchain;
+use crate::job_manager::{JobManager, JobManagerError};
+use crate::scheduler::JobScheduler;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Domain tag of juror selection and vote signatures.
+const DISPUTE_DOMAIN: &[u8] = b"bcai-dispute";
+/// Domain tag of evidence signatures.
+const EVIDENCE_DOMAIN: &[u8] = b"bcai-dispute-evidence";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArbitrationConfig {
+    /// Jurors drawn per dispute.
+    pub jurors: usize,
+    /// Stake a candidate needs to be drawn.
+    pub min_juror_stake: u64,
+    /// Blocks the parties have to file evidence and the jurors to vote.
+    pub voting_blocks: u32,
+    /// Minted to each juror who voted with the majority.
+    pub juror_reward: u64,
+    /// Stake slashed from each juror who voted against the majority or did
+    /// not vote.
+    pub juror_penalty: u64,
+    /// Stake slashed from the worker on a [`Verdict::Slash`].
+    pub worker_penalty: u64,
+}
+
+impl Default for ArbitrationConfig {
+    fn default() -> Self {
+        Self {
+            jurors: 5,
+            min_juror_stake: 100,
+            voting_blocks: 100,
+            juror_reward: 10,
+            juror_penalty: 20,
+            worker_penalty: 100,
+        }
+    }
+}
+
+/// What the committee decides to do with the escrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Verdict {
+    /// The work stands: the worker is paid.
+    Release,
+    /// The work is rejected: the poster gets the reward back.
+    Refund,
+    /// The work was fraudulent: the poster is refunded and the worker loses
+    /// stake.
+    Slash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvidenceKind {
+    Logs,
+    EvaluationReport,
+    Other,
+}
+
+/// A document a party asks the jurors to review, by hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Evidence {
+    pub kind: EvidenceKind,
+    /// Hex SHA-256 of the document.
+    pub hash: String,
+    /// Hex ed25519 key of the party filing it.
+    pub submitted_by: String,
+    #[serde(default)]
+    pub signature: Vec<u8>,
+}
+
+impl Evidence {
+    /// Files the document hashed `hash` in the dispute over `job_id`, signed
+    /// by the party holding `key`.
+    pub fn sign(job_id: u64, kind: EvidenceKind, hash: String, key: &SigningKey) -> Self {
+        let mut evidence = Self {
+            kind,
+            hash,
+            submitted_by: hex::encode(key.verifying_key().to_bytes()),
+            signature: Vec::new(),
+        };
+        let signature: Signature = key.sign(&evidence.message(job_id));
+        evidence.signature = signature.to_bytes().to_vec();
+        evidence
+    }
+
+    /// Verifies the submitter signed this evidence for the dispute over
+    /// `job_id`.
+    pub fn verify(&self, job_id: u64) -> bool {
+        let Ok(pk_bytes) = hex::decode(&self.submitted_by) else { return false };
+        let Ok(pk_bytes) = <[u8; 32]>::try_from(pk_bytes) else { return false };
+        let Ok(vk) = VerifyingKey::from_bytes(&pk_bytes) else { return false };
+        let Ok(sig) = Signature::from_slice(&self.signature) else { return false };
+        vk.verify(&self.message(job_id), &sig).is_ok()
+    }
+
+    fn message(&self, job_id: u64) -> Vec<u8> {
+        let mut msg = EVIDENCE_DOMAIN.to_vec();
+        msg.extend_from_slice(&job_id.to_be_bytes());
+        msg.push(self.kind as u8);
+        msg.extend_from_slice(self.hash.as_bytes());
+        msg
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DisputeError {
+    #[error("job {0} is not escrowed")]
+    JobNotFound(u64),
+    #[error("job {0} is not assigned to a worker")]
+    NotAssigned(u64),
+    #[error("job {0} is already disputed")]
+    AlreadyOpen(u64),
+    #[error("job {0} is not disputed")]
+    NotOpen(u64),
+    #[error("{available} eligible jurors, {needed} needed")]
+    NotEnoughJurors { available: usize, needed: usize },
+    #[error("{0} is not a party to the dispute")]
+    NotAParty(String),
+    #[error("{0} is not a juror of the dispute")]
+    NotAJuror(String),
+    #[error("{0} already voted")]
+    AlreadyVoted(String),
+    #[error("signature by {0} is invalid")]
+    InvalidSignature(String),
+    #[error("voting closed at block {0}")]
+    VotingClosed(u32),
+    #[error("voting is open until block {0}")]
+    VotingOpen(u32),
+    #[error("evidence hash {0} is not hex SHA-256")]
+    InvalidEvidence(String),
+    #[error("job manager error: {0}")]
+    JobManager(String),
+}
+
+impl From<JobManagerError> for DisputeError {
+    fn from(e: JobManagerError) -> Self {
+        Self::JobManager(e.to_string())
+    }
+}
+
+/// A juror's signed verdict on a dispute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedVote {
+    pub job_id: u64,
+    /// Hex ed25519 key of the juror.
+    pub juror: String,
+    pub verdict: Verdict,
+    pub signature: Vec<u8>,
+}
+
+fn vote_message(job_id: u64, verdict: Verdict) -> Vec<u8> {
+    let mut msg = DISPUTE_DOMAIN.to_vec();
+    msg.extend_from_slice(&job_id.to_be_bytes());
+    msg.push(verdict as u8);
+    msg
+}
+
+/// Signs `verdict` on the dispute over `job_id`.
+pub fn sign_vote(job_id: u64, verdict: Verdict, key: &SigningKey) -> SignedVote {
+    let signature: Signature = key.sign(&vote_message(job_id, verdict));
+    SignedVote {
+        job_id,
+        juror: hex::encode(key.verifying_key().to_bytes()),
+        verdict,
+        signature: signature.to_bytes().to_vec(),
+    }
+}
+
+/// Verifies the juror's signature on a vote.
+pub fn verify_vote(vote: &SignedVote) -> bool {
+    let Ok(pk_bytes) = hex::decode(&vote.juror) else { return false };
+    let Ok(pk_bytes) = <[u8; 32]>::try_from(pk_bytes) else { return false };
+    let Ok(vk) = VerifyingKey::from_bytes(&pk_bytes) else { return false };
+    let Ok(sig) = Signature::from_slice(&vote.signature) else { return false };
+    vk.verify(&vote_message(vote.job_id, vote.verdict), &sig).is_ok()
+}
+
+/// Draws `count` distinct jurors from `candidates`, each weighted by its
+/// stake, with randomness derived from `seed` and `job_id`. The same inputs
+/// always draw the same committee.
+pub fn select_jurors(
+    seed: &[u8],
+    job_id: u64,
+    candidates: &[(String, u64)],
+    count: usize,
+) -> Vec<String> {
+    let mut pool: Vec<&(String, u64)> = candidates.iter().filter(|(_, stake)| *stake > 0).collect();
+    pool.sort();
+    pool.dedup_by(|a, b| a.0 == b.0);
+    let mut drawn = Vec::new();
+    while drawn.len() < count && !pool.is_empty() {
+        let round = drawn.len() as u64;
+        let mut hasher = Sha256::new();
+        hasher.update(DISPUTE_DOMAIN);
+        hasher.update(seed);
+        hasher.update(job_id.to_be_bytes());
+        hasher.update(round.to_be_bytes());
+        let digest = hasher.finalize();
+        let draw = u64::from_be_bytes(digest[..8].try_into().expect("8 bytes"));
+        let total: u128 = pool.iter().map(|(_, stake)| *stake as u128).sum();
+        let mut point = draw as u128 % total;
+        let index = pool
+            .iter()
+            .position(|(_, stake)| {
+                if point < *stake as u128 {
+                    return true;
+                }
+                point -= *stake as u128;
+                false
+            })
+            .expect("point is below the total stake");
+        drawn.push(pool.remove(index).0.clone());
+    }
+    drawn
+}
+
+/// One disputed job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dispute {
+    pub job_id: u64,
+    pub poster: String,
+    pub worker: String,
+    pub reward: u64,
+    pub evidence: Vec<Evidence>,
+    pub jurors: Vec<String>,
+    pub votes: BTreeMap<String, Verdict>,
+    pub opened_at: u32,
+    /// Last block at which evidence and votes are accepted.
+    pub closes_at: u32,
+}
+
+impl Dispute {
+    /// Votes for each verdict.
+    pub fn tally(&self) -> BTreeMap<Verdict, usize> {
+        let mut tally = BTreeMap::new();
+        for verdict in self.votes.values() {
+            *tally.entry(*verdict).or_default() += 1;
+        }
+        tally
+    }
+
+    /// The verdict with the most votes; a tie, or no vote at all, refunds.
+    pub fn majority(&self) -> Verdict {
+        let tally = self.tally();
+        let top = tally.values().copied().max().unwrap_or(0);
+        let mut leaders = tally.iter().filter(|(_, n)| **n == top).map(|(v, _)| *v);
+        match (leaders.next(), leaders.next()) {
+            (Some(verdict), None) => verdict,
+            _ => Verdict::Refund,
+        }
+    }
+
+    fn is_party(&self, account: &str) -> bool {
+        account == self.poster || account == self.worker
+    }
+}
+
+/// How a dispute was settled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub job_id: u64,
+    pub verdict: Verdict,
+    pub tally: BTreeMap<Verdict, usize>,
+    /// Jurors paid for voting with the majority.
+    pub rewarded: Vec<String>,
+    /// Jurors slashed for voting against the majority or not voting.
+    pub penalized: Vec<String>,
+}
+
+/// Open disputes, and the committee that settles each.
+#[derive(Debug, Clone, Default)]
+pub struct Arbitration {
+    config: ArbitrationConfig,
+    disputes: BTreeMap<u64, Dispute>,
+}
+
+impl Arbitration {
+    pub fn new(config: ArbitrationConfig) -> Self {
+        Self { config, disputes: BTreeMap::new() }
+    }
+
+    pub fn config(&self) -> &ArbitrationConfig {
+        &self.config
+    }
+
+    /// Opens a dispute between the poster of escrowed job `job_id` and the
+    /// worker `scheduler` assigned it to, at the height of `chain`'s tip.
+    /// Jurors are drawn from the accounts staked in `manager`'s ledger, with
+    /// the tip's hash as the seed.
+    pub fn open(
+        &mut self,
+        manager: &JobManager,
+        scheduler: &JobScheduler,
+        chain: &Blockchain,
+        job_id: u64,
+    ) -> Result<&Dispute, DisputeError> {
+        if self.disputes.contains_key(&job_id) {
+            return Err(DisputeError::AlreadyOpen(job_id));
+        }
+        let (poster, reward) =
+            manager.jobs().get(&job_id).cloned().ok_or(DisputeError::JobNotFound(job_id))?;
+        let worker = scheduler.assigned_worker(job_id).ok_or(DisputeError::NotAssigned(job_id))?;
+        let tip = chain.get_tip();
+        let now = tip.index;
+        let eligible: Vec<(String, u64)> = manager
+            .ledger()
+            .stakes()
+            .filter(|(c, _)| *c != poster && *c != worker)
+            .filter(|(_, stake)| *stake >= self.config.min_juror_stake.max(1))
+            .map(|(c, stake)| (c.to_string(), stake))
+            .collect();
+        let jurors = select_jurors(tip.hash.as_bytes(), job_id, &eligible, self.config.jurors);
+        if jurors.len() < self.config.jurors {
+            let needed = self.config.jurors;
+            return Err(DisputeError::NotEnoughJurors { available: jurors.len(), needed });
+        }
+        let dispute = Dispute {
+            job_id,
+            poster,
+            worker: worker.to_string(),
+            reward,
+            evidence: Vec::new(),
+            jurors,
+            votes: BTreeMap::new(),
+            opened_at: now,
+            closes_at: now.saturating_add(self.config.voting_blocks),
+        };
+        Ok(self.disputes.entry(job_id).or_insert(dispute))
+    }
+
+    pub fn dispute(&self, job_id: u64) -> Option<&Dispute> {
+        self.disputes.get(&job_id)
+    }
+
+    pub fn disputes(&self) -> impl Iterator<Item = &Dispute> {
+        self.disputes.values()
+    }
+
+    fn open_dispute(&mut self, job_id: u64, now: u32) -> Result<&mut Dispute, DisputeError> {
+        let dispute = self.disputes.get_mut(&job_id).ok_or(DisputeError::NotOpen(job_id))?;
+        if now > dispute.closes_at {
+            return Err(DisputeError::VotingClosed(dispute.closes_at));
+        }
+        Ok(dispute)
+    }
+
+    /// Files `evidence` with the dispute over `job_id`, for a party only,
+    /// signed by that party.
+    pub fn submit_evidence(
+        &mut self,
+        job_id: u64,
+        evidence: Evidence,
+        now: u32,
+    ) -> Result<(), DisputeError> {
+        let dispute = self.open_dispute(job_id, now)?;
+        if !dispute.is_party(&evidence.submitted_by) {
+            return Err(DisputeError::NotAParty(evidence.submitted_by));
+        }
+        if !evidence.verify(job_id) {
+            return Err(DisputeError::InvalidSignature(evidence.submitted_by));
+        }
+        if !matches!(hex::decode(&evidence.hash), Ok(hash) if hash.len() == 32) {
+            return Err(DisputeError::InvalidEvidence(evidence.hash));
+        }
+        dispute.evidence.push(evidence);
+        Ok(())
+    }
+
+    /// Records a juror's vote. Votes are final.
+    pub fn vote(&mut self, vote: &SignedVote, now: u32) -> Result<(), DisputeError> {
+        let dispute = self.open_dispute(vote.job_id, now)?;
+        if !dispute.jurors.contains(&vote.juror) {
+            return Err(DisputeError::NotAJuror(vote.juror.clone()));
+        }
+        if dispute.votes.contains_key(&vote.juror) {
+            return Err(DisputeError::AlreadyVoted(vote.juror.clone()));
+        }
+        if !verify_vote(vote) {
+            return Err(DisputeError::InvalidSignature(vote.juror.clone()));
+        }
+        dispute.votes.insert(vote.juror.clone(), vote.verdict);
+        Ok(())
+    }
+
+    /// Carries out the majority verdict on the escrow of `job_id` and pays or
+    /// slashes its jurors, once all of them voted or voting closed by `now`.
+    pub fn settle(
+        &mut self,
+        manager: &mut JobManager,
+        job_id: u64,
+        now: u32,
+    ) -> Result<Resolution, DisputeError> {
+        let dispute = self.disputes.get(&job_id).ok_or(DisputeError::NotOpen(job_id))?;
+        if dispute.votes.len() < dispute.jurors.len() && now <= dispute.closes_at {
+            return Err(DisputeError::VotingOpen(dispute.closes_at));
+        }
+        let verdict = dispute.majority();
+        let payee = match verdict {
+            Verdict::Release => &dispute.worker,
+            Verdict::Refund | Verdict::Slash => &dispute.poster,
+        };
+        manager.complete_job(job_id, &[(payee.clone(), dispute.reward)])?;
+
+        let ledger = manager.ledger_mut();
+        if verdict == Verdict::Slash {
+            let _ = ledger.slash(&dispute.worker, self.config.worker_penalty);
+        }
+        let (mut rewarded, mut penalized) = (Vec::new(), Vec::new());
+        for juror in &dispute.jurors {
+            if dispute.votes.get(juror) == Some(&verdict) {
+                ledger.mint(juror, self.config.juror_reward);
+                rewarded.push(juror.clone());
+            } else {
+                let _ = ledger.slash(juror, self.config.juror_penalty);
+                penalized.push(juror.clone());
+            }
+        }
+        let tally = dispute.tally();
+        self.disputes.remove(&job_id);
+        Ok(Resolution { job_id, verdict, tally, rewarded, penalized })
+    }
+}
@@ -1,6 +1,7 @@
 //! Node allocation logic for primary & replica selection.
 //! Uses a deterministic multi-factor scoring system described in docs.
 
+use crate::large_data_transfer::redundancy::ErasureScheme;
 use serde::{Serialize, Deserialize};
 
 /// Tunable weights for each metric.  Sum need not equal 1 – scores are weighted sum.
@@ -80,4 +81,19 @@ pub fn allocate_nodes(
         seen_regions.push(region);
     }
     selected
-} 
\ No newline at end of file
+} 
+/// Assigns each shard of `scheme` to its own node, shard `i` to the `i`th
+/// node returned, best scored first. `None` when there are fewer eligible
+/// nodes than shards: two shards on one node would lose both together.
+pub fn allocate_shards(
+    policy: &StoragePolicy,
+    metrics: &[NodeMetrics],
+    scheme: ErasureScheme,
+) -> Option<Vec<String>> {
+    let shards = scheme.total_shards();
+    if shards == 0 || shards > 256 {
+        return None;
+    }
+    let nodes = allocate_nodes(policy, metrics, (shards - 1) as u8);
+    (nodes.len() == shards).then_some(nodes)
+}
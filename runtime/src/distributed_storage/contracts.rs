@@ -0,0 +1,405 @@
+//! Storage contracts and proof-of-retrievability audits.
+//!
+//! A [`StorageContract`] pays a node to hold an object for its owner. The
+//! owner escrows the payment when opening it. The contract then waits for
+//! the node to accept it, which locks the collateral out of the node's
+//! stake; a contract the node does not accept within
+//! [`AuditConfig::accept_blocks`] fails and refunds the owner. Every [`AuditConfig::interval_blocks`] the
+//! chain challenges the node for one chunk, picked from the block hash so
+//! the node cannot know it in advance, and the node answers with a
+//! [`StorageProof`]: the chunk itself and its Merkle path to the object's
+//! root. A passed audit pays the node one instalment from escrow. A missed
+//! deadline slashes part of the collateral and flags the object for
+//! re-replication; after [`AuditConfig::max_failures`] the contract is void
+//! and the rest of the escrow goes back to the owner. Collateral still
+//! locked returns to the node's stake once the contract ends or moves.
+
+use crate::large_data_transfer::{
+    chunk::ChunkId,
+    descriptor::LargeDataDescriptor,
+    merkle::{build_merkle_root, merkle_proof, verify_merkle_proof},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ContractError {
+    #[error("storage contract is malformed: {0}")]
+    Malformed(&'static str),
+    #[error("storage contract {0} already exists")]
+    AlreadyExists(String),
+    #[error("unknown storage contract {0}")]
+    Unknown(String),
+    #[error("storage contract {0} is closed")]
+    Closed(String),
+    #[error("{node} does not hold storage contract {contract}")]
+    NotTheNode { contract: String, node: String },
+    #[error("storage contract {0} has no open challenge")]
+    NoChallenge(String),
+    #[error("challenge is for chunk {expected}, not {got}")]
+    WrongChunk { expected: u32, got: u32 },
+    #[error("proof for chunk {0} does not lead to the contract's root")]
+    InvalidProof(u32),
+    #[error("storage contract {0} is not waiting for its node")]
+    NotPending(String),
+    #[error("node stake {available} cannot back collateral {required}")]
+    InsufficientStake { required: u64, available: u64 },
+}
+
+/// How often storage nodes are audited and what failing costs them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditConfig {
+    /// Blocks between challenges of a contract.
+    pub interval_blocks: u32,
+    /// Blocks a node has to answer a challenge.
+    pub response_blocks: u32,
+    /// Failed audits after which a contract is void.
+    pub max_failures: u32,
+    /// Blocks a node has to accept a contract opened or moved to it.
+    #[serde(default = "default_accept_blocks")]
+    pub accept_blocks: u32,
+}
+
+fn default_accept_blocks() -> u32 {
+    20
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            interval_blocks: 20,
+            response_blocks: 5,
+            max_failures: 3,
+            accept_blocks: default_accept_blocks(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ContractStatus {
+    /// Waiting for the node to accept and lock its collateral.
+    Pending,
+    Active,
+    /// Every instalment was paid.
+    Completed,
+    /// Void after too many failed audits.
+    Failed,
+}
+
+/// A chunk the node must prove it holds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Challenge {
+    pub chunk_index: u32,
+    pub issued_at: u32,
+    /// Last block that may include the answer.
+    pub deadline: u32,
+}
+
+/// A node's answer to a challenge.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StorageProof {
+    pub contract_id: String,
+    pub chunk_index: u32,
+    pub data: Vec<u8>,
+    /// Sibling hashes from the chunk up to the root.
+    pub path: Vec<String>,
+}
+
+impl StorageProof {
+    /// Proves holding chunk `chunk_index`, whose content is `data`, of the
+    /// object `descriptor` describes.
+    pub fn new(
+        contract_id: &str,
+        descriptor: &LargeDataDescriptor,
+        chunk_index: u32,
+        data: Vec<u8>,
+    ) -> Option<Self> {
+        let path = merkle_proof(&descriptor.chunk_hashes, chunk_index as usize)?;
+        Some(Self { contract_id: contract_id.to_string(), chunk_index, data, path })
+    }
+}
+
+/// The chunk of a `chunk_count` chunk object challenged at the block with
+/// `block_hash`.
+pub fn challenge_index(block_hash: &str, contract_id: &str, chunk_count: u32) -> u32 {
+    let digest = Sha256::digest(format!("{}{}", block_hash, contract_id).as_bytes());
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&digest[..8]);
+    (u64::from_le_bytes(seed) % chunk_count.max(1) as u64) as u32
+}
+
+/// An agreement to store an object, audited on chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StorageContract {
+    pub contract_id: String,
+    /// Account paying for the storage.
+    pub owner: String,
+    /// Account of the storage node.
+    pub node: String,
+    pub descriptor_hash: String,
+    /// Merkle root of the object's chunk hashes.
+    pub merkle_root: String,
+    pub chunk_count: u32,
+    /// Paid to the node for every passed audit.
+    pub payment_per_audit: u64,
+    /// Payment still held.
+    pub escrow: u64,
+    /// Stake of the node at risk.
+    pub collateral: u64,
+    /// Collateral slashed so far.
+    pub slashed: u64,
+    /// Collateral currently locked out of the node's stake.
+    #[serde(default)]
+    pub locked: u64,
+    /// Last block a pending contract may be accepted in, set once the
+    /// chain first sees it pending.
+    #[serde(default)]
+    pub accept_deadline: Option<u32>,
+    pub challenge: Option<Challenge>,
+    pub passed: u32,
+    pub failed: u32,
+    pub status: ContractStatus,
+    /// Healthy copies of the object the owner wants across its contracts;
+    /// fewer makes the repair daemon move contracts to new nodes.
+    #[serde(default = "default_target_replicas")]
+    pub target_replicas: u8,
+}
+
+fn default_target_replicas() -> u8 {
+    1
+}
+
+impl StorageContract {
+    /// A contract paying `payment_per_audit` for each of `audits` passed
+    /// audits of the object `descriptor` describes.
+    pub fn new(
+        contract_id: &str,
+        owner: &str,
+        node: &str,
+        descriptor: &LargeDataDescriptor,
+        payment_per_audit: u64,
+        audits: u32,
+        collateral: u64,
+    ) -> Self {
+        Self {
+            contract_id: contract_id.to_string(),
+            owner: owner.to_string(),
+            node: node.to_string(),
+            descriptor_hash: descriptor.id.clone(),
+            merkle_root: build_merkle_root(&descriptor.chunk_hashes),
+            chunk_count: descriptor.chunk_hashes.len() as u32,
+            payment_per_audit,
+            escrow: payment_per_audit.saturating_mul(audits as u64),
+            collateral,
+            slashed: 0,
+            locked: 0,
+            accept_deadline: None,
+            challenge: None,
+            passed: 0,
+            failed: 0,
+            status: ContractStatus::Pending,
+            target_replicas: default_target_replicas(),
+        }
+    }
+
+    pub fn with_target_replicas(mut self, target_replicas: u8) -> Self {
+        self.target_replicas = target_replicas;
+        self
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == ContractStatus::Active
+    }
+
+    /// Whether the contract is pending or active.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, ContractStatus::Pending | ContractStatus::Active)
+    }
+
+    /// A new contract needs an id, an object, distinct parties, something
+    /// to pay and no history.
+    pub fn check_well_formed(&self) -> Result<(), ContractError> {
+        if self.contract_id.is_empty() || self.merkle_root.is_empty() || self.chunk_count == 0 {
+            return Err(ContractError::Malformed("missing contract id or object"));
+        }
+        if self.owner == self.node {
+            return Err(ContractError::Malformed("owner stores its own object"));
+        }
+        if self.target_replicas == 0 {
+            return Err(ContractError::Malformed("no replicas wanted"));
+        }
+        if self.payment_per_audit == 0 || self.escrow < self.payment_per_audit {
+            return Err(ContractError::Malformed("no payment escrowed"));
+        }
+        if self.challenge.is_some()
+            || self.passed != 0
+            || self.failed != 0
+            || self.slashed != 0
+            || self.locked != 0
+            || self.accept_deadline.is_some()
+            || self.status != ContractStatus::Pending
+        {
+            return Err(ContractError::Malformed("new contracts start without audits"));
+        }
+        Ok(())
+    }
+
+    /// Check that `sender` may accept the pending contract with `stake`
+    /// backing its collateral.
+    pub fn check_accept(&self, sender: &str, stake: u64) -> Result<(), ContractError> {
+        if self.status != ContractStatus::Pending {
+            return Err(ContractError::NotPending(self.contract_id.clone()));
+        }
+        if sender != self.node {
+            return Err(ContractError::NotTheNode {
+                contract: self.contract_id.clone(),
+                node: sender.to_string(),
+            });
+        }
+        if stake < self.collateral {
+            return Err(ContractError::InsufficientStake {
+                required: self.collateral,
+                available: stake,
+            });
+        }
+        Ok(())
+    }
+
+    /// Activates the contract with its collateral locked, returning the
+    /// collateral to take out of the node's stake.
+    pub fn accept(&mut self) -> u64 {
+        self.status = ContractStatus::Active;
+        self.accept_deadline = None;
+        self.locked = self.collateral;
+        self.locked
+    }
+
+    /// Fails a contract its node did not accept in time.
+    pub fn expire(&mut self) {
+        self.status = ContractStatus::Failed;
+        self.accept_deadline = None;
+    }
+
+    /// Unlocks the collateral left, returning it to give back to the
+    /// node's stake.
+    pub fn release(&mut self) -> u64 {
+        std::mem::take(&mut self.locked)
+    }
+
+    /// Challenges the node at block `index` with hash `block_hash`.
+    pub fn issue_challenge(
+        &mut self,
+        index: u32,
+        block_hash: &str,
+        config: &AuditConfig,
+    ) -> &Challenge {
+        self.challenge.insert(Challenge {
+            chunk_index: challenge_index(block_hash, &self.contract_id, self.chunk_count),
+            issued_at: index,
+            deadline: index.saturating_add(config.response_blocks),
+        })
+    }
+
+    /// Check that `sender` answers the open challenge with a valid proof.
+    pub fn check_proof(&self, sender: &str, proof: &StorageProof) -> Result<(), ContractError> {
+        if !self.is_active() {
+            return Err(ContractError::Closed(self.contract_id.clone()));
+        }
+        if sender != self.node {
+            return Err(ContractError::NotTheNode {
+                contract: self.contract_id.clone(),
+                node: sender.to_string(),
+            });
+        }
+        let challenge = self
+            .challenge
+            .as_ref()
+            .ok_or_else(|| ContractError::NoChallenge(self.contract_id.clone()))?;
+        if proof.chunk_index != challenge.chunk_index {
+            return Err(ContractError::WrongChunk {
+                expected: challenge.chunk_index,
+                got: proof.chunk_index,
+            });
+        }
+        let leaf = ChunkId::from_data(&proof.data);
+        if !verify_merkle_proof(
+            &self.merkle_root,
+            leaf.as_str(),
+            proof.chunk_index as usize,
+            &proof.path,
+        ) {
+            return Err(ContractError::InvalidProof(proof.chunk_index));
+        }
+        Ok(())
+    }
+
+    /// Closes the open challenge as passed, returning the instalment owed
+    /// to the node. The contract completes once the escrow is paid out.
+    pub fn pass(&mut self) -> u64 {
+        self.challenge = None;
+        self.passed += 1;
+        let payout = self.payment_per_audit.min(self.escrow);
+        self.escrow -= payout;
+        if self.escrow == 0 {
+            self.status = ContractStatus::Completed;
+        }
+        payout
+    }
+
+    /// Closes the open challenge as failed, burning part of the locked
+    /// collateral and returning the amount burned. The contract fails after
+    /// `max_failures` failed audits.
+    pub fn fail(&mut self, config: &AuditConfig) -> u64 {
+        self.challenge = None;
+        self.failed += 1;
+        let share = self.collateral.div_ceil(config.max_failures.max(1) as u64);
+        let slash = share.min(self.locked);
+        self.slashed += slash;
+        self.locked -= slash;
+        if self.failed >= config.max_failures {
+            self.status = ContractStatus::Failed;
+        }
+        slash
+    }
+
+    /// Check that `sender` may move the contract to `node`, escrowing
+    /// `audits` more instalments, with `stake` backing the collateral.
+    pub fn check_repair(
+        &self,
+        sender: &str,
+        node: &str,
+        audits: u32,
+        stake: u64,
+    ) -> Result<(), ContractError> {
+        if sender != self.owner {
+            return Err(ContractError::Malformed("sender is not the owner"));
+        }
+        if node == self.node || node == self.owner {
+            return Err(ContractError::Malformed("repair needs a new node"));
+        }
+        if self.escrow == 0 && audits == 0 {
+            return Err(ContractError::Malformed("no payment escrowed"));
+        }
+        if stake < self.collateral {
+            return Err(ContractError::InsufficientStake {
+                required: self.collateral,
+                available: stake,
+            });
+        }
+        Ok(())
+    }
+
+    /// Moves the contract to `node`, which starts with a clean record once
+    /// it accepts, and adds `top_up` to the escrow. Collateral still locked
+    /// must be released first.
+    pub fn reassign(&mut self, node: &str, top_up: u64) {
+        self.node = node.to_string();
+        self.escrow += top_up;
+        self.slashed = 0;
+        self.failed = 0;
+        self.challenge = None;
+        self.accept_deadline = None;
+        self.status = ContractStatus::Pending;
+    }
+}
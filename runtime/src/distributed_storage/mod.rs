@@ -3,10 +3,21 @@ pub mod replication;
 pub mod reward;
 pub mod allocation;
 pub mod daemon;
+pub mod tiering;
+pub mod contracts;
+pub mod repair;
 
 // Re-export commonly used items so callers can simply `use distributed_storage::*`.
 pub use storage::{StorageConfig, ConsistencyLevel, StorageEntry, StorageResult, StorageStats};
 pub use replication::{StorageNode, ReplicationManager};
 pub use reward::{RewardPolicy, calculate_reward};
-pub use allocation::{StoragePolicy, NodeMetrics, allocate_nodes};
+pub use allocation::{StoragePolicy, NodeMetrics, allocate_nodes, allocate_shards};
 pub use daemon::run_auto_heal;
+pub use repair::{plan_repairs, run_repairs, RepairConfig, RepairPlan};
+pub use contracts::{
+    AuditConfig, Challenge, ContractError, ContractStatus, StorageContract, StorageProof,
+};
+pub use tiering::{
+    run_migrations, LifecyclePolicy, Migration, MigrationEngine, StorageClass, StorageClassConfig,
+    TierConfig, TieredObject,
+};
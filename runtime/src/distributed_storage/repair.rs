@@ -0,0 +1,233 @@
+//! Re-replication of objects whose storage contracts went unhealthy.
+//!
+//! A contract's node is unhealthy once its heartbeats stop, once an audit
+//! flags it in [`State::storage_repairs`] or once the contract failed for
+//! good. When fewer healthy nodes hold an object than its contracts'
+//! [`StorageContract::target_replicas`], [`plan_repairs`] picks fresh nodes
+//! with enough stake to back the collateral, and [`run_repairs`] copies the
+//! object's chunks there through the transfer coordinator before moving the
+//! contract to the new node on chain. The new node then has to accept the
+//! contract, locking its own collateral, like any contract opened to it.
+
+use super::contracts::{ContractStatus, StorageContract};
+use super::replication::{ReplicationManager, StorageNode};
+use crate::blockchain::{
+    state::State,
+    transaction::{StorageTx, Transaction},
+    Blockchain,
+};
+use crate::large_data_transfer::{
+    chunk::ChunkId,
+    descriptor::LargeDataDescriptor,
+    manager::ChunkManager,
+    network::{coordinator::NetworkTransferCoordinator, models::NetworkTransferMessage},
+};
+use schnorrkel::SecretKey;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info};
+
+/// How the repair daemon judges nodes and pays their replacements.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairConfig {
+    /// Nodes silent this long are unhealthy.
+    pub heartbeat_timeout: Duration,
+    /// Time between repair passes.
+    pub period: Duration,
+    /// Instalments escrowed for the new node of a repaired contract.
+    pub audits: u32,
+    pub fee: u64,
+}
+
+impl Default for RepairConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout: Duration::from_secs(300),
+            period: Duration::from_secs(60),
+            audits: 10,
+            fee: 1,
+        }
+    }
+}
+
+/// Moving one contract's object to a new node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairPlan {
+    pub contract_id: String,
+    pub descriptor_hash: String,
+    pub failed_node: String,
+    pub new_node: String,
+    pub chunks: Vec<ChunkId>,
+}
+
+impl ReplicationManager {
+    /// Records a heartbeat of `node_id` at unix time `at`.
+    pub fn record_heartbeat(&mut self, node_id: &str, at: u64) {
+        if let Some(node) = self.nodes.iter_mut().find(|n| n.node_id == node_id) {
+            node.last_seen = node.last_seen.max(at);
+        }
+    }
+}
+
+fn is_fresh(node: &StorageNode, now: u64, config: &RepairConfig) -> bool {
+    now.saturating_sub(node.last_seen) < config.heartbeat_timeout.as_secs()
+}
+
+/// Whether the node of `contract` still holds its object, judged at unix
+/// time `now`.
+pub fn is_healthy(
+    contract: &StorageContract,
+    state: &State,
+    nodes: &[StorageNode],
+    now: u64,
+    config: &RepairConfig,
+) -> bool {
+    contract.status != ContractStatus::Failed
+        && state.storage_repairs.get(&contract.descriptor_hash) != Some(&contract.node)
+        && nodes.iter().any(|n| n.node_id == contract.node && is_fresh(n, now, config))
+}
+
+/// Repairs due at unix time `now` for the objects in `descriptors`, the
+/// owner's objects, moving unhealthy contracts to fresh nodes that hold no
+/// copy yet and have the stake to back them, most reliable first. Objects
+/// whose repairs find no such node wait for the next pass.
+pub fn plan_repairs(
+    state: &State,
+    descriptors: &HashMap<String, LargeDataDescriptor>,
+    nodes: &[StorageNode],
+    now: u64,
+    config: &RepairConfig,
+) -> Vec<RepairPlan> {
+    let mut by_object: BTreeMap<&str, Vec<&StorageContract>> = BTreeMap::new();
+    for contract in state.storage_contracts.values() {
+        if contract.status != ContractStatus::Completed {
+            by_object.entry(&contract.descriptor_hash).or_default().push(contract);
+        }
+    }
+    let mut candidates: Vec<&StorageNode> =
+        nodes.iter().filter(|n| is_fresh(n, now, config)).collect();
+    candidates.sort_by(|a, b| b.reliability_score.total_cmp(&a.reliability_score));
+
+    let mut plans = Vec::new();
+    for (descriptor_hash, mut contracts) in by_object {
+        let Some(descriptor) = descriptors.get(descriptor_hash) else { continue };
+        contracts.sort_by(|a, b| a.contract_id.cmp(&b.contract_id));
+        let target = contracts.iter().map(|c| c.target_replicas as usize).max().unwrap_or(1);
+        let (healthy, unhealthy): (Vec<&StorageContract>, Vec<&StorageContract>) =
+            contracts.into_iter().partition(|c| is_healthy(c, state, nodes, now, config));
+        let mut holders: HashSet<&str> = healthy.iter().map(|c| c.node.as_str()).collect();
+        holders.extend(unhealthy.iter().map(|c| c.node.as_str()));
+        let chunks: Vec<ChunkId> =
+            descriptor.chunk_hashes.iter().filter_map(|h| ChunkId::from_hex(h).ok()).collect();
+        for contract in unhealthy.iter().take(target.saturating_sub(healthy.len())) {
+            let stake = |id: &str| state.stakes.get(id).copied().unwrap_or(0);
+            let Some(node) = candidates.iter().find(|n| {
+                !holders.contains(n.node_id.as_str())
+                    && n.node_id != contract.owner
+                    && stake(&n.node_id) >= contract.collateral
+            }) else {
+                break;
+            };
+            holders.insert(&node.node_id);
+            plans.push(RepairPlan {
+                contract_id: contract.contract_id.clone(),
+                descriptor_hash: descriptor_hash.to_string(),
+                failed_node: contract.node.clone(),
+                new_node: node.node_id.clone(),
+                chunks: chunks.clone(),
+            });
+        }
+    }
+    plans
+}
+
+/// Every `config.period`, plans the repairs of the objects `owner_key`
+/// owns, sends their chunks from `chunk_manager` to the new nodes and,
+/// once every chunk is sent, queues a transaction moving the contract.
+/// Contracts with a repair already queued are left alone.
+pub async fn run_repairs(
+    chain: Arc<Mutex<Blockchain>>,
+    replication: Arc<Mutex<ReplicationManager>>,
+    chunk_manager: Arc<ChunkManager>,
+    coordinator: NetworkTransferCoordinator,
+    descriptors: HashMap<String, LargeDataDescriptor>,
+    owner_key: SecretKey,
+    config: RepairConfig,
+) {
+    let owner = hex::encode(owner_key.to_public().to_bytes());
+    let mut ticker = tokio::time::interval(config.period);
+    loop {
+        ticker.tick().await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let nodes = replication.lock().unwrap().nodes.clone();
+        let plans = {
+            let chain = chain.lock().unwrap();
+            let queued: HashSet<&str> = chain
+                .pending_transactions
+                .iter()
+                .filter_map(|tx| match &tx.storage {
+                    Some(StorageTx::RepairStorageContract { contract_id, .. }) => {
+                        Some(contract_id.as_str())
+                    }
+                    _ => None,
+                })
+                .collect();
+            plan_repairs(&chain.state, &descriptors, &nodes, now, &config)
+                .into_iter()
+                .filter(|plan| !queued.contains(plan.contract_id.as_str()))
+                .filter(|plan| chain.state.storage_contracts[&plan.contract_id].owner == owner)
+                .collect::<Vec<_>>()
+        };
+        for plan in plans {
+            if !copy_chunks(&chunk_manager, &coordinator, &plan).await {
+                continue;
+            }
+            let mut chain = chain.lock().unwrap();
+            let tx = Transaction::new_repair_storage_contract_signed(
+                &owner_key,
+                plan.contract_id.clone(),
+                plan.new_node.clone(),
+                config.audits,
+                config.fee,
+                chain.pending_nonce(&owner),
+            );
+            match chain.add_transaction(tx) {
+                Ok(()) => info!(
+                    contract = %plan.contract_id,
+                    from = %plan.failed_node,
+                    to = %plan.new_node,
+                    "Storage contract repaired"
+                ),
+                Err(e) => {
+                    error!(contract = %plan.contract_id, ?e, "Failed to queue contract repair")
+                }
+            }
+        }
+    }
+}
+
+/// Sends every chunk of `plan` to its new node; whether all were sent.
+async fn copy_chunks(
+    chunk_manager: &ChunkManager,
+    coordinator: &NetworkTransferCoordinator,
+    plan: &RepairPlan,
+) -> bool {
+    for chunk_id in &plan.chunks {
+        let Some(chunk) = chunk_manager.get_chunk(chunk_id) else {
+            error!(object = %plan.descriptor_hash, chunk = %chunk_id, "Chunk to repair not held");
+            return false;
+        };
+        let msg = NetworkTransferMessage::ChunkResponse {
+            chunk_id: chunk_id.clone(),
+            data: Some(chunk.data.clone()),
+            error: None,
+        };
+        if let Err(e) = coordinator.send_to_peer(&plan.new_node, msg).await {
+            error!(object = %plan.descriptor_hash, node = %plan.new_node, ?e, "Failed to send repair chunk");
+            return false;
+        }
+    }
+    true
+}
@@ -27,6 +27,25 @@ impl ReplicationManager {
             .max_by(|a, b| a.reliability_score.partial_cmp(&b.reliability_score).unwrap())
     }
 
+    /// Plan of (node_id,key) pairs replacing `failed`, a replica that lost
+    /// the data, so that `required_copies` healthy copies remain.
+    pub fn plan_repair(&self, key: &str, replicas: &[String], failed: &str, required_copies: u32) -> Vec<(String,String)> {
+        let healthy: Vec<String> = replicas.iter().filter(|r| *r != failed).cloned().collect();
+        let mut existing = healthy.clone();
+        existing.push(failed.to_string());
+        let mut plan = Vec::new();
+        while healthy.len() + plan.len() < (required_copies as usize).saturating_add(1) {
+            match self.select_target(&existing) {
+                Some(target) => {
+                    plan.push((target.node_id.clone(), key.to_string()));
+                    existing.push(target.node_id.clone());
+                }
+                None => break,
+            }
+        }
+        plan
+    }
+
     /// Produce plan of (node_id,key) pairs to reach `required_copies` (excluding original).
     pub fn plan_replication(&self, key: &str, replicas: &[String], required_copies: u32) -> Vec<(String,String)> {
         let mut plan = Vec::new();
@@ -34,7 +34,7 @@ impl Default for StorageConfig {
 }
 
 /// Consistency levels for distributed operations
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConsistencyLevel {
     /// Read/write from any single replica
     One,
@@ -44,6 +44,17 @@ pub enum ConsistencyLevel {
     All,
 }
 
+impl ConsistencyLevel {
+    /// Replicas that must agree when `replicas` hold the data.
+    pub fn required(self, replicas: usize) -> usize {
+        match self {
+            ConsistencyLevel::One => 1,
+            ConsistencyLevel::Quorum => replicas / 2 + 1,
+            ConsistencyLevel::All => replicas.max(1),
+        }
+    }
+}
+
 /// Storage entry metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageEntry {
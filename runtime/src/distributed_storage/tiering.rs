@@ -0,0 +1,323 @@
+//! Storage classes and lifecycle migration.
+//!
+//! Nodes serve one [`StorageClass`]: hot nodes keep recent data on fast
+//! disks close to its readers, cold ones keep old data cheaply. Each class
+//! has its own redundancy, expected latency and price. An object declares a
+//! [`LifecyclePolicy`], by default hot for 7 days, warm until 90 and cold
+//! after, and the [`MigrationEngine`] plans moving its chunks onto nodes of
+//! the class its age calls for. [`run_migrations`] carries the plans out.
+
+use super::allocation::{allocate_nodes, NodeMetrics, StoragePolicy};
+use crate::large_data_transfer::{
+    chunk::ChunkId,
+    manager::ChunkManager,
+    network::{coordinator::NetworkTransferCoordinator, models::NetworkTransferMessage},
+    pricing::{quote, PriceQuote},
+    redundancy::{ErasureScheme, RedundancyPolicy},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info};
+
+const DAY: u64 = 24 * 3600;
+
+/// How fast, safe and expensive storage is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum StorageClass {
+    Hot,
+    Warm,
+    Cold,
+}
+
+/// What storing data in one class means.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageClassConfig {
+    /// Copies besides the primary when not erasure coded.
+    pub copies: u8,
+    /// Erasure code objects with this scheme instead of replicating them.
+    pub erasure: Option<ErasureScheme>,
+    /// Time to first byte readers can expect.
+    pub expected_latency: Duration,
+    /// Whole BCAI per GiB stored, redundancy included.
+    pub price_per_gb_bcai: u128,
+}
+
+impl StorageClassConfig {
+    pub fn redundancy(&self) -> RedundancyPolicy {
+        RedundancyPolicy { copies: self.copies, geo_spread: true, erasure: self.erasure }
+    }
+
+    /// Price of storing `bytes` in this class.
+    pub fn quote(&self, bytes: u128) -> PriceQuote {
+        quote(bytes, self.redundancy(), self.price_per_gb_bcai)
+    }
+}
+
+/// The configuration of every class.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TierConfig {
+    pub hot: StorageClassConfig,
+    pub warm: StorageClassConfig,
+    pub cold: StorageClassConfig,
+}
+
+impl Default for TierConfig {
+    fn default() -> Self {
+        Self {
+            hot: StorageClassConfig {
+                copies: 2,
+                erasure: None,
+                expected_latency: Duration::from_millis(50),
+                price_per_gb_bcai: 10,
+            },
+            warm: StorageClassConfig {
+                copies: 1,
+                erasure: None,
+                expected_latency: Duration::from_millis(500),
+                price_per_gb_bcai: 4,
+            },
+            cold: StorageClassConfig {
+                copies: 0,
+                erasure: Some(ErasureScheme::new(4, 2)),
+                expected_latency: Duration::from_secs(60),
+                price_per_gb_bcai: 1,
+            },
+        }
+    }
+}
+
+impl TierConfig {
+    pub fn class(&self, class: StorageClass) -> &StorageClassConfig {
+        match class {
+            StorageClass::Hot => &self.hot,
+            StorageClass::Warm => &self.warm,
+            StorageClass::Cold => &self.cold,
+        }
+    }
+}
+
+/// The classes an object moves through as it ages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifecyclePolicy {
+    /// Class of a new object.
+    pub initial: StorageClass,
+    /// Age at which the object moves to a class, in order of age.
+    pub transitions: Vec<(Duration, StorageClass)>,
+}
+
+impl Default for LifecyclePolicy {
+    /// Hot for 7 days, warm until 90, then cold.
+    fn default() -> Self {
+        Self {
+            initial: StorageClass::Hot,
+            transitions: vec![
+                (Duration::from_secs(7 * DAY), StorageClass::Warm),
+                (Duration::from_secs(90 * DAY), StorageClass::Cold),
+            ],
+        }
+    }
+}
+
+impl LifecyclePolicy {
+    /// Kept in `class` for good.
+    pub fn pinned(class: StorageClass) -> Self {
+        Self { initial: class, transitions: Vec::new() }
+    }
+
+    /// The class an object of `age` belongs in.
+    pub fn class_at(&self, age: Duration) -> StorageClass {
+        self.transitions
+            .iter()
+            .take_while(|(after, _)| age >= *after)
+            .last()
+            .map_or(self.initial, |(_, class)| *class)
+    }
+}
+
+/// An object whose storage class is managed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TieredObject {
+    pub chunks: Vec<ChunkId>,
+    pub size_bytes: u64,
+    pub policy: LifecyclePolicy,
+    /// Unix time the object was stored.
+    pub created_at: u64,
+    pub class: StorageClass,
+    /// Nodes holding it.
+    pub nodes: Vec<String>,
+}
+
+/// Moving an object's chunks to another class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub object: String,
+    pub from: StorageClass,
+    pub to: StorageClass,
+    pub chunks: Vec<ChunkId>,
+    /// Nodes of the new class to hold the object, best first.
+    pub targets: Vec<String>,
+}
+
+/// Tracks objects and the tier of every node, and plans migrations.
+#[derive(Debug, Default)]
+pub struct MigrationEngine {
+    config: TierConfig,
+    policy: StoragePolicy,
+    node_tiers: HashMap<String, StorageClass>,
+    objects: BTreeMap<String, TieredObject>,
+}
+
+impl MigrationEngine {
+    pub fn new(config: TierConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Score nodes of a class by `policy` when choosing targets.
+    pub fn with_storage_policy(mut self, policy: StoragePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn config(&self) -> &TierConfig {
+        &self.config
+    }
+
+    /// Places `node_id` in `class`.
+    pub fn set_node_tier(&mut self, node_id: impl Into<String>, class: StorageClass) {
+        self.node_tiers.insert(node_id.into(), class);
+    }
+
+    pub fn node_tier(&self, node_id: &str) -> Option<StorageClass> {
+        self.node_tiers.get(node_id).copied()
+    }
+
+    /// Manages object `id`, stored on `nodes` at `created_at` in its policy's
+    /// initial class.
+    pub fn track(
+        &mut self,
+        id: impl Into<String>,
+        chunks: Vec<ChunkId>,
+        size_bytes: u64,
+        policy: LifecyclePolicy,
+        created_at: u64,
+        nodes: Vec<String>,
+    ) {
+        let class = policy.initial;
+        self.objects.insert(
+            id.into(),
+            TieredObject { chunks, size_bytes, policy, created_at, class, nodes },
+        );
+    }
+
+    pub fn untrack(&mut self, id: &str) -> Option<TieredObject> {
+        self.objects.remove(id)
+    }
+
+    pub fn object(&self, id: &str) -> Option<&TieredObject> {
+        self.objects.get(id)
+    }
+
+    /// Bytes tracked in each class.
+    pub fn usage(&self) -> BTreeMap<StorageClass, u64> {
+        let mut usage = BTreeMap::new();
+        for object in self.objects.values() {
+            *usage.entry(object.class).or_default() += object.size_bytes;
+        }
+        usage
+    }
+
+    /// Migrations due at unix time `now`, with targets chosen among the
+    /// nodes in `metrics` of the new class. Objects whose class has too few
+    /// eligible nodes wait for the next plan.
+    pub fn plan(&self, now: u64, metrics: &[NodeMetrics]) -> Vec<Migration> {
+        let mut migrations = Vec::new();
+        for (id, object) in &self.objects {
+            let age = Duration::from_secs(now.saturating_sub(object.created_at));
+            let to = object.policy.class_at(age);
+            if to == object.class {
+                continue;
+            }
+            let candidates: Vec<NodeMetrics> = metrics
+                .iter()
+                .filter(|m| self.node_tier(&m.node_id) == Some(to))
+                .cloned()
+                .collect();
+            let needed = self.config.class(to).redundancy().nodes_needed();
+            let targets = allocate_nodes(&self.policy, &candidates, (needed - 1) as u8);
+            if targets.len() < needed {
+                continue;
+            }
+            migrations.push(Migration {
+                object: id.clone(),
+                from: object.class,
+                to,
+                chunks: object.chunks.clone(),
+                targets,
+            });
+        }
+        migrations
+    }
+
+    /// Records `migration` as carried out.
+    pub fn complete(&mut self, migration: &Migration) {
+        if let Some(object) = self.objects.get_mut(&migration.object) {
+            object.class = migration.to;
+            object.nodes = migration.targets.clone();
+        }
+    }
+}
+
+/// Every `period`, plans the migrations due and pushes the chunks of each
+/// migrating object from `chunk_manager` to its target nodes, recording the
+/// migration once every target has every chunk.
+pub async fn run_migrations(
+    engine: Arc<Mutex<MigrationEngine>>,
+    chunk_manager: Arc<ChunkManager>,
+    coordinator: NetworkTransferCoordinator,
+    metrics: Vec<NodeMetrics>,
+    period: Duration,
+) {
+    let mut ticker = tokio::time::interval(period);
+    loop {
+        ticker.tick().await;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let migrations = engine.lock().unwrap().plan(now, &metrics);
+        for migration in migrations {
+            if migrate(&chunk_manager, &coordinator, &migration).await {
+                info!(object = %migration.object, from = ?migration.from, to = ?migration.to, "Object migrated");
+                engine.lock().unwrap().complete(&migration);
+            }
+        }
+    }
+}
+
+/// Sends every chunk of `migration` to every target; whether all arrived.
+async fn migrate(
+    chunk_manager: &ChunkManager,
+    coordinator: &NetworkTransferCoordinator,
+    migration: &Migration,
+) -> bool {
+    for chunk_id in &migration.chunks {
+        let Some(chunk) = chunk_manager.get_chunk(chunk_id) else {
+            error!(object = %migration.object, chunk = %chunk_id, "Chunk to migrate not held");
+            return false;
+        };
+        for node_id in &migration.targets {
+            let msg = NetworkTransferMessage::ChunkResponse {
+                chunk_id: chunk_id.clone(),
+                data: Some(chunk.data.clone()),
+                error: None,
+            };
+            if let Err(e) = coordinator.send_to_peer(node_id, msg).await {
+                error!(object = %migration.object, ?node_id, ?e, "Failed to send migrating chunk");
+                return false;
+            }
+        }
+    }
+    true
+}
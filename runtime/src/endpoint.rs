@@ -0,0 +1,105 @@
+//! On-chain records of inference endpoints and the nodes serving them.
+//!
+//! An endpoint is served by one primary node, with standbys on other nodes
+//! ready to take over. The record is registered by the endpoint's owner;
+//! when the primary fails, the owner or one of the standbys records the
+//! failover, which bumps the record's epoch so that racing or replayed
+//! failovers are rejected.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum EndpointError {
+    #[error("endpoint {0} already registered")]
+    AlreadyRegistered(String),
+    #[error("unknown endpoint {0}")]
+    Unknown(String),
+    #[error("endpoint record is malformed: {0}")]
+    Malformed(&'static str),
+    #[error("{0} may not change endpoint {1}")]
+    Unauthorised(String, String),
+    #[error("{node} does not serve endpoint {endpoint}")]
+    NotAReplica { endpoint: String, node: String },
+    #[error("failover epoch {got} does not follow {current}")]
+    StaleEpoch { current: u64, got: u64 },
+}
+
+/// Who serves an endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointRecord {
+    pub endpoint_id: String,
+    pub model_id: String,
+    /// Account that registered the endpoint.
+    pub owner: String,
+    pub primary: String,
+    pub standbys: Vec<String>,
+    /// Number of failovers so far.
+    pub epoch: u64,
+}
+
+impl EndpointRecord {
+    pub fn new(endpoint_id: &str, model_id: &str, owner: &str, primary: &str, standbys: Vec<String>) -> Self {
+        Self {
+            endpoint_id: endpoint_id.to_string(),
+            model_id: model_id.to_string(),
+            owner: owner.to_string(),
+            primary: primary.to_string(),
+            standbys,
+            epoch: 0,
+        }
+    }
+
+    /// Every node serving the endpoint, primary first.
+    pub fn replicas(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.primary).chain(&self.standbys)
+    }
+
+    /// A registration needs an id, at least one standby and no node listed
+    /// twice.
+    pub fn check_well_formed(&self) -> Result<(), EndpointError> {
+        if self.endpoint_id.is_empty() || self.primary.is_empty() {
+            return Err(EndpointError::Malformed("missing endpoint id or primary"));
+        }
+        if self.standbys.is_empty() {
+            return Err(EndpointError::Malformed("no standby replicas"));
+        }
+        let mut seen = HashSet::new();
+        if !self.replicas().all(|r| seen.insert(r)) {
+            return Err(EndpointError::Malformed("replica listed twice"));
+        }
+        if self.epoch != 0 {
+            return Err(EndpointError::Malformed("new records start at epoch 0"));
+        }
+        Ok(())
+    }
+
+    /// Check that `sender` may make `primary` the primary at `epoch`.
+    pub fn check_failover(&self, sender: &str, primary: &str, epoch: u64) -> Result<(), EndpointError> {
+        if sender != self.owner && !self.replicas().any(|r| r == sender) {
+            return Err(EndpointError::Unauthorised(sender.to_string(), self.endpoint_id.clone()));
+        }
+        if !self.standbys.iter().any(|s| s == primary) {
+            return Err(EndpointError::NotAReplica {
+                endpoint: self.endpoint_id.clone(),
+                node: primary.to_string(),
+            });
+        }
+        if epoch != self.epoch + 1 {
+            return Err(EndpointError::StaleEpoch { current: self.epoch, got: epoch });
+        }
+        Ok(())
+    }
+
+    /// Promote the standby `primary`; the old primary becomes the last
+    /// standby, so it can take over again once it recovers.
+    pub fn failover(&mut self, primary: &str, epoch: u64) {
+        if let Some(i) = self.standbys.iter().position(|s| s == primary) {
+            let promoted = self.standbys.remove(i);
+            let demoted = std::mem::replace(&mut self.primary, promoted);
+            self.standbys.push(demoted);
+            self.epoch = epoch;
+        }
+    }
+}
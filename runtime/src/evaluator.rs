@@ -1,11 +1,50 @@
+pub mod plugin;
+
+use crate::job::Job;
+use crate::pouw::evaluation::sign_evaluation;
+use crate::pouw::types::SignedEvaluation;
+use ed25519_dalek::SigningKey;
+use plugin::{PluginError, PluginRegistry, DEFAULT_GAS_LIMIT};
+use std::sync::{Arc, RwLock};
+
 #[derive(Debug, Clone)]
 pub struct Evaluator {
     node_id: String,
+    /// Evaluator plugins fetched for the jobs this node scores.
+    plugins: Arc<RwLock<PluginRegistry>>,
+    /// Gas each plugin evaluation may burn.
+    pub gas_limit: u64,
 }
 
 impl Evaluator {
     pub fn new(node_id: &str) -> Self {
-        Self { node_id: node_id.to_string() }
+        Self {
+            node_id: node_id.to_string(),
+            plugins: Arc::default(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+        }
+    }
+
+    /// Installs a job's evaluator plugin, returning its hash.
+    pub fn install_plugin(&self, wasm: &[u8]) -> Result<String, PluginError> {
+        self.plugins.write().expect("plugin registry poisoned").install(wasm)
+    }
+
+    /// Scores `outputs` submitted for `job` against the poster's `expected`
+    /// data with the job's plugin, and signs the score as the job's
+    /// evaluation. The plugin must have been installed.
+    pub fn evaluate_outputs(
+        &self,
+        job: &Job,
+        outputs: &[u8],
+        expected: &[u8],
+        key: &SigningKey,
+    ) -> Result<SignedEvaluation, PluginError> {
+        let hash = job.evaluator.as_deref().ok_or(PluginError::NoPlugin)?;
+        let plugin = self.plugins.read().expect("plugin registry poisoned").get(hash)?;
+        let score = plugin.score(outputs, expected, self.gas_limit)?;
+        tracing::debug!(node = %self.node_id, job = job.id, score = score.score_bps, gas = score.gas_used, "plugin evaluation");
+        Ok(sign_evaluation(&job.id.to_string(), score.score_bps, key))
     }
 
     /// Evaluates a training result by verifying the embedded PoUW solution.
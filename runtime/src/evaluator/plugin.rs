@@ -0,0 +1,199 @@
+//! Job-supplied WebAssembly scoring functions.
+//!
+//! A job poster can attach an evaluator plugin to a job so that outputs of
+//! a new kind of task are scored without a runtime release. The job commits
+//! to the plugin by its [`plugin_hash`]; evaluators fetch the module, install
+//! it in a [`PluginRegistry`] and run it in a sandbox: the module may not
+//! import anything, its memory is capped at [`MAX_MEMORY_BYTES`] and every
+//! call is metered against a gas limit.
+//!
+//! A plugin exports its `memory` and two functions:
+//!
+//! - `alloc(len: i32) -> i32` returns a pointer to `len` writable bytes;
+//! - `evaluate(out_ptr: i32, out_len: i32, exp_ptr: i32, exp_len: i32) -> i32`
+//!   scores the submitted outputs against the poster's expected data and
+//!   returns a score in basis points, `0..=10_000`.
+//!
+//! Execution needs the `wasm-evaluators` feature; without it plugins fail
+//! to load with [`PluginError::Unsupported`].
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Gas, in interpreter fuel units, a plugin may burn per evaluation unless
+/// the evaluator sets another limit.
+pub const DEFAULT_GAS_LIMIT: u64 = 50_000_000;
+/// Largest linear memory a plugin may grow to.
+pub const MAX_MEMORY_BYTES: usize = 16 << 20;
+/// Highest score a plugin may return, i.e. a perfect result.
+pub const MAX_SCORE_BPS: u32 = 10_000;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PluginError {
+    #[error("node built without the wasm-evaluators feature")]
+    Unsupported,
+    #[error("invalid module: {0}")]
+    Invalid(String),
+    #[error("plugins may not import host functions, found {0}")]
+    ForbiddenImport(String),
+    #[error("missing export {0}")]
+    MissingExport(&'static str),
+    #[error("no plugin installed with hash {0}")]
+    NotFound(String),
+    #[error("job has no evaluator plugin")]
+    NoPlugin,
+    #[error("ran out of gas after {0} units")]
+    OutOfGas(u64),
+    #[error("plugin trapped: {0}")]
+    Trap(String),
+    #[error("score {0} is outside 0..=10000")]
+    InvalidScore(i32),
+}
+
+/// Hex SHA-256 of a plugin module, the id a job commits to.
+pub fn plugin_hash(wasm: &[u8]) -> String {
+    hex::encode(Sha256::digest(wasm))
+}
+
+/// Result of one sandboxed evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginScore {
+    pub score_bps: u32,
+    pub gas_used: u64,
+}
+
+/// A validated plugin module.
+pub struct EvaluatorPlugin {
+    hash: String,
+    #[cfg(feature = "wasm-evaluators")]
+    engine: wasmi::Engine,
+    #[cfg(feature = "wasm-evaluators")]
+    module: wasmi::Module,
+}
+
+impl std::fmt::Debug for EvaluatorPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvaluatorPlugin").field("hash", &self.hash).finish()
+    }
+}
+
+impl EvaluatorPlugin {
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    #[cfg(not(feature = "wasm-evaluators"))]
+    pub fn load(_wasm: &[u8]) -> Result<Self, PluginError> {
+        Err(PluginError::Unsupported)
+    }
+
+    #[cfg(not(feature = "wasm-evaluators"))]
+    pub fn score(&self, _outputs: &[u8], _expected: &[u8], _gas_limit: u64) -> Result<PluginScore, PluginError> {
+        Err(PluginError::Unsupported)
+    }
+
+    /// Compiles `wasm` and checks that it follows the plugin ABI.
+    #[cfg(feature = "wasm-evaluators")]
+    pub fn load(wasm: &[u8]) -> Result<Self, PluginError> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        let module = wasmi::Module::new(&engine, wasm).map_err(|e| PluginError::Invalid(e.to_string()))?;
+        if let Some(import) = module.imports().next() {
+            return Err(PluginError::ForbiddenImport(format!("{}::{}", import.module(), import.name())));
+        }
+        for export in ["memory", "alloc", "evaluate"] {
+            if !module.exports().any(|e| e.name() == export) {
+                return Err(PluginError::MissingExport(export));
+            }
+        }
+        Ok(Self { hash: plugin_hash(wasm), engine, module })
+    }
+
+    /// Runs `evaluate` over `outputs` and `expected` in a fresh instance
+    /// with `gas_limit` fuel.
+    #[cfg(feature = "wasm-evaluators")]
+    pub fn score(&self, outputs: &[u8], expected: &[u8], gas_limit: u64) -> Result<PluginScore, PluginError> {
+        use wasmi::{Linker, Store, StoreLimits, StoreLimitsBuilder};
+
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.add_fuel(gas_limit).map_err(|e| PluginError::Trap(e.to_string()))?;
+
+        let result = (|| {
+            let instance = Linker::<StoreLimits>::new(&self.engine)
+                .instantiate(&mut store, &self.module)?
+                .start(&mut store)?;
+            let memory = instance.get_memory(&store, "memory").ok_or(PluginError::MissingExport("memory"))?;
+            let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+            let evaluate = instance.get_typed_func::<(i32, i32, i32, i32), i32>(&store, "evaluate")?;
+
+            let write = |store: &mut Store<StoreLimits>, bytes: &[u8]| -> Result<(i32, i32), PluginError> {
+                let len = i32::try_from(bytes.len()).map_err(|_| PluginError::Trap("input too large".into()))?;
+                let ptr = alloc.call(&mut *store, len)?;
+                memory
+                    .write(&mut *store, ptr as u32 as usize, bytes)
+                    .map_err(|e| PluginError::Trap(e.to_string()))?;
+                Ok((ptr, len))
+            };
+            let (out_ptr, out_len) = write(&mut store, outputs)?;
+            let (exp_ptr, exp_len) = write(&mut store, expected)?;
+            Ok::<_, PluginError>(evaluate.call(&mut store, (out_ptr, out_len, exp_ptr, exp_len))?)
+        })();
+
+        let gas_used = store.fuel_consumed().unwrap_or(0);
+        let score = match result {
+            Ok(score) => score,
+            Err(PluginError::OutOfGas(_)) => return Err(PluginError::OutOfGas(gas_limit)),
+            Err(e) => return Err(e),
+        };
+        match u32::try_from(score) {
+            Ok(score_bps) if score_bps <= MAX_SCORE_BPS => Ok(PluginScore { score_bps, gas_used }),
+            _ => Err(PluginError::InvalidScore(score)),
+        }
+    }
+}
+
+#[cfg(feature = "wasm-evaluators")]
+impl From<wasmi::Error> for PluginError {
+    fn from(e: wasmi::Error) -> Self {
+        match e.as_trap_code() {
+            // The limit is filled in by `EvaluatorPlugin::score`.
+            Some(wasmi::core::TrapCode::OutOfFuel) => PluginError::OutOfGas(0),
+            _ => PluginError::Trap(e.to_string()),
+        }
+    }
+}
+
+/// Plugins an evaluator has installed, by hash.
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+    plugins: HashMap<String, Arc<EvaluatorPlugin>>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads and installs `wasm`, returning its hash. Installing the same
+    /// module twice is a no-op.
+    pub fn install(&mut self, wasm: &[u8]) -> Result<String, PluginError> {
+        let hash = plugin_hash(wasm);
+        if !self.plugins.contains_key(&hash) {
+            self.plugins.insert(hash.clone(), Arc::new(EvaluatorPlugin::load(wasm)?));
+        }
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &str) -> Result<Arc<EvaluatorPlugin>, PluginError> {
+        self.plugins.get(hash).cloned().ok_or_else(|| PluginError::NotFound(hash.to_string()))
+    }
+
+    pub fn remove(&mut self, hash: &str) -> bool {
+        self.plugins.remove(hash).is_some()
+    }
+}
@@ -0,0 +1,202 @@
+//! Canary-based poisoning detection for federated rounds.
+//!
+//! Before a round starts the coordinator hands every participant a small,
+//! private set of canary samples with assigned labels to mix into its local
+//! training data. An honest participant's update learns its canaries; an
+//! update trained on poisoned or substituted data does not. After the round
+//! each update, and the aggregated model, is scored on the canaries:
+//! participants that miss theirs are flagged for rejection, and a sharp drop
+//! in the aggregate's canary accuracy marks the whole round as suspect.
+
+use crate::data::Sample;
+use crate::federated::learning::FederatedParticipant;
+use crate::federated::model::{FederatedError, ModelParameters};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// A probe input with the label a clean model is expected to predict.
+pub type CanarySample = Sample;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CanaryConfig {
+    pub canaries_per_participant: usize,
+    /// Participants scoring below this on their own canaries are flagged.
+    pub min_participant_accuracy: f32,
+    /// Largest tolerated fall in global canary accuracy from the previous
+    /// round before the round is treated as poisoned.
+    pub max_global_drop: f32,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self { canaries_per_participant: 16, min_participant_accuracy: 0.75, max_global_drop: 0.2 }
+    }
+}
+
+/// Canaries issued for one round, keyed by participant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryRound {
+    pub round: u32,
+    pub config: CanaryConfig,
+    assignments: HashMap<String, Vec<CanarySample>>,
+}
+
+impl CanaryRound {
+    /// Derives each participant's canaries from the coordinator's secret
+    /// `seed`, the round number and the node id.
+    pub fn inject(
+        round: u32,
+        seed: [u8; 32],
+        participants: &[String],
+        input_dim: usize,
+        num_classes: usize,
+        config: CanaryConfig,
+    ) -> Self {
+        let assignments = participants
+            .iter()
+            .map(|node_id| {
+                let mut h = Sha256::new();
+                h.update(seed);
+                h.update(round.to_le_bytes());
+                h.update(node_id.as_bytes());
+                let mut rng = StdRng::from_seed(h.finalize().into());
+                let canaries = (0..config.canaries_per_participant)
+                    .map(|_| CanarySample {
+                        features: (0..input_dim).map(|_| rng.gen_range(-1.0..1.0)).collect(),
+                        label: rng.gen_range(0..num_classes),
+                    })
+                    .collect();
+                (node_id.clone(), canaries)
+            })
+            .collect();
+        Self { round, config, assignments }
+    }
+
+    /// Canaries the participant must include in its local training data.
+    pub fn canaries_for(&self, node_id: &str) -> Option<&[CanarySample]> {
+        self.assignments.get(node_id).map(Vec::as_slice)
+    }
+
+    /// Appends the participant's canaries to its local training data.
+    /// Returns how many were added.
+    pub fn mix_into(&self, node_id: &str, local: &mut Vec<Sample>) -> usize {
+        let canaries = self.canaries_for(node_id).unwrap_or_default();
+        local.extend_from_slice(canaries);
+        canaries.len()
+    }
+
+    /// Scores every submitted update and the aggregated model. `previous`
+    /// is the aggregate's canary accuracy from the last clean round.
+    pub fn inspect(
+        &self,
+        updates: &HashMap<String, ModelParameters>,
+        aggregated: &ModelParameters,
+        previous: Option<f32>,
+    ) -> Result<CanaryReport, FederatedError> {
+        let mut participant_accuracy = HashMap::new();
+        let mut flagged = Vec::new();
+        for (node_id, canaries) in &self.assignments {
+            let accuracy = match updates.get(node_id) {
+                Some(update) => canary_accuracy(update, canaries)?,
+                // Not submitting is not poisoning; the timeout path handles it.
+                None => continue,
+            };
+            if accuracy < self.config.min_participant_accuracy {
+                flagged.push(node_id.clone());
+            }
+            participant_accuracy.insert(node_id.clone(), accuracy);
+        }
+        flagged.sort();
+
+        let all: Vec<CanarySample> = self.assignments.values().flatten().cloned().collect();
+        let global_accuracy = canary_accuracy(aggregated, &all)?;
+        let round_poisoned = previous.is_some_and(|p| p - global_accuracy > self.config.max_global_drop);
+        Ok(CanaryReport { round: self.round, global_accuracy, participant_accuracy, flagged, round_poisoned })
+    }
+}
+
+/// Outcome of inspecting one round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CanaryReport {
+    pub round: u32,
+    pub global_accuracy: f32,
+    pub participant_accuracy: HashMap<String, f32>,
+    /// Participants whose updates should be excluded from aggregation.
+    pub flagged: Vec<String>,
+    /// The aggregate regressed on canaries; discard the round's model.
+    pub round_poisoned: bool,
+}
+
+impl CanaryReport {
+    /// Drops flagged participants' updates before (re-)aggregation.
+    pub fn reject_flagged(&self, updates: &mut HashMap<String, ModelParameters>) -> usize {
+        let before = updates.len();
+        updates.retain(|node_id, _| !self.flagged.contains(node_id));
+        before - updates.len()
+    }
+
+    /// Lowers the reputation of flagged participants, which also reduces their
+    /// weight under `AggregationStrategy::ReputationWeighted`.
+    pub fn apply_penalties(&self, participants: &mut [FederatedParticipant], penalty: i32) {
+        for p in participants.iter_mut().filter(|p| self.flagged.contains(&p.node_id)) {
+            p.reputation = p.reputation.saturating_sub(penalty);
+        }
+    }
+}
+
+/// Fraction of canaries the model labels correctly.
+pub fn canary_accuracy(model: &ModelParameters, canaries: &[CanarySample]) -> Result<f32, FederatedError> {
+    if canaries.is_empty() {
+        return Ok(1.0);
+    }
+    let mut correct = 0;
+    for canary in canaries {
+        if predict(model, &canary.features)? == canary.label {
+            correct += 1;
+        }
+    }
+    Ok(correct as f32 / canaries.len() as f32)
+}
+
+/// Forward pass of a dense network: `weights` holds each layer's
+/// `out x in` matrix row-major, `biases` each layer's `out` vector; hidden
+/// layers use ReLU and the output is the argmax.
+pub fn predict(model: &ModelParameters, input: &[f32]) -> Result<usize, FederatedError> {
+    let sizes = &model.layer_sizes;
+    if sizes.len() < 2 || sizes[0] != input.len() {
+        return Err(FederatedError::ModelDimensionMismatch {
+            expected: sizes.first().copied().unwrap_or(0),
+            actual: input.len(),
+        });
+    }
+    let weight_count: usize = sizes.windows(2).map(|w| w[0] * w[1]).sum();
+    let bias_count: usize = sizes[1..].iter().sum();
+    if model.weights.len() != weight_count || model.biases.len() != bias_count {
+        return Err(FederatedError::InvalidModelParameters);
+    }
+
+    let mut activations = input.to_vec();
+    let (mut w_off, mut b_off) = (0, 0);
+    for (layer, pair) in sizes.windows(2).enumerate() {
+        let (inputs, outputs) = (pair[0], pair[1]);
+        let last = layer == sizes.len() - 2;
+        activations = (0..outputs)
+            .map(|o| {
+                let row = &model.weights[w_off + o * inputs..w_off + (o + 1) * inputs];
+                let z = row.iter().zip(&activations).map(|(w, a)| w * a).sum::<f32>() + model.biases[b_off + o];
+                if last { z } else { z.max(0.0) }
+            })
+            .collect();
+        w_off += inputs * outputs;
+        b_off += outputs;
+    }
+    Ok(activations
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .unwrap_or(0))
+}
@@ -1,8 +1,15 @@
 pub mod learning;
 pub mod aggregation;
 pub mod model;
+pub mod split;
+pub mod canary;
 
 // Re-export commonly used types
+pub use canary::{CanaryConfig, CanaryReport, CanaryRound, CanarySample};
 pub use aggregation::{AggregationStrategy, FederatedConfig};
 pub use learning::{FederatedParticipant, FederatedRound, FederatedStats};
-pub use model::{FederatedError, ModelMetadata, ModelParameters}; 
\ No newline at end of file
+pub use model::{FederatedError, ModelMetadata, ModelParameters};
+pub use split::{
+    SplitLearningPlan, SplitLearningSession, SplitMessage, SplitPayload, SplitPoint, SplitRole,
+    SplitStage,
+}; 
\ No newline at end of file
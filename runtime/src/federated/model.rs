@@ -16,6 +16,8 @@ pub enum FederatedError {
     InvalidModelParameters,
     #[error("Timeout waiting for participants")]
     ParticipantTimeout,
+    #[error("Split learning protocol error: {0}")]
+    SplitProtocol(String),
 }
 
 /// Model parameters for federated learning
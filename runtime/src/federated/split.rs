@@ -0,0 +1,246 @@
+//! Split learning support.
+//!
+//! The model is cut at a layer boundary: data-holder nodes run the early layers
+//! on their private data and ship only the activations at the cut, compute nodes
+//! run the remaining layers and send back the gradients at the cut. Raw training
+//! data never leaves the data holder. Every exchanged payload is sealed with
+//! AES-256-GCM using the job's channel key before it is handed to the P2P layer.
+
+use crate::federated::FederatedError;
+use crate::large_data_transfer::crypto::CryptoUtils;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Role a participant plays in a split-learning job.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SplitRole {
+    /// Holds the private data and runs the layers before the cut.
+    DataHolder,
+    /// Runs the layers after the cut, including the loss.
+    Compute,
+}
+
+/// Where the model is partitioned.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SplitPoint {
+    /// Width of every layer, input layer first.
+    pub layer_sizes: Vec<usize>,
+    /// Index of the layer whose output is exchanged. Layers `0..=cut_layer`
+    /// run on the data holder.
+    pub cut_layer: usize,
+}
+
+impl SplitPoint {
+    /// Creates a split point; the cut must leave at least one layer on each side.
+    pub fn new(layer_sizes: Vec<usize>, cut_layer: usize) -> Result<Self, FederatedError> {
+        if layer_sizes.len() < 2 || cut_layer == 0 || cut_layer >= layer_sizes.len() - 1 {
+            return Err(FederatedError::SplitProtocol(format!(
+                "cut layer {} invalid for a {}-layer model",
+                cut_layer,
+                layer_sizes.len()
+            )));
+        }
+        Ok(Self { layer_sizes, cut_layer })
+    }
+
+    /// Number of activation values per sample crossing the cut.
+    pub fn activation_width(&self) -> usize {
+        self.layer_sizes[self.cut_layer]
+    }
+
+    /// Layer sizes executed by the data holder.
+    pub fn head_layers(&self) -> &[usize] {
+        &self.layer_sizes[..=self.cut_layer]
+    }
+
+    /// Layer sizes executed by the compute node.
+    pub fn tail_layers(&self) -> &[usize] {
+        &self.layer_sizes[self.cut_layer..]
+    }
+}
+
+/// Assignment of data holders to compute nodes for a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitLearningPlan {
+    pub job_id: u64,
+    pub split: SplitPoint,
+    /// Data holder node id -> compute node id serving it.
+    pub assignments: HashMap<String, String>,
+}
+
+impl SplitLearningPlan {
+    /// Spreads data holders across compute nodes round-robin.
+    pub fn assign(
+        job_id: u64,
+        split: SplitPoint,
+        data_holders: &[String],
+        compute_nodes: &[String],
+    ) -> Result<Self, FederatedError> {
+        if data_holders.is_empty() || compute_nodes.is_empty() {
+            return Err(FederatedError::InsufficientParticipants {
+                required: 2,
+                available: data_holders.len() + compute_nodes.len(),
+            });
+        }
+        let assignments = data_holders
+            .iter()
+            .enumerate()
+            .map(|(i, holder)| (holder.clone(), compute_nodes[i % compute_nodes.len()].clone()))
+            .collect();
+        Ok(Self { job_id, split, assignments })
+    }
+
+    /// Compute node paired with the given data holder.
+    pub fn compute_node_for(&self, data_holder: &str) -> Option<&String> {
+        self.assignments.get(data_holder)
+    }
+
+    /// Role of `node_id` in this plan, if it participates.
+    pub fn role_of(&self, node_id: &str) -> Option<SplitRole> {
+        if self.assignments.contains_key(node_id) {
+            Some(SplitRole::DataHolder)
+        } else if self.assignments.values().any(|c| c == node_id) {
+            Some(SplitRole::Compute)
+        } else {
+            None
+        }
+    }
+}
+
+/// Tensor crossing the cut, before encryption.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SplitPayload {
+    /// Forward pass output of the cut layer, `batch_size * width` values.
+    Activations { batch_size: usize, values: Vec<f32> },
+    /// Loss gradient w.r.t. the cut-layer activations, same shape.
+    Gradients { batch_size: usize, values: Vec<f32> },
+}
+
+impl SplitPayload {
+    fn shape(&self) -> (usize, usize) {
+        match self {
+            SplitPayload::Activations { batch_size, values }
+            | SplitPayload::Gradients { batch_size, values } => (*batch_size, values.len()),
+        }
+    }
+}
+
+/// Encrypted envelope exchanged over the P2P channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitMessage {
+    pub job_id: u64,
+    pub step: u64,
+    pub from: String,
+    pub to: String,
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+impl SplitMessage {
+    /// Serializes and encrypts `payload` with the job channel key.
+    pub fn seal(
+        job_id: u64,
+        step: u64,
+        from: String,
+        to: String,
+        payload: &SplitPayload,
+        key: &[u8; 32],
+    ) -> Result<Self, FederatedError> {
+        let nonce: [u8; 12] = rand::random();
+        let plain = bincode::serialize(payload)
+            .map_err(|e| FederatedError::SplitProtocol(e.to_string()))?;
+        let ciphertext = CryptoUtils::encrypt(&plain, key, &nonce)
+            .map_err(|e| FederatedError::SplitProtocol(e.to_string()))?;
+        Ok(Self { job_id, step, from, to, nonce, ciphertext })
+    }
+
+    /// Decrypts and deserializes the payload.
+    pub fn open(&self, key: &[u8; 32]) -> Result<SplitPayload, FederatedError> {
+        let plain = CryptoUtils::decrypt(&self.ciphertext, key, &self.nonce)
+            .map_err(|e| FederatedError::SplitProtocol(e.to_string()))?;
+        bincode::deserialize(&plain).map_err(|e| FederatedError::SplitProtocol(e.to_string()))
+    }
+}
+
+/// Where a data holder is in its forward/backward cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitStage {
+    /// Compute node is waiting for the next batch of activations.
+    AwaitingActivations,
+    /// Data holder is waiting for gradients of the batch it sent.
+    AwaitingGradients,
+}
+
+/// Coordinator-side bookkeeping that enforces the activation/gradient
+/// ping-pong for every data holder in a plan.
+#[derive(Debug, Clone)]
+pub struct SplitLearningSession {
+    pub plan: SplitLearningPlan,
+    channel_key: [u8; 32],
+    progress: HashMap<String, (u64, SplitStage)>,
+}
+
+impl SplitLearningSession {
+    pub fn new(plan: SplitLearningPlan, channel_key: [u8; 32]) -> Self {
+        let progress = plan
+            .assignments
+            .keys()
+            .map(|h| (h.clone(), (0, SplitStage::AwaitingActivations)))
+            .collect();
+        Self { plan, channel_key, progress }
+    }
+
+    /// Current step and stage for a data holder.
+    pub fn progress(&self, data_holder: &str) -> Option<(u64, SplitStage)> {
+        self.progress.get(data_holder).copied()
+    }
+
+    /// Validates a sealed message against the plan and advances the protocol.
+    /// Returns the decrypted payload so it can be forwarded to the recipient.
+    pub fn accept(&mut self, msg: &SplitMessage) -> Result<SplitPayload, FederatedError> {
+        if msg.job_id != self.plan.job_id {
+            return Err(FederatedError::SplitProtocol(format!("message for job {}", msg.job_id)));
+        }
+        let payload = msg.open(&self.channel_key)?;
+        let (batch_size, len) = payload.shape();
+        let width = self.plan.split.activation_width();
+        let expected = batch_size.checked_mul(width).ok_or_else(|| {
+            FederatedError::SplitProtocol(format!("batch of {} samples is too large", batch_size))
+        })?;
+        if batch_size == 0 || len != expected {
+            return Err(FederatedError::ModelDimensionMismatch { expected, actual: len });
+        }
+
+        let holder = match payload {
+            SplitPayload::Activations { .. } => &msg.from,
+            SplitPayload::Gradients { .. } => &msg.to,
+        };
+        let peer = match payload {
+            SplitPayload::Activations { .. } => &msg.to,
+            SplitPayload::Gradients { .. } => &msg.from,
+        };
+        if self.plan.compute_node_for(holder) != Some(peer) {
+            return Err(FederatedError::SplitProtocol(format!(
+                "{} is not paired with {}",
+                holder, peer
+            )));
+        }
+
+        let entry = self.progress.get_mut(holder).expect("holder present in plan");
+        match (&payload, entry.1) {
+            (SplitPayload::Activations { .. }, SplitStage::AwaitingActivations) if msg.step == entry.0 => {
+                entry.1 = SplitStage::AwaitingGradients;
+            }
+            (SplitPayload::Gradients { .. }, SplitStage::AwaitingGradients) if msg.step == entry.0 => {
+                *entry = (entry.0 + 1, SplitStage::AwaitingActivations);
+            }
+            _ => {
+                return Err(FederatedError::SplitProtocol(format!(
+                    "out-of-order message at step {} for {}",
+                    msg.step, holder
+                )))
+            }
+        }
+        Ok(payload)
+    }
+}
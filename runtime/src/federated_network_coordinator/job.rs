@@ -12,6 +12,9 @@ pub struct FederatedTrainingJob {
     pub data_descriptor: LargeDataDescriptor,
     pub model_architecture: ModelArchitecture,
     pub training_config: FederatedTrainingConfig,
+    /// How the model is trained across participants.
+    #[serde(default)]
+    pub mode: TrainingMode,
     pub current_round: u32,
     pub status: FederatedJobStatus,
     pub total_reward: u64,
@@ -42,6 +45,17 @@ pub struct ModelArchitecture {
     pub parameter_count: u64,
 }
 
+/// Training topology for a job.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum TrainingMode {
+    /// Every participant trains the full model and updates are aggregated.
+    #[default]
+    Federated,
+    /// The model is cut after `cut_layer`; data holders run the head and
+    /// compute nodes run the tail.
+    Split { cut_layer: usize },
+}
+
 /// Federated training configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FederatedTrainingConfig {
@@ -1,6 +1,7 @@
 pub mod error;
 pub mod config;
 pub mod job;
+pub mod split;
 
 
 // Re-export commonly used types
@@ -8,5 +9,6 @@ pub use config::FederatedNetworkConfig;
 pub use error::FederatedNetworkError;
 pub use job::{
     FederatedJobStatus, FederatedTrainingConfig, FederatedTrainingJob, ModelArchitecture,
-    ParticipantInfo, ParticipantStatus,
-}; 
\ No newline at end of file
+    ParticipantInfo, ParticipantStatus, TrainingMode,
+};
+pub use split::plan_split_job; 
\ No newline at end of file
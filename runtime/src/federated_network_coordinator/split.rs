@@ -0,0 +1,47 @@
+use crate::federated::{SplitLearningPlan, SplitPoint};
+use crate::federated_network_coordinator::{
+    FederatedNetworkError, FederatedTrainingJob, TrainingMode,
+};
+
+/// Builds the split-learning plan for a job in `TrainingMode::Split`.
+///
+/// Participants holding data shards become data holders; participants with
+/// GPUs and no shards become compute nodes.
+pub fn plan_split_job(job: &FederatedTrainingJob) -> Result<SplitLearningPlan, FederatedNetworkError> {
+    let cut_layer = match job.mode {
+        TrainingMode::Split { cut_layer } => cut_layer,
+        TrainingMode::Federated => {
+            return Err(FederatedNetworkError::Network(format!(
+                "job {} is not a split-learning job",
+                job.job_id
+            )))
+        }
+    };
+
+    // Transformer blocks keep the hidden width constant, so the embedding
+    // output and every block output share `hidden_size`.
+    let arch = &job.model_architecture;
+    let layer_sizes = vec![arch.hidden_size as usize; arch.num_layers as usize + 1];
+    let split = SplitPoint::new(layer_sizes, cut_layer)?;
+
+    let mut data_holders: Vec<String> = Vec::new();
+    let mut compute_nodes: Vec<String> = Vec::new();
+    for (id, info) in &job.participants {
+        if !info.assigned_shards.is_empty() {
+            data_holders.push(id.clone());
+        } else if info.capability.gpus > 0 {
+            compute_nodes.push(id.clone());
+        }
+    }
+    data_holders.sort();
+    compute_nodes.sort();
+
+    if data_holders.is_empty() || compute_nodes.is_empty() {
+        return Err(FederatedNetworkError::InsufficientParticipants {
+            job_id: job.job_id,
+            required: 2,
+            available: data_holders.len() + compute_nodes.len(),
+        });
+    }
+    Ok(SplitLearningPlan::assign(job.job_id, split, &data_holders, &compute_nodes)?)
+}
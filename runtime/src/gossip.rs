@@ -0,0 +1,201 @@
+//! Typed gossip topics and the validators that guard them.
+//!
+//! Gossip used to share a single `bcai_global` topic, so every node received
+//! (and forwarded) every kind of message. Each kind now has its own topic, and
+//! a message is checked against its topic's validator before gossipsub passes
+//! it on: a rejected message stops at the first honest node and counts
+//! against the peer that sent it.
+
+use prost::Message;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::blockchain::block::Block;
+use crate::blockchain::transaction::{StorageTx, Transaction};
+use crate::blockchain::validation::validate_transaction_stateless;
+use crate::large_data_transfer::network::PayloadDescriptor;
+use crate::network::NetworkMessage;
+use crate::pouw::evaluation::verify_evaluation;
+use crate::wire::{self, proto};
+
+/// The topic everything was gossiped on before topics were split. Nodes no
+/// longer subscribe to it.
+pub const LEGACY_TOPIC: &str = "bcai_global";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GossipTopic {
+    Blocks,
+    Transactions,
+    /// Job postings, volunteers and completions.
+    Jobs,
+    /// Signed PoUW evaluations.
+    Evaluations,
+    /// Node metrics updates from the metrics oracle.
+    Metrics,
+}
+
+impl GossipTopic {
+    pub const ALL: [GossipTopic; 5] = [
+        GossipTopic::Blocks,
+        GossipTopic::Transactions,
+        GossipTopic::Jobs,
+        GossipTopic::Evaluations,
+        GossipTopic::Metrics,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GossipTopic::Blocks => "bcai/blocks/1",
+            GossipTopic::Transactions => "bcai/transactions/1",
+            GossipTopic::Jobs => "bcai/jobs/1",
+            GossipTopic::Evaluations => "bcai/evaluations/1",
+            GossipTopic::Metrics => "bcai/metrics/1",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Largest message accepted on the topic. Bodies above this are
+    /// announced and fetched on demand instead.
+    pub fn max_size(self) -> usize {
+        match self {
+            GossipTopic::Blocks => 4 * 1024 * 1024,
+            GossipTopic::Transactions | GossipTopic::Evaluations => 64 * 1024,
+            GossipTopic::Jobs | GossipTopic::Metrics => 256 * 1024,
+        }
+    }
+
+    /// The topic a transaction is gossiped on.
+    pub fn for_transaction(tx: &Transaction) -> Self {
+        match tx.storage {
+            Some(StorageTx::UpdateMetrics { .. }) => GossipTopic::Metrics,
+            _ => GossipTopic::Transactions,
+        }
+    }
+}
+
+impl std::fmt::Display for GossipTopic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum GossipError {
+    #[error("{topic} message of {size} bytes exceeds {max}")]
+    TooLarge { topic: GossipTopic, size: usize, max: usize },
+    #[error("malformed {topic} message: {reason}")]
+    Malformed { topic: GossipTopic, reason: String },
+    #[error("{kind} does not belong on {topic}")]
+    WrongTopic { topic: GossipTopic, kind: &'static str },
+    #[error("invalid {topic} message: {reason}")]
+    Invalid { topic: GossipTopic, reason: String },
+}
+
+/// The envelopes blocks and transactions are gossiped in; the matching
+/// variants of the p2p service's `WireMessage`.
+#[derive(Deserialize)]
+enum Envelope {
+    Block(Block),
+    Transaction(Transaction),
+    Announce(PayloadDescriptor),
+}
+
+impl Envelope {
+    /// Read a protobuf wire envelope, or the JSON nodes gossiped before
+    /// [`wire::PROTOCOL_VERSION`] 2.
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.first() == Some(&b'{') {
+            return serde_json::from_slice(data).map_err(|e| e.to_string());
+        }
+        let (&version, body) = data.split_first().ok_or("empty message")?;
+        if version != wire::PROTOCOL_VERSION {
+            return Err(wire::WireError::UnsupportedVersion(version).to_string());
+        }
+        let envelope = proto::Envelope::decode(body).map_err(|e| e.to_string())?;
+        let malformed = |e: wire::WireError| e.to_string();
+        match envelope.body {
+            Some(proto::Body::Block(block)) => Ok(Envelope::Block(block.try_into().map_err(malformed)?)),
+            Some(proto::Body::Transaction(tx)) => Ok(Envelope::Transaction(tx.try_into().map_err(malformed)?)),
+            Some(proto::Body::Announce(descriptor)) => Ok(Envelope::Announce(descriptor.into())),
+            _ => Err("not a gossip message".into()),
+        }
+    }
+}
+
+/// Check `data` is a well-formed, correctly signed message of `topic`'s kind.
+/// Only what can be checked without chain state is checked; a transaction
+/// may still fail against the mempool.
+pub fn validate(topic: GossipTopic, data: &[u8]) -> Result<(), GossipError> {
+    if data.len() > topic.max_size() {
+        return Err(GossipError::TooLarge { topic, size: data.len(), max: topic.max_size() });
+    }
+    let invalid = |reason: String| GossipError::Invalid { topic, reason };
+    let wrong = |kind| GossipError::WrongTopic { topic, kind };
+    match topic {
+        GossipTopic::Blocks | GossipTopic::Transactions | GossipTopic::Metrics => {
+            let envelope = Envelope::parse(data).map_err(|reason| GossipError::Malformed { topic, reason })?;
+            match (topic, envelope) {
+                (_, Envelope::Announce(descriptor)) => check_announcement(topic, &descriptor),
+                (GossipTopic::Blocks, Envelope::Block(block)) => {
+                    if block.calculate_hash() != block.hash {
+                        return Err(invalid("block hash is incorrect".into()));
+                    }
+                    block
+                        .transactions
+                        .iter()
+                        .try_for_each(validate_transaction_stateless)
+                        .map_err(|e| invalid(e.to_string()))
+                }
+                (GossipTopic::Blocks, Envelope::Transaction(_)) => Err(wrong("a transaction")),
+                (_, Envelope::Block(_)) => Err(wrong("a block")),
+                (_, Envelope::Transaction(tx)) => {
+                    if GossipTopic::for_transaction(&tx) != topic {
+                        return Err(wrong(if topic == GossipTopic::Metrics {
+                            "a non-metrics transaction"
+                        } else {
+                            "a metrics update"
+                        }));
+                    }
+                    validate_transaction_stateless(&tx).map_err(|e| invalid(e.to_string()))
+                }
+            }
+        }
+        GossipTopic::Jobs | GossipTopic::Evaluations => {
+            // Large bodies published through the payload protocol are
+            // announced in a wire envelope on any topic.
+            if let Ok(Envelope::Announce(descriptor)) = Envelope::parse(data) {
+                return check_announcement(topic, &descriptor);
+            }
+            let message: NetworkMessage = bincode::deserialize(data)
+                .map_err(|e| GossipError::Malformed { topic, reason: e.to_string() })?;
+            match (topic, message) {
+                (GossipTopic::Evaluations, NetworkMessage::PoUWEvaluation { evaluation }) => {
+                    if verify_evaluation(&evaluation) {
+                        Ok(())
+                    } else {
+                        Err(invalid(format!("bad signature from {}", evaluation.validator)))
+                    }
+                }
+                (
+                    GossipTopic::Jobs,
+                    NetworkMessage::JobPosted { .. }
+                    | NetworkMessage::JobVolunteer { .. }
+                    | NetworkMessage::JobCompleted { .. },
+                ) => Ok(()),
+                _ => Err(wrong("this message kind")),
+            }
+        }
+    }
+}
+
+fn check_announcement(topic: GossipTopic, descriptor: &PayloadDescriptor) -> Result<(), GossipError> {
+    let is_digest = descriptor.hash.len() == 64 && descriptor.hash.bytes().all(|b| b.is_ascii_hexdigit());
+    if is_digest {
+        Ok(())
+    } else {
+        Err(GossipError::Invalid { topic, reason: "payload hash is not a SHA-256 digest".into() })
+    }
+}
@@ -1,6 +1,9 @@
 //! Defines the structure of a computational job that can be used for PoUW.
 
+use crate::pouw::PoUWTask;
+use crate::trace::TraceContext;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Represents a generic computational job.
 ///
@@ -12,15 +15,185 @@ pub struct Job {
     pub model_id: String,
     pub dataset_id: String,
     pub iterations: u32,
+    /// Reward escrowed on chain for mining this job, see
+    /// [`crate::blockchain::transaction::StorageTx::PostJob`].
+    #[serde(default)]
+    pub reward: u64,
+    /// Hash of the WASM plugin that scores the job's outputs, see
+    /// [`crate::evaluator::plugin`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluator: Option<String>,
+    /// Kind of useful work the job asks for, see [`crate::pouw::task_types`].
+    /// `None` trains the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Trace started when the job was posted, see [`crate::trace`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceContext>,
+    /// What a node needs to be assigned the job. `None` accepts any node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirements: Option<JobRequirements>,
+    /// Stages whose share of the reward is paid out of escrow as the
+    /// evaluator signs each off, see
+    /// [`crate::blockchain::transaction::StorageTx::SignOffMilestone`]. The
+    /// rest of the reward goes to the miner that trains the job.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<Milestone>,
+    /// Account that signs off the job's milestones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub milestone_evaluator: Option<String>,
+    /// Blocks the escrow waits before refunding what is unpaid to the
+    /// poster. `None` waits
+    /// [`JOB_TIMEOUT_BLOCKS`](crate::blockchain::constants::JOB_TIMEOUT_BLOCKS).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_blocks: Option<u32>,
+}
+
+/// One stage of a long job.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Milestone {
+    pub description: String,
+    /// Part of the job's reward released when the milestone is signed off.
+    pub reward: u64,
+}
+
+/// Minimum node capabilities a job asks for, and which providers the poster
+/// wants to run it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct JobRequirements {
+    pub min_gpu_memory_gb: u32,
+    pub min_stake: u64,
+    pub min_reputation: i32,
+    /// Workers or pools the job is pinned to. When any are named, only they
+    /// may run it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
+    /// Workers or pools that may not run the job.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded: Vec<String>,
+}
+
+impl JobRequirements {
+    /// Whether a node offering `capability` may run the job.
+    pub fn satisfied_by(&self, capability: &crate::node::NodeCapability) -> bool {
+        capability.gpu_memory_gb >= self.min_gpu_memory_gb
+            && capability.available_stake >= self.min_stake
+            && capability.reputation.overall() >= self.min_reputation
+    }
+
+    /// Whether the poster lets `worker`, a member of `pool` if any, run the
+    /// job.
+    pub fn allows(&self, worker: &str, pool: Option<&str>) -> bool {
+        let named = |list: &[String]| list.iter().any(|p| p == worker || Some(p.as_str()) == pool);
+        !named(&self.excluded) && (self.pinned.is_empty() || named(&self.pinned))
+    }
 }
 
 impl Job {
+    /// Creates a job and starts its trace.
     pub fn new(id: u64, model_id: String, dataset_id: String, iterations: u32) -> Self {
         Self {
             id,
             model_id,
             dataset_id,
             iterations,
+            reward: 0,
+            evaluator: None,
+            kind: None,
+            trace: Some(TraceContext::new_root()),
+            requirements: None,
+            milestones: Vec::new(),
+            milestone_evaluator: None,
+            timeout_blocks: None,
+        }
+    }
+
+    /// Id of the job `poster` posts in its transaction with `nonce`. Ids
+    /// of posted jobs are derived so no poster can take another's.
+    pub fn posted_id(poster: &str, nonce: u64) -> u64 {
+        let digest = Sha256::new()
+            .chain_update(b"bcai-job-id")
+            .chain_update(poster.as_bytes())
+            .chain_update(nonce.to_be_bytes())
+            .finalize();
+        u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+    }
+
+    /// Gives the job the id of its posting by `poster` with `nonce`.
+    pub fn posted_by(mut self, poster: &str, nonce: u64) -> Self {
+        self.id = Self::posted_id(poster, nonce);
+        self
+    }
+
+    pub fn with_reward(mut self, reward: u64) -> Self {
+        self.reward = reward;
+        self
+    }
+
+    /// Has the job's outputs scored by the plugin module `wasm`.
+    pub fn with_evaluator(mut self, wasm: &[u8]) -> Self {
+        self.evaluator = Some(crate::evaluator::plugin::plugin_hash(wasm));
+        self
+    }
+
+    /// Only lets nodes meeting `requirements` take the job.
+    pub fn with_requirements(mut self, requirements: JobRequirements) -> Self {
+        self.requirements = Some(requirements);
+        self
+    }
+
+    /// Pays the job's reward in `milestones`, each released once `evaluator`
+    /// signs it off.
+    pub fn with_milestones(mut self, milestones: Vec<Milestone>, evaluator: &str) -> Self {
+        self.milestones = milestones;
+        self.milestone_evaluator = Some(evaluator.to_string());
+        self
+    }
+
+    /// Refunds what is unpaid to the poster `blocks` after the job's escrow
+    /// is posted.
+    pub fn with_timeout(mut self, blocks: u32) -> Self {
+        self.timeout_blocks = Some(blocks);
+        self
+    }
+
+    /// Part of the reward set aside for milestones.
+    pub fn milestone_reward(&self) -> u64 {
+        self.milestones.iter().fold(0, |sum, m| sum.saturating_add(m.reward))
+    }
+
+    /// Asks for the useful work of task type `kind` instead of training.
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    /// Compiles the job into the PoUW task a miner solves for it, so that
+    /// mining the block trains the job's model. The task commits to the job
+    /// id and carries `worker`, the miner's span of the job's trace.
+    pub fn to_task(&self, worker: Option<&TraceContext>) -> PoUWTask {
+        let mut task = PoUWTask::new(self.model_id.clone(), self.dataset_id.clone(), self.iterations);
+        task.job_id = Some(self.id);
+        task.kind = self.kind.clone();
+        task.trace = worker.map(TraceContext::traceparent);
+        task
+    }
+
+    /// Whether `task` trains this job.
+    pub fn matches(&self, task: &PoUWTask) -> bool {
+        task.job_id == Some(self.id)
+            && task.model_id == self.model_id
+            && task.dataset_id == self.dataset_id
+            && task.epochs == self.iterations
+            && task.kind == self.kind
+    }
+
+    /// Joins the trace of a job posted elsewhere, e.g. through the job
+    /// manager, instead of starting a new one. Malformed values are ignored.
+    pub fn with_traceparent(mut self, traceparent: &str) -> Self {
+        if let Some(trace) = TraceContext::continue_from(Some(traceparent)) {
+            self.trace = Some(trace);
         }
+        self
     }
 } 
\ No newline at end of file
@@ -0,0 +1,251 @@
+//! End-to-end encrypted channels between a job's poster and its worker.
+//!
+//! Credentials and private dataset URIs must reach the assigned worker
+//! without going through gossip, where every peer would see them. Poster and
+//! worker each hold an x25519 [`ChannelKey`] and hand out a
+//! [`ChannelKeyCert`] signed by their ledger key, so a channel is bound to
+//! the two accounts on chain rather than to whichever peer ids they use.
+//! Callers check that the other account is the job's poster or its assigned
+//! worker before opening a channel.
+//!
+//! Messages follow the one-way Noise `X` pattern: each is sealed under a key
+//! derived from a fresh ephemeral key and the recipient's static key (`es`)
+//! and from both static keys (`ss`), so only the recipient can open it, it
+//! could only have come from the sender, and a stolen sender key opens
+//! nothing already sent. The job id, both identities and a sequence number
+//! are authenticated with every message, which keeps messages from being
+//! moved to another job or replayed.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use schnorrkel::{signing_context, PublicKey as LedgerKey, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+#[cfg(feature = "p2p")]
+use crate::p2p_service::{codec::WireMessage, P2PError, P2PHandle};
+
+const CHANNEL_DOMAIN: &[u8] = b"bcai-job-channel-v1";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ChannelError {
+    #[error("channel key of {0} is not signed by that account")]
+    BadCertificate(String),
+    #[error("message is not for this channel")]
+    WrongChannel,
+    #[error("message could not be opened")]
+    Decrypt,
+    #[error("message {0} was already received")]
+    Replay(u64),
+    #[error("malformed channel message: {0}")]
+    Malformed(String),
+}
+
+/// Something sensitive a poster and worker exchange about a job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Artifact {
+    /// E.g. an access token for a private bucket.
+    Credential { name: String, secret: String },
+    /// Where to fetch a private dataset.
+    DatasetUri(String),
+    Blob { name: String, data: Vec<u8> },
+}
+
+/// An account's channel key, signed by the account's ledger key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelKeyCert {
+    /// Hex public key of the account.
+    pub identity: String,
+    pub key: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl ChannelKeyCert {
+    fn message(identity: &str, key: &[u8; 32]) -> Vec<u8> {
+        [CHANNEL_DOMAIN, identity.as_bytes(), key].concat()
+    }
+
+    /// Whether the account named by `identity` signed `key`.
+    pub fn verify(&self) -> bool {
+        let Ok(bytes) = hex::decode(&self.identity) else { return false };
+        let Ok(account) = LedgerKey::from_bytes(&bytes) else { return false };
+        let Ok(signature) = Signature::from_bytes(&self.signature) else { return false };
+        let message = Self::message(&self.identity, &self.key);
+        account.verify(signing_context(CHANNEL_DOMAIN).bytes(&message), &signature).is_ok()
+    }
+}
+
+/// An account's x25519 key for job channels and its certificate.
+#[derive(Clone)]
+pub struct ChannelKey {
+    secret: StaticSecret,
+    cert: ChannelKeyCert,
+}
+
+impl std::fmt::Debug for ChannelKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ChannelKey").field(&self.cert.identity).finish()
+    }
+}
+
+impl ChannelKey {
+    /// A fresh channel key certified by the account `ledger`.
+    pub fn generate(ledger: &SecretKey) -> Self {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let key = PublicKey::from(&secret).to_bytes();
+        let account = ledger.to_public();
+        let identity = hex::encode(account.to_bytes());
+        let message = ChannelKeyCert::message(&identity, &key);
+        let signature = ledger.sign(signing_context(CHANNEL_DOMAIN).bytes(&message), &account);
+        Self { secret, cert: ChannelKeyCert { identity, key, signature: signature.to_bytes().to_vec() } }
+    }
+
+    pub fn identity(&self) -> &str {
+        &self.cert.identity
+    }
+
+    pub fn cert(&self) -> &ChannelKeyCert {
+        &self.cert
+    }
+}
+
+/// One sealed [`Artifact`] on its way from one party of a job to the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMessage {
+    pub job_id: u64,
+    /// Identity of the sender.
+    pub from: String,
+    /// Identity of the recipient.
+    pub to: String,
+    /// Counts up from 0 for each sender on a channel.
+    pub seq: u64,
+    pub ephemeral: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+impl ChannelMessage {
+    /// Authenticated along with the ciphertext.
+    fn header(&self) -> Vec<u8> {
+        let mut header = CHANNEL_DOMAIN.to_vec();
+        header.extend_from_slice(&self.job_id.to_be_bytes());
+        header.extend_from_slice(&self.seq.to_be_bytes());
+        for identity in [&self.from, &self.to] {
+            header.extend_from_slice(&(identity.len() as u64).to_be_bytes());
+            header.extend_from_slice(identity.as_bytes());
+        }
+        header
+    }
+}
+
+/// `es` and `ss` mixed with the public keys of both sides.
+fn cipher(es: &[u8; 32], ss: &[u8; 32], ephemeral: &[u8; 32], sender: &[u8; 32], recipient: &[u8; 32]) -> Aes256Gcm {
+    let mut hasher = Sha256::new();
+    hasher.update(CHANNEL_DOMAIN);
+    for part in [es, ss, ephemeral, sender, recipient] {
+        hasher.update(part);
+    }
+    Aes256Gcm::new_from_slice(&hasher.finalize()).expect("SHA-256 output is an AES-256 key")
+}
+
+/// This side of the channel of one job with one other account.
+#[derive(Debug)]
+pub struct JobChannel {
+    job_id: u64,
+    key: ChannelKey,
+    peer: ChannelKeyCert,
+    sent: u64,
+    /// Sequence number of the newest message opened.
+    received: Option<u64>,
+}
+
+impl JobChannel {
+    /// A channel for `job_id` with the holder of `peer`, which must be
+    /// signed by its account.
+    pub fn new(job_id: u64, key: ChannelKey, peer: ChannelKeyCert) -> Result<Self, ChannelError> {
+        if !peer.verify() {
+            return Err(ChannelError::BadCertificate(peer.identity));
+        }
+        Ok(Self { job_id, key, peer, sent: 0, received: None })
+    }
+
+    pub fn job_id(&self) -> u64 {
+        self.job_id
+    }
+
+    /// Identity of the other party.
+    pub fn peer(&self) -> &str {
+        &self.peer.identity
+    }
+
+    pub fn seal(&mut self, artifact: &Artifact) -> Result<ChannelMessage, ChannelError> {
+        let plaintext = bincode::serialize(artifact).map_err(|e| ChannelError::Malformed(e.to_string()))?;
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral = PublicKey::from(&secret).to_bytes();
+        let recipient = PublicKey::from(self.peer.key);
+        let es = secret.diffie_hellman(&recipient);
+        let ss = self.key.secret.diffie_hellman(&recipient);
+        let mut message = ChannelMessage {
+            job_id: self.job_id,
+            from: self.key.cert.identity.clone(),
+            to: self.peer.identity.clone(),
+            seq: self.sent,
+            ephemeral,
+            nonce: [0; 12],
+            ciphertext: Vec::new(),
+        };
+        OsRng.fill_bytes(&mut message.nonce);
+        let cipher = cipher(es.as_bytes(), ss.as_bytes(), &ephemeral, &self.key.cert.key, &self.peer.key);
+        let header = message.header();
+        message.ciphertext = cipher
+            .encrypt(Nonce::from_slice(&message.nonce), Payload { msg: &plaintext, aad: &header })
+            .map_err(|e| ChannelError::Malformed(e.to_string()))?;
+        self.sent += 1;
+        Ok(message)
+    }
+
+    /// Opens a message from the other party. Messages must arrive in the
+    /// order they were sealed; older ones are refused as replays.
+    pub fn open(&mut self, message: &ChannelMessage) -> Result<Artifact, ChannelError> {
+        if message.job_id != self.job_id || message.from != self.peer.identity || message.to != self.key.cert.identity
+        {
+            return Err(ChannelError::WrongChannel);
+        }
+        if self.received.is_some_and(|last| message.seq <= last) {
+            return Err(ChannelError::Replay(message.seq));
+        }
+        let es = self.key.secret.diffie_hellman(&PublicKey::from(message.ephemeral));
+        let ss = self.key.secret.diffie_hellman(&PublicKey::from(self.peer.key));
+        let cipher = cipher(es.as_bytes(), ss.as_bytes(), &message.ephemeral, &self.peer.key, &self.key.cert.key);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&message.nonce), Payload { msg: &message.ciphertext, aad: &message.header() })
+            .map_err(|_| ChannelError::Decrypt)?;
+        let artifact = bincode::deserialize(&plaintext).map_err(|e| ChannelError::Malformed(e.to_string()))?;
+        self.received = Some(message.seq);
+        Ok(artifact)
+    }
+}
+
+/// Ask `peer` for the channel key of the account it runs as.
+#[cfg(feature = "p2p")]
+pub async fn fetch_key(handle: &P2PHandle, peer: &str) -> Result<ChannelKeyCert, P2PError> {
+    let peer_id = peer.parse().map_err(|_| P2PError::PeerNotFound(peer.to_string()))?;
+    match handle.request(peer_id, WireMessage::GetChannelKey).await? {
+        WireMessage::ChannelKey(Some(cert)) if cert.verify() => Ok(cert),
+        WireMessage::ChannelKey(Some(cert)) => {
+            Err(P2PError::Network(ChannelError::BadCertificate(cert.identity).to_string()))
+        }
+        _ => Err(P2PError::Network(format!("{} has no job channel key", peer))),
+    }
+}
+
+/// Send a sealed message to `peer`, which hands it to its job channel sink.
+#[cfg(feature = "p2p")]
+pub async fn send(handle: &P2PHandle, peer: &str, message: ChannelMessage) -> Result<(), P2PError> {
+    let peer_id = peer.parse().map_err(|_| P2PError::PeerNotFound(peer.to_string()))?;
+    handle.request(peer_id, WireMessage::JobChannel(message)).await.map(|_| ())
+}
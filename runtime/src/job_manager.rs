@@ -1,3 +1,7 @@
+use crate::journal::{Journal, JournalError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 /// Placeholder error for job manager ops.
@@ -7,21 +11,55 @@ pub enum JobManagerError {
     Stub(String),
     #[error("insufficient balance for job action")]
     InsufficientBalance,
+    #[error("job not found: {0}")]
+    JobNotFound(u64),
+    #[error("journal error: {0}")]
+    Journal(#[from] JournalError),
+}
+
+/// Job operations that touch both the ledger and the job store, journaled so
+/// that a crash between the two can be repaired on restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobOperation {
+    SubmitJob { job_id: u64, poster: String, reward: u64 },
+    CompleteJob { job_id: u64, reward: u64, payouts: Vec<(String, u64)> },
+}
+
+/// How [`JobManager::recover`] resolved an unfinished operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recovery {
+    Replayed(JobOperation),
+    RolledBack(JobOperation),
 }
 
 /// Minimal stub for job manager, tracks jobs by id.
 #[derive(Debug, Clone)]
 pub struct JobManager {
     ledger: crate::token::TokenLedger,
-    jobs: std::collections::HashMap<u64, (String, u64)>,
+    jobs: HashMap<u64, (String, u64)>,
+    journal: Option<Arc<Mutex<Journal<JobOperation>>>>,
 }
 
 impl JobManager {
     pub fn new(ledger: crate::token::TokenLedger) -> Self {
-        Self {
-            ledger,
-            jobs: std::collections::HashMap::new(),
-        }
+        Self::restore(ledger, HashMap::new())
+    }
+
+    /// Rebuilds a job manager from persisted stores, e.g. after a restart.
+    pub fn restore(ledger: crate::token::TokenLedger, jobs: HashMap<u64, (String, u64)>) -> Self {
+        Self { ledger, jobs, journal: None }
+    }
+
+    /// Journals every multi-store operation. Call [`Self::recover`] after
+    /// attaching a journal that may hold unfinished operations.
+    pub fn with_journal(mut self, journal: Journal<JobOperation>) -> Self {
+        self.journal = Some(Arc::new(Mutex::new(journal)));
+        self
+    }
+
+    /// Account holding the escrowed reward of `job_id`.
+    pub fn escrow_account(job_id: u64) -> String {
+        format!("escrow:{}", job_id)
     }
 
     /// Submits a new job, escrowing the reward from the poster.
@@ -35,14 +73,100 @@ impl JobManager {
             return Err(JobManagerError::InsufficientBalance);
         }
 
-        self
-            .ledger
-            .transfer(poster, "escrow", reward)
-            .map_err(|e| JobManagerError::Stub(e.to_string()))?;
+        let op = self.begin(JobOperation::SubmitJob { job_id, poster: poster.to_string(), reward })?;
+        if let Err(e) = self.ledger.transfer(poster, &Self::escrow_account(job_id), reward) {
+            self.end(op, false)?;
+            return Err(JobManagerError::Stub(e.to_string()));
+        }
         self.jobs.insert(job_id, (poster.to_string(), reward));
+        self.end(op, true)
+    }
+
+    /// Pays `payouts` out of the job's escrow and removes the job.
+    pub fn complete_job(&mut self, job_id: u64, payouts: &[(String, u64)]) -> Result<(), JobManagerError> {
+        let reward = self.jobs.get(&job_id).ok_or(JobManagerError::JobNotFound(job_id))?.1;
+        if payouts.iter().map(|(_, amount)| amount).sum::<u64>() > reward {
+            return Err(JobManagerError::InsufficientBalance);
+        }
+
+        let op = self.begin(JobOperation::CompleteJob { job_id, reward, payouts: payouts.to_vec() })?;
+        self.pay_out(job_id, reward, payouts)?;
+        self.end(op, true)
+    }
+
+    /// Resolves operations the journal shows as unfinished. The ledger is the
+    /// source of truth: a job whose escrow was debited is completed, one whose
+    /// escrow was not is removed again, and payouts resume after the last one
+    /// that reached the ledger.
+    pub fn recover(&mut self) -> Result<Vec<Recovery>, JobManagerError> {
+        let pending = match &self.journal {
+            Some(journal) => journal.lock().expect("journal poisoned").in_flight(),
+            None => return Ok(Vec::new()),
+        };
+        let mut recovered = Vec::new();
+        for entry in pending {
+            let replayed = match &entry.op {
+                JobOperation::SubmitJob { job_id, poster, reward } => {
+                    let escrowed = self.ledger.balance(&Self::escrow_account(*job_id)) >= *reward;
+                    if escrowed {
+                        self.jobs.insert(*job_id, (poster.clone(), *reward));
+                    } else {
+                        self.jobs.remove(job_id);
+                    }
+                    escrowed
+                }
+                JobOperation::CompleteJob { job_id, reward, payouts } => {
+                    self.pay_out(*job_id, *reward, payouts)?;
+                    true
+                }
+            };
+            self.end(Some(entry.id), replayed)?;
+            recovered.push(if replayed { Recovery::Replayed(entry.op) } else { Recovery::RolledBack(entry.op) });
+        }
+        Ok(recovered)
+    }
+
+    /// Pays the payouts not yet reflected in the escrow balance, so that an
+    /// interrupted payout can be resumed without paying anyone twice.
+    fn pay_out(&mut self, job_id: u64, reward: u64, payouts: &[(String, u64)]) -> Result<(), JobManagerError> {
+        let escrow = Self::escrow_account(job_id);
+        let mut paid = reward.saturating_sub(self.ledger.balance(&escrow));
+        for (worker, amount) in payouts {
+            if paid >= *amount {
+                paid -= amount;
+                continue;
+            }
+            self.ledger
+                .transfer(&escrow, worker, *amount)
+                .map_err(|e| JobManagerError::Stub(e.to_string()))?;
+        }
+        self.jobs.remove(&job_id);
         Ok(())
     }
 
+    fn begin(&self, op: JobOperation) -> Result<Option<u64>, JobManagerError> {
+        match &self.journal {
+            Some(journal) => Ok(Some(journal.lock().expect("journal poisoned").begin(op)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn end(&self, op: Option<u64>, commit: bool) -> Result<(), JobManagerError> {
+        if let (Some(journal), Some(id)) = (&self.journal, op) {
+            let mut journal = journal.lock().expect("journal poisoned");
+            if commit {
+                journal.commit(id)?;
+            } else {
+                journal.roll_back(id)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn jobs(&self) -> &HashMap<u64, (String, u64)> {
+        &self.jobs
+    }
+
     pub fn ledger(&self) -> &crate::token::TokenLedger {
         &self.ledger
     }
@@ -50,4 +174,4 @@ impl JobManager {
     pub fn ledger_mut(&mut self) -> &mut crate::token::TokenLedger {
         &mut self.ledger
     }
-} 
\ No newline at end of file
+}
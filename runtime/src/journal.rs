@@ -0,0 +1,136 @@
+//! Write-ahead journal for operations that update several stores.
+//!
+//! An operation is recorded before any store is touched and marked committed
+//! or rolled back once every store agrees, one JSON record per line, synced
+//! to disk before the call returns. After a crash, [`Journal::open`] reports
+//! the operations that never finished so their owner can inspect the stores
+//! and either complete or undo them. The journal is truncated whenever no
+//! operation is in flight, so it stays small.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("corrupt journal record at line {0}")]
+    Corrupt(usize),
+    #[error("operation {0} is not in flight")]
+    UnknownOperation(u64),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record<O> {
+    Begin { id: u64, op: O },
+    Commit { id: u64 },
+    RollBack { id: u64 },
+}
+
+/// An operation that was begun but has not been committed or rolled back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InFlight<O> {
+    pub id: u64,
+    pub op: O,
+}
+
+#[derive(Debug)]
+pub struct Journal<O> {
+    path: PathBuf,
+    file: File,
+    next_id: u64,
+    in_flight: BTreeMap<u64, O>,
+}
+
+impl<O: Serialize + DeserializeOwned + Clone> Journal<O> {
+    /// Opens or creates the journal at `path` and loads the operations left
+    /// in flight. A torn final record, from a crash mid-write, is discarded.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, JournalError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        let mut in_flight = BTreeMap::new();
+        let mut next_id = 0;
+        let mut valid_len = 0;
+        let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+        for (n, line) in lines.iter().enumerate() {
+            let record = match serde_json::from_str::<Record<O>>(line) {
+                Ok(record) if line.ends_with('\n') => record,
+                _ if n + 1 == lines.len() => break,
+                _ => return Err(JournalError::Corrupt(n + 1)),
+            };
+            valid_len += line.len();
+            match record {
+                Record::Begin { id, op } => {
+                    next_id = next_id.max(id + 1);
+                    in_flight.insert(id, op);
+                }
+                Record::Commit { id } | Record::RollBack { id } => {
+                    in_flight.remove(&id);
+                }
+            }
+        }
+        if valid_len < contents.len() {
+            file.set_len(valid_len as u64)?;
+            file.seek(SeekFrom::End(0))?;
+        }
+        Ok(Self { path, file, next_id, in_flight })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Operations left unfinished, oldest first.
+    pub fn in_flight(&self) -> Vec<InFlight<O>> {
+        self.in_flight.iter().map(|(id, op)| InFlight { id: *id, op: op.clone() }).collect()
+    }
+
+    /// Records `op` before any store is changed and returns its id.
+    pub fn begin(&mut self, op: O) -> Result<u64, JournalError> {
+        let id = self.next_id;
+        self.append(&Record::Begin { id, op: op.clone() })?;
+        self.next_id += 1;
+        self.in_flight.insert(id, op);
+        Ok(id)
+    }
+
+    pub fn commit(&mut self, id: u64) -> Result<(), JournalError> {
+        self.finish(id, Record::Commit { id })
+    }
+
+    pub fn roll_back(&mut self, id: u64) -> Result<(), JournalError> {
+        self.finish(id, Record::RollBack { id })
+    }
+
+    fn finish(&mut self, id: u64, record: Record<O>) -> Result<(), JournalError> {
+        if !self.in_flight.contains_key(&id) {
+            return Err(JournalError::UnknownOperation(id));
+        }
+        self.append(&record)?;
+        self.in_flight.remove(&id);
+        if self.in_flight.is_empty() {
+            self.file.set_len(0)?;
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, record: &Record<O>) -> Result<(), JournalError> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
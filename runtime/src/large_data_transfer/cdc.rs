@@ -0,0 +1,138 @@
+//! Content-defined chunking (FastCDC).
+//!
+//! Fixed-size chunks shift when bytes are inserted or removed, so a retrained
+//! model that differs from the last version in a few tensors shares almost
+//! no chunks with it. Content-defined chunking cuts where a rolling gear
+//! hash of the last bytes matches a mask instead, so boundaries follow the
+//! content: after an edit they resynchronise within a chunk or two, and the
+//! new version's descriptor lists mostly chunks the old one already has.
+//! Only those that changed need to be transferred and stored.
+//!
+//! Cuts use normalized chunking: a stricter mask before the average size and
+//! a looser one after it keep chunk sizes close to the average.
+
+use crate::large_data_transfer::{
+    chunk::DataChunk, config::CompressionAlgorithm, descriptor::LargeDataDescriptor,
+    LargeDataError, LargeDataResult,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Chunk size bounds of content-defined chunking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CdcConfig {
+    /// No cut is made before this many bytes.
+    pub min_size: u32,
+    /// Size chunks tend to; a power of two.
+    pub avg_size: u32,
+    /// A cut is forced at this many bytes.
+    pub max_size: u32,
+}
+
+impl Default for CdcConfig {
+    fn default() -> Self {
+        Self { min_size: 512 * 1024, avg_size: 2 * 1024 * 1024, max_size: 8 * 1024 * 1024 }
+    }
+}
+
+impl CdcConfig {
+    /// Bounds around `avg_size`, a quarter of it to four times it.
+    pub fn with_average(avg_size: u32) -> Self {
+        Self { min_size: avg_size / 4, avg_size, max_size: avg_size.saturating_mul(4) }
+    }
+
+    pub fn validate(&self) -> LargeDataResult<()> {
+        if !self.avg_size.is_power_of_two()
+            || self.avg_size < 64
+            || self.min_size > self.avg_size
+            || self.avg_size > self.max_size
+        {
+            return Err(LargeDataError::Config(format!(
+                "content-defined chunking needs min <= avg <= max and a power-of-two avg of at least 64, got {}/{}/{}",
+                self.min_size, self.avg_size, self.max_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// Masks checked before and after the average size: two bits more and
+    /// two bits fewer than the average implies, taken from the top of the
+    /// hash, which depends on the last 64 bytes.
+    fn masks(&self) -> (u64, u64) {
+        let bits = self.avg_size.ilog2();
+        let mask = |bits: u32| !0u64 << (64 - bits);
+        (mask(bits + 2), mask(bits.saturating_sub(2).max(1)))
+    }
+}
+
+/// Random values the rolling hash adds per byte, fixed so that every node
+/// cuts the same content at the same places.
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    // splitmix64 over a fixed seed.
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x6263_6169_2d63_6463;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Length of the first chunk of `data`. `data` shorter than the minimum
+/// size is one chunk; a chunk never exceeds the maximum size.
+pub fn cut_point(data: &[u8], config: &CdcConfig) -> usize {
+    let min = config.min_size as usize;
+    if data.len() <= min {
+        return data.len();
+    }
+    let end = data.len().min(config.max_size as usize);
+    let normal = end.min(config.avg_size as usize);
+    let (strict, loose) = config.masks();
+    let mut hash = 0u64;
+    for (i, byte) in data.iter().enumerate().take(end).skip(min) {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+        let mask = if i < normal { strict } else { loose };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Splits `data` into content-defined chunks.
+pub fn split<'a>(data: &'a [u8], config: &CdcConfig) -> Vec<&'a [u8]> {
+    let mut chunks = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(cut_point(rest, config));
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
+/// Chunks `data` as the object `id`, returning its descriptor and chunks.
+pub fn chunk_object(
+    id: &str,
+    data: &[u8],
+    config: &CdcConfig,
+    compression: CompressionAlgorithm,
+) -> LargeDataResult<(LargeDataDescriptor, Vec<DataChunk>)> {
+    config.validate()?;
+    let chunks = split(data, config)
+        .into_iter()
+        .enumerate()
+        .map(|(index, piece)| DataChunk::new_from_slice(piece.to_vec(), index as u32, compression))
+        .collect::<LargeDataResult<Vec<_>>>()?;
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    let content_hash = format!("{:x}", Sha256::digest(data));
+    let descriptor = LargeDataDescriptor::new(id.to_string(), content_hash, data.len() as u64, hashes);
+    Ok((descriptor, chunks))
+}
@@ -1,4 +1,4 @@
-use crate::large_data_transfer::{chunk::{id::ChunkId, info::ChunkInfo, error::ChunkError}, config::CompressionAlgorithm, error::LargeDataError, LargeDataResult};
+use crate::large_data_transfer::{chunk::{id::ChunkId, info::ChunkInfo, error::ChunkError}, compression::{AdaptiveCompressor, CompressionUtils}, config::CompressionAlgorithm, LargeDataResult};
 use super::core::DataChunk;
 
 impl DataChunk {
@@ -21,11 +21,49 @@ impl DataChunk {
                     (data, 0, CompressionAlgorithm::None)
                 }
             }
-            CompressionAlgorithm::Zstd => {
-                return Err(LargeDataError::Compression("Zstd not implemented".into()));
+            CompressionAlgorithm::Zstd | CompressionAlgorithm::ZstdDict => {
+                let compressed = CompressionUtils::compress(&data, compression)?;
+                Self::smaller(data, compressed, compression)
             }
         };
+        Ok(Self::assemble(final_data, original_size, compressed_size, actual_compression, checksum, index))
+    }
+
+    /// Build a new chunk from raw `data`, compressed by the profile of its
+    /// content type where that makes it smaller.
+    pub fn new_with_compressor(
+        data: Vec<u8>,
+        index: u32,
+        compressor: &AdaptiveCompressor,
+    ) -> LargeDataResult<Self> {
+        let original_size = data.len() as u32;
+        let checksum = crc32fast::hash(&data);
+        let (compressed, algorithm) = compressor.compress(&data)?;
+        let (final_data, compressed_size, actual_compression) = match algorithm {
+            CompressionAlgorithm::None => (data, 0, CompressionAlgorithm::None),
+            _ => Self::smaller(data, compressed, algorithm),
+        };
+        Ok(Self::assemble(final_data, original_size, compressed_size, actual_compression, checksum, index))
+    }
+
+    /// The compressed form if it is smaller, else the data as is.
+    fn smaller(data: Vec<u8>, compressed: Vec<u8>, algorithm: CompressionAlgorithm) -> (Vec<u8>, u32, CompressionAlgorithm) {
+        if compressed.len() < data.len() {
+            let size = compressed.len() as u32;
+            (compressed, size, algorithm)
+        } else {
+            (data, 0, CompressionAlgorithm::None)
+        }
+    }
 
+    fn assemble(
+        final_data: Vec<u8>,
+        original_size: u32,
+        compressed_size: u32,
+        actual_compression: CompressionAlgorithm,
+        checksum: u32,
+        index: u32,
+    ) -> Self {
         let id = ChunkId::from_data(&final_data);
         let info = ChunkInfo {
             id: id.clone(),
@@ -34,18 +72,25 @@ impl DataChunk {
             compression: actual_compression,
             checksum,
             index, replicas: vec![],
+            encryption: None,
         };
 
-        Ok(DataChunk { id, data: final_data, info })
+        DataChunk { id, data: final_data, info }
     }
 
     /// Decompress stored data back to original bytes (no-op if uncompressed).
     pub fn decompress(&self) -> Result<Vec<u8>, ChunkError> {
+        if self.is_encrypted() {
+            return Err(ChunkError::DecompressionFailed("chunk is encrypted; open it first".into()));
+        }
         match self.info.compression {
             CompressionAlgorithm::None => Ok(self.data.clone()),
             CompressionAlgorithm::Lz4 => lz4_flex::decompress_size_prepended(&self.data)
                 .map_err(|e| ChunkError::DecompressionFailed(e.to_string())),
-            CompressionAlgorithm::Zstd => Err(ChunkError::DecompressionFailed("Zstd not implemented".into())),
+            CompressionAlgorithm::Zstd | CompressionAlgorithm::ZstdDict => {
+                CompressionUtils::decompress(&self.data, self.info.compression)
+                    .map_err(|e| ChunkError::DecompressionFailed(e.to_string()))
+            }
         }
     }
 } 
\ No newline at end of file
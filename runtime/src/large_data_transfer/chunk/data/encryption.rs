@@ -0,0 +1,69 @@
+use super::core::DataChunk;
+use crate::large_data_transfer::{
+    chunk::id::ChunkId,
+    config::EncryptionAlgorithm,
+    crypto::{ChunkEncryption, CryptoUtils, RecipientKey, WrappedKey},
+    LargeDataError, LargeDataResult,
+};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+impl DataChunk {
+    /// Whether the stored data is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.info.encryption.is_some()
+    }
+
+    /// Encrypts the stored data under a fresh content key wrapped for each
+    /// of `recipients`. The id stays that of the unencrypted data.
+    pub fn seal(
+        mut self,
+        algorithm: EncryptionAlgorithm,
+        recipients: &[[u8; 32]],
+    ) -> LargeDataResult<Self> {
+        if self.is_encrypted() {
+            return Err(LargeDataError::Encryption(format!(
+                "chunk {} is already encrypted",
+                self.id
+            )));
+        }
+        if recipients.is_empty() {
+            return Err(LargeDataError::Encryption("a chunk needs at least one recipient".into()));
+        }
+        let mut content_key = [0u8; 32];
+        OsRng.fill_bytes(&mut content_key);
+        let keys = recipients
+            .iter()
+            .map(|recipient| WrappedKey::wrap(&content_key, *recipient))
+            .collect::<LargeDataResult<Vec<_>>>()?;
+        self.data = CryptoUtils::seal(algorithm, &content_key, &self.data, &self.aad())?;
+        self.info.encryption = Some(ChunkEncryption { algorithm, keys });
+        Ok(self)
+    }
+
+    /// The chunk as it was before [`DataChunk::seal`], decrypted with the
+    /// content key wrapped for `key`.
+    pub fn open(&self, key: &RecipientKey) -> LargeDataResult<Self> {
+        let encryption = self.info.encryption.as_ref().ok_or_else(|| {
+            LargeDataError::Encryption(format!("chunk {} is not encrypted", self.id))
+        })?;
+        let content_key = encryption.content_key(key)?;
+        let data = CryptoUtils::open(encryption.algorithm, &content_key, &self.data, &self.aad())?;
+        if ChunkId::from_data(&data) != self.id {
+            return Err(LargeDataError::Encryption(format!(
+                "chunk {} decrypts to other data",
+                self.id
+            )));
+        }
+        let mut info = self.info.clone();
+        info.encryption = None;
+        Ok(Self { id: self.id.clone(), data, info })
+    }
+
+    /// Data authenticated with the ciphertext, binding it to this chunk.
+    fn aad(&self) -> Vec<u8> {
+        let mut aad = self.id.0.as_bytes().to_vec();
+        aad.extend_from_slice(&self.info.index.to_le_bytes());
+        aad
+    }
+}
@@ -2,6 +2,7 @@
 
 mod core;
 mod compression;
+mod encryption;
 mod verify;
 
 pub use core::DataChunk; 
\ No newline at end of file
@@ -4,6 +4,9 @@ use super::core::DataChunk;
 impl DataChunk {
     /// Verify content hash, size, checksum, and compression metadata.
     pub fn verify_integrity(&self) -> Result<(), ChunkError> {
+        if self.is_encrypted() {
+            return Err(ChunkError::IntegrityCheckFailed("chunk is encrypted; open it first".into()));
+        }
         let expected_id = crate::large_data_transfer::chunk::id::ChunkId::from_data(&self.data);
         if expected_id != self.id {
             return Err(ChunkError::IntegrityCheckFailed("Content hash mismatch".into()));
@@ -2,6 +2,7 @@
 
 use super::id::ChunkId;
 use crate::large_data_transfer::config::CompressionAlgorithm;
+use crate::large_data_transfer::crypto::ChunkEncryption;
 use serde::{Deserialize, Serialize};
 
 /// Metadata about a data chunk.
@@ -28,6 +29,11 @@ pub struct ChunkInfo {
     /// Node IDs that currently store a replica of this chunk (original holder + copies).
     #[serde(default)]
     pub replicas: Vec<String>,
+
+    /// How the stored data is encrypted at rest, if it is. The sizes and the
+    /// checksum above are those of the data before encryption.
+    #[serde(default)]
+    pub encryption: Option<ChunkEncryption>,
 }
 
 impl ChunkInfo {
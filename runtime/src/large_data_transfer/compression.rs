@@ -1,14 +1,28 @@
 //! Compression Implementation for Large Data Transfer
 //!
-//! Provides helper functions for compressing and decompressing data.  The
-//! implementation is intentionally simple but fully functional using the LZ4
-//! algorithm.  Additional algorithms can be added in later phases.
+//! Provides helper functions for compressing and decompressing data with
+//! LZ4 or zstd, optionally primed with a trained [`dictionary`] of the
+//! content's type. An [`AdaptiveCompressor`] applies the per-content-type
+//! profiles of a [`CompressionConfig`] and picks zstd levels from measured
+//! throughput and ratio.
+//!
+//! [`dictionary`]: super::dictionary
 
 use crate::large_data_transfer::{
-    config::CompressionAlgorithm,
+    config::{CompressionAlgorithm, CompressionConfig, ContentType, LevelSelection},
+    dictionary,
     error::{LargeDataError, LargeDataResult},
 };
+use std::collections::HashMap;
 use std::io::Read;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Zstd level used where none is configured.
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// Bytes of the data looked at to tell its content type.
+const DETECT_BYTES: usize = 4096;
 
 /// Compression helper utilities.
 pub struct CompressionUtils;
@@ -16,11 +30,39 @@ pub struct CompressionUtils;
 impl CompressionUtils {
     /// Compress `data` according to the selected algorithm.
     pub fn compress(data: &[u8], algo: CompressionAlgorithm) -> LargeDataResult<Vec<u8>> {
+        Self::compress_level(data, algo, DEFAULT_ZSTD_LEVEL)
+    }
+
+    /// Compress `data` with zstd at `level`; the level is ignored by the
+    /// other algorithms. [`CompressionAlgorithm::ZstdDict`] output starts
+    /// with the little-endian id of the dictionary used, 0 if none is
+    /// installed for the content's type.
+    pub fn compress_level(
+        data: &[u8],
+        algo: CompressionAlgorithm,
+        level: i32,
+    ) -> LargeDataResult<Vec<u8>> {
         match algo {
             CompressionAlgorithm::None => Ok(data.to_vec()),
             CompressionAlgorithm::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
-            CompressionAlgorithm::Zstd => zstd::bulk::compress(data, 3)
+            CompressionAlgorithm::Zstd => zstd::bulk::compress(data, level)
                 .map_err(|e| LargeDataError::Compression(e.to_string())),
+            CompressionAlgorithm::ZstdDict => {
+                let dictionary = dictionary::registry().for_content(ContentType::detect(data));
+                let (id, frame) = match dictionary {
+                    Some(dictionary) => {
+                        let frame = zstd::bulk::Compressor::with_dictionary(level, &dictionary.bytes)
+                            .and_then(|mut compressor| compressor.compress(data))
+                            .map_err(|e| LargeDataError::Compression(e.to_string()))?;
+                        (dictionary.id, frame)
+                    }
+                    None => (0, Self::compress_level(data, CompressionAlgorithm::Zstd, level)?),
+                };
+                let mut out = Vec::with_capacity(frame.len() + 4);
+                out.extend_from_slice(&id.to_le_bytes());
+                out.extend_from_slice(&frame);
+                Ok(out)
+            }
         }
     }
 
@@ -39,6 +81,160 @@ impl CompressionUtils {
                     .map_err(|e| LargeDataError::Compression(e.to_string()))?;
                 Ok(buf)
             }
+            CompressionAlgorithm::ZstdDict => {
+                if data.len() < 4 {
+                    return Err(LargeDataError::Compression("missing dictionary id".into()));
+                }
+                let (id, frame) = data.split_at(4);
+                let id = u32::from_le_bytes([id[0], id[1], id[2], id[3]]);
+                if id == 0 {
+                    return Self::decompress(frame, CompressionAlgorithm::Zstd);
+                }
+                let dictionary = dictionary::registry().get(id).ok_or_else(|| {
+                    LargeDataError::Compression(format!("dictionary {:08x} is not installed", id))
+                })?;
+                let mut buf = Vec::new();
+                zstd::Decoder::with_dictionary(frame, &dictionary.bytes)
+                    .and_then(|mut decoder| decoder.read_to_end(&mut buf))
+                    .map_err(|e| LargeDataError::Compression(e.to_string()))?;
+                Ok(buf)
+            }
         }
     }
 }
+
+impl ContentType {
+    /// Tells the content type from the first bytes of `data`: binary data
+    /// is taken for tensors, text for newline-delimited JSON when its lines
+    /// are objects, for CSV when its first lines have the same number of
+    /// commas.
+    pub fn detect(data: &[u8]) -> Self {
+        let head = &data[..data.len().min(DETECT_BYTES)];
+        let text = head
+            .iter()
+            .filter(|&&b| b.is_ascii_graphic() || b.is_ascii_whitespace() || b >= 0x80)
+            .count();
+        if head.is_empty() || text * 100 < head.len() * 95 {
+            return Self::Tensor;
+        }
+        let mut lines: Vec<&[u8]> =
+            head.split(|&b| b == b'\n').map(<[u8]>::trim_ascii).filter(|l| !l.is_empty()).collect();
+        // The last line may be cut off.
+        if data.len() > head.len() && lines.len() > 1 {
+            lines.pop();
+        }
+        let Some((first, rest)) = lines.split_first() else {
+            return Self::Other;
+        };
+        if lines.iter().all(|l| l.starts_with(b"{") && l.ends_with(b"}")) {
+            return Self::JsonLog;
+        }
+        let commas = |line: &[u8]| line.iter().filter(|&&b| b == b',').count();
+        if commas(first) > 0 && !rest.is_empty() && rest.iter().all(|l| commas(l) == commas(first)) {
+            return Self::Csv;
+        }
+        Self::Other
+    }
+}
+
+/// How one zstd level did on a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelMeasurement {
+    pub level: i32,
+    /// Original size over compressed size.
+    pub ratio: f64,
+    pub throughput_mb_s: f64,
+}
+
+/// Compresses `sample` at each level of `levels` with `algo`.
+pub fn measure_levels(
+    sample: &[u8],
+    algo: CompressionAlgorithm,
+    levels: &[i32],
+) -> LargeDataResult<Vec<LevelMeasurement>> {
+    levels
+        .iter()
+        .map(|&level| {
+            let start = Instant::now();
+            let compressed = CompressionUtils::compress_level(sample, algo, level)?;
+            let secs = start.elapsed().as_secs_f64().max(1e-9);
+            Ok(LevelMeasurement {
+                level,
+                ratio: sample.len() as f64 / compressed.len().max(1) as f64,
+                throughput_mb_s: sample.len() as f64 / 1e6 / secs,
+            })
+        })
+        .collect()
+}
+
+/// The level with the best ratio among those at least `min_throughput_mb_s`
+/// fast, or the fastest if none is.
+pub fn select_level(measurements: &[LevelMeasurement], min_throughput_mb_s: f64) -> Option<i32> {
+    measurements
+        .iter()
+        .filter(|m| m.throughput_mb_s >= min_throughput_mb_s)
+        .max_by(|a, b| a.ratio.total_cmp(&b.ratio))
+        .or_else(|| measurements.iter().max_by(|a, b| a.throughput_mb_s.total_cmp(&b.throughput_mb_s)))
+        .map(|m| m.level)
+}
+
+/// Compresses data by the profile of its content type, choosing zstd levels
+/// on the first data of each type when level selection is enabled.
+#[derive(Debug)]
+pub struct AdaptiveCompressor {
+    config: CompressionConfig,
+    /// Levels chosen so far, by content type.
+    levels: Mutex<HashMap<ContentType, i32>>,
+}
+
+impl AdaptiveCompressor {
+    pub fn new(config: CompressionConfig) -> Self {
+        Self { config, levels: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &CompressionConfig {
+        &self.config
+    }
+
+    /// Compresses `data`, returning the bytes and the algorithm they are
+    /// compressed with. Data below the configured minimum size, or with
+    /// compression disabled, is returned as is.
+    pub fn compress(&self, data: &[u8]) -> LargeDataResult<(Vec<u8>, CompressionAlgorithm)> {
+        if !self.config.enabled || data.len() < self.config.min_size as usize {
+            return Ok((data.to_vec(), CompressionAlgorithm::None));
+        }
+        let content = ContentType::detect(data);
+        let profile = self.config.profile(content);
+        let level = self.level(content, profile.algorithm, profile.level, data)?;
+        Ok((CompressionUtils::compress_level(data, profile.algorithm, level)?, profile.algorithm))
+    }
+
+    /// The level chosen for `content`, measured on `data` the first time.
+    pub fn level(
+        &self,
+        content: ContentType,
+        algo: CompressionAlgorithm,
+        configured: i32,
+        data: &[u8],
+    ) -> LargeDataResult<i32> {
+        let selection: &LevelSelection = &self.config.level_selection;
+        let zstd = matches!(algo, CompressionAlgorithm::Zstd | CompressionAlgorithm::ZstdDict);
+        if !selection.enabled || !zstd || selection.candidates.is_empty() {
+            return Ok(configured);
+        }
+        if let Some(level) = self.levels.lock().unwrap().get(&content) {
+            return Ok(*level);
+        }
+        let sample = &data[..data.len().min(selection.sample_bytes)];
+        let measurements = measure_levels(sample, algo, &selection.candidates)?;
+        let level = select_level(&measurements, selection.min_throughput_mb_s).unwrap_or(configured);
+        tracing::debug!(?content, level, "compression level selected");
+        self.levels.lock().unwrap().insert(content, level);
+        Ok(level)
+    }
+
+    /// Levels chosen so far, by content type.
+    pub fn selected_levels(&self) -> HashMap<ContentType, i32> {
+        self.levels.lock().unwrap().clone()
+    }
+}
@@ -1,4 +1,4 @@
-use super::{CacheConfig, CompressionConfig, EncryptionConfig, RetryConfig};
+use super::{CacheConfig, CompressionConfig, EncryptionConfig, QosConfig, RetryConfig};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -26,6 +26,9 @@ pub struct LargeDataConfig {
     pub encryption_config: EncryptionConfig,
     /// Retry strategy when chunks fail
     pub retry_config: RetryConfig,
+    /// Priority classes, bandwidth reservations and starvation protection
+    #[serde(default)]
+    pub qos_config: QosConfig,
     /// Interval for peer updates (e.g. refresh peer stats).
     pub peer_update_interval: Duration,
     /// Timeout after which a peer is considered stale and removed.
@@ -45,6 +48,7 @@ impl Default for LargeDataConfig {
             compression_config: CompressionConfig::default(),
             encryption_config: EncryptionConfig::default(),
             retry_config: RetryConfig::default(),
+            qos_config: QosConfig::default(),
             peer_update_interval: Duration::from_secs(30),
             peer_timeout: Duration::from_secs(300),
         }
@@ -5,10 +5,15 @@ use serde::{Deserialize, Serialize};
 pub enum EncryptionAlgorithm {
     None,
     ChaCha20Poly1305,
+    /// ChaCha20-Poly1305 with 24-byte nonces, safe to pick at random
+    XChaCha20Poly1305,
     Aes256Gcm,
 }
 
 /// Encryption configuration controlling optional encryption of chunks.
+/// Chunks are encrypted at rest when both `enabled` and `chunk_encryption`
+/// are set and the chunk manager has a key (see
+/// [`ChunkSealer`](crate::large_data_transfer::crypto::ChunkSealer)).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionConfig {
     /// Enable or disable encryption.
@@ -23,7 +28,7 @@ impl Default for EncryptionConfig {
     fn default() -> Self {
         Self {
             enabled: false,
-            algorithm: EncryptionAlgorithm::ChaCha20Poly1305,
+            algorithm: EncryptionAlgorithm::XChaCha20Poly1305,
             chunk_encryption: false,
         }
     }
@@ -7,10 +7,14 @@ mod core;
 mod cache;
 mod compression;
 mod encryption;
+mod qos;
 mod retry;
 
 pub use core::LargeDataConfig;
 pub use cache::CacheConfig;
-pub use compression::{CompressionConfig, CompressionAlgorithm};
+pub use compression::{
+    CompressionAlgorithm, CompressionConfig, CompressionProfile, ContentType, LevelSelection,
+};
 pub use encryption::{EncryptionConfig, EncryptionAlgorithm};
+pub use qos::QosConfig;
 pub use retry::RetryConfig; 
\ No newline at end of file
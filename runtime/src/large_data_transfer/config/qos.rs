@@ -0,0 +1,47 @@
+use crate::large_data_transfer::types::TransferPriority;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Quality-of-service policy of the transfer scheduler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QosConfig {
+    /// Percent of the download cap reserved for critical transfers.
+    pub critical_reservation: u8,
+    /// Percent of the download cap reserved for high-priority transfers.
+    pub high_reservation: u8,
+    /// Percent of the download cap reserved for normal transfers.
+    pub normal_reservation: u8,
+    /// Percent of the download cap reserved for low-priority transfers.
+    pub low_reservation: u8,
+    /// How long a preempted transfer waits before it is let through for a
+    /// chunk anyway.
+    pub starvation_after: Duration,
+    /// How often the scheduler loop re-evaluates waiting transfers.
+    pub tick: Duration,
+}
+
+impl QosConfig {
+    /// Percent of the download cap reserved for `priority`.
+    pub fn reservation(&self, priority: TransferPriority) -> u8 {
+        match priority {
+            TransferPriority::Critical => self.critical_reservation,
+            TransferPriority::High => self.high_reservation,
+            TransferPriority::Normal => self.normal_reservation,
+            TransferPriority::Low => self.low_reservation,
+        }
+    }
+}
+
+impl Default for QosConfig {
+    fn default() -> Self {
+        Self {
+            critical_reservation: 50,
+            high_reservation: 25,
+            normal_reservation: 15,
+            low_reservation: 10,
+            starvation_after: Duration::from_secs(10),
+            tick: Duration::from_millis(100),
+        }
+    }
+}
@@ -3,9 +3,31 @@
 //! Provides lightweight AES-GCM encryption utilities used for securing chunks
 //! during transfer.  The goal is not to offer a full cryptographic framework but
 //! simply to remove plain-text transmissions from the prototype.
+//!
+//! Chunks are encrypted at rest with a fresh content key each, under
+//! AES-256-GCM, ChaCha20-Poly1305 or XChaCha20-Poly1305. The content key is
+//! wrapped, by x25519 key agreement against an ephemeral key, for every
+//! recipient allowed to read the chunk, and the wrapped keys travel in the
+//! chunk's [`ChunkInfo`](super::chunk::ChunkInfo). The AEAD tag covers the
+//! chunk's id and index too, so a ciphertext moved to another chunk fails to
+//! open.
+
+use aes_gcm::aead::generic_array::typenum::Unsigned;
+use aes_gcm::aead::{self, Aead, AeadCore, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use crate::large_data_transfer::{
+    chunk::DataChunk,
+    config::{EncryptionAlgorithm, EncryptionConfig},
+    error::{LargeDataError, LargeDataResult},
+};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
 
-use aes_gcm::{aead::{Aead, KeyInit}, Aes256Gcm, Nonce};
-use crate::large_data_transfer::error::{LargeDataError, LargeDataResult};
+const WRAP_DOMAIN: &[u8] = b"bcai-chunk-key-v1";
 
 /// Encryption helper utilities.
 pub struct CryptoUtils;
@@ -27,4 +49,222 @@ impl CryptoUtils {
             .decrypt(Nonce::from_slice(nonce), data)
             .map_err(|e| LargeDataError::Encryption(e.to_string()))
     }
+
+    /// Encrypt `data` under `key` with a random nonce, authenticating `aad`
+    /// alongside. The nonce is prepended to the ciphertext.
+    pub fn seal(
+        algorithm: EncryptionAlgorithm,
+        key: &[u8; 32],
+        data: &[u8],
+        aad: &[u8],
+    ) -> LargeDataResult<Vec<u8>> {
+        match algorithm {
+            EncryptionAlgorithm::None => {
+                Err(LargeDataError::Encryption("no encryption algorithm chosen".into()))
+            }
+            EncryptionAlgorithm::Aes256Gcm => seal_with(&Aes256Gcm::new(key.into()), data, aad),
+            EncryptionAlgorithm::ChaCha20Poly1305 => {
+                seal_with(&ChaCha20Poly1305::new(key.into()), data, aad)
+            }
+            EncryptionAlgorithm::XChaCha20Poly1305 => {
+                seal_with(&XChaCha20Poly1305::new(key.into()), data, aad)
+            }
+        }
+    }
+
+    /// Decrypt the output of [`CryptoUtils::seal`]. Fails if the data or
+    /// `aad` were altered.
+    pub fn open(
+        algorithm: EncryptionAlgorithm,
+        key: &[u8; 32],
+        sealed: &[u8],
+        aad: &[u8],
+    ) -> LargeDataResult<Vec<u8>> {
+        match algorithm {
+            EncryptionAlgorithm::None => {
+                Err(LargeDataError::Encryption("no encryption algorithm chosen".into()))
+            }
+            EncryptionAlgorithm::Aes256Gcm => open_with(&Aes256Gcm::new(key.into()), sealed, aad),
+            EncryptionAlgorithm::ChaCha20Poly1305 => {
+                open_with(&ChaCha20Poly1305::new(key.into()), sealed, aad)
+            }
+            EncryptionAlgorithm::XChaCha20Poly1305 => {
+                open_with(&XChaCha20Poly1305::new(key.into()), sealed, aad)
+            }
+        }
+    }
+}
+
+fn seal_with<C: Aead>(cipher: &C, data: &[u8], aad: &[u8]) -> LargeDataResult<Vec<u8>> {
+    let mut nonce = aead::Nonce::<C>::default();
+    OsRng.fill_bytes(nonce.as_mut_slice());
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: data, aad })
+        .map_err(|e| LargeDataError::Encryption(e.to_string()))?;
+    let mut out = Vec::with_capacity(nonce.len() + ciphertext.len());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn open_with<C: Aead>(cipher: &C, sealed: &[u8], aad: &[u8]) -> LargeDataResult<Vec<u8>> {
+    let nonce_len = <C as AeadCore>::NonceSize::USIZE;
+    if sealed.len() < nonce_len {
+        return Err(LargeDataError::Encryption("ciphertext shorter than its nonce".into()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(nonce_len);
+    cipher
+        .decrypt(aead::Nonce::<C>::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| LargeDataError::Encryption("decryption failed: wrong key or tampered data".into()))
+}
+
+/// An x25519 key pair content keys are wrapped for.
+#[derive(Clone)]
+pub struct RecipientKey(StaticSecret);
+
+impl RecipientKey {
+    pub fn generate() -> Self {
+        Self(StaticSecret::random_from_rng(OsRng))
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(StaticSecret::from(bytes))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn public(&self) -> [u8; 32] {
+        PublicKey::from(&self.0).to_bytes()
+    }
+}
+
+impl std::fmt::Debug for RecipientKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RecipientKey").field(&hex::encode(self.public())).finish()
+    }
+}
+
+/// A content key encrypted to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    /// Public key of the recipient.
+    pub recipient: [u8; 32],
+    pub ephemeral: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+fn wrapping_cipher(shared: &[u8; 32], ephemeral: &[u8; 32], recipient: &[u8; 32]) -> Aes256Gcm {
+    let mut hasher = Sha256::new();
+    hasher.update(WRAP_DOMAIN);
+    hasher.update(shared);
+    hasher.update(ephemeral);
+    hasher.update(recipient);
+    Aes256Gcm::new_from_slice(&hasher.finalize()).expect("SHA-256 output is an AES-256 key")
+}
+
+impl WrappedKey {
+    /// Wraps `content_key` for the holder of the secret half of `recipient`.
+    pub fn wrap(content_key: &[u8; 32], recipient: [u8; 32]) -> LargeDataResult<Self> {
+        let secret = EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral = PublicKey::from(&secret).to_bytes();
+        let shared = secret.diffie_hellman(&PublicKey::from(recipient));
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = wrapping_cipher(shared.as_bytes(), &ephemeral, &recipient)
+            .encrypt(Nonce::from_slice(&nonce), content_key.as_slice())
+            .map_err(|e| LargeDataError::Encryption(e.to_string()))?;
+        Ok(Self { recipient, ephemeral, nonce, ciphertext })
+    }
+
+    /// The content key, if `key` is the recipient's.
+    pub fn unwrap_with(&self, key: &RecipientKey) -> LargeDataResult<[u8; 32]> {
+        let shared = key.0.diffie_hellman(&PublicKey::from(self.ephemeral));
+        let bytes = wrapping_cipher(shared.as_bytes(), &self.ephemeral, &key.public())
+            .decrypt(Nonce::from_slice(&self.nonce), self.ciphertext.as_slice())
+            .map_err(|_| LargeDataError::Encryption("content key does not open".into()))?;
+        bytes
+            .try_into()
+            .map_err(|_| LargeDataError::Encryption("wrapped content key has the wrong length".into()))
+    }
+}
+
+/// How a chunk's stored bytes are encrypted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkEncryption {
+    pub algorithm: EncryptionAlgorithm,
+    /// The chunk's content key, once per authorized recipient.
+    pub keys: Vec<WrappedKey>,
+}
+
+impl ChunkEncryption {
+    /// The content key, if `key` is one of the recipients'.
+    pub fn content_key(&self, key: &RecipientKey) -> LargeDataResult<[u8; 32]> {
+        let public = key.public();
+        self.keys
+            .iter()
+            .find(|wrapped| wrapped.recipient == public)
+            .ok_or_else(|| LargeDataError::Encryption("not a recipient of this chunk".into()))?
+            .unwrap_with(key)
+    }
+}
+
+/// Encrypts chunks for this node and a set of other authorized recipients,
+/// and opens them again with this node's key.
+#[derive(Debug, Clone)]
+pub struct ChunkSealer {
+    algorithm: EncryptionAlgorithm,
+    key: RecipientKey,
+    recipients: Vec<[u8; 32]>,
+}
+
+impl ChunkSealer {
+    pub fn new(algorithm: EncryptionAlgorithm, key: RecipientKey) -> Self {
+        let recipients = vec![key.public()];
+        Self { algorithm, key, recipients }
+    }
+
+    /// A sealer if `config` asks for chunk encryption at rest.
+    pub fn from_config(config: &EncryptionConfig, key: RecipientKey) -> Option<Self> {
+        let wanted = config.enabled
+            && config.chunk_encryption
+            && config.algorithm != EncryptionAlgorithm::None;
+        wanted.then(|| Self::new(config.algorithm, key))
+    }
+
+    /// Also lets the holder of `recipient` open the chunks.
+    pub fn with_recipient(mut self, recipient: [u8; 32]) -> Self {
+        if !self.recipients.contains(&recipient) {
+            self.recipients.push(recipient);
+        }
+        self
+    }
+
+    pub fn algorithm(&self) -> EncryptionAlgorithm {
+        self.algorithm
+    }
+
+    pub fn recipients(&self) -> &[[u8; 32]] {
+        &self.recipients
+    }
+
+    /// `chunk` encrypted under a fresh content key. Chunks already
+    /// encrypted are returned as they are.
+    pub fn seal(&self, chunk: DataChunk) -> LargeDataResult<DataChunk> {
+        if chunk.is_encrypted() {
+            return Ok(chunk);
+        }
+        chunk.seal(self.algorithm, &self.recipients)
+    }
+
+    /// `chunk` decrypted with this node's key; plain chunks are returned as
+    /// they are.
+    pub fn open(&self, chunk: DataChunk) -> LargeDataResult<DataChunk> {
+        if !chunk.is_encrypted() {
+            return Ok(chunk);
+        }
+        chunk.open(&self.key)
+    }
 }
@@ -1,6 +1,7 @@
 //! Placeholder implementation for LargeDataDescriptor until full module is restored.
 
 use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
 
 /// Lightweight metadata describing a large data object being transferred.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,4 +18,13 @@ impl LargeDataDescriptor {
     pub fn new(id: String, content_hash: String, size_bytes: u64, chunk_hashes: Vec<String>) -> Self {
         Self { id, content_hash, size_bytes, chunk_hashes }
     }
+
+    /// Indices of the chunks `base` does not have: all a holder of `base`
+    /// needs to transfer to hold this version too.
+    pub fn delta_from(&self, base: &LargeDataDescriptor) -> Vec<u32> {
+        let known: HashSet<&String> = base.chunk_hashes.iter().collect();
+        (0..self.chunk_hashes.len() as u32)
+            .filter(|i| !known.contains(&self.chunk_hashes[*i as usize]))
+            .collect()
+    }
 }
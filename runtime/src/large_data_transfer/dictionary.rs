@@ -0,0 +1,93 @@
+//! Trained zstd dictionaries per content type.
+//!
+//! Chunks of logs and tables are small and look alike, so most of what zstd
+//! learns from one chunk is lost by the next. A dictionary trained on
+//! samples of a content type primes the compressor with what such content
+//! usually contains. [`CompressionAlgorithm::ZstdDict`] chunks name the
+//! dictionary they were compressed with, so decompressing one needs that
+//! dictionary installed: all nodes exchanging such chunks must
+//! [`install`] the same dictionaries, and keep retired ones for old data.
+//!
+//! [`CompressionAlgorithm::ZstdDict`]: super::config::CompressionAlgorithm::ZstdDict
+
+use crate::large_data_transfer::{
+    config::ContentType,
+    error::{LargeDataError, LargeDataResult},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+/// A trained dictionary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dictionary {
+    pub content_type: ContentType,
+    /// Derived from the contents; never 0, which marks data compressed
+    /// without a dictionary.
+    pub id: u32,
+    pub bytes: Vec<u8>,
+}
+
+impl Dictionary {
+    pub fn new(content_type: ContentType, bytes: Vec<u8>) -> Self {
+        let digest = Sha256::digest(&bytes);
+        let id = u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]]).max(1);
+        Self { content_type, id, bytes }
+    }
+
+    /// Trains a dictionary of at most `max_size` bytes on `samples`, pieces
+    /// of data the size of a chunk or smaller.
+    pub fn train<S: AsRef<[u8]>>(
+        content_type: ContentType,
+        samples: &[S],
+        max_size: usize,
+    ) -> LargeDataResult<Self> {
+        let bytes = zstd::dict::from_samples(samples, max_size)
+            .map_err(|e| LargeDataError::Compression(format!("dictionary training failed: {}", e)))?;
+        Ok(Self::new(content_type, bytes))
+    }
+}
+
+/// Dictionaries by id, and the one in use for each content type.
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryRegistry {
+    by_id: HashMap<u32, Arc<Dictionary>>,
+    active: HashMap<ContentType, u32>,
+}
+
+impl DictionaryRegistry {
+    /// Adds `dictionary` and makes it the one new data of its type is
+    /// compressed with. The dictionary it replaces stays available for
+    /// decompression.
+    pub fn insert(&mut self, dictionary: Dictionary) {
+        self.active.insert(dictionary.content_type, dictionary.id);
+        self.by_id.insert(dictionary.id, Arc::new(dictionary));
+    }
+
+    pub fn get(&self, id: u32) -> Option<Arc<Dictionary>> {
+        self.by_id.get(&id).cloned()
+    }
+
+    /// The dictionary new data of type `content` is compressed with.
+    pub fn for_content(&self, content: ContentType) -> Option<Arc<Dictionary>> {
+        self.active.get(&content).and_then(|id| self.get(*id))
+    }
+}
+
+static INSTALLED: RwLock<Option<Arc<DictionaryRegistry>>> = RwLock::new(None);
+
+/// Makes `registry` the process-wide set of dictionaries.
+pub fn install(registry: DictionaryRegistry) {
+    *INSTALLED.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(registry));
+}
+
+/// The installed dictionaries, or none if none were installed.
+pub fn registry() -> Arc<DictionaryRegistry> {
+    static EMPTY: OnceLock<Arc<DictionaryRegistry>> = OnceLock::new();
+    INSTALLED
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| EMPTY.get_or_init(Default::default).clone())
+}
@@ -33,6 +33,18 @@ pub enum LargeDataError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Erasure coding error: {0}")]
+    Erasure(#[from] crate::large_data_transfer::redundancy::ErasureError),
+
+    #[error("Namespace error: {0}")]
+    Namespace(#[from] crate::large_data_transfer::namespace::NamespaceError),
+
+    #[error("Version error: {0}")]
+    Version(#[from] crate::large_data_transfer::versions::VersionError),
+
+    #[error("Quota error: {0}")]
+    Quota(#[from] crate::large_data_transfer::quota::QuotaError),
 }
 
 impl From<TransferError> for LargeDataError {
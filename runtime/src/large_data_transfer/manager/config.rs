@@ -17,6 +17,9 @@ pub struct ChunkManagerConfig {
 
     /// Default time-to-live for a chunk in the cache.
     pub default_expiration: Duration,
+
+    /// Bytes of files each owner may pin; 0 for no limit.
+    pub pin_quota_bytes: u64,
 }
 
 impl Default for ChunkManagerConfig {
@@ -26,6 +29,7 @@ impl Default for ChunkManagerConfig {
             max_memory_bytes: 100 * 1024 * 1024, // 100MB
             cleanup_interval: Duration::from_secs(60),
             default_expiration: Duration::from_secs(3600), // 1 hour
+            pin_quota_bytes: 50 * 1024 * 1024, // 50MB
         }
     }
 }
@@ -30,9 +30,10 @@ impl ChunkManager {
         let mut memory_usage = self.memory_usage.lock().unwrap();
         let now = Instant::now();
         let mut removed_count = 0;
+        let pinned = self.pins.lock().unwrap().chunks();
 
-        chunks.retain(|_, entry| {
-            if entry.expiration.map_or(false, |exp| now > exp) {
+        chunks.retain(|id, entry| {
+            if entry.expiration.map_or(false, |exp| now > exp) && !pinned.contains(id) {
                 *memory_usage -= entry.chunk.size() as u64;
                 removed_count += 1;
                 false // Remove the entry
@@ -67,9 +68,12 @@ impl ChunkManager {
             return false;
         }
 
-        // Find the chunk with the oldest last_accessed time (LRU).
+        // Find the chunk with the oldest last_accessed time (LRU), sparing
+        // pinned ones.
+        let pinned = self.pins.lock().unwrap().chunks();
         let oldest_id = chunks
             .iter()
+            .filter(|(id, _)| !pinned.contains(*id))
             .min_by_key(|(_, entry)| entry.last_accessed)
             .map(|(id, _)| id.clone());
 
@@ -3,8 +3,11 @@
 use super::{
     config::ChunkManagerConfig,
     entry::ChunkEntry,
+    pins::PinSet,
 };
 use crate::large_data_transfer::chunk::ChunkId;
+use crate::large_data_transfer::crypto::{ChunkSealer, RecipientKey};
+use crate::large_data_transfer::LargeDataConfig;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -17,19 +20,52 @@ pub struct ChunkManager {
     pub(crate) chunks: Arc<Mutex<HashMap<ChunkId, ChunkEntry>>>,
     pub(super) memory_usage: Arc<Mutex<u64>>,
     pub(super) last_cleanup: Arc<Mutex<Instant>>,
+    /// Encrypts chunks on the way in and decrypts them on the way out.
+    pub(super) sealer: Option<Arc<ChunkSealer>>,
+    /// Files whose chunks are never evicted.
+    pub(super) pins: Arc<Mutex<PinSet>>,
 }
 
 impl ChunkManager {
     /// Create a new chunk manager with the given configuration.
     pub fn new(config: ChunkManagerConfig) -> Self {
         Self {
+            pins: Arc::new(Mutex::new(PinSet::new(config.pin_quota_bytes))),
             config,
             chunks: Arc::new(Mutex::new(HashMap::new())),
             memory_usage: Arc::new(Mutex::new(0)),
             last_cleanup: Arc::new(Mutex::new(Instant::now())),
+            sealer: None,
         }
     }
 
+    /// Keep chunks encrypted at rest with `sealer`; retrieval decrypts them.
+    pub fn with_encryption(mut self, sealer: ChunkSealer) -> Self {
+        self.sealer = Some(Arc::new(sealer));
+        self
+    }
+
+    /// Start with `pins`, e.g. as saved by an earlier run.
+    pub fn with_pins(mut self, pins: PinSet) -> Self {
+        self.pins = Arc::new(Mutex::new(pins));
+        self
+    }
+
+    /// A chunk manager for `config`, keeping chunks encrypted at rest for
+    /// `key` when its encryption config asks for chunk encryption.
+    pub fn for_config(config: &LargeDataConfig, key: RecipientKey) -> Self {
+        let manager = Self::new(ChunkManagerConfig::from(config));
+        match ChunkSealer::from_config(&config.encryption_config, key) {
+            Some(sealer) => manager.with_encryption(sealer),
+            None => manager,
+        }
+    }
+
+    /// Whether stored chunks are kept encrypted.
+    pub fn encrypts_at_rest(&self) -> bool {
+        self.sealer.is_some()
+    }
+
     /// Create a new chunk manager with a default configuration.
     pub fn default() -> Self {
         Self::new(ChunkManagerConfig::default())
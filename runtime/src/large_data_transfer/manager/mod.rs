@@ -8,6 +8,8 @@ mod entry;
 mod eviction;
 mod info;
 mod manager;
+mod pins;
+mod scrub;
 mod stats;
 mod storage;
 mod retrieval;
@@ -17,4 +19,5 @@ mod tests;
 
 pub use config::ChunkManagerConfig;
 pub use manager::ChunkManager;
+pub use pins::{Pin, PinSet};
 pub use stats::ChunkManagerStats; 
\ No newline at end of file
@@ -0,0 +1,124 @@
+//! Pinning files so their chunks stay cached.
+//!
+//! A pinned file's chunks are never evicted to make room and never expire,
+//! which inference nodes rely on to keep model weights resident. Every owner
+//! may pin up to a quota of bytes, so one user cannot pin the whole cache.
+
+use super::manager::ChunkManager;
+use crate::large_data_transfer::{
+    chunk::ChunkId, descriptor::LargeDataDescriptor, LargeDataError, LargeDataResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A file kept cached for an owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    /// Id of the file's descriptor.
+    pub file_hash: String,
+    pub owner: String,
+    pub chunks: Vec<ChunkId>,
+    pub size_bytes: u64,
+    /// Unix time it was pinned.
+    pub pinned_at: u64,
+}
+
+/// The pins of a node and the quota they are held to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PinSet {
+    /// Bytes each owner may pin; 0 for no limit.
+    pub quota_bytes: u64,
+    /// Pins by owner, then file.
+    pins: BTreeMap<String, BTreeMap<String, Pin>>,
+}
+
+impl PinSet {
+    pub fn new(quota_bytes: u64) -> Self {
+        Self { quota_bytes, pins: BTreeMap::new() }
+    }
+
+    /// Bytes `owner` has pinned.
+    pub fn used_by(&self, owner: &str) -> u64 {
+        self.pins.get(owner).map_or(0, |pins| pins.values().map(|pin| pin.size_bytes).sum())
+    }
+
+    /// Pins the file `descriptor` describes for `owner`. Pinning a file
+    /// again only refreshes it.
+    pub fn pin(&mut self, owner: &str, descriptor: &LargeDataDescriptor) -> LargeDataResult<&Pin> {
+        let held = self
+            .pins
+            .get(owner)
+            .and_then(|pins| pins.get(&descriptor.id))
+            .map_or(0, |pin| pin.size_bytes);
+        let used = self.used_by(owner) - held;
+        if self.quota_bytes > 0 && used + descriptor.size_bytes > self.quota_bytes {
+            return Err(LargeDataError::Cache(format!(
+                "pinning {} needs {} bytes, {} has {} of its {} byte quota left",
+                descriptor.id,
+                descriptor.size_bytes,
+                owner,
+                self.quota_bytes.saturating_sub(used),
+                self.quota_bytes
+            )));
+        }
+        let chunks = descriptor
+            .chunk_hashes
+            .iter()
+            .map(|hash| ChunkId::from_hex(hash))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pin = Pin {
+            file_hash: descriptor.id.clone(),
+            owner: owner.to_string(),
+            chunks,
+            size_bytes: descriptor.size_bytes,
+            pinned_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        let pins = self.pins.entry(owner.to_string()).or_default();
+        pins.insert(descriptor.id.clone(), pin);
+        Ok(&pins[&descriptor.id])
+    }
+
+    /// Releases `owner`'s pin of `file_hash`, if any.
+    pub fn unpin(&mut self, owner: &str, file_hash: &str) -> bool {
+        let Some(pins) = self.pins.get_mut(owner) else { return false };
+        let removed = pins.remove(file_hash).is_some();
+        if pins.is_empty() {
+            self.pins.remove(owner);
+        }
+        removed
+    }
+
+    /// All pins, by owner and file.
+    pub fn pins(&self) -> impl Iterator<Item = &Pin> {
+        self.pins.values().flat_map(BTreeMap::values)
+    }
+
+    /// The chunks of every pinned file.
+    pub fn chunks(&self) -> HashSet<ChunkId> {
+        self.pins().flat_map(|pin| pin.chunks.iter().cloned()).collect()
+    }
+}
+
+impl ChunkManager {
+    /// Keep the chunks of `descriptor`'s file cached for `owner`, whether
+    /// they are already stored or arrive later.
+    pub fn pin(&self, owner: &str, descriptor: &LargeDataDescriptor) -> LargeDataResult<Pin> {
+        self.pins.lock().unwrap().pin(owner, descriptor).cloned()
+    }
+
+    /// Let the chunks of `file_hash` be evicted again unless pinned by
+    /// another owner.
+    pub fn unpin(&self, owner: &str, file_hash: &str) -> bool {
+        self.pins.lock().unwrap().unpin(owner, file_hash)
+    }
+
+    /// The current pins.
+    pub fn pins(&self) -> PinSet {
+        self.pins.lock().unwrap().clone()
+    }
+
+    pub fn is_pinned(&self, chunk_id: &ChunkId) -> bool {
+        self.pins.lock().unwrap().pins().any(|pin| pin.chunks.contains(chunk_id))
+    }
+}
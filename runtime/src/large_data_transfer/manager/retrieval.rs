@@ -3,14 +3,33 @@ use crate::large_data_transfer::chunk::{ChunkId, DataChunk};
 use std::time::Instant;
 
 impl ChunkManager {
-    /// Retrieves a chunk from the cache.
-    /// This updates the chunk's last access time for LRU eviction.
+    /// Retrieves a chunk from the cache, decrypted if it is kept encrypted.
+    /// This updates the chunk's last access time for LRU eviction. A chunk
+    /// that fails to decrypt is treated as missing.
     pub fn get_chunk(&self, chunk_id: &ChunkId) -> Option<DataChunk> {
+        let chunk = self.get_stored_chunk(chunk_id)?;
+        match &self.sealer {
+            Some(sealer) => match sealer.open(chunk) {
+                Ok(chunk) => Some(chunk),
+                Err(e) => {
+                    tracing::warn!(chunk = %chunk_id, error = %e, "stored chunk does not decrypt");
+                    None
+                }
+            },
+            None => Some(chunk),
+        }
+    }
+
+    /// Retrieves a chunk as it is stored, still encrypted if it is kept
+    /// encrypted, e.g. to replicate it to another recipient.
+    pub fn get_stored_chunk(&self, chunk_id: &ChunkId) -> Option<DataChunk> {
         let mut chunks = self.chunks.lock().unwrap();
 
         if let Some(entry) = chunks.get_mut(chunk_id) {
-            // Do not return expired chunks.
-            if entry.expiration.map_or(false, |exp| Instant::now() > exp) {
+            // Do not return expired chunks unless they are pinned.
+            if entry.expiration.map_or(false, |exp| Instant::now() > exp)
+                && !self.is_pinned(chunk_id)
+            {
                 return None;
             }
 
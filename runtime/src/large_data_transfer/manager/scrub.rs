@@ -0,0 +1,50 @@
+use super::manager::ChunkManager;
+use crate::large_data_transfer::{
+    chunk::{ChunkId, DataChunk},
+    scrub::{rehash_matches, ChunkHealth, ScrubTarget},
+};
+use std::time::Instant;
+
+impl ScrubTarget for ChunkManager {
+    fn disk(&self) -> String {
+        "memory".to_string()
+    }
+
+    fn chunk_ids(&self) -> Vec<ChunkId> {
+        self.chunks.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Reads the entry without counting it as an access, so scrubbing does
+    /// not keep cold chunks from being evicted. Encrypted chunks are checked
+    /// by decrypting them.
+    fn check(&self, id: &ChunkId) -> ChunkHealth {
+        let chunk = {
+            let chunks = self.chunks.lock().unwrap();
+            match chunks.get(id) {
+                Some(entry)
+                    if !entry.expiration.is_some_and(|exp| Instant::now() > exp)
+                        || self.is_pinned(id) =>
+                {
+                    entry.chunk.clone()
+                }
+                _ => return ChunkHealth::Missing,
+            }
+        };
+        let plain = match &self.sealer {
+            Some(sealer) => sealer.open(chunk).ok(),
+            None => Some(chunk),
+        };
+        if plain.is_some_and(|chunk| rehash_matches(&chunk, id)) {
+            ChunkHealth::Intact
+        } else {
+            ChunkHealth::Corrupt
+        }
+    }
+
+    fn take(&self, id: &ChunkId) -> Option<DataChunk> {
+        let mut chunks = self.chunks.lock().unwrap();
+        let entry = chunks.remove(id)?;
+        *self.memory_usage.lock().unwrap() -= entry.chunk.size() as u64;
+        Some(entry.chunk)
+    }
+}
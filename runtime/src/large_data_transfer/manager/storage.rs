@@ -14,7 +14,12 @@ use std::time::Instant;
 impl ChunkManager {
     /// Stores a chunk in the cache.
     /// If the cache is full, it may evict other chunks based on the eviction policy.
+    /// With encryption configured the chunk is kept encrypted.
     pub fn store_chunk(&self, chunk: DataChunk) -> LargeDataResult<()> {
+        let chunk = match &self.sealer {
+            Some(sealer) => sealer.seal(chunk)?,
+            None => chunk,
+        };
         let chunk_id = chunk.id().clone();
         let chunk_size = chunk.size() as u64;
 
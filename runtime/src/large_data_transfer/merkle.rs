@@ -1,6 +1,14 @@
+//! Content hashes and Merkle trees over the chunk hashes of an object.
+//!
+//! A level with an odd number of nodes pairs its last node with itself.
+
 use sha2::{Digest, Sha256};
 
-pub(crate) fn calculate_content_hash(chunk_hashes: &[String]) -> String {
+fn hash_pair(left: &str, right: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("{}{}", left, right).as_bytes()))
+}
+
+pub fn calculate_content_hash(chunk_hashes: &[String]) -> String {
     let mut hasher = Sha256::new();
     for h in chunk_hashes {
         hasher.update(h.as_bytes());
@@ -8,7 +16,7 @@ pub(crate) fn calculate_content_hash(chunk_hashes: &[String]) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-pub(crate) fn build_merkle_root(chunk_hashes: &[String]) -> String {
+pub fn build_merkle_root(chunk_hashes: &[String]) -> String {
     if chunk_hashes.is_empty() {
         return String::new();
     }
@@ -19,15 +27,46 @@ pub(crate) fn build_merkle_root(chunk_hashes: &[String]) -> String {
     while level.len() > 1 {
         let mut next = Vec::new();
         for pair in level.chunks(2) {
-            let combined = if pair.len() == 2 {
-                format!("{}{}", pair[0], pair[1])
-            } else {
-                format!("{}{}", pair[0], pair[0])
-            };
-            let hash = Sha256::digest(combined.as_bytes());
-            next.push(format!("{:x}", hash));
+            next.push(hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])));
         }
         level = next;
     }
     level[0].clone()
-} 
\ No newline at end of file
+}
+
+/// The siblings on the path from leaf `index` to the root, bottom up, or
+/// `None` if there is no such leaf.
+pub fn merkle_proof(chunk_hashes: &[String], index: usize) -> Option<Vec<String>> {
+    if index >= chunk_hashes.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    let mut level = chunk_hashes.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling = if index % 2 == 0 { index + 1 } else { index - 1 };
+        proof.push(level.get(sibling).unwrap_or(&level[index]).clone());
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Whether `proof` leads from `leaf` at `index` to `root`.
+pub fn verify_merkle_proof(root: &str, leaf: &str, index: usize, proof: &[String]) -> bool {
+    if proof.len() < usize::BITS as usize && index >> proof.len() != 0 {
+        return false;
+    }
+    let mut hash = leaf.to_string();
+    for (level, sibling) in proof.iter().enumerate() {
+        hash = if (index >> level) % 2 == 0 {
+            hash_pair(&hash, sibling)
+        } else {
+            hash_pair(sibling, &hash)
+        };
+    }
+    hash == root
+}
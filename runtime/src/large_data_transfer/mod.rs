@@ -11,31 +11,49 @@
 //! - Encryption and security features
 
 pub mod cache;
+pub mod cdc;
 pub mod chunk;
 pub mod compression;
 pub mod config;
 pub mod crypto;
+pub mod dictionary;
 pub mod descriptor;
 pub mod error;
 pub mod manager;
+pub mod merkle;
 pub mod network;
 pub mod protocol;
 pub mod types;
 pub mod metadata;
+pub mod namespace;
 pub mod redundancy;
 pub mod pricing;
+pub mod quota;
+pub mod scrub;
+pub mod stream;
+pub mod versions;
 
 // Re-export core types
+pub use cdc::CdcConfig;
 pub use chunk::{ChunkId, ChunkInfo, DataChunk};
+pub use compression::AdaptiveCompressor;
+pub use crypto::{ChunkEncryption, ChunkSealer, RecipientKey, WrappedKey};
 pub use config::{
-    CacheConfig, CompressionConfig, EncryptionConfig, LargeDataConfig, RetryConfig,
+    CacheConfig, CompressionConfig, CompressionProfile, ContentType, EncryptionConfig,
+    LargeDataConfig, LevelSelection, QosConfig, RetryConfig,
 };
+pub use dictionary::{Dictionary, DictionaryRegistry};
 pub use descriptor::LargeDataDescriptor;
 pub use metadata::TransferMetadata;
-pub use redundancy::{RedundancyConfig, RedundancyPolicy};
+pub use namespace::{DirEntry, EntryKind, FileEntry, Namespace, NamespaceError, Permissions};
+pub use quota::{QuotaError, StorageQuota, TenantAccounts, TenantUsage};
+pub use redundancy::{ErasureError, ErasureScheme, RedundancyConfig, RedundancyPolicy};
     pub use pricing::{PriceQuote, quote as quote_price};
 pub use error::{LargeDataError, LargeDataResult};
-pub use manager::{ChunkManager, ChunkManagerConfig};
+pub use manager::{ChunkManager, ChunkManagerConfig, Pin, PinSet};
+pub use scrub::{AuditReport, DiskScrubStats, ScrubConfig, ScrubTarget, Scrubber};
+pub use stream::{ChunkReader, ChunkSink, ChunkSource, ChunkWriter, ConsistentSource};
+pub use versions::{FileVersion, VersionDiff, VersionError, VersionStore};
 
 
 pub use types::{TransferPriority, TransferStats}; 
\ No newline at end of file
@@ -0,0 +1,348 @@
+//! A directory hierarchy over stored objects.
+//!
+//! Objects are addressed by their [`LargeDataDescriptor`]; a [`Namespace`]
+//! gives them paths such as `/datasets/imagenet/train/shard-0`. Every
+//! directory carries default [`Permissions`] which a file takes when it is
+//! created in it and which a directory takes from its parent when none are
+//! given. Files keep their permissions when moved, so moving data does not
+//! silently widen or narrow who can read it.
+
+use super::descriptor::LargeDataDescriptor;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum NamespaceError {
+    #[error("invalid path {0}")]
+    InvalidPath(String),
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("{0} already exists")]
+    AlreadyExists(String),
+    #[error("{0} is not a directory")]
+    NotADirectory(String),
+    #[error("{0} is a directory")]
+    IsADirectory(String),
+    #[error("cannot move {0} into itself")]
+    MoveIntoSelf(String),
+    #[error("{user} may not read {path}")]
+    PermissionDenied { path: String, user: String },
+}
+
+/// Who may read and write an entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permissions {
+    /// May always read and write.
+    pub owner: Option<String>,
+    /// Anyone may read.
+    pub public_read: bool,
+    pub readers: BTreeSet<String>,
+    pub writers: BTreeSet<String>,
+}
+
+impl Permissions {
+    /// Readable by anyone, writable by `owner` only.
+    pub fn public(owner: impl Into<String>) -> Self {
+        Self { owner: Some(owner.into()), public_read: true, ..Default::default() }
+    }
+
+    /// Readable and writable by `owner` only.
+    pub fn private(owner: impl Into<String>) -> Self {
+        Self { owner: Some(owner.into()), ..Default::default() }
+    }
+
+    pub fn with_reader(mut self, user: impl Into<String>) -> Self {
+        self.readers.insert(user.into());
+        self
+    }
+
+    pub fn with_writer(mut self, user: impl Into<String>) -> Self {
+        self.writers.insert(user.into());
+        self
+    }
+
+    fn is_owner(&self, user: &str) -> bool {
+        self.owner.as_deref() == Some(user)
+    }
+
+    pub fn can_read(&self, user: &str) -> bool {
+        self.public_read
+            || self.is_owner(user)
+            || self.readers.contains(user)
+            || self.can_write(user)
+    }
+
+    pub fn can_write(&self, user: &str) -> bool {
+        self.is_owner(user) || self.writers.contains(user)
+    }
+}
+
+/// A stored object at a path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub descriptor: LargeDataDescriptor,
+    pub permissions: Permissions,
+}
+
+/// A directory and the defaults of what is created in it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Directory {
+    pub permissions: Permissions,
+    children: BTreeMap<String, Node>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Node {
+    Directory(Directory),
+    File(FileEntry),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryKind {
+    Directory,
+    File,
+}
+
+/// An entry of a directory listing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirEntry {
+    pub name: String,
+    pub kind: EntryKind,
+    /// Size of the object; 0 for directories.
+    pub size_bytes: u64,
+}
+
+/// Splits an absolute path into its components.
+fn components(path: &str) -> Result<Vec<&str>, NamespaceError> {
+    let invalid = || NamespaceError::InvalidPath(path.to_string());
+    let rest = path.strip_prefix('/').ok_or_else(invalid)?;
+    let parts: Vec<&str> = rest.split('/').filter(|part| !part.is_empty()).collect();
+    if parts.iter().any(|part| *part == "." || *part == "..") {
+        return Err(invalid());
+    }
+    Ok(parts)
+}
+
+/// The path of `parts`, for errors.
+fn join(parts: &[&str]) -> String {
+    format!("/{}", parts.join("/"))
+}
+
+/// `path` without empty components, e.g. `/a/b` for `/a//b/`.
+pub(crate) fn canonical(path: &str) -> Result<String, NamespaceError> {
+    Ok(join(&components(path)?))
+}
+
+/// The tree of directories and files of a node's storage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Namespace {
+    root: Directory,
+}
+
+impl Namespace {
+    /// An empty namespace whose root directory has `permissions`.
+    pub fn new(permissions: Permissions) -> Self {
+        Self { root: Directory { permissions, children: BTreeMap::new() } }
+    }
+
+    fn dir(&self, parts: &[&str]) -> Result<&Directory, NamespaceError> {
+        let mut dir = &self.root;
+        for (i, part) in parts.iter().enumerate() {
+            dir = match dir.children.get(*part) {
+                Some(Node::Directory(child)) => child,
+                Some(Node::File(_)) => {
+                    return Err(NamespaceError::NotADirectory(join(&parts[..=i])))
+                }
+                None => return Err(NamespaceError::NotFound(join(&parts[..=i]))),
+            };
+        }
+        Ok(dir)
+    }
+
+    fn dir_mut(&mut self, parts: &[&str]) -> Result<&mut Directory, NamespaceError> {
+        let mut dir = &mut self.root;
+        for (i, part) in parts.iter().enumerate() {
+            dir = match dir.children.get_mut(*part) {
+                Some(Node::Directory(child)) => child,
+                Some(Node::File(_)) => {
+                    return Err(NamespaceError::NotADirectory(join(&parts[..=i])))
+                }
+                None => return Err(NamespaceError::NotFound(join(&parts[..=i]))),
+            };
+        }
+        Ok(dir)
+    }
+
+    /// The directory at `path`.
+    pub fn directory(&self, path: &str) -> Result<&Directory, NamespaceError> {
+        self.dir(&components(path)?)
+    }
+
+    /// Creates the directory `path` and any missing parents. Directories
+    /// created without `permissions` take their parent's.
+    pub fn mkdir(
+        &mut self,
+        path: &str,
+        permissions: Option<Permissions>,
+    ) -> Result<(), NamespaceError> {
+        let parts = components(path)?;
+        let Some((last, parents)) = parts.split_last() else {
+            return Err(NamespaceError::AlreadyExists(path.to_string()));
+        };
+        let mut dir = &mut self.root;
+        for (i, part) in parents.iter().enumerate() {
+            let inherited = dir.permissions.clone();
+            let child = dir.children.entry(part.to_string()).or_insert_with(|| {
+                Node::Directory(Directory { permissions: inherited, children: BTreeMap::new() })
+            });
+            dir = match child {
+                Node::Directory(child) => child,
+                Node::File(_) => return Err(NamespaceError::NotADirectory(join(&parts[..=i]))),
+            };
+        }
+        if dir.children.contains_key(*last) {
+            return Err(NamespaceError::AlreadyExists(join(&parts)));
+        }
+        let permissions = permissions.unwrap_or_else(|| dir.permissions.clone());
+        dir.children.insert(
+            last.to_string(),
+            Node::Directory(Directory { permissions, children: BTreeMap::new() }),
+        );
+        Ok(())
+    }
+
+    /// Stores `descriptor` at `path` with the default permissions of its
+    /// directory, which must exist. An existing file is replaced, keeping
+    /// its permissions.
+    pub fn put_file(
+        &mut self,
+        path: &str,
+        descriptor: LargeDataDescriptor,
+    ) -> Result<&FileEntry, NamespaceError> {
+        let parts = components(path)?;
+        let (last, parents) =
+            parts.split_last().ok_or_else(|| NamespaceError::IsADirectory(path.to_string()))?;
+        let dir = self.dir_mut(parents)?;
+        let permissions = match dir.children.get(*last) {
+            Some(Node::Directory(_)) => return Err(NamespaceError::IsADirectory(join(&parts))),
+            Some(Node::File(file)) => file.permissions.clone(),
+            None => dir.permissions.clone(),
+        };
+        dir.children.insert(last.to_string(), Node::File(FileEntry { descriptor, permissions }));
+        match &dir.children[*last] {
+            Node::File(file) => Ok(file),
+            Node::Directory(_) => unreachable!("a file was just inserted"),
+        }
+    }
+
+    /// The file at `path`.
+    pub fn file(&self, path: &str) -> Result<&FileEntry, NamespaceError> {
+        let parts = components(path)?;
+        let (last, parents) =
+            parts.split_last().ok_or_else(|| NamespaceError::IsADirectory(path.to_string()))?;
+        match self.dir(parents)?.children.get(*last) {
+            Some(Node::File(file)) => Ok(file),
+            Some(Node::Directory(_)) => Err(NamespaceError::IsADirectory(join(&parts))),
+            None => Err(NamespaceError::NotFound(join(&parts))),
+        }
+    }
+
+    /// The file at `path`, if `user` may read it.
+    pub fn file_for(&self, path: &str, user: &str) -> Result<&FileEntry, NamespaceError> {
+        let file = self.file(path)?;
+        if !file.permissions.can_read(user) {
+            return Err(NamespaceError::PermissionDenied {
+                path: path.to_string(),
+                user: user.to_string(),
+            });
+        }
+        Ok(file)
+    }
+
+    /// The entries of the directory at `path`, by name.
+    pub fn list(&self, path: &str) -> Result<Vec<DirEntry>, NamespaceError> {
+        let dir = self.directory(path)?;
+        Ok(dir
+            .children
+            .iter()
+            .map(|(name, node)| match node {
+                Node::Directory(_) => {
+                    DirEntry { name: name.clone(), kind: EntryKind::Directory, size_bytes: 0 }
+                }
+                Node::File(file) => DirEntry {
+                    name: name.clone(),
+                    kind: EntryKind::File,
+                    size_bytes: file.descriptor.size_bytes,
+                },
+            })
+            .collect())
+    }
+
+    /// Sets the permissions of the file or directory at `path`. For a
+    /// directory these are the defaults of what is created in it from now on.
+    pub fn set_permissions(
+        &mut self,
+        path: &str,
+        permissions: Permissions,
+    ) -> Result<(), NamespaceError> {
+        let parts = components(path)?;
+        let Some((last, parents)) = parts.split_last() else {
+            self.root.permissions = permissions;
+            return Ok(());
+        };
+        match self.dir_mut(parents)?.children.get_mut(*last) {
+            Some(Node::Directory(dir)) => dir.permissions = permissions,
+            Some(Node::File(file)) => file.permissions = permissions,
+            None => return Err(NamespaceError::NotFound(join(&parts))),
+        }
+        Ok(())
+    }
+
+    /// Moves the entry at `from` to the path `to`, whose parent must exist
+    /// and which must be free. Renaming is a move within one directory.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<(), NamespaceError> {
+        let source = components(from)?;
+        let target = components(to)?;
+        let (Some((name, source_dir)), Some((new_name, target_dir))) =
+            (source.split_last(), target.split_last())
+        else {
+            return Err(NamespaceError::InvalidPath("/".to_string()));
+        };
+        if target.len() > source.len() && target.starts_with(&source) {
+            return Err(NamespaceError::MoveIntoSelf(join(&source)));
+        }
+        if !self.dir(source_dir)?.children.contains_key(*name) {
+            return Err(NamespaceError::NotFound(join(&source)));
+        }
+        if self.dir(target_dir)?.children.contains_key(*new_name) {
+            return Err(NamespaceError::AlreadyExists(join(&target)));
+        }
+        let node = self.dir_mut(source_dir)?.children.remove(*name).expect("checked above");
+        self.dir_mut(target_dir)?.children.insert(new_name.to_string(), node);
+        Ok(())
+    }
+
+    /// Moves the entry at `from` into the directory `dir`, keeping its name.
+    pub fn move_into(&mut self, from: &str, dir: &str) -> Result<(), NamespaceError> {
+        let name = components(from)?
+            .last()
+            .ok_or_else(|| NamespaceError::InvalidPath(from.to_string()))?
+            .to_string();
+        let mut target = components(dir)?;
+        target.push(&name);
+        self.rename(from, &join(&target))
+    }
+
+    /// Removes the file or, with everything in it, the directory at `path`.
+    pub fn remove(&mut self, path: &str) -> Result<(), NamespaceError> {
+        let parts = components(path)?;
+        let (last, parents) =
+            parts.split_last().ok_or_else(|| NamespaceError::InvalidPath(path.to_string()))?;
+        self.dir_mut(parents)?
+            .children
+            .remove(*last)
+            .map(|_| ())
+            .ok_or_else(|| NamespaceError::NotFound(join(&parts)))
+    }
+}
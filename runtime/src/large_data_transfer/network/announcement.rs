@@ -0,0 +1,144 @@
+//! Descriptor-only gossip for large payloads.
+//!
+//! Models and metrics are too large to flood through the gossip mesh, where
+//! every peer would receive one copy per mesh neighbour. Publishers instead
+//! gossip a [`PayloadDescriptor`] and serve the body on request; receivers
+//! fetch each body once, however many peers announce it, with at most
+//! `max_concurrent` fetches in flight and the rest queued.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+
+/// What gets gossiped in place of a payload body.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PayloadDescriptor {
+    /// Hex SHA-256 of the body.
+    pub hash: String,
+    pub size: u64,
+}
+
+impl PayloadDescriptor {
+    pub fn for_body(body: &[u8]) -> Self {
+        Self { hash: hex::encode(Sha256::digest(body)), size: body.len() as u64 }
+    }
+
+    /// Whether `body` is the payload this descriptor announces.
+    pub fn matches(&self, body: &[u8]) -> bool {
+        body.len() as u64 == self.size && hex::encode(Sha256::digest(body)) == self.hash
+    }
+}
+
+/// How [`PayloadFetcher::fetch`] handled a request for a body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchStart<P, W> {
+    /// The body is already held locally; the waiter is handed back.
+    Cached(Vec<u8>, Option<W>),
+    /// A fetch for the same body is in flight or queued; the waiter joined it.
+    Joined,
+    /// The fetch limit is reached; the request starts when a slot frees up.
+    Queued,
+    /// Request the body with this hash from `peer` now.
+    Send { peer: P, hash: String },
+}
+
+/// Outcome of a fetch, see [`PayloadFetcher::finish`].
+#[derive(Debug)]
+pub struct Finished<P, W> {
+    /// The verified body, or `None` if the peer failed to supply it.
+    pub body: Option<Vec<u8>>,
+    /// Everyone waiting on the body.
+    pub waiters: Vec<W>,
+    /// A queued fetch that now has a free slot.
+    pub next: Option<(P, String)>,
+}
+
+#[derive(Debug)]
+struct Fetch<W> {
+    descriptor: PayloadDescriptor,
+    waiters: Vec<W>,
+}
+
+/// Local payload bodies plus the fetches for bodies announced by peers.
+///
+/// Generic over the peer id `P` and the waiter `W` notified on completion,
+/// so the bookkeeping is independent of the transport.
+#[derive(Debug)]
+pub struct PayloadFetcher<P, W> {
+    max_concurrent: usize,
+    bodies: HashMap<String, Vec<u8>>,
+    fetches: HashMap<String, Fetch<W>>,
+    queue: VecDeque<(P, String)>,
+    active: usize,
+}
+
+impl<P, W> PayloadFetcher<P, W> {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            bodies: HashMap::new(),
+            fetches: HashMap::new(),
+            queue: VecDeque::new(),
+            active: 0,
+        }
+    }
+
+    /// Stores a body to serve to peers and returns its descriptor to gossip.
+    pub fn publish(&mut self, body: Vec<u8>) -> PayloadDescriptor {
+        let descriptor = PayloadDescriptor::for_body(&body);
+        self.bodies.insert(descriptor.hash.clone(), body);
+        descriptor
+    }
+
+    pub fn body(&self, hash: &str) -> Option<&Vec<u8>> {
+        self.bodies.get(hash)
+    }
+
+    /// The descriptor of a body being fetched or queued.
+    pub fn descriptor(&self, hash: &str) -> Option<&PayloadDescriptor> {
+        self.fetches.get(hash).map(|f| &f.descriptor)
+    }
+
+    /// Fetches currently waiting on a peer.
+    pub fn in_flight(&self) -> usize {
+        self.active
+    }
+
+    /// Requests the body announced by `peer`. Only the first request for a
+    /// body reaches the network; later ones join it.
+    pub fn fetch(&mut self, peer: P, descriptor: PayloadDescriptor, waiter: Option<W>) -> FetchStart<P, W> {
+        if let Some(body) = self.bodies.get(&descriptor.hash) {
+            return FetchStart::Cached(body.clone(), waiter);
+        }
+        if let Some(fetch) = self.fetches.get_mut(&descriptor.hash) {
+            fetch.waiters.extend(waiter);
+            return FetchStart::Joined;
+        }
+        let hash = descriptor.hash.clone();
+        self.fetches.insert(hash.clone(), Fetch { descriptor, waiters: waiter.into_iter().collect() });
+        if self.active >= self.max_concurrent {
+            self.queue.push_back((peer, hash));
+            return FetchStart::Queued;
+        }
+        self.active += 1;
+        FetchStart::Send { peer, hash }
+    }
+
+    /// Completes the in-flight fetch of `hash` with the body the peer sent,
+    /// if any. A body that does not match its descriptor counts as missing.
+    pub fn finish(&mut self, hash: &str, body: Option<Vec<u8>>) -> Finished<P, W> {
+        let Some(fetch) = self.fetches.remove(hash) else {
+            return Finished { body: None, waiters: Vec::new(), next: None };
+        };
+        let body = body.filter(|b| fetch.descriptor.matches(b));
+        if let Some(body) = &body {
+            self.bodies.insert(fetch.descriptor.hash, body.clone());
+        }
+        self.active = self.active.saturating_sub(1);
+        let next = self.queue.pop_front();
+        if next.is_some() {
+            self.active += 1;
+        }
+        Finished { body, waiters: fetch.waiters, next }
+    }
+}
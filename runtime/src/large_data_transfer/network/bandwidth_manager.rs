@@ -1,8 +1,18 @@
 //! Manages network bandwidth allocation, tracking, and limiting.
+//!
+//! [`BandwidthLimiter`] paces transfers under global and per-peer caps.
+//! Concurrent transfers in one direction split the global cap evenly, and
+//! transfers to the same peer split that peer's cap, so one large transfer
+//! cannot starve the others. Each transfer is paced on its own clock: a
+//! message waits until the transfer's share of the cap has paid for it.
 
 use super::coordinator::NetworkTransferCoordinator;
+use super::models::{BandwidthConfig, BandwidthCounters, Direction, PeerBandwidth};
 use crate::large_data_transfer::LargeDataResult;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
 
 impl NetworkTransferCoordinator {
     /// Periodically monitors and logs bandwidth usage.
@@ -62,3 +72,131 @@ impl NetworkTransferCoordinator {
         Ok(true)
     }
 }
+
+/// Identifies a transfer opened with [`BandwidthLimiter::start`].
+pub type TransferId = u64;
+
+#[derive(Debug)]
+struct Transfer<P> {
+    peer: P,
+    direction: Direction,
+    /// When the bytes reserved so far have been paid for.
+    paid_until: Option<Instant>,
+}
+
+/// Fair-share pacing and byte counters for transfers to and from peers.
+///
+/// Generic over the peer id `P`, like
+/// [`PayloadFetcher`](super::PayloadFetcher), so it is independent of the
+/// transport. Time is passed in, which keeps the pacing deterministic.
+#[derive(Debug)]
+pub struct BandwidthLimiter<P> {
+    config: BandwidthConfig,
+    transfers: HashMap<TransferId, Transfer<P>>,
+    next_id: TransferId,
+    peers: HashMap<P, PeerBandwidth>,
+    throttled: u64,
+    throttled_for: Duration,
+}
+
+impl<P: Clone + Eq + Hash> BandwidthLimiter<P> {
+    pub fn new(config: BandwidthConfig) -> Self {
+        Self {
+            config,
+            transfers: HashMap::new(),
+            next_id: 0,
+            peers: HashMap::new(),
+            throttled: 0,
+            throttled_for: Duration::ZERO,
+        }
+    }
+
+    pub fn config(&self) -> &BandwidthConfig {
+        &self.config
+    }
+
+    /// Opens a transfer with `peer`; it takes a share of the caps until
+    /// [`finish`](Self::finish)ed.
+    pub fn start(&mut self, peer: P, direction: Direction) -> TransferId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.transfers.insert(id, Transfer { peer, direction, paid_until: None });
+        id
+    }
+
+    pub fn finish(&mut self, id: TransferId) {
+        self.transfers.remove(&id);
+    }
+
+    /// Transfers open in `direction`.
+    pub fn active(&self, direction: Direction) -> usize {
+        self.transfers.values().filter(|t| t.direction == direction).count()
+    }
+
+    /// Bytes per second transfer `id` may use: its even share of the global
+    /// cap and of its peer's cap, whichever is lower. `None` when neither
+    /// cap is set or the transfer is unknown.
+    pub fn share(&self, id: TransferId) -> Option<u64> {
+        let transfer = self.transfers.get(&id)?;
+        let (global, per_peer) = match transfer.direction {
+            Direction::Upload => (self.config.max_upload, self.config.peer_upload),
+            Direction::Download => (self.config.max_download, self.config.peer_download),
+        };
+        let same_peer = self
+            .transfers
+            .values()
+            .filter(|t| t.direction == transfer.direction && t.peer == transfer.peer)
+            .count() as u64;
+        let global = (global > 0).then(|| global / self.active(transfer.direction) as u64);
+        let per_peer = (per_peer > 0).then(|| per_peer / same_peer);
+        match (global, per_peer) {
+            (Some(a), Some(b)) => Some(a.min(b).max(1)),
+            (a, b) => a.or(b).map(|rate| rate.max(1)),
+        }
+    }
+
+    /// Reserves `bytes` of transfer `id` at `now` and returns how long to
+    /// hold them back. The share is taken at the time of the call, so a
+    /// transfer is never sped up by others finishing after it reserved.
+    pub fn reserve(&mut self, id: TransferId, bytes: u64, now: Instant) -> Duration {
+        let Some(rate) = self.share(id) else { return Duration::ZERO };
+        let Some(transfer) = self.transfers.get_mut(&id) else { return Duration::ZERO };
+        let from = transfer.paid_until.map_or(now, |t| t.max(now));
+        let until = from + Duration::from_secs_f64(bytes as f64 / rate as f64);
+        transfer.paid_until = Some(until);
+        let wait = until.saturating_duration_since(now);
+        if !wait.is_zero() {
+            self.throttled += 1;
+            self.throttled_for += wait;
+        }
+        wait
+    }
+
+    /// Counts `bytes` moved to or from `peer`.
+    pub fn record(&mut self, peer: P, direction: Direction, bytes: u64) {
+        let totals = self.peers.entry(peer).or_default();
+        match direction {
+            Direction::Upload => totals.sent += bytes,
+            Direction::Download => totals.received += bytes,
+        }
+    }
+
+    pub fn peer(&self, peer: &P) -> Option<&PeerBandwidth> {
+        self.peers.get(peer)
+    }
+}
+
+impl<P: Clone + Eq + Hash + Display> BandwidthLimiter<P> {
+    /// Totals, open transfers and per-peer counters for monitoring.
+    pub fn counters(&self) -> BandwidthCounters {
+        BandwidthCounters {
+            bytes_sent: self.peers.values().map(|p| p.sent).sum(),
+            bytes_received: self.peers.values().map(|p| p.received).sum(),
+            active_uploads: self.active(Direction::Upload),
+            active_downloads: self.active(Direction::Download),
+            throttled: self.throttled,
+            throttled_ms: self.throttled_for.as_millis() as u64,
+            peers: self.peers.iter().map(|(peer, totals)| (peer.to_string(), totals.clone())).collect(),
+        }
+    }
+}
@@ -1,15 +1,17 @@
 use crate::large_data_transfer::{
     chunk::{ChunkId, DataChunk},
     manager::ChunkManager,
-    protocol::ProtocolHandler,
+    protocol::{ProtocolHandler, ResumeStore},
     LargeDataConfig,
 };
 use crate::large_data_transfer::network::models::{
     BandwidthTracker, NetworkPeerInfo, NetworkTransferMessage,
 };
+use crate::large_data_transfer::network::progress::TransferEvent;
+use crate::large_data_transfer::network::scheduler::TransferScheduler;
 use dashmap::DashMap;
-use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, RwLock};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot, Notify, RwLock};
 
 /// Central coordinator shared across network tasks.
 pub struct NetworkTransferCoordinator {
@@ -23,6 +25,14 @@ pub struct NetworkTransferCoordinator {
     pub(crate) message_sender: mpsc::UnboundedSender<NetworkTransferMessage>,
     pub(crate) message_receiver: Arc<RwLock<mpsc::UnboundedReceiver<NetworkTransferMessage>>>,
     pub(crate) pending_responses: Arc<DashMap<ChunkId, oneshot::Sender<Option<DataChunk>>>>,
+    /// Where downloads persist their progress so they can be resumed.
+    pub(crate) resume_store: Option<Arc<ResumeStore>>,
+    /// Orders transfers by QoS class, see [`TransferScheduler`].
+    pub(crate) scheduler: Arc<Mutex<TransferScheduler>>,
+    /// Wakes transfers waiting for their turn.
+    pub(crate) scheduler_wake: Arc<Notify>,
+    /// Subscribers to transfer progress.
+    pub(crate) observers: Arc<Mutex<Vec<mpsc::UnboundedSender<TransferEvent>>>>,
 }
 
 impl NetworkTransferCoordinator {
@@ -40,6 +50,7 @@ impl NetworkTransferCoordinator {
             max_upload_mbps: (config.max_upload_rate / 1_000_000) as u32,
             max_download_mbps: (config.max_download_rate / 1_000_000) as u32,
         }));
+        let scheduler = Arc::new(Mutex::new(TransferScheduler::new(config.qos_config.clone())));
         Self {
             local_peer_id,
             config,
@@ -51,8 +62,19 @@ impl NetworkTransferCoordinator {
             message_sender: tx,
             message_receiver: Arc::new(RwLock::new(rx)),
             pending_responses: Arc::new(DashMap::new()),
+            resume_store: None,
+            scheduler,
+            scheduler_wake: Arc::new(Notify::new()),
+            observers: Arc::new(Mutex::new(Vec::new())),
         }
     }
+
+    /// Persists download progress to `store`, see
+    /// [`NetworkTransferCoordinator::resume_transfer`].
+    pub fn with_resume_store(mut self, store: Arc<ResumeStore>) -> Self {
+        self.resume_store = Some(store);
+        self
+    }
 }
 
 impl Clone for NetworkTransferCoordinator {
@@ -68,6 +90,10 @@ impl Clone for NetworkTransferCoordinator {
             message_sender: self.message_sender.clone(),
             message_receiver: self.message_receiver.clone(),
             pending_responses: self.pending_responses.clone(),
+            resume_store: self.resume_store.clone(),
+            scheduler: self.scheduler.clone(),
+            scheduler_wake: self.scheduler_wake.clone(),
+            observers: self.observers.clone(),
         }
     }
 }
@@ -15,6 +15,11 @@ impl NetworkTransferCoordinator {
             total_upload_mbps: bt.total_upload_mbps,
             total_download_mbps: bt.total_download_mbps,
             available_chunks: total_chunks,
+            corrupt_chunks_received: self
+                .peers
+                .iter()
+                .map(|p| p.value().transfer_stats.corrupt_chunks)
+                .sum(),
         }
     }
 } 
\ No newline at end of file
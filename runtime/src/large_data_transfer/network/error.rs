@@ -16,6 +16,10 @@ pub enum NetworkError {
     BandwidthLimitExceeded,
     #[error("Chunk routing failed: {0}")]
     ChunkRoutingFailed(String),
+    #[error("Chunk {chunk} confirmed by {confirmed} of the {required} providers required")]
+    InsufficientProviders { chunk: String, confirmed: usize, required: usize },
+    #[error("Chunk {chunk} unavailable after {attempts} attempts")]
+    ChunkUnavailable { chunk: String, attempts: u32 },
     #[error("Large data error: {0}")]
     LargeData(#[from] LargeDataError),
 }
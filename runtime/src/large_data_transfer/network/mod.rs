@@ -4,13 +4,22 @@
 //! with the underlying P2P network, handling peer management, chunk routing,
 //! and bandwidth control.
 
+pub mod announcement;
 pub mod coordinator;
 pub mod error;
 pub mod models;
 pub mod peer_manager;
+pub mod progress;
+pub mod scheduler;
 pub mod bandwidth_manager;
 pub mod transfer_handler;
 
+pub use announcement::{FetchStart, Finished, PayloadDescriptor, PayloadFetcher};
+pub use bandwidth_manager::{BandwidthLimiter, TransferId};
 pub use coordinator::NetworkTransferCoordinator;
+pub use progress::{TransferEvent, TransferProgress};
+pub use scheduler::TransferScheduler;
 pub use error::NetworkError;
-pub use models::{NetworkPeerInfo, NetworkStats, PeerCapabilities}; 
\ No newline at end of file
+pub use models::{
+    BandwidthConfig, BandwidthCounters, Direction, NetworkPeerInfo, NetworkStats, PeerBandwidth, PeerCapabilities,
+}; 
\ No newline at end of file
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::time::Instant;
 
 #[derive(Debug, Clone)]
@@ -17,4 +18,46 @@ pub struct BandwidthTracker {
     pub(crate) total_download_mbps: f32,
     pub(crate) max_upload_mbps: u32,
     pub(crate) max_download_mbps: u32,
-} 
\ No newline at end of file
+}
+
+/// Bandwidth caps in bytes per second. A cap of 0 leaves it unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandwidthConfig {
+    /// Upload cap shared by all peers.
+    pub max_upload: u64,
+    /// Download cap shared by all peers.
+    pub max_download: u64,
+    /// Upload cap for any one peer.
+    pub peer_upload: u64,
+    /// Download cap for any one peer.
+    pub peer_download: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Upload,
+    Download,
+}
+
+/// Bytes moved to and from one peer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerBandwidth {
+    pub sent: u64,
+    pub received: u64,
+}
+
+/// Snapshot of a [`BandwidthLimiter`](crate::large_data_transfer::network::BandwidthLimiter)
+/// for monitoring.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthCounters {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub active_uploads: usize,
+    pub active_downloads: usize,
+    /// Transfers held back to stay under a cap.
+    pub throttled: u64,
+    /// Total time transfers were held back, in milliseconds.
+    pub throttled_ms: u64,
+    pub peers: BTreeMap<String, PeerBandwidth>,
+}
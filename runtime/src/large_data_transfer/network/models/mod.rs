@@ -7,5 +7,7 @@ mod stats;
 
 pub use peer::{NetworkPeerInfo, PeerCapabilities, PeerTransferStats};
 pub use message::NetworkTransferMessage;
-pub use bandwidth::{BandwidthTracker, BandwidthUsage};
+pub use bandwidth::{
+    BandwidthConfig, BandwidthCounters, BandwidthTracker, BandwidthUsage, Direction, PeerBandwidth,
+};
 pub use stats::NetworkStats; 
\ No newline at end of file
@@ -20,6 +20,8 @@ pub struct PeerTransferStats {
     pub success_rate: f32,
     pub active_transfers: u32,
     pub last_transfer_time: Option<Instant>,
+    /// Chunks received from the peer that did not hash to their id.
+    pub corrupt_chunks: u64,
 }
 
 impl Default for PeerTransferStats {
@@ -30,6 +32,7 @@ impl Default for PeerTransferStats {
             success_rate: 1.0,
             active_transfers: 0,
             last_transfer_time: None,
+            corrupt_chunks: 0,
         }
     }
 }
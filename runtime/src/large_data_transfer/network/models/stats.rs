@@ -6,10 +6,12 @@ pub struct NetworkStats {
     pub total_upload_mbps: f32,
     pub total_download_mbps: f32,
     pub available_chunks: usize,
+    /// Chunks peers served that did not hash to their id.
+    pub corrupt_chunks_received: u64,
 }
 
 impl Default for NetworkStats {
     fn default() -> Self {
-        Self { connected_peers: 0, active_transfers: 0, total_upload_mbps: 0.0, total_download_mbps: 0.0, available_chunks: 0 }
+        Self { connected_peers: 0, active_transfers: 0, total_upload_mbps: 0.0, total_download_mbps: 0.0, available_chunks: 0, corrupt_chunks_received: 0 }
     }
 } 
\ No newline at end of file
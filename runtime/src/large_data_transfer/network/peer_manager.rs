@@ -6,8 +6,15 @@ use super::{
     models::{NetworkPeerInfo, PeerTransferStats},
 };
 use crate::large_data_transfer::{chunk::ChunkId, LargeDataResult};
+use crate::rng::{self, Stream};
+use rand::seq::SliceRandom;
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
 
+/// Reputation a peer loses for every chunk it serves that does not hash to
+/// its id.
+pub const CORRUPTION_PENALTY: f32 = 0.25;
+
 impl NetworkTransferCoordinator {
     /// Add a peer to the network topology.
     pub async fn add_peer(&self, peer_info: NetworkPeerInfo) {
@@ -38,6 +45,35 @@ impl NetworkTransferCoordinator {
         }
     }
 
+    /// What is known about `peer_id`.
+    pub fn peer(&self, peer_id: &str) -> Option<NetworkPeerInfo> {
+        self.peers.get(peer_id).map(|peer| peer.clone())
+    }
+
+    /// Penalize `peer_id` for serving data that does not hash to `chunk_id`:
+    /// it loses [`CORRUPTION_PENALTY`] of its reputation and is no longer
+    /// asked for that chunk.
+    pub fn penalize_peer(&self, peer_id: &str, chunk_id: &ChunkId) {
+        if let Some(mut peer) = self.peers.get_mut(peer_id) {
+            peer.reputation = (peer.reputation - CORRUPTION_PENALTY).max(0.0);
+            peer.transfer_stats.corrupt_chunks += 1;
+            peer.capabilities.available_chunks.retain(|id| id != chunk_id);
+            println!(
+                "🚫 Peer {} served corrupt chunk {} (reputation {:.2})",
+                peer_id, chunk_id, peer.reputation
+            );
+        }
+    }
+
+    /// Corrupt chunks received from each known peer that served any.
+    pub fn corrupt_chunks_by_peer(&self) -> BTreeMap<String, u64> {
+        self.peers
+            .iter()
+            .filter(|peer| peer.transfer_stats.corrupt_chunks > 0)
+            .map(|peer| (peer.peer_id.clone(), peer.transfer_stats.corrupt_chunks))
+            .collect()
+    }
+
     /// Periodically checks for stale peers and removes them.
     pub async fn peer_maintenance_loop(&self) {
         let mut interval = tokio::time::interval(self.config.peer_update_interval);
@@ -74,7 +110,7 @@ impl NetworkTransferCoordinator {
         &self,
         chunk_id: &ChunkId,
     ) -> LargeDataResult<Option<String>> {
-        let mut best_peer = None;
+        let mut best_peers = Vec::new();
         let mut best_score = -1.0; // Initialize with a score that any valid peer can beat
 
         for peer_entry in self.peers.iter() {
@@ -90,10 +126,15 @@ impl NetworkTransferCoordinator {
 
             if score > best_score {
                 best_score = score;
-                best_peer = Some(peer_info.peer_id.clone());
+                best_peers.clear();
+            }
+            if score == best_score {
+                best_peers.push(peer_info.peer_id.clone());
             }
         }
 
-        Ok(best_peer)
+        // Spread load over equally scored peers, reproducibly under a seed.
+        best_peers.sort();
+        Ok(best_peers.choose(&mut rng::stream(Stream::ChunkScheduling)).cloned())
     }
 } 
\ No newline at end of file
@@ -0,0 +1,157 @@
+//! Live progress of transfers for observers.
+//!
+//! [`NetworkTransferCoordinator::subscribe`] hands out a channel receiving a
+//! [`TransferEvent`] whenever a transfer starts, completes a chunk, finishes
+//! or stalls. Each event carries a [`TransferProgress`] snapshot with chunk
+//! and byte counts, the rate so far, an ETA and how many bytes every peer
+//! supplied, enough for the dashboard or the CLI to draw a progress bar.
+//! Subscribers that drop their receiver are forgotten on the next event.
+
+use super::coordinator::NetworkTransferCoordinator;
+use crate::large_data_transfer::{descriptor::LargeDataDescriptor, types::TransferPriority};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Progress of one transfer at the time of an event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferProgress {
+    pub session_id: String,
+    pub priority: TransferPriority,
+    pub chunks_completed: u32,
+    pub total_chunks: u32,
+    /// Bytes of the completed chunks, fetched or reused.
+    pub bytes_completed: u64,
+    pub total_bytes: u64,
+    /// Bytes per second since the transfer (re)started.
+    pub transfer_rate: f64,
+    /// Time left at the rate so far; `None` until a chunk completed.
+    pub eta: Option<Duration>,
+    /// Bytes each peer supplied. Chunks taken from the local cache count
+    /// under this node's own id.
+    pub peers: BTreeMap<String, u64>,
+}
+
+impl TransferProgress {
+    /// Completed share of the chunks, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        match self.total_chunks {
+            0 => 1.0,
+            n => self.chunks_completed as f32 / n as f32,
+        }
+    }
+
+    /// A one-line progress bar `width` cells wide, for terminals.
+    pub fn bar(&self, width: usize) -> String {
+        let filled = ((self.fraction() * width as f32) as usize).min(width);
+        let eta = self.eta.map_or_else(|| "--".to_string(), |eta| format!("{}s", eta.as_secs()));
+        format!(
+            "{} [{}{}] {:>3.0}% {}/{} chunks {:.1} MB/s ETA {}",
+            self.session_id,
+            "#".repeat(filled),
+            "-".repeat(width - filled),
+            self.fraction() * 100.0,
+            self.chunks_completed,
+            self.total_chunks,
+            self.transfer_rate / 1_000_000.0,
+            eta,
+        )
+    }
+}
+
+/// Something that happened to a transfer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "progress", rename_all = "snake_case")]
+pub enum TransferEvent {
+    Started(TransferProgress),
+    /// A chunk completed.
+    Progress(TransferProgress),
+    Completed(TransferProgress),
+    /// No missing chunk could be fetched; the transfer can be resumed later.
+    Stalled(TransferProgress),
+}
+
+impl TransferEvent {
+    pub fn progress(&self) -> &TransferProgress {
+        match self {
+            TransferEvent::Started(p)
+            | TransferEvent::Progress(p)
+            | TransferEvent::Completed(p)
+            | TransferEvent::Stalled(p) => p,
+        }
+    }
+}
+
+/// Builds the [`TransferProgress`] of one run of a transfer.
+#[derive(Debug)]
+pub(crate) struct ProgressTracker {
+    progress: TransferProgress,
+    started: Instant,
+    /// Bytes and chunks completed since `started`, for the rate and ETA.
+    run_bytes: u64,
+    run_chunks: u32,
+}
+
+impl ProgressTracker {
+    /// Tracks `descriptor`'s transfer, `chunks_completed` of its chunks
+    /// holding `bytes_completed` bytes being done already.
+    pub(crate) fn new(
+        descriptor: &LargeDataDescriptor,
+        priority: TransferPriority,
+        chunks_completed: u32,
+        bytes_completed: u64,
+    ) -> Self {
+        Self {
+            progress: TransferProgress {
+                session_id: descriptor.id.clone(),
+                priority,
+                chunks_completed,
+                total_chunks: descriptor.chunk_hashes.len() as u32,
+                bytes_completed,
+                total_bytes: descriptor.size_bytes,
+                transfer_rate: 0.0,
+                eta: None,
+                peers: BTreeMap::new(),
+            },
+            started: Instant::now(),
+            run_bytes: 0,
+            run_chunks: 0,
+        }
+    }
+
+    /// Counts a chunk of `bytes` supplied by `peer`.
+    pub(crate) fn record(&mut self, peer: &str, bytes: u64) {
+        self.progress.chunks_completed += 1;
+        self.progress.bytes_completed += bytes;
+        *self.progress.peers.entry(peer.to_string()).or_default() += bytes;
+        self.run_bytes += bytes;
+        self.run_chunks += 1;
+    }
+
+    pub(crate) fn snapshot(&self) -> TransferProgress {
+        let elapsed = self.started.elapsed();
+        let mut progress = self.progress.clone();
+        if !elapsed.is_zero() {
+            progress.transfer_rate = self.run_bytes as f64 / elapsed.as_secs_f64();
+        }
+        if self.run_chunks > 0 {
+            let remaining = progress.total_chunks.saturating_sub(progress.chunks_completed);
+            progress.eta = Some(elapsed / self.run_chunks * remaining);
+        }
+        progress
+    }
+}
+
+impl NetworkTransferCoordinator {
+    /// A receiver of the [`TransferEvent`]s of every transfer from now on.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<TransferEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.observers.lock().unwrap().push(tx);
+        rx
+    }
+
+    pub(crate) fn emit(&self, event: TransferEvent) {
+        self.observers.lock().unwrap().retain(|observer| observer.send(event.clone()).is_ok());
+    }
+}
@@ -0,0 +1,194 @@
+//! Quality-of-service scheduling of transfers.
+//!
+//! Every transfer runs in a [`TransferPriority`] class. While a transfer of
+//! a class is registered, transfers of the classes it
+//! [preempts](TransferPriority::preempts) wait before each chunk: block sync
+//! and PoUW verification data move first and bulk datasets yield to them.
+//! Classes with transfers running split the download cap by their
+//! reservations in [`QosConfig`], the reservations of idle classes going to
+//! the others in proportion. A preempted transfer that has not run for
+//! [`QosConfig::starvation_after`] is let through for one chunk, so bulk
+//! transfers keep trickling instead of stalling behind a steady stream of
+//! critical ones.
+
+use super::coordinator::NetworkTransferCoordinator;
+use crate::large_data_transfer::{config::QosConfig, types::TransferPriority};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Scheduled {
+    priority: TransferPriority,
+    /// When the transfer last fetched a chunk, or was registered.
+    last_run: Instant,
+}
+
+/// Decides which transfers may fetch their next chunk and at what rate.
+///
+/// Time is passed in, like in
+/// [`BandwidthLimiter`](super::BandwidthLimiter), which keeps decisions
+/// deterministic.
+#[derive(Debug)]
+pub struct TransferScheduler {
+    config: QosConfig,
+    transfers: HashMap<String, Scheduled>,
+    preemptions: u64,
+    starvation_grants: u64,
+}
+
+impl TransferScheduler {
+    pub fn new(config: QosConfig) -> Self {
+        Self { config, transfers: HashMap::new(), preemptions: 0, starvation_grants: 0 }
+    }
+
+    pub fn config(&self) -> &QosConfig {
+        &self.config
+    }
+
+    /// Schedules transfer `id` in `priority`'s class.
+    pub fn register(&mut self, id: impl Into<String>, priority: TransferPriority, now: Instant) {
+        let id = id.into();
+        let newly_preempted = self
+            .transfers
+            .iter()
+            .filter(|(other, t)| {
+                **other != id && !self.is_preempted(other) && priority.preempts(t.priority)
+            })
+            .count();
+        self.preemptions += newly_preempted as u64;
+        self.transfers.insert(id, Scheduled { priority, last_run: now });
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.transfers.remove(id);
+    }
+
+    pub fn priority(&self, id: &str) -> Option<TransferPriority> {
+        self.transfers.get(id).map(|t| t.priority)
+    }
+
+    /// Whether a transfer of a class that preempts `id`'s is registered.
+    pub fn is_preempted(&self, id: &str) -> bool {
+        let Some(transfer) = self.transfers.get(id) else { return false };
+        self.transfers.values().any(|other| other.priority.preempts(transfer.priority))
+    }
+
+    /// Ids of the preempted transfers, in order.
+    pub fn preempted(&self) -> Vec<String> {
+        let mut ids: Vec<String> =
+            self.transfers.keys().filter(|id| self.is_preempted(id)).cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Whether `id` is preempted and has waited long enough to run anyway.
+    pub fn is_starving(&self, id: &str, now: Instant) -> bool {
+        self.is_preempted(id)
+            && self.transfers.get(id).is_some_and(|t| {
+                now.saturating_duration_since(t.last_run) >= self.config.starvation_after
+            })
+    }
+
+    /// Whether `id` may fetch its next chunk at `now`, counting it as run if
+    /// so. Transfers never registered are not held back.
+    pub fn try_run(&mut self, id: &str, now: Instant) -> bool {
+        if self.is_preempted(id) {
+            if !self.is_starving(id, now) {
+                return false;
+            }
+            self.starvation_grants += 1;
+        }
+        if let Some(transfer) = self.transfers.get_mut(id) {
+            transfer.last_run = now;
+        }
+        true
+    }
+
+    /// Bytes per second `id` may use of a `cap`: its class's reservation,
+    /// scaled up by the reservations of classes with nothing running, split
+    /// evenly within the class. `None` when the cap is unlimited or the
+    /// transfer is unknown.
+    pub fn share(&self, id: &str, cap: u64) -> Option<u64> {
+        let transfer = self.transfers.get(id)?;
+        if cap == 0 {
+            return None;
+        }
+        let mut running: BTreeMap<TransferPriority, u64> = BTreeMap::new();
+        for (other, t) in &self.transfers {
+            if other == id || !self.is_preempted(other) {
+                *running.entry(t.priority).or_default() += 1;
+            }
+        }
+        let reserved = |priority| self.config.reservation(priority) as u64;
+        let total: u64 = running.keys().map(|p| reserved(*p)).sum();
+        let class = match total {
+            0 => cap / running.len() as u64,
+            total => cap * reserved(transfer.priority) / total,
+        };
+        Some((class / running[&transfer.priority]).max(1))
+    }
+
+    /// Times a transfer was paused by a higher class arriving.
+    pub fn preemptions(&self) -> u64 {
+        self.preemptions
+    }
+
+    /// Chunks preempted transfers were let through for by starvation
+    /// protection.
+    pub fn starvation_grants(&self) -> u64 {
+        self.starvation_grants
+    }
+}
+
+impl NetworkTransferCoordinator {
+    /// Re-evaluates waiting transfers every QoS tick, so preempted ones go
+    /// on as soon as the critical ones are done and starving ones get their
+    /// chunk on time.
+    pub async fn transfer_scheduling_loop(&self) {
+        let mut interval = tokio::time::interval(self.config.qos_config.tick);
+        let mut preempted = Vec::new();
+        loop {
+            interval.tick().await;
+            let current = self.scheduler.lock().unwrap().preempted();
+            if current != preempted {
+                println!("⏸️ Preempted transfers: {:?}", current);
+                preempted = current;
+            }
+            self.scheduler_wake.notify_waiters();
+        }
+    }
+
+    /// Transfers currently held back by higher classes.
+    pub fn preempted_transfers(&self) -> Vec<String> {
+        self.scheduler.lock().unwrap().preempted()
+    }
+
+    pub(crate) fn schedule(&self, session_id: &str, priority: TransferPriority) {
+        self.scheduler.lock().unwrap().register(session_id, priority, Instant::now());
+    }
+
+    pub(crate) fn unschedule(&self, session_id: &str) {
+        self.scheduler.lock().unwrap().remove(session_id);
+        self.scheduler_wake.notify_waiters();
+    }
+
+    /// Waits until the scheduler lets `session_id` fetch its next chunk.
+    pub(crate) async fn wait_for_turn(&self, session_id: &str) {
+        loop {
+            let wake = self.scheduler_wake.notified();
+            if self.scheduler.lock().unwrap().try_run(session_id, Instant::now()) {
+                return;
+            }
+            let _ = tokio::time::timeout(self.config.qos_config.tick, wake).await;
+        }
+    }
+
+    /// Holds `session_id` back after it received `bytes`, keeping it within
+    /// its class's share of the download cap.
+    pub(crate) async fn pace(&self, session_id: &str, bytes: u64) {
+        let share = self.scheduler.lock().unwrap().share(session_id, self.config.max_download_rate);
+        if let Some(rate) = share {
+            tokio::time::sleep(Duration::from_secs_f64(bytes as f64 / rate as f64)).await;
+        }
+    }
+}
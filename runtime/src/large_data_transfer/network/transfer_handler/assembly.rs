@@ -0,0 +1,98 @@
+use super::super::{coordinator::NetworkTransferCoordinator, error::NetworkError};
+use crate::large_data_transfer::{
+    chunk::{ChunkId, DataChunk},
+    descriptor::LargeDataDescriptor,
+    protocol::resume::chunk_matches,
+    LargeDataResult,
+};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+impl NetworkTransferCoordinator {
+    /// Peers announcing `chunk_id`, most reputable first.
+    fn providers_by_reputation(&self, chunk_id: &ChunkId) -> Vec<String> {
+        let mut providers: Vec<(f32, String)> = self
+            .peers
+            .iter()
+            .filter(|peer| peer.capabilities.available_chunks.contains(chunk_id))
+            .map(|peer| (peer.reputation, peer.peer_id.clone()))
+            .collect();
+        providers.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        providers.into_iter().map(|(_, peer_id)| peer_id).collect()
+    }
+
+    /// Chunk `index` of `descriptor`, from the local cache if intact there
+    /// and otherwise from the peers announcing it, one after the other. A
+    /// peer failing, timing out or serving nothing is skipped; one serving
+    /// data that does not hash to the chunk's id is also penalized. Once
+    /// every provider was tried the pass is retried after the configured
+    /// back-off, up to `max_retries` times.
+    async fn fetch_verified(
+        &self,
+        descriptor: &LargeDataDescriptor,
+        index: u32,
+    ) -> LargeDataResult<DataChunk> {
+        let chunk_id = ChunkId::from_hex(&descriptor.chunk_hashes[index as usize])?;
+        if let Some(chunk) = self
+            .chunk_manager
+            .get_chunk(&chunk_id)
+            .filter(|chunk| chunk_matches(descriptor, index, chunk))
+        {
+            return Ok(chunk);
+        }
+        let retry = &self.config.retry_config;
+        let mut delay = retry.initial_delay;
+        for attempt in 0..=retry.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(delay).await;
+                delay = delay.mul_f32(retry.backoff_multiplier).min(retry.max_delay);
+            }
+            for peer_id in self.providers_by_reputation(&chunk_id) {
+                match self.request_chunk_from(&peer_id, chunk_id.clone()).await {
+                    Ok(Some(mut chunk)) => {
+                        chunk.info.index = index;
+                        if !chunk_matches(descriptor, index, &chunk) {
+                            self.penalize_peer(&peer_id, &chunk_id);
+                            continue;
+                        }
+                        self.chunk_manager.store_chunk(chunk.clone())?;
+                        return Ok(chunk);
+                    }
+                    Ok(None) => println!("⚠️ {} did not serve chunk {}", peer_id, chunk_id),
+                    Err(e) => {
+                        println!("⚠️ Requesting chunk {} from {} failed: {}", chunk_id, peer_id, e)
+                    }
+                }
+            }
+        }
+        Err(NetworkError::ChunkUnavailable {
+            chunk: chunk_id.to_string(),
+            attempts: retry.max_retries + 1,
+        }
+        .into())
+    }
+
+    /// Writes the file `descriptor` describes to `out`, fetching up to
+    /// `max_concurrent_transfers` chunks at once, each verified against the
+    /// descriptor and retried on other peers when one fails. Chunks are
+    /// written in order as soon as those before them are, so only the
+    /// chunks in flight are held in memory. Returns the bytes written.
+    pub async fn assemble_file_parallel<W: AsyncWrite + Unpin>(
+        &self,
+        descriptor: &LargeDataDescriptor,
+        out: &mut W,
+    ) -> LargeDataResult<u64> {
+        let parallelism = self.config.max_concurrent_transfers.max(1);
+        let mut chunks = stream::iter(0..descriptor.chunk_hashes.len() as u32)
+            .map(|index| self.fetch_verified(descriptor, index))
+            .buffered(parallelism);
+        let mut written = 0;
+        while let Some(chunk) = chunks.try_next().await? {
+            let data = chunk.decompress()?;
+            out.write_all(&data).await?;
+            written += data.len() as u64;
+        }
+        out.flush().await?;
+        Ok(written)
+    }
+}
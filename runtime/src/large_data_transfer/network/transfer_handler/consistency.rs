@@ -0,0 +1,74 @@
+use super::super::{coordinator::NetworkTransferCoordinator, error::NetworkError};
+use crate::distributed_storage::ConsistencyLevel;
+use crate::large_data_transfer::{
+    chunk::{ChunkId, DataChunk},
+    LargeDataResult,
+};
+
+impl NetworkTransferCoordinator {
+    /// Peers announcing `chunk_id`, in order of id.
+    fn providers(&self, chunk_id: &ChunkId) -> Vec<String> {
+        let mut providers: Vec<String> = self
+            .peers
+            .iter()
+            .filter(|peer| peer.capabilities.available_chunks.contains(chunk_id))
+            .map(|peer| peer.peer_id.clone())
+            .collect();
+        providers.sort();
+        providers
+    }
+
+    /// Request a chunk once as many of the peers announcing it as `level`
+    /// asks for have served data hashing to its id. Peers are asked one at
+    /// a time; one serving other data, nothing or failing does not count. At
+    /// [`ConsistencyLevel::One`] an intact copy in the local cache is enough.
+    pub async fn request_chunk_consistent(
+        &self,
+        chunk_id: ChunkId,
+        level: ConsistencyLevel,
+    ) -> LargeDataResult<Option<DataChunk>> {
+        let intact = |chunk: &DataChunk| ChunkId::from_data(&chunk.data) == chunk_id;
+        if level == ConsistencyLevel::One {
+            if let Some(chunk) = self.chunk_manager.get_chunk(&chunk_id).filter(intact) {
+                return Ok(Some(chunk));
+            }
+        }
+        let providers = self.providers(&chunk_id);
+        if providers.is_empty() {
+            return Ok(None);
+        }
+        let required = level.required(providers.len());
+        let mut confirmed = 0;
+        let mut verified = None;
+        for peer_id in &providers {
+            match self.request_chunk_from(peer_id, chunk_id.clone()).await {
+                Ok(Some(chunk)) if intact(&chunk) => {
+                    confirmed += 1;
+                    verified.get_or_insert(chunk);
+                }
+                Ok(Some(_)) => self.penalize_peer(peer_id, &chunk_id),
+                Ok(None) => println!("⚠️ {} did not serve chunk {}", peer_id, chunk_id),
+                Err(e) => {
+                    println!("⚠️ Requesting chunk {} from {} failed: {}", chunk_id, peer_id, e)
+                }
+            }
+            if confirmed >= required {
+                break;
+            }
+        }
+        match verified {
+            Some(chunk) if confirmed >= required => {
+                if !self.chunk_manager.has_chunk(&chunk_id) {
+                    self.chunk_manager.store_chunk(chunk.clone())?;
+                }
+                Ok(Some(chunk))
+            }
+            _ => Err(NetworkError::InsufficientProviders {
+                chunk: chunk_id.to_string(),
+                confirmed,
+                required,
+            }
+            .into()),
+        }
+    }
+}
@@ -4,6 +4,8 @@
 //! responsibilities while keeping every file ≤ 100 LOC.
 
 mod announce;
+mod assembly;
+mod consistency;
 mod request;
 mod orchestrator;
 mod process;
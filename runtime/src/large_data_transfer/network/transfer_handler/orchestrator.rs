@@ -1,12 +1,30 @@
 use super::super::coordinator::NetworkTransferCoordinator;
-use crate::large_data_transfer::{descriptor::LargeDataDescriptor, LargeDataResult, TransferStats};
+use super::super::progress::{ProgressTracker, TransferEvent};
+use crate::large_data_transfer::protocol::{
+    resume::chunk_matches, TransferError, TransferSession, TransferState,
+};
+use crate::large_data_transfer::{
+    descriptor::LargeDataDescriptor, LargeDataResult, TransferPriority, TransferStats,
+};
 
 impl NetworkTransferCoordinator {
     /// Break a large data object into chunks and distribute them among peers.
     pub async fn transfer_large_data(
+        &self,
+        descriptor: LargeDataDescriptor,
+        target_peers: Vec<String>,
+    ) -> LargeDataResult<TransferStats> {
+        self.transfer_large_data_with_priority(descriptor, target_peers, TransferPriority::default())
+            .await
+    }
+
+    /// [`transfer_large_data`](Self::transfer_large_data) in the QoS class
+    /// `priority`, e.g. [`TransferPriority::Critical`] for block sync.
+    pub async fn transfer_large_data_with_priority(
         &self,
         descriptor: LargeDataDescriptor,
         _target_peers: Vec<String>,
+        priority: TransferPriority,
     ) -> LargeDataResult<TransferStats> {
         println!(
             "🚀 Starting large data transfer: {} ({} chunks)",
@@ -15,9 +33,57 @@ impl NetworkTransferCoordinator {
         );
 
         let session_id = descriptor.id.clone();
+        if let Some(store) = &self.resume_store {
+            store.begin(&descriptor)?;
+        }
+        match self.active_transfers.get_mut(&session_id) {
+            Some(mut entry) => entry.priority = priority,
+            None => {
+                let mut session = TransferSession::new(session_id.clone());
+                session.descriptor = Some(descriptor);
+                session.priority = priority;
+                session.set_state(TransferState::Active);
+                self.active_transfers.insert(session_id.clone(), session);
+            }
+        }
+        self.spawn_coordination(session_id).await
+    }
+
+    /// Continue an interrupted transfer from its last verified chunk. A
+    /// transfer no longer held in memory, e.g. after a restart, is rebuilt
+    /// from the resume store: the chunks it lists are re-verified and only
+    /// the rest are requested again.
+    pub async fn resume_transfer(&self, session_id: &str) -> LargeDataResult<TransferStats> {
+        if !self.active_transfers.contains_key(session_id) {
+            let restored = match &self.resume_store {
+                Some(store) => store.restore(session_id, &self.chunk_manager)?,
+                None => None,
+            };
+            let session = restored.ok_or_else(|| TransferError::TransferNotFound(session_id.to_string()))?;
+            println!(
+                "⏯️ Resuming transfer {} at {:.0}%",
+                session_id,
+                session.progress(),
+            );
+            self.active_transfers.insert(session_id.to_string(), session);
+        }
+        if let Some(mut entry) = self.active_transfers.get_mut(session_id) {
+            entry.set_state(TransferState::Active);
+        }
+        self.spawn_coordination(session_id.to_string()).await
+    }
+
+    async fn spawn_coordination(&self, session_id: String) -> LargeDataResult<TransferStats> {
         let coordinator = self.clone();
-        tokio::spawn(async move { coordinator.coordinate_chunk_transfers(session_id).await })
-            .await
+        tokio::spawn(async move {
+            let priority =
+                coordinator.active_transfers.get(&session_id).map(|e| e.priority).unwrap_or_default();
+            coordinator.schedule(&session_id, priority);
+            let result = coordinator.coordinate_chunk_transfers(session_id.clone()).await;
+            coordinator.unschedule(&session_id);
+            result
+        })
+        .await
             .map_err(|e| {
                 crate::large_data_transfer::error::LargeDataError::Network(format!(
                     "Transfer coordination failed: {}",
@@ -27,13 +93,16 @@ impl NetworkTransferCoordinator {
     }
 
     /// Basic coordination loop that sequentially requests missing chunks until the
-    /// transfer completes.
+    /// transfer completes, each when the scheduler gives the transfer its turn. Chunks the local cache already holds, such as those a
+    /// new model version shares with the last one, are taken from it instead. A pass in which no missing chunk arrives stops the
+    /// transfer; it keeps what it has and can be resumed later.
     async fn coordinate_chunk_transfers(
         &self,
         session_id: String,
     ) -> LargeDataResult<TransferStats> {
         println!("🔄 Coordinating transfer for session {}", session_id);
 
+        let mut tracker: Option<ProgressTracker> = None;
         loop {
             let (descriptor, pending) = {
                 let mut entry = self.active_transfers.get_mut(&session_id).ok_or_else(|| {
@@ -49,43 +118,111 @@ impl NetworkTransferCoordinator {
                 })?;
 
                 let pending = entry.pending_chunks();
+                let progress = tracker.get_or_insert_with(|| {
+                    let done = (desc.chunk_hashes.len() - pending.len()) as u32;
+                    let tracker =
+                        ProgressTracker::new(&desc, entry.priority, done, entry.stats.bytes_received);
+                    self.emit(TransferEvent::Started(tracker.snapshot()));
+                    tracker
+                });
                 if pending.is_empty() {
+                    self.emit(TransferEvent::Completed(progress.snapshot()));
+                    entry.set_state(TransferState::Completed);
                     let proto_stats = entry.stats.clone();
+                    let total_chunks = desc.chunk_hashes.len() as u32;
                     let result = TransferStats {
                         bytes_transferred: proto_stats.bytes_received,
                         transfer_rate: 0.0,
-                        chunks_completed: proto_stats.chunks_transferred as u32,
-                        total_chunks: desc.chunk_hashes.len() as u32,
+                        chunks_completed: (proto_stats.chunks_transferred + proto_stats.chunks_reused)
+                            as u32,
+                        total_chunks,
                         completion_percentage: 1.0,
                         eta: None,
                         retry_count: entry.retry_count,
                         active_connections: entry.peers.len() as u32,
                         compression_ratio: 1.0,
-                        cache_hit_rate: 0.0,
+                        cache_hit_rate: match total_chunks {
+                            0 => 0.0,
+                            n => proto_stats.chunks_reused as f32 / n as f32,
+                        },
                     };
+                    drop(entry);
                     self.active_transfers.remove(&session_id);
+                    if let Some(store) = &self.resume_store {
+                        store.remove(&session_id)?;
+                    }
                     return Ok(result);
                 }
 
                 (desc, pending)
             };
 
-            for index in pending {
+            let mut progressed = false;
+            for &index in &pending {
                 let chunk_hash = descriptor.chunk_hashes[index as usize].clone();
                 let chunk_id = crate::large_data_transfer::chunk::ChunkId::from_hex(&chunk_hash)?;
-                if let Some(chunk) = self.request_chunk(chunk_id.clone()).await? {
-                    self.chunk_manager.store_chunk(chunk.clone())?;
-                    if let Some(mut entry) = self.active_transfers.get_mut(&session_id) {
-                        entry.set_chunk_status(
-                            index,
-                            crate::large_data_transfer::protocol::ChunkStatus::Complete(
-                                chunk.id.clone(),
-                            ),
-                        );
+                // Chunks shared with a version held locally are not fetched again.
+                let cached = self
+                    .chunk_manager
+                    .get_chunk(&chunk_id)
+                    .filter(|chunk| chunk_matches(&descriptor, index, chunk));
+                let reused = cached.is_some();
+                let (peer, chunk) = match cached {
+                    Some(chunk) => (self.local_peer_id.clone(), chunk),
+                    None => {
+                        self.wait_for_turn(&session_id).await;
+                        let Some((peer, mut chunk)) = self.request_chunk_with_peer(chunk_id.clone()).await?
+                        else {
+                            continue;
+                        };
+                        chunk.info.index = index;
+                        if !chunk_matches(&descriptor, index, &chunk) {
+                            println!("⚠️ Chunk {} of {} failed verification", index, session_id);
+                            self.penalize_peer(&peer, &chunk_id);
+                            continue;
+                        }
+                        self.chunk_manager.store_chunk(chunk.clone())?;
+                        self.pace(&session_id, chunk.len() as u64).await;
+                        (peer, chunk)
+                    }
+                };
+                if let Some(store) = &self.resume_store {
+                    store.record_chunk(&session_id, index, &chunk)?;
+                }
+                if let Some(mut entry) = self.active_transfers.get_mut(&session_id) {
+                    entry.set_chunk_status(
+                        index,
+                        crate::large_data_transfer::protocol::ChunkStatus::Complete(
+                            chunk.id.clone(),
+                        ),
+                    );
+                    if reused {
+                        entry.stats.chunks_reused += 1;
+                    } else {
                         entry.stats.chunks_transferred += 1;
                         entry.stats.bytes_received += chunk.len() as u64;
                     }
                 }
+                if let Some(tracker) = tracker.as_mut() {
+                    tracker.record(&peer, chunk.len() as u64);
+                    self.emit(TransferEvent::Progress(tracker.snapshot()));
+                }
+                progressed = true;
+            }
+
+            if !progressed {
+                if let Some(tracker) = &tracker {
+                    self.emit(TransferEvent::Stalled(tracker.snapshot()));
+                }
+                if let Some(mut entry) = self.active_transfers.get_mut(&session_id) {
+                    entry.set_state(TransferState::Paused);
+                    entry.retry_count += 1;
+                }
+                return Err(crate::large_data_transfer::error::LargeDataError::Network(format!(
+                    "transfer {} stalled with {} chunks missing; resume it once peers hold them",
+                    session_id,
+                    pending.len()
+                )));
             }
         }
     }
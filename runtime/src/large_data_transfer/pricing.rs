@@ -9,11 +9,12 @@ pub struct PriceQuote {
 }
 
 /// Simple static-rate pricing model.
-/// `price_per_gb_bcai` is expressed in **whole BCAI per GiB per copy**.
+/// `price_per_gb_bcai` is expressed in **whole BCAI per GiB stored**, so an
+/// erasure coded object costs its size times the scheme's overhead rather
+/// than once per copy.
 pub fn quote(bytes: u128, policy: RedundancyPolicy, price_per_gb_bcai: u128) -> PriceQuote {
-    let copies = (policy.copies as u128).saturating_add(1); // original + copies
-    let total_bytes = bytes.saturating_mul(copies);
+    let total_bytes = policy.stored_bytes(bytes);
     let gib = 1_073_741_824u128;
-    let price_bcai = ((total_bytes + gib - 1) / gib) * price_per_gb_bcai;
+    let price_bcai = total_bytes.div_ceil(gib).saturating_mul(price_per_gb_bcai);
     PriceQuote { total_bytes, redundancy: policy.copies, price_bcai }
 } 
\ No newline at end of file
@@ -10,6 +10,7 @@ pub mod session_state;
 pub mod peer_management;
 pub mod chunk_tracking;
 pub mod progress_metrics;
+pub mod resume;
 
 #[cfg(test)]
 mod tests;
@@ -17,6 +18,7 @@ mod tests;
 pub use error::TransferError;
 pub use handler::ProtocolHandler;
 pub use message::TransferMessage;
+pub use resume::{ChunkBitmap, ResumeState, ResumeStore};
 pub use session::{PeerInfo, TransferSession};
 pub use stats::TransferStats;
 pub use state::{ChunkStatus, TransferErrorType, TransferState}; 
\ No newline at end of file
@@ -1,4 +1,6 @@
 use super::session::{PeerInfo, TransferSession};
+use crate::rng::{self, Stream};
+use rand::seq::SliceRandom;
 use std::time::Instant;
 
 impl TransferSession {
@@ -10,12 +12,13 @@ impl TransferSession {
 
     /// Find the best peer to download a specific chunk from.
     pub fn best_peer_for_chunk(&self, chunk_index: u32) -> Option<&PeerInfo> {
-        self.peers
-            .values()
-            .filter(|p| p.available_chunks.contains(&chunk_index))
-            .min_by(|a, b| {
-                // Simple algorithm: prefer peer with higher reliability
-                b.reliability.partial_cmp(&a.reliability).unwrap_or(std::cmp::Ordering::Equal)
-            })
+        let holders: Vec<&PeerInfo> =
+            self.peers.values().filter(|p| p.available_chunks.contains(&chunk_index)).collect();
+        // Simple algorithm: prefer peer with higher reliability
+        let best = holders.iter().map(|p| p.reliability).fold(f32::NEG_INFINITY, f32::max);
+        let mut tied: Vec<&PeerInfo> = holders.into_iter().filter(|p| p.reliability >= best).collect();
+        // Spread load over equally reliable peers, reproducibly under a seed.
+        tied.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        tied.choose(&mut rng::stream(Stream::ChunkScheduling)).copied()
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,360 @@
+//! Resuming interrupted transfers.
+//!
+//! A [`ResumeStore`] keeps, for every download in progress, its descriptor, a
+//! [`ChunkBitmap`] of the chunks received and verified so far, and the bodies
+//! of those chunks, under one directory per session. Both are written as each
+//! chunk lands, body first, so a bit is only ever set for a body already on
+//! disk. After a crash or restart, [`ResumeStore::restore`] re-verifies every
+//! chunk the bitmap claims against the descriptor, drops the ones that no
+//! longer check out, and rebuilds the session so only the missing chunks are
+//! fetched again.
+
+use super::{
+    error::TransferError,
+    session::TransferSession,
+    state::{ChunkStatus, TransferState},
+};
+use crate::large_data_transfer::{
+    chunk::{ChunkId, DataChunk},
+    descriptor::LargeDataDescriptor,
+    manager::ChunkManager,
+    scrub::{ChunkHealth, ScrubTarget},
+    LargeDataError, LargeDataResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One bit per chunk of a transfer, set once the chunk is verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkBitmap {
+    len: u32,
+    bits: Vec<u8>,
+}
+
+impl ChunkBitmap {
+    /// A bitmap of `len` chunks, none of them received.
+    pub fn new(len: u32) -> Self {
+        Self { len, bits: vec![0; (len as usize).div_ceil(8)] }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Marks chunk `index` as verified; indices past the end are ignored.
+    pub fn set(&mut self, index: u32) {
+        if index < self.len {
+            self.bits[index as usize / 8] |= 1 << (index % 8);
+        }
+    }
+
+    pub fn clear(&mut self, index: u32) {
+        if index < self.len {
+            self.bits[index as usize / 8] &= !(1 << (index % 8));
+        }
+    }
+
+    pub fn contains(&self, index: u32) -> bool {
+        index < self.len && self.bits[index as usize / 8] & (1 << (index % 8)) != 0
+    }
+
+    /// Number of verified chunks.
+    pub fn count(&self) -> u32 {
+        self.bits.iter().map(|b| b.count_ones()).sum()
+    }
+
+    /// Indices of the chunks still missing, in order.
+    pub fn missing(&self) -> Vec<u32> {
+        (0..self.len).filter(|i| !self.contains(*i)).collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.count() == self.len
+    }
+}
+
+/// What is persisted about one transfer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeState {
+    pub session_id: String,
+    pub descriptor: LargeDataDescriptor,
+    pub verified: ChunkBitmap,
+}
+
+impl ResumeState {
+    pub fn new(descriptor: LargeDataDescriptor) -> Self {
+        let verified = ChunkBitmap::new(descriptor.chunk_hashes.len() as u32);
+        Self { session_id: descriptor.id.clone(), descriptor, verified }
+    }
+}
+
+/// Whether `chunk` is intact and is chunk `index` of `descriptor`.
+pub fn chunk_matches(descriptor: &LargeDataDescriptor, index: u32, chunk: &DataChunk) -> bool {
+    descriptor.chunk_hashes.get(index as usize).is_some_and(|hash| chunk.id.as_str() == hash)
+        && chunk.verify_integrity().is_ok()
+}
+
+/// Per-session bitmaps and chunk bodies under a directory.
+#[derive(Debug)]
+pub struct ResumeStore {
+    dir: PathBuf,
+    /// States of the sessions begun or restored, as last written.
+    open: Mutex<HashMap<String, ResumeState>>,
+}
+
+impl ResumeStore {
+    /// A store under `dir`, created if missing.
+    pub fn open(dir: impl Into<PathBuf>) -> LargeDataResult<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir, open: Mutex::new(HashMap::new()) })
+    }
+
+    /// Directory of `session_id`, named by its hash so any id is a safe
+    /// file name.
+    fn session_dir(&self, session_id: &str) -> PathBuf {
+        self.dir.join(ChunkId::from_data(session_id.as_bytes()).as_str())
+    }
+
+    fn state_path(&self, session_id: &str) -> PathBuf {
+        self.session_dir(session_id).join("state.json")
+    }
+
+    fn chunk_path(&self, session_id: &str, index: u32) -> PathBuf {
+        self.session_dir(session_id).join(format!("{index}.chunk"))
+    }
+
+    /// Starts persisting the transfer of `descriptor`, keeping what an
+    /// earlier attempt at the same chunks already received.
+    pub fn begin(&self, descriptor: &LargeDataDescriptor) -> LargeDataResult<ResumeState> {
+        if let Some(state) = self.load(&descriptor.id)? {
+            if state.descriptor.chunk_hashes == descriptor.chunk_hashes {
+                self.open.lock().unwrap().insert(state.session_id.clone(), state.clone());
+                return Ok(state);
+            }
+            self.remove(&descriptor.id)?;
+        }
+        std::fs::create_dir_all(self.session_dir(&descriptor.id))?;
+        let state = ResumeState::new(descriptor.clone());
+        self.save(&state)?;
+        self.open.lock().unwrap().insert(state.session_id.clone(), state.clone());
+        Ok(state)
+    }
+
+    /// The persisted state of `session_id`, if it has any.
+    pub fn load(&self, session_id: &str) -> LargeDataResult<Option<ResumeState>> {
+        let bytes = match std::fs::read(self.state_path(session_id)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| LargeDataError::Serialization(e.to_string()))
+    }
+
+    fn save(&self, state: &ResumeState) -> LargeDataResult<()> {
+        let json =
+            serde_json::to_vec(state).map_err(|e| LargeDataError::Serialization(e.to_string()))?;
+        // Write then rename, so a crash never leaves half a bitmap behind.
+        let path = self.state_path(&state.session_id);
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Chunks of `session_id` verified so far.
+    pub fn verified(&self, session_id: &str) -> LargeDataResult<Option<ChunkBitmap>> {
+        if let Some(state) = self.open.lock().unwrap().get(session_id) {
+            return Ok(Some(state.verified.clone()));
+        }
+        Ok(self.load(session_id)?.map(|state| state.verified))
+    }
+
+    /// Persists chunk `index` of `session_id`'s transfer and marks it
+    /// verified. A chunk that is not the descriptor's `index`th is refused.
+    pub fn record_chunk(&self, session_id: &str, index: u32, chunk: &DataChunk) -> LargeDataResult<()> {
+        let mut open = self.open.lock().unwrap();
+        if !open.contains_key(session_id) {
+            let state =
+                self.load(session_id)?.ok_or_else(|| TransferError::TransferNotFound(session_id.into()))?;
+            open.insert(session_id.to_string(), state);
+        }
+        let state = open.get_mut(session_id).expect("inserted above");
+        if !chunk_matches(&state.descriptor, index, chunk) {
+            let reason = format!("chunk {index} of {session_id} does not verify");
+            return Err(TransferError::ProtocolViolation(reason).into());
+        }
+        let body =
+            bincode::serialize(chunk).map_err(|e| LargeDataError::Serialization(e.to_string()))?;
+        std::fs::write(self.chunk_path(session_id, index), body)?;
+        state.verified.set(index);
+        self.save(state)
+    }
+
+    fn load_chunk(&self, session_id: &str, index: u32) -> Option<DataChunk> {
+        let body = std::fs::read(self.chunk_path(session_id, index)).ok()?;
+        bincode::deserialize(&body).ok()
+    }
+
+    /// Rebuilds the session of `session_id` from disk. Every chunk the
+    /// bitmap lists is re-verified and put back into `chunks`; the ones
+    /// missing or corrupt are cleared and will be fetched again.
+    pub fn restore(
+        &self,
+        session_id: &str,
+        chunks: &ChunkManager,
+    ) -> LargeDataResult<Option<TransferSession>> {
+        let Some(mut state) = self.load(session_id)? else { return Ok(None) };
+        let mut session = TransferSession::new(session_id.to_string());
+        let mut dropped = false;
+        for index in 0..state.verified.len() {
+            if !state.verified.contains(index) {
+                continue;
+            }
+            let chunk = self.load_chunk(session_id, index);
+            match chunk.filter(|c| chunk_matches(&state.descriptor, index, c)) {
+                Some(chunk) => {
+                    session.stats.chunks_transferred += 1;
+                    session.stats.bytes_received += chunk.len() as u64;
+                    session.chunk_status.insert(index, ChunkStatus::Complete(chunk.id.clone()));
+                    chunks.store_chunk(chunk)?;
+                }
+                None => {
+                    state.verified.clear(index);
+                    dropped = true;
+                }
+            }
+        }
+        if dropped {
+            self.save(&state)?;
+        }
+        session.descriptor = Some(state.descriptor.clone());
+        self.open.lock().unwrap().insert(session_id.to_string(), state);
+        session.set_state(TransferState::Active);
+        Ok(Some(session))
+    }
+
+    /// Forgets `session_id`, e.g. once its transfer completed.
+    pub fn remove(&self, session_id: &str) -> LargeDataResult<()> {
+        self.open.lock().unwrap().remove(session_id);
+        match std::fs::remove_dir_all(self.session_dir(session_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Ids of the transfers that can be resumed.
+    pub fn sessions(&self) -> LargeDataResult<Vec<String>> {
+        let mut sessions = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let state = entry?.path().join("state.json");
+            if let Ok(bytes) = std::fs::read(&state) {
+                if let Ok(state) = serde_json::from_slice::<ResumeState>(&bytes) {
+                    sessions.push(state.session_id);
+                }
+            }
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// States of the sessions holding a verified copy of `id`, with the
+    /// indices it is at.
+    fn holders(&self, id: &ChunkId) -> Vec<(ResumeState, Vec<u32>)> {
+        let sessions = self.sessions().unwrap_or_default();
+        sessions
+            .iter()
+            .filter_map(|session| self.load(session).ok().flatten())
+            .filter_map(|state| {
+                let indices: Vec<u32> = (0..state.verified.len())
+                    .filter(|i| {
+                        state.verified.contains(*i)
+                            && state.descriptor.chunk_hashes[*i as usize] == id.as_str()
+                    })
+                    .collect();
+                (!indices.is_empty()).then_some((state, indices))
+            })
+            .collect()
+    }
+}
+
+impl ScrubTarget for ResumeStore {
+    fn disk(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn chunk_ids(&self) -> Vec<ChunkId> {
+        let mut ids: Vec<ChunkId> = self
+            .sessions()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|session| self.load(session).ok().flatten())
+            .flat_map(|state| {
+                (0..state.verified.len())
+                    .filter(|i| state.verified.contains(*i))
+                    .map(|i| ChunkId(state.descriptor.chunk_hashes[i as usize].clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+        ids.dedup();
+        ids
+    }
+
+    /// Corrupt if any of the bodies stored for `id` no longer verifies.
+    fn check(&self, id: &ChunkId) -> ChunkHealth {
+        let holders = self.holders(id);
+        if holders.is_empty() {
+            return ChunkHealth::Missing;
+        }
+        let intact = holders.iter().all(|(state, indices)| {
+            indices.iter().all(|index| {
+                self.load_chunk(&state.session_id, *index)
+                    .is_some_and(|chunk| chunk_matches(&state.descriptor, *index, &chunk))
+            })
+        });
+        if intact {
+            ChunkHealth::Intact
+        } else {
+            ChunkHealth::Corrupt
+        }
+    }
+
+    /// Deletes the bodies of `id` that no longer verify and clears their
+    /// bits, so resuming fetches them again. Intact copies are kept.
+    fn take(&self, id: &ChunkId) -> Option<DataChunk> {
+        let mut taken = None;
+        for (mut state, indices) in self.holders(id) {
+            let mut cleared = false;
+            for index in indices {
+                let chunk = self.load_chunk(&state.session_id, index);
+                if chunk.as_ref().is_some_and(|c| chunk_matches(&state.descriptor, index, c)) {
+                    continue;
+                }
+                let _ = std::fs::remove_file(self.chunk_path(&state.session_id, index));
+                state.verified.clear(index);
+                cleared = true;
+                taken = taken.or(chunk);
+            }
+            if cleared && self.save(&state).is_ok() {
+                let mut open = self.open.lock().unwrap();
+                if open.contains_key(&state.session_id) {
+                    open.insert(state.session_id.clone(), state);
+                }
+            }
+        }
+        taken
+    }
+}
@@ -4,7 +4,7 @@ use super::{
     state::{ChunkStatus, TransferState},
     stats::TransferStats,
 };
-use crate::large_data_transfer::descriptor::LargeDataDescriptor;
+use crate::large_data_transfer::{descriptor::LargeDataDescriptor, types::TransferPriority};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
@@ -19,6 +19,8 @@ pub struct TransferSession {
     pub chunk_status: HashMap<u32, ChunkStatus>,
     pub last_activity: Instant,
     pub retry_count: u32,
+    /// QoS class the transfer is scheduled in.
+    pub priority: TransferPriority,
 }
 
 /// Information about a peer participating in a transfer session.
@@ -43,6 +45,7 @@ impl TransferSession {
             chunk_status: HashMap::new(),
             last_activity: Instant::now(),
             retry_count: 0,
+            priority: TransferPriority::default(),
         }
     }
 
@@ -7,6 +7,10 @@ use serde::{Deserialize, Serialize};
 pub struct TransferStats {
     /// Total number of chunks transferred.
     pub chunks_transferred: u64,
+    /// Chunks already held locally, e.g. shared with an earlier version,
+    /// and so not transferred.
+    #[serde(default)]
+    pub chunks_reused: u64,
     /// Total bytes sent.
     pub bytes_sent: u64,
     /// Total bytes received.
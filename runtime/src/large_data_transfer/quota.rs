@@ -0,0 +1,211 @@
+//! Per-owner storage quotas and usage accounting.
+//!
+//! [`TenantAccounts`] charges every file stored in a [`Namespace`] to the
+//! owner who stored it and keeps, per owner, the bytes stored, the bytes
+//! served and the storage contracts active, so operators can cap free-tier
+//! users and bill by usage. Files go in through
+//! [`TenantAccounts::store_file`], which refuses those that would take their
+//! owner past the stored-bytes quota, and come out through
+//! [`TenantAccounts::remove`]; charges follow files that are moved.
+//! Replacing a file releases what the old one was charged.
+
+use super::descriptor::LargeDataDescriptor;
+use super::namespace::{canonical, Namespace, NamespaceError};
+use crate::blockchain::state::State;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum QuotaError {
+    #[error("{owner} would store {requested} more bytes over {used}, past the quota of {limit}")]
+    StorageExceeded { owner: String, used: u64, requested: u64, limit: u64 },
+    #[error("{owner} already has {active} of {limit} storage contracts active")]
+    ContractsExceeded { owner: String, active: u32, limit: u32 },
+    #[error(transparent)]
+    Namespace(#[from] NamespaceError),
+}
+
+/// Limits of one owner; `None` is unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageQuota {
+    pub max_bytes_stored: Option<u64>,
+    pub max_active_contracts: Option<u32>,
+}
+
+impl StorageQuota {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_bytes_stored(mut self, max_bytes_stored: u64) -> Self {
+        self.max_bytes_stored = Some(max_bytes_stored);
+        self
+    }
+
+    pub fn with_max_active_contracts(mut self, max_active_contracts: u32) -> Self {
+        self.max_active_contracts = Some(max_active_contracts);
+        self
+    }
+}
+
+/// What one owner uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantUsage {
+    pub bytes_stored: u64,
+    pub files: u32,
+    /// Bytes of the owner's files read back, by anyone.
+    pub bytes_served: u64,
+    pub active_contracts: u32,
+}
+
+/// The owner a stored file is charged to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Charge {
+    owner: String,
+    size_bytes: u64,
+}
+
+/// Whether `path` is `prefix` or lies under it.
+fn under(path: &str, prefix: &str) -> bool {
+    path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Quotas and usage of the owners storing files in a namespace.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TenantAccounts {
+    /// Applies to owners without a quota of their own.
+    pub default_quota: StorageQuota,
+    quotas: BTreeMap<String, StorageQuota>,
+    usage: BTreeMap<String, TenantUsage>,
+    /// Stored files by path.
+    charges: BTreeMap<String, Charge>,
+}
+
+impl TenantAccounts {
+    /// Accounts holding every owner to `default_quota`.
+    pub fn new(default_quota: StorageQuota) -> Self {
+        Self { default_quota, ..Default::default() }
+    }
+
+    pub fn set_quota(&mut self, owner: &str, quota: StorageQuota) {
+        self.quotas.insert(owner.to_string(), quota);
+    }
+
+    pub fn quota(&self, owner: &str) -> &StorageQuota {
+        self.quotas.get(owner).unwrap_or(&self.default_quota)
+    }
+
+    /// What `owner` uses; nothing for unknown owners.
+    pub fn usage(&self, owner: &str) -> TenantUsage {
+        self.usage.get(owner).cloned().unwrap_or_default()
+    }
+
+    /// Every owner using anything, by name.
+    pub fn tenants(&self) -> impl Iterator<Item = (&str, &TenantUsage)> {
+        self.usage.iter().map(|(owner, usage)| (owner.as_str(), usage))
+    }
+
+    fn release(&mut self, path: &str) {
+        if let Some(charge) = self.charges.remove(path) {
+            let usage = self.usage.entry(charge.owner).or_default();
+            usage.bytes_stored -= charge.size_bytes;
+            usage.files -= 1;
+        }
+    }
+
+    /// Stores `descriptor` at `path` in `namespace` on behalf of `owner`,
+    /// unless the file would take the owner past its quota.
+    pub fn store_file(
+        &mut self,
+        namespace: &mut Namespace,
+        path: &str,
+        owner: &str,
+        descriptor: LargeDataDescriptor,
+    ) -> Result<(), QuotaError> {
+        let path = canonical(path)?;
+        let size_bytes = descriptor.size_bytes;
+        if let Some(limit) = self.quota(owner).max_bytes_stored {
+            let replaced = match self.charges.get(&path) {
+                Some(charge) if charge.owner == owner => charge.size_bytes,
+                _ => 0,
+            };
+            let used = self.usage(owner).bytes_stored - replaced;
+            if used + size_bytes > limit {
+                return Err(QuotaError::StorageExceeded {
+                    owner: owner.to_string(),
+                    used,
+                    requested: size_bytes,
+                    limit,
+                });
+            }
+        }
+        namespace.put_file(&path, descriptor)?;
+        self.release(&path);
+        self.charges.insert(path, Charge { owner: owner.to_string(), size_bytes });
+        let usage = self.usage.entry(owner.to_string()).or_default();
+        usage.bytes_stored += size_bytes;
+        usage.files += 1;
+        Ok(())
+    }
+
+    /// Removes the file or directory at `path` from `namespace`, releasing
+    /// the charges of every file removed.
+    pub fn remove(&mut self, namespace: &mut Namespace, path: &str) -> Result<(), QuotaError> {
+        namespace.remove(path)?;
+        let path = canonical(path)?;
+        let removed: Vec<String> =
+            self.charges.keys().filter(|stored| under(stored, &path)).cloned().collect();
+        for stored in removed {
+            self.release(&stored);
+        }
+        Ok(())
+    }
+
+    /// Moves the entry at `from` to `to` in `namespace`, keeping the
+    /// charges of the files moved.
+    pub fn rename(
+        &mut self,
+        namespace: &mut Namespace,
+        from: &str,
+        to: &str,
+    ) -> Result<(), QuotaError> {
+        namespace.rename(from, to)?;
+        let (from, to) = (canonical(from)?, canonical(to)?);
+        let moved: Vec<String> =
+            self.charges.keys().filter(|stored| under(stored, &from)).cloned().collect();
+        for stored in moved {
+            let charge = self.charges.remove(&stored).expect("listed above");
+            let path = format!("{}{}", to, &stored[from.len()..]);
+            self.charges.insert(path, charge);
+        }
+        Ok(())
+    }
+
+    /// Records `bytes` of `owner`'s files being read back.
+    pub fn record_served(&mut self, owner: &str, bytes: u64) {
+        self.usage.entry(owner.to_string()).or_default().bytes_served += bytes;
+    }
+
+    /// Counts the open storage contracts of every owner on the chain,
+    /// pending or active.
+    pub fn sync_contracts(&mut self, state: &State) {
+        for usage in self.usage.values_mut() {
+            usage.active_contracts = 0;
+        }
+        for contract in state.storage_contracts.values().filter(|c| c.is_open()) {
+            self.usage.entry(contract.owner.clone()).or_default().active_contracts += 1;
+        }
+    }
+
+    /// Check that `owner` may open another storage contract.
+    pub fn check_contract(&self, owner: &str) -> Result<(), QuotaError> {
+        let active = self.usage(owner).active_contracts;
+        match self.quota(owner).max_active_contracts {
+            Some(limit) if active >= limit => {
+                Err(QuotaError::ContractsExceeded { owner: owner.to_string(), active, limit })
+            }
+            _ => Ok(()),
+        }
+    }
+}
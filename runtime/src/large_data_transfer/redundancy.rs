@@ -1,4 +1,21 @@
+//! Redundancy policies: full replication or Reed-Solomon erasure coding.
+//!
+//! Replication stores `copies + 1` whole copies of every object. An
+//! [`ErasureScheme`] instead splits the object into `k` data shards and adds
+//! `m` parity shards, each stored on its own node; any `k` of the `k + m`
+//! shards rebuild the object. Surviving `m` lost nodes then costs `(k + m) / k`
+//! times the object's size rather than `m + 1` times.
+//!
+//! The code is systematic, so the data shards are the object itself, and
+//! works over GF(2^8): parity shard `i` is the data shards weighted by row `i`
+//! of a Cauchy matrix, every square submatrix of which is invertible. That is
+//! what makes any `k` shards enough.
+
+use super::chunk::DataChunk;
+use super::config::CompressionAlgorithm;
+use super::LargeDataResult;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 /// Configuration governing redundancy & error-correction. Responsibility: keep
 /// numeric policy values – no storage logic.
@@ -7,11 +24,38 @@ pub struct RedundancyConfig {
     pub replica_count: u32,
     pub erasure_coding: bool,
     pub min_nodes: u32,
+    /// Data shards per object when `erasure_coding` is on.
+    #[serde(default = "default_data_shards")]
+    pub data_shards: u8,
+    /// Parity shards per object when `erasure_coding` is on.
+    #[serde(default = "default_parity_shards")]
+    pub parity_shards: u8,
+}
+
+fn default_data_shards() -> u8 {
+    4
+}
+
+fn default_parity_shards() -> u8 {
+    2
 }
 
 impl Default for RedundancyConfig {
     fn default() -> Self {
-        Self { replica_count: 1, erasure_coding: false, min_nodes: 1 }
+        Self {
+            replica_count: 1,
+            erasure_coding: false,
+            min_nodes: 1,
+            data_shards: default_data_shards(),
+            parity_shards: default_parity_shards(),
+        }
+    }
+}
+
+impl RedundancyConfig {
+    /// The erasure scheme to store objects with, or `None` to replicate.
+    pub fn scheme(&self) -> Option<ErasureScheme> {
+        self.erasure_coding.then_some(ErasureScheme::new(self.data_shards, self.parity_shards))
     }
 }
 
@@ -20,10 +64,260 @@ impl Default for RedundancyConfig {
 pub struct RedundancyPolicy {
     pub copies: u8,
     pub geo_spread: bool,
+    /// Erasure code objects with this scheme instead of replicating them;
+    /// `copies` is then ignored.
+    pub erasure: Option<ErasureScheme>,
+}
+
+impl RedundancyPolicy {
+    /// Bytes stored across the network for an object of `bytes`.
+    pub fn stored_bytes(&self, bytes: u128) -> u128 {
+        match self.erasure {
+            Some(scheme) => {
+                let shard = bytes.div_ceil(scheme.data_shards.max(1) as u128);
+                shard.saturating_mul(scheme.total_shards() as u128)
+            }
+            None => bytes.saturating_mul((self.copies as u128).saturating_add(1)),
+        }
+    }
+
+    /// Distinct nodes an object is spread over.
+    pub fn nodes_needed(&self) -> usize {
+        match self.erasure {
+            Some(scheme) => scheme.total_shards(),
+            None => self.copies as usize + 1,
+        }
+    }
 }
 
 impl From<RedundancyConfig> for RedundancyPolicy {
     fn from(cfg: RedundancyConfig) -> Self {
-        RedundancyPolicy { copies: cfg.replica_count as u8, geo_spread: false }
+        RedundancyPolicy { copies: cfg.replica_count as u8, geo_spread: false, erasure: cfg.scheme() }
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ErasureError {
+    #[error("{data} data and {parity} parity shards is not a valid scheme")]
+    InvalidScheme { data: u8, parity: u8 },
+    #[error("expected {expected} shards, got {got}")]
+    WrongShardCount { expected: usize, got: usize },
+    #[error("{present} shards left, {needed} needed")]
+    TooFewShards { present: usize, needed: usize },
+    #[error("shards differ in size")]
+    ShardSizeMismatch,
+}
+
+/// `k` data shards plus `m` parity shards; any `k` rebuild the object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErasureScheme {
+    pub data_shards: u8,
+    pub parity_shards: u8,
+}
+
+impl ErasureScheme {
+    pub fn new(data_shards: u8, parity_shards: u8) -> Self {
+        Self { data_shards, parity_shards }
+    }
+
+    /// At least one data shard, and at most 256 shards in all: the Cauchy
+    /// matrix needs a distinct field element per shard.
+    pub fn validate(&self) -> Result<(), ErasureError> {
+        if self.data_shards == 0 || self.total_shards() > 256 {
+            return Err(ErasureError::InvalidScheme { data: self.data_shards, parity: self.parity_shards });
+        }
+        Ok(())
+    }
+
+    pub fn total_shards(&self) -> usize {
+        self.data_shards as usize + self.parity_shards as usize
+    }
+
+    /// Stored bytes per byte of the object.
+    pub fn overhead(&self) -> f64 {
+        self.total_shards() as f64 / self.data_shards as f64
+    }
+
+    /// Coefficient of data shard `j` in parity shard `i`.
+    fn coefficient(&self, i: usize, j: usize) -> u8 {
+        gf_inv((self.data_shards as usize + i) as u8 ^ j as u8)
+    }
+
+    /// Row `shard` of the encoding matrix: a unit row for a data shard, a
+    /// Cauchy row for a parity shard.
+    fn row(&self, shard: usize) -> Vec<u8> {
+        let k = self.data_shards as usize;
+        match shard.checked_sub(k) {
+            None => (0..k).map(|j| u8::from(j == shard)).collect(),
+            Some(i) => (0..k).map(|j| self.coefficient(i, j)).collect(),
+        }
+    }
+
+    /// Splits `data` into the scheme's shards, data shards first. The last
+    /// data shard is zero padded; keep the object's length to decode it.
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<Vec<u8>>, ErasureError> {
+        self.validate()?;
+        let k = self.data_shards as usize;
+        let shard_len = data.len().div_ceil(k).max(1);
+        let mut shards: Vec<Vec<u8>> = (0..k)
+            .map(|j| {
+                let mut shard: Vec<u8> = data.iter().skip(j * shard_len).take(shard_len).copied().collect();
+                shard.resize(shard_len, 0);
+                shard
+            })
+            .collect();
+        for i in 0..self.parity_shards as usize {
+            let coefficients = self.row(k + i);
+            shards.push(combine(&coefficients, &shards[..k], shard_len));
+        }
+        Ok(shards)
+    }
+
+    /// Fills in every missing shard of `shards`, from any `k` present ones.
+    pub fn reconstruct(&self, shards: &mut [Option<Vec<u8>>]) -> Result<(), ErasureError> {
+        self.validate()?;
+        let k = self.data_shards as usize;
+        if shards.len() != self.total_shards() {
+            return Err(ErasureError::WrongShardCount { expected: self.total_shards(), got: shards.len() });
+        }
+        let present: Vec<usize> = (0..shards.len()).filter(|i| shards[*i].is_some()).collect();
+        if present.len() < k {
+            return Err(ErasureError::TooFewShards { present: present.len(), needed: k });
+        }
+        let shard_len = shards[present[0]].as_ref().map_or(0, Vec::len);
+        if present.iter().any(|i| shards[*i].as_ref().map_or(0, Vec::len) != shard_len) {
+            return Err(ErasureError::ShardSizeMismatch);
+        }
+
+        if shards[..k].iter().any(Option::is_none) {
+            // Invert the rows of k present shards to get back the data.
+            let rows: Vec<usize> = present[..k].to_vec();
+            let decode = invert(rows.iter().map(|r| self.row(*r)).collect())
+                .expect("every square submatrix of a Cauchy code is invertible");
+            let inputs: Vec<Vec<u8>> = rows.iter().map(|r| shards[*r].clone().expect("present")).collect();
+            for (shard, coefficients) in shards[..k].iter_mut().zip(&decode) {
+                if shard.is_none() {
+                    *shard = Some(combine(coefficients, &inputs, shard_len));
+                }
+            }
+        }
+        let data: Vec<Vec<u8>> = shards[..k].iter().map(|s| s.clone().expect("rebuilt")).collect();
+        for (index, shard) in shards.iter_mut().enumerate().skip(k) {
+            if shard.is_none() {
+                *shard = Some(combine(&self.row(index), &data, shard_len));
+            }
+        }
+        Ok(())
+    }
+
+    /// The `len` bytes of the object `shards` were encoded from.
+    pub fn decode(&self, shards: &[Option<Vec<u8>>], len: usize) -> Result<Vec<u8>, ErasureError> {
+        let mut shards = shards.to_vec();
+        self.reconstruct(&mut shards)?;
+        let mut data: Vec<u8> =
+            shards.into_iter().take(self.data_shards as usize).flatten().flatten().collect();
+        data.truncate(len);
+        Ok(data)
+    }
+
+    /// [`ErasureScheme::encode`] into content-addressed chunks, chunk `i`
+    /// holding shard `i`.
+    pub fn encode_chunks(&self, data: &[u8]) -> LargeDataResult<Vec<DataChunk>> {
+        self.encode(data)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, shard)| DataChunk::new_from_slice(shard, i as u32, CompressionAlgorithm::None))
+            .collect()
+    }
+
+    /// [`ErasureScheme::decode`] from the chunks that could be fetched,
+    /// indexed by shard. Chunks that fail their integrity check count as
+    /// missing.
+    pub fn decode_chunks(&self, chunks: &[Option<DataChunk>], len: usize) -> LargeDataResult<Vec<u8>> {
+        let mut shards = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let shard = match chunk {
+                Some(chunk) if chunk.verify_integrity().is_ok() => Some(chunk.decompress()?),
+                _ => None,
+            };
+            shards.push(shard);
+        }
+        Ok(self.decode(&shards, len)?)
+    }
+}
+
+/// `Σ coefficients[j] · shards[j]`, byte by byte.
+fn combine(coefficients: &[u8], shards: &[Vec<u8>], len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; len];
+    for (c, shard) in coefficients.iter().zip(shards) {
+        if *c == 0 {
+            continue;
+        }
+        for (o, b) in out.iter_mut().zip(shard) {
+            *o ^= gf_mul(*c, *b);
+        }
     }
-} 
\ No newline at end of file
+    out
+}
+
+/// Inverse of a square matrix over GF(2^8) by Gauss-Jordan elimination, or
+/// `None` if it is singular.
+fn invert(mut m: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let n = m.len();
+    let mut inv: Vec<Vec<u8>> = (0..n).map(|i| (0..n).map(|j| u8::from(i == j)).collect()).collect();
+    for col in 0..n {
+        let pivot = (col..n).find(|r| m[*r][col] != 0)?;
+        m.swap(col, pivot);
+        inv.swap(col, pivot);
+        let scale = gf_inv(m[col][col]);
+        m[col].iter_mut().chain(inv[col].iter_mut()).for_each(|v| *v = gf_mul(*v, scale));
+        let (pivot, pivot_inv) = (m[col].clone(), inv[col].clone());
+        for (r, (row, inv_row)) in m.iter_mut().zip(inv.iter_mut()).enumerate() {
+            let factor = row[col];
+            if r == col || factor == 0 {
+                continue;
+            }
+            for (v, p) in row.iter_mut().zip(&pivot).chain(inv_row.iter_mut().zip(&pivot_inv)) {
+                *v ^= gf_mul(factor, *p);
+            }
+        }
+    }
+    Some(inv)
+}
+
+/// Exponent and logarithm tables of GF(2^8) with the polynomial 0x11d. The
+/// exponent table is doubled so a sum of two logarithms indexes it directly.
+static GF: ([u8; 512], [u8; 256]) = gf_tables();
+
+const fn gf_tables() -> ([u8; 512], [u8; 256]) {
+    let mut exp = [0u8; 512];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11d;
+        }
+        i += 1;
+    }
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    (exp, log)
+}
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    GF.0[GF.1[a as usize] as usize + GF.1[b as usize] as usize]
+}
+
+/// Multiplicative inverse; `a` must not be zero.
+fn gf_inv(a: u8) -> u8 {
+    GF.0[255 - GF.1[a as usize] as usize]
+}
@@ -0,0 +1,318 @@
+//! Background scrubbing of stored chunks.
+//!
+//! Chunks on a long-lived storage node can rot without anyone reading them.
+//! The [`Scrubber`] walks every [`ScrubTarget`] in small batches with pauses
+//! in between, so it never competes with transfers, and re-hashes each chunk
+//! against its [`ChunkId`]. A chunk that no longer matches is taken out of
+//! service into quarantine and a [`RepairRequest`] goes out so a healthy
+//! copy is fetched from a peer. Counts are kept per disk, giving the rate of
+//! silent corruption each one suffers.
+//!
+//! [`run_audit`] ties this to the network: corrupt chunks are fetched again,
+//! re-announced for replication, peers serving bad data lose reputation and
+//! an [`AuditReport`] goes to monitoring after every pass.
+
+use crate::large_data_transfer::{
+    chunk::{ChunkId, DataChunk},
+    network::coordinator::NetworkTransferCoordinator,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// What a scrubbed chunk turned out to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkHealth {
+    Intact,
+    Corrupt,
+    /// Removed or expired since it was listed.
+    Missing,
+}
+
+/// A store of chunks the scrubber can walk.
+pub trait ScrubTarget: Send + Sync {
+    /// Name of the disk the chunks are on, for reporting.
+    fn disk(&self) -> String;
+
+    /// Ids of the chunks stored.
+    fn chunk_ids(&self) -> Vec<ChunkId>;
+
+    /// Re-reads `id` and checks that it still hashes to its id.
+    fn check(&self, id: &ChunkId) -> ChunkHealth;
+
+    /// Takes `id` out of service, returning what was stored if it is still
+    /// readable.
+    fn take(&self, id: &ChunkId) -> Option<DataChunk>;
+}
+
+/// Whether `chunk`, as stored, hashes to `id`.
+pub fn rehash_matches(chunk: &DataChunk, id: &ChunkId) -> bool {
+    chunk.id == *id && ChunkId::from_data(&chunk.data) == *id
+}
+
+/// Pacing of the scrubber.
+#[derive(Debug, Clone)]
+pub struct ScrubConfig {
+    /// Chunks checked between pauses.
+    pub batch_size: usize,
+    /// Pause after every batch.
+    pub batch_pause: Duration,
+    /// Time between the starts of two passes over all targets.
+    pub pass_interval: Duration,
+}
+
+impl Default for ScrubConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 64,
+            batch_pause: Duration::from_millis(50),
+            pass_interval: Duration::from_secs(6 * 3600),
+        }
+    }
+}
+
+/// Scrubbing counts of one disk.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiskScrubStats {
+    pub passes: u64,
+    pub chunks_checked: u64,
+    pub corrupt: u64,
+    pub repaired: u64,
+    /// Unix time the last pass finished.
+    pub last_pass: Option<u64>,
+}
+
+impl DiskScrubStats {
+    /// Share of the checked chunks found corrupt.
+    pub fn corruption_rate(&self) -> f64 {
+        if self.chunks_checked == 0 {
+            0.0
+        } else {
+            self.corrupt as f64 / self.chunks_checked as f64
+        }
+    }
+}
+
+/// A corrupt chunk to fetch again from a peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairRequest {
+    pub disk: String,
+    pub chunk_id: ChunkId,
+}
+
+/// A chunk taken out of service.
+#[derive(Debug, Clone)]
+pub struct Quarantined {
+    pub disk: String,
+    /// The corrupt bytes, if they could still be read.
+    pub chunk: Option<DataChunk>,
+    /// Unix time it was found.
+    pub found_at: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[derive(Debug, Default)]
+pub struct Scrubber {
+    config: ScrubConfig,
+    stats: BTreeMap<String, DiskScrubStats>,
+    quarantine: HashMap<ChunkId, Quarantined>,
+    repairs: Option<mpsc::UnboundedSender<RepairRequest>>,
+}
+
+impl Scrubber {
+    pub fn new(config: ScrubConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Sends a [`RepairRequest`] to `repairs` for every corrupt chunk found.
+    pub fn with_repairs(mut self, repairs: mpsc::UnboundedSender<RepairRequest>) -> Self {
+        self.repairs = Some(repairs);
+        self
+    }
+
+    pub fn config(&self) -> &ScrubConfig {
+        &self.config
+    }
+
+    /// Checks `ids` on `target`, quarantining and requesting repair of the
+    /// corrupt ones, which are returned.
+    pub fn scrub(&mut self, target: &dyn ScrubTarget, ids: &[ChunkId]) -> Vec<ChunkId> {
+        let disk = target.disk();
+        let mut corrupt = Vec::new();
+        for id in ids {
+            let health = target.check(id);
+            if health == ChunkHealth::Missing {
+                continue;
+            }
+            let stats = self.stats.entry(disk.clone()).or_default();
+            stats.chunks_checked += 1;
+            if health == ChunkHealth::Intact {
+                continue;
+            }
+            stats.corrupt += 1;
+            tracing::warn!(%disk, chunk = %id, "corrupt chunk quarantined");
+            let chunk = target.take(id);
+            self.quarantine
+                .insert(id.clone(), Quarantined { disk: disk.clone(), chunk, found_at: now() });
+            if let Some(repairs) = &self.repairs {
+                let _ = repairs.send(RepairRequest { disk: disk.clone(), chunk_id: id.clone() });
+            }
+            corrupt.push(id.clone());
+        }
+        corrupt
+    }
+
+    /// Scrubs every chunk of `target` at once, without pauses.
+    pub fn scrub_all(&mut self, target: &dyn ScrubTarget) -> Vec<ChunkId> {
+        let corrupt = self.scrub(target, &target.chunk_ids());
+        self.finish_pass(&target.disk());
+        corrupt
+    }
+
+    fn finish_pass(&mut self, disk: &str) {
+        let stats = self.stats.entry(disk.to_string()).or_default();
+        stats.passes += 1;
+        stats.last_pass = Some(now());
+    }
+
+    /// Counts `id` as repaired, releasing it from quarantine.
+    pub fn record_repair(&mut self, id: &ChunkId) {
+        if let Some(quarantined) = self.quarantine.remove(id) {
+            self.stats.entry(quarantined.disk).or_default().repaired += 1;
+        }
+    }
+
+    pub fn stats(&self) -> &BTreeMap<String, DiskScrubStats> {
+        &self.stats
+    }
+
+    pub fn quarantined(&self) -> &HashMap<ChunkId, Quarantined> {
+        &self.quarantine
+    }
+}
+
+/// Summary of the chunk integrity of a node, for monitoring.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// Unix time of the report.
+    pub at: u64,
+    pub disks: BTreeMap<String, DiskScrubStats>,
+    /// Corrupt chunks still waiting for a healthy copy.
+    pub quarantined: usize,
+    /// Corrupt chunks each peer served.
+    pub peers: BTreeMap<String, u64>,
+}
+
+impl AuditReport {
+    /// Chunks found corrupt on all disks.
+    pub fn corrupt(&self) -> u64 {
+        self.disks.values().map(|stats| stats.corrupt).sum()
+    }
+
+    /// Corrupt chunks replaced by healthy copies on all disks.
+    pub fn repaired(&self) -> u64 {
+        self.disks.values().map(|stats| stats.repaired).sum()
+    }
+}
+
+/// Checks every chunk of `targets` once, a batch at a time.
+async fn scrub_pass(scrubber: &Mutex<Scrubber>, targets: &[Arc<dyn ScrubTarget>]) {
+    let config = scrubber.lock().unwrap().config().clone();
+    for target in targets {
+        let ids = target.chunk_ids();
+        for batch in ids.chunks(config.batch_size.max(1)) {
+            scrubber.lock().unwrap().scrub(target.as_ref(), batch);
+            tokio::time::sleep(config.batch_pause).await;
+        }
+        scrubber.lock().unwrap().finish_pass(&target.disk());
+    }
+}
+
+/// Scrubs `targets` forever, one batch at a time, pausing between batches
+/// and passes.
+pub async fn run_scrubber(scrubber: Arc<Mutex<Scrubber>>, targets: Vec<Arc<dyn ScrubTarget>>) {
+    let pass_interval = scrubber.lock().unwrap().config().pass_interval;
+    loop {
+        let started = tokio::time::Instant::now();
+        scrub_pass(&scrubber, &targets).await;
+        tokio::time::sleep_until(started + pass_interval).await;
+    }
+}
+
+/// Fetches a healthy copy of every chunk in `requests` from peers, stores
+/// it in the coordinator's chunk manager and announces it again so it is
+/// replicated from here. Peers serving data that does not hash to the
+/// chunk's id are penalized and the next one is asked.
+pub async fn run_repairs(
+    scrubber: Arc<Mutex<Scrubber>>,
+    coordinator: NetworkTransferCoordinator,
+    mut requests: mpsc::UnboundedReceiver<RepairRequest>,
+) {
+    while let Some(request) = requests.recv().await {
+        let id = &request.chunk_id;
+        let chunk = loop {
+            match coordinator.request_chunk_with_peer(id.clone()).await {
+                Ok(Some((_, chunk))) if rehash_matches(&chunk, id) => break Some(chunk),
+                Ok(Some((peer, _))) => coordinator.penalize_peer(&peer, id),
+                Ok(None) => break None,
+                Err(e) => {
+                    tracing::warn!(chunk = %id, error = %e, "repair fetch failed");
+                    break None;
+                }
+            }
+        };
+        let Some(chunk) = chunk else {
+            tracing::warn!(chunk = %id, "no healthy copy of corrupt chunk found");
+            continue;
+        };
+        if let Err(e) = coordinator.chunk_manager.store_chunk(chunk) {
+            tracing::warn!(chunk = %id, error = %e, "repaired chunk not stored");
+            continue;
+        }
+        scrubber.lock().unwrap().record_repair(id);
+        if let Err(e) = coordinator.announce_chunks(vec![id.clone()]).await {
+            tracing::warn!(chunk = %id, error = %e, "repaired chunk not announced");
+        }
+    }
+}
+
+/// Audits the integrity of `targets` forever: every pass re-hashes all
+/// their chunks, repairs the corrupt ones from peers through
+/// [`run_repairs`] and sends an [`AuditReport`] to `reports`.
+pub async fn run_audit(
+    coordinator: NetworkTransferCoordinator,
+    targets: Vec<Arc<dyn ScrubTarget>>,
+    config: ScrubConfig,
+    reports: mpsc::UnboundedSender<AuditReport>,
+) {
+    let (repairs, requests) = mpsc::unbounded_channel();
+    let scrubber = Arc::new(Mutex::new(Scrubber::new(config.clone()).with_repairs(repairs)));
+    tokio::spawn(run_repairs(scrubber.clone(), coordinator.clone(), requests));
+    loop {
+        let started = tokio::time::Instant::now();
+        scrub_pass(&scrubber, &targets).await;
+        let report = {
+            let scrubber = scrubber.lock().unwrap();
+            AuditReport {
+                at: now(),
+                disks: scrubber.stats().clone(),
+                quarantined: scrubber.quarantined().len(),
+                peers: coordinator.corrupt_chunks_by_peer(),
+            }
+        };
+        tracing::info!(
+            corrupt = report.corrupt(),
+            repaired = report.repaired(),
+            quarantined = report.quarantined,
+            "integrity audit pass finished"
+        );
+        // Monitoring going away does not stop the audit.
+        let _ = reports.send(report);
+        tokio::time::sleep_until(started + config.pass_interval).await;
+    }
+}
@@ -0,0 +1,500 @@
+//! Streaming reads and writes of chunked large data.
+//!
+//! A [`ChunkReader`] is an [`AsyncRead`] over the object a
+//! [`LargeDataDescriptor`] describes. It fetches the chunks in order, a few
+//! ahead of the one being read, verifies each against the descriptor and
+//! drops it once read, so a dataset of any size streams through a few
+//! chunks of memory. A [`ChunkWriter`] is the converse [`AsyncWrite`]: it
+//! cuts what is written into chunks as they fill, hands each to a
+//! [`ChunkSink`] and, once shut down, describes what it wrote. Up to
+//! [`DEFAULT_WRITE_PARALLELISM`] chunks are compressed and hashed on the
+//! blocking pool and stored at once, so sending one chunk overlaps
+//! preparing the next; writes wait while that many are in flight.
+
+use crate::distributed_storage::ConsistencyLevel;
+use crate::large_data_transfer::{
+    cdc::{self, CdcConfig},
+    chunk::{ChunkId, DataChunk},
+    compression::AdaptiveCompressor,
+    config::CompressionAlgorithm,
+    descriptor::LargeDataDescriptor,
+    manager::ChunkManager,
+    namespace::Namespace,
+    network::NetworkTransferCoordinator,
+    protocol::resume::chunk_matches,
+    versions::VersionStore,
+    LargeDataError, LargeDataResult,
+};
+use futures::future::BoxFuture;
+use futures::stream::{FuturesOrdered, FuturesUnordered, StreamExt};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::task::JoinHandle;
+
+/// Chunks a [`ChunkReader`] fetches ahead of the one being read.
+pub const DEFAULT_READ_AHEAD: usize = 2;
+
+/// Chunks a [`ChunkWriter`] prepares and stores at once.
+pub const DEFAULT_WRITE_PARALLELISM: usize = 4;
+
+/// Where a [`ChunkReader`] gets chunks from.
+pub trait ChunkSource: Send + Unpin + 'static {
+    /// Fetches the chunk with `id`, or `None` if no one holds it.
+    fn fetch(&self, id: ChunkId) -> BoxFuture<'static, LargeDataResult<Option<DataChunk>>>;
+}
+
+/// Where a [`ChunkWriter`] puts the chunks it cuts.
+pub trait ChunkSink: Send + Unpin + 'static {
+    fn put(&self, chunk: DataChunk) -> BoxFuture<'static, LargeDataResult<()>>;
+}
+
+impl ChunkSource for Arc<ChunkManager> {
+    fn fetch(&self, id: ChunkId) -> BoxFuture<'static, LargeDataResult<Option<DataChunk>>> {
+        let chunk = self.get_chunk(&id);
+        Box::pin(async move { Ok(chunk) })
+    }
+}
+
+/// Stores `chunk` in `manager` on the blocking pool, as encrypting it may
+/// take a while.
+async fn store_blocking(manager: Arc<ChunkManager>, chunk: DataChunk) -> LargeDataResult<()> {
+    tokio::task::spawn_blocking(move || manager.store_chunk(chunk))
+        .await
+        .map_err(|e| LargeDataError::Cache(e.to_string()))?
+}
+
+impl ChunkSink for Arc<ChunkManager> {
+    fn put(&self, chunk: DataChunk) -> BoxFuture<'static, LargeDataResult<()>> {
+        Box::pin(store_blocking(self.clone(), chunk))
+    }
+}
+
+impl ChunkSource for NetworkTransferCoordinator {
+    /// Serves chunks from the local cache, and requests the others from
+    /// peers, caching them.
+    fn fetch(&self, id: ChunkId) -> BoxFuture<'static, LargeDataResult<Option<DataChunk>>> {
+        let coordinator = self.clone();
+        Box::pin(async move {
+            if let Some(chunk) = coordinator.chunk_manager.get_chunk(&id) {
+                return Ok(Some(chunk));
+            }
+            let chunk = coordinator.request_chunk(id).await?;
+            if let Some(chunk) = &chunk {
+                coordinator.chunk_manager.store_chunk(chunk.clone())?;
+            }
+            Ok(chunk)
+        })
+    }
+}
+
+/// A [`ChunkSource`] requesting every chunk through a coordinator at a
+/// [`ConsistencyLevel`].
+pub struct ConsistentSource {
+    coordinator: NetworkTransferCoordinator,
+    level: ConsistencyLevel,
+}
+
+impl ChunkSource for ConsistentSource {
+    fn fetch(&self, id: ChunkId) -> BoxFuture<'static, LargeDataResult<Option<DataChunk>>> {
+        let coordinator = self.coordinator.clone();
+        let level = self.level;
+        Box::pin(async move { coordinator.request_chunk_consistent(id, level).await })
+    }
+}
+
+impl ChunkSink for NetworkTransferCoordinator {
+    /// Caches the chunk and announces it to peers.
+    fn put(&self, chunk: DataChunk) -> BoxFuture<'static, LargeDataResult<()>> {
+        let coordinator = self.clone();
+        Box::pin(async move {
+            let id = chunk.id.clone();
+            store_blocking(coordinator.chunk_manager.clone(), chunk).await?;
+            coordinator.announce_chunks(vec![id]).await
+        })
+    }
+}
+
+fn io_error(e: impl std::fmt::Display) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Reads the object a descriptor describes, chunk by chunk.
+pub struct ChunkReader<S> {
+    source: S,
+    descriptor: LargeDataDescriptor,
+    read_ahead: usize,
+    /// Index of the next chunk to fetch.
+    next_fetch: u32,
+    /// Index of the chunk the next fetch to complete belongs to.
+    next_read: u32,
+    in_flight: FuturesOrdered<BoxFuture<'static, LargeDataResult<Option<DataChunk>>>>,
+    /// The chunk being read, decompressed, and how much of it was read.
+    current: Vec<u8>,
+    position: usize,
+}
+
+impl<S: ChunkSource> ChunkReader<S> {
+    pub fn new(source: S, descriptor: LargeDataDescriptor) -> Self {
+        Self {
+            source,
+            descriptor,
+            read_ahead: DEFAULT_READ_AHEAD,
+            next_fetch: 0,
+            next_read: 0,
+            in_flight: FuturesOrdered::new(),
+            current: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Fetches `chunks` ahead of the one being read; `0` fetches each
+    /// chunk only once the previous one was read.
+    pub fn with_read_ahead(mut self, chunks: usize) -> Self {
+        self.read_ahead = chunks;
+        self
+    }
+
+    pub fn descriptor(&self) -> &LargeDataDescriptor {
+        &self.descriptor
+    }
+
+    /// Requests chunks until `read_ahead` are in flight past the current
+    /// one, or none are left.
+    fn fill_window(&mut self) {
+        while self.in_flight.len() <= self.read_ahead {
+            let Some(hash) = self.descriptor.chunk_hashes.get(self.next_fetch as usize) else {
+                break;
+            };
+            self.in_flight.push_back(self.source.fetch(ChunkId(hash.clone())));
+            self.next_fetch += 1;
+        }
+    }
+}
+
+impl<S: ChunkSource> AsyncRead for ChunkReader<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.position < this.current.len() || buf.remaining() == 0 {
+                let n = buf.remaining().min(this.current.len() - this.position);
+                buf.put_slice(&this.current[this.position..this.position + n]);
+                this.position += n;
+                return Poll::Ready(Ok(()));
+            }
+            this.fill_window();
+            let Some(fetched) = ready!(this.in_flight.poll_next_unpin(cx)) else {
+                // Every chunk was read.
+                return Poll::Ready(Ok(()));
+            };
+            let index = this.next_read;
+            this.next_read += 1;
+            let chunk = fetched.map_err(io_error)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("chunk {} of {} is unavailable", index, this.descriptor.id),
+                )
+            })?;
+            if !chunk_matches(&this.descriptor, index, &chunk) {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("chunk {} of {} failed verification", index, this.descriptor.id),
+                )));
+            }
+            this.current = chunk.decompress().map_err(io_error)?;
+            this.position = 0;
+        }
+    }
+}
+
+/// Writes an object as chunks of a fixed size, or of sizes set by the
+/// content.
+pub struct ChunkWriter<S> {
+    sink: S,
+    id: String,
+    /// Bytes buffered before a chunk is cut: the fixed chunk size, or the
+    /// maximum one when chunking by content.
+    chunk_size: usize,
+    compression: CompressionAlgorithm,
+    /// Compresses by content type instead, when set.
+    compressor: Option<Arc<AdaptiveCompressor>>,
+    content_defined: Option<CdcConfig>,
+    /// Chunks of the previous version, which the sink already has.
+    base: HashSet<String>,
+    reused: u32,
+    /// Bytes written since the last chunk was cut.
+    buffer: Vec<u8>,
+    /// Index of the next chunk to cut.
+    next_index: u32,
+    /// Ids of the chunks prepared so far, in order.
+    chunk_hashes: Vec<String>,
+    size_bytes: u64,
+    hasher: Sha256,
+    /// Chunks at most in `preparing` and `storing` together.
+    parallelism: usize,
+    /// Chunks being compressed and hashed, in order.
+    preparing: FuturesOrdered<JoinHandle<LargeDataResult<DataChunk>>>,
+    /// Chunks being handed to the sink.
+    storing: FuturesUnordered<BoxFuture<'static, LargeDataResult<()>>>,
+    shut_down: bool,
+}
+
+impl<S: ChunkSink> ChunkWriter<S> {
+    /// A writer of the object `id` in chunks of `chunk_size` bytes, the last
+    /// one possibly shorter.
+    pub fn new(sink: S, id: &str, chunk_size: u32) -> Self {
+        let chunk_size = chunk_size.max(1) as usize;
+        Self {
+            sink,
+            id: id.to_string(),
+            chunk_size,
+            compression: CompressionAlgorithm::None,
+            compressor: None,
+            content_defined: None,
+            base: HashSet::new(),
+            reused: 0,
+            buffer: Vec::with_capacity(chunk_size),
+            next_index: 0,
+            chunk_hashes: Vec::new(),
+            size_bytes: 0,
+            hasher: Sha256::new(),
+            parallelism: DEFAULT_WRITE_PARALLELISM,
+            preparing: FuturesOrdered::new(),
+            storing: FuturesUnordered::new(),
+            shut_down: false,
+        }
+    }
+
+    /// Prepares and stores up to `chunks` chunks at once; `1` handles each
+    /// chunk only once the previous one was stored.
+    pub fn with_parallelism(mut self, chunks: usize) -> Self {
+        self.parallelism = chunks.max(1);
+        self
+    }
+
+    /// Compresses each chunk with `compression` where that makes it smaller.
+    pub fn with_compression(mut self, compression: CompressionAlgorithm) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Compresses each chunk by the profile of its content type where that
+    /// makes it smaller, overriding [`with_compression`](Self::with_compression).
+    pub fn with_compressor(mut self, compressor: Arc<AdaptiveCompressor>) -> Self {
+        self.compressor = Some(compressor);
+        self
+    }
+
+    /// Cuts chunks where the content says to, see [`cdc`](super::cdc),
+    /// instead of every `chunk_size` bytes.
+    pub fn with_content_defined(mut self, config: CdcConfig) -> LargeDataResult<Self> {
+        config.validate()?;
+        self.chunk_size = config.max_size as usize;
+        self.content_defined = Some(config);
+        Ok(self)
+    }
+
+    /// Writes a new version of `base`: chunks `base` lists are not handed to
+    /// the sink again, only referenced by the descriptor.
+    pub fn with_base(mut self, base: &LargeDataDescriptor) -> Self {
+        self.base = base.chunk_hashes.iter().cloned().collect();
+        self
+    }
+
+    /// Chunks prepared so far that were already in the base version.
+    pub fn reused(&self) -> u32 {
+        self.reused
+    }
+
+    /// The descriptor of everything written, once the writer is shut down.
+    pub fn descriptor(&self) -> Option<LargeDataDescriptor> {
+        self.shut_down.then(|| {
+            LargeDataDescriptor::new(
+                self.id.clone(),
+                format!("{:x}", self.hasher.clone().finalize()),
+                self.size_bytes,
+                self.chunk_hashes.clone(),
+            )
+        })
+    }
+
+    /// Cuts the next chunk off the buffer and starts compressing and
+    /// hashing it on the blocking pool.
+    fn cut(&mut self) {
+        let len = match &self.content_defined {
+            Some(config) => cdc::cut_point(&self.buffer, config),
+            None => self.buffer.len(),
+        };
+        let rest = self.buffer.split_off(len);
+        let data = std::mem::replace(&mut self.buffer, rest);
+        let index = self.next_index;
+        self.next_index += 1;
+        let compressor = self.compressor.clone();
+        let compression = self.compression;
+        self.preparing.push_back(tokio::task::spawn_blocking(move || match compressor {
+            Some(compressor) => DataChunk::new_with_compressor(data, index, &compressor),
+            None => DataChunk::new_from_slice(data, index, compression),
+        }));
+    }
+
+    /// Moves the chunks in flight along: prepared ones are recorded in
+    /// order and, unless the base version has them, handed to the sink.
+    /// Ready once fewer than `limit` chunks are in flight.
+    fn poll_in_flight(&mut self, cx: &mut Context<'_>, limit: usize) -> Poll<io::Result<()>> {
+        loop {
+            while let Poll::Ready(Some(stored)) = self.storing.poll_next_unpin(cx) {
+                stored.map_err(io_error)?;
+            }
+            if let Poll::Ready(Some(prepared)) = self.preparing.poll_next_unpin(cx) {
+                let chunk = prepared.map_err(io_error)?.map_err(io_error)?;
+                self.chunk_hashes.push(chunk.id.as_str().to_string());
+                if self.base.contains(chunk.id.as_str()) {
+                    self.reused += 1;
+                } else {
+                    self.storing.push(self.sink.put(chunk));
+                }
+                continue;
+            }
+            return if self.preparing.len() + self.storing.len() < limit {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            };
+        }
+    }
+}
+
+impl<S: ChunkSink> AsyncWrite for ChunkWriter<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.shut_down {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::BrokenPipe, "writer is shut down")));
+        }
+        loop {
+            ready!(this.poll_in_flight(cx, this.parallelism))?;
+            if this.buffer.len() < this.chunk_size {
+                break;
+            }
+            this.cut();
+        }
+        let n = data.len().min(this.chunk_size - this.buffer.len());
+        this.buffer.extend_from_slice(&data[..n]);
+        this.hasher.update(&data[..n]);
+        this.size_bytes += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    /// Waits for the chunks cut so far to be stored. Bytes short of a full
+    /// chunk stay buffered until more are written or the writer shuts down.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_in_flight(cx, 1)
+    }
+
+    /// Cuts the last chunks and waits for them to be stored.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.poll_in_flight(cx, this.parallelism))?;
+            if this.buffer.is_empty() {
+                break;
+            }
+            this.cut();
+        }
+        ready!(this.poll_in_flight(cx, 1))?;
+        this.shut_down = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl ChunkManager {
+    /// Streams the object `descriptor` describes out of the cache.
+    pub fn open_read(self: &Arc<Self>, descriptor: LargeDataDescriptor) -> ChunkReader<Arc<Self>> {
+        ChunkReader::new(self.clone(), descriptor)
+    }
+
+    /// Streams an object named `id` into the cache in chunks of
+    /// `chunk_size` bytes.
+    pub fn open_write(self: &Arc<Self>, id: &str, chunk_size: u32) -> ChunkWriter<Arc<Self>> {
+        ChunkWriter::new(self.clone(), id, chunk_size)
+    }
+}
+
+impl NetworkTransferCoordinator {
+    /// Streams the object `descriptor` describes, fetching from peers the
+    /// chunks not cached locally.
+    pub fn open_read(&self, descriptor: LargeDataDescriptor) -> ChunkReader<Self> {
+        ChunkReader::new(self.clone(), descriptor)
+    }
+
+    /// Streams the object `descriptor` describes, each chunk confirmed by as
+    /// many of its providers as `level` asks for, see
+    /// [`request_chunk_consistent`](Self::request_chunk_consistent).
+    pub fn open_read_consistent(
+        &self,
+        descriptor: LargeDataDescriptor,
+        level: ConsistencyLevel,
+    ) -> ChunkReader<ConsistentSource> {
+        ChunkReader::new(ConsistentSource { coordinator: self.clone(), level }, descriptor)
+    }
+
+    /// Reads the whole object `descriptor` describes at `level`: `One` is
+    /// fastest, `Quorum` and `All` wait for more providers to agree.
+    pub async fn retrieve_file(
+        &self,
+        descriptor: LargeDataDescriptor,
+        level: ConsistencyLevel,
+    ) -> LargeDataResult<Vec<u8>> {
+        let mut data = Vec::with_capacity(descriptor.size_bytes as usize);
+        self.open_read_consistent(descriptor, level).read_to_end(&mut data).await?;
+        Ok(data)
+    }
+
+    /// Reads the file at `path` in `namespace` on behalf of `user` at
+    /// `level`.
+    pub async fn retrieve_path(
+        &self,
+        namespace: &Namespace,
+        path: &str,
+        user: &str,
+        level: ConsistencyLevel,
+    ) -> LargeDataResult<Vec<u8>> {
+        let descriptor = namespace.file_for(path, user)?.descriptor.clone();
+        self.retrieve_file(descriptor, level).await
+    }
+
+    /// Reads `version` of the file `id` in `versions` at `level`.
+    pub async fn retrieve_file_version(
+        &self,
+        versions: &VersionStore,
+        id: &str,
+        version: u32,
+        level: ConsistencyLevel,
+    ) -> LargeDataResult<Vec<u8>> {
+        let descriptor = versions.version(id, version)?.descriptor.clone();
+        self.retrieve_file(descriptor, level).await
+    }
+
+    /// Streams an object named `id` into the local cache, announcing each
+    /// chunk to peers, in chunks of the configured size and compression.
+    pub fn open_write(&self, id: &str) -> ChunkWriter<Self> {
+        let compression = &self.config.compression_config;
+        let writer = ChunkWriter::new(self.clone(), id, self.config.default_chunk_size);
+        if compression.enabled {
+            writer.with_compressor(Arc::new(AdaptiveCompressor::new(compression.clone())))
+        } else {
+            writer
+        }
+    }
+}
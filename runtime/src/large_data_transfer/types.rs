@@ -3,19 +3,38 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-/// Transfer priority levels
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+/// Transfer priority levels, the QoS classes of the transfer scheduler.
+///
+/// A class preempts the classes at least two levels below it, see
+/// [`TransferPriority::preempts`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TransferPriority {
-    /// Low priority - background transfers
+    /// Low priority - background transfers such as bulk datasets
     Low = 0,
     /// Normal priority - default
     Normal = 1,
     /// High priority - urgent transfers
     High = 2,
-    /// Critical priority - system-critical transfers
+    /// Critical priority - system-critical transfers such as block sync and
+    /// PoUW verification data
     Critical = 3,
 }
 
+impl TransferPriority {
+    pub const ALL: [TransferPriority; 4] = [
+        TransferPriority::Critical,
+        TransferPriority::High,
+        TransferPriority::Normal,
+        TransferPriority::Low,
+    ];
+
+    /// Whether transfers of this class hold back transfers of `other`:
+    /// critical ones pause normal and low ones, high ones pause low ones.
+    pub fn preempts(self, other: TransferPriority) -> bool {
+        self as u8 >= other as u8 + 2
+    }
+}
+
 impl Default for TransferPriority {
     fn default() -> Self {
         TransferPriority::Normal
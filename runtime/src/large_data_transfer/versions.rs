@@ -0,0 +1,184 @@
+//! Version history of files.
+//!
+//! A file keeps a stable logical id while its content changes: every store
+//! adds a [`FileVersion`] holding the new [`LargeDataDescriptor`] and a
+//! [`VersionDiff`] against the version before, so model checkpoints and
+//! dataset revisions can be listed, compared and read back. Rolling back
+//! stores an old version again as the newest, keeping the history intact.
+//! Pruning drops old versions except pinned ones and returns the chunks no
+//! remaining version uses, for the caller to free.
+
+use super::{chunk::ChunkId, descriptor::LargeDataDescriptor};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum VersionError {
+    #[error("no file {0}")]
+    UnknownFile(String),
+    #[error("file {id} has no version {version}")]
+    UnknownVersion { id: String, version: u32 },
+}
+
+/// How a version differs from the one before it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionDiff {
+    /// Chunks the previous version did not have.
+    pub chunks_added: u32,
+    /// Chunks of the previous version no longer used.
+    pub chunks_removed: u32,
+    /// Size change in bytes.
+    pub size_delta: i64,
+}
+
+impl VersionDiff {
+    /// The diff of `new` against `old`, or against nothing.
+    pub fn between(old: Option<&LargeDataDescriptor>, new: &LargeDataDescriptor) -> Self {
+        let Some(old) = old else {
+            return Self {
+                chunks_added: new.chunk_hashes.len() as u32,
+                chunks_removed: 0,
+                size_delta: new.size_bytes as i64,
+            };
+        };
+        Self {
+            chunks_added: new.delta_from(old).len() as u32,
+            chunks_removed: old.delta_from(new).len() as u32,
+            size_delta: new.size_bytes as i64 - old.size_bytes as i64,
+        }
+    }
+}
+
+/// One stored version of a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVersion {
+    /// Numbered from 1, never reused.
+    pub version: u32,
+    pub descriptor: LargeDataDescriptor,
+    /// Unix time it was stored.
+    pub created_at: u64,
+    pub diff: VersionDiff,
+    /// Kept by [`VersionStore::prune`].
+    pub pinned: bool,
+    /// The version it restores, for rollbacks.
+    pub restored_from: Option<u32>,
+}
+
+/// The versions of every file, by logical id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionStore {
+    files: BTreeMap<String, Vec<FileVersion>>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+impl VersionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(
+        &mut self,
+        id: &str,
+        descriptor: LargeDataDescriptor,
+        restored_from: Option<u32>,
+    ) -> u32 {
+        let versions = self.files.entry(id.to_string()).or_default();
+        let latest = versions.last();
+        let version = latest.map_or(1, |v| v.version + 1);
+        let diff = VersionDiff::between(latest.map(|v| &v.descriptor), &descriptor);
+        versions.push(FileVersion {
+            version,
+            descriptor,
+            created_at: now(),
+            diff,
+            pinned: false,
+            restored_from,
+        });
+        version
+    }
+
+    /// Stores `descriptor` as the newest version of `id`, returning its
+    /// number.
+    pub fn store_version(&mut self, id: &str, descriptor: LargeDataDescriptor) -> u32 {
+        self.push(id, descriptor, None)
+    }
+
+    /// The versions of `id` kept, oldest first.
+    pub fn history(&self, id: &str) -> Result<&[FileVersion], VersionError> {
+        self.files
+            .get(id)
+            .map(Vec::as_slice)
+            .ok_or_else(|| VersionError::UnknownFile(id.to_string()))
+    }
+
+    pub fn latest(&self, id: &str) -> Result<&FileVersion, VersionError> {
+        self.history(id)?.last().ok_or_else(|| VersionError::UnknownFile(id.to_string()))
+    }
+
+    pub fn version(&self, id: &str, version: u32) -> Result<&FileVersion, VersionError> {
+        self.history(id)?
+            .iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| VersionError::UnknownVersion { id: id.to_string(), version })
+    }
+
+    /// Stores `version` of `id` again as its newest version, returning the
+    /// new number.
+    pub fn rollback(&mut self, id: &str, version: u32) -> Result<u32, VersionError> {
+        let descriptor = self.version(id, version)?.descriptor.clone();
+        Ok(self.push(id, descriptor, Some(version)))
+    }
+
+    fn version_mut(&mut self, id: &str, version: u32) -> Result<&mut FileVersion, VersionError> {
+        self.files
+            .get_mut(id)
+            .ok_or_else(|| VersionError::UnknownFile(id.to_string()))?
+            .iter_mut()
+            .find(|v| v.version == version)
+            .ok_or_else(|| VersionError::UnknownVersion { id: id.to_string(), version })
+    }
+
+    pub fn pin(&mut self, id: &str, version: u32) -> Result<(), VersionError> {
+        self.version_mut(id, version)?.pinned = true;
+        Ok(())
+    }
+
+    pub fn unpin(&mut self, id: &str, version: u32) -> Result<(), VersionError> {
+        self.version_mut(id, version)?.pinned = false;
+        Ok(())
+    }
+
+    /// Drops the versions of `id` older than its newest `keep`, except
+    /// pinned ones. Returns the chunks of the dropped versions that no kept
+    /// version of any file uses.
+    pub fn prune(&mut self, id: &str, keep: usize) -> Result<Vec<ChunkId>, VersionError> {
+        let versions =
+            self.files.get_mut(id).ok_or_else(|| VersionError::UnknownFile(id.to_string()))?;
+        let cutoff = versions.len().saturating_sub(keep.max(1));
+        let mut dropped = Vec::new();
+        let mut index = 0;
+        versions.retain(|v| {
+            index += 1;
+            let keep = index > cutoff || v.pinned;
+            if !keep {
+                dropped.extend(v.descriptor.chunk_hashes.iter().cloned());
+            }
+            keep
+        });
+        let used: HashSet<&String> =
+            self.files.values().flatten().flat_map(|v| v.descriptor.chunk_hashes.iter()).collect();
+        let mut freed: Vec<ChunkId> = dropped
+            .iter()
+            .filter(|hash| !used.contains(hash))
+            .filter_map(|hash| ChunkId::from_hex(hash).ok())
+            .collect();
+        freed.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        freed.dedup();
+        Ok(freed)
+    }
+}
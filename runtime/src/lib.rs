@@ -11,17 +11,33 @@ pub mod network;
 #[cfg(feature="node")]
 pub mod node;
 pub mod pouw;
+pub mod rl;
 #[cfg(feature="p2p")]
 pub mod p2p_service;
 pub mod wire;
+pub mod gossip;
 pub mod job;
+pub mod endpoint;
+pub mod session_keys;
+pub mod trace;
+pub mod rng;
 pub mod evaluator;
 pub mod trainer;
+pub mod data;
 pub mod job_manager;
+pub mod dispute;
+pub mod scheduler;
+pub mod connection_manager;
+pub mod net_address;
+pub mod onion;
+pub mod job_channel;
+pub mod journal;
 pub mod token;
 pub mod tensor_ops;
 pub mod vm;
 pub use vm::{Instruction, Vm, VmConfig};
+pub mod wasm_vm;
+pub mod container;
 
 // --- Data Model & Placeholder Modules ---
 pub mod consensus_engine;
@@ -41,6 +57,11 @@ pub mod node {
     use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
 
+    pub mod capability_probe;
+    pub mod reputation;
+
+    pub use reputation::{Dimension, Reputation, ReputationBook, ReputationConfig, ReputationWeights};
+
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
     pub enum CapabilityType {
         BasicCompute,
@@ -58,8 +79,12 @@ pub mod node {
         pub gpus: u32,
         pub gpu_memory_gb: u32,
         pub available_stake: u64,
-        pub reputation: i32,
+        pub reputation: Reputation,
         pub capability_types: Vec<CapabilityType>,
+        #[serde(default)]
+        pub min_benchmark_score: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub benchmark: Option<capability_probe::SignedBenchmark>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
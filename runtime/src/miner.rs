@@ -1,10 +1,12 @@
 //! The logic for creating and solving a new block.
 
 use crate::blockchain::{
-    block::Block, chain::Blockchain, transaction::Transaction, validation, BlockchainError,
+    block::Block, chain::Blockchain, state::State, transaction::{StorageTx, Transaction}, validation,
+    BlockchainError,
 };
 use crate::job::Job;
 use crate::pouw::PoUWTask;
+use crate::trace::{stage_span, TraceContext};
 use std::collections::{HashSet, VecDeque};
 use tokio::sync::Mutex;
 use std::sync::Arc;
@@ -26,8 +28,26 @@ pub async fn mine_block(
     // Select valid transactions from the mempool
     let mut transactions_to_include = Vec::new();
     let mut temp_state = chain.state.clone(); // Create a temporary state for validation
+    let next_height = prev_block.index as u64 + 1;
+    let now = chrono::Utc::now().timestamp();
 
-    for tx in mempool_guard.iter() {
+    // Each sender's transactions in nonce order, so a run of them fits in
+    // one block.
+    let mut candidates: Vec<&Transaction> = mempool_guard.iter().collect();
+    candidates.sort_by(|a, b| (&a.from, a.nonce).cmp(&(&b.from, b.nonce)));
+
+    let mut advanced_tasks = HashSet::new();
+    for tx in candidates {
+        // Time-locked transactions stay in the mempool until eligible.
+        if !tx.is_eligible(next_height, now) {
+            continue;
+        }
+        // A block may advance each long task only once.
+        if let Some(StorageTx::CommitProgress { delta }) = &tx.storage {
+            if !advanced_tasks.insert(delta.task_id.clone()) {
+                continue;
+            }
+        }
         if validation::validate_transaction_stateful(tx, &temp_state).is_ok() {
             // If valid, apply it to the temp state and add to our list
             temp_state.apply_transaction(tx)?;
@@ -38,7 +58,8 @@ pub async fn mine_block(
     // We still need data from prev_block before releasing the chain lock.
     let prev_block_hash = prev_block.hash.clone();
     let new_block_index = (prev_block.index + 1) as u32;
-    let difficulty = chain.calculate_next_difficulty();
+    let retarget = chain.next_retarget();
+    let difficulty = retarget.difficulty;
 
     // Drop the locks early (after capturing necessary values)
     drop(mempool_guard);
@@ -46,17 +67,51 @@ pub async fn mine_block(
 
     let tx_root = Block::calculate_merkle_root(&transactions_to_include);
 
-    // Get a job from the queue for the PoUW task.
-    let job = job_queue.lock().await.pop_front();
+    // Take the best-paying escrowed job, whether queued here or posted on
+    // chain through another node, or else the oldest queued one, for the
+    // PoUW task.
+    let job = {
+        let mut queue = job_queue.lock().await;
+        let queued = queue
+            .iter()
+            .enumerate()
+            .filter_map(|(i, job)| escrowed_reward(&temp_state, job).map(|reward| (i, reward)))
+            .max_by_key(|&(i, reward)| (reward, std::cmp::Reverse(i)));
+        let posted = temp_state
+            .job_escrows
+            .values()
+            .filter(|escrow| !escrow.mined)
+            .max_by_key(|escrow| (escrow.mining_reward(), std::cmp::Reverse(escrow.job.id)))
+            .filter(|escrow| queued.map_or(true, |(_, reward)| escrow.mining_reward() > reward));
+        match (posted, queued) {
+            (Some(escrow), _) => {
+                queue.retain(|job| job.id != escrow.job.id);
+                Some(escrow.job.clone())
+            }
+            (None, Some((i, _))) => queue.remove(i),
+            (None, None) => queue.pop_front(),
+        }
+    };
 
-    let pouw_task = if let Some(job) = job {
-        PoUWTask::new(job.model_id, job.dataset_id, job.iterations)
+    let (pouw_task, worker_trace) = if let Some(job) = job {
+        let worker_trace = job.trace.as_ref().map(TraceContext::child);
+        let mut task = job.to_task(worker_trace.as_ref());
+        // Only an escrowed job pays a reward; others are mined as plain work
+        // of the retargeted size.
+        if escrowed_reward(&temp_state, &job).is_none() {
+            task.job_id = None;
+            task.epochs = retarget.epochs;
+        }
+        (task, worker_trace)
     } else {
-        PoUWTask::new("default_model".to_string(), "default_dataset".to_string(), 1)
+        let task = PoUWTask::new("default_model".to_string(), "default_dataset".to_string(), retarget.epochs);
+        (task, None)
     };
 
     // Solve the task to produce a real PoUW solution instead of a placeholder.
-    let pouw_solution = crate::pouw::solve(&pouw_task, difficulty);
+    let pouw_solution = stage_span(worker_trace.as_ref(), "mine")
+        .in_scope(|| crate::pouw::task_types::solve(&pouw_task, difficulty))
+        .map_err(|e| BlockchainError::InvalidBlock(e.to_string()))?;
 
     let new_block = Block::new(
         new_block_index,
@@ -69,4 +124,16 @@ pub async fn mine_block(
     );
 
     Ok(new_block)
-} 
\ No newline at end of file
+}
+
+/// Mining share escrowed on chain for `job`, if it was posted as queued
+/// and not mined yet.
+fn escrowed_reward(state: &State, job: &Job) -> Option<u64> {
+    let escrow = state.job_escrows.get(&job.id).filter(|e| !e.mined)?;
+    let posted = &escrow.job;
+    (posted.model_id == job.model_id
+        && posted.dataset_id == job.dataset_id
+        && posted.iterations == job.iterations
+        && posted.kind == job.kind)
+        .then_some(escrow.mining_reward())
+}
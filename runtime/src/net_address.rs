@@ -0,0 +1,340 @@
+//! Listen and advertised address handling for dual-stack, multi-homed nodes.
+//!
+//! Nodes listen on every IPv4 and IPv6 interface by default. The concrete
+//! addresses the transport reports for those wildcard listeners, plus any
+//! configured external addresses, become candidates for advertisement.
+//! [`AdvertisedAddrs`] ranks them (public before private, then by the
+//! configured address family preference), and drops addresses from the
+//! advertised set after repeated failed reachability checks until they pass
+//! again. Unspecified, loopback and link-local addresses are never
+//! advertised, since peers cannot dial them; `/dns*` names are taken to be
+//! public.
+//!
+//! Nodes run QUIC next to TCP unless configured otherwise, and
+//! [`order_for_dial`] puts the addresses of the preferred transport first so
+//! a failed QUIC attempt falls back to TCP.
+
+use libp2p::multiaddr::Protocol;
+use libp2p::Multiaddr;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("invalid multiaddr {0}: {1}")]
+    Invalid(String, String),
+}
+
+/// Listen and advertisement settings, see the module docs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AddressConfig {
+    /// Multiaddrs to listen on. Empty listens on every IPv4 and IPv6
+    /// interface at the configured port.
+    pub listen: Vec<String>,
+    /// Addresses to advertise besides those of local interfaces, e.g. one
+    /// per uplink of a multi-homed host.
+    pub external: Vec<String>,
+    pub preference: AddressPreference,
+    /// Most addresses advertised at once.
+    pub max_advertised: usize,
+    /// Seconds between reachability checks; 0 disables them, e.g. behind
+    /// a NAT that does not hairpin.
+    pub check_interval_secs: u64,
+    pub check_timeout_ms: u64,
+    /// Failed checks in a row after which an address stops being advertised.
+    pub failures_allowed: u32,
+}
+
+impl Default for AddressConfig {
+    fn default() -> Self {
+        Self {
+            listen: Vec::new(),
+            external: Vec::new(),
+            preference: AddressPreference::default(),
+            max_advertised: 8,
+            check_interval_secs: 60,
+            check_timeout_ms: 2_000,
+            failures_allowed: 3,
+        }
+    }
+}
+
+/// Which address family to list first when advertising.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressPreference {
+    #[default]
+    Ipv6First,
+    Ipv4First,
+    Ipv4Only,
+    Ipv6Only,
+}
+
+impl AddressPreference {
+    fn allows(self, family: Family) -> bool {
+        !matches!(
+            (self, family),
+            (AddressPreference::Ipv4Only, Family::V6) | (AddressPreference::Ipv6Only, Family::V4)
+        )
+    }
+
+    /// Sort key; lower goes first.
+    fn rank(self, family: Family) -> u8 {
+        match (self, family) {
+            (AddressPreference::Ipv4First, Family::V6) | (AddressPreference::Ipv6First, Family::V4) => 2,
+            (_, Family::Any) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Which transports a node runs, and which it dials first when a peer
+/// offers both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportPreference {
+    /// QUIC sets up connections in fewer round trips and passes home NATs
+    /// more often; TCP remains the fallback.
+    #[default]
+    QuicFirst,
+    TcpFirst,
+    QuicOnly,
+    TcpOnly,
+}
+
+impl TransportPreference {
+    pub fn uses_quic(self) -> bool {
+        self != TransportPreference::TcpOnly
+    }
+
+    pub fn uses_tcp(self) -> bool {
+        self != TransportPreference::QuicOnly
+    }
+
+    fn quic_first(self) -> bool {
+        matches!(self, TransportPreference::QuicFirst | TransportPreference::QuicOnly)
+    }
+}
+
+/// Whether `addr` is a `/udp/<port>/quic-v1` address.
+pub fn is_quic(addr: &Multiaddr) -> bool {
+    addr.iter().any(|p| matches!(p, Protocol::QuicV1))
+}
+
+/// `addrs` in the order to dial them, leaving out those of a transport
+/// `preference` does not run.
+pub fn order_for_dial(
+    addrs: impl IntoIterator<Item = Multiaddr>,
+    preference: TransportPreference,
+) -> Vec<Multiaddr> {
+    let mut ordered: Vec<Multiaddr> = addrs
+        .into_iter()
+        .filter(|a| if is_quic(a) { preference.uses_quic() } else { preference.uses_tcp() })
+        .collect();
+    ordered.sort_by_key(|a| is_quic(a) != preference.quic_first());
+    ordered
+}
+
+/// Address family of a multiaddr. `/dns` names may resolve to either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    V4,
+    V6,
+    Any,
+}
+
+impl Family {
+    pub fn of(addr: &Multiaddr) -> Option<Self> {
+        addr.iter().find_map(|p| match p {
+            Protocol::Ip4(_) | Protocol::Dns4(_) => Some(Family::V4),
+            Protocol::Ip6(_) | Protocol::Dns6(_) => Some(Family::V6),
+            Protocol::Dns(_) => Some(Family::Any),
+            _ => None,
+        })
+    }
+}
+
+/// How widely an address is reachable, most reachable last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AddrScope {
+    Unspecified,
+    Loopback,
+    LinkLocal,
+    Private,
+    Public,
+}
+
+impl AddrScope {
+    pub fn of(ip: &IpAddr) -> Self {
+        match ip {
+            ip if ip.is_unspecified() => AddrScope::Unspecified,
+            ip if ip.is_loopback() => AddrScope::Loopback,
+            IpAddr::V4(v4) if v4.is_link_local() => AddrScope::LinkLocal,
+            IpAddr::V4(v4) if v4.is_private() || is_shared_v4(v4) => AddrScope::Private,
+            IpAddr::V6(v6) if v6.segments()[0] & 0xffc0 == 0xfe80 => AddrScope::LinkLocal,
+            // Unique local addresses, fc00::/7.
+            IpAddr::V6(v6) if v6.segments()[0] & 0xfe00 == 0xfc00 => AddrScope::Private,
+            _ => AddrScope::Public,
+        }
+    }
+
+    /// Whether peers elsewhere could dial an address of this scope.
+    pub fn advertisable(self) -> bool {
+        matches!(self, AddrScope::Private | AddrScope::Public)
+    }
+}
+
+/// Carrier-grade NAT space, 100.64.0.0/10.
+fn is_shared_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    a == 100 && (b & 0xc0) == 64
+}
+
+/// The IP of a `/ip4/..` or `/ip6/..` multiaddr.
+pub fn ip_of(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|p| match p {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+/// Scope of `addr`. Names are assumed to resolve to public addresses.
+pub fn scope_of(addr: &Multiaddr) -> Option<AddrScope> {
+    match ip_of(addr) {
+        Some(ip) => Some(AddrScope::of(&ip)),
+        None => Family::of(addr).map(|_| AddrScope::Public),
+    }
+}
+
+/// `host:port` to open a TCP connection to `addr`, if it is a TCP multiaddr.
+pub fn dial_target(addr: &Multiaddr) -> Option<String> {
+    let port = addr.iter().find_map(|p| match p {
+        Protocol::Tcp(port) => Some(port),
+        _ => None,
+    })?;
+    if let Some(ip) = ip_of(addr) {
+        return Some(SocketAddr::new(ip, port).to_string());
+    }
+    addr.iter().find_map(|p| match p {
+        Protocol::Dns(host) | Protocol::Dns4(host) | Protocol::Dns6(host) => Some(format!("{}:{}", host, port)),
+        _ => None,
+    })
+}
+
+/// Wildcard listeners on every IPv4 and IPv6 interface.
+pub fn default_listen_addrs(port: u16) -> Vec<Multiaddr> {
+    [IpAddr::V4(Ipv4Addr::UNSPECIFIED), IpAddr::V6(Ipv6Addr::UNSPECIFIED)]
+        .into_iter()
+        .map(|ip| Multiaddr::from(ip).with(Protocol::Tcp(port)))
+        .collect()
+}
+
+/// Wildcard QUIC listeners on every IPv4 and IPv6 interface.
+pub fn default_quic_listen_addrs(port: u16) -> Vec<Multiaddr> {
+    [IpAddr::V4(Ipv4Addr::UNSPECIFIED), IpAddr::V6(Ipv6Addr::UNSPECIFIED)]
+        .into_iter()
+        .map(|ip| Multiaddr::from(ip).with(Protocol::Udp(port)).with(Protocol::QuicV1))
+        .collect()
+}
+
+pub fn parse_addrs(addrs: &[String]) -> Result<Vec<Multiaddr>, AddressError> {
+    addrs
+        .iter()
+        .map(|a| a.parse().map_err(|e: libp2p::multiaddr::Error| AddressError::Invalid(a.clone(), e.to_string())))
+        .collect()
+}
+
+/// Whether a TCP connection to `addr` opens within `timeout`. QUIC
+/// addresses cannot be probed this way and always fail.
+pub async fn probe(addr: &Multiaddr, timeout: Duration) -> bool {
+    let Some(target) = dial_target(addr) else { return false };
+    matches!(tokio::time::timeout(timeout, tokio::net::TcpStream::connect(target)).await, Ok(Ok(_)))
+}
+
+/// Where a candidate address came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddrSource {
+    /// Reported by the transport for one of our listeners.
+    Listen,
+    /// Configured by the operator, e.g. a NAT or load balancer address.
+    External,
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    addr: Multiaddr,
+    source: AddrSource,
+    family: Family,
+    scope: AddrScope,
+    failures: u32,
+}
+
+/// Candidate addresses to advertise and their health.
+#[derive(Debug, Clone)]
+pub struct AdvertisedAddrs {
+    preference: AddressPreference,
+    max: usize,
+    failures_allowed: u32,
+    candidates: Vec<Candidate>,
+}
+
+impl AdvertisedAddrs {
+    pub fn new(preference: AddressPreference, max: usize, failures_allowed: u32) -> Self {
+        Self { preference, max, failures_allowed, candidates: Vec::new() }
+    }
+
+    pub fn from_config(config: &AddressConfig) -> Self {
+        Self::new(config.preference, config.max_advertised, config.failures_allowed)
+    }
+
+    /// Add a candidate. Returns `false` for addresses that can never be
+    /// advertised or are already known.
+    pub fn add(&mut self, addr: Multiaddr, source: AddrSource) -> bool {
+        let (Some(family), Some(scope)) = (Family::of(&addr), scope_of(&addr)) else { return false };
+        if !scope.advertisable() || self.candidates.iter().any(|c| c.addr == addr) {
+            return false;
+        }
+        self.candidates.push(Candidate { addr, source, family, scope, failures: 0 });
+        true
+    }
+
+    /// Forget a listen address the transport no longer serves.
+    pub fn remove(&mut self, addr: &Multiaddr) {
+        self.candidates.retain(|c| &c.addr != addr);
+    }
+
+    /// Record the outcome of a reachability check of `addr`.
+    pub fn record_check(&mut self, addr: &Multiaddr, reachable: bool) {
+        if let Some(candidate) = self.candidates.iter_mut().find(|c| &c.addr == addr) {
+            candidate.failures = if reachable { 0 } else { candidate.failures.saturating_add(1) };
+        }
+    }
+
+    pub fn is_healthy(&self, addr: &Multiaddr) -> bool {
+        self.candidates.iter().any(|c| &c.addr == addr && c.failures < self.failures_allowed)
+    }
+
+    /// Every candidate, for health checking.
+    pub fn candidates(&self) -> impl Iterator<Item = &Multiaddr> {
+        self.candidates.iter().map(|c| &c.addr)
+    }
+
+    /// Healthy addresses allowed by the preference, best first: public
+    /// before private, the preferred family first, and configured external
+    /// addresses before ones reported by the transport.
+    pub fn advertised(&self) -> Vec<Multiaddr> {
+        let mut healthy: Vec<&Candidate> = self
+            .candidates
+            .iter()
+            .filter(|c| c.failures < self.failures_allowed && self.preference.allows(c.family))
+            .collect();
+        healthy.sort_by_key(|c| {
+            (std::cmp::Reverse(c.scope), self.preference.rank(c.family), c.source != AddrSource::External)
+        });
+        healthy.into_iter().take(self.max).map(|c| c.addr.clone()).collect()
+    }
+}
@@ -9,6 +9,7 @@ use std::collections::HashMap;
 use thiserror::Error;
 use crate::blockchain::{BlockchainError, Transaction, Block};
 use crate::pouw::types::SignedEvaluation;
+use crate::scheduler::Heartbeat;
 
 /// Network message types for distributed coordination
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +26,8 @@ pub enum NetworkMessage {
     TrainingEvaluation { job_id: u64, result_hash: String, is_valid: bool, evaluator_id: String },
     /// Gossip message carrying a signed PoUW evaluation.
     PoUWEvaluation { evaluation: SignedEvaluation },
+    /// Signed liveness heartbeat from a worker.
+    Heartbeat { heartbeat: Heartbeat },
     JobCompleted { job_id: u64, final_model_hash: String },
     StateSync { requesting_node: String, last_known_block: u64 },
     StateSyncResponse { jobs: Vec<DistributedJob>, current_block: u64 },
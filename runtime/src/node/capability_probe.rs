@@ -0,0 +1,482 @@
+//! Hardware detection and benchmarking.
+//!
+//! A [`CapabilityProbe`] finds the node's CPUs, RAM, free disk and GPUs
+//! (through `nvidia-smi`), then runs a short standardized benchmark: a dense
+//! `f32` matrix multiply for GFLOPS and a large buffer copy for memory
+//! bandwidth. [`CapabilityProbe::capability`] turns the results into a
+//! [`NodeCapability`] carrying a [`SignedBenchmark`], so peers can check the
+//! score the node advertises was signed by its key. Job matching compares
+//! [`NodeCapability::benchmark_score`] against a job's
+//! `min_benchmark_score`; [`benchmarked_validators`] keeps the validator
+//! candidates with a good enough score before stake-weighted selection.
+//!
+//! The multiplied matrices are generated from a seed bound to the signer and
+//! the benchmark's timestamp, and the benchmark carries a digest of the
+//! product as a [`KernelProof`]. A verifier recomputes the product, so a
+//! benchmark only attests hardware that actually ran the kernel at that
+//! time. Attestations go stale after a while;
+//! [`CapabilityProbe::refresh`] re-runs the benchmark before they do, and
+//! [`NodeCapability::measured`] is what a fresh attestation shows in place
+//! of the node's own claims.
+
+use super::{CapabilityType, NodeCapability, Reputation};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Instant;
+
+/// Domain tag of benchmark signatures.
+const BENCHMARK_DOMAIN: &[u8] = b"bcai-benchmark";
+/// How often a node re-runs its benchmark, in seconds.
+pub const DEFAULT_REFRESH_SECS: u64 = 3600;
+/// Age after which a benchmark no longer attests anything, in seconds.
+pub const DEFAULT_MAX_AGE_SECS: u64 = 2 * DEFAULT_REFRESH_SECS;
+/// How far ahead of a verifier's clock a benchmark may be timestamped.
+const CLOCK_SKEW_SECS: u64 = 60;
+/// Largest kernel a verifier recomputes.
+pub const MAX_PROOF_MATRIX: u32 = 1024;
+/// RAM from which a node advertises [`CapabilityType::HighMemory`].
+pub const HIGH_MEMORY_MB: u64 = 32 * 1024;
+/// Free disk from which a node advertises [`CapabilityType::Storage`].
+pub const STORAGE_DISK_GB: u64 = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuInfo {
+    pub model: String,
+    pub memory_mb: u64,
+}
+
+/// What [`CapabilityProbe::detect`] found. Unknown sizes are 0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HardwareInfo {
+    pub cpus: u32,
+    pub cpu_model: String,
+    pub memory_mb: u64,
+    pub disk_free_gb: u64,
+    pub gpus: Vec<GpuInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkConfig {
+    /// Side of the square matrices multiplied.
+    pub matrix_size: usize,
+    /// Size of the buffer copied to measure bandwidth.
+    pub buffer_mb: usize,
+    /// Each measurement keeps the best of this many runs.
+    pub rounds: u32,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self { matrix_size: 256, buffer_mb: 64, rounds: 3 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub gflops: f64,
+    pub memory_bandwidth_gbps: f64,
+    /// See [`BenchmarkResult::new`].
+    pub score: u64,
+    /// Output of the seeded kernel, set by [`run_benchmark`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<KernelProof>,
+}
+
+impl BenchmarkResult {
+    /// A result scored as 100 points per GFLOPS plus 10 per GB/s of
+    /// memory bandwidth.
+    pub fn new(gflops: f64, memory_bandwidth_gbps: f64) -> Self {
+        let score = (gflops.max(0.0) * 100.0 + memory_bandwidth_gbps.max(0.0) * 10.0) as u64;
+        Self { gflops, memory_bandwidth_gbps, score, proof: None }
+    }
+
+    pub fn with_proof(mut self, proof: KernelProof) -> Self {
+        self.proof = Some(proof);
+        self
+    }
+}
+
+/// What the benchmark kernel computed: the hex SHA-256 of the product of
+/// two `matrix_size` square matrices generated from `seed`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KernelProof {
+    pub seed: u64,
+    pub matrix_size: u32,
+    pub digest: String,
+}
+
+impl KernelProof {
+    /// Runs the kernel once and records its output.
+    pub fn compute(seed: u64, matrix_size: u32) -> Self {
+        let n = matrix_size.max(1) as usize;
+        let (a, b) = seeded_matrices(n, seed);
+        let mut c = vec![0f32; n * n];
+        matmul(&a, &b, &mut c, n);
+        Self { seed, matrix_size, digest: product_digest(&c) }
+    }
+
+    /// Whether the digest is the kernel's output for the seed. Kernels
+    /// larger than [`MAX_PROOF_MATRIX`] are not recomputed and fail.
+    pub fn check(&self) -> bool {
+        self.matrix_size <= MAX_PROOF_MATRIX && *self == Self::compute(self.seed, self.matrix_size)
+    }
+}
+
+/// Seed of the kernel a benchmark by `signer` at `timestamp` must run, so a
+/// proof can't be lifted from another node or reused later.
+pub fn attestation_seed(signer: &str, timestamp: u64) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(BENCHMARK_DOMAIN)
+        .chain_update(signer.as_bytes())
+        .chain_update(timestamp.to_le_bytes())
+        .finalize();
+    u64::from_le_bytes(digest[..8].try_into().expect("8 bytes"))
+}
+
+/// Hardware and benchmark results signed by the node that measured them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedBenchmark {
+    /// Hex ed25519 key of the node.
+    pub signer: String,
+    pub hardware: HardwareInfo,
+    pub result: BenchmarkResult,
+    /// Unix seconds at which the benchmark ran.
+    pub timestamp: u64,
+    pub signature: Vec<u8>,
+}
+
+impl SignedBenchmark {
+    fn message(hardware: &HardwareInfo, result: &BenchmarkResult, timestamp: u64) -> Vec<u8> {
+        let mut msg = BENCHMARK_DOMAIN.to_vec();
+        msg.extend(bincode::serialize(&(hardware, result, timestamp)).expect("serialize benchmark"));
+        msg
+    }
+
+    pub fn sign(hardware: HardwareInfo, result: BenchmarkResult, timestamp: u64, key: &SigningKey) -> Self {
+        let signature: Signature = key.sign(&Self::message(&hardware, &result, timestamp));
+        Self {
+            signer: hex::encode(key.verifying_key().to_bytes()),
+            hardware,
+            result,
+            timestamp,
+            signature: signature.to_bytes().to_vec(),
+        }
+    }
+
+    /// Whether `signer` signed these results.
+    pub fn verify(&self) -> bool {
+        let Ok(pk_bytes) = hex::decode(&self.signer) else { return false };
+        let Ok(pk_bytes) = <[u8; 32]>::try_from(pk_bytes) else { return false };
+        let Ok(vk) = VerifyingKey::from_bytes(&pk_bytes) else { return false };
+        let Ok(sig) = Signature::from_slice(&self.signature) else { return false };
+        vk.verify(&Self::message(&self.hardware, &self.result, self.timestamp), &sig).is_ok()
+    }
+
+    /// Whether the benchmark is no older than `max_age` seconds at `now`.
+    pub fn is_fresh(&self, now: u64, max_age: u64) -> bool {
+        self.timestamp <= now.saturating_add(CLOCK_SKEW_SECS) && now.saturating_sub(self.timestamp) <= max_age
+    }
+
+    /// Whether the benchmark attests its hardware at `now`: signed by
+    /// `signer`, fresh, and carrying the output of the kernel seeded by
+    /// [`attestation_seed`].
+    pub fn attests(&self, now: u64, max_age: u64) -> bool {
+        self.is_fresh(now, max_age)
+            && self.verify()
+            && self.result.proof.as_ref().is_some_and(|proof| {
+                proof.seed == attestation_seed(&self.signer, self.timestamp) && proof.check()
+            })
+    }
+}
+
+impl NodeCapability {
+    /// Score of the node's benchmark, or 0 without a validly signed one.
+    pub fn benchmark_score(&self) -> u64 {
+        self.benchmark.as_ref().filter(|b| b.verify()).map_or(0, |b| b.result.score)
+    }
+
+    /// The capability the node's benchmark attests at `now`, keeping its
+    /// stake, reputation and requirements but nothing it claims about its
+    /// hardware. `None` without a benchmark attesting anything.
+    pub fn measured(&self, now: u64, max_age: u64) -> Option<NodeCapability> {
+        let benchmark = self.benchmark.as_ref().filter(|b| b.attests(now, max_age))?;
+        let mut measured = capability_from(benchmark.clone(), self.available_stake, self.reputation.clone());
+        measured.min_benchmark_score = self.min_benchmark_score;
+        Some(measured)
+    }
+}
+
+/// Detects hardware and benchmarks the node.
+#[derive(Debug, Clone)]
+pub struct CapabilityProbe {
+    gpu_command: PathBuf,
+    disk_path: PathBuf,
+    benchmark: BenchmarkConfig,
+}
+
+impl Default for CapabilityProbe {
+    fn default() -> Self {
+        Self { gpu_command: PathBuf::from("nvidia-smi"), disk_path: PathBuf::from("."), benchmark: BenchmarkConfig::default() }
+    }
+}
+
+impl CapabilityProbe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queries GPUs through another `nvidia-smi`-compatible CLI.
+    pub fn with_gpu_command(mut self, command: impl Into<PathBuf>) -> Self {
+        self.gpu_command = command.into();
+        self
+    }
+
+    /// Reports free space on the file system holding `path`, by default
+    /// the working directory.
+    pub fn with_disk_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.disk_path = path.into();
+        self
+    }
+
+    pub fn with_benchmark(mut self, benchmark: BenchmarkConfig) -> Self {
+        self.benchmark = benchmark;
+        self
+    }
+
+    pub fn detect(&self) -> HardwareInfo {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let meminfo = std::fs::read_to_string("/proc/meminfo").unwrap_or_default();
+        HardwareInfo {
+            cpus: std::thread::available_parallelism().map_or(1, |n| n.get() as u32),
+            cpu_model: parse_cpu_model(&cpuinfo).unwrap_or_default(),
+            memory_mb: parse_meminfo(&meminfo).unwrap_or(0),
+            disk_free_gb: self.disk_free_gb().unwrap_or(0),
+            gpus: self.gpus(),
+        }
+    }
+
+    fn gpus(&self) -> Vec<GpuInfo> {
+        Command::new(&self.gpu_command)
+            .args(["--query-gpu=name,memory.total", "--format=csv,noheader,nounits"])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map_or_else(Vec::new, |out| parse_nvidia_smi(&String::from_utf8_lossy(&out.stdout)))
+    }
+
+    fn disk_free_gb(&self) -> Option<u64> {
+        let out = Command::new("df").arg("-Pk").arg(&self.disk_path).output().ok()?;
+        if !out.status.success() {
+            return None;
+        }
+        parse_df(&String::from_utf8_lossy(&out.stdout)).map(|kb| kb / (1024 * 1024))
+    }
+
+    /// Benchmarks the node, running the kernel seeded by `seed`.
+    pub fn benchmark(&self, seed: u64) -> BenchmarkResult {
+        run_benchmark(&self.benchmark, seed)
+    }
+
+    /// Detects and benchmarks the node at `timestamp` and signs the results
+    /// with `key`.
+    pub fn attest(&self, key: &SigningKey, timestamp: u64) -> SignedBenchmark {
+        let signer = hex::encode(key.verifying_key().to_bytes());
+        let result = self.benchmark(attestation_seed(&signer, timestamp));
+        SignedBenchmark::sign(self.detect(), result, timestamp, key)
+    }
+
+    /// Detects and benchmarks the node and describes it as a capability
+    /// signed by `key`.
+    pub fn capability(
+        &self,
+        key: &SigningKey,
+        available_stake: u64,
+        reputation: impl Into<Reputation>,
+    ) -> NodeCapability {
+        capability_from(self.attest(key, unix_now()), available_stake, reputation)
+    }
+
+    /// Re-benchmarks `capability` with `key` if its benchmark is missing or
+    /// at least `refresh_secs` old at `now`, updating its hardware to the
+    /// new measurements. Returns whether it did.
+    pub fn refresh(
+        &self,
+        capability: &mut NodeCapability,
+        key: &SigningKey,
+        now: u64,
+        refresh_secs: u64,
+    ) -> bool {
+        let due = capability
+            .benchmark
+            .as_ref()
+            .map_or(true, |b| now.saturating_sub(b.timestamp) >= refresh_secs);
+        if !due {
+            return false;
+        }
+        let benchmark = self.attest(key, now);
+        let mut refreshed = capability_from(benchmark, capability.available_stake, capability.reputation.clone());
+        refreshed.min_benchmark_score = capability.min_benchmark_score;
+        *capability = refreshed;
+        true
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// The capability a signed benchmark shows. GPU memory is that of the
+/// smallest GPU, which every GPU of the node has at least.
+pub fn capability_from(
+    benchmark: SignedBenchmark,
+    available_stake: u64,
+    reputation: impl Into<Reputation>,
+) -> NodeCapability {
+    let hardware = &benchmark.hardware;
+    let mut capability_types = vec![CapabilityType::BasicCompute, CapabilityType::Training, CapabilityType::Inference];
+    if !hardware.gpus.is_empty() {
+        capability_types.push(CapabilityType::GpuAccelerated);
+    }
+    if hardware.memory_mb >= HIGH_MEMORY_MB {
+        capability_types.push(CapabilityType::HighMemory);
+    }
+    if hardware.disk_free_gb >= STORAGE_DISK_GB {
+        capability_types.push(CapabilityType::Storage);
+    }
+    NodeCapability {
+        cpus: hardware.cpus,
+        gpus: hardware.gpus.len() as u32,
+        gpu_memory_gb: hardware.gpus.iter().map(|g| g.memory_mb / 1024).min().unwrap_or(0) as u32,
+        available_stake,
+        reputation: reputation.into(),
+        capability_types,
+        min_benchmark_score: 0,
+        benchmark: Some(benchmark),
+    }
+}
+
+/// Square matrices of small integers drawn from `seed`, so every sum in
+/// their product is exact in `f32` and the product is the same everywhere.
+fn seeded_matrices(n: usize, seed: u64) -> (Vec<f32>, Vec<f32>) {
+    // splitmix64
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) % 16) as f32
+    };
+    let a = (0..n * n).map(|_| next()).collect();
+    let b = (0..n * n).map(|_| next()).collect();
+    (a, b)
+}
+
+fn matmul(a: &[f32], b: &[f32], c: &mut [f32], n: usize) {
+    c.iter_mut().for_each(|v| *v = 0.0);
+    for i in 0..n {
+        for p in 0..n {
+            let aip = a[i * n + p];
+            for j in 0..n {
+                c[i * n + j] += aip * b[p * n + j];
+            }
+        }
+    }
+}
+
+fn product_digest(c: &[f32]) -> String {
+    let mut hasher = Sha256::new();
+    for v in c {
+        hasher.update(v.to_le_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Measures GFLOPS and memory bandwidth, keeping the best of
+/// `config.rounds` runs each. The matrices multiplied are generated from
+/// `seed` and the product's digest is returned as the result's proof.
+pub fn run_benchmark(config: &BenchmarkConfig, seed: u64) -> BenchmarkResult {
+    let n = config.matrix_size.max(1);
+    let (a, b) = seeded_matrices(n, seed);
+    let mut c = vec![0f32; n * n];
+    let mut best_gflops = 0f64;
+    for _ in 0..config.rounds.max(1) {
+        let started = Instant::now();
+        matmul(&a, &b, &mut c, n);
+        std::hint::black_box(&c);
+        let secs = started.elapsed().as_secs_f64().max(1e-9);
+        best_gflops = best_gflops.max(2.0 * (n as f64).powi(3) / secs / 1e9);
+    }
+    let proof = KernelProof { seed, matrix_size: n as u32, digest: product_digest(&c) };
+
+    let bytes = config.buffer_mb.max(1) << 20;
+    let source = vec![1u8; bytes];
+    let mut target = vec![0u8; bytes];
+    let mut best_bandwidth = 0f64;
+    for _ in 0..config.rounds.max(1) {
+        let started = Instant::now();
+        target.copy_from_slice(std::hint::black_box(&source));
+        std::hint::black_box(&target);
+        let secs = started.elapsed().as_secs_f64().max(1e-9);
+        // A copy reads and writes every byte.
+        best_bandwidth = best_bandwidth.max(2.0 * bytes as f64 / secs / 1e9);
+    }
+    BenchmarkResult::new(best_gflops, best_bandwidth).with_proof(proof)
+}
+
+/// The `(id, stake)` candidates whose benchmark is signed by their own key
+/// and scores at least `min_score`, ready for
+/// [`crate::pouw::select_validators`].
+pub fn benchmarked_validators<'a>(
+    candidates: impl IntoIterator<Item = (String, u64, Option<&'a SignedBenchmark>)>,
+    min_score: u64,
+) -> Vec<(String, u64)> {
+    candidates
+        .into_iter()
+        .filter(|(id, _, benchmark)| {
+            benchmark.is_some_and(|b| &b.signer == id && b.result.score >= min_score && b.verify())
+        })
+        .map(|(id, stake, _)| (id, stake))
+        .collect()
+}
+
+/// The first `model name` in `/proc/cpuinfo`.
+pub fn parse_cpu_model(cpuinfo: &str) -> Option<String> {
+    cpuinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("model name"))
+        .and_then(|rest| rest.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+/// `MemTotal` of `/proc/meminfo`, in MiB.
+pub fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let kb: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(kb / 1024)
+}
+
+/// GPUs listed by `nvidia-smi --query-gpu=name,memory.total
+/// --format=csv,noheader,nounits`, one `name, MiB` line each.
+pub fn parse_nvidia_smi(output: &str) -> Vec<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (model, memory) = line.rsplit_once(',')?;
+            Some(GpuInfo { model: model.trim().to_string(), memory_mb: memory.trim().parse().ok()? })
+        })
+        .collect()
+}
+
+/// Available KiB in POSIX `df -Pk` output.
+pub fn parse_df(output: &str) -> Option<u64> {
+    output.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()
+}
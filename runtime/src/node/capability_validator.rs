@@ -10,5 +10,6 @@ impl UnifiedNode {
             && self.capability.gpus >= required.gpus
             && self.capability.gpu_memory_gb >= required.gpu_memory_gb
             && self.capability.available_stake >= required.available_stake
+            && self.capability.benchmark_score() >= required.min_benchmark_score
     }
 } 
\ No newline at end of file
@@ -32,4 +32,10 @@ pub enum NodeError {
 
     #[error("The operation is not valid for the job's current state")]
     InvalidStateTransition,
+
+    #[error("Container execution error: {0}")]
+    Container(#[from] crate::container::ContainerError),
+
+    #[error("Job {0} does not name a container image")]
+    NoContainer(u64),
 } 
\ No newline at end of file
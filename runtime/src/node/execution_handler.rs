@@ -5,7 +5,9 @@ use super::{
     node::UnifiedNode,
     types::{JobStatus, TrainingResult},
 };
+use crate::container::{ContainerOutcome, ContainerRunner};
 use crate::pouw::generate_task_with_timestamp;
+use std::path::Path;
 
 impl UnifiedNode {
     /// Executes a training task and generates a result with PoUW.
@@ -43,6 +45,39 @@ impl UnifiedNode {
         Ok(training_result)
     }
 
+    /// Runs a job in the container its spec names, with the spec's resource
+    /// limits, and returns the run's logs, artifacts and metrics. `input`
+    /// is mounted read-only for the job. A job whose container fails is
+    /// marked failed.
+    pub fn execute_container_job(
+        &mut self,
+        job_id: u64,
+        runner: &ContainerRunner,
+        input: Option<&Path>,
+    ) -> Result<ContainerOutcome, NodeError> {
+        let job = self.distributed_jobs.get_mut(&job_id).ok_or(NodeError::JobNotFound(job_id))?;
+        if job.status != JobStatus::WorkersAssigned {
+            return Err(NodeError::InvalidStateTransition);
+        }
+        let spec = job.container.clone().ok_or(NodeError::NoContainer(job_id))?;
+        if spec.limits.gpus > self.capability.gpus {
+            return Err(NodeError::CapabilityMismatch);
+        }
+
+        job.status = JobStatus::Training;
+        match runner.run(job_id, &spec, input) {
+            Ok(outcome) => {
+                job.status = JobStatus::EvaluationPending;
+                Ok(outcome)
+            }
+            Err(e) => {
+                tracing::warn!(job_id, error = %e, "container job failed");
+                job.status = JobStatus::Failed;
+                Err(e.into())
+            }
+        }
+    }
+
     /// Evaluates a submitted training result.
     pub fn evaluate_training_result(
         &mut self,
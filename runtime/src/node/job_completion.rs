@@ -18,11 +18,12 @@ impl UnifiedNode {
 
         // Pay out the reward to the assigned workers.
         let reward_per_worker = job.reward / job.assigned_workers.len() as u64;
-        for worker_id in &job.assigned_workers {
-            self.job_manager
-                .ledger_mut()
-                .transfer("escrow", worker_id, reward_per_worker)?;
-        }
+        let payouts: Vec<(String, u64)> = job
+            .assigned_workers
+            .iter()
+            .map(|worker_id| (worker_id.clone(), reward_per_worker))
+            .collect();
+        self.job_manager.complete_job(job_id, &payouts)?;
 
         job.status = JobStatus::Completed;
         Ok(())
@@ -3,6 +3,7 @@ use super::{
     node::UnifiedNode,
     types::{DistributedJob, JobStatus, NodeCapability},
 };
+use crate::container::ContainerSpec;
 use crate::job_manager::JobManagerError;
 
 impl UnifiedNode {
@@ -37,9 +38,21 @@ impl UnifiedNode {
             status: JobStatus::Posted,
             created_block: self.current_block,
             completion_deadline: self.current_block + deadline_blocks,
+            container: None,
         };
 
         self.distributed_jobs.insert(job_id, job);
         Ok(job_id)
     }
-} 
\ No newline at end of file
+
+    /// Has workers run a posted job in the container `spec` describes.
+    pub fn set_job_container(&mut self, job_id: u64, spec: ContainerSpec) -> Result<(), NodeError> {
+        let job = self.distributed_jobs.get_mut(&job_id).ok_or(NodeError::JobNotFound(job_id))?;
+        if job.status != JobStatus::Posted {
+            return Err(NodeError::InvalidStateTransition);
+        }
+        spec.validate()?;
+        job.container = Some(spec);
+        Ok(())
+    }
+}
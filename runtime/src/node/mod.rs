@@ -4,10 +4,12 @@
 //! The `UnifiedNode` struct is the central state container, while the logic for
 //! operating on that state is split across various handler modules.
 
+pub mod capability_probe;
 pub mod error;
 pub mod execution_handler;
 pub mod job_handler;
 pub mod node;
+pub mod reputation;
 pub mod stake_handler;
 pub mod types;
 
@@ -22,6 +24,7 @@ mod tests;
 
 pub use error::NodeError;
 pub use node::UnifiedNode;
+pub use reputation::{Dimension, Reputation, ReputationBook, ReputationConfig, ReputationWeights};
 pub use types::{
     CapabilityType, DistributedJob, JobStatus, NodeCapability, NodeStats, NodeStatus,
     TrainingResult,
@@ -0,0 +1,228 @@
+//! Multi-dimensional node reputation with time decay.
+//!
+//! A node is scored separately on training quality, uptime, evaluation
+//! honesty and storage reliability. Every score decays exponentially towards
+//! zero with a configurable half-life, so old behaviour, good or bad, counts
+//! for less than recent behaviour. Schedulers collapse the dimensions into a
+//! single number with [`ReputationWeights`], which can differ per job kind:
+//! a training job cares about training quality, a data validation job about
+//! honest evaluations.
+
+use crate::pouw::task_types::{data_validation, inference, matrix, train, DEFAULT_TASK_KIND};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// One aspect of a node's behaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    TrainingQuality,
+    Uptime,
+    EvaluationHonesty,
+    StorageReliability,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 4] =
+        [Dimension::TrainingQuality, Dimension::Uptime, Dimension::EvaluationHonesty, Dimension::StorageReliability];
+}
+
+/// A score as of `updated_at` (unix seconds); it decays from then on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub value: f64,
+    pub updated_at: u64,
+}
+
+impl Score {
+    /// The score at `now`, halving every `half_life_secs`.
+    pub fn at(&self, now: u64, half_life_secs: u64) -> f64 {
+        if half_life_secs == 0 {
+            return self.value;
+        }
+        let elapsed = now.saturating_sub(self.updated_at) as f64;
+        self.value * 0.5f64.powf(elapsed / half_life_secs as f64)
+    }
+}
+
+/// Per-dimension reputation of a node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reputation {
+    pub training_quality: Score,
+    pub uptime: Score,
+    pub evaluation_honesty: Score,
+    pub storage_reliability: Score,
+}
+
+impl Reputation {
+    /// The same score in every dimension, e.g. for a node known only by a
+    /// single reputation number.
+    pub fn uniform(value: i32) -> Self {
+        let score = Score { value: value as f64, updated_at: 0 };
+        Self { training_quality: score, uptime: score, evaluation_honesty: score, storage_reliability: score }
+    }
+
+    pub fn get(&self, dimension: Dimension) -> &Score {
+        match dimension {
+            Dimension::TrainingQuality => &self.training_quality,
+            Dimension::Uptime => &self.uptime,
+            Dimension::EvaluationHonesty => &self.evaluation_honesty,
+            Dimension::StorageReliability => &self.storage_reliability,
+        }
+    }
+
+    fn get_mut(&mut self, dimension: Dimension) -> &mut Score {
+        match dimension {
+            Dimension::TrainingQuality => &mut self.training_quality,
+            Dimension::Uptime => &mut self.uptime,
+            Dimension::EvaluationHonesty => &mut self.evaluation_honesty,
+            Dimension::StorageReliability => &mut self.storage_reliability,
+        }
+    }
+
+    /// Decay `dimension` to `now`, then add `delta`.
+    pub fn record(&mut self, dimension: Dimension, delta: f64, now: u64, half_life_secs: u64) {
+        let score = self.get_mut(dimension);
+        let value = score.at(now, half_life_secs) + delta;
+        *score = Score { value, updated_at: now.max(score.updated_at) };
+    }
+
+    /// Every dimension decayed to `now`.
+    pub fn decayed(&self, now: u64, half_life_secs: u64) -> Self {
+        let mut out = self.clone();
+        for dimension in Dimension::ALL {
+            let score = out.get_mut(dimension);
+            *score = Score { value: score.at(now, half_life_secs), updated_at: now.max(score.updated_at) };
+        }
+        out
+    }
+
+    /// Weighted average of the dimensions as last recorded.
+    pub fn weighted(&self, weights: &ReputationWeights) -> f64 {
+        let total = weights.total();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        Dimension::ALL.iter().map(|d| weights.get(*d).max(0.0) * self.get(*d).value).sum::<f64>() / total
+    }
+
+    /// Equally weighted average, rounded; comparable to the single
+    /// reputation number nodes used to carry.
+    pub fn overall(&self) -> i32 {
+        self.weighted(&ReputationWeights::default()).round() as i32
+    }
+}
+
+impl From<i32> for Reputation {
+    fn from(value: i32) -> Self {
+        Self::uniform(value)
+    }
+}
+
+/// How much each dimension counts. Only the ratios matter; negative
+/// weights count as zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReputationWeights {
+    pub training_quality: f64,
+    pub uptime: f64,
+    pub evaluation_honesty: f64,
+    pub storage_reliability: f64,
+}
+
+impl Default for ReputationWeights {
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 1.0, 1.0)
+    }
+}
+
+impl ReputationWeights {
+    pub fn new(training_quality: f64, uptime: f64, evaluation_honesty: f64, storage_reliability: f64) -> Self {
+        Self { training_quality, uptime, evaluation_honesty, storage_reliability }
+    }
+
+    pub fn get(&self, dimension: Dimension) -> f64 {
+        match dimension {
+            Dimension::TrainingQuality => self.training_quality,
+            Dimension::Uptime => self.uptime,
+            Dimension::EvaluationHonesty => self.evaluation_honesty,
+            Dimension::StorageReliability => self.storage_reliability,
+        }
+    }
+
+    fn total(&self) -> f64 {
+        Dimension::ALL.iter().map(|d| self.get(*d).max(0.0)).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationConfig {
+    /// Seconds for a score to decay to half; 0 disables decay.
+    pub half_life_secs: u64,
+    /// Weights for job kinds without an entry in `job_weights`.
+    pub default_weights: ReputationWeights,
+    /// Weights by job kind, see [`crate::pouw::task_types`].
+    pub job_weights: BTreeMap<String, ReputationWeights>,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        let training = ReputationWeights::new(3.0, 2.0, 1.0, 0.5);
+        let job_weights = BTreeMap::from([
+            (train::KIND.to_string(), training.clone()),
+            (train::ONNX_KIND.to_string(), training),
+            (matrix::KIND.to_string(), ReputationWeights::new(1.0, 2.0, 1.0, 0.0)),
+            (inference::KIND.to_string(), ReputationWeights::new(1.0, 3.0, 1.0, 0.5)),
+            (data_validation::KIND.to_string(), ReputationWeights::new(0.5, 1.0, 3.0, 2.0)),
+        ]);
+        Self { half_life_secs: 7 * 24 * 3600, default_weights: ReputationWeights::default(), job_weights }
+    }
+}
+
+impl ReputationConfig {
+    /// Weights for jobs of `kind`; `None` is the default task kind.
+    pub fn weights_for(&self, kind: Option<&str>) -> &ReputationWeights {
+        self.job_weights.get(kind.unwrap_or(DEFAULT_TASK_KIND)).unwrap_or(&self.default_weights)
+    }
+}
+
+/// Reputation of every known account.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReputationBook {
+    pub config: ReputationConfig,
+    accounts: HashMap<String, Reputation>,
+}
+
+impl ReputationBook {
+    pub fn new(config: ReputationConfig) -> Self {
+        Self { config, accounts: HashMap::new() }
+    }
+
+    pub fn record(&mut self, account: &str, dimension: Dimension, delta: f64, now: u64) {
+        let half_life = self.config.half_life_secs;
+        self.accounts.entry(account.to_string()).or_default().record(dimension, delta, now, half_life);
+    }
+
+    /// `account`'s reputation decayed to `now`.
+    pub fn get(&self, account: &str, now: u64) -> Reputation {
+        self.accounts.get(account).map(|r| r.decayed(now, self.config.half_life_secs)).unwrap_or_default()
+    }
+
+    /// `account`'s score for a job of `kind` at `now`.
+    pub fn score(&self, account: &str, kind: Option<&str>, now: u64) -> f64 {
+        self.get(account, now).weighted(self.config.weights_for(kind))
+    }
+
+    /// `accounts` by their score for a job of `kind`, best first.
+    pub fn rank<'a>(
+        &self,
+        accounts: impl IntoIterator<Item = &'a str>,
+        kind: Option<&str>,
+        now: u64,
+    ) -> Vec<(String, f64)> {
+        let mut ranked: Vec<(String, f64)> =
+            accounts.into_iter().map(|a| (a.to_string(), self.score(a, kind, now))).collect();
+        ranked.sort_by(|(a, sa), (b, sb)| sb.total_cmp(sa).then_with(|| a.cmp(b)));
+        ranked
+    }
+}
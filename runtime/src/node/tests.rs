@@ -6,8 +6,10 @@ fn default_capability() -> NodeCapability {
         gpus: 1,
         gpu_memory_gb: 16,
         available_stake: 0,
-        reputation: 100,
+        reputation: Reputation::uniform(100),
         capability_types: vec![CapabilityType::Training],
+        min_benchmark_score: 0,
+        benchmark: None,
     }
 }
 
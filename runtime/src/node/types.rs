@@ -1,5 +1,6 @@
 //! Defines the core data structures used by the `UnifiedNode` and its services.
 
+use crate::container::ContainerSpec;
 use crate::pouw::Solution;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -11,8 +12,16 @@ pub struct NodeCapability {
     pub gpus: u32,
     pub gpu_memory_gb: u32,
     pub available_stake: u64,
-    pub reputation: i32,
+    /// Per-dimension reputation, see [`super::reputation`].
+    pub reputation: super::reputation::Reputation,
     pub capability_types: Vec<CapabilityType>,
+    /// Lowest [`NodeCapability::benchmark_score`] a node needs when this
+    /// capability is a job's requirement.
+    #[serde(default)]
+    pub min_benchmark_score: u64,
+    /// Signed hardware benchmark, see [`super::capability_probe`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<super::capability_probe::SignedBenchmark>,
 }
 
 /// The different types of capabilities a node can advertise.
@@ -41,6 +50,9 @@ pub struct DistributedJob {
     pub status: JobStatus,
     pub created_block: u64,
     pub completion_deadline: u64,
+    /// Image the job runs in on workers, see [`crate::container`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerSpec>,
 }
 
 /// The lifecycle status of a `DistributedJob`.
@@ -79,7 +91,7 @@ pub struct NodeStats {
     pub node_id: String,
     pub balance: u64,
     pub staked: u64,
-    pub reputation: i32,
+    pub reputation: super::reputation::Reputation,
     pub jobs_completed: usize,
     pub jobs_active: usize,
 } 
\ No newline at end of file
@@ -0,0 +1,405 @@
+//! Onion routing for private job bids.
+//!
+//! In privacy mode a worker sends its bids through two or three relays
+//! instead of straight to the job poster. Each relay can decrypt only its own
+//! layer, which names the next hop, so the poster sees the last relay as the
+//! sender and no single relay learns both the worker and the poster. A bid
+//! carries a [`ReplyBlock`], a pre-built route back to the worker, through
+//! which the poster counters, accepts or rejects without learning who it is
+//! talking to. The worker reveals itself only when it claims an accepted job
+//! with the token from [`BidMessage::Accept`].
+//!
+//! Every layer is sealed with x25519 key agreement against a fresh ephemeral
+//! key and AES-256-GCM. Replies carry their payload outside the layers,
+//! sealed end to end to the worker, so relays on a reply path see the same
+//! ciphertext. Packets are not padded; a relay can guess its position on a
+//! route from the packet size.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+use crate::rng::{self, Stream};
+
+#[cfg(feature = "p2p")]
+use crate::p2p_service::{codec::WireMessage, P2PError, P2PHandle};
+
+const ONION_DOMAIN: &[u8] = b"bcai-onion-v1";
+
+pub const MIN_HOPS: usize = 2;
+pub const MAX_HOPS: usize = 3;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum OnionError {
+    #[error("onion routes take 2 to 3 hops, not {0}")]
+    Hops(usize),
+    #[error("need {needed} relays, only {available} known")]
+    NotEnoughRelays { needed: usize, available: usize },
+    #[error("not sealed to this key")]
+    Decrypt,
+    #[error("malformed onion message: {0}")]
+    Malformed(String),
+}
+
+/// Whether and how this node hides its bids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnionConfig {
+    /// Route bids and negotiations through relays instead of sending them
+    /// to the poster directly.
+    pub private_bids: bool,
+    /// Relays per route, between [`MIN_HOPS`] and [`MAX_HOPS`].
+    pub hops: usize,
+}
+
+impl Default for OnionConfig {
+    fn default() -> Self {
+        Self { private_bids: false, hops: MAX_HOPS }
+    }
+}
+
+/// A message only the holder of one [`OnionKey`] can read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sealed {
+    pub ephemeral: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+fn cipher(shared: &[u8; 32], ephemeral: &[u8; 32], recipient: &[u8; 32]) -> Aes256Gcm {
+    let mut hasher = Sha256::new();
+    hasher.update(ONION_DOMAIN);
+    hasher.update(shared);
+    hasher.update(ephemeral);
+    hasher.update(recipient);
+    Aes256Gcm::new_from_slice(&hasher.finalize()).expect("SHA-256 output is an AES-256 key")
+}
+
+/// Seal `plaintext` to the holder of the secret half of `recipient`.
+pub fn seal(recipient: &[u8; 32], plaintext: &[u8]) -> Result<Sealed, OnionError> {
+    let secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral = PublicKey::from(&secret).to_bytes();
+    let shared = secret.diffie_hellman(&PublicKey::from(*recipient));
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher(shared.as_bytes(), &ephemeral, recipient)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| OnionError::Malformed(e.to_string()))?;
+    Ok(Sealed { ephemeral, nonce, ciphertext })
+}
+
+/// What one relay finds under its layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Layer {
+    Relay { next: String, inner: Sealed },
+    /// The last hop. Replies bring their payload in the packet instead.
+    Exit { destination: String, payload: Option<Sealed> },
+}
+
+/// An onion on its way through a route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnionPacket {
+    pub header: Sealed,
+    pub payload: Option<Sealed>,
+}
+
+/// Where an onion message goes next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Next {
+    /// Pass `packet` on to the relay `peer`.
+    Relay { peer: String, packet: OnionPacket },
+    /// Hand `payload` to `peer`, which can open it.
+    Deliver { peer: String, payload: Sealed },
+}
+
+/// A node's x25519 key for opening sealed messages and peeling onion layers.
+#[derive(Clone)]
+pub struct OnionKey(StaticSecret);
+
+impl std::fmt::Debug for OnionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OnionKey").field(&hex::encode(self.public())).finish()
+    }
+}
+
+impl OnionKey {
+    pub fn generate() -> Self {
+        Self(StaticSecret::random_from_rng(OsRng))
+    }
+
+    pub fn public(&self) -> [u8; 32] {
+        PublicKey::from(&self.0).to_bytes()
+    }
+
+    pub fn open(&self, sealed: &Sealed) -> Result<Vec<u8>, OnionError> {
+        let shared = self.0.diffie_hellman(&PublicKey::from(sealed.ephemeral));
+        cipher(shared.as_bytes(), &sealed.ephemeral, &self.public())
+            .decrypt(Nonce::from_slice(&sealed.nonce), sealed.ciphertext.as_slice())
+            .map_err(|_| OnionError::Decrypt)
+    }
+
+    /// Remove this relay's layer from `packet`.
+    pub fn peel(&self, packet: OnionPacket) -> Result<Next, OnionError> {
+        let layer: Layer =
+            bincode::deserialize(&self.open(&packet.header)?).map_err(|e| OnionError::Malformed(e.to_string()))?;
+        match layer {
+            Layer::Relay { next, inner } => {
+                Ok(Next::Relay { peer: next, packet: OnionPacket { header: inner, payload: packet.payload } })
+            }
+            Layer::Exit { destination, payload } => payload
+                .or(packet.payload)
+                .map(|payload| Next::Deliver { peer: destination, payload })
+                .ok_or_else(|| OnionError::Malformed("no payload to deliver".into())),
+        }
+    }
+}
+
+/// A relay and the key it peels layers with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayInfo {
+    /// Peer id of the relay.
+    pub peer: String,
+    pub key: [u8; 32],
+}
+
+fn check_hops(hops: usize) -> Result<(), OnionError> {
+    if (MIN_HOPS..=MAX_HOPS).contains(&hops) {
+        Ok(())
+    } else {
+        Err(OnionError::Hops(hops))
+    }
+}
+
+/// `hops` distinct relays picked at random, none of them in `exclude`.
+pub fn choose_route(relays: &[RelayInfo], hops: usize, exclude: &[&str]) -> Result<Vec<RelayInfo>, OnionError> {
+    choose_route_with(relays, hops, exclude, &mut rng::stream(Stream::PeerSelection))
+}
+
+/// [`choose_route`] drawing the relays from `rng`.
+pub fn choose_route_with<R: Rng>(
+    relays: &[RelayInfo],
+    hops: usize,
+    exclude: &[&str],
+    rng: &mut R,
+) -> Result<Vec<RelayInfo>, OnionError> {
+    check_hops(hops)?;
+    let mut eligible: Vec<&RelayInfo> = relays.iter().filter(|r| !exclude.contains(&r.peer.as_str())).collect();
+    eligible.sort_by(|a, b| a.peer.cmp(&b.peer));
+    eligible.dedup_by(|a, b| a.peer == b.peer);
+    if eligible.len() < hops {
+        return Err(OnionError::NotEnoughRelays { needed: hops, available: eligible.len() });
+    }
+    Ok(eligible.choose_multiple(rng, hops).map(|r| (*r).clone()).collect())
+}
+
+fn seal_layer(relay: &RelayInfo, layer: &Layer) -> Result<Sealed, OnionError> {
+    let bytes = bincode::serialize(layer).map_err(|e| OnionError::Malformed(e.to_string()))?;
+    seal(&relay.key, &bytes)
+}
+
+/// Layers around `exit`, the innermost for the last relay of `route`.
+fn wrap_layers(route: &[RelayInfo], exit: Layer) -> Result<Sealed, OnionError> {
+    check_hops(route.len())?;
+    let (last, rest) = route.split_last().ok_or(OnionError::Hops(0))?;
+    let mut header = seal_layer(last, &exit)?;
+    let mut next = &last.peer;
+    for relay in rest.iter().rev() {
+        header = seal_layer(relay, &Layer::Relay { next: next.clone(), inner: header })?;
+        next = &relay.peer;
+    }
+    Ok(header)
+}
+
+/// Send `message`, sealed to `destination_key`, to `destination` through
+/// `route`; an empty route delivers it directly.
+pub fn wrap(
+    route: &[RelayInfo],
+    destination: &str,
+    destination_key: &[u8; 32],
+    message: &[u8],
+) -> Result<Next, OnionError> {
+    let payload = seal(destination_key, message)?;
+    let Some(first) = route.first() else {
+        return Ok(Next::Deliver { peer: destination.to_string(), payload });
+    };
+    let header = wrap_layers(route, Layer::Exit { destination: destination.to_string(), payload: Some(payload) })?;
+    Ok(Next::Relay { peer: first.peer.clone(), packet: OnionPacket { header, payload: None } })
+}
+
+/// A way back to an anonymous sender, for the other side to answer through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplyBlock {
+    /// First relay of the route, or the sender itself if there is none.
+    pub first_hop: String,
+    pub header: Option<Sealed>,
+    /// Replies are sealed to this key; only the sender holds its secret.
+    pub key: [u8; 32],
+}
+
+impl ReplyBlock {
+    /// A block routing replies through `route` to `me`, directly if the
+    /// route is empty, and the key that opens them.
+    pub fn new(route: &[RelayInfo], me: &str) -> Result<(Self, OnionKey), OnionError> {
+        let key = OnionKey::generate();
+        let (first_hop, header) = match route.first() {
+            None => (me.to_string(), None),
+            Some(first) => (
+                first.peer.clone(),
+                Some(wrap_layers(route, Layer::Exit { destination: me.to_string(), payload: None })?),
+            ),
+        };
+        Ok((Self { first_hop, header, key: key.public() }, key))
+    }
+
+    /// Carry `message` back to the block's creator.
+    pub fn wrap(&self, message: &[u8]) -> Result<Next, OnionError> {
+        let payload = seal(&self.key, message)?;
+        let peer = self.first_hop.clone();
+        Ok(match &self.header {
+            Some(header) => Next::Relay { peer, packet: OnionPacket { header: header.clone(), payload: Some(payload) } },
+            None => Next::Deliver { peer, payload },
+        })
+    }
+}
+
+/// Messages of a bid negotiation. Workers send them to the poster, sealed
+/// to its key; the poster answers through the bid's reply block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BidMessage {
+    Bid { job_id: u64, price: u64, reply: ReplyBlock },
+    /// The price the poster would accept instead.
+    Counter { job_id: u64, price: u64 },
+    /// The job is the worker's at `price`; it claims the job with `token`.
+    Accept { job_id: u64, price: u64, token: [u8; 16] },
+    /// Another bid won.
+    Reject { job_id: u64 },
+}
+
+impl BidMessage {
+    pub fn encode(&self) -> Result<Vec<u8>, OnionError> {
+        bincode::serialize(self).map_err(|e| OnionError::Malformed(e.to_string()))
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, OnionError> {
+        bincode::deserialize(bytes).map_err(|e| OnionError::Malformed(e.to_string()))
+    }
+}
+
+/// A poster's record of the bids on its jobs.
+#[derive(Debug, Clone, Default)]
+pub struct BidBook {
+    open: BTreeMap<u64, Vec<(u64, ReplyBlock)>>,
+    /// Claim tokens of awarded jobs, `None` once claimed.
+    awarded: HashMap<u64, Option<[u8; 16]>>,
+}
+
+impl BidBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a bid. Returns `false` if the job was already awarded.
+    pub fn bid(&mut self, job_id: u64, price: u64, reply: ReplyBlock) -> bool {
+        if self.awarded.contains_key(&job_id) {
+            return false;
+        }
+        self.open.entry(job_id).or_default().push((price, reply));
+        true
+    }
+
+    /// Lowest price bid on `job_id` so far.
+    pub fn best(&self, job_id: u64) -> Option<u64> {
+        self.open.get(&job_id)?.iter().map(|(price, _)| *price).min()
+    }
+
+    /// Award `job_id` to the lowest bid, the earliest on a tie. Returns the
+    /// answers to send: an `Accept` for the winner, a `Reject` for the rest.
+    pub fn award(&mut self, job_id: u64) -> Vec<(ReplyBlock, BidMessage)> {
+        let Some(bids) = self.open.remove(&job_id) else { return Vec::new() };
+        let Some(winner) = bids.iter().enumerate().min_by_key(|(_, (price, _))| *price).map(|(i, _)| i) else {
+            return Vec::new();
+        };
+        let mut token = [0u8; 16];
+        OsRng.fill_bytes(&mut token);
+        self.awarded.insert(job_id, Some(token));
+        bids.into_iter()
+            .enumerate()
+            .map(|(i, (price, reply))| {
+                let answer = if i == winner {
+                    BidMessage::Accept { job_id, price, token }
+                } else {
+                    BidMessage::Reject { job_id }
+                };
+                (reply, answer)
+            })
+            .collect()
+    }
+
+    /// Whether `token` claims `job_id`. A token is good for one claim.
+    pub fn claim(&mut self, job_id: u64, token: &[u8; 16]) -> bool {
+        match self.awarded.get_mut(&job_id) {
+            Some(slot) if slot.as_ref() == Some(token) => {
+                *slot = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "p2p")]
+impl Next {
+    /// The peer to send to and the request carrying the message.
+    pub fn into_request(self) -> (String, WireMessage) {
+        match self {
+            Next::Relay { peer, packet } => (peer, WireMessage::Onion(packet)),
+            Next::Deliver { peer, payload } => (peer, WireMessage::OnionDelivery(payload)),
+        }
+    }
+}
+
+#[cfg(feature = "p2p")]
+pub async fn send(handle: &P2PHandle, next: Next) -> Result<(), P2PError> {
+    let (peer, message) = next.into_request();
+    let peer_id = peer.parse().map_err(|_| P2PError::PeerNotFound(peer))?;
+    handle.request(peer_id, message).await.map(|_| ())
+}
+
+/// A fresh route of connected relays avoiding `exclude` in privacy mode,
+/// an empty (direct) route otherwise.
+#[cfg(feature = "p2p")]
+pub async fn route(handle: &P2PHandle, config: &OnionConfig, exclude: &[&str]) -> Result<Vec<RelayInfo>, P2PError> {
+    if !config.private_bids {
+        return Ok(Vec::new());
+    }
+    let relays = handle.relays().await?;
+    choose_route(&relays, config.hops, exclude).map_err(|e| P2PError::Network(e.to_string()))
+}
+
+/// Bid `price` on `job_id` of the poster `poster`, whose onion key is
+/// `poster_key`. `me` is this node's peer id. Returns the key that opens the
+/// poster's answers, which arrive as onion deliveries.
+#[cfg(feature = "p2p")]
+pub async fn send_bid(
+    handle: &P2PHandle,
+    config: &OnionConfig,
+    me: &str,
+    poster: &str,
+    poster_key: &[u8; 32],
+    job_id: u64,
+    price: u64,
+) -> Result<OnionKey, P2PError> {
+    let onion_err = |e: OnionError| P2PError::Network(e.to_string());
+    let (reply, reply_key) = ReplyBlock::new(&route(handle, config, &[me, poster]).await?, me).map_err(onion_err)?;
+    let bid = BidMessage::Bid { job_id, price, reply }.encode().map_err(onion_err)?;
+    let out = route(handle, config, &[me, poster]).await?;
+    send(handle, wrap(&out, poster, poster_key, &bid).map_err(onion_err)?).await?;
+    Ok(reply_key)
+}
@@ -1,20 +1,62 @@
 //! Defines the request-response codec and wire format for P2P messages.
+//!
+//! Messages are sent as a [`crate::wire::PROTOCOL_VERSION`] byte followed
+//! by a protobuf [`proto::Envelope`], unless a `wire-*` feature selects
+//! another [`format::WireFormat`]. Nodes that have not upgraded send bare
+//! JSON, which always opens with `{` or `"` and so can't be mistaken for a
+//! version byte; it is still read.
 
 use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use libp2p::request_response;
 use serde::{Deserialize, Serialize};
 
+use crate::large_data_transfer::network::PayloadDescriptor;
+use crate::wire::{format, proto, WireError};
+
 /// The message format that goes over the wire.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WireMessage {
     Block(crate::blockchain::block::Block),
     Transaction(crate::blockchain::transaction::Transaction),
+    /// Ask a peer for the blocks from `from_height` on, to fill in history
+    /// gossip missed. Peers answer with at most [`MAX_SYNC_BLOCKS`].
+    GetBlocks { from_height: u64 },
+    /// Response to `GetBlocks`, in height order; empty if the peer does not
+    /// have the first one.
+    Blocks(Vec<crate::blockchain::block::Block>),
+    /// Gossiped in place of a large payload, whose body is fetched on demand.
+    Announce(PayloadDescriptor),
+    /// Request the body of an announced payload.
+    GetPayload { hash: String },
+    /// Response to `GetPayload`; `None` if the peer does not hold the body.
+    Payload { hash: String, body: Option<Vec<u8>> },
+    /// Ask a peer which roles it serves.
+    GetRoles,
+    /// Response to `GetRoles`.
+    Roles(Vec<crate::connection_manager::PeerRole>),
+    /// Ask a relay for the key it peels onion layers with.
+    GetOnionKey,
+    /// Response to `GetOnionKey`; `None` if the peer does not relay.
+    OnionKey(Option<[u8; 32]>),
+    /// An onion for the receiving relay to peel and pass on.
+    Onion(crate::onion::OnionPacket),
+    /// A message sealed to the receiver, from the last relay of a route.
+    OnionDelivery(crate::onion::Sealed),
+    /// Ask a peer for the job channel key of the account it runs as.
+    GetChannelKey,
+    /// Response to `GetChannelKey`; `None` if the peer has no channel key.
+    ChannelKey(Option<crate::job_channel::ChannelKeyCert>),
+    /// An artifact sealed to the receiver over a job channel.
+    JobChannel(crate::job_channel::ChannelMessage),
     Ping,
     Pong,
 }
 
+/// Most blocks sent in answer to one `GetBlocks`.
+pub const MAX_SYNC_BLOCKS: usize = 64;
+
 /// The codec used for the request-response protocol.
-/// It uses JSON for serialization.
+/// It uses the protobuf wire envelope, see [`encode_message`].
 #[derive(Debug, Clone, Default)]
 pub struct WireCodec;
 
@@ -73,22 +115,93 @@ impl request_response::Codec for WireCodec {
     }
 }
 
+impl From<&WireMessage> for proto::Envelope {
+    fn from(message: &WireMessage) -> Self {
+        use proto::Body;
+        let body = match message {
+            WireMessage::Block(block) => Body::Block(block.into()),
+            WireMessage::Transaction(tx) => Body::Transaction(tx.into()),
+            WireMessage::GetBlocks { from_height } => Body::GetBlocks(proto::GetBlocks { from_height: *from_height }),
+            WireMessage::Blocks(blocks) => {
+                Body::Blocks(proto::Blocks { blocks: blocks.iter().map(proto::Block::from).collect() })
+            }
+            WireMessage::Announce(descriptor) => Body::Announce(descriptor.into()),
+            WireMessage::GetPayload { hash } => Body::GetPayload(proto::GetPayload { hash: hash.clone() }),
+            WireMessage::Payload { hash, body } => {
+                Body::Payload(proto::Payload { hash: hash.clone(), body: body.clone() })
+            }
+            WireMessage::GetRoles => Body::GetRoles(proto::Empty {}),
+            WireMessage::Roles(roles) => Body::Roles(proto::Roles::new(roles)),
+            WireMessage::GetOnionKey => Body::GetOnionKey(proto::Empty {}),
+            WireMessage::OnionKey(key) => Body::OnionKey(proto::OnionKey::new(*key)),
+            WireMessage::Onion(packet) => Body::Onion(packet.into()),
+            WireMessage::OnionDelivery(sealed) => Body::OnionDelivery(sealed.into()),
+            WireMessage::GetChannelKey => Body::GetChannelKey(proto::Empty {}),
+            WireMessage::ChannelKey(cert) => Body::ChannelKey(proto::ChannelKey::new(cert.as_ref())),
+            WireMessage::JobChannel(message) => Body::JobChannel(message.into()),
+            WireMessage::Ping => Body::Ping(proto::Empty {}),
+            WireMessage::Pong => Body::Pong(proto::Empty {}),
+        };
+        Self { body: Some(body) }
+    }
+}
+
+impl TryFrom<proto::Envelope> for WireMessage {
+    type Error = WireError;
+
+    fn try_from(envelope: proto::Envelope) -> Result<Self, WireError> {
+        use proto::Body;
+        let body = envelope.body.ok_or_else(|| WireError::Malformed("unknown message".into()))?;
+        Ok(match body {
+            Body::Block(block) => WireMessage::Block(block.try_into()?),
+            Body::Transaction(tx) => WireMessage::Transaction(tx.try_into()?),
+            Body::GetBlocks(get) => WireMessage::GetBlocks { from_height: get.from_height },
+            Body::Blocks(blocks) => {
+                WireMessage::Blocks(blocks.blocks.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?)
+            }
+            Body::Announce(descriptor) => WireMessage::Announce(descriptor.into()),
+            Body::GetPayload(get) => WireMessage::GetPayload { hash: get.hash },
+            Body::Payload(payload) => WireMessage::Payload { hash: payload.hash, body: payload.body },
+            Body::GetRoles(_) => WireMessage::GetRoles,
+            Body::Roles(roles) => WireMessage::Roles(roles.known()),
+            Body::GetOnionKey(_) => WireMessage::GetOnionKey,
+            Body::OnionKey(key) => WireMessage::OnionKey(key.key()?),
+            Body::Onion(packet) => WireMessage::Onion(packet.try_into()?),
+            Body::OnionDelivery(sealed) => WireMessage::OnionDelivery(sealed.try_into()?),
+            Body::GetChannelKey(_) => WireMessage::GetChannelKey,
+            Body::ChannelKey(key) => WireMessage::ChannelKey(key.cert()?),
+            Body::JobChannel(message) => WireMessage::JobChannel(message.try_into()?),
+            Body::Ping(_) => WireMessage::Ping,
+            Body::Pong(_) => WireMessage::Pong,
+        })
+    }
+}
+
+/// `message` as sent on the wire and gossiped, in the [`format::SELECTED`]
+/// format.
+pub fn encode_message(message: &WireMessage) -> Vec<u8> {
+    format::frame(format::SELECTED, message).expect("wire messages encode in every format")
+}
+
+/// Read a message sent by [`encode_message`] in any format this build
+/// reads, or the JSON older nodes send.
+pub fn decode_message(bytes: &[u8]) -> Result<WireMessage, WireError> {
+    format::unframe(bytes)
+}
+
 // Helper functions to handle reading and writing, with basic error handling.
-async fn read_message<T, M>(io: &mut T) -> std::io::Result<M>
+async fn read_message<T>(io: &mut T) -> std::io::Result<WireMessage>
 where
     T: AsyncRead + Unpin + Send,
-    M: for<'de> Deserialize<'de>,
 {
     let mut vec = Vec::new();
     io.read_to_end(&mut vec).await?;
-    serde_json::from_slice(&vec).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    decode_message(&vec).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
 }
 
-async fn write_message<T, M>(io: &mut T, msg: &M) -> std::io::Result<()>
+async fn write_message<T>(io: &mut T, msg: &WireMessage) -> std::io::Result<()>
 where
     T: AsyncWrite + Unpin + Send,
-    M: Serialize,
 {
-    let buf = serde_json::to_vec(msg).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-    io.write_all(&buf).await
-} 
\ No newline at end of file
+    io.write_all(&encode_message(msg)).await
+}
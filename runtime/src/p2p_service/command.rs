@@ -1,6 +1,9 @@
 //! Defines the command API for interacting with the P2P service.
 
 use super::{codec::WireMessage, error::P2PError};
+use crate::gossip::GossipTopic;
+use crate::large_data_transfer::network::{BandwidthCounters, PayloadDescriptor};
+use crate::onion::RelayInfo;
 use libp2p::{gossipsub, PeerId};
 use tokio::sync::{mpsc, oneshot};
 
@@ -21,6 +24,26 @@ pub enum Command {
     Bootstrap {
         response: oneshot::Sender<Result<(), P2PError>>,
     },
+    /// Store a large payload locally and gossip only its descriptor.
+    PublishPayload {
+        topic: gossipsub::IdentTopic,
+        body: Vec<u8>,
+        response: oneshot::Sender<Result<PayloadDescriptor, P2PError>>,
+    },
+    /// Fetch the body of a payload announced by `peer`.
+    FetchPayload {
+        peer_id: PeerId,
+        descriptor: PayloadDescriptor,
+        response: oneshot::Sender<Result<Vec<u8>, P2PError>>,
+    },
+    /// List connected relays whose onion keys are known.
+    GetRelays {
+        response: oneshot::Sender<Vec<RelayInfo>>,
+    },
+    /// Bytes moved and time spent throttled, for monitoring.
+    GetBandwidth {
+        response: oneshot::Sender<BandwidthCounters>,
+    },
     /// Send a direct request to a specific peer and await a response.
     Request {
         peer_id: PeerId,
@@ -55,6 +78,41 @@ impl P2PHandle {
         response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))?
     }
 
+    /// Broadcast a message to one of the typed gossip topics.
+    pub async fn gossip(&self, topic: GossipTopic, message: Vec<u8>) -> Result<(), P2PError> {
+        self.send_message(topic.name().to_string(), message).await
+    }
+
+    /// Publish a large payload: peers receive its descriptor through the
+    /// topic and fetch the body from this node on demand.
+    pub async fn publish_payload(&self, topic: String, body: Vec<u8>) -> Result<PayloadDescriptor, P2PError> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.command_sender
+            .send(Command::PublishPayload {
+                topic: gossipsub::IdentTopic::new(topic),
+                body,
+                response: response_sender,
+            })
+            .await
+            .map_err(|e| P2PError::ChannelError(e.to_string()))?;
+        response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))?
+    }
+
+    /// Fetch an announced payload body. Concurrent fetches of the same body
+    /// share a single request.
+    pub async fn fetch_payload(&self, peer_id: PeerId, descriptor: PayloadDescriptor) -> Result<Vec<u8>, P2PError> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.command_sender
+            .send(Command::FetchPayload {
+                peer_id,
+                descriptor,
+                response: response_sender,
+            })
+            .await
+            .map_err(|e| P2PError::ChannelError(e.to_string()))?;
+        response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))?
+    }
+
     /// Get a list of connected peer IDs.
     pub async fn get_peers(&self) -> Result<Vec<PeerId>, P2PError> {
         let (response_sender, response_receiver) = oneshot::channel();
@@ -62,7 +120,7 @@ impl P2PHandle {
             .send(Command::GetPeers { response: response_sender })
             .await
             .map_err(|e| P2PError::ChannelError(e.to_string()))?;
-        response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))?
+        response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))
     }
 
     /// Start the Kademlia bootstrap process.
@@ -88,4 +146,24 @@ impl P2PHandle {
             .map_err(|e| P2PError::ChannelError(e.to_string()))?;
         response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))?
     }
-} 
\ No newline at end of file
+
+    /// Payload bandwidth counters, overall and per peer.
+    pub async fn bandwidth(&self) -> Result<BandwidthCounters, P2PError> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.command_sender
+            .send(Command::GetBandwidth { response: response_sender })
+            .await
+            .map_err(|e| P2PError::ChannelError(e.to_string()))?;
+        response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))
+    }
+
+    /// Connected relays that can carry onion-routed messages.
+    pub async fn relays(&self) -> Result<Vec<RelayInfo>, P2PError> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.command_sender
+            .send(Command::GetRelays { response: response_sender })
+            .await
+            .map_err(|e| P2PError::ChannelError(e.to_string()))?;
+        response_receiver.await.map_err(|e| P2PError::ChannelError(e.to_string()))
+    }
+}
@@ -1,14 +1,39 @@
 //! Configuration for the P2P service.
 
+use crate::connection_manager::{ConnectionLimits, PeerRole};
+use crate::large_data_transfer::network::BandwidthConfig;
+use crate::net_address::{AddressConfig, TransportPreference};
+use crate::onion::OnionConfig;
 use serde::{Deserialize, Serialize};
 
 /// Basic configuration options for P2P networking.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct P2PConfig {
-    /// TCP port to listen on.
+    /// TCP and UDP (QUIC) port to listen on, unless `addresses.listen`
+    /// names addresses.
     pub listen_port: u16,
-    /// Optional external address to advertise.
+    /// Whether to run QUIC, TCP or both, and which to dial first.
+    #[serde(default)]
+    pub transport: TransportPreference,
+    /// Optional external address to advertise, see also `addresses.external`.
     pub external_address: Option<String>,
+    /// Most payload bodies fetched from peers at once; further fetches queue.
+    pub max_concurrent_fetches: usize,
+    /// Roles this node serves, reported to peers that ask.
+    #[serde(default)]
+    pub roles: Vec<PeerRole>,
+    /// Target peer counts per role and the connection cap.
+    #[serde(default)]
+    pub connections: ConnectionLimits,
+    /// Listen addresses and how advertised addresses are chosen.
+    #[serde(default)]
+    pub addresses: AddressConfig,
+    /// Whether bids are routed through relays, see [`crate::onion`].
+    #[serde(default)]
+    pub onion: OnionConfig,
+    /// Upload and download caps for payload transfers.
+    #[serde(default)]
+    pub bandwidth: BandwidthConfig,
 }
 
 impl Default for P2PConfig {
@@ -16,6 +41,13 @@ impl Default for P2PConfig {
         Self {
             listen_port: 0, // Let the OS pick a free port.
             external_address: None,
+            transport: TransportPreference::default(),
+            max_concurrent_fetches: 4,
+            roles: Vec::new(),
+            connections: ConnectionLimits::default(),
+            addresses: AddressConfig::default(),
+            onion: OnionConfig::default(),
+            bandwidth: BandwidthConfig::default(),
         }
     }
 } 
\ No newline at end of file
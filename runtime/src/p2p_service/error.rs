@@ -26,6 +26,9 @@ pub enum P2PError {
     #[error("An I/O error occurred: {0}")]
     IoError(String),
 
+    #[error("Payload unavailable: {0}")]
+    PayloadUnavailable(String),
+
     #[error("A generic network error occurred: {0}")]
     Network(String),
 }
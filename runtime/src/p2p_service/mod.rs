@@ -19,6 +19,7 @@ pub mod service_init;
 #[cfg(test)]
 mod tests;
 
+pub use codec::{decode_message, encode_message, WireMessage, MAX_SYNC_BLOCKS};
 pub use command::P2PHandle;
 pub use config::P2PConfig;
 pub use error::P2PError;
@@ -8,18 +8,41 @@ use super::{
     error::P2PError,
     types::{PeerInfo, P2PStats},
 };
+use crate::blockchain::{Block, Blockchain};
+use crate::large_data_transfer::network::{BandwidthLimiter, PayloadFetcher, TransferId};
+use crate::connection_manager::{ConnectionManager, PeerRole};
+use crate::job_channel::{ChannelKeyCert, ChannelMessage};
+use crate::net_address::{self, AdvertisedAddrs};
+use crate::onion::{OnionKey, OnionPacket, Sealed};
+use crate::scheduler::Heartbeat;
 use futures::StreamExt;
 use libp2p::{
     gossipsub, identity, kad,
     request_response::{self, ProtocolSupport},
-    swarm::{Swarm, SwarmEvent},
+    swarm::{dial_opts::DialOpts, Swarm, SwarmEvent},
     Multiaddr, PeerId,
 };
 use std::collections::HashMap;
+use std::num::NonZeroU8;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, Mutex};
 
-pub(super) const GLOBAL_TOPIC: &str = "bcai_global";
+/// Notified when a payload fetch completes.
+pub(super) type PayloadWaiter = oneshot::Sender<Result<Vec<u8>, P2PError>>;
+
+/// A payload message held back by the bandwidth limiter.
+pub(super) enum Held {
+    /// A body served to a peer.
+    Response {
+        peer: PeerId,
+        channel: request_response::ResponseChannel<WireMessage>,
+        response: WireMessage,
+        transfer: TransferId,
+    },
+    /// A request for a body from a peer.
+    Request { peer: PeerId, hash: String },
+}
 
 /// The main P2P service struct. It owns the libp2p Swarm and handles all
 /// network events and application-level commands.
@@ -29,16 +52,90 @@ pub struct P2PService {
     peers: HashMap<String, PeerInfo>,
     stats: P2PStats,
     start_time: Option<Instant>,
-    config: P2PConfig,
+    pub(super) config: P2PConfig,
     pub(super) request_map: HashMap<
         request_response::RequestId,
         oneshot::Sender<Result<WireMessage, P2PError>>,
     >,
+    /// Published payload bodies and de-duplicated fetches of announced ones.
+    pub(super) payloads: PayloadFetcher<PeerId, PayloadWaiter>,
+    /// Outstanding `GetPayload` requests by payload hash.
+    pub(super) payload_requests: HashMap<request_response::RequestId, String>,
+    /// Paces payload uploads and downloads and counts their bytes.
+    pub(super) bandwidth: BandwidthLimiter<PeerId>,
+    /// Download transfers by payload hash.
+    pub(super) payload_transfers: HashMap<String, TransferId>,
+    pub(super) held: mpsc::UnboundedSender<Held>,
+    pub(super) released: mpsc::UnboundedReceiver<Held>,
+    /// Receives verified worker heartbeats, usually feeding a `JobScheduler`.
+    pub(super) heartbeats: Option<mpsc::UnboundedSender<Heartbeat>>,
+    /// Receives blocks gossiped by peers, usually feeding a chain follower.
+    pub(super) blocks: Option<mpsc::UnboundedSender<Block>>,
+    /// The chain `GetBlocks` requests are answered from.
+    pub(super) chain: Option<Arc<Mutex<Blockchain>>>,
+    /// Keeps per-role peer counts near their targets.
+    pub(super) connections: ConnectionManager<PeerId>,
+    /// Candidate addresses to advertise and their reachability.
+    pub(super) addresses: AdvertisedAddrs,
+    pub(super) address_checks: mpsc::UnboundedSender<(Multiaddr, bool)>,
+    pub(super) address_results: mpsc::UnboundedReceiver<(Multiaddr, bool)>,
+    /// Peels onion layers when relaying and opens messages sealed to us.
+    pub(super) onion_key: OnionKey,
+    /// Addresses Kademlia knows for each peer, dialed in transport
+    /// preference order.
+    pub(super) peer_addrs: HashMap<PeerId, Vec<Multiaddr>>,
+    /// Onion keys of peers that relay.
+    pub(super) relay_keys: HashMap<PeerId, [u8; 32]>,
+    /// Receives messages delivered to us through onion routes.
+    pub(super) onion_deliveries: Option<mpsc::UnboundedSender<Sealed>>,
+    /// Job channel key of the account this node runs as, handed to peers.
+    pub(super) channel_cert: Option<ChannelKeyCert>,
+    /// Receives job channel messages addressed to that account.
+    pub(super) job_channel_deliveries: Option<mpsc::UnboundedSender<ChannelMessage>>,
 }
 
 impl P2PService {
+    /// Forward heartbeats gossiped by workers to `sink`.
+    pub fn with_heartbeat_sink(mut self, sink: mpsc::UnboundedSender<Heartbeat>) -> Self {
+        self.heartbeats = Some(sink);
+        self
+    }
+
+    /// Forward blocks gossiped by peers to `sink`.
+    pub fn with_block_sink(mut self, sink: mpsc::UnboundedSender<Block>) -> Self {
+        self.blocks = Some(sink);
+        self
+    }
+
+    /// Answer peers' `GetBlocks` requests from `chain`.
+    pub fn with_block_source(mut self, chain: Arc<Mutex<Blockchain>>) -> Self {
+        self.chain = Some(chain);
+        self
+    }
+
+    /// Use `key` for onion routing instead of a generated one, so its holder
+    /// can open what is delivered to `sink`.
+    pub fn with_onion(mut self, key: OnionKey, sink: mpsc::UnboundedSender<Sealed>) -> Self {
+        self.onion_key = key;
+        self.onion_deliveries = Some(sink);
+        self
+    }
+
+    /// Hand out `cert` to peers opening job channels and forward messages
+    /// sent over them to `sink`, unopened; the holder of the matching
+    /// `ChannelKey` opens them.
+    pub fn with_job_channels(mut self, cert: ChannelKeyCert, sink: mpsc::UnboundedSender<ChannelMessage>) -> Self {
+        self.channel_cert = Some(cert);
+        self.job_channel_deliveries = Some(sink);
+        self
+    }
+
     /// The main event loop of the P2P service.
     pub async fn run(mut self) {
+        let every = Duration::from_secs(self.config.connections.maintenance_interval_secs.max(1));
+        let mut maintenance = tokio::time::interval(every);
+        let check_every = self.config.addresses.check_interval_secs;
+        let mut address_check = tokio::time::interval(Duration::from_secs(check_every.max(1)));
         loop {
             tokio::select! {
                 event = self.swarm.select_next_some() => {
@@ -47,7 +144,98 @@ impl P2PService {
                 Some(command) = self.command_receiver.recv() => {
                     self.handle_command(command).await;
                 }
+                _ = maintenance.tick() => {
+                    self.maintain_connections();
+                }
+                _ = address_check.tick(), if check_every > 0 => {
+                    self.check_addresses();
+                }
+                Some(held) = self.released.recv() => {
+                    self.release(held);
+                }
+                Some((addr, reachable)) = self.address_results.recv() => {
+                    self.addresses.record_check(&addr, reachable);
+                    if !reachable {
+                        tracing::debug!(%addr, "advertised address failed its reachability check");
+                    }
+                    self.refresh_external_addresses();
+                }
+            }
+        }
+    }
+
+    /// Seconds since the service started, the connection manager's clock.
+    pub(super) fn uptime_secs(&self) -> u64 {
+        self.start_time.map_or(0, |t| t.elapsed().as_secs())
+    }
+
+    /// Probe every candidate address off the event loop; results come back
+    /// through `address_results`.
+    fn check_addresses(&self) {
+        let timeout = Duration::from_millis(self.config.addresses.check_timeout_ms);
+        // Only TCP addresses can be probed; QUIC ones are left as they are.
+        for addr in self.addresses.candidates().filter(|a| net_address::dial_target(a).is_some()).cloned() {
+            let results = self.address_checks.clone();
+            tokio::spawn(async move {
+                let reachable = net_address::probe(&addr, timeout).await;
+                let _ = results.send((addr, reachable));
+            });
+        }
+    }
+
+    /// Make the swarm's external addresses match the healthy, preferred
+    /// candidates.
+    pub(super) fn refresh_external_addresses(&mut self) {
+        let wanted = self.addresses.advertised();
+        let current: Vec<Multiaddr> = self.swarm.external_addresses().cloned().collect();
+        for addr in current.iter().filter(|a| !wanted.contains(a)) {
+            self.swarm.remove_external_address(addr);
+        }
+        for addr in wanted.into_iter().filter(|a| !current.contains(a)) {
+            self.swarm.add_external_address(addr);
+        }
+    }
+
+    /// Prune excess connections and dial peers of under-served roles.
+    fn maintain_connections(&mut self) {
+        let plan = self.connections.plan(self.uptime_secs());
+        for peer in plan.prune {
+            tracing::debug!(%peer, "pruning low-value connection");
+            let _ = self.swarm.disconnect_peer_id(peer);
+        }
+        for peer in plan.dial {
+            let addrs = self.peer_addrs.get(&peer).cloned().unwrap_or_default();
+            let addrs = net_address::order_for_dial(addrs, self.config.transport);
+            // One address at a time, so QUIC is tried before TCP.
+            let opts = DialOpts::peer_id(peer)
+                .addresses(addrs)
+                .extend_addresses_through_behaviour()
+                .override_dial_concurrency_factor(NonZeroU8::MIN)
+                .build();
+            if let Err(e) = self.swarm.dial(opts) {
+                tracing::debug!(%peer, error = %e, "dial failed");
+            }
+        }
+    }
+
+    /// Peel our layer off `packet` and pass it on, if we serve as a relay.
+    pub(super) fn relay_onion(&mut self, packet: OnionPacket) {
+        if !self.config.roles.contains(&PeerRole::Relay) {
+            tracing::debug!("dropping onion packet, not a relay");
+            return;
+        }
+        let (peer, message) = match self.onion_key.peel(packet) {
+            Ok(next) => next.into_request(),
+            Err(e) => {
+                tracing::debug!(error = %e, "dropping onion packet");
+                return;
+            }
+        };
+        match peer.parse::<PeerId>() {
+            Ok(peer) => {
+                self.swarm.behaviour_mut().request_response.send_request(&peer, message);
             }
+            Err(_) => tracing::debug!(%peer, "dropping onion packet for an invalid peer id"),
         }
     }
 
@@ -1,8 +1,13 @@
 use super::{
+    codec::{encode_message, WireMessage},
     command::Command,
     error::P2PError,
-    service::P2PService,
+    service::{Held, P2PService, PayloadWaiter},
 };
+use crate::large_data_transfer::network::{Direction, FetchStart, PayloadDescriptor};
+use crate::onion::RelayInfo;
+use libp2p::{request_response::ResponseChannel, PeerId};
+use std::time::{Duration, Instant};
 
 impl P2PService {
     pub(super) async fn handle_command(&mut self, command: Command) {
@@ -17,6 +22,21 @@ impl P2PService {
                     .map_err(|e| P2PError::SerializationFailed(e.to_string()));
                 let _ = response.send(result);
             }
+            Command::PublishPayload { topic, body, response } => {
+                let descriptor = self.payloads.publish(body);
+                let announcement = encode_message(&WireMessage::Announce(descriptor.clone()));
+                let result = self
+                    .swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .publish(topic, announcement)
+                    .map_err(|e| P2PError::SerializationFailed(e.to_string()))
+                    .map(|_| descriptor);
+                let _ = response.send(result);
+            }
+            Command::FetchPayload { peer_id, descriptor, response } => {
+                self.fetch_payload(peer_id, descriptor, Some(response));
+            }
             Command::GetPeers { response } => {
                 let peers = self
                     .swarm
@@ -37,6 +57,18 @@ impl P2PService {
                     .map_err(|e| P2PError::Network(format!("Bootstrap failed: {:?}", e)));
                 let _ = response.send(result);
             }
+            Command::GetRelays { response } => {
+                let relays = self
+                    .relay_keys
+                    .iter()
+                    .filter(|(peer, _)| self.connections.is_connected(peer))
+                    .map(|(peer, key)| RelayInfo { peer: peer.to_string(), key: *key })
+                    .collect();
+                let _ = response.send(relays);
+            }
+            Command::GetBandwidth { response } => {
+                let _ = response.send(self.bandwidth.counters());
+            }
             Command::Request { peer_id, message, response } => {
                 let request_id = self
                     .swarm
@@ -47,4 +79,98 @@ impl P2PService {
             }
         }
     }
-} 
\ No newline at end of file
+
+    /// Starts or joins the fetch of an announced payload body.
+    pub(super) fn fetch_payload(
+        &mut self,
+        peer_id: PeerId,
+        descriptor: PayloadDescriptor,
+        waiter: Option<PayloadWaiter>,
+    ) {
+        match self.payloads.fetch(peer_id, descriptor, waiter) {
+            FetchStart::Cached(body, waiter) => {
+                if let Some(waiter) = waiter {
+                    let _ = waiter.send(Ok(body));
+                }
+            }
+            FetchStart::Joined | FetchStart::Queued => {}
+            FetchStart::Send { peer, hash } => self.request_payload(peer, hash),
+        }
+    }
+
+    /// Completes the fetch of `hash`, answers its waiters and starts the
+    /// next queued fetch.
+    pub(super) fn finish_payload(&mut self, hash: &str, body: Option<Vec<u8>>) {
+        if let Some(transfer) = self.payload_transfers.remove(hash) {
+            self.bandwidth.finish(transfer);
+        }
+        let finished = self.payloads.finish(hash, body);
+        for waiter in finished.waiters {
+            let result = finished
+                .body
+                .clone()
+                .ok_or_else(|| P2PError::PayloadUnavailable(hash.to_string()));
+            let _ = waiter.send(result);
+        }
+        if let Some((peer, hash)) = finished.next {
+            self.request_payload(peer, hash);
+        }
+    }
+
+    /// Requests a body once the download cap has room for it.
+    fn request_payload(&mut self, peer: PeerId, hash: String) {
+        let size = self.payloads.descriptor(&hash).map_or(0, |d| d.size);
+        let transfer = self.bandwidth.start(peer, Direction::Download);
+        self.payload_transfers.insert(hash.clone(), transfer);
+        let wait = self.bandwidth.reserve(transfer, size, Instant::now());
+        self.hold(wait, Held::Request { peer, hash });
+    }
+
+    /// Answers a request; bodies are sent once the upload cap has room.
+    pub(super) fn respond(&mut self, peer: PeerId, channel: ResponseChannel<WireMessage>, response: WireMessage) {
+        let size = match &response {
+            WireMessage::Payload { body: Some(body), .. } => body.len() as u64,
+            _ => 0,
+        };
+        if size == 0 {
+            let _ = self.swarm.behaviour_mut().request_response.send_response(channel, response);
+            return;
+        }
+        let transfer = self.bandwidth.start(peer, Direction::Upload);
+        let wait = self.bandwidth.reserve(transfer, size, Instant::now());
+        self.hold(wait, Held::Response { peer, channel, response, transfer });
+    }
+
+    /// Releases `held` after `wait`, back through the event loop.
+    fn hold(&mut self, wait: Duration, held: Held) {
+        if wait.is_zero() {
+            self.release(held);
+        } else {
+            let released = self.held.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(wait).await;
+                let _ = released.send(held);
+            });
+        }
+    }
+
+    pub(super) fn release(&mut self, held: Held) {
+        match held {
+            Held::Response { peer, channel, response, transfer } => {
+                self.bandwidth.finish(transfer);
+                if let WireMessage::Payload { body: Some(body), .. } = &response {
+                    self.bandwidth.record(peer, Direction::Upload, body.len() as u64);
+                }
+                let _ = self.swarm.behaviour_mut().request_response.send_response(channel, response);
+            }
+            Held::Request { peer, hash } => {
+                let request_id = self
+                    .swarm
+                    .behaviour_mut()
+                    .request_response
+                    .send_request(&peer, WireMessage::GetPayload { hash: hash.clone() });
+                self.payload_requests.insert(request_id, hash);
+            }
+        }
+    }
+}
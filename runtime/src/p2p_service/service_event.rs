@@ -1,28 +1,71 @@
 use libp2p::{gossipsub, kad, request_response, swarm::SwarmEvent, PeerId};
 use super::{
     behaviour::{BCAIBehaviourEvent, BCAINetworkBehaviour},
+    codec::{decode_message, WireMessage, MAX_SYNC_BLOCKS},
     error::P2PError,
     service::P2PService,
 };
+use crate::connection_manager::PeerRole;
+use crate::large_data_transfer::network::Direction;
+use crate::net_address::AddrSource;
+use crate::gossip::{self, GossipTopic};
+use crate::network::NetworkMessage;
 
 /// Extended implementation for `P2PService` that handles libp2p swarm events.
 impl P2PService {
     pub(super) async fn handle_swarm_event(&mut self, event: SwarmEvent<BCAIBehaviourEvent>) {
         match event {
             SwarmEvent::Behaviour(BCAIBehaviourEvent::Gossipsub(gossipsub::Event::Message {
-                propagation_source: _,
-                message_id: _,
+                propagation_source,
+                message_id,
                 message,
             })) => {
-                println!(
-                    "Received gossipsub message: {:?}",
-                    String::from_utf8_lossy(&message.data)
+                let verdict = validate_gossip(&message);
+                let acceptance = match &verdict {
+                    Ok(()) => gossipsub::MessageAcceptance::Accept,
+                    Err(None) => gossipsub::MessageAcceptance::Ignore,
+                    Err(Some(reason)) => {
+                        tracing::debug!(peer = %propagation_source, %reason, "rejecting gossip");
+                        gossipsub::MessageAcceptance::Reject
+                    }
+                };
+                self.swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                    &message_id,
+                    &propagation_source,
+                    acceptance,
                 );
+                if verdict.is_err() {
+                    self.connections.adjust_score(&propagation_source, -5);
+                    return;
+                }
+                self.connections.adjust_score(&propagation_source, 1);
+                if let Ok(WireMessage::Announce(descriptor)) = decode_message(&message.data) {
+                    // Forwarders relay the announcement before holding the
+                    // body, so fetch from the original publisher.
+                    let peer = message.source.unwrap_or(propagation_source);
+                    self.fetch_payload(peer, descriptor, None);
+                } else if let (Some(sink), Ok(WireMessage::Block(block))) =
+                    (&self.blocks, decode_message(&message.data))
+                {
+                    let _ = sink.send(block);
+                } else if let Ok(NetworkMessage::Heartbeat { heartbeat }) =
+                    bincode::deserialize(&message.data)
+                {
+                    if let Some(sink) = &self.heartbeats {
+                        let _ = sink.send(heartbeat);
+                    }
+                } else {
+                    println!(
+                        "Received gossipsub message: {:?}",
+                        String::from_utf8_lossy(&message.data)
+                    );
+                }
             }
             SwarmEvent::Behaviour(BCAIBehaviourEvent::Kademlia(event)) => {
                 if let kad::Event::OutboundQueryCompleted { result, .. } = event {
                     if let kad::QueryResult::GetClosestPeers(Ok(res)) = result {
                         for peer in res.peers {
+                            self.connections.discovered(peer);
                             let id = peer.to_string();
                             self.peers
                                 .entry(id.clone())
@@ -36,6 +79,9 @@ impl P2PService {
                         }
                         self.stats.peer_count = self.peers.len();
                     }
+                } else if let kad::Event::RoutingUpdated { peer, addresses, .. } = event {
+                    self.connections.discovered(peer);
+                    self.peer_addrs.insert(peer, addresses.iter().cloned().collect());
                 } else {
                     tracing::debug!(?event, "Kademlia event");
                 }
@@ -46,29 +92,130 @@ impl P2PService {
                 match message {
                     request_response::Message::Request { request, channel, .. } => {
                         let response = match request {
-                            super::codec::WireMessage::Ping => super::codec::WireMessage::Pong,
-                            _ => super::codec::WireMessage::Pong,
+                            WireMessage::Ping => WireMessage::Pong,
+                            WireMessage::GetRoles => WireMessage::Roles(self.config.roles.clone()),
+                            WireMessage::GetOnionKey => WireMessage::OnionKey(
+                                self.config.roles.contains(&PeerRole::Relay).then(|| self.onion_key.public()),
+                            ),
+                            WireMessage::Onion(packet) => {
+                                self.relay_onion(packet);
+                                WireMessage::Pong
+                            }
+                            WireMessage::OnionDelivery(payload) => {
+                                if let Some(sink) = &self.onion_deliveries {
+                                    let _ = sink.send(payload);
+                                }
+                                WireMessage::Pong
+                            }
+                            WireMessage::GetChannelKey => WireMessage::ChannelKey(self.channel_cert.clone()),
+                            WireMessage::JobChannel(message) => {
+                                let ours = self.channel_cert.as_ref().is_some_and(|c| c.identity == message.to);
+                                match &self.job_channel_deliveries {
+                                    Some(sink) if ours => {
+                                        let _ = sink.send(message);
+                                    }
+                                    _ => tracing::debug!(%peer, job_id = message.job_id, "dropping job channel message"),
+                                }
+                                WireMessage::Pong
+                            }
+                            WireMessage::GetPayload { hash } => {
+                                let body = self.payloads.body(&hash).cloned();
+                                WireMessage::Payload { hash, body }
+                            }
+                            WireMessage::GetBlocks { from_height } => {
+                                let blocks = match &self.chain {
+                                    Some(chain) => {
+                                        let chain = chain.lock().await;
+                                        let from = usize::try_from(from_height).unwrap_or(usize::MAX);
+                                        chain.blocks.iter().skip(from).take(MAX_SYNC_BLOCKS).cloned().collect()
+                                    }
+                                    None => Vec::new(),
+                                };
+                                WireMessage::Blocks(blocks)
+                            }
+                            _ => WireMessage::Pong,
                         };
-                        let _ = self
-                            .swarm
-                            .behaviour_mut()
-                            .request_response
-                            .send_response(channel, response);
+                        self.respond(peer, channel, response);
                     }
                     request_response::Message::Response { request_id, response } => {
-                        if let Some(tx) = self.request_map.remove(&request_id) {
+                        match &response {
+                            WireMessage::Roles(roles) => {
+                                self.connections.set_roles(peer, roles.iter().copied());
+                                if roles.contains(&PeerRole::Relay) {
+                                    self.swarm
+                                        .behaviour_mut()
+                                        .request_response
+                                        .send_request(&peer, WireMessage::GetOnionKey);
+                                }
+                            }
+                            WireMessage::OnionKey(Some(key)) => {
+                                self.relay_keys.insert(peer, *key);
+                            }
+                            _ => {}
+                        }
+                        if let Some(hash) = self.payload_requests.remove(&request_id) {
+                            let body = match response {
+                                WireMessage::Payload { body, .. } => body,
+                                _ => None,
+                            };
+                            let size = body.as_ref().map_or(0, |b| b.len() as u64);
+                            self.bandwidth.record(peer, Direction::Download, size);
+                            self.finish_payload(&hash, body);
+                        } else if let Some(tx) = self.request_map.remove(&request_id) {
                             let _ = tx.send(Ok(response));
                         }
                     }
                 }
             }
+            SwarmEvent::Behaviour(BCAIBehaviourEvent::RequestResponse(
+                request_response::Event::OutboundFailure { peer, request_id, error, .. },
+            )) => {
+                self.connections.adjust_score(&peer, -5);
+                if let Some(hash) = self.payload_requests.remove(&request_id) {
+                    tracing::debug!(%hash, ?error, "Payload fetch failed");
+                    self.finish_payload(&hash, None);
+                }
+            }
             SwarmEvent::NewListenAddr { address, .. } => {
                 println!("Listening on {}", address);
+                if self.addresses.add(address, AddrSource::Listen) {
+                    self.refresh_external_addresses();
+                }
+            }
+            SwarmEvent::ExpiredListenAddr { address, .. } => {
+                self.addresses.remove(&address);
+                self.refresh_external_addresses();
             }
             SwarmEvent::ConnectionEstablished { peer_id, .. } => {
                 println!("Connected to {}", peer_id);
+                if !self.connections.is_connected(&peer_id) {
+                    let now = self.uptime_secs();
+                    self.connections.connected(peer_id, now);
+                    self.swarm.behaviour_mut().request_response.send_request(&peer_id, WireMessage::GetRoles);
+                }
+            }
+            SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                self.connections.disconnected(&peer_id);
             }
             _ => {}
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Check a gossiped message against its topic's validator. `Err(None)` for
+/// topics this node does not validate, which are neither passed on nor held
+/// against the sender.
+fn validate_gossip(message: &gossipsub::Message) -> Result<(), Option<String>> {
+    let topic = message.topic.as_str();
+    if topic == crate::scheduler::HEARTBEAT_TOPIC {
+        return match bincode::deserialize(&message.data) {
+            Ok(NetworkMessage::Heartbeat { heartbeat }) if heartbeat.verify() => Ok(()),
+            Ok(NetworkMessage::Heartbeat { heartbeat }) => {
+                Err(Some(format!("unsigned heartbeat from {}", heartbeat.node_id)))
+            }
+            _ => Err(Some("not a heartbeat".into())),
+        };
+    }
+    let topic = GossipTopic::from_name(topic).ok_or(None)?;
+    gossip::validate(topic, &message.data).map_err(|e| Some(e.to_string()))
+}
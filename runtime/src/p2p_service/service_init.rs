@@ -6,7 +6,10 @@ use super::{
     error::P2PError,
     types::{PeerInfo, P2PStats},
 };
+use crate::gossip::GossipTopic;
+use futures::future::Either;
 use futures::StreamExt;
+use libp2p::core::muxing::StreamMuxerBox;
 use libp2p::{
     gossipsub, identity, kad,
     request_response::{self, ProtocolSupport},
@@ -17,6 +20,13 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 use super::service::P2PService;
+use crate::connection_manager::ConnectionManager;
+use crate::net_address::{
+    default_listen_addrs, default_quic_listen_addrs, parse_addrs, AddrSource, AdvertisedAddrs,
+    TransportPreference,
+};
+use crate::onion::OnionKey;
+use crate::large_data_transfer::network::{BandwidthLimiter, PayloadFetcher};
 
 impl P2PService {
     /// Create a new P2P service, which includes the service itself and a handle for interaction.
@@ -25,17 +35,38 @@ impl P2PService {
         let local_peer_id = PeerId::from(local_key.public());
         println!("🤖 Local Peer ID: {}", local_peer_id);
 
-        let transport = libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::default())
+        let tcp = libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::default())
             .upgrade(libp2p::core::upgrade::Version::V1)
             .authenticate(
                 libp2p::noise::Config::new(&local_key)
                     .map_err(|e| P2PError::TransportError(e.to_string()))?,
             )
             .multiplex(libp2p::yamux::Config::default())
+            .map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)));
+        let quic = libp2p::quic::tokio::Transport::new(libp2p::quic::Config::new(&local_key))
+            .map(|(peer, connection), _| (peer, StreamMuxerBox::new(connection)));
+        // Each address is dialed over the transport it names.
+        let base = match config.transport {
+            TransportPreference::TcpOnly => tcp.boxed(),
+            TransportPreference::QuicOnly => quic.boxed(),
+            TransportPreference::QuicFirst | TransportPreference::TcpFirst => quic
+                .or_transport(tcp)
+                .map(|either, _| match either {
+                    Either::Left(output) | Either::Right(output) => output,
+                })
+                .boxed(),
+        };
+        // DNS resolution lets `/dns4`, `/dns6` and `/dns` addresses be dialed.
+        let transport = libp2p::dns::tokio::Transport::system(base)
+            .map_err(|e| P2PError::TransportError(e.to_string()))?
             .boxed();
 
+        // Messages are held until their topic's validator has passed them.
+        let max_transmit_size = GossipTopic::ALL.iter().map(|t| t.max_size()).max().unwrap_or_default();
         let gossipsub_config = gossipsub::ConfigBuilder::default()
             .heartbeat_interval(Duration::from_secs(10))
+            .validate_messages()
+            .max_transmit_size(max_transmit_size)
             .build()
             .map_err(|s| P2PError::ConnectionFailed(s.to_string()))?;
 
@@ -60,20 +91,52 @@ impl P2PService {
         };
 
         let mut swarm = Swarm::with_tokio_executor(transport, behaviour, local_peer_id);
-        let listen_addr = format!("/ip4/0.0.0.0/tcp/{}", config.listen_port);
-        swarm.listen_on(listen_addr.parse().unwrap()).unwrap();
+        let listen = if config.addresses.listen.is_empty() {
+            let mut listen = Vec::new();
+            if config.transport.uses_quic() {
+                listen.extend(default_quic_listen_addrs(config.listen_port));
+            }
+            if config.transport.uses_tcp() {
+                listen.extend(default_listen_addrs(config.listen_port));
+            }
+            listen
+        } else {
+            parse_addrs(&config.addresses.listen).map_err(|e| P2PError::TransportError(e.to_string()))?
+        };
+        // Hosts without IPv6 (or IPv4) still start on the other family.
+        let mut listening = 0;
+        for addr in listen {
+            match swarm.listen_on(addr.clone()) {
+                Ok(_) => listening += 1,
+                Err(e) => tracing::warn!(%addr, error = %e, "cannot listen on address"),
+            }
+        }
+        if listening == 0 {
+            return Err(P2PError::TransportError("no usable listen address".into()));
+        }
+
+        let mut addresses = AdvertisedAddrs::from_config(&config.addresses);
+        let external: Vec<String> =
+            config.external_address.iter().chain(&config.addresses.external).cloned().collect();
+        for addr in parse_addrs(&external).map_err(|e| P2PError::TransportError(e.to_string()))? {
+            addresses.add(addr, AddrSource::External);
+        }
+        let (address_checks, address_results) = mpsc::unbounded_channel();
+        let (held, released) = mpsc::unbounded_channel();
 
         let (command_sender, command_receiver) = mpsc::channel(32);
         let handle = P2PHandle::new(command_sender);
 
-        // Subscribe to the global topic
+        for topic in GossipTopic::ALL {
+            swarm.behaviour_mut().gossipsub.subscribe(&gossipsub::IdentTopic::new(topic.name())).unwrap();
+        }
         swarm
             .behaviour_mut()
             .gossipsub
-            .subscribe(&gossipsub::IdentTopic::new(super::service::GLOBAL_TOPIC))
+            .subscribe(&gossipsub::IdentTopic::new(crate::scheduler::HEARTBEAT_TOPIC))
             .unwrap();
 
-        let service = Self {
+        let mut service = Self {
             swarm,
             command_receiver,
             peers: HashMap::new(),
@@ -88,10 +151,30 @@ impl P2PService {
                 network_stats: Default::default(),
             },
             start_time: Some(Instant::now()),
+            payloads: PayloadFetcher::new(config.max_concurrent_fetches),
+            connections: ConnectionManager::new(config.connections.clone()),
+            addresses,
+            address_checks,
+            address_results,
+            payload_requests: HashMap::new(),
+            bandwidth: BandwidthLimiter::new(config.bandwidth.clone()),
+            payload_transfers: HashMap::new(),
+            held,
+            released,
             config,
             request_map: HashMap::new(),
+            heartbeats: None,
+            blocks: None,
+            chain: None,
+            onion_key: OnionKey::generate(),
+            relay_keys: HashMap::new(),
+            peer_addrs: HashMap::new(),
+            onion_deliveries: None,
+            channel_cert: None,
+            job_channel_deliveries: None,
         };
 
+        service.refresh_external_addresses();
         Ok((service, handle))
     }
 } 
\ No newline at end of file
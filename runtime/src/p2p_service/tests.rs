@@ -19,7 +19,24 @@ async fn p2p_service_creation() {
 #[test]
 fn p2p_message_serialization() {
     let msg = WireMessage::Ping;
-    let serialized = serde_json::to_string(&msg).unwrap();
-    let deserialized: WireMessage = serde_json::from_str(&serialized).unwrap();
-    assert!(matches!(deserialized, WireMessage::Ping));
-} 
\ No newline at end of file
+    let serialized = codec::encode_message(&msg);
+    assert!(matches!(codec::decode_message(&serialized).unwrap(), WireMessage::Ping));
+    // Older nodes still send JSON.
+    let legacy = serde_json::to_vec(&msg).unwrap();
+    assert!(matches!(codec::decode_message(&legacy).unwrap(), WireMessage::Ping));
+}
+
+#[test]
+fn block_ranges_round_trip() {
+    let genesis = crate::blockchain::Blockchain::new(Default::default()).get_tip().clone();
+    let request = codec::encode_message(&WireMessage::GetBlocks { from_height: 7 });
+    assert!(matches!(
+        codec::decode_message(&request).unwrap(),
+        WireMessage::GetBlocks { from_height: 7 }
+    ));
+    let response = codec::encode_message(&WireMessage::Blocks(vec![genesis.clone()]));
+    match codec::decode_message(&response).unwrap() {
+        WireMessage::Blocks(blocks) => assert_eq!(blocks, vec![genesis]),
+        other => panic!("decoded {:?}", other),
+    }
+}
@@ -0,0 +1,144 @@
+//! wgpu compute backend. Runs `gradients.wgsl`, one invocation per sample.
+
+use super::{BackendError, GradientBackend};
+use crate::pouw::training::{Dataset, Weights, FEATURES};
+use std::sync::OnceLock;
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// The shader hard-codes two features per sample.
+const _: () = assert!(FEATURES == 2);
+
+pub struct WgpuBackend {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl WgpuBackend {
+    /// Opens the default adapter, or `None` if the machine has no GPU.
+    pub fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = futures::executor::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))?;
+        let (device, queue) = futures::executor::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("pouw-solver"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+            },
+            None,
+        ))
+        .ok()?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("pouw-gradients"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("pouw-gradients"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+        tracing::info!(adapter = %adapter.get_info().name, "PoUW solver using GPU backend");
+        Some(Self { device, queue, pipeline })
+    }
+
+    /// Process-wide backend, opened on first use.
+    pub fn shared() -> Option<&'static Self> {
+        static GPU: OnceLock<Option<WgpuBackend>> = OnceLock::new();
+        GPU.get_or_init(Self::new).as_ref()
+    }
+
+    fn storage(&self, label: &str, values: impl IntoIterator<Item = i64>) -> wgpu::Buffer {
+        let bytes: Vec<u8> = values.into_iter().flat_map(i64::to_le_bytes).collect();
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: &bytes,
+            usage: wgpu::BufferUsages::STORAGE,
+        })
+    }
+}
+
+impl GradientBackend for WgpuBackend {
+    fn name(&self) -> &'static str {
+        "wgpu"
+    }
+
+    fn sample_gradients(&self, data: &Dataset, weights: &Weights) -> Result<Vec<Weights>, BackendError> {
+        let samples = data.labels.len();
+        if samples == 0 {
+            return Ok(Vec::new());
+        }
+        let size = (samples * FEATURES * 8) as u64;
+        let weights = self.storage("weights", weights.iter().copied());
+        let features = self.storage("features", data.features.iter().flatten().copied());
+        let labels = self.storage("labels", data.labels.iter().copied());
+        let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gradients"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gradients-readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: weights.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: features.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: labels.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: output.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((samples as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| BackendError::Device(e.to_string()))?
+            .map_err(|e| BackendError::Device(e.to_string()))?;
+
+        let gradients: Vec<Weights> = slice
+            .get_mapped_range()
+            .chunks_exact(FEATURES * 8)
+            .map(|sample| {
+                let mut gradient = [0i64; FEATURES];
+                for (g, bytes) in gradient.iter_mut().zip(sample.chunks_exact(8)) {
+                    *g = i64::from_le_bytes(bytes.try_into().expect("8-byte chunk"));
+                }
+                gradient
+            })
+            .collect();
+        readback.unmap();
+        if gradients.len() != samples {
+            return Err(BackendError::Incomplete { expected: samples, got: gradients.len() });
+        }
+        Ok(gradients)
+    }
+}
+
+/// WGSL source of the gradient kernel.
+pub const SHADER: &str = include_str!("gradients.wgsl");
@@ -0,0 +1,93 @@
+// Per-sample loss gradients for PoUW training, bit-identical to
+// `training::sample_gradient`. WGSL has no 64-bit integers, so every i64 is
+// carried as a two's-complement (lo, hi) pair of u32.
+
+@group(0) @binding(0) var<storage, read> weights: array<vec2<u32>, 2>;
+@group(0) @binding(1) var<storage, read> features: array<vec2<u32>>;
+@group(0) @binding(2) var<storage, read> labels: array<vec2<u32>>;
+@group(0) @binding(3) var<storage, read_write> gradients: array<vec2<u32>>;
+
+const ONE: vec2<u32> = vec2<u32>(65536u, 0u);
+const HALF: vec2<u32> = vec2<u32>(32768u, 0u);
+const ZERO: vec2<u32> = vec2<u32>(0u, 0u);
+
+fn add(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let lo = a.x + b.x;
+    return vec2<u32>(lo, a.y + b.y + select(0u, 1u, lo < a.x));
+}
+
+fn neg(a: vec2<u32>) -> vec2<u32> {
+    return add(~a, vec2<u32>(1u, 0u));
+}
+
+fn is_neg(a: vec2<u32>) -> bool {
+    return (a.y & 0x80000000u) != 0u;
+}
+
+fn less(a: vec2<u32>, b: vec2<u32>) -> bool {
+    let ha = bitcast<i32>(a.y);
+    let hb = bitcast<i32>(b.y);
+    if ha != hb {
+        return ha < hb;
+    }
+    return a.x < b.x;
+}
+
+// Full 64-bit product of two u32, from 16-bit limbs.
+fn mul_wide(a: u32, b: u32) -> vec2<u32> {
+    let a0 = a & 0xffffu;
+    let a1 = a >> 16u;
+    let b0 = b & 0xffffu;
+    let b1 = b >> 16u;
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let mid = (p00 >> 16u) + (p01 & 0xffffu) + (p10 & 0xffffu);
+    let lo = (p00 & 0xffffu) | (mid << 16u);
+    let hi = a1 * b1 + (p01 >> 16u) + (p10 >> 16u) + (mid >> 16u);
+    return vec2<u32>(lo, hi);
+}
+
+// Wrapping i64 multiplication; the low 64 bits do not depend on sign.
+fn mul(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let p = mul_wide(a.x, b.x);
+    return vec2<u32>(p.x, p.y + a.x * b.y + a.y * b.x);
+}
+
+// Logical shift right by 0 < k < 32.
+fn shr(a: vec2<u32>, k: u32) -> vec2<u32> {
+    return vec2<u32>((a.x >> k) | (a.y << (32u - k)), a.y >> k);
+}
+
+// Division by 2^k rounding toward zero, like Rust's `/` on i64.
+fn div_pow2(a: vec2<u32>, k: u32) -> vec2<u32> {
+    if is_neg(a) {
+        return neg(shr(neg(a), k));
+    }
+    return shr(a, k);
+}
+
+fn sigmoid(z: vec2<u32>) -> vec2<u32> {
+    let s = add(HALF, div_pow2(z, 2u));
+    if is_neg(s) {
+        return ZERO;
+    }
+    if less(ONE, s) {
+        return ONE;
+    }
+    return s;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let sample = id.x;
+    if sample >= arrayLength(&labels) {
+        return;
+    }
+    let x0 = features[2u * sample];
+    let x1 = features[2u * sample + 1u];
+    let z = add(div_pow2(mul(weights[0], x0), 16u), div_pow2(mul(weights[1], x1), 16u));
+    let error = add(sigmoid(z), neg(labels[sample]));
+    gradients[2u * sample] = div_pow2(mul(error, x0), 16u);
+    gradients[2u * sample + 1u] = div_pow2(mul(error, x1), 16u);
+}
@@ -0,0 +1,64 @@
+//! Compute backends for the matrix-heavy part of PoUW training.
+//!
+//! Each epoch evaluates the model on every sample and takes the loss
+//! gradient, one dot product and one outer product per sample. A backend
+//! computes those per-sample gradients; the solver sums them and applies the
+//! step on the CPU. Every backend must reproduce
+//! [`training::sample_gradient`] bit for bit, since verifiers re-train on the
+//! CPU and compare weight hashes.
+//!
+//! With the `wgpu-solver` feature, [`preferred`] picks a GPU backend when an
+//! adapter is available and falls back to [`CpuBackend`] otherwise.
+
+#[cfg(feature = "wgpu-solver")]
+mod gpu;
+
+#[cfg(feature = "wgpu-solver")]
+pub use gpu::{WgpuBackend, SHADER};
+
+use super::training::{self, Dataset, Weights};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BackendError {
+    #[error("GPU device error: {0}")]
+    Device(String),
+    #[error("backend returned {got} gradients for {expected} samples")]
+    Incomplete { expected: usize, got: usize },
+}
+
+pub trait GradientBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Loss gradient of every sample in `data` at `weights`, in sample order.
+    fn sample_gradients(&self, data: &Dataset, weights: &Weights) -> Result<Vec<Weights>, BackendError>;
+}
+
+/// Reference backend; verifiers always train with this one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CpuBackend;
+
+impl GradientBackend for CpuBackend {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn sample_gradients(&self, data: &Dataset, weights: &Weights) -> Result<Vec<Weights>, BackendError> {
+        Ok(data
+            .features
+            .iter()
+            .zip(&data.labels)
+            .map(|(x, label)| training::sample_gradient(weights, x, *label))
+            .collect())
+    }
+}
+
+/// Backend the solver uses: the GPU when built with `wgpu-solver` and an
+/// adapter is present, otherwise the CPU.
+pub fn preferred() -> &'static dyn GradientBackend {
+    #[cfg(feature = "wgpu-solver")]
+    if let Some(gpu) = WgpuBackend::shared() {
+        return gpu;
+    }
+    &CpuBackend
+}
@@ -0,0 +1,292 @@
+//! Commit-reveal evaluation rounds.
+//!
+//! Evaluations recorded in one step let a lazy validator wait for the first
+//! honest score and copy it. An [`EvaluationRound`] splits evaluation into
+//! two phases. During the commit phase each validator publishes only a
+//! [`SignedCommitment`]: a hash over the task, its own key, its accuracy and
+//! a secret salt. Once the commit phase has closed, validators reveal the
+//! signed evaluation and salt behind their commitment. Every score is fixed
+//! before any is visible, and a commitment is bound to the key that made it,
+//! so it cannot be replayed by another validator either.
+//!
+//! Settling a round penalizes every revealed accuracy further from the
+//! median than the tolerance, in proportion to the distance, and every
+//! validator that committed but never revealed. If enough validators
+//! revealed, their evaluations are aggregated into the task's consensus.
+
+use super::evaluation::{sign_evaluation, verify_evaluation};
+use super::outlier::{aggregate, median, AggregationConfig, ConsensusEvaluation};
+use super::types::SignedEvaluation;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Domain tag of commitment signatures, so they cannot pass as evaluations.
+const COMMIT_DOMAIN: &[u8] = b"bcai-evaluation-commit";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitRevealConfig {
+    /// Blocks the commit phase stays open after the first commitment.
+    pub commit_blocks: u32,
+    /// Blocks the reveal phase stays open.
+    pub reveal_blocks: u32,
+    /// Reveals needed for the round to record a consensus.
+    pub min_reveals: usize,
+    /// Deviations from the median up to this many basis points are free.
+    pub tolerance: u32,
+    /// Stake slashed per percentage point of deviation beyond the tolerance.
+    pub penalty_per_percent: u64,
+    /// Most stake one deviating reveal can cost.
+    pub max_penalty: u64,
+    /// Stake slashed from a validator that committed but did not reveal.
+    pub no_reveal_penalty: u64,
+}
+
+impl Default for CommitRevealConfig {
+    fn default() -> Self {
+        Self {
+            commit_blocks: 10,
+            reveal_blocks: 10,
+            min_reveals: 3,
+            tolerance: 200,
+            penalty_per_percent: 1,
+            max_penalty: 20,
+            no_reveal_penalty: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundPhase {
+    Commit,
+    Reveal,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum CommitRevealError {
+    #[error("round is in the {actual:?} phase, not {expected:?}")]
+    WrongPhase { expected: RoundPhase, actual: RoundPhase },
+    #[error("{0} is for another task")]
+    WrongTask(String),
+    #[error("{0} has an invalid signature")]
+    InvalidSignature(String),
+    #[error("{0} already committed")]
+    AlreadyCommitted(String),
+    #[error("{0} did not commit")]
+    NotCommitted(String),
+    #[error("{0} already revealed")]
+    AlreadyRevealed(String),
+    #[error("reveal by {0} does not match its commitment")]
+    Mismatch(String),
+}
+
+/// A validator's signed commitment to an accuracy it has not revealed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCommitment {
+    pub task_id: String,
+    /// Hex ed25519 key of the validator, as in [`SignedEvaluation`].
+    pub validator: String,
+    /// See [`score_commitment`].
+    pub commitment: String,
+    pub signature: Vec<u8>,
+}
+
+/// The evaluation and salt behind a [`SignedCommitment`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluationReveal {
+    pub evaluation: SignedEvaluation,
+    pub salt: [u8; 32],
+}
+
+impl EvaluationReveal {
+    /// Whether this reveal opens `commitment`: same task and validator, a
+    /// validly signed evaluation and a matching hash.
+    pub fn matches(&self, commitment: &SignedCommitment) -> bool {
+        let eval = &self.evaluation;
+        eval.task_id == commitment.task_id
+            && eval.validator == commitment.validator
+            && verify_evaluation(eval)
+            && score_commitment(&eval.task_id, &eval.validator, eval.accuracy, &self.salt) == commitment.commitment
+    }
+}
+
+/// Hex SHA-256 commitment of `validator` to `accuracy` on `task_id`.
+pub fn score_commitment(task_id: &str, validator: &str, accuracy: u32, salt: &[u8; 32]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMIT_DOMAIN);
+    hasher.update((task_id.len() as u64).to_be_bytes());
+    hasher.update(task_id.as_bytes());
+    hasher.update(validator.as_bytes());
+    hasher.update(accuracy.to_be_bytes());
+    hasher.update(salt);
+    hex::encode(hasher.finalize())
+}
+
+fn commitment_message(task_id: &str, commitment: &str) -> Vec<u8> {
+    let mut msg = COMMIT_DOMAIN.to_vec();
+    msg.extend_from_slice(task_id.as_bytes());
+    msg.extend_from_slice(commitment.as_bytes());
+    msg
+}
+
+/// Commits to `accuracy` on `task_id`. `salt` must be random and kept
+/// secret: the commitment is published now, the reveal only in the reveal
+/// phase.
+pub fn commit_evaluation(
+    task_id: &str,
+    accuracy: u32,
+    salt: [u8; 32],
+    key: &SigningKey,
+) -> (SignedCommitment, EvaluationReveal) {
+    let validator = hex::encode(key.verifying_key().to_bytes());
+    let commitment = score_commitment(task_id, &validator, accuracy, &salt);
+    let signature: Signature = key.sign(&commitment_message(task_id, &commitment));
+    let signed = SignedCommitment {
+        task_id: task_id.to_string(),
+        validator,
+        commitment,
+        signature: signature.to_bytes().to_vec(),
+    };
+    (signed, EvaluationReveal { evaluation: sign_evaluation(task_id, accuracy, key), salt })
+}
+
+/// Verifies the validator's signature on a commitment.
+pub fn verify_commitment(commitment: &SignedCommitment) -> bool {
+    let Ok(pk_bytes) = hex::decode(&commitment.validator) else { return false };
+    let Ok(pk_bytes) = <[u8; 32]>::try_from(pk_bytes) else { return false };
+    let Ok(vk) = VerifyingKey::from_bytes(&pk_bytes) else { return false };
+    let Ok(sig) = Signature::from_slice(&commitment.signature) else { return false };
+    vk.verify(&commitment_message(&commitment.task_id, &commitment.commitment), &sig).is_ok()
+}
+
+/// Outcome of a closed round.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settlement {
+    /// The consensus of the reveals, if there were enough of them.
+    pub consensus: Option<ConsensusEvaluation>,
+    /// Stake to slash, by validator.
+    pub penalties: BTreeMap<String, u64>,
+}
+
+/// Commit-reveal evaluation of one task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvaluationRound {
+    pub task_id: String,
+    pub phase: RoundPhase,
+    /// Block at which the current phase started, stamped at the end of the
+    /// block that opened it.
+    pub phase_start: Option<u32>,
+    /// Commitments by validator.
+    pub commitments: BTreeMap<String, SignedCommitment>,
+    /// Revealed evaluations by validator.
+    pub reveals: BTreeMap<String, SignedEvaluation>,
+}
+
+impl EvaluationRound {
+    pub fn new(task_id: &str) -> Self {
+        Self {
+            task_id: task_id.to_string(),
+            phase: RoundPhase::Commit,
+            phase_start: None,
+            commitments: BTreeMap::new(),
+            reveals: BTreeMap::new(),
+        }
+    }
+
+    fn expect_phase(&self, expected: RoundPhase) -> Result<(), CommitRevealError> {
+        if self.phase != expected {
+            return Err(CommitRevealError::WrongPhase { expected, actual: self.phase });
+        }
+        Ok(())
+    }
+
+    /// Checks that `commitment` may be added to the round.
+    pub fn check_commit(&self, commitment: &SignedCommitment) -> Result<(), CommitRevealError> {
+        self.expect_phase(RoundPhase::Commit)?;
+        if commitment.task_id != self.task_id {
+            return Err(CommitRevealError::WrongTask(commitment.validator.clone()));
+        }
+        if self.commitments.contains_key(&commitment.validator) {
+            return Err(CommitRevealError::AlreadyCommitted(commitment.validator.clone()));
+        }
+        if !verify_commitment(commitment) {
+            return Err(CommitRevealError::InvalidSignature(commitment.validator.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn commit(&mut self, commitment: SignedCommitment) -> Result<(), CommitRevealError> {
+        self.check_commit(&commitment)?;
+        self.commitments.insert(commitment.validator.clone(), commitment);
+        Ok(())
+    }
+
+    /// Checks that `reveal` opens a commitment of the round not yet revealed.
+    pub fn check_reveal(&self, reveal: &EvaluationReveal) -> Result<(), CommitRevealError> {
+        self.expect_phase(RoundPhase::Reveal)?;
+        let validator = &reveal.evaluation.validator;
+        let commitment = self
+            .commitments
+            .get(validator)
+            .ok_or_else(|| CommitRevealError::NotCommitted(validator.clone()))?;
+        if self.reveals.contains_key(validator) {
+            return Err(CommitRevealError::AlreadyRevealed(validator.clone()));
+        }
+        if !reveal.matches(commitment) {
+            return Err(CommitRevealError::Mismatch(validator.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn reveal(&mut self, reveal: EvaluationReveal) -> Result<(), CommitRevealError> {
+        self.check_reveal(&reveal)?;
+        self.reveals.insert(reveal.evaluation.validator.clone(), reveal.evaluation);
+        Ok(())
+    }
+
+    /// Moves the round along as of block `index`. Returns true once the
+    /// reveal phase is over and the round should be settled.
+    pub fn advance(&mut self, index: u32, config: &CommitRevealConfig) -> bool {
+        let Some(start) = self.phase_start else {
+            self.phase_start = Some(index);
+            return false;
+        };
+        match self.phase {
+            RoundPhase::Commit if index >= start.saturating_add(config.commit_blocks) => {
+                self.phase = RoundPhase::Reveal;
+                self.phase_start = Some(index);
+                false
+            }
+            RoundPhase::Reveal => index >= start.saturating_add(config.reveal_blocks),
+            RoundPhase::Commit => false,
+        }
+    }
+
+    /// Penalties and consensus of the round as it stands.
+    pub fn settle(&self, config: &CommitRevealConfig) -> Settlement {
+        let reveals: Vec<SignedEvaluation> = self.reveals.values().cloned().collect();
+        let mut accuracies: Vec<u32> = reveals.iter().map(|e| e.accuracy).collect();
+        accuracies.sort_unstable();
+        let center = median(&accuracies);
+
+        let mut penalties = BTreeMap::new();
+        for eval in &reveals {
+            let excess = eval.accuracy.abs_diff(center).saturating_sub(config.tolerance) as u64;
+            if excess > 0 {
+                let penalty = (excess * config.penalty_per_percent).div_ceil(100).min(config.max_penalty);
+                penalties.insert(eval.validator.clone(), penalty);
+            }
+        }
+        for validator in self.commitments.keys().filter(|v| !self.reveals.contains_key(*v)) {
+            penalties.insert(validator.clone(), config.no_reveal_penalty);
+        }
+        penalties.retain(|_, penalty| *penalty > 0);
+
+        let aggregation = AggregationConfig { min_evaluations: config.min_reveals, ..AggregationConfig::default() };
+        let consensus = aggregate(&self.task_id, &reveals, &aggregation).ok();
+        Settlement { consensus, penalties }
+    }
+}
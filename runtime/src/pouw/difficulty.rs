@@ -1,4 +1,14 @@
 //! Implements the adaptive difficulty adjustment algorithm.
+//!
+//! [`retarget`] is the per-block algorithm used by the chain. It looks at a
+//! moving window of recent blocks and adjusts two knobs: the PoW target
+//! (`difficulty`, where a larger number is easier) from the average block
+//! interval, and the task size (`epochs`) from the observed training
+//! throughput, so that training fills a fixed share of the target block
+//! time. Both adjustments are damped and limited per block, and all
+//! arithmetic is integer so every node derives the same values.
+
+use serde::{Deserialize, Serialize};
 
 const DIFFICULTY_ADJUSTMENT_FACTOR: f64 = 0.05; // 5% adjustment factor
 
@@ -29,4 +39,125 @@ pub fn calculate_adaptive_difficulty(
 
     // Clamp the new difficulty to be within a reasonable range.
     (new_difficulty_float as u32).max(1)
-} 
\ No newline at end of file
+}
+
+/// Parameters of the per-block retargeting algorithm.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetargetConfig {
+    /// Desired average interval between blocks.
+    pub target_block_time_secs: u64,
+    /// Number of most recent blocks considered.
+    pub window: usize,
+    /// Share of the observed deviation corrected per block, in percent.
+    pub damping_percent: u64,
+    /// Largest change of either knob per block, in percent.
+    pub max_step_percent: u64,
+    /// Share of the target block time that training should occupy, in percent.
+    pub training_share_percent: u64,
+    /// Hardest allowed target.
+    pub min_difficulty: u32,
+    /// Easiest allowed target.
+    pub max_difficulty: u32,
+    pub min_epochs: u32,
+    pub max_epochs: u32,
+    /// Used while the window holds fewer than two blocks.
+    pub initial: Retarget,
+}
+
+impl Default for RetargetConfig {
+    fn default() -> Self {
+        Self {
+            target_block_time_secs: 60,
+            window: 20,
+            damping_percent: 25,
+            max_step_percent: 25,
+            training_share_percent: 50,
+            min_difficulty: 0x0000_FFFF,
+            max_difficulty: 0x0FFF_FFFF,
+            min_epochs: 1,
+            max_epochs: 10_000,
+            initial: Retarget { difficulty: 0x000F_FFFF, epochs: 10 },
+        }
+    }
+}
+
+/// The two values retargeted each block.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Retarget {
+    /// PoW target passed to the solver; larger is easier.
+    pub difficulty: u32,
+    /// Training epochs for the block's PoUW task.
+    pub epochs: u32,
+}
+
+/// What retargeting needs to know about a past block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSample {
+    /// Block timestamp, in seconds.
+    pub timestamp: i64,
+    /// Epochs of the block's PoUW task.
+    pub epochs: u32,
+    /// Solver-reported training time for the task.
+    pub training_ms: u64,
+}
+
+/// Computes the difficulty and task size for the next block from `current`
+/// (the values used for the tip) and the most recent blocks, oldest first.
+/// Only the last `config.window` samples are used.
+pub fn retarget(config: &RetargetConfig, current: Retarget, recent: &[BlockSample]) -> Retarget {
+    let current = Retarget {
+        difficulty: current.difficulty.clamp(config.min_difficulty, config.max_difficulty),
+        epochs: current.epochs.clamp(config.min_epochs, config.max_epochs),
+    };
+    let window = &recent[recent.len().saturating_sub(config.window.max(2))..];
+    if window.len() < 2 {
+        return current;
+    }
+    Retarget {
+        difficulty: retarget_difficulty(config, current.difficulty, window),
+        epochs: retarget_epochs(config, current.epochs, window),
+    }
+}
+
+/// Scales `value` by `ratio` per mille after damping and limiting the step.
+fn damped_step(config: &RetargetConfig, value: u64, ratio_per_mille: u64) -> u64 {
+    let ratio = ratio_per_mille as i64;
+    let damped = 1000 + (ratio - 1000) * config.damping_percent as i64 / 100;
+    let lower = (1000 * 100 / (100 + config.max_step_percent)) as i64;
+    let upper = (1000 * (100 + config.max_step_percent) / 100) as i64;
+    value * damped.clamp(lower, upper) as u64 / 1000
+}
+
+fn retarget_difficulty(config: &RetargetConfig, current: u32, window: &[BlockSample]) -> u32 {
+    let first = window.first().expect("window has two samples");
+    let last = window.last().expect("window has two samples");
+    let span_ms = (last.timestamp - first.timestamp).max(0) as u64 * 1000;
+    let interval_ms = span_ms / (window.len() as u64 - 1);
+    let target_ms = config.target_block_time_secs.max(1) * 1000;
+
+    // Slow blocks raise the target (easier), fast blocks lower it.
+    let ratio = interval_ms * 1000 / target_ms;
+    let next = damped_step(config, current as u64, ratio);
+    next.clamp(config.min_difficulty as u64, config.max_difficulty as u64) as u32
+}
+
+fn retarget_epochs(config: &RetargetConfig, current: u32, window: &[BlockSample]) -> u32 {
+    let epochs: u64 = window.iter().map(|s| s.epochs as u64).sum();
+    let training_ms: u64 = window.iter().map(|s| s.training_ms).sum();
+    if epochs == 0 || training_ms == 0 {
+        return current;
+    }
+
+    // Epochs that would fill the training share of the target block time at
+    // the observed throughput.
+    let budget_ms = config.target_block_time_secs * 1000 * config.training_share_percent / 100;
+    let desired = budget_ms * epochs / training_ms;
+    let ratio = desired * 1000 / current as u64;
+    let mut next = damped_step(config, current as u64, ratio);
+    // Small tasks would otherwise never move.
+    if next == current as u64 && desired != current as u64 {
+        next = if desired > current as u64 { next + 1 } else { next - 1 };
+    }
+    next.clamp(config.min_epochs as u64, config.max_epochs as u64) as u32
+}
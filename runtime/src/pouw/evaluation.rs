@@ -4,8 +4,69 @@ use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer, Verifier};
 use crate::p2p_service::{P2PHandle, P2PError};
 #[cfg(feature = "p2p")]
 use crate::network::NetworkMessage;
+use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 
+/// Basis points in a whole block reward.
+pub const FULL_REWARD_BPS: u32 = 10_000;
+
+/// Reaching `accuracy` (basis points, like `Solution::accuracy`) earns
+/// `reward_bps` of the block reward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub accuracy: u32,
+    pub reward_bps: u32,
+}
+
+/// Accuracy milestones for partial credit, so that a best-effort result on
+/// a hard task still earns part of the block reward.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    /// Sorted by accuracy; a solution earns the share of the highest one it
+    /// reaches and nothing below the first.
+    pub milestones: Vec<Milestone>,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            milestones: vec![
+                Milestone { accuracy: 5_000, reward_bps: 2_500 },
+                Milestone { accuracy: 6_500, reward_bps: 5_000 },
+                Milestone { accuracy: 8_000, reward_bps: 7_500 },
+                Milestone { accuracy: 9_000, reward_bps: FULL_REWARD_BPS },
+            ],
+        }
+    }
+}
+
+/// Partial-credit score of a solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    pub accuracy: u32,
+    /// Index of the highest milestone reached.
+    pub milestone: Option<usize>,
+    pub reward_bps: u32,
+}
+
+impl Score {
+    /// This score's share of `reward`.
+    pub fn reward_share(&self, reward: u64) -> u64 {
+        (reward as u128 * self.reward_bps.min(FULL_REWARD_BPS) as u128 / FULL_REWARD_BPS as u128) as u64
+    }
+}
+
+/// Scores an accuracy against the milestones in `config`.
+pub fn score(accuracy: u32, config: &ScoringConfig) -> Score {
+    let milestone = config.milestones.iter().rposition(|m| accuracy >= m.accuracy);
+    Score {
+        accuracy,
+        milestone,
+        reward_bps: milestone.map_or(0, |i| config.milestones[i].reward_bps),
+    }
+}
+
 /// Creates a signed evaluation using the validator's signing key.
 pub fn sign_evaluation(task_id: &str, accuracy: u32, key: &SigningKey) -> SignedEvaluation {
     let mut msg = task_id.as_bytes().to_vec();
@@ -42,5 +103,5 @@ pub fn evaluation_hash(eval: &SignedEvaluation) -> String {
 pub async fn broadcast_evaluation(handle: &P2PHandle, eval: &SignedEvaluation) -> Result<(), P2PError> {
     let msg = NetworkMessage::PoUWEvaluation { evaluation: eval.clone() };
     let bytes = bincode::serialize(&msg).map_err(|e| P2PError::SerializationFailed(e.to_string()))?;
-    handle.send_message("pouw_evaluations".into(), bytes).await
+    handle.gossip(crate::gossip::GossipTopic::Evaluations, bytes).await
 }
@@ -0,0 +1,190 @@
+//! Fraud proofs against PoUW solutions accepted on chain.
+//!
+//! Blocks are accepted after the cheap [`super::verify`] check only; the
+//! training run itself is checked optimistically. Any node that re-executes
+//! a solution and finds it wrong can, within the challenge window, submit a
+//! [`FraudProof`] naming the single check that fails together with the value
+//! it recomputed. Validators re-run just that check, so verifying a proof
+//! costs at most one checkpoint segment of training.
+
+use super::training::{self, Dataset, Weights};
+use super::types::{PoUWTask, Solution};
+use super::verifier::{create_task_commitment, meets_difficulty, solution_digest};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// The check a solution fails, with the challenger's recomputed value where
+/// there is one. Hashes are hex SHA-256 over the weights, see
+/// [`training::hash_weights`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailedCheck {
+    /// The solution digest does not meet the block difficulty.
+    Difficulty,
+    /// The solution does not carry one decodable checkpoint per segment.
+    MalformedCheckpoints,
+    /// The final checkpoint does not hash to the claimed model.
+    ModelHash,
+    /// The task's dataset root does not match the dataset it names.
+    DatasetRoot { recomputed: String },
+    /// The final weights do not reach the claimed accuracy.
+    Accuracy { recomputed: u32 },
+    /// Re-training `segment` from the previous checkpoint does not reproduce
+    /// the solution's checkpoint.
+    Segment { segment: usize, recomputed: String },
+}
+
+/// A claim that the PoUW solution in block `block_index` is invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FraudProof {
+    pub block_index: u32,
+    pub block_hash: String,
+    pub task: PoUWTask,
+    pub solution: Solution,
+    pub check: FailedCheck,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum FraudProofError {
+    #[error("solution passes the {0} check")]
+    CheckPasses(&'static str),
+    #[error("recomputed value does not match the proof transcript")]
+    TranscriptMismatch,
+    #[error("segment {0} is out of range")]
+    SegmentOutOfRange(usize),
+}
+
+impl FraudProof {
+    /// Re-executes every check against `solution` and returns a proof for
+    /// the first one that fails, or `None` if the solution is honest.
+    pub fn detect(
+        block_index: u32,
+        block_hash: &str,
+        task: &PoUWTask,
+        solution: &Solution,
+        difficulty: u32,
+    ) -> Option<Self> {
+        let check = find_failure(task, solution, difficulty)?;
+        Some(Self {
+            block_index,
+            block_hash: block_hash.to_string(),
+            task: task.clone(),
+            solution: solution.clone(),
+            check,
+        })
+    }
+
+    /// Hex SHA-256 binding the proof to the task and solution recorded on
+    /// chain when the block was accepted, see [`evidence_hash`].
+    pub fn evidence_hash(&self) -> String {
+        evidence_hash(&self.task, &self.solution)
+    }
+
+    /// Re-runs the claimed check. `Ok` means the solution is fraudulent.
+    pub fn verify(&self, difficulty: u32) -> Result<(), FraudProofError> {
+        let (task, solution) = (&self.task, &self.solution);
+        match &self.check {
+            FailedCheck::Difficulty => fails(!meets(task, solution, difficulty), "difficulty"),
+            FailedCheck::MalformedCheckpoints => fails(checkpoints(task, solution).is_none(), "checkpoint"),
+            FailedCheck::ModelHash => {
+                let checkpoints = checkpoints(task, solution).ok_or(FraudProofError::CheckPasses("checkpoint"))?;
+                let last = checkpoints.last().expect("at least one checkpoint");
+                fails(weights_hash(last) != solution.trained_model_hash, "model hash")
+            }
+            FailedCheck::DatasetRoot { recomputed } => {
+                let claimed = task.dataset_root.as_ref().ok_or(FraudProofError::CheckPasses("dataset root"))?;
+                transcript(&Dataset::for_task(task).merkle_root(), recomputed)?;
+                fails(claimed != recomputed, "dataset root")
+            }
+            FailedCheck::Accuracy { recomputed } => {
+                let checkpoints = checkpoints(task, solution).ok_or(FraudProofError::CheckPasses("checkpoint"))?;
+                let last = checkpoints.last().expect("at least one checkpoint");
+                transcript(&training::accuracy(&Dataset::for_task(task), last), recomputed)?;
+                fails(solution.accuracy != *recomputed, "accuracy")
+            }
+            FailedCheck::Segment { segment, recomputed } => {
+                let checkpoints = checkpoints(task, solution).ok_or(FraudProofError::CheckPasses("checkpoint"))?;
+                let claimed = checkpoints.get(*segment).ok_or(FraudProofError::SegmentOutOfRange(*segment))?;
+                transcript(&weights_hash(&retrain_segment(task, &checkpoints, *segment)), recomputed)?;
+                fails(weights_hash(claimed) != *recomputed, "segment")
+            }
+        }
+    }
+}
+
+/// Hex SHA-256 over the bincode encoding of a task and its solution.
+pub fn evidence_hash(task: &PoUWTask, solution: &Solution) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bincode::serialize(task).expect("serialize task"));
+    hasher.update(bincode::serialize(solution).expect("serialize solution"));
+    hex::encode(hasher.finalize())
+}
+
+fn find_failure(task: &PoUWTask, solution: &Solution, difficulty: u32) -> Option<FailedCheck> {
+    if !meets(task, solution, difficulty) {
+        return Some(FailedCheck::Difficulty);
+    }
+    let checkpoints = match checkpoints(task, solution) {
+        Some(c) => c,
+        None => return Some(FailedCheck::MalformedCheckpoints),
+    };
+    let last = checkpoints.last().expect("at least one checkpoint");
+    if weights_hash(last) != solution.trained_model_hash {
+        return Some(FailedCheck::ModelHash);
+    }
+    let data = Dataset::for_task(task);
+    let root = data.merkle_root();
+    if task.dataset_root.as_ref().is_some_and(|claimed| *claimed != root) {
+        return Some(FailedCheck::DatasetRoot { recomputed: root });
+    }
+    let accuracy = training::accuracy(&data, last);
+    if accuracy != solution.accuracy {
+        return Some(FailedCheck::Accuracy { recomputed: accuracy });
+    }
+    (0..checkpoints.len()).find_map(|segment| {
+        let recomputed = retrain_segment(task, &checkpoints, segment);
+        (recomputed != checkpoints[segment])
+            .then(|| FailedCheck::Segment { segment, recomputed: weights_hash(&recomputed) })
+    })
+}
+
+fn meets(task: &PoUWTask, solution: &Solution, difficulty: u32) -> bool {
+    let model_hash: Option<[u8; 32]> = hex::decode(&solution.trained_model_hash).ok().and_then(|h| h.try_into().ok());
+    model_hash.is_some_and(|h| {
+        meets_difficulty(&solution_digest(&h, &create_task_commitment(task), solution.nonce), difficulty)
+    })
+}
+
+/// Decoded checkpoints, if there is exactly one per segment.
+fn checkpoints(task: &PoUWTask, solution: &Solution) -> Option<Vec<Weights>> {
+    let segments = task.epochs.div_ceil(training::checkpoint_interval(task.epochs)).max(1) as usize;
+    if solution.checkpoints.len() != segments {
+        return None;
+    }
+    solution.checkpoints.iter().map(|c| training::decode_weights(c)).collect()
+}
+
+fn retrain_segment(task: &PoUWTask, checkpoints: &[Weights], segment: usize) -> Weights {
+    let start = if segment == 0 { [0; training::FEATURES] } else { checkpoints[segment - 1] };
+    training::train_epochs(&Dataset::for_task(task), start, training::segment_len(task.epochs, segment))
+}
+
+fn weights_hash(weights: &Weights) -> String {
+    hex::encode(training::hash_weights(weights))
+}
+
+fn fails(failed: bool, check: &'static str) -> Result<(), FraudProofError> {
+    if failed {
+        Ok(())
+    } else {
+        Err(FraudProofError::CheckPasses(check))
+    }
+}
+
+fn transcript<T: PartialEq>(recomputed: &T, claimed: &T) -> Result<(), FraudProofError> {
+    if recomputed == claimed {
+        Ok(())
+    } else {
+        Err(FraudProofError::TranscriptMismatch)
+    }
+}
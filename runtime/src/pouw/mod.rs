@@ -4,28 +4,46 @@
 //! computation, such as training machine learning models. This module provides
 //! the data structures and functions for creating, solving, and verifying PoUW tasks.
 
+pub mod backend;
 pub mod difficulty;
 pub mod solver;
 pub mod task;
+pub mod task_types;
 pub mod types;
 pub mod verifier;
 pub mod validator_selection;
 pub mod evaluation;
 pub mod outlier;
+pub mod commit_reveal;
 pub mod model;
+pub mod training;
+pub mod sampling;
+pub mod fraud;
+pub mod progress;
+#[cfg(feature = "zk-proofs")]
+pub mod zk;
 
 #[cfg(test)]
 mod tests;
 
-pub use difficulty::calculate_adaptive_difficulty;
-pub use solver::solve;
+pub use difficulty::{calculate_adaptive_difficulty, retarget, BlockSample, Retarget, RetargetConfig};
+pub use backend::{BackendError, CpuBackend, GradientBackend};
+pub use solver::{solve, solve_with};
 pub use task::{generate_task, generate_task_with_timestamp};
+pub use task_types::{TaskType, TaskTypeError, TaskTypeRegistry};
 pub use types::{PoUWConfig, Solution, PoUWTask, ValidatorSelectionConfig};
 pub use types::PoUWTask as Task;
-pub use verifier::verify;
+pub use verifier::{verify, verify_by_retraining};
+pub use fraud::{FailedCheck, FraudProof, FraudProofError};
+pub use progress::{LongTask, ProgressDelta, ProgressError, TaskProgress};
+pub use sampling::{challenge_indices, prove_samples, verify_sample_proofs, SampleProof};
 pub use validator_selection::select_validators;
-pub use evaluation::{sign_evaluation, verify_evaluation, evaluation_hash};
+pub use evaluation::{sign_evaluation, verify_evaluation, evaluation_hash, score, Milestone, Score, ScoringConfig};
 #[cfg(feature = "p2p")]
 pub use evaluation::broadcast_evaluation;
-pub use outlier::detect_outliers;
+pub use outlier::{aggregate, collect_evaluations, detect_outliers, AggregationConfig, AggregationError, ConsensusEvaluation};
+pub use commit_reveal::{
+    commit_evaluation, score_commitment, verify_commitment, CommitRevealConfig, CommitRevealError, EvaluationReveal,
+    EvaluationRound, RoundPhase, Settlement, SignedCommitment,
+};
 pub use model::file_hash as onnx_hash;
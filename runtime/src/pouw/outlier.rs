@@ -1,18 +1,181 @@
+//! Robust aggregation of validator evaluations.
+//!
+//! Every selected validator re-evaluates a solution and submits a
+//! [`SignedEvaluation`]. [`aggregate`] turns those into one consensus
+//! accuracy in two passes: evaluations further from the median than a few
+//! median absolute deviations are flagged as outliers, then a Krum-style
+//! score (distance to the nearest honest-majority neighbours) picks the
+//! tightest cluster of the rest, whose median becomes the consensus. Flagged
+//! validators are penalized when the consensus is recorded on chain.
+
+use super::evaluation::verify_evaluation;
 use super::types::SignedEvaluation;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
 
-/// Detects outlier evaluators based on median absolute deviation.
-pub fn detect_outliers(results: &[SignedEvaluation]) -> Vec<String> {
-    if results.is_empty() {
-        return vec![];
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AggregationConfig {
+    /// Evaluations needed before a consensus is formed.
+    pub min_evaluations: usize,
+    /// An evaluation more than this many median absolute deviations from
+    /// the median is an outlier.
+    pub mad_factor: u32,
+    /// Deviations up to this many basis points are never outliers, so
+    /// validators that nearly agree do not flag each other.
+    pub tolerance: u32,
+}
+
+impl Default for AggregationConfig {
+    fn default() -> Self {
+        Self {
+            min_evaluations: 3,
+            mad_factor: 3,
+            tolerance: 100,
+        }
     }
-    let mut accuracies: Vec<u32> = results.iter().map(|r| r.accuracy).collect();
+}
+
+/// The agreed evaluation of a task. Validator lists are sorted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsensusEvaluation {
+    pub task_id: String,
+    /// Median accuracy of the accepted evaluations, in basis points.
+    pub accuracy: u32,
+    /// Validators whose evaluations formed the consensus.
+    pub accepted: Vec<String>,
+    /// Validators flagged as outliers.
+    pub outliers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum AggregationError {
+    #[error("need {need} evaluations, got {got}")]
+    TooFewEvaluations { need: usize, got: usize },
+    #[error("evaluation by {0} is for another task")]
+    WrongTask(String),
+    #[error("evaluation by {0} has an invalid signature")]
+    InvalidSignature(String),
+    #[error("{0} submitted more than one evaluation")]
+    Duplicate(String),
+}
+
+/// Keeps the evaluations of `task_id` submitted by `selected` validators
+/// with a valid signature, the first one per validator, sorted by validator.
+pub fn collect_evaluations(
+    task_id: &str,
+    evaluations: impl IntoIterator<Item = SignedEvaluation>,
+    selected: &[String],
+) -> Vec<SignedEvaluation> {
+    let mut seen = HashSet::new();
+    let mut collected: Vec<SignedEvaluation> = evaluations
+        .into_iter()
+        .filter(|e| e.task_id == task_id && selected.contains(&e.validator) && verify_evaluation(e))
+        .filter(|e| seen.insert(e.validator.clone()))
+        .collect();
+    collected.sort_by(|a, b| a.validator.cmp(&b.validator));
+    collected
+}
+
+/// Aggregates the evaluations of `task_id` into a consensus. Every
+/// evaluation must be for the task, validly signed and from a distinct
+/// validator; the result does not depend on their order.
+pub fn aggregate(
+    task_id: &str,
+    evaluations: &[SignedEvaluation],
+    config: &AggregationConfig,
+) -> Result<ConsensusEvaluation, AggregationError> {
+    if evaluations.len() < config.min_evaluations.max(1) {
+        return Err(AggregationError::TooFewEvaluations {
+            need: config.min_evaluations.max(1),
+            got: evaluations.len(),
+        });
+    }
+    let mut seen = HashSet::new();
+    for eval in evaluations {
+        if eval.task_id != task_id {
+            return Err(AggregationError::WrongTask(eval.validator.clone()));
+        }
+        if !verify_evaluation(eval) {
+            return Err(AggregationError::InvalidSignature(eval.validator.clone()));
+        }
+        if !seen.insert(&eval.validator) {
+            return Err(AggregationError::Duplicate(eval.validator.clone()));
+        }
+    }
+
+    let (center, threshold) = outlier_bounds(evaluations, config);
+    let mut sorted: Vec<&SignedEvaluation> = evaluations.iter().collect();
+    sorted.sort_by(|a, b| a.validator.cmp(&b.validator));
+    let (outliers, inliers): (Vec<&SignedEvaluation>, Vec<&SignedEvaluation>) =
+        sorted.into_iter().partition(|e| e.accuracy.abs_diff(center) > threshold);
+    let accepted = krum_select(&inliers);
+    let mut accuracies: Vec<u32> = accepted.iter().map(|e| e.accuracy).collect();
     accuracies.sort_unstable();
-    let median = accuracies[accuracies.len() / 2];
-    let deviations: Vec<i64> = accuracies.iter().map(|&a| (a as i64 - median as i64).abs()).collect();
-    let median_dev = deviations[deviations.len() / 2];
+
+    Ok(ConsensusEvaluation {
+        task_id: task_id.to_string(),
+        accuracy: median(&accuracies),
+        accepted: accepted.iter().map(|e| e.validator.clone()).collect(),
+        outliers: outliers.iter().map(|e| e.validator.clone()).collect(),
+    })
+}
+
+/// Detects outlier evaluators based on median absolute deviation.
+pub fn detect_outliers(results: &[SignedEvaluation]) -> Vec<String> {
+    let (center, threshold) = outlier_bounds(results, &AggregationConfig::default());
     results
         .iter()
-        .filter(|r| ((r.accuracy as i64 - median as i64).abs()) > median_dev * 3)
+        .filter(|r| r.accuracy.abs_diff(center) > threshold)
         .map(|r| r.validator.clone())
         .collect()
 }
+
+/// Median accuracy of `results` and the largest deviation from it that is
+/// not an outlier.
+fn outlier_bounds(results: &[SignedEvaluation], config: &AggregationConfig) -> (u32, u32) {
+    let mut accuracies: Vec<u32> = results.iter().map(|r| r.accuracy).collect();
+    accuracies.sort_unstable();
+    let center = median(&accuracies);
+    let mut deviations: Vec<u32> = accuracies.iter().map(|a| a.abs_diff(center)).collect();
+    deviations.sort_unstable();
+    (center, median(&deviations).saturating_mul(config.mad_factor).max(config.tolerance))
+}
+
+/// Multi-Krum over scalar accuracies: assuming up to `f = (n - 1) / 3`
+/// faulty evaluations, scores each by its squared distance to its
+/// `n - f - 2` nearest neighbours and keeps the `n - f` lowest-scoring.
+/// Ties keep the earlier evaluation.
+fn krum_select<'a>(evaluations: &[&'a SignedEvaluation]) -> Vec<&'a SignedEvaluation> {
+    let n = evaluations.len();
+    let f = n.saturating_sub(1) / 3;
+    let neighbours = n.saturating_sub(f + 2);
+    let mut scored: Vec<(u128, usize)> = evaluations
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let mut distances: Vec<u128> = evaluations
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, o)| (e.accuracy.abs_diff(o.accuracy) as u128).pow(2))
+                .collect();
+            distances.sort_unstable();
+            (distances.iter().take(neighbours).sum(), i)
+        })
+        .collect();
+    scored.sort_unstable();
+    let mut keep: Vec<usize> = scored.iter().take(n - f).map(|(_, i)| *i).collect();
+    keep.sort_unstable();
+    keep.into_iter().map(|i| evaluations[i]).collect()
+}
+
+/// Lower median of a sorted slice, or 0 if it is empty.
+pub(crate) fn median(sorted: &[u32]) -> u32 {
+    if sorted.is_empty() {
+        0
+    } else {
+        sorted[(sorted.len() - 1) / 2]
+    }
+}
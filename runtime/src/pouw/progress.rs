@@ -0,0 +1,127 @@
+//! PoUW tasks trained across many blocks.
+//!
+//! A long task is registered on chain with its total epoch count. Solvers
+//! then commit progress in bounded deltas: each delta names its epoch range,
+//! carries the weights it started from and the hash of the weights it ended
+//! with. The start weights must hash to the last committed checkpoint and
+//! validators re-train only the delta, so no block verifies more than
+//! `max_epochs_per_block` epochs however large the task is.
+
+use super::training::{self, Dataset, Weights};
+use super::types::PoUWTask;
+use super::verifier::create_task_commitment;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A task whose `epochs` are trained over many blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LongTask {
+    pub task: PoUWTask,
+    /// Most epochs a single block may verify for this task.
+    pub max_epochs_per_block: u32,
+}
+
+impl LongTask {
+    /// Hex task commitment, used as the task's on-chain id.
+    pub fn id(&self) -> String {
+        hex::encode(create_task_commitment(&self.task))
+    }
+}
+
+/// A solver's claim to have trained a long task from `from_epoch` to `to_epoch`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressDelta {
+    pub task_id: String,
+    pub from_epoch: u32,
+    pub to_epoch: u32,
+    /// Encoded weights at `from_epoch`, see [`training::encode_weights`].
+    pub start_weights: String,
+    /// Hex hash of the weights at `to_epoch`.
+    pub checkpoint_hash: String,
+}
+
+/// On-chain progress of a long task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub long_task: LongTask,
+    pub epochs_done: u32,
+    /// Hex hash of the weights at `epochs_done`.
+    pub checkpoint_hash: String,
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ProgressError {
+    #[error("delta is for task {got}, expected {expected}")]
+    WrongTask { expected: String, got: String },
+    #[error("delta starts at epoch {got}, but the task is at epoch {expected}")]
+    WrongStart { expected: u32, got: u32 },
+    #[error("delta covers epochs {from}..{to}, outside 1..={max} epochs up to {total}")]
+    BadRange { from: u32, to: u32, max: u32, total: u32 },
+    #[error("start weights do not match the committed checkpoint")]
+    StartMismatch,
+    #[error("re-training the delta does not reproduce the claimed checkpoint")]
+    CheckpointMismatch,
+}
+
+impl TaskProgress {
+    /// Progress of a newly registered task: no epochs, zero weights.
+    pub fn new(long_task: LongTask) -> Self {
+        Self { long_task, epochs_done: 0, checkpoint_hash: weights_hash(&[0; training::FEATURES]) }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.epochs_done >= self.long_task.task.epochs
+    }
+
+    /// Checks `delta` continues from the committed checkpoint and re-trains
+    /// its epochs to confirm the claimed result.
+    pub fn verify(&self, delta: &ProgressDelta) -> Result<(), ProgressError> {
+        let id = self.long_task.id();
+        if delta.task_id != id {
+            return Err(ProgressError::WrongTask { expected: id, got: delta.task_id.clone() });
+        }
+        if delta.from_epoch != self.epochs_done {
+            return Err(ProgressError::WrongStart { expected: self.epochs_done, got: delta.from_epoch });
+        }
+        let (max, total) = (self.long_task.max_epochs_per_block, self.long_task.task.epochs);
+        let epochs = delta.to_epoch.saturating_sub(delta.from_epoch);
+        if epochs == 0 || epochs > max || delta.to_epoch > total {
+            return Err(ProgressError::BadRange { from: delta.from_epoch, to: delta.to_epoch, max, total });
+        }
+        let start = training::decode_weights(&delta.start_weights)
+            .filter(|w| weights_hash(w) == self.checkpoint_hash)
+            .ok_or(ProgressError::StartMismatch)?;
+        let end = training::train_epochs(&Dataset::for_task(&self.long_task.task), start, epochs);
+        if weights_hash(&end) != delta.checkpoint_hash {
+            return Err(ProgressError::CheckpointMismatch);
+        }
+        Ok(())
+    }
+
+    /// Records a verified delta.
+    pub fn apply(&mut self, delta: &ProgressDelta) {
+        self.epochs_done = delta.to_epoch;
+        self.checkpoint_hash = delta.checkpoint_hash.clone();
+    }
+}
+
+/// Trains up to `max_epochs_per_block` epochs from `start`, the weights at
+/// the task's committed checkpoint, returning the delta to submit and the
+/// new weights to continue from.
+pub fn advance(progress: &TaskProgress, start: &Weights) -> (ProgressDelta, Weights) {
+    let task = &progress.long_task;
+    let epochs = task.max_epochs_per_block.min(task.task.epochs.saturating_sub(progress.epochs_done));
+    let end = training::train_epochs(&Dataset::for_task(&task.task), *start, epochs);
+    let delta = ProgressDelta {
+        task_id: task.id(),
+        from_epoch: progress.epochs_done,
+        to_epoch: progress.epochs_done + epochs,
+        start_weights: training::encode_weights(start),
+        checkpoint_hash: weights_hash(&end),
+    };
+    (delta, end)
+}
+
+fn weights_hash(weights: &Weights) -> String {
+    hex::encode(training::hash_weights(weights))
+}
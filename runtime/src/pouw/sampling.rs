@@ -0,0 +1,259 @@
+//! Dataset commitments and sampling proofs.
+//!
+//! A task commits to its training set with a Merkle root over the samples
+//! (`PoUWTask::dataset_root`). While training, the solver also commits, per
+//! checkpoint segment, to the per-sample gradients evaluated at the
+//! segment's starting weights (`Solution::gradient_roots`). A verifier then
+//! challenges a handful of (segment, sample) pairs derived from the solution
+//! itself and checks, for each, that the sample is in the committed dataset
+//! and that its gradient at the checkpoint weights matches the leaf the
+//! solver committed to. Training on a different or trivial dataset produces
+//! gradient leaves that fail this check, and the verifier never needs the
+//! full dataset.
+
+use super::training::{self, Dataset, Weights, FEATURES, SAMPLES};
+use super::types::{PoUWTask, Solution};
+use super::verifier::create_task_commitment;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const SAMPLE_DOMAIN: &[u8] = b"bcai-pouw-sample";
+const GRADIENT_DOMAIN: &[u8] = b"bcai-pouw-gradient";
+
+/// Opening of one challenged sample.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SampleProof {
+    /// Checkpoint segment whose starting weights the gradient was taken at.
+    pub segment: usize,
+    /// Index of the sample in the dataset.
+    pub index: usize,
+    pub features: [i64; FEATURES],
+    pub label: i64,
+    /// Gradient of this sample at the segment's starting weights.
+    pub gradient: Weights,
+    /// Hex-encoded siblings from the sample leaf up to `dataset_root`.
+    pub sample_path: Vec<String>,
+    /// Hex-encoded siblings from the gradient leaf up to the segment's
+    /// gradient root.
+    pub gradient_path: Vec<String>,
+}
+
+/// Leaf hash for a training sample.
+pub fn sample_leaf(index: usize, features: &[i64; FEATURES], label: i64) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update([0u8]);
+    h.update(SAMPLE_DOMAIN);
+    h.update((index as u64).to_le_bytes());
+    for f in features {
+        h.update(f.to_le_bytes());
+    }
+    h.update(label.to_le_bytes());
+    h.finalize().into()
+}
+
+/// Leaf hash for a per-sample gradient.
+pub fn gradient_leaf(index: usize, gradient: &Weights) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update([0u8]);
+    h.update(GRADIENT_DOMAIN);
+    h.update((index as u64).to_le_bytes());
+    for g in gradient {
+        h.update(g.to_le_bytes());
+    }
+    h.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update([1u8]);
+    h.update(left);
+    h.update(right);
+    h.finalize().into()
+}
+
+/// All tree levels, leaves first. An odd node is paired with itself.
+fn levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels.last().is_some_and(|l| l.len() > 1) {
+        let next = levels
+            .last()
+            .expect("non-empty")
+            .chunks(2)
+            .map(|pair| node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Merkle root over `leaves`; all zeroes for an empty tree.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    levels(leaves.to_vec())
+        .last()
+        .and_then(|l| l.first().copied())
+        .unwrap_or([0u8; 32])
+}
+
+/// Sibling hashes from leaf `index` to the root.
+pub fn merkle_path(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    let levels = levels(leaves.to_vec());
+    let mut path = Vec::new();
+    let mut i = index;
+    for level in &levels[..levels.len() - 1] {
+        let sibling = i ^ 1;
+        path.push(*level.get(sibling).unwrap_or(&level[i]));
+        i /= 2;
+    }
+    path
+}
+
+/// Checks that `leaf` sits at `index` under `root`.
+pub fn verify_path(leaf: [u8; 32], index: usize, path: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let mut hash = leaf;
+    let mut i = index;
+    for sibling in path {
+        hash = if i.is_multiple_of(2) { node_hash(&hash, sibling) } else { node_hash(sibling, &hash) };
+        i /= 2;
+    }
+    i == 0 && &hash == root
+}
+
+impl Dataset {
+    fn sample_leaves(&self) -> Vec<[u8; 32]> {
+        self.features
+            .iter()
+            .zip(&self.labels)
+            .enumerate()
+            .map(|(i, (x, label))| sample_leaf(i, x, *label))
+            .collect()
+    }
+
+    fn gradient_leaves(&self, weights: &Weights) -> Vec<[u8; 32]> {
+        self.features
+            .iter()
+            .zip(&self.labels)
+            .enumerate()
+            .map(|(i, (x, label))| gradient_leaf(i, &training::sample_gradient(weights, x, *label)))
+            .collect()
+    }
+
+    /// Hex Merkle root committed to in `PoUWTask::dataset_root`.
+    pub fn merkle_root(&self) -> String {
+        hex::encode(merkle_root(&self.sample_leaves()))
+    }
+
+    /// Hex Merkle root over every sample's gradient at `weights`.
+    pub fn gradient_root(&self, weights: &Weights) -> String {
+        hex::encode(merkle_root(&self.gradient_leaves(weights)))
+    }
+}
+
+/// Weights at the start of `segment`: zero for the first, otherwise the
+/// previous checkpoint.
+fn segment_start(solution: &Solution, segment: usize) -> Option<Weights> {
+    if segment == 0 {
+        Some([0; FEATURES])
+    } else {
+        training::decode_weights(solution.checkpoints.get(segment - 1)?)
+    }
+}
+
+/// (segment, sample) pairs the solver must open, derived from the task and
+/// the solution's commitments so they are fixed once the solution is.
+pub fn challenge_indices(task: &PoUWTask, solution: &Solution, count: usize) -> Vec<(usize, usize)> {
+    let segments = solution.gradient_roots.len();
+    if segments == 0 {
+        return Vec::new();
+    }
+    let mut seed = Sha256::new();
+    seed.update(create_task_commitment(task));
+    seed.update(solution.trained_model_hash.as_bytes());
+    seed.update(solution.nonce.to_le_bytes());
+    for root in &solution.gradient_roots {
+        seed.update(root.as_bytes());
+    }
+    let mut rng = StdRng::from_seed(seed.finalize().into());
+    (0..count).map(|_| (rng.gen_range(0..segments), rng.gen_range(0..SAMPLES))).collect()
+}
+
+/// Solver side: opens the challenged samples from the local dataset.
+/// Returns `None` if a challenge falls outside the solution's checkpoints.
+pub fn prove_samples(
+    data: &Dataset,
+    solution: &Solution,
+    challenges: &[(usize, usize)],
+) -> Option<Vec<SampleProof>> {
+    let sample_leaves = data.sample_leaves();
+    let hex_path = |path: Vec<[u8; 32]>| path.iter().map(hex::encode).collect();
+    challenges
+        .iter()
+        .map(|&(segment, index)| {
+            let weights = segment_start(solution, segment)?;
+            let features = *data.features.get(index)?;
+            let label = data.labels[index];
+            Some(SampleProof {
+                segment,
+                index,
+                features,
+                label,
+                gradient: training::sample_gradient(&weights, &features, label),
+                sample_path: hex_path(merkle_path(&sample_leaves, index)),
+                gradient_path: hex_path(merkle_path(&data.gradient_leaves(&weights), index)),
+            })
+        })
+        .collect()
+}
+
+/// Verifier side: checks `proofs` answer exactly the challenges derived
+/// from `task` and `solution`, that every opened sample is in the task's
+/// committed dataset, and that its gradient recomputed at the checkpoint
+/// weights matches the solver's gradient commitment.
+pub fn verify_sample_proofs(
+    task: &PoUWTask,
+    solution: &Solution,
+    proofs: &[SampleProof],
+    count: usize,
+) -> bool {
+    let dataset_root = match task.dataset_root.as_deref().and_then(decode_hash) {
+        Some(root) => root,
+        None => return false,
+    };
+    if solution.gradient_roots.is_empty() || solution.gradient_roots.len() != solution.checkpoints.len() {
+        return false;
+    }
+    let challenges = challenge_indices(task, solution, count);
+    if proofs.len() != challenges.len() {
+        return false;
+    }
+
+    proofs.iter().zip(challenges).all(|(proof, (segment, index))| {
+        if proof.segment != segment || proof.index != index {
+            return false;
+        }
+        let (sample_path, gradient_path) = match (decode_path(&proof.sample_path), decode_path(&proof.gradient_path)) {
+            (Some(s), Some(g)) => (s, g),
+            _ => return false,
+        };
+        let gradient_root = match decode_hash(&solution.gradient_roots[segment]) {
+            Some(root) => root,
+            None => return false,
+        };
+        let weights = match segment_start(solution, segment) {
+            Some(w) => w,
+            None => return false,
+        };
+        verify_path(sample_leaf(index, &proof.features, proof.label), index, &sample_path, &dataset_root)
+            && training::sample_gradient(&weights, &proof.features, proof.label) == proof.gradient
+            && verify_path(gradient_leaf(index, &proof.gradient), index, &gradient_path, &gradient_root)
+    })
+}
+
+fn decode_hash(hex_hash: &str) -> Option<[u8; 32]> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
+fn decode_path(path: &[String]) -> Option<Vec<[u8; 32]>> {
+    path.iter().map(|h| decode_hash(h)).collect()
+}
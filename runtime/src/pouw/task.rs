@@ -1,5 +1,6 @@
 //! Defines functions for generating new PoUW tasks.
 
+use super::training::Dataset;
 use super::types::PoUWTask;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -16,15 +17,21 @@ pub fn generate_task(difficulty: u32, seed: u64) -> PoUWTask {
         .unwrap_or_default()
         .as_secs();
 
-    PoUWTask {
+    let mut task = PoUWTask {
         model_id: format!("model_{}", difficulty),
         dataset_id: format!("dataset_{}", difficulty),
         model_hash: None,
         dataset_hash: None,
+        dataset_root: None,
         epochs: difficulty, // Using difficulty as a proxy for epochs
         timestamp,
         challenge,
-    }
+        trace: None,
+        job_id: None,
+        kind: None,
+    };
+    task.dataset_root = Some(Dataset::for_task(&task).merkle_root());
+    task
 }
 
 /// Generates a PoUW task using the given timestamp for determinism.
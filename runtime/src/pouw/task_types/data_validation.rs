@@ -0,0 +1,70 @@
+//! `data-validation`: a validity report over the task's dataset.
+//!
+//! The task must commit to its dataset by [`PoUWTask::dataset_root`]. The
+//! solver rebuilds the dataset, checks it against the root and marks every
+//! sample whose features lie in `[-1, 1]` and whose label is a class. The
+//! report is the root followed by one byte per sample; the solution's
+//! accuracy is the share of valid samples. Verifiers recompute the report.
+
+use super::{seal, sealed_output, TaskType, TaskTypeError, TaskTypeRegistry};
+use crate::pouw::training::{Dataset, ONE};
+use crate::pouw::types::{PoUWConfig, PoUWTask, Solution};
+use crate::pouw::verifier;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const KIND: &str = "data-validation";
+
+/// The task's report and the share of valid samples in basis points.
+pub fn validate(task: &PoUWTask) -> Result<(Vec<u8>, u32), TaskTypeError> {
+    let expected = task
+        .dataset_root
+        .as_deref()
+        .ok_or_else(|| TaskTypeError::InvalidTask("data-validation needs a dataset root".into()))?;
+    let data = Dataset::for_task(task);
+    let root = data.merkle_root();
+    if root != expected {
+        return Err(TaskTypeError::InvalidTask(format!("dataset root is {}, task commits to {}", root, expected)));
+    }
+
+    let mut report = hex::decode(&root).expect("merkle roots are hex");
+    let mut valid = 0;
+    for (x, label) in data.features.iter().zip(&data.labels) {
+        let ok = x.iter().all(|v| (-ONE..=ONE).contains(v)) && (*label == 0 || *label == ONE);
+        report.push(ok as u8);
+        valid += ok as usize;
+    }
+    Ok((report, (valid * 10_000 / data.features.len().max(1)) as u32))
+}
+
+fn hash(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+pub struct DataValidation;
+
+impl TaskType for DataValidation {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+        let started = std::time::Instant::now();
+        let (report, accuracy) = validate(task)?;
+        Ok(seal(task, hash(&report), accuracy, hex::encode(&report), difficulty, started))
+    }
+
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+        if !verifier::verify(task, solution, difficulty, config) {
+            return false;
+        }
+        let Ok((report, accuracy)) = validate(task) else {
+            return false;
+        };
+        sealed_output(solution, hash).is_some_and(|claimed| claimed == report) && solution.accuracy == accuracy
+    }
+}
+
+pub fn register(registry: &mut TaskTypeRegistry) -> Result<(), TaskTypeError> {
+    registry.register(Arc::new(DataValidation))
+}
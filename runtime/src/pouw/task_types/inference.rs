@@ -0,0 +1,72 @@
+//! `batch-inference`: predictions of a model over a batch of inputs.
+//!
+//! The model is the reference fixed-point classifier of
+//! [`crate::pouw::training`] with weights, and the batch of `epochs` inputs
+//! (at most [`MAX_BATCH`]), drawn from an RNG seeded by the task commitment.
+//! The solution carries one byte per prediction and the accuracy against the
+//! inputs' true classes. Inference is cheap next to sealing, so verifiers
+//! recompute the whole batch.
+
+use super::{seal, sealed_output, TaskType, TaskTypeError, TaskTypeRegistry};
+use crate::pouw::training::{self, Weights, FEATURES, ONE};
+use crate::pouw::types::{PoUWConfig, PoUWTask, Solution};
+use crate::pouw::verifier;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const KIND: &str = "batch-inference";
+pub const MAX_BATCH: usize = 4096;
+
+/// The task's model and input batch.
+pub fn batch(task: &PoUWTask) -> (Weights, Vec<[i64; FEATURES]>) {
+    let mut rng = StdRng::from_seed(verifier::create_task_commitment(task));
+    let weights = [(); FEATURES].map(|_| rng.gen_range(-ONE..ONE));
+    let size = (task.epochs as usize).clamp(1, MAX_BATCH);
+    let inputs = (0..size).map(|_| [(); FEATURES].map(|_| rng.gen_range(-ONE..ONE))).collect();
+    (weights, inputs)
+}
+
+/// Predictions, one byte per input, and their accuracy in basis points.
+pub fn infer(task: &PoUWTask) -> (Vec<u8>, u32) {
+    let (weights, inputs) = batch(task);
+    let predictions: Vec<u8> = inputs.iter().map(|x| training::predict(&weights, x) as u8).collect();
+    let correct = inputs
+        .iter()
+        .zip(&predictions)
+        .filter(|(x, p)| (x.iter().sum::<i64>() > 0) == (**p == 1))
+        .count();
+    let accuracy = (correct * 10_000 / inputs.len()) as u32;
+    (predictions, accuracy)
+}
+
+fn hash(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+pub struct BatchInference;
+
+impl TaskType for BatchInference {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+        let started = std::time::Instant::now();
+        let (predictions, accuracy) = infer(task);
+        Ok(seal(task, hash(&predictions), accuracy, hex::encode(&predictions), difficulty, started))
+    }
+
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+        if !verifier::verify(task, solution, difficulty, config) {
+            return false;
+        }
+        let (predictions, accuracy) = infer(task);
+        sealed_output(solution, hash).is_some_and(|claimed| claimed == predictions) && solution.accuracy == accuracy
+    }
+}
+
+pub fn register(registry: &mut TaskTypeRegistry) -> Result<(), TaskTypeError> {
+    registry.register(Arc::new(BatchInference))
+}
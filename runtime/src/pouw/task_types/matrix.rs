@@ -0,0 +1,108 @@
+//! `matrix`: the product of two seeded square matrices.
+//!
+//! Both factors are drawn from an RNG seeded by the task commitment and the
+//! dimension is the task's `epochs`, capped at [`MAX_DIMENSION`]. Entries are
+//! `u32` and arithmetic wraps, so the product is exact everywhere. The
+//! solution carries the product; verifiers check it with [`ROUNDS`] rounds of
+//! Freivalds' algorithm, `O(n²)` each, instead of recomputing it.
+
+use super::{seal, sealed_output, TaskType, TaskTypeError, TaskTypeRegistry};
+use crate::pouw::types::{PoUWConfig, PoUWTask, Solution};
+use crate::pouw::verifier;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const KIND: &str = "matrix";
+pub const MAX_DIMENSION: usize = 64;
+/// Freivalds rounds; a wrong product passes each with probability at most ½.
+pub const ROUNDS: usize = 16;
+
+/// Row-major `n × n` matrix.
+type Matrix = Vec<u32>;
+
+/// Side of the task's matrices.
+pub fn dimension(task: &PoUWTask) -> usize {
+    (task.epochs as usize).clamp(1, MAX_DIMENSION)
+}
+
+/// The two factors of the task.
+pub fn factors(task: &PoUWTask) -> (Matrix, Matrix) {
+    let n = dimension(task);
+    let mut rng = StdRng::from_seed(verifier::create_task_commitment(task));
+    let mut draw = || (0..n * n).map(|_| rng.gen()).collect::<Matrix>();
+    (draw(), draw())
+}
+
+pub fn multiply(a: &[u32], b: &[u32], n: usize) -> Matrix {
+    let mut c = vec![0u32; n * n];
+    for i in 0..n {
+        for k in 0..n {
+            let aik = a[i * n + k];
+            for j in 0..n {
+                c[i * n + j] = c[i * n + j].wrapping_add(aik.wrapping_mul(b[k * n + j]));
+            }
+        }
+    }
+    c
+}
+
+fn apply(m: &[u32], v: &[u32], n: usize) -> Vec<u32> {
+    m.chunks_exact(n)
+        .map(|row| row.iter().zip(v).fold(0u32, |acc, (x, y)| acc.wrapping_add(x.wrapping_mul(*y))))
+        .collect()
+}
+
+fn encode(m: &[u32]) -> Vec<u8> {
+    m.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn hash(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+pub struct MatrixProduct;
+
+impl TaskType for MatrixProduct {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+        let started = std::time::Instant::now();
+        let n = dimension(task);
+        let (a, b) = factors(task);
+        let product = encode(&multiply(&a, &b, n));
+        Ok(seal(task, hash(&product), 10_000, hex::encode(&product), difficulty, started))
+    }
+
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+        if !verifier::verify(task, solution, difficulty, config) {
+            return false;
+        }
+        let n = dimension(task);
+        let product = match sealed_output(solution, hash) {
+            Some(bytes) if bytes.len() == n * n * 4 => bytes,
+            _ => return false,
+        };
+        let c: Matrix = product.chunks_exact(4).map(|b| u32::from_le_bytes(b.try_into().expect("4 bytes"))).collect();
+        let (a, b) = factors(task);
+
+        // The solver has committed to the product before the vectors are
+        // drawn, so it cannot tailor a wrong product to them.
+        let mut seed = Sha256::new();
+        seed.update(verifier::create_task_commitment(task));
+        seed.update(solution.trained_model_hash.as_bytes());
+        seed.update(solution.nonce.to_le_bytes());
+        let mut rng = StdRng::from_seed(seed.finalize().into());
+        (0..ROUNDS).all(|_| {
+            let r: Vec<u32> = (0..n).map(|_| rng.gen_range(0..=1)).collect();
+            apply(&a, &apply(&b, &r, n), n) == apply(&c, &r, n)
+        })
+    }
+}
+
+pub fn register(registry: &mut TaskTypeRegistry) -> Result<(), TaskTypeError> {
+    registry.register(Arc::new(MatrixProduct))
+}
@@ -0,0 +1,119 @@
+//! Kinds of useful work a PoUW task can ask for.
+//!
+//! Every [`TaskType`] solves and verifies one kind of work and is registered
+//! in a [`TaskTypeRegistry`] under the kind tasks name in
+//! [`PoUWTask::kind`]. Block validation checks a task through [`verify`],
+//! which dispatches on the kind through the registry the node installed at
+//! startup, so adding a kind of work means writing a task type and
+//! registering it; consensus code is not touched. Tasks without a kind train
+//! the reference model.
+//!
+//! Built-in kinds:
+//!
+//! - `train`: the reference training run, see [`crate::pouw::training`];
+//! - `onnx-train`: training bound to an off-chain ONNX model and dataset;
+//! - `matrix`: a seeded integer matrix product, checked with Freivalds'
+//!   algorithm;
+//! - `batch-inference`: predictions of a seeded model over a batch of inputs;
+//! - `data-validation`: a per-sample validity report over the task dataset.
+
+use super::types::{PoUWConfig, PoUWTask, Solution};
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+use thiserror::Error;
+
+pub mod data_validation;
+pub mod inference;
+pub mod matrix;
+mod registry;
+pub mod train;
+
+pub use registry::TaskTypeRegistry;
+
+/// Kind of tasks that do not name one.
+pub const DEFAULT_TASK_KIND: &str = train::KIND;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum TaskTypeError {
+    #[error("no task type registered for kind {0}")]
+    UnknownKind(String),
+    #[error("task kind {0} is already registered")]
+    Duplicate(String),
+    #[error("invalid task: {0}")]
+    InvalidTask(String),
+}
+
+/// Solver and verifier of one kind of useful work.
+pub trait TaskType: Send + Sync {
+    /// Kind this type serves, the value of [`PoUWTask::kind`].
+    fn kind(&self) -> &'static str;
+
+    /// Does the task's work and seals the result into a solution meeting
+    /// `difficulty`.
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError>;
+
+    /// Whether `solution` is the task's work and meets `difficulty`. Every
+    /// node must reach the same answer, so this has to be deterministic.
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool;
+}
+
+/// Kind of `task`, or [`DEFAULT_TASK_KIND`].
+pub fn task_kind(task: &PoUWTask) -> &str {
+    task.kind.as_deref().unwrap_or(DEFAULT_TASK_KIND)
+}
+
+static INSTALLED: RwLock<Option<Arc<TaskTypeRegistry>>> = RwLock::new(None);
+
+/// Makes `registry` the process-wide set of task types. Nodes call this at
+/// startup, before validating blocks; all nodes of a network must install
+/// the same kinds.
+pub fn install(registry: TaskTypeRegistry) {
+    *INSTALLED.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(registry));
+}
+
+/// The installed registry, or the built-in task types if none was.
+pub fn registry() -> Arc<TaskTypeRegistry> {
+    static DEFAULTS: OnceLock<Arc<TaskTypeRegistry>> = OnceLock::new();
+    INSTALLED
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| DEFAULTS.get_or_init(|| Arc::new(TaskTypeRegistry::with_defaults())).clone())
+}
+
+/// Solves `task` with the installed type for its kind.
+pub fn solve(task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+    registry().resolve(task)?.solve(task, difficulty)
+}
+
+/// Verifies `solution` with the installed type for the task's kind. Tasks of
+/// unknown kinds never verify.
+pub fn verify(task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+    registry()
+        .resolve(task)
+        .is_ok_and(|task_type| task_type.verify(task, solution, difficulty, config))
+}
+
+/// Seals the hash of a work result: finds the nonce for `result_hash` and
+/// records `output`, the result itself, as the solution's only checkpoint.
+pub(crate) fn seal(
+    task: &PoUWTask,
+    result_hash: [u8; 32],
+    accuracy: u32,
+    output: String,
+    difficulty: u32,
+    started: std::time::Instant,
+) -> Solution {
+    let mut solution = crate::trainer::seal(task, result_hash, accuracy, difficulty, started);
+    solution.checkpoints = vec![output];
+    solution
+}
+
+/// Decoded output a solution sealed with [`seal`] carries, if it is the
+/// result that hashes to the solution's model hash.
+pub(crate) fn sealed_output(solution: &Solution, hash: impl Fn(&[u8]) -> [u8; 32]) -> Option<Vec<u8>> {
+    let [output] = solution.checkpoints.as_slice() else {
+        return None;
+    };
+    let bytes = hex::decode(output).ok()?;
+    (hex::encode(hash(&bytes)) == solution.trained_model_hash).then_some(bytes)
+}
@@ -0,0 +1,60 @@
+use super::{data_validation, inference, matrix, task_kind, train, TaskType, TaskTypeError};
+use crate::pouw::types::PoUWTask;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Task types by the kind of work they serve.
+#[derive(Default)]
+pub struct TaskTypeRegistry {
+    types: HashMap<&'static str, Arc<dyn TaskType>>,
+}
+
+impl std::fmt::Debug for TaskTypeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskTypeRegistry").field("kinds", &self.kinds()).finish()
+    }
+}
+
+impl TaskTypeRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the built-in kinds: `train`, `onnx-train`, `matrix`,
+    /// `batch-inference` and `data-validation`.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for register in [train::register, matrix::register, inference::register, data_validation::register] {
+            register(&mut registry).expect("built-in task kinds are distinct");
+        }
+        registry
+    }
+
+    /// Adds a task type. Each kind can be registered once.
+    pub fn register(&mut self, task_type: Arc<dyn TaskType>) -> Result<(), TaskTypeError> {
+        let kind = task_type.kind();
+        if self.types.contains_key(kind) {
+            return Err(TaskTypeError::Duplicate(kind.to_string()));
+        }
+        self.types.insert(kind, task_type);
+        Ok(())
+    }
+
+    pub fn get(&self, kind: &str) -> Option<Arc<dyn TaskType>> {
+        self.types.get(kind).cloned()
+    }
+
+    /// Task type for the kind of work `task` asks for.
+    pub fn resolve(&self, task: &PoUWTask) -> Result<Arc<dyn TaskType>, TaskTypeError> {
+        let kind = task_kind(task);
+        self.get(kind).ok_or_else(|| TaskTypeError::UnknownKind(kind.to_string()))
+    }
+
+    /// Registered kinds, sorted.
+    pub fn kinds(&self) -> Vec<&'static str> {
+        let mut kinds: Vec<&'static str> = self.types.keys().copied().collect();
+        kinds.sort_unstable();
+        kinds
+    }
+}
@@ -0,0 +1,69 @@
+//! Training tasks.
+//!
+//! `train` is the reference training run every PoUW block has done so far.
+//! `onnx-train` is the same run bound to an off-chain ONNX model and
+//! validation set: the task must name both by hash, the hashes enter the
+//! task commitment, and verifiers re-execute sampled checkpoint segments
+//! instead of only checking the seal.
+
+use super::{TaskType, TaskTypeError, TaskTypeRegistry};
+use crate::pouw::types::{PoUWConfig, PoUWTask, Solution};
+use crate::pouw::{solver, verifier};
+use std::sync::Arc;
+
+pub const KIND: &str = "train";
+pub const ONNX_KIND: &str = "onnx-train";
+/// Checkpoint segments an `onnx-train` verifier re-executes.
+pub const RETRAIN_SAMPLES: usize = 2;
+
+pub struct Train;
+
+impl TaskType for Train {
+    fn kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+        Ok(solver::solve(task, difficulty))
+    }
+
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+        verifier::verify(task, solution, difficulty, config)
+    }
+}
+
+pub struct OnnxTrain;
+
+impl OnnxTrain {
+    fn check(task: &PoUWTask) -> Result<(), TaskTypeError> {
+        let is_sha256 = |h: &Option<String>| h.as_deref().is_some_and(|h| h.len() == 64 && hex::decode(h).is_ok());
+        if !is_sha256(&task.model_hash) {
+            return Err(TaskTypeError::InvalidTask("onnx-train needs the model's SHA-256".into()));
+        }
+        if !is_sha256(&task.dataset_hash) {
+            return Err(TaskTypeError::InvalidTask("onnx-train needs the dataset's SHA-256".into()));
+        }
+        Ok(())
+    }
+}
+
+impl TaskType for OnnxTrain {
+    fn kind(&self) -> &'static str {
+        ONNX_KIND
+    }
+
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+        Self::check(task)?;
+        Ok(solver::solve(task, difficulty))
+    }
+
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+        Self::check(task).is_ok()
+            && verifier::verify_by_retraining(task, solution, difficulty, config, RETRAIN_SAMPLES)
+    }
+}
+
+pub fn register(registry: &mut TaskTypeRegistry) -> Result<(), TaskTypeError> {
+    registry.register(Arc::new(Train))?;
+    registry.register(Arc::new(OnnxTrain))
+}
@@ -0,0 +1,228 @@
+//! Deterministic training used as the PoUW "useful work".
+//!
+//! Everything here is integer arithmetic in Q16.16 fixed point: the synthetic
+//! dataset is drawn from an RNG seeded by the task, gradients are accumulated
+//! as `i64` in sample order and the sigmoid is a piecewise-linear
+//! approximation. The same task therefore produces bit-identical weights on
+//! every platform, which lets verifiers re-execute any segment of training
+//! and compare hashes with the solver's checkpoints.
+
+use super::backend::{CpuBackend, GradientBackend};
+use super::types::PoUWTask;
+use crate::data::{DataError, Sample};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+
+/// Fixed-point scale (1.0 == `ONE`).
+pub const ONE: i64 = 1 << 16;
+/// Upper bound on checkpoints recorded per solution.
+pub const MAX_CHECKPOINTS: u32 = 32;
+
+/// Samples in every task dataset.
+pub const SAMPLES: usize = 100;
+pub const FEATURES: usize = 2;
+
+/// Model parameters in Q16.16.
+pub type Weights = [i64; FEATURES];
+
+/// Seeded synthetic dataset for a task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dataset {
+    pub features: Vec<[i64; FEATURES]>,
+    pub labels: Vec<i64>,
+}
+
+impl Dataset {
+    /// Linearly separable data derived from the task's model, dataset and
+    /// challenge, so solver and verifier see the same samples.
+    pub fn for_task(task: &PoUWTask) -> Self {
+        let mut rng = StdRng::from_seed(task_seed(task));
+        let mut features = Vec::with_capacity(SAMPLES);
+        let mut labels = Vec::with_capacity(SAMPLES);
+        for _ in 0..SAMPLES {
+            let x = rng.gen_range(-ONE..ONE);
+            let y = rng.gen_range(-ONE..ONE);
+            features.push([x, y]);
+            labels.push(if x + y > 0 { ONE } else { 0 });
+        }
+        Self { features, labels }
+    }
+
+    /// Quantizes loaded samples for training. Tasks over real data need
+    /// exactly [`FEATURES`] features per sample and binary labels.
+    pub fn from_samples(samples: &[Sample]) -> Result<Self, DataError> {
+        if samples.is_empty() {
+            return Err(DataError::Empty);
+        }
+        let mut features = Vec::with_capacity(samples.len());
+        let mut labels = Vec::with_capacity(samples.len());
+        for sample in samples {
+            let x: [f32; FEATURES] = sample.features.as_slice().try_into().map_err(|_| {
+                DataError::Schema(format!("expected {} features, got {}", FEATURES, sample.features.len()))
+            })?;
+            if sample.label > 1 {
+                return Err(DataError::Schema(format!("label {} is not binary", sample.label)));
+            }
+            features.push(x.map(|v| (v as f64 * ONE as f64).round() as i64));
+            labels.push(sample.label as i64 * ONE);
+        }
+        Ok(Self { features, labels })
+    }
+
+    /// The dataset as samples for the floating-point trainers.
+    pub fn to_samples(&self) -> Vec<Sample> {
+        self.features
+            .iter()
+            .zip(&self.labels)
+            .map(|(x, &label)| Sample {
+                features: x.iter().map(|&v| v as f32 / ONE as f32).collect(),
+                label: (label >= ONE / 2) as usize,
+            })
+            .collect()
+    }
+}
+
+fn task_seed(task: &PoUWTask) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update(task.model_id.as_bytes());
+    h.update(task.dataset_id.as_bytes());
+    h.update(task.challenge);
+    h.finalize().into()
+}
+
+/// Hard sigmoid: `clamp(0.5 + z / 4, 0, 1)`.
+fn sigmoid(z: i64) -> i64 {
+    (ONE / 2 + z / 4).clamp(0, ONE)
+}
+
+/// Class the model predicts for `x`.
+pub fn predict(weights: &Weights, x: &[i64; FEATURES]) -> bool {
+    dot(weights, x) > 0
+}
+
+fn dot(w: &Weights, x: &[i64; FEATURES]) -> i64 {
+    w.iter().zip(x).map(|(w, x)| w * x / ONE).sum()
+}
+
+/// Loss gradient of a single sample at `weights`.
+pub fn sample_gradient(weights: &Weights, x: &[i64; FEATURES], label: i64) -> Weights {
+    let error = sigmoid(dot(weights, x)) - label;
+    let mut gradient = [0i64; FEATURES];
+    for (g, xi) in gradient.iter_mut().zip(x) {
+        *g = error * xi / ONE;
+    }
+    gradient
+}
+
+/// Runs `epochs` full-batch gradient steps (learning rate 0.5) from `weights`.
+pub fn train_epochs(data: &Dataset, weights: Weights, epochs: u32) -> Weights {
+    train_epochs_with(&CpuBackend, data, weights, epochs)
+}
+
+/// [`train_epochs`] with the per-sample gradients computed by `backend`.
+/// An epoch the backend fails on is recomputed on the CPU.
+pub fn train_epochs_with(backend: &dyn GradientBackend, data: &Dataset, mut weights: Weights, epochs: u32) -> Weights {
+    for _ in 0..epochs {
+        let samples = backend.sample_gradients(data, &weights).unwrap_or_else(|e| {
+            tracing::warn!(backend = backend.name(), error = %e, "falling back to CPU for this epoch");
+            CpuBackend.sample_gradients(data, &weights).expect("CPU backend is infallible")
+        });
+        let mut gradient = [0i64; FEATURES];
+        for sample in samples {
+            for (g, s) in gradient.iter_mut().zip(sample) {
+                *g += s;
+            }
+        }
+        for (w, g) in weights.iter_mut().zip(gradient) {
+            *w -= g / data.features.len() as i64 / 2;
+        }
+    }
+    weights
+}
+
+/// Accuracy in basis points (10000 = 100%).
+pub fn accuracy(data: &Dataset, weights: &Weights) -> u32 {
+    let correct = data
+        .features
+        .iter()
+        .zip(&data.labels)
+        .filter(|(x, label)| (dot(weights, x) > 0) == (**label == ONE))
+        .count();
+    (correct * 10_000 / data.features.len()) as u32
+}
+
+pub fn hash_weights(weights: &Weights) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for w in weights {
+        hasher.update(w.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Hex encoding of the raw weights, as stored in solution checkpoints.
+pub fn encode_weights(weights: &Weights) -> String {
+    hex::encode(weights.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<u8>>())
+}
+
+pub fn decode_weights(encoded: &str) -> Option<Weights> {
+    let bytes = hex::decode(encoded).ok()?;
+    if bytes.len() != FEATURES * 8 {
+        return None;
+    }
+    let mut weights = [0i64; FEATURES];
+    for (w, chunk) in weights.iter_mut().zip(bytes.chunks_exact(8)) {
+        *w = i64::from_le_bytes(chunk.try_into().ok()?);
+    }
+    Some(weights)
+}
+
+/// Epochs between consecutive checkpoints for a task.
+pub fn checkpoint_interval(epochs: u32) -> u32 {
+    epochs.div_ceil(MAX_CHECKPOINTS).max(1)
+}
+
+/// Number of epochs covered by checkpoint `index`.
+pub fn segment_len(epochs: u32, index: usize) -> u32 {
+    let interval = checkpoint_interval(epochs);
+    let start = interval * index as u32;
+    interval.min(epochs.saturating_sub(start))
+}
+
+/// Result of training a task from scratch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainingOutcome {
+    pub weights: Weights,
+    pub accuracy: u32,
+    /// Encoded weights after each checkpoint interval; the last entry is the
+    /// final model.
+    pub checkpoints: Vec<String>,
+    /// Per-segment Merkle roots over the sample gradients at the segment's
+    /// starting weights, see [`super::sampling`].
+    pub gradient_roots: Vec<String>,
+}
+
+/// Trains the task's model from zero weights, recording checkpoints.
+pub fn train(task: &PoUWTask) -> TrainingOutcome {
+    train_with(&CpuBackend, task)
+}
+
+/// [`train`] with the per-sample gradients computed by `backend`.
+pub fn train_with(backend: &dyn GradientBackend, task: &PoUWTask) -> TrainingOutcome {
+    let data = Dataset::for_task(task);
+    let mut weights = [0i64; FEATURES];
+    let mut checkpoints = Vec::new();
+    let mut gradient_roots = Vec::new();
+    let mut index = 0;
+    loop {
+        let len = segment_len(task.epochs, index);
+        if len == 0 && index > 0 {
+            break;
+        }
+        gradient_roots.push(data.gradient_root(&weights));
+        weights = train_epochs_with(backend, &data, weights, len);
+        checkpoints.push(encode_weights(&weights));
+        index += 1;
+    }
+    TrainingOutcome { accuracy: accuracy(&data, &weights), weights, checkpoints, gradient_roots }
+}
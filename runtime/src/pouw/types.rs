@@ -16,12 +16,29 @@ pub struct PoUWTask {
     /// Optional SHA-256 hash of the validation dataset stored on DFS.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dataset_hash: Option<String>,
+    /// Optional hex Merkle root over the training samples, see
+    /// `pouw::sampling`. Solvers prove which samples they trained on
+    /// against this root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset_root: Option<String>,
     /// The number of training epochs required.
     pub epochs: u32,
     /// The timestamp when the task was created, to prevent pre-computation.
     pub timestamp: u64,
     /// A random challenge to ensure task uniqueness.
     pub challenge: [u8; 32],
+    /// W3C `traceparent` of the worker span that mined this task, when it
+    /// came from a traced job. Not part of the task commitment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<String>,
+    /// Id of the escrowed job this task trains, see [`crate::job::Job::to_task`].
+    /// The miner is paid the job's reward on top of the block subsidy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<u64>,
+    /// Kind of useful work, see [`crate::pouw::task_types`]. `None` trains
+    /// the reference model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
 }
 
 /// A PoUW Solution, which provides the result of a completed ML task.
@@ -37,6 +54,23 @@ pub struct Solution {
     pub nonce: u64,
     /// The time it took to compute the solution in milliseconds.
     pub computation_time_ms: u64,
+    /// The part of `computation_time_ms` spent training, used to estimate
+    /// throughput when retargeting the task size.
+    #[serde(default)]
+    pub training_time_ms: u64,
+    /// Hex-encoded model weights at each training checkpoint, used by
+    /// `verifier::verify_by_retraining`. The last entry is the final model.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<String>,
+    /// Hex Merkle roots, one per checkpoint segment, over the per-sample
+    /// gradients at that segment's starting weights.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gradient_roots: Vec<String>,
+    /// Hex Groth16 proof that the final checkpoint is the result of
+    /// training the task, attached by solvers built with `zk-proofs` for
+    /// small tasks. See `pouw::zk`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zk_proof: Option<String>,
 }
 
 /// A signed evaluation result from a validator.
@@ -70,7 +104,7 @@ impl Default for ValidatorSelectionConfig {
 }
 
 /// Configuration for PoUW security parameters.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PoUWConfig {
     /// The baseline difficulty target. A lower value is more difficult.
     pub base_difficulty: u32,
@@ -96,8 +130,17 @@ pub type PoUWSolution = Solution;
 impl PoUWTask {
     /// Creates a new PoUWTask with random challenge and current timestamp.
     pub fn new(model_id: String, dataset_id: String, epochs: u32) -> Self {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+        let mut rng = crate::rng::stream(crate::rng::Stream::TaskChallenge);
+        Self::new_with_rng(model_id, dataset_id, epochs, &mut rng)
+    }
+
+    /// Creates a new PoUWTask whose challenge is drawn from `rng`.
+    pub fn new_with_rng<R: rand::Rng>(
+        model_id: String,
+        dataset_id: String,
+        epochs: u32,
+        rng: &mut R,
+    ) -> Self {
         let mut challenge = [0u8; 32];
         rng.fill(&mut challenge);
         Self {
@@ -105,14 +148,25 @@ impl PoUWTask {
             dataset_id,
             model_hash: None,
             dataset_hash: None,
+            dataset_root: None,
             epochs,
             timestamp: chrono::Utc::now().timestamp() as u64,
             challenge,
+            trace: None,
+            job_id: None,
+            kind: None,
         }
     }
 
-    /// Verifies a solution using the PoUW verifier with default configuration.
+    /// Asks for the useful work of task type `kind` instead of training.
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    /// Verifies a solution with the verifier of the task's type, using the
+    /// default configuration.
     pub fn verify(&self, solution: &PoUWSolution, difficulty: u32) -> bool {
-        crate::pouw::verifier::verify(self, solution, difficulty, &PoUWConfig::default())
+        crate::pouw::task_types::verify(self, solution, difficulty, &PoUWConfig::default())
     }
 }
@@ -1,6 +1,10 @@
 //! Implements the PoUW solution verification logic.
 
+use super::training;
 use super::types::{PoUWConfig, Solution, PoUWTask};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
 use sha2::{Digest, Sha256};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -23,16 +27,80 @@ pub fn verify(
     }
     
     // 3. Re-create the hash and check if it meets the difficulty target.
+    let model_hash = match decode_model_hash(&solution.trained_model_hash) {
+        Some(h) => h,
+        None => return false,
+    };
     let task_commitment = create_task_commitment(task);
-    let mut hasher = Sha256::new();
-    hasher.update(solution.trained_model_hash.as_bytes()); // Assuming this is the core output
-    hasher.update(&task_commitment);
-    hasher.update(&solution.nonce.to_le_bytes());
-    let hash: [u8; 32] = hasher.finalize().into();
+    let hash = solution_digest(&model_hash, &task_commitment, solution.nonce);
 
     meets_difficulty(&hash, difficulty)
 }
 
+/// Runs [`verify`] and additionally re-executes `samples` checkpoint
+/// segments of the training run, chosen by a seed the solver cannot pick
+/// independently of its result. Each re-executed segment must reproduce the
+/// next checkpoint exactly, the final checkpoint must hash to the claimed
+/// model, and the claimed accuracy must match the final weights.
+pub fn verify_by_retraining(
+    task: &PoUWTask,
+    solution: &Solution,
+    difficulty: u32,
+    config: &PoUWConfig,
+    samples: usize,
+) -> bool {
+    if !verify(task, solution, difficulty, config) {
+        return false;
+    }
+
+    let segments = task.epochs.div_ceil(training::checkpoint_interval(task.epochs)).max(1) as usize;
+    if solution.checkpoints.len() != segments {
+        return false;
+    }
+    let checkpoints: Option<Vec<training::Weights>> =
+        solution.checkpoints.iter().map(|c| training::decode_weights(c)).collect();
+    let checkpoints = match checkpoints {
+        Some(c) => c,
+        None => return false,
+    };
+    let final_weights = checkpoints.last().expect("at least one checkpoint");
+    if hex::encode(training::hash_weights(final_weights)) != solution.trained_model_hash {
+        return false;
+    }
+
+    let data = training::Dataset::for_task(task);
+    if task.dataset_root.as_ref().is_some_and(|root| *root != data.merkle_root()) {
+        return false;
+    }
+    if training::accuracy(&data, final_weights) != solution.accuracy {
+        return false;
+    }
+
+    let mut seed = Sha256::new();
+    seed.update(create_task_commitment(task));
+    seed.update(solution.trained_model_hash.as_bytes());
+    seed.update(solution.nonce.to_le_bytes());
+    let mut rng = StdRng::from_seed(seed.finalize().into());
+    sample(&mut rng, segments, samples.min(segments)).into_iter().all(|i| {
+        let start = if i == 0 { [0; training::FEATURES] } else { checkpoints[i - 1] };
+        training::train_epochs(&data, start, training::segment_len(task.epochs, i)) == checkpoints[i]
+    })
+}
+
+/// Hash that must meet the difficulty target: model commitment, task
+/// commitment and nonce. Shared by the solver and the verifier.
+pub fn solution_digest(model_hash: &[u8; 32], task_commitment: &[u8; 32], nonce: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(model_hash);
+    hasher.update(task_commitment);
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn decode_model_hash(hex_hash: &str) -> Option<[u8; 32]> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
 /// Checks if a hash meets the given difficulty target.
 /// A lower difficulty value means a more difficult target.
 pub fn meets_difficulty(hash: &[u8; 32], difficulty: u32) -> bool {
@@ -74,8 +142,17 @@ pub fn create_task_commitment(task: &PoUWTask) -> [u8; 32] {
     if let Some(ref h) = task.dataset_hash {
         hasher.update(h.as_bytes());
     }
+    if let Some(ref root) = task.dataset_root {
+        hasher.update(root.as_bytes());
+    }
     hasher.update(task.epochs.to_le_bytes());
     hasher.update(task.timestamp.to_le_bytes());
     hasher.update(task.challenge);
+    if let Some(job_id) = task.job_id {
+        hasher.update(job_id.to_le_bytes());
+    }
+    if let Some(ref kind) = task.kind {
+        hasher.update(kind.as_bytes());
+    }
     hasher.finalize().into()
 } 
\ No newline at end of file
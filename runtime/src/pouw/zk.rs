@@ -0,0 +1,345 @@
+//! Experimental succinct proofs of PoUW training (`zk-proofs` feature).
+//!
+//! A Groth16 circuit over BN254 restates [`training::train_epochs`] in
+//! R1CS: every fixed-point product, truncating division and hard-sigmoid
+//! clamp is constrained exactly, so a proof shows that the final weights are
+//! what the reference trainer computes from zero weights on the task's
+//! dataset. The dataset is the public input, which a light client derives
+//! from the task without training, and checking a proof costs a few pairings
+//! instead of re-executing any epoch.
+//!
+//! The circuit unrolls every epoch, so only tasks of up to [`MAX_EPOCHS`]
+//! epochs can be proven and each epoch count needs its own keys from
+//! [`setup`]. Intermediate values must fit in [`VALUE_BITS`] signed bits;
+//! runs that leave that range cannot be proven and are verified by
+//! re-execution as before.
+
+use super::solver;
+use super::training::{self, Dataset, Weights, FEATURES, ONE, SAMPLES};
+use super::types::{PoUWConfig, PoUWTask, Solution};
+use super::verifier;
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, Proof};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_r1cs_std::select::CondSelectGadget;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use rand::{CryptoRng, RngCore};
+use thiserror::Error;
+
+/// Largest task, in epochs, that can be proven.
+pub const MAX_EPOCHS: u32 = 4;
+/// Signed width every intermediate training value must fit in.
+pub const VALUE_BITS: usize = 62;
+
+#[derive(Debug, Error)]
+pub enum ZkError {
+    #[error("tasks of {0} epochs cannot be proven (1 to {MAX_EPOCHS})")]
+    Unprovable(u32),
+    #[error("keys are for {keys} epochs, the task has {task}")]
+    KeyMismatch { keys: u32, task: u32 },
+    #[error("training left the provable value range")]
+    OutOfRange,
+    #[error("proof system error: {0}")]
+    Snark(String),
+}
+
+/// Key a solver proves with, for tasks of `epochs` epochs.
+#[derive(Clone)]
+pub struct ZkProvingKey {
+    pub epochs: u32,
+    key: ark_groth16::ProvingKey<Bn254>,
+}
+
+/// Key a light client verifies with, for tasks of `epochs` epochs.
+#[derive(Clone)]
+pub struct ZkVerifyingKey {
+    pub epochs: u32,
+    key: ark_groth16::VerifyingKey<Bn254>,
+}
+
+impl ZkVerifyingKey {
+    /// Hex encoding for distribution to light clients.
+    pub fn encode(&self) -> String {
+        let mut bytes = self.epochs.to_le_bytes().to_vec();
+        self.key.serialize_compressed(&mut bytes).expect("writing to a Vec cannot fail");
+        hex::encode(bytes)
+    }
+
+    pub fn decode(encoded: &str) -> Option<Self> {
+        let bytes = hex::decode(encoded).ok()?;
+        if bytes.len() < 4 {
+            return None;
+        }
+        let (epochs, key) = bytes.split_at(4);
+        Some(Self {
+            epochs: u32::from_le_bytes(epochs.try_into().ok()?),
+            key: ark_groth16::VerifyingKey::deserialize_compressed(key).ok()?,
+        })
+    }
+}
+
+/// Whether tasks of `epochs` epochs are small enough to prove.
+pub fn provable(epochs: u32) -> bool {
+    (1..=MAX_EPOCHS).contains(&epochs)
+}
+
+/// Generates keys for tasks of `epochs` epochs. Whoever knows `rng`'s
+/// output can forge proofs, so keys are only as trustworthy as the setup.
+pub fn setup<R: RngCore + CryptoRng>(epochs: u32, rng: &mut R) -> Result<(ZkProvingKey, ZkVerifyingKey), ZkError> {
+    if !provable(epochs) {
+        return Err(ZkError::Unprovable(epochs));
+    }
+    // The constraints do not depend on the data, only on its shape.
+    let blank = Dataset { features: vec![[0; FEATURES]; SAMPLES], labels: vec![0; SAMPLES] };
+    let circuit = TrainingCircuit { data: blank, start: [0; FEATURES], epochs };
+    let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(circuit, rng).map_err(|e| ZkError::Snark(e.to_string()))?;
+    Ok((ZkProvingKey { epochs, key: pk }, ZkVerifyingKey { epochs, key: vk }))
+}
+
+/// Proves that training `task` from zero weights yields its final weights.
+/// Returns the hex proof for [`Solution::zk_proof`].
+pub fn prove<R: RngCore + CryptoRng>(pk: &ZkProvingKey, task: &PoUWTask, rng: &mut R) -> Result<String, ZkError> {
+    if !provable(task.epochs) {
+        return Err(ZkError::Unprovable(task.epochs));
+    }
+    if pk.epochs != task.epochs {
+        return Err(ZkError::KeyMismatch { keys: pk.epochs, task: task.epochs });
+    }
+    let circuit = TrainingCircuit { data: Dataset::for_task(task), start: [0; FEATURES], epochs: task.epochs };
+    let proof = Groth16::<Bn254>::prove(&pk.key, circuit, rng).map_err(|e| match e {
+        SynthesisError::Unsatisfiable => ZkError::OutOfRange,
+        e => ZkError::Snark(e.to_string()),
+    })?;
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).map_err(|e| ZkError::Snark(e.to_string()))?;
+    Ok(hex::encode(bytes))
+}
+
+/// [`solver::solve`] that also attaches a proof when the task is small
+/// enough for `pk`. Solutions that cannot be proven are returned without one.
+pub fn solve_with_proof<R: RngCore + CryptoRng>(
+    pk: &ZkProvingKey,
+    task: &PoUWTask,
+    difficulty: u32,
+    rng: &mut R,
+) -> Solution {
+    let mut solution = solver::solve(task, difficulty);
+    match prove(pk, task, rng) {
+        Ok(proof) => solution.zk_proof = Some(proof),
+        Err(e) => tracing::debug!(error = %e, "solution left without a proof"),
+    }
+    solution
+}
+
+/// Light-client counterpart of [`verifier::verify_by_retraining`]: runs
+/// [`verifier::verify`], checks that the final checkpoint is the claimed
+/// model with the claimed accuracy, and checks the solution's proof that
+/// the checkpoint is the result of training the task. No epoch is re-run.
+pub fn verify_succinct(
+    task: &PoUWTask,
+    solution: &Solution,
+    difficulty: u32,
+    config: &PoUWConfig,
+    vk: &ZkVerifyingKey,
+) -> bool {
+    if vk.epochs != task.epochs || !verifier::verify(task, solution, difficulty, config) {
+        return false;
+    }
+    let Some(proof) = solution.zk_proof.as_deref().and_then(decode_proof) else {
+        return false;
+    };
+    let Some(final_weights) = solution.checkpoints.last().and_then(|c| training::decode_weights(c)) else {
+        return false;
+    };
+    if hex::encode(training::hash_weights(&final_weights)) != solution.trained_model_hash {
+        return false;
+    }
+    let data = Dataset::for_task(task);
+    if task.dataset_root.as_ref().is_some_and(|root| *root != data.merkle_root()) {
+        return false;
+    }
+    if training::accuracy(&data, &final_weights) != solution.accuracy {
+        return false;
+    }
+    let inputs = public_inputs(&data, &[0; FEATURES], &final_weights);
+    Groth16::<Bn254>::verify(&vk.key, &inputs, &proof).unwrap_or(false)
+}
+
+fn decode_proof(encoded: &str) -> Option<Proof<Bn254>> {
+    Proof::deserialize_compressed(hex::decode(encoded).ok()?.as_slice()).ok()
+}
+
+/// Public inputs in allocation order: the features, sample by sample, then
+/// the starting and the final weights.
+fn public_inputs(data: &Dataset, start: &Weights, end: &Weights) -> Vec<Fr> {
+    data.features.iter().flatten().chain(start).chain(end).map(|v| field(*v)).collect()
+}
+
+fn field(value: i64) -> Fr {
+    if value < 0 {
+        -Fr::from(value.unsigned_abs())
+    } else {
+        Fr::from(value as u64)
+    }
+}
+
+/// `epochs` epochs of training from `start` on `data`. Labels are derived
+/// from the features inside the circuit, as [`Dataset::for_task`] does.
+struct TrainingCircuit {
+    data: Dataset,
+    start: Weights,
+    epochs: u32,
+}
+
+impl ConstraintSynthesizer<Fr> for TrainingCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let g = Gadgets { cs: cs.clone() };
+
+        let mut samples = Vec::with_capacity(self.data.features.len());
+        for features in &self.data.features {
+            let x = features.iter().map(|v| g.input(*v)).collect::<Result<Vec<_>, _>>()?;
+            // The label is ONE exactly when the features sum above zero.
+            let mut sum = g.constant(-1);
+            for xi in &x {
+                sum = g.add(&sum, xi)?;
+            }
+            let positive = g.non_negative(&sum)?;
+            let label = Num {
+                value: if sum.value >= 0 { ONE } else { 0 },
+                var: FpVar::from(positive) * field(ONE),
+            };
+            samples.push((x, label));
+        }
+
+        let mut weights = self.start.iter().map(|v| g.input(*v)).collect::<Result<Vec<_>, _>>()?;
+        let count = samples.len() as i64;
+        for _ in 0..self.epochs {
+            let mut gradient = vec![g.constant(0); FEATURES];
+            for (x, label) in &samples {
+                let mut z = g.constant(0);
+                for (w, xi) in weights.iter().zip(x) {
+                    z = g.add(&z, &g.div(&g.mul(w, xi)?, ONE)?)?;
+                }
+                let error = g.sub(&g.sigmoid(&z)?, label)?;
+                for (gi, xi) in gradient.iter_mut().zip(x) {
+                    *gi = g.add(gi, &g.div(&g.mul(&error, xi)?, ONE)?)?;
+                }
+            }
+            for (w, gi) in weights.iter_mut().zip(&gradient) {
+                *w = g.sub(w, &g.div(&g.div(gi, count)?, 2)?)?;
+            }
+        }
+
+        for w in &weights {
+            FpVar::new_input(cs.clone(), || Ok(field(w.value)))?.enforce_equal(&w.var)?;
+        }
+        Ok(())
+    }
+}
+
+/// A circuit variable together with the `i64` the native trainer computes
+/// for it.
+#[derive(Clone)]
+struct Num {
+    var: FpVar<Fr>,
+    value: i64,
+}
+
+/// Fixed-point training arithmetic in R1CS. Operations whose native result
+/// would overflow fail with [`SynthesisError::Unsatisfiable`].
+struct Gadgets {
+    cs: ConstraintSystemRef<Fr>,
+}
+
+impl Gadgets {
+    fn constant(&self, value: i64) -> Num {
+        Num { var: FpVar::constant(field(value)), value }
+    }
+
+    fn input(&self, value: i64) -> Result<Num, SynthesisError> {
+        Ok(Num { var: FpVar::new_input(self.cs.clone(), || Ok(field(value)))?, value })
+    }
+
+    fn witness(&self, value: i64) -> Result<Num, SynthesisError> {
+        Ok(Num { var: FpVar::new_witness(self.cs.clone(), || Ok(field(value)))?, value })
+    }
+
+    fn add(&self, a: &Num, b: &Num) -> Result<Num, SynthesisError> {
+        let value = a.value.checked_add(b.value).ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(Num { var: &a.var + &b.var, value })
+    }
+
+    fn sub(&self, a: &Num, b: &Num) -> Result<Num, SynthesisError> {
+        let value = a.value.checked_sub(b.value).ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(Num { var: &a.var - &b.var, value })
+    }
+
+    fn mul(&self, a: &Num, b: &Num) -> Result<Num, SynthesisError> {
+        let value = a.value.checked_mul(b.value).ok_or(SynthesisError::Unsatisfiable)?;
+        Ok(Num { var: &a.var * &b.var, value })
+    }
+
+    /// Constrains `n` to [`VALUE_BITS`] signed bits and returns whether it
+    /// is non-negative.
+    fn non_negative(&self, n: &Num) -> Result<Boolean<Fr>, SynthesisError> {
+        self.non_negative_in(n, VALUE_BITS)
+    }
+
+    /// [`non_negative`](Self::non_negative) for `width` signed bits.
+    fn non_negative_in(&self, n: &Num, width: usize) -> Result<Boolean<Fr>, SynthesisError> {
+        let offset = 1i64 << (width - 1);
+        if n.value < -offset || n.value >= offset {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        // n + 2^(width - 1) in `width` bits; the top bit is the sign.
+        let shifted = (n.value + offset) as u64;
+        let mut bits = Vec::with_capacity(width);
+        let mut sum = FpVar::zero();
+        let mut power = Fr::from(1u64);
+        for i in 0..width {
+            let bit = Boolean::new_witness(self.cs.clone(), || Ok((shifted >> i) & 1 == 1))?;
+            sum += FpVar::from(bit.clone()) * power;
+            power = power + power;
+            bits.push(bit);
+        }
+        sum.enforce_equal(&(&n.var + field(offset)))?;
+        Ok(bits.pop().expect("width > 0"))
+    }
+
+    /// `n / d` rounded toward zero, like Rust's `/`, for a constant `d > 0`.
+    fn div(&self, n: &Num, d: i64) -> Result<Num, SynthesisError> {
+        let q = self.witness(n.value / d)?;
+        let r = self.witness(n.value % d)?;
+        (&q.var * field(d) + &r.var).enforce_equal(&n.var)?;
+        // Bounding q and n rules out solutions that wrap the field.
+        self.non_negative(&q)?;
+        let n_non_negative = self.non_negative(n)?;
+        // The remainder takes the sign of n and is smaller than d.
+        let magnitude = Num {
+            var: FpVar::conditionally_select(&n_non_negative, &r.var, &r.var.negate()?)?,
+            value: r.value.abs(),
+        };
+        let width = (i64::BITS - d.leading_zeros()) as usize + 1;
+        self.non_negative_in(&magnitude, width)?.enforce_equal(&Boolean::constant(true))?;
+        let slack = self.sub(&self.constant(d - 1), &magnitude)?;
+        self.non_negative_in(&slack, width)?.enforce_equal(&Boolean::constant(true))?;
+        Ok(q)
+    }
+
+    /// [`training`]'s hard sigmoid, `clamp(ONE / 2 + z / 4, 0, ONE)`.
+    fn sigmoid(&self, z: &Num) -> Result<Num, SynthesisError> {
+        let t = self.add(&self.constant(ONE / 2), &self.div(z, 4)?)?;
+        let above_zero = self.non_negative(&t)?;
+        let below_one = self.non_negative(&self.sub(&self.constant(ONE), &t)?)?;
+        let capped = FpVar::conditionally_select(&below_one, &t.var, &FpVar::constant(field(ONE)))?;
+        Ok(Num {
+            var: FpVar::conditionally_select(&above_zero, &capped, &FpVar::zero())?,
+            value: t.value.clamp(0, ONE),
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use crate::rl::RlError;
+use serde::{Deserialize, Serialize};
+
+/// Where the environment code comes from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum EnvironmentRuntime {
+    /// Gym-compatible environment compiled to WASM, addressed by module hash.
+    Wasm { module_hash: String },
+    /// Gym-compatible environment packaged as an OCI image.
+    Container { image: String, digest: String },
+    /// Environment shipped with the node, referenced by name.
+    Builtin { name: String },
+}
+
+/// Gym-style description of an environment that workers must instantiate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnvironmentSpec {
+    pub id: String,
+    pub runtime: EnvironmentRuntime,
+    pub observation_dim: usize,
+    /// Size of the discrete action space.
+    pub action_count: usize,
+    pub max_episode_steps: u32,
+}
+
+impl EnvironmentSpec {
+    /// Instantiates the environment locally. Only builtin environments can be
+    /// executed in-process; WASM and container runtimes are launched by the
+    /// worker sandbox.
+    pub fn instantiate(&self) -> Result<Box<dyn Environment>, RlError> {
+        match &self.runtime {
+            EnvironmentRuntime::Builtin { name } if name == CorridorEnv::NAME => {
+                Ok(Box::new(CorridorEnv::new(self.observation_dim)))
+            }
+            other => Err(RlError::UnsupportedRuntime(format!("{:?}", other))),
+        }
+    }
+}
+
+/// Outcome of a single environment step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepResult {
+    pub observation: Vec<f32>,
+    pub reward: f32,
+    pub done: bool,
+}
+
+/// Gym-compatible environment interface. Implementations must be fully
+/// deterministic given the seed passed to `reset`.
+pub trait Environment: Send {
+    fn observation_dim(&self) -> usize;
+    fn action_count(&self) -> usize;
+    fn reset(&mut self, seed: u64) -> Vec<f32>;
+    fn step(&mut self, action: usize) -> Result<StepResult, RlError>;
+}
+
+/// One-dimensional corridor: the agent starts at a seeded position and is
+/// rewarded for reaching the right-hand end. Observation is a one-hot
+/// encoding of the position; actions are 0 = left, 1 = right.
+#[derive(Debug, Clone)]
+pub struct CorridorEnv {
+    length: usize,
+    position: usize,
+}
+
+impl CorridorEnv {
+    pub const NAME: &'static str = "corridor";
+
+    pub fn new(length: usize) -> Self {
+        Self { length: length.max(2), position: 0 }
+    }
+
+    /// Spec that references this builtin environment.
+    pub fn spec(length: usize, max_episode_steps: u32) -> EnvironmentSpec {
+        EnvironmentSpec {
+            id: format!("{}-{}", Self::NAME, length),
+            runtime: EnvironmentRuntime::Builtin { name: Self::NAME.to_string() },
+            observation_dim: length,
+            action_count: 2,
+            max_episode_steps,
+        }
+    }
+
+    fn observation(&self) -> Vec<f32> {
+        let mut obs = vec![0.0; self.length];
+        obs[self.position] = 1.0;
+        obs
+    }
+}
+
+impl Environment for CorridorEnv {
+    fn observation_dim(&self) -> usize {
+        self.length
+    }
+
+    fn action_count(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self, seed: u64) -> Vec<f32> {
+        self.position = (seed % (self.length as u64 - 1)) as usize;
+        self.observation()
+    }
+
+    fn step(&mut self, action: usize) -> Result<StepResult, RlError> {
+        match action {
+            0 => self.position = self.position.saturating_sub(1),
+            1 => self.position = (self.position + 1).min(self.length - 1),
+            _ => return Err(RlError::InvalidAction { action, actions: 2 }),
+        }
+        let done = self.position == self.length - 1;
+        Ok(StepResult {
+            observation: self.observation(),
+            reward: if done { 1.0 } else { -0.01 },
+            done,
+        })
+    }
+}
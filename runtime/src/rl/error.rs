@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+/// Errors raised by RL jobs.
+#[derive(Debug, Error)]
+pub enum RlError {
+    #[error("Unsupported environment runtime: {0}")]
+    UnsupportedRuntime(String),
+    #[error("Dimension mismatch: expected {expected}, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+    #[error("Policy needs an observation and an action, got {observation_dim}x{action_count}")]
+    EmptyPolicy { observation_dim: usize, action_count: usize },
+    #[error("Invalid action {action} for action space of {actions}")]
+    InvalidAction { action: usize, actions: usize },
+    #[error("Trajectory stream closed")]
+    StreamClosed,
+}
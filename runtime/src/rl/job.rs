@@ -0,0 +1,48 @@
+use crate::rl::EnvironmentSpec;
+use crate::rng::{self, Stream};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// An RL training job posted to the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RlJob {
+    pub job_id: u64,
+    pub environment: EnvironmentSpec,
+    /// Episodes each rollout worker plays per learner round.
+    pub episodes_per_round: u32,
+    /// Exploration rate workers must use; verifiers replay with the same value.
+    pub epsilon: f32,
+    /// Random challenge that determines every episode seed.
+    pub challenge: [u8; 32],
+    pub reward: u64,
+}
+
+impl RlJob {
+    pub fn new(job_id: u64, environment: EnvironmentSpec, episodes_per_round: u32, reward: u64) -> Self {
+        Self {
+            job_id,
+            environment,
+            episodes_per_round,
+            epsilon: 0.1,
+            challenge: rng::stream(Stream::TaskChallenge).gen(),
+            reward,
+        }
+    }
+
+    /// Deterministic episode seeds for a worker in a round. Workers cannot
+    /// choose favourable seeds, and verifiers can recompute them.
+    pub fn episode_seeds(&self, round: u32, worker_id: &str) -> Vec<u64> {
+        (0..self.episodes_per_round)
+            .map(|episode| {
+                let mut hasher = Sha256::new();
+                hasher.update(self.challenge);
+                hasher.update(round.to_le_bytes());
+                hasher.update(worker_id.as_bytes());
+                hasher.update(episode.to_le_bytes());
+                let digest = hasher.finalize();
+                u64::from_le_bytes(digest[..8].try_into().expect("digest has 8 bytes"))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,21 @@
+//! Reinforcement-learning environment jobs.
+//!
+//! An RL job pairs an environment specification with a policy. Rollout
+//! workers play episodes against their own copy of the environment and stream
+//! the resulting trajectories to a learner node, which updates the policy.
+//! Every episode is driven by a seed derived from the job challenge, so
+//! validators can verify work PoUW-style by replaying a sample of episodes.
+
+pub mod env;
+pub mod error;
+pub mod job;
+pub mod policy;
+pub mod rollout;
+pub mod verification;
+
+pub use env::{CorridorEnv, Environment, EnvironmentRuntime, EnvironmentSpec, StepResult};
+pub use error::RlError;
+pub use job::RlJob;
+pub use policy::LinearPolicy;
+pub use rollout::{Learner, RolloutWorker, Trajectory, Transition};
+pub use verification::{replay_trajectory, verify_rollouts};
@@ -0,0 +1,113 @@
+use crate::rl::RlError;
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Linear action-value policy: one weight row per discrete action.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinearPolicy {
+    pub observation_dim: usize,
+    pub action_count: usize,
+    /// Row-major `action_count x observation_dim` weights.
+    pub weights: Vec<f32>,
+}
+
+impl LinearPolicy {
+    /// A zero-initialised policy; both dimensions must be non-zero.
+    pub fn new(observation_dim: usize, action_count: usize) -> Result<Self, RlError> {
+        let policy = Self { observation_dim, action_count, weights: Vec::new() };
+        let len = policy.weight_count()?;
+        Ok(Self { weights: vec![0.0; len], ..policy })
+    }
+
+    /// Number of weights the dimensions call for.
+    fn weight_count(&self) -> Result<usize, RlError> {
+        let empty = RlError::EmptyPolicy {
+            observation_dim: self.observation_dim,
+            action_count: self.action_count,
+        };
+        if self.observation_dim == 0 || self.action_count == 0 {
+            return Err(empty);
+        }
+        self.observation_dim.checked_mul(self.action_count).ok_or(empty)
+    }
+
+    /// Checks the policy is well formed, since a deserialized one may not
+    /// be, and that `observation` fits it.
+    fn check(&self, observation: &[f32]) -> Result<(), RlError> {
+        let expected = self.weight_count()?;
+        if self.weights.len() != expected {
+            return Err(RlError::DimensionMismatch { expected, actual: self.weights.len() });
+        }
+        if observation.len() != self.observation_dim {
+            return Err(RlError::DimensionMismatch {
+                expected: self.observation_dim,
+                actual: observation.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Score for each action given an observation.
+    pub fn scores(&self, observation: &[f32]) -> Result<Vec<f32>, RlError> {
+        self.check(observation)?;
+        Ok(self
+            .weights
+            .chunks(self.observation_dim)
+            .map(|row| row.iter().zip(observation).map(|(w, o)| w * o).sum())
+            .collect())
+    }
+
+    /// Epsilon-greedy action selection. All randomness comes from `rng` so an
+    /// episode can be replayed exactly from its seed.
+    pub fn act(&self, observation: &[f32], epsilon: f32, rng: &mut StdRng) -> Result<usize, RlError> {
+        let scores = self.scores(observation)?;
+        if rng.gen::<f32>() < epsilon {
+            return Ok(rng.gen_range(0..self.action_count));
+        }
+        let mut best = 0;
+        for (i, s) in scores.iter().enumerate() {
+            if *s > scores[best] {
+                best = i;
+            }
+        }
+        Ok(best)
+    }
+
+    /// Nudges the weights of taken actions towards the observed return minus
+    /// a baseline (REINFORCE-style update for a linear scorer).
+    pub fn reinforce(
+        &mut self,
+        observation: &[f32],
+        action: usize,
+        advantage: f32,
+        learning_rate: f32,
+    ) -> Result<(), RlError> {
+        self.check_step(observation, action)?;
+        let row = &mut self.weights[action * self.observation_dim..(action + 1) * self.observation_dim];
+        for (w, o) in row.iter_mut().zip(observation) {
+            *w += learning_rate * advantage * o;
+        }
+        Ok(())
+    }
+
+    /// Checks `action` taken on `observation` is one the policy scores.
+    pub fn check_step(&self, observation: &[f32], action: usize) -> Result<(), RlError> {
+        self.check(observation)?;
+        if action >= self.action_count {
+            return Err(RlError::InvalidAction { action, actions: self.action_count });
+        }
+        Ok(())
+    }
+
+    /// Commitment to the exact policy parameters used for a rollout.
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update((self.observation_dim as u64).to_le_bytes());
+        hasher.update((self.action_count as u64).to_le_bytes());
+        for w in &self.weights {
+            hasher.update(w.to_le_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::rl::{Environment, LinearPolicy, RlError};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+/// A single (observation, action, reward) step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub observation: Vec<f32>,
+    pub action: usize,
+    pub reward: f32,
+}
+
+/// A complete episode produced by a rollout worker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trajectory {
+    pub worker_id: String,
+    pub episode_seed: u64,
+    /// Hash of the policy that generated the episode.
+    pub policy_hash: String,
+    pub transitions: Vec<Transition>,
+    pub total_reward: f32,
+}
+
+/// Plays episodes against a local environment instance.
+#[derive(Debug, Clone)]
+pub struct RolloutWorker {
+    pub worker_id: String,
+    /// Exploration rate used when selecting actions.
+    pub epsilon: f32,
+    pub max_episode_steps: u32,
+}
+
+impl RolloutWorker {
+    pub fn new(worker_id: &str, epsilon: f32, max_episode_steps: u32) -> Self {
+        Self { worker_id: worker_id.to_string(), epsilon, max_episode_steps }
+    }
+
+    /// Runs one episode. The environment reset and the exploration RNG are
+    /// both seeded from `seed`, making the episode reproducible.
+    pub fn run_episode(
+        &self,
+        env: &mut dyn Environment,
+        policy: &LinearPolicy,
+        seed: u64,
+    ) -> Result<Trajectory, RlError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut observation = env.reset(seed);
+        let mut transitions = Vec::new();
+        let mut total_reward = 0.0;
+
+        for _ in 0..self.max_episode_steps {
+            let action = policy.act(&observation, self.epsilon, &mut rng)?;
+            let step = env.step(action)?;
+            total_reward += step.reward;
+            transitions.push(Transition { observation, action, reward: step.reward });
+            observation = step.observation;
+            if step.done {
+                break;
+            }
+        }
+
+        Ok(Trajectory {
+            worker_id: self.worker_id.clone(),
+            episode_seed: seed,
+            policy_hash: policy.hash(),
+            transitions,
+            total_reward,
+        })
+    }
+
+    /// Runs an episode per seed and streams each trajectory to the learner as
+    /// soon as it completes.
+    pub async fn stream(
+        &self,
+        env: &mut dyn Environment,
+        policy: &LinearPolicy,
+        seeds: &[u64],
+        sink: &mpsc::Sender<Trajectory>,
+    ) -> Result<(), RlError> {
+        for seed in seeds {
+            let trajectory = self.run_episode(env, policy, *seed)?;
+            sink.send(trajectory).await.map_err(|_| RlError::StreamClosed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Learner node: consumes trajectories and updates the shared policy.
+#[derive(Debug, Clone)]
+pub struct Learner {
+    pub policy: LinearPolicy,
+    pub learning_rate: f32,
+    pub discount: f32,
+    buffer: Vec<Trajectory>,
+}
+
+impl Learner {
+    pub fn new(policy: LinearPolicy, learning_rate: f32, discount: f32) -> Self {
+        Self { policy, learning_rate, discount, buffer: Vec::new() }
+    }
+
+    /// Receives up to `count` trajectories from the stream. Trajectories
+    /// produced by a stale policy are discarded.
+    pub async fn collect(&mut self, stream: &mut mpsc::Receiver<Trajectory>, count: usize) -> usize {
+        let current = self.policy.hash();
+        let mut accepted = 0;
+        while accepted < count {
+            match stream.recv().await {
+                Some(t) if t.policy_hash == current => {
+                    self.buffer.push(t);
+                    accepted += 1;
+                }
+                Some(_) => continue,
+                None => break,
+            }
+        }
+        accepted
+    }
+
+    /// Applies one policy update over all buffered trajectories and clears
+    /// the buffer. Returns the mean episode reward of the batch. A batch with
+    /// a step the policy cannot score is dropped without updating anything.
+    pub fn update(&mut self) -> Result<Option<f32>, RlError> {
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let batch = std::mem::take(&mut self.buffer);
+        for step in batch.iter().flat_map(|t| &t.transitions) {
+            self.policy.check_step(&step.observation, step.action)?;
+        }
+        let mean_reward = batch.iter().map(|t| t.total_reward).sum::<f32>() / batch.len() as f32;
+
+        for trajectory in batch {
+            let mut ret = 0.0;
+            for step in trajectory.transitions.iter().rev() {
+                ret = step.reward + self.discount * ret;
+                self.policy.reinforce(&step.observation, step.action, ret, self.learning_rate)?;
+            }
+        }
+        Ok(Some(mean_reward))
+    }
+}
@@ -0,0 +1,54 @@
+//! PoUW-style verification of rollouts by replaying seeded episodes.
+
+use crate::rl::{LinearPolicy, RlError, RlJob, RolloutWorker, Trajectory};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// Replays a trajectory from its seed and checks it matches exactly.
+pub fn replay_trajectory(
+    job: &RlJob,
+    policy: &LinearPolicy,
+    trajectory: &Trajectory,
+) -> Result<bool, RlError> {
+    if trajectory.policy_hash != policy.hash() {
+        return Ok(false);
+    }
+    let mut env = job.environment.instantiate()?;
+    let worker = RolloutWorker::new(
+        &trajectory.worker_id,
+        job.epsilon,
+        job.environment.max_episode_steps,
+    );
+    let replayed = worker.run_episode(env.as_mut(), policy, trajectory.episode_seed)?;
+    Ok(&replayed == trajectory)
+}
+
+/// Verifies a worker's submitted rollouts for a round.
+///
+/// All trajectories must use the seeds assigned by the job challenge; a
+/// random sample of `sample_size` (chosen from `selection_seed`, e.g. a later
+/// block hash) is replayed in full.
+pub fn verify_rollouts(
+    job: &RlJob,
+    policy: &LinearPolicy,
+    round: u32,
+    worker_id: &str,
+    trajectories: &[Trajectory],
+    sample_size: usize,
+    selection_seed: u64,
+) -> Result<bool, RlError> {
+    let mut expected = job.episode_seeds(round, worker_id);
+    let mut submitted: Vec<u64> = trajectories.iter().map(|t| t.episode_seed).collect();
+    expected.sort_unstable();
+    submitted.sort_unstable();
+    if expected != submitted || trajectories.iter().any(|t| t.worker_id != worker_id) {
+        return Ok(false);
+    }
+
+    let mut rng = StdRng::seed_from_u64(selection_seed);
+    for trajectory in trajectories.choose_multiple(&mut rng, sample_size) {
+        if !replay_trajectory(job, policy, trajectory)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
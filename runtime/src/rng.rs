@@ -0,0 +1,85 @@
+//! Seedable randomness for the node's non-cryptographic choices.
+//!
+//! Relay routes, the source a chunk is fetched from and the challenges of
+//! fresh tasks (and with them the synthetic datasets derived from those
+//! challenges) are drawn from per-subsystem [`Stream`]s. With no seed
+//! installed every draw is seeded from the OS. Once a seed is [`install`]ed
+//! the n-th draw of a stream is always the same, so a node, a test or a
+//! simulation replays exactly; streams are independent, so adding draws to
+//! one subsystem does not shift another.
+//!
+//! Validator and juror selection are not drawn from here: they are seeded
+//! by VRF outputs every node must agree on. Key material and encryption
+//! nonces always come from the OS.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+/// A subsystem drawing random numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stream {
+    /// Relays of onion routes.
+    PeerSelection,
+    /// The peer a chunk is requested from among equally good ones.
+    ChunkScheduling,
+    /// Challenges of new PoUW and RL tasks, which seed their synthetic data.
+    TaskChallenge,
+}
+
+impl Stream {
+    fn name(self) -> &'static str {
+        match self {
+            Stream::PeerSelection => "peer_selection",
+            Stream::ChunkScheduling => "chunk_scheduling",
+            Stream::TaskChallenge => "task_challenge",
+        }
+    }
+}
+
+/// The installed seed and the draws made from each stream since.
+struct Seeded {
+    seed: u64,
+    draws: BTreeMap<Stream, u64>,
+}
+
+static SEEDED: Mutex<Option<Seeded>> = Mutex::new(None);
+
+/// Seeds every stream from `seed`, restarting them, or back from the OS
+/// with `None`.
+pub fn install(seed: Option<u64>) {
+    *SEEDED.lock().unwrap_or_else(PoisonError::into_inner) =
+        seed.map(|seed| Seeded { seed, draws: BTreeMap::new() });
+}
+
+/// The installed seed, if any.
+pub fn seed() -> Option<u64> {
+    SEEDED.lock().unwrap_or_else(PoisonError::into_inner).as_ref().map(|s| s.seed)
+}
+
+/// A generator for the next draw of `stream`.
+pub fn stream(stream: Stream) -> StdRng {
+    let mut seeded = SEEDED.lock().unwrap_or_else(PoisonError::into_inner);
+    match seeded.as_mut() {
+        Some(seeded) => {
+            let draw = seeded.draws.entry(stream).or_insert(0);
+            let rng = StdRng::from_seed(derive(seeded.seed, stream, *draw));
+            *draw += 1;
+            rng
+        }
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Seed of the `draw`-th generator of `stream` under `seed`.
+pub fn derive(seed: u64, stream: Stream, draw: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    hasher.update(stream.name().as_bytes());
+    hasher.update(draw.to_le_bytes());
+    hasher.finalize().into()
+}
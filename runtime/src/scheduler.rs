@@ -0,0 +1,261 @@
+//! Job scheduling with worker liveness tracking.
+//!
+//! Workers broadcast signed [`Heartbeat`]s at a fixed interval. The
+//! [`JobScheduler`] remembers when each worker was last heard from; a worker
+//! that misses `max_missed` heartbeats is taken offline, loses reputation and
+//! has its jobs reassigned to live workers.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use thiserror::Error;
+
+#[cfg(feature = "p2p")]
+use crate::network::NetworkMessage;
+#[cfg(feature = "p2p")]
+use crate::p2p_service::{P2PError, P2PHandle};
+
+/// Gossip topic carrying worker heartbeats.
+pub const HEARTBEAT_TOPIC: &str = "worker_heartbeats";
+
+const HEARTBEAT_DOMAIN: &[u8] = b"bcai-heartbeat-v1";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SchedulerError {
+    #[error("invalid heartbeat signature from {0}")]
+    InvalidSignature(String),
+    #[error("stale heartbeat from {node}: {timestamp} <= {last}")]
+    Stale { node: String, timestamp: u64, last: u64 },
+    #[error("unknown worker: {0}")]
+    UnknownWorker(String),
+    #[error("worker {0} is offline")]
+    Offline(String),
+}
+
+/// A worker's periodic proof of life.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// Hex-encoded ed25519 public key of the worker.
+    pub node_id: String,
+    /// Worker clock in unix seconds; must increase between heartbeats.
+    pub timestamp: u64,
+    /// Jobs the worker is currently running.
+    pub active_jobs: Vec<u64>,
+    pub signature: Vec<u8>,
+}
+
+impl Heartbeat {
+    pub fn sign(key: &SigningKey, timestamp: u64, active_jobs: Vec<u64>) -> Self {
+        let node_id = hex::encode(key.verifying_key().to_bytes());
+        let mut heartbeat = Self { node_id, timestamp, active_jobs, signature: Vec::new() };
+        heartbeat.signature = key.sign(&heartbeat.message()).to_bytes().to_vec();
+        heartbeat
+    }
+
+    fn message(&self) -> Vec<u8> {
+        let mut msg = HEARTBEAT_DOMAIN.to_vec();
+        msg.extend_from_slice(self.node_id.as_bytes());
+        msg.extend_from_slice(&self.timestamp.to_le_bytes());
+        for job in &self.active_jobs {
+            msg.extend_from_slice(&job.to_le_bytes());
+        }
+        msg
+    }
+
+    pub fn verify(&self) -> bool {
+        let Ok(bytes) = hex::decode(&self.node_id) else { return false };
+        let Ok(bytes) = <[u8; 32]>::try_from(bytes.as_slice()) else { return false };
+        let Ok(key) = VerifyingKey::from_bytes(&bytes) else { return false };
+        let Ok(signature) = Signature::from_slice(&self.signature) else { return false };
+        key.verify(&self.message(), &signature).is_ok()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    /// Seconds between heartbeats from a worker.
+    pub interval_secs: u64,
+    /// Missed heartbeats after which a worker is considered offline.
+    pub max_missed: u32,
+    /// Reputation lost each time a worker goes offline.
+    pub reputation_penalty: i32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self { interval_secs: 15, max_missed: 3, reputation_penalty: 5 }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct WorkerStatus {
+    /// Local time the last heartbeat was received, not the worker's clock.
+    last_seen: u64,
+    last_timestamp: u64,
+    online: bool,
+    reputation: i32,
+}
+
+/// A job moved off a worker that stopped sending heartbeats. `to` is `None`
+/// when no live worker was available; the job then waits in the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reassignment {
+    pub job_id: u64,
+    pub from: String,
+    pub to: Option<String>,
+}
+
+/// Assigns jobs to workers and reassigns them when workers go silent.
+#[derive(Debug, Clone, Default)]
+pub struct JobScheduler {
+    config: HeartbeatConfig,
+    workers: BTreeMap<String, WorkerStatus>,
+    assignments: BTreeMap<u64, String>,
+    pending: VecDeque<u64>,
+}
+
+impl JobScheduler {
+    pub fn new(config: HeartbeatConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    /// Record a heartbeat received at local time `now`. Unknown workers are
+    /// registered; offline ones come back online.
+    pub fn record_heartbeat(&mut self, heartbeat: &Heartbeat, now: u64) -> Result<(), SchedulerError> {
+        if !heartbeat.verify() {
+            return Err(SchedulerError::InvalidSignature(heartbeat.node_id.clone()));
+        }
+        if let Some(status) = self.workers.get(&heartbeat.node_id) {
+            if heartbeat.timestamp <= status.last_timestamp {
+                return Err(SchedulerError::Stale {
+                    node: heartbeat.node_id.clone(),
+                    timestamp: heartbeat.timestamp,
+                    last: status.last_timestamp,
+                });
+            }
+        }
+        let status = self.workers.entry(heartbeat.node_id.clone()).or_default();
+        status.last_seen = now;
+        status.last_timestamp = heartbeat.timestamp;
+        status.online = true;
+        Ok(())
+    }
+
+    /// Queue `job_id` for the least loaded live worker.
+    pub fn submit(&mut self, job_id: u64) -> Option<String> {
+        match self.least_loaded() {
+            Some(worker) => {
+                self.assignments.insert(job_id, worker.clone());
+                Some(worker)
+            }
+            None => {
+                self.pending.push_back(job_id);
+                None
+            }
+        }
+    }
+
+    /// Assign `job_id` to a specific live worker.
+    pub fn assign(&mut self, job_id: u64, worker: &str) -> Result<(), SchedulerError> {
+        match self.workers.get(worker) {
+            None => return Err(SchedulerError::UnknownWorker(worker.to_string())),
+            Some(status) if !status.online => return Err(SchedulerError::Offline(worker.to_string())),
+            Some(_) => {}
+        }
+        self.pending.retain(|id| *id != job_id);
+        self.assignments.insert(job_id, worker.to_string());
+        Ok(())
+    }
+
+    /// Forget a finished job.
+    pub fn complete(&mut self, job_id: u64) -> Option<String> {
+        self.pending.retain(|id| *id != job_id);
+        self.assignments.remove(&job_id)
+    }
+
+    /// Take workers that missed too many heartbeats offline, penalize them
+    /// and move their jobs (and any queued ones) to live workers.
+    pub fn check_liveness(&mut self, now: u64) -> Vec<Reassignment> {
+        let timeout = self.config.interval_secs.saturating_mul(self.config.max_missed as u64);
+        let mut dead = Vec::new();
+        for (node, status) in self.workers.iter_mut() {
+            if status.online && now.saturating_sub(status.last_seen) >= timeout {
+                status.online = false;
+                status.reputation -= self.config.reputation_penalty;
+                dead.push(node.clone());
+            }
+        }
+
+        let mut moved = Vec::new();
+        for node in &dead {
+            let jobs: Vec<u64> =
+                self.assignments.iter().filter(|(_, w)| *w == node).map(|(id, _)| *id).collect();
+            for job_id in jobs {
+                self.assignments.remove(&job_id);
+                let to = self.submit(job_id);
+                moved.push(Reassignment { job_id, from: node.clone(), to });
+            }
+        }
+
+        // Jobs queued earlier can go to workers that have come online since.
+        while !self.pending.is_empty() {
+            let Some(worker) = self.least_loaded() else { break };
+            let job_id = self.pending.pop_front().expect("queue is not empty");
+            self.assignments.insert(job_id, worker);
+        }
+        moved
+    }
+
+    pub fn assigned_worker(&self, job_id: u64) -> Option<&str> {
+        self.assignments.get(&job_id).map(String::as_str)
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = u64> + '_ {
+        self.pending.iter().copied()
+    }
+
+    pub fn is_online(&self, worker: &str) -> bool {
+        self.workers.get(worker).is_some_and(|s| s.online)
+    }
+
+    /// Net reputation change the scheduler has applied to `worker`.
+    pub fn reputation(&self, worker: &str) -> i32 {
+        self.workers.get(worker).map_or(0, |s| s.reputation)
+    }
+
+    fn least_loaded(&self) -> Option<String> {
+        self.workers
+            .iter()
+            .filter(|(_, status)| status.online)
+            .min_by_key(|(node, _)| self.assignments.values().filter(|w| w == node).count())
+            .map(|(node, _)| node.clone())
+    }
+}
+
+/// Broadcast a heartbeat over gossipsub.
+#[cfg(feature = "p2p")]
+pub async fn broadcast_heartbeat(handle: &P2PHandle, heartbeat: &Heartbeat) -> Result<(), P2PError> {
+    let msg = NetworkMessage::Heartbeat { heartbeat: heartbeat.clone() };
+    let bytes = bincode::serialize(&msg).map_err(|e| P2PError::SerializationFailed(e.to_string()))?;
+    handle.send_message(HEARTBEAT_TOPIC.into(), bytes).await
+}
+
+/// Sign and broadcast a heartbeat every `interval_secs`, listing the jobs
+/// reported by `active_jobs` at the time.
+#[cfg(feature = "p2p")]
+pub async fn run_heartbeats<F>(handle: P2PHandle, key: SigningKey, interval_secs: u64, active_jobs: F)
+where
+    F: Fn() -> Vec<u64>,
+{
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs.max(1)));
+    loop {
+        ticker.tick().await;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if let Err(e) = broadcast_heartbeat(&handle, &Heartbeat::sign(&key, now, active_jobs())).await {
+            tracing::warn!(error = %e, "failed to broadcast heartbeat");
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Session keys acting for an account in its node roles.
+//!
+//! An account's own key holds its funds and stake and should stay cold. The
+//! keys a node uses all the time — to sign consensus votes, to identify
+//! itself to peers and to sign the metrics it reports — are separate ed25519
+//! session keys, one per [`SessionRole`], linked to the account on-chain by a
+//! registration the account signs. Each session key also signs the link, so
+//! no account can claim a key it does not hold. A leaked session key can act
+//! in its role only, never spend from the account, and is replaced by
+//! registering a new set at the next epoch.
+//!
+//! Metrics updates and consensus votes are accepted only when signed with
+//! the sender's registered key for the role, see [`sign_as`] and
+//! [`verify_as`].
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+const SESSION_DOMAIN: &[u8] = b"bcai-session-key-v1";
+/// Domain tag of what session keys sign in their role, distinct from the
+/// link so neither can pass for the other.
+const SESSION_MESSAGE_DOMAIN: &[u8] = b"bcai-session-message-v1";
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum SessionKeyError {
+    #[error("session key registration is malformed: {0}")]
+    Malformed(&'static str),
+    #[error("{0} may not register session keys of {1}")]
+    Unauthorised(String, String),
+    #[error("invalid {0:?} session key")]
+    InvalidKey(SessionRole),
+    #[error("{0:?} session key did not sign its link to the account")]
+    InvalidProof(SessionRole),
+    #[error("session key epoch {got} does not follow {current:?}")]
+    StaleEpoch { current: Option<u64>, got: u64 },
+    #[error("session key {key} is already registered by {account}")]
+    KeyInUse { key: String, account: String },
+}
+
+/// What a node does with a session key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionRole {
+    /// Signing consensus votes and block proposals.
+    Consensus,
+    /// Identifying the node to its peers.
+    P2p,
+    /// Signing the metrics the node reports.
+    Metrics,
+}
+
+fn link_message(account: &str, role: SessionRole, epoch: u64) -> Vec<u8> {
+    let mut msg = SESSION_DOMAIN.to_vec();
+    msg.push(role as u8);
+    msg.extend_from_slice(&epoch.to_be_bytes());
+    msg.extend_from_slice(account.as_bytes());
+    msg
+}
+
+fn role_message(role: SessionRole, message: &[u8]) -> Vec<u8> {
+    let mut msg = SESSION_MESSAGE_DOMAIN.to_vec();
+    msg.push(role as u8);
+    msg.extend_from_slice(message);
+    msg
+}
+
+/// Signs `message` with a session key acting in `role`.
+pub fn sign_as(role: SessionRole, key: &SigningKey, message: &[u8]) -> Vec<u8> {
+    let signature: Signature = key.sign(&role_message(role, message));
+    signature.to_bytes().to_vec()
+}
+
+/// Whether `signature` over `message` was made in `role` by the hex
+/// ed25519 key `public_key`.
+pub fn verify_as(role: SessionRole, public_key: &str, message: &[u8], signature: &[u8]) -> bool {
+    let key = hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok());
+    let (Some(key), Ok(signature)) = (key, Signature::from_slice(signature)) else { return false };
+    key.verify(&role_message(role, message), &signature).is_ok()
+}
+
+/// A session key for one role, with its signature over the link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKey {
+    pub role: SessionRole,
+    /// Hex ed25519 public key.
+    pub public_key: String,
+    pub proof: Vec<u8>,
+}
+
+impl SessionKey {
+    /// Links `key` to `account` for `role` at `epoch`.
+    pub fn sign(account: &str, role: SessionRole, epoch: u64, key: &SigningKey) -> Self {
+        let proof: Signature = key.sign(&link_message(account, role, epoch));
+        Self {
+            role,
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            proof: proof.to_bytes().to_vec(),
+        }
+    }
+
+    /// Check the key signed its link to `account` at `epoch`.
+    pub fn verify(&self, account: &str, epoch: u64) -> Result<(), SessionKeyError> {
+        let key = hex::decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or(SessionKeyError::InvalidKey(self.role))?;
+        let proof = Signature::from_slice(&self.proof)
+            .map_err(|_| SessionKeyError::InvalidProof(self.role))?;
+        key.verify(&link_message(account, self.role, epoch), &proof)
+            .map_err(|_| SessionKeyError::InvalidProof(self.role))
+    }
+}
+
+/// The session keys of an account, replaced as a whole on every rotation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKeys {
+    pub account: String,
+    /// Number of rotations so far.
+    pub epoch: u64,
+    pub keys: Vec<SessionKey>,
+}
+
+impl SessionKeys {
+    pub fn new(account: &str, epoch: u64) -> Self {
+        Self { account: account.to_string(), epoch, keys: Vec::new() }
+    }
+
+    /// Adds `key` for `role`, signing its link to the account.
+    pub fn with_key(mut self, role: SessionRole, key: &SigningKey) -> Self {
+        self.keys.push(SessionKey::sign(&self.account, role, self.epoch, key));
+        self
+    }
+
+    /// Hex public key registered for `role`, if any.
+    pub fn key(&self, role: SessionRole) -> Option<&str> {
+        self.keys.iter().find(|k| k.role == role).map(|k| k.public_key.as_str())
+    }
+
+    /// A registration needs at least one key, one key per role, a distinct
+    /// key for every role and every key's signature over its link.
+    pub fn check_well_formed(&self) -> Result<(), SessionKeyError> {
+        if self.keys.is_empty() {
+            return Err(SessionKeyError::Malformed("no session keys"));
+        }
+        let mut roles = HashSet::new();
+        if !self.keys.iter().all(|k| roles.insert(k.role)) {
+            return Err(SessionKeyError::Malformed("role listed twice"));
+        }
+        // A key shared by two roles would leak both at once.
+        let mut keys = HashSet::new();
+        if !self.keys.iter().all(|k| keys.insert(&k.public_key)) {
+            return Err(SessionKeyError::Malformed("key used for two roles"));
+        }
+        self.keys.iter().try_for_each(|k| k.verify(&self.account, self.epoch))
+    }
+
+    /// Check that `sender` may replace `current`, the account's keys so far,
+    /// with these.
+    pub fn check_rotation(
+        &self,
+        sender: &str,
+        current: Option<&SessionKeys>,
+    ) -> Result<(), SessionKeyError> {
+        if self.account != sender {
+            return Err(SessionKeyError::Unauthorised(sender.to_string(), self.account.clone()));
+        }
+        let expected = current.map_or(0, |c| c.epoch + 1);
+        if self.epoch != expected {
+            return Err(SessionKeyError::StaleEpoch {
+                current: current.map(|c| c.epoch),
+                got: self.epoch,
+            });
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::node::reputation::{Dimension, ReputationBook};
 use thiserror::Error;
 
 /// Errors that can occur when operating on the token ledger.
@@ -16,6 +17,7 @@ pub enum LedgerError {
 pub struct TokenLedger {
     balances: std::collections::HashMap<String, u64>,
     staked: std::collections::HashMap<String, u64>,
+    reputation: ReputationBook,
 }
 
 impl TokenLedger {
@@ -23,6 +25,7 @@ impl TokenLedger {
         Self {
             balances: std::collections::HashMap::new(),
             staked: std::collections::HashMap::new(),
+            reputation: ReputationBook::default(),
         }
     }
 
@@ -82,6 +85,14 @@ impl TokenLedger {
         *self.staked.get(account).unwrap_or(&0)
     }
 
+    /// Every account holding stake, with the amount it holds.
+    pub fn stakes(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.staked
+            .iter()
+            .filter(|(_, stake)| **stake > 0)
+            .map(|(account, stake)| (account.as_str(), *stake))
+    }
+
     pub fn penalize(&mut self, account: &str, amount: u64) -> Result<(), LedgerError> {
         let balance = self.balances.entry(account.to_string()).or_default();
         if *balance < amount {
@@ -91,4 +102,17 @@ impl TokenLedger {
         }
         Ok(())
     }
+
+    /// Adjusts one reputation dimension of `account` at `now` (unix seconds).
+    pub fn record_reputation(&mut self, account: &str, dimension: Dimension, delta: f64, now: u64) {
+        self.reputation.record(account, dimension, delta, now);
+    }
+
+    pub fn reputation(&self) -> &ReputationBook {
+        &self.reputation
+    }
+
+    pub fn reputation_mut(&mut self) -> &mut ReputationBook {
+        &mut self.reputation
+    }
 }
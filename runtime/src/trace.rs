@@ -0,0 +1,94 @@
+//! Correlation of a job's lifecycle across the poster, chain, worker and
+//! evaluator.
+//!
+//! A job is assigned a trace when it is posted. Each stage that handles it
+//! opens a child span and forwards its own context, so every span of one
+//! job shares a `trace_id` and names its parent. The context travels on the
+//! job and, once mined, on the block's PoUW task as a W3C `traceparent`
+//! string, which is how nodes that only see the chain pick the trace up.
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+
+/// Name shared by every lifecycle span; the stage is a field.
+pub const SPAN_NAME: &str = "job_lifecycle";
+
+/// Identifies one span of a job trace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TraceContext {
+    /// 32 hex characters, shared by every span of the job.
+    pub trace_id: String,
+    /// 16 hex characters, unique to this span.
+    pub span_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+}
+
+impl TraceContext {
+    /// Starts a new trace, e.g. when a job is posted.
+    pub fn new_root() -> Self {
+        Self { trace_id: random_hex(16), span_id: random_hex(8), parent_span_id: None }
+    }
+
+    /// A span in the same trace whose parent is `self`.
+    pub fn child(&self) -> Self {
+        Self {
+            trace_id: self.trace_id.clone(),
+            span_id: random_hex(8),
+            parent_span_id: Some(self.span_id.clone()),
+        }
+    }
+
+    /// Encodes this span as a W3C `traceparent` header value.
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-01", self.trace_id, self.span_id)
+    }
+
+    /// Parses a `traceparent` value. The parent of the remote span is not
+    /// carried, so `parent_span_id` is `None`.
+    pub fn from_traceparent(value: &str) -> Option<Self> {
+        let mut parts = value.split('-');
+        let (version, trace_id, span_id, _flags) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() || version != "00" || !is_id(trace_id, 32) || !is_id(span_id, 16) {
+            return None;
+        }
+        Some(Self { trace_id: trace_id.to_string(), span_id: span_id.to_string(), parent_span_id: None })
+    }
+
+    /// Child of the span described by `traceparent`, if one was sent.
+    pub fn continue_from(traceparent: Option<&str>) -> Option<Self> {
+        traceparent.and_then(Self::from_traceparent).map(|remote| remote.child())
+    }
+
+    /// An info-level span for `stage` carrying this context's ids.
+    pub fn span(&self, stage: &str) -> Span {
+        tracing::info_span!(
+            SPAN_NAME,
+            stage,
+            trace_id = %self.trace_id,
+            span_id = %self.span_id,
+            parent_span_id = self.parent_span_id.as_deref().unwrap_or(""),
+        )
+    }
+}
+
+/// Span for `stage` when the work is traced, a disabled span otherwise.
+pub fn stage_span(context: Option<&TraceContext>, stage: &str) -> Span {
+    context.map_or_else(Span::none, |ctx| ctx.span(stage))
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    // All-zero ids are invalid in the W3C format.
+    while buf.iter().all(|b| *b == 0) {
+        rand::thread_rng().fill_bytes(&mut buf);
+    }
+    hex::encode(buf)
+}
+
+fn is_id(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        && value.bytes().any(|b| b != b'0')
+}
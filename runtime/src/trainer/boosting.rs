@@ -0,0 +1,101 @@
+//! Gradient boosting of decision stumps under logistic loss, one stump per
+//! epoch, on the task dataset.
+
+use super::{seal, ModelBackend, TrainerError, TrainerRegistry};
+use crate::pouw::training::{Dataset, FEATURES, ONE};
+use crate::pouw::types::{PoUWSolution, PoUWTask};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const MODEL_TYPE: &str = "boosting";
+
+const SHRINKAGE: f64 = 0.5;
+
+pub struct BoostingBackend;
+
+/// Splits on one feature: `left` below `threshold`, `right` otherwise.
+#[derive(Debug, Clone, Copy)]
+struct Stump {
+    feature: usize,
+    threshold: i64,
+    left: f64,
+    right: f64,
+}
+
+impl Stump {
+    fn predict(&self, x: &[i64; FEATURES]) -> f64 {
+        if x[self.feature] < self.threshold {
+            self.left
+        } else {
+            self.right
+        }
+    }
+
+    /// The stump that best fits `residuals` in squared error, trying every
+    /// sample value of every feature as threshold.
+    fn fit(features: &[[i64; FEATURES]], residuals: &[f64]) -> Self {
+        let total: f64 = residuals.iter().sum();
+        let mut best = (f64::INFINITY, Stump { feature: 0, threshold: i64::MIN, left: 0.0, right: 0.0 });
+        for feature in 0..FEATURES {
+            let mut order: Vec<usize> = (0..features.len()).collect();
+            order.sort_by_key(|&i| features[i][feature]);
+            // Sweep thresholds in order, moving samples to the left side.
+            let (mut left_sum, mut left_n) = (0.0, 0usize);
+            for (k, &i) in order.iter().enumerate() {
+                if k > 0 && features[order[k - 1]][feature] == features[i][feature] {
+                    left_sum += residuals[i];
+                    left_n += 1;
+                    continue;
+                }
+                let right_n = features.len() - left_n;
+                let left = if left_n > 0 { left_sum / left_n as f64 } else { 0.0 };
+                let right = (total - left_sum) / right_n as f64;
+                // Squared error up to a constant: minus the explained variance.
+                let loss = -(left * left_sum + right * (total - left_sum));
+                if loss < best.0 {
+                    best = (loss, Stump { feature, threshold: features[i][feature], left, right });
+                }
+                left_sum += residuals[i];
+                left_n += 1;
+            }
+        }
+        best.1
+    }
+}
+
+impl ModelBackend for BoostingBackend {
+    fn model_type(&self) -> &'static str {
+        MODEL_TYPE
+    }
+
+    fn train(&self, task: &PoUWTask, difficulty: u32) -> Result<PoUWSolution, TrainerError> {
+        let started = std::time::Instant::now();
+        let data = Dataset::for_task(task);
+        let labels: Vec<f64> = data.labels.iter().map(|y| *y as f64 / ONE as f64).collect();
+        let mut scores = vec![0.0; labels.len()];
+        let mut stumps = Vec::with_capacity(task.epochs as usize);
+        for _ in 0..task.epochs {
+            let residuals: Vec<f64> = labels.iter().zip(&scores).map(|(y, f)| y - 1.0 / (1.0 + (-f).exp())).collect();
+            let stump = Stump::fit(&data.features, &residuals);
+            for (score, x) in scores.iter_mut().zip(&data.features) {
+                *score += SHRINKAGE * stump.predict(x);
+            }
+            stumps.push(stump);
+        }
+
+        let correct = scores.iter().zip(&labels).filter(|(f, y)| (**f > 0.0) == (**y > 0.5)).count();
+        let accuracy = (correct * 10_000 / labels.len()) as u32;
+        let mut hasher = Sha256::new();
+        for stump in &stumps {
+            hasher.update((stump.feature as u64).to_le_bytes());
+            hasher.update(stump.threshold.to_le_bytes());
+            hasher.update(stump.left.to_le_bytes());
+            hasher.update(stump.right.to_le_bytes());
+        }
+        Ok(seal(task, hasher.finalize().into(), accuracy, difficulty, started))
+    }
+}
+
+pub fn register(registry: &mut TrainerRegistry) -> Result<(), TrainerError> {
+    registry.register(Arc::new(BoostingBackend))
+}
@@ -0,0 +1,220 @@
+//! Hooks run at the end of every training epoch.
+//!
+//! Backends with an epoch loop hand each [`TrainingCallback`] the epoch's
+//! metrics, the learning rate the next epoch will use and the current
+//! weights. Callbacks can stop training early, reschedule the learning rate
+//! or persist checkpoints; backends without epochs ignore them.
+
+use crate::large_data_transfer::chunk::{ChunkId, DataChunk};
+use crate::large_data_transfer::config::CompressionAlgorithm;
+use crate::large_data_transfer::manager::ChunkManager;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EpochMetrics {
+    /// Epochs completed, starting at 1.
+    pub epoch: u32,
+    pub loss: f64,
+    /// Training accuracy in `[0, 1]`.
+    pub accuracy: f64,
+    /// Learning rate the epoch trained with.
+    pub learning_rate: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    Stop,
+}
+
+/// State of training after an epoch.
+pub struct EpochEnd<'a> {
+    pub metrics: EpochMetrics,
+    /// Learning rate of the next epoch; callbacks may change it.
+    pub learning_rate: &'a mut f64,
+    /// Model parameters, flattened in the backend's order.
+    pub weights: &'a [f64],
+}
+
+pub trait TrainingCallback {
+    fn on_epoch_end(&mut self, end: &mut EpochEnd<'_>) -> Control;
+}
+
+/// Runs every callback in order, even after one has asked to stop, and
+/// stops if any did.
+pub fn on_epoch_end(callbacks: &mut [&mut dyn TrainingCallback], end: &mut EpochEnd<'_>) -> Control {
+    callbacks.iter_mut().fold(Control::Continue, |control, callback| {
+        match callback.on_epoch_end(end) {
+            Control::Stop => Control::Stop,
+            Control::Continue => control,
+        }
+    })
+}
+
+/// Metric [`EarlyStopping`] watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Monitor {
+    /// Lower is better.
+    Loss,
+    /// Higher is better.
+    Accuracy,
+}
+
+/// Stops training once the monitored metric has not improved by more than
+/// `min_delta` for `patience` epochs.
+#[derive(Debug, Clone)]
+pub struct EarlyStopping {
+    pub monitor: Monitor,
+    pub patience: u32,
+    pub min_delta: f64,
+    best: Option<f64>,
+    wait: u32,
+    stopped_epoch: Option<u32>,
+}
+
+impl EarlyStopping {
+    pub fn new(monitor: Monitor, patience: u32) -> Self {
+        Self { monitor, patience, min_delta: 0.0, best: None, wait: 0, stopped_epoch: None }
+    }
+
+    pub fn with_min_delta(mut self, min_delta: f64) -> Self {
+        self.min_delta = min_delta;
+        self
+    }
+
+    /// Best value of the monitored metric so far.
+    pub fn best(&self) -> Option<f64> {
+        self.best
+    }
+
+    /// Epoch at which training was stopped, if it was.
+    pub fn stopped_epoch(&self) -> Option<u32> {
+        self.stopped_epoch
+    }
+}
+
+impl TrainingCallback for EarlyStopping {
+    fn on_epoch_end(&mut self, end: &mut EpochEnd<'_>) -> Control {
+        let value = match self.monitor {
+            Monitor::Loss => end.metrics.loss,
+            Monitor::Accuracy => end.metrics.accuracy,
+        };
+        let improved = self.best.map_or(true, |best| match self.monitor {
+            Monitor::Loss => value < best - self.min_delta,
+            Monitor::Accuracy => value > best + self.min_delta,
+        });
+        if improved {
+            self.best = Some(value);
+            self.wait = 0;
+            return Control::Continue;
+        }
+        self.wait += 1;
+        if self.wait >= self.patience {
+            self.stopped_epoch = Some(end.metrics.epoch);
+            return Control::Stop;
+        }
+        Control::Continue
+    }
+}
+
+/// Learning rate as a function of the epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LrSchedule {
+    Constant,
+    /// Multiplies the rate by `factor` every `every` epochs.
+    Step { every: u32, factor: f64 },
+    /// Multiplies the rate by `gamma` every epoch.
+    Exponential { gamma: f64 },
+    /// Anneals from the initial rate to `min_lr` over `epochs` epochs along
+    /// half a cosine.
+    Cosine { epochs: u32, min_lr: f64 },
+}
+
+impl LrSchedule {
+    /// Rate for epoch `epoch` (starting at 1) of a run that started at
+    /// `initial`.
+    pub fn rate(&self, initial: f64, epoch: u32) -> f64 {
+        let done = epoch.saturating_sub(1);
+        match *self {
+            LrSchedule::Constant => initial,
+            LrSchedule::Step { every, factor } => initial * factor.powi((done / every.max(1)) as i32),
+            LrSchedule::Exponential { gamma } => initial * gamma.powi(done as i32),
+            LrSchedule::Cosine { epochs, min_lr } => {
+                let progress = (done as f64 / epochs.max(1) as f64).min(1.0);
+                min_lr + (initial - min_lr) * (1.0 + (std::f64::consts::PI * progress).cos()) / 2.0
+            }
+        }
+    }
+}
+
+/// Sets each epoch's learning rate from a schedule, starting from the rate
+/// the first epoch trained with.
+#[derive(Debug, Clone)]
+pub struct LrScheduler {
+    pub schedule: LrSchedule,
+    initial: Option<f64>,
+}
+
+impl LrScheduler {
+    pub fn new(schedule: LrSchedule) -> Self {
+        Self { schedule, initial: None }
+    }
+}
+
+impl TrainingCallback for LrScheduler {
+    fn on_epoch_end(&mut self, end: &mut EpochEnd<'_>) -> Control {
+        let initial = *self.initial.get_or_insert(end.metrics.learning_rate);
+        *end.learning_rate = self.schedule.rate(initial, end.metrics.epoch + 1);
+        Control::Continue
+    }
+}
+
+/// Saves the weights to the distributed store every `every` epochs as an
+/// LZ4-compressed chunk of little-endian `f64`s.
+#[derive(Debug)]
+pub struct DfsCheckpoint {
+    manager: Arc<ChunkManager>,
+    pub every: u32,
+    saved: Vec<(u32, ChunkId)>,
+}
+
+impl DfsCheckpoint {
+    pub fn new(manager: Arc<ChunkManager>, every: u32) -> Self {
+        Self { manager, every: every.max(1), saved: Vec::new() }
+    }
+
+    /// Chunks written so far, by epoch.
+    pub fn checkpoints(&self) -> &[(u32, ChunkId)] {
+        &self.saved
+    }
+
+    pub fn latest(&self) -> Option<&ChunkId> {
+        self.saved.last().map(|(_, id)| id)
+    }
+
+    /// Reads checkpointed weights back from `manager`.
+    pub fn load(manager: &ChunkManager, id: &ChunkId) -> Option<Vec<f64>> {
+        let bytes = manager.get_chunk(id)?.decompress().ok()?;
+        Some(bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().expect("8-byte chunk"))).collect())
+    }
+}
+
+impl TrainingCallback for DfsCheckpoint {
+    fn on_epoch_end(&mut self, end: &mut EpochEnd<'_>) -> Control {
+        if end.metrics.epoch % self.every != 0 {
+            return Control::Continue;
+        }
+        let bytes = end.weights.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let stored = DataChunk::new_from_slice(bytes, end.metrics.epoch, CompressionAlgorithm::Lz4).and_then(|chunk| {
+            let id = chunk.id().clone();
+            self.manager.store_chunk(chunk).map(|_| id)
+        });
+        match stored {
+            Ok(id) => self.saved.push((end.metrics.epoch, id)),
+            // A lost checkpoint should not abort the run.
+            Err(e) => tracing::warn!(epoch = end.metrics.epoch, error = %e, "checkpoint not saved"),
+        }
+        Control::Continue
+    }
+}
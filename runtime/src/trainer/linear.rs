@@ -0,0 +1,25 @@
+//! The PoUW reference model: fixed-point logistic regression, see
+//! [`crate::pouw::training`]. Its solutions carry checkpoints and can be
+//! verified by re-training.
+
+use super::{ModelBackend, TrainerError, TrainerRegistry};
+use crate::pouw::types::{PoUWSolution, PoUWTask};
+use std::sync::Arc;
+
+pub const MODEL_TYPE: &str = "linear";
+
+pub struct LinearBackend;
+
+impl ModelBackend for LinearBackend {
+    fn model_type(&self) -> &'static str {
+        MODEL_TYPE
+    }
+
+    fn train(&self, task: &PoUWTask, difficulty: u32) -> Result<PoUWSolution, TrainerError> {
+        Ok(crate::pouw::solve(task, difficulty))
+    }
+}
+
+pub fn register(registry: &mut TrainerRegistry) -> Result<(), TrainerError> {
+    registry.register(Arc::new(LinearBackend))
+}
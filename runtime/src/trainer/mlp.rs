@@ -0,0 +1,166 @@
+//! Multi-layer perceptron: one `tanh` hidden layer and a sigmoid output,
+//! trained by full-batch gradient descent in `f64` on the task dataset.
+
+use super::callbacks::{self, Control, EpochEnd, EpochMetrics, TrainingCallback};
+use super::{seal, ModelBackend, TrainerError, TrainerRegistry};
+use crate::pouw::training::{Dataset, FEATURES};
+use crate::pouw::types::{PoUWSolution, PoUWTask};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+pub const MODEL_TYPE: &str = "mlp";
+
+/// Learning rate of the first epoch; callbacks may schedule the rest.
+const LEARNING_RATE: f64 = 0.5;
+
+pub struct MlpBackend {
+    pub hidden: usize,
+}
+
+impl Default for MlpBackend {
+    fn default() -> Self {
+        Self { hidden: 8 }
+    }
+}
+
+struct Mlp {
+    w1: Vec<[f64; FEATURES]>,
+    b1: Vec<f64>,
+    w2: Vec<f64>,
+    b2: f64,
+}
+
+impl Mlp {
+    /// Small random weights seeded by the task, so retraining reproduces
+    /// the model.
+    fn init(hidden: usize, task: &PoUWTask) -> Self {
+        let mut rng = StdRng::from_seed(task.challenge);
+        let mut weight = || rng.gen_range(-0.5..0.5);
+        Self {
+            w1: (0..hidden).map(|_| [weight(), weight()]).collect(),
+            b1: vec![0.0; hidden],
+            w2: (0..hidden).map(|_| weight()).collect(),
+            b2: 0.0,
+        }
+    }
+
+    /// Hidden activations and output probability for `x`.
+    fn forward(&self, x: &[f64; FEATURES]) -> (Vec<f64>, f64) {
+        let h: Vec<f64> = self
+            .w1
+            .iter()
+            .zip(&self.b1)
+            .map(|(w, b)| (w[0] * x[0] + w[1] * x[1] + b).tanh())
+            .collect();
+        let z = h.iter().zip(&self.w2).map(|(h, w)| h * w).sum::<f64>() + self.b2;
+        (h, 1.0 / (1.0 + (-z).exp()))
+    }
+
+    fn step(&mut self, xs: &[[f64; FEATURES]], ys: &[f64], learning_rate: f64) {
+        let hidden = self.w2.len();
+        let mut gw1 = vec![[0.0; FEATURES]; hidden];
+        let mut gb1 = vec![0.0; hidden];
+        let mut gw2 = vec![0.0; hidden];
+        let mut gb2 = 0.0;
+        for (x, y) in xs.iter().zip(ys) {
+            let (h, p) = self.forward(x);
+            let dz = p - y;
+            gb2 += dz;
+            for j in 0..hidden {
+                gw2[j] += dz * h[j];
+                let dh = dz * self.w2[j] * (1.0 - h[j] * h[j]);
+                gb1[j] += dh;
+                gw1[j][0] += dh * x[0];
+                gw1[j][1] += dh * x[1];
+            }
+        }
+        let scale = learning_rate / xs.len() as f64;
+        for j in 0..hidden {
+            self.w2[j] -= scale * gw2[j];
+            self.b1[j] -= scale * gb1[j];
+            self.w1[j][0] -= scale * gw1[j][0];
+            self.w1[j][1] -= scale * gw1[j][1];
+        }
+        self.b2 -= scale * gb2;
+    }
+
+    /// Mean cross-entropy loss and accuracy on `xs`.
+    fn evaluate(&self, xs: &[[f64; FEATURES]], ys: &[f64]) -> (f64, f64) {
+        let mut loss = 0.0;
+        let mut correct = 0;
+        for (x, y) in xs.iter().zip(ys) {
+            let p = self.forward(x).1.clamp(1e-12, 1.0 - 1e-12);
+            loss -= y * p.ln() + (1.0 - y) * (1.0 - p).ln();
+            if (p > 0.5) == (*y > 0.5) {
+                correct += 1;
+            }
+        }
+        (loss / xs.len() as f64, correct as f64 / xs.len() as f64)
+    }
+
+    /// Parameters in the order they are hashed.
+    fn weights(&self) -> Vec<f64> {
+        self.w1.iter().flatten().chain(&self.b1).chain(&self.w2).chain([&self.b2]).copied().collect()
+    }
+
+    fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for w in self.weights() {
+            hasher.update(w.to_le_bytes());
+        }
+        hasher.finalize().into()
+    }
+}
+
+impl ModelBackend for MlpBackend {
+    fn model_type(&self) -> &'static str {
+        MODEL_TYPE
+    }
+
+    fn train(&self, task: &PoUWTask, difficulty: u32) -> Result<PoUWSolution, TrainerError> {
+        self.train_with_callbacks(task, difficulty, &mut [])
+    }
+
+    fn train_with_callbacks(
+        &self,
+        task: &PoUWTask,
+        difficulty: u32,
+        callbacks: &mut [&mut dyn TrainingCallback],
+    ) -> Result<PoUWSolution, TrainerError> {
+        if self.hidden == 0 {
+            return Err(TrainerError::Training("MLP needs at least one hidden unit".into()));
+        }
+        let started = std::time::Instant::now();
+        let samples = Dataset::for_task(task).to_samples();
+        let xs: Vec<[f64; FEATURES]> =
+            samples.iter().map(|s| [s.features[0] as f64, s.features[1] as f64]).collect();
+        let ys: Vec<f64> = samples.iter().map(|s| s.label as f64).collect();
+
+        let mut model = Mlp::init(self.hidden, task);
+        let mut learning_rate = LEARNING_RATE;
+        for epoch in 1..=task.epochs {
+            model.step(&xs, &ys, learning_rate);
+            if callbacks.is_empty() {
+                continue;
+            }
+            let (loss, accuracy) = model.evaluate(&xs, &ys);
+            let weights = model.weights();
+            let mut end = EpochEnd {
+                metrics: EpochMetrics { epoch, loss, accuracy, learning_rate },
+                learning_rate: &mut learning_rate,
+                weights: &weights,
+            };
+            if callbacks::on_epoch_end(callbacks, &mut end) == Control::Stop {
+                break;
+            }
+        }
+        let accuracy = (model.evaluate(&xs, &ys).1 * 10_000.0).round() as u32;
+        Ok(seal(task, model.hash(), accuracy, difficulty, started))
+    }
+}
+
+pub fn register(registry: &mut TrainerRegistry) -> Result<(), TrainerError> {
+    registry.register(Arc::new(MlpBackend::default()))
+}
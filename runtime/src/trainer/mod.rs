@@ -0,0 +1,156 @@
+//! Training of the model a task asks for.
+//!
+//! Model types are plugins: every [`ModelBackend`] registers itself in a
+//! [`TrainerRegistry`] under the type it trains, and [`Trainer`] picks the
+//! backend from the task's model id, `"<type>:<name>"`. Ids without a type
+//! prefix train the PoUW reference model. Adding a model type means writing
+//! a backend and registering it; the solver is not touched.
+
+use crate::pouw::types::{PoUWSolution, PoUWTask};
+use callbacks::TrainingCallback;
+use crate::trace::{stage_span, TraceContext};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+pub mod boosting;
+pub mod callbacks;
+pub mod linear;
+pub mod mlp;
+mod registry;
+
+pub use registry::TrainerRegistry;
+
+/// Model type of tasks whose model id has no type prefix.
+pub const DEFAULT_MODEL_TYPE: &str = linear::MODEL_TYPE;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum TrainerError {
+    #[error("no trainer registered for model type {0}")]
+    UnknownModelType(String),
+    #[error("model type {0} is already registered")]
+    Duplicate(String),
+    #[error("training failed: {0}")]
+    Training(String),
+}
+
+/// Trains one model type.
+pub trait ModelBackend: Send + Sync {
+    /// Type this backend trains, the prefix of the model ids it serves.
+    fn model_type(&self) -> &'static str;
+
+    /// Trains the task's model and seals the result into a PoUW solution
+    /// meeting `difficulty`.
+    fn train(&self, task: &PoUWTask, difficulty: u32) -> Result<PoUWSolution, TrainerError>;
+
+    /// Like [`ModelBackend::train`], calling `callbacks` after every epoch.
+    /// Backends without an epoch loop ignore them.
+    fn train_with_callbacks(
+        &self,
+        task: &PoUWTask,
+        difficulty: u32,
+        callbacks: &mut [&mut dyn TrainingCallback],
+    ) -> Result<PoUWSolution, TrainerError> {
+        let _ = callbacks;
+        self.train(task, difficulty)
+    }
+}
+
+/// Model type of `task`: the part of its model id before the first `:`, or
+/// [`DEFAULT_MODEL_TYPE`].
+pub fn model_type(task: &PoUWTask) -> &str {
+    task.model_id.split_once(':').map_or(DEFAULT_MODEL_TYPE, |(kind, _)| kind)
+}
+
+#[derive(Clone)]
+pub struct Trainer {
+    node_id: String,
+    registry: Arc<TrainerRegistry>,
+}
+
+impl std::fmt::Debug for Trainer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Trainer")
+            .field("node_id", &self.node_id)
+            .field("model_types", &self.registry.model_types())
+            .finish()
+    }
+}
+
+impl Trainer {
+    /// A trainer with every built-in backend.
+    pub fn new(node_id: &str) -> Self {
+        Self::with_registry(node_id, Arc::new(TrainerRegistry::with_defaults()))
+    }
+
+    pub fn with_registry(node_id: &str, registry: Arc<TrainerRegistry>) -> Self {
+        Self { node_id: node_id.to_string(), registry }
+    }
+
+    pub fn registry(&self) -> &TrainerRegistry {
+        &self.registry
+    }
+
+    /// Executes useful work and returns metrics & solution.
+    ///
+    /// This now records the time spent solving the PoUW task and exposes it in
+    /// the returned metrics map so callers can track actual training duration.
+    pub fn execute(&self, task: &PoUWTask) -> Result<TrainingOutput, TrainerError> {
+        self.execute_with_callbacks(task, &mut [])
+    }
+
+    /// [`Trainer::execute`] with epoch callbacks, e.g. early stopping.
+    pub fn execute_with_callbacks(
+        &self,
+        task: &PoUWTask,
+        callbacks: &mut [&mut dyn TrainingCallback],
+    ) -> Result<TrainingOutput, TrainerError> {
+        let trace = TraceContext::continue_from(task.trace.as_deref());
+        let _span = stage_span(trace.as_ref(), "train").entered();
+        let backend = self.registry.resolve(task)?;
+        let start = std::time::Instant::now();
+
+        // Perform the PoUW solving against the easiest target for now; a
+        // lower target is harder to meet.
+        let solution = backend.train_with_callbacks(task, u32::MAX, callbacks)?;
+
+        let duration_ms = start.elapsed().as_millis() as f64;
+        let mut metrics = HashMap::new();
+        metrics.insert("duration_ms".to_string(), duration_ms);
+        metrics.insert("accuracy".to_string(), solution.accuracy as f64 / 10_000.0);
+
+        Ok(TrainingOutput { model_type: backend.model_type().to_string(), metrics, solution })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingOutput {
+    /// Model type of the backend that trained the task.
+    pub model_type: String,
+    pub metrics: HashMap<String, f64>,
+    pub solution: PoUWSolution,
+}
+
+/// Seals a model trained outside the PoUW reference trainer: finds the
+/// nonce for `weights_hash` and reports `accuracy`. Such solutions carry no
+/// checkpoints, so they cannot be verified by re-training.
+pub(crate) fn seal(
+    task: &PoUWTask,
+    weights_hash: [u8; 32],
+    accuracy: u32,
+    difficulty: u32,
+    started: std::time::Instant,
+) -> PoUWSolution {
+    let training_time_ms = started.elapsed().as_millis() as u64;
+    let nonce = crate::pouw::solver::find_nonce(task, &weights_hash, difficulty);
+    PoUWSolution {
+        trained_model_hash: hex::encode(weights_hash),
+        accuracy,
+        nonce,
+        computation_time_ms: started.elapsed().as_millis() as u64,
+        training_time_ms,
+        checkpoints: Vec::new(),
+        gradient_roots: Vec::new(),
+        zk_proof: None,
+    }
+}
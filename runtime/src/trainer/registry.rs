@@ -0,0 +1,54 @@
+use super::{boosting, linear, mlp, model_type, ModelBackend, TrainerError};
+use crate::pouw::types::PoUWTask;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Model backends by the model type they train.
+#[derive(Default)]
+pub struct TrainerRegistry {
+    backends: HashMap<&'static str, Arc<dyn ModelBackend>>,
+}
+
+impl TrainerRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with the built-in backends: the PoUW reference `linear`
+    /// model, `mlp` and `boosting`.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for register in [linear::register, mlp::register, boosting::register] {
+            register(&mut registry).expect("built-in model types are distinct");
+        }
+        registry
+    }
+
+    /// Adds a backend. Each model type can be registered once.
+    pub fn register(&mut self, backend: Arc<dyn ModelBackend>) -> Result<(), TrainerError> {
+        let kind = backend.model_type();
+        if self.backends.contains_key(kind) {
+            return Err(TrainerError::Duplicate(kind.to_string()));
+        }
+        self.backends.insert(kind, backend);
+        Ok(())
+    }
+
+    pub fn get(&self, model_type: &str) -> Option<Arc<dyn ModelBackend>> {
+        self.backends.get(model_type).cloned()
+    }
+
+    /// Backend for the model type `task` asks for.
+    pub fn resolve(&self, task: &PoUWTask) -> Result<Arc<dyn ModelBackend>, TrainerError> {
+        let kind = model_type(task);
+        self.get(kind).ok_or_else(|| TrainerError::UnknownModelType(kind.to_string()))
+    }
+
+    /// Registered model types, sorted.
+    pub fn model_types(&self) -> Vec<&'static str> {
+        let mut types: Vec<&'static str> = self.backends.keys().copied().collect();
+        types.sort_unstable();
+        types
+    }
+}
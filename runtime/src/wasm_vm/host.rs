@@ -0,0 +1,191 @@
+//! The host API, see the [module docs](super).
+
+use super::{Capabilities, WasmVmConfig, HOST_MODULE, MAX_METRICS, MAX_METRIC_NAME};
+use crate::data::Sample;
+use std::sync::Arc;
+use wasmi::core::{Trap, TrapCode};
+use wasmi::{Caller, Engine, Extern, Linker, StoreLimits, StoreLimitsBuilder};
+
+/// Fuel charged per element a host function reads, writes or computes, so
+/// host work counts against the budget like guest instructions.
+const FUEL_PER_ELEMENT: u64 = 1;
+
+pub(super) struct Host {
+    pub(super) limits: StoreLimits,
+    dataset: Arc<[Sample]>,
+    max_output_bytes: usize,
+    pub(super) metrics: Vec<(String, f64)>,
+    pub(super) output: Vec<u8>,
+}
+
+impl Host {
+    pub(super) fn new(dataset: Arc<[Sample]>, config: &WasmVmConfig) -> Self {
+        Self {
+            limits: StoreLimitsBuilder::new().memory_size(config.max_memory_bytes).instances(1).build(),
+            dataset,
+            max_output_bytes: config.max_output_bytes,
+            metrics: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+}
+
+type HostCaller<'a> = Caller<'a, Host>;
+
+fn charge(caller: &mut HostCaller<'_>, elements: usize) -> Result<(), Trap> {
+    caller
+        .consume_fuel(elements as u64 * FUEL_PER_ELEMENT)
+        .map(|_| ())
+        .map_err(|_| Trap::from(TrapCode::OutOfFuel))
+}
+
+fn memory(caller: &HostCaller<'_>) -> Result<wasmi::Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new("module exports no memory"))
+}
+
+fn range(ptr: i32, len: usize) -> Result<std::ops::Range<usize>, Trap> {
+    let start = ptr as u32 as usize;
+    start.checked_add(len).map(|end| start..end).ok_or_else(|| Trap::from(TrapCode::MemoryOutOfBounds))
+}
+
+fn read_bytes(caller: &HostCaller<'_>, ptr: i32, len: usize) -> Result<Vec<u8>, Trap> {
+    let memory = memory(caller)?;
+    memory
+        .data(caller)
+        .get(range(ptr, len)?)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| Trap::from(TrapCode::MemoryOutOfBounds))
+}
+
+fn read_f32s(caller: &HostCaller<'_>, ptr: i32, len: usize) -> Result<Vec<f32>, Trap> {
+    let bytes = read_bytes(caller, ptr, len.checked_mul(4).ok_or(Trap::from(TrapCode::MemoryOutOfBounds))?)?;
+    Ok(bytes.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().expect("4 bytes"))).collect())
+}
+
+fn write_f32s(caller: &mut HostCaller<'_>, ptr: i32, values: &[f32]) -> Result<(), Trap> {
+    let memory = memory(caller)?;
+    let target = memory
+        .data_mut(caller)
+        .get_mut(range(ptr, values.len() * 4)?)
+        .ok_or_else(|| Trap::from(TrapCode::MemoryOutOfBounds))?;
+    for (chunk, value) in target.chunks_exact_mut(4).zip(values) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    Ok(())
+}
+
+fn length(len: i32) -> Result<usize, Trap> {
+    usize::try_from(len).map_err(|_| Trap::new(format!("negative length {}", len)))
+}
+
+/// A linker with the host functions `capabilities` grant.
+pub(super) fn linker(engine: &Engine, capabilities: &Capabilities) -> Result<Linker<Host>, wasmi::errors::LinkerError> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(HOST_MODULE, "write_output", |mut caller: HostCaller<'_>, ptr: i32, len: i32| -> Result<(), Trap> {
+        let len = length(len)?;
+        if len > caller.data().max_output_bytes {
+            return Err(Trap::new(format!("output of {} bytes exceeds the limit", len)));
+        }
+        charge(&mut caller, len)?;
+        let output = read_bytes(&caller, ptr, len)?;
+        caller.data_mut().output = output;
+        Ok(())
+    })?;
+
+    if capabilities.dataset {
+        linker.func_wrap(HOST_MODULE, "dataset_len", |caller: HostCaller<'_>| -> i32 {
+            caller.data().dataset.len().try_into().unwrap_or(i32::MAX)
+        })?;
+        linker.func_wrap(HOST_MODULE, "dataset_dim", |caller: HostCaller<'_>| -> i32 {
+            caller.data().dataset.first().map_or(0, |s| s.features.len().try_into().unwrap_or(i32::MAX))
+        })?;
+        linker.func_wrap(
+            HOST_MODULE,
+            "dataset_read",
+            |mut caller: HostCaller<'_>, index: i32, ptr: i32| -> Result<i32, Trap> {
+                let sample = match usize::try_from(index).ok().and_then(|i| caller.data().dataset.get(i)) {
+                    Some(sample) => sample.clone(),
+                    None => return Ok(-1),
+                };
+                charge(&mut caller, sample.features.len())?;
+                write_f32s(&mut caller, ptr, &sample.features)?;
+                Ok(sample.label.try_into().unwrap_or(i32::MAX))
+            },
+        )?;
+    }
+
+    if capabilities.tensor_ops {
+        linker.func_wrap(
+            HOST_MODULE,
+            "tensor_add",
+            |mut caller: HostCaller<'_>, a: i32, b: i32, out: i32, len: i32| -> Result<(), Trap> {
+                let len = length(len)?;
+                charge(&mut caller, len)?;
+                let (a, b) = (read_f32s(&caller, a, len)?, read_f32s(&caller, b, len)?);
+                let sum: Vec<f32> = a.iter().zip(&b).map(|(x, y)| x + y).collect();
+                write_f32s(&mut caller, out, &sum)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            "tensor_relu",
+            |mut caller: HostCaller<'_>, x: i32, out: i32, len: i32| -> Result<(), Trap> {
+                let len = length(len)?;
+                charge(&mut caller, len)?;
+                let relu: Vec<f32> = read_f32s(&caller, x, len)?.iter().map(|v| v.max(0.0)).collect();
+                write_f32s(&mut caller, out, &relu)
+            },
+        )?;
+        linker.func_wrap(
+            HOST_MODULE,
+            "tensor_matmul",
+            |mut caller: HostCaller<'_>, a: i32, b: i32, out: i32, m: i32, k: i32, n: i32| -> Result<(), Trap> {
+                let (m, k, n) = (length(m)?, length(k)?, length(n)?);
+                let size = |rows: usize, cols: usize| rows.checked_mul(cols).ok_or(Trap::from(TrapCode::MemoryOutOfBounds));
+                let (mk, kn, mn) = (size(m, k)?, size(k, n)?, size(m, n)?);
+                // Bounds-check the output before allocating it.
+                if range(out, mn.saturating_mul(4))?.end > memory(&caller)?.data(&caller).len() {
+                    return Err(Trap::from(TrapCode::MemoryOutOfBounds));
+                }
+                charge(&mut caller, mk.saturating_mul(n).max(mn))?;
+                let (a, b) = (read_f32s(&caller, a, mk)?, read_f32s(&caller, b, kn)?);
+                let mut product = vec![0f32; mn];
+                for i in 0..m {
+                    for p in 0..k {
+                        let aip = a[i * k + p];
+                        for j in 0..n {
+                            product[i * n + j] += aip * b[p * n + j];
+                        }
+                    }
+                }
+                write_f32s(&mut caller, out, &product)
+            },
+        )?;
+    }
+
+    if capabilities.metrics {
+        linker.func_wrap(
+            HOST_MODULE,
+            "report_metric",
+            |mut caller: HostCaller<'_>, ptr: i32, len: i32, value: f64| -> Result<(), Trap> {
+                let len = length(len)?;
+                if len > MAX_METRIC_NAME {
+                    return Err(Trap::new("metric name too long"));
+                }
+                if caller.data().metrics.len() >= MAX_METRICS {
+                    return Err(Trap::new("too many metric reports"));
+                }
+                let name = String::from_utf8(read_bytes(&caller, ptr, len)?)
+                    .map_err(|_| Trap::new("metric name is not UTF-8"))?;
+                caller.data_mut().metrics.push((name, value));
+                Ok(())
+            },
+        )?;
+    }
+
+    Ok(linker)
+}
@@ -0,0 +1,230 @@
+//! WebAssembly VM for job-supplied training and evaluation code.
+//!
+//! A job can ship its own training or evaluation loop as a WASM module. The
+//! worker runs it in a sandbox: every instruction is metered against a fuel
+//! budget, linear memory is capped, and the only host functions the module
+//! may import are the parts of the host API its [`Capabilities`] grant, all
+//! under the `bcai` import module:
+//!
+//! - dataset: `dataset_len() -> i32`, `dataset_dim() -> i32` and
+//!   `dataset_read(index: i32, ptr: i32) -> i32`, which writes the sample's
+//!   features as little-endian `f32`s at `ptr` and returns its label, or -1
+//!   past the end;
+//! - tensor ops over `f32` buffers in the module's memory:
+//!   `tensor_add(a, b, out, len)`, `tensor_relu(x, out, len)` and
+//!   `tensor_matmul(a, b, out, m, k, n)`, charged per element;
+//! - metrics: `report_metric(name_ptr, name_len, value: f64)`.
+//!
+//! `write_output(ptr: i32, len: i32)`, which sets the run's artifact (e.g.
+//! the trained weights), is always available. The module exports its
+//! `memory` and the entry points the job names, each `() -> i32` returning
+//! 0 on success.
+//!
+//! Execution needs the `wasm-jobs` feature; without it modules fail to load
+//! with [`WasmVmError::Unsupported`].
+
+#[cfg(feature = "wasm-jobs")]
+mod host;
+
+use crate::data::Sample;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Import module of the host API.
+pub const HOST_MODULE: &str = "bcai";
+/// Longest metric name a module may report.
+pub const MAX_METRIC_NAME: usize = 64;
+/// Metric reports allowed per run.
+pub const MAX_METRICS: usize = 1024;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WasmVmError {
+    #[error("node built without the wasm-jobs feature")]
+    Unsupported,
+    #[error("invalid module: {0}")]
+    Invalid(String),
+    #[error("import {0} is not in the host API granted to the job")]
+    ForbiddenImport(String),
+    #[error("missing export {0}")]
+    MissingExport(String),
+    #[error("ran out of fuel after {0} units")]
+    OutOfFuel(u64),
+    #[error("module trapped: {0}")]
+    Trap(String),
+    #[error("entry point returned {0}")]
+    Exit(i32),
+}
+
+/// Parts of the host API a module may import.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub dataset: bool,
+    pub tensor_ops: bool,
+    pub metrics: bool,
+}
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Self { dataset: true, tensor_ops: true, metrics: true }
+    }
+
+    /// Host functions these capabilities grant.
+    pub fn functions(&self) -> Vec<&'static str> {
+        let mut functions = vec!["write_output"];
+        if self.dataset {
+            functions.extend(["dataset_len", "dataset_dim", "dataset_read"]);
+        }
+        if self.tensor_ops {
+            functions.extend(["tensor_add", "tensor_relu", "tensor_matmul"]);
+        }
+        if self.metrics {
+            functions.push("report_metric");
+        }
+        functions
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmVmConfig {
+    /// Fuel, in interpreter units, one run may burn.
+    pub fuel: u64,
+    /// Largest linear memory the module may grow to.
+    pub max_memory_bytes: usize,
+    /// Largest artifact `write_output` accepts.
+    pub max_output_bytes: usize,
+    pub capabilities: Capabilities,
+}
+
+impl Default for WasmVmConfig {
+    fn default() -> Self {
+        Self {
+            fuel: 1_000_000_000,
+            max_memory_bytes: 64 << 20,
+            max_output_bytes: 16 << 20,
+            capabilities: Capabilities::all(),
+        }
+    }
+}
+
+/// What a run reported through the host API.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunReport {
+    /// Metrics in the order they were reported.
+    pub metrics: Vec<(String, f64)>,
+    /// Last artifact passed to `write_output`.
+    pub output: Vec<u8>,
+    pub fuel_used: u64,
+}
+
+impl RunReport {
+    /// Last value reported for `name`.
+    pub fn metric(&self, name: &str) -> Option<f64> {
+        self.metrics.iter().rev().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+}
+
+/// A validated job module.
+pub struct WasmVm {
+    hash: String,
+    config: WasmVmConfig,
+    #[cfg(feature = "wasm-jobs")]
+    engine: wasmi::Engine,
+    #[cfg(feature = "wasm-jobs")]
+    module: wasmi::Module,
+    #[cfg(feature = "wasm-jobs")]
+    linker: wasmi::Linker<host::Host>,
+}
+
+impl std::fmt::Debug for WasmVm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmVm").field("hash", &self.hash).field("config", &self.config).finish()
+    }
+}
+
+/// Hex SHA-256 of a job module.
+pub fn module_hash(wasm: &[u8]) -> String {
+    hex::encode(Sha256::digest(wasm))
+}
+
+impl WasmVm {
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    pub fn config(&self) -> &WasmVmConfig {
+        &self.config
+    }
+
+    #[cfg(not(feature = "wasm-jobs"))]
+    pub fn load(_wasm: &[u8], _config: WasmVmConfig) -> Result<Self, WasmVmError> {
+        Err(WasmVmError::Unsupported)
+    }
+
+    #[cfg(not(feature = "wasm-jobs"))]
+    pub fn run(&self, _entry: &str, _dataset: Arc<[Sample]>) -> Result<RunReport, WasmVmError> {
+        Err(WasmVmError::Unsupported)
+    }
+
+    /// Compiles `wasm` and checks that it only imports host functions
+    /// `config` grants and exports its memory.
+    #[cfg(feature = "wasm-jobs")]
+    pub fn load(wasm: &[u8], config: WasmVmConfig) -> Result<Self, WasmVmError> {
+        let mut engine_config = wasmi::Config::default();
+        engine_config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&engine_config);
+        let module = wasmi::Module::new(&engine, wasm).map_err(|e| WasmVmError::Invalid(e.to_string()))?;
+
+        let granted = config.capabilities.functions();
+        for import in module.imports() {
+            if import.module() != HOST_MODULE || !granted.contains(&import.name()) {
+                return Err(WasmVmError::ForbiddenImport(format!("{}::{}", import.module(), import.name())));
+            }
+        }
+        if !module.exports().any(|e| e.name() == "memory") {
+            return Err(WasmVmError::MissingExport("memory".into()));
+        }
+        let linker = host::linker(&engine, &config.capabilities).map_err(|e| WasmVmError::Invalid(e.to_string()))?;
+        Ok(Self { hash: module_hash(wasm), config, engine, module, linker })
+    }
+
+    /// Calls `entry` in a fresh instance that reads from `dataset`.
+    #[cfg(feature = "wasm-jobs")]
+    pub fn run(&self, entry: &str, dataset: Arc<[Sample]>) -> Result<RunReport, WasmVmError> {
+        use wasmi::Store;
+
+        let mut store = Store::new(&self.engine, host::Host::new(dataset, &self.config));
+        store.limiter(|host| &mut host.limits);
+        store.add_fuel(self.config.fuel).map_err(|e| WasmVmError::Trap(e.to_string()))?;
+
+        let result = (|| {
+            let instance = self.linker.instantiate(&mut store, &self.module)?.start(&mut store)?;
+            let func = instance
+                .get_typed_func::<(), i32>(&store, entry)
+                .map_err(|_| WasmVmError::MissingExport(entry.to_string()))?;
+            Ok::<_, WasmVmError>(func.call(&mut store, ())?)
+        })();
+
+        let fuel_used = store.fuel_consumed().unwrap_or(0);
+        match result {
+            Ok(0) => {}
+            Ok(code) => return Err(WasmVmError::Exit(code)),
+            Err(WasmVmError::OutOfFuel(_)) => return Err(WasmVmError::OutOfFuel(self.config.fuel)),
+            Err(e) => return Err(e),
+        }
+        let host = store.into_data();
+        Ok(RunReport { metrics: host.metrics, output: host.output, fuel_used })
+    }
+}
+
+#[cfg(feature = "wasm-jobs")]
+impl From<wasmi::Error> for WasmVmError {
+    fn from(e: wasmi::Error) -> Self {
+        match e.as_trap_code() {
+            // The budget is filled in by `WasmVm::run`.
+            Some(wasmi::core::TrapCode::OutOfFuel) => WasmVmError::OutOfFuel(0),
+            _ => WasmVmError::Trap(e.to_string()),
+        }
+    }
+}
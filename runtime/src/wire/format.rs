@@ -0,0 +1,168 @@
+//! Interchangeable encodings of wire messages.
+//!
+//! Protobuf is what nodes send by default. JSON, the format of the first
+//! nodes, and bincode, the legacy version 1 envelope, stay readable, and rkyv
+//! is available with the `wire-rkyv` feature for comparison; `benches/wire_formats.rs`
+//! measures every format on every message kind. Building with one of the
+//! `wire-json`, `wire-bincode` or `wire-rkyv` features makes a node send
+//! that format instead, see [`SELECTED`].
+//!
+//! A framed message starts with a byte telling its format, except JSON,
+//! which starts with `{` or `"` and is recognised by it. Only nodes built
+//! with `wire-rkyv` read rkyv frames.
+
+use super::{proto, WireError, BINCODE_VERSION, PROTOCOL_VERSION};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// First byte of rkyv frames. Not a protocol version: versions stay below it.
+pub const RKYV_TAG: u8 = 0x80;
+
+/// An encoding of wire messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireFormat {
+    /// The [`proto::Envelope`], tagged with [`PROTOCOL_VERSION`].
+    Protobuf,
+    /// Serde JSON, untagged.
+    Json,
+    /// Serde bincode, tagged with [`BINCODE_VERSION`].
+    Bincode,
+    /// An rkyv archive of the [`proto::Envelope`], tagged with [`RKYV_TAG`].
+    #[cfg(feature = "wire-rkyv")]
+    Rkyv,
+}
+
+/// The format this node sends.
+#[cfg(feature = "wire-rkyv")]
+pub const SELECTED: WireFormat = WireFormat::Rkyv;
+/// The format this node sends.
+#[cfg(all(feature = "wire-bincode", not(feature = "wire-rkyv")))]
+pub const SELECTED: WireFormat = WireFormat::Bincode;
+/// The format this node sends.
+#[cfg(all(feature = "wire-json", not(any(feature = "wire-bincode", feature = "wire-rkyv"))))]
+pub const SELECTED: WireFormat = WireFormat::Json;
+/// The format this node sends.
+#[cfg(not(any(feature = "wire-json", feature = "wire-bincode", feature = "wire-rkyv")))]
+pub const SELECTED: WireFormat = WireFormat::Protobuf;
+
+/// A message type that travels in every [`WireFormat`]; it must also
+/// convert into a [`proto::Envelope`].
+pub trait WireEncode:
+    Serialize + DeserializeOwned + TryFrom<proto::Envelope, Error = WireError>
+{
+}
+
+impl<M> WireEncode for M where
+    M: Serialize + DeserializeOwned + TryFrom<proto::Envelope, Error = WireError>
+{
+}
+
+impl WireFormat {
+    /// The formats this build speaks.
+    pub fn all() -> Vec<WireFormat> {
+        let mut formats = vec![WireFormat::Protobuf, WireFormat::Json, WireFormat::Bincode];
+        #[cfg(feature = "wire-rkyv")]
+        formats.push(WireFormat::Rkyv);
+        formats
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WireFormat::Protobuf => "protobuf",
+            WireFormat::Json => "json",
+            WireFormat::Bincode => "bincode",
+            #[cfg(feature = "wire-rkyv")]
+            WireFormat::Rkyv => "rkyv",
+        }
+    }
+
+    /// The byte frames of this format start with, if any.
+    pub fn tag(self) -> Option<u8> {
+        match self {
+            WireFormat::Protobuf => Some(PROTOCOL_VERSION),
+            WireFormat::Json => None,
+            WireFormat::Bincode => Some(BINCODE_VERSION),
+            #[cfg(feature = "wire-rkyv")]
+            WireFormat::Rkyv => Some(RKYV_TAG),
+        }
+    }
+
+    /// `message` in this format, without a frame.
+    pub fn encode<M>(self, message: &M) -> Result<Vec<u8>, WireError>
+    where
+        M: WireEncode,
+        for<'a> proto::Envelope: From<&'a M>,
+    {
+        let malformed = |e: &dyn std::fmt::Display| WireError::Malformed(e.to_string());
+        match self {
+            WireFormat::Protobuf => {
+                Ok(prost::Message::encode_to_vec(&proto::Envelope::from(message)))
+            }
+            WireFormat::Json => serde_json::to_vec(message).map_err(|e| malformed(&e)),
+            WireFormat::Bincode => bincode::serialize(message).map_err(|e| malformed(&e)),
+            #[cfg(feature = "wire-rkyv")]
+            WireFormat::Rkyv => rkyv::to_bytes::<_, 1024>(&proto::Envelope::from(message))
+                .map(|bytes| bytes.to_vec())
+                .map_err(|e| malformed(&e)),
+        }
+    }
+
+    /// A message from the output of [`WireFormat::encode`].
+    pub fn decode<M>(self, bytes: &[u8]) -> Result<M, WireError>
+    where
+        M: WireEncode,
+        for<'a> proto::Envelope: From<&'a M>,
+    {
+        let malformed = |e: &dyn std::fmt::Display| WireError::Malformed(e.to_string());
+        match self {
+            WireFormat::Protobuf => <proto::Envelope as prost::Message>::decode(bytes)
+                .map_err(|e| malformed(&e))?
+                .try_into(),
+            WireFormat::Json => serde_json::from_slice(bytes).map_err(|e| malformed(&e)),
+            WireFormat::Bincode => bincode::deserialize(bytes).map_err(|e| malformed(&e)),
+            #[cfg(feature = "wire-rkyv")]
+            WireFormat::Rkyv => {
+                // Archives are read in place and must be aligned.
+                let mut aligned = rkyv::AlignedVec::with_capacity(bytes.len());
+                aligned.extend_from_slice(bytes);
+                rkyv::from_bytes::<proto::Envelope>(&aligned).map_err(|e| malformed(&e))?.try_into()
+            }
+        }
+    }
+
+    /// The format of `frame` and its body.
+    pub fn detect(frame: &[u8]) -> Result<(WireFormat, &[u8]), WireError> {
+        match frame.split_first() {
+            None => Err(WireError::Empty),
+            Some((b'{' | b'"', _)) => Ok((WireFormat::Json, frame)),
+            Some((&PROTOCOL_VERSION, body)) => Ok((WireFormat::Protobuf, body)),
+            Some((&BINCODE_VERSION, body)) => Ok((WireFormat::Bincode, body)),
+            #[cfg(feature = "wire-rkyv")]
+            Some((&RKYV_TAG, body)) => Ok((WireFormat::Rkyv, body)),
+            Some((&version, _)) => Err(WireError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// `message` encoded in `format` behind its tag.
+pub fn frame<M>(format: WireFormat, message: &M) -> Result<Vec<u8>, WireError>
+where
+    M: WireEncode,
+    for<'a> proto::Envelope: From<&'a M>,
+{
+    let body = format.encode(message)?;
+    let mut bytes = Vec::with_capacity(body.len() + 1);
+    bytes.extend(format.tag());
+    bytes.extend(body);
+    Ok(bytes)
+}
+
+/// A message framed by [`frame`] in any format this build reads.
+pub fn unframe<M>(bytes: &[u8]) -> Result<M, WireError>
+where
+    M: WireEncode,
+    for<'a> proto::Envelope: From<&'a M>,
+{
+    let (format, body) = WireFormat::detect(bytes)?;
+    format.decode(body)
+}
@@ -0,0 +1,198 @@
+//! Defines the wire protocol for messages exchanged between BCAI nodes.
+//!
+//! This module contains the serializable message types that are sent over the P2P network.
+//! Using a unified `WireMessage` enum ensures that all communication is strongly typed
+//! and can be versioned and handled gracefully.
+//!
+//! Every message travels in an envelope whose first byte is the protocol
+//! version it was encoded with. When two nodes connect they exchange a
+//! [`Hello`] naming the versions and features each supports, and
+//! [`Hello::negotiate`] settles on the highest common version and the shared
+//! features. A node refuses envelopes of versions it does not speak instead
+//! of misreading them, so a format change bumps [`PROTOCOL_VERSION`] and
+//! older versions stay readable until [`MIN_PROTOCOL_VERSION`] moves past
+//! them. Version [`HANDSHAKE_VERSION`] is reserved for the hello itself,
+//! whose format never changes.
+//!
+//! Version 2 encodes messages as protobuf, see [`proto`] and
+//! `runtime/proto/wire.proto`, so fields can be added under new tags without
+//! a version bump and clients in other languages can speak the protocol.
+//! Version 1, the original bincode encoding, is still decoded for peers that
+//! have not upgraded yet. Other encodings of the same messages, and the
+//! build features selecting them, are in [`format`].
+
+pub mod format;
+pub mod proto;
+
+use crate::blockchain::{Block, Transaction};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// Envelope version this node encodes with.
+pub const PROTOCOL_VERSION: u8 = 2;
+/// Oldest envelope version this node still reads.
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+/// Envelope version of the legacy bincode encoding.
+pub const BINCODE_VERSION: u8 = 1;
+/// Envelope version of [`Hello`] frames.
+pub const HANDSHAKE_VERSION: u8 = 0;
+
+/// The top-level message envelope for all P2P communication.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WireMessage {
+    /// A new block that has been mined or received.
+    Block(Block),
+    /// A new transaction submitted to the network.
+    Transaction(Transaction),
+    /// A request to get blocks from a certain height.
+    GetBlocks { from_height: u64 },
+    /// A response containing a batch of blocks.
+    Blocks(Vec<Block>),
+    /// A generic ping message for testing connectivity.
+    Ping,
+    /// A generic pong response.
+    Pong,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WireError {
+    #[error("empty envelope")]
+    Empty,
+    #[error("unsupported protocol version {0}")]
+    UnsupportedVersion(u8),
+    #[error("no common protocol version: we speak {local_min}..={local_max}, peer {remote_min}..={remote_max}")]
+    Incompatible { local_min: u8, local_max: u8, remote_min: u8, remote_max: u8 },
+    #[error("malformed envelope: {0}")]
+    Malformed(String),
+}
+
+/// Optional capabilities a node announces in its [`Hello`]. Peers announce
+/// them by name, so names unknown to this node are carried along and
+/// ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Gossip split into typed topics, see [`crate::gossip`].
+    TypedGossip,
+    /// Onion-routed bidding, see [`crate::onion`].
+    OnionRouting,
+    /// Large payloads fetched by hash after an announcement.
+    PayloadTransfer,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 3] = [Feature::TypedGossip, Feature::OnionRouting, Feature::PayloadTransfer];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::TypedGossip => "typed-gossip",
+            Feature::OnionRouting => "onion-routing",
+            Feature::PayloadTransfer => "payload-transfer",
+        }
+    }
+}
+
+/// What a node speaks, exchanged when a connection opens.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Hello {
+    pub min_version: u8,
+    pub max_version: u8,
+    pub features: BTreeSet<String>,
+}
+
+impl Default for Hello {
+    /// Everything this build supports.
+    fn default() -> Self {
+        Self::new(MIN_PROTOCOL_VERSION, PROTOCOL_VERSION, Feature::ALL)
+    }
+}
+
+impl Hello {
+    pub fn new(min_version: u8, max_version: u8, features: impl IntoIterator<Item = Feature>) -> Self {
+        let features = features.into_iter().map(|f| f.name().to_string()).collect();
+        Self { min_version, max_version, features }
+    }
+
+    /// The highest version both sides speak and the features both announce.
+    pub fn negotiate(&self, remote: &Hello) -> Result<Session, WireError> {
+        let version = self.max_version.min(remote.max_version);
+        if version < self.min_version.max(remote.min_version) || version == HANDSHAKE_VERSION {
+            return Err(WireError::Incompatible {
+                local_min: self.min_version,
+                local_max: self.max_version,
+                remote_min: remote.min_version,
+                remote_max: remote.max_version,
+            });
+        }
+        let features = self.features.intersection(&remote.features).cloned().collect();
+        Ok(Session { version, features })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![HANDSHAKE_VERSION];
+        bytes.extend(bincode::serialize(self).expect("hello serializes"));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, WireError> {
+        match bytes.split_first() {
+            None => Err(WireError::Empty),
+            Some((&HANDSHAKE_VERSION, body)) => {
+                bincode::deserialize(body).map_err(|e| WireError::Malformed(e.to_string()))
+            }
+            Some((&version, _)) => Err(WireError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// What two connected nodes agreed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub version: u8,
+    pub features: BTreeSet<String>,
+}
+
+impl Session {
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.features.contains(feature.name())
+    }
+
+    /// Envelope `message` at the negotiated version.
+    pub fn encode(&self, message: &WireMessage) -> Result<Vec<u8>, WireError> {
+        encode(self.version, message)
+    }
+}
+
+fn supported(version: u8) -> bool {
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version)
+}
+
+/// Envelope `message` at `version`.
+pub fn encode(version: u8, message: &WireMessage) -> Result<Vec<u8>, WireError> {
+    if !supported(version) {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    let mut bytes = vec![version];
+    if version == BINCODE_VERSION {
+        bytes.extend(bincode::serialize(message).map_err(|e| WireError::Malformed(e.to_string()))?);
+    } else {
+        proto::Envelope::from(message).encode(&mut bytes).expect("vec has capacity");
+    }
+    Ok(bytes)
+}
+
+/// Open an envelope, returning the version it was encoded with and the
+/// message.
+pub fn decode(bytes: &[u8]) -> Result<(u8, WireMessage), WireError> {
+    let (&version, body) = bytes.split_first().ok_or(WireError::Empty)?;
+    if !supported(version) {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    let message = if version == BINCODE_VERSION {
+        bincode::deserialize(body).map_err(|e| WireError::Malformed(e.to_string()))?
+    } else {
+        proto::Envelope::decode(body).map_err(|e| WireError::Malformed(e.to_string()))?.try_into()?
+    };
+    Ok((version, message))
+}
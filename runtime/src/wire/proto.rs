@@ -0,0 +1,658 @@
+//! Protobuf schema of the wire format, mirrored in `runtime/proto/wire.proto`.
+//!
+//! With the `wire-rkyv` feature the same types also archive with rkyv, see
+//! [`WireFormat::Rkyv`](super::format::WireFormat).
+//!
+//! Every field carries an explicit tag, so fields can be added without
+//! breaking older readers, which skip tags they do not know, and clients in
+//! other languages can generate their types from the `.proto` file. Tags are
+//! never reused: a removed field's tag stays reserved. Storage payloads of
+//! transactions travel as their JSON encoding until they get a schema of
+//! their own.
+
+use super::WireError;
+use crate::blockchain::transaction::{MultisigSignature, StorageTx, TimeLock as ChainTimeLock};
+use crate::connection_manager::PeerRole as ChainPeerRole;
+use crate::job_channel::{ChannelKeyCert as ChainChannelKeyCert, ChannelMessage as ChainChannelMessage};
+use crate::large_data_transfer::network::PayloadDescriptor as ChainPayloadDescriptor;
+use crate::onion::{OnionPacket as ChainOnionPacket, Sealed as ChainSealed};
+use crate::pouw::types::{PoUWSolution, PoUWTask};
+
+/// One message on the wire. Unknown bodies, i.e. message kinds added after
+/// this build, decode to `None`.
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Envelope {
+    #[prost(
+        oneof = "Body",
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18"
+    )]
+    pub body: Option<Body>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub enum Body {
+    #[prost(message, tag = "1")]
+    Block(Block),
+    #[prost(message, tag = "2")]
+    Transaction(Transaction),
+    #[prost(message, tag = "3")]
+    GetBlocks(GetBlocks),
+    #[prost(message, tag = "4")]
+    Blocks(Blocks),
+    #[prost(message, tag = "5")]
+    Ping(Empty),
+    #[prost(message, tag = "6")]
+    Pong(Empty),
+    #[prost(message, tag = "7")]
+    Announce(PayloadDescriptor),
+    #[prost(message, tag = "8")]
+    GetPayload(GetPayload),
+    #[prost(message, tag = "9")]
+    Payload(Payload),
+    #[prost(message, tag = "10")]
+    GetRoles(Empty),
+    #[prost(message, tag = "11")]
+    Roles(Roles),
+    #[prost(message, tag = "12")]
+    GetOnionKey(Empty),
+    #[prost(message, tag = "13")]
+    OnionKey(OnionKey),
+    #[prost(message, tag = "14")]
+    Onion(OnionPacket),
+    #[prost(message, tag = "15")]
+    OnionDelivery(Sealed),
+    #[prost(message, tag = "16")]
+    GetChannelKey(Empty),
+    #[prost(message, tag = "17")]
+    ChannelKey(ChannelKey),
+    #[prost(message, tag = "18")]
+    JobChannel(ChannelMessage),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Empty {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct GetBlocks {
+    #[prost(uint64, tag = "1")]
+    pub from_height: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Blocks {
+    #[prost(message, repeated, tag = "1")]
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Block {
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    #[prost(string, tag = "2")]
+    pub hash: String,
+    #[prost(string, tag = "3")]
+    pub prev_hash: String,
+    #[prost(int64, tag = "4")]
+    pub timestamp: i64,
+    #[prost(message, repeated, tag = "5")]
+    pub transactions: Vec<Transaction>,
+    #[prost(uint32, tag = "6")]
+    pub difficulty: u32,
+    #[prost(string, tag = "7")]
+    pub miner: String,
+    #[prost(message, optional, tag = "8")]
+    pub task: Option<Task>,
+    #[prost(message, optional, tag = "9")]
+    pub solution: Option<Solution>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Task {
+    #[prost(string, tag = "1")]
+    pub model_id: String,
+    #[prost(string, tag = "2")]
+    pub dataset_id: String,
+    #[prost(string, optional, tag = "3")]
+    pub model_hash: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub dataset_hash: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub dataset_root: Option<String>,
+    #[prost(uint32, tag = "6")]
+    pub epochs: u32,
+    #[prost(uint64, tag = "7")]
+    pub timestamp: u64,
+    /// 32 bytes.
+    #[prost(bytes = "vec", tag = "8")]
+    pub challenge: Vec<u8>,
+    #[prost(string, optional, tag = "9")]
+    pub trace: Option<String>,
+    #[prost(uint64, optional, tag = "10")]
+    pub job_id: Option<u64>,
+    #[prost(string, optional, tag = "11")]
+    pub kind: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Solution {
+    #[prost(string, tag = "1")]
+    pub trained_model_hash: String,
+    #[prost(uint32, tag = "2")]
+    pub accuracy: u32,
+    #[prost(uint64, tag = "3")]
+    pub nonce: u64,
+    #[prost(uint64, tag = "4")]
+    pub computation_time_ms: u64,
+    #[prost(uint64, tag = "5")]
+    pub training_time_ms: u64,
+    #[prost(string, repeated, tag = "6")]
+    pub checkpoints: Vec<String>,
+    #[prost(string, repeated, tag = "7")]
+    pub gradient_roots: Vec<String>,
+    #[prost(string, optional, tag = "8")]
+    pub zk_proof: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Transaction {
+    #[prost(string, tag = "1")]
+    pub from: String,
+    #[prost(string, tag = "2")]
+    pub to: String,
+    #[prost(uint64, tag = "3")]
+    pub amount: u64,
+    #[prost(uint64, tag = "4")]
+    pub fee: u64,
+    #[prost(uint64, tag = "5")]
+    pub nonce: u64,
+    #[prost(string, optional, tag = "6")]
+    pub signature: Option<String>,
+    #[prost(message, repeated, tag = "7")]
+    pub multisig_signatures: Vec<CoSignature>,
+    #[prost(oneof = "TimeLock", tags = "8, 9")]
+    pub valid_after: Option<TimeLock>,
+    /// JSON encoding of the storage payload.
+    #[prost(bytes = "vec", optional, tag = "10")]
+    pub storage: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub enum TimeLock {
+    #[prost(uint64, tag = "8")]
+    BlockHeight(u64),
+    #[prost(int64, tag = "9")]
+    Timestamp(i64),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct CoSignature {
+    #[prost(string, tag = "1")]
+    pub signer: String,
+    #[prost(string, tag = "2")]
+    pub signature: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct PayloadDescriptor {
+    #[prost(string, tag = "1")]
+    pub hash: String,
+    #[prost(uint64, tag = "2")]
+    pub size: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct GetPayload {
+    #[prost(string, tag = "1")]
+    pub hash: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Payload {
+    #[prost(string, tag = "1")]
+    pub hash: String,
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub body: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum PeerRole {
+    Unspecified = 0,
+    Validator = 1,
+    StorageProvider = 2,
+    Relay = 3,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Roles {
+    #[prost(enumeration = "PeerRole", repeated, tag = "1")]
+    pub roles: Vec<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct OnionKey {
+    /// 32 bytes; absent if the peer does not relay.
+    #[prost(bytes = "vec", optional, tag = "1")]
+    pub key: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct Sealed {
+    /// 32 bytes.
+    #[prost(bytes = "vec", tag = "1")]
+    pub ephemeral: Vec<u8>,
+    /// 12 bytes.
+    #[prost(bytes = "vec", tag = "2")]
+    pub nonce: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub ciphertext: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct OnionPacket {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<Sealed>,
+    #[prost(message, optional, tag = "2")]
+    pub payload: Option<Sealed>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct ChannelKeyCert {
+    #[prost(string, tag = "1")]
+    pub identity: String,
+    /// 32 bytes.
+    #[prost(bytes = "vec", tag = "2")]
+    pub key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub signature: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct ChannelKey {
+    /// Absent if the peer has no job channel key.
+    #[prost(message, optional, tag = "1")]
+    pub cert: Option<ChannelKeyCert>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+#[cfg_attr(feature = "wire-rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), archive(check_bytes))]
+pub struct ChannelMessage {
+    #[prost(uint64, tag = "1")]
+    pub job_id: u64,
+    #[prost(string, tag = "2")]
+    pub from: String,
+    #[prost(string, tag = "3")]
+    pub to: String,
+    #[prost(uint64, tag = "4")]
+    pub seq: u64,
+    #[prost(message, optional, tag = "5")]
+    pub sealed: Option<Sealed>,
+}
+
+fn malformed(reason: impl Into<String>) -> WireError {
+    WireError::Malformed(reason.into())
+}
+
+fn array<const N: usize>(bytes: Vec<u8>, what: &str) -> Result<[u8; N], WireError> {
+    bytes.try_into().map_err(|_| malformed(format!("{what} must be {N} bytes")))
+}
+
+fn required<T>(field: Option<T>, what: &str) -> Result<T, WireError> {
+    field.ok_or_else(|| malformed(format!("missing {what}")))
+}
+
+impl From<&crate::blockchain::Block> for Block {
+    fn from(block: &crate::blockchain::Block) -> Self {
+        Self {
+            index: block.index,
+            hash: block.hash.clone(),
+            prev_hash: block.prev_hash.clone(),
+            timestamp: block.timestamp,
+            transactions: block.transactions.iter().map(Transaction::from).collect(),
+            difficulty: block.difficulty,
+            miner: block.miner.clone(),
+            task: Some(Task::from(&block.task)),
+            solution: Some(Solution::from(&block.solution)),
+        }
+    }
+}
+
+impl TryFrom<Block> for crate::blockchain::Block {
+    type Error = WireError;
+
+    fn try_from(block: Block) -> Result<Self, WireError> {
+        Ok(Self {
+            index: block.index,
+            hash: block.hash,
+            prev_hash: block.prev_hash,
+            timestamp: block.timestamp,
+            transactions: block.transactions.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?,
+            difficulty: block.difficulty,
+            miner: block.miner,
+            task: required(block.task, "block task")?.try_into()?,
+            solution: required(block.solution, "block solution")?.into(),
+        })
+    }
+}
+
+impl From<&PoUWTask> for Task {
+    fn from(task: &PoUWTask) -> Self {
+        Self {
+            model_id: task.model_id.clone(),
+            dataset_id: task.dataset_id.clone(),
+            model_hash: task.model_hash.clone(),
+            dataset_hash: task.dataset_hash.clone(),
+            dataset_root: task.dataset_root.clone(),
+            epochs: task.epochs,
+            timestamp: task.timestamp,
+            challenge: task.challenge.to_vec(),
+            trace: task.trace.clone(),
+            job_id: task.job_id,
+            kind: task.kind.clone(),
+        }
+    }
+}
+
+impl TryFrom<Task> for PoUWTask {
+    type Error = WireError;
+
+    fn try_from(task: Task) -> Result<Self, WireError> {
+        Ok(Self {
+            model_id: task.model_id,
+            dataset_id: task.dataset_id,
+            model_hash: task.model_hash,
+            dataset_hash: task.dataset_hash,
+            dataset_root: task.dataset_root,
+            epochs: task.epochs,
+            timestamp: task.timestamp,
+            challenge: array(task.challenge, "task challenge")?,
+            trace: task.trace,
+            job_id: task.job_id,
+            kind: task.kind,
+        })
+    }
+}
+
+impl From<&PoUWSolution> for Solution {
+    fn from(solution: &PoUWSolution) -> Self {
+        Self {
+            trained_model_hash: solution.trained_model_hash.clone(),
+            accuracy: solution.accuracy,
+            nonce: solution.nonce,
+            computation_time_ms: solution.computation_time_ms,
+            training_time_ms: solution.training_time_ms,
+            checkpoints: solution.checkpoints.clone(),
+            gradient_roots: solution.gradient_roots.clone(),
+            zk_proof: solution.zk_proof.clone(),
+        }
+    }
+}
+
+impl From<Solution> for PoUWSolution {
+    fn from(solution: Solution) -> Self {
+        Self {
+            trained_model_hash: solution.trained_model_hash,
+            accuracy: solution.accuracy,
+            nonce: solution.nonce,
+            computation_time_ms: solution.computation_time_ms,
+            training_time_ms: solution.training_time_ms,
+            checkpoints: solution.checkpoints,
+            gradient_roots: solution.gradient_roots,
+            zk_proof: solution.zk_proof,
+        }
+    }
+}
+
+impl From<&crate::blockchain::Transaction> for Transaction {
+    fn from(tx: &crate::blockchain::Transaction) -> Self {
+        Self {
+            from: tx.from.clone(),
+            to: tx.to.clone(),
+            amount: tx.amount,
+            fee: tx.fee,
+            nonce: tx.nonce,
+            signature: tx.signature.clone(),
+            multisig_signatures: tx
+                .multisig_signatures
+                .iter()
+                .map(|s| CoSignature { signer: s.signer.clone(), signature: s.signature.clone() })
+                .collect(),
+            valid_after: tx.valid_after.map(|lock| match lock {
+                ChainTimeLock::BlockHeight(height) => TimeLock::BlockHeight(height),
+                ChainTimeLock::Timestamp(at) => TimeLock::Timestamp(at),
+            }),
+            storage: tx.storage.as_ref().map(|s| serde_json::to_vec(s).expect("storage payloads serialize")),
+        }
+    }
+}
+
+impl TryFrom<Transaction> for crate::blockchain::Transaction {
+    type Error = WireError;
+
+    fn try_from(tx: Transaction) -> Result<Self, WireError> {
+        let storage = tx
+            .storage
+            .map(|bytes| serde_json::from_slice::<StorageTx>(&bytes))
+            .transpose()
+            .map_err(|e| malformed(format!("storage payload: {e}")))?;
+        Ok(Self {
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            fee: tx.fee,
+            nonce: tx.nonce,
+            storage,
+            signature: tx.signature,
+            multisig_signatures: tx
+                .multisig_signatures
+                .into_iter()
+                .map(|s| MultisigSignature { signer: s.signer, signature: s.signature })
+                .collect(),
+            valid_after: tx.valid_after.map(|lock| match lock {
+                TimeLock::BlockHeight(height) => ChainTimeLock::BlockHeight(height),
+                TimeLock::Timestamp(at) => ChainTimeLock::Timestamp(at),
+            }),
+        })
+    }
+}
+
+impl From<&ChainPayloadDescriptor> for PayloadDescriptor {
+    fn from(descriptor: &ChainPayloadDescriptor) -> Self {
+        Self { hash: descriptor.hash.clone(), size: descriptor.size }
+    }
+}
+
+impl From<PayloadDescriptor> for ChainPayloadDescriptor {
+    fn from(descriptor: PayloadDescriptor) -> Self {
+        Self { hash: descriptor.hash, size: descriptor.size }
+    }
+}
+
+impl From<ChainPeerRole> for PeerRole {
+    fn from(role: ChainPeerRole) -> Self {
+        match role {
+            ChainPeerRole::Validator => PeerRole::Validator,
+            ChainPeerRole::StorageProvider => PeerRole::StorageProvider,
+            ChainPeerRole::Relay => PeerRole::Relay,
+        }
+    }
+}
+
+impl Roles {
+    pub fn new(roles: &[ChainPeerRole]) -> Self {
+        Self { roles: roles.iter().map(|r| PeerRole::from(*r) as i32).collect() }
+    }
+
+    /// The roles this build knows; roles added later are skipped.
+    pub fn known(&self) -> Vec<ChainPeerRole> {
+        self.roles
+            .iter()
+            .filter_map(|r| match PeerRole::try_from(*r).ok()? {
+                PeerRole::Validator => Some(ChainPeerRole::Validator),
+                PeerRole::StorageProvider => Some(ChainPeerRole::StorageProvider),
+                PeerRole::Relay => Some(ChainPeerRole::Relay),
+                PeerRole::Unspecified => None,
+            })
+            .collect()
+    }
+}
+
+impl OnionKey {
+    pub fn new(key: Option<[u8; 32]>) -> Self {
+        Self { key: key.map(|k| k.to_vec()) }
+    }
+
+    pub fn key(self) -> Result<Option<[u8; 32]>, WireError> {
+        self.key.map(|k| array(k, "onion key")).transpose()
+    }
+}
+
+impl From<&ChainSealed> for Sealed {
+    fn from(sealed: &ChainSealed) -> Self {
+        Self {
+            ephemeral: sealed.ephemeral.to_vec(),
+            nonce: sealed.nonce.to_vec(),
+            ciphertext: sealed.ciphertext.clone(),
+        }
+    }
+}
+
+impl TryFrom<Sealed> for ChainSealed {
+    type Error = WireError;
+
+    fn try_from(sealed: Sealed) -> Result<Self, WireError> {
+        Ok(Self {
+            ephemeral: array(sealed.ephemeral, "ephemeral key")?,
+            nonce: array(sealed.nonce, "nonce")?,
+            ciphertext: sealed.ciphertext,
+        })
+    }
+}
+
+impl From<&ChainOnionPacket> for OnionPacket {
+    fn from(packet: &ChainOnionPacket) -> Self {
+        Self { header: Some(Sealed::from(&packet.header)), payload: packet.payload.as_ref().map(Sealed::from) }
+    }
+}
+
+impl TryFrom<OnionPacket> for ChainOnionPacket {
+    type Error = WireError;
+
+    fn try_from(packet: OnionPacket) -> Result<Self, WireError> {
+        Ok(Self {
+            header: required(packet.header, "onion header")?.try_into()?,
+            payload: packet.payload.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+impl From<&ChainChannelKeyCert> for ChannelKeyCert {
+    fn from(cert: &ChainChannelKeyCert) -> Self {
+        Self { identity: cert.identity.clone(), key: cert.key.to_vec(), signature: cert.signature.clone() }
+    }
+}
+
+impl TryFrom<ChannelKeyCert> for ChainChannelKeyCert {
+    type Error = WireError;
+
+    fn try_from(cert: ChannelKeyCert) -> Result<Self, WireError> {
+        Ok(Self { identity: cert.identity, key: array(cert.key, "channel key")?, signature: cert.signature })
+    }
+}
+
+impl ChannelKey {
+    pub fn new(cert: Option<&ChainChannelKeyCert>) -> Self {
+        Self { cert: cert.map(ChannelKeyCert::from) }
+    }
+
+    pub fn cert(self) -> Result<Option<ChainChannelKeyCert>, WireError> {
+        self.cert.map(TryInto::try_into).transpose()
+    }
+}
+
+impl From<&ChainChannelMessage> for ChannelMessage {
+    fn from(message: &ChainChannelMessage) -> Self {
+        Self {
+            job_id: message.job_id,
+            from: message.from.clone(),
+            to: message.to.clone(),
+            seq: message.seq,
+            sealed: Some(Sealed {
+                ephemeral: message.ephemeral.to_vec(),
+                nonce: message.nonce.to_vec(),
+                ciphertext: message.ciphertext.clone(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<ChannelMessage> for ChainChannelMessage {
+    type Error = WireError;
+
+    fn try_from(message: ChannelMessage) -> Result<Self, WireError> {
+        let sealed = required(message.sealed, "sealed artifact")?;
+        Ok(Self {
+            job_id: message.job_id,
+            from: message.from,
+            to: message.to,
+            seq: message.seq,
+            ephemeral: array(sealed.ephemeral, "ephemeral key")?,
+            nonce: array(sealed.nonce, "nonce")?,
+            ciphertext: sealed.ciphertext,
+        })
+    }
+}
+
+impl From<&super::WireMessage> for Envelope {
+    fn from(message: &super::WireMessage) -> Self {
+        use super::WireMessage;
+        let body = match message {
+            WireMessage::Block(block) => Body::Block(block.into()),
+            WireMessage::Transaction(tx) => Body::Transaction(tx.into()),
+            WireMessage::GetBlocks { from_height } => Body::GetBlocks(GetBlocks { from_height: *from_height }),
+            WireMessage::Blocks(blocks) => Body::Blocks(Blocks { blocks: blocks.iter().map(Block::from).collect() }),
+            WireMessage::Ping => Body::Ping(Empty {}),
+            WireMessage::Pong => Body::Pong(Empty {}),
+        };
+        Self { body: Some(body) }
+    }
+}
+
+impl TryFrom<Envelope> for super::WireMessage {
+    type Error = WireError;
+
+    fn try_from(envelope: Envelope) -> Result<Self, WireError> {
+        use super::WireMessage;
+        Ok(match required(envelope.body, "message body")? {
+            Body::Block(block) => WireMessage::Block(block.try_into()?),
+            Body::Transaction(tx) => WireMessage::Transaction(tx.try_into()?),
+            Body::GetBlocks(get) => WireMessage::GetBlocks { from_height: get.from_height },
+            Body::Blocks(blocks) => {
+                WireMessage::Blocks(blocks.blocks.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?)
+            }
+            Body::Ping(_) => WireMessage::Ping,
+            Body::Pong(_) => WireMessage::Pong,
+            _ => return Err(malformed("not a chain message")),
+        })
+    }
+}
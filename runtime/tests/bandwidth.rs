@@ -0,0 +1,69 @@
+use runtime::large_data_transfer::network::{BandwidthConfig, BandwidthLimiter, Direction, PeerBandwidth};
+use std::time::{Duration, Instant};
+
+fn limiter(config: BandwidthConfig) -> BandwidthLimiter<&'static str> {
+    BandwidthLimiter::new(config)
+}
+
+#[test]
+fn no_caps_never_hold_transfers_back() {
+    let mut bandwidth = limiter(BandwidthConfig::default());
+    let transfer = bandwidth.start("a", Direction::Upload);
+    assert_eq!(bandwidth.share(transfer), None);
+    assert_eq!(bandwidth.reserve(transfer, 1 << 30, Instant::now()), Duration::ZERO);
+    assert_eq!(bandwidth.counters().throttled, 0);
+}
+
+#[test]
+fn concurrent_transfers_split_the_global_cap() {
+    let config = BandwidthConfig { max_upload: 1_000, ..BandwidthConfig::default() };
+    let mut bandwidth = limiter(config);
+    let now = Instant::now();
+    let first = bandwidth.start("a", Direction::Upload);
+    assert_eq!(bandwidth.reserve(first, 500, now), Duration::from_millis(500));
+
+    let second = bandwidth.start("b", Direction::Upload);
+    assert_eq!(bandwidth.share(first), Some(500));
+    assert_eq!(bandwidth.reserve(second, 500, now), Duration::from_secs(1));
+    // The first transfer's next bytes queue behind what it already reserved.
+    assert_eq!(bandwidth.reserve(first, 500, now), Duration::from_millis(1_500));
+
+    // Downloads are paced separately.
+    let download = bandwidth.start("a", Direction::Download);
+    assert_eq!(bandwidth.share(download), None);
+
+    bandwidth.finish(second);
+    assert_eq!(bandwidth.share(first), Some(1_000));
+    let counters = bandwidth.counters();
+    assert_eq!((counters.active_uploads, counters.active_downloads), (1, 1));
+    assert_eq!(counters.throttled, 3);
+    assert_eq!(counters.throttled_ms, 3_000);
+}
+
+#[test]
+fn a_peer_cap_is_shared_by_that_peers_transfers() {
+    let config = BandwidthConfig { max_download: 10_000, peer_download: 1_000, ..BandwidthConfig::default() };
+    let mut bandwidth = limiter(config);
+    let a1 = bandwidth.start("a", Direction::Download);
+    let a2 = bandwidth.start("a", Direction::Download);
+    let b = bandwidth.start("b", Direction::Download);
+    assert_eq!(bandwidth.share(a1), Some(500));
+    assert_eq!(bandwidth.share(a2), Some(500));
+    // Peer b's own cap is lower than its third of the global cap.
+    assert_eq!(bandwidth.share(b), Some(1_000));
+}
+
+#[test]
+fn counters_report_bytes_per_peer() {
+    let mut bandwidth = limiter(BandwidthConfig::default());
+    bandwidth.record("a", Direction::Upload, 100);
+    bandwidth.record("a", Direction::Download, 40);
+    bandwidth.record("b", Direction::Upload, 10);
+    assert_eq!(bandwidth.peer(&"a"), Some(&PeerBandwidth { sent: 100, received: 40 }));
+
+    let counters = bandwidth.counters();
+    assert_eq!((counters.bytes_sent, counters.bytes_received), (110, 40));
+    assert_eq!(counters.peers["b"], PeerBandwidth { sent: 10, received: 0 });
+    let json = serde_json::to_value(&counters).unwrap();
+    assert_eq!(json["peers"]["a"]["received"], 40);
+}
@@ -0,0 +1,99 @@
+use runtime::federated::canary::{canary_accuracy, predict};
+use runtime::federated::{CanaryConfig, CanaryRound, CanarySample, ModelMetadata, ModelParameters};
+use std::collections::HashMap;
+
+const DIM: usize = 64;
+const CLASSES: usize = 2;
+
+fn model(weights: Vec<f32>) -> ModelParameters {
+    ModelParameters {
+        weights,
+        biases: vec![0.0; CLASSES],
+        layer_sizes: vec![DIM, CLASSES],
+        metadata: ModelMetadata {
+            model_id: "fl".into(),
+            version: 1,
+            training_rounds: 0,
+            participant_count: 0,
+            accuracy: 0.0,
+            loss: 0.0,
+            created_at: 0,
+        },
+    }
+}
+
+/// Stand-in for local training: a prototype classifier whose class rows are
+/// the sums of the canaries assigned to that class. `flip` mislabels them.
+fn train(canaries: &[CanarySample], flip: bool, scale: f32) -> ModelParameters {
+    let mut weights = vec![0.0; DIM * CLASSES];
+    for c in canaries {
+        let label = if flip { 1 - c.label } else { c.label };
+        for (w, x) in weights[label * DIM..(label + 1) * DIM].iter_mut().zip(&c.features) {
+            *w += x * scale;
+        }
+    }
+    model(weights)
+}
+
+fn average(models: &[&ModelParameters]) -> ModelParameters {
+    let mut weights = vec![0.0; DIM * CLASSES];
+    for m in models {
+        for (w, x) in weights.iter_mut().zip(&m.weights) {
+            *w += x / models.len() as f32;
+        }
+    }
+    model(weights)
+}
+
+fn nodes() -> Vec<String> {
+    vec!["alice".into(), "bob".into(), "carol".into(), "mallory".into()]
+}
+
+#[test]
+fn canaries_are_deterministic_and_private_per_participant() {
+    let config = CanaryConfig { canaries_per_participant: 4, ..CanaryConfig::default() };
+    let a = CanaryRound::inject(1, [7; 32], &nodes(), DIM, CLASSES, config.clone());
+    let b = CanaryRound::inject(1, [7; 32], &nodes(), DIM, CLASSES, config.clone());
+    assert_eq!(a.canaries_for("alice"), b.canaries_for("alice"));
+    assert_ne!(a.canaries_for("alice"), a.canaries_for("bob"));
+    let next = CanaryRound::inject(2, [7; 32], &nodes(), DIM, CLASSES, config);
+    assert_ne!(a.canaries_for("alice"), next.canaries_for("alice"));
+    assert!(a.canaries_for("eve").is_none());
+}
+
+#[test]
+fn poisoned_participant_is_flagged_and_rejected() {
+    let config = CanaryConfig { canaries_per_participant: 4, ..CanaryConfig::default() };
+    let round = CanaryRound::inject(3, [9; 32], &nodes(), DIM, CLASSES, config);
+
+    let mut updates: HashMap<String, ModelParameters> = nodes()
+        .into_iter()
+        .map(|n| {
+            let poisoned = n == "mallory";
+            let update = train(round.canaries_for(&n).unwrap(), poisoned, if poisoned { 20.0 } else { 1.0 });
+            (n, update)
+        })
+        .collect();
+
+    let honest: Vec<&ModelParameters> = ["alice", "bob", "carol"].iter().map(|n| &updates[*n]).collect();
+    let clean = round.inspect(&updates, &average(&honest), None).unwrap();
+    assert_eq!(clean.participant_accuracy["alice"], 1.0);
+    assert_eq!(clean.flagged, vec!["mallory".to_string()]);
+    assert!(!clean.round_poisoned);
+
+    let all: Vec<&ModelParameters> = updates.values().collect();
+    let boosted = round.inspect(&updates, &average(&all), Some(clean.global_accuracy)).unwrap();
+    assert!(boosted.round_poisoned, "global accuracy {} vs {}", boosted.global_accuracy, clean.global_accuracy);
+
+    assert_eq!(boosted.reject_flagged(&mut updates), 1);
+    assert!(!updates.contains_key("mallory"));
+}
+
+#[test]
+fn forward_pass_checks_dimensions() {
+    let m = model(vec![0.0; DIM * CLASSES]);
+    assert!(predict(&m, &[0.0; 3]).is_err());
+    let mut broken = m.clone();
+    broken.weights.pop();
+    assert!(canary_accuracy(&broken, &[CanarySample { features: vec![0.0; DIM], label: 0 }]).is_err());
+}
@@ -0,0 +1,168 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::node::capability_probe::{
+    attestation_seed, benchmarked_validators, capability_from, parse_cpu_model, parse_df, parse_meminfo,
+    parse_nvidia_smi, run_benchmark, BenchmarkConfig, BenchmarkResult, CapabilityProbe, GpuInfo, HardwareInfo,
+    KernelProof, SignedBenchmark, DEFAULT_MAX_AGE_SECS, DEFAULT_REFRESH_SECS, MAX_PROOF_MATRIX,
+};
+use runtime::node::CapabilityType;
+
+fn quick() -> BenchmarkConfig {
+    BenchmarkConfig { matrix_size: 32, buffer_mb: 1, rounds: 1 }
+}
+
+fn hardware() -> HardwareInfo {
+    HardwareInfo {
+        cpus: 16,
+        cpu_model: "AMD EPYC 7543".into(),
+        memory_mb: 64 * 1024,
+        disk_free_gb: 120,
+        gpus: vec![
+            GpuInfo { model: "NVIDIA A100-SXM4-80GB".into(), memory_mb: 81_920 },
+            GpuInfo { model: "NVIDIA A100-SXM4-40GB".into(), memory_mb: 40_960 },
+        ],
+    }
+}
+
+#[test]
+fn system_reports_are_parsed() {
+    let cpuinfo = "processor\t: 0\nvendor_id\t: AuthenticAMD\nmodel name\t: AMD EPYC 7543 32-Core Processor\n";
+    assert_eq!(parse_cpu_model(cpuinfo).as_deref(), Some("AMD EPYC 7543 32-Core Processor"));
+    assert_eq!(parse_meminfo("MemTotal:       65794036 kB\nMemFree:  1 kB\n"), Some(64_251));
+    assert_eq!(
+        parse_nvidia_smi("NVIDIA GeForce RTX 4090, 24564\nTesla T4, 15360\n"),
+        vec![
+            GpuInfo { model: "NVIDIA GeForce RTX 4090".into(), memory_mb: 24_564 },
+            GpuInfo { model: "Tesla T4".into(), memory_mb: 15_360 },
+        ]
+    );
+    let df = "Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/nvme0n1p2 982940 1000 524288000 1% /\n";
+    assert_eq!(parse_df(df), Some(524_288_000));
+}
+
+#[test]
+fn capabilities_follow_the_hardware() {
+    let key = SigningKey::generate(&mut OsRng);
+    let benchmark = SignedBenchmark::sign(hardware(), BenchmarkResult::new(50.0, 20.0), 1, &key);
+    let capability = capability_from(benchmark, 500, 10);
+
+    assert_eq!((capability.cpus, capability.gpus, capability.gpu_memory_gb), (16, 2, 40));
+    assert!(capability.capability_types.contains(&CapabilityType::GpuAccelerated));
+    assert!(capability.capability_types.contains(&CapabilityType::HighMemory));
+    assert!(!capability.capability_types.contains(&CapabilityType::Storage));
+    assert_eq!(capability.benchmark_score(), 5_200);
+}
+
+#[test]
+fn tampered_benchmarks_score_nothing() {
+    let key = SigningKey::generate(&mut OsRng);
+    let mut benchmark = SignedBenchmark::sign(hardware(), BenchmarkResult::new(10.0, 5.0), 1, &key);
+    assert!(benchmark.verify());
+    benchmark.result = BenchmarkResult::new(1_000.0, 5.0);
+    assert!(!benchmark.verify());
+    assert_eq!(capability_from(benchmark, 0, 0).benchmark_score(), 0);
+}
+
+#[test]
+fn validators_need_their_own_good_benchmark() {
+    let (fast, slow, borrower) = (
+        SigningKey::generate(&mut OsRng),
+        SigningKey::generate(&mut OsRng),
+        SigningKey::generate(&mut OsRng),
+    );
+    let id = |key: &SigningKey| hex::encode(key.verifying_key().to_bytes());
+    let fast_bench = SignedBenchmark::sign(hardware(), BenchmarkResult::new(40.0, 10.0), 1, &fast);
+    let slow_bench = SignedBenchmark::sign(hardware(), BenchmarkResult::new(1.0, 1.0), 1, &slow);
+
+    let eligible = benchmarked_validators(
+        [
+            (id(&fast), 100, Some(&fast_bench)),
+            (id(&slow), 100, Some(&slow_bench)),
+            (id(&borrower), 100, Some(&fast_bench)),
+            ("unbenchmarked".to_string(), 100, None),
+        ],
+        1_000,
+    );
+    assert_eq!(eligible, vec![(id(&fast), 100)]);
+}
+
+#[test]
+fn benchmarks_measure_something() {
+    let result = run_benchmark(&quick(), 7);
+    assert!(result.gflops > 0.0 && result.memory_bandwidth_gbps > 0.0);
+    assert!(result.score > 0);
+    assert_eq!(result.proof, Some(KernelProof::compute(7, 32)));
+}
+
+#[test]
+fn benchmarks_prove_the_seeded_kernel_ran() {
+    let key = SigningKey::generate(&mut OsRng);
+    let probe = CapabilityProbe::new().with_gpu_command("/nonexistent/nvidia-smi").with_benchmark(quick());
+    let benchmark = probe.attest(&key, 1_000);
+    let proof = benchmark.result.proof.clone().unwrap();
+    assert_eq!(proof.seed, attestation_seed(&benchmark.signer, 1_000));
+    assert!(proof.check());
+    assert!(benchmark.attests(1_000 + DEFAULT_MAX_AGE_SECS, DEFAULT_MAX_AGE_SECS));
+    assert!(!benchmark.attests(1_001 + DEFAULT_MAX_AGE_SECS, DEFAULT_MAX_AGE_SECS));
+    assert!(!benchmark.attests(0, DEFAULT_MAX_AGE_SECS));
+
+    // Signed by the node, but the kernel output is made up.
+    let mut wrong = proof.clone();
+    wrong.digest = KernelProof::compute(proof.seed + 1, proof.matrix_size).digest;
+    assert!(!wrong.check());
+    let forged = SignedBenchmark::sign(hardware(), benchmark.result.clone().with_proof(wrong), 1_000, &key);
+    assert!(forged.verify() && !forged.attests(1_000, DEFAULT_MAX_AGE_SECS));
+    // An old run re-signed as new was seeded for another time.
+    let replayed = SignedBenchmark::sign(hardware(), benchmark.result.clone(), 5_000, &key);
+    assert!(!replayed.attests(5_000, DEFAULT_MAX_AGE_SECS));
+    let unproven = SignedBenchmark::sign(hardware(), BenchmarkResult::new(10.0, 1.0), 1_000, &key);
+    assert!(unproven.verify() && !unproven.attests(1_000, DEFAULT_MAX_AGE_SECS));
+    let huge = KernelProof { seed: 1, matrix_size: MAX_PROOF_MATRIX + 1, digest: String::new() };
+    assert!(!huge.check());
+}
+
+#[test]
+fn measured_hardware_replaces_claims_and_is_refreshed() {
+    let key = SigningKey::generate(&mut OsRng);
+    let signer = hex::encode(key.verifying_key().to_bytes());
+    let result = run_benchmark(&quick(), attestation_seed(&signer, 1_000));
+    let mut claimed = capability_from(SignedBenchmark::sign(hardware(), result, 1_000, &key), 500, 10);
+    claimed.gpus = 8;
+    claimed.gpu_memory_gb = 80;
+    claimed.min_benchmark_score = 3;
+
+    let measured = claimed.measured(1_000, DEFAULT_MAX_AGE_SECS).unwrap();
+    assert_eq!((measured.gpus, measured.gpu_memory_gb), (2, 40));
+    assert_eq!((measured.available_stake, measured.min_benchmark_score), (500, 3));
+    assert!(claimed.measured(1_001 + DEFAULT_MAX_AGE_SECS, DEFAULT_MAX_AGE_SECS).is_none());
+
+    let probe = CapabilityProbe::new().with_gpu_command("/nonexistent/nvidia-smi").with_benchmark(quick());
+    let now = 1_000 + DEFAULT_REFRESH_SECS;
+    assert!(!probe.refresh(&mut claimed, &key, now - 1, DEFAULT_REFRESH_SECS));
+    assert!(probe.refresh(&mut claimed, &key, now, DEFAULT_REFRESH_SECS));
+    assert_eq!(claimed.benchmark.as_ref().unwrap().timestamp, now);
+    assert_eq!((claimed.gpus, claimed.available_stake, claimed.min_benchmark_score), (0, 500, 3));
+    assert!(claimed.measured(now + DEFAULT_MAX_AGE_SECS, DEFAULT_MAX_AGE_SECS).is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn probes_read_gpus_from_the_gpu_cli() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("bcai-probe-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let smi = dir.join("fake-nvidia-smi");
+    std::fs::write(&smi, "#!/bin/sh\necho 'NVIDIA L4, 23034'\n").unwrap();
+    std::fs::set_permissions(&smi, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let probe = CapabilityProbe::new().with_gpu_command(&smi).with_disk_path(&dir).with_benchmark(quick());
+    let capability = probe.capability(&SigningKey::generate(&mut OsRng), 0, 0);
+    assert_eq!(capability.gpus, 1);
+    assert_eq!(capability.gpu_memory_gb, 22);
+    assert!(capability.cpus >= 1);
+    assert!(capability.benchmark_score() > 0);
+
+    let missing = CapabilityProbe::new().with_gpu_command(dir.join("absent"));
+    assert!(missing.detect().gpus.is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,115 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::blockchain::archive::{
+    restore_range, verify_archive, ArchiveError, ArchiveStore, DfsStore, DirectoryStore,
+};
+use runtime::blockchain::{ArchiveConfig, Archiver, Block, Blockchain, BlockchainConfig};
+use runtime::large_data_transfer::ChunkManager;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bcai-archive-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// A chain with `height` blocks after genesis, linked but not mined.
+fn chain(height: u32) -> Blockchain {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    for index in 1..=height {
+        let tip = chain.get_tip().clone();
+        chain.blocks.push(Block::new(index, tip.hash, Vec::new(), tip.difficulty, "miner".into(), tip.task, tip.solution));
+    }
+    chain
+}
+
+fn config() -> ArchiveConfig {
+    ArchiveConfig { segment_blocks: 4, finality_depth: 3, snapshots: true }
+}
+
+#[test]
+fn only_final_complete_ranges_are_exported() {
+    let root = dir("ranges");
+    let store = Arc::new(DirectoryStore::new(&root));
+    let key = SigningKey::generate(&mut OsRng);
+    let mut archiver = Archiver::new(store.clone(), key.clone(), config());
+
+    // Blocks 0..=3 are final at tip 6, 4..=7 not until tip 10.
+    let mut chain = chain(9);
+    let manifests = archiver.archive_ready(&chain).unwrap();
+    assert_eq!(manifests.iter().map(|m| (m.from, m.to)).collect::<Vec<_>>(), vec![(0, 3)]);
+    assert!(archiver.archive_ready(&chain).unwrap().is_empty());
+
+    for index in 10..=12 {
+        let tip = chain.get_tip().clone();
+        chain.blocks.push(Block::new(index, tip.hash, Vec::new(), tip.difficulty, "m".into(), tip.task, tip.solution));
+    }
+    let more = archiver.archive_ready(&chain).unwrap();
+    assert_eq!(more.iter().map(|m| (m.from, m.to)).collect::<Vec<_>>(), vec![(4, 7)]);
+    assert_eq!(more[0].prev_manifest, Some(manifests[0].hash()));
+    assert_eq!(archiver.next_block(), 8);
+
+    let on_disk = store.manifests().unwrap();
+    let signer = hex::encode(key.verifying_key().to_bytes());
+    let report = verify_archive(store.as_ref(), &on_disk, Some(&signer)).unwrap();
+    assert_eq!((report.ranges, report.blocks, report.snapshots), (2, 8, 2));
+    assert_eq!(report.last_hash.as_deref(), Some(chain.blocks[7].hash.as_str()));
+    assert_eq!(restore_range(store.as_ref(), &on_disk[1]).unwrap(), chain.blocks[4..=7].to_vec());
+
+    let stranger = hex::encode(SigningKey::generate(&mut OsRng).verifying_key().to_bytes());
+    assert!(verify_archive(store.as_ref(), &on_disk, Some(&stranger)).is_err());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn tampering_is_detected() {
+    let root = dir("tamper");
+    let store = Arc::new(DirectoryStore::new(&root));
+    let mut archiver = Archiver::new(store.clone(), SigningKey::generate(&mut OsRng), config());
+    let manifests = archiver.archive_ready(&chain(14)).unwrap();
+    assert_eq!(manifests.len(), 3);
+    assert!(verify_archive(store.as_ref(), &manifests, None).is_ok());
+
+    // A missing range breaks the manifest links.
+    let gap = [manifests[0].clone(), manifests[2].clone()];
+    assert!(matches!(verify_archive(store.as_ref(), &gap, None), Err(ArchiveError::Corrupt(_))));
+
+    // A forged manifest no longer verifies.
+    let mut forged = manifests.clone();
+    forged[1].last_hash = "0".repeat(64);
+    assert!(matches!(verify_archive(store.as_ref(), &forged, None), Err(ArchiveError::Corrupt(_))));
+
+    // Nor does an object rewritten on disk.
+    let location = &manifests[1].blocks.location;
+    let mut bytes = store.get(location).unwrap();
+    bytes[10] ^= 1;
+    store.put(location, &bytes).unwrap();
+    assert!(matches!(verify_archive(store.as_ref(), &manifests, None), Err(ArchiveError::Corrupt(_))));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn archives_resume_and_can_live_in_the_dfs() {
+    let store = Arc::new(DfsStore::new(Arc::new(ChunkManager::default())));
+    let key = SigningKey::generate(&mut OsRng);
+    let config = ArchiveConfig { snapshots: false, ..config() };
+    let chain = chain(14);
+
+    let mut first = Archiver::new(store.clone(), key.clone(), ArchiveConfig { segment_blocks: 4, finality_depth: 7, ..config.clone() });
+    let mut manifests = first.archive_ready(&chain).unwrap();
+    assert_eq!(manifests.len(), 2);
+
+    let mut resumed = Archiver::new(store.clone(), key, config).resume_after(manifests.last().unwrap());
+    manifests.extend(resumed.archive_ready(&chain).unwrap());
+    assert_eq!(manifests.len(), 3);
+    let report = verify_archive(store.as_ref(), &manifests, None).unwrap();
+    assert_eq!((report.blocks, report.snapshots), (12, 0));
+}
+
+#[test]
+fn directory_keys_stay_inside_the_root() {
+    let store = DirectoryStore::new(dir("escape"));
+    assert!(matches!(store.put("../outside", b"x"), Err(ArchiveError::Store(_))));
+    assert!(matches!(store.get("/etc/passwd"), Err(ArchiveError::Store(_))));
+}
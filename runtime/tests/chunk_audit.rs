@@ -0,0 +1,106 @@
+use runtime::distributed_storage::ConsistencyLevel;
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::{
+    NetworkPeerInfo, NetworkTransferCoordinator, PeerCapabilities,
+};
+use runtime::large_data_transfer::scrub::{run_audit, ScrubConfig, ScrubTarget};
+use runtime::large_data_transfer::{ChunkManager, DataChunk, LargeDataConfig};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Three chunks, the last stored with a flipped bit under its own id.
+fn rotten_manager() -> (Arc<ChunkManager>, Vec<DataChunk>) {
+    let chunks: Vec<DataChunk> = (0..3)
+        .map(|i| {
+            DataChunk::new_from_slice(vec![i as u8 + 1; 4096], i, CompressionAlgorithm::None)
+                .unwrap()
+        })
+        .collect();
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks[..2] {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let mut rotten = chunks[2].clone();
+    rotten.data[9] ^= 0x01;
+    manager.store_chunk(rotten).unwrap();
+    (manager, chunks)
+}
+
+/// A coordinator serving its own cache over the loopback channel, with a
+/// peer `bad` announcing every chunk of `chunks`.
+async fn coordinator(
+    manager: Arc<ChunkManager>,
+    chunks: &[DataChunk],
+) -> NetworkTransferCoordinator {
+    let coordinator =
+        NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager);
+    let serving = coordinator.clone();
+    tokio::spawn(async move { serving.message_processing_loop().await });
+    coordinator
+        .add_peer(NetworkPeerInfo {
+            peer_id: "bad".into(),
+            addresses: vec![],
+            capabilities: PeerCapabilities {
+                max_bandwidth_mbps: 100,
+                max_concurrent_transfers: 4,
+                supported_compression: vec![],
+                storage_capacity_gb: 1,
+                available_chunks: chunks.iter().map(|c| c.id.clone()).collect(),
+            },
+            reputation: 1.0,
+            last_seen: Instant::now(),
+            transfer_stats: Default::default(),
+        })
+        .await;
+    coordinator
+}
+
+#[tokio::test]
+async fn peers_serving_corrupt_chunks_are_penalized() {
+    let (manager, chunks) = rotten_manager();
+    let coordinator = coordinator(manager, &chunks).await;
+
+    // The loopback serves the rotten copy on behalf of `bad`.
+    let id = chunks[2].id.clone();
+    assert!(coordinator
+        .request_chunk_consistent(id.clone(), ConsistencyLevel::Quorum)
+        .await
+        .is_err());
+
+    let bad = coordinator.peer("bad").unwrap();
+    assert_eq!(bad.reputation, 0.75);
+    assert_eq!(bad.transfer_stats.corrupt_chunks, 1);
+    assert!(!bad.capabilities.available_chunks.contains(&id));
+    assert_eq!(coordinator.corrupt_chunks_by_peer()["bad"], 1);
+    assert_eq!(coordinator.get_network_stats().await.corrupt_chunks_received, 1);
+
+    // Healthy chunks do not cost reputation.
+    let good = coordinator
+        .request_chunk_consistent(chunks[0].id.clone(), ConsistencyLevel::All)
+        .await
+        .unwrap();
+    assert_eq!(good.unwrap().data, chunks[0].data);
+    assert_eq!(coordinator.peer("bad").unwrap().reputation, 0.75);
+}
+
+#[tokio::test]
+async fn audit_passes_report_corruption_to_monitoring() {
+    let (manager, chunks) = rotten_manager();
+    let coordinator = coordinator(manager.clone(), &chunks).await;
+    let config = ScrubConfig {
+        batch_size: 2,
+        batch_pause: Duration::ZERO,
+        pass_interval: Duration::from_secs(3600),
+    };
+    let (tx, mut reports) = mpsc::unbounded_channel();
+    let targets: Vec<Arc<dyn ScrubTarget>> = vec![manager.clone()];
+    tokio::spawn(run_audit(coordinator, targets, config, tx));
+
+    let report =
+        tokio::time::timeout(Duration::from_secs(5), reports.recv()).await.unwrap().unwrap();
+    let stats = &report.disks["memory"];
+    assert_eq!((stats.passes, stats.chunks_checked, stats.corrupt), (1, 3, 1));
+    assert_eq!((report.corrupt(), report.repaired(), report.quarantined), (1, 0, 1));
+    assert!(!manager.has_chunk(&chunks[2].id) && manager.has_chunk(&chunks[0].id));
+}
@@ -0,0 +1,84 @@
+use runtime::large_data_transfer::config::{CompressionAlgorithm, EncryptionAlgorithm};
+use runtime::large_data_transfer::{
+    ChunkManager, ChunkSealer, DataChunk, EncryptionConfig, LargeDataConfig, RecipientKey,
+};
+
+fn chunk(index: u32) -> DataChunk {
+    let data: Vec<u8> = (0..8192u32).flat_map(|i| (i % 97 + index).to_le_bytes()).collect();
+    DataChunk::new_from_slice(data, index, CompressionAlgorithm::Lz4).unwrap()
+}
+
+#[test]
+fn sealed_chunks_open_for_every_recipient_and_algorithm() {
+    for algorithm in [
+        EncryptionAlgorithm::Aes256Gcm,
+        EncryptionAlgorithm::ChaCha20Poly1305,
+        EncryptionAlgorithm::XChaCha20Poly1305,
+    ] {
+        let (owner, reader, stranger) =
+            (RecipientKey::generate(), RecipientKey::generate(), RecipientKey::generate());
+        let plain = chunk(0);
+        let sealed = plain.clone().seal(algorithm, &[owner.public(), reader.public()]).unwrap();
+        assert!(sealed.is_encrypted());
+        assert_eq!(sealed.id, plain.id);
+        assert_ne!(sealed.data, plain.data);
+        assert!(sealed.decompress().is_err());
+        assert!(sealed.verify_integrity().is_err());
+
+        for key in [&owner, &reader] {
+            let opened = sealed.open(key).unwrap();
+            assert!(!opened.is_encrypted());
+            opened.verify_integrity().unwrap();
+            assert_eq!(opened.decompress().unwrap(), plain.decompress().unwrap());
+        }
+        assert!(sealed.open(&stranger).is_err());
+
+        // The tag covers the data and binds it to its chunk.
+        let mut tampered = sealed.clone();
+        let last = tampered.data.len() - 1;
+        tampered.data[last] ^= 1;
+        assert!(tampered.open(&owner).is_err());
+        let mut moved = sealed.clone();
+        moved.info.index = 1;
+        assert!(moved.open(&owner).is_err());
+    }
+}
+
+#[test]
+fn encrypting_managers_store_ciphertext_and_return_plaintext() {
+    let key = RecipientKey::generate();
+    let mut config = LargeDataConfig::default();
+    assert!(!ChunkManager::for_config(&config, key.clone()).encrypts_at_rest());
+    config.encryption_config =
+        EncryptionConfig { enabled: true, chunk_encryption: true, ..EncryptionConfig::default() };
+    let manager = ChunkManager::for_config(&config, key.clone());
+    assert!(manager.encrypts_at_rest());
+
+    let plain = chunk(3);
+    manager.store_chunk(plain.clone()).unwrap();
+    let stored = manager.get_stored_chunk(&plain.id).unwrap();
+    let encryption = stored.info.encryption.as_ref().unwrap();
+    assert_eq!(encryption.algorithm, EncryptionAlgorithm::XChaCha20Poly1305);
+    assert_ne!(stored.data, plain.data);
+
+    let retrieved = manager.get_chunk(&plain.id).unwrap();
+    assert!(!retrieved.is_encrypted());
+    assert_eq!(retrieved.data, plain.data);
+
+    // Another authorized node opens the stored chunk with its own key.
+    let peer = RecipientKey::generate();
+    let shared = ChunkManager::default().with_encryption(
+        ChunkSealer::new(EncryptionAlgorithm::Aes256Gcm, key).with_recipient(peer.public()),
+    );
+    shared.store_chunk(plain.clone()).unwrap();
+    let replica = shared.get_stored_chunk(&plain.id).unwrap();
+    assert_eq!(replica.open(&peer).unwrap().data, plain.data);
+
+    // A chunk that no longer decrypts is not served.
+    let mut tampered = replica;
+    tampered.data[0] ^= 1;
+    shared.remove_chunk(&plain.id);
+    shared.store_chunk(tampered).unwrap();
+    assert!(shared.get_stored_chunk(&plain.id).is_some());
+    assert!(shared.get_chunk(&plain.id).is_none());
+}
@@ -0,0 +1,60 @@
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::{
+    ChunkManager, ChunkManagerConfig, DataChunk, LargeDataDescriptor, PinSet,
+};
+use std::time::Duration;
+
+fn chunk(byte: u8) -> DataChunk {
+    DataChunk::new_from_slice(vec![byte; 1024], 0, CompressionAlgorithm::None).unwrap()
+}
+
+fn descriptor(id: &str, chunks: &[&DataChunk]) -> LargeDataDescriptor {
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    LargeDataDescriptor::new(id.into(), id.into(), 1024 * chunks.len() as u64, hashes)
+}
+
+#[test]
+fn pinned_chunks_survive_eviction_and_expiry() {
+    let manager = ChunkManager::new(ChunkManagerConfig {
+        max_memory_bytes: 2048,
+        default_expiration: Duration::from_millis(20),
+        cleanup_interval: Duration::ZERO,
+        ..Default::default()
+    });
+    let (weights, a, b) = (chunk(1), chunk(2), chunk(3));
+    manager.pin("inference", &descriptor("model", &[&weights])).unwrap();
+    manager.store_chunk(weights.clone()).unwrap();
+    manager.store_chunk(a.clone()).unwrap();
+
+    // The least recently used chunk is pinned, so the other one goes.
+    manager.store_chunk(b.clone()).unwrap();
+    assert!(manager.has_chunk(&weights.id) && !manager.has_chunk(&a.id));
+
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(manager.cleanup(), 1);
+    assert_eq!(manager.get_chunk(&weights.id).unwrap().data, weights.data);
+    assert!(manager.is_pinned(&weights.id) && !manager.is_pinned(&b.id));
+
+    assert!(manager.unpin("inference", "model"));
+    assert!(!manager.unpin("inference", "model"));
+    assert!(manager.get_chunk(&weights.id).is_none());
+}
+
+#[test]
+fn pins_are_held_to_a_quota_per_owner() {
+    let (a, b, c) = (chunk(1), chunk(2), chunk(3));
+    let mut pins = PinSet::new(2048);
+    pins.pin("alice", &descriptor("ab", &[&a, &b])).unwrap();
+    assert!(pins.pin("alice", &descriptor("c", &[&c])).is_err());
+    // Pinning the same file again does not count twice; others have their own quota.
+    pins.pin("alice", &descriptor("ab", &[&a, &b])).unwrap();
+    pins.pin("bob", &descriptor("c", &[&c])).unwrap();
+    assert_eq!((pins.used_by("alice"), pins.used_by("bob")), (2048, 1024));
+    assert_eq!(pins.chunks().len(), 3);
+
+    let json = serde_json::to_string(&pins).unwrap();
+    let manager = ChunkManager::default().with_pins(serde_json::from_str(&json).unwrap());
+    let owners: Vec<String> = manager.pins().pins().map(|pin| pin.owner.clone()).collect();
+    assert_eq!(owners, vec!["alice", "bob"]);
+    assert!(manager.is_pinned(&c.id));
+}
@@ -0,0 +1,84 @@
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::config::EncryptionAlgorithm;
+use runtime::large_data_transfer::protocol::ResumeStore;
+use runtime::large_data_transfer::scrub::{RepairRequest, ScrubConfig, ScrubTarget, Scrubber};
+use runtime::large_data_transfer::{
+    ChunkManager, ChunkSealer, DataChunk, LargeDataDescriptor, RecipientKey,
+};
+use tokio::sync::mpsc;
+
+fn chunks(n: u32) -> Vec<DataChunk> {
+    (0..n)
+        .map(|i| {
+            DataChunk::new_from_slice(vec![i as u8; 4096], i, CompressionAlgorithm::None).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn corrupt_chunks_are_quarantined_and_repaired() {
+    let chunks = chunks(3);
+    let manager = ChunkManager::default();
+    for chunk in &chunks[..2] {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let mut rotten = chunks[2].clone();
+    rotten.data[17] ^= 0x20;
+    manager.store_chunk(rotten).unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut scrubber = Scrubber::new(ScrubConfig::default()).with_repairs(tx);
+    assert_eq!(scrubber.scrub_all(&manager), vec![chunks[2].id.clone()]);
+    assert!(!manager.has_chunk(&chunks[2].id) && manager.has_chunk(&chunks[0].id));
+    assert_eq!(
+        rx.try_recv().unwrap(),
+        RepairRequest { disk: "memory".into(), chunk_id: chunks[2].id.clone() }
+    );
+    assert!(scrubber.quarantined()[&chunks[2].id].chunk.is_some());
+
+    let stats = &scrubber.stats()["memory"];
+    assert_eq!((stats.passes, stats.chunks_checked, stats.corrupt), (1, 3, 1));
+    assert!((stats.corruption_rate() - 1.0 / 3.0).abs() < 1e-9);
+
+    // A second pass finds nothing new; the repair releases the quarantine.
+    assert!(scrubber.scrub_all(&manager).is_empty());
+    scrubber.record_repair(&chunks[2].id);
+    assert!(scrubber.quarantined().is_empty());
+    let stats = &scrubber.stats()["memory"];
+    assert_eq!((stats.passes, stats.chunks_checked, stats.repaired), (2, 5, 1));
+
+    // Chunks kept encrypted are checked through their plaintext.
+    let sealed = ChunkManager::default().with_encryption(ChunkSealer::new(
+        EncryptionAlgorithm::XChaCha20Poly1305,
+        RecipientKey::generate(),
+    ));
+    sealed.store_chunk(chunks[0].clone()).unwrap();
+    assert!(Scrubber::default().scrub_all(&sealed).is_empty());
+}
+
+#[test]
+fn damaged_resume_bodies_are_dropped_for_refetch() {
+    let dir = std::env::temp_dir().join(format!("bcai-scrub-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let chunks = chunks(3);
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    let descriptor = LargeDataDescriptor::new("set".into(), "set".into(), 3 * 4096, hashes);
+    let store = ResumeStore::open(&dir).unwrap();
+    store.begin(&descriptor).unwrap();
+    for (index, chunk) in chunks.iter().enumerate() {
+        store.record_chunk("set", index as u32, chunk).unwrap();
+    }
+    assert_eq!(store.chunk_ids().len(), 3);
+
+    let body = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path().join("1.chunk");
+    let mut damaged = std::fs::read(&body).unwrap();
+    damaged[100] ^= 1;
+    std::fs::write(&body, damaged).unwrap();
+
+    let mut scrubber = Scrubber::default();
+    assert_eq!(scrubber.scrub_all(&store), vec![chunks[1].id.clone()]);
+    assert!(!body.exists());
+    assert_eq!(store.verified("set").unwrap().unwrap().missing(), vec![1]);
+    assert_eq!(scrubber.stats()[&store.disk()].corrupt, 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
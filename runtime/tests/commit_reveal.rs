@@ -0,0 +1,194 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use runtime::blockchain::state::EvaluationTask;
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::pouw::{
+    commit_evaluation, CommitRevealConfig, CommitRevealError, EvaluationReveal, EvaluationRound, RoundPhase,
+    SignedCommitment,
+};
+use schnorrkel::{Keypair, SecretKey};
+
+const TASK: &str = "task-1";
+
+fn salt() -> [u8; 32] {
+    let mut salt = [0u8; 32];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+fn commit(accuracy: u32) -> (SignedCommitment, EvaluationReveal) {
+    commit_evaluation(TASK, accuracy, salt(), &SigningKey::generate(&mut OsRng))
+}
+
+fn config() -> CommitRevealConfig {
+    CommitRevealConfig { commit_blocks: 2, reveal_blocks: 2, ..CommitRevealConfig::default() }
+}
+
+/// A round that has committed to `accuracies` and entered its reveal phase.
+fn revealing(accuracies: &[u32]) -> (EvaluationRound, Vec<EvaluationReveal>) {
+    let mut round = EvaluationRound::new(TASK);
+    let mut reveals = Vec::new();
+    for accuracy in accuracies {
+        let (commitment, reveal) = commit(*accuracy);
+        round.commit(commitment).unwrap();
+        reveals.push(reveal);
+    }
+    assert!(!round.advance(10, &config()));
+    assert!(!round.advance(12, &config()));
+    assert_eq!(round.phase, RoundPhase::Reveal);
+    (round, reveals)
+}
+
+#[test]
+fn reveals_must_open_their_own_commitment() {
+    let (mut round, reveals) = revealing(&[9_000, 8_000]);
+
+    let mut changed = reveals[0].clone();
+    changed.evaluation = runtime::pouw::sign_evaluation(TASK, 9_500, &SigningKey::generate(&mut OsRng));
+    assert!(matches!(round.check_reveal(&changed), Err(CommitRevealError::NotCommitted(_))));
+
+    let mut wrong_salt = reveals[0].clone();
+    wrong_salt.salt = salt();
+    let validator = reveals[0].evaluation.validator.clone();
+    assert_eq!(round.check_reveal(&wrong_salt), Err(CommitRevealError::Mismatch(validator.clone())));
+
+    round.reveal(reveals[0].clone()).unwrap();
+    assert_eq!(round.reveal(reveals[0].clone()), Err(CommitRevealError::AlreadyRevealed(validator)));
+}
+
+#[test]
+fn commitments_cannot_be_copied_or_made_late() {
+    let mut round = EvaluationRound::new(TASK);
+    let (honest, _) = commit(9_000);
+    round.commit(honest.clone()).unwrap();
+
+    // Claiming someone else's commitment needs their signature.
+    let copier = SigningKey::generate(&mut OsRng);
+    let mut copied = honest.clone();
+    copied.validator = hex::encode(copier.verifying_key().to_bytes());
+    assert!(matches!(round.check_commit(&copied), Err(CommitRevealError::InvalidSignature(_))));
+    assert!(matches!(round.check_commit(&honest), Err(CommitRevealError::AlreadyCommitted(_))));
+
+    round.advance(1, &config());
+    round.advance(3, &config());
+    let (late, _) = commit(9_000);
+    assert_eq!(
+        round.check_commit(&late),
+        Err(CommitRevealError::WrongPhase { expected: RoundPhase::Commit, actual: RoundPhase::Reveal })
+    );
+}
+
+#[test]
+fn settlement_penalizes_deviation_and_missing_reveals() {
+    let (mut round, reveals) = revealing(&[9_000, 9_100, 8_950, 6_000, 9_000]);
+    for reveal in &reveals[..4] {
+        round.reveal(reveal.clone()).unwrap();
+    }
+    let settlement = round.settle(&config());
+
+    let deviant = &reveals[3].evaluation.validator;
+    let silent = &reveals[4].evaluation.validator;
+    // 2950 bps off the median of 8950, well past the cap.
+    assert_eq!(settlement.penalties[deviant], 20);
+    assert_eq!(settlement.penalties[silent], config().no_reveal_penalty);
+    assert_eq!(settlement.penalties.len(), 2);
+
+    let consensus = settlement.consensus.unwrap();
+    assert_eq!(consensus.accuracy, 9_000);
+    assert!(consensus.outliers.contains(deviant));
+}
+
+#[test]
+fn too_few_reveals_record_no_consensus() {
+    let (mut round, reveals) = revealing(&[9_000, 9_000, 9_000]);
+    round.reveal(reveals[0].clone()).unwrap();
+    let settlement = round.settle(&config());
+    assert!(settlement.consensus.is_none());
+    assert_eq!(settlement.penalties.len(), 2);
+}
+
+/// A chain where every validator has staked 50 and was selected for
+/// [`TASK`], plus a funded submitter.
+fn setup(validators: &[String]) -> (Blockchain, SecretKey) {
+    let mut chain = Blockchain::new(BlockchainConfig { commit_reveal: config(), ..BlockchainConfig::default() });
+    for validator in validators {
+        chain.state.set_balance(validator, 50);
+        chain.state.stake_tokens(validator, 50).unwrap();
+    }
+    let selected = validators.to_vec();
+    chain.state.evaluation_tasks.insert(TASK.into(), EvaluationTask { selected, opened_at: 0 });
+    let submitter = Keypair::generate().secret.clone();
+    chain.state.set_balance(&hex::encode(submitter.to_public().to_bytes()), 100);
+    (chain, submitter)
+}
+
+fn submit(chain: &mut Blockchain, tx: &Transaction) {
+    validate_transaction_stateful(tx, &chain.state).unwrap();
+    chain.state.apply_transaction(tx).unwrap();
+}
+
+#[test]
+fn rounds_settle_on_chain() {
+    let accuracies = [9_000, 9_050, 8_980, 5_000];
+    let (commitments, reveals): (Vec<_>, Vec<_>) = accuracies.iter().map(|a| commit(*a)).unzip();
+    let (late, _) = commit(9_000);
+    let mut validators: Vec<String> = reveals.iter().map(|r| r.evaluation.validator.clone()).collect();
+    validators.push(late.validator.clone());
+    let (mut chain, submitter) = setup(&validators);
+    let config = chain.state.consensus.commit_reveal.clone();
+    let mut nonce = 0;
+
+    for commitment in &commitments {
+        submit(&mut chain, &Transaction::new_evaluation_commit_signed(&submitter, commitment.clone(), 1, nonce));
+        nonce += 1;
+    }
+    // Reveals wait for the commit phase to close.
+    let early = Transaction::new_evaluation_reveal_signed(&submitter, reveals[0].clone(), 1, nonce);
+    assert!(validate_transaction_stateful(&early, &chain.state).is_err());
+
+    chain.state.advance_evaluation_rounds(5, &config);
+    chain.state.advance_evaluation_rounds(7, &config);
+    let late = Transaction::new_evaluation_commit_signed(&submitter, late, 1, nonce);
+    assert!(validate_transaction_stateful(&late, &chain.state).unwrap_err().to_string().contains("phase"));
+
+    for reveal in &reveals {
+        submit(&mut chain, &Transaction::new_evaluation_reveal_signed(&submitter, reveal.clone(), 1, nonce));
+        nonce += 1;
+    }
+    assert!(chain.state.advance_evaluation_rounds(8, &config).is_empty());
+    assert_eq!(chain.state.advance_evaluation_rounds(9, &config), vec![TASK.to_string()]);
+
+    assert!(chain.state.evaluation_rounds.is_empty());
+    assert_eq!(chain.state.consensus_evaluations[TASK].accuracy, 9_000);
+    assert_eq!(chain.state.stakes[&reveals[3].evaluation.validator], 50 - config.max_penalty);
+    for honest in &reveals[..3] {
+        assert_eq!(chain.state.stakes[&honest.evaluation.validator], 50);
+    }
+
+    let again = Transaction::new_evaluation_commit_signed(&submitter, commit(9_000).0, 1, nonce);
+    assert!(validate_transaction_stateful(&again, &chain.state).is_err());
+}
+
+#[test]
+fn only_selected_validators_commit_to_open_tasks() {
+    let (selected, _) = commit(9_000);
+    let (mut chain, submitter) = setup(&[selected.validator.clone()]);
+
+    // Staked, but not selected for the task.
+    let (sybil, _) = commit(1_000);
+    chain.state.set_balance(&sybil.validator, 50);
+    chain.state.stake_tokens(&sybil.validator, 50).unwrap();
+    let tx = Transaction::new_evaluation_commit_signed(&submitter, sybil.clone(), 1, 0);
+    assert!(validate_transaction_stateful(&tx, &chain.state).unwrap_err().to_string().contains("not selected"));
+
+    // No round opens for a task the chain does not know.
+    let key = SigningKey::generate(&mut OsRng);
+    let (made_up, _) = commit_evaluation("made-up", 9_000, salt(), &key);
+    let tx = Transaction::new_evaluation_commit_signed(&submitter, made_up, 1, 0);
+    assert!(validate_transaction_stateful(&tx, &chain.state).unwrap_err().to_string().contains("not open"));
+
+    submit(&mut chain, &Transaction::new_evaluation_commit_signed(&submitter, selected, 1, 0));
+    assert!(chain.state.evaluation_rounds.contains_key(TASK));
+}
@@ -0,0 +1,53 @@
+//! Helpers shared by the integration tests. Each test crate includes this
+//! module and uses only some of them.
+#![allow(dead_code)]
+
+use runtime::blockchain::{Block, Blockchain, Transaction};
+use runtime::job::Job;
+use runtime::miner;
+use schnorrkel::{Keypair, SecretKey};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Address the helpers mine blocks to.
+pub const MINER: &str = "miner";
+
+/// A fresh account key.
+pub fn key() -> SecretKey {
+    Keypair::generate().secret.clone()
+}
+
+/// The account address of `sk`.
+pub fn pk_hex(sk: &SecretKey) -> String {
+    hex::encode(sk.to_public().to_bytes())
+}
+
+/// The verifier rejects solutions computed in under 100ms, which the small
+/// tasks of a test always are. The time is part of the block hash but not of
+/// the PoUW check.
+pub fn pad_computation_time(block: &mut Block) {
+    block.solution.computation_time_ms = block.solution.computation_time_ms.max(100);
+    block.hash = block.calculate_hash();
+}
+
+/// Mines the next block over `queue` with an empty mempool, returning the
+/// jobs it left queued.
+pub async fn mine_jobs(chain: &Arc<Mutex<Blockchain>>, queue: VecDeque<Job>) -> (Block, VecDeque<Job>) {
+    let queue = Arc::new(Mutex::new(queue));
+    let mut block = miner::mine_block(MINER.into(), chain.clone(), Arc::new(Mutex::new(HashSet::new())), queue.clone())
+        .await
+        .unwrap();
+    pad_computation_time(&mut block);
+    let rest = queue.lock().await.clone();
+    (block, rest)
+}
+
+/// Mines the next block and places `txs` in it; the transactions are not
+/// part of the PoUW check either.
+pub async fn mine(chain: &Arc<Mutex<Blockchain>>, txs: Vec<Transaction>) -> Block {
+    let (mut block, _) = mine_jobs(chain, VecDeque::new()).await;
+    block.transactions = txs;
+    block.hash = block.calculate_hash();
+    block
+}
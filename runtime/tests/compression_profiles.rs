@@ -0,0 +1,103 @@
+use runtime::large_data_transfer::compression::{
+    measure_levels, select_level, CompressionUtils, LevelMeasurement,
+};
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::dictionary::{self, Dictionary, DictionaryRegistry};
+use runtime::large_data_transfer::{AdaptiveCompressor, CompressionConfig, ContentType, DataChunk};
+
+fn log_line(i: usize) -> String {
+    format!(
+        "{{\"epoch\":{},\"step\":{},\"loss\":{:.4},\"accuracy\":{:.4},\"lr\":0.001,\"worker\":\"node-{}\"}}\n",
+        i / 100,
+        i,
+        1.0 / (i + 1) as f64,
+        i as f64 / (i + 10) as f64,
+        i % 7
+    )
+}
+
+fn tensor(len: usize) -> Vec<u8> {
+    (0..len / 4).flat_map(|i| ((i % 64) as f32 * 0.5).to_le_bytes()).collect()
+}
+
+#[test]
+fn content_types_are_detected() {
+    assert_eq!(ContentType::detect(&tensor(8192)), ContentType::Tensor);
+    let log: String = (0..200).map(log_line).collect();
+    assert_eq!(ContentType::detect(log.as_bytes()), ContentType::JsonLog);
+    let csv: String = (0..300).map(|i| format!("{},{},{:.3}\n", i, i * 2, i as f64 / 3.0)).collect();
+    assert_eq!(ContentType::detect(csv.as_bytes()), ContentType::Csv);
+    assert_eq!(ContentType::detect(b"plain words, on a line\nand more words\n"), ContentType::Other);
+}
+
+#[test]
+fn dictionaries_shrink_small_chunks_of_similar_content() {
+    let samples: Vec<String> = (0..2_000).map(log_line).collect();
+    let first = Dictionary::train(ContentType::JsonLog, &samples, 8 * 1024).unwrap();
+    let mut registry = DictionaryRegistry::default();
+    registry.insert(first.clone());
+    dictionary::install(registry.clone());
+
+    let chunk: String = (5_000..5_012).map(log_line).collect();
+    let with = CompressionUtils::compress_level(chunk.as_bytes(), CompressionAlgorithm::ZstdDict, 9).unwrap();
+    let without = CompressionUtils::compress_level(chunk.as_bytes(), CompressionAlgorithm::Zstd, 9).unwrap();
+    assert_eq!(u32::from_le_bytes(with[..4].try_into().unwrap()), first.id);
+    assert!(with.len() < without.len(), "{} vs {}", with.len(), without.len());
+    assert_eq!(
+        CompressionUtils::decompress(&with, CompressionAlgorithm::ZstdDict).unwrap(),
+        chunk.as_bytes()
+    );
+
+    let stored = DataChunk::new_from_slice(chunk.clone().into_bytes(), 0, CompressionAlgorithm::ZstdDict).unwrap();
+    assert_eq!(stored.info.compression, CompressionAlgorithm::ZstdDict);
+    assert_eq!(stored.decompress().unwrap(), chunk.as_bytes());
+
+    // A retrained dictionary takes over; data compressed with the old one
+    // still decompresses.
+    let retrained: Vec<String> = (10_000..12_000).map(log_line).collect();
+    registry.insert(Dictionary::train(ContentType::JsonLog, &retrained, 8 * 1024).unwrap());
+    dictionary::install(registry);
+    assert_ne!(dictionary::registry().for_content(ContentType::JsonLog).unwrap().id, first.id);
+    assert_eq!(stored.decompress().unwrap(), chunk.as_bytes());
+
+    let mut unknown = with.clone();
+    unknown[..4].copy_from_slice(&0xdead_beef_u32.to_le_bytes());
+    assert!(CompressionUtils::decompress(&unknown, CompressionAlgorithm::ZstdDict).is_err());
+}
+
+#[test]
+fn levels_trade_throughput_for_ratio() {
+    let m = |level, ratio, throughput_mb_s| LevelMeasurement { level, ratio, throughput_mb_s };
+    let measured = [m(1, 2.0, 400.0), m(3, 2.4, 200.0), m(9, 2.9, 60.0), m(19, 3.1, 5.0)];
+    assert_eq!(select_level(&measured, 50.0), Some(9));
+    assert_eq!(select_level(&measured, 300.0), Some(1));
+    // None is fast enough: the fastest wins.
+    assert_eq!(select_level(&measured, 1_000.0), Some(1));
+    assert_eq!(select_level(&[], 50.0), None);
+
+    let sample = tensor(64 * 1024);
+    let measured = measure_levels(&sample, CompressionAlgorithm::Zstd, &[1, 9]).unwrap();
+    assert_eq!(measured.len(), 2);
+    assert!(measured.iter().all(|m| m.ratio > 1.0 && m.throughput_mb_s > 0.0));
+}
+
+#[test]
+fn profiles_pick_the_algorithm_per_content_type() {
+    let config = CompressionConfig::default().with_dictionary_profiles();
+    let compressor = AdaptiveCompressor::new(config.clone());
+    let data = tensor(256 * 1024);
+    let (compressed, algorithm) = compressor.compress(&data).unwrap();
+    assert_eq!(algorithm, CompressionAlgorithm::Zstd);
+    assert_eq!(CompressionUtils::decompress(&compressed, algorithm).unwrap(), data);
+    let level = compressor.selected_levels()[&ContentType::Tensor];
+    assert!(config.level_selection.candidates.contains(&level));
+
+    let (small, algorithm) = compressor.compress(b"tiny").unwrap();
+    assert_eq!((small.as_slice(), algorithm), (&b"tiny"[..], CompressionAlgorithm::None));
+
+    // Without profiles everything gets the global algorithm.
+    let plain = AdaptiveCompressor::new(CompressionConfig::default());
+    assert_eq!(plain.compress(&data).unwrap().1, CompressionAlgorithm::Lz4);
+    let chunk = DataChunk::new_with_compressor(data.clone(), 0, &compressor).unwrap();
+    assert_eq!(chunk.decompress().unwrap(), data);
+}
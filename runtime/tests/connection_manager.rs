@@ -0,0 +1,80 @@
+use runtime::connection_manager::{ConnectionLimits, ConnectionManager, PeerRole};
+
+fn limits() -> ConnectionLimits {
+    ConnectionLimits {
+        validators: 2,
+        storage_providers: 1,
+        relays: 1,
+        max_peers: 4,
+        grace_secs: 10,
+        dial_backoff_secs: 30,
+        maintenance_interval_secs: 5,
+    }
+}
+
+fn connect(manager: &mut ConnectionManager<u32>, peer: u32, roles: &[PeerRole], score: i64) {
+    manager.connected(peer, 0);
+    manager.set_roles(peer, roles.iter().copied());
+    manager.adjust_score(&peer, score);
+}
+
+#[test]
+fn under_served_roles_are_dialed() {
+    let mut manager = ConnectionManager::new(limits());
+    connect(&mut manager, 1, &[PeerRole::Validator], 0);
+    manager.set_roles(10, [PeerRole::Validator]);
+    manager.set_roles(11, [PeerRole::Validator]);
+    manager.adjust_score(&11, 5);
+    manager.set_roles(20, [PeerRole::Relay]);
+    manager.discovered(30);
+    manager.discovered(31);
+
+    // One validator short: the better of the two. The relay, and one
+    // unknown peer for the still missing storage provider.
+    let plan = manager.plan(0);
+    assert!(plan.prune.is_empty());
+    assert_eq!(plan.dial, vec![11, 20, 30]);
+
+    // Dialed peers wait out the backoff while the others get their turn.
+    assert_eq!(manager.plan(10).dial, vec![10, 31]);
+    assert!(manager.plan(20).dial.is_empty());
+    assert_eq!(manager.plan(30).dial, vec![11, 20, 30]);
+}
+
+#[test]
+fn dials_stop_at_the_connection_cap() {
+    let mut manager = ConnectionManager::new(limits());
+    for peer in 1..=3 {
+        connect(&mut manager, peer, &[PeerRole::StorageProvider], 0);
+    }
+    manager.set_roles(10, [PeerRole::Validator]);
+    manager.set_roles(11, [PeerRole::Validator]);
+    assert_eq!(manager.plan(0).dial, vec![10]);
+}
+
+#[test]
+fn excess_low_value_peers_are_pruned() {
+    let mut manager = ConnectionManager::new(limits());
+    connect(&mut manager, 1, &[PeerRole::Validator], 10);
+    connect(&mut manager, 2, &[PeerRole::Validator], 8);
+    connect(&mut manager, 3, &[PeerRole::Validator], 1);
+    connect(&mut manager, 4, &[PeerRole::Relay], -3);
+    connect(&mut manager, 5, &[], 0);
+    connect(&mut manager, 6, &[], 4);
+    assert_eq!(manager.count(PeerRole::Validator), 3);
+
+    // Too new to judge yet.
+    assert!(manager.plan(5).prune.is_empty());
+
+    // Two over the cap. The low-scoring relay is the only one and stays;
+    // the third validator and the worst unknown peer go.
+    let plan = manager.plan(10);
+    assert_eq!(plan.prune, vec![5, 3]);
+    assert!(plan.dial.is_empty());
+
+    for peer in &plan.prune {
+        manager.disconnected(peer);
+    }
+    assert_eq!(manager.connected_count(), 4);
+    assert!(manager.plan(20).prune.is_empty());
+}
@@ -0,0 +1,173 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::blockchain::constants::OUTLIER_SLASH;
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::state::EvaluationTask;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::pouw::types::{SignedEvaluation, ValidatorSelectionConfig};
+use runtime::pouw::{aggregate, collect_evaluations, sign_evaluation, AggregationConfig, AggregationError};
+use schnorrkel::{Keypair, SecretKey};
+
+const TASK: &str = "task-1";
+
+fn evaluations(accuracies: &[u32]) -> Vec<SignedEvaluation> {
+    accuracies
+        .iter()
+        .map(|a| sign_evaluation(TASK, *a, &SigningKey::generate(&mut OsRng)))
+        .collect()
+}
+
+/// A chain where every evaluator has staked 50 and was selected for
+/// [`TASK`], plus a funded submitter.
+fn setup(evals: &[SignedEvaluation]) -> (Blockchain, SecretKey) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    for eval in evals {
+        chain.state.set_balance(&eval.validator, 50);
+        chain.state.stake_tokens(&eval.validator, 50).unwrap();
+    }
+    let selected = evals.iter().map(|e| e.validator.clone()).collect();
+    chain.state.evaluation_tasks.insert(TASK.into(), EvaluationTask { selected, opened_at: 0 });
+    let submitter = Keypair::generate().secret.clone();
+    chain.state.set_balance(&hex::encode(submitter.to_public().to_bytes()), 10);
+    (chain, submitter)
+}
+
+#[test]
+fn outliers_are_flagged_and_excluded_from_the_consensus() {
+    let evals = evaluations(&[9_000, 8_950, 9_050, 100, 9_010]);
+    let consensus = aggregate(TASK, &evals, &AggregationConfig::default()).unwrap();
+
+    assert_eq!(consensus.outliers, vec![evals[3].validator.clone()]);
+    assert!(!consensus.accepted.contains(&evals[3].validator));
+    assert!((8_950..=9_050).contains(&consensus.accuracy));
+}
+
+#[test]
+fn near_agreement_flags_no_one() {
+    let evals = evaluations(&[9_000, 9_000, 9_000, 9_040]);
+    let consensus = aggregate(TASK, &evals, &AggregationConfig::default()).unwrap();
+    assert!(consensus.outliers.is_empty());
+    assert_eq!(consensus.accuracy, 9_000);
+}
+
+#[test]
+fn consensus_does_not_depend_on_submission_order() {
+    let evals = evaluations(&[7_000, 7_200, 6_900, 2_000, 7_100, 7_050]);
+    let mut reversed = evals.clone();
+    reversed.reverse();
+    let config = AggregationConfig::default();
+    assert_eq!(aggregate(TASK, &evals, &config).unwrap(), aggregate(TASK, &reversed, &config).unwrap());
+}
+
+#[test]
+fn malformed_evaluation_sets_are_rejected() {
+    let config = AggregationConfig::default();
+    let evals = evaluations(&[9_000, 9_000, 9_000]);
+
+    assert_eq!(
+        aggregate(TASK, &evals[..2], &config),
+        Err(AggregationError::TooFewEvaluations { need: 3, got: 2 })
+    );
+
+    let mut forged = evals.clone();
+    forged[1].accuracy = 100;
+    assert_eq!(aggregate(TASK, &forged, &config), Err(AggregationError::InvalidSignature(evals[1].validator.clone())));
+
+    let mut duplicated = evals.clone();
+    duplicated[2] = duplicated[0].clone();
+    assert_eq!(aggregate(TASK, &duplicated, &config), Err(AggregationError::Duplicate(evals[0].validator.clone())));
+
+    let other = sign_evaluation("other", 9_000, &SigningKey::generate(&mut OsRng));
+    let mut wrong_task = evals.clone();
+    wrong_task[0] = other.clone();
+    assert_eq!(aggregate(TASK, &wrong_task, &config), Err(AggregationError::WrongTask(other.validator)));
+}
+
+#[test]
+fn collection_keeps_only_selected_signed_evaluations_for_the_task() {
+    let mut evals = evaluations(&[9_000, 8_000, 7_000]);
+    let selected: Vec<String> = evals[..2].iter().map(|e| e.validator.clone()).collect();
+    let resubmitted = evals[0].clone();
+    let mut forged = evals[1].clone();
+    forged.accuracy = 1;
+    evals.extend([resubmitted, forged]);
+
+    let collected = collect_evaluations(TASK, evals.clone(), &selected);
+    let mut expected = evals[..2].to_vec();
+    expected.sort_by(|a, b| a.validator.cmp(&b.validator));
+    assert_eq!(collected, expected);
+}
+
+#[test]
+fn recording_a_consensus_slashes_outliers_once() {
+    let evals = evaluations(&[9_000, 8_950, 9_050, 100]);
+    let (mut chain, submitter) = setup(&evals);
+
+    let tx = Transaction::new_consensus_evaluation_signed(&submitter, TASK.into(), evals.clone(), 1, 0);
+    validate_transaction_stateful(&tx, &chain.state).unwrap();
+    chain.state.apply_transaction(&tx).unwrap();
+
+    let consensus = &chain.state.consensus_evaluations[TASK];
+    assert_eq!(consensus.outliers, vec![evals[3].validator.clone()]);
+    assert_eq!(chain.state.stakes[&evals[3].validator], 50 - OUTLIER_SLASH);
+    for honest in &evals[..3] {
+        assert_eq!(chain.state.stakes[&honest.validator], 50);
+    }
+
+    let again = Transaction::new_consensus_evaluation_signed(&submitter, TASK.into(), evals, 1, 0);
+    assert!(validate_transaction_stateful(&again, &chain.state).is_err());
+    assert!(!chain.state.evaluation_tasks.contains_key(TASK));
+}
+
+#[test]
+fn only_a_quorum_of_the_selected_validators_records_a_consensus() {
+    let evals = evaluations(&[9_000, 8_950, 9_050, 9_000, 9_020]);
+    let (mut chain, submitter) = setup(&evals[..4]);
+    chain.state.set_balance(&evals[4].validator, 50);
+    chain.state.stake_tokens(&evals[4].validator, 50).unwrap();
+
+    // A staked validator that was not selected cannot join in.
+    let sybil = [&evals[..3], &evals[4..]].concat();
+    let tx = Transaction::new_consensus_evaluation_signed(&submitter, TASK.into(), sybil, 1, 0);
+    assert!(validate_transaction_stateful(&tx, &chain.state).unwrap_err().to_string().contains("not selected"));
+
+    // Nor can a submitter leave out selected validators past the quorum.
+    let partial = Transaction::new_consensus_evaluation_signed(&submitter, TASK.into(), evals[..2].to_vec(), 1, 0);
+    assert!(validate_transaction_stateful(&partial, &chain.state).unwrap_err().to_string().contains("needs"));
+
+    // Tasks never opened on chain take no evaluations at all.
+    let others = (0..3).map(|_| sign_evaluation("unknown", 9_000, &SigningKey::generate(&mut OsRng))).collect();
+    let unknown = Transaction::new_consensus_evaluation_signed(&submitter, "unknown".into(), others, 1, 0);
+    assert!(validate_transaction_stateful(&unknown, &chain.state).unwrap_err().to_string().contains("not open"));
+
+    let tx = Transaction::new_consensus_evaluation_signed(&submitter, TASK.into(), evals[..3].to_vec(), 1, 0);
+    validate_transaction_stateful(&tx, &chain.state).unwrap();
+}
+
+#[test]
+fn evaluators_are_selected_from_stake_by_the_opening_block() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    for (i, staker) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+        chain.state.set_balance(staker, 100);
+        chain.state.stake_tokens(staker, 10 * (i as u64 + 1)).unwrap();
+    }
+    let config = ValidatorSelectionConfig::default();
+    let selected = chain.state.open_evaluation_task(TASK, 1, "block-1", &config).selected.clone();
+    assert!(!selected.is_empty() && selected.len() <= config.subset_size);
+    assert!(selected.iter().all(|v| chain.state.stakes.contains_key(v)));
+
+    // Reopening keeps the first selection; another chain at the same block
+    // selects the same validators.
+    assert_eq!(chain.state.open_evaluation_task(TASK, 2, "block-2", &config).selected, selected);
+    let mut replica = chain.state.clone();
+    replica.evaluation_tasks.clear();
+    assert_eq!(replica.open_evaluation_task(TASK, 1, "block-1", &config).selected, selected);
+}
+
+#[test]
+fn evaluations_from_unstaked_keys_are_rejected() {
+    let evals = evaluations(&[9_000, 9_000, 9_000]);
+    let (chain, submitter) = setup(&evals[..2]);
+    let tx = Transaction::new_consensus_evaluation_signed(&submitter, TASK.into(), evals, 1, 0);
+    assert!(validate_transaction_stateful(&tx, &chain.state).is_err());
+}
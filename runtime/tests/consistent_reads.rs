@@ -0,0 +1,83 @@
+use runtime::distributed_storage::ConsistencyLevel;
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::{
+    NetworkPeerInfo, NetworkTransferCoordinator, PeerCapabilities,
+};
+use runtime::large_data_transfer::{ChunkManager, DataChunk, LargeDataConfig, LargeDataDescriptor};
+use std::sync::Arc;
+use std::time::Instant;
+
+fn chunks(n: u32) -> Vec<DataChunk> {
+    (0..n)
+        .map(|i| {
+            DataChunk::new_from_slice(vec![i as u8 + 1; 4096], i, CompressionAlgorithm::None)
+                .unwrap()
+        })
+        .collect()
+}
+
+/// A coordinator serving its own cache over the loopback channel, with
+/// `providers` peers announcing every chunk of `chunks`.
+async fn coordinator(
+    manager: Arc<ChunkManager>,
+    chunks: &[DataChunk],
+    providers: &[&str],
+) -> NetworkTransferCoordinator {
+    let coordinator =
+        NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager);
+    let serving = coordinator.clone();
+    tokio::spawn(async move { serving.message_processing_loop().await });
+    for peer in providers {
+        coordinator
+            .add_peer(NetworkPeerInfo {
+                peer_id: peer.to_string(),
+                addresses: vec![],
+                capabilities: PeerCapabilities {
+                    max_bandwidth_mbps: 100,
+                    max_concurrent_transfers: 4,
+                    supported_compression: vec![],
+                    storage_capacity_gb: 1,
+                    available_chunks: chunks.iter().map(|c| c.id.clone()).collect(),
+                },
+                reputation: 1.0,
+                last_seen: Instant::now(),
+                transfer_stats: Default::default(),
+            })
+            .await;
+    }
+    coordinator
+}
+
+#[test]
+fn levels_require_one_a_majority_or_every_replica() {
+    let required = |level: ConsistencyLevel| [1, 2, 3, 4, 5].map(|n| level.required(n));
+    assert_eq!(required(ConsistencyLevel::One), [1, 1, 1, 1, 1]);
+    assert_eq!(required(ConsistencyLevel::Quorum), [1, 2, 2, 3, 3]);
+    assert_eq!(required(ConsistencyLevel::All), [1, 2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn files_are_read_once_enough_providers_agree() {
+    let chunks = chunks(3);
+    let data: Vec<u8> = chunks.iter().flat_map(|c| c.data.clone()).collect();
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    let descriptor =
+        LargeDataDescriptor::new("file".into(), "file".into(), data.len() as u64, hashes);
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let coordinator = coordinator(manager.clone(), &chunks, &["a", "b", "c"]).await;
+    for level in [ConsistencyLevel::One, ConsistencyLevel::Quorum, ConsistencyLevel::All] {
+        assert_eq!(coordinator.retrieve_file(descriptor.clone(), level).await.unwrap(), data);
+    }
+
+    // Once the copy every provider serves has rotted, no level is met.
+    let mut rotten = chunks[1].clone();
+    rotten.data[7] ^= 1;
+    manager.store_chunk(rotten).unwrap();
+    let err =
+        coordinator.retrieve_file(descriptor.clone(), ConsistencyLevel::Quorum).await.unwrap_err();
+    assert!(err.to_string().contains("confirmed by 0 of the 2"), "{err}");
+    assert!(coordinator.retrieve_file(descriptor, ConsistencyLevel::One).await.is_err());
+}
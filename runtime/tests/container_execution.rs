@@ -0,0 +1,100 @@
+use runtime::container::{ContainerError, ContainerRunner, ContainerSpec, ResourceLimits, OUTPUT_DIR};
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+fn work_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bcai-container-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A stand-in for `docker` that runs `script` with `$cmd` set to the
+/// subcommand and `$out` to the host side of the output mount.
+#[cfg(unix)]
+fn fake_runtime(dir: &Path, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("fake-docker");
+    let body = format!(
+        "#!/bin/sh\ncmd=\"$1\"\nwhile [ $# -gt 0 ]; do\n  case \"$2\" in *:{}) out=\"${{2%%:*}}\";; esac\n  shift\ndone\n{}\n",
+        OUTPUT_DIR, script
+    );
+    std::fs::write(&path, body).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+fn gpu_spec() -> ContainerSpec {
+    ContainerSpec::new("pytorch/pytorch@sha256:abc")
+        .with_command(&["python", "train.py"])
+        .with_env("EPOCHS", "3")
+        .with_limits(ResourceLimits { cpus: Some(2.5), memory_mb: Some(4096), gpus: 1, timeout_secs: 60 })
+}
+
+#[test]
+fn run_args_apply_limits_and_isolation() {
+    let runner = ContainerRunner::new("/work");
+    let args = runner.run_args(7, &gpu_spec(), Some(Path::new("/data")), Path::new("/work/job-7"));
+    let joined = args.join(" ");
+    for expected in [
+        "--network none",
+        "--cpus 2.5",
+        "--memory 4096m --memory-swap 4096m",
+        "--gpus 1",
+        "-v /data:/bcai/in:ro",
+        "-v /work/job-7:/bcai/out",
+        "-e EPOCHS=3",
+    ] {
+        assert!(joined.contains(expected), "missing {:?} in {}", expected, joined);
+    }
+    assert!(joined.ends_with("pytorch/pytorch@sha256:abc python train.py"));
+}
+
+#[test]
+fn specs_cannot_inject_runtime_options() {
+    assert!(matches!(ContainerSpec::new("--privileged").validate(), Err(ContainerError::InvalidSpec(_))));
+    assert!(matches!(ContainerSpec::new("img\n--rm").validate(), Err(ContainerError::InvalidSpec(_))));
+    assert!(matches!(ContainerSpec::new("img").with_env("A=B", "c").validate(), Err(ContainerError::InvalidSpec(_))));
+    let no_time = ResourceLimits { timeout_secs: 0, ..ResourceLimits::default() };
+    assert!(matches!(ContainerSpec::new("img").with_limits(no_time).validate(), Err(ContainerError::InvalidSpec(_))));
+    assert!(gpu_spec().validate().is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_collect_logs_artifacts_and_metrics() {
+    let dir = work_dir("ok");
+    let runtime = fake_runtime(
+        &dir,
+        r#"echo "epoch 1 loss 0.5"; echo "warning" >&2
+printf weights > "$out/model.bin"
+echo '{"accuracy": 0.93, "loss": 0.21}' > "$out/metrics.json""#,
+    );
+    let outcome = ContainerRunner::new(&dir).with_runtime(runtime).run(1, &ContainerSpec::new("img"), None).unwrap();
+
+    assert!(outcome.logs.contains("epoch 1 loss 0.5") && outcome.logs.contains("warning"));
+    assert_eq!(outcome.metrics["accuracy"], 0.93);
+    let model = outcome.artifact("model.bin").unwrap();
+    assert_eq!(model.size, 7);
+    assert_eq!(model.hash, hex::encode(<sha2::Sha256 as sha2::Digest>::digest(b"weights")));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn failures_and_timeouts_are_reported() {
+    let dir = work_dir("fail");
+    let failing = fake_runtime(&dir, "echo 'CUDA out of memory' >&2; exit 3");
+    let runner = ContainerRunner::new(&dir).with_runtime(failing);
+    match runner.run(2, &ContainerSpec::new("img"), None) {
+        Err(ContainerError::Failed { code: 3, logs }) => assert!(logs.contains("CUDA out of memory")),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let hanging = fake_runtime(&dir, "[ \"$cmd\" = rm ] || sleep 30");
+    let limits = ResourceLimits { timeout_secs: 1, ..ResourceLimits::default() };
+    let spec = ContainerSpec::new("img").with_limits(limits);
+    let result = ContainerRunner::new(&dir).with_runtime(hanging).run(3, &spec, None);
+    assert!(matches!(result, Err(ContainerError::Timeout(1))));
+    std::fs::remove_dir_all(dir).unwrap();
+}
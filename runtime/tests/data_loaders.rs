@@ -0,0 +1,106 @@
+use runtime::data::csv::CsvLoader;
+use runtime::data::hub::HubLoader;
+use runtime::data::{load_all, train_test_split, ColumnKind, DataError, DatasetLoader, Normalizer, Sample};
+use runtime::federated::{CanaryConfig, CanaryRound};
+use runtime::pouw::generate_task;
+use runtime::pouw::training::{Dataset, ONE};
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bcai-data-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn sample(features: &[f32], label: usize) -> Sample {
+    Sample { features: features.to_vec(), label }
+}
+
+#[test]
+fn csv_schema_is_inferred_and_rows_stream() {
+    let dir = temp_dir("csv");
+    let path = dir.join("flowers.csv");
+    std::fs::write(
+        &path,
+        "length,colour,species\n1.5,red,\"rosa, wild\"\n2.0,blue,iris\n\n0.5,red,iris\n",
+    )
+    .unwrap();
+
+    let mut loader = CsvLoader::open(&path, "species").unwrap();
+    let schema = loader.schema().clone();
+    assert_eq!(schema.columns[0].kind, ColumnKind::Numeric);
+    assert_eq!(schema.columns[1].kind, ColumnKind::Categorical(vec!["blue".into(), "red".into()]));
+    assert_eq!(schema.num_classes(), Some(2));
+    assert_eq!(schema.feature_names(), vec!["length", "colour"]);
+
+    let samples = load_all(&mut loader).unwrap();
+    assert_eq!(samples, vec![sample(&[1.5, 1.0], 1), sample(&[2.0, 0.0], 0), sample(&[0.5, 1.0], 0)]);
+
+    assert!(matches!(CsvLoader::open(&path, "petals"), Err(DataError::Schema(_))));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn hub_splits_read_shards_and_class_names() {
+    let dir = temp_dir("hub");
+    std::fs::create_dir_all(dir.join("data")).unwrap();
+    std::fs::write(dir.join("data/train-00000.jsonl"), "{\"x\": 0.5, \"label\": \"pos\"}\n").unwrap();
+    std::fs::write(dir.join("data/train-00001.jsonl"), "{\"x\": -1, \"label\": \"neg\"}\n").unwrap();
+    std::fs::write(dir.join("test.jsonl"), "{\"x\": 2, \"label\": \"pos\"}\n").unwrap();
+    std::fs::write(
+        dir.join("dataset_info.json"),
+        r#"{"features": {"label": {"names": ["pos", "neg"]}}}"#,
+    )
+    .unwrap();
+
+    let train = load_all(&mut HubLoader::open(&dir, "train", "label").unwrap()).unwrap();
+    assert_eq!(train, vec![sample(&[0.5], 0), sample(&[-1.0], 1)]);
+    let test = load_all(&mut HubLoader::open(&dir, "test", "label").unwrap()).unwrap();
+    assert_eq!(test, vec![sample(&[2.0], 0)]);
+    assert!(HubLoader::open(&dir, "validation", "label").is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn normalizer_standardizes_features() {
+    let mut samples = vec![sample(&[1.0, 5.0], 0), sample(&[3.0, 5.0], 1)];
+    let normalizer = Normalizer::fit(&samples).unwrap();
+    assert_eq!(normalizer.mean, vec![2.0, 5.0]);
+    assert_eq!(normalizer.std, vec![1.0, 1.0]);
+    normalizer.apply_all(&mut samples);
+    assert_eq!(samples[0].features, vec![-1.0, 0.0]);
+    assert!(matches!(Normalizer::fit(&Vec::<Sample>::new()), Err(DataError::Empty)));
+}
+
+#[test]
+fn splits_are_seeded_and_disjoint() {
+    let samples: Vec<Sample> = (0..10).map(|i| sample(&[i as f32], 0)).collect();
+    let (train, test) = train_test_split(samples.clone(), 0.3, 7);
+    assert_eq!((train.len(), test.len()), (7, 3));
+    assert_eq!(train_test_split(samples, 0.3, 7), (train.clone(), test.clone()));
+    let mut all: Vec<f32> = train.iter().chain(&test).map(|s| s.features[0]).collect();
+    all.sort_by(f32::total_cmp);
+    assert_eq!(all, (0..10).map(|i| i as f32).collect::<Vec<_>>());
+}
+
+#[test]
+fn pouw_datasets_round_trip_through_samples() {
+    let data = Dataset::for_task(&generate_task(1, 3));
+    assert_eq!(Dataset::from_samples(&data.to_samples()).unwrap(), data);
+
+    let loaded = Dataset::from_samples(&[sample(&[0.5, -1.0], 1)]).unwrap();
+    assert_eq!((loaded.features[0], loaded.labels[0]), ([ONE / 2, -ONE], ONE));
+    assert!(Dataset::from_samples(&[sample(&[0.5], 1)]).is_err());
+    assert!(Dataset::from_samples(&[sample(&[0.5, 0.5], 2)]).is_err());
+}
+
+#[test]
+fn canaries_mix_into_local_data() {
+    let participants = vec!["a".to_string()];
+    let round = CanaryRound::inject(1, [0; 32], &participants, 2, 2, CanaryConfig::default());
+    let mut local = vec![sample(&[0.0, 0.0], 0)];
+    assert_eq!(round.mix_into("a", &mut local), 16);
+    assert_eq!(local.len(), 17);
+    assert_eq!(round.mix_into("b", &mut local), 0);
+}
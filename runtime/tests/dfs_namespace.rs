@@ -0,0 +1,110 @@
+use runtime::distributed_storage::ConsistencyLevel;
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::NetworkTransferCoordinator;
+use runtime::large_data_transfer::{
+    ChunkManager, DataChunk, DirEntry, EntryKind, LargeDataConfig, LargeDataDescriptor,
+    LargeDataError, Namespace, NamespaceError, Permissions,
+};
+use std::sync::Arc;
+
+fn descriptor(id: &str, size: u64) -> LargeDataDescriptor {
+    LargeDataDescriptor::new(id.into(), id.into(), size, vec![])
+}
+
+#[test]
+fn files_are_organized_into_directories() {
+    let mut ns = Namespace::new(Permissions::public("root"));
+    ns.mkdir("/datasets/imagenet/train", None).unwrap();
+    ns.put_file("/datasets/imagenet/train/shard-0", descriptor("a", 10)).unwrap();
+    ns.put_file("/datasets/imagenet/labels", descriptor("b", 3)).unwrap();
+
+    assert_eq!(
+        ns.list("/datasets/imagenet").unwrap(),
+        vec![
+            DirEntry { name: "labels".into(), kind: EntryKind::File, size_bytes: 3 },
+            DirEntry { name: "train".into(), kind: EntryKind::Directory, size_bytes: 0 },
+        ]
+    );
+    assert_eq!(ns.file("//datasets/imagenet/train/shard-0/").unwrap().descriptor.id, "a");
+    assert_eq!(
+        ns.put_file("/models/x", descriptor("c", 1)).unwrap_err(),
+        NamespaceError::NotFound("/models".into())
+    );
+    assert_eq!(
+        ns.mkdir("/datasets/imagenet/labels/x", None).unwrap_err(),
+        NamespaceError::NotADirectory("/datasets/imagenet/labels".into())
+    );
+    assert!(matches!(ns.file("/datasets/../etc"), Err(NamespaceError::InvalidPath(_))));
+
+    // Renaming moves within a directory; moving keeps the name.
+    ns.rename("/datasets/imagenet/labels", "/datasets/imagenet/classes").unwrap();
+    ns.mkdir("/archive", None).unwrap();
+    ns.move_into("/datasets/imagenet/train", "/archive").unwrap();
+    assert_eq!(ns.file("/archive/train/shard-0").unwrap().descriptor.id, "a");
+    assert_eq!(ns.list("/datasets/imagenet").unwrap()[0].name, "classes");
+    assert_eq!(
+        ns.move_into("/archive", "/archive/train").unwrap_err(),
+        NamespaceError::MoveIntoSelf("/archive".into())
+    );
+    assert_eq!(
+        ns.rename("/archive/train", "/datasets/imagenet/classes").unwrap_err(),
+        NamespaceError::AlreadyExists("/datasets/imagenet/classes".into())
+    );
+
+    ns.remove("/archive").unwrap();
+    assert!(ns.list("/archive").is_err());
+}
+
+#[test]
+fn files_inherit_their_directory_permissions() {
+    let mut ns = Namespace::new(Permissions::public("root"));
+    ns.mkdir("/private/alice", Some(Permissions::private("alice").with_reader("bob"))).unwrap();
+    ns.mkdir("/private/alice/notes", None).unwrap();
+    ns.put_file("/private/alice/notes/todo", descriptor("t", 1)).unwrap();
+    ns.put_file("/readme", descriptor("r", 1)).unwrap();
+
+    assert_eq!(ns.directory("/private").unwrap().permissions, Permissions::public("root"));
+    let todo = &ns.file("/private/alice/notes/todo").unwrap().permissions;
+    assert!(todo.can_write("alice") && todo.can_read("bob") && !todo.can_write("bob"));
+    assert!(!todo.can_read("eve"));
+    assert!(ns.file("/readme").unwrap().permissions.can_read("eve"));
+
+    // Moved files keep their permissions; new defaults apply to new files only.
+    ns.move_into("/private/alice/notes/todo", "/").unwrap();
+    assert!(!ns.file("/todo").unwrap().permissions.can_read("eve"));
+    ns.set_permissions("/private/alice/notes", Permissions::private("alice")).unwrap();
+    ns.put_file("/private/alice/notes/diary", descriptor("d", 1)).unwrap();
+    assert!(!ns.file("/private/alice/notes/diary").unwrap().permissions.can_read("bob"));
+    assert!(ns.file("/todo").unwrap().permissions.can_read("bob"));
+}
+
+#[tokio::test]
+async fn files_are_retrieved_by_path() {
+    let chunk = DataChunk::new_from_slice(vec![7; 4096], 0, CompressionAlgorithm::None).unwrap();
+    let manager = Arc::new(ChunkManager::default());
+    manager.store_chunk(chunk.clone()).unwrap();
+    let coordinator =
+        NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager);
+
+    let mut ns = Namespace::new(Permissions::private("alice"));
+    ns.mkdir("/models", None).unwrap();
+    let descriptor = LargeDataDescriptor::new(
+        "weights".into(),
+        "weights".into(),
+        4096,
+        vec![chunk.id.as_str().to_string()],
+    );
+    ns.put_file("/models/weights", descriptor).unwrap();
+
+    let data = coordinator
+        .retrieve_path(&ns, "/models/weights", "alice", ConsistencyLevel::One)
+        .await
+        .unwrap();
+    assert_eq!(data, chunk.data);
+    let denied =
+        coordinator.retrieve_path(&ns, "/models/weights", "eve", ConsistencyLevel::One).await;
+    assert!(matches!(
+        denied,
+        Err(LargeDataError::Namespace(NamespaceError::PermissionDenied { .. }))
+    ));
+}
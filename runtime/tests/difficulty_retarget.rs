@@ -0,0 +1,172 @@
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::pouw::{retarget, BlockSample, Retarget, RetargetConfig};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+mod common;
+use common::mine_jobs;
+
+/// Deterministic network model: training runs at `epochs_per_sec`, and the
+/// nonce search takes the expected time to hit `difficulty` at `hashrate`.
+struct Network {
+    epochs_per_sec: f64,
+    hashrate: f64,
+}
+
+impl Network {
+    fn block(&self, at: Retarget) -> (u64, u64) {
+        let training_ms = (at.epochs as f64 / self.epochs_per_sec * 1000.0) as u64;
+        let pow_ms = (2f64.powi(32) / (at.difficulty as f64 + 1.0) / self.hashrate * 1000.0) as u64;
+        (training_ms, training_ms + pow_ms)
+    }
+}
+
+/// Mines `blocks` blocks, returning the per-block intervals in seconds and
+/// the retarget applied to each.
+fn simulate(
+    config: &RetargetConfig,
+    network: &Network,
+    start: Retarget,
+    blocks: usize,
+    samples: &mut Vec<BlockSample>,
+) -> (Vec<f64>, Vec<Retarget>) {
+    let mut current = start;
+    let mut clock_ms = samples.last().map_or(0, |s| s.timestamp as u64 * 1000);
+    let mut intervals = Vec::new();
+    let mut history = Vec::new();
+    for _ in 0..blocks {
+        let (training_ms, block_ms) = network.block(current);
+        clock_ms += block_ms;
+        intervals.push(block_ms as f64 / 1000.0);
+        samples.push(BlockSample { timestamp: (clock_ms / 1000) as i64, epochs: current.epochs, training_ms });
+        current = retarget(config, current, samples);
+        history.push(current);
+    }
+    (intervals, history)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+#[test]
+fn converges_to_target_block_time_and_training_share() {
+    let config = RetargetConfig::default();
+    let network = Network { epochs_per_sec: 2.0, hashrate: 100.0 };
+    let mut samples = Vec::new();
+    let (intervals, history) = simulate(&config, &network, config.initial, 400, &mut samples);
+
+    let settled = mean(&intervals[300..]);
+    assert!((settled - 60.0).abs() < 3.0, "settled block time {}", settled);
+    let epochs = history.last().unwrap().epochs;
+    assert!((55..=65).contains(&epochs), "epochs {}", epochs);
+}
+
+#[test]
+fn does_not_oscillate_after_settling() {
+    let config = RetargetConfig::default();
+    let network = Network { epochs_per_sec: 2.0, hashrate: 100.0 };
+    let mut samples = Vec::new();
+    let (_, history) = simulate(&config, &network, config.initial, 400, &mut samples);
+
+    // Once settled, consecutive targets stay within 2% of each other and
+    // never flip direction with a large swing.
+    let tail = &history[300..];
+    for pair in tail.windows(2) {
+        let change = pair[1].difficulty as f64 / pair[0].difficulty as f64 - 1.0;
+        assert!(change.abs() < 0.02, "step of {:.3} between {:?}", change, pair);
+    }
+    let min = tail.iter().map(|r| r.difficulty).min().unwrap() as f64;
+    let max = tail.iter().map(|r| r.difficulty).max().unwrap() as f64;
+    assert!(max / min < 1.1, "difficulty range {}..{}", min, max);
+}
+
+#[test]
+fn recovers_from_hashrate_shock_without_overshoot() {
+    let config = RetargetConfig::default();
+    let mut samples = Vec::new();
+    let before = Network { epochs_per_sec: 2.0, hashrate: 100.0 };
+    let (_, history) = simulate(&config, &before, config.initial, 300, &mut samples);
+
+    // Hashrate quadruples: blocks speed up, then return to target.
+    let after = Network { epochs_per_sec: 2.0, hashrate: 400.0 };
+    let (intervals, _) = simulate(&config, &after, *history.last().unwrap(), 300, &mut samples);
+    assert!(intervals[0] < 50.0);
+    let settled = mean(&intervals[200..]);
+    assert!((settled - 60.0).abs() < 3.0, "settled block time {}", settled);
+    assert!(intervals.iter().all(|t| *t < 75.0), "overshoot to {:?}", intervals.iter().cloned().fold(0.0, f64::max));
+}
+
+#[test]
+fn steps_and_targets_are_bounded() {
+    let config = RetargetConfig::default();
+    let current = Retarget { difficulty: 0x0010_0000, epochs: 100 };
+
+    // Blocks a hundred times too slow with nearly free training.
+    let slow: Vec<BlockSample> = (0..10)
+        .map(|i| BlockSample { timestamp: i * 6_000, epochs: 100, training_ms: 1 })
+        .collect();
+    let next = retarget(&config, current, &slow);
+    assert_eq!(next.difficulty, 0x0010_0000 * 125 / 100);
+    assert_eq!(next.epochs, 125);
+
+    // Blocks with zero spacing and very slow training.
+    let fast: Vec<BlockSample> = (0..10)
+        .map(|_| BlockSample { timestamp: 0, epochs: 100, training_ms: 10_000_000 })
+        .collect();
+    let next = retarget(&config, current, &fast);
+    assert_eq!(next.difficulty, 0x0010_0000 * 800 / 1000);
+    assert_eq!(next.epochs, 80);
+
+    // Repeated pushes saturate at the configured limits.
+    let mut at = current;
+    for _ in 0..200 {
+        at = retarget(&config, at, &slow);
+    }
+    assert_eq!(at, Retarget { difficulty: config.max_difficulty, epochs: config.max_epochs });
+    for _ in 0..200 {
+        at = retarget(&config, at, &fast);
+    }
+    assert_eq!(at, Retarget { difficulty: config.min_difficulty, epochs: config.min_epochs });
+
+    // Too little history keeps the current values.
+    assert_eq!(retarget(&config, current, &slow[..1]), current);
+}
+
+#[test]
+fn chain_uses_initial_retarget_at_genesis() {
+    let chain = Blockchain::new(BlockchainConfig::default());
+    assert_eq!(chain.next_retarget(), RetargetConfig::default().initial);
+    assert_eq!(chain.calculate_next_difficulty(), RetargetConfig::default().initial.difficulty);
+}
+
+#[test]
+fn local_config_cannot_change_the_expected_retarget() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    chain.config.retarget.initial = Retarget { difficulty: u32::MAX, epochs: 1 };
+    assert_eq!(chain.next_retarget(), RetargetConfig::default().initial);
+}
+
+#[tokio::test]
+async fn blocks_must_use_the_retargeted_difficulty_and_task_size() {
+    let chain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let (block, _) = mine_jobs(&chain, VecDeque::new()).await;
+    let mut chain = chain.lock().await;
+    let expected = chain.next_retarget();
+    assert_eq!((block.difficulty, block.task.epochs), (expected.difficulty, expected.epochs));
+
+    let mut easier = block.clone();
+    easier.difficulty = RetargetConfig::default().max_difficulty;
+    easier.hash = easier.calculate_hash();
+    let err = chain.add_block(easier).unwrap_err();
+    assert!(err.to_string().contains("difficulty"), "{}", err);
+
+    let mut smaller = block.clone();
+    smaller.task.epochs = 1;
+    smaller.hash = smaller.calculate_hash();
+    let err = chain.add_block(smaller).unwrap_err();
+    assert!(err.to_string().contains("epochs"), "{}", err);
+
+    chain.add_block(block).unwrap();
+}
@@ -0,0 +1,164 @@
+use ed25519_dalek::SigningKey;
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::dispute::{
+    select_jurors, sign_vote, Arbitration, ArbitrationConfig, DisputeError, Evidence, EvidenceKind, Verdict,
+};
+use runtime::job_manager::JobManager;
+use runtime::scheduler::{Heartbeat, JobScheduler};
+use runtime::token::TokenLedger;
+
+fn key(i: u8) -> SigningKey {
+    SigningKey::from_bytes(&[i; 32])
+}
+
+fn id(key: &SigningKey) -> String {
+    hex::encode(key.verifying_key().to_bytes())
+}
+
+fn poster() -> SigningKey {
+    key(10)
+}
+
+fn worker() -> SigningKey {
+    key(11)
+}
+
+/// Job 1 escrowing 300 from the poster and assigned to the worker, and six
+/// other staked accounts.
+fn setup() -> (JobManager, JobScheduler, Vec<SigningKey>) {
+    let mut ledger = TokenLedger::new();
+    ledger.mint(&id(&poster()), 1_000);
+    ledger.mint(&id(&worker()), 500);
+    ledger.stake(&id(&worker()), 500).unwrap();
+    let keys: Vec<SigningKey> = (1..=6).map(key).collect();
+    for k in &keys {
+        ledger.mint(&id(k), 200);
+        ledger.stake(&id(k), 200).unwrap();
+    }
+    let mut manager = JobManager::new(ledger);
+    manager.submit_job(&id(&poster()), 1, 300).unwrap();
+    let mut scheduler = JobScheduler::default();
+    scheduler.record_heartbeat(&Heartbeat::sign(&worker(), 1, Vec::new()), 1).unwrap();
+    scheduler.assign(1, &id(&worker())).unwrap();
+    (manager, scheduler, keys)
+}
+
+/// A chain at height 0, whose tip seeds the draw.
+fn chain() -> Blockchain {
+    Blockchain::new(BlockchainConfig::default())
+}
+
+fn config() -> ArbitrationConfig {
+    ArbitrationConfig { jurors: 3, voting_blocks: 10, ..ArbitrationConfig::default() }
+}
+
+fn evidence(kind: EvidenceKind, by: &SigningKey) -> Evidence {
+    Evidence::sign(1, kind, hex::encode([7u8; 32]), by)
+}
+
+#[test]
+fn the_majority_verdict_settles_the_escrow() {
+    let (mut manager, scheduler, keys) = setup();
+    let mut court = Arbitration::new(config());
+    let dispute = court.open(&manager, &scheduler, &chain(), 1).unwrap();
+    assert_eq!(dispute.worker, id(&worker()));
+    let jurors = dispute.jurors.clone();
+    assert_eq!(jurors.len(), 3);
+    assert!(!jurors.contains(&id(&poster())) && !jurors.contains(&id(&worker())));
+    assert_eq!(court.open(&manager, &scheduler, &chain(), 1).unwrap_err(), DisputeError::AlreadyOpen(1));
+
+    court.submit_evidence(1, evidence(EvidenceKind::EvaluationReport, &poster()), 1).unwrap();
+    court.submit_evidence(1, evidence(EvidenceKind::Logs, &worker()), 2).unwrap();
+    assert_eq!(
+        court.submit_evidence(1, evidence(EvidenceKind::Logs, &keys[0]), 2),
+        Err(DisputeError::NotAParty(id(&keys[0])))
+    );
+    // Evidence is signed by the party it claims to come from.
+    let mut impersonated = evidence(EvidenceKind::Logs, &keys[0]);
+    impersonated.submitted_by = id(&worker());
+    assert_eq!(court.submit_evidence(1, impersonated, 2), Err(DisputeError::InvalidSignature(id(&worker()))));
+    let mut altered = evidence(EvidenceKind::Logs, &poster());
+    altered.hash = hex::encode([8u8; 32]);
+    assert!(matches!(court.submit_evidence(1, altered, 2), Err(DisputeError::InvalidSignature(_))));
+    let bad = Evidence::sign(1, EvidenceKind::Other, "not-a-hash".into(), &poster());
+    assert!(matches!(court.submit_evidence(1, bad, 2), Err(DisputeError::InvalidEvidence(_))));
+
+    let juror_keys: Vec<&SigningKey> = keys.iter().filter(|k| jurors.contains(&id(k))).collect();
+    let outsider = keys.iter().find(|k| !jurors.contains(&id(k))).unwrap();
+    let stranger = court.vote(&sign_vote(1, Verdict::Slash, outsider), 3);
+    assert!(matches!(stranger, Err(DisputeError::NotAJuror(_))));
+    let mut forged = sign_vote(1, Verdict::Release, juror_keys[0]);
+    forged.verdict = Verdict::Slash;
+    assert!(matches!(court.vote(&forged, 3), Err(DisputeError::InvalidSignature(_))));
+
+    court.vote(&sign_vote(1, Verdict::Slash, juror_keys[0]), 3).unwrap();
+    court.vote(&sign_vote(1, Verdict::Slash, juror_keys[1]), 4).unwrap();
+    let again = court.vote(&sign_vote(1, Verdict::Refund, juror_keys[1]), 4);
+    assert!(matches!(again, Err(DisputeError::AlreadyVoted(_))));
+    assert_eq!(court.settle(&mut manager, 1, 5).unwrap_err(), DisputeError::VotingOpen(10));
+    court.vote(&sign_vote(1, Verdict::Release, juror_keys[2]), 5).unwrap();
+
+    let resolution = court.settle(&mut manager, 1, 5).unwrap();
+    assert_eq!(resolution.verdict, Verdict::Slash);
+    assert_eq!(resolution.tally[&Verdict::Slash], 2);
+    let mut rewarded = resolution.rewarded.clone();
+    rewarded.sort();
+    let mut coherent = vec![id(juror_keys[0]), id(juror_keys[1])];
+    coherent.sort();
+    assert_eq!(rewarded, coherent);
+    assert_eq!(resolution.penalized, vec![id(juror_keys[2])]);
+
+    let ledger = manager.ledger();
+    assert_eq!(ledger.balance(&id(&poster())), 1_000);
+    assert_eq!(ledger.staked(&id(&worker())), 400);
+    assert_eq!((ledger.balance(&id(juror_keys[0])), ledger.staked(&id(juror_keys[0]))), (10, 200));
+    assert_eq!((ledger.balance(&id(juror_keys[2])), ledger.staked(&id(juror_keys[2]))), (0, 180));
+    assert!(manager.jobs().is_empty() && court.dispute(1).is_none());
+}
+
+#[test]
+fn voting_closes_and_abstainers_are_slashed() {
+    let (mut manager, scheduler, keys) = setup();
+    let mut court = Arbitration::new(config());
+    let jurors = court.open(&manager, &scheduler, &chain(), 1).unwrap().jurors.clone();
+    let juror = keys.iter().find(|k| id(k) == jurors[0]).unwrap();
+    court.vote(&sign_vote(1, Verdict::Release, juror), 10).unwrap();
+    assert_eq!(court.vote(&sign_vote(1, Verdict::Release, juror), 11), Err(DisputeError::VotingClosed(10)));
+
+    // One vote for release against two abstentions still carries.
+    let resolution = court.settle(&mut manager, 1, 11).unwrap();
+    assert_eq!(resolution.verdict, Verdict::Release);
+    assert_eq!(resolution.penalized.len(), 2);
+    assert_eq!(manager.ledger().balance(&id(&worker())), 300);
+    assert_eq!(court.settle(&mut manager, 1, 11).unwrap_err(), DisputeError::NotOpen(1));
+    assert_eq!(court.open(&manager, &scheduler, &chain(), 1).unwrap_err(), DisputeError::JobNotFound(1));
+}
+
+#[test]
+fn only_assigned_jobs_can_be_disputed() {
+    let (mut manager, scheduler, _) = setup();
+    manager.submit_job(&id(&poster()), 2, 100).unwrap();
+    let mut court = Arbitration::new(config());
+    assert_eq!(court.open(&manager, &scheduler, &chain(), 2).unwrap_err(), DisputeError::NotAssigned(2));
+}
+
+#[test]
+fn jurors_are_drawn_by_stake_from_a_shared_seed() {
+    let pool: Vec<(String, u64)> =
+        vec![("whale".into(), 10_000), ("a".into(), 1), ("b".into(), 1), ("zero".into(), 0)];
+    let drawn = select_jurors(b"seed", 9, &pool, 3);
+    assert_eq!(drawn, select_jurors(b"seed", 9, &pool, 3));
+    assert_eq!(drawn.len(), 3);
+    assert!(!drawn.contains(&"zero".to_string()));
+    let whale_first =
+        (0..20).filter(|job| select_jurors(b"seed", *job, &pool, 1)[0] == "whale").count();
+    assert!(whale_first >= 18, "{whale_first}");
+
+    // Candidates under the minimum stake are never drawn.
+    let (manager, scheduler, _) = setup();
+    let mut court = Arbitration::new(ArbitrationConfig { min_juror_stake: 201, ..config() });
+    assert_eq!(
+        court.open(&manager, &scheduler, &chain(), 1).unwrap_err(),
+        DisputeError::NotEnoughJurors { available: 0, needed: 3 }
+    );
+}
@@ -0,0 +1,74 @@
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::endpoint::{EndpointError, EndpointRecord};
+use schnorrkel::{Keypair, SecretKey};
+
+fn funded_key(chain: &mut Blockchain) -> (SecretKey, String) {
+    let key = Keypair::generate().secret.clone();
+    let address = hex::encode(key.to_public().to_bytes());
+    chain.state.set_balance(&address, 100);
+    (key, address)
+}
+
+fn record(owner: &str, primary: &str) -> EndpointRecord {
+    EndpointRecord::new("ep-1", "model-1", owner, primary, vec!["node-b".into(), "node-c".into()])
+}
+
+fn apply(chain: &mut Blockchain, tx: &Transaction) {
+    validate_transaction_stateful(tx, &chain.state).unwrap();
+    chain.state.apply_transaction(tx).unwrap();
+}
+
+#[test]
+fn standbys_take_over_and_the_epoch_advances() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (owner_key, owner) = funded_key(&mut chain);
+    let (standby_key, standby) = funded_key(&mut chain);
+    let mut registered = record(&owner, "node-a");
+    registered.standbys = vec![standby.clone(), "node-c".into()];
+    apply(&mut chain, &Transaction::new_register_endpoint_signed(&owner_key, registered, 1, 0));
+
+    // The standby promotes itself when the primary dies.
+    let tx = Transaction::new_failover_endpoint_signed(&standby_key, "ep-1".into(), standby.clone(), 1, 1, 0);
+    apply(&mut chain, &tx);
+    let stored = &chain.state.endpoints["ep-1"];
+    assert_eq!((stored.primary.as_str(), stored.epoch), (standby.as_str(), 1));
+    assert_eq!(stored.standbys, vec!["node-c".to_string(), "node-a".to_string()]);
+
+    // A replay of the same failover is stale.
+    let replay = Transaction::new_failover_endpoint_signed(&owner_key, "ep-1".into(), "node-c".into(), 1, 1, 1);
+    let err = validate_transaction_stateful(&replay, &chain.state).unwrap_err();
+    assert!(err.to_string().contains("epoch"), "{}", err);
+    let next = Transaction::new_failover_endpoint_signed(&owner_key, "ep-1".into(), "node-a".into(), 2, 1, 1);
+    apply(&mut chain, &next);
+    assert_eq!(chain.state.endpoints["ep-1"].primary, "node-a");
+}
+
+#[test]
+fn registrations_and_failovers_are_checked() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (owner_key, owner) = funded_key(&mut chain);
+    let (stranger_key, _) = funded_key(&mut chain);
+
+    // Only the owner registers, once.
+    let tx = Transaction::new_register_endpoint_signed(&stranger_key, record(&owner, "node-a"), 1, 0);
+    assert!(validate_transaction_stateful(&tx, &chain.state).is_err());
+    apply(&mut chain, &Transaction::new_register_endpoint_signed(&owner_key, record(&owner, "node-a"), 1, 0));
+    let again = Transaction::new_register_endpoint_signed(&owner_key, record(&owner, "node-a"), 1, 1);
+    assert!(validate_transaction_stateful(&again, &chain.state).is_err());
+
+    // Strangers cannot move the endpoint, nor can it move to a non-standby.
+    let hijack = Transaction::new_failover_endpoint_signed(&stranger_key, "ep-1".into(), "node-b".into(), 1, 1, 0);
+    assert!(validate_transaction_stateful(&hijack, &chain.state).is_err());
+    let stored = &chain.state.endpoints["ep-1"];
+    assert_eq!(
+        stored.check_failover(&owner, "node-z", 1),
+        Err(EndpointError::NotAReplica { endpoint: "ep-1".into(), node: "node-z".into() })
+    );
+    assert_eq!(stored.check_failover(&owner, "node-b", 3), Err(EndpointError::StaleEpoch { current: 0, got: 3 }));
+
+    let lonely = EndpointRecord::new("ep-2", "model-1", &owner, "node-a", Vec::new());
+    assert_eq!(lonely.check_well_formed(), Err(EndpointError::Malformed("no standby replicas")));
+    let twice = EndpointRecord::new("ep-2", "model-1", &owner, "node-a", vec!["node-a".into()]);
+    assert_eq!(twice.check_well_formed(), Err(EndpointError::Malformed("replica listed twice")));
+}
@@ -0,0 +1,88 @@
+use runtime::distributed_storage::{allocate_shards, NodeMetrics, StoragePolicy};
+use runtime::large_data_transfer::pricing;
+use runtime::large_data_transfer::{ErasureError, ErasureScheme, RedundancyConfig, RedundancyPolicy};
+
+fn object(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+#[test]
+fn any_k_shards_rebuild_the_object() {
+    let scheme = ErasureScheme::new(4, 3);
+    let data = object(1_001);
+    let shards = scheme.encode(&data).unwrap();
+    assert_eq!(shards.len(), 7);
+    assert!(shards.iter().all(|s| s.len() == 251));
+    // The code is systematic: the data shards are the object itself.
+    assert_eq!(&shards[0][..], &data[..251]);
+
+    // Every way of losing three of the seven shards.
+    for a in 0..7 {
+        for b in a + 1..7 {
+            for c in b + 1..7 {
+                let mut partial: Vec<Option<Vec<u8>>> = shards.iter().cloned().map(Some).collect();
+                for lost in [a, b, c] {
+                    partial[lost] = None;
+                }
+                assert_eq!(scheme.decode(&partial, data.len()).unwrap(), data, "lost {a}, {b}, {c}");
+                scheme.reconstruct(&mut partial).unwrap();
+                assert_eq!(partial, shards.iter().cloned().map(Some).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    let mut partial: Vec<Option<Vec<u8>>> = shards.iter().cloned().map(Some).collect();
+    partial[..4].iter_mut().for_each(|s| *s = None);
+    assert_eq!(scheme.decode(&partial, data.len()), Err(ErasureError::TooFewShards { present: 3, needed: 4 }));
+    assert!(matches!(scheme.decode(&partial[..6], 1), Err(ErasureError::WrongShardCount { .. })));
+    assert!(ErasureScheme::new(0, 2).encode(&data).is_err());
+    assert!(ErasureScheme::new(200, 100).validate().is_err());
+}
+
+#[test]
+fn corrupt_chunks_count_as_lost() {
+    let scheme = ErasureScheme::new(3, 2);
+    let data = object(10_000);
+    let chunks = scheme.encode_chunks(&data).unwrap();
+    assert_eq!(chunks.iter().map(|c| c.info.index).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+    let mut fetched: Vec<_> = chunks.into_iter().map(Some).collect();
+    fetched[0] = None;
+    fetched[2].as_mut().unwrap().data[5] ^= 0xff;
+    assert_eq!(scheme.decode_chunks(&fetched, data.len()).unwrap(), data);
+    fetched[4] = None;
+    assert!(scheme.decode_chunks(&fetched, data.len()).is_err());
+}
+
+#[test]
+fn erasure_coding_cuts_the_storage_bill() {
+    let gib = 1u128 << 30;
+    let replicated = RedundancyPolicy { copies: 2, geo_spread: true, erasure: None };
+    let coded = RedundancyPolicy { erasure: Some(ErasureScheme::new(4, 2)), ..replicated };
+    // Both survive the loss of two nodes.
+    assert_eq!(pricing::quote(8 * gib, replicated, 10).price_bcai, 240);
+    assert_eq!(pricing::quote(8 * gib, coded, 10).price_bcai, 120);
+    assert_eq!((replicated.nodes_needed(), coded.nodes_needed()), (3, 6));
+
+    let config = RedundancyConfig { erasure_coding: true, ..RedundancyConfig::default() };
+    assert_eq!(RedundancyPolicy::from(config).erasure, Some(ErasureScheme::new(4, 2)));
+    assert_eq!(RedundancyPolicy::from(RedundancyConfig::default()).erasure, None);
+}
+
+#[test]
+fn shards_are_placed_on_distinct_nodes() {
+    let metrics: Vec<NodeMetrics> = (0..5)
+        .map(|i| NodeMetrics {
+            node_id: format!("n{i}"),
+            reputation: 0.5 + i as f32 / 10.0,
+            free_capacity: 0.8,
+            latency_ms: 20,
+            region: format!("r{}", i % 2),
+            energy_score: 0.5,
+            utilisation: 0.2,
+        })
+        .collect();
+    let placement = allocate_shards(&StoragePolicy::default(), &metrics, ErasureScheme::new(3, 2)).unwrap();
+    assert_eq!(placement, vec!["n4", "n3", "n2", "n1", "n0"]);
+    assert!(allocate_shards(&StoragePolicy::default(), &metrics, ErasureScheme::new(4, 2)).is_none());
+}
@@ -0,0 +1,113 @@
+use runtime::blockchain::constants::{BLOCK_REWARD, CHALLENGE_WINDOW_BLOCKS};
+use runtime::blockchain::state::{PoUWClaim, State};
+use runtime::blockchain::{Blockchain, BlockchainConfig, GenesisConfig, RebateConfig, Transaction};
+use runtime::pouw;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+mod common;
+use common::{key, mine, pk_hex, MINER};
+
+fn claim() -> PoUWClaim {
+    PoUWClaim {
+        block_hash: "h".into(),
+        miner: MINER.into(),
+        reward: 100,
+        difficulty: 0,
+        evidence_hash: "e".into(),
+    }
+}
+
+#[test]
+fn rebates_are_off_by_default() {
+    let config: BlockchainConfig = serde_json::from_str(r#"{"max_transactions_per_block": 10}"#).unwrap();
+    assert_eq!(config.rebate, RebateConfig::default());
+    assert_eq!(config.rebate.burn(1_000), 0);
+
+    let half = RebateConfig { rebate_bps: 5_000, ..RebateConfig::default() };
+    assert_eq!(half.burn(11), 5);
+    assert!(!half.is_epoch_end(0));
+    assert!(half.is_epoch_end(200));
+}
+
+#[test]
+fn reputation_follows_claims() {
+    let mut state = State::new();
+    state.record_pouw_claim(1, claim());
+    state.record_pouw_claim(2, claim());
+    assert!(!state.reputation.contains_key(MINER));
+
+    // Block 1's claim closes unchallenged.
+    state.record_pouw_claim(CHALLENGE_WINDOW_BLOCKS + 1, claim());
+    assert_eq!(state.reputation[MINER], 1);
+
+    // A fraud proof against block 2 wipes the miner's reputation.
+    state.resolve_fraud_proof(2).unwrap();
+    assert!(!state.reputation.contains_key(MINER));
+}
+
+#[tokio::test]
+async fn burned_fees_are_minted_back_to_reputable_payers_at_epoch_end() {
+    let config = BlockchainConfig {
+        rebate: RebateConfig { epoch_blocks: 2, rebate_bps: 5_000, min_reputation: 3 },
+        ..BlockchainConfig::default()
+    };
+    let scoring = config.scoring.clone();
+    let mut chain = Blockchain::new(config);
+    let (reliable, newcomer) = (key(), key());
+    for payer in [&reliable, &newcomer] {
+        chain.state.set_balance(&pk_hex(payer), 100);
+    }
+    chain.state.reputation.insert(pk_hex(&reliable), 5);
+    let chain = Arc::new(Mutex::new(chain));
+
+    let recipient = key().to_public();
+    let txs = vec![
+        Transaction::new_transfer(&reliable, recipient, 20, 10, 0),
+        Transaction::new_transfer(&newcomer, recipient, 20, 10, 0),
+    ];
+    let block = mine(&chain, txs).await;
+    let subsidy = pouw::score(block.solution.accuracy, &scoring).reward_share(BLOCK_REWARD);
+    chain.lock().await.add_block(block).unwrap();
+    {
+        let chain = chain.lock().await;
+        // Half of each fee is burned instead of paid to the miner.
+        assert_eq!(chain.get_balance(MINER), subsidy + 10);
+        assert_eq!(chain.state.pending_rebates[&pk_hex(&reliable)], 5);
+        assert_eq!(chain.get_balance(&pk_hex(&reliable)), 70);
+    }
+
+    // Block 2 closes the epoch.
+    let block = mine(&chain, vec![]).await;
+    chain.lock().await.add_block(block).unwrap();
+    let chain = chain.lock().await;
+    assert_eq!(chain.get_balance(&pk_hex(&reliable)), 75);
+    assert_eq!(chain.get_balance(&pk_hex(&newcomer)), 70);
+    assert!(chain.state.pending_rebates.is_empty());
+}
+
+#[tokio::test]
+async fn fees_are_burned_as_genesis_says_whatever_the_local_config() {
+    let payer = key();
+    let rebate = RebateConfig { rebate_bps: 5_000, ..RebateConfig::default() };
+    let genesis = GenesisConfig {
+        initial_balances: BTreeMap::from([(pk_hex(&payer), 100)]),
+        blockchain: BlockchainConfig { rebate: rebate.clone(), ..BlockchainConfig::default() },
+        ..GenesisConfig::default()
+    };
+    let mut chain = Blockchain::with_genesis(genesis.clone()).unwrap();
+    assert_eq!(chain.state.consensus, genesis.blockchain.consensus_params());
+    // A node configured to burn nothing still settles as genesis says.
+    chain.config.rebate = RebateConfig::default();
+    let scoring = chain.state.consensus.scoring.clone();
+    let chain = Arc::new(Mutex::new(chain));
+
+    let block = mine(&chain, vec![Transaction::new_transfer(&payer, key().to_public(), 20, 11, 0)]).await;
+    let subsidy = pouw::score(block.solution.accuracy, &scoring).reward_share(BLOCK_REWARD);
+    chain.lock().await.add_block(block).unwrap();
+    let chain = chain.lock().await;
+    assert_eq!(rebate.kept(11), 6);
+    assert_eq!(chain.get_balance(MINER), subsidy + 6);
+    assert_eq!(chain.state.pending_rebates[&pk_hex(&payer)], 5);
+}
@@ -0,0 +1,91 @@
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::{
+    NetworkPeerInfo, NetworkTransferCoordinator, PeerCapabilities,
+};
+use runtime::large_data_transfer::{ChunkManager, DataChunk, LargeDataConfig, LargeDataDescriptor};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn chunks(n: u32) -> Vec<DataChunk> {
+    (0..n)
+        .map(|i| {
+            let data = (0..4096).map(|b| (b * 7 + i as usize) as u8).collect::<Vec<_>>();
+            DataChunk::new_from_slice(data, i, CompressionAlgorithm::None).unwrap()
+        })
+        .collect()
+}
+
+fn descriptor(chunks: &[DataChunk]) -> LargeDataDescriptor {
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    LargeDataDescriptor::new("file".into(), "file".into(), 4096 * chunks.len() as u64, hashes)
+}
+
+/// A coordinator serving its own cache over the loopback channel, with
+/// peers `a` and `b` announcing every chunk, and retries a millisecond
+/// apart.
+async fn coordinator(
+    manager: Arc<ChunkManager>,
+    chunks: &[DataChunk],
+) -> NetworkTransferCoordinator {
+    let mut config = LargeDataConfig::default();
+    config.max_concurrent_transfers = 3;
+    config.chunk_timeout = Duration::from_millis(200);
+    config.retry_config.initial_delay = Duration::from_millis(1);
+    let coordinator = NetworkTransferCoordinator::new("local".into(), config, manager);
+    let serving = coordinator.clone();
+    tokio::spawn(async move { serving.message_processing_loop().await });
+    for peer in ["a", "b"] {
+        coordinator
+            .add_peer(NetworkPeerInfo {
+                peer_id: peer.to_string(),
+                addresses: vec![],
+                capabilities: PeerCapabilities {
+                    max_bandwidth_mbps: 100,
+                    max_concurrent_transfers: 4,
+                    supported_compression: vec![],
+                    storage_capacity_gb: 1,
+                    available_chunks: chunks.iter().map(|c| c.id.clone()).collect(),
+                },
+                reputation: 1.0,
+                last_seen: Instant::now(),
+                transfer_stats: Default::default(),
+            })
+            .await;
+    }
+    coordinator
+}
+
+#[tokio::test]
+async fn chunks_are_written_in_order() {
+    let chunks = chunks(8);
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let coordinator = coordinator(manager, &chunks).await;
+    let mut out = Vec::new();
+    let written = coordinator.assemble_file_parallel(&descriptor(&chunks), &mut out).await.unwrap();
+    let data: Vec<u8> = chunks.iter().flat_map(|c| c.data.clone()).collect();
+    assert_eq!((written, out), (data.len() as u64, data));
+}
+
+#[tokio::test]
+async fn corrupt_chunks_are_rejected_and_their_peers_penalized() {
+    let chunks = chunks(4);
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    // Every copy of chunk 2 has rotted, so each peer serves bad data.
+    let mut rotten = chunks[2].clone();
+    rotten.data[7] ^= 1;
+    manager.store_chunk(rotten).unwrap();
+    let coordinator = coordinator(manager, &chunks).await;
+
+    let mut out = Vec::new();
+    let err = coordinator.assemble_file_parallel(&descriptor(&chunks), &mut out).await.unwrap_err();
+    assert!(err.to_string().contains("unavailable after 4 attempts"), "{err}");
+    assert!(out.len() <= 2 * 4096, "nothing past the missing chunk is written");
+    let corrupt = coordinator.corrupt_chunks_by_peer();
+    assert_eq!(corrupt.values().sum::<u64>(), 2, "{corrupt:?}");
+}
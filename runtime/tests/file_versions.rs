@@ -0,0 +1,96 @@
+use runtime::distributed_storage::ConsistencyLevel;
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::NetworkTransferCoordinator;
+use runtime::large_data_transfer::{
+    ChunkManager, DataChunk, LargeDataConfig, LargeDataDescriptor, LargeDataError, VersionDiff,
+    VersionError, VersionStore,
+};
+use std::sync::Arc;
+
+fn chunk(byte: u8) -> DataChunk {
+    DataChunk::new_from_slice(vec![byte; 4096], 0, CompressionAlgorithm::None).unwrap()
+}
+
+fn descriptor(chunks: &[&DataChunk]) -> LargeDataDescriptor {
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    LargeDataDescriptor::new("ckpt".into(), "ckpt".into(), 4096 * chunks.len() as u64, hashes)
+}
+
+#[test]
+fn versions_keep_history_diffs_and_roll_back() {
+    let (a, b, c) = (chunk(1), chunk(2), chunk(3));
+    let mut store = VersionStore::new();
+    assert_eq!(store.store_version("model", descriptor(&[&a, &b])), 1);
+    assert_eq!(store.store_version("model", descriptor(&[&a, &c])), 2);
+    assert_eq!(store.store_version("model", descriptor(&[&a, &c, &b])), 3);
+
+    let history = store.history("model").unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(
+        history[0].diff,
+        VersionDiff { chunks_added: 2, chunks_removed: 0, size_delta: 8192 }
+    );
+    assert_eq!(history[1].diff, VersionDiff { chunks_added: 1, chunks_removed: 1, size_delta: 0 });
+    assert_eq!(
+        history[2].diff,
+        VersionDiff { chunks_added: 1, chunks_removed: 0, size_delta: 4096 }
+    );
+
+    // Rolling back adds the old content as the newest version.
+    assert_eq!(store.rollback("model", 1).unwrap(), 4);
+    let latest = store.latest("model").unwrap();
+    assert_eq!(latest.restored_from, Some(1));
+    assert_eq!(
+        latest.descriptor.chunk_hashes,
+        store.version("model", 1).unwrap().descriptor.chunk_hashes
+    );
+    assert_eq!(
+        store.rollback("model", 9).unwrap_err(),
+        VersionError::UnknownVersion { id: "model".into(), version: 9 }
+    );
+    assert_eq!(store.history("other").unwrap_err(), VersionError::UnknownFile("other".into()));
+}
+
+#[test]
+fn pruning_keeps_pinned_versions_and_shared_chunks() {
+    let (a, b, c, d) = (chunk(1), chunk(2), chunk(3), chunk(4));
+    let mut store = VersionStore::new();
+    store.store_version("model", descriptor(&[&a, &b]));
+    store.store_version("model", descriptor(&[&a, &c]));
+    store.store_version("model", descriptor(&[&a, &d]));
+    store.store_version("dataset", descriptor(&[&c]));
+    store.pin("model", 1).unwrap();
+
+    // Version 2 goes; `c` is still used by the dataset.
+    assert!(store.prune("model", 1).unwrap().is_empty());
+    let kept: Vec<u32> = store.history("model").unwrap().iter().map(|v| v.version).collect();
+    assert_eq!(kept, vec![1, 3]);
+
+    store.unpin("model", 1).unwrap();
+    assert_eq!(store.prune("model", 1).unwrap(), vec![b.id.clone()]);
+    assert_eq!(store.latest("model").unwrap().version, 3);
+}
+
+#[tokio::test]
+async fn old_versions_are_retrieved_by_number() {
+    let (a, b) = (chunk(1), chunk(2));
+    let manager = Arc::new(ChunkManager::default());
+    manager.store_chunk(a.clone()).unwrap();
+    manager.store_chunk(b.clone()).unwrap();
+    let coordinator =
+        NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager);
+    let mut store = VersionStore::new();
+    store.store_version("ckpt", descriptor(&[&a]));
+    store.store_version("ckpt", descriptor(&[&a, &b]));
+
+    let v1 =
+        coordinator.retrieve_file_version(&store, "ckpt", 1, ConsistencyLevel::One).await.unwrap();
+    assert_eq!(v1, a.data);
+    let v2 =
+        coordinator.retrieve_file_version(&store, "ckpt", 2, ConsistencyLevel::One).await.unwrap();
+    assert_eq!(v2.len(), 8192);
+    assert!(matches!(
+        coordinator.retrieve_file_version(&store, "ckpt", 3, ConsistencyLevel::One).await,
+        Err(LargeDataError::Version(VersionError::UnknownVersion { .. }))
+    ));
+}
@@ -0,0 +1,119 @@
+use runtime::blockchain::constants::CHALLENGE_WINDOW_BLOCKS;
+use runtime::blockchain::state::{PoUWClaim, State};
+use runtime::blockchain::validation::{validate_fraud_proof, validate_transaction_stateful};
+use runtime::blockchain::{Block, Blockchain, BlockchainConfig, Transaction};
+use runtime::pouw::training;
+use runtime::pouw::{FailedCheck, FraudProof};
+use schnorrkel::SecretKey;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+mod common;
+use common::{key, mine, pk_hex, MINER};
+
+/// A chain whose miner has staked 500, plus a funded challenger.
+fn setup() -> (Arc<Mutex<Blockchain>>, SecretKey) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    chain.state.set_balance(MINER, 1_000);
+    chain.state.stake_tokens(MINER, 500).unwrap();
+    let challenger = key();
+    chain.state.set_balance(&pk_hex(&challenger), 10);
+    (Arc::new(Mutex::new(chain)), challenger)
+}
+
+/// Replaces checkpoint `segment` with weights the training run never produced.
+fn tamper(block: &mut Block, segment: usize) {
+    let mut weights = training::decode_weights(&block.solution.checkpoints[segment]).unwrap();
+    weights[0] += 1;
+    block.solution.checkpoints[segment] = training::encode_weights(&weights);
+}
+
+fn proof_for(block: &Block) -> Option<FraudProof> {
+    FraudProof::detect(block.index, &block.hash, &block.task, &block.solution, block.difficulty)
+}
+
+#[tokio::test]
+async fn honest_solution_has_no_fraud_proof() {
+    let (chain, _) = setup();
+    let block = mine(&chain, vec![]).await;
+    assert!(proof_for(&block).is_none());
+}
+
+#[tokio::test]
+async fn valid_proof_slashes_stake_and_reverts_reward() {
+    let (chain, challenger) = setup();
+    let mut block = mine(&chain, vec![]).await;
+    assert!(block.solution.checkpoints.len() > 3);
+    tamper(&mut block, 2);
+    chain.lock().await.add_block(block.clone()).unwrap();
+
+    let reward = chain.lock().await.state.pouw_claims[&block.index].reward;
+    assert!(reward > 0);
+    assert_eq!(chain.lock().await.get_balance(MINER), 500 + reward);
+
+    let proof = proof_for(&block).unwrap();
+    assert!(matches!(proof.check, FailedCheck::Segment { segment: 2, .. }));
+    let tx = Transaction::new_fraud_proof_signed(&challenger, proof.clone(), 1, 0);
+    validate_transaction_stateful(&tx, &chain.lock().await.state).unwrap();
+
+    let next = mine(&chain, vec![tx]).await;
+    chain.lock().await.add_block(next.clone()).unwrap();
+
+    let chain = chain.lock().await;
+    let next_reward = chain.state.pouw_claims[&next.index].reward;
+    assert_eq!(chain.state.stakes[MINER], 0);
+    assert_eq!(chain.get_balance(MINER), 500 + next_reward);
+    assert_eq!(chain.get_balance(&pk_hex(&challenger)), 9);
+    assert!(!chain.state.pouw_claims.contains_key(&block.index));
+
+    // The claim is resolved, so the same proof cannot slash twice.
+    assert!(validate_fraud_proof(&proof, &chain.state).is_err());
+}
+
+#[tokio::test]
+async fn proof_must_match_the_block_and_its_transcript() {
+    let (chain, _) = setup();
+    let mut block = mine(&chain, vec![]).await;
+    tamper(&mut block, 1);
+    chain.lock().await.add_block(block.clone()).unwrap();
+    let state = chain.lock().await.state.clone();
+    let proof = proof_for(&block).unwrap();
+    validate_fraud_proof(&proof, &state).unwrap();
+
+    let mut wrong_transcript = proof.clone();
+    if let FailedCheck::Segment { recomputed, .. } = &mut wrong_transcript.check {
+        *recomputed = "00".repeat(32);
+    }
+    assert!(validate_fraud_proof(&wrong_transcript, &state).is_err());
+
+    let mut passing_check = proof.clone();
+    passing_check.check = FailedCheck::ModelHash;
+    assert!(validate_fraud_proof(&passing_check, &state).is_err());
+
+    // A proof built over a different solution than the one on chain.
+    let mut other_solution = proof.clone();
+    other_solution.solution.checkpoints[1] = block.solution.checkpoints[0].clone();
+    assert!(validate_fraud_proof(&other_solution, &state).is_err());
+
+    let mut other_block = proof;
+    other_block.block_index += 1;
+    assert!(validate_fraud_proof(&other_block, &state).is_err());
+}
+
+#[test]
+fn claims_close_after_the_challenge_window() {
+    let claim = PoUWClaim {
+        block_hash: "h".into(),
+        miner: MINER.into(),
+        reward: 100,
+        difficulty: 0,
+        evidence_hash: "e".into(),
+    };
+    let mut state = State::new();
+    state.record_pouw_claim(1, claim.clone());
+    state.record_pouw_claim(CHALLENGE_WINDOW_BLOCKS, claim.clone());
+    assert!(state.pouw_claims.contains_key(&1));
+    state.record_pouw_claim(CHALLENGE_WINDOW_BLOCKS + 1, claim);
+    assert!(!state.pouw_claims.contains_key(&1));
+    assert_eq!(state.pouw_claims.len(), 2);
+}
@@ -0,0 +1,93 @@
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::{Blockchain, BlockchainError, GenesisConfig, GenesisValidator, StoragePricing, Transaction};
+use runtime::pouw::PoUWConfig;
+use std::collections::BTreeMap;
+
+mod common;
+use common::pk_hex;
+
+fn key(c: char) -> String {
+    c.to_string().repeat(64)
+}
+
+fn config() -> GenesisConfig {
+    GenesisConfig {
+        chain_id: "bcai-testnet-1".into(),
+        timestamp: 1_700_000_000,
+        initial_balances: BTreeMap::from([(key('a'), 5_000), (key('b'), 250)]),
+        validators: vec![GenesisValidator { pubkey: key('c'), stake: 100 }],
+        ..GenesisConfig::default()
+    }
+}
+
+#[test]
+fn chain_state_follows_genesis_file() {
+    let path = std::env::temp_dir().join(format!("bcai-genesis-{}.json", std::process::id()));
+    config().save(&path).unwrap();
+    let chain = Blockchain::from_genesis(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(chain.get_balance(&key('a')), 5_000);
+    assert_eq!(chain.get_balance(&key('b')), 250);
+    assert_eq!(chain.state.stakes.get(&key('c')), Some(&100));
+    assert_eq!(chain.get_nonce(&key('a')), 0);
+    assert_eq!(chain.get_tip().timestamp, 1_700_000_000);
+    assert_eq!(chain.get_tip().prev_hash, config().hash());
+    assert_eq!(chain.genesis.as_ref().unwrap().chain_id, "bcai-testnet-1");
+}
+
+#[test]
+fn same_genesis_gives_same_block_and_different_chains_diverge() {
+    let a = Blockchain::with_genesis(config()).unwrap();
+    let b = Blockchain::with_genesis(config()).unwrap();
+    assert_eq!(a.get_tip().hash, b.get_tip().hash);
+
+    let other = GenesisConfig { chain_id: "bcai-testnet-2".into(), ..config() };
+    assert_ne!(Blockchain::with_genesis(other).unwrap().get_tip().hash, a.get_tip().hash);
+}
+
+#[test]
+fn invalid_genesis_is_rejected() {
+    let bad_key = GenesisConfig { initial_balances: BTreeMap::from([("alice".into(), 1)]), ..config() };
+    assert!(matches!(Blockchain::with_genesis(bad_key), Err(BlockchainError::InvalidGenesis(_))));
+
+    let mut low_stake = config();
+    low_stake.validator_selection.min_stake = 1_000;
+    assert!(matches!(Blockchain::with_genesis(low_stake), Err(BlockchainError::InvalidGenesis(_))));
+
+    let overflow = GenesisConfig {
+        initial_balances: BTreeMap::from([(key('a'), u64::MAX), (key('b'), 1)]),
+        ..config()
+    };
+    assert!(overflow.validate().is_err());
+
+    assert!(matches!(
+        Blockchain::from_genesis("/nonexistent/genesis.json"),
+        Err(BlockchainError::InvalidGenesis(_))
+    ));
+}
+
+#[test]
+fn pouw_validator_and_pricing_settings_apply_to_the_chain() {
+    let owner = common::key();
+    let mut genesis = config();
+    genesis.pouw = PoUWConfig { min_computation_ms: 500, ..PoUWConfig::default() };
+    genesis.validator_selection.subset_size = 5;
+    genesis.storage_pricing = StoragePricing { price_per_gb_bcai: 40, default_redundancy: 1 };
+    genesis.initial_balances.insert(pk_hex(&owner), 1_000);
+    let chain = Blockchain::with_genesis(genesis.clone()).unwrap();
+    assert_eq!(chain.state.consensus.pouw, genesis.pouw);
+    assert_eq!(chain.state.consensus.validator_selection.subset_size, 5);
+    assert_eq!(chain.state.consensus.storage_pricing, genesis.storage_pricing);
+
+    // One GiB kept twice at 40 per GiB, or three times on three listed nodes.
+    let gib = 1 << 30;
+    let nodes = vec!["n1".to_string(), "n2".to_string(), "n3".to_string()];
+    let store = |price, replicas: &[String]| {
+        Transaction::new_store_file_signed(&owner, "d".into(), gib, price, replicas.to_vec(), 1, 0)
+    };
+    assert!(validate_transaction_stateful(&store(79, &[]), &chain.state).is_err());
+    assert!(validate_transaction_stateful(&store(80, &[]), &chain.state).is_ok());
+    assert!(validate_transaction_stateful(&store(119, &nodes), &chain.state).is_err());
+    assert!(validate_transaction_stateful(&store(120, &nodes), &chain.state).is_ok());
+}
@@ -0,0 +1,115 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::blockchain::{Block, Blockchain, BlockchainConfig, Transaction};
+use runtime::gossip::{validate, GossipError, GossipTopic};
+use runtime::network::NetworkMessage;
+use runtime::pouw::evaluation::sign_evaluation;
+use runtime::wire::{encode, WireMessage, PROTOCOL_VERSION};
+use schnorrkel::Keypair;
+use serde_json::json;
+
+fn transfer() -> Transaction {
+    let sender = Keypair::generate();
+    let mut tx = Transaction::new(String::new(), "bob".into(), 5, 1, 0);
+    tx.sign(&sender.secret);
+    tx
+}
+
+fn block(transactions: Vec<Transaction>) -> Block {
+    let chain = Blockchain::new(BlockchainConfig::default());
+    let tip = chain.get_tip().clone();
+    Block::new(1, tip.hash, transactions, tip.difficulty, "miner".into(), tip.task, tip.solution)
+}
+
+/// `value` in the JSON envelope `WireMessage` gossips it in.
+fn envelope(kind: &str, value: impl serde::Serialize) -> Vec<u8> {
+    let mut message = serde_json::Map::new();
+    message.insert(kind.to_string(), serde_json::to_value(value).unwrap());
+    serde_json::to_vec(&message).unwrap()
+}
+
+#[test]
+fn topics_have_distinct_names() {
+    for topic in GossipTopic::ALL {
+        assert_eq!(GossipTopic::from_name(topic.name()), Some(topic));
+        assert_ne!(topic.name(), runtime::gossip::LEGACY_TOPIC);
+    }
+    assert_eq!(GossipTopic::from_name("bcai_global"), None);
+}
+
+#[test]
+fn blocks_and_transactions_are_checked_before_propagation() {
+    let tx = transfer();
+    assert_eq!(validate(GossipTopic::Transactions, &envelope("Transaction", &tx)), Ok(()));
+    assert_eq!(GossipTopic::for_transaction(&tx), GossipTopic::Transactions);
+
+    let mut forged = tx.clone();
+    forged.amount = 500;
+    let err = validate(GossipTopic::Transactions, &envelope("Transaction", &forged)).unwrap_err();
+    assert!(matches!(err, GossipError::Invalid { topic: GossipTopic::Transactions, .. }));
+
+    let good = block(vec![tx.clone()]);
+    assert_eq!(validate(GossipTopic::Blocks, &envelope("Block", &good)), Ok(()));
+    let mut tampered = good.clone();
+    tampered.timestamp += 1;
+    assert!(matches!(validate(GossipTopic::Blocks, &envelope("Block", &tampered)), Err(GossipError::Invalid { .. })));
+    let bad_tx = block(vec![forged]);
+    assert!(matches!(validate(GossipTopic::Blocks, &envelope("Block", &bad_tx)), Err(GossipError::Invalid { .. })));
+
+    // Each kind only on its own topic.
+    assert!(matches!(
+        validate(GossipTopic::Transactions, &envelope("Block", &good)),
+        Err(GossipError::WrongTopic { .. })
+    ));
+    assert!(matches!(validate(GossipTopic::Metrics, &envelope("Transaction", &tx)), Err(GossipError::WrongTopic { .. })));
+    assert!(matches!(validate(GossipTopic::Blocks, b"not json"), Err(GossipError::Malformed { .. })));
+}
+
+#[test]
+fn protobuf_envelopes_are_validated_like_json() {
+    let tx = transfer();
+    let message = WireMessage::Block(block(vec![tx.clone()]));
+    let current = encode(PROTOCOL_VERSION, &message).unwrap();
+    assert_eq!(validate(GossipTopic::Blocks, &current), Ok(()));
+    assert!(matches!(validate(GossipTopic::Transactions, &current), Err(GossipError::WrongTopic { .. })));
+
+    let mut forged = tx;
+    forged.amount = 500;
+    let current = encode(PROTOCOL_VERSION, &WireMessage::Transaction(forged)).unwrap();
+    assert!(matches!(validate(GossipTopic::Transactions, &current), Err(GossipError::Invalid { .. })));
+    // Block sync messages are never gossiped.
+    let sync = encode(PROTOCOL_VERSION, &WireMessage::GetBlocks { from_height: 0 }).unwrap();
+    assert!(matches!(validate(GossipTopic::Blocks, &sync), Err(GossipError::Malformed { .. })));
+}
+
+#[test]
+fn evaluations_and_jobs_have_their_own_validators() {
+    let key = SigningKey::generate(&mut OsRng);
+    let evaluation = sign_evaluation("task-1", 93, &key);
+    let message = bincode::serialize(&NetworkMessage::PoUWEvaluation { evaluation: evaluation.clone() }).unwrap();
+    assert_eq!(validate(GossipTopic::Evaluations, &message), Ok(()));
+    assert!(matches!(validate(GossipTopic::Jobs, &message), Err(GossipError::WrongTopic { .. })));
+
+    let mut inflated = evaluation;
+    inflated.accuracy = 100;
+    let message = bincode::serialize(&NetworkMessage::PoUWEvaluation { evaluation: inflated }).unwrap();
+    assert!(matches!(validate(GossipTopic::Evaluations, &message), Err(GossipError::Invalid { .. })));
+
+    let done = NetworkMessage::JobCompleted { job_id: 7, final_model_hash: "abc".into() };
+    assert_eq!(validate(GossipTopic::Jobs, &bincode::serialize(&done).unwrap()), Ok(()));
+
+    // Large bodies are announced on any topic, by digest.
+    let announcement = envelope("Announce", json!({ "hash": "ab".repeat(32), "size": 10 }));
+    assert_eq!(validate(GossipTopic::Jobs, &announcement), Ok(()));
+    let bogus = envelope("Announce", json!({ "hash": "xyz", "size": 10 }));
+    assert!(matches!(validate(GossipTopic::Blocks, &bogus), Err(GossipError::Invalid { .. })));
+}
+
+#[test]
+fn oversized_messages_are_rejected_unread() {
+    let huge = vec![0u8; GossipTopic::Transactions.max_size() + 1];
+    assert_eq!(
+        validate(GossipTopic::Transactions, &huge),
+        Err(GossipError::TooLarge { topic: GossipTopic::Transactions, size: huge.len(), max: 64 * 1024 })
+    );
+}
@@ -0,0 +1,89 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::scheduler::{Heartbeat, HeartbeatConfig, JobScheduler, Reassignment, SchedulerError};
+
+fn config() -> HeartbeatConfig {
+    HeartbeatConfig { interval_secs: 10, max_missed: 3, reputation_penalty: 5 }
+}
+
+fn id(key: &SigningKey) -> String {
+    hex::encode(key.verifying_key().to_bytes())
+}
+
+#[test]
+fn heartbeats_must_be_signed_and_fresh() {
+    let key = SigningKey::generate(&mut OsRng);
+    let mut scheduler = JobScheduler::new(config());
+    scheduler.record_heartbeat(&Heartbeat::sign(&key, 100, vec![]), 0).unwrap();
+    assert!(scheduler.is_online(&id(&key)));
+
+    let mut forged = Heartbeat::sign(&key, 110, vec![]);
+    forged.active_jobs.push(7);
+    assert!(!forged.verify());
+    assert_eq!(scheduler.record_heartbeat(&forged, 10), Err(SchedulerError::InvalidSignature(id(&key))));
+
+    let replayed = Heartbeat::sign(&key, 100, vec![]);
+    assert!(matches!(scheduler.record_heartbeat(&replayed, 10), Err(SchedulerError::Stale { .. })));
+}
+
+#[test]
+fn silent_workers_lose_their_jobs_and_reputation() {
+    let (alive, silent) = (SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng));
+    let mut scheduler = JobScheduler::new(config());
+    scheduler.record_heartbeat(&Heartbeat::sign(&alive, 1, vec![]), 0).unwrap();
+    scheduler.record_heartbeat(&Heartbeat::sign(&silent, 1, vec![]), 0).unwrap();
+    scheduler.assign(1, &id(&silent)).unwrap();
+    scheduler.assign(2, &id(&silent)).unwrap();
+
+    scheduler.record_heartbeat(&Heartbeat::sign(&alive, 2, vec![]), 20).unwrap();
+    // Two missed heartbeats are tolerated.
+    assert!(scheduler.check_liveness(29).is_empty());
+
+    let moved = scheduler.check_liveness(30);
+    assert_eq!(
+        moved,
+        vec![
+            Reassignment { job_id: 1, from: id(&silent), to: Some(id(&alive)) },
+            Reassignment { job_id: 2, from: id(&silent), to: Some(id(&alive)) },
+        ]
+    );
+    assert!(!scheduler.is_online(&id(&silent)));
+    assert_eq!(scheduler.reputation(&id(&silent)), -5);
+    assert_eq!(scheduler.reputation(&id(&alive)), 0);
+    assert_eq!(scheduler.assign(3, &id(&silent)), Err(SchedulerError::Offline(id(&silent))));
+
+    // The outage is only penalized once.
+    assert!(scheduler.check_liveness(60).iter().all(|r| r.from != id(&silent)));
+    assert_eq!(scheduler.reputation(&id(&silent)), -5);
+}
+
+#[test]
+fn orphaned_jobs_wait_for_a_live_worker() {
+    let (first, second) = (SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng));
+    let mut scheduler = JobScheduler::new(config());
+    scheduler.record_heartbeat(&Heartbeat::sign(&first, 1, vec![]), 0).unwrap();
+    assert_eq!(scheduler.submit(9), Some(id(&first)));
+
+    let moved = scheduler.check_liveness(30);
+    assert_eq!(moved, vec![Reassignment { job_id: 9, from: id(&first), to: None }]);
+    assert_eq!(scheduler.pending().collect::<Vec<_>>(), vec![9]);
+
+    scheduler.record_heartbeat(&Heartbeat::sign(&second, 1, vec![]), 40).unwrap();
+    assert!(scheduler.check_liveness(41).is_empty());
+    assert_eq!(scheduler.assigned_worker(9), Some(id(&second).as_str()));
+    assert_eq!(scheduler.pending().count(), 0);
+}
+
+#[test]
+fn jobs_go_to_the_least_loaded_worker() {
+    let keys: Vec<_> = (0..2).map(|_| SigningKey::generate(&mut OsRng)).collect();
+    let mut scheduler = JobScheduler::new(config());
+    for key in &keys {
+        scheduler.record_heartbeat(&Heartbeat::sign(key, 1, vec![]), 0).unwrap();
+    }
+    let first = scheduler.submit(1).unwrap();
+    let second = scheduler.submit(2).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(scheduler.complete(1), Some(first.clone()));
+    assert_eq!(scheduler.submit(3), Some(first));
+}
@@ -0,0 +1,102 @@
+use runtime::job_channel::{Artifact, ChannelError, ChannelKey, ChannelMessage, JobChannel};
+use runtime::wire::proto;
+use schnorrkel::SecretKey;
+
+fn account() -> SecretKey {
+    SecretKey::generate()
+}
+
+/// Channels for `job_id` at the poster's and at the worker's end.
+fn pair(job_id: u64) -> (JobChannel, JobChannel, ChannelKey, ChannelKey) {
+    let (poster, worker) = (ChannelKey::generate(&account()), ChannelKey::generate(&account()));
+    let at_poster = JobChannel::new(job_id, poster.clone(), worker.cert().clone()).unwrap();
+    let at_worker = JobChannel::new(job_id, worker.clone(), poster.cert().clone()).unwrap();
+    (at_poster, at_worker, poster, worker)
+}
+
+#[test]
+fn artifacts_reach_only_the_other_party() {
+    let (mut poster, mut worker, poster_key, _) = pair(7);
+    let credential = Artifact::Credential { name: "s3".into(), secret: "AKIA-secret".into() };
+    let sealed = poster.seal(&credential).unwrap();
+    assert_eq!((sealed.job_id, sealed.seq, sealed.to.as_str()), (7, 0, worker.peer()));
+    assert_ne!(sealed.to, sealed.from);
+    assert!(!sealed.ciphertext.windows(11).any(|w| w == b"AKIA-secret"));
+    assert_eq!(worker.open(&sealed).unwrap(), credential);
+
+    let dataset = Artifact::DatasetUri("s3://private/cifar".into());
+    let reply = worker.seal(&dataset).unwrap();
+    assert_eq!(poster.open(&reply).unwrap(), dataset);
+
+    // A third account addressed in the worker's place still can't open it.
+    let outsider_key = ChannelKey::generate(&account());
+    let mut outsider = JobChannel::new(7, outsider_key.clone(), poster_key.cert().clone()).unwrap();
+    assert_eq!(outsider.open(&sealed), Err(ChannelError::WrongChannel));
+    let mut readdressed = poster.seal(&credential).unwrap();
+    readdressed.to = outsider_key.identity().to_string();
+    assert_eq!(outsider.open(&readdressed), Err(ChannelError::Decrypt));
+}
+
+#[test]
+fn tampered_replayed_and_misdirected_messages_are_refused() {
+    let (mut poster, mut worker, _, _) = pair(7);
+    let first = poster.seal(&Artifact::Blob { name: "weights".into(), data: vec![1, 2, 3] }).unwrap();
+    let second = poster.seal(&Artifact::DatasetUri("ipfs://x".into())).unwrap();
+
+    let mut flipped = first.clone();
+    flipped.ciphertext[0] ^= 1;
+    assert_eq!(worker.open(&flipped), Err(ChannelError::Decrypt));
+    // The sequence number is authenticated, so it can't be bumped past the
+    // replay check either.
+    let mut bumped = first.clone();
+    bumped.seq = 9;
+    assert_eq!(worker.open(&bumped), Err(ChannelError::Decrypt));
+
+    worker.open(&first).unwrap();
+    assert_eq!(worker.open(&first), Err(ChannelError::Replay(0)));
+    worker.open(&second).unwrap();
+    assert_eq!(worker.open(&first), Err(ChannelError::Replay(0)));
+
+    // Another job's channel between the same accounts does not take it.
+    let (mut other_poster, _, _, _) = pair(8);
+    let other = other_poster.seal(&Artifact::DatasetUri("ipfs://y".into())).unwrap();
+    assert_eq!(worker.open(&other), Err(ChannelError::WrongChannel));
+    let mut moved = poster.seal(&Artifact::DatasetUri("ipfs://z".into())).unwrap();
+    moved.job_id = 8;
+    assert_eq!(worker.open(&moved), Err(ChannelError::WrongChannel));
+}
+
+#[test]
+fn channel_keys_must_be_signed_by_their_account() {
+    let (mine, theirs) = (ChannelKey::generate(&account()), ChannelKey::generate(&account()));
+    assert!(theirs.cert().verify());
+
+    // A key claimed for another account is refused.
+    let mut forged = ChannelKey::generate(&account()).cert().clone();
+    forged.identity = theirs.identity().to_string();
+    assert!(!forged.verify());
+    let err = JobChannel::new(1, mine.clone(), forged).unwrap_err();
+    assert_eq!(err, ChannelError::BadCertificate(theirs.identity().to_string()));
+
+    let mut swapped = theirs.cert().clone();
+    swapped.key = ChannelKey::generate(&account()).cert().key;
+    assert!(!swapped.verify());
+    let mut garbage = theirs.cert().clone();
+    garbage.identity = "not hex".into();
+    assert!(!garbage.verify());
+}
+
+#[test]
+fn channel_messages_survive_protobuf() {
+    let (mut poster, mut worker, poster_key, _) = pair(3);
+    let sealed = poster.seal(&Artifact::DatasetUri("s3://bucket/key".into())).unwrap();
+    let decoded: ChannelMessage = proto::ChannelMessage::from(&sealed).try_into().unwrap();
+    assert_eq!(decoded, sealed);
+    assert_eq!(worker.open(&decoded).unwrap(), Artifact::DatasetUri("s3://bucket/key".into()));
+
+    let key = proto::ChannelKey::new(Some(poster_key.cert()));
+    assert_eq!(key.cert().unwrap().as_ref(), Some(poster_key.cert()));
+    let mut short = proto::ChannelMessage::from(&sealed);
+    short.sealed.as_mut().unwrap().nonce.pop();
+    assert!(ChannelMessage::try_from(short).is_err());
+}
@@ -0,0 +1,112 @@
+use runtime::job_manager::{JobManager, JobOperation, Recovery};
+use runtime::journal::Journal;
+use runtime::token::TokenLedger;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn journal_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bcai-journal-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn ledger(poster: &str, amount: u64) -> TokenLedger {
+    let mut ledger = TokenLedger::new();
+    ledger.mint(poster, amount);
+    ledger
+}
+
+fn submit(job_id: u64, reward: u64) -> JobOperation {
+    JobOperation::SubmitJob { job_id, poster: "poster".into(), reward }
+}
+
+#[test]
+fn finished_operations_leave_the_journal_empty() {
+    let path = journal_path("clean");
+    let mut manager = JobManager::new(ledger("poster", 100)).with_journal(Journal::open(&path).unwrap());
+    manager.submit_job("poster", 1, 60).unwrap();
+    manager.complete_job(1, &[("w1".into(), 30), ("w2".into(), 30)]).unwrap();
+
+    assert_eq!(manager.ledger().balance("w2"), 30);
+    assert!(manager.jobs().is_empty());
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn crash_after_escrow_replays_the_job_record() {
+    let path = journal_path("escrowed");
+    let mut ledger = ledger("poster", 100);
+    {
+        let mut journal = Journal::open(&path).unwrap();
+        journal.begin(submit(1, 40)).unwrap();
+        ledger.transfer("poster", &JobManager::escrow_account(1), 40).unwrap();
+        // Crash before the job store was updated.
+    }
+
+    let mut manager = JobManager::restore(ledger, HashMap::new()).with_journal(Journal::open(&path).unwrap());
+    assert_eq!(manager.recover().unwrap(), vec![Recovery::Replayed(submit(1, 40))]);
+    assert_eq!(manager.jobs()[&1], ("poster".to_string(), 40));
+    assert!(Journal::<JobOperation>::open(&path).unwrap().in_flight().is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn job_recorded_without_escrow_is_rolled_back() {
+    let path = journal_path("unescrowed");
+    {
+        let mut journal = Journal::open(&path).unwrap();
+        journal.begin(submit(2, 40)).unwrap();
+    }
+    let jobs = HashMap::from([(2, ("poster".to_string(), 40))]);
+
+    let mut manager = JobManager::restore(ledger("poster", 100), jobs).with_journal(Journal::open(&path).unwrap());
+    assert_eq!(manager.recover().unwrap(), vec![Recovery::RolledBack(submit(2, 40))]);
+    assert!(manager.jobs().is_empty());
+    assert_eq!(manager.ledger().balance("poster"), 100);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn interrupted_payout_resumes_without_paying_twice() {
+    let path = journal_path("payout");
+    let payouts = vec![("w1".to_string(), 20), ("w2".to_string(), 20), ("w3".to_string(), 20)];
+    let mut ledger = ledger("poster", 100);
+    ledger.transfer("poster", &JobManager::escrow_account(3), 70).unwrap();
+    {
+        let mut journal = Journal::open(&path).unwrap();
+        journal.begin(JobOperation::CompleteJob { job_id: 3, reward: 70, payouts: payouts.clone() }).unwrap();
+        ledger.transfer(&JobManager::escrow_account(3), "w1", 20).unwrap();
+    }
+    let jobs = HashMap::from([(3, ("poster".to_string(), 70))]);
+
+    let mut manager = JobManager::restore(ledger, jobs).with_journal(Journal::open(&path).unwrap());
+    assert_eq!(manager.recover().unwrap().len(), 1);
+    for worker in ["w1", "w2", "w3"] {
+        assert_eq!(manager.ledger().balance(worker), 20, "{}", worker);
+    }
+    assert_eq!(manager.ledger().balance(&JobManager::escrow_account(3)), 10);
+    assert!(manager.jobs().is_empty());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn torn_final_record_is_discarded() {
+    let path = journal_path("torn");
+    {
+        let mut journal = Journal::open(&path).unwrap();
+        journal.begin(submit(1, 10)).unwrap();
+    }
+    std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"record\":\"beg").unwrap();
+
+    let mut journal = Journal::<JobOperation>::open(&path).unwrap();
+    assert_eq!(journal.in_flight().len(), 1);
+    let id = journal.begin(submit(2, 10)).unwrap();
+    drop(journal);
+
+    let journal = Journal::<JobOperation>::open(&path).unwrap();
+    let ops: Vec<_> = journal.in_flight().into_iter().map(|e| (e.id, e.op)).collect();
+    assert_eq!(ops, vec![(0, submit(1, 10)), (id, submit(2, 10))]);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,138 @@
+use runtime::blockchain::constants::BLOCK_REWARD;
+use runtime::blockchain::validation::{validate_job_binding, validate_transaction_stateful};
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::job::Job;
+use runtime::pouw::{self, PoUWTask};
+use schnorrkel::SecretKey;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+mod common;
+use common::{key, mine_jobs, pk_hex, MINER};
+
+/// A chain with a poster funded with 1_000.
+fn setup() -> (Blockchain, SecretKey) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let poster = key();
+    chain.state.set_balance(&pk_hex(&poster), 1_000);
+    (chain, poster)
+}
+
+/// Posts `job` with `nonce`, returning it under the id the post derives.
+fn post(chain: &mut Blockchain, poster: &SecretKey, job: Job, nonce: u64) -> Job {
+    let job = job.posted_by(&pk_hex(poster), nonce);
+    let tx = Transaction::new_post_job_signed(poster, job.clone(), 1, nonce);
+    validate_transaction_stateful(&tx, &chain.state).unwrap();
+    chain.state.apply_transaction(&tx).unwrap();
+    job
+}
+
+#[test]
+fn posting_a_job_escrows_its_reward() {
+    let (mut chain, poster) = setup();
+    let job = post(&mut chain, &poster, Job::new(1, "m".into(), "d".into(), 1).with_reward(300), 0);
+
+    assert_eq!(job.id, Job::posted_id(&pk_hex(&poster), 0));
+    assert_eq!(chain.get_balance(&pk_hex(&poster)), 1_000 - 300 - 1);
+    let escrow = &chain.state.job_escrows[&job.id];
+    assert_eq!(escrow.poster, pk_hex(&poster));
+    assert_eq!(escrow.job.reward, 300);
+}
+
+#[test]
+fn invalid_postings_are_rejected() {
+    let (mut chain, poster) = setup();
+    let posted = post(&mut chain, &poster, Job::new(1, "m".into(), "d".into(), 1).with_reward(300), 0);
+    let job = |reward| Job::new(0, "m".into(), "d".into(), 1).with_reward(reward).posted_by(&pk_hex(&poster), 1);
+
+    // The poster picks neither an id of its own nor one already taken.
+    let chosen = Transaction::new_post_job_signed(&poster, Job::new(7, "m".into(), "d".into(), 1).with_reward(5), 1, 1);
+    assert!(validate_transaction_stateful(&chosen, &chain.state).is_err());
+    let duplicate = Transaction::new_post_job_signed(&poster, posted.with_reward(5), 1, 1);
+    assert!(validate_transaction_stateful(&duplicate, &chain.state).is_err());
+
+    let unpaid = Transaction::new_post_job_signed(&poster, job(0), 1, 1);
+    assert!(validate_transaction_stateful(&unpaid, &chain.state).is_err());
+
+    let unaffordable = Transaction::new_post_job_signed(&poster, job(5_000), 1, 1);
+    assert!(validate_transaction_stateful(&unaffordable, &chain.state).is_err());
+
+    let valid = Transaction::new_post_job_signed(&poster, job(5), 1, 1);
+    assert!(validate_transaction_stateful(&valid, &chain.state).is_ok());
+}
+
+#[tokio::test]
+async fn miner_takes_the_best_paying_escrowed_job() {
+    let (mut chain, poster) = setup();
+    let cheap = post(&mut chain, &poster, Job::new(0, "cheap".into(), "d".into(), 1).with_reward(10), 0);
+    let rich = post(&mut chain, &poster, Job::new(0, "rich".into(), "d".into(), 1).with_reward(500), 1);
+    let unposted = Job::new(3, "free".into(), "d".into(), 1);
+    let chain = Arc::new(Mutex::new(chain));
+
+    let queue = VecDeque::from([unposted, cheap.clone(), rich.clone()]);
+    let (block, rest) = mine_jobs(&chain, queue).await;
+
+    assert_eq!(block.task.job_id, Some(rich.id));
+    assert_eq!(block.task.model_id, "rich");
+    assert_eq!(rest.iter().map(|j| j.id).collect::<Vec<_>>(), vec![3, cheap.id]);
+}
+
+#[tokio::test]
+async fn miner_takes_escrowed_jobs_posted_through_other_nodes() {
+    let (mut chain, poster) = setup();
+    let posted = post(&mut chain, &poster, Job::new(0, "posted".into(), "d".into(), 1).with_reward(200), 0);
+    let chain = Arc::new(Mutex::new(chain));
+
+    // The local queue only has unescrowed work; the on-chain escrow pays.
+    let (block, rest) = mine_jobs(&chain, VecDeque::from([Job::new(3, "free".into(), "d".into(), 1)])).await;
+    assert_eq!(block.task.job_id, Some(posted.id));
+    assert_eq!(block.task.model_id, "posted");
+    assert_eq!(rest.iter().map(|j| j.id).collect::<Vec<_>>(), vec![3]);
+    assert!(validate_job_binding(&block.task, &chain.lock().await.state).is_ok());
+}
+
+#[tokio::test]
+async fn unescrowed_jobs_are_mined_as_plain_work() {
+    let (chain, _) = setup();
+    let chain = Arc::new(Mutex::new(chain));
+    let (block, _) = mine_jobs(&chain, VecDeque::from([Job::new(9, "m".into(), "d".into(), 1)])).await;
+    assert_eq!(block.task.model_id, "m");
+    assert_eq!(block.task.job_id, None);
+}
+
+#[tokio::test]
+async fn settlement_splits_the_job_reward_by_score() {
+    let (mut chain, poster) = setup();
+    let job = post(&mut chain, &poster, Job::new(1, "m".into(), "d".into(), 1).with_reward(400), 0);
+    let scoring = chain.state.consensus.scoring.clone();
+    let chain = Arc::new(Mutex::new(chain));
+
+    let (block, _) = mine_jobs(&chain, VecDeque::from([job])).await;
+    let score = pouw::score(block.solution.accuracy, &scoring);
+    let earned = score.reward_share(400);
+    chain.lock().await.add_block(block.clone()).unwrap();
+
+    let chain = chain.lock().await;
+    assert!(chain.state.job_escrows.is_empty());
+    assert_eq!(chain.get_balance(MINER), score.reward_share(BLOCK_REWARD) + earned);
+    assert_eq!(chain.get_balance(&pk_hex(&poster)), 1_000 - 1 - earned);
+    // A fraud proof reverts the job share along with the subsidy.
+    assert_eq!(chain.state.pouw_claims[&block.index].reward, chain.get_balance(MINER));
+}
+
+#[test]
+fn tasks_must_train_the_escrowed_job() {
+    let (mut chain, poster) = setup();
+    let job = post(&mut chain, &poster, Job::new(1, "m".into(), "d".into(), 1).with_reward(100), 0);
+
+    assert!(validate_job_binding(&job.to_task(None), &chain.state).is_ok());
+    assert!(validate_job_binding(&PoUWTask::new("m".into(), "d".into(), 1), &chain.state).is_ok());
+
+    let mut other_model = job.to_task(None);
+    other_model.model_id = "other".into();
+    assert!(validate_job_binding(&other_model, &chain.state).is_err());
+
+    let unposted = Job::new(2, "m".into(), "d".into(), 1).to_task(None);
+    assert!(validate_job_binding(&unposted, &chain.state).is_err());
+}
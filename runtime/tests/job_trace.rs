@@ -0,0 +1,134 @@
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::job::Job;
+use runtime::miner;
+use runtime::trace::{TraceContext, SPAN_NAME};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+mod common;
+use common::pad_computation_time;
+
+type Spans = Arc<StdMutex<Vec<HashMap<String, String>>>>;
+
+/// Records the fields of every lifecycle span.
+struct Recorder(Spans);
+
+#[derive(Default)]
+struct Fields(HashMap<String, String>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Recorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        if attrs.metadata().name() == SPAN_NAME {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+}
+
+#[test]
+fn traceparent_round_trips() {
+    let root = TraceContext::new_root();
+    assert_eq!(root.trace_id.len(), 32);
+    assert_eq!(root.span_id.len(), 16);
+
+    let parsed = TraceContext::from_traceparent(&root.traceparent()).unwrap();
+    assert_eq!(parsed.trace_id, root.trace_id);
+    assert_eq!(parsed.span_id, root.span_id);
+
+    let child = TraceContext::continue_from(Some(&root.traceparent())).unwrap();
+    assert_eq!(child.trace_id, root.trace_id);
+    assert_eq!(child.parent_span_id.as_deref(), Some(root.span_id.as_str()));
+    assert_ne!(child.span_id, root.span_id);
+}
+
+#[test]
+fn malformed_traceparent_is_rejected() {
+    let zero_trace = format!("00-{}-{}-01", "0".repeat(32), "1".repeat(16));
+    let upper = format!("00-{}-{}-01", "A".repeat(32), "1".repeat(16));
+    for value in ["", "00-abc-def-01", "01-x-y-z", zero_trace.as_str(), upper.as_str()] {
+        assert!(TraceContext::from_traceparent(value).is_none(), "{}", value);
+    }
+
+    let job = Job::new(1, "m".into(), "d".into(), 1);
+    let trace = job.trace.clone();
+    assert_eq!(job.with_traceparent("garbage").trace, trace);
+}
+
+#[test]
+fn job_posted_elsewhere_joins_its_trace() {
+    let posted = TraceContext::new_root();
+    let job = Job::new(7, "m".into(), "d".into(), 1).with_traceparent(&posted.traceparent());
+    let trace = job.trace.unwrap();
+    assert_eq!(trace.trace_id, posted.trace_id);
+    assert_eq!(trace.parent_span_id, Some(posted.span_id));
+}
+
+#[tokio::test]
+async fn one_trace_covers_mining_evaluation_and_settlement() {
+    let spans: Spans = Arc::default();
+    let subscriber = tracing_subscriber::registry().with(Recorder(spans.clone()));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let chain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let job = Job::new(1, "model".into(), "dataset".into(), 1);
+    let posted = job.trace.clone().unwrap();
+    let queue = Arc::new(Mutex::new(VecDeque::from([job])));
+
+    let mut block = miner::mine_block("miner".into(), chain.clone(), Arc::new(Mutex::new(HashSet::new())), queue)
+        .await
+        .unwrap();
+    let carried = TraceContext::from_traceparent(block.task.trace.as_deref().unwrap()).unwrap();
+    assert_eq!(carried.trace_id, posted.trace_id);
+    pad_computation_time(&mut block);
+    chain.lock().await.add_block(block).unwrap();
+
+    let spans = spans.lock().unwrap();
+    let by_stage: HashMap<&str, &HashMap<String, String>> =
+        spans.iter().map(|s| (s["stage"].as_str(), s)).collect();
+    for stage in ["mine", "evaluate", "settle"] {
+        assert_eq!(by_stage[stage]["trace_id"], posted.trace_id, "{}", stage);
+    }
+    // The worker span hangs off the poster; evaluation and settlement hang
+    // off the worker span carried on chain.
+    assert_eq!(by_stage["mine"]["parent_span_id"], posted.span_id);
+    assert_eq!(by_stage["mine"]["span_id"], carried.span_id);
+    assert_eq!(by_stage["evaluate"]["parent_span_id"], carried.span_id);
+    assert_eq!(by_stage["settle"]["parent_span_id"], carried.span_id);
+}
+
+#[tokio::test]
+async fn untraced_blocks_emit_no_lifecycle_spans() {
+    let spans: Spans = Arc::default();
+    let subscriber = tracing_subscriber::registry().with(Recorder(spans.clone()));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let chain = Arc::new(Mutex::new(Blockchain::new(BlockchainConfig::default())));
+    let mut block = miner::mine_block(
+        "miner".into(),
+        chain.clone(),
+        Arc::new(Mutex::new(HashSet::new())),
+        Arc::new(Mutex::new(VecDeque::new())),
+    )
+    .await
+    .unwrap();
+    assert!(block.task.trace.is_none());
+    pad_computation_time(&mut block);
+    chain.lock().await.add_block(block).unwrap();
+    assert!(spans.lock().unwrap().is_empty());
+}
@@ -0,0 +1,112 @@
+use runtime::blockchain::validation::{validate_progress_per_block, validate_transaction_stateful};
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::pouw::progress::advance;
+use runtime::pouw::training::{self, Weights};
+use runtime::pouw::{generate_task, LongTask, ProgressError, TaskProgress};
+use schnorrkel::{Keypair, SecretKey};
+
+fn long_task(epochs: u32, max_epochs_per_block: u32) -> LongTask {
+    let mut task = generate_task(1, 7);
+    task.epochs = epochs;
+    LongTask { task, max_epochs_per_block }
+}
+
+fn funded_key(chain: &mut Blockchain) -> SecretKey {
+    let key = Keypair::generate().secret.clone();
+    chain.state.set_balance(&hex::encode(key.to_public().to_bytes()), 100);
+    key
+}
+
+/// A chain with `task` registered and a funded solver key.
+fn setup(task: &LongTask) -> (Blockchain, SecretKey) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let solver = funded_key(&mut chain);
+    let tx = Transaction::new_long_task_signed(&solver, task.clone(), 1, 0);
+    validate_transaction_stateful(&tx, &chain.state).unwrap();
+    chain.state.apply_transaction(&tx).unwrap();
+    (chain, solver)
+}
+
+#[test]
+fn deltas_advance_the_checkpoint_until_complete() {
+    let task = long_task(5, 2);
+    let (mut chain, _) = setup(&task);
+    let mut weights: Weights = [0; training::FEATURES];
+    let mut deltas = 0;
+    while !chain.state.long_tasks[&task.id()].is_complete() {
+        let (delta, next) = advance(&chain.state.long_tasks[&task.id()], &weights);
+        let tx = Transaction::new_progress_signed(&funded_key(&mut chain), delta, 1, 0);
+        validate_transaction_stateful(&tx, &chain.state).unwrap();
+        chain.state.apply_transaction(&tx).unwrap();
+        weights = next;
+        deltas += 1;
+    }
+
+    let progress = &chain.state.long_tasks[&task.id()];
+    assert_eq!(progress.epochs_done, 5);
+    assert_eq!(deltas, 3, "5 epochs at 2 per block take 3 deltas");
+    let expected = training::train_epochs(&training::Dataset::for_task(&task.task), [0; training::FEATURES], 5);
+    assert_eq!(progress.checkpoint_hash, hex::encode(training::hash_weights(&expected)));
+}
+
+#[test]
+fn deltas_that_do_not_continue_the_checkpoint_are_rejected() {
+    let task = long_task(6, 2);
+    let progress = TaskProgress::new(task.clone());
+    let zero = [0; training::FEATURES];
+    let (delta, _) = advance(&progress, &zero);
+    progress.verify(&delta).unwrap();
+
+    let mut wrong_start = delta.clone();
+    wrong_start.start_weights = training::encode_weights(&[1, 0]);
+    assert_eq!(progress.verify(&wrong_start), Err(ProgressError::StartMismatch));
+
+    let mut wrong_hash = delta.clone();
+    wrong_hash.checkpoint_hash = hex::encode([0u8; 32]);
+    assert_eq!(progress.verify(&wrong_hash), Err(ProgressError::CheckpointMismatch));
+
+    let mut too_long = delta.clone();
+    too_long.to_epoch = 3;
+    assert!(matches!(progress.verify(&too_long), Err(ProgressError::BadRange { .. })));
+
+    let mut skipped = delta;
+    skipped.from_epoch = 2;
+    skipped.to_epoch = 4;
+    assert_eq!(progress.verify(&skipped), Err(ProgressError::WrongStart { expected: 0, got: 2 }));
+}
+
+#[test]
+fn registration_and_progress_are_checked_against_state() {
+    let task = long_task(4, 2);
+    let (chain, solver) = setup(&task);
+
+    let nonce = chain.state.get_nonce(&hex::encode(solver.to_public().to_bytes()));
+
+    let again = Transaction::new_long_task_signed(&solver, task.clone(), 1, nonce);
+    assert!(validate_transaction_stateful(&again, &chain.state).is_err());
+    let idle = Transaction::new_long_task_signed(&solver, long_task(4, 0), 1, nonce);
+    assert!(validate_transaction_stateful(&idle, &chain.state).is_err());
+    let fresh = Transaction::new_long_task_signed(&solver, long_task(3, 1), 1, nonce);
+    validate_transaction_stateful(&fresh, &chain.state).unwrap();
+
+    let (delta, _) = advance(&TaskProgress::new(long_task(3, 1)), &[0; training::FEATURES]);
+    let unknown = Transaction::new_progress_signed(&solver, delta, 1, nonce);
+    assert!(validate_transaction_stateful(&unknown, &chain.state).is_err());
+}
+
+#[test]
+fn a_block_advances_each_task_at_most_once() {
+    let task = long_task(4, 2);
+    let (chain, solver) = setup(&task);
+    let progress = chain.state.long_tasks[&task.id()].clone();
+    let (first, weights) = advance(&progress, &[0; training::FEATURES]);
+    let mut after = progress.clone();
+    after.apply(&first);
+    let (second, _) = advance(&after, &weights);
+    let txs = vec![
+        Transaction::new_progress_signed(&solver, first, 1, 1),
+        Transaction::new_progress_signed(&solver, second, 1, 2),
+    ];
+    assert!(validate_progress_per_block(&txs).is_err());
+    assert!(validate_progress_per_block(&txs[..1]).is_ok());
+}
@@ -0,0 +1,165 @@
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::job::{Job, Milestone};
+use runtime::pouw::{score, ScoringConfig};
+use schnorrkel::SecretKey;
+
+mod common;
+use common::{key, pk_hex};
+
+fn milestone(description: &str, reward: u64) -> Milestone {
+    Milestone { description: description.into(), reward }
+}
+
+/// A month-long job of 900, a third of it paid per milestone, as `poster`
+/// posts it with its first nonce.
+fn job(poster: &SecretKey, evaluator: &SecretKey) -> Job {
+    Job::new(0, "llm".into(), "corpus".into(), 1)
+        .with_reward(900)
+        .with_milestones(
+            vec![milestone("week 1 checkpoint", 300), milestone("week 2 checkpoint", 300)],
+            &pk_hex(evaluator),
+        )
+        .posted_by(&pk_hex(poster), 0)
+}
+
+/// A chain with a funded poster and evaluator.
+fn setup() -> (Blockchain, SecretKey, SecretKey) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let poster = key();
+    let evaluator = key();
+    chain.state.set_balance(&pk_hex(&poster), 1_000);
+    chain.state.set_balance(&pk_hex(&evaluator), 10);
+    (chain, poster, evaluator)
+}
+
+fn apply(chain: &mut Blockchain, tx: &Transaction) {
+    validate_transaction_stateful(tx, &chain.state).unwrap();
+    chain.state.apply_transaction(tx).unwrap();
+}
+
+#[test]
+fn signed_off_milestones_are_paid_from_escrow() {
+    let (mut chain, poster, evaluator) = setup();
+    let id = job(&poster, &evaluator).id;
+    apply(&mut chain, &Transaction::new_post_job_signed(&poster, job(&poster, &evaluator), 1, 0));
+    assert_eq!(chain.state.job_escrows[&id].remaining(), 900);
+
+    let sign_off = |milestone, nonce| {
+        Transaction::new_milestone_sign_off_signed(&evaluator, id, milestone, "worker".into(), 1, nonce)
+    };
+    apply(&mut chain, &sign_off(1, 0));
+    assert_eq!(chain.get_balance("worker"), 300);
+    assert_eq!(chain.get_balance(&pk_hex(&evaluator)), 9);
+    assert_eq!(chain.state.job_escrows[&id].remaining(), 600);
+
+    // Each milestone is paid once, and only for milestones of the job.
+    assert!(validate_transaction_stateful(&sign_off(1, 1), &chain.state).is_err());
+    assert!(validate_transaction_stateful(&sign_off(2, 1), &chain.state).is_err());
+    let by_poster = Transaction::new_milestone_sign_off_signed(&poster, id, 0, "worker".into(), 1, 1);
+    assert!(validate_transaction_stateful(&by_poster, &chain.state).is_err());
+
+    // Mining releases only the share not set aside for milestones, and the
+    // milestone left stays escrowed for its sign-off.
+    let full = score(10_000, &ScoringConfig::default());
+    assert_eq!(chain.state.settle_job(id, "miner", &full), Some(300));
+    assert_eq!(chain.state.settle_job(id, "miner", &full), None);
+    assert_eq!(chain.get_balance("miner"), 300);
+    assert_eq!(chain.state.job_escrows[&id].remaining(), 300);
+    apply(&mut chain, &sign_off(0, 1));
+    assert_eq!(chain.get_balance("worker"), 600);
+    assert!(chain.state.job_escrows.is_empty());
+    assert_eq!(chain.get_balance(&pk_hex(&poster)), 1_000 - 900 - 1);
+}
+
+#[test]
+fn mining_before_sign_off_leaves_milestones_escrowed() {
+    let (mut chain, poster, evaluator) = setup();
+    let id = job(&poster, &evaluator).id;
+    apply(&mut chain, &Transaction::new_post_job_signed(&poster, job(&poster, &evaluator), 1, 0));
+
+    // A half-score miner earns half the mining share; the poster gets the
+    // other half back, but none of the milestone rewards.
+    let half = score(5_000, &ScoringConfig::default());
+    let earned = chain.state.settle_job(id, "miner", &half).unwrap();
+    assert!(earned < 300);
+    assert_eq!(chain.get_balance(&pk_hex(&poster)), 1_000 - 900 - 1 + (300 - earned));
+    assert_eq!(chain.state.job_escrows[&id].remaining(), 600);
+
+    for (milestone, nonce) in [(0, 0), (1, 1)] {
+        let tx = Transaction::new_milestone_sign_off_signed(&evaluator, id, milestone, "worker".into(), 1, nonce);
+        apply(&mut chain, &tx);
+    }
+    assert_eq!(chain.get_balance("worker"), 600);
+    assert!(chain.state.job_escrows.is_empty());
+}
+
+#[test]
+fn invalid_milestone_plans_are_rejected() {
+    let (chain, poster, evaluator) = setup();
+    let post = |job: Job| Transaction::new_post_job_signed(&poster, job, 1, 0);
+    let base = || Job::new(0, "llm".into(), "corpus".into(), 1).with_reward(500).posted_by(&pk_hex(&poster), 0);
+
+    let over = base().with_milestones(vec![milestone("a", 300), milestone("b", 300)], &pk_hex(&evaluator));
+    assert!(validate_transaction_stateful(&post(over), &chain.state).is_err());
+    let free = base().with_milestones(vec![milestone("a", 0)], &pk_hex(&evaluator));
+    assert!(validate_transaction_stateful(&post(free), &chain.state).is_err());
+    let mut unsigned = base().with_milestones(vec![milestone("a", 100)], &pk_hex(&evaluator));
+    unsigned.milestone_evaluator = None;
+    assert!(validate_transaction_stateful(&post(unsigned), &chain.state).is_err());
+
+    let unposted = Transaction::new_milestone_sign_off_signed(&evaluator, 7, 0, "worker".into(), 1, 0);
+    assert!(validate_transaction_stateful(&unposted, &chain.state).is_err());
+}
+
+#[test]
+fn untouched_jobs_can_be_cancelled_by_their_poster() {
+    let (mut chain, poster, evaluator) = setup();
+    let id = job(&poster, &evaluator).id;
+    apply(&mut chain, &Transaction::new_post_job_signed(&poster, job(&poster, &evaluator), 1, 0));
+
+    let by_evaluator = Transaction::new_cancel_job_signed(&evaluator, id, 1, 0);
+    assert!(validate_transaction_stateful(&by_evaluator, &chain.state).is_err());
+    let unposted = Transaction::new_cancel_job_signed(&poster, 7, 1, 1);
+    assert!(validate_transaction_stateful(&unposted, &chain.state).is_err());
+
+    apply(&mut chain, &Transaction::new_cancel_job_signed(&poster, id, 1, 1));
+    assert!(chain.state.job_escrows.is_empty());
+    assert_eq!(chain.get_balance(&pk_hex(&poster)), 1_000 - 1 - 1);
+}
+
+#[test]
+fn paid_jobs_cannot_be_cancelled() {
+    let (mut chain, poster, evaluator) = setup();
+    let id = job(&poster, &evaluator).id;
+    apply(&mut chain, &Transaction::new_post_job_signed(&poster, job(&poster, &evaluator), 1, 0));
+    apply(&mut chain, &Transaction::new_milestone_sign_off_signed(&evaluator, id, 0, "worker".into(), 1, 0));
+    assert!(validate_transaction_stateful(&Transaction::new_cancel_job_signed(&poster, id, 1, 1), &chain.state).is_err());
+
+    let (mut chain, poster, evaluator) = setup();
+    let id = job(&poster, &evaluator).id;
+    apply(&mut chain, &Transaction::new_post_job_signed(&poster, job(&poster, &evaluator), 1, 0));
+    chain.state.settle_job(id, "miner", &score(10_000, &ScoringConfig::default()));
+    assert!(validate_transaction_stateful(&Transaction::new_cancel_job_signed(&poster, id, 1, 1), &chain.state).is_err());
+}
+
+#[test]
+fn timed_out_jobs_refund_their_unsigned_milestones() {
+    let (mut chain, poster, evaluator) = setup();
+    let posted = job(&poster, &evaluator).with_timeout(50);
+    let id = posted.id;
+    apply(&mut chain, &Transaction::new_post_job_signed(&poster, posted, 1, 0));
+
+    // The block posting the job starts its timeout.
+    assert!(chain.state.advance_job_escrows(10).is_empty());
+    assert_eq!(chain.state.job_escrows[&id].expires_at, Some(60));
+
+    // Mined and half signed off, the job still times out with a milestone
+    // unsigned, which goes back to the poster.
+    let earned = chain.state.settle_job(id, "miner", &score(10_000, &ScoringConfig::default())).unwrap();
+    apply(&mut chain, &Transaction::new_milestone_sign_off_signed(&evaluator, id, 0, "worker".into(), 1, 0));
+    assert!(chain.state.advance_job_escrows(59).is_empty());
+    assert_eq!(chain.state.advance_job_escrows(60), vec![id]);
+    assert!(chain.state.job_escrows.is_empty());
+    assert_eq!(chain.get_balance(&pk_hex(&poster)), 1_000 - 1 - earned - 300);
+}
@@ -0,0 +1,85 @@
+use runtime::blockchain::state::State;
+use runtime::blockchain::transaction::{MultisigAccount, Transaction};
+use runtime::blockchain::validation::{validate_transaction_stateful, validate_transaction_stateless};
+
+mod common;
+use common::{key, pk_hex};
+
+#[test]
+fn two_of_three_spend() {
+    let creator = key();
+    let signers = [key(), key(), key()];
+    let mut state = State::new();
+    state.set_balance(&pk_hex(&creator), 100);
+
+    let register = Transaction::new_register_multisig_signed(
+        &creator,
+        signers.iter().map(pk_hex).collect(),
+        2,
+        1,
+        0,
+    );
+    validate_transaction_stateless(&register).unwrap();
+    validate_transaction_stateful(&register, &state).unwrap();
+    state.apply_transaction(&register).unwrap();
+
+    let address = register.to.clone();
+    assert_eq!(
+        address,
+        MultisigAccount::new(signers.iter().rev().map(pk_hex).collect(), 2).address()
+    );
+    assert!(state.multisig_accounts.contains_key(&address));
+    state.set_balance(&address, 50);
+
+    let mut spend = Transaction::new_multisig_transfer(address.clone(), "bob".into(), 20, 1, 0);
+    spend.add_multisig_signature(&signers[0]);
+    validate_transaction_stateless(&spend).unwrap();
+    assert!(validate_transaction_stateful(&spend, &state).is_err());
+
+    // A duplicate approval from the same signer does not count twice.
+    spend.add_multisig_signature(&signers[0]);
+    assert!(validate_transaction_stateful(&spend, &state).is_err());
+    spend.multisig_signatures.pop();
+
+    spend.add_multisig_signature(&signers[2]);
+    let txid = spend.hash();
+    validate_transaction_stateful(&spend, &state).unwrap();
+    // Co-signatures do not change the id, and an outsider's is refused.
+    let mut padded = spend.clone();
+    padded.add_multisig_signature(&signers[1]);
+    assert_eq!(padded.hash(), txid);
+    padded.multisig_signatures.swap(0, 2);
+    assert_eq!(padded.hash(), txid);
+    padded.add_multisig_signature(&key());
+    assert!(validate_transaction_stateful(&padded, &state).is_err());
+
+    state.apply_transaction(&spend).unwrap();
+    assert_eq!(state.get_balance(&address), 29);
+    assert_eq!(state.get_balance("bob"), 20);
+}
+
+#[test]
+fn outsider_signatures_and_bad_thresholds_rejected() {
+    let creator = key();
+    let signers = [key(), key()];
+    let members: Vec<String> = signers.iter().map(pk_hex).collect();
+
+    let bad = Transaction::new_register_multisig_signed(&creator, members.clone(), 3, 0, 0);
+    assert!(validate_transaction_stateless(&bad).is_err());
+
+    let mut state = State::new();
+    let register = Transaction::new_register_multisig_signed(&creator, members, 1, 0, 0);
+    state.apply_transaction(&register).unwrap();
+    state.set_balance(&register.to, 10);
+
+    let mut spend = Transaction::new_multisig_transfer(register.to.clone(), "bob".into(), 5, 0, 0);
+    spend.add_multisig_signature(&key());
+    validate_transaction_stateless(&spend).unwrap();
+    assert!(validate_transaction_stateful(&spend, &state).is_err());
+
+    // Tampering with the amount after signing invalidates the approvals.
+    let mut spend = Transaction::new_multisig_transfer(register.to.clone(), "bob".into(), 5, 0, 0);
+    spend.add_multisig_signature(&signers[1]);
+    spend.amount = 9;
+    assert!(validate_transaction_stateless(&spend).is_err());
+}
@@ -0,0 +1,115 @@
+use libp2p::Multiaddr;
+use runtime::net_address::{
+    default_listen_addrs, default_quic_listen_addrs, dial_target, order_for_dial, parse_addrs,
+    probe, AddrScope, AddrSource, AddressError, AddressPreference, AdvertisedAddrs,
+    TransportPreference,
+};
+use std::time::Duration;
+
+fn addr(s: &str) -> Multiaddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn scopes_follow_the_address_ranges() {
+    let scope = |ip: &str| AddrScope::of(&ip.parse().unwrap());
+    assert_eq!(scope("0.0.0.0"), AddrScope::Unspecified);
+    assert_eq!(scope("::"), AddrScope::Unspecified);
+    assert_eq!(scope("::1"), AddrScope::Loopback);
+    assert_eq!(scope("fe80::1"), AddrScope::LinkLocal);
+    assert_eq!(scope("169.254.3.4"), AddrScope::LinkLocal);
+    assert_eq!(scope("fd12:3456::1"), AddrScope::Private);
+    assert_eq!(scope("100.72.0.1"), AddrScope::Private);
+    assert_eq!(scope("10.1.2.3"), AddrScope::Private);
+    assert_eq!(scope("2001:db8::7"), AddrScope::Public);
+    assert_eq!(scope("203.0.113.9"), AddrScope::Public);
+}
+
+#[test]
+fn listening_covers_both_families() {
+    assert_eq!(default_listen_addrs(4001), vec![addr("/ip4/0.0.0.0/tcp/4001"), addr("/ip6/::/tcp/4001")]);
+    assert_eq!(dial_target(&addr("/ip6/2001:db8::7/tcp/9")).as_deref(), Some("[2001:db8::7]:9"));
+    assert_eq!(dial_target(&addr("/dns6/node.example/tcp/9")).as_deref(), Some("node.example:9"));
+    assert!(matches!(parse_addrs(&["/ip6/nope".to_string()]), Err(AddressError::Invalid(..))));
+}
+
+#[test]
+fn quic_is_dialed_before_tcp() {
+    assert_eq!(
+        default_quic_listen_addrs(4001),
+        vec![addr("/ip4/0.0.0.0/udp/4001/quic-v1"), addr("/ip6/::/udp/4001/quic-v1")]
+    );
+    let tcp = addr("/ip6/2001:db8::7/tcp/4001");
+    let quic = addr("/ip6/2001:db8::7/udp/4001/quic-v1");
+    let both = || vec![tcp.clone(), quic.clone()];
+    assert_eq!(order_for_dial(both(), TransportPreference::QuicFirst), vec![quic.clone(), tcp.clone()]);
+    assert_eq!(order_for_dial(both(), TransportPreference::TcpFirst), vec![tcp.clone(), quic.clone()]);
+    assert_eq!(order_for_dial(both(), TransportPreference::QuicOnly), vec![quic.clone()]);
+    assert_eq!(order_for_dial(both(), TransportPreference::TcpOnly), vec![tcp.clone()]);
+    // QUIC addresses have no TCP port to probe.
+    assert_eq!(dial_target(&quic), None);
+}
+
+fn multi_homed(preference: AddressPreference) -> AdvertisedAddrs {
+    let mut addrs = AdvertisedAddrs::new(preference, 8, 2);
+    for listen in ["/ip4/10.0.0.5/tcp/4001", "/ip4/203.0.113.9/tcp/4001", "/ip6/2001:db8::7/tcp/4001"] {
+        assert!(addrs.add(addr(listen), AddrSource::Listen));
+    }
+    // Never advertised.
+    for local in ["/ip4/127.0.0.1/tcp/4001", "/ip6/fe80::1/tcp/4001", "/ip6/::/tcp/4001"] {
+        assert!(!addrs.add(addr(local), AddrSource::Listen));
+    }
+    addrs
+}
+
+#[test]
+fn public_addresses_of_the_preferred_family_go_first() {
+    let addrs = multi_homed(AddressPreference::Ipv6First);
+    assert_eq!(
+        addrs.advertised(),
+        vec![addr("/ip6/2001:db8::7/tcp/4001"), addr("/ip4/203.0.113.9/tcp/4001"), addr("/ip4/10.0.0.5/tcp/4001")]
+    );
+
+    let mut v4 = multi_homed(AddressPreference::Ipv4First);
+    assert!(v4.add(addr("/ip4/198.51.100.2/tcp/4001"), AddrSource::External));
+    assert_eq!(
+        v4.advertised(),
+        vec![
+            addr("/ip4/198.51.100.2/tcp/4001"),
+            addr("/ip4/203.0.113.9/tcp/4001"),
+            addr("/ip6/2001:db8::7/tcp/4001"),
+            addr("/ip4/10.0.0.5/tcp/4001"),
+        ]
+    );
+
+    let only = multi_homed(AddressPreference::Ipv6Only);
+    assert_eq!(only.advertised(), vec![addr("/ip6/2001:db8::7/tcp/4001")]);
+}
+
+#[test]
+fn unreachable_addresses_stop_being_advertised() {
+    let mut addrs = multi_homed(AddressPreference::Ipv6First);
+    let v6 = addr("/ip6/2001:db8::7/tcp/4001");
+    addrs.record_check(&v6, false);
+    assert!(addrs.is_healthy(&v6));
+    addrs.record_check(&v6, false);
+    assert!(!addrs.is_healthy(&v6));
+    assert!(!addrs.advertised().contains(&v6));
+
+    addrs.record_check(&v6, true);
+    assert_eq!(addrs.advertised()[0], v6);
+
+    addrs.remove(&v6);
+    assert_eq!(addrs.candidates().count(), 2);
+}
+
+#[tokio::test]
+async fn probes_open_a_connection() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let timeout = Duration::from_secs(2);
+    assert!(probe(&addr(&format!("/ip4/127.0.0.1/tcp/{}", port)), timeout).await);
+    drop(listener);
+    assert!(!probe(&addr(&format!("/ip4/127.0.0.1/tcp/{}", port)), timeout).await);
+    assert!(!probe(&addr("/ip4/127.0.0.1/udp/9"), timeout).await);
+}
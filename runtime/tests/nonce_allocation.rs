@@ -0,0 +1,97 @@
+use runtime::blockchain::{Blockchain, BlockchainConfig, NonceAllocator, Transaction};
+use runtime::job::Job;
+use runtime::miner;
+use schnorrkel::SecretKey;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[test]
+fn nonces_are_handed_out_once_and_gaps_refilled() {
+    let mut nonces = NonceAllocator::new(5).with_lease(10);
+    assert_eq!((nonces.allocate(0), nonces.allocate(0), nonces.allocate(1)), (5, 6, 7));
+    assert_eq!(nonces.in_flight(), vec![5, 6, 7]);
+
+    // 7 arrives first: 5 and 6 hold it back.
+    nonces.submit(7);
+    assert_eq!(nonces.gaps(), vec![5, 6]);
+    // 6 is given up and handed out again before anything new.
+    assert!(nonces.release(6));
+    assert!(!nonces.release(6));
+    assert_eq!(nonces.allocate(2), 6);
+    // 5's client never comes back; its lease runs out.
+    assert_eq!(nonces.peek(), 8);
+    assert_eq!(nonces.allocate(10), 5);
+    assert_eq!(nonces.expire(12), vec![6]);
+    assert_eq!(nonces.peek(), 6);
+
+    // A nonce picked without the allocator frees the ones it skips.
+    nonces.submit(10);
+    assert_eq!(nonces.allocate(12), 6);
+    assert_eq!(nonces.allocate(12), 8);
+    assert_eq!(nonces.allocate(12), 9);
+    assert_eq!(nonces.allocate(12), 11);
+
+    // Once the chain moves past them, nonces are forgotten.
+    nonces.sync(8);
+    assert_eq!(nonces.base(), 8);
+    assert_eq!(nonces.in_flight(), vec![8, 9, 10, 11]);
+    nonces.sync(3);
+    assert_eq!(nonces.base(), 8);
+    nonces.sync(12);
+    assert!(nonces.is_idle());
+    assert_eq!(nonces.allocate(20), 12);
+}
+
+#[test]
+fn the_mempool_takes_an_accounts_transactions_in_sequence() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let sender = SecretKey::generate();
+    let from = hex::encode(sender.to_public().to_bytes());
+    chain.state.set_balance(&from, 1_000);
+    let to = SecretKey::generate().to_public();
+    let transfer = |nonce| Transaction::new_transfer(&sender, to, 10, 1, nonce);
+
+    chain.add_transaction(transfer(0)).unwrap();
+    assert_eq!(chain.pending_nonce(&from), 1);
+    // Neither the nonce already pending nor one past the next is taken.
+    assert!(chain.add_transaction(transfer(0)).is_err());
+    assert!(chain.add_transaction(transfer(2)).is_err());
+    chain.add_transaction(transfer(1)).unwrap();
+    assert_eq!(chain.pending_nonce(&from), 2);
+    assert_eq!(chain.get_nonce(&from), 0);
+
+    // Applied in a block, the account's nonce moves past them.
+    let mut state = chain.state.clone();
+    for tx in &chain.pending_transactions {
+        state.apply_transaction(tx).unwrap();
+    }
+    assert_eq!(state.get_nonce(&from), 2);
+}
+
+#[tokio::test]
+async fn mined_blocks_clear_the_pending_transactions_they_used_up() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let sender = SecretKey::generate();
+    let from = hex::encode(sender.to_public().to_bytes());
+    chain.state.set_balance(&from, 1_000);
+    let to = SecretKey::generate().to_public();
+    chain.add_transaction(Transaction::new_transfer(&sender, to, 10, 1, 0)).unwrap();
+    chain.add_transaction(Transaction::new_transfer(&sender, to, 10, 1, 1)).unwrap();
+    let chain = Arc::new(Mutex::new(chain));
+
+    // A block uses up nonce 0 with another transaction: the pending one
+    // with nonce 0 can never be mined and goes, the one after it stays.
+    let replacement = Transaction::new_transfer(&sender, to, 20, 1, 0);
+    let mempool = Arc::new(Mutex::new(HashSet::from([replacement.clone()])));
+    let queue = Arc::new(Mutex::new(VecDeque::from([Job::new(1, "m".into(), "d".into(), 1)])));
+    let mut block = miner::mine_block("miner".into(), chain.clone(), mempool, queue).await.unwrap();
+    block.solution.computation_time_ms = block.solution.computation_time_ms.max(100);
+    block.hash = block.calculate_hash();
+    assert_eq!(block.transactions, vec![replacement]);
+
+    let mut chain = chain.lock().await;
+    chain.add_block(block).unwrap();
+    assert_eq!(chain.pending_transactions.iter().map(|tx| tx.nonce).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(chain.pending_nonce(&from), 2);
+}
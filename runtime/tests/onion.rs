@@ -0,0 +1,118 @@
+use runtime::onion::{
+    choose_route, seal, wrap, BidBook, BidMessage, Next, OnionError, OnionKey, RelayInfo, ReplyBlock, Sealed,
+};
+
+fn relays(n: usize) -> (Vec<RelayInfo>, Vec<OnionKey>) {
+    let keys: Vec<OnionKey> = (0..n).map(|_| OnionKey::generate()).collect();
+    let infos = keys.iter().enumerate().map(|(i, k)| RelayInfo { peer: format!("relay-{}", i), key: k.public() }).collect();
+    (infos, keys)
+}
+
+/// Pass `next` through the relays until it is delivered, returning the hops
+/// it took, the recipient and the sealed payload.
+fn route_through(mut next: Next, relays: &[RelayInfo], keys: &[OnionKey]) -> (Vec<String>, String, Sealed) {
+    let mut hops = Vec::new();
+    loop {
+        match next {
+            Next::Relay { peer, packet } => {
+                let i = relays.iter().position(|r| r.peer == peer).expect("known relay");
+                hops.push(peer);
+                // Only the relay's own key peels its layer.
+                let other = &keys[(i + 1) % keys.len()];
+                assert_eq!(other.peel(packet.clone()), Err(OnionError::Decrypt));
+                next = keys[i].peel(packet).unwrap();
+            }
+            Next::Deliver { peer, payload } => return (hops, peer, payload),
+        }
+    }
+}
+
+#[test]
+fn bids_reach_the_poster_through_every_relay() {
+    let (relays, keys) = relays(3);
+    let poster = OnionKey::generate();
+    let next = wrap(&relays, "poster", &poster.public(), b"bid").unwrap();
+
+    let (hops, to, payload) = route_through(next, &relays, &keys);
+    assert_eq!(hops, vec!["relay-0", "relay-1", "relay-2"]);
+    assert_eq!(to, "poster");
+    assert_eq!(poster.open(&payload).unwrap(), b"bid");
+    assert_eq!(keys[2].open(&payload), Err(OnionError::Decrypt));
+
+    let sealed = seal(&poster.public(), b"direct").unwrap();
+    assert_eq!(poster.open(&sealed).unwrap(), b"direct");
+}
+
+#[test]
+fn answers_travel_back_through_the_reply_block() {
+    let (relays, keys) = relays(2);
+    let (reply, reply_key) = ReplyBlock::new(&relays, "worker").unwrap();
+    let counter = BidMessage::Counter { job_id: 7, price: 90 };
+
+    for _ in 0..2 {
+        let next = reply.wrap(&counter.encode().unwrap()).unwrap();
+        let (hops, to, payload) = route_through(next, &relays, &keys);
+        assert_eq!(hops, vec!["relay-0", "relay-1"]);
+        assert_eq!(to, "worker");
+        assert_eq!(BidMessage::decode(&reply_key.open(&payload).unwrap()).unwrap(), counter);
+    }
+
+    // Without privacy mode the block points straight at the worker.
+    let (direct, direct_key) = ReplyBlock::new(&[], "worker").unwrap();
+    match direct.wrap(b"hi").unwrap() {
+        Next::Deliver { peer, payload } => {
+            assert_eq!(peer, "worker");
+            assert_eq!(direct_key.open(&payload).unwrap(), b"hi");
+        }
+        other => panic!("expected a direct delivery, got {:?}", other),
+    }
+}
+
+#[test]
+fn routes_have_two_or_three_distinct_relays() {
+    let (relays, _) = relays(4);
+    for hops in [2, 3] {
+        let route = choose_route(&relays, hops, &["relay-0"]).unwrap();
+        assert_eq!(route.len(), hops);
+        assert!(route.iter().all(|r| r.peer != "relay-0"));
+        assert!(route.windows(2).all(|w| w[0].peer != w[1].peer));
+    }
+    assert_eq!(choose_route(&relays, 1, &[]), Err(OnionError::Hops(1)));
+    assert_eq!(choose_route(&relays, 4, &[]), Err(OnionError::Hops(4)));
+    assert_eq!(
+        choose_route(&relays, 3, &["relay-1", "relay-2"]),
+        Err(OnionError::NotEnoughRelays { needed: 3, available: 2 })
+    );
+    assert_eq!(wrap(&relays[..1], "poster", &[9; 32], b"x"), Err(OnionError::Hops(1)));
+}
+
+#[test]
+fn the_lowest_bid_wins_and_claims_once() {
+    let (relays, _) = relays(2);
+    let mut keys = Vec::new();
+    let mut book = BidBook::new();
+    for price in [120, 80, 95] {
+        let (reply, key) = ReplyBlock::new(&relays, "worker").unwrap();
+        assert!(book.bid(1, price, reply));
+        keys.push(key);
+    }
+    assert_eq!(book.best(1), Some(80));
+
+    let answers = book.award(1);
+    assert_eq!(answers.len(), 3);
+    let token = match &answers[1] {
+        (reply, BidMessage::Accept { job_id: 1, price: 80, token }) => {
+            assert_eq!(reply.key, keys[1].public());
+            *token
+        }
+        other => panic!("expected the second bid to win, got {:?}", other),
+    };
+    assert!(matches!(answers[0].1, BidMessage::Reject { job_id: 1 }));
+    assert!(matches!(answers[2].1, BidMessage::Reject { job_id: 1 }));
+
+    let (late, _) = ReplyBlock::new(&relays, "worker").unwrap();
+    assert!(!book.bid(1, 10, late));
+    assert!(!book.claim(1, &[0; 16]));
+    assert!(book.claim(1, &token));
+    assert!(!book.claim(1, &token));
+}
@@ -0,0 +1,79 @@
+use runtime::blockchain::constants::BLOCK_REWARD;
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::miner;
+use runtime::pouw::{score, Milestone, ScoringConfig};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[test]
+fn score_follows_the_highest_milestone_reached() {
+    let config = ScoringConfig::default();
+    let shares: Vec<(u32, u32)> = [0, 4_999, 5_000, 6_499, 6_500, 8_000, 8_999, 9_000, 10_000]
+        .into_iter()
+        .map(|accuracy| (accuracy, score(accuracy, &config).reward_bps))
+        .collect();
+    assert_eq!(
+        shares,
+        vec![
+            (0, 0),
+            (4_999, 0),
+            (5_000, 2_500),
+            (6_499, 2_500),
+            (6_500, 5_000),
+            (8_000, 7_500),
+            (8_999, 7_500),
+            (9_000, 10_000),
+            (10_000, 10_000),
+        ]
+    );
+    assert_eq!(score(4_999, &config).milestone, None);
+    assert_eq!(score(8_500, &config).milestone, Some(2));
+}
+
+#[test]
+fn reward_share_is_proportional_and_capped() {
+    let config = ScoringConfig { milestones: vec![Milestone { accuracy: 0, reward_bps: 3_333 }] };
+    assert_eq!(score(1, &config).reward_share(100), 33);
+    assert_eq!(score(1, &config).reward_share(u64::MAX), (u64::MAX as u128 * 3_333 / 10_000) as u64);
+
+    let over = ScoringConfig { milestones: vec![Milestone { accuracy: 0, reward_bps: 20_000 }] };
+    assert_eq!(score(1, &over).reward_share(100), 100);
+}
+
+#[test]
+fn scoring_config_defaults_when_absent() {
+    let config: BlockchainConfig = serde_json::from_str(r#"{"max_transactions_per_block": 10}"#).unwrap();
+    assert_eq!(config.scoring, ScoringConfig::default());
+}
+
+#[tokio::test]
+async fn block_reward_is_split_by_score() {
+    let config = BlockchainConfig {
+        scoring: ScoringConfig {
+            milestones: vec![
+                Milestone { accuracy: 6_000, reward_bps: 4_000 },
+                Milestone { accuracy: 9_500, reward_bps: 10_000 },
+            ],
+        },
+        ..BlockchainConfig::default()
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::new(config)));
+
+    let mut block = miner::mine_block(
+        "miner".into(),
+        chain.clone(),
+        Arc::new(Mutex::new(HashSet::new())),
+        Arc::new(Mutex::new(VecDeque::new())),
+    )
+    .await
+    .unwrap();
+    // The verifier rejects solutions computed in under 100ms. Both the time
+    // and the claimed accuracy are part of the block hash.
+    block.solution.computation_time_ms = block.solution.computation_time_ms.max(100);
+    block.solution.accuracy = 7_000;
+    block.hash = block.calculate_hash();
+    chain.lock().await.add_block(block).unwrap();
+
+    assert_eq!(chain.lock().await.get_balance("miner"), BLOCK_REWARD * 4 / 10);
+}
@@ -0,0 +1,84 @@
+use runtime::large_data_transfer::network::{FetchStart, PayloadDescriptor, PayloadFetcher};
+
+fn model(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+#[test]
+fn descriptor_matches_only_its_body() {
+    let body = model(1024);
+    let descriptor = PayloadDescriptor::for_body(&body);
+    assert_eq!(descriptor.size, 1024);
+    assert!(descriptor.matches(&body));
+    assert!(!descriptor.matches(&body[1..]));
+
+    let mut tampered = body.clone();
+    tampered[0] ^= 1;
+    assert!(!descriptor.matches(&tampered));
+}
+
+#[test]
+fn repeated_announcements_share_one_fetch() {
+    let body = model(4096);
+    let descriptor = PayloadDescriptor::for_body(&body);
+    let mut fetcher = PayloadFetcher::<&str, u32>::new(4);
+
+    assert_eq!(
+        fetcher.fetch("a", descriptor.clone(), Some(1)),
+        FetchStart::Send { peer: "a", hash: descriptor.hash.clone() }
+    );
+    assert_eq!(fetcher.fetch("b", descriptor.clone(), None), FetchStart::Joined);
+    assert_eq!(fetcher.fetch("c", descriptor.clone(), Some(2)), FetchStart::Joined);
+    assert_eq!(fetcher.in_flight(), 1);
+
+    let finished = fetcher.finish(&descriptor.hash, Some(body.clone()));
+    assert_eq!(finished.body.as_ref(), Some(&body));
+    assert_eq!(finished.waiters, vec![1, 2]);
+    assert_eq!(fetcher.in_flight(), 0);
+    assert_eq!(fetcher.fetch("d", descriptor, Some(3)), FetchStart::Cached(body, Some(3)));
+}
+
+#[test]
+fn fetches_beyond_the_limit_queue_until_a_slot_frees() {
+    let bodies: Vec<Vec<u8>> = (1..=3).map(model).collect();
+    let descriptors: Vec<_> = bodies.iter().map(|b| PayloadDescriptor::for_body(b)).collect();
+    let mut fetcher = PayloadFetcher::<&str, ()>::new(2);
+
+    assert!(matches!(fetcher.fetch("a", descriptors[0].clone(), None), FetchStart::Send { .. }));
+    assert!(matches!(fetcher.fetch("a", descriptors[1].clone(), None), FetchStart::Send { .. }));
+    assert_eq!(fetcher.fetch("b", descriptors[2].clone(), None), FetchStart::Queued);
+    assert_eq!(fetcher.fetch("c", descriptors[2].clone(), None), FetchStart::Joined);
+    assert_eq!(fetcher.in_flight(), 2);
+
+    let finished = fetcher.finish(&descriptors[0].hash, Some(bodies[0].clone()));
+    assert_eq!(finished.next, Some(("b", descriptors[2].hash.clone())));
+    assert_eq!(fetcher.in_flight(), 2);
+
+    assert!(fetcher.finish(&descriptors[1].hash, Some(bodies[1].clone())).next.is_none());
+    assert!(fetcher.finish(&descriptors[2].hash, Some(bodies[2].clone())).next.is_none());
+    assert_eq!(fetcher.in_flight(), 0);
+}
+
+#[test]
+fn mismatched_body_fails_the_fetch_and_is_not_kept() {
+    let body = model(512);
+    let descriptor = PayloadDescriptor::for_body(&body);
+    let mut fetcher = PayloadFetcher::<&str, u32>::new(1);
+    fetcher.fetch("a", descriptor.clone(), Some(1));
+
+    let finished = fetcher.finish(&descriptor.hash, Some(model(511)));
+    assert_eq!(finished.body, None);
+    assert_eq!(finished.waiters, vec![1]);
+    assert!(fetcher.body(&descriptor.hash).is_none());
+
+    // A later announcement retries the fetch.
+    assert!(matches!(fetcher.fetch("b", descriptor, None), FetchStart::Send { peer: "b", .. }));
+}
+
+#[test]
+fn published_bodies_are_served_locally() {
+    let mut fetcher = PayloadFetcher::<&str, ()>::new(1);
+    let descriptor = fetcher.publish(model(64));
+    assert_eq!(fetcher.body(&descriptor.hash), Some(&model(64)));
+    assert_eq!(fetcher.in_flight(), 0);
+}
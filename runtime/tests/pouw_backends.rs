@@ -0,0 +1,82 @@
+use runtime::pouw::backend::{self, BackendError, CpuBackend, GradientBackend};
+use runtime::pouw::training::{self, Dataset, Weights};
+use runtime::pouw::{generate_task, solve_with};
+
+/// A backend whose device always fails.
+struct Broken;
+
+impl GradientBackend for Broken {
+    fn name(&self) -> &'static str {
+        "broken"
+    }
+
+    fn sample_gradients(&self, _: &Dataset, _: &Weights) -> Result<Vec<Weights>, BackendError> {
+        Err(BackendError::Device("lost".into()))
+    }
+}
+
+#[test]
+fn cpu_backend_matches_sample_gradient() {
+    let data = Dataset::for_task(&generate_task(1, 3));
+    let weights = [12_345, -67_890];
+    let gradients = CpuBackend.sample_gradients(&data, &weights).unwrap();
+    assert_eq!(gradients.len(), data.labels.len());
+    for ((x, label), gradient) in data.features.iter().zip(&data.labels).zip(gradients) {
+        assert_eq!(gradient, training::sample_gradient(&weights, x, *label));
+    }
+}
+
+#[test]
+fn failed_epochs_fall_back_to_the_cpu() {
+    let mut task = generate_task(1, 5);
+    task.epochs = 12;
+    assert_eq!(training::train_with(&Broken, &task), training::train(&task));
+}
+
+#[test]
+fn preferred_backend_reproduces_cpu_solutions() {
+    let task = generate_task(1, 9);
+    let preferred = solve_with(backend::preferred(), &task, 1);
+    let cpu = solve_with(&CpuBackend, &task, 1);
+    assert_eq!(preferred.trained_model_hash, cpu.trained_model_hash);
+    assert_eq!(preferred.checkpoints, cpu.checkpoints);
+    assert_eq!(preferred.nonce, cpu.nonce);
+}
+
+#[cfg(not(feature = "wgpu-solver"))]
+#[test]
+fn solver_uses_the_cpu_without_gpu_support() {
+    assert_eq!(backend::preferred().name(), "cpu");
+}
+
+#[cfg(feature = "wgpu-solver")]
+mod gpu {
+    use super::*;
+    use runtime::pouw::backend::{WgpuBackend, SHADER};
+    use wgpu::naga;
+
+    #[test]
+    fn shader_validates() {
+        let module = naga::front::wgsl::parse_str(SHADER).unwrap();
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+            .validate(&module)
+            .unwrap();
+    }
+
+    /// Runs only on machines with a GPU adapter.
+    #[test]
+    fn gpu_gradients_are_bit_identical() {
+        let Some(gpu) = WgpuBackend::shared() else {
+            return;
+        };
+        let data = Dataset::for_task(&generate_task(1, 3));
+        for weights in [[0, 0], [65_536, -65_536], [40_000_000, -3], [-1 << 40, 1 << 33]] {
+            assert_eq!(
+                gpu.sample_gradients(&data, &weights).unwrap(),
+                CpuBackend.sample_gradients(&data, &weights).unwrap(),
+                "weights {:?}",
+                weights
+            );
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use runtime::pouw::{generate_task, solve, training, verify, verify_by_retraining, PoUWConfig};
+
+const DIFFICULTY: u32 = 0x0FFF_FFFF;
+
+fn config() -> PoUWConfig {
+    PoUWConfig { min_computation_ms: 0, ..PoUWConfig::default() }
+}
+
+#[test]
+fn training_is_bit_for_bit_reproducible() {
+    let task = generate_task(40, 7);
+    let a = training::train(&task);
+    let b = training::train(&task);
+    assert_eq!(a, b);
+    assert_eq!(a.checkpoints.len(), 20);
+    assert!(a.accuracy > 9_000, "accuracy {}", a.accuracy);
+
+    let other = generate_task(40, 8);
+    assert_ne!(training::train(&other).weights, a.weights);
+}
+
+#[test]
+fn solver_output_passes_both_verifiers() {
+    let task = generate_task(40, 7);
+    let solution = solve(&task, DIFFICULTY);
+    assert!(verify(&task, &solution, DIFFICULTY, &config()));
+    assert!(verify_by_retraining(&task, &solution, DIFFICULTY, &config(), 4));
+    assert!(verify_by_retraining(&task, &solution, DIFFICULTY, &config(), usize::MAX));
+}
+
+#[test]
+fn retraining_catches_forged_checkpoints_and_accuracy() {
+    let task = generate_task(40, 7);
+    let solution = solve(&task, DIFFICULTY);
+
+    // A middle checkpoint that was never trained to is caught once sampled.
+    let mut forged = solution.clone();
+    forged.checkpoints[10] = training::encode_weights(&[1, 1]);
+    assert!(verify(&task, &forged, DIFFICULTY, &config()));
+    assert!(!verify_by_retraining(&task, &forged, DIFFICULTY, &config(), usize::MAX));
+
+    let mut inflated = solution.clone();
+    inflated.accuracy = 10_000;
+    assert!(!verify_by_retraining(&task, &inflated, DIFFICULTY, &config(), 1));
+
+    let mut truncated = solution;
+    truncated.checkpoints.pop();
+    assert!(!verify_by_retraining(&task, &truncated, DIFFICULTY, &config(), 1));
+}
@@ -0,0 +1,72 @@
+use runtime::pouw::sampling::{merkle_path, merkle_root, verify_path};
+use runtime::pouw::{
+    challenge_indices, generate_task, prove_samples, solve, training, verify_sample_proofs,
+};
+
+const DIFFICULTY: u32 = 0x0FFF_FFFF;
+const CHALLENGES: usize = 8;
+
+#[test]
+fn merkle_paths_verify_for_every_leaf_of_an_odd_tree() {
+    let leaves: Vec<[u8; 32]> = (0..7u8).map(|i| [i; 32]).collect();
+    let root = merkle_root(&leaves);
+    for (i, leaf) in leaves.iter().enumerate() {
+        let path = merkle_path(&leaves, i);
+        assert!(verify_path(*leaf, i, &path, &root));
+        assert!(!verify_path(*leaf, (i + 1) % leaves.len(), &path, &root));
+    }
+}
+
+#[test]
+fn honest_solver_answers_challenges() {
+    let task = generate_task(40, 7);
+    let data = training::Dataset::for_task(&task);
+    assert_eq!(task.dataset_root.as_deref(), Some(data.merkle_root().as_str()));
+
+    let solution = solve(&task, DIFFICULTY);
+    assert_eq!(solution.gradient_roots.len(), solution.checkpoints.len());
+
+    let challenges = challenge_indices(&task, &solution, CHALLENGES);
+    assert_eq!(challenges.len(), CHALLENGES);
+    let proofs = prove_samples(&data, &solution, &challenges).expect("challenges in range");
+    assert!(verify_sample_proofs(&task, &solution, &proofs, CHALLENGES));
+
+    // Answering someone else's challenges is rejected.
+    let mut shifted = proofs.clone();
+    shifted.rotate_left(1);
+    assert!(!verify_sample_proofs(&task, &solution, &shifted, CHALLENGES));
+}
+
+#[test]
+fn training_on_another_dataset_fails_sampling() {
+    let task = generate_task(40, 7);
+    let honest = solve(&task, DIFFICULTY);
+
+    // Solver trains on a trivial dataset but keeps the committed root.
+    let mut trivial = training::Dataset::for_task(&task);
+    for (x, label) in trivial.features.iter_mut().zip(trivial.labels.iter_mut()) {
+        *x = [training::ONE, training::ONE];
+        *label = training::ONE;
+    }
+    let mut forged = honest.clone();
+    forged.gradient_roots = honest
+        .gradient_roots
+        .iter()
+        .map(|_| trivial.gradient_root(&[0; training::FEATURES]))
+        .collect();
+
+    let challenges = challenge_indices(&task, &forged, CHALLENGES);
+    let proofs = prove_samples(&trivial, &forged, &challenges).expect("challenges in range");
+    assert!(!verify_sample_proofs(&task, &forged, &proofs, CHALLENGES));
+
+    // Opening real samples against gradients of the wrong data also fails.
+    let real = training::Dataset::for_task(&task);
+    let proofs = prove_samples(&real, &forged, &challenges).expect("challenges in range");
+    assert!(!verify_sample_proofs(&task, &forged, &proofs, CHALLENGES));
+
+    // Tasks without a dataset commitment cannot be sample-verified.
+    let mut uncommitted = task.clone();
+    uncommitted.dataset_root = None;
+    let proofs = prove_samples(&real, &honest, &challenge_indices(&uncommitted, &honest, CHALLENGES)).unwrap();
+    assert!(!verify_sample_proofs(&uncommitted, &honest, &proofs, CHALLENGES));
+}
@@ -0,0 +1,128 @@
+#![cfg(unix)]
+
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::blockchain::state::State;
+use runtime::blockchain::{Block, Blockchain, BlockchainConfig};
+use runtime::consensus_engine::{
+    verify_proposal, verify_vote, ConsensusProposal, ConsensusSigner, LocalSigner, RemoteSigner,
+    SignerError, SignerServer, Vote, VoteType,
+};
+use runtime::session_keys::{SessionKeys, SessionRole};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::UnixListener;
+
+fn scratch(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bcai-signer-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn vote(height: u32, round: u32, block_hash: &str) -> Vote {
+    Vote {
+        voter_id: "validator".into(),
+        block_hash: block_hash.into(),
+        vote_type: VoteType::Precommit,
+        height,
+        round,
+        timestamp: 1_700_000_000,
+        signature: String::new(),
+    }
+}
+
+fn proposal() -> ConsensusProposal {
+    let chain = Blockchain::new(BlockchainConfig::default());
+    let tip = chain.get_tip().clone();
+    let block =
+        Block::new(1, tip.hash, Vec::new(), tip.difficulty, "miner".into(), tip.task, tip.solution);
+    ConsensusProposal {
+        proposer_id: "validator".into(),
+        block,
+        timestamp: 1_700_000_000,
+        round: 0,
+        votes: HashMap::new(),
+        signature: String::new(),
+    }
+}
+
+/// A server on a fresh socket, with the key it signs with.
+fn serve(name: &str, secret: &[u8]) -> (PathBuf, SigningKey) {
+    let socket = scratch(name);
+    let key = SigningKey::generate(&mut OsRng);
+    let signer = LocalSigner::new(key.clone());
+    let listener = UnixListener::bind(&socket).unwrap();
+    let server = Arc::new(SignerServer::new(signer, secret));
+    tokio::spawn(server.serve(listener));
+    (socket, key)
+}
+
+/// A state in which the validator registered `key` for `role`.
+fn registered(role: SessionRole, key: &SigningKey) -> State {
+    let mut state = State::new();
+    state.session_keys.insert("validator".into(), SessionKeys::new("validator", 0).with_key(role, key));
+    state
+}
+
+#[tokio::test]
+async fn the_daemon_signs_through_the_remote_signer() {
+    let (socket, key) = serve("sign", b"shared secret");
+    let signer = RemoteSigner::connect(&socket, b"shared secret").await.unwrap();
+    assert_eq!(signer.public_key(), hex::encode(key.verifying_key().to_bytes()));
+    let state = registered(SessionRole::Consensus, &key);
+
+    let mut vote = vote(7, 0, "aa");
+    signer.sign_vote(&mut vote).await.unwrap();
+    assert!(verify_vote(&vote, &state));
+    let mut tampered = vote.clone();
+    tampered.block_hash = "bb".into();
+    assert!(!verify_vote(&tampered, &state));
+
+    let mut proposal = proposal();
+    signer.sign_proposal(&mut proposal).await.unwrap();
+    assert!(verify_proposal(&proposal, &state));
+
+    // Only the key registered for the consensus role counts.
+    assert!(!verify_vote(&vote, &State::new()));
+    assert!(!verify_vote(&vote, &registered(SessionRole::Metrics, &key)));
+    assert!(!verify_proposal(&proposal, &registered(SessionRole::Consensus, &SigningKey::generate(&mut OsRng))));
+}
+
+#[tokio::test]
+async fn clients_without_the_secret_are_refused() {
+    let (socket, _) = serve("auth", b"shared secret");
+    let err = RemoteSigner::connect(&socket, b"guess").await.err().unwrap();
+    assert!(
+        matches!(err, SignerError::Refused(ref reason) if reason.contains("authenticated")),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn conflicting_and_stale_votes_are_not_signed() {
+    let (socket, _) = serve("equivocate", b"shared secret");
+    let signer = RemoteSigner::connect(&socket, b"shared secret").await.unwrap();
+    signer.sign_vote(&mut vote(7, 1, "aa")).await.unwrap();
+    // Signing the same block again is harmless.
+    signer.sign_vote(&mut vote(7, 1, "aa")).await.unwrap();
+
+    let err = signer.sign_vote(&mut vote(7, 1, "bb")).await.unwrap_err();
+    assert!(err.to_string().contains("already signed block aa"), "{err}");
+    let err = signer.sign_vote(&mut vote(7, 0, "cc")).await.unwrap_err();
+    assert!(err.to_string().contains("behind"), "{err}");
+    signer.sign_vote(&mut vote(8, 0, "bb")).await.unwrap();
+}
+
+#[tokio::test]
+async fn the_signed_position_survives_a_restart() {
+    let state = scratch("state.json");
+    let key = SigningKey::generate(&mut OsRng);
+    let signer = LocalSigner::new(key.clone()).with_state_path(&state).unwrap();
+    signer.sign_vote(&mut vote(9, 2, "aa")).await.unwrap();
+
+    let restarted = LocalSigner::new(key).with_state_path(&state).unwrap();
+    assert_eq!(restarted.last_signed("precommit").unwrap().height, 9);
+    assert!(restarted.sign_vote(&mut vote(9, 2, "bb")).await.is_err());
+    std::fs::remove_file(state).unwrap();
+}
@@ -0,0 +1,66 @@
+use runtime::node::{Dimension, Reputation, ReputationBook, ReputationConfig, ReputationWeights};
+use runtime::pouw::task_types::{data_validation, train};
+use runtime::token::TokenLedger;
+
+const DAY: u64 = 24 * 3600;
+
+fn book() -> ReputationBook {
+    ReputationBook::new(ReputationConfig { half_life_secs: DAY, ..ReputationConfig::default() })
+}
+
+#[test]
+fn scores_halve_every_half_life() {
+    let mut book = book();
+    book.record("alice", Dimension::Uptime, 80.0, 0);
+    assert_eq!(book.get("alice", DAY).uptime.value, 40.0);
+    assert_eq!(book.get("alice", 2 * DAY).uptime.value, 20.0);
+
+    // New records land on the decayed value.
+    book.record("alice", Dimension::Uptime, 10.0, DAY);
+    assert_eq!(book.get("alice", DAY).uptime.value, 50.0);
+    assert_eq!(book.get("alice", 2 * DAY).uptime.value, 25.0);
+
+    // Bad behaviour fades too.
+    book.record("bob", Dimension::EvaluationHonesty, -40.0, 0);
+    assert_eq!(book.get("bob", DAY).evaluation_honesty.value, -20.0);
+    assert_eq!(book.get("nobody", DAY), Reputation::default());
+}
+
+#[test]
+fn weights_depend_on_the_job_kind() {
+    let mut book = book();
+    book.record("trainer", Dimension::TrainingQuality, 90.0, 0);
+    book.record("trainer", Dimension::Uptime, 30.0, 0);
+    book.record("validator", Dimension::EvaluationHonesty, 90.0, 0);
+    book.record("validator", Dimension::StorageReliability, 60.0, 0);
+
+    let accounts = ["validator", "trainer"];
+    let by_training = book.rank(accounts, Some(train::KIND), 0);
+    assert_eq!(by_training[0].0, "trainer");
+    let by_validation = book.rank(accounts, Some(data_validation::KIND), 0);
+    assert_eq!(by_validation[0].0, "validator");
+
+    // Unknown kinds use the default, equal weights.
+    assert_eq!(book.score("trainer", Some("unknown"), 0), 30.0);
+    assert_eq!(book.score("validator", Some("unknown"), 0), 37.5);
+}
+
+#[test]
+fn a_single_number_maps_to_every_dimension() {
+    let reputation = Reputation::uniform(42);
+    for dimension in Dimension::ALL {
+        assert_eq!(reputation.get(dimension).value, 42.0);
+    }
+    assert_eq!(reputation.overall(), 42);
+    assert_eq!(reputation.weighted(&ReputationWeights::new(5.0, 0.0, -1.0, 1.0)), 42.0);
+    assert_eq!(Reputation::from(-3).overall(), -3);
+}
+
+#[test]
+fn the_ledger_keeps_reputation_per_account() {
+    let mut ledger = TokenLedger::new();
+    ledger.record_reputation("alice", Dimension::TrainingQuality, 12.0, 100);
+    assert_eq!(ledger.reputation().get("alice", 100).training_quality.value, 12.0);
+    ledger.reputation_mut().config.half_life_secs = 0;
+    assert_eq!(ledger.reputation().get("alice", 100 + 365 * DAY).training_quality.value, 12.0);
+}
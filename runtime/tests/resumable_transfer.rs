@@ -0,0 +1,138 @@
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::{NetworkPeerInfo, NetworkTransferCoordinator, PeerCapabilities};
+use runtime::large_data_transfer::protocol::{ChunkBitmap, ResumeStore};
+use runtime::large_data_transfer::{ChunkManager, DataChunk, LargeDataConfig, LargeDataDescriptor};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bcai-resume-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn chunks(n: u32) -> Vec<DataChunk> {
+    (0..n)
+        .map(|i| DataChunk::new_from_slice(vec![i as u8; 4096], i, CompressionAlgorithm::None).unwrap())
+        .collect()
+}
+
+fn descriptor(id: &str, chunks: &[DataChunk]) -> LargeDataDescriptor {
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    LargeDataDescriptor::new(id.into(), id.into(), 4096 * chunks.len() as u64, hashes)
+}
+
+#[test]
+fn bitmaps_track_verified_chunks() {
+    let mut bitmap = ChunkBitmap::new(10);
+    bitmap.set(0);
+    bitmap.set(9);
+    bitmap.set(10);
+    assert!(bitmap.contains(9) && !bitmap.contains(10));
+    assert_eq!(bitmap.count(), 2);
+    bitmap.clear(0);
+    assert_eq!(bitmap.missing(), (0..9).collect::<Vec<_>>());
+    assert!(!bitmap.is_complete());
+}
+
+#[test]
+fn restored_sessions_keep_only_chunks_that_still_verify() {
+    let dir = scratch("store");
+    let chunks = chunks(4);
+    let descriptor = descriptor("dataset-a", &chunks);
+    let store = ResumeStore::open(&dir).unwrap();
+    store.begin(&descriptor).unwrap();
+    for index in [0, 1, 3] {
+        store.record_chunk("dataset-a", index, &chunks[index as usize]).unwrap();
+    }
+    // A chunk filed under the wrong index is refused.
+    assert!(store.record_chunk("dataset-a", 2, &chunks[0]).is_err());
+
+    // The process restarts and chunk 1's body was damaged on disk.
+    drop(store);
+    let store = ResumeStore::open(&dir).unwrap();
+    assert_eq!(store.sessions().unwrap(), vec!["dataset-a".to_string()]);
+    let body = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path().join("1.chunk");
+    let mut damaged = std::fs::read(&body).unwrap();
+    damaged[100] ^= 1;
+    std::fs::write(&body, damaged).unwrap();
+
+    let manager = ChunkManager::default();
+    let session = store.restore("dataset-a", &manager).unwrap().unwrap();
+    assert_eq!(session.pending_chunks(), vec![1, 2]);
+    assert_eq!(session.progress(), 50.0);
+    assert!(manager.has_chunk(&chunks[3].id) && !manager.has_chunk(&chunks[1].id));
+    assert_eq!(store.verified("dataset-a").unwrap().unwrap().missing(), vec![1, 2]);
+
+    // The same id with other contents starts over.
+    let mut reordered = descriptor.clone();
+    reordered.chunk_hashes.reverse();
+    assert_eq!(store.begin(&reordered).unwrap().verified.count(), 0);
+    store.remove("dataset-a").unwrap();
+    assert!(store.sessions().unwrap().is_empty());
+    assert!(store.restore("dataset-a", &manager).unwrap().is_none());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// A coordinator serving its own chunk manager over the loopback channel,
+/// with one peer announcing every chunk of `chunks`.
+async fn coordinator(
+    manager: Arc<ChunkManager>,
+    store: Arc<ResumeStore>,
+    chunks: &[DataChunk],
+) -> NetworkTransferCoordinator {
+    let coordinator =
+        NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager)
+            .with_resume_store(store);
+    let serving = coordinator.clone();
+    tokio::spawn(async move { serving.message_processing_loop().await });
+    coordinator
+        .add_peer(NetworkPeerInfo {
+            peer_id: "seed".into(),
+            addresses: vec![],
+            capabilities: PeerCapabilities {
+                max_bandwidth_mbps: 100,
+                max_concurrent_transfers: 4,
+                supported_compression: vec![],
+                storage_capacity_gb: 1,
+                available_chunks: chunks.iter().map(|c| c.id.clone()).collect(),
+            },
+            reputation: 1.0,
+            last_seen: Instant::now(),
+            transfer_stats: Default::default(),
+        })
+        .await;
+    coordinator
+}
+
+#[tokio::test]
+async fn interrupted_transfers_resume_after_a_restart() {
+    let dir = scratch("coordinator");
+    let chunks = chunks(4);
+    let descriptor = descriptor("model-v1", &chunks);
+
+    // The seed only has the first half when the download starts.
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks[..2] {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let store = Arc::new(ResumeStore::open(&dir).unwrap());
+    let first = coordinator(manager, store.clone(), &chunks).await;
+    assert!(first.transfer_large_data(descriptor.clone(), vec![]).await.is_err());
+    assert_eq!(store.verified("model-v1").unwrap().unwrap().missing(), vec![2, 3]);
+
+    // After a restart nothing is in memory; the rest is now available.
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks[2..] {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let store = Arc::new(ResumeStore::open(&dir).unwrap());
+    let second = coordinator(manager.clone(), store.clone(), &chunks).await;
+    let stats = second.resume_transfer("model-v1").await.unwrap();
+    assert_eq!((stats.chunks_completed, stats.total_chunks), (4, 4));
+    assert!(chunks.iter().all(|c| manager.has_chunk(&c.id)));
+    assert!(store.sessions().unwrap().is_empty());
+    assert!(second.resume_transfer("model-v1").await.is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,79 @@
+use rand::{rngs::StdRng, SeedableRng};
+use runtime::rl::{
+    verify_rollouts, CorridorEnv, Learner, LinearPolicy, RlError, RlJob, RolloutWorker,
+};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn workers_stream_to_learner_and_rollouts_replay() {
+    let job = RlJob::new(1, CorridorEnv::spec(6, 30), 4, 100);
+    let policy = LinearPolicy::new(6, 2).unwrap();
+    let worker = RolloutWorker::new("worker-1", job.epsilon, job.environment.max_episode_steps);
+    let seeds = job.episode_seeds(0, "worker-1");
+
+    let (tx, mut rx) = mpsc::channel(8);
+    let mut env = job.environment.instantiate().unwrap();
+    worker.stream(env.as_mut(), &policy, &seeds, &tx).await.unwrap();
+
+    let mut submitted = Vec::new();
+    let mut learner = Learner::new(policy.clone(), 0.1, 0.9);
+    while let Ok(t) = rx.try_recv() {
+        submitted.push(t);
+    }
+    assert_eq!(submitted.len(), 4);
+    assert!(verify_rollouts(&job, &policy, 0, "worker-1", &submitted, 2, 42).unwrap());
+
+    for t in &submitted {
+        tx.send(t.clone()).await.unwrap();
+    }
+    assert_eq!(learner.collect(&mut rx, 4).await, 4);
+    assert!(learner.update().unwrap().is_some());
+    assert_ne!(learner.policy.hash(), policy.hash());
+}
+
+#[test]
+fn tampered_rollouts_fail_verification() {
+    let job = RlJob::new(2, CorridorEnv::spec(5, 20), 3, 100);
+    let policy = LinearPolicy::new(5, 2).unwrap();
+    let worker = RolloutWorker::new("w", job.epsilon, job.environment.max_episode_steps);
+    let mut env = job.environment.instantiate().unwrap();
+    let mut rollouts: Vec<_> = job
+        .episode_seeds(0, "w")
+        .into_iter()
+        .map(|s| worker.run_episode(env.as_mut(), &policy, s).unwrap())
+        .collect();
+    assert!(verify_rollouts(&job, &policy, 0, "w", &rollouts, 3, 7).unwrap());
+
+    rollouts[1].total_reward += 1.0;
+    assert!(!verify_rollouts(&job, &policy, 0, "w", &rollouts, 3, 7).unwrap());
+
+    // Self-chosen seeds are rejected without replay.
+    rollouts[1] = worker.run_episode(env.as_mut(), &policy, 12345).unwrap();
+    assert!(!verify_rollouts(&job, &policy, 0, "w", &rollouts, 0, 7).unwrap());
+}
+
+#[test]
+fn malformed_policies_and_actions_are_errors() {
+    assert!(matches!(LinearPolicy::new(0, 2), Err(RlError::EmptyPolicy { .. })));
+    assert!(matches!(LinearPolicy::new(3, 0), Err(RlError::EmptyPolicy { .. })));
+    assert!(matches!(LinearPolicy::new(usize::MAX, 2), Err(RlError::EmptyPolicy { .. })));
+
+    let mut policy = LinearPolicy::new(3, 2).unwrap();
+    let observation = [1.0, 0.0, 0.5];
+    assert!(matches!(
+        policy.reinforce(&observation, 2, 1.0, 0.1),
+        Err(RlError::InvalidAction { action: 2, actions: 2 })
+    ));
+    assert!(matches!(
+        policy.reinforce(&observation[..2], 1, 1.0, 0.1),
+        Err(RlError::DimensionMismatch { expected: 3, actual: 2 })
+    ));
+    policy.reinforce(&observation, 1, 1.0, 0.1).unwrap();
+
+    // A deserialized policy is checked as it is used.
+    let mut rng = StdRng::seed_from_u64(0);
+    let empty = LinearPolicy { observation_dim: 0, action_count: 0, weights: Vec::new() };
+    assert!(matches!(empty.act(&[], 0.5, &mut rng), Err(RlError::EmptyPolicy { .. })));
+    let truncated = LinearPolicy { weights: vec![0.0; 4], ..policy };
+    assert!(matches!(truncated.scores(&observation), Err(RlError::DimensionMismatch { expected: 6, actual: 4 })));
+}
@@ -0,0 +1,76 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use runtime::large_data_transfer::protocol::{PeerInfo, TransferSession};
+use runtime::onion::{choose_route, choose_route_with, RelayInfo};
+use runtime::pouw::PoUWTask;
+use runtime::rng::{self, Stream};
+use std::time::Instant;
+
+fn relays() -> Vec<RelayInfo> {
+    (0..12).map(|i| RelayInfo { peer: format!("relay-{}", i), key: [i as u8; 32] }).collect()
+}
+
+fn session() -> TransferSession {
+    let mut session = TransferSession::new("content".into());
+    for i in 0..6 {
+        session.add_peer(PeerInfo {
+            node_id: format!("peer-{}", i),
+            available_chunks: vec![0, 1, 2],
+            bandwidth: 1_000,
+            reliability: if i == 5 { 0.5 } else { 0.9 },
+            last_seen: Instant::now(),
+        });
+    }
+    session
+}
+
+/// Everything a node would draw in a short run.
+fn draws() -> (Vec<[u8; 32]>, Vec<Vec<String>>, Vec<String>) {
+    let challenges = (0..4).map(|_| PoUWTask::new("m".into(), "d".into(), 1).challenge).collect();
+    let routes = (0..4)
+        .map(|_| {
+            choose_route(&relays(), 3, &["relay-0"]).unwrap().into_iter().map(|r| r.peer).collect()
+        })
+        .collect();
+    let session = session();
+    let sources =
+        (0..16).map(|i| session.best_peer_for_chunk(i % 3).unwrap().node_id.clone()).collect();
+    (challenges, routes, sources)
+}
+
+// One test, since the installed seed is process-wide.
+#[test]
+fn an_installed_seed_replays_every_stream() {
+    rng::install(Some(7));
+    assert_eq!(rng::seed(), Some(7));
+    let first = draws();
+    rng::install(Some(7));
+    assert_eq!(draws(), first);
+
+    let (challenges, routes, sources) = &first;
+    assert!(challenges.windows(2).all(|w| w[0] != w[1]));
+    assert!(routes.iter().flatten().all(|peer| peer != "relay-0"));
+    // Equally reliable peers share the load; the less reliable one is never asked.
+    assert!(sources.iter().any(|s| s != &sources[0]));
+    assert!(sources.iter().all(|s| s != "peer-5"));
+
+    rng::install(Some(8));
+    assert_ne!(draws(), first);
+
+    // Streams are independent: drawing routes does not move the challenges.
+    rng::install(Some(7));
+    choose_route(&relays(), 3, &[]).unwrap();
+    assert_eq!(PoUWTask::new("m".into(), "d".into(), 1).challenge, first.0[0]);
+
+    // The n-th draw of a stream is derived from the seed alone.
+    rng::install(Some(7));
+    let mut derived = StdRng::from_seed(rng::derive(7, Stream::PeerSelection, 0));
+    assert_eq!(
+        choose_route(&relays(), 3, &[]).unwrap(),
+        choose_route_with(&relays(), 3, &[], &mut derived).unwrap()
+    );
+
+    rng::install(None);
+    assert_eq!(rng::seed(), None);
+    assert_ne!(PoUWTask::new("m".into(), "d".into(), 1).challenge, first.0[0]);
+}
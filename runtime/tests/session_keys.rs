@@ -0,0 +1,136 @@
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+use runtime::blockchain::validation::{validate_transaction_stateful, validate_transaction_stateless};
+use runtime::distributed_storage::allocation::NodeMetrics;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::session_keys::{SessionKeys, SessionRole};
+use schnorrkel::{Keypair, SecretKey};
+
+fn account(chain: &mut Blockchain) -> (SecretKey, String) {
+    let key = Keypair::generate().secret.clone();
+    let address = hex::encode(key.to_public().to_bytes());
+    chain.state.set_balance(&address, 100);
+    (key, address)
+}
+
+fn hex_key(key: &SigningKey) -> String {
+    hex::encode(key.verifying_key().to_bytes())
+}
+
+fn register(chain: &mut Blockchain, key: &SecretKey, keys: SessionKeys) -> Result<(), String> {
+    let nonce = chain.state.get_nonce(&keys.account);
+    let tx = Transaction::new_session_keys_signed(key, keys, 1, nonce);
+    validate_transaction_stateful(&tx, &chain.state).map_err(|e| e.to_string())?;
+    chain.state.apply_transaction(&tx).unwrap();
+    Ok(())
+}
+
+#[test]
+fn session_keys_are_linked_to_their_account_and_rotate() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (account_key, address) = account(&mut chain);
+    let consensus = SigningKey::generate(&mut OsRng);
+    let p2p = SigningKey::generate(&mut OsRng);
+    let keys = SessionKeys::new(&address, 0)
+        .with_key(SessionRole::Consensus, &consensus)
+        .with_key(SessionRole::P2p, &p2p);
+    register(&mut chain, &account_key, keys).unwrap();
+    assert_eq!(
+        chain.state.session_account(SessionRole::Consensus, &hex_key(&consensus)),
+        Some(address.as_str())
+    );
+    assert_eq!(chain.state.session_account(SessionRole::P2p, &hex_key(&consensus)), None);
+    assert_eq!(chain.state.get_balance(&address), 99);
+
+    // After a leak, the consensus key is replaced at the next epoch only.
+    let rotated = SigningKey::generate(&mut OsRng);
+    let replay = SessionKeys::new(&address, 0).with_key(SessionRole::Consensus, &rotated);
+    assert!(register(&mut chain, &account_key, replay).unwrap_err().contains("epoch"));
+    let next = SessionKeys::new(&address, 1)
+        .with_key(SessionRole::Consensus, &rotated)
+        .with_key(SessionRole::P2p, &p2p);
+    register(&mut chain, &account_key, next).unwrap();
+    assert_eq!(chain.state.session_account(SessionRole::Consensus, &hex_key(&consensus)), None);
+    assert_eq!(
+        chain.state.session_account(SessionRole::Consensus, &hex_key(&rotated)),
+        Some(address.as_str())
+    );
+}
+
+#[test]
+fn registrations_must_be_signed_by_the_account_and_every_session_key() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (account_key, address) = account(&mut chain);
+    let (other_key, other) = account(&mut chain);
+    let session = SigningKey::generate(&mut OsRng);
+
+    // Only the account may link keys to itself.
+    let keys = SessionKeys::new(&address, 0).with_key(SessionRole::Metrics, &session);
+    assert!(register(&mut chain, &other_key, keys.clone())
+        .unwrap_err()
+        .contains("may not register"));
+
+    // A key signing the link to another account, or at another epoch, is refused.
+    let mut forged = SessionKeys::new(&other, 0).with_key(SessionRole::Metrics, &session);
+    forged.keys[0].proof = keys.keys[0].proof.clone();
+    assert!(register(&mut chain, &other_key, forged).unwrap_err().contains("did not sign"));
+
+    // One key may not serve two roles.
+    let shared = SessionKeys::new(&address, 0)
+        .with_key(SessionRole::Consensus, &session)
+        .with_key(SessionRole::Metrics, &session);
+    assert!(register(&mut chain, &account_key, shared).unwrap_err().contains("two roles"));
+    assert!(register(&mut chain, &account_key, SessionKeys::new(&address, 0)).is_err());
+
+    // Nor may two accounts claim the same key.
+    register(&mut chain, &account_key, keys).unwrap();
+    let claimed = SessionKeys::new(&other, 0).with_key(SessionRole::Metrics, &session);
+    assert!(register(&mut chain, &other_key, claimed).unwrap_err().contains("already registered"));
+}
+
+fn metrics(node_id: &str) -> Vec<NodeMetrics> {
+    vec![NodeMetrics {
+        node_id: node_id.into(),
+        reputation: 0.9,
+        free_capacity: 0.5,
+        latency_ms: 20,
+        region: "eu".into(),
+        energy_score: 0.7,
+        utilisation: 0.5,
+    }]
+}
+
+#[test]
+fn metrics_count_only_when_signed_by_the_metrics_session_key() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (account_key, address) = account(&mut chain);
+    let (metrics_key, consensus_key) = (SigningKey::generate(&mut OsRng), SigningKey::generate(&mut OsRng));
+    let check = |chain: &Blockchain, tx: &Transaction| {
+        validate_transaction_stateless(tx).and_then(|_| validate_transaction_stateful(tx, &chain.state))
+    };
+
+    // Unregistered keys are refused.
+    let report = Transaction::new_update_metrics_signed(&address, &metrics_key, metrics(&address), 0);
+    assert!(check(&chain, &report).is_err());
+
+    let keys = SessionKeys::new(&address, 0)
+        .with_key(SessionRole::Metrics, &metrics_key)
+        .with_key(SessionRole::Consensus, &consensus_key);
+    register(&mut chain, &account_key, keys).unwrap();
+    let nonce = chain.state.get_nonce(&address);
+    let report = Transaction::new_update_metrics_signed(&address, &metrics_key, metrics(&address), nonce);
+    check(&chain, &report).unwrap();
+
+    // The key of another role, metrics about another node and a tampered
+    // report are refused.
+    let wrong_role = Transaction::new_update_metrics_signed(&address, &consensus_key, metrics(&address), nonce);
+    assert!(check(&chain, &wrong_role).is_err());
+    let other_node = Transaction::new_update_metrics_signed(&address, &metrics_key, metrics("other"), nonce);
+    assert!(check(&chain, &other_node).is_err());
+    let mut tampered = report.clone();
+    tampered.nonce += 1;
+    assert!(check(&chain, &tampered).is_err());
+
+    chain.state.apply_transaction(&report).unwrap();
+    assert_eq!(chain.state.node_metrics[&address].region, "eu");
+}
@@ -0,0 +1,67 @@
+use runtime::consensus_engine::{GuardError, SlashingGuard};
+use std::path::PathBuf;
+
+fn state_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("bcai-guard-{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn never_signs_twice_at_a_height_and_round() {
+    let guard = SlashingGuard::in_memory();
+    guard.check_and_record("prevote", 5, 0, "aa").unwrap();
+    guard.check_and_record("prevote", 5, 0, "aa").unwrap();
+    assert!(matches!(
+        guard.check_and_record("prevote", 5, 0, "bb"),
+        Err(GuardError::DoubleSign { height: 5, round: 0, .. })
+    ));
+    assert!(matches!(
+        guard.check_and_record("prevote", 4, 3, "cc"),
+        Err(GuardError::Regression { height: 4, .. })
+    ));
+    // Kinds are guarded apart, and a later round may pick another block.
+    guard.check_and_record("precommit", 5, 0, "bb").unwrap();
+    guard.check_and_record("prevote", 5, 1, "bb").unwrap();
+}
+
+#[test]
+fn marks_survive_restarts_and_the_file_is_never_torn() {
+    let path = state_path("persist");
+    SlashingGuard::open(&path).unwrap().check_and_record("proposal", 8, 2, "aa").unwrap();
+    assert!(!path.with_extension("tmp").exists());
+
+    let reopened = SlashingGuard::open(&path).unwrap();
+    assert_eq!(reopened.last_signed("proposal").unwrap().height, 8);
+    assert!(reopened.check_and_record("proposal", 8, 2, "bb").is_err());
+
+    std::fs::write(&path, b"{\"signed\":").unwrap();
+    assert!(matches!(SlashingGuard::open(&path), Err(GuardError::Corrupt(_))));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn a_restored_guard_refuses_heights_below_the_floor() {
+    let path = state_path("restore");
+    let guard = SlashingGuard::open(&path).unwrap();
+    guard.check_and_record("precommit", 10, 0, "aa").unwrap();
+    let backup = std::fs::read(&path).unwrap();
+    guard.check_and_record("precommit", 11, 0, "bb").unwrap();
+
+    // Restored from the backup, the guard only knows height 10...
+    std::fs::write(&path, backup).unwrap();
+    let restored = SlashingGuard::open(&path).unwrap();
+    assert_eq!(restored.last_signed("precommit").unwrap().height, 10);
+    // ...until the chain's tip sets the floor.
+    restored.raise_floor(11).unwrap();
+    restored.raise_floor(3).unwrap();
+    assert_eq!(restored.floor(), Some(11));
+    assert!(matches!(
+        restored.check_and_record("precommit", 11, 0, "cc"),
+        Err(GuardError::BelowFloor { height: 11, floor: 11, .. })
+    ));
+    restored.check_and_record("precommit", 12, 0, "cc").unwrap();
+    assert_eq!(SlashingGuard::open(&path).unwrap().floor(), Some(11));
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,69 @@
+use runtime::federated::{
+    FederatedError, SplitLearningPlan, SplitLearningSession, SplitMessage, SplitPayload,
+    SplitPoint, SplitRole, SplitStage,
+};
+
+fn session() -> SplitLearningSession {
+    let split = SplitPoint::new(vec![8, 4, 4, 2], 1).unwrap();
+    let plan = SplitLearningPlan::assign(
+        7,
+        split,
+        &["holder-a".to_string(), "holder-b".to_string()],
+        &["gpu-1".to_string()],
+    )
+    .unwrap();
+    SplitLearningSession::new(plan, [9u8; 32])
+}
+
+#[test]
+fn split_point_rejects_degenerate_cuts() {
+    assert!(SplitPoint::new(vec![8, 4], 1).is_err());
+    assert!(SplitPoint::new(vec![8, 4, 2], 0).is_err());
+    let split = SplitPoint::new(vec![8, 4, 2], 1).unwrap();
+    assert_eq!(split.activation_width(), 4);
+    assert_eq!(split.head_layers(), &[8, 4]);
+    assert_eq!(split.tail_layers(), &[4, 2]);
+}
+
+#[test]
+fn activations_and_gradients_alternate() {
+    let key = [9u8; 32];
+    let mut session = session();
+    assert_eq!(session.plan.role_of("holder-a"), Some(SplitRole::DataHolder));
+    assert_eq!(session.plan.role_of("gpu-1"), Some(SplitRole::Compute));
+
+    let acts = SplitPayload::Activations { batch_size: 2, values: vec![0.5; 8] };
+    let msg = SplitMessage::seal(7, 0, "holder-a".into(), "gpu-1".into(), &acts, &key).unwrap();
+    assert_ne!(msg.ciphertext, bincode::serialize(&acts).unwrap());
+    assert_eq!(session.accept(&msg).unwrap(), acts);
+    assert_eq!(session.progress("holder-a"), Some((0, SplitStage::AwaitingGradients)));
+
+    // Replaying the forward pass before gradients come back is rejected.
+    assert!(matches!(session.accept(&msg), Err(FederatedError::SplitProtocol(_))));
+
+    let grads = SplitPayload::Gradients { batch_size: 2, values: vec![0.1; 8] };
+    let msg = SplitMessage::seal(7, 0, "gpu-1".into(), "holder-a".into(), &grads, &key).unwrap();
+    session.accept(&msg).unwrap();
+    assert_eq!(session.progress("holder-a"), Some((1, SplitStage::AwaitingActivations)));
+}
+
+#[test]
+fn rejects_wrong_shape_and_wrong_key() {
+    let mut session = session();
+    let bad = SplitPayload::Activations { batch_size: 2, values: vec![0.5; 7] };
+    let msg = SplitMessage::seal(7, 0, "holder-b".into(), "gpu-1".into(), &bad, &[9u8; 32]).unwrap();
+    assert!(matches!(
+        session.accept(&msg),
+        Err(FederatedError::ModelDimensionMismatch { expected: 8, actual: 7 })
+    ));
+
+    // A batch size whose activation count overflows is refused, not wrapped.
+    let huge = SplitPayload::Activations { batch_size: usize::MAX / 2, values: vec![0.5; 2] };
+    let msg = SplitMessage::seal(7, 0, "holder-b".into(), "gpu-1".into(), &huge, &[9u8; 32]).unwrap();
+    assert!(matches!(session.accept(&msg), Err(FederatedError::SplitProtocol(_))));
+    assert_eq!(session.progress("holder-b"), Some((0, SplitStage::AwaitingActivations)));
+
+    let acts = SplitPayload::Activations { batch_size: 1, values: vec![0.5; 4] };
+    let msg = SplitMessage::seal(7, 0, "holder-b".into(), "gpu-1".into(), &acts, &[1u8; 32]).unwrap();
+    assert!(session.accept(&msg).is_err());
+}
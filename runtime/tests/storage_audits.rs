@@ -0,0 +1,161 @@
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::distributed_storage::{AuditConfig, ContractStatus, StorageContract, StorageProof};
+use runtime::large_data_transfer::merkle::{build_merkle_root, merkle_proof, verify_merkle_proof};
+use runtime::large_data_transfer::{ChunkId, LargeDataDescriptor};
+use schnorrkel::{Keypair, SecretKey};
+
+fn chunks() -> Vec<Vec<u8>> {
+    (0..5u8).map(|i| vec![i; 64]).collect()
+}
+
+fn descriptor() -> LargeDataDescriptor {
+    let hashes = chunks().iter().map(|data| ChunkId::from_data(data).0).collect();
+    LargeDataDescriptor::new("model-weights".into(), "content".into(), 320, hashes)
+}
+
+fn funded_key(chain: &mut Blockchain) -> (SecretKey, String) {
+    let key = Keypair::generate().secret.clone();
+    let address = hex::encode(key.to_public().to_bytes());
+    chain.state.set_balance(&address, 1_000);
+    (key, address)
+}
+
+fn apply(chain: &mut Blockchain, tx: &Transaction) {
+    validate_transaction_stateful(tx, &chain.state).unwrap();
+    chain.state.apply_transaction(tx).unwrap();
+}
+
+/// A chain with contract `c-1` paying a staked node 10 per audit for 3
+/// audits, backed by 300 of collateral the node accepted to lock.
+fn contracted() -> (Blockchain, SecretKey, String, String) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (owner_key, owner) = funded_key(&mut chain);
+    let (node_key, node) = funded_key(&mut chain);
+    chain.state.stake_tokens(&node, 300).unwrap();
+    let contract = StorageContract::new("c-1", &owner, &node, &descriptor(), 10, 3, 300);
+    apply(&mut chain, &Transaction::new_open_storage_contract_signed(&owner_key, contract, 1, 0));
+    assert_eq!(chain.state.get_balance(&owner), 1_000 - 30 - 1);
+    assert_eq!(chain.state.storage_contracts["c-1"].status, ContractStatus::Pending);
+    apply(&mut chain, &Transaction::new_accept_storage_contract_signed(&node_key, "c-1".into(), 1, 0));
+    assert_eq!((chain.state.stakes[&node], chain.state.storage_contracts["c-1"].locked), (0, 300));
+    (chain, node_key, node, owner)
+}
+
+#[test]
+fn merkle_proofs_bind_chunk_and_position() {
+    let hashes = descriptor().chunk_hashes;
+    let root = build_merkle_root(&hashes);
+    for (i, hash) in hashes.iter().enumerate() {
+        let proof = merkle_proof(&hashes, i).unwrap();
+        assert!(verify_merkle_proof(&root, hash, i, &proof));
+        assert!(!verify_merkle_proof(&root, hash, (i + 1) % hashes.len(), &proof));
+    }
+    assert!(merkle_proof(&hashes, hashes.len()).is_none());
+}
+
+#[test]
+fn answered_challenges_pay_the_node() {
+    let (mut chain, node_key, node, _) = contracted();
+    let config = AuditConfig::default();
+    assert!(chain.state.advance_storage_audits(config.interval_blocks, "block-a", &config).is_empty());
+    let challenge = chain.state.storage_contracts["c-1"].challenge.clone().unwrap();
+    let index = challenge.chunk_index;
+
+    // The wrong chunk does not lead to the root.
+    let other = (index as usize + 1) % chunks().len();
+    let mut forged = StorageProof::new("c-1", &descriptor(), index, chunks()[other].clone()).unwrap();
+    let tx = Transaction::new_storage_proof_signed(&node_key, forged.clone(), 1, 1);
+    assert!(validate_transaction_stateful(&tx, &chain.state).is_err());
+    forged.data = chunks()[index as usize].clone();
+    apply(&mut chain, &Transaction::new_storage_proof_signed(&node_key, forged, 1, 1));
+
+    let contract = &chain.state.storage_contracts["c-1"];
+    assert_eq!((contract.passed, contract.escrow, contract.challenge.is_none()), (1, 20, true));
+    assert_eq!(chain.state.get_balance(&node), 1_000 - 300 + 10 - 2);
+
+    // The deadline passes without effect once answered.
+    assert!(chain.state.advance_storage_audits(challenge.deadline, "block-b", &config).is_empty());
+    assert_eq!(chain.state.storage_contracts["c-1"].locked, 300);
+}
+
+#[test]
+fn missed_deadlines_slash_collateral_and_flag_repairs() {
+    let (mut chain, _, node, owner) = contracted();
+    let config = AuditConfig::default();
+    for round in 1..=config.max_failures {
+        let issued = round * config.interval_blocks;
+        chain.state.advance_storage_audits(issued, "block", &config);
+        let deadline = chain.state.storage_contracts["c-1"].challenge.as_ref().unwrap().deadline;
+        assert_eq!(chain.state.advance_storage_audits(deadline, "block", &config), vec!["c-1".to_string()]);
+        assert_eq!(chain.state.storage_contracts["c-1"].locked, 300 - 100 * round as u64);
+    }
+    assert_eq!(chain.state.stakes[&node], 0);
+    assert_eq!(chain.state.storage_repairs["model-weights"], node);
+
+    // The void contract refunds the owner and is no longer audited.
+    let contract = &chain.state.storage_contracts["c-1"];
+    assert_eq!((contract.status, contract.escrow), (ContractStatus::Failed, 0));
+    assert_eq!(chain.state.get_balance(&owner), 1_000 - 1);
+    assert!(chain.state.advance_storage_audits(100 * config.interval_blocks, "block", &config).is_empty());
+}
+
+#[test]
+fn contracts_need_backing_stake() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (owner_key, owner) = funded_key(&mut chain);
+    let contract = StorageContract::new("c-1", &owner, "node", &descriptor(), 10, 3, 300);
+    let tx = Transaction::new_open_storage_contract_signed(&owner_key, contract, 1, 0);
+    let err = validate_transaction_stateful(&tx, &chain.state).unwrap_err();
+    assert!(err.to_string().contains("stake"), "{}", err);
+}
+
+#[test]
+fn nodes_must_accept_contracts_with_stake_not_already_locked() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let (owner_key, owner) = funded_key(&mut chain);
+    let (node_key, node) = funded_key(&mut chain);
+    let (stranger_key, _) = funded_key(&mut chain);
+    chain.state.stake_tokens(&node, 300).unwrap();
+    for (nonce, id) in ["c-1", "c-2"].into_iter().enumerate() {
+        let contract = StorageContract::new(id, &owner, &node, &descriptor(), 10, 3, 300);
+        apply(&mut chain, &Transaction::new_open_storage_contract_signed(&owner_key, contract, 1, nonce as u64));
+    }
+
+    // Unaccepted contracts are not audited, and only the node accepts.
+    let config = AuditConfig::default();
+    chain.state.advance_storage_audits(config.interval_blocks, "block", &config);
+    assert!(chain.state.storage_contracts["c-1"].challenge.is_none());
+    let hijack = Transaction::new_accept_storage_contract_signed(&stranger_key, "c-1".into(), 1, 0);
+    assert!(validate_transaction_stateful(&hijack, &chain.state).is_err());
+
+    // One stake backs one contract's collateral at a time.
+    apply(&mut chain, &Transaction::new_accept_storage_contract_signed(&node_key, "c-1".into(), 1, 0));
+    let second = Transaction::new_accept_storage_contract_signed(&node_key, "c-2".into(), 1, 1);
+    let err = validate_transaction_stateful(&second, &chain.state).unwrap_err();
+    assert!(err.to_string().contains("stake"), "{}", err);
+    assert!(chain.state.unstake_tokens(&node, 1).is_err());
+
+    // The contract left pending expires and refunds its owner.
+    let before = chain.state.get_balance(&owner);
+    chain.state.advance_storage_audits(config.interval_blocks + config.accept_blocks, "block", &config);
+    let expired = &chain.state.storage_contracts["c-2"];
+    assert_eq!((expired.status, expired.escrow), (ContractStatus::Failed, 0));
+    assert_eq!(chain.state.get_balance(&owner), before + 30);
+    assert_eq!(chain.state.storage_contracts["c-1"].status, ContractStatus::Active);
+}
+
+#[test]
+fn completed_contracts_unlock_the_collateral() {
+    let (mut chain, node_key, node, _) = contracted();
+    let config = AuditConfig::default();
+    for round in 1..=3u32 {
+        chain.state.advance_storage_audits(round * config.interval_blocks, "block", &config);
+        let index = chain.state.storage_contracts["c-1"].challenge.as_ref().unwrap().chunk_index;
+        let proof = StorageProof::new("c-1", &descriptor(), index, chunks()[index as usize].clone()).unwrap();
+        apply(&mut chain, &Transaction::new_storage_proof_signed(&node_key, proof, 1, round as u64));
+    }
+    let contract = &chain.state.storage_contracts["c-1"];
+    assert_eq!((contract.status, contract.locked), (ContractStatus::Completed, 0));
+    assert_eq!(chain.state.stakes[&node], 300);
+}
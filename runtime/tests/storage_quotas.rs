@@ -0,0 +1,76 @@
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::distributed_storage::StorageContract;
+use runtime::large_data_transfer::{
+    LargeDataDescriptor, Namespace, Permissions, QuotaError, StorageQuota, TenantAccounts,
+    TenantUsage,
+};
+
+fn descriptor(id: &str, size: u64) -> LargeDataDescriptor {
+    LargeDataDescriptor::new(id.into(), id.into(), size, vec!["00".repeat(32)])
+}
+
+/// A namespace with `/free` and `/paid`, free-tier owners capped at 100
+/// bytes and `carol` unlimited.
+fn accounts() -> (Namespace, TenantAccounts) {
+    let mut ns = Namespace::new(Permissions::public("root"));
+    ns.mkdir("/free", None).unwrap();
+    ns.mkdir("/paid/models", None).unwrap();
+    let mut accounts = TenantAccounts::new(StorageQuota::unlimited().with_max_bytes_stored(100));
+    accounts.set_quota("carol", StorageQuota::unlimited());
+    (ns, accounts)
+}
+
+#[test]
+fn stores_past_the_quota_are_refused() {
+    let (mut ns, mut accounts) = accounts();
+    accounts.store_file(&mut ns, "/free/a", "alice", descriptor("a", 60)).unwrap();
+    let err = accounts.store_file(&mut ns, "/free/b", "alice", descriptor("b", 50)).unwrap_err();
+    assert_eq!(
+        err,
+        QuotaError::StorageExceeded { owner: "alice".into(), used: 60, requested: 50, limit: 100 }
+    );
+    assert!(ns.file("/free/b").is_err());
+
+    // Replacing a file only needs room for the difference.
+    accounts.store_file(&mut ns, "/free/a", "alice", descriptor("a2", 90)).unwrap();
+    assert_eq!((accounts.usage("alice").bytes_stored, accounts.usage("alice").files), (90, 1));
+
+    accounts.store_file(&mut ns, "/paid/models/big", "carol", descriptor("c", 10_000)).unwrap();
+    assert_eq!(accounts.usage("carol").bytes_stored, 10_000);
+}
+
+#[test]
+fn charges_follow_moved_files_and_end_with_them() {
+    let (mut ns, mut accounts) = accounts();
+    accounts.store_file(&mut ns, "/paid/models/v1", "bob", descriptor("v1", 40)).unwrap();
+    accounts.store_file(&mut ns, "/paid/models/v2", "bob", descriptor("v2", 50)).unwrap();
+    accounts.rename(&mut ns, "/paid/models", "/paid/archive").unwrap();
+    assert_eq!(ns.file("/paid/archive/v1").unwrap().descriptor.size_bytes, 40);
+
+    accounts.remove(&mut ns, "/paid/archive/v1").unwrap();
+    assert_eq!(accounts.usage("bob").bytes_stored, 50);
+    accounts.remove(&mut ns, "/paid").unwrap();
+    accounts.record_served("bob", 4096);
+    assert_eq!(
+        accounts.usage("bob"),
+        TenantUsage { bytes_stored: 0, files: 0, bytes_served: 4096, active_contracts: 0 }
+    );
+}
+
+#[test]
+fn active_contracts_are_counted_and_capped() {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    for id in ["c-1", "c-2"] {
+        let contract = StorageContract::new(id, "alice", "node", &descriptor(id, 64), 5, 2, 0);
+        chain.state.storage_contracts.insert(id.into(), contract);
+    }
+    let (_, mut accounts) = accounts();
+    accounts.set_quota("alice", StorageQuota::unlimited().with_max_active_contracts(2));
+    accounts.sync_contracts(&chain.state);
+    assert_eq!(accounts.usage("alice").active_contracts, 2);
+    assert!(matches!(
+        accounts.check_contract("alice"),
+        Err(QuotaError::ContractsExceeded { active: 2, limit: 2, .. })
+    ));
+    assert!(accounts.check_contract("bob").is_ok());
+}
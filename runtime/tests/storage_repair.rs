@@ -0,0 +1,123 @@
+use runtime::blockchain::validation::validate_transaction_stateful;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::distributed_storage::{
+    plan_repairs, AuditConfig, ContractStatus, RepairConfig, StorageContract, StorageNode,
+};
+use runtime::large_data_transfer::{ChunkId, LargeDataDescriptor};
+use schnorrkel::{Keypair, SecretKey};
+use std::collections::HashMap;
+
+const NOW: u64 = 10_000;
+
+fn descriptor() -> LargeDataDescriptor {
+    let hashes = (0..3u8).map(|i| ChunkId::from_data(&[i; 32]).0).collect();
+    LargeDataDescriptor::new("dataset".into(), "content".into(), 96, hashes)
+}
+
+fn node(id: &str, last_seen: u64, reliability_score: f32) -> StorageNode {
+    StorageNode {
+        node_id: id.into(),
+        address: format!("{}:9000", id),
+        capacity: 1 << 30,
+        used_space: 0,
+        last_seen,
+        reliability_score,
+    }
+}
+
+fn apply(chain: &mut Blockchain, tx: &Transaction) {
+    validate_transaction_stateful(tx, &chain.state).unwrap();
+    chain.state.apply_transaction(tx).unwrap();
+}
+
+/// Two contracts of one object wanting two replicas, on nodes `a` and `b`.
+fn contracted() -> (Blockchain, SecretKey, String) {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    let owner_key = Keypair::generate().secret.clone();
+    let owner = hex::encode(owner_key.to_public().to_bytes());
+    chain.state.set_balance(&owner, 1_000);
+    for id in ["a", "b", "c", "d"] {
+        chain.state.set_balance(id, 100);
+        chain.state.stake_tokens(id, 50).unwrap();
+    }
+    for (nonce, (id, node)) in [("c-1", "a"), ("c-2", "b")].into_iter().enumerate() {
+        let contract =
+            StorageContract::new(id, &owner, node, &descriptor(), 5, 4, 50).with_target_replicas(2);
+        let tx = Transaction::new_open_storage_contract_signed(&owner_key, contract, 1, nonce as u64);
+        apply(&mut chain, &tx);
+        chain.state.accept_storage_contract(node, id).unwrap();
+    }
+    (chain, owner_key, owner)
+}
+
+fn descriptors() -> HashMap<String, LargeDataDescriptor> {
+    HashMap::from([("dataset".to_string(), descriptor())])
+}
+
+#[test]
+fn healthy_objects_need_no_repair() {
+    let (chain, _, _) = contracted();
+    let nodes = [node("a", NOW, 0.9), node("b", NOW, 0.9), node("c", NOW, 0.9)];
+    assert!(plan_repairs(&chain.state, &descriptors(), &nodes, NOW, &RepairConfig::default()).is_empty());
+}
+
+#[test]
+fn silent_nodes_are_replaced_by_the_most_reliable_staked_node() {
+    let (mut chain, owner_key, owner) = contracted();
+    chain.state.set_balance("rich", 100);
+    let nodes = [
+        node("a", NOW, 0.9),
+        node("b", NOW - 600, 0.9),
+        node("c", NOW, 0.5),
+        node("d", NOW, 0.8),
+        // Most reliable, but without stake to back the collateral.
+        node("rich", NOW, 1.0),
+    ];
+    let plans = plan_repairs(&chain.state, &descriptors(), &nodes, NOW, &RepairConfig::default());
+    assert_eq!(plans.len(), 1);
+    let plan = &plans[0];
+    assert_eq!((plan.contract_id.as_str(), plan.failed_node.as_str()), ("c-2", "b"));
+    assert_eq!(plan.new_node, "d");
+    assert_eq!(plan.chunks.len(), 3);
+
+    // Moving the contract escrows the new node's instalments.
+    let before = chain.state.get_balance(&owner);
+    let tx = Transaction::new_repair_storage_contract_signed(&owner_key, "c-2".into(), "d".into(), 4, 1, 2);
+    apply(&mut chain, &tx);
+    let contract = &chain.state.storage_contracts["c-2"];
+    assert_eq!((contract.node.as_str(), contract.escrow, contract.status), ("d", 40, ContractStatus::Pending));
+    assert_eq!(chain.state.get_balance(&owner), before - 20 - 1);
+    // The old node gets its collateral back; the new one locks its own.
+    assert_eq!(chain.state.stakes["b"], 50);
+    assert_eq!(chain.state.accept_storage_contract("d", "c-2"), Some(50));
+    assert!(plan_repairs(&chain.state, &descriptors(), &nodes, NOW, &RepairConfig::default()).is_empty());
+}
+
+#[test]
+fn failed_audits_trigger_repair_and_the_flag_clears() {
+    let (mut chain, owner_key, _) = contracted();
+    let nodes = [node("a", NOW, 0.9), node("b", NOW, 0.9), node("c", NOW, 0.7)];
+    let config = AuditConfig::default();
+    chain.state.advance_storage_audits(config.interval_blocks, "block", &config);
+    chain.state.storage_contracts.get_mut("c-2").unwrap().challenge = None;
+    let deadline = chain.state.storage_contracts["c-1"].challenge.as_ref().unwrap().deadline;
+    assert_eq!(chain.state.advance_storage_audits(deadline, "block", &config), vec!["c-1".to_string()]);
+
+    let plans = plan_repairs(&chain.state, &descriptors(), &nodes, NOW, &RepairConfig::default());
+    assert_eq!(plans.iter().map(|p| p.new_node.as_str()).collect::<Vec<_>>(), vec!["c"]);
+
+    // Only the owner may move a contract, and only to a staked node.
+    let stranger = Keypair::generate().secret.clone();
+    let hijack = Transaction::new_repair_storage_contract_signed(&stranger, "c-1".into(), "c".into(), 4, 1, 0);
+    assert!(validate_transaction_stateful(&hijack, &chain.state).is_err());
+    let unstaked =
+        Transaction::new_repair_storage_contract_signed(&owner_key, "c-1".into(), "nobody".into(), 4, 1, 2);
+    assert!(validate_transaction_stateful(&unstaked, &chain.state).is_err());
+
+    let tx = Transaction::new_repair_storage_contract_signed(&owner_key, "c-1".into(), "c".into(), 4, 1, 2);
+    apply(&mut chain, &tx);
+    chain.state.accept_storage_contract("c", "c-1").unwrap();
+    let contract = &chain.state.storage_contracts["c-1"];
+    assert_eq!((contract.status, contract.failed), (ContractStatus::Active, 0));
+    assert!(chain.state.storage_repairs.is_empty());
+}
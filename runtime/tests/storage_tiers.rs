@@ -0,0 +1,90 @@
+use runtime::distributed_storage::{
+    LifecyclePolicy, MigrationEngine, NodeMetrics, StorageClass, TierConfig,
+};
+use runtime::large_data_transfer::ChunkId;
+use std::time::Duration;
+
+const DAY: u64 = 24 * 3600;
+
+fn node(id: &str, reputation: f32) -> NodeMetrics {
+    NodeMetrics {
+        node_id: id.into(),
+        reputation,
+        free_capacity: 0.8,
+        latency_ms: 20,
+        region: id.into(),
+        energy_score: 0.5,
+        utilisation: 0.2,
+    }
+}
+
+#[test]
+fn lifecycle_policies_pick_the_class_by_age() {
+    let policy = LifecyclePolicy::default();
+    let class_at = |days: u64| policy.class_at(Duration::from_secs(days * DAY));
+    assert_eq!(class_at(0), StorageClass::Hot);
+    assert_eq!(class_at(6), StorageClass::Hot);
+    assert_eq!(class_at(7), StorageClass::Warm);
+    assert_eq!(class_at(89), StorageClass::Warm);
+    assert_eq!(class_at(400), StorageClass::Cold);
+    assert_eq!(
+        LifecyclePolicy::pinned(StorageClass::Warm).class_at(Duration::MAX),
+        StorageClass::Warm
+    );
+
+    // Colder classes are cheaper per byte stored.
+    let tiers = TierConfig::default();
+    let gib = 1 << 30;
+    let price = |class| tiers.class(class).quote(100 * gib).price_bcai;
+    assert!(price(StorageClass::Hot) > price(StorageClass::Warm));
+    assert!(price(StorageClass::Warm) > price(StorageClass::Cold));
+    assert!(tiers.cold.expected_latency > tiers.hot.expected_latency);
+}
+
+#[test]
+fn objects_migrate_to_nodes_of_their_next_class() {
+    let mut engine = MigrationEngine::new(TierConfig::default());
+    let mut metrics = Vec::new();
+    for (id, class) in
+        [("h1", StorageClass::Hot), ("h2", StorageClass::Hot), ("h3", StorageClass::Hot)]
+            .into_iter()
+            .chain(["w1", "w2", "w3"].map(|id| (id, StorageClass::Warm)))
+            .chain(["c1", "c2", "c3"].map(|id| (id, StorageClass::Cold)))
+    {
+        engine.set_node_tier(id, class);
+        metrics.push(node(id, if id.ends_with('3') { 0.9 } else { 0.6 }));
+    }
+    let chunks = vec![ChunkId::from_data(b"a"), ChunkId::from_data(b"b")];
+    let nodes = vec!["h1".to_string(), "h2".into(), "h3".into()];
+    engine.track("dataset", chunks.clone(), 1000, LifecyclePolicy::default(), 0, nodes);
+    engine.track(
+        "pinned",
+        chunks.clone(),
+        10,
+        LifecyclePolicy::pinned(StorageClass::Hot),
+        0,
+        vec![],
+    );
+
+    assert!(engine.plan(6 * DAY, &metrics).is_empty());
+    let plan = engine.plan(8 * DAY, &metrics);
+    assert_eq!(plan.len(), 1);
+    let migration = &plan[0];
+    assert_eq!((migration.from, migration.to), (StorageClass::Hot, StorageClass::Warm));
+    assert_eq!(migration.chunks, chunks);
+    assert_eq!(migration.targets, vec!["w3".to_string(), "w1".into()]);
+    engine.complete(migration);
+    assert_eq!(engine.object("dataset").unwrap().class, StorageClass::Warm);
+    assert_eq!(engine.usage()[&StorageClass::Warm], 1000);
+
+    // Cold storage erasure codes over six nodes; it waits until there are.
+    assert!(engine.plan(100 * DAY, &metrics).is_empty());
+    for id in ["c4", "c5", "c6"] {
+        engine.set_node_tier(id, StorageClass::Cold);
+        metrics.push(node(id, 0.5));
+    }
+    let plan = engine.plan(100 * DAY, &metrics);
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].targets.len(), 6);
+    assert!(plan[0].targets.iter().all(|id| id.starts_with('c')));
+}
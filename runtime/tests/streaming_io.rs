@@ -0,0 +1,136 @@
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::NetworkTransferCoordinator;
+use runtime::large_data_transfer::{
+    ChunkId, ChunkManager, ChunkSink, ChunkWriter, DataChunk, LargeDataConfig, LargeDataDescriptor,
+    LargeDataResult,
+};
+use futures::future::BoxFuture;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn dataset(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 7) as u8).collect()
+}
+
+async fn write(manager: &Arc<ChunkManager>, data: &[u8]) -> LargeDataDescriptor {
+    let mut writer = manager.open_write("dataset", 1_000).with_compression(CompressionAlgorithm::Lz4);
+    // Written in pieces that straddle chunk boundaries.
+    for piece in data.chunks(333) {
+        writer.write_all(piece).await.unwrap();
+    }
+    assert!(writer.descriptor().is_none());
+    writer.shutdown().await.unwrap();
+    writer.descriptor().unwrap()
+}
+
+#[tokio::test]
+async fn objects_stream_in_and_out_chunk_by_chunk() {
+    let manager = Arc::new(ChunkManager::default());
+    let data = dataset(10_500);
+    let descriptor = write(&manager, &data).await;
+    assert_eq!(descriptor.chunk_hashes.len(), 11);
+    assert_eq!(descriptor.size_bytes, 10_500);
+    assert!(descriptor.chunk_hashes.iter().all(|h| manager.has_chunk(&ChunkId(h.clone()))));
+
+    let mut streamed = Vec::new();
+    manager.open_read(descriptor.clone()).read_to_end(&mut streamed).await.unwrap();
+    assert_eq!(streamed, data);
+
+    // Small reads without read-ahead see the same bytes.
+    let mut reader = manager.open_read(descriptor).with_read_ahead(0);
+    let mut streamed = Vec::new();
+    let mut buf = [0u8; 64];
+    loop {
+        let n = reader.read(&mut buf).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        streamed.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(streamed, data);
+}
+
+#[tokio::test]
+async fn missing_or_tampered_chunks_fail_the_read() {
+    let manager = Arc::new(ChunkManager::default());
+    let data = dataset(3_000);
+    let descriptor = write(&manager, &data).await;
+
+    // A chunk damaged in the cache is caught before it is read.
+    let second = ChunkId(descriptor.chunk_hashes[1].clone());
+    let mut damaged = manager.get_chunk(&second).unwrap();
+    damaged.data[0] ^= 1;
+    manager.store_chunk(damaged).unwrap();
+    let mut out = Vec::new();
+    let err = manager.open_read(descriptor.clone()).read_to_end(&mut out).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    manager.remove_chunk(&second);
+    let mut out = Vec::new();
+    let err = manager.open_read(descriptor).read_to_end(&mut out).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[tokio::test]
+async fn the_coordinator_streams_through_its_cache() {
+    let manager = Arc::new(ChunkManager::default());
+    let coordinator = NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager.clone());
+    let data = dataset(5_000_000);
+    let mut writer = coordinator.open_write("weights");
+    writer.write_all(&data).await.unwrap();
+    writer.shutdown().await.unwrap();
+    let descriptor = writer.descriptor().unwrap();
+    // Two full chunks of the default 2 MB and the rest.
+    assert_eq!(descriptor.chunk_hashes.len(), 3);
+    let first = manager.get_chunk(&ChunkId(descriptor.chunk_hashes[0].clone())).unwrap();
+    assert!(first.info.is_compressed());
+
+    let mut streamed = Vec::new();
+    coordinator.open_read(descriptor).read_to_end(&mut streamed).await.unwrap();
+    assert_eq!(streamed, data);
+}
+
+/// A sink taking a while per chunk, recording the order chunks arrive in
+/// and the most it held at once.
+#[derive(Clone, Default)]
+struct SlowSink {
+    order: Arc<Mutex<Vec<String>>>,
+    active: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl ChunkSink for SlowSink {
+    fn put(&self, chunk: DataChunk) -> BoxFuture<'static, LargeDataResult<()>> {
+        self.order.lock().unwrap().push(chunk.id.0);
+        let (active, peak) = (self.active.clone(), self.peak.clone());
+        Box::pin(async move {
+            peak.fetch_max(active.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn writers_store_chunks_in_parallel_and_in_order() {
+    let data = dataset(20_000);
+    let mut descriptors = Vec::new();
+    for parallelism in [1, 3] {
+        let sink = SlowSink::default();
+        let mut writer = ChunkWriter::new(sink.clone(), "dataset", 1_000).with_parallelism(parallelism);
+        writer.write_all(&data).await.unwrap();
+        writer.shutdown().await.unwrap();
+        let descriptor = writer.descriptor().unwrap();
+        assert_eq!(descriptor.chunk_hashes, *sink.order.lock().unwrap());
+        assert_eq!(sink.active.load(Ordering::SeqCst), 0);
+        let peak = sink.peak.load(Ordering::SeqCst);
+        assert!(peak <= parallelism && (parallelism == 1 || peak > 1), "{peak} at once");
+        descriptors.push(descriptor);
+    }
+    assert_eq!(descriptors[0].chunk_hashes, descriptors[1].chunk_hashes);
+    assert_eq!(descriptors[0].content_hash, descriptors[1].content_hash);
+}
@@ -0,0 +1,120 @@
+use runtime::pouw::task_types::{self, task_kind, DEFAULT_TASK_KIND};
+use runtime::pouw::{generate_task, PoUWConfig, PoUWTask, Solution, TaskType, TaskTypeError, TaskTypeRegistry};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+fn config() -> PoUWConfig {
+    PoUWConfig { min_computation_ms: 0, ..PoUWConfig::default() }
+}
+
+fn task(kind: &str, epochs: u32) -> PoUWTask {
+    generate_task(epochs, 11).with_kind(kind)
+}
+
+/// The reference training run under another name, standing in for an out-of-tree kind of work.
+struct Echo;
+
+impl TaskType for Echo {
+    fn kind(&self) -> &'static str {
+        "echo"
+    }
+
+    fn solve(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TaskTypeError> {
+        Ok(runtime::pouw::solve(task, difficulty))
+    }
+
+    fn verify(&self, task: &PoUWTask, solution: &Solution, difficulty: u32, config: &PoUWConfig) -> bool {
+        runtime::pouw::verify(task, solution, difficulty, config)
+    }
+}
+
+#[test]
+fn defaults_cover_the_built_in_kinds() {
+    let registry = TaskTypeRegistry::with_defaults();
+    assert_eq!(
+        registry.kinds(),
+        vec!["batch-inference", "data-validation", "matrix", "onnx-train", "train"]
+    );
+    assert_eq!(task_kind(&generate_task(1, 1)), DEFAULT_TASK_KIND);
+    assert!(matches!(
+        registry.resolve(&task("render", 1)),
+        Err(TaskTypeError::UnknownKind(kind)) if kind == "render"
+    ));
+}
+
+#[test]
+fn every_built_in_kind_solves_and_verifies() {
+    for kind in ["train", "matrix", "batch-inference", "data-validation"] {
+        let task = task(kind, 16);
+        let solution = task_types::solve(&task, u32::MAX).unwrap();
+        assert!(task_types::verify(&task, &solution, u32::MAX, &config()), "{} did not verify", kind);
+    }
+}
+
+#[test]
+fn solutions_do_not_verify_as_another_kind() {
+    let matrix_task = task("matrix", 8);
+    let solution = task_types::solve(&matrix_task, u32::MAX).unwrap();
+    let as_inference = PoUWTask { kind: Some("batch-inference".into()), ..matrix_task };
+    assert!(!task_types::verify(&as_inference, &solution, u32::MAX, &config()));
+}
+
+#[test]
+fn freivalds_rejects_a_wrong_product() {
+    let task = task("matrix", 8);
+    let mut solution = task_types::solve(&task, u32::MAX).unwrap();
+
+    let mut product = hex::decode(&solution.checkpoints[0]).unwrap();
+    product[0] ^= 1;
+    // Reseal so only the product, not its hash, is wrong.
+    solution.trained_model_hash = hex::encode(Sha256::digest(&product));
+    solution.checkpoints = vec![hex::encode(&product)];
+    solution.nonce = runtime::pouw::solver::find_nonce(
+        &task,
+        &hex::decode(&solution.trained_model_hash).unwrap().try_into().unwrap(),
+        u32::MAX,
+    );
+    assert!(!task_types::verify(&task, &solution, u32::MAX, &config()));
+}
+
+#[test]
+fn inference_and_validation_results_are_recomputed() {
+    for kind in ["batch-inference", "data-validation"] {
+        let task = task(kind, 32);
+        let mut solution = task_types::solve(&task, u32::MAX).unwrap();
+        solution.accuracy ^= 1;
+        assert!(!task_types::verify(&task, &solution, u32::MAX, &config()), "{} accepted a wrong accuracy", kind);
+    }
+
+    let mut unrooted = task("data-validation", 1);
+    unrooted.dataset_root = None;
+    assert!(matches!(task_types::solve(&unrooted, u32::MAX), Err(TaskTypeError::InvalidTask(_))));
+}
+
+#[test]
+fn onnx_training_needs_committed_model_and_data() {
+    let mut task = task("onnx-train", 8);
+    assert!(matches!(task_types::solve(&task, u32::MAX), Err(TaskTypeError::InvalidTask(_))));
+
+    task.model_hash = Some("ab".repeat(32));
+    task.dataset_hash = Some("cd".repeat(32));
+    let solution = task_types::solve(&task, u32::MAX).unwrap();
+    assert!(task_types::verify(&task, &solution, u32::MAX, &config()));
+
+    let mut forged = solution.clone();
+    let last = forged.checkpoints.len() - 1;
+    forged.checkpoints[last] = runtime::pouw::training::encode_weights(&[1, 2]);
+    assert!(!task_types::verify(&task, &forged, u32::MAX, &config()));
+}
+
+#[test]
+fn new_kinds_register_without_touching_consensus() {
+    let mut registry = TaskTypeRegistry::with_defaults();
+    registry.register(Arc::new(Echo)).unwrap();
+    assert_eq!(registry.register(Arc::new(Echo)), Err(TaskTypeError::Duplicate("echo".into())));
+
+    let task = task("echo", 4);
+    let echo = registry.resolve(&task).unwrap();
+    let solution = echo.solve(&task, u32::MAX).unwrap();
+    assert!(echo.verify(&task, &solution, u32::MAX, &config()));
+}
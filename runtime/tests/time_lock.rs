@@ -0,0 +1,119 @@
+use runtime::blockchain::constants::MAX_DEFERRED_PER_SENDER;
+use runtime::blockchain::transaction::{TimeLock, Transaction};
+use runtime::blockchain::{Blockchain, BlockchainConfig};
+use runtime::miner;
+use schnorrkel::{Keypair, SecretKey};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A transfer of `amount` unlocked at block `height`.
+fn locked(sender: &SecretKey, amount: u64, nonce: u64, height: u64) -> Transaction {
+    let mut tx = Transaction::new(String::new(), "bob".into(), amount, 1, nonce)
+        .with_valid_after(TimeLock::BlockHeight(height));
+    tx.sign(sender);
+    tx
+}
+
+/// A chain with `sender` funded with 1_000.
+fn funded(sender: &Keypair) -> Blockchain {
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    chain.state.set_balance(&hex::encode(sender.public.to_bytes()), 1_000);
+    chain
+}
+
+#[test]
+fn time_locked_transfer_is_deferred_until_eligible() {
+    let sender = Keypair::generate();
+    let recipient = Keypair::generate();
+    let mut chain = Blockchain::new(BlockchainConfig::default());
+    chain.state.set_balance(&hex::encode(sender.public.to_bytes()), 1_000);
+
+    let unlock_at = chrono::Utc::now().timestamp() + 3_600;
+    let mut tx = Transaction::new(
+        String::new(),
+        hex::encode(recipient.public.to_bytes()),
+        10,
+        1,
+        0,
+    )
+    .with_valid_after(TimeLock::Timestamp(unlock_at));
+    tx.sign(&sender.secret);
+    assert!(tx.verify_signature());
+
+    chain.add_transaction(tx.clone()).unwrap();
+    assert_eq!(chain.get_stats().pending_txs, 0);
+    assert_eq!(chain.get_stats().deferred_txs, 1);
+
+    assert_eq!(chain.promote_deferred(unlock_at - 1), 0);
+    assert_eq!(chain.promote_deferred(unlock_at), 1);
+    assert_eq!(chain.get_pending_transactions(10), vec![tx]);
+    assert!(chain.deferred_transactions.is_empty());
+}
+
+#[test]
+fn time_lock_is_covered_by_signature() {
+    let sender = Keypair::generate();
+    let mut tx = Transaction::new(String::new(), "bob".into(), 5, 0, 0)
+        .with_valid_after(TimeLock::BlockHeight(10));
+    tx.sign(&sender.secret);
+    assert!(!tx.is_eligible(9, 0));
+    assert!(tx.is_eligible(10, 0));
+
+    tx.valid_after = Some(TimeLock::BlockHeight(1));
+    assert!(!tx.verify_signature());
+    tx.valid_after = None;
+    assert!(!tx.verify_signature());
+}
+
+#[tokio::test]
+async fn adding_a_block_promotes_what_it_unlocks() {
+    let sender = Keypair::generate();
+    let mut chain = funded(&sender);
+    let tx = locked(&sender.secret, 10, 0, 2);
+    chain.add_transaction(tx.clone()).unwrap();
+    assert_eq!(chain.deferred_transactions, vec![tx.clone()]);
+
+    let chain = Arc::new(Mutex::new(chain));
+    let mut block = miner::mine_block(
+        "miner".into(),
+        chain.clone(),
+        Arc::new(Mutex::new(HashSet::new())),
+        Arc::new(Mutex::new(VecDeque::new())),
+    )
+    .await
+    .unwrap();
+    // The verifier rejects solutions computed in under 100ms.
+    block.solution.computation_time_ms = block.solution.computation_time_ms.max(100);
+    block.hash = block.calculate_hash();
+    let mut chain = chain.lock().await;
+    chain.add_block(block).unwrap();
+
+    assert!(chain.deferred_transactions.is_empty());
+    assert_eq!(chain.get_pending_transactions(10), vec![tx]);
+}
+
+#[test]
+fn only_transactions_that_could_apply_are_deferred() {
+    let sender = Keypair::generate();
+    let mut chain = funded(&sender);
+
+    // A nonce already pending, or already deferred, is refused.
+    let mut pending = Transaction::new(String::new(), "bob".into(), 10, 1, 0);
+    pending.sign(&sender.secret);
+    chain.add_transaction(pending).unwrap();
+    assert!(chain.add_transaction(locked(&sender.secret, 10, 0, 5)).is_err());
+    chain.add_transaction(locked(&sender.secret, 10, 1, 5)).unwrap();
+    assert!(chain.add_transaction(locked(&sender.secret, 20, 1, 5)).is_err());
+
+    // So is a transfer the sender cannot cover with what it already deferred.
+    assert!(chain.add_transaction(locked(&sender.secret, 990, 2, 5)).is_err());
+
+    // Each sender has a bounded share of the pool.
+    for nonce in 2..=MAX_DEFERRED_PER_SENDER as u64 {
+        chain.add_transaction(locked(&sender.secret, 10, nonce, 5)).unwrap();
+    }
+    assert_eq!(chain.deferred_transactions.len(), MAX_DEFERRED_PER_SENDER);
+    let over = locked(&sender.secret, 10, MAX_DEFERRED_PER_SENDER as u64 + 1, 5);
+    assert!(chain.add_transaction(over).is_err());
+}
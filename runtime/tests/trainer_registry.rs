@@ -0,0 +1,78 @@
+use runtime::pouw::{generate_task, verifier, PoUWConfig, PoUWTask, Solution};
+use runtime::trainer::{model_type, ModelBackend, Trainer, TrainerError, TrainerRegistry, DEFAULT_MODEL_TYPE};
+use std::sync::Arc;
+
+fn task(model_id: &str, epochs: u32) -> PoUWTask {
+    let mut task = generate_task(epochs, 7);
+    task.model_id = model_id.to_string();
+    task
+}
+
+/// Seals a fixed model hash, standing in for an out-of-tree model type.
+struct Constant;
+
+impl ModelBackend for Constant {
+    fn model_type(&self) -> &'static str {
+        "constant"
+    }
+
+    fn train(&self, task: &PoUWTask, difficulty: u32) -> Result<Solution, TrainerError> {
+        let mut solution = runtime::pouw::solve(task, difficulty);
+        solution.accuracy = 5_000;
+        Ok(solution)
+    }
+}
+
+#[test]
+fn model_type_comes_from_the_model_id_prefix() {
+    assert_eq!(model_type(&task("mlp:digits", 1)), "mlp");
+    assert_eq!(model_type(&task("boosting:a:b", 1)), "boosting");
+    assert_eq!(model_type(&task("model_5", 1)), DEFAULT_MODEL_TYPE);
+}
+
+#[test]
+fn defaults_cover_the_built_in_model_types() {
+    let registry = TrainerRegistry::with_defaults();
+    assert_eq!(registry.model_types(), vec!["boosting", "linear", "mlp"]);
+    assert!(matches!(
+        registry.resolve(&task("cnn:digits", 1)),
+        Err(TrainerError::UnknownModelType(kind)) if kind == "cnn"
+    ));
+}
+
+#[test]
+fn unprefixed_tasks_train_the_verifiable_reference_model() {
+    let task = task("model_20", 20);
+    let output = Trainer::new("node").execute(&task).unwrap();
+    assert_eq!(output.model_type, "linear");
+    assert!(!output.solution.checkpoints.is_empty());
+    let config = PoUWConfig { min_computation_ms: 0, ..PoUWConfig::default() };
+    assert!(verifier::verify_by_retraining(&task, &output.solution, u32::MAX, &config, usize::MAX));
+}
+
+#[test]
+fn plugin_models_learn_the_task_and_are_reproducible() {
+    let trainer = Trainer::new("node");
+    for kind in ["mlp", "boosting"] {
+        let task = task(&format!("{}:toy", kind), 50);
+        let output = trainer.execute(&task).unwrap();
+        assert_eq!(output.model_type, kind);
+        assert!(output.solution.accuracy > 8_000, "{} accuracy {}", kind, output.solution.accuracy);
+        assert!(output.solution.checkpoints.is_empty());
+        assert_eq!(output.metrics["accuracy"], output.solution.accuracy as f64 / 10_000.0);
+
+        let again = trainer.execute(&task).unwrap();
+        assert_eq!(again.solution.trained_model_hash, output.solution.trained_model_hash);
+    }
+}
+
+#[test]
+fn new_model_types_register_without_touching_the_solver() {
+    let mut registry = TrainerRegistry::with_defaults();
+    registry.register(Arc::new(Constant)).unwrap();
+    assert_eq!(registry.register(Arc::new(Constant)), Err(TrainerError::Duplicate("constant".into())));
+
+    let trainer = Trainer::with_registry("node", Arc::new(registry));
+    let output = trainer.execute(&task("constant:x", 1)).unwrap();
+    assert_eq!((output.model_type.as_str(), output.solution.accuracy), ("constant", 5_000));
+}
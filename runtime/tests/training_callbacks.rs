@@ -0,0 +1,107 @@
+use runtime::large_data_transfer::ChunkManager;
+use runtime::pouw::{generate_task, PoUWTask};
+use runtime::trainer::callbacks::{
+    Control, DfsCheckpoint, EarlyStopping, EpochEnd, EpochMetrics, LrSchedule, LrScheduler, Monitor, TrainingCallback,
+};
+use runtime::trainer::Trainer;
+use std::sync::Arc;
+
+fn task(model_id: &str, epochs: u32) -> PoUWTask {
+    let mut task = generate_task(epochs, 7);
+    task.model_id = model_id.to_string();
+    task
+}
+
+/// Remembers every epoch it sees.
+#[derive(Default)]
+struct Log(Vec<EpochMetrics>);
+
+impl TrainingCallback for Log {
+    fn on_epoch_end(&mut self, end: &mut EpochEnd<'_>) -> Control {
+        self.0.push(end.metrics);
+        Control::Continue
+    }
+}
+
+fn end_of(epoch: u32, loss: f64, learning_rate: &mut f64) -> EpochEnd<'_> {
+    EpochEnd {
+        metrics: EpochMetrics { epoch, loss, accuracy: 1.0 - loss, learning_rate: *learning_rate },
+        learning_rate,
+        weights: &[],
+    }
+}
+
+#[test]
+fn schedules_decay_from_the_initial_rate() {
+    assert_eq!(LrSchedule::Constant.rate(0.5, 9), 0.5);
+    let step = LrSchedule::Step { every: 2, factor: 0.5 };
+    assert_eq!((step.rate(1.0, 1), step.rate(1.0, 2), step.rate(1.0, 3), step.rate(1.0, 5)), (1.0, 1.0, 0.5, 0.25));
+    assert_eq!(LrSchedule::Exponential { gamma: 0.5 }.rate(1.0, 3), 0.25);
+    let cosine = LrSchedule::Cosine { epochs: 10, min_lr: 0.1 };
+    assert_eq!(cosine.rate(1.0, 1), 1.0);
+    assert!((cosine.rate(1.0, 6) - 0.55).abs() < 1e-12);
+    assert_eq!(cosine.rate(1.0, 50), 0.1);
+}
+
+#[test]
+fn early_stopping_waits_out_its_patience() {
+    let mut stopping = EarlyStopping::new(Monitor::Loss, 2).with_min_delta(0.01);
+    let mut lr = 0.1;
+    let losses = [1.0, 0.5, 0.495, 0.6];
+    let controls: Vec<Control> = losses
+        .iter()
+        .enumerate()
+        .map(|(i, loss)| stopping.on_epoch_end(&mut end_of(i as u32 + 1, *loss, &mut lr)))
+        .collect();
+    assert_eq!(controls, vec![Control::Continue, Control::Continue, Control::Continue, Control::Stop]);
+    assert_eq!((stopping.best(), stopping.stopped_epoch()), (Some(0.5), Some(4)));
+}
+
+#[test]
+fn mlp_training_stops_early_and_reproducibly() {
+    let trainer = Trainer::new("node");
+    let task = task("mlp:toy", 2_000);
+    let run = || {
+        let mut stopping = EarlyStopping::new(Monitor::Accuracy, 20);
+        let mut log = Log::default();
+        let output = trainer.execute_with_callbacks(&task, &mut [&mut stopping, &mut log]).unwrap();
+        (output, stopping.stopped_epoch(), log.0.len())
+    };
+    let (output, stopped, epochs) = run();
+    let stopped = stopped.expect("accuracy plateaus well before 2000 epochs");
+    assert_eq!(epochs as u32, stopped);
+    assert!(output.solution.accuracy > 8_000);
+    assert_eq!(run().0.solution.trained_model_hash, output.solution.trained_model_hash);
+}
+
+#[test]
+fn schedulers_set_the_next_epochs_rate() {
+    let mut scheduler = LrScheduler::new(LrSchedule::Step { every: 2, factor: 0.1 });
+    let mut log = Log::default();
+    Trainer::new("node").execute_with_callbacks(&task("mlp:toy", 5), &mut [&mut scheduler, &mut log]).unwrap();
+    let rates: Vec<f64> = log.0.iter().map(|m| m.learning_rate).collect();
+    assert_eq!(rates.len(), 5);
+    assert_eq!(rates[..2], [0.5, 0.5]);
+    assert!((rates[2] - 0.05).abs() < 1e-12 && (rates[4] - 0.005).abs() < 1e-12);
+}
+
+#[test]
+fn checkpoints_are_written_to_the_chunk_store() {
+    let manager = Arc::new(ChunkManager::default());
+    let mut checkpoint = DfsCheckpoint::new(manager.clone(), 10);
+    Trainer::new("node").execute_with_callbacks(&task("mlp:toy", 30), &mut [&mut checkpoint]).unwrap();
+    let epochs: Vec<u32> = checkpoint.checkpoints().iter().map(|(epoch, _)| *epoch).collect();
+    assert_eq!(epochs, vec![10, 20, 30]);
+    // 8 hidden units: two input weights, a bias and an output weight each,
+    // plus the output bias.
+    let weights = DfsCheckpoint::load(&manager, checkpoint.latest().unwrap()).unwrap();
+    assert_eq!(weights.len(), 33);
+}
+
+#[test]
+fn backends_without_epochs_ignore_callbacks() {
+    let mut log = Log::default();
+    let output = Trainer::new("node").execute_with_callbacks(&task("model_5", 5), &mut [&mut log]).unwrap();
+    assert_eq!(output.model_type, "linear");
+    assert!(log.0.is_empty());
+}
@@ -0,0 +1,80 @@
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::network::{NetworkTransferCoordinator, TransferEvent};
+use runtime::large_data_transfer::{
+    ChunkManager, DataChunk, LargeDataConfig, LargeDataDescriptor, TransferPriority,
+};
+use std::sync::Arc;
+
+fn chunks(n: u32) -> Vec<DataChunk> {
+    (0..n)
+        .map(|i| {
+            DataChunk::new_from_slice(vec![i as u8; 4096], i, CompressionAlgorithm::None).unwrap()
+        })
+        .collect()
+}
+
+fn descriptor(id: &str, chunks: &[DataChunk]) -> LargeDataDescriptor {
+    let hashes = chunks.iter().map(|c| c.id.as_str().to_string()).collect();
+    LargeDataDescriptor::new(id.into(), id.into(), 4096 * chunks.len() as u64, hashes)
+}
+
+#[tokio::test]
+async fn subscribers_see_every_chunk_of_a_transfer() {
+    let chunks = chunks(3);
+    let manager = Arc::new(ChunkManager::default());
+    for chunk in &chunks {
+        manager.store_chunk(chunk.clone()).unwrap();
+    }
+    let coordinator =
+        NetworkTransferCoordinator::new("local".into(), LargeDataConfig::default(), manager);
+    let mut events = coordinator.subscribe();
+    let dropped = coordinator.subscribe();
+    drop(dropped);
+
+    coordinator
+        .transfer_large_data_with_priority(
+            descriptor("dataset", &chunks),
+            vec![],
+            TransferPriority::Low,
+        )
+        .await
+        .unwrap();
+
+    let mut seen = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        seen.push(event);
+    }
+    assert_eq!(seen.len(), 5);
+    assert!(matches!(&seen[0], TransferEvent::Started(p) if p.chunks_completed == 0));
+    for (i, event) in seen[1..4].iter().enumerate() {
+        let TransferEvent::Progress(progress) = event else { panic!("{event:?}") };
+        assert_eq!(progress.chunks_completed, i as u32 + 1);
+        assert_eq!(progress.bytes_completed, 4096 * (i as u64 + 1));
+        assert!(progress.eta.is_some());
+    }
+    let TransferEvent::Completed(done) = &seen[4] else { panic!("{:?}", seen[4]) };
+    assert_eq!((done.chunks_completed, done.total_chunks), (3, 3));
+    assert_eq!(done.priority, TransferPriority::Low);
+    assert_eq!(done.peers["local"], 3 * 4096);
+    assert_eq!(done.fraction(), 1.0);
+    assert!(done.bar(10).starts_with("dataset [##########] 100% 3/3 chunks"));
+}
+
+#[tokio::test]
+async fn stalled_transfers_are_reported() {
+    let chunks = chunks(2);
+    let coordinator = NetworkTransferCoordinator::new(
+        "local".into(),
+        LargeDataConfig::default(),
+        Arc::new(ChunkManager::default()),
+    );
+    let mut events = coordinator.subscribe();
+    assert!(coordinator.transfer_large_data(descriptor("model", &chunks), vec![]).await.is_err());
+
+    assert!(matches!(events.try_recv().unwrap(), TransferEvent::Started(_)));
+    let TransferEvent::Stalled(stalled) = events.try_recv().unwrap() else { panic!() };
+    assert_eq!((stalled.chunks_completed, stalled.total_chunks), (0, 2));
+    assert_eq!(stalled.eta, None);
+    assert!(stalled.bar(4).contains("[----]   0%"));
+    assert!(events.try_recv().is_err());
+}
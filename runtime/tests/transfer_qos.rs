@@ -0,0 +1,57 @@
+use runtime::large_data_transfer::network::TransferScheduler;
+use runtime::large_data_transfer::{QosConfig, TransferPriority};
+use std::time::{Duration, Instant};
+
+#[test]
+fn critical_transfers_preempt_bulk_ones_until_they_starve() {
+    let start = Instant::now();
+    let config = QosConfig { starvation_after: Duration::from_secs(5), ..QosConfig::default() };
+    let mut scheduler = TransferScheduler::new(config);
+    scheduler.register("dataset", TransferPriority::Low, start);
+    scheduler.register("model", TransferPriority::Normal, start);
+    assert!(scheduler.try_run("dataset", start) && scheduler.try_run("model", start));
+
+    // Block sync arrives: both bulk transfers wait, a high one does not.
+    scheduler.register("blocks", TransferPriority::Critical, start);
+    scheduler.register("checkpoint", TransferPriority::High, start);
+    assert_eq!(scheduler.preempted(), vec!["dataset".to_string(), "model".to_string()]);
+    assert_eq!(scheduler.preemptions(), 2);
+    assert!(scheduler.try_run("blocks", start) && scheduler.try_run("checkpoint", start));
+    assert!(!scheduler.try_run("dataset", start + Duration::from_secs(1)));
+
+    // A starving transfer gets one chunk, then waits again.
+    let later = start + Duration::from_secs(5);
+    assert!(scheduler.is_starving("model", later));
+    assert!(scheduler.try_run("model", later));
+    assert!(!scheduler.try_run("model", later + Duration::from_secs(1)));
+    assert_eq!(scheduler.starvation_grants(), 1);
+
+    // Once the critical transfer is done only the low one stays paused.
+    scheduler.remove("blocks");
+    assert_eq!(scheduler.preempted(), vec!["dataset".to_string()]);
+    assert!(scheduler.try_run("model", later));
+    scheduler.remove("checkpoint");
+    assert!(scheduler.preempted().is_empty());
+}
+
+#[test]
+fn running_classes_split_the_cap_by_reservation() {
+    let now = Instant::now();
+    let mut scheduler = TransferScheduler::new(QosConfig::default());
+    scheduler.register("a", TransferPriority::Normal, now);
+    assert_eq!(scheduler.share("a", 0), None);
+    assert_eq!(scheduler.share("a", 1_000), Some(1_000));
+
+    // Reservations of idle classes go to the running ones in proportion.
+    scheduler.register("b", TransferPriority::High, now);
+    scheduler.register("c", TransferPriority::High, now);
+    assert_eq!(scheduler.share("a", 4_000), Some(1_500));
+    assert_eq!(scheduler.share("b", 4_000), Some(1_250));
+
+    // A preempted transfer let through keeps its own class's reservation.
+    scheduler.register("d", TransferPriority::Low, now);
+    assert!(scheduler.is_preempted("d"));
+    assert_eq!(scheduler.share("d", 5_000), Some(1_000));
+    assert_eq!(scheduler.share("a", 5_000), Some(1_875));
+    assert_eq!(scheduler.share("unknown", 5_000), None);
+}
@@ -0,0 +1,113 @@
+use runtime::evaluator::plugin::{plugin_hash, EvaluatorPlugin, PluginError};
+use runtime::job::Job;
+
+/// Scores the share of expected bytes the outputs reproduce.
+#[cfg(feature = "wasm-evaluators")]
+const MATCHING_BYTES: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 1024))
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "evaluate") (param $out i32) (param $out_len i32) (param $exp i32) (param $exp_len i32) (result i32)
+    (local $i i32) (local $hits i32)
+    (if (i32.eqz (local.get $exp_len)) (then (return (i32.const 0))))
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_u (local.get $i) (local.get $exp_len)))
+        (if (i32.and
+              (i32.lt_u (local.get $i) (local.get $out_len))
+              (i32.eq (i32.load8_u (i32.add (local.get $out) (local.get $i)))
+                      (i32.load8_u (i32.add (local.get $exp) (local.get $i)))))
+          (then (local.set $hits (i32.add (local.get $hits) (i32.const 1)))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (i32.div_u (i32.mul (local.get $hits) (i32.const 10000)) (local.get $exp_len))))
+"#;
+
+/// A plugin whose `evaluate` has the given body.
+fn plugin(body: &str) -> Vec<u8> {
+    wat::parse_str(format!(
+        r#"(module
+             (memory (export "memory") 1)
+             (func (export "alloc") (param i32) (result i32) (i32.const 0))
+             (func (export "evaluate") (param i32 i32 i32 i32) (result i32) {}))"#,
+        body
+    ))
+    .unwrap()
+}
+
+#[test]
+fn jobs_commit_to_their_plugin_by_hash() {
+    let wasm = plugin("(i32.const 1)");
+    let job = Job::new(1, "m".into(), "d".into(), 1).with_evaluator(&wasm);
+    assert_eq!(job.evaluator, Some(plugin_hash(&wasm)));
+    assert_eq!(Job::new(2, "m".into(), "d".into(), 1).evaluator, None);
+}
+
+#[cfg(not(feature = "wasm-evaluators"))]
+#[test]
+fn plugins_need_the_feature() {
+    assert_eq!(EvaluatorPlugin::load(&plugin("(i32.const 1)")).unwrap_err(), PluginError::Unsupported);
+}
+
+#[cfg(feature = "wasm-evaluators")]
+#[test]
+fn plugins_score_outputs_against_expected_data() {
+    let plugin = EvaluatorPlugin::load(&wat::parse_str(MATCHING_BYTES).unwrap()).unwrap();
+    let score = plugin.score(b"abcd", b"abcf", 1_000_000).unwrap();
+    assert_eq!(score.score_bps, 7_500);
+    assert!(score.gas_used > 0);
+    assert_eq!(plugin.score(b"", b"xyz", 1_000_000).unwrap().score_bps, 0);
+}
+
+#[cfg(feature = "wasm-evaluators")]
+#[test]
+fn plugins_run_sandboxed() {
+    let load = |wat: &str| EvaluatorPlugin::load(&wat::parse_str(wat).unwrap()).map(|_| ());
+    assert!(matches!(
+        load(r#"(module (import "env" "now" (func)) (memory (export "memory") 1))"#),
+        Err(PluginError::ForbiddenImport(name)) if name == "env::now"
+    ));
+    assert_eq!(load(r#"(module (memory (export "memory") 1))"#), Err(PluginError::MissingExport("alloc")));
+    assert!(matches!(EvaluatorPlugin::load(b"not wasm"), Err(PluginError::Invalid(_))));
+
+    let spin = EvaluatorPlugin::load(&plugin("(loop $l (br $l)) (i32.const 0)")).unwrap();
+    assert_eq!(spin.score(b"", b"", 10_000), Err(PluginError::OutOfGas(10_000)));
+
+    // Growing memory past the cap fails inside the plugin.
+    let greedy = EvaluatorPlugin::load(&plugin("(memory.grow (i32.const 1000))")).unwrap();
+    assert_eq!(greedy.score(b"", b"", 10_000), Err(PluginError::InvalidScore(-1)));
+
+    let generous = EvaluatorPlugin::load(&plugin("(i32.const 20000)")).unwrap();
+    assert_eq!(generous.score(b"", b"", 10_000), Err(PluginError::InvalidScore(20_000)));
+}
+
+#[cfg(feature = "wasm-evaluators")]
+#[test]
+fn evaluators_sign_plugin_scores() {
+    use runtime::evaluator::plugin::PluginRegistry;
+    use runtime::evaluator::Evaluator;
+    use runtime::pouw::evaluation::verify_evaluation;
+
+    let wasm = wat::parse_str(MATCHING_BYTES).unwrap();
+    let job = Job::new(9, "custom:ocr".into(), "scans".into(), 1).with_evaluator(&wasm);
+    let evaluator = Evaluator::new("validator");
+    let key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+    assert!(matches!(
+        evaluator.evaluate_outputs(&job, b"ab", b"ab", &key),
+        Err(PluginError::NotFound(_))
+    ));
+
+    assert_eq!(evaluator.install_plugin(&wasm).unwrap(), job.evaluator.clone().unwrap());
+    let evaluation = evaluator.evaluate_outputs(&job, b"ab", b"ax", &key).unwrap();
+    assert_eq!((evaluation.task_id.as_str(), evaluation.accuracy), ("9", 5_000));
+    assert!(verify_evaluation(&evaluation));
+
+    let mut registry = PluginRegistry::new();
+    assert_eq!(registry.install(&wasm).unwrap(), registry.install(&wasm).unwrap());
+    assert!(registry.remove(job.evaluator.as_deref().unwrap()));
+}
@@ -0,0 +1,113 @@
+#[cfg(feature = "wasm-jobs")]
+use runtime::data::Sample;
+use runtime::wasm_vm::{Capabilities, WasmVm, WasmVmConfig, WasmVmError};
+#[cfg(feature = "wasm-jobs")]
+use std::sync::Arc;
+
+/// Sums the features of every sample with `tensor_add`, reports how many
+/// samples are labelled 1 and outputs the sums.
+#[cfg(feature = "wasm-jobs")]
+const SUM_FEATURES: &str = r#"
+(module
+  (import "bcai" "dataset_len" (func $len (result i32)))
+  (import "bcai" "dataset_read" (func $read (param i32 i32) (result i32)))
+  (import "bcai" "tensor_add" (func $add (param i32 i32 i32 i32)))
+  (import "bcai" "report_metric" (func $metric (param i32 i32 f64)))
+  (import "bcai" "write_output" (func $output (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "positives")
+  (func (export "train") (result i32)
+    (local $i i32) (local $positives i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.ge_s (local.get $i) (call $len)))
+        (local.set $positives (i32.add (local.get $positives) (call $read (local.get $i) (i32.const 128))))
+        (call $add (i32.const 64) (i32.const 128) (i32.const 64) (i32.const 2))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $next)))
+    (call $metric (i32.const 0) (i32.const 9) (f64.convert_i32_s (local.get $positives)))
+    (call $output (i32.const 64) (i32.const 8))
+    (i32.const 0)))
+"#;
+
+/// A module whose `train` has the given body.
+fn module(body: &str) -> Vec<u8> {
+    wat::parse_str(format!(r#"(module (memory (export "memory") 1) (func (export "train") (result i32) {}))"#, body))
+        .unwrap()
+}
+
+#[cfg(feature = "wasm-jobs")]
+fn dataset() -> Arc<[Sample]> {
+    vec![
+        Sample { features: vec![1.0, 2.0], label: 1 },
+        Sample { features: vec![3.0, 4.0], label: 0 },
+        Sample { features: vec![5.0, 6.0], label: 1 },
+    ]
+    .into()
+}
+
+#[test]
+fn capabilities_grant_host_functions() {
+    assert_eq!(Capabilities::default().functions(), vec!["write_output"]);
+    let dataset_only = Capabilities { dataset: true, ..Capabilities::default() };
+    assert!(dataset_only.functions().contains(&"dataset_read"));
+    assert!(!dataset_only.functions().contains(&"tensor_matmul"));
+    assert_eq!(Capabilities::all().functions().len(), 8);
+}
+
+#[cfg(not(feature = "wasm-jobs"))]
+#[test]
+fn modules_need_the_feature() {
+    let vm = WasmVm::load(&module("(i32.const 0)"), WasmVmConfig::default());
+    assert_eq!(vm.unwrap_err(), WasmVmError::Unsupported);
+}
+
+#[cfg(feature = "wasm-jobs")]
+#[test]
+fn modules_train_through_the_host_api() {
+    let vm = WasmVm::load(&wat::parse_str(SUM_FEATURES).unwrap(), WasmVmConfig::default()).unwrap();
+    let report = vm.run("train", dataset()).unwrap();
+    assert_eq!(report.metric("positives"), Some(2.0));
+    let sums: Vec<f32> = report.output.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+    assert_eq!(sums, vec![9.0, 12.0]);
+    assert!(report.fuel_used > 0);
+
+    assert_eq!(vm.run("evaluate", dataset()).unwrap_err(), WasmVmError::MissingExport("evaluate".into()));
+}
+
+#[cfg(feature = "wasm-jobs")]
+#[test]
+fn imports_are_limited_to_granted_capabilities() {
+    let dataset_only = Capabilities { dataset: true, ..Capabilities::default() };
+    let config = WasmVmConfig { capabilities: dataset_only, ..WasmVmConfig::default() };
+    assert_eq!(
+        WasmVm::load(&wat::parse_str(SUM_FEATURES).unwrap(), config).unwrap_err(),
+        WasmVmError::ForbiddenImport("bcai::tensor_add".into())
+    );
+    let foreign = wat::parse_str(r#"(module (import "env" "now" (func)) (memory (export "memory") 1))"#).unwrap();
+    assert_eq!(
+        WasmVm::load(&foreign, WasmVmConfig::default()).unwrap_err(),
+        WasmVmError::ForbiddenImport("env::now".into())
+    );
+    let no_memory = wat::parse_str(r#"(module (func (export "train") (result i32) (i32.const 0)))"#).unwrap();
+    assert_eq!(
+        WasmVm::load(&no_memory, WasmVmConfig::default()).unwrap_err(),
+        WasmVmError::MissingExport("memory".into())
+    );
+}
+
+#[cfg(feature = "wasm-jobs")]
+#[test]
+fn runs_are_metered_and_memory_capped() {
+    let config = WasmVmConfig { fuel: 10_000, max_memory_bytes: 2 << 16, ..WasmVmConfig::default() };
+
+    let spin = WasmVm::load(&module("(loop $l (br $l)) (i32.const 0)"), config.clone()).unwrap();
+    assert_eq!(spin.run("train", dataset()).unwrap_err(), WasmVmError::OutOfFuel(10_000));
+
+    // Growing past two pages fails, and the module reports it as its exit code.
+    let grow = WasmVm::load(&module("(memory.grow (i32.const 4))"), config.clone()).unwrap();
+    assert_eq!(grow.run("train", dataset()).unwrap_err(), WasmVmError::Exit(-1));
+
+    let trap = WasmVm::load(&module("(unreachable)"), config).unwrap();
+    assert!(matches!(trap.run("train", dataset()), Err(WasmVmError::Trap(_))));
+}
@@ -0,0 +1,122 @@
+use prost::Message;
+use runtime::blockchain::transaction::TimeLock;
+use runtime::blockchain::{Blockchain, BlockchainConfig, Transaction};
+use runtime::wire::format::{self, WireFormat};
+use runtime::wire::{
+    decode, encode, proto, Feature, Hello, WireError, WireMessage, BINCODE_VERSION, HANDSHAKE_VERSION,
+    PROTOCOL_VERSION,
+};
+
+#[test]
+fn peers_settle_on_the_highest_common_version() {
+    let ours = Hello::new(1, 3, [Feature::TypedGossip, Feature::OnionRouting]);
+    let mut theirs = Hello::new(2, 4, [Feature::TypedGossip, Feature::PayloadTransfer]);
+    // A feature from a newer build is carried along but never agreed on.
+    theirs.features.insert("zk-proofs".into());
+
+    let session = ours.negotiate(&theirs).unwrap();
+    assert_eq!(session.version, 3);
+    assert_eq!(theirs.negotiate(&ours).unwrap(), session);
+    assert!(session.supports(Feature::TypedGossip));
+    assert!(!session.supports(Feature::OnionRouting));
+    assert_eq!(session.features.len(), 1);
+
+    let old = Hello::new(1, 1, []);
+    assert_eq!(
+        theirs.negotiate(&old),
+        Err(WireError::Incompatible { local_min: 2, local_max: 4, remote_min: 1, remote_max: 1 })
+    );
+}
+
+#[test]
+fn hellos_survive_the_wire() {
+    let hello = Hello::default();
+    let bytes = hello.encode();
+    assert_eq!(bytes[0], HANDSHAKE_VERSION);
+    assert_eq!(Hello::decode(&bytes), Ok(hello));
+    assert_eq!(Hello::decode(&[]), Err(WireError::Empty));
+    assert_eq!(Hello::decode(&[PROTOCOL_VERSION]), Err(WireError::UnsupportedVersion(PROTOCOL_VERSION)));
+}
+
+#[test]
+fn envelopes_carry_their_version() {
+    let session = Hello::default().negotiate(&Hello::default()).unwrap();
+    let bytes = session.encode(&WireMessage::GetBlocks { from_height: 7 }).unwrap();
+    assert_eq!(bytes[0], PROTOCOL_VERSION);
+    let (version, message) = decode(&bytes).unwrap();
+    assert_eq!(version, PROTOCOL_VERSION);
+    assert!(matches!(message, WireMessage::GetBlocks { from_height: 7 }));
+
+    // A newer format is refused rather than misread.
+    let mut future = bytes.clone();
+    future[0] = PROTOCOL_VERSION + 1;
+    assert_eq!(decode(&future).unwrap_err(), WireError::UnsupportedVersion(PROTOCOL_VERSION + 1));
+    assert!(matches!(encode(PROTOCOL_VERSION + 1, &WireMessage::Ping), Err(WireError::UnsupportedVersion(_))));
+    assert!(matches!(decode(&bytes[..1]), Err(WireError::Malformed(_))));
+}
+
+#[test]
+fn blocks_round_trip_through_protobuf_and_legacy_bincode() {
+    let chain = Blockchain::new(BlockchainConfig::default());
+    let mut block = chain.get_tip().clone();
+    let tx = Transaction::new("alice".into(), "bob".into(), 5, 1, 3).with_valid_after(TimeLock::Timestamp(-4));
+    block.transactions.push(tx);
+    let message = WireMessage::Blocks(vec![block.clone()]);
+
+    let current = encode(PROTOCOL_VERSION, &message).unwrap();
+    let legacy = encode(BINCODE_VERSION, &message).unwrap();
+    assert_eq!(legacy[0], BINCODE_VERSION);
+    for bytes in [&current, &legacy] {
+        match decode(bytes).unwrap().1 {
+            WireMessage::Blocks(blocks) => assert_eq!(blocks, vec![block.clone()]),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+    assert!(current.len() < serde_json::to_vec(&message).unwrap().len());
+}
+
+#[test]
+fn newer_fields_and_messages_are_tolerated() {
+    // A field under a tag this build does not know is skipped.
+    let mut bytes = vec![PROTOCOL_VERSION];
+    proto::Envelope::from(&WireMessage::GetBlocks { from_height: 9 }).encode(&mut bytes).unwrap();
+    bytes.extend([0xa0, 0x06, 0x01]); // field 100, varint 1
+    assert!(matches!(decode(&bytes).unwrap().1, WireMessage::GetBlocks { from_height: 9 }));
+
+    // A message kind this build does not know is refused, not misread.
+    let unknown = [PROTOCOL_VERSION, 0xa2, 0x06, 0x00]; // field 100, empty message
+    assert!(matches!(decode(&unknown), Err(WireError::Malformed(_))));
+
+    let mut task = proto::Block::from(Blockchain::new(BlockchainConfig::default()).get_tip());
+    task.task.as_mut().unwrap().challenge.pop();
+    let mut short = vec![PROTOCOL_VERSION];
+    proto::Envelope { body: Some(proto::Body::Block(task)) }.encode(&mut short).unwrap();
+    assert!(matches!(decode(&short), Err(WireError::Malformed(_))));
+}
+
+#[test]
+fn every_format_frames_every_message() {
+    let chain = Blockchain::new(BlockchainConfig::default());
+    let messages = [
+        WireMessage::Ping,
+        WireMessage::Pong,
+        WireMessage::GetBlocks { from_height: 3 },
+        WireMessage::Transaction(Transaction::new("alice".into(), "bob".into(), 5, 1, 3)),
+        WireMessage::Block(chain.get_tip().clone()),
+        WireMessage::Blocks(vec![chain.get_tip().clone(); 2]),
+    ];
+    for format in WireFormat::all() {
+        for message in &messages {
+            let framed = format::frame(format, message).unwrap();
+            assert_eq!(WireFormat::detect(&framed).unwrap().0, format);
+            let decoded: WireMessage = format::unframe(&framed).unwrap();
+            assert_eq!(format.encode(&decoded).unwrap(), format.encode(message).unwrap());
+        }
+    }
+    // Protobuf frames are the envelopes of the versioned protocol.
+    let block = WireMessage::Block(chain.get_tip().clone());
+    assert_eq!(format::frame(WireFormat::Protobuf, &block).unwrap(), encode(PROTOCOL_VERSION, &block).unwrap());
+    assert_eq!(format::frame(WireFormat::Bincode, &block).unwrap(), encode(BINCODE_VERSION, &block).unwrap());
+    assert!(WireFormat::all().contains(&format::SELECTED));
+    assert_eq!(WireFormat::detect(&[0x7f]).unwrap_err(), WireError::UnsupportedVersion(0x7f));
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "zk-proofs")]
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use runtime::pouw::zk::{self, ZkError, ZkVerifyingKey, MAX_EPOCHS};
+use runtime::pouw::{generate_task, solve, training, PoUWConfig};
+
+const DIFFICULTY: u32 = 0x0FFF_FFFF;
+
+fn config() -> PoUWConfig {
+    PoUWConfig { min_computation_ms: 0, ..PoUWConfig::default() }
+}
+
+#[test]
+fn only_small_tasks_are_provable() {
+    let mut rng = StdRng::seed_from_u64(1);
+    assert!(matches!(zk::setup(0, &mut rng), Err(ZkError::Unprovable(0))));
+    assert!(matches!(zk::setup(MAX_EPOCHS + 1, &mut rng), Err(ZkError::Unprovable(_))));
+}
+
+/// One setup covers every case, as it dominates the running time.
+#[test]
+fn light_clients_verify_training_without_re_executing_it() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let (pk, vk) = zk::setup(1, &mut rng).unwrap();
+    let task = generate_task(1, 7);
+
+    let solution = zk::solve_with_proof(&pk, &task, DIFFICULTY, &mut rng);
+    assert!(solution.zk_proof.is_some());
+    assert!(zk::verify_succinct(&task, &solution, DIFFICULTY, &config(), &vk));
+
+    // Keys travel to light clients encoded.
+    let vk = ZkVerifyingKey::decode(&vk.encode()).unwrap();
+    assert!(zk::verify_succinct(&task, &solution, DIFFICULTY, &config(), &vk));
+
+    // Without a proof, or with keys for another size, nothing is accepted.
+    let unproven = solve(&task, DIFFICULTY);
+    assert!(!zk::verify_succinct(&task, &unproven, DIFFICULTY, &config(), &vk));
+    let larger = generate_task(2, 7);
+    assert!(matches!(zk::prove(&pk, &larger, &mut rng), Err(ZkError::KeyMismatch { keys: 1, task: 2 })));
+
+    // The proof binds the task's dataset and the final weights.
+    let other_task = generate_task(1, 8);
+    let mut replayed = solve(&other_task, DIFFICULTY);
+    replayed.zk_proof = solution.zk_proof.clone();
+    assert!(!zk::verify_succinct(&other_task, &replayed, DIFFICULTY, &config(), &vk));
+
+    let mut forged = solution.clone();
+    let mut weights = training::decode_weights(forged.checkpoints.last().unwrap()).unwrap();
+    weights[0] += 1;
+    *forged.checkpoints.last_mut().unwrap() = training::encode_weights(&weights);
+    assert!(!zk::verify_succinct(&task, &forged, DIFFICULTY, &config(), &vk));
+}
@@ -0,0 +1,72 @@
+use super::metrics::FairnessReport;
+use crate::ml::model_registry::ModelVersion;
+use serde::{Deserialize, Serialize};
+
+/// Reason a model version failed the fairness gate.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FairnessViolation {
+    /// The version has never been evaluated for fairness.
+    NotEvaluated,
+    /// A required protected attribute is absent from the latest report.
+    MissingAttribute(String),
+    DemographicParity { attribute: String, difference: f64, limit: f64 },
+    EqualizedOdds { attribute: String, difference: f64, limit: f64 },
+}
+
+/// Thresholds a model version must meet before promotion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FairnessGate {
+    pub max_demographic_parity_difference: f64,
+    pub max_equalized_odds_difference: f64,
+    /// Attributes that must appear in the report.
+    #[serde(default)]
+    pub required_attributes: Vec<String>,
+}
+
+impl Default for FairnessGate {
+    fn default() -> Self {
+        Self {
+            max_demographic_parity_difference: 0.1,
+            max_equalized_odds_difference: 0.1,
+            required_attributes: Vec::new(),
+        }
+    }
+}
+
+impl FairnessGate {
+    /// Checks the most recent fairness report of `version`.
+    pub fn check(&self, version: &ModelVersion) -> Result<(), Vec<FairnessViolation>> {
+        match version.fairness_reports.last() {
+            Some(report) => self.check_report(report),
+            None => Err(vec![FairnessViolation::NotEvaluated]),
+        }
+    }
+
+    pub fn check_report(&self, report: &FairnessReport) -> Result<(), Vec<FairnessViolation>> {
+        let mut violations: Vec<FairnessViolation> = self
+            .required_attributes
+            .iter()
+            .filter(|name| report.attribute(name).is_none())
+            .map(|name| FairnessViolation::MissingAttribute(name.clone()))
+            .collect();
+        for attr in &report.attributes {
+            let dp = attr.demographic_parity_difference();
+            if dp > self.max_demographic_parity_difference {
+                violations.push(FairnessViolation::DemographicParity {
+                    attribute: attr.attribute.clone(),
+                    difference: dp,
+                    limit: self.max_demographic_parity_difference,
+                });
+            }
+            let eo = attr.equalized_odds_difference();
+            if eo > self.max_equalized_odds_difference {
+                violations.push(FairnessViolation::EqualizedOdds {
+                    attribute: attr.attribute.clone(),
+                    difference: eo,
+                    limit: self.max_equalized_odds_difference,
+                });
+            }
+        }
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+}
@@ -0,0 +1,121 @@
+use super::metrics::{AttributeFairness, FairnessReport, GroupStats};
+use crate::ml::model_registry::ModelVersion;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FairnessError {
+    #[error("row {row} has no usable value in column `{column}`")]
+    MissingColumn { row: usize, column: String },
+    #[error("evaluation set is empty")]
+    EmptyEvaluationSet,
+}
+
+/// A binary classifier under evaluation.
+pub trait Predictor {
+    fn predict(&self, row: &Map<String, Value>) -> bool;
+}
+
+impl<F: Fn(&Map<String, Value>) -> bool> Predictor for F {
+    fn predict(&self, row: &Map<String, Value>) -> bool {
+        self(row)
+    }
+}
+
+/// Labelled rows the model is evaluated on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationSet {
+    pub rows: Vec<Map<String, Value>>,
+    /// Boolean (or 0/1) ground-truth column.
+    pub label_column: String,
+}
+
+/// Maps a dataset column onto the groups of a protected attribute.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProtectedAttribute {
+    /// Attribute name used in reports, e.g. `gender`.
+    pub name: String,
+    pub column: String,
+    /// Raw column value -> group label, e.g. bucketing ages. Values not in
+    /// the map are used as-is; an empty map uses raw values throughout.
+    #[serde(default)]
+    pub groups: HashMap<String, String>,
+}
+
+impl ProtectedAttribute {
+    pub fn new(name: &str, column: &str) -> Self {
+        Self { name: name.to_string(), column: column.to_string(), groups: HashMap::new() }
+    }
+
+    pub fn map_group(mut self, value: &str, group: &str) -> Self {
+        self.groups.insert(value.to_string(), group.to_string());
+        self
+    }
+
+    fn group_of(&self, row: &Map<String, Value>) -> Option<String> {
+        let raw = match row.get(&self.column)? {
+            Value::String(s) => s.clone(),
+            Value::Null => return None,
+            other => other.to_string(),
+        };
+        Some(self.groups.get(&raw).cloned().unwrap_or(raw))
+    }
+}
+
+/// Runs models over an evaluation set and computes per-attribute fairness.
+#[derive(Debug, Clone, Default)]
+pub struct FairnessEvaluator {
+    pub attributes: Vec<ProtectedAttribute>,
+}
+
+impl FairnessEvaluator {
+    pub fn new(attributes: Vec<ProtectedAttribute>) -> Self {
+        Self { attributes }
+    }
+
+    pub fn evaluate(&self, model: &dyn Predictor, data: &EvaluationSet) -> Result<FairnessReport, FairnessError> {
+        if data.rows.is_empty() {
+            return Err(FairnessError::EmptyEvaluationSet);
+        }
+        let mut per_attribute: Vec<BTreeMap<String, GroupStats>> = vec![BTreeMap::new(); self.attributes.len()];
+        for (i, row) in data.rows.iter().enumerate() {
+            let actual = match row.get(&data.label_column) {
+                Some(Value::Bool(b)) => *b,
+                Some(Value::Number(n)) => n.as_f64().is_some_and(|v| v != 0.0),
+                _ => return Err(FairnessError::MissingColumn { row: i, column: data.label_column.clone() }),
+            };
+            let predicted = model.predict(row);
+            for (attribute, groups) in self.attributes.iter().zip(per_attribute.iter_mut()) {
+                let group = attribute
+                    .group_of(row)
+                    .ok_or_else(|| FairnessError::MissingColumn { row: i, column: attribute.column.clone() })?;
+                groups.entry(group).or_default().record(predicted, actual);
+            }
+        }
+        Ok(FairnessReport {
+            evaluated_at: Utc::now(),
+            samples: data.rows.len() as u64,
+            attributes: self
+                .attributes
+                .iter()
+                .zip(per_attribute)
+                .map(|(a, groups)| AttributeFairness { attribute: a.name.clone(), groups })
+                .collect(),
+        })
+    }
+
+    /// Evaluates and records the report on the model version.
+    pub fn evaluate_version(
+        &self,
+        version: &mut ModelVersion,
+        model: &dyn Predictor,
+        data: &EvaluationSet,
+    ) -> Result<FairnessReport, FairnessError> {
+        let report = self.evaluate(model, data)?;
+        version.fairness_reports.push(report.clone());
+        Ok(report)
+    }
+}
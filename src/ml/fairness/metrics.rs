@@ -0,0 +1,119 @@
+use crate::ml::model_card::BiasAudit;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Confusion counts and derived rates for one protected group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GroupStats {
+    pub true_positives: u64,
+    pub false_positives: u64,
+    pub true_negatives: u64,
+    pub false_negatives: u64,
+}
+
+impl GroupStats {
+    pub fn record(&mut self, predicted: bool, actual: bool) {
+        match (predicted, actual) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, false) => self.true_negatives += 1,
+            (false, true) => self.false_negatives += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.true_positives + self.false_positives + self.true_negatives + self.false_negatives
+    }
+
+    /// P(ŷ = 1).
+    pub fn selection_rate(&self) -> Option<f64> {
+        ratio(self.true_positives + self.false_positives, self.total())
+    }
+
+    /// P(ŷ = 1 | y = 1).
+    pub fn true_positive_rate(&self) -> Option<f64> {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+
+    /// P(ŷ = 1 | y = 0).
+    pub fn false_positive_rate(&self) -> Option<f64> {
+        ratio(self.false_positives, self.false_positives + self.true_negatives)
+    }
+}
+
+fn ratio(num: u64, den: u64) -> Option<f64> {
+    (den > 0).then(|| num as f64 / den as f64)
+}
+
+/// Largest pairwise gap of a rate across groups; groups where the rate is
+/// undefined (no samples) are skipped.
+fn spread(groups: &BTreeMap<String, GroupStats>, rate: impl Fn(&GroupStats) -> Option<f64>) -> f64 {
+    let rates: Vec<f64> = groups.values().filter_map(rate).collect();
+    if rates.len() < 2 {
+        return 0.0;
+    }
+    let max = rates.iter().copied().fold(f64::MIN, f64::max);
+    let min = rates.iter().copied().fold(f64::MAX, f64::min);
+    max - min
+}
+
+/// Fairness metrics for one protected attribute.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AttributeFairness {
+    pub attribute: String,
+    pub groups: BTreeMap<String, GroupStats>,
+}
+
+impl AttributeFairness {
+    /// Max difference in selection rate between any two groups.
+    pub fn demographic_parity_difference(&self) -> f64 {
+        spread(&self.groups, GroupStats::selection_rate)
+    }
+
+    /// Max of the TPR and FPR differences between any two groups.
+    pub fn equalized_odds_difference(&self) -> f64 {
+        spread(&self.groups, GroupStats::true_positive_rate)
+            .max(spread(&self.groups, GroupStats::false_positive_rate))
+    }
+
+    /// Lowest selection rate divided by the highest (the "80% rule" ratio).
+    pub fn disparate_impact_ratio(&self) -> Option<f64> {
+        let rates: Vec<f64> = self.groups.values().filter_map(GroupStats::selection_rate).collect();
+        let max = rates.iter().copied().fold(f64::MIN, f64::max);
+        let min = rates.iter().copied().fold(f64::MAX, f64::min);
+        (!rates.is_empty() && max > 0.0).then(|| min / max)
+    }
+}
+
+/// Outcome of one fairness evaluation of a model version.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FairnessReport {
+    pub evaluated_at: DateTime<Utc>,
+    pub samples: u64,
+    pub attributes: Vec<AttributeFairness>,
+}
+
+impl FairnessReport {
+    pub fn attribute(&self, name: &str) -> Option<&AttributeFairness> {
+        self.attributes.iter().find(|a| a.attribute == name)
+    }
+
+    /// Selection rate per group as model-card bias audits, each with the
+    /// given disparity budget.
+    pub fn to_bias_audits(&self, max_disparity: f64) -> Vec<BiasAudit> {
+        self.attributes
+            .iter()
+            .map(|a| BiasAudit {
+                attribute: a.attribute.clone(),
+                metric: "selection_rate".into(),
+                groups: a
+                    .groups
+                    .iter()
+                    .filter_map(|(g, s)| Some((g.clone(), s.selection_rate()?)))
+                    .collect(),
+                max_disparity,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,21 @@
+//! Bias and fairness evaluation.
+//!
+//! The [`FairnessEvaluator`] runs a model over a labelled evaluation set and
+//! groups the predictions by each protected attribute in its column mapping.
+//! Per-group rates feed the demographic parity and equalized odds metrics;
+//! the resulting [`FairnessReport`] is attached to the model version in the
+//! registry, and [`ModelMetadata::promote`] only activates a version whose
+//! latest report passes a [`FairnessGate`].
+//!
+//! [`ModelMetadata::promote`]: crate::ml::model_registry::ModelMetadata::promote
+
+pub mod gate;
+pub mod harness;
+pub mod metrics;
+
+#[cfg(test)]
+mod tests;
+
+pub use gate::{FairnessGate, FairnessViolation};
+pub use harness::{EvaluationSet, FairnessError, FairnessEvaluator, Predictor, ProtectedAttribute};
+pub use metrics::{AttributeFairness, FairnessReport, GroupStats};
@@ -0,0 +1,114 @@
+use super::*;
+use crate::ml::model_registry::{ModelMetadata, ModelVersion, PromotionError};
+use serde_json::{json, Map, Value};
+
+fn row(gender: &str, age: u32, label: bool, score: f64) -> Map<String, Value> {
+    json!({ "gender": gender, "age_band": age, "approved": label, "score": score })
+        .as_object()
+        .unwrap()
+        .clone()
+}
+
+/// Ten applicants per gender; the model approves when `score > 0.5`.
+fn dataset() -> EvaluationSet {
+    let mut rows = Vec::new();
+    for i in 0..10 {
+        let label = i < 5;
+        rows.push(row("f", 1, label, if i < 3 || i == 9 { 0.9 } else { 0.1 }));
+        rows.push(row("m", 2, label, if i < 5 || i >= 8 { 0.9 } else { 0.1 }));
+    }
+    EvaluationSet { rows, label_column: "approved".into() }
+}
+
+fn threshold(row: &Map<String, Value>) -> bool {
+    row["score"].as_f64().unwrap() > 0.5
+}
+
+fn version() -> ModelVersion {
+    ModelVersion {
+        version: "1.0".into(),
+        model_format: "onnx".into(),
+        created_at: chrono::Utc::now(),
+        artifacts: vec![],
+        training_metadata: None,
+        fairness_reports: vec![],
+    }
+}
+
+#[test]
+fn computes_parity_and_equalized_odds() {
+    let evaluator = FairnessEvaluator::new(vec![ProtectedAttribute::new("gender", "gender")]);
+    let report = evaluator.evaluate(&threshold, &dataset()).unwrap();
+    assert_eq!(report.samples, 20);
+
+    let gender = report.attribute("gender").unwrap();
+    // f: 4/10 selected, TPR 3/5, FPR 1/5. m: 7/10 selected, TPR 5/5, FPR 2/5.
+    assert!((gender.demographic_parity_difference() - 0.3).abs() < 1e-9);
+    assert!((gender.equalized_odds_difference() - 0.4).abs() < 1e-9);
+    assert!((gender.disparate_impact_ratio().unwrap() - 4.0 / 7.0).abs() < 1e-9);
+
+    let audits = report.to_bias_audits(0.2);
+    assert!(!audits[0].passed());
+}
+
+#[test]
+fn column_mapping_groups_raw_values() {
+    let age = ProtectedAttribute::new("age", "age_band").map_group("1", "young").map_group("2", "old");
+    let report = FairnessEvaluator::new(vec![age]).evaluate(&threshold, &dataset()).unwrap();
+    let groups: Vec<&String> = report.attribute("age").unwrap().groups.keys().collect();
+    assert_eq!(groups, ["old", "young"]);
+
+    let missing = ProtectedAttribute::new("race", "race");
+    assert!(matches!(
+        FairnessEvaluator::new(vec![missing]).evaluate(&threshold, &dataset()),
+        Err(FairnessError::MissingColumn { row: 0, .. })
+    ));
+}
+
+#[test]
+fn gate_blocks_promotion_until_fair() {
+    let gate = FairnessGate { required_attributes: vec!["gender".into()], ..FairnessGate::default() };
+    let mut version = version();
+    assert_eq!(gate.check(&version), Err(vec![FairnessViolation::NotEvaluated]));
+
+    let evaluator = FairnessEvaluator::new(vec![ProtectedAttribute::new("gender", "gender")]);
+    evaluator.evaluate_version(&mut version, &threshold, &dataset()).unwrap();
+    let violations = gate.check(&version).unwrap_err();
+    assert_eq!(violations.len(), 2);
+
+    let fair = |row: &Map<String, Value>| row["approved"].as_bool().unwrap();
+    evaluator.evaluate_version(&mut version, &fair, &dataset()).unwrap();
+    assert_eq!(version.fairness_reports.len(), 2);
+    assert!(gate.check(&version).is_ok());
+}
+
+#[test]
+fn registry_promotes_only_versions_that_pass_the_gate() {
+    let gate = FairnessGate { required_attributes: vec!["gender".into()], ..FairnessGate::default() };
+    let mut model = ModelMetadata {
+        id: uuid::Uuid::new_v4(),
+        name: "credit".into(),
+        description: String::new(),
+        versions: vec![version()],
+        tags: vec![],
+        owner: "alice".into(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        active_version: None,
+    };
+    assert_eq!(model.promote("2.0", &gate), Err(PromotionError::UnknownVersion("2.0".into())));
+    assert_eq!(
+        model.promote("1.0", &gate),
+        Err(PromotionError::Fairness(vec![FairnessViolation::NotEvaluated]))
+    );
+
+    let evaluator = FairnessEvaluator::new(vec![ProtectedAttribute::new("gender", "gender")]);
+    evaluator.evaluate_version(&mut model.versions[0], &threshold, &dataset()).unwrap();
+    assert!(matches!(model.promote("1.0", &gate), Err(PromotionError::Fairness(v)) if v.len() == 2));
+    assert!(model.active().is_none());
+
+    let fair = |row: &Map<String, Value>| row["approved"].as_bool().unwrap();
+    evaluator.evaluate_version(&mut model.versions[0], &fair, &dataset()).unwrap();
+    model.promote("1.0", &gate).unwrap();
+    assert_eq!(model.active().map(|v| v.version.as_str()), Some("1.0"));
+}
@@ -0,0 +1,212 @@
+use super::error::GatewayError;
+use super::gateway::HttpRequest;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const KEY_HEADER: &str = "x-bcai-key";
+pub const TIMESTAMP_HEADER: &str = "x-bcai-timestamp";
+pub const NONCE_HEADER: &str = "x-bcai-nonce";
+pub const SIGNATURE_HEADER: &str = "x-bcai-signature";
+
+/// Default tolerance between the caller's clock and ours.
+pub const DEFAULT_MAX_SKEW_SECS: i64 = 300;
+
+/// Public description of an issued key. The secret is never part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub key_id: String,
+    pub tenant: String,
+    /// Endpoints this key may call.
+    pub endpoints: HashSet<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub revoked: bool,
+}
+
+/// Returned once at issuance; the registry keeps only its own copy of the
+/// secret for verification.
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+    pub key_id: String,
+    /// Hex-encoded HMAC secret.
+    pub secret: String,
+}
+
+impl IssuedApiKey {
+    /// Signs a request the way clients are expected to.
+    pub fn sign(&self, method: &str, path: &str, timestamp: i64, nonce: &str, body: &[u8]) -> SignedHeaders {
+        let secret = hex::decode(&self.secret).expect("issued secrets are hex");
+        SignedHeaders {
+            key_id: self.key_id.clone(),
+            timestamp,
+            nonce: nonce.to_string(),
+            signature: sign_request(&secret, method, path, timestamp, nonce, body),
+        }
+    }
+}
+
+/// Authentication headers carried by every gateway request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedHeaders {
+    pub key_id: String,
+    pub timestamp: i64,
+    pub nonce: String,
+    /// Hex-encoded HMAC-SHA256 over the canonical request.
+    pub signature: String,
+}
+
+impl SignedHeaders {
+    /// Reads the headers from a request; header names are matched
+    /// case-insensitively.
+    pub fn from_request(request: &HttpRequest) -> Result<Self, GatewayError> {
+        let timestamp = request
+            .header(TIMESTAMP_HEADER)
+            .ok_or(GatewayError::MissingHeader(TIMESTAMP_HEADER))?;
+        Ok(Self {
+            key_id: request.header(KEY_HEADER).ok_or(GatewayError::MissingHeader(KEY_HEADER))?.to_string(),
+            timestamp: timestamp.parse().map_err(|_| GatewayError::MissingHeader(TIMESTAMP_HEADER))?,
+            nonce: request.header(NONCE_HEADER).ok_or(GatewayError::MissingHeader(NONCE_HEADER))?.to_string(),
+            signature: request
+                .header(SIGNATURE_HEADER)
+                .ok_or(GatewayError::MissingHeader(SIGNATURE_HEADER))?
+                .to_string(),
+        })
+    }
+
+    /// Header name/value pairs to attach to an outgoing request.
+    pub fn to_headers(&self) -> Vec<(String, String)> {
+        vec![
+            (KEY_HEADER.to_string(), self.key_id.clone()),
+            (TIMESTAMP_HEADER.to_string(), self.timestamp.to_string()),
+            (NONCE_HEADER.to_string(), self.nonce.clone()),
+            (SIGNATURE_HEADER.to_string(), self.signature.clone()),
+        ]
+    }
+}
+
+/// Canonical string covered by the signature: method, path, timestamp,
+/// nonce and the SHA-256 of the body, newline separated.
+fn canonical_request(method: &str, path: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        method.to_ascii_uppercase(),
+        path,
+        timestamp,
+        nonce,
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+fn mac(secret: &[u8]) -> HmacSha256 {
+    HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length")
+}
+
+/// Hex HMAC-SHA256 signature for a request.
+pub fn sign_request(secret: &[u8], method: &str, path: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    let mut mac = mac(secret);
+    mac.update(canonical_request(method, path, timestamp, nonce, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+struct KeyEntry {
+    key: ApiKey,
+    secret: Vec<u8>,
+    /// Nonces seen inside the skew window, with their request timestamp.
+    nonces: HashMap<String, i64>,
+}
+
+/// Issues tenant API keys and authenticates signed requests.
+pub struct ApiKeyRegistry {
+    keys: HashMap<String, KeyEntry>,
+    max_skew_secs: i64,
+}
+
+impl Default for ApiKeyRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SKEW_SECS)
+    }
+}
+
+impl ApiKeyRegistry {
+    pub fn new(max_skew_secs: i64) -> Self {
+        Self { keys: HashMap::new(), max_skew_secs }
+    }
+
+    /// Issues a new key for `tenant` scoped to `endpoints`.
+    pub fn issue(&mut self, tenant: &str, endpoints: impl IntoIterator<Item = Uuid>) -> IssuedApiKey {
+        let mut secret = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let key = ApiKey {
+            key_id: format!("bk_{}", Uuid::new_v4().simple()),
+            tenant: tenant.to_string(),
+            endpoints: endpoints.into_iter().collect(),
+            created_at: Utc::now(),
+            revoked: false,
+        };
+        let issued = IssuedApiKey { key_id: key.key_id.clone(), secret: hex::encode(&secret) };
+        self.keys.insert(key.key_id.clone(), KeyEntry { key, secret, nonces: HashMap::new() });
+        issued
+    }
+
+    pub fn revoke(&mut self, key_id: &str) -> Result<(), GatewayError> {
+        let entry = self.keys.get_mut(key_id).ok_or_else(|| GatewayError::UnknownKey(key_id.to_string()))?;
+        entry.key.revoked = true;
+        Ok(())
+    }
+
+    pub fn get(&self, key_id: &str) -> Option<&ApiKey> {
+        self.keys.get(key_id).map(|e| &e.key)
+    }
+
+    /// Keys issued to a tenant.
+    pub fn keys_for_tenant(&self, tenant: &str) -> Vec<&ApiKey> {
+        self.keys.values().map(|e| &e.key).filter(|k| k.tenant == tenant).collect()
+    }
+
+    /// Verifies the request signature, freshness and nonce, and that the key
+    /// may call `endpoint_id`. Returns the authenticated key.
+    pub fn authenticate(
+        &mut self,
+        request: &HttpRequest,
+        endpoint_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<ApiKey, GatewayError> {
+        let headers = SignedHeaders::from_request(request)?;
+        let max_skew = self.max_skew_secs.max(0) as u64;
+        let entry = self
+            .keys
+            .get_mut(&headers.key_id)
+            .ok_or_else(|| GatewayError::UnknownKey(headers.key_id.clone()))?;
+        if entry.key.revoked {
+            return Err(GatewayError::RevokedKey(headers.key_id));
+        }
+        if now.timestamp().abs_diff(headers.timestamp) > max_skew {
+            return Err(GatewayError::StaleTimestamp(headers.timestamp));
+        }
+
+        let signature = hex::decode(&headers.signature).map_err(|_| GatewayError::BadSignature)?;
+        let mut mac = mac(&entry.secret);
+        mac.update(
+            canonical_request(&request.method, &request.path, headers.timestamp, &headers.nonce, &request.body)
+                .as_bytes(),
+        );
+        mac.verify_slice(&signature).map_err(|_| GatewayError::BadSignature)?;
+
+        if !entry.key.endpoints.contains(&endpoint_id) {
+            return Err(GatewayError::EndpointNotAllowed { key_id: headers.key_id, endpoint_id });
+        }
+
+        entry.nonces.retain(|_, ts| now.timestamp().abs_diff(*ts) <= max_skew);
+        if entry.nonces.contains_key(&headers.nonce) {
+            return Err(GatewayError::Replay(headers.nonce));
+        }
+        entry.nonces.insert(headers.nonce, headers.timestamp);
+        Ok(entry.key.clone())
+    }
+}
@@ -0,0 +1,64 @@
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum GatewayError {
+    #[error("missing header {0}")]
+    MissingHeader(&'static str),
+    #[error("unknown API key {0}")]
+    UnknownKey(String),
+    #[error("API key {0} has been revoked")]
+    RevokedKey(String),
+    #[error("request signature does not match")]
+    BadSignature,
+    #[error("request timestamp {0} outside the accepted window")]
+    StaleTimestamp(i64),
+    #[error("nonce {0} already used")]
+    Replay(String),
+    #[error("API key {key_id} is not authorised for endpoint {endpoint_id}")]
+    EndpointNotAllowed { key_id: String, endpoint_id: Uuid },
+    #[error("endpoint not found: {0}")]
+    EndpointNotFound(Uuid),
+    #[error("endpoint {0} is not serving")]
+    EndpointUnavailable(Uuid),
+    #[error("no route for {method} {path}")]
+    NoRoute { method: String, path: String },
+    #[error("malformed request body: {0}")]
+    MalformedBody(String),
+}
+
+impl GatewayError {
+    /// HTTP status code reported to the caller.
+    pub fn status(&self) -> u16 {
+        match self {
+            GatewayError::MissingHeader(_)
+            | GatewayError::UnknownKey(_)
+            | GatewayError::RevokedKey(_)
+            | GatewayError::BadSignature
+            | GatewayError::StaleTimestamp(_)
+            | GatewayError::Replay(_) => 401,
+            GatewayError::EndpointNotAllowed { .. } => 403,
+            GatewayError::EndpointNotFound(_) | GatewayError::NoRoute { .. } => 404,
+            GatewayError::EndpointUnavailable(_) => 503,
+            GatewayError::MalformedBody(_) => 400,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum WarmPoolError {
+    #[error("failed to load model {model}: {reason}")]
+    Load { model: String, reason: String },
+    #[error("model {model} needs {bytes} bytes but only {budget} are available")]
+    ExceedsBudget { model: String, bytes: u64, budget: u64 },
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum RouterError {
+    #[error("endpoint {0} has no replicas")]
+    NoReplicas(Uuid),
+    #[error("all {attempts} attempts failed, last error: {last_error}")]
+    AttemptsExhausted { attempts: u32, last_error: String },
+    #[error("retry budget exhausted after {attempts} attempts, last error: {last_error}")]
+    BudgetExhausted { attempts: u32, last_error: String },
+}
@@ -0,0 +1,263 @@
+use super::router::{InferenceRouter, ReplicaClient};
+use super::{InferenceRequest, ModelEndpoint};
+use async_trait::async_trait;
+use chrono::Utc;
+use runtime::endpoint::EndpointRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Records failovers in the endpoint's on-chain record, e.g. by submitting
+/// a `FailoverEndpoint` transaction.
+#[async_trait]
+pub trait EndpointRegistry: Send + Sync {
+    async fn record_failover(&self, endpoint_id: Uuid, primary: &str, epoch: u64) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    pub check_interval: Duration,
+    pub check_timeout: Duration,
+    /// Consecutive failed health checks before the primary is replaced.
+    pub failure_threshold: u32,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(10),
+            check_timeout: Duration::from_secs(2),
+            failure_threshold: 3,
+        }
+    }
+}
+
+/// The node serving an endpoint and the standbys ready to take over, as in
+/// its on-chain [`EndpointRecord`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StandbySet {
+    pub endpoint_id: Uuid,
+    pub primary: String,
+    pub standbys: Vec<String>,
+    pub epoch: u64,
+}
+
+impl StandbySet {
+    pub fn new(endpoint_id: Uuid, primary: &str, standbys: Vec<String>) -> Self {
+        Self { endpoint_id, primary: primary.to_string(), standbys, epoch: 0 }
+    }
+
+    /// Up to `count` standbys for `endpoint` among `candidates` (node and
+    /// free CPU, memory and GPU memory), preferring the roomiest nodes.
+    pub fn choose(
+        endpoint: &ModelEndpoint,
+        primary: &str,
+        candidates: &[(String, NodeResources)],
+        count: usize,
+    ) -> Self {
+        let needs = &endpoint.resources;
+        let mut capable: Vec<&(String, NodeResources)> = candidates
+            .iter()
+            .filter(|(node, free)| {
+                node != primary
+                    && free.cpu >= needs.cpu
+                    && free.memory_gb >= needs.memory_gb
+                    && needs.gpu.as_ref().is_none_or(|gpu| free.gpu_memory_gb >= gpu.memory_gb)
+            })
+            .collect();
+        capable.sort_by(|a, b| b.1.memory_gb.total_cmp(&a.1.memory_gb).then_with(|| a.0.cmp(&b.0)));
+        let standbys = capable.into_iter().take(count).map(|(node, _)| node.clone()).collect();
+        Self::new(endpoint.id, primary, standbys)
+    }
+
+    pub fn from_record(record: &EndpointRecord) -> Option<Self> {
+        let endpoint_id = record.endpoint_id.parse().ok()?;
+        Some(Self {
+            endpoint_id,
+            primary: record.primary.clone(),
+            standbys: record.standbys.clone(),
+            epoch: record.epoch,
+        })
+    }
+
+    /// The record to register on chain for `endpoint`, owned by `owner`.
+    pub fn to_record(&self, endpoint: &ModelEndpoint, owner: &str) -> EndpointRecord {
+        EndpointRecord {
+            endpoint_id: self.endpoint_id.to_string(),
+            model_id: endpoint.model_id.to_string(),
+            owner: owner.to_string(),
+            primary: self.primary.clone(),
+            standbys: self.standbys.clone(),
+            epoch: self.epoch,
+        }
+    }
+}
+
+/// Free capacity of a node that could host a standby.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodeResources {
+    pub cpu: f64,
+    pub memory_gb: f64,
+    pub gpu_memory_gb: f64,
+}
+
+/// A primary replaced by a standby.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailoverEvent {
+    pub endpoint_id: Uuid,
+    pub from: String,
+    pub to: String,
+    pub epoch: u64,
+    /// Whether the on-chain record was updated. Failed updates are retried
+    /// on later checks; traffic moves to the new primary regardless.
+    pub recorded: bool,
+}
+
+#[derive(Debug)]
+struct GroupState {
+    set: StandbySet,
+    failures: u32,
+    /// Primary and epoch as last written to the registry.
+    recorded: (String, u64),
+}
+
+/// Health-checks each endpoint's primary and, when it keeps failing,
+/// routes the endpoint to the first standby that answers and records the
+/// change on chain.
+pub struct FailoverManager<C, R> {
+    router: Arc<InferenceRouter<C>>,
+    registry: R,
+    config: FailoverConfig,
+    groups: Mutex<HashMap<Uuid, GroupState>>,
+}
+
+impl<C: ReplicaClient, R: EndpointRegistry> FailoverManager<C, R> {
+    pub fn new(router: Arc<InferenceRouter<C>>, registry: R, config: FailoverConfig) -> Self {
+        Self { router, registry, config, groups: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn registry(&self) -> &R {
+        &self.registry
+    }
+
+    /// Serve `set.endpoint_id` from `set.primary`, keeping the standbys in
+    /// reserve.
+    pub fn register(&self, set: StandbySet) {
+        self.router.set_replicas(set.endpoint_id, vec![set.primary.clone()]);
+        let recorded = (set.primary.clone(), set.epoch);
+        let endpoint_id = set.endpoint_id;
+        let group = GroupState { set, failures: 0, recorded };
+        self.groups.lock().expect("failover manager poisoned").insert(endpoint_id, group);
+    }
+
+    pub fn standby_set(&self, endpoint_id: Uuid) -> Option<StandbySet> {
+        let groups = self.groups.lock().expect("failover manager poisoned");
+        groups.get(&endpoint_id).map(|g| g.set.clone())
+    }
+
+    /// Check every primary once, failing over those past the threshold.
+    pub async fn check(&self) -> Vec<FailoverEvent> {
+        let sets: Vec<StandbySet> = {
+            let groups = self.groups.lock().expect("failover manager poisoned");
+            groups.values().map(|g| g.set.clone()).collect()
+        };
+        let mut events = Vec::new();
+        for set in sets {
+            self.retry_recording(&set).await;
+            let healthy = self.healthy(set.endpoint_id, &set.primary).await;
+            let failures = {
+                let mut groups = self.groups.lock().expect("failover manager poisoned");
+                let Some(group) = groups.get_mut(&set.endpoint_id) else { continue };
+                group.failures = if healthy { 0 } else { group.failures + 1 };
+                group.failures
+            };
+            if failures < self.config.failure_threshold.max(1) {
+                continue;
+            }
+            if let Some(event) = self.fail_over(set).await {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Run [`FailoverManager::check`] every `check_interval`.
+    pub async fn run(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(self.config.check_interval);
+        loop {
+            ticker.tick().await;
+            self.check().await;
+        }
+    }
+
+    async fn fail_over(&self, mut set: StandbySet) -> Option<FailoverEvent> {
+        let mut next = None;
+        for standby in &set.standbys {
+            if self.healthy(set.endpoint_id, standby).await {
+                next = Some(standby.clone());
+                break;
+            }
+        }
+        let to = next?;
+        let from = std::mem::replace(&mut set.primary, to.clone());
+        set.standbys.retain(|s| *s != to);
+        set.standbys.push(from.clone());
+        self.router.set_replicas(set.endpoint_id, vec![to.clone()]);
+
+        // Epochs follow the on-chain record, which may lag if an earlier
+        // update failed; moving back to the recorded primary needs no update.
+        let (recorded_primary, recorded_epoch) = self.recorded(set.endpoint_id)?;
+        let recorded = if to == recorded_primary {
+            set.epoch = recorded_epoch;
+            true
+        } else {
+            set.epoch = recorded_epoch + 1;
+            self.registry.record_failover(set.endpoint_id, &to, set.epoch).await.is_ok()
+        };
+        let endpoint_id = set.endpoint_id;
+        let event = FailoverEvent { endpoint_id, from, to, epoch: set.epoch, recorded };
+        let mut groups = self.groups.lock().expect("failover manager poisoned");
+        let group = groups.get_mut(&endpoint_id)?;
+        group.failures = 0;
+        if recorded {
+            group.recorded = (set.primary.clone(), set.epoch);
+        }
+        group.set = set;
+        Some(event)
+    }
+
+    fn recorded(&self, endpoint_id: Uuid) -> Option<(String, u64)> {
+        let groups = self.groups.lock().expect("failover manager poisoned");
+        groups.get(&endpoint_id).map(|g| g.recorded.clone())
+    }
+
+    /// Write a failover whose on-chain update failed earlier.
+    async fn retry_recording(&self, set: &StandbySet) {
+        let Some((recorded_primary, _)) = self.recorded(set.endpoint_id) else { return };
+        if recorded_primary == set.primary {
+            return;
+        }
+        if self.registry.record_failover(set.endpoint_id, &set.primary, set.epoch).await.is_ok() {
+            let mut groups = self.groups.lock().expect("failover manager poisoned");
+            if let Some(group) = groups.get_mut(&set.endpoint_id) {
+                group.recorded = (set.primary.clone(), set.epoch);
+            }
+        }
+    }
+
+    async fn healthy(&self, endpoint_id: Uuid, replica: &str) -> bool {
+        let request = InferenceRequest {
+            id: Uuid::new_v4(),
+            endpoint_id,
+            input_data: serde_json::Value::Null,
+            parameters: HashMap::new(),
+            created_at: Utc::now(),
+            metadata: HashMap::from([("health_check".to_string(), "true".to_string())]),
+        };
+        let check = self.router.client().infer(replica, &request);
+        let answer = tokio::time::timeout(self.config.check_timeout, check).await;
+        matches!(answer, Ok(Ok(r)) if r.error.is_none())
+    }
+}
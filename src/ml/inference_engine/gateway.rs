@@ -0,0 +1,163 @@
+use super::auth::ApiKeyRegistry;
+use super::error::GatewayError;
+use super::metering::{PricingPlan, SettlementBatch, UsageCounters, UsageMeter};
+use super::{EndpointStatus, InferenceRequest, InferenceResponse, ModelEndpoint};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Transport-neutral HTTP request as seen by the gateway.
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn new(method: &str, path: &str, body: Vec<u8>) -> Self {
+        Self { method: method.to_string(), path: path.to_string(), headers: HashMap::new(), body }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_ascii_lowercase(), value.to_string());
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        Self { status, body: serde_json::to_vec(value).unwrap_or_default() }
+    }
+
+    fn error(err: &GatewayError) -> Self {
+        Self::json(err.status(), &serde_json::json!({ "error": err.to_string() }))
+    }
+}
+
+/// Runs the model behind an endpoint.
+pub trait InferenceHandler {
+    fn infer(&self, endpoint: &ModelEndpoint, request: &InferenceRequest) -> InferenceResponse;
+}
+
+impl<F> InferenceHandler for F
+where
+    F: Fn(&ModelEndpoint, &InferenceRequest) -> InferenceResponse,
+{
+    fn infer(&self, endpoint: &ModelEndpoint, request: &InferenceRequest) -> InferenceResponse {
+        self(endpoint, request)
+    }
+}
+
+/// Body of `POST /v1/endpoints/{id}/infer`.
+#[derive(Debug, Deserialize)]
+struct InferBody {
+    input_data: serde_json::Value,
+    #[serde(default)]
+    parameters: HashMap<String, serde_json::Value>,
+}
+
+/// Authenticating, metering front door for public model endpoints.
+pub struct InferenceGateway<H> {
+    handler: H,
+    endpoints: HashMap<Uuid, ModelEndpoint>,
+    keys: ApiKeyRegistry,
+    meter: UsageMeter,
+}
+
+impl<H: InferenceHandler> InferenceGateway<H> {
+    pub fn new(handler: H, keys: ApiKeyRegistry) -> Self {
+        Self { handler, endpoints: HashMap::new(), keys, meter: UsageMeter::new(Utc::now()) }
+    }
+
+    /// Exposes `endpoint`; calls are billed to the caller's tenant and paid
+    /// to `payee`.
+    pub fn register_endpoint(&mut self, endpoint: ModelEndpoint, payee: &str, pricing: PricingPlan) {
+        self.meter.set_pricing(endpoint.id, payee, pricing);
+        self.endpoints.insert(endpoint.id, endpoint);
+    }
+
+    pub fn keys(&self) -> &ApiKeyRegistry {
+        &self.keys
+    }
+
+    pub fn keys_mut(&mut self) -> &mut ApiKeyRegistry {
+        &mut self.keys
+    }
+
+    pub fn meter(&self) -> &UsageMeter {
+        &self.meter
+    }
+
+    /// Closes the billing period and hands the usage to the settlement job.
+    pub fn export_usage(&mut self, now: DateTime<Utc>) -> SettlementBatch {
+        self.meter.export(now)
+    }
+
+    /// Routes and serves one request.
+    pub fn handle(&mut self, request: &HttpRequest, now: DateTime<Utc>) -> HttpResponse {
+        match self.serve(request, now) {
+            Ok(response) => HttpResponse::json(200, &response),
+            Err(err) => HttpResponse::error(&err),
+        }
+    }
+
+    fn serve(&mut self, request: &HttpRequest, now: DateTime<Utc>) -> Result<InferenceResponse, GatewayError> {
+        let endpoint_id = route(request)?;
+        let endpoint = self.endpoints.get(&endpoint_id).ok_or(GatewayError::EndpointNotFound(endpoint_id))?;
+        let key = self.keys.authenticate(request, endpoint_id, now)?;
+        if endpoint.status != EndpointStatus::Active {
+            return Err(GatewayError::EndpointUnavailable(endpoint_id));
+        }
+
+        let body: InferBody =
+            serde_json::from_slice(&request.body).map_err(|e| GatewayError::MalformedBody(e.to_string()))?;
+        let inference = InferenceRequest {
+            id: Uuid::new_v4(),
+            endpoint_id,
+            input_data: body.input_data,
+            parameters: body.parameters,
+            created_at: now,
+            metadata: HashMap::from([("tenant".to_string(), key.tenant.clone())]),
+        };
+
+        let started = Instant::now();
+        let response = self.handler.infer(endpoint, &inference);
+        let failed = response.error.is_some();
+        let call = UsageCounters {
+            requests: u64::from(!failed),
+            errors: u64::from(failed),
+            input_bytes: if failed { 0 } else { request.body.len() as u64 },
+            output_bytes: if failed { 0 } else { serde_json::to_vec(&response.output_data).map_or(0, |b| b.len() as u64) },
+            compute_ms: started.elapsed().as_millis() as u64,
+        };
+        self.meter.record(&key.key_id, &key.tenant, endpoint_id, &call);
+        Ok(response)
+    }
+}
+
+/// Extracts the endpoint id from `POST /v1/endpoints/{id}/infer`.
+fn route(request: &HttpRequest) -> Result<Uuid, GatewayError> {
+    let no_route = || GatewayError::NoRoute { method: request.method.clone(), path: request.path.clone() };
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.to_ascii_uppercase().as_str(), segments.as_slice()) {
+        ("POST", ["v1", "endpoints", id, "infer"]) => id.parse().map_err(|_| no_route()),
+        _ => Err(no_route()),
+    }
+}
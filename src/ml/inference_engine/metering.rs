@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use runtime::token::{LedgerError, TokenLedger};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Per-endpoint price list, in BCAI base units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PricingPlan {
+    pub per_request: u64,
+    /// Charged per started KiB of request plus response payload.
+    pub per_kib: u64,
+}
+
+impl PricingPlan {
+    pub fn charge(&self, counters: &UsageCounters) -> u64 {
+        let kib = (counters.input_bytes + counters.output_bytes).div_ceil(1024);
+        self.per_request
+            .saturating_mul(counters.requests)
+            .saturating_add(self.per_kib.saturating_mul(kib))
+    }
+}
+
+/// Usage accumulated by one key on one endpoint since the last export.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageCounters {
+    pub requests: u64,
+    /// Requests that reached the model but returned an error; not billed.
+    pub errors: u64,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    pub compute_ms: u64,
+}
+
+impl UsageCounters {
+    pub fn add(&mut self, other: &UsageCounters) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.input_bytes += other.input_bytes;
+        self.output_bytes += other.output_bytes;
+        self.compute_ms += other.compute_ms;
+    }
+}
+
+/// One line of a settlement: what `tenant` owes `payee` for an endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementEntry {
+    pub tenant: String,
+    pub key_id: String,
+    pub endpoint_id: Uuid,
+    pub payee: String,
+    pub usage: UsageCounters,
+    pub amount: u64,
+}
+
+/// Usage exported for one billing period, consumed by the ledger
+/// settlement job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementBatch {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub entries: Vec<SettlementEntry>,
+}
+
+/// Outcome of applying a batch to the ledger.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettlementReceipt {
+    pub settled: Vec<SettlementEntry>,
+    /// Entries the tenant could not pay, to be carried into the next batch.
+    pub unpaid: Vec<SettlementEntry>,
+}
+
+impl SettlementBatch {
+    pub fn total(&self) -> u64 {
+        self.entries.iter().map(|e| e.amount).sum()
+    }
+
+    /// Transfers every entry from tenant to payee. An entry the tenant
+    /// cannot cover is reported as unpaid rather than aborting the batch.
+    pub fn settle(&self, ledger: &mut TokenLedger) -> SettlementReceipt {
+        let mut receipt = SettlementReceipt::default();
+        for entry in &self.entries {
+            match ledger.transfer(&entry.tenant, &entry.payee, entry.amount) {
+                Ok(()) => receipt.settled.push(entry.clone()),
+                Err(LedgerError::InsufficientBalance) | Err(LedgerError::InsufficientStaked) => {
+                    receipt.unpaid.push(entry.clone())
+                }
+            }
+        }
+        receipt
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MeteredEndpoint {
+    payee: String,
+    pricing: PricingPlan,
+}
+
+/// Per-key usage counters for every metered endpoint.
+#[derive(Debug, Clone)]
+pub struct UsageMeter {
+    endpoints: HashMap<Uuid, MeteredEndpoint>,
+    /// (key id, endpoint) -> (tenant, counters)
+    usage: HashMap<(String, Uuid), (String, UsageCounters)>,
+    period_start: DateTime<Utc>,
+}
+
+impl UsageMeter {
+    pub fn new(period_start: DateTime<Utc>) -> Self {
+        Self { endpoints: HashMap::new(), usage: HashMap::new(), period_start }
+    }
+
+    /// Sets who is paid for calls to `endpoint_id` and at what price.
+    pub fn set_pricing(&mut self, endpoint_id: Uuid, payee: &str, pricing: PricingPlan) {
+        self.endpoints.insert(endpoint_id, MeteredEndpoint { payee: payee.to_string(), pricing });
+    }
+
+    /// Adds one call's usage to the key's counters for `endpoint_id`.
+    pub fn record(&mut self, key_id: &str, tenant: &str, endpoint_id: Uuid, call: &UsageCounters) {
+        let (_, counters) = self
+            .usage
+            .entry((key_id.to_string(), endpoint_id))
+            .or_insert_with(|| (tenant.to_string(), UsageCounters::default()));
+        counters.add(call);
+    }
+
+    /// Counters accumulated by `key_id` on `endpoint_id` in the open period.
+    pub fn usage(&self, key_id: &str, endpoint_id: Uuid) -> Option<&UsageCounters> {
+        self.usage.get(&(key_id.to_string(), endpoint_id)).map(|(_, c)| c)
+    }
+
+    /// Amount `key_id` owes so far in the open period.
+    pub fn amount_due(&self, key_id: &str) -> u64 {
+        self.usage
+            .iter()
+            .filter(|((k, _), _)| k == key_id)
+            .filter_map(|((_, endpoint), (_, counters))| {
+                self.endpoints.get(endpoint).map(|m| m.pricing.charge(counters))
+            })
+            .sum()
+    }
+
+    /// Closes the period at `now`, returning the priced usage and resetting
+    /// the counters. Usage on endpoints without pricing is dropped.
+    pub fn export(&mut self, now: DateTime<Utc>) -> SettlementBatch {
+        let mut entries: Vec<SettlementEntry> = self
+            .usage
+            .drain()
+            .filter_map(|((key_id, endpoint_id), (tenant, usage))| {
+                let metered = self.endpoints.get(&endpoint_id)?;
+                Some(SettlementEntry {
+                    amount: metered.pricing.charge(&usage),
+                    tenant,
+                    key_id,
+                    endpoint_id,
+                    payee: metered.payee.clone(),
+                    usage,
+                })
+            })
+            .filter(|e| e.amount > 0)
+            .collect();
+        entries.sort_by(|a, b| (&a.tenant, &a.key_id, a.endpoint_id).cmp(&(&b.tenant, &b.key_id, b.endpoint_id)));
+        let batch = SettlementBatch { period_start: self.period_start, period_end: now, entries };
+        self.period_start = now;
+        batch
+    }
+}
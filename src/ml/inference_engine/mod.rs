@@ -0,0 +1,115 @@
+//! Model serving data models and the authenticated HTTP gateway.
+//!
+//! Public endpoints are reached through an [`InferenceGateway`]. Callers hold
+//! per-tenant API keys issued by an [`ApiKeyRegistry`] and sign every request
+//! with HMAC-SHA256; each accepted call is counted by a [`UsageMeter`], whose
+//! counters are periodically exported as a [`SettlementBatch`] for the ledger
+//! settlement job. Models behind the endpoints are served from a
+//! [`WarmPool`] that keeps the most-requested ones resident on the GPU;
+//! requests to remote worker replicas go through an [`InferenceRouter`]
+//! that hedges slow calls and retries failures within a budget. A
+//! [`ProbeRunner`] on each participating node sends canned inputs to every
+//! replica, and a [`ProbeMonitor`] turns the results into availability
+//! figures and the replica health the router routes around. Endpoints with
+//! standbys on other nodes are watched by a [`FailoverManager`], which moves
+//! traffic to a standby when the primary stops answering and records the
+//! change in the on-chain endpoint record.
+
+pub mod auth;
+pub mod error;
+pub mod failover;
+pub mod gateway;
+pub mod metering;
+pub mod probe;
+pub mod router;
+pub mod warm_pool;
+
+#[cfg(test)]
+mod tests;
+
+pub use auth::{sign_request, ApiKey, ApiKeyRegistry, IssuedApiKey, SignedHeaders};
+pub use error::{GatewayError, RouterError, WarmPoolError};
+pub use failover::{EndpointRegistry, FailoverConfig, FailoverEvent, FailoverManager, NodeResources, StandbySet};
+pub use gateway::{HttpRequest, HttpResponse, InferenceGateway, InferenceHandler};
+pub use metering::{PricingPlan, SettlementBatch, SettlementEntry, SettlementReceipt, UsageCounters, UsageMeter};
+pub use probe::{
+    EndpointHealth, OutputRange, ProbeConfig, ProbeMonitor, ProbeOutcome, ProbeResult, ProbeRunner, SyntheticProbe,
+};
+pub use router::{
+    HedgeConfig, InferenceRouter, ReplicaClient, RetryPolicy, RoutedResponse, RouterConfig, RouterStats,
+};
+pub use warm_pool::{ModelKey, ModelLoader, PooledInference, WarmPool, WarmPoolConfig, WarmPoolStats};
+
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+// --- Core Data Structures for Inference ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelEndpoint {
+    pub id: Uuid,
+    pub model_id: Uuid,
+    pub model_version: String,
+    pub status: EndpointStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub replicas: u32,
+    pub resources: ResourceRequirements,
+    pub load_balancing_strategy: LoadBalancingStrategy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceRequest {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub input_data: serde_json::Value,
+    pub parameters: HashMap<String, serde_json::Value>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub metadata: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceResponse {
+    pub request_id: Uuid,
+    pub output_data: serde_json::Value,
+    pub error: Option<String>,
+    pub performance_metrics: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceRequirements {
+    pub cpu: f64,
+    pub memory_gb: f64,
+    pub gpu: Option<GpuRequirement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuRequirement {
+    pub gpu_type: String,
+    pub memory_gb: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EndpointStatus {
+    Creating,
+    Active,
+    Updating,
+    Inactive,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum LoadBalancingStrategy {
+    RoundRobin,
+    LeastConnections,
+    Random,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointMetrics {
+    pub request_count: u64,
+    pub error_count: u64,
+    pub average_latency_ms: f64,
+    pub throughput_rps: f64,
+}
@@ -0,0 +1,364 @@
+use super::router::{InferenceRouter, ReplicaClient};
+use super::InferenceRequest;
+use crate::ml::monitoring::SloTracker;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Probe results kept per endpoint for its availability.
+const RESULT_WINDOW: usize = 200;
+
+/// A numeric output field, addressed by JSON pointer (`""` for the whole
+/// output), that must fall within `[min, max]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputRange {
+    pub pointer: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// A canned input sent periodically to every replica of an endpoint, and
+/// the outputs it should produce.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntheticProbe {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub name: String,
+    pub input: serde_json::Value,
+    pub expect: Vec<OutputRange>,
+    pub interval: Duration,
+}
+
+impl SyntheticProbe {
+    pub fn new(endpoint_id: Uuid, name: &str, input: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            endpoint_id,
+            name: name.to_string(),
+            input,
+            expect: Vec::new(),
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_expectation(mut self, pointer: &str, min: f64, max: f64) -> Self {
+        self.expect.push(OutputRange { pointer: pointer.to_string(), min, max });
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Err describing the first expectation `output` misses.
+    pub fn check(&self, output: &serde_json::Value) -> Result<(), String> {
+        for range in &self.expect {
+            match output.pointer(&range.pointer).and_then(|v| v.as_f64()) {
+                Some(v) if v >= range.min && v <= range.max => {}
+                Some(v) => {
+                    let (pointer, min, max) = (&range.pointer, range.min, range.max);
+                    return Err(format!("{} = {} outside [{}, {}]", pointer, v, min, max));
+                }
+                None => return Err(format!("{} missing or not a number", range.pointer)),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ProbeOutcome {
+    Passed,
+    /// The replica answered with an error, or not at all.
+    Failed(String),
+    TimedOut,
+    /// The replica answered, but not within the expected ranges.
+    UnexpectedOutput(String),
+}
+
+/// One probe of one replica, as seen from the `origin` node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub probe_id: Uuid,
+    pub endpoint_id: Uuid,
+    pub replica: String,
+    pub origin: String,
+    pub at: DateTime<Utc>,
+    pub latency_ms: u64,
+    pub outcome: ProbeOutcome,
+}
+
+impl ProbeResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == ProbeOutcome::Passed
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeConfig {
+    pub timeout: Duration,
+    /// Consecutive failures, seen from one origin, after which that origin
+    /// considers a replica down.
+    pub failure_threshold: u32,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self { timeout: Duration::from_secs(5), failure_threshold: 3 }
+    }
+}
+
+/// Fires the registered probes at endpoint replicas from one node. Each
+/// node that takes part in probing runs its own runner and reports to a
+/// shared [`ProbeMonitor`].
+pub struct ProbeRunner<C> {
+    origin: String,
+    client: C,
+    config: ProbeConfig,
+    probes: Mutex<Vec<SyntheticProbe>>,
+    last_run: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+impl<C: ReplicaClient> ProbeRunner<C> {
+    pub fn new(origin: &str, client: C, config: ProbeConfig) -> Self {
+        Self {
+            origin: origin.to_string(),
+            client,
+            config,
+            probes: Mutex::new(Vec::new()),
+            last_run: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn add(&self, probe: SyntheticProbe) -> Uuid {
+        let id = probe.id;
+        self.probes.lock().expect("probe runner poisoned").push(probe);
+        id
+    }
+
+    pub fn remove(&self, probe_id: Uuid) -> bool {
+        let mut probes = self.probes.lock().expect("probe runner poisoned");
+        let before = probes.len();
+        probes.retain(|p| p.id != probe_id);
+        self.last_run.lock().expect("probe runner poisoned").remove(&probe_id);
+        probes.len() != before
+    }
+
+    pub fn probes(&self) -> Vec<SyntheticProbe> {
+        self.probes.lock().expect("probe runner poisoned").clone()
+    }
+
+    /// Send `probe` to `replica` and judge the answer.
+    pub async fn probe(
+        &self,
+        probe: &SyntheticProbe,
+        replica: &str,
+        now: DateTime<Utc>,
+    ) -> ProbeResult {
+        let request = InferenceRequest {
+            id: Uuid::new_v4(),
+            endpoint_id: probe.endpoint_id,
+            input_data: probe.input.clone(),
+            parameters: HashMap::new(),
+            created_at: now,
+            metadata: HashMap::from([
+                ("synthetic".to_string(), "true".to_string()),
+                ("origin".to_string(), self.origin.clone()),
+            ]),
+        };
+        let started = Instant::now();
+        let answer = tokio::time::timeout(self.config.timeout, self.client.infer(replica, &request));
+        let outcome = match answer.await {
+            Err(_) => ProbeOutcome::TimedOut,
+            Ok(Err(error)) => ProbeOutcome::Failed(error),
+            Ok(Ok(response)) => match response.error {
+                Some(error) => ProbeOutcome::Failed(error),
+                None => match probe.check(&response.output_data) {
+                    Ok(()) => ProbeOutcome::Passed,
+                    Err(reason) => ProbeOutcome::UnexpectedOutput(reason),
+                },
+            },
+        };
+        ProbeResult {
+            probe_id: probe.id,
+            endpoint_id: probe.endpoint_id,
+            replica: replica.to_string(),
+            origin: self.origin.clone(),
+            at: now,
+            latency_ms: started.elapsed().as_millis() as u64,
+            outcome,
+        }
+    }
+
+    /// Run every probe whose interval has elapsed against each replica
+    /// `replicas` lists for its endpoint.
+    pub async fn run_due(
+        &self,
+        replicas: &HashMap<Uuid, Vec<String>>,
+        now: DateTime<Utc>,
+    ) -> Vec<ProbeResult> {
+        let due: Vec<SyntheticProbe> = {
+            let mut last_run = self.last_run.lock().expect("probe runner poisoned");
+            self.probes()
+                .into_iter()
+                .filter(|p| {
+                    let interval =
+                        chrono::Duration::from_std(p.interval).unwrap_or(chrono::Duration::MAX);
+                    let due = !matches!(last_run.get(&p.id), Some(last) if now - *last < interval);
+                    if due {
+                        last_run.insert(p.id, now);
+                    }
+                    due
+                })
+                .collect()
+        };
+        let mut results = Vec::new();
+        for probe in &due {
+            for replica in replicas.get(&probe.endpoint_id).into_iter().flatten() {
+                results.push(self.probe(probe, replica, now).await);
+            }
+        }
+        results
+    }
+
+    /// Probe the replicas `router` knows about every `tick`, reporting to
+    /// `monitor` and updating the router's view of replica health.
+    pub async fn run(
+        self: Arc<Self>,
+        router: Arc<InferenceRouter<C>>,
+        monitor: Arc<ProbeMonitor>,
+        tick: Duration,
+    ) {
+        let mut ticker = tokio::time::interval(tick);
+        loop {
+            ticker.tick().await;
+            let mut replicas = HashMap::new();
+            for probe in self.probes() {
+                let endpoint_id = probe.endpoint_id;
+                replicas.entry(endpoint_id).or_insert_with(|| router.replicas(endpoint_id));
+            }
+            for result in self.run_due(&replicas, Utc::now()).await {
+                monitor.record(&result);
+            }
+            monitor.apply(&router);
+        }
+    }
+}
+
+/// Probe-derived health of an endpoint, for dashboards and the autoscaler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub endpoint_id: Uuid,
+    /// Share of recent probes that passed; 1.0 before any probe ran.
+    pub availability: f64,
+    pub probes: usize,
+    pub p95_latency_ms: Option<u64>,
+    pub healthy_replicas: Vec<String>,
+    pub unhealthy_replicas: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct MonitorState {
+    /// Recent results per endpoint: passed and latency.
+    results: HashMap<Uuid, VecDeque<(bool, u64)>>,
+    /// Consecutive failures per replica and origin.
+    streaks: HashMap<String, HashMap<String, u32>>,
+    endpoint_replicas: HashMap<Uuid, Vec<String>>,
+}
+
+/// Collects probe results from every origin. A replica is unhealthy when a
+/// majority of the origins probing it see it failing repeatedly, so one
+/// node with a bad network path cannot take a replica out of rotation.
+/// Results can also be counted against the endpoint's SLOs.
+#[derive(Debug, Default)]
+pub struct ProbeMonitor {
+    config: ProbeConfig,
+    slos: Option<Arc<SloTracker>>,
+    state: Mutex<MonitorState>,
+}
+
+impl ProbeMonitor {
+    pub fn new(config: ProbeConfig) -> Self {
+        Self { config, slos: None, state: Mutex::new(MonitorState::default()) }
+    }
+
+    /// Also record every probe as a request in `slos`.
+    pub fn with_slo_tracker(mut self, slos: Arc<SloTracker>) -> Self {
+        self.slos = Some(slos);
+        self
+    }
+
+    pub fn record(&self, result: &ProbeResult) {
+        if let Some(slos) = &self.slos {
+            slos.record(result.endpoint_id, result.at, result.latency_ms, result.passed());
+        }
+        let mut state = self.state.lock().expect("probe monitor poisoned");
+        let results = state.results.entry(result.endpoint_id).or_default();
+        results.push_back((result.passed(), result.latency_ms));
+        if results.len() > RESULT_WINDOW {
+            results.pop_front();
+        }
+        let replicas = state.endpoint_replicas.entry(result.endpoint_id).or_default();
+        if !replicas.contains(&result.replica) {
+            replicas.push(result.replica.clone());
+        }
+        let streak = state
+            .streaks
+            .entry(result.replica.clone())
+            .or_default()
+            .entry(result.origin.clone())
+            .or_default();
+        *streak = if result.passed() { 0 } else { *streak + 1 };
+    }
+
+    pub fn replica_healthy(&self, replica: &str) -> bool {
+        let state = self.state.lock().expect("probe monitor poisoned");
+        self.healthy(&state, replica)
+    }
+
+    pub fn health(&self, endpoint_id: Uuid) -> EndpointHealth {
+        let state = self.state.lock().expect("probe monitor poisoned");
+        let results = state.results.get(&endpoint_id);
+        let probes = results.map_or(0, |r| r.len());
+        let passed = results.map_or(0, |r| r.iter().filter(|(ok, _)| *ok).count());
+        let mut latencies: Vec<u64> =
+            results.into_iter().flatten().filter(|(ok, _)| *ok).map(|(_, ms)| *ms).collect();
+        latencies.sort_unstable();
+        let (healthy_replicas, unhealthy_replicas) = state
+            .endpoint_replicas
+            .get(&endpoint_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|r| self.healthy(&state, r));
+        EndpointHealth {
+            endpoint_id,
+            availability: if probes == 0 { 1.0 } else { passed as f64 / probes as f64 },
+            probes,
+            p95_latency_ms: latencies.get((latencies.len().max(1) - 1) * 95 / 100).copied(),
+            healthy_replicas,
+            unhealthy_replicas,
+        }
+    }
+
+    /// Route around replicas the probes consider down, and back to those
+    /// that recovered.
+    pub fn apply<C: ReplicaClient>(&self, router: &InferenceRouter<C>) {
+        let state = self.state.lock().expect("probe monitor poisoned");
+        for replica in state.streaks.keys() {
+            router.set_replica_health(replica, self.healthy(&state, replica));
+        }
+    }
+
+    fn healthy(&self, state: &MonitorState, replica: &str) -> bool {
+        let Some(origins) = state.streaks.get(replica) else {
+            return true;
+        };
+        let failing = origins.values().filter(|s| **s >= self.config.failure_threshold).count();
+        failing * 2 <= origins.len()
+    }
+}
@@ -0,0 +1,368 @@
+use super::error::RouterError;
+use super::{InferenceRequest, InferenceResponse, LoadBalancingStrategy, ModelEndpoint};
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Latency samples kept per endpoint for the hedge delay.
+const LATENCY_WINDOW: usize = 200;
+/// Samples needed before the observed p95 replaces the configured delay.
+const MIN_LATENCY_SAMPLES: usize = 20;
+
+/// Sends a request to one worker replica.
+#[async_trait]
+pub trait ReplicaClient: Send + Sync {
+    async fn infer(&self, replica: &str, request: &InferenceRequest) -> Result<InferenceResponse, String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HedgeConfig {
+    /// Latency percentile after which a hedged request is sent.
+    pub percentile: f64,
+    /// Delay used until enough latencies have been observed.
+    pub initial_delay: Duration,
+    /// Lower bound on the hedge delay, so a very fast endpoint is not
+    /// hedged on every request.
+    pub min_delay: Duration,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 0.95,
+            initial_delay: Duration::from_millis(500),
+            min_delay: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Retries are paid from a token bucket: each request deposits `ratio`
+/// tokens and each retry or hedge withdraws one, so extra load stays
+/// bounded at roughly `ratio` times the request rate when replicas fail.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Attempts per request, including the first.
+    pub max_attempts: u32,
+    pub backoff: Duration,
+    pub budget_ratio: f64,
+    /// Tokens available before any traffic.
+    pub min_budget: f64,
+    pub max_budget: f64,
+    /// Deadline for a single attempt.
+    pub attempt_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(10),
+            budget_ratio: 0.1,
+            min_budget: 10.0,
+            max_budget: 100.0,
+            attempt_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RouterConfig {
+    /// `None` disables hedging.
+    pub hedge: Option<HedgeConfig>,
+    pub retry: RetryPolicy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouterStats {
+    pub requests: u64,
+    pub hedges: u64,
+    /// Hedged requests that answered before the original.
+    pub hedge_wins: u64,
+    pub retries: u64,
+    /// Retries or hedges skipped because the budget was empty.
+    pub budget_exhausted: u64,
+}
+
+/// A response together with how it was obtained.
+#[derive(Debug, Clone)]
+pub struct RoutedResponse {
+    pub response: InferenceResponse,
+    pub replica: String,
+    pub attempts: u32,
+    pub hedged: bool,
+}
+
+#[derive(Debug)]
+struct RetryBudget {
+    tokens: f64,
+}
+
+#[derive(Debug, Default)]
+struct LatencyWindow {
+    samples: VecDeque<Duration>,
+}
+
+impl LatencyWindow {
+    fn record(&mut self, latency: Duration) {
+        self.samples.push_back(latency);
+        if self.samples.len() > LATENCY_WINDOW {
+            self.samples.pop_front();
+        }
+    }
+
+    fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.samples.len() < MIN_LATENCY_SAMPLES {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+        Some(sorted[index.min(sorted.len() - 1)])
+    }
+}
+
+/// Spreads requests across an endpoint's replicas, hedging slow requests
+/// and retrying failed ones within a shared retry budget. Replicas marked
+/// unhealthy are tried only after every healthy one.
+pub struct InferenceRouter<C> {
+    client: C,
+    config: RouterConfig,
+    replicas: Mutex<HashMap<Uuid, Vec<String>>>,
+    unhealthy: Mutex<HashSet<String>>,
+    latencies: Mutex<HashMap<Uuid, LatencyWindow>>,
+    in_flight: Mutex<HashMap<String, usize>>,
+    budget: Mutex<RetryBudget>,
+    stats: Mutex<RouterStats>,
+    next: AtomicUsize,
+}
+
+/// Decrements a replica's in-flight count when an attempt ends, including
+/// when a losing hedge is cancelled.
+struct InFlight<'a> {
+    counts: &'a Mutex<HashMap<String, usize>>,
+    replica: String,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Some(count) = self.counts.lock().expect("router poisoned").get_mut(&self.replica) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+impl<C: ReplicaClient> InferenceRouter<C> {
+    pub fn new(client: C, config: RouterConfig) -> Self {
+        let tokens = config.retry.min_budget;
+        Self {
+            client,
+            config,
+            replicas: Mutex::new(HashMap::new()),
+            unhealthy: Mutex::new(HashSet::new()),
+            latencies: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            budget: Mutex::new(RetryBudget { tokens }),
+            stats: Mutex::new(RouterStats::default()),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn set_replicas(&self, endpoint_id: Uuid, replicas: Vec<String>) {
+        self.replicas.lock().expect("router poisoned").insert(endpoint_id, replicas);
+    }
+
+    pub fn replicas(&self, endpoint_id: Uuid) -> Vec<String> {
+        self.replicas.lock().expect("router poisoned").get(&endpoint_id).cloned().unwrap_or_default()
+    }
+
+    /// Mark a replica healthy or not, e.g. from synthetic probe results.
+    pub fn set_replica_health(&self, replica: &str, healthy: bool) {
+        let mut unhealthy = self.unhealthy.lock().expect("router poisoned");
+        if healthy {
+            unhealthy.remove(replica);
+        } else {
+            unhealthy.insert(replica.to_string());
+        }
+    }
+
+    pub fn is_healthy(&self, replica: &str) -> bool {
+        !self.unhealthy.lock().expect("router poisoned").contains(replica)
+    }
+
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    pub fn stats(&self) -> RouterStats {
+        *self.stats.lock().expect("router poisoned")
+    }
+
+    /// Current hedge delay for an endpoint, if hedging is enabled.
+    pub fn hedge_delay(&self, endpoint_id: Uuid) -> Option<Duration> {
+        let hedge = self.config.hedge.as_ref()?;
+        let observed = self
+            .latencies
+            .lock()
+            .expect("router poisoned")
+            .get(&endpoint_id)
+            .and_then(|w| w.percentile(hedge.percentile));
+        Some(observed.unwrap_or(hedge.initial_delay).max(hedge.min_delay))
+    }
+
+    pub async fn route(
+        &self,
+        endpoint: &ModelEndpoint,
+        request: &InferenceRequest,
+    ) -> Result<RoutedResponse, RouterError> {
+        let order = self.replica_order(endpoint)?;
+        self.deposit();
+        self.stats.lock().expect("router poisoned").requests += 1;
+
+        let policy = &self.config.retry;
+        let mut cursor = 0;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let last_error = match self.hedged_attempt(endpoint.id, &order, &mut cursor, request).await {
+                Ok((response, replica, hedged)) => {
+                    return Ok(RoutedResponse { response, replica, attempts, hedged });
+                }
+                Err(err) => err,
+            };
+            if attempts >= policy.max_attempts.max(1) {
+                return Err(RouterError::AttemptsExhausted { attempts, last_error });
+            }
+            if !self.withdraw() {
+                return Err(RouterError::BudgetExhausted { attempts, last_error });
+            }
+            self.stats.lock().expect("router poisoned").retries += 1;
+            tokio::time::sleep(policy.backoff * attempts).await;
+        }
+    }
+
+    /// One attempt, hedged onto the next replica if the first is slower
+    /// than the hedge delay. Returns the first successful answer.
+    async fn hedged_attempt(
+        &self,
+        endpoint_id: Uuid,
+        order: &[String],
+        cursor: &mut usize,
+        request: &InferenceRequest,
+    ) -> Result<(InferenceResponse, String, bool), String> {
+        let primary = order[*cursor % order.len()].as_str();
+        *cursor += 1;
+        let first = self.call(endpoint_id, primary, request);
+        tokio::pin!(first);
+
+        let delay = match self.hedge_delay(endpoint_id) {
+            Some(delay) if order.len() > 1 => delay,
+            _ => return first.await.map(|r| (r, primary.to_string(), false)),
+        };
+        tokio::select! {
+            result = &mut first => return result.map(|r| (r, primary.to_string(), false)),
+            _ = tokio::time::sleep(delay) => {}
+        }
+        if !self.withdraw() {
+            return first.await.map(|r| (r, primary.to_string(), false));
+        }
+
+        let secondary = order[*cursor % order.len()].as_str();
+        *cursor += 1;
+        self.stats.lock().expect("router poisoned").hedges += 1;
+        let second = self.call(endpoint_id, secondary, request);
+        tokio::pin!(second);
+        tokio::select! {
+            result = &mut first => match result {
+                Ok(r) => Ok((r, primary.to_string(), false)),
+                Err(_) => second.await.map(|r| (r, secondary.to_string(), true)),
+            },
+            result = &mut second => match result {
+                Ok(r) => {
+                    self.stats.lock().expect("router poisoned").hedge_wins += 1;
+                    Ok((r, secondary.to_string(), true))
+                }
+                Err(_) => first.await.map(|r| (r, primary.to_string(), false)),
+            },
+        }
+    }
+
+    async fn call(&self, endpoint_id: Uuid, replica: &str, request: &InferenceRequest) -> Result<InferenceResponse, String> {
+        *self.in_flight.lock().expect("router poisoned").entry(replica.to_string()).or_default() += 1;
+        let _guard = InFlight { counts: &self.in_flight, replica: replica.to_string() };
+
+        let started = Instant::now();
+        let result = tokio::time::timeout(self.config.retry.attempt_timeout, self.client.infer(replica, request))
+            .await
+            .map_err(|_| format!("replica {} timed out", replica))
+            .and_then(|r| r);
+        if result.is_ok() {
+            self.latencies
+                .lock()
+                .expect("router poisoned")
+                .entry(endpoint_id)
+                .or_default()
+                .record(started.elapsed());
+        }
+        match result {
+            Ok(response) => match response.error {
+                Some(error) => Err(error),
+                None => Ok(response),
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Replicas in the order they will be tried, per the endpoint's
+    /// load-balancing strategy.
+    fn replica_order(&self, endpoint: &ModelEndpoint) -> Result<Vec<String>, RouterError> {
+        let mut replicas = self
+            .replicas
+            .lock()
+            .expect("router poisoned")
+            .get(&endpoint.id)
+            .cloned()
+            .unwrap_or_default();
+        if replicas.is_empty() {
+            return Err(RouterError::NoReplicas(endpoint.id));
+        }
+        match endpoint.load_balancing_strategy {
+            LoadBalancingStrategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed) % replicas.len();
+                replicas.rotate_left(start);
+            }
+            LoadBalancingStrategy::Random => {
+                let start = rand::thread_rng().gen_range(0..replicas.len());
+                replicas.rotate_left(start);
+            }
+            LoadBalancingStrategy::LeastConnections => {
+                let in_flight = self.in_flight.lock().expect("router poisoned");
+                replicas.sort_by_key(|r| in_flight.get(r).copied().unwrap_or(0));
+            }
+        }
+        let unhealthy = self.unhealthy.lock().expect("router poisoned");
+        replicas.sort_by_key(|r| unhealthy.contains(r));
+        Ok(replicas)
+    }
+
+    fn deposit(&self) {
+        let policy = &self.config.retry;
+        let mut budget = self.budget.lock().expect("router poisoned");
+        budget.tokens = (budget.tokens + policy.budget_ratio).min(policy.max_budget.max(policy.min_budget));
+    }
+
+    fn withdraw(&self) -> bool {
+        let mut budget = self.budget.lock().expect("router poisoned");
+        if budget.tokens >= 1.0 {
+            budget.tokens -= 1.0;
+            true
+        } else {
+            self.stats.lock().expect("router poisoned").budget_exhausted += 1;
+            false
+        }
+    }
+}
@@ -0,0 +1,542 @@
+use super::*;
+use chrono::{DateTime, Duration, Utc};
+use crate::ml::monitoring::ColdStartRecorder;
+use runtime::token::TokenLedger;
+use serde_json::json;
+use std::sync::Arc;
+
+fn endpoint(status: EndpointStatus) -> ModelEndpoint {
+    ModelEndpoint {
+        id: Uuid::new_v4(),
+        model_id: Uuid::new_v4(),
+        model_version: "1.0".into(),
+        status,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        replicas: 1,
+        resources: ResourceRequirements { cpu: 1.0, memory_gb: 1.0, gpu: None },
+        load_balancing_strategy: LoadBalancingStrategy::RoundRobin,
+    }
+}
+
+fn echo(_: &ModelEndpoint, request: &InferenceRequest) -> InferenceResponse {
+    InferenceResponse {
+        request_id: request.id,
+        output_data: json!({ "echo": request.input_data }),
+        error: None,
+        performance_metrics: HashMap::new(),
+    }
+}
+
+type EchoGateway = InferenceGateway<fn(&ModelEndpoint, &InferenceRequest) -> InferenceResponse>;
+
+fn gateway() -> (EchoGateway, Uuid) {
+    let mut gateway: EchoGateway = InferenceGateway::new(echo, ApiKeyRegistry::default());
+    let ep = endpoint(EndpointStatus::Active);
+    let id = ep.id;
+    gateway.register_endpoint(ep, "model-owner", PricingPlan { per_request: 10, per_kib: 1 });
+    (gateway, id)
+}
+
+fn signed(key: &IssuedApiKey, endpoint: Uuid, nonce: &str, now: DateTime<Utc>) -> HttpRequest {
+    signed_at(key, endpoint, nonce, now.timestamp())
+}
+
+fn signed_at(key: &IssuedApiKey, endpoint: Uuid, nonce: &str, timestamp: i64) -> HttpRequest {
+    let path = format!("/v1/endpoints/{}/infer", endpoint);
+    let body = serde_json::to_vec(&json!({ "input_data": [1, 2, 3] })).unwrap();
+    let headers = key.sign("POST", &path, timestamp, nonce, &body);
+    headers
+        .to_headers()
+        .into_iter()
+        .fold(HttpRequest::new("POST", &path, body), |req, (k, v)| req.with_header(&k, &v))
+}
+
+#[test]
+fn signed_requests_are_served_and_metered() {
+    let (mut gateway, id) = gateway();
+    let key = gateway.keys_mut().issue("acme", [id]);
+    let now = Utc::now();
+
+    for nonce in ["n1", "n2"] {
+        let response = gateway.handle(&signed(&key, id, nonce, now), now);
+        assert_eq!(response.status, 200, "{}", String::from_utf8_lossy(&response.body));
+    }
+    let usage = gateway.meter().usage(&key.key_id, id).unwrap();
+    assert_eq!(usage.requests, 2);
+    assert_eq!(usage.errors, 0);
+    assert!(usage.input_bytes > 0 && usage.output_bytes > 0);
+    assert_eq!(gateway.meter().amount_due(&key.key_id), 21);
+}
+
+#[test]
+fn authentication_failures_map_to_status_codes() {
+    let (mut gateway, id) = gateway();
+    let key = gateway.keys_mut().issue("acme", [id]);
+    let other = Uuid::new_v4();
+    let now = Utc::now();
+
+    // Tampered body.
+    let mut tampered = signed(&key, id, "n1", now);
+    tampered.body = br#"{"input_data":[9]}"#.to_vec();
+    assert_eq!(gateway.handle(&tampered, now).status, 401);
+
+    // Replayed nonce.
+    let request = signed(&key, id, "n2", now);
+    assert_eq!(gateway.handle(&request, now).status, 200);
+    assert_eq!(gateway.handle(&request, now).status, 401);
+
+    // Stale timestamp.
+    let old = now - Duration::minutes(10);
+    assert_eq!(gateway.handle(&signed(&key, id, "n3", old), now).status, 401);
+    // Timestamps at the ends of the range are stale too, not an overflow.
+    for (nonce, timestamp) in [("n7", i64::MIN), ("n8", i64::MAX)] {
+        assert_eq!(gateway.handle(&signed_at(&key, id, nonce, timestamp), now).status, 401);
+    }
+
+    // Missing headers.
+    let bare = HttpRequest::new("POST", &format!("/v1/endpoints/{}/infer", id), b"{}".to_vec());
+    assert_eq!(gateway.handle(&bare, now).status, 401);
+
+    // Key not scoped to a second endpoint.
+    let second = endpoint(EndpointStatus::Active);
+    let second_id = second.id;
+    gateway.register_endpoint(second, "model-owner", PricingPlan { per_request: 1, per_kib: 0 });
+    assert_eq!(gateway.handle(&signed(&key, second_id, "n4", now), now).status, 403);
+
+    // Unknown endpoint and revoked key.
+    assert_eq!(gateway.handle(&signed(&key, other, "n5", now), now).status, 404);
+    gateway.keys_mut().revoke(&key.key_id).unwrap();
+    assert_eq!(gateway.handle(&signed(&key, id, "n6", now), now).status, 401);
+
+    assert_eq!(gateway.meter().usage(&key.key_id, id).unwrap().requests, 1);
+}
+
+#[test]
+fn exported_usage_settles_on_the_ledger() {
+    let (mut gateway, id) = gateway();
+    let paying = gateway.keys_mut().issue("acme", [id]);
+    let broke = gateway.keys_mut().issue("nocash", [id]);
+    let now = Utc::now();
+    assert_eq!(gateway.handle(&signed(&paying, id, "a", now), now).status, 200);
+    assert_eq!(gateway.handle(&signed(&broke, id, "b", now), now).status, 200);
+
+    let batch = gateway.export_usage(now);
+    assert_eq!(batch.entries.len(), 2);
+    assert_eq!(batch.total(), 22);
+    assert!(gateway.meter().usage(&paying.key_id, id).is_none());
+
+    let mut ledger = TokenLedger::new();
+    ledger.mint("acme", 100);
+    let receipt = batch.settle(&mut ledger);
+    assert_eq!(receipt.settled.len(), 1);
+    assert_eq!(receipt.unpaid[0].tenant, "nocash");
+    assert_eq!(ledger.balance("acme"), 89);
+    assert_eq!(ledger.balance("model-owner"), 11);
+}
+
+/// Loader that knows a fixed set of models, sized in GiB.
+struct FakeLoader {
+    sizes: HashMap<Uuid, u64>,
+}
+
+impl ModelLoader for FakeLoader {
+    type Model = ModelKey;
+
+    fn load(&self, key: &ModelKey) -> Result<ModelKey, String> {
+        if self.sizes.contains_key(&key.model_id) {
+            Ok(key.clone())
+        } else {
+            Err("weights not found".into())
+        }
+    }
+
+    fn memory_bytes(&self, model: &ModelKey) -> u64 {
+        self.sizes[&model.model_id] << 30
+    }
+}
+
+fn pool(sizes: &[u64], max_resident: usize, budget_gb: u64) -> (WarmPool<FakeLoader>, Vec<ModelKey>) {
+    let keys: Vec<ModelKey> = sizes.iter().map(|_| ModelKey::new(Uuid::new_v4(), "1.0")).collect();
+    let loader = FakeLoader {
+        sizes: keys.iter().zip(sizes).map(|(k, s)| (k.model_id, *s)).collect(),
+    };
+    let config = WarmPoolConfig { max_resident, gpu_memory_budget_bytes: budget_gb << 30 };
+    (WarmPool::new(loader, config), keys)
+}
+
+#[test]
+fn warm_pool_keeps_most_requested_models_and_reports_cold_starts() {
+    let recorder = Arc::new(ColdStartRecorder::new());
+    let (pool, keys) = pool(&[1, 1, 1], 2, 8);
+    let mut pool = pool.with_recorder(recorder.clone());
+
+    for _ in 0..3 {
+        pool.acquire(&keys[0]).unwrap();
+    }
+    let (_, cold) = pool.acquire(&keys[1]).unwrap();
+    assert!(cold.is_some());
+    pool.acquire(&keys[1]).unwrap();
+
+    // A third model evicts the less requested of the two residents.
+    pool.acquire(&keys[2]).unwrap();
+    assert!(pool.is_resident(&keys[0]));
+    assert!(!pool.is_resident(&keys[1]));
+
+    let stats = pool.stats();
+    assert_eq!((stats.hits, stats.cold_starts, stats.evictions), (3, 3, 1));
+    let metrics = recorder.metrics(keys[0].model_id, "1.0").unwrap();
+    assert_eq!(metrics.count, 1);
+    assert_eq!(recorder.all().len(), 3);
+}
+
+#[test]
+fn warm_pool_respects_gpu_memory_budget() {
+    let (mut pool, keys) = pool(&[6, 3, 9, 1], 4, 8);
+    pool.acquire(&keys[0]).unwrap();
+    pool.acquire(&keys[0]).unwrap();
+    pool.acquire(&keys[1]).unwrap();
+    assert!(!pool.is_resident(&keys[0]), "6 + 3 GiB exceeds the 8 GiB budget");
+    assert!(pool.resident_bytes() <= 8 << 30);
+
+    assert!(matches!(pool.acquire(&keys[2]), Err(WarmPoolError::ExceedsBudget { .. })));
+    let missing = ModelKey::new(Uuid::new_v4(), "1.0");
+    assert!(matches!(pool.acquire(&missing), Err(WarmPoolError::Load { .. })));
+
+    // Preloading skips what cannot fit alongside the rest of the batch.
+    let skipped = pool.preload(&[keys[1].clone(), keys[3].clone(), keys[0].clone()]);
+    assert_eq!(skipped, vec![keys[0].clone()]);
+}
+
+#[test]
+fn warm_pool_rebalances_towards_recent_demand() {
+    let (mut pool, keys) = pool(&[1, 1, 1], 2, 8);
+    for _ in 0..4 {
+        pool.acquire(&keys[0]).unwrap();
+        pool.acquire(&keys[1]).unwrap();
+    }
+    pool.decay_demand();
+    pool.decay_demand();
+    for _ in 0..5 {
+        pool.acquire(&keys[2]).unwrap();
+    }
+    assert_eq!(pool.hottest()[0], keys[2]);
+    assert!(pool.rebalance().is_empty());
+    assert!(pool.hottest().iter().all(|k| pool.is_resident(k)));
+}
+
+#[test]
+fn pooled_handler_serves_through_the_gateway() {
+    let (mut warm, keys) = pool(&[1], 2, 8);
+    let mut ep = endpoint(EndpointStatus::Active);
+    ep.model_id = keys[0].model_id;
+    assert!(warm.preload(&keys).is_empty());
+    let handler = PooledInference::new(warm, |model: &ModelKey, request: &InferenceRequest| InferenceResponse {
+        request_id: request.id,
+        output_data: json!({ "model": model.to_string() }),
+        error: None,
+        performance_metrics: HashMap::new(),
+    });
+
+    let request = InferenceRequest {
+        id: Uuid::new_v4(),
+        endpoint_id: ep.id,
+        input_data: json!(1),
+        parameters: HashMap::new(),
+        created_at: Utc::now(),
+        metadata: HashMap::new(),
+    };
+    let response = handler.infer(&ep, &request);
+    assert!(response.error.is_none());
+    assert!(!response.performance_metrics.contains_key("cold_start_ms"), "preloaded model was warm");
+    assert_eq!(handler.pool().stats().hits, 1);
+}
+
+/// Replica client with a fixed latency per replica; replicas named `bad*`
+/// always fail.
+struct FakeReplicas {
+    latency: HashMap<String, u64>,
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+impl FakeReplicas {
+    fn new(latency: &[(&str, u64)]) -> Self {
+        Self {
+            latency: latency.iter().map(|(r, ms)| (r.to_string(), *ms)).collect(),
+            calls: Default::default(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ReplicaClient for FakeReplicas {
+    async fn infer(&self, replica: &str, request: &InferenceRequest) -> Result<InferenceResponse, String> {
+        self.calls.lock().unwrap().push(replica.to_string());
+        tokio::time::sleep(std::time::Duration::from_millis(self.latency[replica])).await;
+        if replica.starts_with("bad") {
+            return Err(format!("{} is down", replica));
+        }
+        Ok(echo(&endpoint(EndpointStatus::Active), request))
+    }
+}
+
+fn routed(replicas: &[(&str, u64)], config: RouterConfig) -> (InferenceRouter<FakeReplicas>, ModelEndpoint) {
+    let router = InferenceRouter::new(FakeReplicas::new(replicas), config);
+    let ep = endpoint(EndpointStatus::Active);
+    router.set_replicas(ep.id, replicas.iter().map(|(r, _)| r.to_string()).collect());
+    (router, ep)
+}
+
+fn request_for(ep: &ModelEndpoint) -> InferenceRequest {
+    InferenceRequest {
+        id: Uuid::new_v4(),
+        endpoint_id: ep.id,
+        input_data: json!(1),
+        parameters: HashMap::new(),
+        created_at: Utc::now(),
+        metadata: HashMap::new(),
+    }
+}
+
+fn hedged(initial_ms: u64) -> RouterConfig {
+    RouterConfig {
+        hedge: Some(HedgeConfig { initial_delay: std::time::Duration::from_millis(initial_ms), ..Default::default() }),
+        retry: RetryPolicy::default(),
+    }
+}
+
+#[tokio::test]
+async fn slow_replica_is_hedged() {
+    let (router, ep) = routed(&[("slow", 1_000), ("fast", 5)], hedged(20));
+    let started = std::time::Instant::now();
+    let routed = router.route(&ep, &request_for(&ep)).await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_millis(500));
+    assert_eq!(routed.replica, "fast");
+    assert!(routed.hedged);
+    let stats = router.stats();
+    assert_eq!((stats.hedges, stats.hedge_wins, stats.retries), (1, 1, 0));
+}
+
+#[tokio::test]
+async fn fast_replicas_are_not_hedged_and_p95_sets_the_delay() {
+    let (router, ep) = routed(&[("a", 1), ("b", 1)], hedged(200));
+    assert_eq!(router.hedge_delay(ep.id), Some(std::time::Duration::from_millis(200)));
+    for _ in 0..25 {
+        let routed = router.route(&ep, &request_for(&ep)).await.unwrap();
+        assert!(!routed.hedged);
+    }
+    assert_eq!(router.stats().hedges, 0);
+    assert!(router.hedge_delay(ep.id).unwrap() < std::time::Duration::from_millis(200));
+
+    // Round robin alternates the primary replica.
+    let calls = router.client().calls.lock().unwrap().clone();
+    assert_eq!(&calls[..2], &["a".to_string(), "b".to_string()]);
+}
+
+#[tokio::test]
+async fn failed_attempts_are_retried_on_another_replica() {
+    let (router, ep) = routed(&[("bad", 1), ("good", 1)], RouterConfig::default());
+    let routed = router.route(&ep, &request_for(&ep)).await.unwrap();
+    assert_eq!(routed.replica, "good");
+    assert_eq!(routed.attempts, 2);
+    assert_eq!(router.stats().retries, 1);
+}
+
+#[tokio::test]
+async fn retry_budget_bounds_extra_load() {
+    let config = RouterConfig {
+        hedge: None,
+        retry: RetryPolicy {
+            max_attempts: 5,
+            budget_ratio: 0.0,
+            min_budget: 1.0,
+            backoff: std::time::Duration::ZERO,
+            ..Default::default()
+        },
+    };
+    let (router, ep) = routed(&[("bad-1", 1), ("bad-2", 1)], config);
+    let first = router.route(&ep, &request_for(&ep)).await.unwrap_err();
+    assert!(matches!(first, RouterError::BudgetExhausted { attempts: 2, .. }));
+    let second = router.route(&ep, &request_for(&ep)).await.unwrap_err();
+    assert!(matches!(second, RouterError::BudgetExhausted { attempts: 1, .. }));
+    assert_eq!(router.stats().retries, 1);
+
+    let empty = endpoint(EndpointStatus::Active);
+    assert_eq!(router.route(&empty, &request_for(&empty)).await.unwrap_err(), RouterError::NoReplicas(empty.id));
+}
+
+#[tokio::test]
+async fn probes_check_every_replica_once_per_interval() {
+    let ep = endpoint(EndpointStatus::Active);
+    let runner = ProbeRunner::new("node-a", FakeReplicas::new(&[("good", 1), ("bad", 1)]), ProbeConfig::default());
+    runner.add(SyntheticProbe::new(ep.id, "echo", json!(0.5)).with_expectation("/echo", 0.0, 1.0));
+    let replicas = HashMap::from([(ep.id, vec!["good".to_string(), "bad".to_string()])]);
+
+    let now = Utc::now();
+    let results = runner.run_due(&replicas, now).await;
+    let outcomes: Vec<(&str, bool)> = results.iter().map(|r| (r.replica.as_str(), r.passed())).collect();
+    assert_eq!(outcomes, vec![("good", true), ("bad", false)]);
+    assert!(matches!(&results[1].outcome, ProbeOutcome::Failed(e) if e == "bad is down"));
+    assert_eq!(results[0].origin, "node-a");
+    assert!(runner.run_due(&replicas, now + Duration::seconds(30)).await.is_empty());
+    assert_eq!(runner.run_due(&replicas, now + Duration::seconds(60)).await.len(), 2);
+
+    let off = SyntheticProbe::new(ep.id, "range", json!(7)).with_expectation("/echo", 0.0, 1.0);
+    let result = runner.probe(&off, "good", now).await;
+    assert_eq!(result.outcome, ProbeOutcome::UnexpectedOutput("/echo = 7 outside [0, 1]".into()));
+    let missing = SyntheticProbe::new(ep.id, "missing", json!(1)).with_expectation("/score", 0.0, 1.0);
+    assert!(matches!(runner.probe(&missing, "good", now).await.outcome, ProbeOutcome::UnexpectedOutput(_)));
+}
+
+#[tokio::test]
+async fn replicas_failing_from_most_origins_are_routed_around() {
+    let (router, ep) = routed(&[("a", 1), ("b", 1)], RouterConfig::default());
+    let slos = Arc::new(crate::ml::monitoring::SloTracker::default());
+    let slo = slos.define(crate::ml::monitoring::Slo::availability(&ep, 0.99)).unwrap();
+    let monitor = ProbeMonitor::new(ProbeConfig::default()).with_slo_tracker(slos.clone());
+    let result = |replica: &str, origin: &str, passed: bool| ProbeResult {
+        probe_id: Uuid::new_v4(),
+        endpoint_id: ep.id,
+        replica: replica.to_string(),
+        origin: origin.to_string(),
+        at: Utc::now(),
+        latency_ms: 10,
+        outcome: if passed { ProbeOutcome::Passed } else { ProbeOutcome::TimedOut },
+    };
+
+    // Only one of three origins fails to reach `a`: a network problem on
+    // that node, not a dead replica.
+    for _ in 0..3 {
+        monitor.record(&result("a", "node-1", false));
+        monitor.record(&result("a", "node-2", true));
+        monitor.record(&result("a", "node-3", true));
+        monitor.record(&result("b", "node-1", true));
+    }
+    assert!(monitor.replica_healthy("a"));
+
+    for _ in 0..3 {
+        monitor.record(&result("a", "node-2", false));
+    }
+    assert!(!monitor.replica_healthy("a"));
+    monitor.apply(&router);
+    for _ in 0..2 {
+        assert_eq!(router.route(&ep, &request_for(&ep)).await.unwrap().replica, "b");
+    }
+
+    let health = monitor.health(ep.id);
+    assert_eq!((health.probes, health.healthy_replicas.clone()), (15, vec!["b".to_string()]));
+    assert_eq!(health.unhealthy_replicas, vec!["a".to_string()]);
+    assert!((health.availability - 9.0 / 15.0).abs() < 1e-9);
+    assert_eq!(slos.status(slo, Utc::now()).unwrap().total, 15);
+
+    monitor.record(&result("a", "node-1", true));
+    monitor.record(&result("a", "node-2", true));
+    monitor.apply(&router);
+    assert!(router.is_healthy("a"));
+}
+
+/// Applies failovers to a chain state the way a block would.
+struct ChainRegistry {
+    state: std::sync::Mutex<runtime::blockchain::state::State>,
+    key: schnorrkel::SecretKey,
+    offline: std::sync::atomic::AtomicBool,
+}
+
+impl ChainRegistry {
+    fn new(set: &StandbySet, ep: &ModelEndpoint) -> Self {
+        let key = schnorrkel::Keypair::generate().secret.clone();
+        let owner = hex::encode(key.to_public().to_bytes());
+        let mut state = runtime::blockchain::state::State::new();
+        state.set_balance(&owner, 100);
+        let tx = runtime::blockchain::Transaction::new_register_endpoint_signed(&key, set.to_record(ep, &owner), 1, 0);
+        runtime::blockchain::validation::validate_transaction_stateful(&tx, &state).unwrap();
+        state.apply_transaction(&tx).unwrap();
+        Self { state: std::sync::Mutex::new(state), key, offline: Default::default() }
+    }
+
+    fn record(&self, ep: &ModelEndpoint) -> runtime::endpoint::EndpointRecord {
+        self.state.lock().unwrap().endpoints[&ep.id.to_string()].clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl EndpointRegistry for ChainRegistry {
+    async fn record_failover(&self, endpoint_id: Uuid, primary: &str, epoch: u64) -> Result<(), String> {
+        if self.offline.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err("chain unreachable".into());
+        }
+        let mut state = self.state.lock().unwrap();
+        let nonce = state.get_nonce(&hex::encode(self.key.to_public().to_bytes()));
+        let tx = runtime::blockchain::Transaction::new_failover_endpoint_signed(
+            &self.key,
+            endpoint_id.to_string(),
+            primary.to_string(),
+            epoch,
+            1,
+            nonce,
+        );
+        runtime::blockchain::validation::validate_transaction_stateful(&tx, &state).map_err(|e| e.to_string())?;
+        state.apply_transaction(&tx).map_err(|e| e.to_string())
+    }
+}
+
+#[tokio::test]
+async fn dead_primaries_fail_over_to_a_healthy_standby_on_chain() {
+    let replicas = [("bad-primary", 1), ("bad-standby", 1), ("good", 1), ("spare", 1)];
+    let router = Arc::new(InferenceRouter::new(FakeReplicas::new(&replicas), RouterConfig::default()));
+    let ep = endpoint(EndpointStatus::Active);
+    let set = StandbySet::new(ep.id, "bad-primary", vec!["bad-standby".into(), "good".into()]);
+    let registry = ChainRegistry::new(&set, &ep);
+    let config = FailoverConfig { failure_threshold: 2, ..FailoverConfig::default() };
+    let manager = FailoverManager::new(router.clone(), registry, config);
+    manager.register(set);
+
+    assert!(manager.check().await.is_empty(), "one failed check is not enough");
+    let events = manager.check().await;
+    assert_eq!(events.len(), 1);
+    assert_eq!((events[0].from.as_str(), events[0].to.as_str(), events[0].epoch), ("bad-primary", "good", 1));
+    assert!(events[0].recorded);
+    assert_eq!(router.route(&ep, &request_for(&ep)).await.unwrap().replica, "good");
+
+    let record = manager.registry().record(&ep);
+    assert_eq!((record.primary.as_str(), record.epoch), ("good", 1));
+    assert_eq!(StandbySet::from_record(&record), manager.standby_set(ep.id));
+    assert!(manager.check().await.is_empty(), "the new primary is healthy");
+}
+
+#[tokio::test]
+async fn failovers_are_recorded_once_the_chain_is_reachable() {
+    let router = Arc::new(InferenceRouter::new(FakeReplicas::new(&[("bad", 1), ("good", 1)]), RouterConfig::default()));
+    let ep = endpoint(EndpointStatus::Active);
+    let set = StandbySet::new(ep.id, "bad", vec!["good".into()]);
+    let registry = ChainRegistry::new(&set, &ep);
+    registry.offline.store(true, std::sync::atomic::Ordering::SeqCst);
+    let config = FailoverConfig { failure_threshold: 1, ..FailoverConfig::default() };
+    let manager = FailoverManager::new(router.clone(), registry, config);
+    manager.register(set);
+
+    let events = manager.check().await;
+    assert!(!events[0].recorded);
+    assert_eq!(router.replicas(ep.id), vec!["good".to_string()]);
+    assert_eq!(manager.registry().record(&ep).primary, "bad");
+
+    manager.registry().offline.store(false, std::sync::atomic::Ordering::SeqCst);
+    manager.check().await;
+    let record = manager.registry().record(&ep);
+    assert_eq!((record.primary.as_str(), record.epoch, record.standbys.clone()), ("good", 1, vec!["bad".to_string()]));
+}
+
+#[test]
+fn standbys_are_chosen_among_capable_nodes() {
+    let ep = endpoint(EndpointStatus::Active);
+    let node = |cpu, memory_gb| NodeResources { cpu, memory_gb, gpu_memory_gb: 0.0 };
+    let candidates = vec![
+        ("primary".to_string(), node(8.0, 64.0)),
+        ("small".to_string(), node(0.5, 8.0)),
+        ("medium".to_string(), node(2.0, 8.0)),
+        ("large".to_string(), node(4.0, 32.0)),
+    ];
+    let set = StandbySet::choose(&ep, "primary", &candidates, 3);
+    assert_eq!(set.standbys, vec!["large".to_string(), "medium".to_string()]);
+    assert_eq!(set.epoch, 0);
+}
@@ -0,0 +1,280 @@
+use super::error::WarmPoolError;
+use super::gateway::InferenceHandler;
+use super::{InferenceRequest, InferenceResponse, ModelEndpoint};
+use crate::ml::monitoring::ColdStartRecorder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// A servable model version.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModelKey {
+    pub model_id: Uuid,
+    pub model_version: String,
+}
+
+impl ModelKey {
+    pub fn new(model_id: Uuid, model_version: &str) -> Self {
+        Self { model_id, model_version: model_version.to_string() }
+    }
+
+    pub fn for_endpoint(endpoint: &ModelEndpoint) -> Self {
+        Self::new(endpoint.model_id, &endpoint.model_version)
+    }
+}
+
+impl std::fmt::Display for ModelKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.model_id, self.model_version)
+    }
+}
+
+/// Loads model weights onto the serving device.
+pub trait ModelLoader {
+    type Model;
+    fn load(&self, key: &ModelKey) -> Result<Self::Model, String>;
+    /// Device memory held by a loaded model.
+    fn memory_bytes(&self, model: &Self::Model) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarmPoolConfig {
+    /// Number of models kept resident (the K most requested).
+    pub max_resident: usize,
+    /// GPU memory available to resident models.
+    pub gpu_memory_budget_bytes: u64,
+}
+
+impl Default for WarmPoolConfig {
+    fn default() -> Self {
+        Self { max_resident: 4, gpu_memory_budget_bytes: 16 * 1024 * 1024 * 1024 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarmPoolStats {
+    pub hits: u64,
+    pub cold_starts: u64,
+    pub preloads: u64,
+    pub evictions: u64,
+}
+
+impl WarmPoolStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.cold_starts;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct Resident<M> {
+    model: Arc<M>,
+    memory_bytes: u64,
+    last_used: u64,
+}
+
+/// Keeps the most-requested models loaded. Models are evicted lazily: only
+/// when loading another one would exceed the resident count or the memory
+/// budget, and then the least-requested resident goes first.
+pub struct WarmPool<L: ModelLoader> {
+    loader: L,
+    config: WarmPoolConfig,
+    resident: HashMap<ModelKey, Resident<L::Model>>,
+    demand: HashMap<ModelKey, u64>,
+    tick: u64,
+    stats: WarmPoolStats,
+    recorder: Option<Arc<ColdStartRecorder>>,
+}
+
+impl<L: ModelLoader> WarmPool<L> {
+    pub fn new(loader: L, config: WarmPoolConfig) -> Self {
+        Self {
+            loader,
+            config,
+            resident: HashMap::new(),
+            demand: HashMap::new(),
+            tick: 0,
+            stats: WarmPoolStats::default(),
+            recorder: None,
+        }
+    }
+
+    /// Reports every cold start's load latency to `recorder`.
+    pub fn with_recorder(mut self, recorder: Arc<ColdStartRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn stats(&self) -> WarmPoolStats {
+        self.stats
+    }
+
+    pub fn is_resident(&self, key: &ModelKey) -> bool {
+        self.resident.contains_key(key)
+    }
+
+    pub fn resident_bytes(&self) -> u64 {
+        self.resident.values().map(|r| r.memory_bytes).sum()
+    }
+
+    /// Returns the loaded model, loading it first on a miss. The duration is
+    /// the cold-start latency when a load was needed.
+    pub fn acquire(&mut self, key: &ModelKey) -> Result<(Arc<L::Model>, Option<Duration>), WarmPoolError> {
+        self.tick += 1;
+        *self.demand.entry(key.clone()).or_default() += 1;
+        if let Some(resident) = self.resident.get_mut(key) {
+            resident.last_used = self.tick;
+            self.stats.hits += 1;
+            return Ok((resident.model.clone(), None));
+        }
+
+        let (model, elapsed) = self.load(key, &HashSet::new())?;
+        self.stats.cold_starts += 1;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(key.model_id, &key.model_version, elapsed.as_millis() as u64);
+        }
+        Ok((model, Some(elapsed)))
+    }
+
+    /// Loads models ahead of traffic, e.g. at startup. Preloads never evict
+    /// each other; models that do not fit are skipped and returned.
+    pub fn preload(&mut self, keys: &[ModelKey]) -> Vec<ModelKey> {
+        let protected: HashSet<ModelKey> = keys.iter().cloned().collect();
+        let mut skipped = Vec::new();
+        for key in keys {
+            if self.resident.contains_key(key) {
+                continue;
+            }
+            match self.load(key, &protected) {
+                Ok(_) => self.stats.preloads += 1,
+                Err(_) => skipped.push(key.clone()),
+            }
+        }
+        skipped
+    }
+
+    /// The K most-requested models, most requested first.
+    pub fn hottest(&self) -> Vec<ModelKey> {
+        let mut ranked: Vec<(&ModelKey, &u64)> = self.demand.iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.to_string().cmp(&b.0.to_string())));
+        ranked.into_iter().take(self.config.max_resident).map(|(k, _)| k.clone()).collect()
+    }
+
+    /// Preloads any of the K most-requested models that are not resident.
+    pub fn rebalance(&mut self) -> Vec<ModelKey> {
+        self.preload(&self.hottest())
+    }
+
+    /// Halves all demand counters so the ranking follows recent traffic.
+    pub fn decay_demand(&mut self) {
+        self.demand.retain(|_, count| {
+            *count /= 2;
+            *count > 0
+        });
+    }
+
+    fn load(
+        &mut self,
+        key: &ModelKey,
+        protected: &HashSet<ModelKey>,
+    ) -> Result<(Arc<L::Model>, Duration), WarmPoolError> {
+        let started = Instant::now();
+        let model = self
+            .loader
+            .load(key)
+            .map_err(|reason| WarmPoolError::Load { model: key.to_string(), reason })?;
+        let elapsed = started.elapsed();
+        let memory_bytes = self.loader.memory_bytes(&model);
+        if memory_bytes > self.config.gpu_memory_budget_bytes {
+            return Err(WarmPoolError::ExceedsBudget {
+                model: key.to_string(),
+                bytes: memory_bytes,
+                budget: self.config.gpu_memory_budget_bytes,
+            });
+        }
+        self.make_room(key, memory_bytes, protected)?;
+
+        let model = Arc::new(model);
+        self.resident.insert(key.clone(), Resident { model: model.clone(), memory_bytes, last_used: self.tick });
+        Ok((model, elapsed))
+    }
+
+    fn make_room(&mut self, key: &ModelKey, bytes: u64, protected: &HashSet<ModelKey>) -> Result<(), WarmPoolError> {
+        while self.resident.len() >= self.config.max_resident.max(1)
+            || self.resident_bytes() + bytes > self.config.gpu_memory_budget_bytes
+        {
+            let victim = self
+                .resident
+                .iter()
+                .filter(|(k, _)| !protected.contains(*k))
+                .min_by_key(|(k, r)| (self.demand.get(*k).copied().unwrap_or(0), r.last_used))
+                .map(|(k, _)| k.clone());
+            match victim {
+                Some(victim) => {
+                    self.resident.remove(&victim);
+                    self.stats.evictions += 1;
+                }
+                None => {
+                    return Err(WarmPoolError::ExceedsBudget {
+                        model: key.to_string(),
+                        bytes,
+                        budget: self.config.gpu_memory_budget_bytes.saturating_sub(self.resident_bytes()),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [`InferenceHandler`] that serves every endpoint from a shared warm pool.
+pub struct PooledInference<L: ModelLoader, R> {
+    pool: Mutex<WarmPool<L>>,
+    run: R,
+}
+
+impl<L, R> PooledInference<L, R>
+where
+    L: ModelLoader,
+    R: Fn(&L::Model, &InferenceRequest) -> InferenceResponse,
+{
+    pub fn new(pool: WarmPool<L>, run: R) -> Self {
+        Self { pool: Mutex::new(pool), run }
+    }
+
+    pub fn pool(&self) -> std::sync::MutexGuard<'_, WarmPool<L>> {
+        self.pool.lock().expect("warm pool poisoned")
+    }
+}
+
+impl<L, R> InferenceHandler for PooledInference<L, R>
+where
+    L: ModelLoader,
+    R: Fn(&L::Model, &InferenceRequest) -> InferenceResponse,
+{
+    fn infer(&self, endpoint: &ModelEndpoint, request: &InferenceRequest) -> InferenceResponse {
+        let acquired = self.pool().acquire(&ModelKey::for_endpoint(endpoint));
+        match acquired {
+            Ok((model, cold_start)) => {
+                let mut response = (self.run)(&model, request);
+                if let Some(latency) = cold_start {
+                    response
+                        .performance_metrics
+                        .insert("cold_start_ms".to_string(), latency.as_secs_f64() * 1000.0);
+                }
+                response
+            }
+            Err(err) => InferenceResponse {
+                request_id: request.id,
+                output_data: serde_json::Value::Null,
+                error: Some(err.to_string()),
+                performance_metrics: HashMap::new(),
+            },
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use super::error::IngestError;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+
+/// A record pulled from a source, before schema validation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IngestRecord {
+    /// Name of the connector that produced the record.
+    pub source: String,
+    pub key: Option<String>,
+    /// Timestamp reported by the source; datasets whose schema declares an
+    /// event-time field prefer that instead.
+    pub event_time: DateTime<Utc>,
+    pub payload: Value,
+}
+
+/// A streaming source polled by an [`IngestPipeline`](super::IngestPipeline).
+#[async_trait]
+pub trait Connector: Send {
+    fn name(&self) -> &str;
+
+    /// Returns every record that became available since the last poll.
+    async fn poll(&mut self) -> Result<Vec<IngestRecord>, IngestError>;
+}
+
+fn parse_json(source: &str, bytes: &[u8]) -> Result<Value, IngestError> {
+    serde_json::from_slice(bytes).map_err(|e| IngestError::MalformedPayload {
+        source_name: source.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+// --- Kafka ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaConfig {
+    pub brokers: Vec<String>,
+    pub topic: String,
+    pub group_id: String,
+    /// Upper bound on messages fetched per partition per poll.
+    pub max_batch: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaMessage {
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<Vec<u8>>,
+    pub payload: Vec<u8>,
+    pub timestamp_ms: i64,
+}
+
+/// Minimal consumer interface so the connector does not depend on a
+/// particular Kafka client library.
+#[async_trait]
+pub trait KafkaConsumer: Send {
+    async fn partitions(&mut self, topic: &str) -> Result<Vec<i32>, IngestError>;
+
+    /// Fetches up to `max` messages from `partition` starting at `offset`.
+    async fn fetch(
+        &mut self,
+        topic: &str,
+        partition: i32,
+        offset: i64,
+        max: usize,
+    ) -> Result<Vec<KafkaMessage>, IngestError>;
+}
+
+/// Reads JSON messages from a Kafka topic, tracking the next offset of
+/// every partition itself. Messages that are not JSON are skipped and
+/// counted rather than failing the poll.
+pub struct KafkaConnector {
+    pub config: KafkaConfig,
+    consumer: Box<dyn KafkaConsumer>,
+    offsets: HashMap<i32, i64>,
+    malformed: u64,
+}
+
+impl KafkaConnector {
+    pub fn new(config: KafkaConfig, consumer: Box<dyn KafkaConsumer>) -> Self {
+        Self { config, consumer, offsets: HashMap::new(), malformed: 0 }
+    }
+
+    /// Next offset to read for each partition, suitable for committing.
+    pub fn offsets(&self) -> &HashMap<i32, i64> {
+        &self.offsets
+    }
+
+    /// Messages skipped so far because their payload was not JSON.
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+}
+
+#[async_trait]
+impl Connector for KafkaConnector {
+    fn name(&self) -> &str {
+        &self.config.topic
+    }
+
+    async fn poll(&mut self) -> Result<Vec<IngestRecord>, IngestError> {
+        let mut records = Vec::new();
+        let mut offsets = Vec::new();
+        let mut malformed = 0;
+        for partition in self.consumer.partitions(&self.config.topic).await? {
+            let mut offset = *self.offsets.get(&partition).unwrap_or(&0);
+            let messages = self
+                .consumer
+                .fetch(&self.config.topic, partition, offset, self.config.max_batch)
+                .await?;
+            for msg in messages {
+                offset = msg.offset + 1;
+                let Ok(payload) = parse_json(&self.config.topic, &msg.payload) else {
+                    malformed += 1;
+                    continue;
+                };
+                records.push(IngestRecord {
+                    source: self.config.topic.clone(),
+                    key: msg.key.map(|k| String::from_utf8_lossy(&k).into_owned()),
+                    event_time: Utc.timestamp_millis_opt(msg.timestamp_ms).single().unwrap_or_else(Utc::now),
+                    payload,
+                });
+            }
+            offsets.push((partition, offset));
+        }
+        // Offsets only move once the records read up to them are returned,
+        // so a failed fetch rereads the whole batch next poll.
+        self.offsets.extend(offsets);
+        self.malformed += malformed;
+        Ok(records)
+    }
+}
+
+// --- Webhook ---
+
+/// Handle given to the HTTP layer; each call to [`WebhookHandle::submit`]
+/// queues one JSON body for the connector.
+#[derive(Debug, Clone)]
+pub struct WebhookHandle {
+    name: String,
+    secret: Option<String>,
+    tx: mpsc::Sender<IngestRecord>,
+}
+
+impl WebhookHandle {
+    /// Queues a pushed payload. Fails if the shared secret does not match or
+    /// the queue is full, so the HTTP layer can answer 401/429 respectively.
+    pub fn submit(&self, body: &[u8], secret: Option<&str>) -> Result<(), IngestError> {
+        if self.secret.is_some() && self.secret.as_deref() != secret {
+            return Err(IngestError::Source {
+                connector: self.name.clone(),
+                reason: "invalid webhook secret".into(),
+            });
+        }
+        let record = IngestRecord {
+            source: self.name.clone(),
+            key: None,
+            event_time: Utc::now(),
+            payload: parse_json(&self.name, body)?,
+        };
+        self.tx.try_send(record).map_err(|e| IngestError::Source {
+            connector: self.name.clone(),
+            reason: e.to_string(),
+        })
+    }
+}
+
+/// Receives records pushed over HTTP.
+pub struct WebhookConnector {
+    name: String,
+    rx: mpsc::Receiver<IngestRecord>,
+}
+
+impl WebhookConnector {
+    pub fn new(name: &str, capacity: usize, secret: Option<String>) -> (Self, WebhookHandle) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let handle = WebhookHandle { name: name.to_string(), secret, tx };
+        (Self { name: name.to_string(), rx }, handle)
+    }
+}
+
+#[async_trait]
+impl Connector for WebhookConnector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn poll(&mut self) -> Result<Vec<IngestRecord>, IngestError> {
+        let mut records = Vec::new();
+        while let Ok(record) = self.rx.try_recv() {
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
+// --- S3 polling ---
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectSummary {
+    pub key: String,
+    pub last_modified: DateTime<Utc>,
+    pub size: u64,
+}
+
+/// Object-store operations needed by [`S3PollingConnector`].
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn list(&self, bucket: &str, prefix: &str) -> Result<Vec<ObjectSummary>, IngestError>;
+    async fn get(&self, bucket: &str, key: &str) -> Result<Vec<u8>, IngestError>;
+}
+
+/// Periodically lists a bucket prefix and ingests new JSON-lines objects.
+/// Each object is read once; objects rewritten in place are not re-read.
+/// Lines that are not JSON are skipped and counted.
+pub struct S3PollingConnector {
+    name: String,
+    bucket: String,
+    prefix: String,
+    store: Box<dyn ObjectStore>,
+    seen: HashSet<String>,
+    malformed: u64,
+}
+
+impl S3PollingConnector {
+    pub fn new(bucket: &str, prefix: &str, store: Box<dyn ObjectStore>) -> Self {
+        Self {
+            name: format!("s3://{}/{}", bucket, prefix),
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            store,
+            seen: HashSet::new(),
+            malformed: 0,
+        }
+    }
+
+    /// Lines skipped so far because they were not JSON.
+    pub fn malformed(&self) -> u64 {
+        self.malformed
+    }
+}
+
+#[async_trait]
+impl Connector for S3PollingConnector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn poll(&mut self) -> Result<Vec<IngestRecord>, IngestError> {
+        let mut objects = self.store.list(&self.bucket, &self.prefix).await?;
+        objects.retain(|o| !self.seen.contains(&o.key));
+        objects.sort_by(|a, b| a.last_modified.cmp(&b.last_modified).then_with(|| a.key.cmp(&b.key)));
+
+        let mut records = Vec::new();
+        let mut read = Vec::new();
+        let mut malformed = 0;
+        for object in objects {
+            let body = self.store.get(&self.bucket, &object.key).await?;
+            for line in body.split(|b| *b == b'\n').filter(|l| !l.iter().all(u8::is_ascii_whitespace)) {
+                let Ok(payload) = parse_json(&self.name, line) else {
+                    malformed += 1;
+                    continue;
+                };
+                records.push(IngestRecord {
+                    source: self.name.clone(),
+                    key: Some(object.key.clone()),
+                    event_time: object.last_modified,
+                    payload,
+                });
+            }
+            read.push(object.key);
+        }
+        // Objects only count as read once their records are returned, so a
+        // failed get rereads them all next poll.
+        self.seen.extend(read);
+        self.malformed += malformed;
+        Ok(records)
+    }
+}
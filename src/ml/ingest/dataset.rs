@@ -0,0 +1,171 @@
+use super::connector::IngestRecord;
+use super::error::IngestError;
+use super::schema::RecordSchema;
+use super::watermark::Watermark;
+use chrono::{DateTime, Duration, Utc};
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::{ChunkManager, DataChunk};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct DatasetConfig {
+    /// Buffered records are sealed into a DFS segment once this many arrive.
+    pub segment_records: usize,
+    /// How far behind the newest event a record may arrive and still be kept.
+    pub allowed_lateness: Duration,
+}
+
+impl Default for DatasetConfig {
+    fn default() -> Self {
+        Self { segment_records: 1024, allowed_lateness: Duration::minutes(5) }
+    }
+}
+
+/// A sealed batch of records stored as one chunk in the DFS.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetSegment {
+    /// Content address of the chunk holding the JSON-lines payload.
+    pub chunk_id: String,
+    pub records: usize,
+    pub min_event_time: DateTime<Utc>,
+    pub max_event_time: DateTime<Utc>,
+}
+
+/// Counts for one call to [`DatasetRegistry::append`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppendOutcome {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub late: usize,
+    /// Segments sealed while appending.
+    pub sealed: usize,
+}
+
+/// A dataset that grows continuously from one or more connectors.
+#[derive(Debug, Clone)]
+pub struct IngestDataset {
+    pub id: Uuid,
+    pub name: String,
+    pub schema: RecordSchema,
+    pub config: DatasetConfig,
+    pub watermark: Watermark,
+    pub segments: Vec<DatasetSegment>,
+    pub total_records: u64,
+    pub rejected_records: u64,
+    pub late_records: u64,
+    buffer: Vec<(DateTime<Utc>, Value)>,
+}
+
+impl IngestDataset {
+    /// Records accepted but not yet sealed into a segment.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+/// Datasets registered for ingestion on this node, backed by the DFS chunk
+/// store.
+#[derive(Debug, Clone)]
+pub struct DatasetRegistry {
+    datasets: HashMap<Uuid, IngestDataset>,
+    storage: Arc<ChunkManager>,
+}
+
+impl DatasetRegistry {
+    pub fn new(storage: Arc<ChunkManager>) -> Self {
+        Self { datasets: HashMap::new(), storage }
+    }
+
+    pub fn register(&mut self, name: &str, schema: RecordSchema, config: DatasetConfig) -> Result<Uuid, IngestError> {
+        if self.datasets.values().any(|d| d.name == name) {
+            return Err(IngestError::DatasetExists(name.to_string()));
+        }
+        let id = Uuid::new_v4();
+        let dataset = IngestDataset {
+            id,
+            name: name.to_string(),
+            schema,
+            watermark: Watermark::new(config.allowed_lateness),
+            config,
+            segments: Vec::new(),
+            total_records: 0,
+            rejected_records: 0,
+            late_records: 0,
+            buffer: Vec::new(),
+        };
+        self.datasets.insert(id, dataset);
+        Ok(id)
+    }
+
+    pub fn dataset(&self, id: Uuid) -> Option<&IngestDataset> {
+        self.datasets.get(&id)
+    }
+
+    pub fn storage(&self) -> &Arc<ChunkManager> {
+        &self.storage
+    }
+
+    /// Validates and buffers records, sealing segments as buffers fill up.
+    /// Invalid and late records are counted and dropped rather than failing
+    /// the whole batch.
+    pub fn append(&mut self, id: Uuid, records: Vec<IngestRecord>) -> Result<AppendOutcome, IngestError> {
+        let dataset = self.datasets.get_mut(&id).ok_or(IngestError::DatasetNotFound(id))?;
+        let mut outcome = AppendOutcome::default();
+        for record in records {
+            if dataset.schema.validate(&record.payload).is_err() {
+                outcome.rejected += 1;
+                continue;
+            }
+            let event_time = dataset.schema.event_time(&record.payload).unwrap_or(record.event_time);
+            if !dataset.watermark.observe(event_time) {
+                outcome.late += 1;
+                continue;
+            }
+            dataset.buffer.push((event_time, record.payload));
+            outcome.accepted += 1;
+            if dataset.buffer.len() >= dataset.config.segment_records {
+                seal(dataset, &self.storage)?;
+                outcome.sealed += 1;
+            }
+        }
+        dataset.total_records += outcome.accepted as u64;
+        dataset.rejected_records += outcome.rejected as u64;
+        dataset.late_records += outcome.late as u64;
+        Ok(outcome)
+    }
+
+    /// Seals whatever is buffered into a segment, even if it is not full.
+    pub fn flush(&mut self, id: Uuid) -> Result<Option<DatasetSegment>, IngestError> {
+        let dataset = self.datasets.get_mut(&id).ok_or(IngestError::DatasetNotFound(id))?;
+        if dataset.buffer.is_empty() {
+            return Ok(None);
+        }
+        seal(dataset, &self.storage).map(Some)
+    }
+}
+
+fn seal(dataset: &mut IngestDataset, storage: &ChunkManager) -> Result<DatasetSegment, IngestError> {
+    let mut body = Vec::new();
+    for (_, payload) in &dataset.buffer {
+        serde_json::to_writer(&mut body, payload).map_err(|e| IngestError::Storage(e.to_string()))?;
+        body.push(b'\n');
+    }
+    let chunk = DataChunk::new_from_slice(body, dataset.segments.len() as u32, CompressionAlgorithm::Lz4)
+        .map_err(|e| IngestError::Storage(e.to_string()))?;
+    let chunk_id = chunk.id().as_str().to_string();
+    storage.store_chunk(chunk).map_err(|e| IngestError::Storage(e.to_string()))?;
+
+    let buffer = std::mem::take(&mut dataset.buffer);
+    let segment = DatasetSegment {
+        chunk_id,
+        records: buffer.len(),
+        min_event_time: buffer.iter().map(|(t, _)| *t).min().expect("non-empty buffer"),
+        max_event_time: buffer.iter().map(|(t, _)| *t).max().expect("non-empty buffer"),
+    };
+    dataset.segments.push(segment.clone());
+    Ok(segment)
+}
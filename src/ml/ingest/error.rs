@@ -0,0 +1,18 @@
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum IngestError {
+    #[error("dataset not found: {0}")]
+    DatasetNotFound(Uuid),
+    #[error("dataset already registered: {0}")]
+    DatasetExists(String),
+    #[error("field `{field}` {reason}")]
+    SchemaViolation { field: String, reason: String },
+    #[error("malformed payload from {source_name}: {reason}")]
+    MalformedPayload { source_name: String, reason: String },
+    #[error("connector {connector} failed: {reason}")]
+    Source { connector: String, reason: String },
+    #[error("storage error: {0}")]
+    Storage(String),
+}
@@ -0,0 +1,29 @@
+//! Streaming data ingestion.
+//!
+//! Connectors pull records from external sources (Kafka topics, webhook
+//! pushes, S3 buckets) and append them to datasets registered with a
+//! [`DatasetRegistry`]. Every record is validated against the dataset schema
+//! and checked against an event-time watermark before it is buffered; full
+//! buffers are sealed into segments stored as chunks in the DFS. An
+//! [`IngestPipeline`] drives the connectors and emits [`RetrainRequest`]s for
+//! the re-training pipelines bound to each dataset.
+
+pub mod connector;
+pub mod dataset;
+pub mod error;
+pub mod pipeline;
+pub mod schema;
+pub mod watermark;
+
+#[cfg(test)]
+mod tests;
+
+pub use connector::{
+    Connector, IngestRecord, KafkaConfig, KafkaConnector, KafkaConsumer, KafkaMessage, ObjectStore,
+    ObjectSummary, S3PollingConnector, WebhookConnector, WebhookHandle,
+};
+pub use dataset::{AppendOutcome, DatasetConfig, DatasetRegistry, DatasetSegment, IngestDataset};
+pub use error::IngestError;
+pub use pipeline::{IngestPipeline, IngestReport, RetrainPolicy, RetrainRequest};
+pub use schema::{FieldSchema, FieldType, RecordSchema};
+pub use watermark::Watermark;
@@ -0,0 +1,146 @@
+use super::connector::Connector;
+use super::dataset::{AppendOutcome, DatasetRegistry};
+use super::error::IngestError;
+use crate::ml::pipeline_orchestrator::{ExecutionStatus, PipelineExecution};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// When a dataset should kick off its re-training pipeline.
+#[derive(Debug, Clone)]
+pub struct RetrainPolicy {
+    pub pipeline_id: Uuid,
+    /// Minimum number of newly sealed records since the last trigger.
+    pub min_new_records: usize,
+    /// Minimum wall-clock time between two triggers.
+    pub min_interval: Duration,
+}
+
+/// Asks the orchestrator to re-train on a dataset up to `watermark`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetrainRequest {
+    pub pipeline_id: Uuid,
+    pub dataset_id: Uuid,
+    pub watermark: Option<DateTime<Utc>>,
+    /// Chunk ids of every sealed segment, oldest first.
+    pub segments: Vec<String>,
+    pub new_records: usize,
+}
+
+impl RetrainRequest {
+    /// A pending execution of the bound pipeline, attributed to ingestion.
+    pub fn into_execution(self) -> PipelineExecution {
+        PipelineExecution {
+            id: Uuid::new_v4(),
+            pipeline_id: self.pipeline_id,
+            status: ExecutionStatus::Pending,
+            task_statuses: HashMap::new(),
+            started_at: Utc::now(),
+            completed_at: None,
+            triggered_by: format!("ingest:{}", self.dataset_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IngestReport {
+    pub appended: HashMap<Uuid, AppendOutcome>,
+    /// Connectors whose poll failed; the others still ran.
+    pub errors: Vec<IngestError>,
+    pub retrain: Vec<RetrainRequest>,
+}
+
+#[derive(Debug, Clone)]
+struct RetrainState {
+    policy: RetrainPolicy,
+    sealed_records_at_trigger: usize,
+    last_trigger: Option<DateTime<Utc>>,
+}
+
+/// Drives connectors into their datasets and decides when to re-train.
+pub struct IngestPipeline {
+    pub registry: DatasetRegistry,
+    bindings: Vec<(Box<dyn Connector>, Uuid)>,
+    retrain: HashMap<Uuid, RetrainState>,
+}
+
+impl IngestPipeline {
+    pub fn new(registry: DatasetRegistry) -> Self {
+        Self { registry, bindings: Vec::new(), retrain: HashMap::new() }
+    }
+
+    /// Routes everything `connector` produces into `dataset_id`.
+    pub fn bind(&mut self, connector: Box<dyn Connector>, dataset_id: Uuid) -> Result<(), IngestError> {
+        self.registry.dataset(dataset_id).ok_or(IngestError::DatasetNotFound(dataset_id))?;
+        self.bindings.push((connector, dataset_id));
+        Ok(())
+    }
+
+    pub fn set_retrain_policy(&mut self, dataset_id: Uuid, policy: RetrainPolicy) -> Result<(), IngestError> {
+        self.registry.dataset(dataset_id).ok_or(IngestError::DatasetNotFound(dataset_id))?;
+        self.retrain.insert(
+            dataset_id,
+            RetrainState { policy, sealed_records_at_trigger: 0, last_trigger: None },
+        );
+        Ok(())
+    }
+
+    /// Polls every connector once, appends the results and evaluates the
+    /// re-training policies. Only sealed segments count towards a trigger so
+    /// that re-training always sees data that is durable in the DFS.
+    pub async fn run_once(&mut self, now: DateTime<Utc>) -> IngestReport {
+        let mut report = IngestReport::default();
+        for (connector, dataset_id) in &mut self.bindings {
+            let outcome = connector
+                .poll()
+                .await
+                .and_then(|records| self.registry.append(*dataset_id, records));
+            match outcome {
+                Ok(o) => {
+                    let total = report.appended.entry(*dataset_id).or_default();
+                    total.accepted += o.accepted;
+                    total.rejected += o.rejected;
+                    total.late += o.late;
+                    total.sealed += o.sealed;
+                }
+                Err(e) => report.errors.push(e),
+            }
+        }
+
+        for (dataset_id, state) in &mut self.retrain {
+            let Some(dataset) = self.registry.dataset(*dataset_id) else { continue };
+            let sealed: usize = dataset.segments.iter().map(|s| s.records).sum();
+            let new_records = sealed - state.sealed_records_at_trigger;
+            let interval_elapsed = state.last_trigger.is_none_or(|t| now - t >= state.policy.min_interval);
+            if new_records == 0 || new_records < state.policy.min_new_records || !interval_elapsed {
+                continue;
+            }
+            state.sealed_records_at_trigger = sealed;
+            state.last_trigger = Some(now);
+            report.retrain.push(RetrainRequest {
+                pipeline_id: state.policy.pipeline_id,
+                dataset_id: *dataset_id,
+                watermark: dataset.watermark.current(),
+                segments: dataset.segments.iter().map(|s| s.chunk_id.clone()).collect(),
+                new_records,
+            });
+        }
+        report
+    }
+
+    /// Runs [`IngestPipeline::run_once`] every `interval`, forwarding
+    /// re-training requests until the receiver is dropped.
+    pub async fn run(mut self, interval: std::time::Duration, tx: mpsc::Sender<RetrainRequest>) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for request in self.run_once(Utc::now()).await.retrain {
+                if tx.send(request).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
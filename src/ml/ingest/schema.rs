@@ -0,0 +1,118 @@
+use super::error::IngestError;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FieldType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    /// RFC 3339 string or integer milliseconds since the Unix epoch.
+    Timestamp,
+    /// Any JSON value, including nested objects and arrays.
+    Json,
+}
+
+impl FieldType {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Float => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Timestamp => parse_timestamp(value).is_some(),
+            FieldType::Json => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub field_type: FieldType,
+    pub required: bool,
+}
+
+/// Shape every record appended to a dataset must have.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct RecordSchema {
+    pub fields: Vec<FieldSchema>,
+    /// Field carrying the record's event time. When unset the timestamp
+    /// reported by the source is used instead.
+    pub event_time_field: Option<String>,
+    /// Reject records with fields not listed in `fields`.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl RecordSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, name: &str, field_type: FieldType, required: bool) -> Self {
+        self.fields.push(FieldSchema { name: name.to_string(), field_type, required });
+        self
+    }
+
+    pub fn with_event_time(mut self, name: &str) -> Self {
+        self.event_time_field = Some(name.to_string());
+        self
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Checks a record against the schema. Null values count as missing.
+    pub fn validate(&self, record: &Value) -> Result<(), IngestError> {
+        let object = record.as_object().ok_or_else(|| IngestError::SchemaViolation {
+            field: "$".into(),
+            reason: "record is not a JSON object".into(),
+        })?;
+        for field in &self.fields {
+            match object.get(&field.name).filter(|v| !v.is_null()) {
+                None if field.required => {
+                    return Err(IngestError::SchemaViolation {
+                        field: field.name.clone(),
+                        reason: "is required".into(),
+                    })
+                }
+                None => {}
+                Some(value) if !field.field_type.accepts(value) => {
+                    return Err(IngestError::SchemaViolation {
+                        field: field.name.clone(),
+                        reason: format!("expected {:?}", field.field_type),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        if self.strict {
+            if let Some(extra) = object.keys().find(|k| !self.fields.iter().any(|f| &f.name == *k)) {
+                return Err(IngestError::SchemaViolation {
+                    field: extra.clone(),
+                    reason: "is not declared in the schema".into(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Event time carried by the record itself, if the schema declares one.
+    pub fn event_time(&self, record: &Value) -> Option<DateTime<Utc>> {
+        let name = self.event_time_field.as_ref()?;
+        parse_timestamp(record.get(name)?)
+    }
+}
+
+pub(crate) fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc)),
+        Value::Number(n) => Utc.timestamp_millis_opt(n.as_i64()?).single(),
+        _ => None,
+    }
+}
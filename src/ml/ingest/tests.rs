@@ -0,0 +1,200 @@
+use super::*;
+use async_trait::async_trait;
+use chrono::{Duration, TimeZone, Utc};
+use runtime::large_data_transfer::{ChunkId, ChunkManager};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn schema() -> RecordSchema {
+    RecordSchema::new()
+        .field("user", FieldType::String, true)
+        .field("clicks", FieldType::Integer, true)
+        .field("ts", FieldType::Timestamp, true)
+        .with_event_time("ts")
+}
+
+fn event(user: &str, clicks: i64, ts_ms: i64) -> Vec<u8> {
+    serde_json::to_vec(&json!({ "user": user, "clicks": clicks, "ts": ts_ms })).unwrap()
+}
+
+struct MemoryConsumer {
+    partitions: HashMap<i32, Vec<KafkaMessage>>,
+    /// Partition whose next fetch fails.
+    failing: Option<i32>,
+}
+
+#[async_trait]
+impl KafkaConsumer for MemoryConsumer {
+    async fn partitions(&mut self, _topic: &str) -> Result<Vec<i32>, IngestError> {
+        let mut ids: Vec<i32> = self.partitions.keys().copied().collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn fetch(&mut self, _topic: &str, partition: i32, offset: i64, max: usize) -> Result<Vec<KafkaMessage>, IngestError> {
+        if self.failing.take_if(|p| *p == partition).is_some() {
+            return Err(IngestError::Source { connector: "kafka".into(), reason: "broker unavailable".into() });
+        }
+        Ok(self.partitions[&partition].iter().filter(|m| m.offset >= offset).take(max).cloned().collect())
+    }
+}
+
+struct MemoryBucket {
+    objects: Vec<(ObjectSummary, Vec<u8>)>,
+}
+
+#[async_trait]
+impl ObjectStore for MemoryBucket {
+    async fn list(&self, _bucket: &str, prefix: &str) -> Result<Vec<ObjectSummary>, IngestError> {
+        Ok(self.objects.iter().filter(|(o, _)| o.key.starts_with(prefix)).map(|(o, _)| o.clone()).collect())
+    }
+
+    async fn get(&self, _bucket: &str, key: &str) -> Result<Vec<u8>, IngestError> {
+        Ok(self.objects.iter().find(|(o, _)| o.key == key).map(|(_, b)| b.clone()).unwrap_or_default())
+    }
+}
+
+#[test]
+fn schema_rejects_missing_and_mistyped_fields() {
+    let schema = schema().strict();
+    assert!(schema.validate(&json!({ "user": "a", "clicks": 3, "ts": "2024-01-01T00:00:00Z" })).is_ok());
+    assert!(matches!(
+        schema.validate(&json!({ "user": "a", "ts": 0 })),
+        Err(IngestError::SchemaViolation { field, .. }) if field == "clicks"
+    ));
+    assert!(schema.validate(&json!({ "user": "a", "clicks": "3", "ts": 0 })).is_err());
+    assert!(schema.validate(&json!({ "user": "a", "clicks": 3, "ts": 0, "extra": 1 })).is_err());
+}
+
+#[test]
+fn watermark_drops_late_events() {
+    let mut wm = Watermark::new(Duration::seconds(10));
+    let t0 = Utc.timestamp_opt(1_000, 0).unwrap();
+    assert!(wm.observe(t0));
+    assert!(wm.observe(t0 + Duration::seconds(30)));
+    assert_eq!(wm.current(), Some(t0 + Duration::seconds(20)));
+    assert!(wm.observe(t0 + Duration::seconds(25)));
+    assert!(!wm.observe(t0 + Duration::seconds(5)));
+    assert_eq!(wm.current(), Some(t0 + Duration::seconds(20)));
+}
+
+#[tokio::test]
+async fn kafka_records_are_sealed_into_dfs_segments() {
+    let storage = Arc::new(ChunkManager::default());
+    let mut registry = DatasetRegistry::new(storage.clone());
+    let config = DatasetConfig { segment_records: 2, allowed_lateness: Duration::seconds(60) };
+    let id = registry.register("clicks", schema(), config).unwrap();
+
+    let messages = (0..5)
+        .map(|i| KafkaMessage {
+            partition: 0,
+            offset: i,
+            key: None,
+            payload: if i == 3 { b"{\"user\": 1}".to_vec() } else { event("u", i, 1_000 + i) },
+            timestamp_ms: 0,
+        })
+        .collect();
+    let consumer = MemoryConsumer { partitions: HashMap::from([(0, messages)]), failing: None };
+    let config = KafkaConfig { brokers: vec!["localhost:9092".into()], topic: "clicks".into(), group_id: "bcai".into(), max_batch: 100 };
+    let mut kafka = KafkaConnector::new(config, Box::new(consumer));
+
+    let records = kafka.poll().await.unwrap();
+    assert_eq!(kafka.offsets()[&0], 5);
+    assert!(kafka.poll().await.unwrap().is_empty());
+
+    let outcome = registry.append(id, records).unwrap();
+    assert_eq!(outcome, AppendOutcome { accepted: 4, rejected: 1, late: 0, sealed: 2 });
+
+    let dataset = registry.dataset(id).unwrap();
+    assert_eq!(dataset.segments.len(), 2);
+    assert_eq!(dataset.buffered(), 0);
+    let chunk = storage.get_chunk(&ChunkId::from_hex(&dataset.segments[1].chunk_id).unwrap()).unwrap();
+    let body = String::from_utf8(chunk.decompress().unwrap()).unwrap();
+    assert_eq!(body.lines().count(), 2);
+    assert_eq!(dataset.segments[0].min_event_time, Utc.timestamp_millis_opt(1_000).unwrap());
+}
+
+#[tokio::test]
+async fn kafka_skips_malformed_messages_and_keeps_offsets_on_failure() {
+    let message = |partition, offset: i64, payload: Vec<u8>| KafkaMessage { partition, offset, key: None, payload, timestamp_ms: 0 };
+    let consumer = MemoryConsumer {
+        partitions: HashMap::from([
+            (0, vec![message(0, 0, event("u", 0, 0)), message(0, 1, b"not json".to_vec()), message(0, 2, event("u", 2, 0))]),
+            (1, vec![message(1, 0, event("v", 0, 0))]),
+        ]),
+        failing: Some(1),
+    };
+    let config = KafkaConfig { brokers: vec!["localhost:9092".into()], topic: "clicks".into(), group_id: "bcai".into(), max_batch: 100 };
+    let mut kafka = KafkaConnector::new(config, Box::new(consumer));
+
+    // A failed fetch loses nothing: partition 0 is read again next poll.
+    assert!(kafka.poll().await.is_err());
+    assert!(kafka.offsets().is_empty());
+
+    let records = kafka.poll().await.unwrap();
+    assert_eq!(records.iter().map(|r| r.payload["user"].as_str().unwrap()).collect::<Vec<_>>(), ["u", "u", "v"]);
+    assert_eq!(kafka.malformed(), 1);
+    assert_eq!(kafka.offsets(), &HashMap::from([(0, 3), (1, 1)]));
+}
+
+#[tokio::test]
+async fn webhook_checks_secret_and_parses_json() {
+    let (mut connector, handle) = WebhookConnector::new("hook", 8, Some("s3cret".into()));
+    assert!(handle.submit(&event("a", 1, 0), Some("wrong")).is_err());
+    assert!(matches!(handle.submit(b"not json", Some("s3cret")), Err(IngestError::MalformedPayload { .. })));
+    handle.submit(&event("a", 1, 0), Some("s3cret")).unwrap();
+    let records = connector.poll().await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].payload["user"], "a");
+}
+
+#[tokio::test]
+async fn s3_polling_triggers_retraining_once_enough_data_is_sealed() {
+    let storage = Arc::new(ChunkManager::default());
+    let mut registry = DatasetRegistry::new(storage);
+    let config = DatasetConfig { segment_records: 3, allowed_lateness: Duration::seconds(60) };
+    let id = registry.register("clicks", schema(), config).unwrap();
+
+    let lines: Vec<u8> = (0..4).flat_map(|i| [event("u", i, 2_000 + i), b"\n".to_vec()].concat()).collect();
+    let bucket = MemoryBucket {
+        objects: vec![(
+            ObjectSummary { key: "in/part-0.jsonl".into(), last_modified: Utc::now(), size: lines.len() as u64 },
+            lines,
+        )],
+    };
+
+    let mut pipeline = IngestPipeline::new(registry);
+    pipeline.bind(Box::new(S3PollingConnector::new("data", "in/", Box::new(bucket))), id).unwrap();
+    let pipeline_id = uuid::Uuid::new_v4();
+    let policy = RetrainPolicy { pipeline_id, min_new_records: 3, min_interval: Duration::minutes(10) };
+    pipeline.set_retrain_policy(id, policy).unwrap();
+
+    let now = Utc::now();
+    let report = pipeline.run_once(now).await;
+    assert!(report.errors.is_empty());
+    assert_eq!(report.appended[&id].accepted, 4);
+    assert_eq!(report.retrain.len(), 1);
+    let request = report.retrain[0].clone();
+    assert_eq!(request.new_records, 3);
+    assert_eq!(request.segments.len(), 1);
+    assert_eq!(request.watermark, Some(Utc.timestamp_millis_opt(2_003).unwrap() - Duration::seconds(60)));
+    assert_eq!(request.into_execution().pipeline_id, pipeline_id);
+
+    // The object was already consumed, so nothing new arrives.
+    let report = pipeline.run_once(now + Duration::minutes(20)).await;
+    assert_eq!(report.appended[&id].accepted, 0);
+    assert!(report.retrain.is_empty());
+}
+
+#[tokio::test]
+async fn s3_polling_skips_malformed_lines() {
+    let body = [event("u", 0, 0), b"\n{truncated\n".to_vec(), event("u", 1, 0)].concat();
+    let bucket = MemoryBucket {
+        objects: vec![(ObjectSummary { key: "in/part-0.jsonl".into(), last_modified: Utc::now(), size: body.len() as u64 }, body)],
+    };
+    let mut connector = S3PollingConnector::new("data", "in/", Box::new(bucket));
+    assert_eq!(connector.poll().await.unwrap().len(), 2);
+    assert_eq!(connector.malformed(), 1);
+    assert!(connector.poll().await.unwrap().is_empty());
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Event-time watermark with a fixed allowed lateness.
+///
+/// The watermark trails the largest event time seen by `allowed_lateness`.
+/// Records older than the watermark are considered late and are dropped, so
+/// everything at or before the watermark is complete and safe to train on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    pub allowed_lateness: Duration,
+    max_event_time: Option<DateTime<Utc>>,
+}
+
+impl Watermark {
+    pub fn new(allowed_lateness: Duration) -> Self {
+        Self { allowed_lateness, max_event_time: None }
+    }
+
+    /// Current watermark, or `None` before the first record.
+    pub fn current(&self) -> Option<DateTime<Utc>> {
+        self.max_event_time.map(|t| t - self.allowed_lateness)
+    }
+
+    pub fn is_late(&self, event_time: DateTime<Utc>) -> bool {
+        self.current().is_some_and(|w| event_time < w)
+    }
+
+    /// Records an on-time event and advances the watermark. Returns false,
+    /// leaving the watermark untouched, if the event is late.
+    pub fn observe(&mut self, event_time: DateTime<Utc>) -> bool {
+        if self.is_late(event_time) {
+            return false;
+        }
+        if self.max_event_time.is_none_or(|max| event_time > max) {
+            self.max_event_time = Some(event_time);
+        }
+        true
+    }
+}
@@ -3,4 +3,8 @@ pub mod pipeline_orchestrator;
 pub mod model_registry;
 pub mod distributed_training;
 pub mod inference_engine;
-pub mod monitoring; 
\ No newline at end of file
+pub mod monitoring;
+pub mod vector_store;
+pub mod ingest;
+pub mod model_card;
+pub mod fairness; 
\ No newline at end of file
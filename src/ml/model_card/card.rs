@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// Owner-supplied statement of what the model is for.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct IntendedUse {
+    pub primary_uses: Vec<String>,
+    pub primary_users: Vec<String>,
+    pub out_of_scope: Vec<String>,
+}
+
+/// A dataset that contributed to training.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataLineage {
+    pub dataset_id: Uuid,
+    /// Artifact name or DFS location the data was read from, if known.
+    pub source: Option<String>,
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrainingSummary {
+    pub framework: String,
+    pub framework_version: String,
+    pub hyperparameters: BTreeMap<String, serde_json::Value>,
+}
+
+/// Metrics measured on one evaluation dataset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvaluationResult {
+    pub dataset_id: Uuid,
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// Per-group values of one metric across a sensitive attribute.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BiasAudit {
+    pub attribute: String,
+    pub metric: String,
+    pub groups: BTreeMap<String, f64>,
+    /// Largest acceptable gap between the best and worst group.
+    pub max_disparity: f64,
+}
+
+impl BiasAudit {
+    /// Gap between the best and worst performing group.
+    pub fn disparity(&self) -> f64 {
+        let max = self.groups.values().copied().fold(f64::MIN, f64::max);
+        let min = self.groups.values().copied().fold(f64::MAX, f64::min);
+        if self.groups.is_empty() { 0.0 } else { max - min }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.disparity() <= self.max_disparity
+    }
+}
+
+/// Documentation for one model version.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelCard {
+    pub model_id: Uuid,
+    pub model_name: String,
+    pub version: String,
+    pub model_format: String,
+    pub owner: String,
+    pub description: String,
+    pub generated_at: DateTime<Utc>,
+    pub intended_use: IntendedUse,
+    pub data_lineage: Vec<DataLineage>,
+    pub training: Option<TrainingSummary>,
+    pub metrics: BTreeMap<String, f64>,
+    pub evaluations: Vec<EvaluationResult>,
+    pub bias_audits: Vec<BiasAudit>,
+}
+
+impl ModelCard {
+    /// True when every bias audit is within its disparity budget.
+    pub fn is_compliant(&self) -> bool {
+        self.bias_audits.iter().all(BiasAudit::passed)
+    }
+
+    /// Renders the card as a standalone HTML fragment for the dashboard.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<article class=\"model-card\"><h1>{} v{}</h1><p>{}</p><p>Owner: {} &middot; Format: {} &middot; Generated: {}</p>",
+            escape(&self.model_name),
+            escape(&self.version),
+            escape(&self.description),
+            escape(&self.owner),
+            escape(&self.model_format),
+            self.generated_at.to_rfc3339()
+        );
+
+        html.push_str("<h2>Intended use</h2>");
+        list(&mut html, "Primary uses", &self.intended_use.primary_uses);
+        list(&mut html, "Primary users", &self.intended_use.primary_users);
+        list(&mut html, "Out of scope", &self.intended_use.out_of_scope);
+
+        html.push_str("<h2>Training data</h2><ul>");
+        for lineage in &self.data_lineage {
+            let _ = write!(
+                html,
+                "<li>{} {}</li>",
+                lineage.dataset_id,
+                escape(lineage.source.as_deref().unwrap_or(""))
+            );
+        }
+        html.push_str("</ul>");
+
+        if let Some(training) = &self.training {
+            let _ = write!(
+                html,
+                "<h2>Training</h2><p>{} {}</p>",
+                escape(&training.framework),
+                escape(&training.framework_version)
+            );
+            table(&mut html, training.hyperparameters.iter().map(|(k, v)| (k.as_str(), v.to_string())));
+        }
+
+        html.push_str("<h2>Metrics</h2>");
+        table(&mut html, self.metrics.iter().map(|(k, v)| (k.as_str(), format!("{:.4}", v))));
+
+        if !self.evaluations.is_empty() {
+            html.push_str("<h2>Evaluation results</h2>");
+            for eval in &self.evaluations {
+                let _ = write!(html, "<h3>{}</h3>", eval.dataset_id);
+                table(&mut html, eval.metrics.iter().map(|(k, v)| (k.as_str(), format!("{:.4}", v))));
+            }
+        }
+
+        if !self.bias_audits.is_empty() {
+            html.push_str("<h2>Bias audits</h2>");
+            for audit in &self.bias_audits {
+                let _ = write!(
+                    html,
+                    "<h3>{} by {}: {} (disparity {:.4}, limit {:.4})</h3>",
+                    escape(&audit.metric),
+                    escape(&audit.attribute),
+                    if audit.passed() { "pass" } else { "FAIL" },
+                    audit.disparity(),
+                    audit.max_disparity
+                );
+                table(&mut html, audit.groups.iter().map(|(k, v)| (k.as_str(), format!("{:.4}", v))));
+            }
+        }
+        html.push_str("</article>");
+        html
+    }
+}
+
+fn list(html: &mut String, title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    let _ = write!(html, "<h3>{}</h3><ul>", title);
+    for item in items {
+        let _ = write!(html, "<li>{}</li>", escape(item));
+    }
+    html.push_str("</ul>");
+}
+
+fn table<'a>(html: &mut String, rows: impl Iterator<Item = (&'a str, String)>) {
+    html.push_str("<table>");
+    for (key, value) in rows {
+        let _ = write!(html, "<tr><th>{}</th><td>{}</td></tr>", escape(key), escape(&value));
+    }
+    html.push_str("</table>");
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,103 @@
+use super::card::{BiasAudit, DataLineage, EvaluationResult, IntendedUse, ModelCard, TrainingSummary};
+use crate::ml::model_registry::{ArtifactType, ModelMetadata};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ModelCardError {
+    #[error("model {model} has no version {version}")]
+    UnknownModelVersion { model: Uuid, version: String },
+    #[error("model card signature is invalid")]
+    InvalidSignature,
+    #[error("storage error: {0}")]
+    Storage(String),
+}
+
+/// Results of one experiment run against a model version.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExperimentRecord {
+    pub experiment_id: Uuid,
+    pub model_id: Uuid,
+    pub version: String,
+    pub evaluations: Vec<EvaluationResult>,
+    pub bias_audits: Vec<BiasAudit>,
+}
+
+/// Collects experiment data and produces cards for registered models.
+#[derive(Debug, Clone, Default)]
+pub struct ModelCardGenerator {
+    experiments: Vec<ExperimentRecord>,
+}
+
+impl ModelCardGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_experiment(&mut self, experiment: ExperimentRecord) {
+        self.experiments.push(experiment);
+    }
+
+    /// Builds the card for `version` of `model`. Lineage comes from the
+    /// version's training metadata and dataset artifacts; evaluations and
+    /// audits from every experiment recorded for that version.
+    pub fn generate(
+        &self,
+        model: &ModelMetadata,
+        version: &str,
+        intended_use: IntendedUse,
+    ) -> Result<ModelCard, ModelCardError> {
+        let model_version = model
+            .versions
+            .iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| ModelCardError::UnknownModelVersion { model: model.id, version: version.to_string() })?;
+
+        let mut data_lineage: Vec<DataLineage> = model_version
+            .artifacts
+            .iter()
+            .filter(|a| matches!(a.artifact_type, ArtifactType::Dataset))
+            .map(|a| DataLineage {
+                dataset_id: a.id,
+                source: Some(a.location.clone()),
+                checksum: Some(a.checksum.clone()),
+            })
+            .collect();
+        if let Some(meta) = &model_version.training_metadata {
+            if !data_lineage.iter().any(|d| d.dataset_id == meta.dataset_id) {
+                data_lineage.insert(0, DataLineage { dataset_id: meta.dataset_id, source: None, checksum: None });
+            }
+        }
+
+        let experiments = self
+            .experiments
+            .iter()
+            .filter(|e| e.model_id == model.id && e.version == version);
+
+        Ok(ModelCard {
+            model_id: model.id,
+            model_name: model.name.clone(),
+            version: version.to_string(),
+            model_format: model_version.model_format.clone(),
+            owner: model.owner.clone(),
+            description: model.description.clone(),
+            generated_at: Utc::now(),
+            intended_use,
+            data_lineage,
+            training: model_version.training_metadata.as_ref().map(|m| TrainingSummary {
+                framework: m.training_framework.clone(),
+                framework_version: m.framework_version.clone(),
+                hyperparameters: m.hyperparameters.clone().into_iter().collect(),
+            }),
+            metrics: model_version
+                .training_metadata
+                .as_ref()
+                .map(|m| m.metrics.clone().into_iter().collect())
+                .unwrap_or_default(),
+            evaluations: experiments.clone().flat_map(|e| e.evaluations.clone()).collect(),
+            bias_audits: experiments.flat_map(|e| e.bias_audits.clone()).collect(),
+        })
+    }
+}
@@ -0,0 +1,19 @@
+//! Model cards and compliance metadata.
+//!
+//! A [`ModelCardGenerator`] assembles a [`ModelCard`] for one model version
+//! from the registry entry and the experiments run against it: training data
+//! lineage, headline metrics, evaluation results and bias audits, plus the
+//! intended-use statement supplied by the owner. Cards are signed by the
+//! publisher and stored in the DFS; the returned artifact is linked from the
+//! model version so the dashboard can fetch and render it.
+
+pub mod card;
+pub mod generator;
+pub mod signed;
+
+#[cfg(test)]
+mod tests;
+
+pub use card::{BiasAudit, DataLineage, EvaluationResult, IntendedUse, ModelCard, TrainingSummary};
+pub use generator::{ExperimentRecord, ModelCardError, ModelCardGenerator};
+pub use signed::{link_model_card, SignedModelCard};
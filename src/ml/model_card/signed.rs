@@ -0,0 +1,82 @@
+use super::card::ModelCard;
+use super::generator::ModelCardError;
+use crate::ml::model_registry::{ArtifactType, ModelArtifact, ModelVersion};
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::{ChunkId, ChunkManager, DataChunk};
+use schnorrkel::{signing_context, PublicKey, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const SIGNING_CONTEXT: &[u8] = b"bcai-model-card";
+
+/// A model card together with the publisher's Schnorrkel signature over its
+/// canonical JSON encoding.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignedModelCard {
+    pub card: ModelCard,
+    /// Hex-encoded public key of the publisher.
+    pub signer: String,
+    /// Hex-encoded signature.
+    pub signature: String,
+}
+
+impl SignedModelCard {
+    pub fn sign(card: ModelCard, secret_key: &SecretKey) -> Self {
+        let public = secret_key.to_public();
+        let msg = serde_json::to_vec(&card).expect("model card serializes");
+        let sig = secret_key.sign(signing_context(SIGNING_CONTEXT).bytes(&msg), &public);
+        Self {
+            card,
+            signer: hex::encode(public.to_bytes()),
+            signature: hex::encode(sig.to_bytes()),
+        }
+    }
+
+    pub fn verify(&self) -> bool {
+        let public = hex::decode(&self.signer).ok().and_then(|b| PublicKey::from_bytes(&b).ok());
+        let sig = hex::decode(&self.signature).ok().and_then(|b| Signature::from_bytes(&b).ok());
+        let (Some(public), Some(sig)) = (public, sig) else { return false };
+        let msg = serde_json::to_vec(&self.card).expect("model card serializes");
+        public.verify(signing_context(SIGNING_CONTEXT).bytes(&msg), &sig).is_ok()
+    }
+
+    /// Stores the signed card as a DFS chunk and returns the artifact that
+    /// points at it.
+    pub fn publish(&self, storage: &ChunkManager) -> Result<ModelArtifact, ModelCardError> {
+        let body = serde_json::to_vec(self).map_err(|e| ModelCardError::Storage(e.to_string()))?;
+        let size = body.len() as u64;
+        let chunk = DataChunk::new_from_slice(body, 0, CompressionAlgorithm::Lz4)
+            .map_err(|e| ModelCardError::Storage(e.to_string()))?;
+        let chunk_id = chunk.id().as_str().to_string();
+        storage.store_chunk(chunk).map_err(|e| ModelCardError::Storage(e.to_string()))?;
+        Ok(ModelArtifact {
+            id: Uuid::new_v4(),
+            name: format!("{}-{}-model-card.json", self.card.model_name, self.card.version),
+            artifact_type: ArtifactType::ModelCard,
+            location: format!("dfs://{}", chunk_id),
+            size_bytes: size,
+            checksum: chunk_id,
+        })
+    }
+
+    /// Reads a published card back from the DFS and checks its signature.
+    pub fn fetch(storage: &ChunkManager, artifact: &ModelArtifact) -> Result<Self, ModelCardError> {
+        let id = artifact.location.strip_prefix("dfs://").unwrap_or(&artifact.location);
+        let chunk_id = ChunkId::from_hex(id).map_err(|e| ModelCardError::Storage(e.to_string()))?;
+        let chunk = storage
+            .get_chunk(&chunk_id)
+            .ok_or_else(|| ModelCardError::Storage(format!("chunk {} not found", id)))?;
+        let body = chunk.decompress().map_err(|e| ModelCardError::Storage(e.to_string()))?;
+        let signed: Self = serde_json::from_slice(&body).map_err(|e| ModelCardError::Storage(e.to_string()))?;
+        if !signed.verify() {
+            return Err(ModelCardError::InvalidSignature);
+        }
+        Ok(signed)
+    }
+}
+
+/// Attaches a published card to its model version, replacing any older card.
+pub fn link_model_card(version: &mut ModelVersion, artifact: ModelArtifact) {
+    version.artifacts.retain(|a| !matches!(a.artifact_type, ArtifactType::ModelCard));
+    version.artifacts.push(artifact);
+}
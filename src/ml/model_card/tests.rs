@@ -0,0 +1,118 @@
+use super::*;
+use crate::ml::model_registry::{ArtifactType, ModelArtifact, ModelMetadata, ModelVersion, TrainingMetadata};
+use runtime::large_data_transfer::ChunkManager;
+use schnorrkel::Keypair;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+fn model(dataset_id: Uuid) -> ModelMetadata {
+    ModelMetadata {
+        id: Uuid::new_v4(),
+        name: "credit-risk".into(),
+        description: "Scores loan applications <beta>".into(),
+        versions: vec![ModelVersion {
+            version: "2.0".into(),
+            model_format: "onnx".into(),
+            created_at: chrono::Utc::now(),
+            artifacts: vec![ModelArtifact {
+                id: dataset_id,
+                name: "applications".into(),
+                artifact_type: ArtifactType::Dataset,
+                location: "dfs://applications-2024".into(),
+                size_bytes: 1024,
+                checksum: "abc".into(),
+            }],
+            training_metadata: Some(TrainingMetadata {
+                training_framework: "candle".into(),
+                framework_version: "0.4".into(),
+                dataset_id,
+                hyperparameters: HashMap::from([("lr".to_string(), serde_json::json!(0.01))]),
+                metrics: HashMap::from([("accuracy".to_string(), 0.91)]),
+            }),
+            fairness_reports: vec![],
+        }],
+        tags: vec![],
+        owner: "risk-team".into(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        active_version: None,
+    }
+}
+
+fn experiment(model: &ModelMetadata, disparity_budget: f64) -> ExperimentRecord {
+    ExperimentRecord {
+        experiment_id: Uuid::new_v4(),
+        model_id: model.id,
+        version: "2.0".into(),
+        evaluations: vec![EvaluationResult {
+            dataset_id: Uuid::new_v4(),
+            metrics: BTreeMap::from([("auc".to_string(), 0.88)]),
+        }],
+        bias_audits: vec![BiasAudit {
+            attribute: "age_band".into(),
+            metric: "approval_rate".into(),
+            groups: BTreeMap::from([("18-25".to_string(), 0.42), ("26-60".to_string(), 0.50)]),
+            max_disparity: disparity_budget,
+        }],
+    }
+}
+
+#[test]
+fn card_collects_lineage_metrics_and_audits() {
+    let dataset_id = Uuid::new_v4();
+    let model = model(dataset_id);
+    let mut generator = ModelCardGenerator::new();
+    generator.record_experiment(experiment(&model, 0.1));
+
+    let card = generator.generate(&model, "2.0", IntendedUse::default()).unwrap();
+    assert_eq!(card.data_lineage.len(), 1);
+    assert_eq!(card.data_lineage[0].source.as_deref(), Some("dfs://applications-2024"));
+    assert_eq!(card.metrics["accuracy"], 0.91);
+    assert_eq!(card.evaluations.len(), 1);
+    assert!(card.is_compliant());
+
+    let html = card.to_html();
+    assert!(html.contains("credit-risk v2.0"));
+    assert!(html.contains("&lt;beta&gt;"));
+    assert!(html.contains("approval_rate by age_band: pass"));
+
+    assert_eq!(
+        generator.generate(&model, "9.9", IntendedUse::default()),
+        Err(ModelCardError::UnknownModelVersion { model: model.id, version: "9.9".into() })
+    );
+}
+
+#[test]
+fn failing_audit_marks_card_non_compliant() {
+    let model = model(Uuid::new_v4());
+    let mut generator = ModelCardGenerator::new();
+    generator.record_experiment(experiment(&model, 0.05));
+    let card = generator.generate(&model, "2.0", IntendedUse::default()).unwrap();
+    assert!(!card.is_compliant());
+    assert!(card.to_html().contains("FAIL"));
+}
+
+#[test]
+fn signed_card_round_trips_through_dfs() {
+    let mut model = model(Uuid::new_v4());
+    let card = ModelCardGenerator::new().generate(&model, "2.0", IntendedUse::default()).unwrap();
+    let keypair = Keypair::generate();
+    let signed = SignedModelCard::sign(card, &keypair.secret);
+    assert!(signed.verify());
+
+    let storage = ChunkManager::default();
+    let artifact = signed.publish(&storage).unwrap();
+    link_model_card(&mut model.versions[0], artifact.clone());
+    link_model_card(&mut model.versions[0], artifact.clone());
+    let cards = model.versions[0]
+        .artifacts
+        .iter()
+        .filter(|a| matches!(a.artifact_type, ArtifactType::ModelCard))
+        .count();
+    assert_eq!(cards, 1);
+    assert_eq!(SignedModelCard::fetch(&storage, &artifact).unwrap(), signed);
+
+    let mut tampered = signed.clone();
+    tampered.card.owner = "mallory".into();
+    assert!(!tampered.verify());
+}
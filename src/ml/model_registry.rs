@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
+use thiserror::Error;
 use uuid::Uuid;
+use crate::ml::fairness::{FairnessGate, FairnessViolation};
 
 // --- Core Data Structures for the Model Registry ---
 
@@ -14,6 +16,40 @@ pub struct ModelMetadata {
     pub owner: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Version currently serving, set by [`ModelMetadata::promote`].
+    #[serde(default)]
+    pub active_version: Option<String>,
+}
+
+/// Reason a version could not be promoted.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PromotionError {
+    #[error("unknown model version {0}")]
+    UnknownVersion(String),
+    #[error("fairness gate failed: {0:?}")]
+    Fairness(Vec<FairnessViolation>),
+}
+
+impl ModelMetadata {
+    /// Makes `version` the active version once its latest fairness report
+    /// passes `gate`. A version that fails keeps the current one active.
+    pub fn promote(&mut self, version: &str, gate: &FairnessGate) -> Result<(), PromotionError> {
+        let candidate = self
+            .versions
+            .iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| PromotionError::UnknownVersion(version.to_string()))?;
+        gate.check(candidate).map_err(PromotionError::Fairness)?;
+        self.active_version = Some(version.to_string());
+        self.updated_at = chrono::Utc::now();
+        Ok(())
+    }
+
+    /// The version currently serving, if one was promoted.
+    pub fn active(&self) -> Option<&ModelVersion> {
+        let active = self.active_version.as_deref()?;
+        self.versions.iter().find(|v| v.version == active)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +59,9 @@ pub struct ModelVersion {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub artifacts: Vec<ModelArtifact>,
     pub training_metadata: Option<TrainingMetadata>,
+    /// Fairness evaluations run against this version, oldest first.
+    #[serde(default)]
+    pub fairness_reports: Vec<crate::ml::fairness::FairnessReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +81,8 @@ pub enum ArtifactType {
     Configuration,
     Code,
     Dataset,
+    /// Signed model card published to the DFS.
+    ModelCard,
     Other(String),
 }
 
@@ -30,6 +30,9 @@ pub enum AlertType {
     DataQualityIssue,
     ModelAccuracyDrop,
     SystemFailure,
+    /// An SLO's error budget is being spent faster than it can last.
+    ErrorBudgetBurn,
+    ErrorBudgetExhausted,
     Custom(String),
 }
 
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Latency samples kept per model version.
+const MAX_SAMPLES: usize = 256;
+
+/// Total cold starts and the most recent latencies, per model version.
+type Samples = HashMap<(Uuid, String), (u64, VecDeque<u64>)>;
+
+/// Summary of the time taken to load a model before it could serve.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColdStartMetrics {
+    pub model_id: Uuid,
+    pub model_version: String,
+    pub count: u64,
+    pub last_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Collects cold-start latencies reported by the inference warm pool.
+/// Shared between the pool and the monitoring side, hence the interior lock.
+#[derive(Debug, Default)]
+pub struct ColdStartRecorder {
+    samples: Mutex<Samples>,
+}
+
+impl ColdStartRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, model_id: Uuid, model_version: &str, latency_ms: u64) {
+        let mut samples = self.samples.lock().expect("cold start recorder poisoned");
+        let (count, window) = samples.entry((model_id, model_version.to_string())).or_default();
+        *count += 1;
+        window.push_back(latency_ms);
+        if window.len() > MAX_SAMPLES {
+            window.pop_front();
+        }
+    }
+
+    pub fn metrics(&self, model_id: Uuid, model_version: &str) -> Option<ColdStartMetrics> {
+        let samples = self.samples.lock().expect("cold start recorder poisoned");
+        samples
+            .get(&(model_id, model_version.to_string()))
+            .map(|(count, window)| summarize(model_id, model_version, *count, window))
+    }
+
+    /// Metrics for every model that has cold-started at least once.
+    pub fn all(&self) -> Vec<ColdStartMetrics> {
+        let samples = self.samples.lock().expect("cold start recorder poisoned");
+        let mut all: Vec<_> = samples
+            .iter()
+            .map(|((id, version), (count, window))| summarize(*id, version, *count, window))
+            .collect();
+        all.sort_by(|a, b| (a.model_id, &a.model_version).cmp(&(b.model_id, &b.model_version)));
+        all
+    }
+}
+
+fn summarize(model_id: Uuid, model_version: &str, count: u64, window: &VecDeque<u64>) -> ColdStartMetrics {
+    let mut sorted: Vec<u64> = window.iter().copied().collect();
+    sorted.sort_unstable();
+    let percentile = |p: usize| sorted[((sorted.len() - 1) * p) / 100];
+    ColdStartMetrics {
+        model_id,
+        model_version: model_version.to_string(),
+        count,
+        last_ms: *window.back().expect("recorded at least once"),
+        p50_ms: percentile(50),
+        p95_ms: percentile(95),
+        max_ms: *sorted.last().expect("recorded at least once"),
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::dashboard::TimeRange;
+
+/// One rollup level: buckets of `resolution`, kept for `retention`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollupTier {
+    pub resolution: Duration,
+    pub retention: Duration,
+}
+
+/// How long metric history is kept, and at which resolutions. Raw points
+/// are rolled up into the first tier, each tier into the next.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub raw_retention: Duration,
+    /// Raw points kept per metric regardless of age, bounding memory for
+    /// metrics reported at high rates.
+    pub max_raw_points: usize,
+    /// From finest to coarsest; each resolution must divide the next.
+    pub tiers: Vec<RollupTier>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        const HOUR: u64 = 3600;
+        const DAY: u64 = 24 * HOUR;
+        Self {
+            raw_retention: Duration::from_secs(DAY),
+            max_raw_points: 100_000,
+            tiers: vec![
+                RollupTier { resolution: Duration::from_secs(300), retention: Duration::from_secs(30 * DAY) },
+                RollupTier { resolution: Duration::from_secs(HOUR), retention: Duration::from_secs(365 * DAY) },
+            ],
+        }
+    }
+}
+
+/// Count, sum and extremes of the points in one bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Rollup {
+    fn point(value: f64) -> Self {
+        Self { count: 1, sum: value, min: value, max: value }
+    }
+
+    fn merge(&mut self, other: &Rollup) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+}
+
+/// A point or bucket returned by a range query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Time of the point, or start of the bucket.
+    pub timestamp: DateTime<Utc>,
+    pub count: u64,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Result of [`MetricHistory::query`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesRange {
+    pub metric: String,
+    /// Bucket width of the samples; zero for raw points.
+    pub resolution: Duration,
+    pub samples: Vec<Sample>,
+}
+
+#[derive(Debug, Default)]
+struct TierData {
+    buckets: BTreeMap<i64, Rollup>,
+    /// Source data before this time (unix seconds) has been rolled up.
+    rolled_until: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    /// Unix seconds and value, in arrival order.
+    raw: Vec<(i64, f64)>,
+    tiers: Vec<TierData>,
+}
+
+/// Metric history with tiered retention. Recording only appends raw points;
+/// [`MetricHistory::downsample`], run periodically by
+/// [`MetricHistory::run_downsampling`], rolls complete buckets up tier by
+/// tier and drops whatever is past its tier's retention. Shared between
+/// reporters and the dashboard, hence the interior lock.
+#[derive(Debug)]
+pub struct MetricHistory {
+    policy: RetentionPolicy,
+    series: Mutex<HashMap<String, Series>>,
+}
+
+impl Default for MetricHistory {
+    fn default() -> Self {
+        Self::new(RetentionPolicy::default())
+    }
+}
+
+fn secs(duration: Duration) -> i64 {
+    duration.as_secs().min(i64::MAX as u64) as i64
+}
+
+fn time(unix_secs: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(unix_secs, 0).single().unwrap_or_default()
+}
+
+impl MetricHistory {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Self { policy, series: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    pub fn record(&self, metric: &str, at: DateTime<Utc>, value: f64) {
+        let mut series = self.series.lock().expect("metric history poisoned");
+        let series = series.entry(metric.to_string()).or_default();
+        series.raw.push((at.timestamp(), value));
+        if series.raw.len() > self.policy.max_raw_points {
+            let excess = series.raw.len() - self.policy.max_raw_points;
+            series.raw.drain(..excess);
+        }
+    }
+
+    /// Names of every recorded metric, sorted.
+    pub fn metrics(&self) -> Vec<String> {
+        let series = self.series.lock().expect("metric history poisoned");
+        let mut names: Vec<String> = series.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Roll every bucket that ended by `now` up into its tier, then drop
+    /// data past retention. Points arriving for a bucket that was already
+    /// rolled up are kept raw only.
+    pub fn downsample(&self, now: DateTime<Utc>) {
+        let now = now.timestamp();
+        let mut series = self.series.lock().expect("metric history poisoned");
+        for series in series.values_mut() {
+            series.tiers.resize_with(self.policy.tiers.len(), TierData::default);
+            for (i, tier) in self.policy.tiers.iter().enumerate() {
+                let resolution = secs(tier.resolution).max(1);
+                let boundary = now - now.rem_euclid(resolution);
+                let (sources, rest) = series.tiers.split_at_mut(i);
+                let target = &mut rest[0];
+                let from = target.rolled_until.unwrap_or(i64::MIN);
+                let source: Vec<(i64, Rollup)> = match sources.last() {
+                    None => series
+                        .raw
+                        .iter()
+                        .filter(|(t, _)| *t >= from && *t < boundary)
+                        .map(|(t, v)| (*t, Rollup::point(*v)))
+                        .collect(),
+                    Some(finer) => finer.buckets.range(from..boundary).map(|(t, r)| (*t, *r)).collect(),
+                };
+                for (t, rollup) in source {
+                    let start = t - t.rem_euclid(resolution);
+                    target
+                        .buckets
+                        .entry(start)
+                        .and_modify(|b| b.merge(&rollup))
+                        .or_insert(rollup);
+                }
+                target.rolled_until = Some(boundary);
+            }
+
+            let raw_cutoff = now - secs(self.policy.raw_retention);
+            series.raw.retain(|(t, _)| *t >= raw_cutoff);
+            for (tier, data) in self.policy.tiers.iter().zip(&mut series.tiers) {
+                let cutoff = now - secs(tier.retention);
+                data.buckets = data.buckets.split_off(&cutoff);
+            }
+        }
+        series.retain(|_, s| !s.raw.is_empty() || s.tiers.iter().any(|t| !t.buckets.is_empty()));
+    }
+
+    /// Samples of `metric` within `range`, from the finest level whose
+    /// retention still reaches back to the start of the range as of `now`.
+    /// Rollup tiers hold only buckets that have been downsampled.
+    pub fn query(&self, metric: &str, range: &TimeRange, now: DateTime<Utc>) -> Option<SeriesRange> {
+        let series = self.series.lock().expect("metric history poisoned");
+        let series = series.get(metric)?;
+        let (start, end, now) = (range.start.timestamp(), range.end.timestamp(), now.timestamp());
+        if start >= end {
+            return Some(SeriesRange { metric: metric.to_string(), resolution: Duration::ZERO, samples: Vec::new() });
+        }
+
+        let covers = |retention: Duration| start >= now - secs(retention);
+        if covers(self.policy.raw_retention) || self.policy.tiers.is_empty() {
+            let mut points: Vec<(i64, f64)> =
+                series.raw.iter().copied().filter(|(t, _)| *t >= start && *t < end).collect();
+            points.sort_by_key(|(t, _)| *t);
+            let samples = points
+                .into_iter()
+                .map(|(t, v)| Sample { timestamp: time(t), count: 1, mean: v, min: v, max: v })
+                .collect();
+            return Some(SeriesRange { metric: metric.to_string(), resolution: Duration::ZERO, samples });
+        }
+
+        let level = self
+            .policy
+            .tiers
+            .iter()
+            .position(|tier| covers(tier.retention))
+            .unwrap_or(self.policy.tiers.len() - 1);
+        let resolution = self.policy.tiers[level].resolution;
+        // Include the bucket the range starts in.
+        let first = start - start.rem_euclid(secs(resolution).max(1));
+        let samples = series
+            .tiers
+            .get(level)
+            .map(|data| {
+                data.buckets
+                    .range(first..end)
+                    .map(|(t, r)| Sample {
+                        timestamp: time(*t),
+                        count: r.count,
+                        mean: r.mean(),
+                        min: r.min,
+                        max: r.max,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(SeriesRange { metric: metric.to_string(), resolution, samples })
+    }
+
+    /// Runs [`MetricHistory::downsample`] every `interval`.
+    pub async fn run_downsampling(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.downsample(Utc::now());
+        }
+    }
+}
@@ -7,10 +7,21 @@ pub mod alert;
 pub mod rule;
 pub mod dashboard;
 pub mod metrics;
+pub mod cold_start;
+pub mod training;
+pub mod history;
+pub mod slo;
+
+#[cfg(test)]
+mod tests;
 
 pub use metrics::MLMetrics;
 pub use performance::PerformanceMetrics;
 pub use data_quality::DataQualityMetrics;
 pub use model_quality::ModelQualityMetrics;
 pub use system::SystemMetrics;
-pub use business::BusinessMetrics; 
\ No newline at end of file
+pub use business::BusinessMetrics;
+pub use cold_start::{ColdStartMetrics, ColdStartRecorder};
+pub use training::{TrainingRecorder, TrainingStream};
+pub use history::{MetricHistory, RetentionPolicy, RollupTier, SeriesRange};
+pub use slo::{BurnRateRule, Objective, Slo, SloConfig, SloError, SloStatus, SloTracker}; 
\ No newline at end of file
@@ -0,0 +1,379 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::alert::{Alert, AlertSeverity, AlertStatus, AlertType};
+use crate::ml::inference_engine::ModelEndpoint;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum SloError {
+    #[error("SLO not found: {0}")]
+    NotFound(Uuid),
+    #[error("SLO target must be between 0 and 1 exclusive, got {0}")]
+    InvalidTarget(f64),
+    #[error("deployments to endpoint {endpoint_id} are frozen: error budget of SLO {slo} exhausted")]
+    DeploymentFrozen { endpoint_id: Uuid, slo: String },
+}
+
+/// What counts as a good request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    /// Succeeded within `threshold_ms`. Failed requests are bad however
+    /// fast they were.
+    Latency { threshold_ms: u64 },
+    /// Succeeded.
+    Availability,
+}
+
+/// A service level objective on one endpoint: at least `target` of the
+/// requests over the rolling `window` are good.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Slo {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub model_id: Uuid,
+    pub name: String,
+    pub objective: Objective,
+    pub target: f64,
+    pub window: Duration,
+    /// Refuse deployments to the endpoint while the error budget is spent.
+    pub freeze_deployments: bool,
+}
+
+impl Slo {
+    /// `target` of the requests answered within `threshold_ms`, e.g. 99% < 200ms.
+    pub fn latency(endpoint: &ModelEndpoint, threshold_ms: u64, target: f64) -> Self {
+        let name = format!("{}% < {}ms", target * 100.0, threshold_ms);
+        Self::new(endpoint, name, Objective::Latency { threshold_ms }, target)
+    }
+
+    /// `target` of the requests succeeding, e.g. 99.9% availability.
+    pub fn availability(endpoint: &ModelEndpoint, target: f64) -> Self {
+        let name = format!("{}% availability", target * 100.0);
+        Self::new(endpoint, name, Objective::Availability, target)
+    }
+
+    fn new(endpoint: &ModelEndpoint, name: String, objective: Objective, target: f64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            endpoint_id: endpoint.id,
+            model_id: endpoint.model_id,
+            name,
+            objective,
+            target,
+            window: Duration::from_secs(30 * 24 * 3600),
+            freeze_deployments: false,
+        }
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_deployment_freeze(mut self) -> Self {
+        self.freeze_deployments = true;
+        self
+    }
+
+    fn is_good(&self, latency_ms: u64, success: bool) -> bool {
+        match self.objective {
+            Objective::Latency { threshold_ms } => success && latency_ms <= threshold_ms,
+            Objective::Availability => success,
+        }
+    }
+}
+
+/// Alert when the budget burns at `burn_rate` times the sustainable pace
+/// over both windows: the long one shows the burn is significant, the
+/// short one that it is still going on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurnRateRule {
+    pub long_window: Duration,
+    pub short_window: Duration,
+    pub burn_rate: f64,
+    pub severity: AlertSeverity,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SloConfig {
+    /// Width of the buckets requests are counted in.
+    pub bucket: Duration,
+    pub rules: Vec<BurnRateRule>,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        const MINUTE: u64 = 60;
+        Self {
+            bucket: Duration::from_secs(MINUTE),
+            rules: vec![
+                // 2% of a 30-day budget in an hour.
+                BurnRateRule {
+                    long_window: Duration::from_secs(60 * MINUTE),
+                    short_window: Duration::from_secs(5 * MINUTE),
+                    burn_rate: 14.4,
+                    severity: AlertSeverity::Critical,
+                },
+                // 5% of a 30-day budget in six hours.
+                BurnRateRule {
+                    long_window: Duration::from_secs(360 * MINUTE),
+                    short_window: Duration::from_secs(30 * MINUTE),
+                    burn_rate: 6.0,
+                    severity: AlertSeverity::High,
+                },
+            ],
+        }
+    }
+}
+
+/// Compliance of one SLO over its window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SloStatus {
+    pub slo_id: Uuid,
+    pub endpoint_id: Uuid,
+    pub good: u64,
+    pub total: u64,
+    /// Share of good requests; 1.0 when there were none.
+    pub compliance: f64,
+    /// Share of the error budget left; negative once overspent.
+    pub budget_remaining: f64,
+    pub exhausted: bool,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    slos: HashMap<Uuid, Slo>,
+    /// Good and total requests per SLO, by bucket start (unix seconds).
+    counts: HashMap<Uuid, BTreeMap<i64, (u64, u64)>>,
+    /// Burn-rate rules (by index) and exhausted budgets currently alerted on,
+    /// so each condition alerts once until it clears.
+    burning: HashSet<(Uuid, usize)>,
+    exhausted: HashSet<Uuid>,
+}
+
+/// Rolling SLO compliance and error-budget burn for inference endpoints.
+/// The gateway records every request; [`SloTracker::evaluate`], run
+/// periodically, turns fast burns and spent budgets into alerts. Shared
+/// between the gateway and the monitoring side, hence the interior lock.
+#[derive(Debug, Default)]
+pub struct SloTracker {
+    config: SloConfig,
+    inner: Mutex<Inner>,
+}
+
+fn secs(duration: Duration) -> i64 {
+    duration.as_secs().min(i64::MAX as u64) as i64
+}
+
+impl SloTracker {
+    pub fn new(config: SloConfig) -> Self {
+        Self { config, inner: Mutex::new(Inner::default()) }
+    }
+
+    pub fn define(&self, slo: Slo) -> Result<Uuid, SloError> {
+        if !(slo.target > 0.0 && slo.target < 1.0) {
+            return Err(SloError::InvalidTarget(slo.target));
+        }
+        let id = slo.id;
+        let mut inner = self.inner.lock().expect("slo tracker poisoned");
+        inner.slos.insert(id, slo);
+        Ok(id)
+    }
+
+    pub fn remove(&self, slo_id: Uuid) -> Result<Slo, SloError> {
+        let mut inner = self.inner.lock().expect("slo tracker poisoned");
+        inner.counts.remove(&slo_id);
+        inner.burning.retain(|(id, _)| *id != slo_id);
+        inner.exhausted.remove(&slo_id);
+        inner.slos.remove(&slo_id).ok_or(SloError::NotFound(slo_id))
+    }
+
+    /// SLOs defined on `endpoint_id`.
+    pub fn slos(&self, endpoint_id: Uuid) -> Vec<Slo> {
+        let inner = self.inner.lock().expect("slo tracker poisoned");
+        let mut slos: Vec<Slo> =
+            inner.slos.values().filter(|s| s.endpoint_id == endpoint_id).cloned().collect();
+        slos.sort_by(|a, b| a.name.cmp(&b.name));
+        slos
+    }
+
+    /// Count one request to `endpoint_id` against each of its SLOs.
+    pub fn record(&self, endpoint_id: Uuid, at: DateTime<Utc>, latency_ms: u64, success: bool) {
+        let bucket = secs(self.config.bucket).max(1);
+        let t = at.timestamp();
+        let start = t - t.rem_euclid(bucket);
+        let longest_rule = self.config.rules.iter().map(|r| secs(r.long_window)).max().unwrap_or(0);
+        let mut inner = self.inner.lock().expect("slo tracker poisoned");
+        let Inner { slos, counts, .. } = &mut *inner;
+        for slo in slos.values().filter(|s| s.endpoint_id == endpoint_id) {
+            let counts = counts.entry(slo.id).or_default();
+            let (good, total) = counts.entry(start).or_default();
+            *total += 1;
+            if slo.is_good(latency_ms, success) {
+                *good += 1;
+            }
+            let keep = secs(slo.window).max(longest_rule);
+            *counts = counts.split_off(&(t - keep - bucket));
+        }
+    }
+
+    pub fn status(&self, slo_id: Uuid, now: DateTime<Utc>) -> Result<SloStatus, SloError> {
+        let inner = self.inner.lock().expect("slo tracker poisoned");
+        let slo = inner.slos.get(&slo_id).ok_or(SloError::NotFound(slo_id))?;
+        Ok(self.status_of(&inner, slo, now))
+    }
+
+    /// How many times faster than sustainable the budget burned over the
+    /// last `window`: 1.0 spends exactly the budget by the end of the SLO
+    /// window.
+    pub fn burn_rate(
+        &self,
+        slo_id: Uuid,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<f64, SloError> {
+        let inner = self.inner.lock().expect("slo tracker poisoned");
+        let slo = inner.slos.get(&slo_id).ok_or(SloError::NotFound(slo_id))?;
+        Ok(self.burn_rate_of(&inner, slo, window, now))
+    }
+
+    /// Alerts for SLOs that started burning fast or ran out of budget since
+    /// the last evaluation.
+    pub fn evaluate(&self, now: DateTime<Utc>) -> Vec<Alert> {
+        let mut inner = self.inner.lock().expect("slo tracker poisoned");
+        let mut slos: Vec<Slo> = inner.slos.values().cloned().collect();
+        slos.sort_by_key(|s| s.id);
+        let mut alerts = Vec::new();
+        for slo in &slos {
+            for (i, rule) in self.config.rules.iter().enumerate() {
+                let long = self.burn_rate_of(&inner, slo, rule.long_window, now);
+                let short = self.burn_rate_of(&inner, slo, rule.short_window, now);
+                if long >= rule.burn_rate && short >= rule.burn_rate {
+                    if inner.burning.insert((slo.id, i)) {
+                        let description = format!(
+                            "error budget of {} burning {:.1}x over {}s (threshold {:.1}x)",
+                            slo.name,
+                            long,
+                            rule.long_window.as_secs(),
+                            rule.burn_rate
+                        );
+                        alerts.push(Alert {
+                            description,
+                            threshold_value: rule.burn_rate,
+                            actual_value: long,
+                            ..alert(slo, AlertType::ErrorBudgetBurn, rule.severity.clone(), now)
+                        });
+                    }
+                } else {
+                    inner.burning.remove(&(slo.id, i));
+                }
+            }
+
+            let status = self.status_of(&inner, slo, now);
+            if status.exhausted {
+                if inner.exhausted.insert(slo.id) {
+                    alerts.push(Alert {
+                        description: format!("error budget of {} exhausted", slo.name),
+                        threshold_value: slo.target,
+                        actual_value: status.compliance,
+                        ..alert(slo, AlertType::ErrorBudgetExhausted, AlertSeverity::Critical, now)
+                    });
+                }
+            } else {
+                inner.exhausted.remove(&slo.id);
+            }
+        }
+        alerts
+    }
+
+    /// Err when an SLO on `endpoint_id` that freezes deployments has spent
+    /// its error budget.
+    pub fn check_deployment(&self, endpoint_id: Uuid, now: DateTime<Utc>) -> Result<(), SloError> {
+        let inner = self.inner.lock().expect("slo tracker poisoned");
+        let frozen = inner
+            .slos
+            .values()
+            .filter(|s| s.endpoint_id == endpoint_id && s.freeze_deployments)
+            .find(|s| self.status_of(&inner, s, now).exhausted);
+        match frozen {
+            Some(slo) => Err(SloError::DeploymentFrozen { endpoint_id, slo: slo.name.clone() }),
+            None => Ok(()),
+        }
+    }
+
+    fn counts_over(
+        &self,
+        inner: &Inner,
+        slo: &Slo,
+        window: Duration,
+        now: DateTime<Utc>,
+    ) -> (u64, u64) {
+        let now = now.timestamp();
+        let bucket = secs(self.config.bucket).max(1);
+        let from = now - secs(window);
+        inner
+            .counts
+            .get(&slo.id)
+            .map(|counts| {
+                counts
+                    .range(from - from.rem_euclid(bucket)..=now)
+                    .fold((0, 0), |(good, total), (_, (g, t))| (good + g, total + t))
+            })
+            .unwrap_or((0, 0))
+    }
+
+    fn status_of(&self, inner: &Inner, slo: &Slo, now: DateTime<Utc>) -> SloStatus {
+        let (good, total) = self.counts_over(inner, slo, slo.window, now);
+        let (compliance, budget_remaining) = if total == 0 {
+            (1.0, 1.0)
+        } else {
+            let bad = (total - good) as f64;
+            let allowed = total as f64 * (1.0 - slo.target);
+            (good as f64 / total as f64, 1.0 - bad / allowed)
+        };
+        SloStatus {
+            slo_id: slo.id,
+            endpoint_id: slo.endpoint_id,
+            good,
+            total,
+            compliance,
+            budget_remaining,
+            exhausted: budget_remaining <= 0.0,
+        }
+    }
+
+    fn burn_rate_of(&self, inner: &Inner, slo: &Slo, window: Duration, now: DateTime<Utc>) -> f64 {
+        let (good, total) = self.counts_over(inner, slo, window, now);
+        if total == 0 {
+            return 0.0;
+        }
+        let error_rate = (total - good) as f64 / total as f64;
+        error_rate / (1.0 - slo.target)
+    }
+}
+
+fn alert(slo: &Slo, alert_type: AlertType, severity: AlertSeverity, now: DateTime<Utc>) -> Alert {
+    let metadata = HashMap::from([
+        ("slo_id".to_string(), slo.id.to_string()),
+        ("endpoint_id".to_string(), slo.endpoint_id.to_string()),
+    ]);
+    Alert {
+        id: Uuid::new_v4(),
+        model_id: slo.model_id,
+        alert_type,
+        severity,
+        title: format!("SLO {} on endpoint {}", slo.name, slo.endpoint_id),
+        description: String::new(),
+        threshold_value: 0.0,
+        actual_value: 0.0,
+        created_at: now,
+        resolved_at: None,
+        status: AlertStatus::Active,
+        metadata,
+    }
+}
@@ -0,0 +1,158 @@
+use super::alert::{AlertSeverity, AlertType};
+use super::dashboard::TimeRange;
+use super::*;
+use crate::ml::inference_engine::{
+    EndpointStatus, LoadBalancingStrategy, ModelEndpoint, ResourceRequirements,
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+const HOUR: i64 = 3600;
+const DAY: i64 = 24 * HOUR;
+
+fn at(secs: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(secs, 0).unwrap()
+}
+
+fn endpoint() -> ModelEndpoint {
+    ModelEndpoint {
+        id: uuid::Uuid::new_v4(),
+        model_id: uuid::Uuid::new_v4(),
+        model_version: "1.0".into(),
+        status: EndpointStatus::Active,
+        created_at: at(0),
+        updated_at: at(0),
+        replicas: 1,
+        resources: ResourceRequirements { cpu: 1.0, memory_gb: 1.0, gpu: None },
+        load_balancing_strategy: LoadBalancingStrategy::RoundRobin,
+    }
+}
+
+fn range(start: i64, end: i64) -> TimeRange {
+    TimeRange { start: at(start), end: at(end) }
+}
+
+#[test]
+fn recent_ranges_are_served_raw() {
+    let history = MetricHistory::default();
+    for (t, v) in [(10, 1.0), (70, 3.0), (5, 2.0)] {
+        history.record("latency_ms", at(t), v);
+    }
+    let result = history.query("latency_ms", &range(0, 60), at(100)).unwrap();
+    assert_eq!(result.resolution, std::time::Duration::ZERO);
+    let values: Vec<f64> = result.samples.iter().map(|s| s.mean).collect();
+    assert_eq!(values, vec![2.0, 1.0]);
+    assert!(history.query("unknown", &range(0, 60), at(100)).is_none());
+    assert_eq!(history.metrics(), vec!["latency_ms"]);
+}
+
+#[test]
+fn complete_buckets_roll_up_tier_by_tier() {
+    let history = MetricHistory::default();
+    // One point a minute for two hours.
+    for minute in 0..120 {
+        history.record("throughput_rps", at(minute * 60), minute as f64);
+    }
+    // Only buckets that ended by then are rolled up: 23 five-minute buckets
+    // and the first hour.
+    let now = 2 * HOUR - 30;
+    history.downsample(at(now));
+
+    // Two days later the rest is rolled up, raw points are gone and
+    // five-minute buckets remain.
+    let later = at(now) + Duration::days(2);
+    history.downsample(later);
+    let five = history.query("throughput_rps", &range(0, 2 * HOUR), later).unwrap();
+    assert_eq!(five.resolution, std::time::Duration::from_secs(300));
+    assert_eq!(five.samples.len(), 24);
+    let first = &five.samples[0];
+    assert_eq!((first.count, first.min, first.max, first.mean), (5, 0.0, 4.0, 2.0));
+
+    // Past the five-minute retention only hourly buckets reach back.
+    let months_later = at(0) + Duration::days(60);
+    let hourly = history.query("throughput_rps", &range(0, 2 * HOUR), months_later).unwrap();
+    assert_eq!(hourly.resolution, std::time::Duration::from_secs(3600));
+    let hours: Vec<(u64, f64)> = hourly.samples.iter().map(|s| (s.count, s.mean)).collect();
+    assert_eq!(hours, vec![(60, 29.5), (60, 89.5)]);
+}
+
+#[test]
+fn data_past_retention_is_dropped() {
+    let history = MetricHistory::default();
+    history.record("error_rate", at(0), 0.5);
+    history.downsample(at(HOUR));
+    history.downsample(at(400 * DAY));
+    assert!(history.metrics().is_empty());
+
+    let capped = MetricHistory::new(RetentionPolicy { max_raw_points: 2, ..RetentionPolicy::default() });
+    for t in 0..5 {
+        capped.record("queue_size", at(t), t as f64);
+    }
+    let kept = capped.query("queue_size", &range(0, 10), at(10)).unwrap();
+    assert_eq!(kept.samples.iter().map(|s| s.mean).collect::<Vec<_>>(), vec![3.0, 4.0]);
+}
+
+#[test]
+fn slos_track_compliance_and_remaining_budget() {
+    let ep = endpoint();
+    let tracker = SloTracker::default();
+    let latency = tracker.define(Slo::latency(&ep, 200, 0.99)).unwrap();
+    let availability = tracker.define(Slo::availability(&ep, 0.999)).unwrap();
+    assert_eq!(tracker.define(Slo::availability(&ep, 1.0)), Err(SloError::InvalidTarget(1.0)));
+
+    // 1000 requests: five too slow, one failed fast.
+    for i in 0..1000 {
+        let latency_ms = if i % 200 == 0 { 500 } else { 50 };
+        tracker.record(ep.id, at(i), latency_ms, i != 1);
+    }
+    tracker.record(uuid::Uuid::new_v4(), at(10), 900, false);
+
+    let now = at(1000);
+    let status = tracker.status(latency, now).unwrap();
+    assert_eq!((status.good, status.total), (994, 1000));
+    assert!((status.budget_remaining - 0.4).abs() < 1e-9);
+    assert!(!status.exhausted);
+    let status = tracker.status(availability, now).unwrap();
+    assert_eq!(status.good, 999);
+    assert!(status.budget_remaining.abs() < 1e-9 && status.exhausted);
+    let burn = tracker.burn_rate(latency, std::time::Duration::from_secs(3600), now).unwrap();
+    assert!((burn - 0.6).abs() < 1e-9);
+    assert_eq!(tracker.slos(ep.id).len(), 2);
+}
+
+#[test]
+fn fast_burns_alert_once_and_freeze_deployments() {
+    let ep = endpoint();
+    let tracker = SloTracker::default();
+    let slo = Slo::availability(&ep, 0.999).with_window(std::time::Duration::from_secs(3600));
+    tracker.define(slo.with_deployment_freeze()).unwrap();
+
+    // A burst of failures: one request in ten.
+    let start = 10 * HOUR;
+    for i in 0..100 {
+        tracker.record(ep.id, at(start + i), 20, i % 10 != 0);
+    }
+    let now = at(start + 100);
+    let alerts = tracker.evaluate(now);
+    let kinds: Vec<(AlertType, AlertSeverity)> =
+        alerts.iter().map(|a| (a.alert_type.clone(), a.severity.clone())).collect();
+    assert!(matches!(
+        kinds.as_slice(),
+        [
+            (AlertType::ErrorBudgetBurn, AlertSeverity::Critical),
+            (AlertType::ErrorBudgetBurn, AlertSeverity::High),
+            (AlertType::ErrorBudgetExhausted, AlertSeverity::Critical),
+        ]
+    ));
+    assert_eq!(alerts[0].model_id, ep.model_id);
+    assert!(tracker.evaluate(now).is_empty());
+    let frozen = tracker.check_deployment(ep.id, now);
+    assert!(matches!(frozen, Err(SloError::DeploymentFrozen { .. })));
+
+    // Once the burst leaves the window the budget is back and nothing burns.
+    let later = start + 2 * HOUR;
+    for i in 0..10 {
+        tracker.record(ep.id, at(later - i), 20, true);
+    }
+    assert!(tracker.evaluate(at(later)).is_empty());
+    assert_eq!(tracker.check_deployment(ep.id, at(later)), Ok(()));
+}
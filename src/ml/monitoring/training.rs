@@ -0,0 +1,51 @@
+use runtime::trainer::callbacks::{Control, EpochEnd, EpochMetrics, TrainingCallback};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Per-epoch training metrics streamed from running jobs, keyed by job id.
+/// Shared between trainers and the monitoring side, hence the interior lock.
+#[derive(Debug, Default)]
+pub struct TrainingRecorder {
+    history: Mutex<HashMap<String, Vec<EpochMetrics>>>,
+}
+
+impl TrainingRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, job_id: &str, metrics: EpochMetrics) {
+        let mut history = self.history.lock().expect("training recorder poisoned");
+        history.entry(job_id.to_string()).or_default().push(metrics);
+    }
+
+    /// Every epoch recorded for the job, in order.
+    pub fn history(&self, job_id: &str) -> Vec<EpochMetrics> {
+        let history = self.history.lock().expect("training recorder poisoned");
+        history.get(job_id).cloned().unwrap_or_default()
+    }
+
+    pub fn latest(&self, job_id: &str) -> Option<EpochMetrics> {
+        let history = self.history.lock().expect("training recorder poisoned");
+        history.get(job_id).and_then(|epochs| epochs.last().copied())
+    }
+
+    /// A callback that streams the job's epochs into this recorder.
+    pub fn stream(self: &Arc<Self>, job_id: &str) -> TrainingStream {
+        TrainingStream { recorder: Arc::clone(self), job_id: job_id.to_string() }
+    }
+}
+
+/// Training callback feeding a [`TrainingRecorder`].
+#[derive(Debug, Clone)]
+pub struct TrainingStream {
+    recorder: Arc<TrainingRecorder>,
+    job_id: String,
+}
+
+impl TrainingCallback for TrainingStream {
+    fn on_epoch_end(&mut self, end: &mut EpochEnd<'_>) -> Control {
+        self.recorder.record(&self.job_id, end.metrics);
+        Control::Continue
+    }
+}
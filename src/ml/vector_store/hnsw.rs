@@ -0,0 +1,246 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DistanceMetric {
+    Euclidean,
+    Cosine,
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            DistanceMetric::Euclidean => {
+                a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+            }
+            DistanceMetric::Cosine => {
+                let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+                let na = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+                let nb = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+                if na == 0.0 || nb == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot / (na * nb)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswConfig {
+    /// Maximum neighbours per node on upper layers (layer 0 allows `2 * m`).
+    pub m: usize,
+    /// Candidate list size while inserting.
+    pub ef_construction: usize,
+    /// Candidate list size while searching.
+    pub ef_search: usize,
+    pub metric: DistanceMetric,
+    /// Seed for level assignment so index builds are reproducible.
+    pub seed: u64,
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self { m: 16, ef_construction: 100, ef_search: 50, metric: DistanceMetric::Cosine, seed: 42 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    id: String,
+    vector: Vec<f32>,
+    /// Neighbour lists, one per layer the node lives on.
+    links: Vec<Vec<usize>>,
+    /// Removed nodes still route searches but are never returned.
+    #[serde(default)]
+    removed: bool,
+}
+
+/// (distance, node index) ordered by distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate(f32, usize);
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// Hierarchical Navigable Small World graph over fixed-dimension vectors.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswIndex {
+    pub config: HnswConfig,
+    pub dimension: usize,
+    nodes: Vec<Node>,
+    entry_point: Option<usize>,
+    /// Number of removed nodes.
+    #[serde(default)]
+    removed: usize,
+    #[serde(skip, default = "default_rng")]
+    rng: Option<StdRng>,
+}
+
+fn default_rng() -> Option<StdRng> {
+    None
+}
+
+impl HnswIndex {
+    pub fn new(dimension: usize, config: HnswConfig) -> Self {
+        let rng = Some(StdRng::seed_from_u64(config.seed));
+        Self { config, dimension, nodes: Vec::new(), entry_point: None, removed: 0, rng }
+    }
+
+    /// Number of vectors that searches can return.
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.removed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the vector stored under `id`, returning whether there was one.
+    /// Its node stays in the graph so neighbour links remain intact.
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.nodes.iter_mut().find(|n| !n.removed && n.id == id) {
+            Some(node) => {
+                node.removed = true;
+                self.removed += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn random_level(&mut self) -> usize {
+        let seed = self.config.seed.wrapping_add(self.nodes.len() as u64);
+        let rng = self.rng.get_or_insert_with(|| StdRng::seed_from_u64(seed));
+        let ml = 1.0 / (self.config.m.max(2) as f64).ln();
+        let r: f64 = rng.gen_range(f64::EPSILON..1.0);
+        (-r.ln() * ml).floor() as usize
+    }
+
+    fn dist(&self, query: &[f32], idx: usize) -> f32 {
+        self.config.metric.distance(query, &self.nodes[idx].vector)
+    }
+
+    /// Beam search on one layer; returns up to `ef` closest nodes, nearest first.
+    fn search_layer(&self, query: &[f32], entry: usize, ef: usize, layer: usize) -> Vec<Candidate> {
+        let mut visited = HashSet::from([entry]);
+        let first = Candidate(self.dist(query, entry), entry);
+        // Min-heap of candidates to expand, max-heap of current results.
+        let mut candidates = BinaryHeap::from([std::cmp::Reverse(first)]);
+        let mut results = BinaryHeap::from([first]);
+
+        while let Some(std::cmp::Reverse(current)) = candidates.pop() {
+            let worst = results.peek().map(|c| c.0).unwrap_or(f32::INFINITY);
+            if current.0 > worst && results.len() >= ef {
+                break;
+            }
+            for &n in self.nodes[current.1].links.get(layer).into_iter().flatten() {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let d = self.dist(query, n);
+                let worst = results.peek().map(|c| c.0).unwrap_or(f32::INFINITY);
+                if results.len() < ef || d < worst {
+                    candidates.push(std::cmp::Reverse(Candidate(d, n)));
+                    results.push(Candidate(d, n));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 { self.config.m * 2 } else { self.config.m }
+    }
+
+    /// Inserts a vector under `id`. Panics in debug builds if the dimension
+    /// does not match; callers validate dimensions beforehand.
+    pub fn insert(&mut self, id: String, vector: Vec<f32>) {
+        debug_assert_eq!(vector.len(), self.dimension);
+        let level = self.random_level();
+        let idx = self.nodes.len();
+        self.nodes.push(Node { id, vector, links: vec![Vec::new(); level + 1], removed: false });
+
+        let Some(mut entry) = self.entry_point else {
+            self.entry_point = Some(idx);
+            return;
+        };
+        let query = self.nodes[idx].vector.clone();
+        let top = self.nodes[entry].links.len() - 1;
+
+        // Greedy descent through layers above the new node's level.
+        for layer in (level + 1..=top).rev() {
+            entry = self.search_layer(&query, entry, 1, layer)[0].1;
+        }
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, entry, self.config.ef_construction, layer);
+            let neighbours: Vec<usize> =
+                found.iter().take(self.max_links(layer)).map(|c| c.1).collect();
+            for &n in &neighbours {
+                self.nodes[n].links[layer].push(idx);
+                self.prune(n, layer);
+            }
+            self.nodes[idx].links[layer] = neighbours;
+            entry = found[0].1;
+        }
+
+        if level > top {
+            self.entry_point = Some(idx);
+        }
+    }
+
+    /// Keeps only the closest `max_links` neighbours of a node on a layer.
+    fn prune(&mut self, node: usize, layer: usize) {
+        let max = self.max_links(layer);
+        if self.nodes[node].links[layer].len() <= max {
+            return;
+        }
+        let base = self.nodes[node].vector.clone();
+        let mut scored: Vec<Candidate> = self.nodes[node].links[layer]
+            .iter()
+            .map(|&n| Candidate(self.dist(&base, n), n))
+            .collect();
+        scored.sort();
+        self.nodes[node].links[layer] = scored.into_iter().take(max).map(|c| c.1).collect();
+    }
+
+    /// Returns up to `k` (id, distance) pairs, nearest first.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(String, f32)> {
+        let Some(mut entry) = self.entry_point else {
+            return Vec::new();
+        };
+        if query.len() != self.dimension {
+            return Vec::new();
+        }
+        let top = self.nodes[entry].links.len() - 1;
+        for layer in (1..=top).rev() {
+            entry = self.search_layer(query, entry, 1, layer)[0].1;
+        }
+        // Widen the beam by the removed nodes it may have to skip.
+        let ef = self.config.ef_search.max(k.saturating_add(self.removed));
+        self.search_layer(query, entry, ef, 0)
+            .into_iter()
+            .filter(|c| !self.nodes[c.1].removed)
+            .take(k)
+            .map(|c| (self.nodes[c.1].id.clone(), c.0))
+            .collect()
+    }
+}
@@ -0,0 +1,18 @@
+//! Embedding service and vector index subsystem.
+//!
+//! Embeddings produced by registered models are stored in HNSW
+//! approximate-nearest-neighbor indexes. Each collection is split into shards
+//! that are placed on storage nodes; queries fan out to every shard and the
+//! per-shard results are merged. RAG-style inference endpoints use
+//! [`VectorStore::query`] to retrieve context documents.
+
+pub mod hnsw;
+pub mod shard;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+pub use hnsw::{DistanceMetric, HnswConfig, HnswIndex};
+pub use shard::{ShardPlacement, VectorShard};
+pub use store::{EmbeddingRecord, QueryRequest, QueryResult, VectorCollection, VectorStore, VectorStoreError};
@@ -0,0 +1,55 @@
+use super::hnsw::{HnswConfig, HnswIndex};
+use serde::{Deserialize, Serialize};
+
+/// How a collection's shards are spread across storage nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardPlacement {
+    pub shard_count: u32,
+    /// Storage nodes hosting shards; shard `i` lives on `nodes[i % len]`
+    /// plus the next `replicas - 1` nodes.
+    pub nodes: Vec<String>,
+    pub replicas: usize,
+}
+
+impl ShardPlacement {
+    pub fn new(shard_count: u32, nodes: Vec<String>, replicas: usize) -> Self {
+        Self { shard_count: shard_count.max(1), nodes, replicas: replicas.max(1) }
+    }
+
+    /// Shard that owns a record id. Uses FNV-1a so placement is stable
+    /// across builds and platforms.
+    pub fn shard_for(&self, record_id: &str) -> u32 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in record_id.as_bytes() {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (hash % self.shard_count as u64) as u32
+    }
+
+    /// Storage nodes holding a replica of `shard_id`.
+    pub fn nodes_for(&self, shard_id: u32) -> Vec<String> {
+        if self.nodes.is_empty() {
+            return Vec::new();
+        }
+        (0..self.replicas.min(self.nodes.len()))
+            .map(|r| self.nodes[(shard_id as usize + r) % self.nodes.len()].clone())
+            .collect()
+    }
+}
+
+/// One shard of a collection: an independent HNSW index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorShard {
+    pub shard_id: u32,
+    /// Storage nodes serving this shard.
+    pub nodes: Vec<String>,
+    pub index: HnswIndex,
+}
+
+impl VectorShard {
+    pub fn new(shard_id: u32, nodes: Vec<String>, dimension: usize, config: HnswConfig) -> Self {
+        let config = HnswConfig { seed: config.seed.wrapping_add(shard_id as u64), ..config };
+        Self { shard_id, nodes, index: HnswIndex::new(dimension, config) }
+    }
+}
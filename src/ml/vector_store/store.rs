@@ -0,0 +1,196 @@
+use super::hnsw::HnswConfig;
+use super::shard::{ShardPlacement, VectorShard};
+use crate::ml::model_registry::ModelMetadata;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum VectorStoreError {
+    #[error("collection not found: {0}")]
+    CollectionNotFound(String),
+    #[error("collection already exists: {0}")]
+    CollectionExists(String),
+    #[error("model {model} has no version {version}")]
+    UnknownModelVersion { model: Uuid, version: String },
+    #[error("dimension mismatch: expected {expected}, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+    #[error("duplicate record id: {0}")]
+    DuplicateRecord(String),
+}
+
+/// An embedding plus the payload returned to RAG endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRecord {
+    pub id: String,
+    pub vector: Vec<f32>,
+    /// Source text chunk, if the embedding was produced from text.
+    pub text: Option<String>,
+    pub metadata: HashMap<String, String>,
+}
+
+/// Embeddings produced by one version of a registered model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorCollection {
+    pub name: String,
+    pub model_id: Uuid,
+    pub model_version: String,
+    pub dimension: usize,
+    pub placement: ShardPlacement,
+    pub shards: Vec<VectorShard>,
+    records: HashMap<String, EmbeddingRecord>,
+}
+
+impl VectorCollection {
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&EmbeddingRecord> {
+        self.records.get(id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRequest {
+    pub collection: String,
+    pub vector: Vec<f32>,
+    pub top_k: usize,
+    /// Only return records whose metadata contains all of these pairs.
+    #[serde(default)]
+    pub filter: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub id: String,
+    pub distance: f32,
+    pub shard_id: u32,
+    pub text: Option<String>,
+    pub metadata: HashMap<String, String>,
+}
+
+/// Registry of vector collections served by this node.
+#[derive(Debug, Clone, Default)]
+pub struct VectorStore {
+    collections: HashMap<String, VectorCollection>,
+}
+
+impl VectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a collection for embeddings from `model` at `model_version`.
+    pub fn create_collection(
+        &mut self,
+        name: &str,
+        model: &ModelMetadata,
+        model_version: &str,
+        dimension: usize,
+        placement: ShardPlacement,
+        config: HnswConfig,
+    ) -> Result<&VectorCollection, VectorStoreError> {
+        if self.collections.contains_key(name) {
+            return Err(VectorStoreError::CollectionExists(name.to_string()));
+        }
+        if !model.versions.iter().any(|v| v.version == model_version) {
+            return Err(VectorStoreError::UnknownModelVersion {
+                model: model.id,
+                version: model_version.to_string(),
+            });
+        }
+        let shards = (0..placement.shard_count)
+            .map(|i| VectorShard::new(i, placement.nodes_for(i), dimension, config.clone()))
+            .collect();
+        let collection = VectorCollection {
+            name: name.to_string(),
+            model_id: model.id,
+            model_version: model_version.to_string(),
+            dimension,
+            placement,
+            shards,
+            records: HashMap::new(),
+        };
+        Ok(self.collections.entry(name.to_string()).or_insert(collection))
+    }
+
+    pub fn collection(&self, name: &str) -> Option<&VectorCollection> {
+        self.collections.get(name)
+    }
+
+    /// Adds embeddings to a collection, routing each one to its shard. A
+    /// record whose id is already stored replaces it, vector included. An id
+    /// listed twice in `records` rejects the whole batch.
+    pub fn upsert(&mut self, collection: &str, records: Vec<EmbeddingRecord>) -> Result<usize, VectorStoreError> {
+        let col = self
+            .collections
+            .get_mut(collection)
+            .ok_or_else(|| VectorStoreError::CollectionNotFound(collection.to_string()))?;
+        let mut batch = HashSet::new();
+        for record in &records {
+            if record.vector.len() != col.dimension {
+                return Err(VectorStoreError::DimensionMismatch {
+                    expected: col.dimension,
+                    actual: record.vector.len(),
+                });
+            }
+            if !batch.insert(&record.id) {
+                return Err(VectorStoreError::DuplicateRecord(record.id.clone()));
+            }
+        }
+        let count = records.len();
+        for record in records {
+            let shard = &mut col.shards[col.placement.shard_for(&record.id) as usize];
+            if col.records.contains_key(&record.id) {
+                shard.index.remove(&record.id);
+            }
+            shard.index.insert(record.id.clone(), record.vector.clone());
+            col.records.insert(record.id.clone(), record);
+        }
+        Ok(count)
+    }
+
+    /// Fans the query out to every shard and merges the nearest results.
+    pub fn query(&self, request: &QueryRequest) -> Result<Vec<QueryResult>, VectorStoreError> {
+        let col = self
+            .collections
+            .get(&request.collection)
+            .ok_or_else(|| VectorStoreError::CollectionNotFound(request.collection.clone()))?;
+        if request.vector.len() != col.dimension {
+            return Err(VectorStoreError::DimensionMismatch {
+                expected: col.dimension,
+                actual: request.vector.len(),
+            });
+        }
+
+        // Over-fetch when filtering so that enough matches survive.
+        let fetch = if request.filter.is_empty() { request.top_k } else { request.top_k.saturating_mul(4) };
+        let mut results: Vec<QueryResult> = col
+            .shards
+            .iter()
+            .flat_map(|shard| {
+                shard.index.search(&request.vector, fetch).into_iter().map(move |(id, d)| (shard.shard_id, id, d))
+            })
+            .filter_map(|(shard_id, id, distance)| {
+                let record = col.records.get(&id)?;
+                let matches = request.filter.iter().all(|(k, v)| record.metadata.get(k) == Some(v));
+                matches.then(|| QueryResult {
+                    id,
+                    distance,
+                    shard_id,
+                    text: record.text.clone(),
+                    metadata: record.metadata.clone(),
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        results.truncate(request.top_k);
+        Ok(results)
+    }
+}
@@ -0,0 +1,141 @@
+use super::*;
+use crate::ml::model_registry::{ModelMetadata, ModelVersion};
+use std::collections::HashMap;
+
+fn model() -> ModelMetadata {
+    ModelMetadata {
+        id: uuid::Uuid::new_v4(),
+        name: "embedder".into(),
+        description: String::new(),
+        versions: vec![ModelVersion {
+            version: "1.0".into(),
+            model_format: "onnx".into(),
+            created_at: chrono::Utc::now(),
+            artifacts: vec![],
+            training_metadata: None,
+            fairness_reports: vec![],
+        }],
+        tags: vec![],
+        owner: "alice".into(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        active_version: None,
+    }
+}
+
+fn record(i: usize) -> EmbeddingRecord {
+    let angle = i as f32 * 0.1;
+    let mut metadata = HashMap::new();
+    metadata.insert("parity".to_string(), (i % 2).to_string());
+    EmbeddingRecord {
+        id: format!("doc-{}", i),
+        vector: vec![angle.cos(), angle.sin(), 0.5],
+        text: Some(format!("chunk {}", i)),
+        metadata,
+    }
+}
+
+#[test]
+fn hnsw_finds_exact_neighbour() {
+    let config = HnswConfig { metric: DistanceMetric::Euclidean, ..Default::default() };
+    let mut index = HnswIndex::new(2, config);
+    for i in 0..200 {
+        index.insert(format!("p{}", i), vec![(i % 20) as f32, (i / 20) as f32]);
+    }
+    let hits = index.search(&[7.0, 3.0], 3);
+    assert_eq!(hits[0].0, "p67");
+    assert_eq!(hits[0].1, 0.0);
+    assert_eq!(hits.len(), 3);
+}
+
+#[test]
+fn sharded_query_merges_results() {
+    let mut store = VectorStore::new();
+    let placement = ShardPlacement::new(3, vec!["n1".into(), "n2".into()], 2);
+    store
+        .create_collection("docs", &model(), "1.0", 3, placement, HnswConfig::default())
+        .unwrap();
+    store.upsert("docs", (0..60).map(record).collect()).unwrap();
+
+    let col = store.collection("docs").unwrap();
+    assert_eq!(col.len(), 60);
+    assert_eq!(col.shards.iter().map(|s| s.index.len()).sum::<usize>(), 60);
+    assert_eq!(col.shards[2].nodes, vec!["n1".to_string(), "n2".to_string()]);
+
+    let query = QueryRequest { collection: "docs".into(), vector: record(10).vector, top_k: 3, filter: HashMap::new() };
+    let hits = store.query(&query).unwrap();
+    assert_eq!(hits[0].id, "doc-10");
+    assert_eq!(hits[0].text.as_deref(), Some("chunk 10"));
+
+    let mut filter = HashMap::new();
+    filter.insert("parity".to_string(), "1".to_string());
+    let hits = store.query(&QueryRequest { filter, ..query }).unwrap();
+    assert!(hits.iter().all(|h| h.metadata["parity"] == "1"));
+}
+
+#[test]
+fn rejects_unknown_versions_and_bad_dimensions() {
+    let mut store = VectorStore::new();
+    let placement = ShardPlacement::new(1, vec![], 1);
+    assert!(matches!(
+        store.create_collection("x", &model(), "9.9", 3, placement.clone(), HnswConfig::default()),
+        Err(VectorStoreError::UnknownModelVersion { .. })
+    ));
+    store.create_collection("x", &model(), "1.0", 3, placement, HnswConfig::default()).unwrap();
+    let bad = EmbeddingRecord { id: "a".into(), vector: vec![1.0], text: None, metadata: HashMap::new() };
+    assert_eq!(
+        store.upsert("x", vec![bad]),
+        Err(VectorStoreError::DimensionMismatch { expected: 3, actual: 1 })
+    );
+}
+
+#[test]
+fn rejects_ids_repeated_in_a_batch() {
+    let mut store = VectorStore::new();
+    let placement = ShardPlacement::new(2, vec![], 1);
+    store.create_collection("x", &model(), "1.0", 3, placement, HnswConfig::default()).unwrap();
+    assert_eq!(
+        store.upsert("x", vec![record(1), record(2), record(1)]),
+        Err(VectorStoreError::DuplicateRecord("doc-1".into()))
+    );
+    // Nothing of a rejected batch is stored.
+    assert_eq!(store.collection("x").unwrap().len(), 0);
+}
+
+#[test]
+fn upsert_replaces_stored_records() {
+    let mut store = VectorStore::new();
+    let placement = ShardPlacement::new(2, vec![], 1);
+    store.create_collection("x", &model(), "1.0", 3, placement, HnswConfig::default()).unwrap();
+    store.upsert("x", (0..10).map(record).collect()).unwrap();
+
+    let mut moved = record(1);
+    moved.vector = record(7).vector;
+    moved.text = Some("rewritten".into());
+    assert_eq!(store.upsert("x", vec![moved]), Ok(1));
+
+    let col = store.collection("x").unwrap();
+    assert_eq!(col.len(), 10);
+    assert_eq!(col.shards.iter().map(|s| s.index.len()).sum::<usize>(), 10);
+    assert_eq!(col.get("doc-1").unwrap().text.as_deref(), Some("rewritten"));
+
+    // The old vector is gone from the index and the new one is found once.
+    let near_old = QueryRequest { collection: "x".into(), vector: record(1).vector, top_k: 1, filter: HashMap::new() };
+    assert_ne!(store.query(&near_old).unwrap()[0].id, "doc-1");
+    let near_new = QueryRequest { vector: record(7).vector, top_k: 10, ..near_old };
+    let hits = store.query(&near_new).unwrap();
+    let found: Vec<_> = hits.iter().filter(|h| h.id == "doc-1").collect();
+    assert_eq!(found.len(), 1);
+    assert!(found[0].distance < 1e-6);
+}
+
+#[test]
+fn filtered_queries_accept_any_top_k() {
+    let mut store = VectorStore::new();
+    let placement = ShardPlacement::new(1, vec![], 1);
+    store.create_collection("x", &model(), "1.0", 3, placement, HnswConfig::default()).unwrap();
+    store.upsert("x", (0..4).map(record).collect()).unwrap();
+    let filter = HashMap::from([("parity".to_string(), "0".to_string())]);
+    let query = QueryRequest { collection: "x".into(), vector: record(0).vector, top_k: usize::MAX, filter };
+    assert_eq!(store.query(&query).unwrap().len(), 2);
+}
@@ -0,0 +1,21 @@
+use runtime::blockchain::BlockchainError;
+use runtime::large_data_transfer::LargeDataError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TestkitError {
+    #[error("invalid network spec: {0}")]
+    Spec(String),
+    #[error("chain error: {0}")]
+    Chain(#[from] BlockchainError),
+    #[error("chunk store error: {0}")]
+    Storage(#[from] LargeDataError),
+    #[error("fixture I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("fixture serialization failed: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("corrupt fixture: {0}")]
+    Corrupt(String),
+}
+
+pub type TestkitResult<T> = Result<T, TestkitError>;
@@ -0,0 +1,156 @@
+//! Export and import of built networks.
+//!
+//! A fixture is a JSON file holding everything a [`Network`] consists of:
+//! the genesis configuration, the blocks, the state they settled to, the
+//! account keys, the jobs and the stored files with their chunks. Importing
+//! one checks that the blocks still form a hash chain on top of the genesis
+//! and that every chunk a file lists is present, but does not re-run the
+//! blocks.
+
+use crate::error::{TestkitError, TestkitResult};
+use crate::network::{Account, JobFixture, Network, NetworkSpec, StoredFile};
+use runtime::blockchain::state::State;
+use runtime::blockchain::{Block, Blockchain, GenesisConfig};
+use runtime::large_data_transfer::{ChunkId, ChunkManager, DataChunk};
+use schnorrkel::SecretKey;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Version of the fixture format; fixtures of another version are refused.
+pub const FIXTURE_VERSION: u32 = 1;
+
+/// A stored file and its chunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileFixture {
+    #[serde(flatten)]
+    pub file: StoredFile,
+    pub chunks: Vec<DataChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub version: u32,
+    /// The spec the network was built from.
+    pub spec: NetworkSpec,
+    pub genesis: GenesisConfig,
+    /// Blocks after genesis.
+    pub blocks: Vec<Block>,
+    pub state: State,
+    /// Hex-encoded secret keys of the accounts.
+    pub accounts: Vec<String>,
+    pub jobs: Vec<JobFixture>,
+    pub files: Vec<FileFixture>,
+}
+
+impl Fixture {
+    pub fn export(network: &Network) -> TestkitResult<Self> {
+        let genesis = network.chain.genesis.clone().ok_or_else(|| {
+            TestkitError::Corrupt("network was not built from a genesis config".into())
+        })?;
+        let files = network
+            .files
+            .iter()
+            .map(|file| {
+                let chunks = file
+                    .descriptor
+                    .chunk_hashes
+                    .iter()
+                    .map(|hash| {
+                        network.chunks.get_chunk(&ChunkId(hash.clone())).ok_or_else(|| {
+                            TestkitError::Corrupt(format!("chunk {} is missing", hash))
+                        })
+                    })
+                    .collect::<TestkitResult<Vec<_>>>()?;
+                Ok(FileFixture { file: file.clone(), chunks })
+            })
+            .collect::<TestkitResult<Vec<_>>>()?;
+        Ok(Self {
+            version: FIXTURE_VERSION,
+            spec: network.spec.clone(),
+            genesis,
+            blocks: network.chain.blocks[1..].to_vec(),
+            state: network.chain.state.clone(),
+            accounts: network.accounts.iter().map(|a| hex::encode(a.secret.to_bytes())).collect(),
+            jobs: network.jobs.clone(),
+            files,
+        })
+    }
+
+    /// Rebuilds the network the fixture was exported from.
+    pub fn import(self) -> TestkitResult<Network> {
+        if self.version != FIXTURE_VERSION {
+            return Err(TestkitError::Corrupt(format!(
+                "fixture version {} is not {}",
+                self.version, FIXTURE_VERSION
+            )));
+        }
+        let mut chain = Blockchain::with_genesis(self.genesis)?;
+        for block in self.blocks {
+            let tip = chain.get_tip();
+            if block.index != tip.index + 1
+                || block.prev_hash != tip.hash
+                || block.hash != block.calculate_hash()
+            {
+                return Err(TestkitError::Corrupt(format!(
+                    "block {} does not extend the chain",
+                    block.index
+                )));
+            }
+            chain.blocks.push(block);
+        }
+        chain.state = self.state;
+        let senders: Vec<String> = chain
+            .blocks
+            .iter()
+            .flat_map(|b| b.transactions.iter().map(|tx| tx.from.clone()))
+            .collect();
+        for from in senders {
+            let nonce = chain.state.get_nonce(&from);
+            chain.account_nonces.insert(from, nonce);
+        }
+
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|key| {
+                hex::decode(key)
+                    .ok()
+                    .and_then(|bytes| SecretKey::from_bytes(&bytes).ok())
+                    .map(Account::new)
+                    .ok_or_else(|| TestkitError::Corrupt(format!("bad account key {}", key)))
+            })
+            .collect::<TestkitResult<Vec<_>>>()?;
+
+        let chunks = Arc::new(ChunkManager::default());
+        let mut files = Vec::with_capacity(self.files.len());
+        for FileFixture { file, chunks: pieces } in self.files {
+            for piece in pieces {
+                chunks.store_chunk(piece)?;
+            }
+            if let Some(missing) = file
+                .descriptor
+                .chunk_hashes
+                .iter()
+                .find(|h| !chunks.has_chunk(&ChunkId((*h).clone())))
+            {
+                return Err(TestkitError::Corrupt(format!(
+                    "file {} lacks chunk {}",
+                    file.descriptor.id, missing
+                )));
+            }
+            files.push(file);
+        }
+
+        Ok(Network { spec: self.spec, chain, accounts, jobs: self.jobs, files, chunks })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> TestkitResult<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> TestkitResult<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
@@ -0,0 +1,18 @@
+//! Populated network states for integration tests.
+//!
+//! Tests across the workspace need the same kind of setup: funded accounts,
+//! jobs in various stages, a chain with some history and files in the DFS
+//! chunk store. [`NetworkSpec`] builds such a [`Network`] deterministically
+//! from a seed, and a [`Fixture`] exports it to a JSON file and imports it
+//! back, so a test can load a checked-in state instead of hand-rolling one.
+//! [`mine`] mines real blocks on top of it.
+
+pub mod error;
+pub mod fixture;
+pub mod mining;
+pub mod network;
+
+pub use error::{TestkitError, TestkitResult};
+pub use fixture::{FileFixture, Fixture, FIXTURE_VERSION};
+pub use mining::{mine, MINER};
+pub use network::{Account, JobFixture, JobStatus, Network, NetworkSpec, StoredFile};
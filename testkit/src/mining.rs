@@ -0,0 +1,26 @@
+//! Mining real blocks on top of a built network in tests.
+
+use crate::error::TestkitResult;
+use runtime::blockchain::{Block, Blockchain, Transaction};
+use runtime::job::Job;
+use runtime::miner;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Miner address of blocks mined by [`mine`].
+pub const MINER: &str = "miner";
+
+/// Mines the next block of `chain` from `mempool` and `jobs` without adding
+/// it. The verifier rejects solutions computed in under 100ms, which the
+/// small tasks of a test always are; the time is not part of the PoUW check.
+pub async fn mine(
+    chain: &Arc<Mutex<Blockchain>>,
+    mempool: Arc<Mutex<HashSet<Transaction>>>,
+    jobs: Arc<Mutex<VecDeque<Job>>>,
+) -> TestkitResult<Block> {
+    let mut block = miner::mine_block(MINER.into(), chain.clone(), mempool, jobs).await?;
+    block.solution.computation_time_ms = block.solution.computation_time_ms.max(100);
+    block.hash = block.calculate_hash();
+    Ok(block)
+}
@@ -0,0 +1,389 @@
+//! Deterministic construction of populated network states.
+//!
+//! Blocks are settled by [`BlockProcessor::apply_block`], the code a real
+//! block goes through after validation, but carry no proof of useful work:
+//! building a hundred blocks takes milliseconds instead of a hundred
+//! training runs. A fixture chain is therefore meant to be loaded, not
+//! re-validated with [`Blockchain::add_block`]; blocks mined on top of it
+//! validate as usual.
+
+use crate::error::{TestkitError, TestkitResult};
+use crate::fixture::Fixture;
+use runtime::blockchain::block_processor::BlockProcessor;
+use runtime::blockchain::{Block, Blockchain, GenesisConfig, Transaction};
+use runtime::job::Job;
+use runtime::large_data_transfer::cdc::{self, CdcConfig};
+use runtime::large_data_transfer::config::CompressionAlgorithm;
+use runtime::large_data_transfer::{ChunkManager, LargeDataDescriptor};
+use runtime::pouw::{PoUWTask, Solution};
+use schnorrkel::{ExpansionMode, MiniSecretKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Chain id of built networks.
+pub const CHAIN_ID: &str = "bcai-testkit";
+/// Timestamp of the genesis block; block `n` is `n` intervals later.
+pub const GENESIS_TIMESTAMP: i64 = 1_700_000_000;
+pub const BLOCK_INTERVAL_SECS: i64 = 10;
+/// Fee paid by every generated transaction.
+pub const FEE: u64 = 1;
+/// Transactions settled per block at most.
+const TXS_PER_BLOCK: usize = 4;
+/// Accuracy of every generated PoUW solution, in basis points.
+const ACCURACY: u32 = 9_000;
+/// Average chunk size of stored files.
+const FILE_CHUNK_SIZE: u32 = 4096;
+
+/// What a built network holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSpec {
+    pub accounts: usize,
+    /// Jobs, cycling through queued, escrowed and completed.
+    pub jobs: usize,
+    /// Blocks after genesis.
+    pub blocks: u32,
+    /// Files stored in the DFS and paid for on chain.
+    pub files: usize,
+    pub file_size: usize,
+    /// Genesis balance of every account.
+    pub balance: u64,
+    pub job_reward: u64,
+    /// Price paid for storing each file.
+    pub storage_price: u64,
+    /// Keys, payloads and challenges all derive from the seed.
+    pub seed: u64,
+}
+
+impl Default for NetworkSpec {
+    fn default() -> Self {
+        Self {
+            accounts: 10,
+            jobs: 9,
+            blocks: 100,
+            files: 3,
+            file_size: 64 * 1024,
+            balance: 1_000_000,
+            job_reward: 500,
+            storage_price: 50,
+            seed: 0,
+        }
+    }
+}
+
+impl NetworkSpec {
+    /// Just `accounts` funded accounts, without jobs or files and with only
+    /// the blocks settling their opening transfers.
+    pub fn accounts_only(accounts: usize) -> Self {
+        Self {
+            accounts,
+            jobs: 0,
+            blocks: accounts.div_ceil(TXS_PER_BLOCK) as u32,
+            files: 0,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_accounts(mut self, accounts: usize) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn with_blocks(mut self, blocks: u32) -> Self {
+        self.blocks = blocks;
+        self
+    }
+
+    pub fn with_files(mut self, files: usize, file_size: usize) -> Self {
+        self.files = files;
+        self.file_size = file_size;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builds the network. The same spec always builds the same network.
+    pub fn build(&self) -> TestkitResult<Network> {
+        if self.accounts == 0 {
+            return Err(TestkitError::Spec("at least one account is needed".into()));
+        }
+        let keys: Vec<SecretKey> = (0..self.accounts).map(|i| account_key(self.seed, i)).collect();
+        let accounts: Vec<Account> = keys.into_iter().map(Account::new).collect();
+        let genesis = GenesisConfig {
+            chain_id: CHAIN_ID.to_string(),
+            timestamp: GENESIS_TIMESTAMP,
+            initial_balances: accounts
+                .iter()
+                .map(|a| (a.public_key.clone(), self.balance))
+                .collect(),
+            ..GenesisConfig::default()
+        };
+        let mut chain = Blockchain::with_genesis(genesis)?;
+        let mut nonces = vec![0u64; accounts.len()];
+        let mut next_nonce = |i: usize| {
+            nonces[i] += 1;
+            nonces[i] - 1
+        };
+        let mut txs = VecDeque::new();
+
+        // A transfer from every account to the next.
+        for (i, account) in accounts.iter().enumerate() {
+            let to = accounts[(i + 1) % accounts.len()].secret.to_public();
+            txs.push_back((
+                None,
+                Transaction::new_transfer(&account.secret, to, 100, FEE, next_nonce(i)),
+            ));
+        }
+
+        let mut jobs = Vec::with_capacity(self.jobs);
+        for j in 0..self.jobs {
+            let status = [JobStatus::Queued, JobStatus::Escrowed, JobStatus::Completed][j % 3];
+            let poster = j % accounts.len();
+            let mut job =
+                Job::new(j as u64 + 1, format!("model-{}", j), format!("dataset-{}", j), 1)
+                    .with_reward(self.job_reward);
+            job.trace = None;
+            if status != JobStatus::Queued {
+                let nonce = next_nonce(poster);
+                job = job.posted_by(&accounts[poster].public_key, nonce);
+                let tx = Transaction::new_post_job_signed(
+                    &accounts[poster].secret,
+                    job.clone(),
+                    FEE,
+                    nonce,
+                );
+                txs.push_back((Some(job.id), tx));
+            }
+            jobs.push(JobFixture { job, status, poster: accounts[poster].public_key.clone() });
+        }
+
+        let chunks = Arc::new(ChunkManager::default());
+        let mut files = Vec::with_capacity(self.files);
+        for f in 0..self.files {
+            let owner = f % accounts.len();
+            let data = pseudo_random(self.seed, &format!("file/{}", f), self.file_size);
+            let content_hash = hex::encode(Sha256::digest(&data));
+            let config = CdcConfig::with_average(FILE_CHUNK_SIZE);
+            let (descriptor, pieces) =
+                cdc::chunk_object(&content_hash, &data, &config, CompressionAlgorithm::None)?;
+            for piece in pieces {
+                chunks.store_chunk(piece)?;
+            }
+            let tx = Transaction::new_store_file_signed(
+                &accounts[owner].secret,
+                descriptor.id.clone(),
+                descriptor.size_bytes as u128,
+                self.storage_price as u128,
+                Vec::new(),
+                FEE,
+                next_nonce(owner),
+            );
+            txs.push_back((None, tx));
+            files.push(StoredFile { descriptor, owner: accounts[owner].public_key.clone() });
+        }
+
+        // Completed jobs are mined in a block after the one posting them.
+        let mut to_complete: VecDeque<&Job> =
+            jobs.iter().filter(|j| j.status == JobStatus::Completed).map(|j| &j.job).collect();
+        let mut posted_at = HashMap::new();
+        for index in 1..=self.blocks {
+            let batch: Vec<_> = (0..TXS_PER_BLOCK).map_while(|_| txs.pop_front()).collect();
+            let ready = to_complete
+                .front()
+                .is_some_and(|job| posted_at.get(&job.id).is_some_and(|&at| at < index));
+            let job = if ready { to_complete.pop_front() } else { None };
+            for (job_id, _) in &batch {
+                if let Some(id) = job_id {
+                    posted_at.insert(*id, index);
+                }
+            }
+            let miner = &accounts[index as usize % accounts.len()].public_key;
+            let block = self.block(
+                &chain,
+                index,
+                batch.into_iter().map(|(_, tx)| tx).collect(),
+                miner,
+                job,
+            );
+            BlockProcessor::apply_block(&block, &mut chain.state)?;
+            for tx in &block.transactions {
+                chain.account_nonces.insert(tx.from.clone(), chain.state.get_nonce(&tx.from));
+            }
+            chain.blocks.push(block);
+        }
+        if !txs.is_empty() || !to_complete.is_empty() {
+            return Err(TestkitError::Spec(format!(
+                "{} blocks cannot settle {} accounts, {} jobs and {} files",
+                self.blocks, self.accounts, self.jobs, self.files
+            )));
+        }
+
+        Ok(Network { spec: self.clone(), chain, accounts, jobs, files, chunks })
+    }
+
+    /// Block `index` on top of `chain`, training `job` if given.
+    fn block(
+        &self,
+        chain: &Blockchain,
+        index: u32,
+        transactions: Vec<Transaction>,
+        miner: &str,
+        job: Option<&Job>,
+    ) -> Block {
+        let tip = chain.get_tip();
+        let timestamp = GENESIS_TIMESTAMP + index as i64 * BLOCK_INTERVAL_SECS;
+        let mut task = match job {
+            Some(job) => job.to_task(None),
+            None => PoUWTask::new("testkit-model".into(), "testkit-dataset".into(), 1),
+        };
+        task.timestamp = timestamp as u64;
+        task.challenge.copy_from_slice(&pseudo_random(
+            self.seed,
+            &format!("challenge/{}", index),
+            32,
+        ));
+        let solution = Solution {
+            trained_model_hash: hex::encode(pseudo_random(
+                self.seed,
+                &format!("model/{}", index),
+                32,
+            )),
+            accuracy: ACCURACY,
+            nonce: 0,
+            computation_time_ms: 100,
+            training_time_ms: 100,
+            checkpoints: Vec::new(),
+            gradient_roots: Vec::new(),
+            zk_proof: None,
+        };
+        let mut block = Block::new(
+            index,
+            tip.hash.clone(),
+            transactions,
+            tip.difficulty,
+            miner.to_string(),
+            task,
+            solution,
+        );
+        block.timestamp = timestamp;
+        block.hash = block.calculate_hash();
+        block
+    }
+}
+
+/// A funded account and its key.
+#[derive(Clone)]
+pub struct Account {
+    pub secret: SecretKey,
+    /// Hex-encoded public key, the account's address.
+    pub public_key: String,
+}
+
+impl Account {
+    pub fn new(secret: SecretKey) -> Self {
+        let public_key = hex::encode(secret.to_public().to_bytes());
+        Self { secret, public_key }
+    }
+}
+
+/// How far a job has come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting in the job queue, not posted on chain.
+    Queued,
+    /// Posted, its reward held in escrow.
+    Escrowed,
+    /// Mined, its escrow released.
+    Completed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobFixture {
+    pub job: Job,
+    pub status: JobStatus,
+    /// Public key of the account that posted or queued the job.
+    pub poster: String,
+}
+
+/// A file in the chunk store, paid for on chain by `owner`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredFile {
+    pub descriptor: LargeDataDescriptor,
+    pub owner: String,
+}
+
+/// A populated network state.
+pub struct Network {
+    pub spec: NetworkSpec,
+    pub chain: Blockchain,
+    pub accounts: Vec<Account>,
+    pub jobs: Vec<JobFixture>,
+    pub files: Vec<StoredFile>,
+    /// Chunks of the stored files.
+    pub chunks: Arc<ChunkManager>,
+}
+
+impl Network {
+    /// Jobs waiting for a miner, in the order they were queued.
+    pub fn job_queue(&self) -> VecDeque<Job> {
+        self.jobs_in(JobStatus::Queued).cloned().collect()
+    }
+
+    pub fn jobs_in(&self, status: JobStatus) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(move |j| j.status == status).map(|j| &j.job)
+    }
+
+    /// Balances of the accounts, by public key.
+    pub fn balances(&self) -> BTreeMap<String, u64> {
+        self.accounts
+            .iter()
+            .map(|a| (a.public_key.clone(), self.chain.get_balance(&a.public_key)))
+            .collect()
+    }
+
+    pub fn fixture(&self) -> TestkitResult<Fixture> {
+        Fixture::export(self)
+    }
+
+    /// Writes the network to `path` as a fixture.
+    pub fn save(&self, path: impl AsRef<Path>) -> TestkitResult<()> {
+        self.fixture()?.save(path)
+    }
+
+    /// Loads the network saved at `path`.
+    pub fn load(path: impl AsRef<Path>) -> TestkitResult<Self> {
+        Fixture::load(path)?.import()
+    }
+}
+
+/// Key of account `index`.
+fn account_key(seed: u64, index: usize) -> SecretKey {
+    let bytes = pseudo_random(seed, &format!("account/{}", index), 32);
+    MiniSecretKey::from_bytes(&bytes)
+        .expect("32 bytes make a mini secret key")
+        .expand(ExpansionMode::Ed25519)
+}
+
+/// `len` bytes derived from `seed` and `label`.
+pub(crate) fn pseudo_random(seed: u64, label: &str, len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len + 32);
+    let mut counter = 0u64;
+    while out.len() < len {
+        out.extend_from_slice(&Sha256::digest(format!("testkit/{}/{}/{}", seed, label, counter)));
+        counter += 1;
+    }
+    out.truncate(len);
+    out
+}
@@ -0,0 +1,98 @@
+use runtime::blockchain::Transaction;
+use runtime::large_data_transfer::ChunkId;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use testkit::{Fixture, JobStatus, NetworkSpec, TestkitError};
+use tokio::sync::Mutex;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bcai-testkit-{}-{}.json", name, std::process::id()))
+}
+
+#[test]
+fn networks_are_populated_and_reproducible() {
+    let network = NetworkSpec::default().build().unwrap();
+    assert_eq!(network.chain.blocks.len(), 101);
+    assert_eq!(network.accounts.len(), 10);
+    assert_eq!(network.job_queue().len(), 3);
+    let escrowed: Vec<u64> = network.jobs_in(JobStatus::Escrowed).map(|j| j.id).collect();
+    assert_eq!(escrowed.len(), 3);
+    assert!(escrowed.iter().all(|id| network.chain.state.job_escrows.contains_key(id)));
+    assert!(network.jobs_in(JobStatus::Completed).all(|j| !network
+        .chain
+        .state
+        .job_escrows
+        .contains_key(&j.id)));
+    assert_eq!(network.files.len(), 3);
+    for file in &network.files {
+        assert_eq!(file.descriptor.size_bytes, 64 * 1024);
+        assert!(file
+            .descriptor
+            .chunk_hashes
+            .iter()
+            .all(|h| network.chunks.has_chunk(&ChunkId(h.clone()))));
+    }
+
+    let again = NetworkSpec::default().build().unwrap();
+    assert_eq!(again.chain.get_tip().hash, network.chain.get_tip().hash);
+    assert_eq!(again.balances(), network.balances());
+    let other = NetworkSpec::default().with_seed(1).build().unwrap();
+    assert_ne!(other.accounts[0].public_key, network.accounts[0].public_key);
+}
+
+#[test]
+fn fixtures_round_trip_through_files() {
+    let network = NetworkSpec::default().with_blocks(20).build().unwrap();
+    let path = temp_path("round-trip");
+    network.save(&path).unwrap();
+    let loaded = testkit::Network::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.chain.get_tip().hash, network.chain.get_tip().hash);
+    assert_eq!(loaded.chain.state, network.chain.state);
+    assert_eq!(loaded.balances(), network.balances());
+    assert_eq!(loaded.jobs, network.jobs);
+    let hashes = |n: &testkit::Network| -> Vec<Vec<String>> {
+        n.files.iter().map(|f| f.descriptor.chunk_hashes.clone()).collect()
+    };
+    assert_eq!(hashes(&loaded), hashes(&network));
+
+    // Accounts carry on where the fixture left off.
+    let (alice, bob) = (&loaded.accounts[0], &loaded.accounts[1]);
+    let nonce = loaded.chain.get_nonce(&alice.public_key);
+    assert!(nonce > 0);
+    let tx = Transaction::new_transfer(&alice.secret, bob.secret.to_public(), 10, 1, nonce);
+    loaded.chain.validate_transaction(&tx).unwrap();
+}
+
+#[test]
+fn broken_specs_and_fixtures_are_refused() {
+    let err = NetworkSpec::default().with_blocks(3).build().err().unwrap();
+    assert!(matches!(err, TestkitError::Spec(_)));
+
+    let network = NetworkSpec::default().with_blocks(20).build().unwrap();
+    let mut fixture = network.fixture().unwrap();
+    fixture.blocks[5].timestamp += 1;
+    assert!(matches!(fixture.import().err().unwrap(), TestkitError::Corrupt(_)));
+
+    let mut fixture: Fixture = network.fixture().unwrap();
+    fixture.files[0].chunks.pop();
+    assert!(matches!(fixture.import().err().unwrap(), TestkitError::Corrupt(_)));
+}
+
+#[tokio::test]
+async fn blocks_mine_on_top_of_built_networks() {
+    let network = NetworkSpec::accounts_only(5).build().unwrap();
+    assert_eq!(network.chain.blocks.len(), 3);
+    assert!(network.files.is_empty() && network.jobs.is_empty());
+
+    let (alice, bob) = (&network.accounts[0], &network.accounts[1]);
+    let nonce = network.chain.get_nonce(&alice.public_key);
+    let tx = Transaction::new_transfer(&alice.secret, bob.secret.to_public(), 10, 1, nonce);
+    let chain = Arc::new(Mutex::new(network.chain));
+    let mempool = Arc::new(Mutex::new(HashSet::from([tx.clone()])));
+    let block = testkit::mine(&chain, mempool, Arc::default()).await.unwrap();
+    assert_eq!(block.transactions, vec![tx]);
+    chain.lock().await.add_block(block).unwrap();
+}